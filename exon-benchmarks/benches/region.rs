@@ -0,0 +1,103 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use exon::ExonSession;
+use exon_benchmarks::synthetic::{
+    data_dir_from_env, records_from_env, register_table, SyntheticData,
+};
+use tokio::runtime::Runtime;
+
+const REGION: &str = "chr1:1-500000";
+
+fn bench_region_pushdown(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+
+    let data = SyntheticData::new(data_dir_from_env(), records_from_env());
+    let files = data.generate().unwrap();
+
+    let ctx = ExonSession::new_exon().unwrap();
+    let indexed = runtime
+        .block_on(data.generate_indexed(&ctx, &files))
+        .unwrap();
+
+    runtime.block_on(async {
+        register_table(&ctx, "vcf", "VCF", &files.vcf, None)
+            .await
+            .unwrap();
+        register_table(
+            &ctx,
+            "indexed_vcf",
+            "INDEXED_VCF",
+            &indexed.vcf,
+            Some("compression gzip"),
+        )
+        .await
+        .unwrap();
+        register_table(&ctx, "gff", "GFF", &files.gff, None)
+            .await
+            .unwrap();
+        register_table(
+            &ctx,
+            "indexed_gff",
+            "INDEXED_GFF",
+            &indexed.gff,
+            Some("compression gzip"),
+        )
+        .await
+        .unwrap();
+    });
+
+    // Each pair runs the same query with and without the index, so the difference is the
+    // benefit of the region pushdown.
+    let queries = [
+        (
+            "vcf/indexed",
+            format!("SELECT * FROM indexed_vcf WHERE vcf_region_filter('{REGION}', chrom, pos) = true"),
+        ),
+        (
+            "vcf/filter",
+            "SELECT * FROM vcf WHERE chrom = 'chr1' AND pos BETWEEN 1 AND 500000".to_string(),
+        ),
+        (
+            "gff/indexed",
+            format!("SELECT * FROM indexed_gff WHERE gff_region_filter('{REGION}', seqname, start) = true"),
+        ),
+        (
+            "gff/filter",
+            "SELECT * FROM gff WHERE seqname = 'chr1' AND start BETWEEN 1 AND 500000".to_string(),
+        ),
+    ];
+
+    let mut group = c.benchmark_group("region");
+
+    for (name, sql) in queries {
+        group.bench_function(name, |b| {
+            b.to_async(&runtime).iter(|| async {
+                ctx.session
+                    .sql(&sql)
+                    .await
+                    .unwrap()
+                    .collect()
+                    .await
+                    .unwrap();
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_region_pushdown);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use exon::ExonSession;
+use exon_benchmarks::synthetic::{
+    data_dir_from_env, records_from_env, register_table, SyntheticData,
+};
+use tokio::runtime::Runtime;
+
+fn bench_scans(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+
+    let data = SyntheticData::new(data_dir_from_env(), records_from_env());
+    let files = data.generate().unwrap();
+
+    let ctx = ExonSession::new_exon().unwrap();
+
+    let tables = [
+        ("fasta", "FASTA", &files.fasta),
+        ("fastq", "FASTQ", &files.fastq),
+        ("vcf", "VCF", &files.vcf),
+        ("gff", "GFF", &files.gff),
+        ("bed", "BED", &files.bed),
+        ("sam", "SAM", &files.sam),
+    ];
+
+    let mut group = c.benchmark_group("scan");
+    group.throughput(Throughput::Elements(data.records() as u64));
+
+    for (name, stored_as, path) in tables {
+        runtime
+            .block_on(register_table(&ctx, name, stored_as, path, None))
+            .unwrap();
+
+        // Select every column so the whole record is parsed.
+        let sql = format!("SELECT * FROM {name}");
+
+        group.bench_function(name, |b| {
+            b.to_async(&runtime).iter(|| async {
+                ctx.session
+                    .sql(&sql)
+                    .await
+                    .unwrap()
+                    .collect()
+                    .await
+                    .unwrap();
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_scans);
+criterion_main!(benches);
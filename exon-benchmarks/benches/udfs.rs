@@ -0,0 +1,72 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use exon::ExonSession;
+use exon_benchmarks::synthetic::{
+    data_dir_from_env, records_from_env, register_table, SyntheticData,
+};
+use tokio::runtime::Runtime;
+
+fn bench_udfs(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+
+    let data = SyntheticData::new(data_dir_from_env(), records_from_env());
+    let files = data.generate().unwrap();
+
+    let ctx = ExonSession::new_exon().unwrap();
+
+    runtime.block_on(async {
+        register_table(&ctx, "fastq", "FASTQ", &files.fastq, None)
+            .await
+            .unwrap();
+
+        // Materialize the reads so the benches measure the kernels rather than the scan.
+        ctx.session
+            .sql("CREATE TABLE reads AS SELECT sequence, quality_scores FROM fastq")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+    });
+
+    let queries = [
+        (
+            "reverse_complement",
+            "SELECT reverse_complement(sequence) FROM reads",
+        ),
+        ("gc_content", "SELECT gc_content(sequence) FROM reads"),
+        (
+            "quality_scores_to_list",
+            "SELECT quality_scores_to_list(quality_scores) FROM reads",
+        ),
+    ];
+
+    let mut group = c.benchmark_group("udf");
+    group.throughput(Throughput::Elements(data.records() as u64));
+
+    for (name, sql) in queries {
+        group.bench_function(name, |b| {
+            b.to_async(&runtime).iter(|| async {
+                ctx.session.sql(sql).await.unwrap().collect().await.unwrap();
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_udfs);
+criterion_main!(benches);
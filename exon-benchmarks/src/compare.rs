@@ -0,0 +1,158 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// The mean time of one benchmark in a saved baseline and in the latest run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// The benchmark id, e.g. `scan/fasta`.
+    pub id: String,
+
+    /// The mean time in the baseline, in nanoseconds.
+    pub baseline: f64,
+
+    /// The mean time in the latest run, in nanoseconds.
+    pub current: f64,
+}
+
+impl Comparison {
+    /// The relative change from the baseline, e.g. 0.1 when the latest run is 10% slower.
+    pub fn change(&self) -> f64 {
+        (self.current - self.baseline) / self.baseline
+    }
+
+    /// If the latest run is slower than the baseline by more than `threshold`.
+    pub fn is_regression(&self, threshold: f64) -> bool {
+        self.change() > threshold
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<48} {:>14.0} ns {:>14.0} ns {:>+8.2}%",
+            self.id,
+            self.baseline,
+            self.current,
+            self.change() * 100.0
+        )
+    }
+}
+
+/// Compare the latest criterion results in `criterion_dir` with the baseline saved by
+/// `cargo bench -- --save-baseline <baseline>`.
+///
+/// Benchmarks that are missing from either run are skipped.
+pub fn compare_baseline(criterion_dir: &Path, baseline: &str) -> io::Result<Vec<Comparison>> {
+    let mut comparisons = vec![];
+    let mut dirs = vec![criterion_dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let baseline_estimates = dir.join(baseline).join("estimates.json");
+        let current_estimates = dir.join("new").join("estimates.json");
+
+        if baseline_estimates.is_file() && current_estimates.is_file() {
+            let id = dir
+                .strip_prefix(criterion_dir)
+                .unwrap_or(&dir)
+                .to_string_lossy()
+                .replace(std::path::MAIN_SEPARATOR, "/");
+
+            comparisons.push(Comparison {
+                id,
+                baseline: read_mean(&baseline_estimates)?,
+                current: read_mean(&current_estimates)?,
+            });
+
+            continue;
+        }
+
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+
+            // The report directory holds criterion's HTML output, not results.
+            if path.is_dir() && !path.ends_with("report") {
+                dirs.push(path);
+            }
+        }
+    }
+
+    comparisons.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(comparisons)
+}
+
+/// The default location of criterion's results for a workspace.
+pub fn default_criterion_dir() -> PathBuf {
+    std::env::var("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("target"))
+        .join("criterion")
+}
+
+fn read_mean(path: &Path) -> io::Result<f64> {
+    let estimates: serde_json::Value = serde_json::from_reader(std::fs::File::open(path)?)?;
+
+    estimates["mean"]["point_estimate"].as_f64().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("missing mean estimate in {}", path.display()),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::compare_baseline;
+
+    fn write_estimate(dir: &Path, mean: f64) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(
+            dir.join("estimates.json"),
+            format!(r#"{{"mean": {{"point_estimate": {mean}}}}}"#),
+        )
+    }
+
+    #[test]
+    fn test_compare_baseline() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join("exon-benchmarks-compare-test");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        write_estimate(&dir.join("scan/fasta/main"), 100.0)?;
+        write_estimate(&dir.join("scan/fasta/new"), 125.0)?;
+        write_estimate(&dir.join("scan/vcf/main"), 100.0)?;
+        write_estimate(&dir.join("scan/vcf/new"), 95.0)?;
+        write_estimate(&dir.join("scan/gff/new"), 95.0)?;
+
+        let comparisons = compare_baseline(&dir, "main")?;
+
+        assert_eq!(comparisons.len(), 2);
+        assert_eq!(comparisons[0].id, "scan/fasta");
+        assert!(comparisons[0].is_regression(0.1));
+        assert_eq!(comparisons[1].id, "scan/vcf");
+        assert!(!comparisons[1].is_regression(0.1));
+
+        std::fs::remove_dir_all(&dir)?;
+
+        Ok(())
+    }
+}
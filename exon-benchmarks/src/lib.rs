@@ -0,0 +1,22 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers shared by the exon benchmarks: synthetic datasets for the criterion benches and a
+//! comparison of criterion results against a saved baseline.
+
+/// Compare criterion results against a saved baseline.
+pub mod compare;
+
+/// Generate synthetic files for the benches.
+pub mod synthetic;
@@ -24,6 +24,10 @@ use exon::{
     },
     new_exon_config, ExonRuntimeEnvExt, ExonSession,
 };
+use exon_benchmarks::{
+    compare::{compare_baseline, default_criterion_dir},
+    synthetic::{data_dir_from_env, records_from_env, SyntheticData},
+};
 use tracing::Level;
 use tracing_subscriber::FmtSubscriber;
 
@@ -90,6 +94,30 @@ enum Commands {
         #[arg(short, long)]
         path: String,
     },
+    /// Generate the synthetic files used by the criterion benches
+    Generate {
+        /// directory to write to, defaults to EXON_BENCH_DATA_DIR
+        #[arg(short, long)]
+        path: Option<String>,
+
+        /// number of records per file, defaults to EXON_BENCH_RECORDS
+        #[arg(short, long)]
+        records: Option<usize>,
+    },
+    /// Compare the latest criterion run with a saved baseline, failing on regressions
+    Compare {
+        /// name of the baseline saved with `cargo bench -- --save-baseline`
+        #[arg(short, long, default_value = "main")]
+        baseline: String,
+
+        /// criterion output directory, defaults to target/criterion
+        #[arg(short, long)]
+        criterion_dir: Option<String>,
+
+        /// relative slowdown that counts as a regression
+        #[arg(short, long, default_value_t = 0.1)]
+        threshold: f64,
+    },
 }
 
 #[derive(Parser)]
@@ -258,6 +286,61 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             eprintln!("Count: {count}");
         }
+        Some(Commands::Generate { path, records }) => {
+            let dir = path
+                .as_ref()
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(data_dir_from_env);
+            let records = records.unwrap_or_else(records_from_env);
+
+            let data = SyntheticData::new(dir, records);
+            let files = data.generate()?;
+
+            let ctx = ExonSession::new_exon()?;
+            let indexed = data.generate_indexed(&ctx, &files).await?;
+
+            eprintln!("Files: {:#?}", files);
+            eprintln!("Indexed Files: {:#?}", indexed);
+        }
+        Some(Commands::Compare {
+            baseline,
+            criterion_dir,
+            threshold,
+        }) => {
+            let criterion_dir = criterion_dir
+                .as_ref()
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(default_criterion_dir);
+
+            let comparisons = compare_baseline(&criterion_dir, baseline)?;
+            if comparisons.is_empty() {
+                return Err(format!(
+                    "no results to compare with baseline '{}' in {}",
+                    baseline,
+                    criterion_dir.display()
+                )
+                .into());
+            }
+
+            for comparison in &comparisons {
+                eprintln!("{}", comparison);
+            }
+
+            let regressions = comparisons
+                .iter()
+                .filter(|c| c.is_regression(*threshold))
+                .count();
+
+            if regressions > 0 {
+                return Err(format!(
+                    "{} of {} benchmarks regressed by more than {:.0}%",
+                    regressions,
+                    comparisons.len(),
+                    threshold * 100.0
+                )
+                .into());
+            }
+        }
         None => {}
     }
 
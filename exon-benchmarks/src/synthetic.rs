@@ -0,0 +1,397 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use exon::ExonSession;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// The environment variable that sets the number of records in each synthetic file.
+pub const RECORDS_ENV: &str = "EXON_BENCH_RECORDS";
+
+/// The number of records in each synthetic file when `EXON_BENCH_RECORDS` is not set.
+pub const DEFAULT_RECORDS: usize = 10_000;
+
+/// The environment variable that sets where synthetic files are written.
+pub const DATA_DIR_ENV: &str = "EXON_BENCH_DATA_DIR";
+
+/// The contigs records are spread across, with their lengths.
+pub const CONTIGS: &[(&str, u64)] = &[
+    ("chr1", 2_000_000),
+    ("chr2", 1_500_000),
+    ("chr3", 1_000_000),
+];
+
+const BASES: &[u8] = b"ACGT";
+
+/// Read the number of records from `EXON_BENCH_RECORDS`, falling back to `DEFAULT_RECORDS`.
+pub fn records_from_env() -> usize {
+    std::env::var(RECORDS_ENV)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_RECORDS)
+}
+
+/// Read the data directory from `EXON_BENCH_DATA_DIR`, falling back to a directory in the
+/// system temp dir.
+pub fn data_dir_from_env() -> PathBuf {
+    std::env::var(DATA_DIR_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| std::env::temp_dir().join("exon-benchmarks"))
+}
+
+/// Register `path` as an external table on the session.
+pub async fn register_table(
+    ctx: &ExonSession,
+    name: &str,
+    stored_as: &str,
+    path: &Path,
+    options: Option<&str>,
+) -> exon::Result<()> {
+    let options = options
+        .map(|o| format!(" OPTIONS ({o})"))
+        .unwrap_or_default();
+
+    ctx.session
+        .sql(&format!(
+            "CREATE EXTERNAL TABLE {name} STORED AS {stored_as} LOCATION '{}'{options}",
+            path.display()
+        ))
+        .await?
+        .collect()
+        .await?;
+
+    Ok(())
+}
+
+/// Paths to the generated files.
+#[derive(Debug, Clone)]
+pub struct SyntheticFiles {
+    pub fasta: PathBuf,
+    pub fastq: PathBuf,
+    pub vcf: PathBuf,
+    pub gff: PathBuf,
+    pub bed: PathBuf,
+    pub sam: PathBuf,
+}
+
+/// Paths to the bgzipped and tabix indexed copies of the generated files.
+#[derive(Debug, Clone)]
+pub struct IndexedSyntheticFiles {
+    pub vcf: PathBuf,
+    pub gff: PathBuf,
+}
+
+/// Generates deterministic synthetic files of a configurable size.
+#[derive(Debug, Clone)]
+pub struct SyntheticData {
+    dir: PathBuf,
+    records: usize,
+    sequence_length: usize,
+    seed: u64,
+}
+
+impl SyntheticData {
+    /// Create a generator that writes files with `records` records to `dir`.
+    pub fn new(dir: impl Into<PathBuf>, records: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            records,
+            sequence_length: 150,
+            seed: 42,
+        }
+    }
+
+    /// Set the length of the FASTA, FASTQ and SAM sequences.
+    pub fn with_sequence_length(mut self, sequence_length: usize) -> Self {
+        self.sequence_length = sequence_length;
+        self
+    }
+
+    /// Set the random seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The number of records in each file.
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// Write the files, reusing ones already generated with the same size.
+    pub fn generate(&self) -> io::Result<SyntheticFiles> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let files = SyntheticFiles {
+            fasta: self.path("fasta"),
+            fastq: self.path("fastq"),
+            vcf: self.path("vcf"),
+            gff: self.path("gff"),
+            bed: self.path("bed"),
+            sam: self.path("sam"),
+        };
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        self.write_if_missing(&files.fasta, |w| self.write_fasta(w, &mut rng))?;
+        self.write_if_missing(&files.fastq, |w| self.write_fastq(w, &mut rng))?;
+        self.write_if_missing(&files.vcf, |w| self.write_vcf(w, &mut rng))?;
+        self.write_if_missing(&files.gff, |w| self.write_gff(w, &mut rng))?;
+        self.write_if_missing(&files.bed, |w| self.write_bed(w, &mut rng))?;
+        self.write_if_missing(&files.sam, |w| self.write_sam(w, &mut rng))?;
+
+        Ok(files)
+    }
+
+    /// Write bgzipped and tabix indexed copies of the VCF and GFF files with `COPY TO`.
+    pub async fn generate_indexed(
+        &self,
+        ctx: &ExonSession,
+        files: &SyntheticFiles,
+    ) -> exon::Result<IndexedSyntheticFiles> {
+        let indexed = IndexedSyntheticFiles {
+            vcf: self.path("vcf.gz"),
+            gff: self.path("gff.gz"),
+        };
+
+        for (source, target, stored_as) in [
+            (&files.vcf, &indexed.vcf, "VCF"),
+            (&files.gff, &indexed.gff, "GFF"),
+        ] {
+            if target.exists() {
+                continue;
+            }
+
+            let table = format!("synthetic_{}", stored_as.to_lowercase());
+            register_table(ctx, &table, stored_as, source, None).await?;
+
+            ctx.session
+                .sql(&format!(
+                    "COPY {table} TO '{}' STORED AS {stored_as} OPTIONS (compression 'bgzip', tabix 'true')",
+                    target.display()
+                ))
+                .await?
+                .collect()
+                .await?;
+
+            ctx.session.deregister_table(table.as_str())?;
+        }
+
+        Ok(indexed)
+    }
+
+    fn path(&self, extension: &str) -> PathBuf {
+        self.dir.join(format!(
+            "synthetic-{}-{}.{}",
+            self.records, self.seed, extension
+        ))
+    }
+
+    fn write_if_missing(
+        &self,
+        path: &Path,
+        write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+    ) -> io::Result<()> {
+        if path.exists() {
+            return Ok(());
+        }
+
+        // Write to a temporary file first so an interrupted run doesn't leave a partial file.
+        let tmp = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        write(&mut writer)?;
+        writer.flush()?;
+
+        std::fs::rename(tmp, path)
+    }
+
+    /// The contig and 1-based position of the i-th record, so records are sorted and spread
+    /// evenly across `CONTIGS`.
+    fn position(&self, i: usize) -> (&'static str, u64) {
+        let per_contig = self.records.div_ceil(CONTIGS.len()).max(1);
+        let (name, length) = CONTIGS[i / per_contig];
+
+        let step = (length / per_contig as u64).max(1);
+        let pos = (i % per_contig) as u64 * step + 1;
+
+        (name, pos)
+    }
+
+    fn sequence(&self, rng: &mut StdRng) -> String {
+        (0..self.sequence_length)
+            .map(|_| BASES[rng.gen_range(0..BASES.len())] as char)
+            .collect()
+    }
+
+    fn write_fasta(&self, w: &mut impl Write, rng: &mut StdRng) -> io::Result<()> {
+        for i in 0..self.records {
+            let sequence = self.sequence(rng);
+            writeln!(w, ">seq{i} synthetic sequence")?;
+
+            for line in sequence.as_bytes().chunks(60) {
+                w.write_all(line)?;
+                writeln!(w)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_fastq(&self, w: &mut impl Write, rng: &mut StdRng) -> io::Result<()> {
+        for i in 0..self.records {
+            let sequence = self.sequence(rng);
+            let quality: String = (0..self.sequence_length)
+                .map(|_| (b'!' + rng.gen_range(2..41)) as char)
+                .collect();
+
+            writeln!(w, "@read{i}\n{sequence}\n+\n{quality}")?;
+        }
+
+        Ok(())
+    }
+
+    fn write_vcf(&self, w: &mut impl Write, rng: &mut StdRng) -> io::Result<()> {
+        writeln!(w, "##fileformat=VCFv4.3")?;
+        for (name, length) in CONTIGS {
+            writeln!(w, "##contig=<ID={name},length={length}>")?;
+        }
+        writeln!(
+            w,
+            "##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Total depth\">"
+        )?;
+        writeln!(
+            w,
+            "##INFO=<ID=AF,Number=A,Type=Float,Description=\"Allele frequency\">"
+        )?;
+        writeln!(w, "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO")?;
+
+        for i in 0..self.records {
+            let (chrom, pos) = self.position(i);
+            let reference = BASES[rng.gen_range(0..BASES.len())];
+            let alternate = BASES[(BASES.iter().position(|b| *b == reference).unwrap_or(0)
+                + rng.gen_range(1..BASES.len()))
+                % BASES.len()];
+
+            writeln!(
+                w,
+                "{chrom}\t{pos}\tvar{i}\t{}\t{}\t{}\tPASS\tDP={};AF={:.3}",
+                reference as char,
+                alternate as char,
+                rng.gen_range(10..100),
+                rng.gen_range(1..200),
+                rng.gen_range(0.0..1.0),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_gff(&self, w: &mut impl Write, rng: &mut StdRng) -> io::Result<()> {
+        writeln!(w, "##gff-version 3")?;
+
+        for i in 0..self.records {
+            let (seqname, start) = self.position(i);
+            let end = start + rng.gen_range(50..2_000);
+            let strand = if rng.gen_bool(0.5) { '+' } else { '-' };
+
+            writeln!(
+                w,
+                "{seqname}\tsynthetic\tgene\t{start}\t{end}\t.\t{strand}\t.\tID=gene{i};Name=GENE{i}"
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_bed(&self, w: &mut impl Write, rng: &mut StdRng) -> io::Result<()> {
+        for i in 0..self.records {
+            let (chrom, pos) = self.position(i);
+            let start = pos - 1;
+            let end = start + rng.gen_range(50..2_000);
+            let strand = if rng.gen_bool(0.5) { '+' } else { '-' };
+
+            writeln!(
+                w,
+                "{chrom}\t{start}\t{end}\tfeature{i}\t{}\t{strand}",
+                rng.gen_range(0..1000)
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_sam(&self, w: &mut impl Write, rng: &mut StdRng) -> io::Result<()> {
+        writeln!(w, "@HD\tVN:1.6\tSO:coordinate")?;
+        for (name, length) in CONTIGS {
+            writeln!(w, "@SQ\tSN:{name}\tLN:{length}")?;
+        }
+
+        for i in 0..self.records {
+            let (reference, pos) = self.position(i);
+            let sequence = self.sequence(rng);
+            let quality: String = (0..self.sequence_length)
+                .map(|_| (b'!' + rng.gen_range(2..41)) as char)
+                .collect();
+            let flag = if rng.gen_bool(0.5) { 0 } else { 16 };
+
+            writeln!(
+                w,
+                "read{i}\t{flag}\t{reference}\t{pos}\t60\t{}M\t*\t0\t0\t{sequence}\t{quality}",
+                self.sequence_length
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufRead;
+
+    use super::SyntheticData;
+
+    #[test]
+    fn test_generate() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join("exon-benchmarks-synthetic-test");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let files = SyntheticData::new(&dir, 10)
+            .with_sequence_length(20)
+            .generate()?;
+
+        let count_lines = |path: &std::path::Path| -> std::io::Result<usize> {
+            let file = std::io::BufReader::new(std::fs::File::open(path)?);
+            Ok(file.lines().count())
+        };
+
+        assert_eq!(count_lines(&files.fasta)?, 20);
+        assert_eq!(count_lines(&files.fastq)?, 40);
+        // 7 header lines
+        assert_eq!(count_lines(&files.vcf)?, 17);
+        assert_eq!(count_lines(&files.gff)?, 11);
+        assert_eq!(count_lines(&files.bed)?, 10);
+        // 4 header lines
+        assert_eq!(count_lines(&files.sam)?, 14);
+
+        std::fs::remove_dir_all(&dir)?;
+
+        Ok(())
+    }
+}
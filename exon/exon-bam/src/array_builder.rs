@@ -12,21 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::Arc;
+use std::{fmt::Write, sync::Arc};
 
 use arrow::{
-    array::{ArrayRef, GenericListBuilder, GenericStringBuilder, Int32Builder, Int64Builder},
+    array::{ArrayRef, BinaryBuilder, GenericStringBuilder, Int64Builder},
+    datatypes::DataType,
     error::ArrowError,
 };
-use exon_common::ExonArrayBuilder;
-use exon_sam::TagsBuilder;
+use exon_common::{packed_sequence::pack_sequence, ExonArrayBuilder};
+use exon_sam::{
+    has_compact_types, FlagsBuilder, MappingQualitiesBuilder, PacBioBuilder, QualityScoresBuilder,
+    TagsBuilder, PACBIO_COLUMN_OFFSET,
+};
 use noodles::sam::{
-    alignment::record::{cigar::op::Kind, Cigar},
+    alignment::{
+        record::{cigar::op::Kind, data::field::Tag},
+        record_buf::Data,
+    },
     Header,
 };
 
 const BATCH_SIZE: usize = 8192;
 
+const MISSING_QUALITY_SCORE: u8 = 0xff;
+
 use crate::BAMConfig;
 
 use super::indexed_async_batch_stream::SemiLazyRecord;
@@ -34,18 +43,24 @@ use super::indexed_async_batch_stream::SemiLazyRecord;
 /// Builds an vector of arrays from a SAM file.
 pub struct BAMArrayBuilder {
     names: GenericStringBuilder<i32>,
-    flags: Int32Builder,
+    flags: FlagsBuilder,
     references: GenericStringBuilder<i32>,
     starts: Int64Builder,
     ends: Int64Builder,
-    mapping_qualities: GenericStringBuilder<i32>,
+    mapping_qualities: MappingQualitiesBuilder,
     cigar: GenericStringBuilder<i32>,
     mate_references: GenericStringBuilder<i32>,
     sequences: GenericStringBuilder<i32>,
-    quality_scores: GenericListBuilder<i32, Int64Builder>,
+    packed_sequences: Option<BinaryBuilder>,
+    packed_sequence: Vec<u8>,
+    /// The decoded bases of the current record, reused across records.
+    sequence: Vec<u8>,
+    quality_scores: QualityScoresBuilder,
 
     tags: TagsBuilder,
 
+    pacbio: PacBioBuilder,
+
     projection: Vec<usize>,
 
     rows: usize,
@@ -64,8 +79,6 @@ impl BAMArrayBuilder {
 
         let item_capacity = BATCH_SIZE;
 
-        let quality_score_inner = Int64Builder::new();
-
         let tags_builder = bam_config
             .file_schema
             .field_with_name("tags")
@@ -73,23 +86,36 @@ impl BAMArrayBuilder {
                 TagsBuilder::try_from(field.data_type()).unwrap()
             });
 
+        let packed_sequences = bam_config
+            .file_schema
+            .field_with_name("sequence")
+            .is_ok_and(|field| field.data_type() == &DataType::Binary)
+            .then(BinaryBuilder::new);
+
+        let compact = has_compact_types(&bam_config.file_schema);
+
         Self {
             names: GenericStringBuilder::<i32>::new(),
-            flags: Int32Builder::new(),
+            flags: FlagsBuilder::new(compact),
             references: GenericStringBuilder::<i32>::with_capacity(
                 item_capacity,
                 item_capacity * 10,
             ),
             starts: Int64Builder::with_capacity(item_capacity),
             ends: Int64Builder::with_capacity(item_capacity),
-            mapping_qualities: GenericStringBuilder::<i32>::new(),
+            mapping_qualities: MappingQualitiesBuilder::new(compact),
             cigar: GenericStringBuilder::<i32>::new(),
             mate_references: GenericStringBuilder::<i32>::new(),
             sequences: GenericStringBuilder::<i32>::new(),
-            quality_scores: GenericListBuilder::new(quality_score_inner),
+            packed_sequences,
+            packed_sequence: Vec::new(),
+            sequence: Vec::new(),
+            quality_scores: QualityScoresBuilder::new(compact),
 
             tags: tags_builder,
 
+            pacbio: PacBioBuilder::default(),
+
             projection: bam_config.projection(),
 
             rows: 0,
@@ -98,8 +124,15 @@ impl BAMArrayBuilder {
         }
     }
 
-    /// Appends a record to the builder.
+    /// Appends a record to the builder, reading its fields from the lazy record buffer.
     pub(crate) fn append(&mut self, record: &SemiLazyRecord) -> Result<(), ArrowError> {
+        // The data fields are only decoded if the tags or PacBio columns are projected.
+        let data = if self.projection.iter().any(|col_idx| *col_idx >= 10) {
+            decode_data(record.record())?
+        } else {
+            Data::default()
+        };
+
         for col_idx in self.projection.iter() {
             match col_idx {
                 0 => {
@@ -112,10 +145,9 @@ impl BAMArrayBuilder {
                     }
                 }
                 1 => {
-                    let flag_bits = record.record().flags().bits();
-                    self.flags.append_value(flag_bits as i32);
+                    self.flags.append_value(record.record().flags().bits());
                 }
-                2 => match record.record().reference_sequence_id() {
+                2 => match record.reference_sequence_id() {
                     Some(reference_sequence_id) => {
                         let reference_name = &self.reference_names[reference_sequence_id];
 
@@ -127,26 +159,19 @@ impl BAMArrayBuilder {
                 },
                 3 => {
                     self.starts
-                        .append_option(record.record().alignment_start().map(|v| v.get() as i64));
+                        .append_option(record.alignment_start().map(|v| v.get() as i64));
                 }
                 4 => {
                     let alignment_end = record.alignment_end().map(|v| v.get() as i64);
                     self.ends.append_option(alignment_end);
                 }
                 5 => {
-                    self.mapping_qualities.append_option(
-                        record
-                            .record()
-                            .mapping_quality()
-                            .map(|v| v.get().to_string()),
-                    );
+                    self.mapping_qualities
+                        .append_option(record.record().mapping_quality().map(|v| v.get()));
                 }
                 6 => {
-                    let cigar = record.record().cigar();
-
-                    let mut cigar_to_print = Vec::new();
-
-                    for op_result in cigar.iter() {
+                    // The operations are written straight into the string builder's buffer.
+                    for op_result in record.record().cigar().iter() {
                         let op = op_result?;
 
                         let kind_str = match op.kind() {
@@ -161,12 +186,13 @@ impl BAMArrayBuilder {
                             Kind::SequenceMatch => "=",
                         };
 
-                        cigar_to_print.push(format!("{}{}", op.len(), kind_str));
+                        write!(self.cigar, "{}{}", op.len(), kind_str)
+                            .map_err(|e| ArrowError::ExternalError(Box::new(e)))?;
                     }
 
-                    self.cigar.append_value(cigar_to_print.join(""));
+                    self.cigar.append_value("");
                 }
-                7 => match record.record().mate_reference_sequence_id() {
+                7 => match record.record().mate_reference_sequence_id().transpose()? {
                     Some(mate_reference_sequence_id) => {
                         let mate_reference_name = &self.reference_names[mate_reference_sequence_id];
 
@@ -177,31 +203,40 @@ impl BAMArrayBuilder {
                     }
                 },
                 8 => {
-                    let sequence = record.record().sequence().as_ref();
-                    let sequence_str = std::str::from_utf8(sequence)?;
+                    // BAM sequences are 4 bits per base, so they're decoded into a reused buffer.
+                    self.sequence.clear();
+                    self.sequence.extend(record.record().sequence().iter());
+
+                    if let Some(packed_sequences) = &mut self.packed_sequences {
+                        pack_sequence(&self.sequence, &mut self.packed_sequence)
+                            .map_err(|e| ArrowError::ExternalError(Box::new(e)))?;
 
-                    self.sequences.append_value(sequence_str);
+                        packed_sequences.append_value(&self.packed_sequence);
+                    } else {
+                        let sequence_str = std::str::from_utf8(&self.sequence)?;
+
+                        self.sequences.append_value(sequence_str);
+                    }
                 }
                 9 => {
                     let quality_scores = record.record().quality_scores();
-
-                    let quality_scores_str = quality_scores.as_ref();
-                    let slice_i8: &[i8] = unsafe {
-                        std::slice::from_raw_parts(
-                            quality_scores_str.as_ptr() as *const i8,
-                            quality_scores_str.len(),
-                        )
-                    };
-
-                    // Convert the i8s into i64s
-                    let slice_i64 = slice_i8.iter().map(|v| *v as i64).collect::<Vec<_>>();
-
-                    self.quality_scores.values().append_slice(&slice_i64);
-                    self.quality_scores.append(true);
+                    let quality_scores = quality_scores.as_ref();
+
+                    // Missing quality scores are stored as 0xFF for every base.
+                    if quality_scores
+                        .iter()
+                        .all(|score| *score == MISSING_QUALITY_SCORE)
+                    {
+                        self.quality_scores.append(&[]);
+                    } else {
+                        self.quality_scores.append(quality_scores);
+                    }
                 }
                 10 => {
-                    let data = record.record().data();
-                    self.tags.append(data)?;
+                    self.tags.append(&data)?;
+                }
+                11..=15 => {
+                    self.pacbio.append(col_idx - PACBIO_COLUMN_OFFSET, &data)?;
                 }
                 _ => {
                     return Err(ArrowError::InvalidArgumentError(format!(
@@ -224,19 +259,23 @@ impl BAMArrayBuilder {
         for col_idx in self.projection.iter() {
             match col_idx {
                 0 => arrays.push(Arc::new(self.names.finish())),
-                1 => arrays.push(Arc::new(self.flags.finish())),
+                1 => arrays.push(self.flags.finish()),
                 2 => arrays.push(Arc::new(self.references.finish())),
                 3 => arrays.push(Arc::new(self.starts.finish())),
                 4 => arrays.push(Arc::new(self.ends.finish())),
-                5 => arrays.push(Arc::new(self.mapping_qualities.finish())),
+                5 => arrays.push(self.mapping_qualities.finish()),
                 6 => arrays.push(Arc::new(self.cigar.finish())),
                 7 => arrays.push(Arc::new(self.mate_references.finish())),
-                8 => arrays.push(Arc::new(self.sequences.finish())),
-                9 => arrays.push(Arc::new(self.quality_scores.finish())),
+                8 => match &mut self.packed_sequences {
+                    Some(packed_sequences) => arrays.push(Arc::new(packed_sequences.finish())),
+                    None => arrays.push(Arc::new(self.sequences.finish())),
+                },
+                9 => arrays.push(self.quality_scores.finish()),
                 10 => {
                     let tags = self.tags.finish();
                     arrays.push(Arc::new(tags))
                 }
+                11..=15 => arrays.push(self.pacbio.finish(col_idx - PACBIO_COLUMN_OFFSET)),
                 _ => panic!("Invalid column index {} for SAM", col_idx),
             }
         }
@@ -256,3 +295,19 @@ impl ExonArrayBuilder for BAMArrayBuilder {
         self.rows
     }
 }
+
+/// Decode the data fields of a lazy record. A long cigar's CG field is skipped, as the lazy record
+/// already reads it as the record's cigar.
+fn decode_data(record: &noodles::bam::Record) -> std::io::Result<Data> {
+    let mut data = Data::default();
+
+    for result in record.data().iter() {
+        let (tag, value) = result?;
+
+        if tag != Tag::CIGAR {
+            data.insert(tag, value.try_into()?);
+        }
+    }
+
+    Ok(data)
+}
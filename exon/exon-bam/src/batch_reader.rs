@@ -16,12 +16,17 @@ use std::sync::Arc;
 
 use arrow::{error::ArrowError, record_batch::RecordBatch};
 
-use exon_common::ExonArrayBuilder;
+use exon_common::{
+    ExonArrayBuilder, ReaderLimitError, RecordSampling, SampleMethod, StratifiedReservoirs,
+};
 use futures::Stream;
-use noodles::sam::{alignment::RecordBuf, Header};
+use noodles::{bgzf::VirtualPosition, sam::Header};
 use tokio::io::{AsyncBufRead, AsyncRead};
 
-use crate::{indexed_async_batch_stream::SemiLazyRecord, BAMArrayBuilder, BAMConfig};
+use crate::{
+    indexed_async_batch_stream::SemiLazyRecord, limited_reader::read_header_with_limits,
+    BAMArrayBuilder, BAMConfig,
+};
 
 /// A batch reader for BAM files.
 pub struct BatchReader<R>
@@ -35,6 +40,15 @@ where
     config: Arc<BAMConfig>,
 
     header: Arc<Header>,
+
+    /// The virtual position of the first record not to read, if the reader stops before EOF.
+    end: Option<VirtualPosition>,
+
+    /// The records of a reservoir sample left to return, once the file has been read.
+    sampled: Option<std::vec::IntoIter<SemiLazyRecord>>,
+
+    /// The buffer records are read into.
+    buf: Vec<u8>,
 }
 
 impl<R> BatchReader<R>
@@ -44,20 +58,52 @@ where
     pub async fn new(inner: R, config: Arc<BAMConfig>) -> std::io::Result<Self> {
         let mut reader = noodles::bam::AsyncReader::new(inner);
 
-        let header = reader.read_header().await.map_err(|e| {
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("invalid BAM header: {}", e),
-            )
-        })?;
+        let header = read_header_with_limits(reader.get_mut(), &config.reader_limits)
+            .await
+            .map_err(|e| {
+                if ReaderLimitError::find(&e).is_some() {
+                    return e;
+                }
+
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("invalid BAM header: {}", e),
+                )
+            })?;
 
         Ok(Self {
             reader,
             config,
             header: Arc::new(header),
+            end: None,
+            sampled: None,
+            buf: Vec::new(),
         })
     }
 
+    /// Create a batch reader from a BAM reader positioned at a record, with the header read
+    /// separately.
+    pub fn from_reader(
+        reader: noodles::bam::AsyncReader<noodles::bgzf::AsyncReader<R>>,
+        header: Arc<Header>,
+        config: Arc<BAMConfig>,
+    ) -> Self {
+        Self {
+            reader,
+            config,
+            header,
+            end: None,
+            sampled: None,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Stop reading at `end`, the virtual position of a record relative to the reader.
+    pub fn with_end(mut self, end: VirtualPosition) -> Self {
+        self.end = Some(end);
+        self
+    }
+
     pub fn into_stream(self) -> impl Stream<Item = Result<RecordBatch, ArrowError>> {
         futures::stream::unfold(self, |mut reader| async move {
             match reader.read_batch().await {
@@ -68,16 +114,19 @@ where
         })
     }
 
-    async fn read_record(&mut self) -> Result<Option<RecordBuf>, ArrowError> {
-        let mut record_buf = RecordBuf::default();
+    async fn read_record(&mut self, record: &mut SemiLazyRecord) -> Result<Option<()>, ArrowError> {
+        if let Some(end) = self.end {
+            if self.reader.get_ref().virtual_position() >= end {
+                return Ok(None);
+            }
+        }
 
-        match self
-            .reader
-            .read_record_buf(&self.header, &mut record_buf)
+        match record
+            .read_from(&mut self.reader, &mut self.buf, &self.config.reader_limits)
             .await
         {
             Ok(0) => Ok(None),
-            Ok(_) => Ok(Some(record_buf)),
+            Ok(_) => Ok(Some(())),
             Err(e) => {
                 let err = std::io::Error::new(e.kind(), format!("Error: {:?}", e));
                 Err(ArrowError::ExternalError(Box::new(err)))
@@ -85,19 +134,79 @@ where
         }
     }
 
+    /// Whether a record is in a fractional sample, if any, by its name.
+    fn is_sampled(&self, record: &SemiLazyRecord) -> bool {
+        self.config
+            .sampling
+            .is_none_or(|sampling| match record.record().name() {
+                Some(name) => sampling.keep(name),
+                None => sampling.keep(b""),
+            })
+    }
+
+    /// Read the rest of the records into a reservoir sample, of the whole file or of each region.
+    async fn read_sample(
+        &mut self,
+        sampling: RecordSampling,
+    ) -> Result<Vec<SemiLazyRecord>, ArrowError> {
+        let mut record = SemiLazyRecord::default();
+
+        match sampling.method() {
+            SampleMethod::Stratified { size, region_size } => {
+                let mut reservoirs = StratifiedReservoirs::new(sampling, size, region_size);
+
+                while self.read_record(&mut record).await?.is_some() {
+                    reservoirs.offer(
+                        record.reference_sequence_id(),
+                        record.alignment_start().map(usize::from),
+                        || record.clone(),
+                    );
+                }
+
+                Ok(reservoirs.into_items())
+            }
+            SampleMethod::Reservoir(size) => {
+                let mut reservoir = sampling.reservoir(size, ());
+
+                while self.read_record(&mut record).await?.is_some() {
+                    reservoir.offer(|| record.clone());
+                }
+
+                Ok(reservoir.into_items())
+            }
+            SampleMethod::Fraction(_) => Ok(Vec::new()),
+        }
+    }
+
     async fn read_batch(&mut self) -> Result<Option<RecordBatch>, ArrowError> {
         let mut builder = BAMArrayBuilder::create(self.header.clone(), self.config.clone());
+        let mut rows = 0;
 
-        for i in 0..self.config.batch_size {
-            if let Some(record) = self.read_record().await? {
-                let semi_lazy_record = SemiLazyRecord::try_from(record)?;
+        if let Some(sampling) = self.config.sampling.filter(RecordSampling::is_per_file) {
+            if self.sampled.is_none() {
+                let records = self.read_sample(sampling).await?;
+                self.sampled = Some(records.into_iter());
+            }
 
-                builder.append(&semi_lazy_record)?;
-            } else if i == 0 {
-                return Ok(None);
-            } else {
-                break;
+            if let Some(sampled) = self.sampled.as_mut() {
+                for record in sampled.take(self.config.batch_size) {
+                    builder.append(&record)?;
+                    rows += 1;
+                }
             }
+        } else {
+            let mut record = SemiLazyRecord::default();
+
+            while rows < self.config.batch_size && self.read_record(&mut record).await?.is_some() {
+                if self.is_sampled(&record) {
+                    builder.append(&record)?;
+                    rows += 1;
+                }
+            }
+        }
+
+        if rows == 0 {
+            return Ok(None);
         }
 
         let schema = self.config.projected_schema()?;
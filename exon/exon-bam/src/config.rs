@@ -15,6 +15,7 @@
 use std::sync::Arc;
 
 use arrow::datatypes::SchemaRef;
+use exon_common::{ReaderLimits, RecordSampling};
 use object_store::ObjectStore;
 
 /// The configuration for the BAM data source.
@@ -30,6 +31,12 @@ pub struct BAMConfig {
 
     /// Any projections to apply to the resulting batches.
     pub projection: Option<Vec<usize>>,
+
+    /// A sample of the records to read, instead of all of them.
+    pub sampling: Option<RecordSampling>,
+
+    /// The limits on header size, record size, sequence length, and tag count.
+    pub reader_limits: ReaderLimits,
 }
 
 impl BAMConfig {
@@ -40,6 +47,8 @@ impl BAMConfig {
             file_schema,
             batch_size: 8096,
             projection: None,
+            sampling: None,
+            reader_limits: ReaderLimits::default(),
         }
     }
 
@@ -61,6 +70,18 @@ impl BAMConfig {
         self
     }
 
+    /// Set the reader limits.
+    pub fn with_reader_limits(mut self, reader_limits: ReaderLimits) -> Self {
+        self.reader_limits = reader_limits;
+        self
+    }
+
+    /// Set the sample of the records to read.
+    pub fn with_sampling(mut self, sampling: Option<RecordSampling>) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
     /// Get the projection, returning the identity projection if none is set.
     pub fn projection(&self) -> Vec<usize> {
         self.projection
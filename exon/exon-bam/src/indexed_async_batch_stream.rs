@@ -18,48 +18,65 @@ use arrow::{
     error::{ArrowError, Result as ArrowResult},
     record_batch::RecordBatch,
 };
-use exon_common::ExonArrayBuilder;
+use exon_common::{ExonArrayBuilder, ReaderLimits};
 use futures::Stream;
 use noodles::{
     core::{region::Interval, Position, Region},
-    sam::{alignment::RecordBuf, header::ReferenceSequences, Header},
+    sam::{self, header::ReferenceSequences, Header},
 };
-use tokio::io::AsyncBufRead;
+use tokio::io::{AsyncBufRead, AsyncRead};
 
-use crate::ExonBAMError;
+use super::{array_builder::BAMArrayBuilder, limited_reader::read_record_with_limits, BAMConfig};
 
-use super::{array_builder::BAMArrayBuilder, BAMConfig};
-
-/// This is a semi-lazy record that can be used to filter on the region without
-/// having to decode the entire record or re-decode the cigar.
+/// A lazy BAM record with its positions decoded, so it can be filtered on the region without
+/// decoding the rest of the record or re-decoding the cigar.
+///
+/// The record buffer is reused as records are read into it.
+#[derive(Clone, Default)]
 pub(crate) struct SemiLazyRecord {
-    inner: RecordBuf,
+    inner: noodles::bam::Record,
+    reference_sequence_id: Option<usize>,
+    alignment_start: Option<Position>,
     alignment_end: Option<Position>,
 }
 
-impl TryFrom<RecordBuf> for SemiLazyRecord {
-    type Error = ExonBAMError;
+impl SemiLazyRecord {
+    /// Read the next record into this one, using `buf` to hold its bytes, returning the number of
+    /// bytes read, 0 at EOF.
+    pub(crate) async fn read_from<R>(
+        &mut self,
+        reader: &mut noodles::bam::AsyncReader<R>,
+        buf: &mut Vec<u8>,
+        limits: &ReaderLimits,
+    ) -> std::io::Result<usize>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let bytes_read =
+            read_record_with_limits(reader.get_mut(), buf, &mut self.inner, limits).await?;
+
+        if bytes_read > 0 {
+            self.reference_sequence_id = self.inner.reference_sequence_id().transpose()?;
+            self.alignment_start = self.inner.alignment_start().transpose()?;
+            self.alignment_end = sam::alignment::Record::alignment_end(&self.inner).transpose()?;
+        }
 
-    fn try_from(record: RecordBuf) -> Result<Self, Self::Error> {
-        let alignment_end = record.alignment_end();
+        Ok(bytes_read)
+    }
 
-        Ok(Self {
-            inner: record,
-            alignment_end,
-        })
+    pub fn reference_sequence_id(&self) -> Option<usize> {
+        self.reference_sequence_id
     }
-}
 
-impl SemiLazyRecord {
     pub fn alignment_start(&self) -> Option<Position> {
-        self.inner.alignment_start()
+        self.alignment_start
     }
 
     pub fn alignment_end(&self) -> Option<Position> {
         self.alignment_end
     }
 
-    pub fn record(&self) -> &RecordBuf {
+    pub fn record(&self) -> &noodles::bam::Record {
         &self.inner
     }
 
@@ -68,7 +85,7 @@ impl SemiLazyRecord {
         region_sequence_id: usize,
         region_interval: &Interval,
     ) -> std::io::Result<bool> {
-        let reference_sequence_id = self.inner.reference_sequence_id();
+        let reference_sequence_id = self.reference_sequence_id();
 
         let alignment_start = self.alignment_start();
         let alignment_end = self.alignment_end();
@@ -108,6 +125,13 @@ where
 
     /// The max uncompressed bytes read.
     max_bytes: Option<u16>,
+
+    /// The interval of the previous region on the same reference sequence, if any. Records that
+    /// also intersect it were already read for that region.
+    preceding_interval: Option<Interval>,
+
+    /// The buffer records are read into.
+    buf: Vec<u8>,
 }
 
 fn get_reference_sequence_for_region(
@@ -145,6 +169,8 @@ where
             region_reference,
             region_interval,
             max_bytes: None,
+            preceding_interval: None,
+            buf: Vec::new(),
         })
     }
 
@@ -152,6 +178,22 @@ where
         self.max_bytes = Some(max_bytes);
     }
 
+    /// Skip records that intersect the given interval on the region's reference sequence.
+    pub fn set_preceding_interval(&mut self, preceding_interval: Interval) {
+        self.preceding_interval = Some(preceding_interval);
+    }
+
+    fn is_in_region(&self, record: &SemiLazyRecord) -> std::io::Result<bool> {
+        if !record.intersects(self.region_reference, &self.region_interval)? {
+            return Ok(false);
+        }
+
+        match &self.preceding_interval {
+            Some(interval) => Ok(!record.intersects(self.region_reference, interval)?),
+            None => Ok(true),
+        }
+    }
+
     /// Stream the record batches from the VCF file.
     pub fn into_stream(self) -> impl Stream<Item = Result<RecordBatch, ArrowError>> {
         futures::stream::unfold(self, |mut reader| async move {
@@ -163,14 +205,16 @@ where
         })
     }
 
-    async fn read_record(&mut self, record: &mut RecordBuf) -> std::io::Result<Option<()>> {
+    async fn read_record(&mut self, record: &mut SemiLazyRecord) -> std::io::Result<Option<()>> {
         if let Some(max_bytes) = self.max_bytes {
             if self.reader.get_ref().virtual_position().uncompressed() >= max_bytes {
                 return Ok(None);
             }
         }
 
-        let bytes_read = self.reader.read_record_buf(&self.header, record).await?;
+        let bytes_read = record
+            .read_from(&mut self.reader, &mut self.buf, &self.config.reader_limits)
+            .await?;
 
         if bytes_read == 0 {
             Ok(None)
@@ -181,14 +225,12 @@ where
 
     async fn read_record_batch(&mut self) -> ArrowResult<Option<arrow::record_batch::RecordBatch>> {
         let mut builder = BAMArrayBuilder::create(self.header.clone(), self.config.clone());
-        let mut record = RecordBuf::default();
+        let mut record = SemiLazyRecord::default();
 
         for i in 0..self.config.batch_size {
             if self.read_record(&mut record).await?.is_some() {
-                let semi_lazy_record = SemiLazyRecord::try_from(record.clone())?;
-
-                if semi_lazy_record.intersects(self.region_reference, &self.region_interval)? {
-                    builder.append(&semi_lazy_record)?;
+                if self.is_in_region(&record)? {
+                    builder.append(&record)?;
                 }
             } else if i == 0 {
                 return Ok(None);
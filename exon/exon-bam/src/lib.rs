@@ -17,9 +17,11 @@ mod batch_reader;
 mod config;
 mod error;
 mod indexed_async_batch_stream;
+mod limited_reader;
 
 pub use array_builder::BAMArrayBuilder;
 pub use batch_reader::BatchReader;
 pub use config::BAMConfig;
 pub use error::ExonBAMError;
 pub use indexed_async_batch_stream::IndexedAsyncBatchStream;
+pub use limited_reader::{read_header_with_limits, read_record_with_limits};
@@ -0,0 +1,238 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+
+use exon_common::{
+    read_exact_onto, read_length_prefix, read_required_length_prefix, ReaderLimit, ReaderLimits,
+};
+use noodles::{bam, sam};
+use tokio::io::AsyncRead;
+
+/// The BAM magic number.
+const MAGIC_NUMBER: [u8; 4] = *b"BAM\x01";
+
+/// The smallest size of a reference sequence in the header, the name length, a one byte name, and
+/// the sequence length.
+const MIN_REFERENCE_SEQUENCE_SIZE: usize = 9;
+
+/// Read the BAM header from the decompressed stream.
+///
+/// The SAM header text and reference sequence lengths are checked against the header size limit
+/// before the header is buffered, so a corrupt length can't allocate more than the limit.
+pub async fn read_header_with_limits<R>(
+    reader: &mut R,
+    limits: &ReaderLimits,
+) -> io::Result<sam::Header>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buf = Vec::new();
+
+    read_exact_onto(reader, &mut buf, MAGIC_NUMBER.len()).await?;
+    if buf[..] != MAGIC_NUMBER {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid BAM header: invalid magic number",
+        ));
+    }
+
+    let l_text = read_required_length_prefix(reader, &mut buf).await?;
+    limits.check(ReaderLimit::HeaderSize, buf.len().saturating_add(l_text))?;
+    read_exact_onto(reader, &mut buf, l_text).await?;
+
+    let n_ref = read_required_length_prefix(reader, &mut buf).await?;
+    limits.check(
+        ReaderLimit::HeaderSize,
+        buf.len()
+            .saturating_add(n_ref.saturating_mul(MIN_REFERENCE_SEQUENCE_SIZE)),
+    )?;
+
+    for _ in 0..n_ref {
+        let l_name = read_required_length_prefix(reader, &mut buf).await?;
+
+        // The name is followed by the reference sequence length.
+        let n = l_name.saturating_add(4);
+        limits.check(ReaderLimit::HeaderSize, buf.len().saturating_add(n))?;
+        read_exact_onto(reader, &mut buf, n).await?;
+    }
+
+    bam::io::Reader::from(&buf[..]).read_header()
+}
+
+/// Read a record from the decompressed stream into `record`, using `buf` to hold its bytes.
+///
+/// The block size is checked against the record size limit before the record is buffered, and the
+/// sequence length and tag count once it's read. Returns the block size, or 0 at EOF.
+pub async fn read_record_with_limits<R>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    record: &mut bam::Record,
+    limits: &ReaderLimits,
+) -> io::Result<usize>
+where
+    R: AsyncRead + Unpin,
+{
+    buf.clear();
+
+    let block_size = match read_length_prefix(reader, buf).await? {
+        None | Some(0) => return Ok(0),
+        Some(n) => n,
+    };
+
+    limits.check(ReaderLimit::RecordSize, block_size)?;
+    read_exact_onto(reader, buf, block_size).await?;
+
+    bam::io::Reader::from(&buf[..]).read_record(record)?;
+
+    limits.check(ReaderLimit::SequenceLength, record.sequence().len())?;
+    limits.check(ReaderLimit::AttributeCount, record.data().iter().count())?;
+
+    Ok(block_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use exon_common::{ReaderLimitError, ReaderLimits};
+    use noodles::bam;
+
+    use super::{read_header_with_limits, read_record_with_limits};
+
+    fn header_bytes(text: &[u8], reference_sequences: &[(&[u8], u32)]) -> Vec<u8> {
+        let mut buf = b"BAM\x01".to_vec();
+        buf.extend((text.len() as u32).to_le_bytes());
+        buf.extend(text);
+        buf.extend((reference_sequences.len() as u32).to_le_bytes());
+
+        for (name, length) in reference_sequences {
+            buf.extend((name.len() as u32 + 1).to_le_bytes());
+            buf.extend(*name);
+            buf.push(0);
+            buf.extend(length.to_le_bytes());
+        }
+
+        buf
+    }
+
+    fn record_bytes() -> Vec<u8> {
+        let mut record = vec![
+            0xff, 0xff, 0xff, 0xff, // ref_id = -1
+            0xff, 0xff, 0xff, 0xff, // pos = -1
+            0x02, // l_read_name = 2
+            0xff, // mapq = 255
+            0x48, 0x12, // bin = 4680
+            0x00, 0x00, // n_cigar_op = 0
+            0x04, 0x00, // flag = 4
+            0x04, 0x00, 0x00, 0x00, // l_seq = 4
+            0xff, 0xff, 0xff, 0xff, // next_ref_id = -1
+            0xff, 0xff, 0xff, 0xff, // next_pos = -1
+            0x00, 0x00, 0x00, 0x00, // tlen = 0
+            b'r', 0x00, // read_name = "r\x00"
+            0x12, 0x48, // seq = ACGT
+            0xff, 0xff, 0xff, 0xff, // qual
+            b'N', b'M', b'C', 0x00, // NM:C:0
+        ];
+
+        let mut buf = (record.len() as u32).to_le_bytes().to_vec();
+        buf.append(&mut record);
+        buf
+    }
+
+    #[tokio::test]
+    async fn test_read_within_limits() -> Result<(), Box<dyn std::error::Error>> {
+        let mut src = header_bytes(b"@HD\tVN:1.6\n", &[(b"sq0", 8)]);
+        src.extend(record_bytes());
+
+        let limits = ReaderLimits::default();
+        let mut reader = &src[..];
+
+        let header = read_header_with_limits(&mut reader, &limits).await?;
+        assert_eq!(header.reference_sequences().len(), 1);
+
+        let mut buf = Vec::new();
+        let mut record = bam::Record::default();
+
+        let n = read_record_with_limits(&mut reader, &mut buf, &mut record, &limits).await?;
+        assert_eq!(n, 44);
+        assert_eq!(record.sequence().len(), 4);
+
+        let n = read_record_with_limits(&mut reader, &mut buf, &mut record, &limits).await?;
+        assert_eq!(n, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_header_size_limit() {
+        let limits = ReaderLimits {
+            max_header_size: 16,
+            ..Default::default()
+        };
+
+        // The text length is checked before the text is read.
+        let mut src = b"BAM\x01".to_vec();
+        src.extend(u32::MAX.to_le_bytes());
+
+        let err = read_header_with_limits(&mut &src[..], &limits)
+            .await
+            .unwrap_err();
+        assert!(ReaderLimitError::find(&err).is_some());
+
+        let src = header_bytes(b"", &[(b"sq0", 8), (b"sq1", 8)]);
+        let err = read_header_with_limits(&mut &src[..], &limits)
+            .await
+            .unwrap_err();
+        assert!(ReaderLimitError::find(&err).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_record_limits() {
+        let mut buf = Vec::new();
+        let mut record = bam::Record::default();
+
+        let limits = ReaderLimits {
+            max_record_size: 16,
+            ..Default::default()
+        };
+
+        let mut src = u32::MAX.to_le_bytes().to_vec();
+        src.extend(record_bytes());
+
+        let err = read_record_with_limits(&mut &src[..], &mut buf, &mut record, &limits)
+            .await
+            .unwrap_err();
+        assert!(ReaderLimitError::find(&err).is_some());
+
+        let limits = ReaderLimits {
+            max_sequence_length: 3,
+            ..Default::default()
+        };
+
+        let src = record_bytes();
+        let err = read_record_with_limits(&mut &src[..], &mut buf, &mut record, &limits)
+            .await
+            .unwrap_err();
+        assert!(ReaderLimitError::find(&err).is_some());
+
+        let limits = ReaderLimits {
+            max_attribute_count: 0,
+            ..Default::default()
+        };
+
+        let err = read_record_with_limits(&mut &src[..], &mut buf, &mut record, &limits)
+            .await
+            .unwrap_err();
+        assert!(ReaderLimitError::find(&err).is_some());
+    }
+}
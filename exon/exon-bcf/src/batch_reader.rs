@@ -19,10 +19,13 @@ use exon_common::ExonArrayBuilder;
 use arrow::{error::ArrowError, record_batch::RecordBatch};
 
 use exon_vcf::VCFArrayBuilder;
-use noodles::{bcf::Record, vcf::variant::RecordBuf};
+use noodles::bcf::Record;
 use tokio::io::{AsyncBufRead, AsyncRead};
 
-use crate::config::BCFConfig;
+use crate::{
+    config::BCFConfig,
+    limited_reader::{read_header_with_limits, read_record_with_limits},
+};
 
 pub struct BatchReader<R>
 where
@@ -36,6 +39,9 @@ where
 
     /// The header.
     header: Arc<noodles::vcf::Header>,
+
+    /// The buffer records are read into.
+    buf: Vec<u8>,
 }
 
 impl<R> BatchReader<R>
@@ -44,27 +50,32 @@ where
 {
     pub async fn new(inner: R, config: Arc<BCFConfig>) -> std::io::Result<Self> {
         let mut reader = noodles::bcf::AsyncReader::new(inner);
-        // reader.read_file_format().await?;
 
-        let header = reader.read_header().await?;
+        let header = read_header_with_limits(reader.get_mut(), &config.reader_limits).await?;
 
         Ok(Self {
             reader,
             config,
             header: Arc::new(header),
+            buf: Vec::new(),
         })
     }
 
-    async fn read_record(&mut self) -> std::io::Result<Option<RecordBuf>> {
-        let mut lazy_record = Record::default();
-
-        match self.reader.read_record(&mut lazy_record).await? {
+    /// Read the next record, which is appended without converting it to a `RecordBuf`, so only
+    /// the FORMAT values of the selected samples are decoded.
+    async fn read_record(&mut self) -> std::io::Result<Option<Record>> {
+        let mut record = Record::default();
+
+        match read_record_with_limits(
+            self.reader.get_mut(),
+            &mut self.buf,
+            &mut record,
+            &self.config.reader_limits,
+        )
+        .await?
+        {
             0 => Ok(None),
-            _ => {
-                let vcf_record = RecordBuf::try_from_variant_record(&self.header, &lazy_record)?;
-
-                Ok(Some(vcf_record))
-            }
+            _ => Ok(Some(record)),
         }
     }
 
@@ -74,7 +85,8 @@ where
             self.config.batch_size,
             None,
             self.header.clone(),
-        )?;
+        )?
+        .with_sample_selection(self.config.sample_selection(&self.header)?);
 
         for _ in 0..self.config.batch_size {
             match self.read_record().await? {
@@ -139,7 +151,8 @@ impl BatchAdapter {
             self.config.batch_size,
             self.config.projection.clone(),
             self.header.clone(),
-        )?;
+        )?
+        .with_sample_selection(self.config.sample_selection(&self.header)?);
 
         for _ in 0..self.config.batch_size {
             match self.record_iterator.next() {
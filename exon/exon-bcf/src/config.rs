@@ -14,8 +14,10 @@
 
 use std::sync::Arc;
 
-use arrow::datatypes::SchemaRef;
-use exon_common::DEFAULT_BATCH_SIZE;
+use arrow::{datatypes::SchemaRef, error::ArrowError};
+use exon_common::{ReaderLimits, DEFAULT_BATCH_SIZE};
+use exon_vcf::SampleSelection;
+use noodles::vcf::Header;
 use object_store::ObjectStore;
 
 /// Configuration for a BCF datasource.
@@ -31,6 +33,12 @@ pub struct BCFConfig {
 
     /// Any projections to apply to the resulting batches.
     pub projection: Option<Vec<usize>>,
+
+    /// The names of the samples to decode the FORMAT values of, or all samples if `None`.
+    pub samples: Option<Vec<String>>,
+
+    /// The limits on header size, record size, and INFO and FORMAT key count.
+    pub reader_limits: ReaderLimits,
 }
 
 impl BCFConfig {
@@ -41,6 +49,8 @@ impl BCFConfig {
             batch_size: DEFAULT_BATCH_SIZE,
             file_schema,
             projection: None,
+            samples: None,
+            reader_limits: ReaderLimits::default(),
         }
     }
 
@@ -62,6 +72,26 @@ impl BCFConfig {
         self
     }
 
+    /// Set the samples to decode the FORMAT values of.
+    pub fn with_samples(mut self, samples: Option<Vec<String>>) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Set the reader limits.
+    pub fn with_reader_limits(mut self, reader_limits: ReaderLimits) -> Self {
+        self.reader_limits = reader_limits;
+        self
+    }
+
+    /// Resolve the selected samples against the header of a file.
+    pub fn sample_selection(&self, header: &Header) -> Result<Option<SampleSelection>, ArrowError> {
+        self.samples
+            .as_ref()
+            .map(|samples| SampleSelection::try_new(header, samples))
+            .transpose()
+    }
+
     /// Get the projection, returning the identity projection if none is set.
     pub fn projection(&self) -> Vec<usize> {
         self.projection
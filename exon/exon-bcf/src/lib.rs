@@ -14,6 +14,8 @@
 
 mod batch_reader;
 mod config;
+mod limited_reader;
 
 pub use batch_reader::{BatchAdapter, BatchReader};
 pub use config::BCFConfig;
+pub use limited_reader::{query_with_limits, read_header_with_limits, read_record_with_limits};
@@ -0,0 +1,265 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+
+use exon_common::{
+    read_exact_onto, read_length_prefix, read_required_length_prefix, ReaderLimit, ReaderLimits,
+};
+use noodles::{
+    bcf, bgzf,
+    core::{region::Interval, Region},
+    csi::BinningIndex,
+    vcf,
+    vcf::variant::record::Info,
+};
+use tokio::io::{AsyncRead, AsyncSeek};
+
+/// The size of the magic number and format version at the start of a BCF file.
+const PREFIX_SIZE: usize = 5;
+
+/// Read the BCF header from the decompressed stream.
+///
+/// The header text length is checked against the header size limit before the header is buffered.
+pub async fn read_header_with_limits<R>(
+    reader: &mut R,
+    limits: &ReaderLimits,
+) -> io::Result<vcf::Header>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buf = Vec::new();
+
+    read_exact_onto(reader, &mut buf, PREFIX_SIZE).await?;
+
+    let l_text = read_required_length_prefix(reader, &mut buf).await?;
+    limits.check(ReaderLimit::HeaderSize, buf.len().saturating_add(l_text))?;
+    read_exact_onto(reader, &mut buf, l_text).await?;
+
+    bcf::io::Reader::from(&buf[..]).read_header()
+}
+
+/// Read a record from the decompressed stream into `record`, using `buf` to hold its bytes.
+///
+/// The shared and per sample lengths are checked against the record size limit before the record
+/// is buffered, and the INFO and FORMAT key count once it's read. Returns the record size, or 0 at
+/// EOF.
+pub async fn read_record_with_limits<R>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    record: &mut bcf::Record,
+    limits: &ReaderLimits,
+) -> io::Result<usize>
+where
+    R: AsyncRead + Unpin,
+{
+    buf.clear();
+
+    let l_shared = match read_length_prefix(reader, buf).await? {
+        None | Some(0) => return Ok(0),
+        Some(n) => n,
+    };
+
+    let l_indiv = read_required_length_prefix(reader, buf).await?;
+
+    let record_size = l_shared.saturating_add(l_indiv);
+    limits.check(ReaderLimit::RecordSize, record_size)?;
+    read_exact_onto(reader, buf, record_size).await?;
+
+    bcf::io::Reader::from(&buf[..]).read_record(record)?;
+
+    let attribute_count = record.info().len() + record.samples()?.format_count();
+    limits.check(ReaderLimit::AttributeCount, attribute_count)?;
+
+    Ok(record_size)
+}
+
+/// Read the records that intersect `region` using the index, checking each against the limits.
+pub async fn query_with_limits<R, I>(
+    reader: &mut bgzf::AsyncReader<R>,
+    header: &vcf::Header,
+    index: &I,
+    region: &Region,
+    limits: &ReaderLimits,
+) -> io::Result<Vec<bcf::Record>>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+    I: BinningIndex,
+{
+    let region_name = std::str::from_utf8(region.name())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let reference_sequence_id = header
+        .string_maps()
+        .contigs()
+        .get_index_of(region_name)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("region does not exist in contigs: {region}"),
+            )
+        })?;
+
+    let interval = region.interval();
+    let chunks = index.query(reference_sequence_id, interval)?;
+
+    let mut buf = Vec::new();
+    let mut records = Vec::new();
+
+    for chunk in chunks {
+        reader.seek(chunk.start()).await?;
+
+        while reader.virtual_position() < chunk.end() {
+            let mut record = bcf::Record::default();
+
+            if read_record_with_limits(reader, &mut buf, &mut record, limits).await? == 0 {
+                break;
+            }
+
+            if intersects(&record, reference_sequence_id, interval)? {
+                records.push(record);
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+fn intersects(
+    record: &bcf::Record,
+    reference_sequence_id: usize,
+    interval: Interval,
+) -> io::Result<bool> {
+    if record.reference_sequence_id()? != reference_sequence_id {
+        return Ok(false);
+    }
+
+    let Some(start) = record.variant_start().transpose()? else {
+        return Ok(false);
+    };
+
+    let end = record.end()?;
+
+    Ok(Interval::from(start..=end).intersects(interval))
+}
+
+#[cfg(test)]
+mod tests {
+    use exon_common::{ReaderLimitError, ReaderLimits};
+    use noodles::{bcf, vcf::variant::record::Info};
+
+    use super::{read_header_with_limits, read_record_with_limits};
+
+    const HEADER_TEXT: &[u8] = b"##fileformat=VCFv4.3
+##FILTER=<ID=PASS,Description=\"All filters passed\">
+##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">
+##contig=<ID=sq0>
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+\0";
+
+    fn header_bytes() -> Vec<u8> {
+        let mut buf = b"BCF\x02\x02".to_vec();
+        buf.extend((HEADER_TEXT.len() as u32).to_le_bytes());
+        buf.extend(HEADER_TEXT);
+        buf
+    }
+
+    fn record_bytes() -> Vec<u8> {
+        let mut site = vec![
+            0x00, 0x00, 0x00, 0x00, // chrom = 0
+            0x00, 0x00, 0x00, 0x00, // pos = 0
+            0x01, 0x00, 0x00, 0x00, // rlen = 1
+            0x01, 0x00, 0x80, 0x7f, // qual = missing
+            0x01, 0x00, // n_info = 1
+            0x01, 0x00, // n_allele = 1
+            0x00, 0x00, 0x00, // n_sample = 0
+            0x00, // n_fmt = 0
+            0x07, // id = missing
+            0x17, b'A', // ref = A
+            0x00, // filter = none
+            0x11, 0x02, // DP
+            0x11, 0x08, // DP = 8
+        ];
+
+        let mut buf = (site.len() as u32).to_le_bytes().to_vec();
+        buf.extend(0u32.to_le_bytes());
+        buf.append(&mut site);
+        buf
+    }
+
+    #[tokio::test]
+    async fn test_read_within_limits() -> Result<(), Box<dyn std::error::Error>> {
+        let mut src = header_bytes();
+        src.extend(record_bytes());
+
+        let limits = ReaderLimits::default();
+        let mut reader = &src[..];
+
+        let header = read_header_with_limits(&mut reader, &limits).await?;
+        assert_eq!(header.contigs().len(), 1);
+
+        let mut buf = Vec::new();
+        let mut record = bcf::Record::default();
+
+        let n = read_record_with_limits(&mut reader, &mut buf, &mut record, &limits).await?;
+        assert_eq!(n, 32);
+        assert_eq!(record.info().len(), 1);
+
+        let n = read_record_with_limits(&mut reader, &mut buf, &mut record, &limits).await?;
+        assert_eq!(n, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_header_size_limit() {
+        let limits = ReaderLimits {
+            max_header_size: 16,
+            ..Default::default()
+        };
+
+        let src = header_bytes();
+        let err = read_header_with_limits(&mut &src[..], &limits)
+            .await
+            .unwrap_err();
+        assert!(ReaderLimitError::find(&err).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_record_limits() {
+        let mut buf = Vec::new();
+        let mut record = bcf::Record::default();
+
+        let limits = ReaderLimits {
+            max_record_size: 16,
+            ..Default::default()
+        };
+
+        let src = record_bytes();
+        let err = read_record_with_limits(&mut &src[..], &mut buf, &mut record, &limits)
+            .await
+            .unwrap_err();
+        assert!(ReaderLimitError::find(&err).is_some());
+
+        let limits = ReaderLimits {
+            max_attribute_count: 0,
+            ..Default::default()
+        };
+
+        let err = read_record_with_limits(&mut &src[..], &mut buf, &mut record, &limits)
+            .await
+            .unwrap_err();
+        assert!(ReaderLimitError::find(&err).is_some());
+    }
+}
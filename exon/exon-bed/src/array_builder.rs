@@ -16,9 +16,9 @@ use std::sync::Arc;
 
 use arrow::{
     array::{ArrayRef, GenericStringBuilder, Int64Builder},
-    datatypes::SchemaRef,
+    datatypes::{DataType, SchemaRef},
 };
-use exon_common::ExonArrayBuilder;
+use exon_common::{ExonArrayBuilder, Strand, StrandBuilder};
 
 use super::bed_record_builder::BEDRecord;
 
@@ -28,7 +28,7 @@ pub struct BEDArrayBuilder {
     ends: Int64Builder,
     names: GenericStringBuilder<i32>,
     scores: Int64Builder,
-    strands: GenericStringBuilder<i32>,
+    strands: StrandBuilder,
     thick_starts: Int64Builder,
     thick_ends: Int64Builder,
     colors: GenericStringBuilder<i32>,
@@ -48,13 +48,19 @@ impl BEDArrayBuilder {
             None => (0..schema.fields().len()).collect(),
         };
 
+        let strands = schema
+            .field_with_name("strand")
+            .map_or(StrandBuilder::new(&DataType::Utf8), |field| {
+                StrandBuilder::new(field.data_type())
+            });
+
         Self {
             reference_sequence_names: GenericStringBuilder::<i32>::new(),
             starts: Int64Builder::new(),
             ends: Int64Builder::new(),
             names: GenericStringBuilder::<i32>::new(),
             scores: Int64Builder::new(),
-            strands: GenericStringBuilder::<i32>::new(),
+            strands,
             thick_starts: Int64Builder::new(),
             thick_ends: Int64Builder::new(),
             colors: GenericStringBuilder::<i32>::new(),
@@ -78,7 +84,17 @@ impl BEDArrayBuilder {
                 2 => self.ends.append_value(record.end() as i64),
                 3 => self.names.append_option(record.name()),
                 4 => self.scores.append_option(record.score()),
-                5 => self.strands.append_option(record.strand()),
+                5 => {
+                    let strand = record
+                        .strand()
+                        .map(|strand| strand.parse::<Strand>())
+                        .transpose()
+                        .map_err(std::io::Error::other)?;
+
+                    self.strands
+                        .append_option(strand)
+                        .map_err(std::io::Error::other)?;
+                }
                 6 => self
                     .thick_starts
                     .append_option(record.thick_start().map(|x| x as i64)),
@@ -108,7 +124,7 @@ impl BEDArrayBuilder {
                 2 => arrays.push(Arc::new(self.ends.finish())),
                 3 => arrays.push(Arc::new(self.names.finish())),
                 4 => arrays.push(Arc::new(self.scores.finish())),
-                5 => arrays.push(Arc::new(self.strands.finish())),
+                5 => arrays.push(self.strands.finish()),
                 6 => arrays.push(Arc::new(self.thick_starts.finish())),
                 7 => arrays.push(Arc::new(self.thick_ends.finish())),
                 8 => arrays.push(Arc::new(self.colors.finish())),
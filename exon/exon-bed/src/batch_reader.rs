@@ -16,7 +16,7 @@ use std::{str::FromStr, sync::Arc};
 
 use arrow::{error::ArrowError, record_batch::RecordBatch};
 
-use exon_common::ExonArrayBuilder;
+use exon_common::{BoundedReader, ExonArrayBuilder};
 use futures::Stream;
 use noodles::{
     bed::feature::{record::Strand, record_buf::OtherFields, RecordBuf},
@@ -29,7 +29,7 @@ use super::{array_builder::BEDArrayBuilder, bed_record_builder::BEDRecord, confi
 /// A batch reader for BED files.
 pub struct BatchReader<R> {
     /// The underlying BED reader.
-    reader: R,
+    reader: BoundedReader<R>,
 
     /// The BED configuration.
     config: Arc<BEDConfig>,
@@ -41,7 +41,7 @@ where
 {
     pub fn new(inner: R, config: Arc<BEDConfig>) -> Self {
         Self {
-            reader: inner,
+            reader: BoundedReader::new(inner, &config.reader_limits),
             config,
         }
     }
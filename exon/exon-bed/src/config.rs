@@ -15,7 +15,7 @@
 use std::sync::Arc;
 
 use arrow::datatypes::SchemaRef;
-use exon_common::DEFAULT_BATCH_SIZE;
+use exon_common::{ReaderLimits, DEFAULT_BATCH_SIZE};
 use object_store::ObjectStore;
 
 use crate::ExonBEDResult;
@@ -37,6 +37,9 @@ pub struct BEDConfig {
 
     /// The number of fields of the BED to read.
     pub n_fields: Option<usize>,
+
+    /// The limits on line length.
+    pub reader_limits: ReaderLimits,
 }
 
 impl BEDConfig {
@@ -48,6 +51,7 @@ impl BEDConfig {
             file_schema,
             projection: None,
             n_fields: None,
+            reader_limits: ReaderLimits::default(),
         }
     }
 
@@ -57,6 +61,12 @@ impl BEDConfig {
         self
     }
 
+    /// Set the reader limits.
+    pub fn with_reader_limits(mut self, reader_limits: ReaderLimits) -> Self {
+        self.reader_limits = reader_limits;
+        self
+    }
+
     /// Set the batch size.
     pub fn with_batch_size(mut self, batch_size: usize) -> Self {
         self.batch_size = batch_size;
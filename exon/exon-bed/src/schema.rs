@@ -59,6 +59,16 @@ impl BEDSchemaBuilder {
         self.partition_fields.extend(fields);
     }
 
+    /// Sets the data type of the strand field, if the file has one, see
+    /// [`exon_common::StrandEncoding`].
+    pub fn set_strand_data_type(&mut self, data_type: DataType) {
+        for field in self.file_fields.iter_mut() {
+            if field.name() == "strand" {
+                *field = Field::new("strand", data_type.clone(), field.is_nullable());
+            }
+        }
+    }
+
     /// Returns the schema and the projection indexes for the file's schema
     pub fn build(self) -> TableSchema {
         let mut fields = self.file_fields.clone();
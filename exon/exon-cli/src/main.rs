@@ -26,7 +26,7 @@ use datafusion_cli::print_format::PrintFormat;
 use datafusion_cli::print_options::{MaxRows, PrintOptions};
 use exon::{new_exon_config, ExonSession};
 use object_store::ObjectStore;
-use tracing_subscriber::{EnvFilter, FmtSubscriber};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 
 struct ExonCLISession {
     exon_session: ExonSession,
@@ -106,6 +106,13 @@ struct Args {
 
     #[clap(long, help = "Enables console syntax highlighting")]
     color: bool,
+
+    #[cfg(feature = "otlp")]
+    #[clap(
+        long,
+        help = "Export scan spans to this OTLP gRPC endpoint, e.g. http://localhost:4317"
+    )]
+    otlp_endpoint: Option<String>,
 }
 
 #[tokio::main]
@@ -113,8 +120,31 @@ pub async fn main() -> Result<()> {
     let args = Args::parse();
 
     let filter = EnvFilter::new(std::env::var("EXON_LOG").unwrap_or_else(|_| "OFF".to_string()));
-    let subscriber = FmtSubscriber::builder().with_env_filter(filter).finish();
-    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+    let fmt_layer = tracing_subscriber::fmt::layer().with_filter(filter);
+
+    #[cfg(feature = "otlp")]
+    let otlp = args
+        .otlp_endpoint
+        .as_deref()
+        .map(|endpoint| exon::telemetry::OtlpTracing::try_new(endpoint, "exon-cli"))
+        .transpose()?;
+
+    #[cfg(feature = "otlp")]
+    let otlp_layer = otlp.as_ref().map(|otlp| {
+        let filter = EnvFilter::new(
+            std::env::var("EXON_OTLP_LOG").unwrap_or_else(|_| "exon=info".to_string()),
+        );
+        otlp.layer().with_filter(filter)
+    });
+
+    #[cfg(not(feature = "otlp"))]
+    let otlp_layer: Option<tracing_subscriber::layer::Identity> = None;
+
+    tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(otlp_layer)
+        .try_init()
+        .expect("setting default subscriber failed");
 
     let ctx = ExonCLISession::try_new()?;
 
@@ -0,0 +1,168 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// The extension of bloom filter sidecars, which are written next to the file they index.
+pub const BLOOM_FILTER_EXTENSION: &str = "bloom";
+
+const BLOOM_FILTER_HEADER: &str = "#exon-bloom-filter\tv1";
+
+/// The number of bits per value, for a false positive rate of about 1%.
+const BITS_PER_VALUE: usize = 10;
+
+/// The number of hashes per value, which is optimal for [`BITS_PER_VALUE`].
+const NUM_HASHES: u32 = 7;
+
+/// A bloom filter over string values, e.g. the variant IDs of a VCF file.
+///
+/// Values are hashed with FNV-1a, so a serialized filter can be read by any version of Exon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    /// Build a filter from the hashes of its values, see [`BloomFilter::hash`].
+    pub fn from_hashes(mut hashes: Vec<u64>) -> Self {
+        hashes.sort_unstable();
+        hashes.dedup();
+
+        let num_words = (hashes.len() * BITS_PER_VALUE).div_ceil(64).max(1);
+
+        let mut filter = Self {
+            bits: vec![0; num_words],
+            num_hashes: NUM_HASHES,
+        };
+
+        for hash in hashes {
+            for bit in filter.bit_indexes(hash) {
+                filter.bits[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+
+        filter
+    }
+
+    /// The hash of a value that's stored in the filter.
+    pub fn hash(value: &str) -> u64 {
+        value.bytes().fold(0xcbf29ce484222325, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+
+    /// Check if the filter may contain a value, which is false only if it definitely doesn't.
+    pub fn may_contain(&self, value: &str) -> bool {
+        self.bit_indexes(Self::hash(value))
+            .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// The bits of a hash, derived from it and a remix of it by double hashing.
+    fn bit_indexes(&self, hash: u64) -> impl Iterator<Item = usize> {
+        let num_bits = self.bits.len() as u64 * 64;
+
+        let h1 = hash;
+        let h2 = splitmix64(hash) | 1;
+
+        (0..self.num_hashes as u64)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+
+    /// Serialize the filter as a header line followed by its bits in little-endian words.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = format!(
+            "{}\t{}\t{}\n",
+            BLOOM_FILTER_HEADER,
+            self.bits.len(),
+            self.num_hashes
+        )
+        .into_bytes();
+
+        for word in &self.bits {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    /// Parse a filter serialized with [`BloomFilter::to_bytes`], or `None` if it's invalid.
+    pub fn try_from_bytes(bytes: &[u8]) -> Option<Self> {
+        let newline = bytes.iter().position(|b| *b == b'\n')?;
+        let header = std::str::from_utf8(&bytes[..newline]).ok()?;
+
+        let fields = header
+            .strip_prefix(BLOOM_FILTER_HEADER)?
+            .strip_prefix('\t')?
+            .split('\t')
+            .collect::<Vec<_>>();
+
+        let [num_words, num_hashes] = fields.as_slice() else {
+            return None;
+        };
+
+        let num_words = num_words.parse::<usize>().ok()?;
+        let num_hashes = num_hashes.parse::<u32>().ok()?;
+
+        let words = &bytes[newline + 1..];
+        if num_words == 0 || words.len() != num_words * 8 {
+            return None;
+        }
+
+        let bits = words
+            .chunks_exact(8)
+            .map(|word| u64::from_le_bytes(word.try_into().unwrap_or_default()))
+            .collect();
+
+        Some(Self { bits, num_hashes })
+    }
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BloomFilter;
+
+    #[test]
+    fn test_bloom_filter() {
+        let values = (0..1000).map(|i| format!("rs{}", i)).collect::<Vec<_>>();
+        let filter =
+            BloomFilter::from_hashes(values.iter().map(|v| BloomFilter::hash(v)).collect());
+
+        assert!(values.iter().all(|v| filter.may_contain(v)));
+
+        let false_positives = (1000..11000)
+            .filter(|i| filter.may_contain(&format!("rs{}", i)))
+            .count();
+        assert!(false_positives < 300, "{} false positives", false_positives);
+
+        let parsed = BloomFilter::try_from_bytes(&filter.to_bytes());
+        assert_eq!(parsed, Some(filter));
+
+        assert_eq!(
+            BloomFilter::try_from_bytes(b"#exon-bloom-filter\tv1\t2\t7\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_empty_bloom_filter() {
+        let filter = BloomFilter::from_hashes(Vec::new());
+        assert!(!filter.may_contain("rs1"));
+    }
+}
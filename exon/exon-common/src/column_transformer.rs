@@ -0,0 +1,555 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, RwLock},
+};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, GenericStringArray, OffsetSizeTrait},
+    datatypes::{DataType, Schema},
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::{
+    aead::{self, Aad, LessSafeKey, Nonce, UnboundKey, NONCE_LEN},
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+
+/// The direction data flows through a column transformer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnTransformDirection {
+    /// Values are being read from storage, e.g. decrypted.
+    Scan,
+
+    /// Values are being written to storage, e.g. encrypted.
+    Sink,
+}
+
+/// A transformation applied to a sensitive string column as it is scanned or written.
+///
+/// Implementations receive the session's key material with every call so they stay stateless
+/// and can be shared across sessions through the [`ColumnTransformerRegistry`].
+pub trait ColumnTransformer: Debug + Send + Sync {
+    /// The name used to reference the transformer in `exon.column_transforms`.
+    fn name(&self) -> &str;
+
+    /// Transform a single non-null value.
+    fn transform_value(
+        &self,
+        value: &str,
+        key: &[u8],
+        direction: ColumnTransformDirection,
+    ) -> Result<String, ArrowError>;
+
+    /// Transform a whole column, preserving nulls and the string type of the input.
+    fn transform(
+        &self,
+        column: &ArrayRef,
+        key: &[u8],
+        direction: ColumnTransformDirection,
+    ) -> Result<ArrayRef, ArrowError> {
+        match column.data_type() {
+            DataType::Utf8 => {
+                map_strings::<i32>(column, |v| self.transform_value(v, key, direction))
+            }
+            DataType::LargeUtf8 => {
+                map_strings::<i64>(column, |v| self.transform_value(v, key, direction))
+            }
+            dt => Err(ArrowError::InvalidArgumentError(format!(
+                "column transformer {} only supports string columns, got {}",
+                self.name(),
+                dt
+            ))),
+        }
+    }
+}
+
+fn map_strings<O: OffsetSizeTrait>(
+    column: &ArrayRef,
+    f: impl Fn(&str) -> Result<String, ArrowError>,
+) -> Result<ArrayRef, ArrowError> {
+    let array = column.as_string::<O>();
+
+    let values = array
+        .iter()
+        .map(|v| v.map(&f).transpose())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Arc::new(GenericStringArray::<O>::from(values)))
+}
+
+/// Encrypts values with AES-256-GCM on write and decrypts them on scan.
+///
+/// Stored values are the base64 encoding of a random 12 byte nonce followed by the ciphertext
+/// and authentication tag. The key must be 32 bytes.
+#[derive(Debug, Default)]
+pub struct AesGcmTransformer;
+
+impl AesGcmTransformer {
+    fn key(key: &[u8]) -> Result<LessSafeKey, ArrowError> {
+        let key = UnboundKey::new(&aead::AES_256_GCM, key).map_err(|_| {
+            ArrowError::InvalidArgumentError(format!(
+                "aes_gcm requires a 32 byte key, got {} bytes",
+                key.len()
+            ))
+        })?;
+
+        Ok(LessSafeKey::new(key))
+    }
+
+    fn encrypt(value: &str, key: &LessSafeKey, rng: &SystemRandom) -> Result<String, ArrowError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        rng.fill(&mut nonce)
+            .map_err(|_| ArrowError::ComputeError("failed to generate a nonce".to_string()))?;
+
+        let mut in_out = value.as_bytes().to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::empty(),
+            &mut in_out,
+        )
+        .map_err(|_| ArrowError::ComputeError("failed to encrypt value".to_string()))?;
+
+        let mut out = nonce.to_vec();
+        out.extend_from_slice(&in_out);
+
+        Ok(STANDARD.encode(out))
+    }
+
+    fn decrypt(value: &str, key: &LessSafeKey) -> Result<String, ArrowError> {
+        let mut bytes = STANDARD.decode(value).map_err(|e| {
+            ArrowError::ComputeError(format!("encrypted value is not valid base64: {}", e))
+        })?;
+
+        if bytes.len() < NONCE_LEN {
+            return Err(ArrowError::ComputeError(
+                "encrypted value is shorter than its nonce".to_string(),
+            ));
+        }
+
+        let mut in_out = bytes.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&bytes)
+            .map_err(|_| ArrowError::ComputeError("invalid nonce".to_string()))?;
+
+        let plaintext = key
+            .open_in_place(nonce, Aad::empty(), &mut in_out)
+            .map_err(|_| {
+                ArrowError::ComputeError(
+                    "failed to decrypt value, the key may be wrong".to_string(),
+                )
+            })?;
+
+        String::from_utf8(plaintext.to_vec())
+            .map_err(|e| ArrowError::ComputeError(format!("decrypted value is not UTF-8: {}", e)))
+    }
+}
+
+impl ColumnTransformer for AesGcmTransformer {
+    fn name(&self) -> &str {
+        "aes_gcm"
+    }
+
+    fn transform_value(
+        &self,
+        value: &str,
+        key: &[u8],
+        direction: ColumnTransformDirection,
+    ) -> Result<String, ArrowError> {
+        let key = Self::key(key)?;
+
+        match direction {
+            ColumnTransformDirection::Scan => Self::decrypt(value, &key),
+            ColumnTransformDirection::Sink => Self::encrypt(value, &key, &SystemRandom::new()),
+        }
+    }
+
+    fn transform(
+        &self,
+        column: &ArrayRef,
+        key: &[u8],
+        direction: ColumnTransformDirection,
+    ) -> Result<ArrayRef, ArrowError> {
+        // Build the key once per column rather than once per value.
+        let key = Self::key(key)?;
+        let rng = SystemRandom::new();
+
+        let f = |v: &str| match direction {
+            ColumnTransformDirection::Scan => Self::decrypt(v, &key),
+            ColumnTransformDirection::Sink => Self::encrypt(v, &key, &rng),
+        };
+
+        match column.data_type() {
+            DataType::Utf8 => map_strings::<i32>(column, f),
+            DataType::LargeUtf8 => map_strings::<i64>(column, f),
+            dt => Err(ArrowError::InvalidArgumentError(format!(
+                "column transformer {} only supports string columns, got {}",
+                self.name(),
+                dt
+            ))),
+        }
+    }
+}
+
+/// Replaces values with their hex encoded HMAC-SHA256 in both directions.
+///
+/// Tokens are deterministic for a given key, so tokenized columns can still be joined and
+/// grouped on, but the original values can't be recovered.
+#[derive(Debug, Default)]
+pub struct HmacTokenTransformer;
+
+impl ColumnTransformer for HmacTokenTransformer {
+    fn name(&self) -> &str {
+        "hmac_token"
+    }
+
+    fn transform_value(
+        &self,
+        value: &str,
+        key: &[u8],
+        _direction: ColumnTransformDirection,
+    ) -> Result<String, ArrowError> {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+        let tag = hmac::sign(&key, value.as_bytes());
+
+        Ok(tag.as_ref().iter().map(|b| format!("{:02x}", b)).collect())
+    }
+}
+
+/// Supplies the key material passed to the column transformers.
+///
+/// The key is held by the session's [`ColumnTransformerRegistry`] rather than a config option,
+/// so it isn't listed with the session's settings.
+pub trait ColumnTransformKeyProvider: Debug + Send + Sync {
+    /// The key, read each time the column transforms of a query are planned.
+    fn key(&self) -> Result<Vec<u8>, ArrowError>;
+}
+
+/// A key provider for a fixed key. Its debug output doesn't include the key.
+pub struct StaticColumnTransformKey {
+    key: Vec<u8>,
+}
+
+impl StaticColumnTransformKey {
+    /// Create a provider for the raw key bytes.
+    pub fn new(key: Vec<u8>) -> Self {
+        Self { key }
+    }
+
+    /// Create a provider for a hex encoded key.
+    pub fn try_from_hex(hex_key: &str) -> Result<Self, ArrowError> {
+        Ok(Self::new(decode_hex(hex_key.trim())?))
+    }
+}
+
+impl Debug for StaticColumnTransformKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StaticColumnTransformKey")
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl ColumnTransformKeyProvider for StaticColumnTransformKey {
+    fn key(&self) -> Result<Vec<u8>, ArrowError> {
+        Ok(self.key.clone())
+    }
+}
+
+/// The column transformers available to a session, keyed by name, and the provider of their key.
+///
+/// The default registry contains the built-in `aes_gcm` and `hmac_token` transformers and no key
+/// provider.
+#[derive(Debug)]
+pub struct ColumnTransformerRegistry {
+    transformers: RwLock<HashMap<String, Arc<dyn ColumnTransformer>>>,
+    key_provider: RwLock<Option<Arc<dyn ColumnTransformKeyProvider>>>,
+}
+
+impl Default for ColumnTransformerRegistry {
+    fn default() -> Self {
+        let registry = Self {
+            transformers: RwLock::new(HashMap::new()),
+            key_provider: RwLock::new(None),
+        };
+
+        registry.register(Arc::new(AesGcmTransformer));
+        registry.register(Arc::new(HmacTokenTransformer));
+
+        registry
+    }
+}
+
+impl ColumnTransformerRegistry {
+    /// Register a transformer, replacing any existing transformer with the same name.
+    pub fn register(&self, transformer: Arc<dyn ColumnTransformer>) {
+        let mut transformers = self.transformers.write().expect("registry lock poisoned");
+        transformers.insert(transformer.name().to_string(), transformer);
+    }
+
+    /// Get a transformer by name.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn ColumnTransformer>> {
+        let transformers = self.transformers.read().expect("registry lock poisoned");
+        transformers.get(name).cloned()
+    }
+
+    /// Set the provider of the key passed to the transformers, replacing any existing provider.
+    pub fn set_key_provider(&self, key_provider: Arc<dyn ColumnTransformKeyProvider>) {
+        let mut current = self.key_provider.write().expect("registry lock poisoned");
+        *current = Some(key_provider);
+    }
+
+    /// Get the key from the key provider, or `None` if no provider is set.
+    pub fn key(&self) -> Result<Option<Vec<u8>>, ArrowError> {
+        let key_provider = self.key_provider.read().expect("registry lock poisoned");
+        key_provider.as_ref().map(|p| p.key()).transpose()
+    }
+}
+
+/// The set of column transformations configured for a session.
+#[derive(Clone)]
+pub struct ColumnTransforms {
+    columns: HashMap<String, Arc<dyn ColumnTransformer>>,
+    key: Vec<u8>,
+}
+
+impl Debug for ColumnTransforms {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ColumnTransforms")
+            .field("columns", &self.columns)
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl ColumnTransforms {
+    /// Parse a spec like `sample_id=hmac_token,name=aes_gcm`, with the transformers and key of
+    /// the registry.
+    ///
+    /// Returns `None` if the spec is empty.
+    pub fn try_new(
+        spec: &str,
+        registry: &ColumnTransformerRegistry,
+    ) -> Result<Option<Self>, ArrowError> {
+        let mut columns = HashMap::new();
+
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (column, name) = entry.split_once('=').ok_or_else(|| {
+                ArrowError::InvalidArgumentError(format!(
+                    "invalid column transform {}, expected column=transformer",
+                    entry
+                ))
+            })?;
+
+            let transformer = registry.get(name.trim()).ok_or_else(|| {
+                ArrowError::InvalidArgumentError(format!(
+                    "unknown column transformer {}",
+                    name.trim()
+                ))
+            })?;
+
+            columns.insert(column.trim().to_string(), transformer);
+        }
+
+        if columns.is_empty() {
+            return Ok(None);
+        }
+
+        let key = registry.key()?.unwrap_or_default();
+        if key.is_empty() {
+            return Err(ArrowError::InvalidArgumentError(
+                "a column transform key provider must be set when column transforms are configured"
+                    .to_string(),
+            ));
+        }
+
+        Ok(Some(Self { columns, key }))
+    }
+
+    /// Returns true if the column is transformed.
+    pub fn contains(&self, column: &str) -> bool {
+        self.columns.contains_key(column)
+    }
+
+    /// Returns true if any of the batch's fields are transformed.
+    pub fn applies_to(&self, schema: &Schema) -> bool {
+        schema.fields().iter().any(|f| self.contains(f.name()))
+    }
+
+    /// Apply the transformations to the matching columns of a batch.
+    pub fn transform_batch(
+        &self,
+        batch: RecordBatch,
+        direction: ColumnTransformDirection,
+    ) -> Result<RecordBatch, ArrowError> {
+        if !self.applies_to(batch.schema_ref()) {
+            return Ok(batch);
+        }
+
+        let schema = batch.schema();
+        let columns = schema
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .map(|(field, column)| match self.columns.get(field.name()) {
+                Some(transformer) => transformer.transform(column, &self.key, direction),
+                None => Ok(Arc::clone(column)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        RecordBatch::try_new(schema, columns)
+    }
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, ArrowError> {
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .filter(|b| b.len() == 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+                .ok_or_else(|| {
+                    ArrowError::InvalidArgumentError(
+                        "column transform key must be an even length hex string".to_string(),
+                    )
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Array, ArrayRef, AsArray, Int32Array, StringArray},
+        datatypes::{DataType, Field, Schema},
+        record_batch::RecordBatch,
+    };
+
+    use super::{
+        ColumnTransformDirection, ColumnTransformerRegistry, ColumnTransforms,
+        StaticColumnTransformKey,
+    };
+
+    const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    fn registry_with_key(hex_key: &str) -> ColumnTransformerRegistry {
+        let registry = ColumnTransformerRegistry::default();
+        registry.set_key_provider(Arc::new(
+            StaticColumnTransformKey::try_from_hex(hex_key).unwrap(),
+        ));
+
+        registry
+    }
+
+    fn batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, true),
+            Field::new("sample_id", DataType::Utf8, true),
+            Field::new("count", DataType::Int32, false),
+        ]));
+
+        let name: ArrayRef = Arc::new(StringArray::from(vec![Some("alice"), None, Some("bob")]));
+        let sample: ArrayRef =
+            Arc::new(StringArray::from(vec![Some("s1"), Some("s2"), Some("s1")]));
+        let count: ArrayRef = Arc::new(Int32Array::from(vec![1, 2, 3]));
+
+        RecordBatch::try_new(schema, vec![name, sample, count]).unwrap()
+    }
+
+    #[test]
+    fn test_aes_gcm_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let registry = registry_with_key(KEY);
+        let transforms = ColumnTransforms::try_new("name=aes_gcm", &registry)?.unwrap();
+
+        let encrypted = transforms.transform_batch(batch(), ColumnTransformDirection::Sink)?;
+        let names = encrypted.column(0).as_string::<i32>();
+        assert_ne!(names.value(0), "alice");
+        assert!(names.is_null(1));
+
+        let decrypted = transforms.transform_batch(encrypted, ColumnTransformDirection::Scan)?;
+        assert_eq!(decrypted, batch());
+
+        let other_registry = registry_with_key(&"ff".repeat(32));
+        let wrong = ColumnTransforms::try_new("name=aes_gcm", &other_registry)?.unwrap();
+        let encrypted = transforms.transform_batch(batch(), ColumnTransformDirection::Sink)?;
+        assert!(wrong
+            .transform_batch(encrypted, ColumnTransformDirection::Scan)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hmac_token_is_deterministic() -> Result<(), Box<dyn std::error::Error>> {
+        let registry = registry_with_key(KEY);
+        let transforms = ColumnTransforms::try_new("sample_id=hmac_token", &registry)?.unwrap();
+
+        let tokenized = transforms.transform_batch(batch(), ColumnTransformDirection::Scan)?;
+        let samples = tokenized.column(1).as_string::<i32>();
+
+        assert_eq!(samples.value(0), samples.value(2));
+        assert_ne!(samples.value(0), samples.value(1));
+        assert_eq!(samples.value(0).len(), 64);
+
+        // Untransformed columns are passed through.
+        assert_eq!(tokenized.column(0), batch().column(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_specs() {
+        let registry = registry_with_key(KEY);
+
+        assert!(
+            ColumnTransforms::try_new("", &ColumnTransformerRegistry::default())
+                .unwrap()
+                .is_none()
+        );
+        assert!(ColumnTransforms::try_new("name", &registry).is_err());
+        assert!(ColumnTransforms::try_new("name=rot13", &registry).is_err());
+        assert!(ColumnTransforms::try_new(
+            "name=hmac_token",
+            &ColumnTransformerRegistry::default()
+        )
+        .is_err());
+        assert!(ColumnTransforms::try_new("name=hmac_token", &registry_with_key("")).is_err());
+        assert!(StaticColumnTransformKey::try_from_hex("zz").is_err());
+
+        let transforms = ColumnTransforms::try_new("count=hmac_token", &registry)
+            .unwrap()
+            .unwrap();
+        assert!(transforms
+            .transform_batch(batch(), ColumnTransformDirection::Scan)
+            .is_err());
+    }
+
+    #[test]
+    fn test_key_is_redacted() {
+        let key = StaticColumnTransformKey::try_from_hex(KEY).unwrap();
+        assert!(!format!("{:?}", key).contains(KEY));
+
+        let transforms = ColumnTransforms::try_new("name=aes_gcm", &registry_with_key(KEY))
+            .unwrap()
+            .unwrap();
+        assert!(!format!("{:?}", transforms).contains("[0, 1, 2"));
+    }
+}
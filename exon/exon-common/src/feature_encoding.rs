@@ -0,0 +1,313 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Typed encodings of the strand and feature type columns of GFF, GTF, and BED tables.
+//!
+//! Strands are Utf8 by default, and can be dictionary encoded or stored as Int8 codes, which
+//! `strand_symbol` renders. Feature types are Utf8 or dictionary encoded.
+
+use std::{str::FromStr, sync::Arc};
+
+use arrow::{
+    array::{ArrayRef, AsArray, Int8Builder, StringArray, StringBuilder, StringDictionaryBuilder},
+    compute::cast,
+    datatypes::{DataType, Int32Type, Int8Type},
+    error::{ArrowError, Result},
+};
+
+/// The strand of a feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strand {
+    /// The forward strand, `+`.
+    Forward,
+
+    /// The reverse strand, `-`.
+    Reverse,
+
+    /// A strand that's relevant but unknown, `?`.
+    Unknown,
+}
+
+impl Strand {
+    /// The strand's symbol.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Forward => "+",
+            Self::Reverse => "-",
+            Self::Unknown => "?",
+        }
+    }
+
+    /// The strand's Int8 code, 1 for forward, -1 for reverse, and 0 for unknown.
+    pub fn code(&self) -> i8 {
+        match self {
+            Self::Forward => 1,
+            Self::Reverse => -1,
+            Self::Unknown => 0,
+        }
+    }
+
+    /// The strand of an Int8 code, see [`Strand::code`].
+    pub fn from_code(code: i8) -> Result<Self> {
+        match code {
+            1 => Ok(Self::Forward),
+            -1 => Ok(Self::Reverse),
+            0 => Ok(Self::Unknown),
+            _ => Err(ArrowError::InvalidArgumentError(format!(
+                "invalid strand code: {code}"
+            ))),
+        }
+    }
+}
+
+impl FromStr for Strand {
+    type Err = ArrowError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "+" => Ok(Self::Forward),
+            "-" => Ok(Self::Reverse),
+            "?" => Ok(Self::Unknown),
+            _ => Err(ArrowError::ParseError(format!("invalid strand: {s}"))),
+        }
+    }
+}
+
+/// How the strand columns are encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StrandEncoding {
+    /// The strand symbols as strings.
+    #[default]
+    Utf8,
+
+    /// The strand symbols as a dictionary with Int8 keys.
+    Dictionary,
+
+    /// The strands as Int8 codes, see [`Strand::code`].
+    Int8,
+}
+
+impl StrandEncoding {
+    /// The data type of a strand column with this encoding.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Self::Utf8 => DataType::Utf8,
+            Self::Dictionary => {
+                DataType::Dictionary(Box::new(DataType::Int8), Box::new(DataType::Utf8))
+            }
+            Self::Int8 => DataType::Int8,
+        }
+    }
+}
+
+impl FromStr for StrandEncoding {
+    type Err = ArrowError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "utf8" => Ok(Self::Utf8),
+            "dictionary" => Ok(Self::Dictionary),
+            "int8" => Ok(Self::Int8),
+            _ => Err(ArrowError::InvalidArgumentError(format!(
+                "invalid strand encoding: {s}, expected utf8, dictionary, or int8"
+            ))),
+        }
+    }
+}
+
+/// The data type of a dictionary encoded feature type column.
+pub fn feature_type_dictionary_data_type() -> DataType {
+    DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8))
+}
+
+/// Builds a strand column in one of the [`StrandEncoding`]s.
+pub enum StrandBuilder {
+    /// Strand symbols.
+    Utf8(StringBuilder),
+
+    /// Dictionary encoded strand symbols.
+    Dictionary(StringDictionaryBuilder<Int8Type>),
+
+    /// Strand codes.
+    Int8(Int8Builder),
+}
+
+impl StrandBuilder {
+    /// Creates a builder for a strand column of the data type, defaulting to Utf8.
+    pub fn new(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Dictionary(_, _) => Self::Dictionary(StringDictionaryBuilder::new()),
+            DataType::Int8 => Self::Int8(Int8Builder::new()),
+            _ => Self::Utf8(StringBuilder::new()),
+        }
+    }
+
+    /// Appends a strand, which is null if it's missing. An unknown strand is only kept as a
+    /// code, the symbol columns have always stored it as null.
+    pub fn append_option(&mut self, strand: Option<Strand>) -> Result<()> {
+        let symbol = strand
+            .filter(|strand| *strand != Strand::Unknown)
+            .map(|strand| strand.as_str());
+
+        match self {
+            Self::Utf8(builder) => builder.append_option(symbol),
+            Self::Dictionary(builder) => match symbol {
+                Some(symbol) => {
+                    builder.append(symbol)?;
+                }
+                None => builder.append_null(),
+            },
+            Self::Int8(builder) => builder.append_option(strand.map(|strand| strand.code())),
+        }
+
+        Ok(())
+    }
+
+    /// Finishes the builder and returns the array.
+    pub fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::Utf8(builder) => Arc::new(builder.finish()),
+            Self::Dictionary(builder) => Arc::new(builder.finish()),
+            Self::Int8(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// Builds a feature type column, either as Utf8 or dictionary encoded.
+pub enum FeatureTypeBuilder {
+    /// Feature types as strings.
+    Utf8(StringBuilder),
+
+    /// Dictionary encoded feature types.
+    Dictionary(StringDictionaryBuilder<Int32Type>),
+}
+
+impl FeatureTypeBuilder {
+    /// Creates a builder for a feature type column of the data type, defaulting to Utf8.
+    pub fn new(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Dictionary(_, _) => Self::Dictionary(StringDictionaryBuilder::new()),
+            _ => Self::Utf8(StringBuilder::new()),
+        }
+    }
+
+    /// Appends a feature type.
+    pub fn append_value(&mut self, feature_type: &str) -> Result<()> {
+        match self {
+            Self::Utf8(builder) => builder.append_value(feature_type),
+            Self::Dictionary(builder) => {
+                builder.append(feature_type)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finishes the builder and returns the array.
+    pub fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::Utf8(builder) => Arc::new(builder.finish()),
+            Self::Dictionary(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// Renders a strand column of any encoding as Utf8 symbols, including `?` for unknown codes.
+pub fn strands_as_utf8(strands: &ArrayRef) -> Result<ArrayRef> {
+    match strands.data_type() {
+        DataType::Int8 => {
+            let symbols = strands
+                .as_primitive::<Int8Type>()
+                .iter()
+                .map(|code| {
+                    code.map(|code| Strand::from_code(code).map(|strand| strand.as_str()))
+                        .transpose()
+                })
+                .collect::<Result<StringArray>>()?;
+
+            Ok(Arc::new(symbols))
+        }
+        _ => cast(strands, &DataType::Utf8),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Array, Int8Array};
+
+    use super::*;
+
+    fn build(encoding: StrandEncoding) -> Result<ArrayRef> {
+        let mut builder = StrandBuilder::new(&encoding.data_type());
+
+        for strand in ["+", "-", "?"] {
+            builder.append_option(Some(strand.parse()?))?;
+        }
+        builder.append_option(None)?;
+
+        Ok(builder.finish())
+    }
+
+    #[test]
+    fn test_strand_encodings() -> Result<()> {
+        for encoding in [
+            StrandEncoding::Utf8,
+            StrandEncoding::Dictionary,
+            StrandEncoding::Int8,
+        ] {
+            let strands = build(encoding)?;
+            assert_eq!(strands.data_type(), &encoding.data_type());
+
+            let symbols = strands_as_utf8(&strands)?;
+            let symbols = symbols.as_string::<i32>();
+
+            assert_eq!(symbols.value(0), "+");
+            assert_eq!(symbols.value(1), "-");
+            assert!(symbols.is_null(3));
+
+            if encoding == StrandEncoding::Int8 {
+                assert_eq!(symbols.value(2), "?");
+            } else {
+                assert!(symbols.is_null(2));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_strands() {
+        assert!("*".parse::<Strand>().is_err());
+        assert!("bytes".parse::<StrandEncoding>().is_err());
+
+        let codes: ArrayRef = Arc::new(Int8Array::from(vec![2]));
+        assert!(strands_as_utf8(&codes).is_err());
+    }
+
+    #[test]
+    fn test_feature_type_dictionary() -> Result<()> {
+        let mut builder = FeatureTypeBuilder::new(&feature_type_dictionary_data_type());
+
+        for feature_type in ["gene", "exon", "exon"] {
+            builder.append_value(feature_type)?;
+        }
+
+        let feature_types = builder.finish();
+        let feature_types = feature_types.as_dictionary::<Int32Type>();
+
+        assert_eq!(feature_types.values().len(), 2);
+        assert_eq!(feature_types.keys().values(), &[0, 1, 1]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// The schema metadata key of the genome build a table's coordinates are on.
+pub const GENOME_BUILD_METADATA_KEY: &str = "genome_build";
+
+/// The common names of genome builds, lowercased, and the build they refer to.
+const GENOME_BUILD_ALIASES: &[(&str, &str)] = &[
+    ("grch37", "GRCh37"),
+    ("hg19", "GRCh37"),
+    ("b37", "GRCh37"),
+    ("v37", "GRCh37"),
+    ("hs37d5", "GRCh37"),
+    ("grch38", "GRCh38"),
+    ("hg38", "GRCh38"),
+    ("b38", "GRCh38"),
+    ("hs38", "GRCh38"),
+    ("hs38dh", "GRCh38"),
+    ("ncbi36", "NCBI36"),
+    ("hg18", "NCBI36"),
+    ("t2t-chm13", "T2T-CHM13"),
+    ("chm13", "T2T-CHM13"),
+    ("hs1", "T2T-CHM13"),
+    ("grcm38", "GRCm38"),
+    ("mm10", "GRCm38"),
+    ("grcm39", "GRCm39"),
+    ("mm39", "GRCm39"),
+];
+
+fn lookup_alias(name: &str) -> Option<&'static str> {
+    let name = name.to_ascii_lowercase();
+
+    GENOME_BUILD_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, build)| *build)
+}
+
+/// The canonical name of a declared genome build, e.g. `GRCh38` for `hg38`.
+///
+/// Names that aren't a known alias are kept as declared, so other builds can be compared too.
+pub fn canonical_genome_build(name: &str) -> String {
+    let name = name.trim();
+
+    lookup_alias(name)
+        .map(String::from)
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// Infer the genome build from a reference name or path in a file header, e.g.
+/// `file:///references/hs37d5.fa` or `GRCh38.p7`.
+pub fn infer_genome_build(reference: &str) -> Option<&'static str> {
+    reference
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+        .filter(|token| !token.is_empty())
+        .find_map(|token| lookup_alias(token).or_else(|| token.split('-').find_map(lookup_alias)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_genome_build() {
+        assert_eq!(canonical_genome_build("hg38"), "GRCh38");
+        assert_eq!(canonical_genome_build(" GRCh37 "), "GRCh37");
+        assert_eq!(canonical_genome_build("CanFam3.1"), "CanFam3.1");
+    }
+
+    #[test]
+    fn test_infer_genome_build() {
+        let references = [
+            (
+                "file:///references/Human/1000Genomes_hs37d5/all/fasta/hs37d5.fa",
+                Some("GRCh37"),
+            ),
+            ("GRCh38.p7", Some("GRCh38")),
+            ("s3://bucket/GRCh38_full_analysis_set.fa", Some("GRCh38")),
+            ("T2T-CHM13", Some("T2T-CHM13")),
+            ("hg19-chr20.fa", Some("GRCh37")),
+            ("reference.fa", None),
+        ];
+
+        for (reference, expected) in references {
+            assert_eq!(infer_genome_build(reference), expected, "{}", reference);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::{collections::HashSet, sync::Arc};
+
+use crate::BloomFilter;
+
+/// The extension of identifier index sidecars, which are written next to the file they index.
+pub const IDENTIFIER_INDEX_EXTENSION: &str = "ids";
+
+const IDENTIFIER_INDEX_HEADER: &str = "#exon-identifier-index\tv1";
+
+/// A prefilter on the identifiers of a record, e.g. the IDs of a VCF record.
+///
+/// Scanners use it to skip the records without one of the looked up identifiers before they're
+/// appended to arrays. Like a [`crate::SequenceFilter`], the lookups are still evaluated on the
+/// scan's output, so a filter can be looser than them but never stricter.
+#[derive(Debug, Clone)]
+pub struct IdentifierFilter {
+    identifiers: Vec<Arc<HashSet<String>>>,
+}
+
+impl IdentifierFilter {
+    /// Create a filter that matches the records with any of the identifiers.
+    pub fn new(identifiers: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            identifiers: vec![Arc::new(identifiers.into_iter().collect())],
+        }
+    }
+
+    /// Combine two filters, so a record has to match both.
+    pub fn and(mut self, other: IdentifierFilter) -> Self {
+        self.identifiers.extend(other.identifiers);
+        self
+    }
+
+    /// Check if the identifiers of a record match all of the filter's lookups.
+    pub fn is_match<'a, I>(&self, ids: I) -> bool
+    where
+        I: IntoIterator<Item = &'a str> + Clone,
+    {
+        self.identifiers
+            .iter()
+            .all(|identifiers| ids.clone().into_iter().any(|id| identifiers.contains(id)))
+    }
+}
+
+/// A chunk of the records of a file, and a bloom filter of their identifiers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentifierChunk {
+    /// The byte offset of the chunk's first record
+    pub start: u64,
+
+    /// The byte offset after the chunk's last record
+    pub end: u64,
+
+    /// The identifiers of the chunk's records
+    pub filter: BloomFilter,
+}
+
+/// A hashed identifier index over the byte ranges of an uncompressed file's records, so a lookup
+/// only reads the chunks that may have one of its identifiers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdentifierChunkIndex {
+    chunks: Vec<IdentifierChunk>,
+}
+
+impl IdentifierChunkIndex {
+    /// The chunks of the index, in the order of the file.
+    pub fn chunks(&self) -> &[IdentifierChunk] {
+        &self.chunks
+    }
+
+    /// Serialize the index as a header line, followed by a line with the byte range and filter
+    /// size of each chunk and the chunk's serialized filter.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes =
+            format!("{}\t{}\n", IDENTIFIER_INDEX_HEADER, self.chunks.len()).into_bytes();
+
+        for chunk in &self.chunks {
+            let filter = chunk.filter.to_bytes();
+
+            bytes
+                .extend(format!("{}\t{}\t{}\n", chunk.start, chunk.end, filter.len()).into_bytes());
+            bytes.extend(filter);
+        }
+
+        bytes
+    }
+
+    /// Parse an index serialized with [`IdentifierChunkIndex::to_bytes`], or `None` if it's invalid.
+    pub fn try_from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (header, mut bytes) = split_line(bytes)?;
+
+        let num_chunks = header
+            .strip_prefix(IDENTIFIER_INDEX_HEADER)?
+            .strip_prefix('\t')?
+            .parse::<usize>()
+            .ok()?;
+
+        let mut chunks = Vec::with_capacity(num_chunks);
+
+        for _ in 0..num_chunks {
+            let (line, rest) = split_line(bytes)?;
+
+            let fields = line
+                .split('\t')
+                .map(|f| f.parse::<u64>().ok())
+                .collect::<Option<Vec<_>>>()?;
+
+            let [start, end, filter_size] = fields.as_slice() else {
+                return None;
+            };
+
+            let filter_size = usize::try_from(*filter_size).ok()?;
+            if start > end || rest.len() < filter_size {
+                return None;
+            }
+
+            chunks.push(IdentifierChunk {
+                start: *start,
+                end: *end,
+                filter: BloomFilter::try_from_bytes(&rest[..filter_size])?,
+            });
+
+            bytes = &rest[filter_size..];
+        }
+
+        bytes.is_empty().then_some(Self { chunks })
+    }
+}
+
+fn split_line(bytes: &[u8]) -> Option<(&str, &[u8])> {
+    let newline = bytes.iter().position(|b| *b == b'\n')?;
+    let line = std::str::from_utf8(&bytes[..newline]).ok()?;
+
+    Some((line, &bytes[newline + 1..]))
+}
+
+/// Builds an [`IdentifierChunkIndex`] from the records of a file, starting a new chunk once a chunk
+/// spans at least the chunk size.
+#[derive(Debug)]
+pub struct IdentifierChunkIndexBuilder {
+    chunk_size: u64,
+    chunks: Vec<IdentifierChunk>,
+    start: u64,
+    end: u64,
+    hashes: Vec<u64>,
+}
+
+impl IdentifierChunkIndexBuilder {
+    /// Create a builder with chunks of about `chunk_size` bytes.
+    pub fn new(chunk_size: u64) -> Self {
+        Self {
+            chunk_size,
+            chunks: Vec::new(),
+            start: 0,
+            end: 0,
+            hashes: Vec::new(),
+        }
+    }
+
+    /// Add a record at the byte range `start..end` of the file and its identifiers.
+    pub fn push<'a>(&mut self, start: u64, end: u64, ids: impl IntoIterator<Item = &'a str>) {
+        if self.start == self.end {
+            self.start = start;
+        }
+
+        self.end = end;
+        self.hashes.extend(ids.into_iter().map(BloomFilter::hash));
+
+        if self.end - self.start >= self.chunk_size {
+            self.finish_chunk();
+        }
+    }
+
+    fn finish_chunk(&mut self) {
+        if self.start == self.end {
+            return;
+        }
+
+        self.chunks.push(IdentifierChunk {
+            start: self.start,
+            end: self.end,
+            filter: BloomFilter::from_hashes(std::mem::take(&mut self.hashes)),
+        });
+
+        self.start = self.end;
+    }
+
+    /// Build the index.
+    pub fn finish(mut self) -> IdentifierChunkIndex {
+        self.finish_chunk();
+
+        IdentifierChunkIndex {
+            chunks: self.chunks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifier_filter() {
+        let filter = IdentifierFilter::new(["rs1".to_string(), "rs2".to_string()]);
+
+        assert!(filter.is_match("rs3;rs2".split(';')));
+        assert!(!filter.is_match("rs3".split(';')));
+        assert!(!filter.is_match("".split(';')));
+
+        let filter = filter.and(IdentifierFilter::new(["rs3".to_string()]));
+
+        assert!(filter.is_match("rs3;rs2".split(';')));
+        assert!(!filter.is_match("rs1".split(';')));
+    }
+
+    #[test]
+    fn test_identifier_index() {
+        let mut builder = IdentifierChunkIndexBuilder::new(10);
+
+        builder.push(20, 26, ["rs1"]);
+        builder.push(26, 32, ["rs2", "rs3"]);
+        builder.push(32, 38, ["rs4"]);
+
+        let index = builder.finish();
+
+        let ranges = index
+            .chunks()
+            .iter()
+            .map(|c| (c.start, c.end))
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, vec![(20, 32), (32, 38)]);
+
+        assert!(index.chunks()[0].filter.may_contain("rs3"));
+        assert!(index.chunks()[1].filter.may_contain("rs4"));
+
+        let parsed = IdentifierChunkIndex::try_from_bytes(&index.to_bytes());
+        assert_eq!(parsed, Some(index.clone()));
+
+        let mut truncated = index.to_bytes();
+        truncated.pop();
+        assert_eq!(IdentifierChunkIndex::try_from_bytes(&truncated), None);
+
+        let empty = IdentifierChunkIndexBuilder::new(10).finish();
+        assert_eq!(
+            IdentifierChunkIndex::try_from_bytes(&empty.to_bytes()),
+            Some(IdentifierChunkIndex::default())
+        );
+    }
+}
@@ -16,10 +16,45 @@
 mod object_store_files_from_table_path;
 
 mod array_builder;
+mod bloom_filter;
+mod column_transformer;
+mod feature_encoding;
+mod genome_build;
+mod identifier_index;
+mod quality_scores;
+mod reader_limits;
+mod record_sampling;
+mod sequence_filter;
 mod table_schema;
 
+pub mod packed_sequence;
+
 pub use array_builder::ExonArrayBuilder;
+pub use bloom_filter::{BloomFilter, BLOOM_FILTER_EXTENSION};
+pub use column_transformer::{
+    AesGcmTransformer, ColumnTransformDirection, ColumnTransformKeyProvider, ColumnTransformer,
+    ColumnTransformerRegistry, ColumnTransforms, HmacTokenTransformer, StaticColumnTransformKey,
+};
+pub use feature_encoding::{
+    feature_type_dictionary_data_type, strands_as_utf8, FeatureTypeBuilder, Strand, StrandBuilder,
+    StrandEncoding,
+};
+pub use genome_build::{canonical_genome_build, infer_genome_build, GENOME_BUILD_METADATA_KEY};
+pub use identifier_index::{
+    IdentifierChunk, IdentifierChunkIndex, IdentifierChunkIndexBuilder, IdentifierFilter,
+    IDENTIFIER_INDEX_EXTENSION,
+};
 pub use object_store_files_from_table_path::object_store_files_from_table_path;
+pub use quality_scores::{
+    append_phred33_quality_scores, append_quality_scores, append_raw_quality_scores, PHRED_OFFSET,
+};
+pub use reader_limits::{
+    read_exact_onto, read_length_prefix, read_required_length_prefix, BoundedReader, ReaderLimit,
+    ReaderLimitError, ReaderLimits, DEFAULT_MAX_ATTRIBUTE_COUNT, DEFAULT_MAX_HEADER_SIZE,
+    DEFAULT_MAX_RECORD_SIZE, DEFAULT_MAX_SEQUENCE_LENGTH,
+};
+pub use record_sampling::{RecordSampling, Reservoir, SampleMethod, StratifiedReservoirs};
+pub use sequence_filter::SequenceFilter;
 pub use table_schema::TableSchema;
 pub use table_schema::TableSchemaBuilder;
 
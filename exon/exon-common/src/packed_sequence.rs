@@ -0,0 +1,162 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Nucleotide sequences packed with 2 bits per base.
+//!
+//! A packed sequence starts with its length as a little-endian `u32`, followed by the bases four
+//! to a byte with the first base in the high bits, where A, C, G, and T are 0 to 3. Runs of N are
+//! packed as A, and their starts and lengths follow the bases as pairs of little-endian `u32`s.
+
+use std::{error::Error, fmt::Display};
+
+const LENGTH_SIZE: usize = 4;
+
+/// An error packing or unpacking a sequence.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PackedSequenceError {
+    /// A base other than A, C, G, T, or N.
+    InvalidBase(u8),
+    /// A packed sequence that's shorter than its length or has a partial N run.
+    Truncated,
+}
+
+impl Display for PackedSequenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackedSequenceError::InvalidBase(base) => {
+                write!(f, "Invalid base to pack: {}", *base as char)
+            }
+            PackedSequenceError::Truncated => write!(f, "Truncated packed sequence"),
+        }
+    }
+}
+
+impl Error for PackedSequenceError {}
+
+fn packed_bases_len(len: usize) -> usize {
+    len.div_ceil(4)
+}
+
+/// Pack a sequence of uppercase A, C, G, T, and N, replacing the contents of `packed`.
+pub fn pack_sequence(sequence: &[u8], packed: &mut Vec<u8>) -> Result<(), PackedSequenceError> {
+    packed.clear();
+    packed.extend_from_slice(&(sequence.len() as u32).to_le_bytes());
+    packed.resize(LENGTH_SIZE + packed_bases_len(sequence.len()), 0);
+
+    let mut n_run: Option<(usize, usize)> = None;
+    let mut n_runs = Vec::new();
+
+    for (i, base) in sequence.iter().enumerate() {
+        let code = match base {
+            b'A' | b'N' => 0,
+            b'C' => 1,
+            b'G' => 2,
+            b'T' => 3,
+            _ => return Err(PackedSequenceError::InvalidBase(*base)),
+        };
+
+        packed[LENGTH_SIZE + i / 4] |= code << (6 - 2 * (i % 4));
+
+        n_run = match (n_run, *base == b'N') {
+            (Some((start, len)), true) => Some((start, len + 1)),
+            (None, true) => Some((i, 1)),
+            (Some(run), false) => {
+                n_runs.push(run);
+                None
+            }
+            (None, false) => None,
+        };
+    }
+
+    n_runs.extend(n_run);
+
+    for (start, len) in n_runs {
+        packed.extend_from_slice(&(start as u32).to_le_bytes());
+        packed.extend_from_slice(&(len as u32).to_le_bytes());
+    }
+
+    Ok(())
+}
+
+/// Get the length of a packed sequence.
+pub fn packed_sequence_len(packed: &[u8]) -> Result<usize, PackedSequenceError> {
+    let length = packed
+        .get(..LENGTH_SIZE)
+        .ok_or(PackedSequenceError::Truncated)?;
+
+    Ok(u32::from_le_bytes([length[0], length[1], length[2], length[3]]) as usize)
+}
+
+/// Split a packed sequence into its length, its packed bases, and its N runs.
+fn split_packed_sequence(
+    packed: &[u8],
+) -> Result<(usize, &[u8], Vec<(usize, usize)>), PackedSequenceError> {
+    let len = packed_sequence_len(packed)?;
+
+    let bases_end = LENGTH_SIZE + packed_bases_len(len);
+    let bases = packed
+        .get(LENGTH_SIZE..bases_end)
+        .ok_or(PackedSequenceError::Truncated)?;
+
+    let n_runs = &packed[bases_end..];
+    if n_runs.len() % 8 != 0 {
+        return Err(PackedSequenceError::Truncated);
+    }
+
+    let n_runs = n_runs
+        .chunks_exact(8)
+        .map(|run| {
+            let start = u32::from_le_bytes([run[0], run[1], run[2], run[3]]) as usize;
+            let len = u32::from_le_bytes([run[4], run[5], run[6], run[7]]) as usize;
+            (start, len)
+        })
+        .collect();
+
+    Ok((len, bases, n_runs))
+}
+
+/// Unpack a packed sequence into its bases.
+pub fn unpack_sequence(packed: &[u8]) -> Result<Vec<u8>, PackedSequenceError> {
+    const BASES: [u8; 4] = [b'A', b'C', b'G', b'T'];
+
+    let (len, bases, n_runs) = split_packed_sequence(packed)?;
+
+    let mut sequence = (0..len)
+        .map(|i| BASES[((bases[i / 4] >> (6 - 2 * (i % 4))) & 0b11) as usize])
+        .collect::<Vec<_>>();
+
+    for (start, run_len) in n_runs {
+        sequence
+            .get_mut(start..start + run_len)
+            .ok_or(PackedSequenceError::Truncated)?
+            .fill(b'N');
+    }
+
+    Ok(sequence)
+}
+
+/// Count the G and C bases of a packed sequence without unpacking it.
+pub fn packed_gc_count(packed: &[u8]) -> Result<usize, PackedSequenceError> {
+    // The padding of the last byte and the N runs are packed as A, so they're never counted.
+    let (_, bases, _) = split_packed_sequence(packed)?;
+
+    Ok(bases
+        .iter()
+        .map(|byte| {
+            (0..4)
+                .filter(|i| matches!((byte >> (2 * i)) & 0b11, 1 | 2))
+                .count()
+        })
+        .sum())
+}
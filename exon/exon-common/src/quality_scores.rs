@@ -0,0 +1,144 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decoding of Phred quality scores into Arrow list builders.
+//!
+//! Scores are decoded a fixed size chunk at a time into a stack buffer that's appended to the
+//! list's value buffer, so the inner loop has no branches and can be vectorized, and no
+//! intermediate `Vec` is allocated per record.
+
+use arrow::{
+    array::{GenericListBuilder, PrimitiveBuilder, UInt8Builder},
+    datatypes::ArrowPrimitiveType,
+};
+
+/// The offset of Phred quality scores encoded as ASCII, e.g. in FASTQ.
+pub const PHRED_OFFSET: u8 = 33;
+
+const CHUNK_SIZE: usize = 64;
+
+/// Append raw quality scores, as stored in SAM, BAM, and CRAM records, as one list.
+///
+/// Scores are read as signed bytes, so a missing score of 0xFF is decoded as -1.
+pub fn append_quality_scores<T>(
+    builder: &mut GenericListBuilder<i32, PrimitiveBuilder<T>>,
+    scores: &[u8],
+) where
+    T: ArrowPrimitiveType,
+    T::Native: From<i8>,
+{
+    append_decoded(builder, scores, |score| score as i8);
+}
+
+/// Append raw quality scores as one list of unsigned bytes, as they're stored.
+///
+/// Unlike [`append_quality_scores`], nothing is decoded, so a missing score of 0xFF is kept as 255.
+pub fn append_raw_quality_scores(
+    builder: &mut GenericListBuilder<i32, UInt8Builder>,
+    scores: &[u8],
+) {
+    builder.values().append_slice(scores);
+    builder.append(true);
+}
+
+/// Append ASCII encoded quality scores, as stored in FASTQ records, as one list.
+pub fn append_phred33_quality_scores<T>(
+    builder: &mut GenericListBuilder<i32, PrimitiveBuilder<T>>,
+    encoded: &[u8],
+) where
+    T: ArrowPrimitiveType,
+    T::Native: From<i8>,
+{
+    append_decoded(builder, encoded, |c| c.wrapping_sub(PHRED_OFFSET) as i8);
+}
+
+#[inline(always)]
+fn append_decoded<T, F>(
+    builder: &mut GenericListBuilder<i32, PrimitiveBuilder<T>>,
+    src: &[u8],
+    decode: F,
+) where
+    T: ArrowPrimitiveType,
+    T::Native: From<i8>,
+    F: Fn(u8) -> i8,
+{
+    let values = builder.values();
+
+    let mut buf = [T::Native::default(); CHUNK_SIZE];
+
+    for chunk in src.chunks(CHUNK_SIZE) {
+        for (dst, s) in buf.iter_mut().zip(chunk) {
+            *dst = T::Native::from(decode(*s));
+        }
+
+        values.append_slice(&buf[..chunk.len()]);
+    }
+
+    builder.append(true);
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{Array, AsArray, GenericListBuilder, Int32Builder, Int64Builder, UInt8Builder},
+        datatypes::{Int32Type, Int64Type, UInt8Type},
+    };
+
+    use super::{append_phred33_quality_scores, append_quality_scores, append_raw_quality_scores};
+
+    #[test]
+    fn test_append_quality_scores() {
+        let mut builder = GenericListBuilder::<i32, Int64Builder>::new(Int64Builder::new());
+
+        let scores = (0..100).map(|i| i % 42).chain([0xff]).collect::<Vec<u8>>();
+        append_quality_scores(&mut builder, &scores);
+        append_quality_scores(&mut builder, &[]);
+
+        let list = builder.finish();
+        assert_eq!(list.len(), 2);
+
+        let expected = (0..100).map(|i| i % 42).chain([-1]).collect::<Vec<i64>>();
+        assert_eq!(
+            list.value(0).as_primitive::<Int64Type>().values(),
+            &expected[..]
+        );
+        assert!(list.value(1).is_empty());
+    }
+
+    #[test]
+    fn test_append_raw_quality_scores() {
+        let mut builder = GenericListBuilder::<i32, UInt8Builder>::new(UInt8Builder::new());
+
+        append_raw_quality_scores(&mut builder, &[0, 40, 0xff]);
+
+        let list = builder.finish();
+        assert_eq!(
+            list.value(0).as_primitive::<UInt8Type>().values(),
+            &[0, 40, 255]
+        );
+    }
+
+    #[test]
+    fn test_append_phred33_quality_scores() {
+        let mut builder = GenericListBuilder::<i32, Int32Builder>::new(Int32Builder::new());
+
+        append_phred33_quality_scores(&mut builder, b"!+5I~");
+
+        let list = builder.finish();
+        assert_eq!(
+            list.value(0).as_primitive::<Int32Type>().values(),
+            &[0, 10, 20, 40, 93]
+        );
+    }
+}
@@ -0,0 +1,491 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    error::Error,
+    fmt::Display,
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncReadExt, ReadBuf};
+
+/// The default maximum size of a single record in bytes.
+pub const DEFAULT_MAX_RECORD_SIZE: usize = 256 * 1024 * 1024;
+
+/// The default maximum sequence length, the largest value an Arrow string array can hold.
+pub const DEFAULT_MAX_SEQUENCE_LENGTH: usize = i32::MAX as usize;
+
+/// The default maximum number of attributes, tags, or fields on a single record.
+pub const DEFAULT_MAX_ATTRIBUTE_COUNT: usize = 65_536;
+
+/// The default maximum size of a file header in bytes.
+pub const DEFAULT_MAX_HEADER_SIZE: usize = 256 * 1024 * 1024;
+
+/// A limit on the input a batch reader accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReaderLimit {
+    /// The size of a single record, or line for line oriented formats, in bytes.
+    RecordSize,
+
+    /// The length of a single sequence.
+    SequenceLength,
+
+    /// The number of attributes, tags, or fields on a single record.
+    AttributeCount,
+
+    /// The size of the file header in bytes.
+    HeaderSize,
+}
+
+impl ReaderLimit {
+    /// The name of the session configuration option that sets the limit.
+    pub fn config_name(&self) -> &'static str {
+        match self {
+            ReaderLimit::RecordSize => "exon.max_record_size",
+            ReaderLimit::SequenceLength => "exon.max_sequence_length",
+            ReaderLimit::AttributeCount => "exon.max_attribute_count",
+            ReaderLimit::HeaderSize => "exon.max_header_size",
+        }
+    }
+}
+
+impl Display for ReaderLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReaderLimit::RecordSize => write!(f, "record size"),
+            ReaderLimit::SequenceLength => write!(f, "sequence length"),
+            ReaderLimit::AttributeCount => write!(f, "attribute count"),
+            ReaderLimit::HeaderSize => write!(f, "header size"),
+        }
+    }
+}
+
+/// An error returned when the input exceeds one of the [`ReaderLimits`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReaderLimitError {
+    /// The limit that was exceeded.
+    pub limit: ReaderLimit,
+
+    /// The size seen when reading stopped, a lower bound on the actual size.
+    pub size: usize,
+
+    /// The configured maximum.
+    pub max: usize,
+
+    /// The offset in bytes into the decompressed input the limit was hit at, if known.
+    pub position: Option<u64>,
+}
+
+impl ReaderLimitError {
+    /// Find a reader limit error in the chain of sources of an error.
+    ///
+    /// `std::io::Error` is looked through since the readers surface the error as its payload.
+    pub fn find<'a>(error: &'a (dyn Error + 'static)) -> Option<&'a ReaderLimitError> {
+        let mut current = Some(error);
+
+        while let Some(error) = current {
+            if let Some(e) = error.downcast_ref::<ReaderLimitError>() {
+                return Some(e);
+            }
+
+            current = match error.downcast_ref::<io::Error>() {
+                Some(e) => e.get_ref().map(|e| e as &(dyn Error + 'static)),
+                None => error.source(),
+            };
+        }
+
+        None
+    }
+}
+
+impl Display for ReaderLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of at least {} exceeds the maximum of {}, set {} to raise it",
+            self.limit,
+            self.size,
+            self.max,
+            self.limit.config_name()
+        )
+    }
+}
+
+impl Error for ReaderLimitError {}
+
+impl From<ReaderLimitError> for io::Error {
+    fn from(error: ReaderLimitError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+/// Caps on the input the batch readers accept, so corrupt or hostile files fail with an error
+/// rather than allocating without bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderLimits {
+    /// The maximum size of a single record in bytes.
+    pub max_record_size: usize,
+
+    /// The maximum length of a single sequence.
+    pub max_sequence_length: usize,
+
+    /// The maximum number of attributes, tags, or fields on a single record.
+    pub max_attribute_count: usize,
+
+    /// The maximum size of a file header in bytes.
+    pub max_header_size: usize,
+}
+
+impl Default for ReaderLimits {
+    fn default() -> Self {
+        Self {
+            max_record_size: DEFAULT_MAX_RECORD_SIZE,
+            max_sequence_length: DEFAULT_MAX_SEQUENCE_LENGTH,
+            max_attribute_count: DEFAULT_MAX_ATTRIBUTE_COUNT,
+            max_header_size: DEFAULT_MAX_HEADER_SIZE,
+        }
+    }
+}
+
+impl ReaderLimits {
+    /// Get the maximum for a limit.
+    pub fn max(&self, limit: ReaderLimit) -> usize {
+        match limit {
+            ReaderLimit::RecordSize => self.max_record_size,
+            ReaderLimit::SequenceLength => self.max_sequence_length,
+            ReaderLimit::AttributeCount => self.max_attribute_count,
+            ReaderLimit::HeaderSize => self.max_header_size,
+        }
+    }
+
+    /// Check a size against a limit.
+    pub fn check(&self, limit: ReaderLimit, size: usize) -> Result<(), ReaderLimitError> {
+        let max = self.max(limit);
+
+        if size > max {
+            return Err(ReaderLimitError {
+                limit,
+                size,
+                max,
+                position: None,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Read a little endian `u32` length prefix onto the end of `buf`, returning `None` at EOF.
+///
+/// Binary formats read their length prefixes with this, so the length can be checked against a
+/// limit before the bytes it covers are buffered.
+pub async fn read_length_prefix<R>(reader: &mut R, buf: &mut Vec<u8>) -> io::Result<Option<usize>>
+where
+    R: AsyncRead + Unpin,
+{
+    let start = buf.len();
+    buf.resize(start + 4, 0);
+
+    let mut bytes_read = 0;
+
+    while bytes_read < 4 {
+        match reader.read(&mut buf[start + bytes_read..]).await? {
+            0 if bytes_read == 0 => {
+                buf.truncate(start);
+                return Ok(None);
+            }
+            0 => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "early eof")),
+            n => bytes_read += n,
+        }
+    }
+
+    let length = u32::from_le_bytes([buf[start], buf[start + 1], buf[start + 2], buf[start + 3]]);
+
+    Ok(Some(length as usize))
+}
+
+/// Read a little endian `u32` length prefix onto the end of `buf`, failing at EOF.
+pub async fn read_required_length_prefix<R>(reader: &mut R, buf: &mut Vec<u8>) -> io::Result<usize>
+where
+    R: AsyncRead + Unpin,
+{
+    read_length_prefix(reader, buf)
+        .await?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "early eof"))
+}
+
+/// Read exactly `n` more bytes onto the end of `buf`.
+pub async fn read_exact_onto<R>(reader: &mut R, buf: &mut Vec<u8>, n: usize) -> io::Result<()>
+where
+    R: AsyncRead + Unpin,
+{
+    let start = buf.len();
+    buf.resize(start + n, 0);
+    reader.read_exact(&mut buf[start..]).await?;
+
+    Ok(())
+}
+
+/// A buffered reader that fails once a line exceeds the record size limit, or once a budget of
+/// bytes, e.g. for the header, runs out.
+///
+/// The checks happen as the data is handed out, so a caller reading lines or records into a
+/// buffer never grows it past the limit.
+#[derive(Debug)]
+pub struct BoundedReader<R> {
+    inner: R,
+
+    /// The maximum line length, excluding the newline.
+    max_line_length: usize,
+
+    /// The length of the line read so far.
+    line_length: usize,
+
+    /// The limit, its maximum, and the bytes consumed against the current budget.
+    budget: Option<(ReaderLimit, usize, usize)>,
+
+    /// The number of bytes consumed.
+    position: u64,
+
+    /// Whether the buffer last returned ends with a newline, and its length.
+    pending: (usize, bool),
+}
+
+impl<R> BoundedReader<R> {
+    /// Create a bounded reader that caps lines at the record size limit.
+    pub fn new(inner: R, limits: &ReaderLimits) -> Self {
+        Self {
+            inner,
+            max_line_length: limits.max_record_size,
+            line_length: 0,
+            budget: None,
+            position: 0,
+            pending: (0, false),
+        }
+    }
+
+    /// Start a budget of bytes, any read past `max` bytes fails with `limit`.
+    pub fn start_budget(&mut self, limit: ReaderLimit, max: usize) {
+        self.budget = Some((limit, max, 0));
+    }
+
+    /// End the current budget.
+    pub fn end_budget(&mut self) {
+        self.budget = None;
+    }
+
+    /// The number of bytes read so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R> AsyncBufRead for BoundedReader<R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+        let buf = ready!(Pin::new(&mut this.inner).poll_fill_buf(cx))?;
+
+        if buf.is_empty() {
+            this.pending = (0, false);
+            return Poll::Ready(Ok(buf));
+        }
+
+        // Hand out at most one line at a time so consume can track line lengths.
+        let (mut end, mut ends_line) = match buf.iter().position(|b| *b == b'\n') {
+            Some(i) => (i + 1, true),
+            None => (buf.len(), false),
+        };
+
+        let content_length = if ends_line { end - 1 } else { end };
+        let room = this.max_line_length.saturating_sub(this.line_length);
+
+        if content_length > room {
+            if room == 0 {
+                return Poll::Ready(Err(ReaderLimitError {
+                    limit: ReaderLimit::RecordSize,
+                    size: this.line_length + content_length,
+                    max: this.max_line_length,
+                    position: Some(this.position),
+                }
+                .into()));
+            }
+
+            end = room;
+            ends_line = false;
+        }
+
+        if let Some((limit, max, used)) = this.budget {
+            let remaining = max.saturating_sub(used);
+
+            if remaining == 0 {
+                return Poll::Ready(Err(ReaderLimitError {
+                    limit,
+                    size: used + end,
+                    max,
+                    position: Some(this.position),
+                }
+                .into()));
+            }
+
+            if end > remaining {
+                end = remaining;
+                ends_line = false;
+            }
+        }
+
+        this.pending = (end, ends_line);
+
+        Poll::Ready(Ok(&buf[..end]))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+
+        let (pending_length, ends_line) = this.pending;
+
+        if ends_line && amt == pending_length {
+            this.line_length = 0;
+        } else {
+            this.line_length += amt;
+        }
+
+        this.pending = (pending_length.saturating_sub(amt), ends_line);
+        this.position += amt as u64;
+
+        if let Some((_, _, used)) = this.budget.as_mut() {
+            *used += amt;
+        }
+
+        Pin::new(&mut this.inner).consume(amt);
+    }
+}
+
+impl<R> AsyncRead for BoundedReader<R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let available = ready!(self.as_mut().poll_fill_buf(cx))?;
+
+        let n = available.len().min(buf.remaining());
+        buf.put_slice(&available[..n]);
+
+        self.consume(n);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncBufReadExt;
+
+    use super::{BoundedReader, ReaderLimit, ReaderLimitError, ReaderLimits};
+
+    fn limits(max_record_size: usize) -> ReaderLimits {
+        ReaderLimits {
+            max_record_size,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lines_within_limit() -> Result<(), Box<dyn std::error::Error>> {
+        let mut reader = BoundedReader::new(&b"abcd\nef\n\nabcd"[..], &limits(4));
+
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await? == 0 {
+                break;
+            }
+            lines.push(line);
+        }
+
+        assert_eq!(lines, vec!["abcd\n", "ef\n", "\n", "abcd"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_long_line_fails() -> Result<(), Box<dyn std::error::Error>> {
+        let mut reader = BoundedReader::new(&b"ab\nabcdef\n"[..], &limits(4));
+
+        let mut line = String::new();
+        reader.read_line(&mut line).await?;
+
+        line.clear();
+        let err = reader.read_line(&mut line).await.unwrap_err();
+        assert!(line.len() <= 4);
+
+        let err = ReaderLimitError::find(&err).unwrap();
+        assert_eq!(err.limit, ReaderLimit::RecordSize);
+        assert_eq!(err.max, 4);
+        assert_eq!(err.position, Some(7));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_budget() -> Result<(), Box<dyn std::error::Error>> {
+        let mut reader = BoundedReader::new(&b"#a\n#b\nrecord\n"[..], &limits(100));
+
+        reader.start_budget(ReaderLimit::HeaderSize, 6);
+        let mut line = String::new();
+        reader.read_line(&mut line).await?;
+        reader.read_line(&mut line).await?;
+        assert_eq!(line, "#a\n#b\n");
+
+        let err = reader.read_line(&mut line).await.unwrap_err();
+        let err = ReaderLimitError::find(&err).unwrap();
+        assert_eq!(err.limit, ReaderLimit::HeaderSize);
+
+        reader.end_budget();
+        line.clear();
+        reader.read_line(&mut line).await?;
+        assert_eq!(line, "record\n");
+
+        let limits = ReaderLimits::default();
+        assert!(limits.check(ReaderLimit::AttributeCount, 10).is_ok());
+        assert!(limits
+            .check(ReaderLimit::AttributeCount, limits.max_attribute_count + 1)
+            .is_err());
+
+        Ok(())
+    }
+}
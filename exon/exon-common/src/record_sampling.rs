@@ -0,0 +1,299 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::{
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
+};
+
+/// How the records of a scan are sampled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleMethod {
+    /// Keep each record with a probability, decided by a hash of its name, so the mates of paired
+    /// reads in different files are sampled together.
+    Fraction(f64),
+    /// Keep a uniform sample of this many records from each file.
+    Reservoir(usize),
+    /// Keep a uniform sample of `size` records from each window of `region_size` bases of each
+    /// reference sequence, and of the unmapped records, so every region is represented.
+    Stratified { size: usize, region_size: u64 },
+}
+
+/// A sample of the records of a scan, taken before the columns of the records are built.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecordSampling {
+    method: SampleMethod,
+    seed: u64,
+}
+
+/// The SplitMix64 finalizer, which mixes the bits of a hash or counter.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// A seeded FNV-1a hash, which is stable across platforms and releases.
+struct SeededHasher(u64);
+
+impl Hasher for SeededHasher {
+    fn finish(&self) -> u64 {
+        mix(self.0)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+fn seeded_hash(seed: u64, value: impl Hash) -> u64 {
+    let mut hasher = SeededHasher(0xcbf29ce484222325 ^ mix(seed));
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl RecordSampling {
+    /// Sample records with a method and a seed of 0.
+    pub fn new(method: SampleMethod) -> Self {
+        Self { method, seed: 0 }
+    }
+
+    /// Set the seed, so different seeds take different samples.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The method of the sample.
+    pub fn method(&self) -> SampleMethod {
+        self.method
+    }
+
+    /// Whether the sample is taken per file, so a file can't be split across partitions.
+    pub fn is_per_file(&self) -> bool {
+        !matches!(self.method, SampleMethod::Fraction(_))
+    }
+
+    /// Whether to keep a record with a name, when sampling a fraction of the records.
+    pub fn keep(&self, name: &[u8]) -> bool {
+        let SampleMethod::Fraction(fraction) = self.method else {
+            return true;
+        };
+
+        // The top 53 bits of the hash are a uniform number in [0, 1).
+        let x = (seeded_hash(self.seed, name) >> 11) as f64 / (1u64 << 53) as f64;
+
+        x < fraction
+    }
+
+    /// An empty reservoir for the records of a stratum, e.g. a region, of the sample.
+    pub fn reservoir<T>(&self, size: usize, stratum: impl Hash) -> Reservoir<T> {
+        Reservoir::new(size, seeded_hash(self.seed, stratum))
+    }
+}
+
+/// A uniform sample of a stream of items, kept with reservoir sampling.
+#[derive(Debug)]
+pub struct Reservoir<T> {
+    size: usize,
+    seen: u64,
+    state: u64,
+    items: Vec<T>,
+}
+
+impl<T> Reservoir<T> {
+    /// Create an empty reservoir of a size, whose random choices are seeded.
+    pub fn new(size: usize, seed: u64) -> Self {
+        Self {
+            size,
+            seen: 0,
+            state: seed,
+            items: Vec::new(),
+        }
+    }
+
+    /// A uniform random number below `n`.
+    fn next_below(&mut self, n: u64) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        ((u128::from(mix(self.state)) * u128::from(n)) >> 64) as u64
+    }
+
+    /// Offer the next item of the stream, which is only built if it's kept.
+    pub fn offer(&mut self, item: impl FnOnce() -> T) {
+        self.seen += 1;
+
+        if self.items.len() < self.size {
+            self.items.push(item());
+            return;
+        }
+
+        let index = self.next_below(self.seen);
+        if let Ok(index) = usize::try_from(index) {
+            if index < self.size {
+                self.items[index] = item();
+            }
+        }
+    }
+
+    /// The number of items offered to the reservoir.
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    /// The sampled items.
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+}
+
+/// Reservoirs of the records in each window of the reference sequences, for a stratified sample.
+#[derive(Debug)]
+pub struct StratifiedReservoirs<T> {
+    sampling: RecordSampling,
+    size: usize,
+    region_size: u64,
+    reservoirs: BTreeMap<(Option<usize>, u64), Reservoir<T>>,
+}
+
+impl<T> StratifiedReservoirs<T> {
+    /// Create empty reservoirs of `size` records for windows of `region_size` bases.
+    pub fn new(sampling: RecordSampling, size: usize, region_size: u64) -> Self {
+        Self {
+            sampling,
+            size,
+            region_size: region_size.max(1),
+            reservoirs: BTreeMap::new(),
+        }
+    }
+
+    /// Offer a record at a reference sequence and 1-based position, which are `None` for
+    /// unmapped records.
+    pub fn offer(
+        &mut self,
+        reference_sequence_id: Option<usize>,
+        position: Option<usize>,
+        item: impl FnOnce() -> T,
+    ) {
+        let window = match (reference_sequence_id, position) {
+            (Some(_), Some(position)) => position.saturating_sub(1) as u64 / self.region_size,
+            _ => 0,
+        };
+        let stratum = (reference_sequence_id, window);
+
+        let (sampling, size) = (self.sampling, self.size);
+        self.reservoirs
+            .entry(stratum)
+            .or_insert_with(|| sampling.reservoir(size, stratum))
+            .offer(item);
+    }
+
+    /// The sampled records, ordered by reference sequence and window, with the unmapped records
+    /// first.
+    pub fn into_items(self) -> Vec<T> {
+        self.reservoirs
+            .into_values()
+            .flat_map(Reservoir::into_items)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keep_fraction() {
+        let sampling = RecordSampling::new(SampleMethod::Fraction(0.25)).with_seed(7);
+
+        let names = (0..10_000)
+            .map(|i| format!("read{}", i))
+            .collect::<Vec<_>>();
+        let kept = names
+            .iter()
+            .filter(|name| sampling.keep(name.as_bytes()))
+            .count();
+
+        assert!((2_250..2_750).contains(&kept), "kept {}", kept);
+
+        // The same names are kept again, e.g. the mates of paired reads.
+        let again = names
+            .iter()
+            .filter(|name| sampling.keep(name.as_bytes()))
+            .count();
+        assert_eq!(kept, again);
+
+        // Another seed keeps other names.
+        let other = sampling.with_seed(8);
+        assert!(names
+            .iter()
+            .any(|name| sampling.keep(name.as_bytes()) != other.keep(name.as_bytes())));
+
+        let all = RecordSampling::new(SampleMethod::Fraction(1.0));
+        assert!(names.iter().all(|name| all.keep(name.as_bytes())));
+    }
+
+    #[test]
+    fn test_reservoir() {
+        let mut reservoir = Reservoir::new(10, 42);
+        let mut built = 0;
+
+        for i in 0..1_000 {
+            reservoir.offer(|| {
+                built += 1;
+                i
+            });
+        }
+
+        assert_eq!(reservoir.seen(), 1_000);
+
+        let items = reservoir.into_items();
+        assert_eq!(items.len(), 10);
+        assert!(items.iter().any(|i| *i >= 10));
+
+        // Items are only built when they're kept.
+        assert!(built < 1_000);
+
+        let mut reservoir = Reservoir::new(10, 42);
+        (0..3).for_each(|i| reservoir.offer(|| i));
+        assert_eq!(reservoir.into_items(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_stratified_reservoirs() {
+        let sampling = RecordSampling::new(SampleMethod::Stratified {
+            size: 2,
+            region_size: 100,
+        });
+        let mut reservoirs = StratifiedReservoirs::new(sampling, 2, 100);
+
+        for position in 1..=1_000 {
+            reservoirs.offer(Some(0), Some(position), || (Some(0), position));
+        }
+        reservoirs.offer(None, None, || (None, 0));
+
+        let items = reservoirs.into_items();
+
+        // Two records of each of the ten windows, and the unmapped record first.
+        assert_eq!(items.len(), 21);
+        assert_eq!(items[0], (None, 0));
+
+        for (window, records) in items[1..].chunks(2).enumerate() {
+            for (_, position) in records {
+                assert_eq!((position - 1) / 100, window);
+            }
+        }
+    }
+}
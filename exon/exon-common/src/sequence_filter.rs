@@ -0,0 +1,75 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use regex::bytes::Regex;
+
+/// A prefilter on the raw bytes of a record's sequence.
+///
+/// Scanners use it to skip the records that can't match the pattern predicates of a query before
+/// they're appended to arrays. The predicates are still evaluated on the scan's output, so a
+/// filter can be looser than them but never stricter.
+#[derive(Debug, Clone)]
+pub struct SequenceFilter {
+    patterns: Vec<Regex>,
+}
+
+impl SequenceFilter {
+    /// Create a filter from a SQL `LIKE` pattern, where `\` escapes `%` and `_`.
+    pub fn try_new_like(pattern: &str, case_insensitive: bool) -> Result<Self, regex::Error> {
+        let mut regex = String::from(if case_insensitive { "(?is)^" } else { "(?s)^" });
+
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '%' => regex.push_str(".*"),
+                '_' => regex.push('.'),
+                '\\' => match chars.next() {
+                    Some(escaped) => regex.push_str(&regex::escape(&escaped.to_string())),
+                    None => regex.push_str(&regex::escape("\\")),
+                },
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+
+        regex.push('$');
+
+        Self::try_new_regex(&regex, false)
+    }
+
+    /// Create a filter from a regular expression that matches anywhere in the sequence.
+    pub fn try_new_regex(pattern: &str, case_insensitive: bool) -> Result<Self, regex::Error> {
+        let pattern = if case_insensitive {
+            format!("(?i){}", pattern)
+        } else {
+            pattern.to_string()
+        };
+
+        Ok(Self {
+            patterns: vec![Regex::new(&pattern)?],
+        })
+    }
+
+    /// Combine two filters, so a sequence has to match both.
+    pub fn and(mut self, other: SequenceFilter) -> Self {
+        self.patterns.extend(other.patterns);
+        self
+    }
+
+    /// Check if a sequence matches all of the filter's patterns.
+    pub fn is_match(&self, sequence: &[u8]) -> bool {
+        self.patterns
+            .iter()
+            .all(|pattern| pattern.is_match(sequence))
+    }
+}
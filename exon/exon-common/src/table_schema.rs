@@ -14,10 +14,12 @@
 
 use std::sync::Arc;
 
-use arrow::datatypes::{Field, Fields, Schema, SchemaRef};
+use arrow::datatypes::{DataType, Field, Fields, Schema, SchemaRef};
 
 use datafusion::error::Result;
 
+use crate::GENOME_BUILD_METADATA_KEY;
+
 /// A builder for `TableSchema`.
 pub struct TableSchemaBuilder {
     file_fields: Vec<Field>,
@@ -53,6 +55,17 @@ impl TableSchemaBuilder {
         self
     }
 
+    /// Sets the data type of the file field with the name, if there is one.
+    pub fn with_field_data_type(mut self, name: &str, data_type: DataType) -> Self {
+        for field in self.file_fields.iter_mut() {
+            if field.name() == name {
+                *field = Field::new(name, data_type.clone(), field.is_nullable());
+            }
+        }
+
+        self
+    }
+
     /// Adds partition fields to the `TableSchema`.
     pub fn add_partition_fields(mut self, fields: Vec<Field>) -> Self {
         self.partition_fields.extend(fields);
@@ -110,4 +123,27 @@ impl TableSchema {
     pub fn table_schema(&self) -> SchemaRef {
         self.schema.clone()
     }
+
+    /// Set the genome build the table's coordinates are on in the schema metadata, if known.
+    pub fn with_genome_build(mut self, genome_build: Option<&str>) -> Self {
+        if let Some(genome_build) = genome_build {
+            let mut metadata = self.schema.metadata().clone();
+            metadata.insert(
+                GENOME_BUILD_METADATA_KEY.to_string(),
+                genome_build.to_string(),
+            );
+
+            self.schema = Arc::new(self.schema.as_ref().clone().with_metadata(metadata));
+        }
+
+        self
+    }
+
+    /// Get the genome build the table's coordinates are on, if known
+    pub fn genome_build(&self) -> Option<&str> {
+        self.schema
+            .metadata()
+            .get(GENOME_BUILD_METADATA_KEY)
+            .map(|b| b.as_str())
+    }
 }
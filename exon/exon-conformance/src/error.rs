@@ -0,0 +1,75 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{error::Error, fmt::Display};
+
+use arrow::error::ArrowError;
+use datafusion::error::DataFusionError;
+use exon::ExonError;
+
+/// Errors raised while running conformance checks.
+#[derive(Debug)]
+pub enum ExonConformanceError {
+    /// An error from Exon while reading or writing.
+    ExonError(ExonError),
+
+    /// An error from DataFusion while executing a query.
+    DataFusionError(DataFusionError),
+
+    /// An error from Arrow while rendering batches.
+    ArrowError(ArrowError),
+
+    /// An IO error, e.g. reading a golden file.
+    IoError(std::io::Error),
+}
+
+impl Display for ExonConformanceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExonConformanceError::ExonError(e) => write!(f, "Exon error: {}", e),
+            ExonConformanceError::DataFusionError(e) => write!(f, "DataFusion error: {}", e),
+            ExonConformanceError::ArrowError(e) => write!(f, "Arrow error: {}", e),
+            ExonConformanceError::IoError(e) => write!(f, "IO error: {}", e),
+        }
+    }
+}
+
+impl Error for ExonConformanceError {}
+
+impl From<ExonError> for ExonConformanceError {
+    fn from(e: ExonError) -> Self {
+        ExonConformanceError::ExonError(e)
+    }
+}
+
+impl From<DataFusionError> for ExonConformanceError {
+    fn from(e: DataFusionError) -> Self {
+        ExonConformanceError::DataFusionError(e)
+    }
+}
+
+impl From<ArrowError> for ExonConformanceError {
+    fn from(e: ArrowError) -> Self {
+        ExonConformanceError::ArrowError(e)
+    }
+}
+
+impl From<std::io::Error> for ExonConformanceError {
+    fn from(e: std::io::Error) -> Self {
+        ExonConformanceError::IoError(e)
+    }
+}
+
+/// A result type for conformance checks.
+pub type Result<T> = std::result::Result<T, ExonConformanceError>;
@@ -0,0 +1,210 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use exon::ExonSession;
+
+use crate::Result;
+
+/// Quote a column name so reserved words like `end` can be selected.
+fn quote_columns(columns: &[&str]) -> String {
+    columns
+        .iter()
+        .map(|c| format!("\"{}\"", c))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A file format that Exon can both read and write.
+///
+/// Reading goes through the format's scan table function and writing goes through `COPY ... TO
+/// ... STORED AS`, so the defaults are enough for any format Exon registers both for.
+#[async_trait]
+pub trait ReadWriteFormat: Send + Sync + std::fmt::Debug {
+    /// The name used in `STORED AS`, e.g. `FASTA`.
+    fn stored_as(&self) -> &str;
+
+    /// The table function used to read a file, e.g. `fasta_scan`.
+    fn scan_function(&self) -> &str;
+
+    /// The file extension used for files written during checks.
+    fn file_extension(&self) -> &str;
+
+    /// The columns that are expected to survive a write and a read unchanged.
+    fn columns(&self) -> &[&str];
+
+    /// The columns used to order rows before they're compared.
+    fn sort_columns(&self) -> &[&str] {
+        self.columns()
+    }
+
+    /// Read the conformance columns from the file at `path`, in a deterministic order.
+    async fn read(&self, session: &ExonSession, path: &str) -> Result<Vec<RecordBatch>> {
+        let sql = format!(
+            "SELECT {} FROM {}('{}') ORDER BY {}",
+            quote_columns(self.columns()),
+            self.scan_function(),
+            path,
+            quote_columns(self.sort_columns()),
+        );
+
+        let batches = session.sql(&sql).await?.collect().await?;
+
+        Ok(batches)
+    }
+
+    /// Write the conformance columns from the file at `source_path` to `target_path`.
+    async fn write(
+        &self,
+        session: &ExonSession,
+        source_path: &str,
+        target_path: &str,
+    ) -> Result<()> {
+        let sql = format!(
+            "COPY (SELECT {} FROM {}('{}')) TO '{}' STORED AS {}",
+            quote_columns(self.columns()),
+            self.scan_function(),
+            source_path,
+            target_path,
+            self.stored_as(),
+        );
+
+        session.sql(&sql).await?.collect().await?;
+
+        Ok(())
+    }
+}
+
+/// The FASTA reader and writer.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Fasta;
+
+impl ReadWriteFormat for Fasta {
+    fn stored_as(&self) -> &str {
+        "FASTA"
+    }
+
+    fn scan_function(&self) -> &str {
+        "fasta_scan"
+    }
+
+    fn file_extension(&self) -> &str {
+        "fasta"
+    }
+
+    fn columns(&self) -> &[&str] {
+        &["id", "description", "sequence"]
+    }
+}
+
+/// The FASTQ reader and writer.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Fastq;
+
+impl ReadWriteFormat for Fastq {
+    fn stored_as(&self) -> &str {
+        "FASTQ"
+    }
+
+    fn scan_function(&self) -> &str {
+        "fastq_scan"
+    }
+
+    fn file_extension(&self) -> &str {
+        "fastq"
+    }
+
+    fn columns(&self) -> &[&str] {
+        &["name", "description", "sequence", "quality_scores"]
+    }
+}
+
+/// The GFF3 reader and writer.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Gff;
+
+impl ReadWriteFormat for Gff {
+    fn stored_as(&self) -> &str {
+        "GFF"
+    }
+
+    fn scan_function(&self) -> &str {
+        "gff_scan"
+    }
+
+    fn file_extension(&self) -> &str {
+        "gff"
+    }
+
+    fn columns(&self) -> &[&str] {
+        &[
+            "seqname",
+            "source",
+            "type",
+            "start",
+            "end",
+            "score",
+            "strand",
+            "phase",
+            "attributes",
+        ]
+    }
+
+    // Map columns can't be sorted on.
+    fn sort_columns(&self) -> &[&str] {
+        &[
+            "seqname", "start", "end", "type", "source", "strand", "phase", "score",
+        ]
+    }
+}
+
+/// The BED reader and writer, limited to the BED6 columns the reader round trips.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Bed;
+
+impl ReadWriteFormat for Bed {
+    fn stored_as(&self) -> &str {
+        "BED"
+    }
+
+    fn scan_function(&self) -> &str {
+        "bed_scan"
+    }
+
+    fn file_extension(&self) -> &str {
+        "bed"
+    }
+
+    fn columns(&self) -> &[&str] {
+        &[
+            "reference_sequence_name",
+            "start",
+            "end",
+            "name",
+            "score",
+            "strand",
+        ]
+    }
+}
+
+/// All the formats Exon can both read and write.
+pub fn formats() -> Vec<Box<dyn ReadWriteFormat>> {
+    vec![
+        Box::new(Fasta),
+        Box::new(Fastq),
+        Box::new(Gff),
+        Box::new(Bed),
+    ]
+}
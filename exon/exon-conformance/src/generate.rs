@@ -0,0 +1,127 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Random, valid records for round-trip property tests.
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+
+const NUCLEOTIDES: &[u8] = b"ACGT";
+
+fn random_sequence(rng: &mut StdRng, length: usize) -> String {
+    (0..length)
+        .map(|_| *NUCLEOTIDES.choose(rng).unwrap() as char)
+        .collect()
+}
+
+fn random_quality_scores(rng: &mut StdRng, length: usize) -> String {
+    (0..length)
+        .map(|_| rng.gen_range(b'!'..=b'J') as char)
+        .collect()
+}
+
+/// A FASTA file with `n` records.
+pub(crate) fn fasta(rng: &mut StdRng, n: usize) -> String {
+    let mut contents = String::new();
+
+    for i in 0..n {
+        let length = rng.gen_range(1..200);
+
+        contents.push_str(&format!(">seq{} description {}\n", i, rng.gen::<u32>()));
+        contents.push_str(&random_sequence(rng, length));
+        contents.push('\n');
+    }
+
+    contents
+}
+
+/// A FASTQ file with `n` records.
+pub(crate) fn fastq(rng: &mut StdRng, n: usize) -> String {
+    let mut contents = String::new();
+
+    for i in 0..n {
+        let length = rng.gen_range(1..200);
+
+        contents.push_str(&format!("@read{} description {}\n", i, rng.gen::<u32>()));
+        contents.push_str(&random_sequence(rng, length));
+        contents.push_str("\n+\n");
+        contents.push_str(&random_quality_scores(rng, length));
+        contents.push('\n');
+    }
+
+    contents
+}
+
+/// A BED6 file with `n` records.
+pub(crate) fn bed(rng: &mut StdRng, n: usize) -> String {
+    let mut contents = String::new();
+
+    for i in 0..n {
+        let chrom = ["chr1", "chr2", "chrX"].choose(rng).unwrap();
+        let start = rng.gen_range(0..1_000_000);
+        let end = start + rng.gen_range(1..10_000);
+        let strand = ["+", "-", "."].choose(rng).unwrap();
+
+        contents.push_str(&format!(
+            "{}\t{}\t{}\tfeature{}\t{}\t{}\n",
+            chrom,
+            start,
+            end,
+            i,
+            rng.gen_range(0..=1000),
+            strand
+        ));
+    }
+
+    contents
+}
+
+/// A GFF3 file with `n` records.
+pub(crate) fn gff(rng: &mut StdRng, n: usize) -> String {
+    let mut contents = String::from("##gff-version 3\n");
+
+    for i in 0..n {
+        let seqname = ["sq0", "sq1", "chr1"].choose(rng).unwrap();
+        let ty = ["gene", "exon", "CDS"].choose(rng).unwrap();
+        let start = rng.gen_range(1..1_000_000);
+        let end = start + rng.gen_range(0..10_000);
+        let strand = ["+", "-"].choose(rng).unwrap();
+
+        let score = if rng.gen_bool(0.5) {
+            (rng.gen_range(0..10_000) as f32 / 10.0).to_string()
+        } else {
+            ".".to_string()
+        };
+
+        let phase = if *ty == "CDS" {
+            rng.gen_range(0..3).to_string()
+        } else {
+            ".".to_string()
+        };
+
+        contents.push_str(&format!(
+            "{}\tsource{}\t{}\t{}\t{}\t{}\t{}\t{}\tID=feature{};Note=a%3Bb,c\n",
+            seqname,
+            rng.gen_range(0..3),
+            ty,
+            start,
+            end,
+            score,
+            strand,
+            phase,
+            i
+        ));
+    }
+
+    contents
+}
@@ -0,0 +1,74 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use exon::ExonSession;
+
+use crate::{
+    render::{diff_rows, render_rows, RowDifference},
+    ReadWriteFormat, Result,
+};
+
+/// Set this environment variable to rewrite golden files from the current output.
+pub const UPDATE_GOLDEN_ENV_VAR: &str = "EXON_UPDATE_GOLDEN";
+
+/// The result of comparing a file's contents with its golden file.
+#[derive(Debug, Clone)]
+pub struct GoldenReport {
+    /// The rows that differ from the golden file.
+    pub differences: Vec<RowDifference>,
+}
+
+impl GoldenReport {
+    /// Returns true if the file reads identically to its golden file.
+    pub fn is_conformant(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+/// Read `path` with `format` and compare it with the rows in `golden_path`.
+///
+/// A golden file has a header line of the column names, followed by one line per row with tab
+/// separated values as rendered by [`render_rows`]. If the `EXON_UPDATE_GOLDEN` environment
+/// variable is set, the golden file is (re)written instead.
+pub async fn check_golden(
+    session: &ExonSession,
+    format: &dyn ReadWriteFormat,
+    path: &str,
+    golden_path: &Path,
+) -> Result<GoldenReport> {
+    let mut actual = vec![format.columns().join("\t")];
+    actual.extend(render_rows(&format.read(session, path).await?)?);
+
+    if std::env::var_os(UPDATE_GOLDEN_ENV_VAR).is_some() {
+        let mut contents = actual.join("\n");
+        contents.push('\n');
+
+        std::fs::write(golden_path, contents)?;
+
+        return Ok(GoldenReport {
+            differences: vec![],
+        });
+    }
+
+    let expected = std::fs::read_to_string(golden_path)?
+        .lines()
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    Ok(GoldenReport {
+        differences: diff_rows(&expected, &actual),
+    })
+}
@@ -0,0 +1,171 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![warn(missing_docs)]
+
+//! Conformance checks for Exon's readers and writers.
+//!
+//! Each format that Exon can both read and write implements [`ReadWriteFormat`]. The checks in
+//! this crate are public so downstream users can validate their own files:
+//!
+//! - [`round_trip`] reads a file, writes it back out with Exon's writer, and reads the written
+//!   file to make sure nothing changed.
+//! - [`check_golden`] reads a file and compares the rows with a golden file.
+
+mod error;
+mod format;
+#[cfg(test)]
+mod generate;
+mod golden;
+mod render;
+mod round_trip;
+
+pub use error::{ExonConformanceError, Result};
+pub use format::{formats, Bed, Fasta, Fastq, Gff, ReadWriteFormat};
+pub use golden::{check_golden, GoldenReport, UPDATE_GOLDEN_ENV_VAR};
+pub use render::{diff_rows, render_rows, RowDifference};
+pub use round_trip::{round_trip, RoundTripReport};
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use exon::ExonSession;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{check_golden, generate, round_trip, Bed, Fasta, Fastq, Gff, ReadWriteFormat};
+
+    fn test_data_path(data_type: &str, file_name: &str) -> String {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../exon-core/test-data/datasources")
+            .join(data_type)
+            .join(file_name)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn golden_path(file_name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("golden")
+            .join(file_name)
+    }
+
+    fn output_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("exon-conformance").join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_golden_files() -> Result<(), Box<dyn std::error::Error>> {
+        let session = ExonSession::new_exon()?;
+
+        let cases: Vec<(Box<dyn ReadWriteFormat>, String, &str)> = vec![
+            (
+                Box::new(Fasta),
+                test_data_path("fasta", "test.fasta"),
+                "test.fasta.tsv",
+            ),
+            (
+                Box::new(Fastq),
+                test_data_path("fastq", "test.fastq"),
+                "test.fastq.tsv",
+            ),
+            (
+                Box::new(Bed),
+                test_data_path("bed", "test.bed"),
+                "test.bed.tsv",
+            ),
+        ];
+
+        for (format, path, golden) in cases {
+            let report =
+                check_golden(&session, format.as_ref(), &path, &golden_path(golden)).await?;
+
+            assert!(
+                report.is_conformant(),
+                "{} differs from {}: {:?}",
+                path,
+                golden,
+                report.differences
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_test_data() -> Result<(), Box<dyn std::error::Error>> {
+        let session = ExonSession::new_exon()?;
+        let output_dir = output_dir("test-data");
+
+        let cases: Vec<(Box<dyn ReadWriteFormat>, String)> = vec![
+            (Box::new(Fasta), test_data_path("fasta", "test.fasta")),
+            (Box::new(Fastq), test_data_path("fastq", "test.fastq")),
+            (Box::new(Gff), test_data_path("gff-prod", "ecoli.gff")),
+            (Box::new(Bed), test_data_path("bed", "test.bed")),
+        ];
+
+        for (format, path) in cases {
+            let report = round_trip(&session, format.as_ref(), &path, &output_dir).await?;
+
+            assert!(report.original_rows > 0);
+            assert!(
+                report.is_conformant(),
+                "{} did not round trip: {:?}",
+                path,
+                report.differences
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_generated() -> Result<(), Box<dyn std::error::Error>> {
+        let session = ExonSession::new_exon()?;
+        let output_dir = output_dir("generated");
+
+        for seed in 0..8 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let n_records = 1 + seed as usize * 25;
+
+            let cases: Vec<(Box<dyn ReadWriteFormat>, String)> = vec![
+                (Box::new(Fasta), generate::fasta(&mut rng, n_records)),
+                (Box::new(Fastq), generate::fastq(&mut rng, n_records)),
+                (Box::new(Gff), generate::gff(&mut rng, n_records)),
+                (Box::new(Bed), generate::bed(&mut rng, n_records)),
+            ];
+
+            for (format, contents) in cases {
+                let path = output_dir.join(format!("{}.{}", seed, format.file_extension()));
+                std::fs::write(&path, contents)?;
+
+                let path = path.to_string_lossy();
+                let report = round_trip(&session, format.as_ref(), &path, &output_dir).await?;
+
+                assert_eq!(report.original_rows, n_records);
+                assert!(
+                    report.is_conformant(),
+                    "{} did not round trip (seed {}): {:?}",
+                    path,
+                    seed,
+                    report.differences
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use arrow::{
+    record_batch::RecordBatch,
+    util::display::{ArrayFormatter, FormatOptions},
+};
+
+use crate::Result;
+
+/// A row that differs between the expected and actual output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowDifference {
+    /// The 0-based index of the row.
+    pub row: usize,
+
+    /// The expected row, or `None` if the actual output has extra rows.
+    pub expected: Option<String>,
+
+    /// The actual row, or `None` if the actual output is missing rows.
+    pub actual: Option<String>,
+}
+
+impl std::fmt::Display for RowDifference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "row {}: expected {:?}, got {:?}",
+            self.row, self.expected, self.actual
+        )
+    }
+}
+
+/// Render each row of the batches as its tab separated column values, nulls are `NULL`.
+pub fn render_rows(batches: &[RecordBatch]) -> Result<Vec<String>> {
+    let options = FormatOptions::default().with_null("NULL");
+    let mut rows = Vec::new();
+
+    for batch in batches {
+        let formatters = batch
+            .columns()
+            .iter()
+            .map(|c| ArrayFormatter::try_new(c.as_ref(), &options))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        for i in 0..batch.num_rows() {
+            let row = formatters
+                .iter()
+                .map(|f| f.value(i).to_string())
+                .collect::<Vec<_>>()
+                .join("\t");
+
+            rows.push(row);
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Compare the expected rows with the actual rows, returning every row that differs.
+pub fn diff_rows(expected: &[String], actual: &[String]) -> Vec<RowDifference> {
+    let n_rows = expected.len().max(actual.len());
+
+    (0..n_rows)
+        .filter_map(|row| {
+            let expected = expected.get(row);
+            let actual = actual.get(row);
+
+            if expected == actual {
+                None
+            } else {
+                Some(RowDifference {
+                    row,
+                    expected: expected.cloned(),
+                    actual: actual.cloned(),
+                })
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff_rows;
+
+    #[test]
+    fn test_diff_rows() {
+        let expected = vec!["a\t1".to_string(), "b\t2".to_string()];
+        let actual = vec!["a\t1".to_string(), "b\t3".to_string(), "c\t4".to_string()];
+
+        let differences = diff_rows(&expected, &actual);
+
+        assert_eq!(differences.len(), 2);
+        assert_eq!(differences[0].row, 1);
+        assert_eq!(differences[1].expected, None);
+        assert_eq!(differences[1].actual, Some("c\t4".to_string()));
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use exon::ExonSession;
+
+use crate::{
+    render::{diff_rows, render_rows, RowDifference},
+    ReadWriteFormat, Result,
+};
+
+/// The result of reading a file, writing it back out, and reading the written file.
+#[derive(Debug, Clone)]
+pub struct RoundTripReport {
+    /// The number of rows read from the original file.
+    pub original_rows: usize,
+
+    /// The number of rows read from the written file.
+    pub round_trip_rows: usize,
+
+    /// The rows that changed in the round trip.
+    pub differences: Vec<RowDifference>,
+}
+
+impl RoundTripReport {
+    /// Returns true if the written file reads back identically to the original.
+    pub fn is_conformant(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+/// Read `path` with `format`, write it to a file in `output_dir`, and compare what's read back.
+///
+/// The written file is left in `output_dir` so a failing round trip can be inspected.
+pub async fn round_trip(
+    session: &ExonSession,
+    format: &dyn ReadWriteFormat,
+    path: &str,
+    output_dir: &Path,
+) -> Result<RoundTripReport> {
+    let file_stem = Path::new(path)
+        .file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or("input");
+
+    let output_path = output_dir.join(format!(
+        "{}.round-trip.{}",
+        file_stem,
+        format.file_extension()
+    ));
+    let output_path = output_path.to_string_lossy();
+
+    let original = render_rows(&format.read(session, path).await?)?;
+
+    format.write(session, path, &output_path).await?;
+
+    let round_trip = render_rows(&format.read(session, &output_path).await?)?;
+
+    Ok(RoundTripReport {
+        original_rows: original.len(),
+        round_trip_rows: round_trip.len(),
+        differences: diff_rows(&original, &round_trip),
+    })
+}
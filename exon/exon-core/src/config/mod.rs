@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod udtf;
+
+use std::{sync::Arc, time::Duration};
+
 use datafusion::{
     catalog::Session,
     common::extensions_options,
@@ -19,7 +23,22 @@ use datafusion::{
     prelude::SessionConfig,
 };
 
-use crate::error::{ExonError, Result};
+use exon_common::{
+    canonical_genome_build, ColumnTransformerRegistry, ColumnTransforms, ReaderLimits,
+    StrandEncoding,
+};
+use exon_fasta::SequenceDataType;
+use exon_io::{ObjectCache, RetryPolicy};
+
+use crate::{
+    error::{ExonError, Result},
+    udfs::{
+        liftover::{LiftoverRegistry, LiftoverUnmapped},
+        sketch::SketchRegistry,
+    },
+};
+
+pub use self::udtf::ExonSettingsFunction;
 
 pub const BATCH_SIZE: usize = 8 * 1024;
 
@@ -42,6 +61,9 @@ pub fn new_exon_config() -> SessionConfig {
         .with_repartition_windows(true)
         .with_repartition_file_scans(true)
         .with_target_partitions(num_cpus::get())
+        .with_extension(Arc::new(ColumnTransformerRegistry::default()))
+        .with_extension(Arc::new(LiftoverRegistry::default()))
+        .with_extension(Arc::new(SketchRegistry::default()))
 }
 
 pub fn extract_config_from_state(session_state: &dyn Session) -> Result<&ExonConfigExtension> {
@@ -62,14 +84,191 @@ pub fn extract_exon_config(session_config: &SessionConfig) -> Result<&ExonConfig
     Ok(config)
 }
 
+/// The reader limits of the session, or the defaults when Exon isn't configured.
+pub fn reader_limits(session_config: &SessionConfig) -> ReaderLimits {
+    extract_exon_config(session_config)
+        .map(|config| config.reader_limits())
+        .unwrap_or_default()
+}
+
 extensions_options! {
     /// Exon config options.
     pub struct ExonConfigExtension {
+        /// Parse the INFO fields of VCF and BCF tables into a struct column.
         pub vcf_parse_info: bool, default = false
+        /// Parse the FORMAT fields of VCF and BCF tables into a list of structs per sample.
         pub vcf_parse_formats: bool, default = false
+        /// Add typed END, SVTYPE, SVLEN, CIPOS, CIEND, and breakend mate columns to VCF tables.
+        pub vcf_parse_structural_variants: bool, default = false
+        /// Dictionary encode the GT and FILTER values of VCF tables, so the values repeated across
+        /// samples and records are stored once per batch.
+        pub vcf_dictionary_encode_genotypes: bool, default = false
+        /// Parse the tags of SAM tables into a struct column.
         pub sam_parse_tags: bool, default = false
+        /// Parse the tags of BAM tables into a struct column.
         pub bam_parse_tags: bool, default = false
+        /// Add `zmw`, `num_passes`, `read_quality`, `ipd`, and `pulse_width` columns from the
+        /// PacBio tags to BAM tables.
+        pub bam_pacbio_columns: bool, default = false
+        /// Parse the tags of CRAM tables into a struct column.
         pub cram_parse_tags: bool, default = false
+        /// Use UInt16 `flag`, UInt8 `mapping_quality`, and list of UInt8 `quality_score` columns in
+        /// SAM, BAM, and CRAM tables, instead of Int32, Utf8, and list of Int64.
+        pub alignment_compact_types: bool, default = false
+        /// How the `strand` columns of GFF, GTF, and BED tables are encoded, `utf8`, `dictionary`,
+        /// or `int8` codes of 1, -1, and 0 for an unknown strand, which `strand_symbol` renders.
+        pub strand_encoding: String, default = String::from("utf8")
+        /// Dictionary encode the `type` columns of GFF and GTF tables.
+        pub dictionary_encode_feature_types: bool, default = false
+        /// The type of the `sequence` column of FASTA tables, `utf8`, `large_utf8`,
+        /// `integer_encode_protein`, `integer_encode_dna`, or `packed_dna`, unless a table sets
+        /// `fasta.sequence_data_type`.
+        pub fasta_sequence_data_type: String, default = String::from("utf8")
+        /// The initial capacity in bytes of the buffer for each FASTA sequence, unless a table
+        /// sets `fasta.sequence_buffer_capacity`.
+        pub fasta_sequence_buffer_capacity: usize, default = 512
+        /// Push `LIKE` and regular expression filters on FASTQ sequences down to the scan, which
+        /// skips the records that can't match before building their columns.
+        pub sequence_filter_pushdown: bool, default = true
+        /// Answer `COUNT(*)` over whole reference sequences of indexed BAM and VCF tables from the
+        /// index metadata, instead of scanning the records.
+        pub index_count_pushdown: bool, default = true
+        /// The number of consecutive retries of a failed object store read.
+        pub object_store_max_retries: usize, default = 5
+        /// The backoff in milliseconds before the first retry of a failed object store read.
+        pub object_store_retry_backoff_ms: u64, default = 100
+        /// Add `atoms` and `bonds` list columns with the connection table to SDF tables.
+        pub sdf_parse_structure: bool, default = false
+        /// Skip chunks of uncompressed SDF files using a property min/max sidecar index.
+        pub sdf_property_index: bool, default = false
+        /// Verify MD5 sidecars and BGZF block CRCs when reading files.
+        pub verify_checksums: bool, default = false
+        /// Use ETags that look like MD5 digests when a file has no `.md5` sidecar.
+        pub verify_checksums_with_etag: bool, default = false
+        /// Add `_file_path`, `_file_last_modified`, `_file_size`, and `_record_index` columns to
+        /// BED, FASTQ, GFF, and VCF tables.
+        pub provenance_columns: bool, default = false
+        /// Scan BED, FASTQ, GFF, and VCF files up to this size in bytes once to report exact row
+        /// and null counts to the planner, 0 disables it.
+        pub exact_statistics_max_file_size: usize, default = 0
+        /// Prune VCF and GFF files for identifier lookups, e.g. `array_has(id, 'rs123')`, with
+        /// bloom filter sidecars, which are written next to each file on its first lookup.
+        pub identifier_bloom_filters: bool, default = false
+        /// Skip the VCF records without a looked up ID, e.g. `array_has_any(id, ['rs1', 'rs2'])`,
+        /// and read only the chunks of uncompressed files that may have one with hashed ID index
+        /// sidecars, which are written next to each file on its first lookup.
+        pub identifier_indexes: bool, default = false
+        /// Pack the sequences of FASTQ and BAM tables with 2 bits per base into binary columns,
+        /// which can be read with `unpack_sequence`.
+        pub pack_sequences: bool, default = false
+        /// Columns to transform in every scan and write by name, e.g.
+        /// `sample_id=hmac_token,name=aes_gcm`. The key is set with
+        /// `ExonSession::register_column_transform_key_provider`.
+        pub column_transforms: String, default = String::new()
+        /// Append a JSON line with the table, filters, and byte ranges read to this local file for
+        /// every executed scan partition, empty disables it.
+        pub audit_log_path: String, default = String::new()
+        /// The user recorded in audit log entries.
+        pub audit_user: String, default = String::new()
+        /// The genome build of the tables that don't declare one in their options or file headers,
+        /// e.g. `GRCh38`, empty leaves them undeclared.
+        pub genome_build: String, default = String::new()
+        /// Fail queries that join tables declared on different genome builds.
+        pub genome_build_check: bool, default = false
+        /// Lift the coordinates of one side of a join of tables on different genome builds to the
+        /// build of the other, with a chain registered by `ExonSession::register_liftover`.
+        pub genome_build_liftover: bool, default = false
+        /// What lifting coordinates does with positions that aren't in the chain, `drop` their
+        /// rows, set them to `null`, or `error`.
+        pub liftover_unmapped: String, default = String::from("drop")
+        /// A local directory to cache remote reference files in, e.g. FASTA files and their
+        /// indexes, empty disables the cache.
+        pub cache_directory: String, default = String::new()
+        /// The maximum total size in bytes of the files in the cache directory.
+        pub cache_max_size: usize, default = 10 * 1024 * 1024 * 1024
+        /// The maximum size in bytes of a single record, or line for text formats.
+        pub max_record_size: usize, default = exon_common::DEFAULT_MAX_RECORD_SIZE
+        /// The maximum length of a single sequence.
+        pub max_sequence_length: usize, default = exon_common::DEFAULT_MAX_SEQUENCE_LENGTH
+        /// The maximum number of attributes, tags, or fields on a single record.
+        pub max_attribute_count: usize, default = exon_common::DEFAULT_MAX_ATTRIBUTE_COUNT
+        /// The maximum size in bytes of a file header.
+        pub max_header_size: usize, default = exon_common::DEFAULT_MAX_HEADER_SIZE
+    }
+}
+
+impl ExonConfigExtension {
+    /// The retry policy for object stores registered by Exon.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::default()
+            .with_max_retries(self.object_store_max_retries)
+            .with_initial_backoff(Duration::from_millis(self.object_store_retry_backoff_ms))
+    }
+
+    /// The encoding of the strand columns of GFF, GTF, and BED tables.
+    pub fn strand_encoding(&self) -> Result<StrandEncoding> {
+        Ok(self.strand_encoding.trim().parse()?)
+    }
+
+    /// The default type of the sequence column of FASTA tables.
+    pub fn fasta_sequence_data_type(&self) -> Result<SequenceDataType> {
+        Ok(self.fasta_sequence_data_type.trim().parse()?)
+    }
+
+    /// The cache for remote reference files, if a cache directory is set.
+    pub fn object_cache(&self) -> Option<ObjectCache> {
+        if self.cache_directory.trim().is_empty() {
+            return None;
+        }
+
+        Some(ObjectCache::new(
+            self.cache_directory.trim(),
+            self.cache_max_size,
+        ))
+    }
+
+    /// The canonical name of the session's genome build, if one is set.
+    pub fn genome_build(&self) -> Option<String> {
+        if self.genome_build.trim().is_empty() {
+            return None;
+        }
+
+        Some(canonical_genome_build(&self.genome_build))
+    }
+
+    /// The limits on the input the batch readers accept.
+    pub fn reader_limits(&self) -> ReaderLimits {
+        ReaderLimits {
+            max_record_size: self.max_record_size,
+            max_sequence_length: self.max_sequence_length,
+            max_attribute_count: self.max_attribute_count,
+            max_header_size: self.max_header_size,
+        }
+    }
+
+    /// What lifting coordinates across genome builds does with unmapped positions.
+    pub fn liftover_unmapped(&self) -> Result<LiftoverUnmapped> {
+        Ok(self.liftover_unmapped.trim().parse()?)
+    }
+
+    /// The configured column transforms, resolved against the session's transformer registry
+    /// and its key provider.
+    pub fn column_transforms(
+        &self,
+        session_config: &SessionConfig,
+    ) -> Result<Option<ColumnTransforms>> {
+        if self.column_transforms.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let registry = session_config
+            .get_extension::<ColumnTransformerRegistry>()
+            .unwrap_or_default();
+
+        Ok(ColumnTransforms::try_new(
+            &self.column_transforms,
+            &registry,
+        )?)
     }
 }
 
@@ -79,7 +278,13 @@ impl ConfigExtension for ExonConfigExtension {
 
 #[cfg(test)]
 mod tests {
-    use crate::{config::ExonConfigExtension, new_exon_config, ExonSession};
+    use arrow::array::AsArray;
+    use exon_common::{ReaderLimits, StrandEncoding};
+    use exon_fasta::SequenceDataType;
+
+    use crate::{
+        config::ExonConfigExtension, new_exon_config, udfs::liftover::LiftoverUnmapped, ExonSession,
+    };
 
     #[tokio::test]
     async fn test_config_set_with_defaults() -> Result<(), Box<dyn std::error::Error>> {
@@ -93,9 +298,41 @@ mod tests {
 
         assert!(!exon_config.vcf_parse_info);
         assert!(!exon_config.vcf_parse_formats);
+        assert!(!exon_config.vcf_parse_structural_variants);
+        assert!(!exon_config.vcf_dictionary_encode_genotypes);
         assert!(!exon_config.sam_parse_tags);
         assert!(!exon_config.bam_parse_tags);
+        assert!(!exon_config.bam_pacbio_columns);
         assert!(!exon_config.cram_parse_tags);
+        assert!(!exon_config.alignment_compact_types);
+        assert_eq!(exon_config.strand_encoding()?, StrandEncoding::Utf8);
+        assert!(!exon_config.dictionary_encode_feature_types);
+        assert!(matches!(
+            exon_config.fasta_sequence_data_type()?,
+            SequenceDataType::Utf8
+        ));
+        assert_eq!(exon_config.fasta_sequence_buffer_capacity, 512);
+        assert!(exon_config.sequence_filter_pushdown);
+        assert!(exon_config.index_count_pushdown);
+        assert_eq!(exon_config.object_store_max_retries, 5);
+        assert_eq!(exon_config.object_store_retry_backoff_ms, 100);
+        assert!(!exon_config.verify_checksums);
+        assert!(!exon_config.sdf_parse_structure);
+        assert!(!exon_config.sdf_property_index);
+        assert!(!exon_config.provenance_columns);
+        assert_eq!(exon_config.exact_statistics_max_file_size, 0);
+        assert!(!exon_config.identifier_bloom_filters);
+        assert!(!exon_config.identifier_indexes);
+        assert!(!exon_config.pack_sequences);
+        assert!(exon_config.column_transforms.is_empty());
+        assert!(exon_config.column_transforms(&config)?.is_none());
+        assert!(exon_config.audit_log_path.is_empty());
+        assert!(exon_config.object_cache().is_none());
+        assert!(exon_config.genome_build().is_none());
+        assert!(!exon_config.genome_build_check);
+        assert!(!exon_config.genome_build_liftover);
+        assert_eq!(exon_config.liftover_unmapped()?, LiftoverUnmapped::Drop);
+        assert_eq!(exon_config.reader_limits(), ReaderLimits::default());
 
         Ok(())
     }
@@ -110,6 +347,10 @@ mod tests {
         options.set("exon.sam_parse_tags", "true")?;
         options.set("exon.bam_parse_tags", "true")?;
         options.set("exon.cram_parse_tags", "true")?;
+        options.set("exon.object_store_max_retries", "2")?;
+        options.set("exon.genome_build", "hg38")?;
+        options.set("exon.strand_encoding", "Int8")?;
+        options.set("exon.liftover_unmapped", "Null")?;
 
         let exon_config = config
             .options()
@@ -122,6 +363,10 @@ mod tests {
         assert!(exon_config.sam_parse_tags);
         assert!(exon_config.bam_parse_tags);
         assert!(exon_config.cram_parse_tags);
+        assert_eq!(exon_config.retry_policy().max_retries(), 2);
+        assert_eq!(exon_config.genome_build().as_deref(), Some("GRCh38"));
+        assert_eq!(exon_config.strand_encoding()?, StrandEncoding::Int8);
+        assert_eq!(exon_config.liftover_unmapped()?, LiftoverUnmapped::Null);
 
         Ok(())
     }
@@ -152,4 +397,33 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_exon_settings() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        ctx.session
+            .sql("SET exon.fasta_sequence_data_type = 'large_utf8'")
+            .await?;
+
+        let batches = ctx
+            .session
+            .sql("SELECT value, description FROM exon_settings() WHERE name = 'exon.fasta_sequence_data_type'")
+            .await?
+            .collect()
+            .await?;
+
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_rows(), 1);
+
+        let values = batches[0].column(0).as_string::<i32>();
+        assert_eq!(values.value(0), "large_utf8");
+
+        let descriptions = batches[0].column(1).as_string::<i32>();
+        assert!(descriptions
+            .value(0)
+            .starts_with("The type of the `sequence` column"));
+
+        Ok(())
+    }
 }
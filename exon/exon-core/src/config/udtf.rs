@@ -0,0 +1,86 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use arrow::{
+    array::{RecordBatch, StringArray},
+    datatypes::{DataType, Field, Schema},
+};
+use datafusion::{
+    config::{ConfigExtension, ExtensionOptions},
+    datasource::{function::TableFunctionImpl, MemTable, TableProvider},
+    error::{DataFusionError, Result},
+    execution::context::SessionContext,
+    logical_expr::Expr,
+};
+
+use super::{extract_config_from_state, ExonConfigExtension};
+
+/// A table function that lists the `exon.*` settings of the session with their current values
+/// and descriptions, e.g. `SELECT * FROM exon_settings()`.
+pub struct ExonSettingsFunction {
+    ctx: SessionContext,
+}
+
+impl std::fmt::Debug for ExonSettingsFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExonSettingsFunction").finish()
+    }
+}
+
+impl ExonSettingsFunction {
+    /// Create a new `ExonSettingsFunction`.
+    pub fn new(ctx: SessionContext) -> Self {
+        Self { ctx }
+    }
+}
+
+impl TableFunctionImpl for ExonSettingsFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        if !exprs.is_empty() {
+            return Err(DataFusionError::Plan(
+                "exon_settings takes no arguments".to_string(),
+            ));
+        }
+
+        let state = self.ctx.state();
+        let entries = extract_config_from_state(&state)?.entries();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("value", DataType::Utf8, true),
+            Field::new("description", DataType::Utf8, false),
+        ]));
+
+        let names = entries
+            .iter()
+            .map(|entry| format!("{}.{}", ExonConfigExtension::PREFIX, entry.key))
+            .collect::<StringArray>();
+        let values = entries
+            .iter()
+            .map(|entry| entry.value.as_deref())
+            .collect::<StringArray>();
+        let descriptions = entries
+            .iter()
+            .map(|entry| Some(entry.description))
+            .collect::<StringArray>();
+
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![Arc::new(names), Arc::new(values), Arc::new(descriptions)],
+        )?;
+
+        Ok(Arc::new(MemTable::try_new(schema, vec![vec![batch]])?))
+    }
+}
@@ -18,10 +18,16 @@ use datafusion::{
     datasource::physical_plan::{FileMeta, FileOpenFuture, FileOpener},
     error::DataFusionError,
 };
-use exon_bam::{BAMConfig, BatchReader};
+use exon_bam::{read_header_with_limits, BAMConfig, BatchReader};
 use futures::{StreamExt, TryStreamExt};
+use noodles::bgzf::VirtualPosition;
+use object_store::{GetOptions, GetRange};
 use tokio_util::io::StreamReader;
 
+use crate::streaming_bgzf::AsyncBGZFReader;
+
+use super::linear_index_split::BAMFileSplit;
+
 /// Implements a datafusion `FileOpener` for BAM files.
 pub struct BAMOpener {
     /// The base configuration for the file scan.
@@ -39,6 +45,14 @@ impl FileOpener for BAMOpener {
     fn open(&self, file_meta: FileMeta) -> datafusion::error::Result<FileOpenFuture> {
         let config = Arc::clone(&self.config);
 
+        // A split file only reads the records in its part, see `BAMScan::repartitioned`.
+        let split = file_meta
+            .extensions
+            .as_ref()
+            .and_then(|e| e.downcast_ref::<BAMFileSplit>())
+            .copied()
+            .unwrap_or_default();
+
         Ok(Box::pin(async move {
             let get_request = config.object_store.get(file_meta.location()).await?;
 
@@ -46,7 +60,50 @@ impl FileOpener for BAMOpener {
             let stream_reader = Box::pin(get_stream.map_err(DataFusionError::from));
             let stream_reader = StreamReader::new(stream_reader);
 
-            let reader = BatchReader::new(stream_reader, config).await?;
+            let Some(start) = split.start else {
+                let mut reader = BatchReader::new(stream_reader, config).await?;
+
+                if let Some(end) = split.end {
+                    reader = reader.with_end(end);
+                }
+
+                return Ok(reader.into_stream().boxed());
+            };
+
+            // The header is needed to decode the records, so it's read before skipping ahead.
+            let mut header_reader = noodles::bam::AsyncReader::new(stream_reader);
+            let header =
+                read_header_with_limits(header_reader.get_mut(), &config.reader_limits).await?;
+
+            let get_options = GetOptions {
+                range: Some(GetRange::Offset(start.compressed() as usize)),
+                ..Default::default()
+            };
+
+            let stream = config
+                .object_store
+                .get_opts(file_meta.location(), get_options)
+                .await?
+                .into_stream()
+                .map_err(DataFusionError::from);
+
+            // The reader's virtual positions are relative to the start of the requested range.
+            let to_relative = |vp: VirtualPosition| {
+                VirtualPosition::try_from((vp.compressed() - start.compressed(), vp.uncompressed()))
+                    .map_err(|e| DataFusionError::Execution(e.to_string()))
+            };
+
+            let mut bgzf_reader = AsyncBGZFReader::from_reader(StreamReader::new(Box::pin(stream)));
+            bgzf_reader
+                .scan_to_virtual_position(to_relative(start)?)
+                .await?;
+
+            let bam_reader = noodles::bam::AsyncReader::from(bgzf_reader.into_inner());
+            let mut reader = BatchReader::from_reader(bam_reader, Arc::new(header), config);
+
+            if let Some(end) = split.end {
+                reader = reader.with_end(to_relative(end)?);
+            }
 
             Ok(reader.into_stream().boxed())
         }))
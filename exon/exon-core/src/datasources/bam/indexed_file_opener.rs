@@ -18,36 +18,35 @@ use datafusion::{
     datasource::physical_plan::{FileMeta, FileOpenFuture, FileOpener},
     error::DataFusionError,
 };
-use exon_bam::{BAMConfig, IndexedAsyncBatchStream};
+use exon_bam::{read_header_with_limits, BAMConfig, IndexedAsyncBatchStream};
 use futures::{StreamExt, TryStreamExt};
-use noodles::core::Region;
 use object_store::{GetOptions, GetRange};
 use tokio_util::io::StreamReader;
 
 use crate::{
-    datasources::indexed_file::indexed_bgzf_file::BGZFIndexedOffsets,
+    datasources::indexed_file::indexed_bgzf_file::BGZFRegionOffsets,
     streaming_bgzf::AsyncBGZFReader,
 };
 
 /// Implements a datafusion `FileOpener` for BAM files.
+///
+/// Each file is one index chunk of a region-sharded scan, with its byte range and region in a
+/// [`BGZFRegionOffsets`] extension.
 pub struct IndexedBAMOpener {
     /// The base configuration for the file scan.
     config: Arc<BAMConfig>,
-    // An optional region to filter on.
-    region: Arc<Region>,
 }
 
 impl IndexedBAMOpener {
     /// Create a new BAM file opener.
-    pub fn new(config: Arc<BAMConfig>, region: Arc<Region>) -> Self {
-        Self { config, region }
+    pub fn new(config: Arc<BAMConfig>) -> Self {
+        Self { config }
     }
 }
 
 impl FileOpener for IndexedBAMOpener {
     fn open(&self, file_meta: FileMeta) -> datafusion::error::Result<FileOpenFuture> {
         let config = Arc::clone(&self.config);
-        let region = Arc::clone(&self.region);
 
         Ok(Box::pin(async move {
             let get_request = config.object_store.get(file_meta.location()).await?;
@@ -58,11 +57,12 @@ impl FileOpener for IndexedBAMOpener {
 
             let mut first_bam_reader = noodles::bam::AsyncReader::new(stream_reader);
 
-            let header = first_bam_reader.read_header().await?;
+            let header =
+                read_header_with_limits(first_bam_reader.get_mut(), &config.reader_limits).await?;
             let header_offset = first_bam_reader.get_ref().virtual_position();
 
-            let offsets = if let Some(ref ext) = file_meta.extensions {
-                ext.downcast_ref::<BGZFIndexedOffsets>()
+            let shard = if let Some(ref ext) = file_meta.extensions {
+                ext.downcast_ref::<BGZFRegionOffsets>()
                     .ok_or(DataFusionError::Execution(
                         "Missing index offsets for BAM file".to_string(),
                     ))?
@@ -72,8 +72,10 @@ impl FileOpener for IndexedBAMOpener {
                 ));
             };
 
-            let vp_start = offsets.start;
-            let vp_end = offsets.end;
+            tracing::Span::current().record("region", shard.region.to_string());
+
+            let vp_start = shard.offsets.start;
+            let vp_end = shard.offsets.end;
 
             let bgzf_reader = if vp_end.compressed() == 0 {
                 let stream = config
@@ -131,8 +133,16 @@ impl FileOpener for IndexedBAMOpener {
 
             let header = Arc::new(header);
 
-            let mut batch_stream =
-                IndexedAsyncBatchStream::try_new(bam_reader, config, header, region)?;
+            let mut batch_stream = IndexedAsyncBatchStream::try_new(
+                bam_reader,
+                config,
+                header,
+                Arc::clone(&shard.region),
+            )?;
+
+            if let Some(preceding_interval) = shard.preceding_interval {
+                batch_stream.set_preceding_interval(preceding_interval);
+            }
 
             if vp_start.compressed() == vp_end.compressed() {
                 batch_stream.set_max_bytes(vp_end.uncompressed());
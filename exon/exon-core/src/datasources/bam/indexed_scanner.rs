@@ -14,7 +14,8 @@
 
 use std::{any::Any, fmt, sync::Arc};
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::indexed_file_opener::IndexedBAMOpener;
 use arrow::datatypes::SchemaRef;
@@ -41,8 +42,8 @@ pub struct IndexedBAMScan {
     /// Metrics for the execution plan.
     metrics: ExecutionPlanMetricsSet,
 
-    // A region filter for the scan.
-    region: Arc<Region>,
+    /// The regions the scan is sharded over.
+    regions: Vec<Region>,
 
     /// The plan properties cache.
     properties: PlanProperties,
@@ -52,24 +53,34 @@ pub struct IndexedBAMScan {
 }
 
 impl IndexedBAMScan {
-    /// Create a new BAM scan.
-    pub fn new(base_config: FileScanConfig, region: Arc<Region>) -> Self {
+    /// Create a new BAM scan over files sharded by region, see [`IndexedBAMOpener`].
+    pub fn new(base_config: FileScanConfig, regions: Vec<Region>) -> Self {
         let (projected_schema, statistics, properties) = base_config.project_with_properties();
 
         Self {
             base_config,
             projected_schema,
             metrics: ExecutionPlanMetricsSet::new(),
-            region,
+            regions,
             properties,
             statistics,
         }
     }
+
+    /// Return the base configuration for the scan.
+    pub fn base_config(&self) -> &FileScanConfig {
+        &self.base_config
+    }
+
+    /// Return the regions the scan is sharded over.
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
 }
 
 impl DisplayAs for IndexedBAMScan {
     fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter) -> std::fmt::Result {
-        write!(f, "IndexedBAMScan: ")?;
+        write!(f, "IndexedBAMScan: regions={}, ", self.regions.len())?;
         self.base_config.fmt_as(t, f)
     }
 }
@@ -92,7 +103,7 @@ impl ExecutionPlan for IndexedBAMScan {
             return Ok(None);
         }
 
-        let file_groups = self.base_config.regroup_files_by_size(target_partitions);
+        let file_groups = self.base_config.regroup_file_groups(target_partitions);
 
         let mut new_plan = self.clone();
         new_plan.base_config.file_groups = file_groups;
@@ -134,11 +145,17 @@ impl ExecutionPlan for IndexedBAMScan {
 
         let config = BAMConfig::new(object_store, Arc::clone(&self.base_config.file_schema))
             .with_batch_size(batch_size)
-            .with_projection(self.base_config.file_projection());
+            .with_projection(self.base_config.file_projection())
+            .with_reader_limits(reader_limits(context.session_config()));
 
-        let opener = IndexedBAMOpener::new(Arc::new(config), Arc::clone(&self.region));
+        let opener = IndexedBAMOpener::new(Arc::new(config));
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
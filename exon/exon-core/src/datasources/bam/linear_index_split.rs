@@ -0,0 +1,328 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{cmp::Reverse, sync::Arc};
+
+use datafusion::{datasource::listing::PartitionedFile, error::Result};
+use noodles::bgzf::VirtualPosition;
+use object_store::{path::Path, ObjectMeta, ObjectStore};
+
+/// The virtual positions a BAM file can be split at, the offsets in the linear index of its BAI.
+///
+/// Each offset is the start of the first record overlapping a 16kbp window, so splitting at one
+/// never splits a record.
+#[derive(Debug, Clone)]
+pub(crate) struct BAMSplitPoints(Vec<VirtualPosition>);
+
+/// The records of a BAM file a partition reads, from `start` up to but excluding `end`. A missing
+/// start is the first record and a missing end is the end of the file.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct BAMFileSplit {
+    pub(crate) start: Option<VirtualPosition>,
+    pub(crate) end: Option<VirtualPosition>,
+}
+
+/// Read the split points of a BAM file from its BAI, if it has one.
+pub(crate) async fn read_split_points(
+    object_store: &Arc<dyn ObjectStore>,
+    object_meta: &ObjectMeta,
+) -> Result<Option<BAMSplitPoints>> {
+    let index_path = Path::from(format!("{}.bai", object_meta.location));
+
+    let index_bytes = match object_store.get(&index_path).await {
+        Ok(get_result) => get_result.bytes().await?,
+        Err(object_store::Error::NotFound { .. }) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut index_reader = noodles::bam::bai::Reader::new(std::io::Cursor::new(index_bytes));
+    let index = index_reader.read_index()?;
+
+    let mut offsets = index
+        .reference_sequences()
+        .iter()
+        .flat_map(|reference_sequence| reference_sequence.index().iter().copied())
+        .filter(|offset| *offset != VirtualPosition::default())
+        .collect::<Vec<_>>();
+
+    offsets.sort();
+    offsets.dedup();
+
+    if offsets.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(BAMSplitPoints(offsets)))
+}
+
+/// Pick up to `n - 1` of the sorted `candidates` to split a file of `file_size` compressed bytes
+/// into `n` parts of about the same size.
+pub(crate) fn balanced_split_points(
+    candidates: &[VirtualPosition],
+    file_size: usize,
+    n: usize,
+) -> Vec<VirtualPosition> {
+    // Splitting at the first record would leave the first part empty.
+    let candidates = candidates.get(1..).unwrap_or_default();
+
+    let mut split_points: Vec<VirtualPosition> = Vec::new();
+
+    for k in 1..n {
+        let target = (file_size * k / n) as u64;
+
+        let nearest = candidates
+            .iter()
+            .filter(|c| split_points.last().map_or(true, |last| *c > last))
+            .min_by_key(|c| c.compressed().abs_diff(target));
+
+        if let Some(nearest) = nearest {
+            split_points.push(*nearest);
+        }
+    }
+
+    split_points
+}
+
+/// Regroup files with split points into `target_partitions` groups of parts of about the same
+/// compressed size. Each file is split into a number of parts proportional to its size.
+///
+/// Returns `None` if none of the files have split points.
+pub(crate) fn split_file_groups(
+    file_groups: &[Vec<PartitionedFile>],
+    target_partitions: usize,
+) -> Option<Vec<Vec<PartitionedFile>>> {
+    let split_points = |file: &PartitionedFile| {
+        file.extensions
+            .as_ref()
+            .and_then(|e| e.downcast_ref::<BAMSplitPoints>())
+            .cloned()
+    };
+
+    let files = file_groups.iter().flatten().collect::<Vec<_>>();
+
+    if !files.iter().any(|f| split_points(f).is_some()) {
+        return None;
+    }
+
+    let total_size = files
+        .iter()
+        .map(|f| f.object_meta.size)
+        .sum::<usize>()
+        .max(1);
+
+    let mut parts = Vec::new();
+
+    for file in files {
+        let file_size = file.object_meta.size;
+
+        let Some(BAMSplitPoints(candidates)) = split_points(file) else {
+            parts.push((file_size, file.clone()));
+            continue;
+        };
+
+        let n = (target_partitions * file_size).div_ceil(total_size).max(1);
+
+        let mut bounds = vec![None];
+        bounds.extend(
+            balanced_split_points(&candidates, file_size, n)
+                .into_iter()
+                .map(Some),
+        );
+        bounds.push(None);
+
+        for bound in bounds.windows(2) {
+            let (start, end) = (bound[0], bound[1]);
+
+            let start_offset = start.map_or(0, |s| s.compressed() as usize);
+            let end_offset = end.map_or(file_size, |e| e.compressed() as usize);
+
+            let mut part = file.clone();
+            part.extensions = Some(Arc::new(BAMFileSplit { start, end }));
+
+            parts.push((end_offset.saturating_sub(start_offset), part));
+        }
+    }
+
+    // Add the largest parts first, each to the group with the fewest bytes so far.
+    parts.sort_by_key(|(size, _)| Reverse(*size));
+
+    let mut groups = vec![(0, Vec::new()); target_partitions.min(parts.len())];
+
+    for (size, part) in parts {
+        if let Some(group) = groups.iter_mut().min_by_key(|(total, _)| *total) {
+            group.0 += size;
+            group.1.push(part);
+        }
+    }
+
+    Some(groups.into_iter().map(|(_, group)| group).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write as _, num::NonZeroUsize, path::Path, sync::Arc};
+
+    use arrow::{array::AsArray, datatypes::Int64Type};
+    use datafusion::physical_plan::{ExecutionPlan, ExecutionPlanProperties};
+    use noodles::{
+        bam,
+        bgzf::VirtualPosition,
+        core::Position,
+        csi::binning_index::{
+            index::reference_sequence::{bin::Chunk, index::LinearIndex},
+            Indexer,
+        },
+        sam::{
+            self,
+            alignment::{
+                io::Write,
+                record::{
+                    cigar::{op::Kind, Op},
+                    Flags,
+                },
+                record_buf::{Cigar, Sequence},
+                RecordBuf,
+            },
+            header::record::value::{map::ReferenceSequence, Map},
+        },
+    };
+
+    use crate::{config::new_exon_config, datasources::bam::BAMScan, ExonSession};
+
+    use super::balanced_split_points;
+
+    /// Write a BAM file and its BAI with a record every 20kbp, each in its own BGZF block.
+    fn write_bam(path: &Path, n_records: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let header = sam::Header::builder()
+            .add_reference_sequence(
+                "chr1",
+                Map::<ReferenceSequence>::new(NonZeroUsize::try_from(100_000_000)?),
+            )
+            .build();
+
+        let mut writer = bam::io::Writer::new(std::fs::File::create(path)?);
+        writer.write_header(&header)?;
+
+        let mut indexer = Indexer::<LinearIndex>::default();
+
+        for i in 0..n_records {
+            let start = Position::try_from(i * 20_000 + 1)?;
+            let end = Position::try_from(i * 20_000 + 4)?;
+
+            let record = RecordBuf::builder()
+                .set_name(format!("read{}", i))
+                .set_flags(Flags::empty())
+                .set_reference_sequence_id(0)
+                .set_alignment_start(start)
+                .set_cigar([Op::new(Kind::Match, 4)].into_iter().collect::<Cigar>())
+                .set_sequence(Sequence::from(b"ACGT".to_vec()))
+                .build();
+
+            writer.get_mut().flush()?;
+
+            let chunk_start = writer.get_ref().virtual_position();
+            writer.write_alignment_record(&header, &record)?;
+            let chunk_end = writer.get_ref().virtual_position();
+
+            indexer.add_record(
+                Some((0, start, end, true)),
+                Chunk::new(chunk_start, chunk_end),
+            )?;
+        }
+
+        writer.try_finish()?;
+
+        let index = indexer.build(header.reference_sequences().len());
+        bam::bai::write(format!("{}.bai", path.display()), &index)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_balanced_split_points() -> Result<(), Box<dyn std::error::Error>> {
+        let candidates = [
+            (0u64, 10u16),
+            (100, 0),
+            (100, 20),
+            (250, 0),
+            (400, 0),
+            (900, 0),
+        ]
+        .into_iter()
+        .map(VirtualPosition::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+        let split_points = balanced_split_points(&candidates, 1000, 4);
+        assert_eq!(
+            split_points,
+            vec![candidates[3], candidates[4], candidates[5]]
+        );
+
+        // There are fewer candidates than parts, so the candidates are used once each.
+        let split_points = balanced_split_points(&candidates[..3], 1000, 4);
+        assert_eq!(split_points, vec![candidates[1], candidates[2]]);
+
+        assert!(balanced_split_points(&candidates, 1000, 1).is_empty());
+
+        Ok(())
+    }
+
+    /// The number of partitions of the BAM scan in a plan.
+    fn scan_partition_count(plan: &Arc<dyn ExecutionPlan>) -> Option<usize> {
+        if plan.as_any().downcast_ref::<BAMScan>().is_some() {
+            return Some(plan.output_partitioning().partition_count());
+        }
+
+        plan.children().into_iter().find_map(scan_partition_count)
+    }
+
+    #[tokio::test]
+    async fn test_split_bam_scan() -> Result<(), Box<dyn std::error::Error>> {
+        let bam_path = std::env::temp_dir().join("test_split_bam_scan.bam");
+        write_bam(&bam_path, 100)?;
+
+        let ctx = ExonSession::with_config_exon(new_exon_config().with_target_partitions(4))?;
+
+        let df = ctx
+            .sql(&format!(
+                "SELECT COUNT(DISTINCT name) AS n FROM bam_scan('{}')",
+                bam_path.to_str().unwrap()
+            ))
+            .await?;
+
+        let plan = df.create_physical_plan().await?;
+        assert_eq!(scan_partition_count(&plan), Some(4));
+
+        // Every record is read exactly once across the parts.
+        let batches =
+            datafusion::physical_plan::collect(Arc::clone(&plan), ctx.session.task_ctx()).await?;
+        let n = batches[0].column(0).as_primitive::<Int64Type>().value(0);
+        assert_eq!(n, 100);
+
+        let batches = ctx
+            .sql(&format!(
+                "SELECT name FROM bam_scan('{}')",
+                bam_path.to_str().unwrap()
+            ))
+            .await?
+            .collect()
+            .await?;
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 100);
+
+        std::fs::remove_file(&bam_path)?;
+        std::fs::remove_file(format!("{}.bai", bam_path.display()))?;
+
+        Ok(())
+    }
+}
@@ -19,15 +19,21 @@
 mod file_opener;
 mod indexed_file_opener;
 mod indexed_scanner;
+mod linear_index_split;
+mod pileup;
 mod scanner;
+mod umi_dedup;
 
 /// Table provider for BAM files.
 pub mod table_provider;
 
 pub use file_opener::BAMOpener;
 pub use indexed_scanner::IndexedBAMScan;
+pub use pileup::PileupTable;
 pub use scanner::BAMScan;
+pub use umi_dedup::{UmiDedupFunction, UmiDedupTable};
 
 mod udtf;
 pub use udtf::BAMIndexedScanFunction;
+pub use udtf::BAMPileupFunction;
 pub use udtf::BAMScanFunction;
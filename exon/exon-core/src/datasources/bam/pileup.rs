@@ -0,0 +1,87 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::datatypes::SchemaRef;
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::{TableProvider, TableType},
+    error::Result,
+    logical_expr::Expr,
+    physical_plan::{expressions::col, projection::ProjectionExec, ExecutionPlan},
+};
+use noodles::core::Region;
+
+use crate::physical_plan::pileup_exec::{pileup_schema, PileupExec};
+
+/// A table of the per-position allele counts of the alignments in a region of a BAM file.
+#[derive(Debug)]
+pub struct PileupTable {
+    inner: Arc<dyn TableProvider>,
+    region: Region,
+    schema: SchemaRef,
+}
+
+impl PileupTable {
+    /// Create a new pileup over `region` of the alignments in the `inner` table.
+    pub fn new(inner: Arc<dyn TableProvider>, region: Region) -> Self {
+        Self {
+            inner,
+            region,
+            schema: pileup_schema(),
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for PileupTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let input = self.inner.scan(state, None, &[], None).await?;
+        let pileup = Arc::new(PileupExec::try_new(input, self.region.clone())?);
+
+        let Some(projection) = projection else {
+            return Ok(pileup);
+        };
+
+        let exprs = projection
+            .iter()
+            .map(|i| {
+                let name = self.schema.field(*i).name();
+                Ok((col(name, &self.schema)?, name.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(ProjectionExec::try_new(exprs, pileup)?))
+    }
+}
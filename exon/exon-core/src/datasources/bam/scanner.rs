@@ -24,11 +24,13 @@ use datafusion::{
     },
 };
 use exon_bam::BAMConfig;
+use exon_common::RecordSampling;
 use noodles::core::Region;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
-use super::file_opener::BAMOpener;
+use super::{file_opener::BAMOpener, linear_index_split::split_file_groups};
 
 #[derive(Debug, Clone)]
 /// Implements a datafusion `ExecutionPlan` for BAM files.
@@ -50,6 +52,9 @@ pub struct BAMScan {
 
     /// The statistics for the scan.
     statistics: Statistics,
+
+    /// A sample of the records to read, instead of all of them.
+    sampling: Option<RecordSampling>,
 }
 
 impl BAMScan {
@@ -64,9 +69,16 @@ impl BAMScan {
             region_filter: None,
             properties,
             statistics,
+            sampling: None,
         }
     }
 
+    /// Read a sample of the records, which is taken before their columns are built.
+    pub fn with_sampling(mut self, sampling: Option<RecordSampling>) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
     /// Set the region filter for the scan.
     pub fn with_region_filter(mut self, region_filter: Region) -> Self {
         self.region_filter = Some(region_filter);
@@ -106,7 +118,14 @@ impl ExecutionPlan for BAMScan {
             return Ok(None);
         }
 
-        let file_groups = self.base_config.regroup_files_by_size(target_partitions);
+        // Files with a BAI are split into parts at its linear index, otherwise whole files are
+        // grouped. Reservoir samples are taken per file, so those files aren't split.
+        let split_files = match self.sampling {
+            Some(sampling) if sampling.is_per_file() => None,
+            _ => split_file_groups(&self.base_config.file_groups, target_partitions),
+        };
+        let file_groups = split_files
+            .unwrap_or_else(|| self.base_config.regroup_files_by_size(target_partitions));
 
         let mut new_plan = self.clone();
         new_plan.base_config.file_groups = file_groups;
@@ -148,11 +167,18 @@ impl ExecutionPlan for BAMScan {
 
         let config = BAMConfig::new(object_store, Arc::clone(&self.base_config.file_schema))
             .with_batch_size(batch_size)
-            .with_projection(self.base_config.file_projection());
+            .with_projection(self.base_config.file_projection())
+            .with_sampling(self.sampling)
+            .with_reader_limits(reader_limits(context.session_config()));
 
         let opener = BAMOpener::new(Arc::new(config));
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
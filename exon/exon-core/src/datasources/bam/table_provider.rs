@@ -15,17 +15,20 @@
 use std::{any::Any, sync::Arc};
 
 use crate::{
+    config::reader_limits,
     datasources::{
         exon_listing_table_options::{
             ExonIndexedListingOptions, ExonListingConfig, ExonListingOptions,
         },
         hive_partition::filter_matches_partition_cols,
-        indexed_file::indexed_bgzf_file::{
-            augment_partitioned_file_with_byte_range, IndexedBGZFFile,
+        indexed_file::{
+            indexed_bgzf_file::{shard_partitioned_file_by_region, IndexedBGZFFile},
+            region::merge_regions,
         },
     },
     error::Result as ExonResult,
     physical_plan::{infer_region, object_store::pruned_partition_list},
+    planner_events::PLANNER_EVENT_TARGET,
 };
 use arrow::datatypes::{Field, Schema, SchemaRef};
 use async_trait::async_trait;
@@ -40,14 +43,16 @@ use datafusion::{
     physical_plan::{empty::EmptyExec, ExecutionPlan, Statistics},
     prelude::Expr,
 };
-use exon_common::TableSchema;
+use exon_bam::{read_header_with_limits, read_record_with_limits};
+use exon_common::{RecordSampling, TableSchema};
 use exon_sam::SAMSchemaBuilder;
 use futures::{StreamExt, TryStreamExt};
+use itertools::Itertools;
 use noodles::{core::Region, sam::alignment::RecordBuf};
 use object_store::ObjectStore;
 use tokio_util::io::StreamReader;
 
-use super::{indexed_scanner::IndexedBAMScan, BAMScan};
+use super::{indexed_scanner::IndexedBAMScan, linear_index_split::read_split_points, BAMScan};
 
 #[derive(Debug, Clone)]
 /// Listing options for a BAM table
@@ -66,6 +71,18 @@ pub struct ListingBAMTableOptions {
 
     /// Whether to infer the schema from the tags
     tag_as_struct: bool,
+
+    /// Whether to pack the sequences with 2 bits per base
+    pack_sequences: bool,
+
+    /// Whether to add the PacBio tag columns
+    pacbio_columns: bool,
+
+    /// Whether to use the compact flag, mapping quality, and quality score types
+    compact_types: bool,
+
+    /// A sample of the records to read
+    sampling: Option<RecordSampling>,
 }
 
 impl Default for ListingBAMTableOptions {
@@ -75,7 +92,11 @@ impl Default for ListingBAMTableOptions {
             table_partition_cols: Vec::new(),
             indexed: false,
             tag_as_struct: false,
+            pack_sequences: false,
+            pacbio_columns: false,
+            compact_types: false,
             region: Vec::new(),
+            sampling: None,
         }
     }
 }
@@ -98,7 +119,7 @@ impl ExonListingOptions for ListingBAMTableOptions {
         &self,
         conf: FileScanConfig,
     ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
-        let scan = BAMScan::new(conf);
+        let scan = BAMScan::new(conf).with_sampling(self.sampling);
         Ok(Arc::new(scan))
     }
 }
@@ -124,14 +145,7 @@ impl ExonIndexedListingOptions for ListingBAMTableOptions {
             ));
         }
 
-        if regions.len() > 1 {
-            return Err(DataFusionError::Execution(
-                "BAM currently only supports 1 region".to_string(),
-            ));
-        }
-
-        let region = Arc::new(regions[0].clone());
-        let scan = IndexedBAMScan::new(conf, region);
+        let scan = IndexedBAMScan::new(conf, regions);
         Ok(Arc::new(scan))
     }
 }
@@ -160,9 +174,22 @@ impl ListingBAMTableOptions {
         state: &dyn Session,
         table_path: &ListingTableUrl,
     ) -> datafusion::error::Result<TableSchema> {
+        let mut schema_builder = SAMSchemaBuilder::default();
+
+        if self.pack_sequences {
+            schema_builder = schema_builder.with_packed_sequences();
+        }
+
+        if self.pacbio_columns {
+            schema_builder = schema_builder.with_pacbio_fields();
+        }
+
+        if self.compact_types {
+            schema_builder = schema_builder.with_compact_types();
+        }
+
         if !self.tag_as_struct {
-            let builder = SAMSchemaBuilder::default()
-                .with_partition_fields(self.table_partition_cols.clone()); // TODO: get rid of clone
+            let builder = schema_builder.with_partition_fields(self.table_partition_cols.clone()); // TODO: get rid of clone
             let table_schema = builder.build();
 
             return Ok(table_schema);
@@ -179,7 +206,7 @@ impl ListingBAMTableOptions {
         )
         .await;
 
-        let mut schema_builder = SAMSchemaBuilder::default();
+        let limits = reader_limits(state.config());
 
         while let Some(f) = files.next().await {
             let f = f?;
@@ -190,11 +217,17 @@ impl ListingBAMTableOptions {
             let stream_reader = StreamReader::new(stream_reader);
             let mut reader = noodles::bam::AsyncReader::new(stream_reader);
 
-            let header = reader.read_header().await?;
+            let header = read_header_with_limits(reader.get_mut(), &limits).await?;
 
-            let mut record = RecordBuf::default();
+            let mut buf = Vec::new();
+            let mut record = noodles::bam::Record::default();
 
-            reader.read_record_buf(&header, &mut record).await?;
+            if read_record_with_limits(reader.get_mut(), &mut buf, &mut record, &limits).await? == 0
+            {
+                continue;
+            }
+
+            let record = RecordBuf::try_from_alignment_record(&header, &record)?;
 
             let data = record.data();
             schema_builder = schema_builder.with_tags_data_type_from_data(data)?;
@@ -218,6 +251,31 @@ impl ListingBAMTableOptions {
         self.tag_as_struct = tag_as_struct;
         self
     }
+
+    /// Pack the sequences with 2 bits per base, see the `unpack_sequence` UDF
+    pub fn with_pack_sequences(mut self, pack_sequences: bool) -> Self {
+        self.pack_sequences = pack_sequences;
+        self
+    }
+
+    /// Add typed columns for the PacBio `zm`, `np`, `rq`, `ip`, and `pw` tags
+    pub fn with_pacbio_columns(mut self, pacbio_columns: bool) -> Self {
+        self.pacbio_columns = pacbio_columns;
+        self
+    }
+
+    /// Use UInt16 flags, UInt8 mapping qualities, and lists of UInt8 quality scores
+    pub fn with_compact_types(mut self, compact_types: bool) -> Self {
+        self.compact_types = compact_types;
+        self
+    }
+
+    /// Read a sample of the records of each file, by fraction, with reservoir sampling, or with
+    /// reservoir sampling per region
+    pub fn with_sampling(mut self, sampling: Option<RecordSampling>) -> Self {
+        self.sampling = sampling;
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -235,6 +293,44 @@ impl<T: ExonIndexedListingOptions> ListingBAMTable<T> {
             table_schema,
         }
     }
+
+    /// Read the header of the first file in the table, so it can be passed through when the
+    /// table is written as SAM or BAM.
+    pub(crate) async fn read_header(
+        &self,
+        state: &dyn Session,
+    ) -> Result<Option<noodles::sam::Header>> {
+        let Some(table_path) = self.config.first_table_path() else {
+            return Ok(None);
+        };
+
+        let store = state.runtime_env().object_store(table_path)?;
+
+        let mut files = exon_common::object_store_files_from_table_path(
+            &store,
+            table_path.as_ref(),
+            table_path.prefix(),
+            self.config.options.file_extension(),
+            None,
+        )
+        .await;
+
+        let Some(f) = files.next().await else {
+            return Ok(None);
+        };
+
+        let get_result = store.get(&f?.location).await?;
+
+        let stream_reader = Box::pin(get_result.into_stream().map_err(DataFusionError::from));
+        let stream_reader = StreamReader::new(stream_reader);
+        let mut reader = noodles::bam::AsyncReader::new(stream_reader);
+
+        let limits = reader_limits(state.config());
+
+        Ok(Some(
+            read_header_with_limits(reader.get_mut(), &limits).await?,
+        ))
+    }
 }
 
 #[async_trait]
@@ -257,17 +353,19 @@ impl<T: ExonIndexedListingOptions + 'static> TableProvider for ListingBAMTable<T
     ) -> Result<Vec<TableProviderFilterPushDown>> {
         Ok(filters
             .iter()
-            .map(|f| match f {
-                Expr::ScalarFunction(s) if s.name() == "bam_region_filter" => {
-                    if s.args.len() == 2 || s.args.len() == 4 {
-                        tracing::debug!("Pushing down region filter");
-                        TableProviderFilterPushDown::Exact
-                    } else {
-                        tracing::debug!("Unsupported number of arguments for region filter");
-                        filter_matches_partition_cols(f, self.config.options.table_partition_cols())
-                    }
+            .map(|f| {
+                if infer_region::is_region_filter(f, "bam_region_filter") {
+                    tracing::debug!(
+                        target: PLANNER_EVENT_TARGET,
+                        event = "filter_pushdown",
+                        filter = %f,
+                        pushdown = ?TableProviderFilterPushDown::Exact,
+                        reason = "region",
+                    );
+                    TableProviderFilterPushDown::Exact
+                } else {
+                    filter_matches_partition_cols(f, self.config.options.table_partition_cols())
                 }
-                _ => filter_matches_partition_cols(f, self.config.options.table_partition_cols()),
             })
             .collect())
     }
@@ -287,45 +385,35 @@ impl<T: ExonIndexedListingOptions + 'static> TableProvider for ListingBAMTable<T
 
         let object_store = state.runtime_env().object_store(url.object_store())?;
 
-        let regions = filters
+        // Each region filter is a single region or a disjunction of regions.
+        let mut filter_regions = filters
             .iter()
-            .map(|f| {
-                if let Expr::ScalarFunction(s) = f {
-                    let r = infer_region::infer_region_from_udf(s, "bam_region_filter")?;
-                    Ok(r)
-                } else {
-                    Ok(None)
-                }
-            })
+            .map(|f| infer_region::infer_regions_from_expr(f, "bam_region_filter"))
             .collect::<ExonResult<Vec<_>>>()?
             .into_iter()
             .flatten()
             .collect::<Vec<_>>();
 
-        let regions = if self.config.options.indexed() {
-            if regions.len() == 1 {
-                regions
-            } else {
-                self.config.options.regions().to_vec()
-            }
-        } else {
-            regions
-        };
-
-        if regions.is_empty() && self.config.options.indexed() {
+        if filter_regions.len() > 1 {
             return Err(DataFusionError::Plan(
-                "INDEXED_BAM table type requires a region filter. See the 'bam_region_filter' function.".to_string(),
+                "Only one region filter is supported, combine regions with OR".to_string(),
             ));
         }
 
-        if regions.len() > 1 {
+        let regions = match filter_regions.pop() {
+            Some(regions) => regions,
+            None if self.config.options.indexed() => self.config.options.regions().to_vec(),
+            None => Vec::new(),
+        };
+
+        if regions.is_empty() && self.config.options.indexed() {
             return Err(DataFusionError::Plan(
-                "Only one region filter is supported".to_string(),
+                "INDEXED_BAM table type requires a region filter. See the 'bam_region_filter' function.".to_string(),
             ));
         }
 
         if regions.is_empty() {
-            let file_list = pruned_partition_list(
+            let mut file_list = pruned_partition_list(
                 &object_store,
                 url,
                 filters,
@@ -336,6 +424,20 @@ impl<T: ExonIndexedListingOptions + 'static> TableProvider for ListingBAMTable<T
             .try_collect::<Vec<_>>()
             .await?;
 
+            // Indexed files can be split into parts when the scan is repartitioned.
+            let session_config = state.config();
+            if session_config.target_partitions() > 1
+                && session_config.options().optimizer.repartition_file_scans
+            {
+                for file in file_list.iter_mut() {
+                    if let Some(split_points) =
+                        read_split_points(&object_store, &file.object_meta).await?
+                    {
+                        file.extensions = Some(Arc::new(split_points));
+                    }
+                }
+            }
+
             let file_scan_config = FileScanConfig {
                 object_store_url: url.object_store(),
                 file_schema: self.table_schema.file_schema()?,
@@ -363,28 +465,37 @@ impl<T: ExonIndexedListingOptions + 'static> TableProvider for ListingBAMTable<T
             pruned_partition_list(&object_store, url, filters, file_extension, partition_cols)
                 .await?;
 
-        let mut file_partition_with_ranges = Vec::new();
-
-        let region = regions[0].clone();
+        // Plan one file group per reference sequence, holding the index chunks of its regions
+        // across all files, so the scan can run reference sequences in parallel.
+        let regions = merge_regions(&regions);
+        let n_groups = regions.iter().dedup_by(|a, b| a.name() == b.name()).count();
+        let mut file_groups = vec![Vec::new(); n_groups];
 
         while let Some(f) = file_list.next().await {
             let f = f?;
 
-            let file_byte_range = augment_partitioned_file_with_byte_range(
+            let shards = shard_partitioned_file_by_region(
                 Arc::clone(&object_store),
                 &f,
-                &region,
+                &regions,
                 &IndexedBGZFFile::Bam,
             )
             .await?;
 
-            file_partition_with_ranges.extend(file_byte_range);
+            for (group, shard) in file_groups.iter_mut().zip(shards) {
+                group.extend(shard);
+            }
+        }
+
+        file_groups.retain(|g| !g.is_empty());
+        if file_groups.is_empty() {
+            file_groups.push(Vec::new());
         }
 
         let file_scan_config = FileScanConfig {
             object_store_url: url.object_store(),
             file_schema: self.table_schema.file_schema()?,
-            file_groups: vec![file_partition_with_ranges],
+            file_groups,
             statistics: Statistics::new_unknown(self.table_schema.file_schema()?.as_ref()),
             projection: projection.cloned(),
             limit,
@@ -395,7 +506,7 @@ impl<T: ExonIndexedListingOptions + 'static> TableProvider for ListingBAMTable<T
         let table = self
             .config
             .options
-            .create_physical_plan_with_regions(file_scan_config, vec![region])
+            .create_physical_plan_with_regions(file_scan_config, regions)
             .await?;
 
         return Ok(table);
@@ -29,7 +29,10 @@ use datafusion::{
 };
 use exon_common::TableSchema;
 
-use super::table_provider::{ListingBAMTable, ListingBAMTableOptions};
+use super::{
+    pileup::PileupTable,
+    table_provider::{ListingBAMTable, ListingBAMTableOptions},
+};
 
 /// A table function that returns a table provider for a BAM file.
 #[derive(Default)]
@@ -64,7 +67,10 @@ impl TableFunctionImpl for BAMScanFunction {
         let state = self.ctx.state();
         let config = extract_config_from_state(&state)?;
 
-        let options = ListingBAMTableOptions::default().with_tag_as_struct(config.bam_parse_tags);
+        let options = ListingBAMTableOptions::default()
+            .with_tag_as_struct(config.bam_parse_tags)
+            .with_pacbio_columns(config.bam_pacbio_columns)
+            .with_compact_types(config.alignment_compact_types);
 
         let schema = futures::executor::block_on(async {
             let schema = options
@@ -131,7 +137,9 @@ impl TableFunctionImpl for BAMIndexedScanFunction {
 
         let options = ListingBAMTableOptions::default()
             .with_regions(vec![region])
-            .with_tag_as_struct(config.bam_parse_tags);
+            .with_tag_as_struct(config.bam_parse_tags)
+            .with_pacbio_columns(config.bam_pacbio_columns)
+            .with_compact_types(config.alignment_compact_types);
 
         let schema = futures::executor::block_on(async {
             let schema = options
@@ -148,3 +156,66 @@ impl TableFunctionImpl for BAMIndexedScanFunction {
         Ok(Arc::new(listing_table))
     }
 }
+
+/// A table function that returns the per-position allele counts of a region of an indexed BAM
+/// file.
+pub struct BAMPileupFunction {
+    ctx: SessionContext,
+}
+
+impl Debug for BAMPileupFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BAMPileupFunction").finish()
+    }
+}
+
+impl BAMPileupFunction {
+    /// Create a new `BAMPileupFunction`.
+    pub fn new(ctx: SessionContext) -> Self {
+        Self { ctx }
+    }
+}
+
+impl TableFunctionImpl for BAMPileupFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let Some(Expr::Literal(ScalarValue::Utf8(Some(path)))) = exprs.first() else {
+            return Err(DataFusionError::Internal(
+                "this function requires the path to be specified as the first argument".into(),
+            ));
+        };
+
+        let listing_table_url = ListingTableUrl::parse(path)?;
+
+        futures::executor::block_on(async {
+            self.ctx
+                .runtime_env()
+                .exon_register_object_store_url(listing_table_url.as_ref())
+                .await
+        })?;
+
+        let Some(Expr::Literal(ScalarValue::Utf8(Some(region_str)))) = exprs.get(1) else {
+            return Err(DataFusionError::Internal(
+                "this function requires the region to be specified as the second argument".into(),
+            ));
+        };
+
+        let region: noodles::core::Region = region_str.parse().map_err(ExonError::from)?;
+
+        // Tags aren't needed for the pileup, so they're left as a map regardless of the config.
+        let options = ListingBAMTableOptions::default().with_regions(vec![region.clone()]);
+
+        let schema = futures::executor::block_on(async {
+            let schema = options
+                .infer_schema(&self.ctx.state(), &listing_table_url)
+                .await?;
+
+            Ok::<TableSchema, datafusion::error::DataFusionError>(schema)
+        })?;
+
+        let listing_table_config = ExonListingConfig::new_with_options(listing_table_url, options);
+
+        let listing_table = ListingBAMTable::new(listing_table_config, schema);
+
+        Ok(Arc::new(PileupTable::new(Arc::new(listing_table), region)))
+    }
+}
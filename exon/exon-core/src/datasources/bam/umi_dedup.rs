@@ -0,0 +1,160 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, fmt::Debug, sync::Arc};
+
+use arrow::datatypes::SchemaRef;
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::{function::TableFunctionImpl, TableProvider, TableType},
+    error::{DataFusionError, Result},
+    execution::context::SessionContext,
+    logical_expr::{Expr, LogicalPlan},
+    physical_plan::{expressions::col, projection::ProjectionExec, ExecutionPlan},
+    scalar::ScalarValue,
+};
+
+use crate::physical_plan::umi_dedup_exec::{umi_dedup_schema, UmiDedupExec};
+
+/// A table of alignments with their UMI clusters and duplicate flags.
+#[derive(Debug)]
+pub struct UmiDedupTable {
+    input: LogicalPlan,
+    window: i64,
+    schema: SchemaRef,
+}
+
+impl UmiDedupTable {
+    /// Create a new table over the output of `input`, which must have the reference, start, flag,
+    /// and umi columns, grouping alignments whose starts are within `window`.
+    pub fn try_new(input: LogicalPlan, window: i64) -> Result<Self> {
+        let input_schema = input.schema();
+
+        for name in ["reference", "start", "flag", "umi"] {
+            if !input_schema.has_column_with_unqualified_name(name) {
+                return Err(DataFusionError::Plan(format!(
+                    "UMI deduplication requires an alignment table with a {} column",
+                    name
+                )));
+            }
+        }
+
+        let schema = umi_dedup_schema(input_schema.as_arrow());
+
+        Ok(Self {
+            input,
+            window,
+            schema,
+        })
+    }
+}
+
+#[async_trait]
+impl TableProvider for UmiDedupTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let input = state.create_physical_plan(&self.input).await?;
+        let exec = Arc::new(UmiDedupExec::try_new(input, self.window)?);
+
+        let Some(projection) = projection else {
+            return Ok(exec);
+        };
+
+        let exprs = projection
+            .iter()
+            .map(|i| {
+                let name = self.schema.field(*i).name();
+                Ok((col(name, &self.schema)?, name.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(ProjectionExec::try_new(exprs, exec)?))
+    }
+}
+
+/// A table function that marks the PCR duplicates of an alignment table by their UMIs.
+///
+/// The first argument is a table name or a subquery with a umi column, e.g.
+/// `(SELECT *, extract_umi(name) AS umi FROM bam_table)`, and the optional second argument is the
+/// window in bases within which alignment starts are grouped, by default 0.
+pub struct UmiDedupFunction {
+    ctx: SessionContext,
+}
+
+impl Debug for UmiDedupFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UmiDedupFunction").finish()
+    }
+}
+
+impl UmiDedupFunction {
+    /// Create a new `UmiDedupFunction`.
+    pub fn new(ctx: SessionContext) -> Self {
+        Self { ctx }
+    }
+}
+
+impl TableFunctionImpl for UmiDedupFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let window = match exprs.get(1) {
+            Some(Expr::Literal(ScalarValue::Int64(Some(window)))) if *window >= 0 => *window,
+            None => 0,
+            _ => {
+                return Err(DataFusionError::Plan(
+                    "the window must be a non-negative integer literal".to_string(),
+                ))
+            }
+        };
+
+        let table_name = match exprs.first() {
+            Some(Expr::ScalarSubquery(subquery)) => {
+                let input = subquery.subquery.as_ref().clone();
+                return Ok(Arc::new(UmiDedupTable::try_new(input, window)?));
+            }
+            Some(Expr::Literal(ScalarValue::Utf8(Some(table_name)))) => table_name.clone(),
+            Some(Expr::Column(column)) => column.flat_name(),
+            _ => {
+                return Err(DataFusionError::Plan(
+                    "this function requires a table name or subquery as its first argument"
+                        .to_string(),
+                ))
+            }
+        };
+
+        let df = futures::executor::block_on(self.ctx.table(table_name.as_str()))?;
+
+        Ok(Arc::new(UmiDedupTable::try_new(
+            df.into_unoptimized_plan(),
+            window,
+        )?))
+    }
+}
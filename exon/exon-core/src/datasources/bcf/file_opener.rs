@@ -18,7 +18,7 @@ use datafusion::{
     datasource::physical_plan::{FileMeta, FileOpenFuture, FileOpener},
     error::DataFusionError,
 };
-use exon_bcf::{BCFConfig, BatchAdapter, BatchReader};
+use exon_bcf::{query_with_limits, read_header_with_limits, BCFConfig, BatchAdapter, BatchReader};
 use futures::{StreamExt, TryStreamExt};
 use noodles::{bcf, core::Region, csi};
 use object_store::GetResultPayload;
@@ -61,20 +61,19 @@ impl FileOpener for BCFOpener {
             match get_result.payload {
                 GetResultPayload::File(file, path) => match region {
                     Some(region) => {
-                        let mut reader = bcf::io::Reader::new(file);
-                        let header = reader.read_header()?;
+                        let file = BufReader::new(tokio::fs::File::from_std(file));
+                        let mut reader = bcf::AsyncReader::new(file);
 
-                        let index = csi::read(path.with_extension("bcf.csi"))?;
-
-                        let query = reader.query(&header, &index, &region)?;
+                        let limits = &config.reader_limits;
+                        let header = read_header_with_limits(reader.get_mut(), limits).await?;
 
-                        let mut records = Vec::new();
+                        let index = csi::read(path.with_extension("bcf.csi"))?;
 
-                        for result in query {
-                            records.push(result);
-                        }
+                        let records =
+                            query_with_limits(reader.get_mut(), &header, &index, &region, limits)
+                                .await?;
 
-                        let boxed_iter = Box::new(records.into_iter());
+                        let boxed_iter = Box::new(records.into_iter().map(Ok));
 
                         let batch_adapter = BatchAdapter::new(boxed_iter, config, header.into());
                         let batch_stream = futures::stream::iter(batch_adapter);
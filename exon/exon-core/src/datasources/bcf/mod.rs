@@ -26,4 +26,4 @@ pub use self::file_opener::BCFOpener;
 pub use self::scanner::BCFScan;
 
 mod udtf;
-pub use self::udtf::BCFScanFunction;
+pub use self::udtf::{BCFIndexedScanFunction, BCFScanFunction};
@@ -26,7 +26,8 @@ use datafusion::{
 use exon_bcf::BCFConfig;
 use noodles::core::Region;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::BCFOpener;
 
@@ -50,6 +51,9 @@ pub struct BCFScan {
 
     /// The statistics for the scan.
     statistics: Statistics,
+
+    /// The samples to decode the FORMAT values of, or all samples if `None`.
+    samples: Option<Vec<String>>,
 }
 
 impl BCFScan {
@@ -64,9 +68,16 @@ impl BCFScan {
             region_filter: None,
             properties,
             statistics,
+            samples: None,
         }
     }
 
+    /// Only decode the FORMAT values of these samples.
+    pub fn with_samples(mut self, samples: Option<Vec<String>>) -> Self {
+        self.samples = samples;
+        self
+    }
+
     /// Set the region filter for the scan.
     pub fn with_region_filter(mut self, region_filter: Region) -> Self {
         self.region_filter = Some(region_filter);
@@ -131,7 +142,9 @@ impl ExecutionPlan for BCFScan {
 
         let config = BCFConfig::new(object_store, Arc::clone(&self.base_config.file_schema))
             .with_batch_size(batch_size)
-            .with_some_projection(Some(self.base_config.file_projection()));
+            .with_some_projection(Some(self.base_config.file_projection()))
+            .with_samples(self.samples.clone())
+            .with_reader_limits(reader_limits(context.session_config()));
 
         let mut opener = BCFOpener::new(Arc::new(config));
 
@@ -139,7 +152,12 @@ impl ExecutionPlan for BCFScan {
             opener = opener.with_region_filter(region.clone());
         }
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
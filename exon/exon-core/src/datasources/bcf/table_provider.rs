@@ -27,13 +27,16 @@ use datafusion::{
     physical_plan::{empty::EmptyExec, ExecutionPlan},
     prelude::Expr,
 };
+use exon_bcf::read_header_with_limits;
 use exon_common::TableSchema;
+use exon_vcf::SampleSelection;
 use futures::TryStreamExt;
 use noodles::{bcf, core::Region};
 use object_store::ObjectStore;
 use tokio_util::io::StreamReader;
 
 use crate::{
+    config::reader_limits,
     datasources::{
         exon_listing_table_options::{
             ExonIndexedListingOptions, ExonListingConfig, ExonListingOptions,
@@ -57,6 +60,8 @@ pub struct ListingBCFTableOptions {
     regions: Vec<Region>,
 
     table_partition_cols: Vec<Field>,
+
+    samples: Option<Vec<String>>,
 }
 
 impl Default for ListingBCFTableOptions {
@@ -65,6 +70,7 @@ impl Default for ListingBCFTableOptions {
             file_extension: ExonFileType::BCF.get_file_extension(FileCompressionType::UNCOMPRESSED),
             regions: Vec::new(),
             table_partition_cols: Vec::new(),
+            samples: None,
         }
     }
 }
@@ -84,6 +90,11 @@ impl ListingBCFTableOptions {
         }
     }
 
+    /// Only decode the FORMAT values of these samples, in this order
+    pub fn with_samples(self, samples: Option<Vec<String>>) -> Self {
+        Self { samples, ..self }
+    }
+
     /// Infer the schema for the table
     pub async fn infer_schema<'a>(
         &self,
@@ -108,7 +119,13 @@ impl ListingBCFTableOptions {
         let stream_reader = StreamReader::new(stream_reader);
 
         let mut bcf_reader = bcf::AsyncReader::new(stream_reader);
-        let header = bcf_reader.read_header().await?;
+
+        let limits = reader_limits(state.config());
+        let header = read_header_with_limits(bcf_reader.get_mut(), &limits).await?;
+
+        if let Some(samples) = &self.samples {
+            SampleSelection::try_new(&header, samples)?;
+        }
 
         let mut schema_builder = VCFSchemaBuilder::default()
             .with_header(header)
@@ -136,7 +153,7 @@ impl ExonListingOptions for ListingBCFTableOptions {
         &self,
         conf: FileScanConfig,
     ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
-        let scan = BCFScan::new(conf.clone());
+        let scan = BCFScan::new(conf.clone()).with_samples(self.samples.clone());
         Ok(Arc::new(scan))
     }
 }
@@ -168,7 +185,7 @@ impl ExonIndexedListingOptions for ListingBCFTableOptions {
             ));
         }
 
-        let mut scan = BCFScan::new(conf.clone());
+        let mut scan = BCFScan::new(conf.clone()).with_samples(self.samples.clone());
 
         if let Some(region) = region.first() {
             scan = scan.with_region_filter(region.clone());
@@ -14,12 +14,16 @@
 
 use std::sync::Arc;
 
-use crate::datasources::{exon_listing_table_options::ExonListingConfig, ScanFunction};
+use crate::{
+    datasources::{exon_listing_table_options::ExonListingConfig, ScanFunction},
+    error::ExonError,
+};
 use datafusion::{
-    datasource::{function::TableFunctionImpl, TableProvider},
-    error::Result,
+    datasource::{function::TableFunctionImpl, listing::ListingTableUrl, TableProvider},
+    error::{DataFusionError, Result},
     execution::context::SessionContext,
     logical_expr::Expr,
+    scalar::ScalarValue,
 };
 use exon_common::TableSchema;
 
@@ -66,3 +70,57 @@ impl TableFunctionImpl for BCFScanFunction {
         Ok(Arc::new(listing_table))
     }
 }
+
+/// A table function that returns a table provider for a region of an indexed BCF file.
+pub struct BCFIndexedScanFunction {
+    ctx: SessionContext,
+}
+
+impl std::fmt::Debug for BCFIndexedScanFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BCFIndexedScanFunction").finish()
+    }
+}
+
+impl BCFIndexedScanFunction {
+    /// Create a new indexed BCF scan function.
+    pub fn new(ctx: SessionContext) -> Self {
+        Self { ctx }
+    }
+}
+
+impl TableFunctionImpl for BCFIndexedScanFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let Some(Expr::Literal(ScalarValue::Utf8(Some(path)))) = exprs.first() else {
+            return Err(DataFusionError::Internal(
+                "this function requires the path to be specified as the first argument".into(),
+            ));
+        };
+
+        let listing_table_url = ListingTableUrl::parse(path)?;
+
+        let Some(Expr::Literal(ScalarValue::Utf8(Some(region_str)))) = exprs.get(1) else {
+            return Err(DataFusionError::Internal(
+                "this function requires the region to be specified as the second argument".into(),
+            ));
+        };
+
+        let region = region_str.parse().map_err(ExonError::from)?;
+
+        let options = ListingBCFTableOptions::default().with_regions(vec![region]);
+
+        let schema = futures::executor::block_on(async {
+            let schema = options
+                .infer_schema(&self.ctx.state(), &listing_table_url)
+                .await?;
+
+            Ok::<TableSchema, datafusion::error::DataFusionError>(schema)
+        })?;
+
+        let config = ExonListingConfig::new_with_options(listing_table_url, options);
+
+        let listing_table = ListingBCFTable::new(config, schema);
+
+        Ok(Arc::new(listing_table))
+    }
+}
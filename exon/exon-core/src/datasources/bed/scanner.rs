@@ -28,7 +28,8 @@ use datafusion::{
 };
 use exon_bed::BEDConfig;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::BEDOpener;
 
@@ -153,12 +154,18 @@ impl ExecutionPlan for BEDScan {
         let config = BEDConfig::new(object_store, Arc::clone(&self.base_config.file_schema))
             .with_n_fields(self.n_fields)
             .with_batch_size(batch_size)
-            .with_some_projection(Some(self.base_config.file_projection()));
+            .with_some_projection(Some(self.base_config.file_projection()))
+            .with_reader_limits(reader_limits(context.session_config()));
 
         let config = Arc::new(config);
         let opener = BEDOpener::new(config, self.file_compression_type);
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
@@ -12,34 +12,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{any::Any, sync::Arc};
+use std::sync::Arc;
 
-use crate::{
-    datasources::{
-        exon_listing_table_options::{ExonListingConfig, ExonListingOptions},
-        hive_partition::filter_matches_partition_cols,
-        ExonFileType,
-    },
-    physical_plan::{
-        file_scan_config_builder::FileScanConfigBuilder, object_store::pruned_partition_list,
-    },
+use crate::datasources::{
+    exon_listing_table::{ExonFileFormatOptions, ExonListingTable},
+    exon_listing_table_options::ExonListingOptions,
+    ExonFileType,
 };
-use arrow::datatypes::{Field, Schema, SchemaRef};
+use arrow::datatypes::Field;
 use async_trait::async_trait;
 use datafusion::{
     catalog::Session,
     datasource::{
-        file_format::file_compression_type::FileCompressionType, physical_plan::FileScanConfig,
-        TableProvider,
+        file_format::file_compression_type::FileCompressionType, listing::ListingTableUrl,
+        physical_plan::FileScanConfig,
     },
     error::{DataFusionError, Result},
-    logical_expr::{TableProviderFilterPushDown, TableType},
-    physical_plan::{empty::EmptyExec, ExecutionPlan},
-    prelude::Expr,
+    physical_plan::ExecutionPlan,
 };
 use exon_bed::BEDSchemaBuilder;
-use exon_common::TableSchema;
-use futures::TryStreamExt;
+use exon_common::{StrandEncoding, TableSchema};
 
 use super::BEDScan;
 
@@ -57,6 +49,9 @@ pub struct ListingBEDTableOptions {
 
     /// The number of fields in the BED file, assumed to be valid by this point
     n_fields: usize,
+
+    /// How the strand column is encoded
+    strand_encoding: StrandEncoding,
 }
 
 #[async_trait]
@@ -99,6 +94,7 @@ impl ListingBEDTableOptions {
             file_compression_type,
             table_partition_cols: Vec::new(),
             n_fields: 12,
+            strand_encoding: StrandEncoding::default(),
         }
     }
 
@@ -107,6 +103,14 @@ impl ListingBEDTableOptions {
         Self { n_fields, ..self }
     }
 
+    /// Set how the strand column is encoded
+    pub fn with_strand_encoding(self, strand_encoding: StrandEncoding) -> Self {
+        Self {
+            strand_encoding,
+            ..self
+        }
+    }
+
     /// Set the file extension
     pub fn with_file_extension(self, file_extension: String) -> Self {
         Self {
@@ -129,97 +133,23 @@ impl ListingBEDTableOptions {
             DataFusionError::Execution(format!("Error creating BED schema builder: {}", e,))
         })?;
         // let mut schema_builder = BEDSchemaBuilder::default();
+        schema_builder.set_strand_data_type(self.strand_encoding.data_type());
         schema_builder.add_partition_fields(self.table_partition_cols.clone());
 
         Ok(schema_builder.build())
     }
 }
 
-#[derive(Debug, Clone)]
-/// A BED listing table
-pub struct ListingBEDTable<T: ExonListingOptions> {
-    config: ExonListingConfig<T>,
-
-    table_schema: TableSchema,
-}
-
-impl<T: ExonListingOptions> ListingBEDTable<T> {
-    /// Create a new VCF listing table
-    pub fn new(config: ExonListingConfig<T>, table_schema: TableSchema) -> Self {
-        Self {
-            config,
-            table_schema,
-        }
-    }
-}
-
 #[async_trait]
-impl<T: ExonListingOptions + 'static> TableProvider for ListingBEDTable<T> {
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-
-    fn schema(&self) -> SchemaRef {
-        Arc::clone(&self.table_schema.table_schema())
-    }
-
-    fn table_type(&self) -> TableType {
-        TableType::Base
-    }
-
-    fn supports_filters_pushdown(
+impl ExonFileFormatOptions for ListingBEDTableOptions {
+    async fn infer_table_schema(
         &self,
-        filters: &[&Expr],
-    ) -> Result<Vec<TableProviderFilterPushDown>> {
-        Ok(filters
-            .iter()
-            .map(|f| filter_matches_partition_cols(f, self.config.options.table_partition_cols()))
-            .collect())
-    }
-
-    async fn scan(
-        &self,
-        state: &dyn Session,
-        projection: Option<&Vec<usize>>,
-        filters: &[Expr],
-        limit: Option<usize>,
-    ) -> Result<Arc<dyn ExecutionPlan>> {
-        let first_path = if let Some(url) = self.config.inner.table_paths.first() {
-            url
-        } else {
-            return Ok(Arc::new(EmptyExec::new(Arc::new(Schema::empty()))));
-        };
-
-        let object_store = state
-            .runtime_env()
-            .object_store(first_path.object_store())?;
-
-        let file_list = pruned_partition_list(
-            &object_store,
-            first_path,
-            filters,
-            self.config.options.file_extension(),
-            self.config.options.table_partition_cols(),
-        )
-        .await?
-        .try_collect::<Vec<_>>()
-        .await?;
-
-        let file_schema = self.table_schema.file_schema()?;
-
-        let file_scan_config =
-            FileScanConfigBuilder::new(first_path.object_store(), file_schema, vec![file_list])
-                .projection_option(projection.cloned())
-                .limit_option(limit)
-                .table_partition_cols(self.config.options.table_partition_cols().to_vec())
-                .build();
-
-        let plan = self
-            .config
-            .options
-            .create_physical_plan(file_scan_config)
-            .await?;
-
-        Ok(plan)
+        _state: &dyn Session,
+        _table_path: &ListingTableUrl,
+    ) -> Result<TableSchema> {
+        self.infer_schema()
     }
 }
+
+/// A BED listing table
+pub type ListingBEDTable<T = ListingBEDTableOptions> = ExonListingTable<T>;
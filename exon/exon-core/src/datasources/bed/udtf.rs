@@ -15,6 +15,7 @@
 use std::sync::Arc;
 
 use crate::{
+    config::extract_config_from_state,
     datasources::{exon_listing_table_options::ExonListingConfig, ScanFunction},
     ExonRuntimeEnvExt,
 };
@@ -24,7 +25,6 @@ use datafusion::{
     execution::context::SessionContext,
     logical_expr::Expr,
 };
-use exon_bed::BEDSchemaBuilder;
 
 use super::table_provider::{ListingBEDTable, ListingBEDTableOptions};
 
@@ -56,10 +56,14 @@ impl TableFunctionImpl for BEDScanFunction {
                 .await
         })?;
 
-        let schema = BEDSchemaBuilder::default().build();
+        let state = self.ctx.state();
+        let config = extract_config_from_state(&state)?;
 
         let listing_table_options =
-            ListingBEDTableOptions::new(listing_scan_function.file_compression_type);
+            ListingBEDTableOptions::new(listing_scan_function.file_compression_type)
+                .with_strand_encoding(config.strand_encoding()?);
+
+        let schema = listing_table_options.infer_schema()?;
 
         let listing_table_config = ExonListingConfig::new_with_options(
             listing_scan_function.listing_table_url,
@@ -26,7 +26,7 @@ use datafusion::{
 use exon_bigwig::value_batch_reader::BigWigValueConfig;
 use noodles::core::Region;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::FileOpener;
 
@@ -134,7 +134,12 @@ impl ExecutionPlan for Scanner {
 
         let opener = FileOpener::new(Arc::new(config));
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
@@ -40,7 +40,7 @@ use datafusion::{
 use exon_bigwig::zoom_batch_reader::BigWigZoomConfig;
 use noodles::core::Region;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::FileOpener;
 
@@ -153,7 +153,12 @@ impl ExecutionPlan for Scanner {
 
         let opener = FileOpener::new(Arc::new(config));
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
@@ -0,0 +1,240 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, BooleanBuilder, Float64Builder, Int32Builder, Int64Builder, StringBuilder},
+    datatypes::DataType,
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use super::{classification_config::ClassificationConfig, classification_file_kind::Value};
+
+/// Builds a column of a classification file from the parsed values.
+enum ColumnBuilder {
+    Boolean(BooleanBuilder),
+    Int32(Int32Builder),
+    Int64(Int64Builder),
+    Float64(Float64Builder),
+    Utf8(StringBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Boolean => Self::Boolean(BooleanBuilder::new()),
+            DataType::Int32 => Self::Int32(Int32Builder::new()),
+            DataType::Int64 => Self::Int64(Int64Builder::new()),
+            DataType::Float64 => Self::Float64(Float64Builder::new()),
+            _ => Self::Utf8(StringBuilder::new()),
+        }
+    }
+
+    fn append(&mut self, value: &Value) -> Result<(), ArrowError> {
+        match (self, value) {
+            (Self::Boolean(builder), Value::Boolean(value)) => builder.append_value(*value),
+            (Self::Int32(builder), Value::Int32(value)) => builder.append_value(*value),
+            (Self::Int64(builder), Value::Int64(value)) => builder.append_option(*value),
+            (Self::Float64(builder), Value::Float64(value)) => builder.append_value(*value),
+            (Self::Utf8(builder), Value::Utf8(value)) => builder.append_option(*value),
+            (_, value) => {
+                return Err(ArrowError::SchemaError(format!(
+                    "Value {value:?} doesn't match the type of its column"
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::Boolean(builder) => Arc::new(builder.finish()),
+            Self::Int32(builder) => Arc::new(builder.finish()),
+            Self::Int64(builder) => Arc::new(builder.finish()),
+            Self::Float64(builder) => Arc::new(builder.finish()),
+            Self::Utf8(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// Reads the lines of a kraken2 or centrifuge file into record batches.
+pub struct ClassificationBatchReader<R> {
+    /// The underlying reader.
+    reader: R,
+
+    /// The configuration for this reader.
+    config: Arc<ClassificationConfig>,
+}
+
+impl<R> ClassificationBatchReader<R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    /// Create a new batch reader.
+    pub fn new(reader: R, config: Arc<ClassificationConfig>) -> Self {
+        Self { reader, config }
+    }
+
+    pub async fn read_batch(&mut self) -> Result<Option<RecordBatch>, ArrowError> {
+        let kind = self.config.kind;
+
+        let mut builders = self
+            .config
+            .file_schema
+            .fields()
+            .iter()
+            .map(|field| ColumnBuilder::new(field.data_type()))
+            .collect::<Vec<_>>();
+
+        let mut line = String::new();
+        let mut rows = 0;
+
+        while rows < self.config.batch_size {
+            line.clear();
+            if self.reader.read_line(&mut line).await? == 0 {
+                break;
+            }
+
+            let trimmed = line.trim_end_matches(['\n', '\r']);
+            if trimmed.trim().is_empty() || kind.is_header(trimmed) {
+                continue;
+            }
+
+            let values = kind.parse_line(trimmed)?;
+            for (builder, value) in builders.iter_mut().zip(values.iter()) {
+                builder.append(value)?;
+            }
+
+            rows += 1;
+        }
+
+        if rows == 0 {
+            return Ok(None);
+        }
+
+        let columns = builders
+            .iter_mut()
+            .map(|builder| builder.finish())
+            .collect::<Vec<_>>();
+
+        let batch = RecordBatch::try_new(Arc::clone(&self.config.file_schema), columns)?;
+
+        match &self.config.projection {
+            Some(projection) => Ok(Some(batch.project(projection)?)),
+            None => Ok(Some(batch)),
+        }
+    }
+
+    pub fn into_stream(self) -> impl futures::Stream<Item = Result<RecordBatch, ArrowError>> {
+        futures::stream::unfold(self, |mut reader| async move {
+            match reader.read_batch().await {
+                Ok(Some(batch)) => Some((Ok(batch), reader)),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), reader)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{AsArray, Int64Array},
+        datatypes::{Int32Type, Schema},
+    };
+    use object_store::local::LocalFileSystem;
+
+    use crate::datasources::classification::ClassificationFileKind;
+
+    use super::*;
+
+    fn config(kind: ClassificationFileKind) -> Arc<ClassificationConfig> {
+        let file_schema = Arc::new(Schema::new(kind.fields()));
+
+        Arc::new(
+            ClassificationConfig::new(Arc::new(LocalFileSystem::new()), file_schema, kind)
+                .with_batch_size(2),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_read_kraken_report() -> Result<(), ArrowError> {
+        let report = "  5.00\t5\t5\tU\t0\tunclassified\n\
+             95.00\t95\t0\tR\t1\troot\n\
+             95.00\t95\t10\tD\t2\t  Bacteria\n";
+
+        let mut reader = ClassificationBatchReader::new(
+            report.as_bytes(),
+            config(ClassificationFileKind::KrakenReport),
+        );
+
+        let batch = reader.read_batch().await?.unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.column(3).null_count(), 2);
+
+        let batch = reader.read_batch().await?.unwrap();
+        assert_eq!(batch.column(7).as_string::<i32>().value(0), "Bacteria");
+        assert_eq!(batch.column(8).as_primitive::<Int32Type>().value(0), 1);
+
+        assert!(reader.read_batch().await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_centrifuge_output() -> Result<(), ArrowError> {
+        let output =
+            "readID\tseqID\ttaxID\tscore\t2ndBestScore\thitLength\tqueryLength\tnumMatches\n\
+            read1\tNC_000913.3\t562\t4225\t0\t80\t150\t1\n\
+            read2\tunclassified\t0\t0\t0\t0\t150\t1\n";
+
+        let config = Arc::new(
+            ClassificationConfig::new(
+                Arc::new(LocalFileSystem::new()),
+                Arc::new(Schema::new(
+                    ClassificationFileKind::CentrifugeOutput.fields(),
+                )),
+                ClassificationFileKind::CentrifugeOutput,
+            )
+            .with_some_projection(Some(vec![0, 2])),
+        );
+
+        let mut reader = ClassificationBatchReader::new(output.as_bytes(), config);
+
+        let batch = reader.read_batch().await?.unwrap();
+        assert_eq!(batch.num_columns(), 2);
+        assert_eq!(
+            batch.column(1).as_any().downcast_ref::<Int64Array>(),
+            Some(&Int64Array::from(vec![562, 0]))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_invalid_line() {
+        let mut reader = ClassificationBatchReader::new(
+            "C\tread1\n".as_bytes(),
+            config(ClassificationFileKind::KrakenOutput),
+        );
+
+        assert!(reader.read_batch().await.is_err());
+    }
+}
@@ -0,0 +1,356 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt::Display, str::FromStr};
+
+use arrow::{
+    datatypes::{DataType, Field},
+    error::ArrowError,
+};
+
+/// A value of a column parsed from a line of a classification file.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value<'a> {
+    Boolean(bool),
+    Int32(i32),
+    Int64(Option<i64>),
+    Float64(f64),
+    Utf8(Option<&'a str>),
+}
+
+/// A kind of metagenomic classification file, written by kraken2 or centrifuge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClassificationFileKind {
+    /// A kraken2 `--report`, one row per taxon with the reads of its clade. Bracken and
+    /// `centrifuge-kreport` write reports in the same format.
+    KrakenReport,
+
+    /// The per-read output of kraken2, one row per read with the taxon it's classified as.
+    KrakenOutput,
+
+    /// The `--report-file` of centrifuge, one row per taxon with its reads and abundance.
+    CentrifugeReport,
+
+    /// The per-read output of centrifuge, one row per classification of a read.
+    CentrifugeOutput,
+}
+
+fn invalid_line(kind: ClassificationFileKind, message: String) -> ArrowError {
+    ArrowError::ParseError(format!("Invalid {kind} line: {message}"))
+}
+
+impl ClassificationFileKind {
+    /// The default extension of the files of this kind.
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            Self::KrakenReport => "kreport",
+            Self::KrakenOutput => "kraken",
+            Self::CentrifugeReport | Self::CentrifugeOutput => "tsv",
+        }
+    }
+
+    /// The fields of a file of this kind.
+    pub fn fields(&self) -> Vec<Field> {
+        match self {
+            Self::KrakenReport => vec![
+                Field::new("percentage", DataType::Float64, false),
+                Field::new("clade_reads", DataType::Int64, false),
+                Field::new("taxon_reads", DataType::Int64, false),
+                Field::new("minimizers", DataType::Int64, true),
+                Field::new("distinct_minimizers", DataType::Int64, true),
+                Field::new("rank_code", DataType::Utf8, false),
+                Field::new("tax_id", DataType::Int64, false),
+                Field::new("name", DataType::Utf8, false),
+                Field::new("depth", DataType::Int32, false),
+            ],
+            Self::KrakenOutput => vec![
+                Field::new("classified", DataType::Boolean, false),
+                Field::new("read_id", DataType::Utf8, false),
+                Field::new("tax_id", DataType::Int64, false),
+                Field::new("name", DataType::Utf8, true),
+                Field::new("read_length", DataType::Int64, false),
+                Field::new("mate_length", DataType::Int64, true),
+                Field::new("kmer_mappings", DataType::Utf8, true),
+            ],
+            Self::CentrifugeReport => vec![
+                Field::new("name", DataType::Utf8, false),
+                Field::new("tax_id", DataType::Int64, false),
+                Field::new("rank", DataType::Utf8, false),
+                Field::new("genome_size", DataType::Int64, false),
+                Field::new("reads", DataType::Int64, false),
+                Field::new("unique_reads", DataType::Int64, false),
+                Field::new("abundance", DataType::Float64, false),
+            ],
+            Self::CentrifugeOutput => vec![
+                Field::new("read_id", DataType::Utf8, false),
+                Field::new("seq_id", DataType::Utf8, false),
+                Field::new("tax_id", DataType::Int64, false),
+                Field::new("score", DataType::Int64, false),
+                Field::new("second_best_score", DataType::Int64, false),
+                Field::new("hit_length", DataType::Int64, false),
+                Field::new("query_length", DataType::Int64, false),
+                Field::new("num_matches", DataType::Int64, false),
+            ],
+        }
+    }
+
+    /// Whether a line is the header of a file of this kind. Only centrifuge writes headers.
+    pub fn is_header(&self, line: &str) -> bool {
+        match self {
+            Self::CentrifugeReport => line.starts_with("name\ttaxID\t"),
+            Self::CentrifugeOutput => line.starts_with("readID\tseqID\t"),
+            Self::KrakenReport | Self::KrakenOutput => false,
+        }
+    }
+
+    /// Parse a line into the values of the fields of this kind.
+    pub(crate) fn parse_line<'a>(&self, line: &'a str) -> Result<Vec<Value<'a>>, ArrowError> {
+        let fields = line.split('\t').collect::<Vec<_>>();
+
+        let int = |value: &str| {
+            value
+                .trim()
+                .parse::<i64>()
+                .map_err(|_| invalid_line(*self, format!("expected an integer, found {value}")))
+        };
+        let float = |value: &str| {
+            value
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| invalid_line(*self, format!("expected a number, found {value}")))
+        };
+
+        match (self, fields.as_slice()) {
+            (Self::KrakenReport, [percentage, clade_reads, taxon_reads, rest @ ..])
+                if rest.len() == 3 || rest.len() == 5 =>
+            {
+                // With --report-minimizer-data the minimizer counts come before the rank code.
+                let (minimizers, distinct_minimizers, rest) = match rest {
+                    [minimizers, distinct_minimizers, rest @ ..] if rest.len() == 3 => (
+                        Some(int(minimizers)?),
+                        Some(int(distinct_minimizers)?),
+                        rest,
+                    ),
+                    _ => (None, None, rest),
+                };
+
+                let [rank_code, tax_id, name] = rest else {
+                    unreachable!("the report has three columns after its counts");
+                };
+
+                // The names are indented by two spaces for each level below the root.
+                let trimmed_name = name.trim_start_matches(' ');
+                let depth = (name.len() - trimmed_name.len()) / 2;
+
+                Ok(vec![
+                    Value::Float64(float(percentage)?),
+                    Value::Int64(Some(int(clade_reads)?)),
+                    Value::Int64(Some(int(taxon_reads)?)),
+                    Value::Int64(minimizers),
+                    Value::Int64(distinct_minimizers),
+                    Value::Utf8(Some(rank_code.trim())),
+                    Value::Int64(Some(int(tax_id)?)),
+                    Value::Utf8(Some(trimmed_name)),
+                    Value::Int32(depth as i32),
+                ])
+            }
+            (Self::KrakenOutput, [status, read_id, taxon, length, rest @ ..])
+                if rest.len() <= 1 =>
+            {
+                let classified = match *status {
+                    "C" => true,
+                    "U" => false,
+                    _ => {
+                        return Err(invalid_line(
+                            *self,
+                            format!("expected C or U, found {status}"),
+                        ))
+                    }
+                };
+
+                // With --use-names the taxon is e.g. `Escherichia coli (taxid 562)`.
+                let (name, tax_id) = match taxon
+                    .strip_suffix(')')
+                    .and_then(|taxon| taxon.rsplit_once(" (taxid "))
+                {
+                    Some((name, tax_id)) => (Some(name), tax_id),
+                    None => (None, *taxon),
+                };
+
+                // Paired reads have the lengths of both mates, e.g. `150|148`.
+                let (read_length, mate_length) = match length.split_once('|') {
+                    Some((read_length, mate_length)) => (read_length, Some(mate_length)),
+                    None => (*length, None),
+                };
+
+                Ok(vec![
+                    Value::Boolean(classified),
+                    Value::Utf8(Some(read_id)),
+                    Value::Int64(Some(int(tax_id)?)),
+                    Value::Utf8(name),
+                    Value::Int64(Some(int(read_length)?)),
+                    Value::Int64(mate_length.map(int).transpose()?),
+                    Value::Utf8(rest.first().copied()),
+                ])
+            }
+            (
+                Self::CentrifugeReport,
+                [name, tax_id, rank, genome_size, reads, unique_reads, abundance],
+            ) => Ok(vec![
+                Value::Utf8(Some(name)),
+                Value::Int64(Some(int(tax_id)?)),
+                Value::Utf8(Some(rank)),
+                Value::Int64(Some(int(genome_size)?)),
+                Value::Int64(Some(int(reads)?)),
+                Value::Int64(Some(int(unique_reads)?)),
+                Value::Float64(float(abundance)?),
+            ]),
+            (Self::CentrifugeOutput, [read_id, seq_id, tax_id, numbers @ ..])
+                if numbers.len() == 5 =>
+            {
+                let mut values = vec![
+                    Value::Utf8(Some(read_id)),
+                    Value::Utf8(Some(seq_id)),
+                    Value::Int64(Some(int(tax_id)?)),
+                ];
+
+                for number in numbers {
+                    values.push(Value::Int64(Some(int(number)?)));
+                }
+
+                Ok(values)
+            }
+            _ => Err(invalid_line(
+                *self,
+                format!(
+                    "expected {} tab separated columns, found {}",
+                    self.column_counts(),
+                    fields.len()
+                ),
+            )),
+        }
+    }
+
+    /// The number of columns of the lines of this kind, for errors.
+    fn column_counts(&self) -> &'static str {
+        match self {
+            Self::KrakenReport => "6 or 8",
+            Self::KrakenOutput => "4 or 5",
+            Self::CentrifugeReport => "7",
+            Self::CentrifugeOutput => "8",
+        }
+    }
+}
+
+impl Display for ClassificationFileKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::KrakenReport => write!(f, "kraken_report"),
+            Self::KrakenOutput => write!(f, "kraken_output"),
+            Self::CentrifugeReport => write!(f, "centrifuge_report"),
+            Self::CentrifugeOutput => write!(f, "centrifuge_output"),
+        }
+    }
+}
+
+impl FromStr for ClassificationFileKind {
+    type Err = ArrowError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "kraken_report" => Ok(Self::KrakenReport),
+            "kraken_output" => Ok(Self::KrakenOutput),
+            "centrifuge_report" => Ok(Self::CentrifugeReport),
+            "centrifuge_output" => Ok(Self::CentrifugeOutput),
+            _ => Err(ArrowError::InvalidArgumentError(format!(
+                "Unknown classification file kind {s}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClassificationFileKind, Value};
+
+    #[test]
+    fn test_parse_kraken_report() -> Result<(), Box<dyn std::error::Error>> {
+        let kind = ClassificationFileKind::KrakenReport;
+
+        let values = kind.parse_line("  1.50\t30\t12\tS\t562\t              Escherichia coli")?;
+        assert_eq!(values[0], Value::Float64(1.5));
+        assert_eq!(values[3], Value::Int64(None));
+        assert_eq!(values[5], Value::Utf8(Some("S")));
+        assert_eq!(values[7], Value::Utf8(Some("Escherichia coli")));
+        assert_eq!(values[8], Value::Int32(7));
+
+        let values =
+            kind.parse_line(" 10.00\t200\t0\t1500\t900\tR1\t131567\t  cellular organisms")?;
+        assert_eq!(values[3], Value::Int64(Some(1500)));
+        assert_eq!(values[4], Value::Int64(Some(900)));
+        assert_eq!(values[6], Value::Int64(Some(131567)));
+        assert_eq!(values[8], Value::Int32(1));
+
+        assert!(kind
+            .parse_line("1.50\t30\tS\t562\tEscherichia coli")
+            .is_err());
+        assert!(kind
+            .parse_line("1.50\tthirty\t12\tS\t562\tEscherichia coli")
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_kraken_output() -> Result<(), Box<dyn std::error::Error>> {
+        let kind = ClassificationFileKind::KrakenOutput;
+
+        let values = kind.parse_line("C\tread1\t562\t150\t562:13 561:4 0:99")?;
+        assert_eq!(values[0], Value::Boolean(true));
+        assert_eq!(values[2], Value::Int64(Some(562)));
+        assert_eq!(values[3], Value::Utf8(None));
+        assert_eq!(values[5], Value::Int64(None));
+        assert_eq!(values[6], Value::Utf8(Some("562:13 561:4 0:99")));
+
+        let values =
+            kind.parse_line("C\tread2\tEscherichia coli (taxid 562)\t150|148\t562:13 |:| 561:4")?;
+        assert_eq!(values[2], Value::Int64(Some(562)));
+        assert_eq!(values[3], Value::Utf8(Some("Escherichia coli")));
+        assert_eq!(values[4], Value::Int64(Some(150)));
+        assert_eq!(values[5], Value::Int64(Some(148)));
+
+        assert!(kind.parse_line("X\tread3\t0\t150\t0:116").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_centrifuge() -> Result<(), Box<dyn std::error::Error>> {
+        let kind = ClassificationFileKind::CentrifugeOutput;
+        assert!(kind.is_header(
+            "readID\tseqID\ttaxID\tscore\t2ndBestScore\thitLength\tqueryLength\tnumMatches"
+        ));
+
+        let values = kind.parse_line("read1\tNC_000913.3\t562\t4225\t0\t80\t150\t1")?;
+        assert_eq!(values.len(), 8);
+        assert_eq!(values[1], Value::Utf8(Some("NC_000913.3")));
+        assert_eq!(values[3], Value::Int64(Some(4225)));
+
+        let kind = ClassificationFileKind::CentrifugeReport;
+        let values = kind.parse_line("Escherichia coli\t562\tspecies\t4641652\t10\t8\t0.75")?;
+        assert_eq!(values[6], Value::Float64(0.75));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A datafusion compatible datasource for the metagenomic classification files of kraken2 and
+//! centrifuge, i.e. their per-taxon reports and per-read outputs.
+//!
+//! Each kind of file has a `tax_id` column, so the results can be summarized with the taxonomy
+//! UDFs, e.g. `lineage(tax_id)`.
+
+mod classification_batch_reader;
+mod classification_config;
+mod classification_file_kind;
+mod classification_opener;
+mod classification_scanner;
+
+/// Table provider for kraken2 and centrifuge files.
+pub mod table_provider;
+
+pub use self::classification_config::ClassificationConfig;
+pub use self::classification_file_kind::ClassificationFileKind;
+pub use self::classification_opener::ClassificationOpener;
+pub use self::classification_scanner::ClassificationScan;
+
+mod udtf;
+pub use self::udtf::ClassificationScanFunction;
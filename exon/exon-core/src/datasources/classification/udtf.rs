@@ -0,0 +1,60 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use super::{
+    table_provider::{ListingClassificationTable, ListingClassificationTableOptions},
+    ClassificationFileKind,
+};
+use crate::datasources::{exon_listing_table_options::ExonListingConfig, ScanFunction};
+use datafusion::{
+    datasource::{function::TableFunctionImpl, TableProvider},
+    error::Result,
+    logical_expr::Expr,
+};
+
+/// A table function that scans one kind of kraken2 or centrifuge file.
+#[derive(Debug)]
+pub struct ClassificationScanFunction {
+    kind: ClassificationFileKind,
+}
+
+impl ClassificationScanFunction {
+    /// Create a new scan function for a kind of classification file.
+    pub fn new(kind: ClassificationFileKind) -> Self {
+        Self { kind }
+    }
+}
+
+impl TableFunctionImpl for ClassificationScanFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let listing_scan_function = ScanFunction::try_from(exprs)?;
+
+        let listing_table_options = ListingClassificationTableOptions::new(
+            self.kind,
+            listing_scan_function.file_compression_type,
+        );
+        let schema = listing_table_options.infer_schema();
+
+        let listing_table_config = ExonListingConfig::new_with_options(
+            listing_scan_function.listing_table_url,
+            listing_table_options,
+        );
+
+        let listing_table = ListingClassificationTable::new(listing_table_config, schema);
+
+        Ok(Arc::new(listing_table))
+    }
+}
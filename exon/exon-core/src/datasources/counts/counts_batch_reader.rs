@@ -0,0 +1,436 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, Float64Builder, Int64Builder, StringBuilder},
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use super::{
+    counts_config::CountsConfig,
+    counts_schema_builder::{detect_format, split_fields},
+    CountsFormat, CountsLayout, CountsSchemaBuilder,
+};
+
+/// The prefix of the counters HTSeq-count writes after the genes, e.g. `__no_feature`.
+const HTSEQ_COUNTER_PREFIX: &str = "__";
+
+fn invalid_counts(message: String) -> ArrowError {
+    ArrowError::ParseError(format!("Invalid counts file: {message}"))
+}
+
+/// Builds the columns of a batch in the layout of the table.
+struct CountsArrayBuilder {
+    layout: CountsLayout,
+    gene_ids: StringBuilder,
+    /// The sample of each row of the long layout.
+    samples: StringBuilder,
+    /// The chr, start, end, and strand columns of featureCounts in the wide layout.
+    metadata: Vec<StringBuilder>,
+    lengths: Int64Builder,
+    /// The count column of the long layout, or a column per sample of the wide layout.
+    counts: Vec<Float64Builder>,
+}
+
+impl CountsArrayBuilder {
+    fn new(layout: CountsLayout, format: CountsFormat, samples: usize) -> Self {
+        let (metadata, counts) = match (layout, format) {
+            (CountsLayout::Long, _) => (0, 1),
+            (CountsLayout::Wide, CountsFormat::FeatureCounts) => (4, samples),
+            (CountsLayout::Wide, CountsFormat::HTSeq | CountsFormat::Matrix) => (0, samples),
+        };
+
+        Self {
+            layout,
+            gene_ids: StringBuilder::new(),
+            samples: StringBuilder::new(),
+            metadata: (0..metadata).map(|_| StringBuilder::new()).collect(),
+            lengths: Int64Builder::new(),
+            counts: (0..counts).map(|_| Float64Builder::new()).collect(),
+        }
+    }
+
+    /// Append the counts of a gene, returning the number of rows appended.
+    fn append(
+        &mut self,
+        gene_id: &str,
+        metadata: &[&str],
+        length: Option<i64>,
+        samples: &[String],
+        counts: &[f64],
+    ) -> usize {
+        match self.layout {
+            CountsLayout::Long => {
+                for (sample, count) in samples.iter().zip(counts) {
+                    self.gene_ids.append_value(gene_id);
+                    self.samples.append_value(sample);
+                    self.counts[0].append_value(*count);
+                    self.lengths.append_option(length);
+                }
+
+                samples.len()
+            }
+            CountsLayout::Wide => {
+                self.gene_ids.append_value(gene_id);
+
+                if !self.metadata.is_empty() {
+                    for (builder, value) in self.metadata.iter_mut().zip(metadata) {
+                        builder.append_value(value);
+                    }
+                    self.lengths.append_option(length);
+                }
+
+                for (builder, count) in self.counts.iter_mut().zip(counts) {
+                    builder.append_value(*count);
+                }
+
+                1
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(self.gene_ids.finish())];
+
+        match self.layout {
+            CountsLayout::Long => {
+                columns.push(Arc::new(self.samples.finish()));
+                columns.push(Arc::new(self.counts[0].finish()));
+                columns.push(Arc::new(self.lengths.finish()));
+            }
+            CountsLayout::Wide => {
+                if !self.metadata.is_empty() {
+                    for builder in self.metadata.iter_mut() {
+                        columns.push(Arc::new(builder.finish()));
+                    }
+                    columns.push(Arc::new(self.lengths.finish()));
+                }
+
+                for builder in self.counts.iter_mut() {
+                    columns.push(Arc::new(builder.finish()));
+                }
+            }
+        }
+
+        columns
+    }
+}
+
+/// Reads a featureCounts, HTSeq-count, or counts matrix file into record batches, melting the
+/// sample columns into rows for the long layout.
+pub struct CountsBatchReader<R> {
+    /// The underlying reader.
+    reader: R,
+
+    /// The configuration for this reader.
+    config: Arc<CountsConfig>,
+
+    /// The format of the file.
+    format: CountsFormat,
+
+    /// The samples of the count columns.
+    samples: Vec<String>,
+
+    /// The first line of an HTSeq file, which is read to detect the format.
+    first_line: Option<String>,
+}
+
+impl<R> CountsBatchReader<R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    /// Create a batch reader, detecting the format of the file from its first line. The sample of
+    /// an HTSeq file is its file name up to the first `.`.
+    pub async fn try_new(
+        mut reader: R,
+        config: Arc<CountsConfig>,
+        file_name: &str,
+    ) -> Result<Self, ArrowError> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).await? == 0 {
+                break;
+            }
+
+            if !line.starts_with('#') && !line.trim().is_empty() {
+                break;
+            }
+        }
+
+        let (format, mut samples) = detect_format(&line);
+
+        let first_line = if format == CountsFormat::HTSeq {
+            let sample = file_name.split('.').next().unwrap_or(file_name);
+            samples = vec![sample.to_string()];
+
+            Some(line)
+        } else {
+            None
+        };
+
+        if config.layout == CountsLayout::Wide {
+            let schema = CountsSchemaBuilder::wide(format, &samples)
+                .and_then(|builder| builder.build().file_schema())
+                .map_err(|e| ArrowError::ExternalError(Box::new(e)))?;
+
+            let columns = schema.fields().iter().map(|f| f.name()).collect::<Vec<_>>();
+            let expected_columns = config
+                .file_schema
+                .fields()
+                .iter()
+                .map(|f| f.name())
+                .collect::<Vec<_>>();
+
+            if columns != expected_columns {
+                return Err(ArrowError::SchemaError(format!(
+                    "Counts columns {columns:?} don't match the table columns {expected_columns:?}"
+                )));
+            }
+        }
+
+        Ok(Self {
+            reader,
+            config,
+            format,
+            samples,
+            first_line,
+        })
+    }
+
+    pub async fn read_batch(&mut self) -> Result<Option<RecordBatch>, ArrowError> {
+        let mut builder =
+            CountsArrayBuilder::new(self.config.layout, self.format, self.samples.len());
+
+        let metadata_columns = match self.format {
+            CountsFormat::FeatureCounts => 6,
+            CountsFormat::HTSeq | CountsFormat::Matrix => 1,
+        };
+
+        let mut line = String::new();
+        let mut counts = Vec::with_capacity(self.samples.len());
+        let mut rows = 0;
+
+        while rows < self.config.batch_size {
+            line.clear();
+            match self.first_line.take() {
+                Some(first_line) => line = first_line,
+                None => {
+                    if self.reader.read_line(&mut line).await? == 0 {
+                        break;
+                    }
+                }
+            }
+
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields = split_fields(&line);
+            if fields.len() != metadata_columns + self.samples.len() {
+                return Err(invalid_counts(format!(
+                    "expected {} columns, found {} in {}",
+                    metadata_columns + self.samples.len(),
+                    fields.len(),
+                    line.trim_end()
+                )));
+            }
+
+            let gene_id = fields[0];
+            if self.format == CountsFormat::HTSeq && gene_id.starts_with(HTSEQ_COUNTER_PREFIX) {
+                continue;
+            }
+
+            let length = match self.format {
+                CountsFormat::FeatureCounts => {
+                    Some(fields[5].trim().parse::<i64>().map_err(|_| {
+                        invalid_counts(format!("invalid length {} of {gene_id}", fields[5]))
+                    })?)
+                }
+                CountsFormat::HTSeq | CountsFormat::Matrix => None,
+            };
+
+            counts.clear();
+            for count in &fields[metadata_columns..] {
+                counts.push(
+                    count.trim().parse::<f64>().map_err(|_| {
+                        invalid_counts(format!("invalid count {count} of {gene_id}"))
+                    })?,
+                );
+            }
+
+            rows += builder.append(
+                gene_id,
+                &fields[1..metadata_columns],
+                length,
+                &self.samples,
+                &counts,
+            );
+        }
+
+        if rows == 0 {
+            return Ok(None);
+        }
+
+        let batch = RecordBatch::try_new(Arc::clone(&self.config.file_schema), builder.finish())?;
+
+        match &self.config.projection {
+            Some(projection) => Ok(Some(batch.project(projection)?)),
+            None => Ok(Some(batch)),
+        }
+    }
+
+    pub fn into_stream(self) -> impl futures::Stream<Item = Result<RecordBatch, ArrowError>> {
+        futures::stream::unfold(self, |mut reader| async move {
+            match reader.read_batch().await {
+                Ok(Some(batch)) => Some((Ok(batch), reader)),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), reader)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{AsArray, Float64Array},
+        datatypes::Int64Type,
+    };
+    use object_store::local::LocalFileSystem;
+
+    use super::*;
+
+    const FEATURE_COUNTS: &str = "# Program:featureCounts v2.0.1; Command:\"featureCounts\"\n\
+        Geneid\tChr\tStart\tEnd\tStrand\tLength\t/data/S1.bam\t/data/S2.bam\n\
+        g1\tchr1;chr1\t11869;12010\t12227;12057\t+;+\t1735\t10\t0\n\
+        g2\tchr1\t14404\t29570\t-\t1351\t3\t7\n";
+
+    fn config(layout: CountsLayout, samples: &[String]) -> Arc<CountsConfig> {
+        let builder = match layout {
+            CountsLayout::Long => CountsSchemaBuilder::long(),
+            CountsLayout::Wide => {
+                CountsSchemaBuilder::wide(CountsFormat::FeatureCounts, samples).unwrap()
+            }
+        };
+
+        Arc::new(CountsConfig::new(
+            Arc::new(LocalFileSystem::new()),
+            builder.build().file_schema().unwrap(),
+            layout,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_read_long() -> Result<(), ArrowError> {
+        let mut reader = CountsBatchReader::try_new(
+            FEATURE_COUNTS.as_bytes(),
+            config(CountsLayout::Long, &[]),
+            "counts.txt",
+        )
+        .await?;
+
+        let batch = reader.read_batch().await?.unwrap();
+        assert_eq!(batch.num_rows(), 4);
+
+        let samples = batch.column(1).as_string::<i32>();
+        assert_eq!(
+            samples.iter().flatten().collect::<Vec<_>>(),
+            vec!["S1", "S2", "S1", "S2"]
+        );
+        assert_eq!(
+            batch.column(2).as_any().downcast_ref::<Float64Array>(),
+            Some(&Float64Array::from(vec![10.0, 0.0, 3.0, 7.0]))
+        );
+        assert_eq!(batch.column(3).as_primitive::<Int64Type>().value(2), 1351);
+
+        assert!(reader.read_batch().await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_wide() -> Result<(), ArrowError> {
+        let samples = vec!["S1".to_string(), "S2".to_string()];
+
+        let mut reader = CountsBatchReader::try_new(
+            FEATURE_COUNTS.as_bytes(),
+            config(CountsLayout::Wide, &samples),
+            "counts.txt",
+        )
+        .await?;
+
+        let batch = reader.read_batch().await?.unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.num_columns(), 8);
+        assert_eq!(batch.column(1).as_string::<i32>().value(0), "chr1;chr1");
+        assert_eq!(
+            batch.column(7).as_any().downcast_ref::<Float64Array>(),
+            Some(&Float64Array::from(vec![0.0, 7.0]))
+        );
+
+        let other_samples = vec!["S1".to_string(), "S3".to_string()];
+        let result = CountsBatchReader::try_new(
+            FEATURE_COUNTS.as_bytes(),
+            config(CountsLayout::Wide, &other_samples),
+            "counts.txt",
+        )
+        .await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_htseq() -> Result<(), ArrowError> {
+        let htseq = "g1\t10\ng2\t3\n__no_feature\t120\n__ambiguous\t4\n";
+
+        let mut reader = CountsBatchReader::try_new(
+            htseq.as_bytes(),
+            config(CountsLayout::Long, &[]),
+            "S1.htseq.txt",
+        )
+        .await?;
+
+        let batch = reader.read_batch().await?.unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.column(0).as_string::<i32>().value(0), "g1");
+        assert_eq!(batch.column(1).as_string::<i32>().value(1), "S1");
+        assert_eq!(batch.column(3).null_count(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_count() -> Result<(), ArrowError> {
+        let matrix = "gene\tS1\tS2\ng1\t10\tNA\n";
+
+        let mut reader = CountsBatchReader::try_new(
+            matrix.as_bytes(),
+            config(CountsLayout::Long, &[]),
+            "matrix.txt",
+        )
+        .await?;
+
+        assert!(reader.read_batch().await.is_err());
+
+        Ok(())
+    }
+}
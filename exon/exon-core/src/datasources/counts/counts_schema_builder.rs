@@ -0,0 +1,223 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{str::FromStr, sync::Arc};
+
+use arrow::datatypes::{DataType, Field, Schema};
+use datafusion::error::{DataFusionError, Result};
+use exon_common::TableSchema;
+
+/// The leading columns of a featureCounts table, before the column of each sample.
+const FEATURE_COUNTS_COLUMNS: [&str; 6] = ["Geneid", "Chr", "Start", "End", "Strand", "Length"];
+
+/// The extensions of the alignment files that featureCounts names its sample columns after.
+const ALIGNMENT_EXTENSIONS: [&str; 3] = [".bam", ".sam", ".cram"];
+
+/// How the counts of a counts table are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CountsLayout {
+    /// One row per gene and sample, i.e. the matrix melted into `(gene_id, sample, count)`.
+    #[default]
+    Long,
+    /// One row per gene with a column per sample, as in the file.
+    Wide,
+}
+
+impl FromStr for CountsLayout {
+    type Err = DataFusionError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "long" => Ok(Self::Long),
+            "wide" => Ok(Self::Wide),
+            _ => Err(DataFusionError::Configuration(format!(
+                "invalid counts layout: {s}, expected long or wide"
+            ))),
+        }
+    }
+}
+
+/// The format of a counts file, detected from its first line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountsFormat {
+    /// featureCounts output, with gene metadata columns before the samples.
+    FeatureCounts,
+    /// HTSeq-count output, the counts of one sample without a header.
+    HTSeq,
+    /// A matrix with a header of the gene id column and a column per sample.
+    Matrix,
+}
+
+/// Split a line of a counts file into its tab separated fields.
+pub(crate) fn split_fields(line: &str) -> Vec<&str> {
+    line.trim_end_matches(['\r', '\n']).split('\t').collect()
+}
+
+/// The sample of a column, which for featureCounts is the path of its alignment file, e.g. `S1`
+/// for `/data/S1.sorted.bam`.
+pub(crate) fn sample_name(column: &str) -> &str {
+    let file_name = column.rsplit('/').next().unwrap_or(column);
+
+    if ALIGNMENT_EXTENSIONS
+        .iter()
+        .any(|extension| file_name.to_lowercase().ends_with(extension))
+    {
+        file_name.split('.').next().unwrap_or(file_name)
+    } else {
+        file_name
+    }
+}
+
+/// Detect the format of a counts file from its first line after any `#` comments, returning the
+/// format and the samples of its columns.
+///
+/// HTSeq files have no header, so their first line is a count and they have no sample columns.
+pub(crate) fn detect_format(line: &str) -> (CountsFormat, Vec<String>) {
+    let fields = split_fields(line);
+
+    if fields.starts_with(&FEATURE_COUNTS_COLUMNS) {
+        let samples = fields[FEATURE_COUNTS_COLUMNS.len()..]
+            .iter()
+            .map(|column| sample_name(column).to_string())
+            .collect();
+
+        return (CountsFormat::FeatureCounts, samples);
+    }
+
+    match fields.as_slice() {
+        [_, count] if count.trim().parse::<f64>().is_ok() => (CountsFormat::HTSeq, vec![]),
+        [_, samples @ ..] => (
+            CountsFormat::Matrix,
+            samples
+                .iter()
+                .map(|column| sample_name(column).to_string())
+                .collect(),
+        ),
+        [] => (CountsFormat::Matrix, vec![]),
+    }
+}
+
+/// Builds the schema of a counts table.
+pub struct CountsSchemaBuilder {
+    file_fields: Vec<Field>,
+    partition_fields: Vec<Field>,
+}
+
+impl CountsSchemaBuilder {
+    /// The schema of the long layout, where the length is the gene length of featureCounts.
+    pub fn long() -> Self {
+        let file_fields = vec![
+            Field::new("gene_id", DataType::Utf8, false),
+            Field::new("sample", DataType::Utf8, false),
+            Field::new("count", DataType::Float64, false),
+            Field::new("length", DataType::Int64, true),
+        ];
+
+        Self {
+            file_fields,
+            partition_fields: vec![],
+        }
+    }
+
+    /// The schema of the wide layout of a file of a format with the samples of its header.
+    pub fn wide(format: CountsFormat, samples: &[String]) -> Result<Self> {
+        let mut file_fields = vec![Field::new("gene_id", DataType::Utf8, false)];
+
+        match format {
+            CountsFormat::FeatureCounts => file_fields.extend([
+                Field::new("chr", DataType::Utf8, false),
+                Field::new("start", DataType::Utf8, false),
+                Field::new("end", DataType::Utf8, false),
+                Field::new("strand", DataType::Utf8, false),
+                Field::new("length", DataType::Int64, false),
+            ]),
+            CountsFormat::HTSeq => {
+                return Err(DataFusionError::Configuration(
+                    "HTSeq counts have one sample per file, so can't be read with the wide counts layout".to_string(),
+                ))
+            }
+            CountsFormat::Matrix => {}
+        }
+
+        file_fields.extend(
+            samples
+                .iter()
+                .map(|sample| Field::new(sample, DataType::Float64, true)),
+        );
+
+        Ok(Self {
+            file_fields,
+            partition_fields: vec![],
+        })
+    }
+
+    pub fn add_partition_fields(&mut self, partition_fields: Vec<Field>) {
+        self.partition_fields.extend(partition_fields);
+    }
+
+    pub fn build(self) -> TableSchema {
+        let mut fields = self.file_fields.clone();
+        fields.extend(self.partition_fields);
+
+        let schema = Schema::new(fields);
+
+        let projection: Vec<usize> = (0..self.file_fields.len()).collect();
+
+        TableSchema::new(Arc::new(schema), projection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect_format, sample_name, CountsFormat, CountsSchemaBuilder};
+
+    #[test]
+    fn test_detect_format() {
+        let (format, samples) =
+            detect_format("Geneid\tChr\tStart\tEnd\tStrand\tLength\t/data/S1.sorted.bam\tS2.bam\n");
+        assert_eq!(format, CountsFormat::FeatureCounts);
+        assert_eq!(samples, vec!["S1", "S2"]);
+
+        let (format, samples) = detect_format("ENSG00000223972\t12\n");
+        assert_eq!(format, CountsFormat::HTSeq);
+        assert!(samples.is_empty());
+
+        let (format, samples) = detect_format("gene\tS1.rep1\tS2.rep1\n");
+        assert_eq!(format, CountsFormat::Matrix);
+        assert_eq!(samples, vec!["S1.rep1", "S2.rep1"]);
+
+        assert_eq!(sample_name("S1.rep1"), "S1.rep1");
+        assert_eq!(sample_name("runs/S1.CRAM"), "S1");
+    }
+
+    #[test]
+    fn test_wide_schema() -> Result<(), Box<dyn std::error::Error>> {
+        let samples = vec!["S1".to_string(), "S2".to_string()];
+
+        let schema = CountsSchemaBuilder::wide(CountsFormat::FeatureCounts, &samples)?
+            .build()
+            .file_schema()?;
+        assert_eq!(schema.fields().len(), 8);
+        assert_eq!(schema.field(6).name(), "S1");
+
+        let schema = CountsSchemaBuilder::wide(CountsFormat::Matrix, &samples)?
+            .build()
+            .file_schema()?;
+        assert_eq!(schema.fields().len(), 3);
+
+        assert!(CountsSchemaBuilder::wide(CountsFormat::HTSeq, &[]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A datafusion compatible datasource for gene counts from RNA-seq quantification, i.e.
+//! featureCounts output, HTSeq-count output, and generic matrices of a gene id column and a
+//! column per sample.
+//!
+//! The format is detected from the first line of each file. By default, the counts are melted into
+//! a long `(gene_id, sample, count, length)` table, so files of different samples can be read as
+//! one table, while the `wide` layout keeps the columns of the file.
+
+mod counts_batch_reader;
+mod counts_config;
+mod counts_opener;
+mod counts_scanner;
+mod counts_schema_builder;
+
+/// Table provider for counts files.
+pub mod table_provider;
+
+pub use self::counts_config::CountsConfig;
+pub use self::counts_opener::CountsOpener;
+pub use self::counts_scanner::CountsScan;
+pub use self::counts_schema_builder::{CountsFormat, CountsLayout, CountsSchemaBuilder};
+
+mod udtf;
+pub use self::udtf::CountsScanFunction;
@@ -0,0 +1,359 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::datatypes::{Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::{
+        file_format::file_compression_type::FileCompressionType, listing::ListingTableUrl,
+        physical_plan::FileScanConfig, TableProvider,
+    },
+    error::{DataFusionError, Result},
+    logical_expr::{TableProviderFilterPushDown, TableType},
+    physical_plan::{empty::EmptyExec, ExecutionPlan},
+    prelude::Expr,
+};
+use exon_common::TableSchema;
+use futures::TryStreamExt;
+use tokio::io::AsyncBufReadExt;
+use tokio_util::io::StreamReader;
+
+use crate::{
+    datasources::{
+        exon_file_type::get_file_extension_with_compression,
+        exon_listing_table_options::{ExonListingConfig, ExonListingOptions},
+        hive_partition::filter_matches_partition_cols,
+        ExonFileType,
+    },
+    physical_plan::{
+        file_scan_config_builder::FileScanConfigBuilder, object_store::pruned_partition_list,
+    },
+};
+
+use super::{counts_schema_builder::detect_format, CountsLayout, CountsScan, CountsSchemaBuilder};
+
+#[derive(Debug, Clone)]
+/// Listing options for a featureCounts, HTSeq-count, or counts matrix table
+pub struct ListingCountsTableOptions {
+    /// File extension for the table
+    file_extension: String,
+
+    /// File compression type
+    file_compression_type: FileCompressionType,
+
+    /// Partition columns for the table
+    table_partition_cols: Vec<Field>,
+
+    /// The layout of the counts
+    layout: CountsLayout,
+}
+
+#[async_trait]
+impl ExonListingOptions for ListingCountsTableOptions {
+    fn table_partition_cols(&self) -> &[Field] {
+        &self.table_partition_cols
+    }
+
+    fn file_extension(&self) -> &str {
+        &self.file_extension
+    }
+
+    fn file_compression_type(&self) -> FileCompressionType {
+        self.file_compression_type
+    }
+
+    async fn create_physical_plan(
+        &self,
+        conf: FileScanConfig,
+    ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
+        let scan = CountsScan::new(conf, self.layout, self.file_compression_type);
+        Ok(Arc::new(scan))
+    }
+}
+
+impl Default for ListingCountsTableOptions {
+    fn default() -> Self {
+        Self::new(FileCompressionType::UNCOMPRESSED)
+    }
+}
+
+impl ListingCountsTableOptions {
+    /// Create a new set of options
+    pub fn new(file_compression_type: FileCompressionType) -> Self {
+        let file_extension = ExonFileType::Counts.get_file_extension(file_compression_type);
+
+        Self {
+            file_extension,
+            file_compression_type,
+            table_partition_cols: Vec::new(),
+            layout: CountsLayout::default(),
+        }
+    }
+
+    /// Set the partition columns for the table
+    pub fn with_table_partition_cols(self, table_partition_cols: Vec<Field>) -> Self {
+        Self {
+            table_partition_cols,
+            ..self
+        }
+    }
+
+    /// Set the file extension for the table, e.g. `tsv`, or the default of `txt`
+    pub fn with_file_extension(self, file_extension: Option<String>) -> Self {
+        let file_extension = match file_extension {
+            Some(file_extension) => {
+                get_file_extension_with_compression(&file_extension, self.file_compression_type)
+            }
+            None => ExonFileType::Counts.get_file_extension(self.file_compression_type),
+        };
+
+        Self {
+            file_extension,
+            ..self
+        }
+    }
+
+    /// Set the layout of the counts
+    pub fn with_layout(self, layout: CountsLayout) -> Self {
+        Self { layout, ..self }
+    }
+
+    /// Infer the schema for the table, which for the wide layout comes from the header of the
+    /// first file
+    pub async fn infer_schema(
+        &self,
+        state: &dyn Session,
+        table_path: &ListingTableUrl,
+    ) -> Result<TableSchema> {
+        let mut schema_builder = match self.layout {
+            CountsLayout::Long => CountsSchemaBuilder::long(),
+            CountsLayout::Wide => self.infer_wide_schema(state, table_path).await?,
+        };
+
+        schema_builder.add_partition_fields(self.table_partition_cols.clone());
+
+        Ok(schema_builder.build())
+    }
+
+    async fn infer_wide_schema(
+        &self,
+        state: &dyn Session,
+        table_path: &ListingTableUrl,
+    ) -> Result<CountsSchemaBuilder> {
+        let store = state.runtime_env().object_store(table_path)?;
+
+        let objects = exon_common::object_store_files_from_table_path(
+            &store,
+            table_path.as_ref(),
+            table_path.prefix(),
+            self.file_extension.as_str(),
+            None,
+        )
+        .await
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        let object = objects.first().ok_or_else(|| {
+            DataFusionError::Execution("No objects found in the table path".to_string())
+        })?;
+
+        let get_result = store.get(&object.location).await?;
+
+        let stream_reader = Box::pin(get_result.into_stream().map_err(DataFusionError::from));
+        let stream_reader = self.file_compression_type.convert_stream(stream_reader)?;
+        let mut stream_reader = StreamReader::new(stream_reader);
+
+        let mut header = String::new();
+        loop {
+            header.clear();
+            if stream_reader.read_line(&mut header).await? == 0 || !header.starts_with('#') {
+                break;
+            }
+        }
+
+        let (format, samples) = detect_format(&header);
+
+        CountsSchemaBuilder::wide(format, &samples)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A counts listing table
+pub struct ListingCountsTable {
+    table_schema: TableSchema,
+
+    config: ExonListingConfig<ListingCountsTableOptions>,
+}
+
+impl ListingCountsTable {
+    /// Create a new counts listing table
+    pub fn new(
+        config: ExonListingConfig<ListingCountsTableOptions>,
+        table_schema: TableSchema,
+    ) -> Self {
+        Self {
+            table_schema,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for ListingCountsTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.table_schema.table_schema())
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        Ok(filters
+            .iter()
+            .map(|f| filter_matches_partition_cols(f, self.config.options.table_partition_cols()))
+            .collect())
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let url = if let Some(url) = self.config.inner.table_paths.first() {
+            url.object_store()
+        } else {
+            return Ok(Arc::new(EmptyExec::new(Arc::new(Schema::empty()))));
+        };
+
+        let object_store = state.runtime_env().object_store(url.clone())?;
+
+        let file_list = pruned_partition_list(
+            &object_store,
+            &self.config.inner.table_paths[0],
+            filters,
+            self.config.options.file_extension(),
+            self.config.options.table_partition_cols(),
+        )
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        let file_schema = self.table_schema.file_schema()?;
+        let file_scan_config = FileScanConfigBuilder::new(url, file_schema, vec![file_list])
+            .projection_option(projection.cloned())
+            .table_partition_cols(self.config.options.table_partition_cols().to_vec())
+            .limit_option(limit)
+            .build();
+
+        let plan = self
+            .config
+            .options
+            .create_physical_plan(file_scan_config)
+            .await?;
+
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        datasources::{
+            counts::{table_provider::ListingCountsTableOptions, CountsLayout},
+            ExonFileType, ExonListingTableFactory,
+        },
+        ExonSession,
+    };
+
+    use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
+    use exon_test::{test_listing_table_url, test_path};
+
+    #[tokio::test]
+    async fn test_file_extension() -> Result<(), Box<dyn std::error::Error>> {
+        let options = ListingCountsTableOptions::default();
+        assert_eq!(options.file_extension, "txt");
+
+        let options_with_tsv = ListingCountsTableOptions::new(FileCompressionType::GZIP)
+            .with_file_extension(Some("tsv".to_string()));
+        assert_eq!(options_with_tsv.file_extension, "tsv.gz");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_listing() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        let session_state = ctx.session.state();
+
+        let table_path = test_listing_table_url("counts");
+        let table = ExonListingTableFactory::new()
+            .create_from_file_type(
+                &session_state,
+                ExonFileType::Counts,
+                FileCompressionType::UNCOMPRESSED,
+                table_path.to_string(),
+                Vec::new(),
+                &HashMap::new(),
+            )
+            .await?;
+
+        let row_cnt = ctx.session.read_table(table)?.count().await?;
+        assert_eq!(row_cnt, 9);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_infer_wide_schema() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        let session_state = ctx.session.state();
+
+        let table_path = test_path("counts", "featurecounts.txt");
+        let table_path =
+            datafusion::datasource::listing::ListingTableUrl::parse(table_path.to_str().unwrap())?;
+
+        let schema = ListingCountsTableOptions::default()
+            .with_layout(CountsLayout::Wide)
+            .infer_schema(&session_state, &table_path)
+            .await?;
+
+        let columns = schema
+            .file_schema()?
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            columns,
+            vec!["gene_id", "chr", "start", "end", "strand", "length", "S1", "S2"]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use super::{
+    table_provider::{ListingCountsTable, ListingCountsTableOptions},
+    CountsSchemaBuilder,
+};
+use crate::datasources::{exon_listing_table_options::ExonListingConfig, ScanFunction};
+use datafusion::{
+    datasource::{function::TableFunctionImpl, TableProvider},
+    error::Result,
+    logical_expr::Expr,
+};
+
+/// A table function that scans counts files in the long layout, i.e. a row per gene and sample.
+#[derive(Debug, Default)]
+pub struct CountsScanFunction {}
+
+impl TableFunctionImpl for CountsScanFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let listing_scan_function = ScanFunction::try_from(exprs)?;
+
+        let listing_table_options =
+            ListingCountsTableOptions::new(listing_scan_function.file_compression_type);
+
+        // The long layout doesn't depend on the header, so the files aren't read here.
+        let schema = CountsSchemaBuilder::long().build();
+
+        let listing_table_config = ExonListingConfig::new_with_options(
+            listing_scan_function.listing_table_url,
+            listing_table_options,
+        );
+
+        let listing_table = ListingCountsTable::new(listing_table_config, schema);
+
+        Ok(Arc::new(listing_table))
+    }
+}
@@ -18,9 +18,8 @@ use datafusion::{
     datasource::physical_plan::{FileMeta, FileOpenFuture, FileOpener},
     error::DataFusionError,
 };
-use exon_cram::{AsyncBatchStream, CRAMConfig};
+use exon_cram::{read_file_definition, read_file_header_with_limits, AsyncBatchStream, CRAMConfig};
 use futures::{StreamExt, TryStreamExt};
-use noodles::sam::Header;
 use tokio_util::io::StreamReader;
 
 #[derive(Debug)]
@@ -49,12 +48,10 @@ impl FileOpener for CRAMOpener {
             let stream_reader = StreamReader::new(stream_reader);
 
             let mut cram_reader = noodles::cram::AsyncReader::new(stream_reader);
-            cram_reader.read_file_definition().await?;
+            read_file_definition(&mut cram_reader).await?;
 
-            let header = cram_reader.read_file_header().await?;
-            let header: Header = header.parse().map_err(|_| {
-                DataFusionError::Execution("Failed to parse CRAM header".to_string())
-            })?;
+            let header =
+                read_file_header_with_limits(cram_reader.get_mut(), &config.reader_limits).await?;
 
             let batch_stream = AsyncBatchStream::try_new(
                 cram_reader,
@@ -26,11 +26,49 @@ pub(crate) struct CRAMIndexData {
     pub offset: u64,
 }
 
+impl CRAMIndexData {
+    /// The reference sequence of the first slice in the container.
+    fn reference_sequence_id(&self) -> Option<usize> {
+        self.records.first().and_then(|r| r.reference_sequence_id())
+    }
+}
+
+/// Group the partitioned files from [`augment_file_with_crai_record_chunks`] by the reference
+/// sequence of their container, so the scan can plan one partition per reference sequence.
+pub(crate) fn group_by_reference_sequence(
+    files: Vec<PartitionedFile>,
+) -> Vec<Vec<PartitionedFile>> {
+    files
+        .into_iter()
+        .into_group_map_by(|f| {
+            f.extensions
+                .as_ref()
+                .and_then(|ext| ext.downcast_ref::<CRAMIndexData>())
+                .and_then(|data| data.reference_sequence_id())
+        })
+        .into_iter()
+        .sorted_by_key(|(id, _)| *id)
+        .map(|(_, files)| files)
+        .collect()
+}
+
+/// Split a partitioned file into one partitioned file per container holding slices that start in
+/// any of the regions, with [`CRAMIndexData`] as the extension. A slice is read once even if it
+/// starts in more than one region.
+#[tracing::instrument(
+    name = "exon.resolve_index",
+    skip_all,
+    fields(
+        path = %partitioned_file.object_meta.location,
+        regions = regions.len(),
+        chunks = tracing::field::Empty
+    )
+)]
 pub(crate) async fn augment_file_with_crai_record_chunks(
     object_store: Arc<dyn ObjectStore>,
     header: &noodles::sam::Header,
     partitioned_file: &PartitionedFile,
-    region: &Region,
+    regions: &[Region],
 ) -> ExonResult<Vec<PartitionedFile>> {
     let path = format!("{}.crai", partitioned_file.object_meta.location);
     let path = Path::from(path);
@@ -40,26 +78,25 @@ pub(crate) async fn augment_file_with_crai_record_chunks(
 
     let index_records = noodles::cram::crai::Reader::new(cursor).read_index()?;
 
+    let region_ids = regions
+        .iter()
+        .filter_map(|region| {
+            let seq_id = header.reference_sequences().get_index_of(region.name())?;
+            Some((seq_id, region.interval()))
+        })
+        .collect::<Vec<_>>();
+
     let chunks = index_records
         .iter()
         .filter(|r| {
-            if let Some(seq_id) = header.reference_sequences().get_index_of(region.name()) {
-                if let Some(r_seq_id) = r.reference_sequence_id() {
-                    if seq_id != r_seq_id {
-                        return false;
-                    }
-
-                    if let Some(start) = r.alignment_start() {
-                        region.interval().contains(start)
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
+            let (Some(r_seq_id), Some(start)) = (r.reference_sequence_id(), r.alignment_start())
+            else {
+                return false;
+            };
+
+            region_ids
+                .iter()
+                .any(|(seq_id, interval)| *seq_id == r_seq_id && interval.contains(start))
         })
         .sorted_by(|a, b| a.offset().cmp(&b.offset()))
         .chunk_by(|a| a.offset())
@@ -81,5 +118,7 @@ pub(crate) async fn augment_file_with_crai_record_chunks(
         })
         .collect::<Vec<PartitionedFile>>();
 
+    tracing::Span::current().record("chunks", chunks.len());
+
     Ok(chunks)
 }
@@ -26,7 +26,8 @@ use datafusion::{
 };
 use exon_cram::CRAMConfig;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::indexed_file_opener::IndexedCRAMOpener;
 
@@ -92,7 +93,7 @@ impl ExecutionPlan for IndexedCRAMScan {
             return Ok(None);
         }
 
-        let file_groups = self.base_config.regroup_files_by_size(target_partitions);
+        let file_groups = self.base_config.regroup_file_groups(target_partitions);
 
         let mut new_plan = self.clone();
         new_plan.base_config.file_groups = file_groups;
@@ -138,11 +139,17 @@ impl ExecutionPlan for IndexedCRAMScan {
             self.reference.clone(),
         )
         .with_batch_size(batch_size)
-        .with_projection(self.base_config.file_projection());
+        .with_projection(self.base_config.file_projection())
+        .with_reader_limits(reader_limits(context.session_config()));
 
         let opener = IndexedCRAMOpener::new(Arc::new(config));
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
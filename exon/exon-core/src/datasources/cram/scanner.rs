@@ -25,7 +25,8 @@ use datafusion::{
 };
 use exon_cram::CRAMConfig;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::CRAMOpener;
 
@@ -149,10 +150,16 @@ impl ExecutionPlan for CRAMScan {
             self.reference.clone(),
         )
         .with_batch_size(batch_size)
-        .with_projection(self.base_config().file_projection());
+        .with_projection(self.base_config().file_projection())
+        .with_reader_limits(reader_limits(context.session_config()));
 
         let opener = CRAMOpener::new(Arc::new(config));
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
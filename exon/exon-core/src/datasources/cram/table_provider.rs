@@ -28,15 +28,18 @@ use datafusion::{
     logical_expr::{Expr, TableProviderFilterPushDown, TableType},
     physical_plan::ExecutionPlan,
 };
-use exon_common::TableSchema;
-use exon_cram::ObjectStoreFastaRepositoryAdapter;
+use exon_common::{ReaderLimits, TableSchema};
+use exon_cram::{
+    read_data_container_with_limits, read_file_definition, read_file_header_with_limits,
+};
 use exon_sam::SAMSchemaBuilder;
 use futures::{StreamExt, TryStreamExt};
-use noodles::{core::Region, sam::Header};
+use noodles::core::Region;
 use object_store::{ObjectMeta, ObjectStore};
 use tokio_util::io::StreamReader;
 
 use crate::{
+    config::reader_limits,
     datasources::hive_partition::filter_matches_partition_cols,
     error::{ExonError, Result as ExonResult},
     physical_plan::{
@@ -46,7 +49,8 @@ use crate::{
 };
 
 use super::{
-    index::augment_file_with_crai_record_chunks, indexed_scanner::IndexedCRAMScan,
+    index::{augment_file_with_crai_record_chunks, group_by_reference_sequence},
+    indexed_scanner::IndexedCRAMScan,
     scanner::CRAMScan,
 };
 
@@ -84,6 +88,9 @@ pub struct ListingCRAMTableOptions {
     /// Whether to use the tag as struct.
     tag_as_struct: bool,
 
+    /// Whether to use the compact flag, mapping quality, and quality score types.
+    compact_types: bool,
+
     /// If the underlying CRAM file is indexed.
     indexed: bool,
 
@@ -127,6 +134,13 @@ impl ListingCRAMTableOptions {
         self
     }
 
+    /// Set the compact_types option, for UInt16 flags, UInt8 mapping qualities, and lists of
+    /// UInt8 quality scores.
+    pub fn with_compact_types(mut self, compact_types: bool) -> Self {
+        self.compact_types = compact_types;
+        self
+    }
+
     /// Set the partition columns for the table.
     pub fn with_table_partition_cols(mut self, table_partition_cols: Vec<Field>) -> Self {
         self.table_partition_cols = table_partition_cols;
@@ -144,14 +158,20 @@ impl ListingCRAMTableOptions {
         &self,
         store: &Arc<dyn ObjectStore>,
         objects: &[ObjectMeta],
+        limits: &ReaderLimits,
     ) -> ExonResult<TableSchema> {
         if objects.is_empty() {
             return Err(ExonError::ExecutionError("No objects found".to_string()));
         }
 
+        let mut schema_builder = SAMSchemaBuilder::default();
+
+        if self.compact_types {
+            schema_builder = schema_builder.with_compact_types();
+        }
+
         if !self.tag_as_struct {
-            let builder = SAMSchemaBuilder::default()
-                .with_partition_fields(self.table_partition_cols.clone());
+            let builder = schema_builder.with_partition_fields(self.table_partition_cols.clone());
             let table_schema = builder.build();
 
             return Ok(table_schema);
@@ -162,33 +182,28 @@ impl ListingCRAMTableOptions {
         let stream_reader = Box::pin(get_result.into_stream().map_err(ExonError::from));
         let stream_reader = StreamReader::new(stream_reader);
 
-        let reference_sequence_repository = match &self.fasta_reference {
-            Some(reference) => {
-                let object_store_adapter = ObjectStoreFastaRepositoryAdapter::try_new(
-                    Arc::clone(store),
-                    reference.to_string(),
-                )
-                .await?;
-
-                noodles::fasta::Repository::new(object_store_adapter)
-            }
-            None => noodles::fasta::Repository::default(),
+        // Only the tags are needed, so the records aren't resolved against the reference.
+        let mut cram_reader = noodles::cram::AsyncReader::new(stream_reader);
+
+        read_file_definition(&mut cram_reader).await?;
+        read_file_header_with_limits(cram_reader.get_mut(), limits).await?;
+
+        let mut buf = Vec::new();
+        let container =
+            read_data_container_with_limits(cram_reader.get_mut(), &mut buf, limits).await?;
+
+        let record = match container {
+            Some(container) => match container.slices().first() {
+                Some(slice) => slice
+                    .records(container.compression_header())?
+                    .into_iter()
+                    .next(),
+                None => None,
+            },
+            None => None,
         };
 
-        let mut cram_reader = noodles::cram::r#async::io::reader::Builder::default()
-            .set_reference_sequence_repository(reference_sequence_repository)
-            .build_from_reader(stream_reader);
-
-        cram_reader.read_file_definition().await?;
-        let header = cram_reader.read_file_header().await?;
-        let header: Header = header
-            .to_owned()
-            .parse()
-            .map_err(|_| DataFusionError::Execution("Unable to parse header".to_string()))?;
-
-        let mut schema_builder = SAMSchemaBuilder::default();
-
-        if let Some(Ok(record)) = cram_reader.records(&header).next().await {
+        if let Some(record) = record {
             schema_builder = schema_builder.with_tags_data_type_from_data(record.data())?;
         } else {
             return Err(ExonError::ExecutionError(
@@ -224,7 +239,10 @@ impl ListingCRAMTableOptions {
             .await
             .map_err(|e| DataFusionError::Execution(format!("Unable to get path info: {}", e)))?;
 
-        self.infer_schema_from_object_meta(&store, &files).await
+        let limits = reader_limits(state.config());
+
+        self.infer_schema_from_object_meta(&store, &files, &limits)
+            .await
     }
 
     async fn create_physical_plan_with_region(
@@ -291,10 +309,8 @@ impl TableProvider for ListingCRAMTable {
         let pushdown = filters
             .iter()
             .map(|f| {
-                if let Expr::ScalarFunction(s) = f {
-                    if s.name() == "cram_region_filter" {
-                        return Ok(TableProviderFilterPushDown::Exact);
-                    }
+                if infer_region::is_region_filter(f, "cram_region_filter") {
+                    return Ok(TableProviderFilterPushDown::Exact);
                 }
 
                 let pt = filter_matches_partition_cols(f, &self.options.table_partition_cols);
@@ -348,45 +364,31 @@ impl TableProvider for ListingCRAMTable {
 
         tracing::info!("for indexed CRAM, using filters: {:?}", filters);
 
-        let regions = filters
+        // Each region filter is a single region or a disjunction of regions.
+        let mut filter_regions = filters
             .iter()
-            .map(|f| {
-                if let Expr::ScalarFunction(s) = f {
-                    let r = infer_region::infer_region_from_udf(s, "cram_region_filter")?;
-                    Ok(r)
-                } else {
-                    Ok(None)
-                }
-            })
+            .map(|f| infer_region::infer_regions_from_expr(f, "cram_region_filter"))
             .collect::<ExonResult<Vec<_>>>()?
             .into_iter()
             .flatten()
             .collect::<Vec<_>>();
 
-        tracing::info!("regions: {:?}", regions);
-
-        let regions = if self.options.indexed {
-            if regions.len() == 1 {
-                regions
-            } else {
-                match self.options.region.clone() {
-                    Some(region) => vec![region],
-                    None => regions,
-                }
-            }
-        } else {
-            regions
-        };
+        tracing::info!("regions: {:?}", filter_regions);
 
-        if regions.is_empty() && self.options.indexed {
+        if filter_regions.len() > 1 {
             return Err(DataFusionError::Plan(
-                "An indexed CRAM table type requires a region filter. See the 'cram_region_filter' function.".to_string(),
+                "Only one region filter is supported, combine regions with OR".to_string(),
             ));
         }
 
-        if regions.len() > 1 {
+        let regions = match filter_regions.pop() {
+            Some(regions) => regions,
+            None => self.options.region.clone().into_iter().collect(),
+        };
+
+        if regions.is_empty() {
             return Err(DataFusionError::Plan(
-                "Only one region filter is supported".to_string(),
+                "An indexed CRAM table type requires a region filter. See the 'cram_region_filter' function.".to_string(),
             ));
         }
 
@@ -399,8 +401,8 @@ impl TableProvider for ListingCRAMTable {
         )
         .await?;
 
+        let limits = reader_limits(state.config());
         let mut file_partition_with_ranges = Vec::new();
-        let region = regions[0].clone();
 
         while let Some(f) = file_list.next().await {
             let f = f?;
@@ -412,28 +414,31 @@ impl TableProvider for ListingCRAMTable {
             let stream_reader = StreamReader::new(stream_reader);
 
             let mut cram_reader = noodles::cram::AsyncReader::new(stream_reader);
-            cram_reader.read_file_definition().await?;
+            read_file_definition(&mut cram_reader).await?;
 
-            let header = cram_reader.read_file_header().await?;
-            let header: Header = header.parse().map_err(|_| {
-                DataFusionError::Execution("Failed to parse CRAM header".to_string())
-            })?;
+            let header = read_file_header_with_limits(cram_reader.get_mut(), &limits).await?;
 
             let file_byte_range = augment_file_with_crai_record_chunks(
                 Arc::clone(&object_store),
                 &header,
                 &f,
-                &region,
+                &regions,
             )
             .await?;
 
             file_partition_with_ranges.extend(file_byte_range);
         }
 
+        // Plan one file group per reference sequence so the scan can run them in parallel.
+        let mut file_groups = group_by_reference_sequence(file_partition_with_ranges);
+        if file_groups.is_empty() {
+            file_groups.push(Vec::new());
+        }
+
         let file_scan_config = FileScanConfig {
             object_store_url: object_store_url.clone(),
             file_schema: self.table_schema.file_schema()?,
-            file_groups: vec![file_partition_with_ranges],
+            file_groups,
             statistics: Statistics::new_unknown(self.table_schema.file_schema()?.as_ref()),
             projection: projection.cloned(),
             limit,
@@ -28,6 +28,11 @@ pub trait ExonFileScanConfig {
     /// Repartition the file groups into whole partitions.
     fn regroup_files_by_size(&self, target_partitions: usize) -> Vec<Vec<PartitionedFile>>;
 
+    /// Repartition the file groups, keeping each existing group in one partition when there are
+    /// at least as many groups as target partitions, and falling back to
+    /// [`Self::regroup_files_by_size`] otherwise.
+    fn regroup_file_groups(&self, target_partitions: usize) -> Vec<Vec<PartitionedFile>>;
+
     /// Get the file schema projection.
     fn file_projection(&self) -> Vec<usize>;
 
@@ -40,6 +45,14 @@ impl ExonFileScanConfig for FileScanConfig {
         regroup_files_by_size(&self.file_groups, target_partitions)
     }
 
+    fn regroup_file_groups(&self, target_partitions: usize) -> Vec<Vec<PartitionedFile>> {
+        if self.file_groups.len() < target_partitions {
+            return regroup_files_by_size(&self.file_groups, target_partitions);
+        }
+
+        pack_file_groups(&self.file_groups, target_partitions)
+    }
+
     /// Get the schema, statistics, and plan properties for the scan.
     fn project_with_properties(&self) -> (Arc<Schema>, Statistics, PlanProperties) {
         let (schema, statistics, projected_output_ordering) = self.project();
@@ -104,3 +117,66 @@ fn regroup_files_by_size(
 
     new_file_groups
 }
+
+/// Pack whole file groups into the target number of partitions, largest group first, each into
+/// the partition with the fewest files so far.
+fn pack_file_groups(
+    file_groups: &[Vec<PartitionedFile>],
+    target_partitions: usize,
+) -> Vec<Vec<PartitionedFile>> {
+    let mut new_file_groups = vec![Vec::new(); target_partitions];
+
+    for group in file_groups
+        .iter()
+        .sorted_by_key(|g| std::cmp::Reverse(g.len()))
+    {
+        if let Some(smallest) = new_file_groups.iter_mut().min_by_key(|g| g.len()) {
+            smallest.extend(group.iter().cloned());
+        }
+    }
+
+    new_file_groups.retain(|fg| !fg.is_empty());
+
+    new_file_groups
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::datasource::listing::PartitionedFile;
+
+    use super::pack_file_groups;
+
+    fn group(name: &str, n: usize) -> Vec<PartitionedFile> {
+        (0..n)
+            .map(|i| PartitionedFile::new(format!("{name}/{i}"), 10))
+            .collect()
+    }
+
+    #[test]
+    fn test_pack_file_groups() {
+        let file_groups = vec![
+            group("chr1", 4),
+            group("chr2", 1),
+            group("chr3", 2),
+            group("chr4", 2),
+        ];
+
+        let packed = pack_file_groups(&file_groups, 2);
+
+        let mut sizes = packed.iter().map(Vec::len).collect::<Vec<_>>();
+        sizes.sort();
+        assert_eq!(sizes, vec![4, 5]);
+
+        // Each group stays within one partition.
+        for name in ["chr1", "chr2", "chr3", "chr4"] {
+            let partitions = packed
+                .iter()
+                .filter(|g| {
+                    g.iter()
+                        .any(|f| f.object_meta.location.as_ref().starts_with(name))
+                })
+                .count();
+            assert_eq!(partitions, 1);
+        }
+    }
+}
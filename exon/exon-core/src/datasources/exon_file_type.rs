@@ -18,7 +18,12 @@ use datafusion::{
     datasource::file_format::file_compression_type::FileCompressionType, error::DataFusionError,
 };
 
-use crate::error::ExonError;
+use exon_illumina::IlluminaFileKind;
+
+use crate::{
+    datasources::{classification::ClassificationFileKind, quantification::QuantificationFileKind},
+    error::ExonError,
+};
 
 /// The type of file.
 #[derive(Debug, Clone)]
@@ -94,12 +99,40 @@ pub enum ExonFileType {
     #[cfg(feature = "mzml")]
     MZML,
 
+    /// imzML file format, i.e. mzML metadata with the spectra in an .ibd file.
+    #[cfg(feature = "mzml")]
+    IMZML,
+
     /// FCS file format.
     #[cfg(feature = "fcs")]
     FCS,
 
     /// SDF file format.
     SDF,
+
+    /// POD5 nanopore signal file format.
+    POD5,
+
+    /// Nanopore sequencing summary file format.
+    SequencingSummary,
+
+    /// 10x Genomics feature-barcode matrix (MatrixMarket) file format.
+    MTX,
+
+    /// VCF Zarr store format.
+    VCFZarr,
+
+    /// Illumina run folder files, e.g. RunInfo.xml or InterOp metrics.
+    Illumina(IlluminaFileKind),
+
+    /// Metagenomic classification files, e.g. kraken2 reports or centrifuge output.
+    Classification(ClassificationFileKind),
+
+    /// Gene counts, e.g. featureCounts or HTSeq-count output.
+    Counts,
+
+    /// Transcript quantification files, i.e. salmon `quant.sf` or kallisto `abundance.tsv`.
+    Quantification(QuantificationFileKind),
 }
 
 impl FromStr for ExonFileType {
@@ -126,6 +159,8 @@ impl FromStr for ExonFileType {
             "SAM" => Ok(Self::SAM),
             #[cfg(feature = "mzml")]
             "MZML" => Ok(Self::MZML),
+            #[cfg(feature = "mzml")]
+            "IMZML" => Ok(Self::IMZML),
             #[cfg(feature = "genbank")]
             "GENBANK" | "GBK" | "GB" => Ok(Self::GENBANK),
             "HMMDOMTAB" => Ok(Self::HMMDOMTAB),
@@ -136,7 +171,21 @@ impl FromStr for ExonFileType {
             "CRAM" => Ok(Self::CRAM),
             "FA" => Ok(Self::FASTA),
             "SDF" => Ok(Self::SDF),
-            _ => Err(ExonError::InvalidFileType(s)),
+            "POD5" => Ok(Self::POD5),
+            "SEQUENCING_SUMMARY" => Ok(Self::SequencingSummary),
+            "MTX" => Ok(Self::MTX),
+            "VCF_ZARR" | "VCZ" => Ok(Self::VCFZarr),
+            "COUNTS" => Ok(Self::Counts),
+            _ => match s.strip_prefix("ILLUMINA_").map(IlluminaFileKind::from_str) {
+                Some(Ok(kind)) => Ok(Self::Illumina(kind)),
+                _ => match ClassificationFileKind::from_str(&s) {
+                    Ok(kind) => Ok(Self::Classification(kind)),
+                    Err(_) => match QuantificationFileKind::from_str(&s) {
+                        Ok(kind) => Ok(Self::Quantification(kind)),
+                        Err(_) => Err(ExonError::InvalidFileType(s)),
+                    },
+                },
+            },
         }
     }
 }
@@ -161,6 +210,8 @@ impl Display for ExonFileType {
             Self::SAM => write!(f, "SAM"),
             #[cfg(feature = "mzml")]
             Self::MZML => write!(f, "MZML"),
+            #[cfg(feature = "mzml")]
+            Self::IMZML => write!(f, "IMZML"),
             #[cfg(feature = "genbank")]
             Self::GENBANK => write!(f, "GENBANK"),
             Self::HMMDOMTAB => write!(f, "HMMDOMTAB"),
@@ -171,6 +222,14 @@ impl Display for ExonFileType {
             Self::CRAM => write!(f, "CRAM"),
             Self::FA => write!(f, "FA"),
             Self::SDF => write!(f, "SDF"),
+            Self::POD5 => write!(f, "POD5"),
+            Self::SequencingSummary => write!(f, "SEQUENCING_SUMMARY"),
+            Self::MTX => write!(f, "MTX"),
+            Self::VCFZarr => write!(f, "VCF_ZARR"),
+            Self::Illumina(kind) => write!(f, "ILLUMINA_{}", kind.to_string().to_uppercase()),
+            Self::Classification(kind) => write!(f, "{}", kind.to_string().to_uppercase()),
+            Self::Counts => write!(f, "COUNTS"),
+            Self::Quantification(kind) => write!(f, "{}", kind.to_string().to_uppercase()),
         }
     }
 }
@@ -195,6 +254,12 @@ impl ExonFileType {
         match self {
             ExonFileType::BigWigZoom => "bw".to_string(),
             ExonFileType::BigWigValue => "bw".to_string(),
+            ExonFileType::SequencingSummary => "txt".to_string(),
+            ExonFileType::VCFZarr => "vcz".to_string(),
+            ExonFileType::Illumina(kind) => kind.file_name().to_string(),
+            ExonFileType::Classification(kind) => kind.file_extension().to_string(),
+            ExonFileType::Counts => "txt".to_string(),
+            ExonFileType::Quantification(kind) => kind.file_name().to_string(),
             _ => self.to_string().to_lowercase(),
         }
     }
@@ -240,7 +305,7 @@ pub fn infer_file_type_and_compression(
 mod tests {
     use std::str::FromStr;
 
-    use super::ExonFileType;
+    use super::{ClassificationFileKind, ExonFileType, IlluminaFileKind, QuantificationFileKind};
 
     #[test]
     fn test_display() {
@@ -259,6 +324,8 @@ mod tests {
         assert_eq!(ExonFileType::BED.to_string(), "BED");
         #[cfg(feature = "mzml")]
         assert_eq!(ExonFileType::MZML.to_string(), "MZML");
+        #[cfg(feature = "mzml")]
+        assert_eq!(ExonFileType::IMZML.to_string(), "IMZML");
         assert_eq!(ExonFileType::GTF.to_string(), "GTF");
         #[cfg(feature = "fcs")]
         assert_eq!(ExonFileType::FCS.to_string(), "FCS");
@@ -268,6 +335,26 @@ mod tests {
         assert_eq!(ExonFileType::CRAM.to_string(), "CRAM");
         assert_eq!(ExonFileType::BigWigZoom.to_string(), "BIGWIG_ZOOM");
         assert_eq!(ExonFileType::BigWigValue.to_string(), "BIGWIG_VALUE");
+        assert_eq!(ExonFileType::POD5.to_string(), "POD5");
+        assert_eq!(
+            ExonFileType::SequencingSummary.to_string(),
+            "SEQUENCING_SUMMARY"
+        );
+        assert_eq!(ExonFileType::MTX.to_string(), "MTX");
+        assert_eq!(ExonFileType::VCFZarr.to_string(), "VCF_ZARR");
+        assert_eq!(
+            ExonFileType::Illumina(IlluminaFileKind::TileMetrics).to_string(),
+            "ILLUMINA_TILE_METRICS"
+        );
+        assert_eq!(
+            ExonFileType::Classification(ClassificationFileKind::KrakenReport).to_string(),
+            "KRAKEN_REPORT"
+        );
+        assert_eq!(ExonFileType::Counts.to_string(), "COUNTS");
+        assert_eq!(
+            ExonFileType::Quantification(QuantificationFileKind::SalmonQuant).to_string(),
+            "SALMON_QUANT"
+        );
     }
 
     #[test]
@@ -296,6 +383,8 @@ mod tests {
         assert_eq!(ExonFileType::BED.get_base_file_extension(), "bed");
         #[cfg(feature = "mzml")]
         assert_eq!(ExonFileType::MZML.get_base_file_extension(), "mzml");
+        #[cfg(feature = "mzml")]
+        assert_eq!(ExonFileType::IMZML.get_base_file_extension(), "imzml");
         assert_eq!(ExonFileType::GTF.get_base_file_extension(), "gtf");
         #[cfg(feature = "fcs")]
         assert_eq!(ExonFileType::FCS.get_base_file_extension(), "fcs");
@@ -304,10 +393,61 @@ mod tests {
         assert_eq!(ExonFileType::FNA.get_base_file_extension(), "fna");
         assert_eq!(ExonFileType::CRAM.get_base_file_extension(), "cram");
         assert_eq!(ExonFileType::BigWigZoom.get_base_file_extension(), "bw");
+        assert_eq!(ExonFileType::POD5.get_base_file_extension(), "pod5");
+        assert_eq!(
+            ExonFileType::SequencingSummary.get_base_file_extension(),
+            "txt"
+        );
+        assert_eq!(ExonFileType::MTX.get_base_file_extension(), "mtx");
+        assert_eq!(ExonFileType::VCFZarr.get_base_file_extension(), "vcz");
+        assert_eq!(
+            ExonFileType::Illumina(IlluminaFileKind::RunInfo).get_base_file_extension(),
+            "runinfo.xml"
+        );
+        assert_eq!(
+            ExonFileType::Classification(ClassificationFileKind::CentrifugeOutput)
+                .get_base_file_extension(),
+            "tsv"
+        );
+        assert_eq!(ExonFileType::Counts.get_base_file_extension(), "txt");
+        assert_eq!(
+            ExonFileType::Quantification(QuantificationFileKind::KallistoAbundance)
+                .get_base_file_extension(),
+            "abundance.tsv"
+        );
     }
 
     #[test]
     fn test_from_str_errors() {
         assert!(ExonFileType::from_str("foo").is_err());
+        assert!(ExonFileType::from_str("illumina_foo").is_err());
+    }
+
+    #[test]
+    fn test_from_str_illumina() {
+        assert!(matches!(
+            ExonFileType::from_str("illumina_quality_metrics"),
+            Ok(ExonFileType::Illumina(IlluminaFileKind::QualityMetrics))
+        ));
+    }
+
+    #[test]
+    fn test_from_str_classification() {
+        assert!(matches!(
+            ExonFileType::from_str("kraken_output"),
+            Ok(ExonFileType::Classification(
+                ClassificationFileKind::KrakenOutput
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_from_str_quantification() {
+        assert!(matches!(
+            ExonFileType::from_str("salmon_quant"),
+            Ok(ExonFileType::Quantification(
+                QuantificationFileKind::SalmonQuant
+            ))
+        ));
     }
 }
@@ -0,0 +1,1003 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::datatypes::{Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::{
+        file_format::file_compression_type::FileCompressionType,
+        listing::{FileRange, ListingTableUrl, PartitionedFile},
+        physical_plan::FileScanConfig,
+        TableProvider,
+    },
+    error::{DataFusionError, Result},
+    execution::TaskContext,
+    logical_expr::{TableProviderFilterPushDown, TableType},
+    physical_plan::{ExecutionPlan, Statistics},
+    prelude::Expr,
+    scalar::ScalarValue,
+};
+use exon_common::{IdentifierFilter, SequenceFilter, TableSchema};
+use futures::TryStreamExt;
+use noodles::core::Region;
+
+use crate::{
+    datasources::{
+        exon_listing_table_options::{ExonListingConfig, ExonListingOptions},
+        file_statistics::{merge_file_statistics, FileStatisticsCollector},
+        hive_partition::filter_matches_partition_cols,
+        identifier_index::{
+            build_bloom_filter, build_identifier_chunk_index, read_bloom_filter,
+            read_identifier_chunk_index, IdentifierPredicate, IDENTIFIER_INDEX_CHUNK_SIZE,
+        },
+        indexed_file::indexed_bgzf_file::{
+            augment_partitioned_file_with_byte_range, IndexedBGZFFile,
+        },
+        provenance::{
+            add_file_provenance_values, file_provenance_fields, provenance_fields,
+            ProvenanceProjection,
+        },
+    },
+    error::Result as ExonResult,
+    physical_plan::{
+        file_scan_config_builder::FileScanConfigBuilder, infer_region,
+        infer_sequence_filter::infer_sequence_filter, object_store::pruned_partition_list,
+    },
+    planner_events::PLANNER_EVENT_TARGET,
+};
+
+/// Describes how a format can be queried by region through a BGZF index.
+#[derive(Debug)]
+pub struct RegionIndex<'a> {
+    /// The table type used in error messages, e.g. `INDEXED_VCF`
+    pub(crate) table_type: &'static str,
+
+    /// The name of the region filter UDF that is pushed down to the scan
+    pub(crate) filter_name: &'static str,
+
+    /// The type of index used to find the byte ranges for a region
+    pub(crate) indexed_file: IndexedBGZFFile,
+
+    /// True if the table must be queried with a region
+    pub(crate) indexed: bool,
+
+    /// The regions set on the table options
+    pub(crate) regions: &'a [Region],
+}
+
+/// Describes the identifiers of a format that lookups can prune files by with bloom filter
+/// sidecars.
+#[derive(Debug)]
+pub struct IdentifierIndex {
+    /// The column of the identifiers, a string, list of strings, or map of lists of strings
+    pub(crate) column: &'static str,
+
+    /// The keys of a map column whose values are identifiers, e.g. `gene_id`
+    pub(crate) map_keys: &'static [&'static str],
+
+    /// The tab separated field of the identifiers in a record line, e.g. `ID` in a VCF, if the
+    /// scan can skip records by identifier and read uncompressed files by chunks of records
+    pub(crate) record_field: Option<usize>,
+}
+
+#[async_trait]
+/// The format specific part of an [`ExonListingTable`].
+///
+/// A format provides the schema inference and the physical plans, the listing, filter pushdown,
+/// and region handling are shared.
+pub trait ExonFileFormatOptions: ExonListingOptions + 'static {
+    /// Infer the schema of the table, including the partition columns
+    async fn infer_table_schema(
+        &self,
+        state: &dyn Session,
+        table_path: &ListingTableUrl,
+    ) -> Result<TableSchema>;
+
+    /// The region index for the format, or `None` if the format can't be queried by region
+    fn region_index(&self) -> Option<RegionIndex<'_>> {
+        None
+    }
+
+    /// Create a physical plan for the byte ranges of a region
+    async fn create_physical_plan_with_region(
+        &self,
+        _conf: FileScanConfig,
+        _region: Region,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Err(DataFusionError::NotImplemented(
+            "Region queries are not supported for this format".to_string(),
+        ))
+    }
+
+    /// The identifiers that lookups can prune files by, or `None` if the format has none
+    fn identifier_index(&self) -> Option<IdentifierIndex> {
+        None
+    }
+
+    /// Create a physical plan that skips the records that don't match an identifier filter
+    async fn create_physical_plan_with_identifier_filter(
+        &self,
+        _conf: FileScanConfig,
+        _identifier_filter: IdentifierFilter,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Err(DataFusionError::NotImplemented(
+            "Identifier filters are not supported for this format".to_string(),
+        ))
+    }
+
+    /// The column that `LIKE` and regular expression filters can be pushed down to as a
+    /// prefilter, or `None` if the format can't prefilter records
+    fn sequence_column(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Create a physical plan that skips the records that don't match a sequence filter
+    async fn create_physical_plan_with_sequence_filter(
+        &self,
+        _conf: FileScanConfig,
+        _sequence_filter: SequenceFilter,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Err(DataFusionError::NotImplemented(
+            "Sequence filters are not supported for this format".to_string(),
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A listing table for any format that implements [`ExonFileFormatOptions`]
+pub struct ExonListingTable<O> {
+    /// The schema for the table
+    table_schema: TableSchema,
+
+    /// The config for the table
+    config: ExonListingConfig<O>,
+
+    /// The exact statistics of the small files that have been scanned
+    collected_statistics: FileStatisticsCollector,
+}
+
+impl<O: ExonFileFormatOptions> ExonListingTable<O> {
+    /// Create a new listing table
+    pub fn new(config: ExonListingConfig<O>, table_schema: TableSchema) -> Self {
+        Self {
+            table_schema,
+            config,
+            collected_statistics: FileStatisticsCollector::default(),
+        }
+    }
+
+    /// Create a new listing table, inferring the schema from the options
+    pub async fn try_new_with_inferred_schema(
+        state: &dyn Session,
+        config: ExonListingConfig<O>,
+    ) -> Result<Self> {
+        let table_path = config.first_table_path().ok_or_else(|| {
+            DataFusionError::Execution("No table paths found in the configuration".to_string())
+        })?;
+
+        let table_schema = config.options.infer_table_schema(state, table_path).await?;
+
+        Ok(Self::new(config, table_schema))
+    }
+
+    /// Get the regions from the region filters and the options
+    fn regions(&self, filters: &[Expr]) -> Result<Vec<Region>> {
+        let region_index = match self.config.options.region_index() {
+            Some(region_index) => region_index,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut regions = region_index.regions.to_vec();
+
+        let filter_regions = filters
+            .iter()
+            .map(|f| match f {
+                Expr::ScalarFunction(s) => {
+                    infer_region::infer_region_from_udf(s, region_index.filter_name)
+                }
+                _ => Ok(None),
+            })
+            .collect::<ExonResult<Vec<_>>>()?;
+
+        regions.extend(filter_regions.into_iter().flatten());
+
+        if regions.len() > 1 {
+            return Err(DataFusionError::NotImplemented(
+                "Multiple regions are not supported yet".to_string(),
+            ));
+        }
+
+        if regions.is_empty() && region_index.indexed {
+            return Err(DataFusionError::Plan(format!(
+                "{} table requires a region filter. See the UDF '{}'.",
+                region_index.table_type, region_index.filter_name
+            )));
+        }
+
+        Ok(regions)
+    }
+
+    /// Combine the pattern filters on the sequence column into a prefilter. The record index
+    /// counts the scanned records, so there's no prefilter if the provenance columns are added.
+    fn sequence_filter<'a>(
+        &self,
+        filters: impl IntoIterator<Item = &'a Expr>,
+    ) -> Option<SequenceFilter> {
+        if self.config.provenance_columns || !self.config.sequence_filters {
+            return None;
+        }
+
+        let column = self.config.options.sequence_column()?;
+
+        filters
+            .into_iter()
+            .filter_map(|f| infer_sequence_filter(f, column))
+            .reduce(SequenceFilter::and)
+    }
+
+    /// Get the identifier lookups that files can be pruned by or records prefiltered by, which is
+    /// none unless bloom filters or identifier indexes are enabled.
+    fn identifier_predicates<'a>(
+        &self,
+        filters: impl IntoIterator<Item = &'a Expr>,
+    ) -> Vec<IdentifierPredicate> {
+        let index = match self.config.options.identifier_index() {
+            Some(index)
+                if self.config.bloom_filters || self.identifier_record_field().is_some() =>
+            {
+                index
+            }
+            _ => return Vec::new(),
+        };
+
+        filters
+            .into_iter()
+            .filter_map(|f| IdentifierPredicate::try_from_expr(f, &index))
+            .collect()
+    }
+
+    /// The field of the identifiers in a record line, if identifier indexes are enabled and the
+    /// format can prefilter records by identifier. The record index counts the scanned records,
+    /// so there's no prefilter if the provenance columns are added.
+    fn identifier_record_field(&self) -> Option<usize> {
+        if self.config.provenance_columns || !self.config.identifier_indexes {
+            return None;
+        }
+
+        self.config.options.identifier_index()?.record_field
+    }
+
+    /// Combine the identifier lookups into a prefilter, so a record has to match all of them.
+    fn identifier_filter(&self, predicates: &[IdentifierPredicate]) -> Option<IdentifierFilter> {
+        self.identifier_record_field()?;
+
+        predicates
+            .iter()
+            .map(IdentifierPredicate::identifier_filter)
+            .reduce(IdentifierFilter::and)
+    }
+
+    /// Replace uncompressed files with the byte ranges of their chunks that the identifier index
+    /// sidecars don't rule out for the lookups, building the sidecars of the files that don't
+    /// have one yet.
+    async fn split_files_by_identifier(
+        &self,
+        state: &dyn Session,
+        url: &ListingTableUrl,
+        file_partitions: Vec<PartitionedFile>,
+        predicates: &[IdentifierPredicate],
+    ) -> Result<Vec<PartitionedFile>> {
+        let record_field = match self.identifier_record_field() {
+            Some(record_field)
+                if !predicates.is_empty()
+                    && self.config.start_after_offset.is_none()
+                    && self.config.options.file_compression_type()
+                        == FileCompressionType::UNCOMPRESSED =>
+            {
+                record_field
+            }
+            _ => return Ok(file_partitions),
+        };
+
+        let object_store = state.runtime_env().object_store(url.object_store())?;
+
+        let mut split_file_partitions = Vec::with_capacity(file_partitions.len());
+
+        for f in file_partitions {
+            let index = match read_identifier_chunk_index(&object_store, &f.object_meta).await? {
+                Some(index) => index,
+                None => {
+                    build_identifier_chunk_index(
+                        &object_store,
+                        &f.object_meta,
+                        record_field,
+                        IDENTIFIER_INDEX_CHUNK_SIZE,
+                    )
+                    .await?
+                }
+            };
+
+            // Adjacent chunks are merged, so they're read with one request.
+            let mut ranges: Vec<FileRange> = Vec::new();
+
+            for chunk in index
+                .chunks()
+                .iter()
+                .filter(|chunk| predicates.iter().all(|p| p.may_match(&chunk.filter)))
+            {
+                match ranges.last_mut() {
+                    Some(range) if range.end == chunk.start as i64 => range.end = chunk.end as i64,
+                    _ => ranges.push(FileRange {
+                        start: chunk.start as i64,
+                        end: chunk.end as i64,
+                    }),
+                }
+            }
+
+            split_file_partitions.extend(ranges.into_iter().map(|range| PartitionedFile {
+                range: Some(range),
+                ..f.clone()
+            }));
+        }
+
+        Ok(split_file_partitions)
+    }
+
+    /// Remove the files whose bloom filter sidecars rule out one of the lookups, building the
+    /// sidecars of the files that don't have one yet.
+    async fn prune_files_by_identifier(
+        &self,
+        state: &dyn Session,
+        url: &ListingTableUrl,
+        file_partitions: Vec<PartitionedFile>,
+        predicates: &[IdentifierPredicate],
+    ) -> Result<Vec<PartitionedFile>> {
+        let index = match self.config.options.identifier_index() {
+            Some(index) if self.config.bloom_filters && !predicates.is_empty() => index,
+            _ => return Ok(file_partitions),
+        };
+
+        let object_store = state.runtime_env().object_store(url.object_store())?;
+
+        let file_schema = self.table_schema.file_schema()?;
+        let column_index = file_schema.index_of(index.column)?;
+
+        let mut pruned_file_partitions = Vec::with_capacity(file_partitions.len());
+
+        for f in file_partitions {
+            let filter = match read_bloom_filter(&object_store, &f.object_meta).await? {
+                Some(filter) => filter,
+                None => {
+                    let file = PartitionedFile {
+                        partition_values: Vec::new(),
+                        range: None,
+                        ..f.clone()
+                    };
+
+                    let conf = FileScanConfigBuilder::new(
+                        url.object_store(),
+                        Arc::clone(&file_schema),
+                        vec![vec![file]],
+                    )
+                    .projection_option(Some(vec![column_index]))
+                    .build();
+
+                    let plan = self.config.options.create_physical_plan(conf).await?;
+
+                    build_bloom_filter(
+                        &object_store,
+                        &f.object_meta,
+                        &index,
+                        plan,
+                        Arc::new(TaskContext::from(state)),
+                    )
+                    .await?
+                }
+            };
+
+            if predicates.iter().all(|p| p.may_match(&filter)) {
+                pruned_file_partitions.push(f);
+            }
+        }
+
+        Ok(pruned_file_partitions)
+    }
+
+    /// Get the exact statistics of the files, scanning the ones that aren't cached yet, or unknown
+    /// statistics if any file is too large
+    async fn exact_statistics(
+        &self,
+        state: &dyn Session,
+        url: &ListingTableUrl,
+        file_partitions: &[PartitionedFile],
+    ) -> Result<Statistics> {
+        let file_schema = self.table_schema.file_schema()?;
+        let max_file_size = self.config.exact_statistics_max_file_size;
+
+        let small_files = max_file_size > 0
+            && file_partitions
+                .iter()
+                .all(|f| f.range.is_none() && f.object_meta.size <= max_file_size);
+
+        if !small_files {
+            return Ok(Statistics::new_unknown(&file_schema));
+        }
+
+        let mut statistics = Vec::with_capacity(file_partitions.len());
+
+        for f in file_partitions {
+            let file_statistics = match self.collected_statistics.get(&f.object_meta) {
+                Some(file_statistics) => file_statistics,
+                None => {
+                    let file = PartitionedFile {
+                        partition_values: Vec::new(),
+                        ..f.clone()
+                    };
+
+                    let conf = FileScanConfigBuilder::new(
+                        url.object_store(),
+                        Arc::clone(&file_schema),
+                        vec![vec![file]],
+                    )
+                    .build();
+
+                    let plan = self.config.options.create_physical_plan(conf).await?;
+
+                    self.collected_statistics
+                        .collect(&f.object_meta, plan, Arc::new(TaskContext::from(state)))
+                        .await?
+                }
+            };
+
+            statistics.push(file_statistics);
+        }
+
+        Ok(merge_file_statistics(&file_schema, &statistics))
+    }
+
+    fn file_scan_config(
+        &self,
+        url: &ListingTableUrl,
+        mut file_partitions: Vec<PartitionedFile>,
+        projection: Option<&Vec<usize>>,
+        limit: Option<usize>,
+    ) -> Result<FileScanConfig> {
+        let mut table_partition_cols = self.config.options.table_partition_cols().to_vec();
+
+        // The file provenance columns are partition columns, so the file stream attaches them to
+        // the records of each file.
+        if self.config.provenance_columns {
+            table_partition_cols.extend(file_provenance_fields());
+
+            for file in file_partitions.iter_mut() {
+                add_file_provenance_values(&url.object_store(), file);
+            }
+        }
+
+        let file_scan_config = FileScanConfigBuilder::new(
+            url.object_store(),
+            self.table_schema.file_schema()?,
+            vec![file_partitions],
+        )
+        .projection_option(projection.cloned())
+        .table_partition_cols(table_partition_cols)
+        .limit_option(limit)
+        .build();
+
+        Ok(file_scan_config)
+    }
+}
+
+#[async_trait]
+impl<O: ExonFileFormatOptions> TableProvider for ExonListingTable<O> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        let table_schema = self.table_schema.table_schema();
+
+        if !self.config.provenance_columns {
+            return table_schema;
+        }
+
+        let fields = table_schema
+            .fields()
+            .iter()
+            .map(|f| f.as_ref().clone())
+            .chain(provenance_fields())
+            .collect::<Vec<Field>>();
+
+        Arc::new(Schema::new_with_metadata(
+            fields,
+            table_schema.metadata().clone(),
+        ))
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        let filter_name = self
+            .config
+            .options
+            .region_index()
+            .map(|region_index| region_index.filter_name);
+
+        Ok(filters
+            .iter()
+            .map(|f| {
+                let (pushdown, reason) = match f {
+                    // Only a literal region with the column arguments can be applied by the
+                    // scan, other calls of the region filter are evaluated after it.
+                    Expr::ScalarFunction(s) if Some(s.name()) == filter_name => {
+                        let applied = matches!(s.args.len(), 2 | 3)
+                            && matches!(s.args[0], Expr::Literal(ScalarValue::Utf8(Some(_))));
+
+                        if applied {
+                            (TableProviderFilterPushDown::Exact, "region")
+                        } else {
+                            (TableProviderFilterPushDown::Unsupported, "region_shape")
+                        }
+                    }
+                    _ if self.sequence_filter([*f]).is_some() => {
+                        (TableProviderFilterPushDown::Inexact, "sequence_filter")
+                    }
+                    _ if !self.identifier_predicates([*f]).is_empty() => (
+                        TableProviderFilterPushDown::Inexact,
+                        if self.config.bloom_filters {
+                            "identifier_bloom_filter"
+                        } else {
+                            "identifier_index"
+                        },
+                    ),
+                    _ => (
+                        filter_matches_partition_cols(
+                            f,
+                            self.config.options.table_partition_cols(),
+                        ),
+                        "partition",
+                    ),
+                };
+
+                tracing::debug!(
+                    target: PLANNER_EVENT_TARGET,
+                    event = "filter_pushdown",
+                    filter = %f,
+                    pushdown = ?pushdown,
+                    reason,
+                );
+
+                pushdown
+            })
+            .collect())
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let url = self
+            .config
+            .first_table_path()
+            .ok_or(DataFusionError::Execution(
+                "No table paths found in the configuration".to_string(),
+            ))?;
+
+        let object_store = state.runtime_env().object_store(url.object_store())?;
+
+        let region = self.regions(filters)?.pop();
+
+        let provenance = self.config.provenance_columns.then(|| {
+            ProvenanceProjection::new(self.table_schema.table_schema().fields().len(), projection)
+        });
+
+        let scan_projection = provenance.as_ref().map(|p| p.scan_projection());
+        let projection = scan_projection.as_ref().or(projection);
+
+        let file_list = pruned_partition_list(
+            &object_store,
+            url,
+            filters,
+            self.config.options.file_extension(),
+            self.config.options.table_partition_cols(),
+        )
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        let n_listed = file_list.len();
+
+        let identifier_predicates = self.identifier_predicates(filters);
+
+        let file_list = self
+            .prune_files_by_identifier(state, url, file_list, &identifier_predicates)
+            .await?;
+
+        tracing::debug!(
+            target: PLANNER_EVENT_TARGET,
+            event = "files_pruned",
+            table_path = %url,
+            listed = n_listed,
+            remaining = file_list.len(),
+            region = ?region,
+        );
+
+        let plan = match (region, self.config.options.region_index()) {
+            (Some(region), Some(region_index)) => {
+                let mut file_partitions = Vec::new();
+
+                for f in file_list {
+                    let file_byte_range = augment_partitioned_file_with_byte_range(
+                        Arc::clone(&object_store),
+                        &f,
+                        &region,
+                        &region_index.indexed_file,
+                    )
+                    .await?;
+
+                    file_partitions.extend(file_byte_range);
+                }
+
+                let file_scan_config =
+                    self.file_scan_config(url, file_partitions, projection, limit)?;
+
+                self.config
+                    .options
+                    .create_physical_plan_with_region(file_scan_config, region)
+                    .await?
+            }
+            _ => {
+                let mut file_partitions = file_list;
+
+                // Files are read up to their listed size, so an appended-to file can be resumed
+                // where this scan ended.
+                if let Some(offset) = self.config.start_after_offset {
+                    file_partitions.retain(|f| f.object_meta.size as u64 > offset);
+
+                    for f in file_partitions.iter_mut() {
+                        f.range = Some(FileRange {
+                            start: offset as i64,
+                            end: f.object_meta.size as i64,
+                        });
+                    }
+                }
+
+                let statistics = self.exact_statistics(state, url, &file_partitions).await?;
+
+                let file_partitions = self
+                    .split_files_by_identifier(state, url, file_partitions, &identifier_predicates)
+                    .await?;
+
+                let mut file_scan_config =
+                    self.file_scan_config(url, file_partitions, projection, limit)?;
+
+                let sequence_filter = self.sequence_filter(filters);
+                let identifier_filter = self.identifier_filter(&identifier_predicates);
+
+                match (sequence_filter, identifier_filter) {
+                    (Some(sequence_filter), _) => {
+                        file_scan_config.statistics = statistics.to_inexact();
+
+                        self.config
+                            .options
+                            .create_physical_plan_with_sequence_filter(
+                                file_scan_config,
+                                sequence_filter,
+                            )
+                            .await?
+                    }
+                    (None, Some(identifier_filter)) => {
+                        file_scan_config.statistics = statistics.to_inexact();
+
+                        self.config
+                            .options
+                            .create_physical_plan_with_identifier_filter(
+                                file_scan_config,
+                                identifier_filter,
+                            )
+                            .await?
+                    }
+                    (None, None) => {
+                        file_scan_config.statistics = statistics;
+
+                        self.config
+                            .options
+                            .create_physical_plan(file_scan_config)
+                            .await?
+                    }
+                }
+            }
+        };
+
+        match provenance {
+            Some(provenance) => provenance.project(plan, &self.schema()),
+            None => Ok(plan),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::{
+        common::stats::Precision,
+        datasource::{
+            file_format::file_compression_type::FileCompressionType, listing::ListingTableUrl,
+            TableProvider,
+        },
+        functions_nested::expr_fn::{array_has, array_has_any, make_array},
+        logical_expr::{expr::ScalarFunction, ScalarUDF, TableProviderFilterPushDown},
+        physical_plan::collect,
+        prelude::{col, lit, Expr},
+    };
+    use exon_test::test_path;
+
+    use crate::{
+        datasources::{
+            bed::table_provider::ListingBEDTableOptions,
+            exon_listing_table_options::ExonListingConfig,
+            vcf::{ListingVCFTableOptions, VCFScan},
+        },
+        udfs::vcf::vcf_region_filter::VCFRegionFilterUDF,
+        ExonSession,
+    };
+
+    use super::ExonListingTable;
+
+    #[tokio::test]
+    async fn test_indexed_table_requires_region() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        let state = ctx.session.state();
+
+        let table_path = test_path("vcf", "index.vcf.gz");
+        let table_path = ListingTableUrl::parse(table_path.to_str().ok_or("Invalid path")?)?;
+
+        let options = ListingVCFTableOptions::new(FileCompressionType::GZIP, true);
+        let config = ExonListingConfig::new_with_options(table_path, options);
+
+        let table = ExonListingTable::try_new_with_inferred_schema(&state, config).await?;
+
+        let err = table.scan(&state, None, &[], None).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("INDEXED_VCF table requires a region filter"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_region_filter_pushdown_shapes() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        let state = ctx.session.state();
+
+        let table_path = test_path("vcf", "index.vcf.gz");
+        let table_path = ListingTableUrl::parse(table_path.to_str().ok_or("Invalid path")?)?;
+
+        let options = ListingVCFTableOptions::new(FileCompressionType::GZIP, true);
+        let config = ExonListingConfig::new_with_options(table_path, options);
+
+        let table = ExonListingTable::try_new_with_inferred_schema(&state, config).await?;
+
+        let udf = std::sync::Arc::new(ScalarUDF::from(VCFRegionFilterUDF::default()));
+        let region_filter = |args: Vec<Expr>| {
+            Expr::ScalarFunction(ScalarFunction::new_udf(std::sync::Arc::clone(&udf), args))
+        };
+
+        let literal_region = region_filter(vec![lit("1"), col("chrom")]);
+        let literal_region_with_pos = region_filter(vec![lit("1:1-100"), col("chrom"), col("pos")]);
+        let column_region = region_filter(vec![col("id"), col("chrom")]);
+        let region_only = region_filter(vec![lit("1")]);
+
+        let pushdown = table.supports_filters_pushdown(&[
+            &literal_region,
+            &literal_region_with_pos,
+            &column_region,
+            &region_only,
+        ])?;
+        assert_eq!(
+            pushdown,
+            vec![
+                TableProviderFilterPushDown::Exact,
+                TableProviderFilterPushDown::Exact,
+                TableProviderFilterPushDown::Unsupported,
+                TableProviderFilterPushDown::Unsupported,
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unindexed_table_pushdown() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        let state = ctx.session.state();
+
+        let table_path = test_path("bed", "test.bed");
+        let table_path = ListingTableUrl::parse(table_path.to_str().ok_or("Invalid path")?)?;
+
+        let options = ListingBEDTableOptions::new(FileCompressionType::UNCOMPRESSED);
+        let config = ExonListingConfig::new_with_options(table_path, options);
+
+        let table = ExonListingTable::try_new_with_inferred_schema(&state, config).await?;
+
+        let filter = col("start").gt(lit(10));
+        let pushdown = table.supports_filters_pushdown(&[&filter])?;
+        assert_eq!(pushdown, vec![TableProviderFilterPushDown::Unsupported]);
+
+        let plan = table.scan(&state, None, &[], None).await?;
+        assert_eq!(plan.schema().fields().len(), 12);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_exact_statistics_for_small_files() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        let state = ctx.session.state();
+
+        let table_path = test_path("bed", "test.bed");
+        let table_path = ListingTableUrl::parse(table_path.to_str().ok_or("Invalid path")?)?;
+
+        for (max_file_size, num_rows) in [
+            (0, Precision::Absent),
+            (100, Precision::Absent),
+            (1024, Precision::Exact(10)),
+        ] {
+            let options = ListingBEDTableOptions::new(FileCompressionType::UNCOMPRESSED);
+            let config = ExonListingConfig::new_with_options(table_path.clone(), options)
+                .with_exact_statistics_max_file_size(max_file_size);
+
+            let table = ExonListingTable::try_new_with_inferred_schema(&state, config).await?;
+
+            let plan = table.scan(&state, Some(&vec![0, 6]), &[], None).await?;
+            let statistics = plan.statistics()?;
+
+            assert_eq!(statistics.num_rows, num_rows);
+
+            if max_file_size == 1024 {
+                assert_eq!(
+                    statistics.column_statistics[0].null_count,
+                    Precision::Exact(0)
+                );
+                assert_eq!(
+                    statistics.column_statistics[1].null_count,
+                    Precision::Exact(10)
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_prune_files_by_identifier() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        let state = ctx.session.state();
+
+        let directory = std::env::temp_dir().join("test_prune_files_by_identifier");
+        std::fs::create_dir_all(&directory)?;
+
+        for (name, id) in [("a.vcf", "rs1"), ("b.vcf", "rs2")] {
+            std::fs::write(
+                directory.join(name),
+                format!(
+                    "##fileformat=VCFv4.3\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n1\t100\t{}\tA\tG\t.\t.\t.\n",
+                    id
+                ),
+            )?;
+
+            let _ = std::fs::remove_file(directory.join(format!("{}.bloom", name)));
+        }
+
+        let table_path = format!("{}/", directory.to_str().ok_or("Invalid path")?);
+        let table_path = ListingTableUrl::parse(table_path)?;
+
+        let options = ListingVCFTableOptions::new(FileCompressionType::UNCOMPRESSED, false);
+        let config =
+            ExonListingConfig::new_with_options(table_path, options).with_bloom_filters(true);
+
+        let table = ExonListingTable::try_new_with_inferred_schema(&state, config).await?;
+
+        let filter = array_has(col("id"), lit("rs2"));
+        let pushdown = table.supports_filters_pushdown(&[&filter])?;
+        assert_eq!(pushdown, vec![TableProviderFilterPushDown::Inexact]);
+
+        let plan = table.scan(&state, None, &[filter], None).await?;
+        let scan = plan
+            .as_any()
+            .downcast_ref::<VCFScan>()
+            .ok_or("Expected a VCF scan")?;
+
+        let files = scan
+            .base_config()
+            .file_groups
+            .iter()
+            .flatten()
+            .map(|f| f.object_meta.location.filename().map(String::from))
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec![Some("b.vcf".to_string())]);
+
+        assert!(directory.join("a.vcf.bloom").exists());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_identifier_index_prefilter() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        let state = ctx.session.state();
+
+        let directory = std::env::temp_dir().join("test_identifier_index_prefilter");
+        std::fs::create_dir_all(&directory)?;
+
+        let records = (0..100)
+            .map(|i| format!("1\t{}\trs{}\tA\tG\t.\t.\t.\n", i + 1, i))
+            .collect::<String>();
+
+        std::fs::write(
+            directory.join("a.vcf"),
+            format!(
+                "##fileformat=VCFv4.3\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n{}",
+                records
+            ),
+        )?;
+        let _ = std::fs::remove_file(directory.join("a.vcf.ids"));
+
+        let table_path = format!("{}/", directory.to_str().ok_or("Invalid path")?);
+        let table_path = ListingTableUrl::parse(table_path)?;
+
+        let options = ListingVCFTableOptions::new(FileCompressionType::UNCOMPRESSED, false);
+        let config =
+            ExonListingConfig::new_with_options(table_path, options).with_identifier_indexes(true);
+
+        let table = ExonListingTable::try_new_with_inferred_schema(&state, config).await?;
+
+        let filter = array_has_any(col("id"), make_array(vec![lit("rs7"), lit("rs42")]));
+        let pushdown = table.supports_filters_pushdown(&[&filter])?;
+        assert_eq!(pushdown, vec![TableProviderFilterPushDown::Inexact]);
+
+        let plan = table.scan(&state, None, &[filter], None).await?;
+        let scan = plan
+            .as_any()
+            .downcast_ref::<VCFScan>()
+            .ok_or("Expected a VCF scan")?;
+
+        let ranges = scan
+            .base_config()
+            .file_groups
+            .iter()
+            .flatten()
+            .map(|f| f.range.is_some())
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, vec![true]);
+
+        assert!(directory.join("a.vcf.ids").exists());
+
+        let batches = collect(plan, ctx.session.task_ctx()).await?;
+        let num_rows = batches.iter().map(|b| b.num_rows()).sum::<usize>();
+        assert_eq!(num_rows, 2);
+
+        Ok(())
+    }
+}
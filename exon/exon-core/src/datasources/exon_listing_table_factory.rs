@@ -24,11 +24,13 @@ use datafusion::{
     },
     logical_expr::CreateExternalTable,
 };
+use exon_common::{canonical_genome_build, RecordSampling, SampleMethod};
 use url::Url;
 
 use crate::{
     config::extract_config_from_state,
     datasources::{fasta::FASTAOptions, ExonFileType},
+    planner_events::{field_names, PLANNER_EVENT_TARGET},
     ExonError, ExonRuntimeEnvExt,
 };
 
@@ -37,16 +39,35 @@ use super::{
     bcf::table_provider::{ListingBCFTable, ListingBCFTableOptions},
     bed::table_provider::{ListingBEDTable, ListingBEDTableOptions},
     bigwig,
+    classification::table_provider::{
+        ListingClassificationTable, ListingClassificationTableOptions,
+    },
+    counts::{
+        table_provider::{ListingCountsTable, ListingCountsTableOptions},
+        CountsLayout,
+    },
     cram::table_provider::{ListingCRAMTableConfig, ListingCRAMTableOptions},
     exon_listing_table_options::ExonListingConfig,
+    explicit_schema::ExplicitSchemaTable,
     fasta::table_provider::{ListingFASTATable, ListingFASTATableOptions},
     fastq::table_provider::{ListingFASTQTable, ListingFASTQTableOptions},
     gff::table_provider::{ListingGFFTable, ListingGFFTableOptions},
     gtf::table_provider::{ListingGTFTable, ListingGTFTableOptions},
     hmmdomtab::table_provider::{ListingHMMDomTabTable, ListingHMMDomTabTableOptions},
+    illumina::table_provider::{ListingIlluminaTable, ListingIlluminaTableOptions},
+    mtx::table_provider::{ListingMTXTable, ListingMTXTableOptions},
+    pod5::table_provider::{ListingPod5Table, ListingPod5TableOptions},
+    quantification::table_provider::{
+        ListingQuantificationTable, ListingQuantificationTableOptions,
+    },
     sam::table_provider::{ListingSAMTable, ListingSAMTableOptions},
     sdf::{ListingSDFTable, ListingSDFTableOptions},
+    sequencing_summary::table_provider::{
+        ListingSequencingSummaryTable, ListingSequencingSummaryTableOptions,
+    },
+    table_object_store::{TableObjectStore, TableObjectStoreTable},
     vcf::{ListingVCFTable, ListingVCFTableOptions},
+    vcf_zarr::table_provider::{ListingVCFZarrTable, ListingVCFZarrTableOptions},
 };
 
 #[cfg(feature = "fcs")]
@@ -61,6 +82,108 @@ use super::genbank::table_provider::{ListingGenbankTable, ListingGenbankTableOpt
 const FILE_EXTENSION_OPTION: &str = "format.file_extension";
 const INDEXED_OPTION: &str = "format.indexed";
 const INDEXED_TRUE_VALUE: &str = "true";
+const START_AFTER_OFFSET_OPTION: &str = "format.start_after_offset";
+const GENOME_BUILD_OPTION: &str = "format.genome_build";
+const SAMPLES_OPTION: &str = "format.samples";
+const SAMPLE_FRACTION_OPTION: &str = "format.sample_fraction";
+const SAMPLE_SIZE_OPTION: &str = "format.sample_size";
+const SAMPLE_REGION_SIZE_OPTION: &str = "format.sample_region_size";
+const SAMPLE_SEED_OPTION: &str = "format.sample_seed";
+const COUNTS_LAYOUT_OPTION: &str = "format.counts_layout";
+
+/// Parse the byte offset to resume a scan from, which only makes sense for uncompressed files.
+fn start_after_offset(
+    options: &HashMap<String, String>,
+    file_compression_type: FileCompressionType,
+) -> datafusion::common::Result<Option<u64>> {
+    let Some(offset) = options.get(START_AFTER_OFFSET_OPTION) else {
+        return Ok(None);
+    };
+
+    if file_compression_type != FileCompressionType::UNCOMPRESSED {
+        return Err(datafusion::error::DataFusionError::Execution(
+            "start_after_offset requires uncompressed files".to_string(),
+        ));
+    }
+
+    let offset = offset.parse::<u64>().map_err(|_| {
+        datafusion::error::DataFusionError::Execution(format!(
+            "start_after_offset must be a byte offset, got {}",
+            offset
+        ))
+    })?;
+
+    Ok(Some(offset))
+}
+
+/// The samples of a VCF or BCF table to decode the FORMAT values of, from a comma separated
+/// `samples` option, e.g. `'NA12878,NA12891'`.
+fn samples(options: &HashMap<String, String>) -> Option<Vec<String>> {
+    options.get(SAMPLES_OPTION).map(|samples| {
+        samples
+            .split(',')
+            .map(|sample| sample.trim().to_string())
+            .filter(|sample| !sample.is_empty())
+            .collect()
+    })
+}
+
+/// Parse an option, which is described by `kind` in the error for an invalid value.
+fn parse_option<T: FromStr>(
+    options: &HashMap<String, String>,
+    option: &str,
+    kind: &str,
+) -> datafusion::common::Result<Option<T>> {
+    options
+        .get(option)
+        .map(|value| {
+            value.trim().parse::<T>().map_err(|_| {
+                datafusion::error::DataFusionError::Execution(format!(
+                    "{} must be {}, got {}",
+                    option.trim_start_matches("format."),
+                    kind,
+                    value
+                ))
+            })
+        })
+        .transpose()
+}
+
+/// The sample of the records of a table to read, like `TABLESAMPLE`, from the `sample_fraction`
+/// option, or the `sample_size` option for a reservoir sample of each file, or of each window of
+/// `sample_region_size` bases. The `sample_seed` option picks another sample.
+fn record_sampling(
+    options: &HashMap<String, String>,
+) -> datafusion::common::Result<Option<RecordSampling>> {
+    let fraction = parse_option::<f64>(options, SAMPLE_FRACTION_OPTION, "a fraction")?;
+    let size = parse_option::<usize>(options, SAMPLE_SIZE_OPTION, "a number of records")?;
+    let region_size = parse_option::<u64>(options, SAMPLE_REGION_SIZE_OPTION, "a number of bases")?;
+    let seed = parse_option::<u64>(options, SAMPLE_SEED_OPTION, "a non-negative integer")?;
+
+    let method =
+        match (fraction, size, region_size) {
+            (None, None, None) => return Ok(None),
+            (Some(fraction), None, None) if fraction > 0.0 && fraction <= 1.0 => {
+                SampleMethod::Fraction(fraction)
+            }
+            (None, Some(size), None) if size > 0 => SampleMethod::Reservoir(size),
+            (None, Some(size), Some(region_size)) if size > 0 && region_size > 0 => {
+                SampleMethod::Stratified { size, region_size }
+            }
+            _ => return Err(datafusion::error::DataFusionError::Execution(
+                "Sample tables with a sample_fraction in (0, 1], or a positive sample_size and \
+                 optionally a positive sample_region_size"
+                    .to_string(),
+            )),
+        };
+
+    let sampling = RecordSampling::new(method);
+
+    Ok(Some(match seed {
+        Some(seed) => sampling.with_seed(seed),
+        None => sampling,
+    }))
+}
 
 /// A `ListingTableFactory` that adapts Exon FileFormats to `TableProvider`s.
 #[derive(Debug, Clone, Default)]
@@ -86,13 +209,25 @@ impl ExonListingTableFactory {
 
         let exon_config_extension = extract_config_from_state(state)?;
 
+        // A declared genome build takes precedence over the one in the file headers.
+        let genome_build = options
+            .get(GENOME_BUILD_OPTION)
+            .map(|genome_build| canonical_genome_build(genome_build.as_str()));
+
         match file_type {
             ExonFileType::BAM => {
                 let options = ListingBAMTableOptions::default()
                     .with_table_partition_cols(table_partition_cols)
-                    .with_tag_as_struct(exon_config_extension.bam_parse_tags);
+                    .with_tag_as_struct(exon_config_extension.bam_parse_tags)
+                    .with_pack_sequences(exon_config_extension.pack_sequences)
+                    .with_pacbio_columns(exon_config_extension.bam_pacbio_columns)
+                    .with_compact_types(exon_config_extension.alignment_compact_types)
+                    .with_sampling(record_sampling(options)?);
 
-                let table_schema = options.infer_schema(state, &table_path).await?;
+                let table_schema = options
+                    .infer_schema(state, &table_path)
+                    .await?
+                    .with_genome_build(genome_build.as_deref());
 
                 let config = ExonListingConfig::new_with_options(table_path, options);
                 let table = ListingBAMTable::new(config, table_schema);
@@ -101,11 +236,18 @@ impl ExonListingTableFactory {
             }
             ExonFileType::BED => {
                 let options = ListingBEDTableOptions::new(file_compression_type)
-                    .with_table_partition_cols(table_partition_cols);
+                    .with_table_partition_cols(table_partition_cols)
+                    .with_strand_encoding(exon_config_extension.strand_encoding()?);
 
-                let table_schema = options.infer_schema()?;
+                let table_schema = options
+                    .infer_schema()?
+                    .with_genome_build(genome_build.as_deref());
 
-                let config = ExonListingConfig::new_with_options(table_path, options);
+                let config = ExonListingConfig::new_with_options(table_path, options)
+                    .with_provenance_columns(exon_config_extension.provenance_columns)
+                    .with_exact_statistics_max_file_size(
+                        exon_config_extension.exact_statistics_max_file_size,
+                    );
                 let table = ListingBEDTable::new(config, table_schema);
 
                 Ok(Arc::new(table))
@@ -113,9 +255,13 @@ impl ExonListingTableFactory {
             ExonFileType::SAM => {
                 let options = ListingSAMTableOptions::default()
                     .with_table_partition_cols(table_partition_cols)
-                    .with_tag_as_struct(exon_config_extension.sam_parse_tags);
+                    .with_tag_as_struct(exon_config_extension.sam_parse_tags)
+                    .with_compact_types(exon_config_extension.alignment_compact_types);
 
-                let table_schema = options.infer_schema(state, &table_path).await?;
+                let table_schema = options
+                    .infer_schema(state, &table_path)
+                    .await?
+                    .with_genome_build(genome_build.as_deref());
 
                 let config = ExonListingConfig::new_with_options(table_path, options);
                 let table = ListingSAMTable::new(config, table_schema);
@@ -131,33 +277,72 @@ impl ExonListingTableFactory {
 
                 let options = ListingGFFTableOptions::new(file_compression_type)
                     .with_indexed(true)
-                    .with_table_partition_cols(table_partition_cols);
-
-                let file_schema = options.infer_schema().await?;
-
-                let config = ExonListingConfig::new_with_options(table_path, options);
+                    .with_table_partition_cols(table_partition_cols)
+                    .with_strand_encoding(exon_config_extension.strand_encoding()?)
+                    .with_dictionary_feature_types(
+                        exon_config_extension.dictionary_encode_feature_types,
+                    );
+
+                let file_schema = options
+                    .infer_schema()
+                    .await?
+                    .with_genome_build(genome_build.as_deref());
+
+                let config = ExonListingConfig::new_with_options(table_path, options)
+                    .with_provenance_columns(exon_config_extension.provenance_columns)
+                    .with_exact_statistics_max_file_size(
+                        exon_config_extension.exact_statistics_max_file_size,
+                    )
+                    .with_bloom_filters(exon_config_extension.identifier_bloom_filters);
                 let table = ListingGFFTable::new(config, file_schema);
 
                 Ok(Arc::new(table))
             }
             ExonFileType::GFF => {
+                let offset = start_after_offset(options, file_compression_type)?;
+
                 let options = ListingGFFTableOptions::new(file_compression_type)
                     .with_indexed(
                         options.get(INDEXED_OPTION) == Some(&INDEXED_TRUE_VALUE.to_string()),
                     )
                     .with_file_extension(options.get(FILE_EXTENSION_OPTION).cloned())
-                    .with_table_partition_cols(table_partition_cols);
+                    .with_table_partition_cols(table_partition_cols)
+                    .with_strand_encoding(exon_config_extension.strand_encoding()?)
+                    .with_dictionary_feature_types(
+                        exon_config_extension.dictionary_encode_feature_types,
+                    );
+
+                let file_schema = options
+                    .infer_schema()
+                    .await?
+                    .with_genome_build(genome_build.as_deref());
+
+                let config = ExonListingConfig::new_with_options(table_path, options)
+                    .with_provenance_columns(exon_config_extension.provenance_columns)
+                    .with_exact_statistics_max_file_size(
+                        exon_config_extension.exact_statistics_max_file_size,
+                    )
+                    .with_start_after_offset(offset)
+                    .with_bloom_filters(exon_config_extension.identifier_bloom_filters);
+                let table = ListingGFFTable::new(config, file_schema);
 
-                let file_schema = options.infer_schema().await?;
+                Ok(Arc::new(table))
+            }
+            #[cfg(feature = "mzml")]
+            ExonFileType::MZML => {
+                let options = ListingMzMLTableOptions::new(file_compression_type)
+                    .with_table_partition_cols(table_partition_cols);
+                let schema = options.infer_schema().await?;
 
                 let config = ExonListingConfig::new_with_options(table_path, options);
-                let table = ListingGFFTable::new(config, file_schema);
+                let table = ListingMzMLTable::new(config, schema);
 
                 Ok(Arc::new(table))
             }
             #[cfg(feature = "mzml")]
-            ExonFileType::MZML => {
+            ExonFileType::IMZML => {
                 let options = ListingMzMLTableOptions::new(file_compression_type)
+                    .with_imzml(true)
                     .with_table_partition_cols(table_partition_cols);
                 let schema = options.infer_schema().await?;
 
@@ -176,11 +361,108 @@ impl ExonListingTableFactory {
 
                 Ok(Arc::new(table))
             }
-            ExonFileType::GTF => {
-                let options = ListingGTFTableOptions::new(file_compression_type)
+            ExonFileType::POD5 => {
+                let options = ListingPod5TableOptions::new(file_compression_type)
+                    .with_table_partition_cols(table_partition_cols);
+                let table_schema = options.infer_schema().await?;
+
+                let config = ExonListingConfig::new_with_options(table_path, options);
+                let table = ListingPod5Table::new(config, table_schema);
+
+                Ok(Arc::new(table))
+            }
+            ExonFileType::SequencingSummary => {
+                let options = ListingSequencingSummaryTableOptions::new(file_compression_type)
+                    .with_table_partition_cols(table_partition_cols);
+                let table_schema = options.infer_schema(state, &table_path).await?;
+
+                let config = ExonListingConfig::new_with_options(table_path, options);
+                let table = ListingSequencingSummaryTable::new(config, table_schema);
+
+                Ok(Arc::new(table))
+            }
+            ExonFileType::MTX => {
+                let options = ListingMTXTableOptions::new(file_compression_type)
+                    .with_table_partition_cols(table_partition_cols);
+                let table_schema = options.infer_schema().await?;
+
+                let config = ExonListingConfig::new_with_options(table_path, options);
+                let table = ListingMTXTable::new(config, table_schema);
+
+                Ok(Arc::new(table))
+            }
+            ExonFileType::Illumina(kind) => {
+                let options = ListingIlluminaTableOptions::new(kind)
+                    .with_table_partition_cols(table_partition_cols);
+                let table_schema = options.infer_schema();
+
+                let config = ExonListingConfig::new_with_options(table_path, options);
+                let table = ListingIlluminaTable::new(config, table_schema);
+
+                Ok(Arc::new(table))
+            }
+            ExonFileType::Classification(kind) => {
+                let options = ListingClassificationTableOptions::new(kind, file_compression_type)
+                    .with_file_extension(options.get(FILE_EXTENSION_OPTION).cloned())
                     .with_table_partition_cols(table_partition_cols);
                 let table_schema = options.infer_schema();
 
+                let config = ExonListingConfig::new_with_options(table_path, options);
+                let table = ListingClassificationTable::new(config, table_schema);
+
+                Ok(Arc::new(table))
+            }
+            ExonFileType::Counts => {
+                let layout = options
+                    .get(COUNTS_LAYOUT_OPTION)
+                    .map(|layout| layout.parse::<CountsLayout>())
+                    .transpose()?
+                    .unwrap_or_default();
+
+                let options = ListingCountsTableOptions::new(file_compression_type)
+                    .with_file_extension(options.get(FILE_EXTENSION_OPTION).cloned())
+                    .with_layout(layout)
+                    .with_table_partition_cols(table_partition_cols);
+                let table_schema = options.infer_schema(state, &table_path).await?;
+
+                let config = ExonListingConfig::new_with_options(table_path, options);
+                let table = ListingCountsTable::new(config, table_schema);
+
+                Ok(Arc::new(table))
+            }
+            ExonFileType::Quantification(kind) => {
+                let options = ListingQuantificationTableOptions::new(kind, file_compression_type)
+                    .with_table_partition_cols(table_partition_cols);
+                let table_schema = options.infer_schema();
+
+                let config = ExonListingConfig::new_with_options(table_path, options);
+                let table = ListingQuantificationTable::new(config, table_schema);
+
+                Ok(Arc::new(table))
+            }
+            ExonFileType::VCFZarr => {
+                let options = ListingVCFZarrTableOptions::new();
+                let table_schema = options
+                    .infer_schema(state, &table_path)
+                    .await?
+                    .with_genome_build(genome_build.as_deref());
+
+                let config = ExonListingConfig::new_with_options(table_path, options);
+                let table = ListingVCFZarrTable::new(config, table_schema);
+
+                Ok(Arc::new(table))
+            }
+            ExonFileType::GTF => {
+                let options = ListingGTFTableOptions::new(file_compression_type)
+                    .with_table_partition_cols(table_partition_cols)
+                    .with_strand_encoding(exon_config_extension.strand_encoding()?)
+                    .with_dictionary_feature_types(
+                        exon_config_extension.dictionary_encode_feature_types,
+                    );
+                let table_schema = options
+                    .infer_schema()
+                    .with_genome_build(genome_build.as_deref());
+
                 let config = ExonListingConfig::new_with_options(table_path, options);
                 let table = ListingGTFTable::new(config, table_schema);
 
@@ -198,8 +480,12 @@ impl ExonListingTableFactory {
             }
             ExonFileType::BCF => {
                 let options = ListingBCFTableOptions::default()
-                    .with_table_partition_cols(table_partition_cols);
-                let table_schema = options.infer_schema(state, &table_path).await?;
+                    .with_table_partition_cols(table_partition_cols)
+                    .with_samples(samples(options));
+                let table_schema = options
+                    .infer_schema(state, &table_path)
+                    .await?
+                    .with_genome_build(genome_build.as_deref());
 
                 let config = ExonListingConfig::new_with_options(table_path, options);
                 let table = ListingBCFTable::new(config, table_schema);
@@ -207,14 +493,33 @@ impl ExonListingTableFactory {
                 Ok(Arc::new(table))
             }
             ExonFileType::VCF => {
+                let offset = start_after_offset(options, file_compression_type)?;
+
                 let vcf_options = ListingVCFTableOptions::new(file_compression_type, false)
                     .with_table_partition_cols(table_partition_cols)
                     .with_parse_info(exon_config_extension.vcf_parse_info)
-                    .with_parse_formats(exon_config_extension.vcf_parse_formats);
+                    .with_parse_formats(exon_config_extension.vcf_parse_formats)
+                    .with_parse_structural_variants(
+                        exon_config_extension.vcf_parse_structural_variants,
+                    )
+                    .with_dictionary_encode_genotypes(
+                        exon_config_extension.vcf_dictionary_encode_genotypes,
+                    )
+                    .with_samples(samples(options));
 
-                let table_schema = vcf_options.infer_schema(state, &table_path).await?;
+                let table_schema = vcf_options
+                    .infer_schema(state, &table_path)
+                    .await?
+                    .with_genome_build(genome_build.as_deref());
 
-                let config = ExonListingConfig::new_with_options(table_path, vcf_options);
+                let config = ExonListingConfig::new_with_options(table_path, vcf_options)
+                    .with_provenance_columns(exon_config_extension.provenance_columns)
+                    .with_exact_statistics_max_file_size(
+                        exon_config_extension.exact_statistics_max_file_size,
+                    )
+                    .with_start_after_offset(offset)
+                    .with_bloom_filters(exon_config_extension.identifier_bloom_filters)
+                    .with_identifier_indexes(exon_config_extension.identifier_indexes);
 
                 let table = ListingVCFTable::new(config, table_schema);
                 Ok(Arc::new(table))
@@ -223,11 +528,26 @@ impl ExonListingTableFactory {
                 let vcf_options = ListingVCFTableOptions::new(file_compression_type, true)
                     .with_parse_info(exon_config_extension.vcf_parse_info)
                     .with_parse_formats(exon_config_extension.vcf_parse_formats)
-                    .with_table_partition_cols(table_partition_cols);
+                    .with_parse_structural_variants(
+                        exon_config_extension.vcf_parse_structural_variants,
+                    )
+                    .with_dictionary_encode_genotypes(
+                        exon_config_extension.vcf_dictionary_encode_genotypes,
+                    )
+                    .with_table_partition_cols(table_partition_cols)
+                    .with_samples(samples(options));
 
-                let table_schema = vcf_options.infer_schema(state, &table_path).await?;
+                let table_schema = vcf_options
+                    .infer_schema(state, &table_path)
+                    .await?
+                    .with_genome_build(genome_build.as_deref());
 
-                let config = ExonListingConfig::new_with_options(table_path, vcf_options);
+                let config = ExonListingConfig::new_with_options(table_path, vcf_options)
+                    .with_provenance_columns(exon_config_extension.provenance_columns)
+                    .with_exact_statistics_max_file_size(
+                        exon_config_extension.exact_statistics_max_file_size,
+                    )
+                    .with_bloom_filters(exon_config_extension.identifier_bloom_filters);
 
                 let table = ListingVCFTable::new(config, table_schema);
                 Ok(Arc::new(table))
@@ -236,9 +556,15 @@ impl ExonListingTableFactory {
                 let options = ListingBAMTableOptions::default()
                     .with_indexed(true)
                     .with_table_partition_cols(table_partition_cols)
-                    .with_tag_as_struct(exon_config_extension.bam_parse_tags);
+                    .with_tag_as_struct(exon_config_extension.bam_parse_tags)
+                    .with_pack_sequences(exon_config_extension.pack_sequences)
+                    .with_pacbio_columns(exon_config_extension.bam_pacbio_columns)
+                    .with_compact_types(exon_config_extension.alignment_compact_types);
 
-                let table_schema = options.infer_schema(state, &table_path).await?;
+                let table_schema = options
+                    .infer_schema(state, &table_path)
+                    .await?
+                    .with_genome_build(genome_build.as_deref());
 
                 let config = ExonListingConfig::new_with_options(table_path, options);
 
@@ -246,7 +572,8 @@ impl ExonListingTableFactory {
                 Ok(Arc::new(table))
             }
             ExonFileType::FASTA | ExonFileType::FA | ExonFileType::FAA | ExonFileType::FNA => {
-                let fasta_options = FASTAOptions::try_from(options)?;
+                let fasta_options =
+                    FASTAOptions::try_from(options)?.with_session_defaults(exon_config_extension);
 
                 let fasta_sequence_type = fasta_options.fasta_sequence_data_type()?;
                 let sequence_buffer_capacity = fasta_options.sequence_buffer_capacity()?;
@@ -266,24 +593,48 @@ impl ExonListingTableFactory {
             }
             ExonFileType::FASTQ | ExonFileType::FQ => {
                 let extension = options.get(FILE_EXTENSION_OPTION).map(|s| s.as_str());
+                let offset = start_after_offset(options, file_compression_type)?;
+                let sampling = record_sampling(options)?;
+
+                if let Some(SampleMethod::Stratified { .. }) = sampling.map(|s| s.method()) {
+                    return Err(datafusion::error::DataFusionError::Execution(
+                        "sample_region_size requires aligned records, e.g. a BAM table".to_string(),
+                    ));
+                }
+
+                // The exact statistics of a sampled table would count all of its records.
+                let exact_statistics_max_file_size = match sampling {
+                    Some(_) => 0,
+                    None => exon_config_extension.exact_statistics_max_file_size,
+                };
 
                 let options = ListingFASTQTableOptions::new(file_compression_type)
                     .with_table_partition_cols(table_partition_cols)
-                    .with_some_file_extension(extension);
+                    .with_some_file_extension(extension)
+                    .with_pack_sequences(exon_config_extension.pack_sequences)
+                    .with_sampling(sampling);
 
                 let schema = options.infer_schema();
 
-                let config = ExonListingConfig::new_with_options(table_path, options);
-                let table = ListingFASTQTable::try_new(config, schema)?;
+                let config = ExonListingConfig::new_with_options(table_path, options)
+                    .with_provenance_columns(exon_config_extension.provenance_columns)
+                    .with_exact_statistics_max_file_size(exact_statistics_max_file_size)
+                    .with_start_after_offset(offset)
+                    .with_sequence_filters(exon_config_extension.sequence_filter_pushdown);
+                let table = ListingFASTQTable::new(config, schema);
 
                 Ok(Arc::new(table))
             }
             ExonFileType::CRAM => {
                 let options = ListingCRAMTableOptions::try_from(options)?
                     .with_table_partition_cols(table_partition_cols)
-                    .with_tag_as_struct(exon_config_extension.cram_parse_tags);
+                    .with_tag_as_struct(exon_config_extension.cram_parse_tags)
+                    .with_compact_types(exon_config_extension.alignment_compact_types);
 
-                let table_schema = options.infer_schema(state, &table_path).await?;
+                let table_schema = options
+                    .infer_schema(state, &table_path)
+                    .await?
+                    .with_genome_build(genome_build.as_deref());
 
                 let config = ListingCRAMTableConfig::new(table_path, options);
 
@@ -298,7 +649,9 @@ impl ExonListingTableFactory {
                 let options = super::bigwig::value::ListingTableOptions::new()
                     .with_table_partition_cols(table_partition_cols);
 
-                let table_schema = options.infer_schema()?;
+                let table_schema = options
+                    .infer_schema()?
+                    .with_genome_build(genome_build.as_deref());
 
                 let config = ExonListingConfig::new_with_options(table_path, options);
                 let table = bigwig::value::ListingTable::new(config, table_schema);
@@ -322,7 +675,9 @@ impl ExonListingTableFactory {
                 let options = bigwig::zoom::ListingTableOptions::new(reduction_level)
                     .with_table_partition_cols(table_partition_cols);
 
-                let table_schema = options.infer_schema()?;
+                let table_schema = options
+                    .infer_schema()?
+                    .with_genome_build(genome_build.as_deref());
 
                 let config = bigwig::zoom::ListingTableConfig::new(table_path, options);
                 let table = bigwig::zoom::ListingTable::try_new(config, table_schema)?;
@@ -344,12 +699,14 @@ impl ExonListingTableFactory {
             ExonFileType::SDF => {
                 let options = ListingSDFTableOptions::default()
                     .with_file_compression_type(file_compression_type)
+                    .with_parse_structure(exon_config_extension.sdf_parse_structure)
                     .with_table_partition_cols(table_partition_cols);
 
                 let table_schema = options.infer_schema(state, &table_path).await?;
 
                 let config = ExonListingConfig::new_with_options(table_path, options);
-                let table = ListingSDFTable::new(config, table_schema);
+                let table = ListingSDFTable::new(config, table_schema)
+                    .with_property_index(exon_config_extension.sdf_property_index);
 
                 Ok(Arc::new(table))
             }
@@ -387,26 +744,65 @@ impl TableProviderFactory for ExonListingTableFactory {
 
         let file_type = ExonFileType::from_str(&cmd.file_type)?;
 
-        // Attempt to register the object store if it hasn't been registered yet.
+        // Register the object store if it hasn't been registered yet. It's shared by every table
+        // on the host, so a table that reads its files differently wraps it instead.
         let table_path = ListingTableUrl::parse(&cmd.location)?;
         let url: &Url = table_path.as_ref();
 
-        state
-            .runtime_env()
-            .exon_register_object_store_url(url)
-            .await?;
+        let exon_config = extract_config_from_state(state)?;
+        let runtime_env = state.runtime_env();
+
+        if runtime_env.object_store(&table_path).is_err() {
+            runtime_env
+                .exon_register_object_store_url_with_retry_policy(url, exon_config.retry_policy())
+                .await?;
+        }
+
+        // The schema is inferred through the table's store too.
+        let table_object_store = TableObjectStore::try_new(exon_config, runtime_env, url)?;
+        let table_state = table_object_store
+            .as_ref()
+            .map(|table_object_store| table_object_store.session_state(state))
+            .transpose()?;
+        let create_state: &dyn Session = match &table_state {
+            Some(table_state) => table_state,
+            None => state,
+        };
 
         let options = &cmd.options;
 
-        self.create_from_file_type(
-            state,
-            file_type,
-            file_compression_type,
-            cmd.location.clone(),
-            table_partition_cols,
-            options,
-        )
-        .await
+        let table = self
+            .create_from_file_type(
+                create_state,
+                file_type,
+                file_compression_type,
+                cmd.location.clone(),
+                table_partition_cols,
+                options,
+            )
+            .await?;
+
+        let table: Arc<dyn TableProvider> = match table_object_store {
+            Some(table_object_store) => {
+                Arc::new(TableObjectStoreTable::new(table, table_object_store))
+            }
+            None => table,
+        };
+
+        tracing::debug!(
+            target: PLANNER_EVENT_TARGET,
+            event = "schema_inferred",
+            file_type = %cmd.file_type,
+            location = %cmd.location,
+            columns = %field_names(&table.schema()),
+        );
+
+        // A column list is the table's contract, which the inferred schema is coerced into.
+        if schema.fields().is_empty() {
+            Ok(table)
+        } else {
+            Ok(Arc::new(ExplicitSchemaTable::try_new(table, schema)?))
+        }
     }
 }
 
@@ -421,7 +817,9 @@ mod tests {
     };
     use object_store::local::LocalFileSystem;
 
-    use crate::{datasources::ExonListingTableFactory, ExonSession};
+    use crate::{
+        datasources::ExonListingTableFactory, find_checksum_error, ChecksumKind, ExonSession,
+    };
 
     #[tokio::test]
     async fn test_in_catalog() -> Result<(), Box<dyn std::error::Error>> {
@@ -461,4 +859,68 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_verify_checksums() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = std::env::temp_dir().join("test_verify_checksums");
+        std::fs::create_dir_all(&temp_dir)?;
+
+        let fasta_path = temp_dir.join("test.fasta");
+        std::fs::copy(exon_test::test_path("fasta", "test.fasta"), &fasta_path)?;
+        std::fs::write(
+            temp_dir.join("test.fasta.md5"),
+            "00000000000000000000000000000000  test.fasta\n",
+        )?;
+
+        let ctx = ExonSession::new_exon()?;
+        ctx.session.sql("SET exon.verify_checksums = true").await?;
+
+        let sql = format!(
+            "CREATE EXTERNAL TABLE fasta_table STORED AS FASTA LOCATION '{}'",
+            fasta_path.to_str().ok_or("Invalid path")?
+        );
+        ctx.session.sql(&sql).await?.collect().await?;
+
+        let err = ctx
+            .session
+            .sql("SELECT * FROM fasta_table")
+            .await?
+            .collect()
+            .await
+            .unwrap_err();
+
+        let checksum_error = find_checksum_error(&err).ok_or("Expected a checksum error")?;
+        assert_eq!(checksum_error.kind, ChecksumKind::Md5);
+
+        // Verification is set per table, so a table created without it reads the same file, and
+        // the first table still verifies it.
+        ctx.session.sql("SET exon.verify_checksums = false").await?;
+
+        let sql = format!(
+            "CREATE EXTERNAL TABLE unverified_fasta_table STORED AS FASTA LOCATION '{}'",
+            fasta_path.to_str().ok_or("Invalid path")?
+        );
+        ctx.session.sql(&sql).await?.collect().await?;
+
+        let batches = ctx
+            .session
+            .sql("SELECT * FROM unverified_fasta_table")
+            .await?
+            .collect()
+            .await?;
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+
+        let err = ctx
+            .session
+            .sql("SELECT * FROM fasta_table")
+            .await?
+            .collect()
+            .await
+            .unwrap_err();
+        assert!(find_checksum_error(&err).is_some());
+
+        std::fs::remove_dir_all(temp_dir)?;
+
+        Ok(())
+    }
 }
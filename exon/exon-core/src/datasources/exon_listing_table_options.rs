@@ -140,6 +140,25 @@ pub struct ExonListingConfig<T> {
 
     /// The options for the table
     pub options: Arc<T>,
+
+    /// Whether to add the provenance columns, e.g. the file path and record index
+    pub provenance_columns: bool,
+
+    /// The byte offset to start reading files at, to resume ingesting appended-to files
+    pub start_after_offset: Option<u64>,
+
+    /// The size in bytes up to which files are scanned for exact statistics, 0 disables it
+    pub exact_statistics_max_file_size: usize,
+
+    /// Whether to prune files for identifier lookups with bloom filter sidecars
+    pub bloom_filters: bool,
+
+    /// Whether to prefilter records for identifier lookups, and to read only the chunks of
+    /// uncompressed files that may match with identifier index sidecars
+    pub identifier_indexes: bool,
+
+    /// Whether to push pattern filters on the sequence column down to the scan
+    pub sequence_filters: bool,
 }
 
 impl<T> ExonListingConfig<T> {
@@ -148,9 +167,55 @@ impl<T> ExonListingConfig<T> {
         Self {
             inner: ListingTableConfig::new(table_path),
             options: Arc::new(options),
+            provenance_columns: false,
+            start_after_offset: None,
+            exact_statistics_max_file_size: 0,
+            bloom_filters: false,
+            identifier_indexes: false,
+            sequence_filters: true,
         }
     }
 
+    /// Add the provenance columns to the table, e.g. the file path and record index
+    pub fn with_provenance_columns(mut self, provenance_columns: bool) -> Self {
+        self.provenance_columns = provenance_columns;
+        self
+    }
+
+    /// Read files from a byte offset, which must be at the start of a record, up to their size
+    /// when they were listed
+    pub fn with_start_after_offset(mut self, start_after_offset: Option<u64>) -> Self {
+        self.start_after_offset = start_after_offset;
+        self
+    }
+
+    /// Scan files up to a size in bytes for exact row and null counts, 0 disables it
+    pub fn with_exact_statistics_max_file_size(mut self, max_file_size: usize) -> Self {
+        self.exact_statistics_max_file_size = max_file_size;
+        self
+    }
+
+    /// Prune files for identifier lookups with bloom filter sidecars, which are written next to
+    /// each file on its first lookup
+    pub fn with_bloom_filters(mut self, bloom_filters: bool) -> Self {
+        self.bloom_filters = bloom_filters;
+        self
+    }
+
+    /// Prefilter records for identifier lookups, and read only the chunks of uncompressed files
+    /// that may match with identifier index sidecars, which are written next to each file on its
+    /// first lookup
+    pub fn with_identifier_indexes(mut self, identifier_indexes: bool) -> Self {
+        self.identifier_indexes = identifier_indexes;
+        self
+    }
+
+    /// Push pattern filters on the sequence column down to the scan as a prefilter
+    pub fn with_sequence_filters(mut self, sequence_filters: bool) -> Self {
+        self.sequence_filters = sequence_filters;
+        self
+    }
+
     /// Get the first table path
     pub fn first_table_path(&self) -> Option<&ListingTableUrl> {
         self.inner.table_paths.first()
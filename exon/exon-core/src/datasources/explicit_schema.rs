@@ -0,0 +1,217 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::{any::Any, sync::Arc};
+
+use arrow::{
+    compute::can_cast_types,
+    datatypes::{Schema, SchemaRef},
+};
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::TableProvider,
+    error::{DataFusionError, Result},
+    logical_expr::{TableProviderFilterPushDown, TableType},
+    physical_plan::{
+        expressions::{cast, col},
+        projection::ProjectionExec,
+        ExecutionPlan,
+    },
+    prelude::Expr,
+};
+
+use crate::planner_events::PLANNER_EVENT_TARGET;
+
+/// A table with a user-supplied schema, e.g. from the column list of a `CREATE EXTERNAL TABLE`,
+/// over a table with the schema exon infers for the format.
+///
+/// Each column is read from the inferred column of the same name and cast to the declared type,
+/// and inferred columns that aren't declared are dropped, so the table's schema doesn't change
+/// when the inferred one does.
+#[derive(Debug)]
+pub struct ExplicitSchemaTable {
+    /// The table with the inferred schema
+    inner: Arc<dyn TableProvider>,
+
+    /// The user-supplied schema
+    schema: SchemaRef,
+
+    /// The index of each column of the schema in the inner table's schema
+    inner_indices: Vec<usize>,
+}
+
+impl ExplicitSchemaTable {
+    /// Create a table with the schema over the inner table, which must have a column of the
+    /// same name that can be cast to the declared type for each column of the schema.
+    pub fn try_new(inner: Arc<dyn TableProvider>, schema: SchemaRef) -> Result<Self> {
+        let inner_schema = inner.schema();
+
+        let inner_indices = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let index = inner_schema.index_of(field.name()).map_err(|_| {
+                    DataFusionError::Plan(format!(
+                        "The column {} is not in the table's inferred schema",
+                        field.name()
+                    ))
+                })?;
+
+                let inner_type = inner_schema.field(index).data_type();
+                if !can_cast_types(inner_type, field.data_type()) {
+                    return Err(DataFusionError::Plan(format!(
+                        "The column {} can't be cast from {} to {}",
+                        field.name(),
+                        inner_type,
+                        field.data_type()
+                    )));
+                }
+
+                Ok(index)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let cast = schema
+            .fields()
+            .iter()
+            .zip(&inner_indices)
+            .filter(|(field, i)| inner_schema.field(**i).data_type() != field.data_type())
+            .map(|(field, _)| field.name().as_str())
+            .collect::<Vec<_>>();
+
+        let dropped = inner_schema
+            .fields()
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !inner_indices.contains(i))
+            .map(|(_, field)| field.name().as_str())
+            .collect::<Vec<_>>();
+
+        tracing::debug!(
+            target: PLANNER_EVENT_TARGET,
+            event = "schema_coerced",
+            cast = %cast.join(", "),
+            dropped = %dropped.join(", "),
+        );
+
+        // Keep the inferred metadata, e.g. the genome build, unless it's declared.
+        let mut metadata = inner_schema.metadata().clone();
+        metadata.extend(schema.metadata().clone());
+
+        let schema = Arc::new(Schema::new_with_metadata(schema.fields().clone(), metadata));
+
+        Ok(Self {
+            inner,
+            schema,
+            inner_indices,
+        })
+    }
+
+    /// True if the columns of the filter have the same type in the inner table, so the inner
+    /// table can use it, e.g. to prune partitions.
+    fn passes_through(&self, filter: &Expr) -> bool {
+        let inner_schema = self.inner.schema();
+
+        filter.column_refs().iter().all(|column| {
+            match (
+                self.schema.field_with_name(&column.name),
+                inner_schema.field_with_name(&column.name),
+            ) {
+                (Ok(field), Ok(inner_field)) => field.data_type() == inner_field.data_type(),
+                _ => false,
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl TableProvider for ExplicitSchemaTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        self.inner.table_type()
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        let inner_pushdown = self.inner.supports_filters_pushdown(filters)?;
+
+        Ok(filters
+            .iter()
+            .zip(inner_pushdown)
+            .map(|(f, pushdown)| {
+                if self.passes_through(f) {
+                    pushdown
+                } else {
+                    TableProviderFilterPushDown::Unsupported
+                }
+            })
+            .collect())
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let projection = match projection {
+            Some(projection) => projection.clone(),
+            None => (0..self.schema.fields().len()).collect(),
+        };
+
+        let mut inner_projection = projection
+            .iter()
+            .map(|i| self.inner_indices[*i])
+            .collect::<Vec<_>>();
+        inner_projection.sort_unstable();
+        inner_projection.dedup();
+
+        let filters = filters
+            .iter()
+            .filter(|f| self.passes_through(f))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let input = self
+            .inner
+            .scan(state, Some(&inner_projection), &filters, limit)
+            .await?;
+        let input_schema = input.schema();
+
+        let exprs = projection
+            .iter()
+            .map(|i| {
+                let field = self.schema.field(*i);
+                let expr = col(field.name(), &input_schema)?;
+
+                Ok((
+                    cast(expr, &input_schema, field.data_type().clone())?,
+                    field.name().to_string(),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(ProjectionExec::try_new(exprs, input)?))
+    }
+}
@@ -14,7 +14,8 @@
 
 use std::{any::Any, sync::Arc};
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 use arrow::datatypes::SchemaRef;
 use datafusion::{
     common::Statistics,
@@ -155,11 +156,17 @@ impl ExecutionPlan for IndexedFASTAScanner {
         let config = FASTAConfig::new(object_store, Arc::clone(&self.base_config.file_schema))
             .with_batch_size(context.session_config().batch_size())
             .with_fasta_sequence_buffer_capacity(self.fasta_sequence_buffer_capacity)
-            .with_projection(self.base_config.file_projection());
+            .with_projection(self.base_config.file_projection())
+            .with_reader_limits(reader_limits(context.session_config()));
 
         let opener = IndexedFASTAOpener::new(Arc::new(config), self.file_compression_type);
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
 }
@@ -30,7 +30,8 @@ use datafusion::{
 };
 use exon_fasta::{FASTAConfig, SequenceDataType};
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::FASTAOpener;
 
@@ -168,11 +169,17 @@ impl ExecutionPlan for FASTAScan {
             .with_batch_size(batch_size)
             .with_fasta_sequence_buffer_capacity(self.fasta_sequence_buffer_capacity)
             .with_sequence_data_type(self.sequence_data_type.clone())
-            .with_projection(self.base_config.file_projection());
+            .with_projection(self.base_config.file_projection())
+            .with_reader_limits(reader_limits(context.session_config()));
 
         let opener = FASTAOpener::new(Arc::new(config), self.file_compression_type);
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
@@ -24,7 +24,7 @@ use datafusion::common::config_err;
 use datafusion::error::Result as DfResult;
 use exon_fasta::SequenceDataType;
 
-use crate::ExonError;
+use crate::{config::ExonConfigExtension, ExonError};
 
 #[derive(Debug, Clone, Default)]
 /// Options for the FASTA data source.
@@ -40,6 +40,15 @@ impl FASTAOptions {
         self.file_extension.as_deref().unwrap_or("fasta")
     }
 
+    /// Fill the sequence data type and buffer capacity the table doesn't set with the session's.
+    pub fn with_session_defaults(mut self, config: &ExonConfigExtension) -> Self {
+        self.fasta_sequence_data_type
+            .get_or_insert_with(|| config.fasta_sequence_data_type.trim().to_string());
+        self.sequence_buffer_capacity
+            .get_or_insert_with(|| config.fasta_sequence_buffer_capacity.to_string());
+        self
+    }
+
     /// Get the sequence data type for the FASTA file. If None, return Utf8.
     pub fn fasta_sequence_data_type(&self) -> crate::Result<SequenceDataType> {
         if let Some(fasta_sequence_data_type) = &self.fasta_sequence_data_type {
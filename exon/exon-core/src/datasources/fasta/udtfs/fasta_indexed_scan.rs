@@ -24,11 +24,11 @@ use datafusion::{
     logical_expr::Expr,
     scalar::ScalarValue,
 };
-use exon_fasta::FASTASchemaBuilder;
 use noodles::core::Region;
 use object_store::{path::Path, ObjectStore};
 
 use crate::{
+    config::extract_config_from_state,
     datasources::{
         exon_listing_table_options::ExonListingConfig,
         fasta::table_provider::{ListingFASTATable, ListingFASTATableOptions},
@@ -87,8 +87,6 @@ impl TableFunctionImpl for FastaIndexedScanFunction {
             .or(passed_compression_type)
             .unwrap_or(FileCompressionType::UNCOMPRESSED);
 
-        let fasta_schema = FASTASchemaBuilder::default().build();
-
         futures::executor::block_on(async {
             self.ctx
                 .runtime_env()
@@ -108,7 +106,12 @@ impl TableFunctionImpl for FastaIndexedScanFunction {
 
         let region = Region::from_str(region_str);
 
-        let mut listing_table_options = ListingFASTATableOptions::new(compression_type);
+        let state = self.ctx.state();
+        let config = extract_config_from_state(&state)?;
+
+        let mut listing_table_options = ListingFASTATableOptions::new(compression_type)
+            .with_sequence_data_type(config.fasta_sequence_data_type()?)
+            .with_sequence_buffer_capacity(config.fasta_sequence_buffer_capacity);
 
         match (region_file_check, region) {
             (Ok(_), _) => {
@@ -125,6 +128,8 @@ impl TableFunctionImpl for FastaIndexedScanFunction {
             }
         }
 
+        let fasta_schema = futures::executor::block_on(listing_table_options.infer_schema())?;
+
         let listing_table_config =
             ExonListingConfig::new_with_options(listing_table_url, listing_table_options);
 
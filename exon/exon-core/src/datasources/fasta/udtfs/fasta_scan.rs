@@ -20,9 +20,9 @@ use datafusion::{
     execution::context::SessionContext,
     logical_expr::Expr,
 };
-use exon_fasta::FASTASchemaBuilder;
 
 use crate::{
+    config::extract_config_from_state,
     datasources::{
         exon_listing_table_options::ExonListingConfig,
         fasta::table_provider::{ListingFASTATable, ListingFASTATableOptions},
@@ -60,10 +60,15 @@ impl TableFunctionImpl for FastaScanFunction {
                 .await
         })?;
 
-        let fasta_schema = FASTASchemaBuilder::default().build();
+        let state = self.ctx.state();
+        let config = extract_config_from_state(&state)?;
 
         let listing_table_options =
-            ListingFASTATableOptions::new(listing_scan_function.file_compression_type);
+            ListingFASTATableOptions::new(listing_scan_function.file_compression_type)
+                .with_sequence_data_type(config.fasta_sequence_data_type()?)
+                .with_sequence_buffer_capacity(config.fasta_sequence_buffer_capacity);
+
+        let fasta_schema = futures::executor::block_on(listing_table_options.infer_schema())?;
 
         let listing_table_config = ExonListingConfig::new_with_options(
             listing_scan_function.listing_table_url,
@@ -0,0 +1,166 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::{
+    array::{Array, AsArray},
+    compute::cast,
+    datatypes::{DataType, SchemaRef},
+};
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::{TableProvider, TableType},
+    error::{DataFusionError, Result},
+    execution::TaskContext,
+    logical_expr::{Expr, LogicalPlan},
+    physical_plan::{collect, expressions::col, projection::ProjectionExec, ExecutionPlan},
+};
+
+use crate::physical_plan::demultiplex_exec::{demultiplex_schema, DemultiplexExec, SampleBarcodes};
+
+/// Where to find the barcode in a read and how many mismatches to allow.
+#[derive(Debug, Clone)]
+pub struct DemultiplexOptions {
+    /// The 0-based start of the barcode region of the sequence.
+    pub start: usize,
+
+    /// The length of the barcode region, by default the length of the barcodes.
+    pub length: Option<usize>,
+
+    /// The maximum number of mismatches between a barcode region and a sample's barcode.
+    pub max_mismatches: usize,
+}
+
+/// A table of the reads of a FASTQ table with the sample each read is assigned to by its barcode.
+#[derive(Debug)]
+pub struct DemultiplexTable {
+    inner: Arc<dyn TableProvider>,
+    barcodes: LogicalPlan,
+    options: DemultiplexOptions,
+    schema: SchemaRef,
+}
+
+impl DemultiplexTable {
+    /// Create a new demultiplexed table of the reads of `inner` with the samples and barcodes of
+    /// `barcodes`, which must have sample and barcode columns.
+    pub fn try_new(
+        inner: Arc<dyn TableProvider>,
+        barcodes: LogicalPlan,
+        options: DemultiplexOptions,
+    ) -> Result<Self> {
+        for name in ["sample", "barcode"] {
+            if !barcodes.schema().has_column_with_unqualified_name(name) {
+                return Err(DataFusionError::Plan(format!(
+                    "Demultiplexing requires a barcodes table with a {} column",
+                    name
+                )));
+            }
+        }
+
+        let schema = demultiplex_schema(&inner.schema());
+
+        Ok(Self {
+            inner,
+            barcodes,
+            options,
+            schema,
+        })
+    }
+
+    /// Read the sample barcodes, which are expected to fit in memory.
+    async fn sample_barcodes(&self, state: &dyn Session) -> Result<SampleBarcodes> {
+        let plan = state.create_physical_plan(&self.barcodes).await?;
+        let batches = collect(plan, Arc::new(TaskContext::from(state))).await?;
+
+        let mut barcodes = Vec::new();
+
+        for batch in batches {
+            let column = |name: &str| -> Result<_> {
+                let array = batch.column_by_name(name).ok_or_else(|| {
+                    DataFusionError::Execution(format!("Missing barcodes column {}", name))
+                })?;
+
+                Ok(cast(array, &DataType::Utf8)?)
+            };
+
+            let samples = column("sample")?;
+            let samples = samples.as_string::<i32>();
+
+            let sample_barcodes = column("barcode")?;
+            let sample_barcodes = sample_barcodes.as_string::<i32>();
+
+            for i in 0..batch.num_rows() {
+                if samples.is_null(i) || sample_barcodes.is_null(i) {
+                    continue;
+                }
+
+                barcodes.push((
+                    samples.value(i).to_string(),
+                    sample_barcodes.value(i).to_string(),
+                ));
+            }
+        }
+
+        SampleBarcodes::try_new(
+            barcodes,
+            self.options.start,
+            self.options.length,
+            self.options.max_mismatches,
+        )
+    }
+}
+
+#[async_trait]
+impl TableProvider for DemultiplexTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let barcodes = Arc::new(self.sample_barcodes(state).await?);
+
+        let input = self.inner.scan(state, None, &[], None).await?;
+        let exec = Arc::new(DemultiplexExec::try_new(input, barcodes)?);
+
+        let Some(projection) = projection else {
+            return Ok(exec);
+        };
+
+        let exprs = projection
+            .iter()
+            .map(|i| {
+                let name = self.schema.field(*i).name();
+                Ok((col(name, &self.schema)?, name.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(ProjectionExec::try_new(exprs, exec)?))
+    }
+}
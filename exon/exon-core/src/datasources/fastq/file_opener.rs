@@ -27,7 +27,9 @@ use futures::{StreamExt, TryStreamExt};
 use tokio::io::AsyncBufReadExt;
 use tokio_util::io::StreamReader;
 
-use crate::streaming_bgzf::is_bgzip_valid_header;
+use crate::{
+    physical_plan::object_store::file_range_get_options, streaming_bgzf::is_bgzip_valid_header,
+};
 
 /// Implements a datafusion `FileOpener` for FASTQ files.
 pub struct FASTQOpener {
@@ -90,7 +92,12 @@ impl FileOpener for FASTQOpener {
                     }
                 }
                 _ => {
-                    let get_result = config.object_store.get(file_meta.location()).await?;
+                    // Uncompressed files can be read from a range, e.g. to resume an incremental
+                    // scan, and it starts on a record boundary.
+                    let get_result = config
+                        .object_store
+                        .get_opts(file_meta.location(), file_range_get_options(&file_meta))
+                        .await?;
 
                     let stream = Box::pin(get_result.into_stream().map_err(DataFusionError::from));
 
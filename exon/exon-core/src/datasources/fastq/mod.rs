@@ -16,14 +16,16 @@
 //!
 //! This module provides functionality for working with FASTQ files as a data source.
 
+mod demultiplex;
 mod file_opener;
 mod scanner;
 
 /// Table provider for FASTQ files.
 pub mod table_provider;
 
+pub use self::demultiplex::{DemultiplexOptions, DemultiplexTable};
 pub use self::file_opener::FASTQOpener;
 pub use self::scanner::FASTQScan;
 
 mod udtf;
-pub use self::udtf::FastqScanFunction;
+pub use self::udtf::{DemultiplexFunction, FastqScanFunction};
@@ -26,9 +26,11 @@ use datafusion::{
         PlanProperties, SendableRecordBatchStream,
     },
 };
+use exon_common::{RecordSampling, SequenceFilter};
 use exon_fastq::FASTQConfig;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::FASTQOpener;
 
@@ -52,6 +54,15 @@ pub struct FASTQScan {
 
     /// The statistics for the scan.
     statistics: Statistics,
+
+    /// A prefilter on the sequences of the records to read.
+    sequence_filter: Option<SequenceFilter>,
+
+    /// Whether to pack the sequences with 2 bits per base.
+    pack_sequences: bool,
+
+    /// A sample of the records to read, instead of all of them.
+    sampling: Option<RecordSampling>,
 }
 
 impl FASTQScan {
@@ -66,8 +77,29 @@ impl FASTQScan {
             metrics: ExecutionPlanMetricsSet::new(),
             properties,
             statistics,
+            sequence_filter: None,
+            pack_sequences: false,
+            sampling: None,
         }
     }
+
+    /// Set whether to pack the sequences with 2 bits per base.
+    pub fn with_pack_sequences(mut self, pack_sequences: bool) -> Self {
+        self.pack_sequences = pack_sequences;
+        self
+    }
+
+    /// Read a sample of the records, which is taken before their columns are built.
+    pub fn with_sampling(mut self, sampling: Option<RecordSampling>) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Skip the records that don't match a sequence filter.
+    pub fn with_sequence_filter(mut self, sequence_filter: SequenceFilter) -> Self {
+        self.sequence_filter = Some(sequence_filter);
+        self
+    }
 }
 
 impl DisplayAs for FASTQScan {
@@ -136,13 +168,22 @@ impl ExecutionPlan for FASTQScan {
 
         let config = FASTQConfig::new(object_store)
             .with_batch_size(batch_size)
-            .with_projection(self.base_config.file_projection());
+            .with_projection(self.base_config.file_projection())
+            .with_sequence_filter(self.sequence_filter.clone())
+            .with_pack_sequences(self.pack_sequences)
+            .with_sampling(self.sampling)
+            .with_reader_limits(reader_limits(context.session_config()));
 
         let config = Arc::new(config);
 
         let opener = FASTQOpener::new(config, self.file_compression_type);
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
@@ -12,32 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{any::Any, sync::Arc};
+use std::sync::Arc;
 
-use arrow::datatypes::{Field, Schema, SchemaRef};
+use arrow::datatypes::Field;
 use async_trait::async_trait;
 use datafusion::{
     catalog::Session,
-    datasource::{file_format::file_compression_type::FileCompressionType, TableProvider},
+    datasource::{
+        file_format::file_compression_type::FileCompressionType, listing::ListingTableUrl,
+        physical_plan::FileScanConfig,
+    },
     error::Result,
-    logical_expr::{TableProviderFilterPushDown, TableType},
-    physical_plan::{empty::EmptyExec, ExecutionPlan},
-    prelude::Expr,
+    physical_plan::ExecutionPlan,
 };
-use exon_common::TableSchema;
-use exon_fastq::new_fastq_schema_builder;
-use futures::TryStreamExt;
-
-use crate::{
-    datasources::{
-        exon_file_type::get_file_extension_with_compression,
-        exon_listing_table_options::{ExonListingConfig, ExonListingOptions},
-        hive_partition::filter_matches_partition_cols,
-        ExonFileType,
-    },
-    physical_plan::{
-        file_scan_config_builder::FileScanConfigBuilder, object_store::pruned_partition_list,
-    },
+use exon_common::{RecordSampling, SequenceFilter, TableSchema};
+use exon_fastq::{new_fastq_schema_builder, new_packed_fastq_schema_builder};
+
+use crate::datasources::{
+    exon_file_type::get_file_extension_with_compression,
+    exon_listing_table::{ExonFileFormatOptions, ExonListingTable},
+    exon_listing_table_options::ExonListingOptions,
+    ExonFileType,
 };
 
 use super::FASTQScan;
@@ -53,6 +48,12 @@ pub struct ListingFASTQTableOptions {
 
     /// The table partition columns
     table_partition_cols: Vec<Field>,
+
+    /// Whether to pack the sequences with 2 bits per base
+    pack_sequences: bool,
+
+    /// A sample of the records to read
+    sampling: Option<RecordSampling>,
 }
 
 impl Default for ListingFASTQTableOptions {
@@ -61,6 +62,8 @@ impl Default for ListingFASTQTableOptions {
             file_extension: String::from("fastq"),
             file_compression_type: FileCompressionType::UNCOMPRESSED,
             table_partition_cols: Vec::new(),
+            pack_sequences: false,
+            sampling: None,
         }
     }
 }
@@ -83,7 +86,9 @@ impl ExonListingOptions for ListingFASTQTableOptions {
         &self,
         conf: datafusion::datasource::physical_plan::FileScanConfig,
     ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
-        let scan = FASTQScan::new(conf, self.file_compression_type());
+        let scan = FASTQScan::new(conf, self.file_compression_type())
+            .with_pack_sequences(self.pack_sequences)
+            .with_sampling(self.sampling);
 
         Ok(Arc::new(scan))
     }
@@ -98,6 +103,8 @@ impl ListingFASTQTableOptions {
             file_extension,
             file_compression_type,
             table_partition_cols: Vec::new(),
+            pack_sequences: false,
+            sampling: None,
         }
     }
 
@@ -126,104 +133,65 @@ impl ListingFASTQTableOptions {
         }
     }
 
-    /// Infer the schema for the underlying files
-    pub fn infer_schema(&self) -> TableSchema {
-        let builder =
-            new_fastq_schema_builder().add_partition_fields(self.table_partition_cols.clone());
-        builder.build()
+    /// Pack the sequences with 2 bits per base, see the `unpack_sequence` UDF
+    pub fn with_pack_sequences(self, pack_sequences: bool) -> Self {
+        Self {
+            pack_sequences,
+            ..self
+        }
     }
-}
 
-#[derive(Debug, Clone)]
-/// A FASTQ listing table
-pub struct ListingFASTQTable<T> {
-    /// The schema for the table
-    table_schema: TableSchema,
+    /// Read a sample of the records of each file, by fraction or with reservoir sampling
+    pub fn with_sampling(self, sampling: Option<RecordSampling>) -> Self {
+        Self { sampling, ..self }
+    }
 
-    /// The config for the table
-    config: ExonListingConfig<T>,
-}
+    /// Infer the schema for the underlying files
+    pub fn infer_schema(&self) -> TableSchema {
+        let builder = if self.pack_sequences {
+            new_packed_fastq_schema_builder()
+        } else {
+            new_fastq_schema_builder()
+        };
 
-impl<T: ExonListingOptions> ListingFASTQTable<T> {
-    /// Create a new VCF listing table
-    pub fn try_new(config: ExonListingConfig<T>, table_schema: TableSchema) -> Result<Self> {
-        Ok(Self {
-            table_schema,
-            config,
-        })
+        builder
+            .add_partition_fields(self.table_partition_cols.clone())
+            .build()
     }
 }
 
 #[async_trait]
-impl<T: ExonListingOptions + 'static> TableProvider for ListingFASTQTable<T> {
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-
-    fn schema(&self) -> SchemaRef {
-        Arc::clone(&self.table_schema.table_schema())
-    }
-
-    fn table_type(&self) -> TableType {
-        TableType::Base
+impl ExonFileFormatOptions for ListingFASTQTableOptions {
+    async fn infer_table_schema(
+        &self,
+        _state: &dyn Session,
+        _table_path: &ListingTableUrl,
+    ) -> Result<TableSchema> {
+        Ok(self.infer_schema())
     }
 
-    fn supports_filters_pushdown(
-        &self,
-        filters: &[&Expr],
-    ) -> Result<Vec<TableProviderFilterPushDown>> {
-        Ok(filters
-            .iter()
-            .map(|f| filter_matches_partition_cols(f, self.config.options.table_partition_cols()))
-            .collect())
+    fn sequence_column(&self) -> Option<&'static str> {
+        // Pattern predicates can't be evaluated on packed sequences.
+        (!self.pack_sequences).then_some("sequence")
     }
 
-    async fn scan(
+    async fn create_physical_plan_with_sequence_filter(
         &self,
-        state: &dyn Session,
-        projection: Option<&Vec<usize>>,
-        filters: &[Expr],
-        limit: Option<usize>,
+        conf: FileScanConfig,
+        sequence_filter: SequenceFilter,
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        let object_store_url = if let Some(url) = self.config.inner.table_paths.first() {
-            url.object_store()
-        } else {
-            return Ok(Arc::new(EmptyExec::new(Arc::new(Schema::empty()))));
-        };
-
-        let object_store = state.runtime_env().object_store(object_store_url.clone())?;
-
-        let file_list = pruned_partition_list(
-            &object_store,
-            &self.config.inner.table_paths[0],
-            filters,
-            self.config.options.file_extension(),
-            self.config.options.table_partition_cols(),
-        )
-        .await?
-        .try_collect::<Vec<_>>()
-        .await?;
-
-        let file_scan_config = FileScanConfigBuilder::new(
-            object_store_url.clone(),
-            self.table_schema.file_schema()?,
-            vec![file_list],
-        )
-        .projection_option(projection.cloned())
-        .table_partition_cols(self.config.options.table_partition_cols().to_vec())
-        .limit_option(limit)
-        .build();
-
-        let plan = self
-            .config
-            .options
-            .create_physical_plan(file_scan_config)
-            .await?;
+        let scan = FASTQScan::new(conf, self.file_compression_type())
+            .with_pack_sequences(self.pack_sequences)
+            .with_sampling(self.sampling)
+            .with_sequence_filter(sequence_filter);
 
-        Ok(plan)
+        Ok(Arc::new(scan))
     }
 }
 
+/// A FASTQ listing table
+pub type ListingFASTQTable<T = ListingFASTQTableOptions> = ExonListingTable<T>;
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -16,9 +16,10 @@ use std::sync::Arc;
 
 use datafusion::{
     datasource::{function::TableFunctionImpl, TableProvider},
-    error::Result,
+    error::{DataFusionError, Result},
     execution::context::SessionContext,
-    logical_expr::Expr,
+    logical_expr::{Expr, LogicalPlan},
+    scalar::ScalarValue,
 };
 use exon_fastq::new_fastq_schema_builder;
 
@@ -27,7 +28,10 @@ use crate::{
     ExonRuntimeEnvExt,
 };
 
-use super::table_provider::{ListingFASTQTable, ListingFASTQTableOptions};
+use super::{
+    demultiplex::{DemultiplexOptions, DemultiplexTable},
+    table_provider::{ListingFASTQTable, ListingFASTQTableOptions},
+};
 
 /// A table function that returns a table provider for a FASTQ file.
 pub struct FastqScanFunction {
@@ -47,26 +51,121 @@ impl FastqScanFunction {
     }
 }
 
+/// Create a listing table for the FASTQ path and optional compression type in `exprs`.
+fn fastq_listing_table(ctx: &SessionContext, exprs: &[Expr]) -> Result<ListingFASTQTable> {
+    let listing_scan_function = ScanFunction::try_from(exprs)?;
+
+    futures::executor::block_on(async {
+        ctx.runtime_env()
+            .exon_register_object_store_url(listing_scan_function.listing_table_url.as_ref())
+            .await
+    })?;
+
+    let fasta_schema = new_fastq_schema_builder().build();
+
+    let options = ListingFASTQTableOptions::new(listing_scan_function.file_compression_type);
+
+    let listing_table_config =
+        ExonListingConfig::new_with_options(listing_scan_function.listing_table_url, options);
+
+    Ok(ListingFASTQTable::new(listing_table_config, fasta_schema))
+}
+
 impl TableFunctionImpl for FastqScanFunction {
     fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
-        let listing_scan_function = ScanFunction::try_from(exprs)?;
+        Ok(Arc::new(fastq_listing_table(&self.ctx, exprs)?))
+    }
+}
+
+/// Get a non-negative integer argument of a table function.
+fn usize_argument(expr: &Expr, name: &str) -> Result<usize> {
+    let value = match expr {
+        Expr::Literal(ScalarValue::Int64(Some(value))) => usize::try_from(*value).ok(),
+        _ => None,
+    };
+
+    value.ok_or_else(|| {
+        DataFusionError::Plan(format!("{} must be a non-negative integer literal", name))
+    })
+}
+
+/// A table function that assigns the reads of a FASTQ file to samples by their barcodes, e.g.
+/// `demultiplex('reads.fastq.gz', 'barcodes', 1)`.
+///
+/// The arguments are the path, the barcodes as a table name or subquery with sample and barcode
+/// columns, the maximum number of mismatches, and optionally the 1-based start of the barcode in
+/// the sequence, by default 1, and its length, by default the length of the barcodes.
+pub struct DemultiplexFunction {
+    ctx: SessionContext,
+}
+
+impl std::fmt::Debug for DemultiplexFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DemultiplexFunction").finish()
+    }
+}
+
+impl DemultiplexFunction {
+    /// Create a new `DemultiplexFunction`.
+    pub fn new(ctx: SessionContext) -> Self {
+        Self { ctx }
+    }
+
+    fn barcodes_plan(&self, expr: &Expr) -> Result<LogicalPlan> {
+        let table_name = match expr {
+            Expr::ScalarSubquery(subquery) => return Ok(subquery.subquery.as_ref().clone()),
+            Expr::Literal(ScalarValue::Utf8(Some(table_name))) => table_name.clone(),
+            Expr::Column(column) => column.flat_name(),
+            _ => {
+                return Err(DataFusionError::Plan(
+                    "the barcodes must be a table name or subquery".to_string(),
+                ))
+            }
+        };
+
+        let df = futures::executor::block_on(self.ctx.table(table_name.as_str()))?;
+
+        Ok(df.into_unoptimized_plan())
+    }
+}
+
+impl TableFunctionImpl for DemultiplexFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        if !(3..=5).contains(&exprs.len()) {
+            return Err(DataFusionError::Plan(
+                "demultiplex takes a path, barcodes, the maximum number of mismatches, and \
+                 optionally the barcode start and length"
+                    .to_string(),
+            ));
+        }
 
-        futures::executor::block_on(async {
-            self.ctx
-                .runtime_env()
-                .exon_register_object_store_url(listing_scan_function.listing_table_url.as_ref())
-                .await
-        })?;
+        let listing_table = fastq_listing_table(&self.ctx, &exprs[..1])?;
+        let barcodes = self.barcodes_plan(&exprs[1])?;
 
-        let fasta_schema = new_fastq_schema_builder().build();
+        let max_mismatches = usize_argument(&exprs[2], "max_mismatch")?;
 
-        let options = ListingFASTQTableOptions::new(listing_scan_function.file_compression_type);
+        let start = match exprs.get(3) {
+            Some(expr) => usize_argument(expr, "barcode_start")?
+                .checked_sub(1)
+                .ok_or_else(|| DataFusionError::Plan("barcode_start is 1-based".to_string()))?,
+            None => 0,
+        };
 
-        let listing_table_config =
-            ExonListingConfig::new_with_options(listing_scan_function.listing_table_url, options);
+        let length = exprs
+            .get(4)
+            .map(|expr| usize_argument(expr, "barcode_length"))
+            .transpose()?;
 
-        let listing_table = ListingFASTQTable::try_new(listing_table_config, fasta_schema)?;
+        let options = DemultiplexOptions {
+            start,
+            length,
+            max_mismatches,
+        };
 
-        Ok(Arc::new(listing_table))
+        Ok(Arc::new(DemultiplexTable::try_new(
+            Arc::new(listing_table),
+            barcodes,
+            options,
+        )?))
     }
 }
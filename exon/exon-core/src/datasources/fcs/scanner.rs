@@ -30,7 +30,7 @@ use exon_fcs::FCSConfig;
 
 // file format moted to physcial plan
 
-use crate::datasources::ExonFileScanConfig;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::FCSOpener;
 
@@ -153,7 +153,12 @@ impl ExecutionPlan for FCSScan {
             .with_projection(self.base_config.file_projection());
 
         let opener = FCSOpener::new(Arc::new(config), self.file_compression_type);
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
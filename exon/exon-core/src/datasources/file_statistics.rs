@@ -0,0 +1,159 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt::Debug, sync::Arc};
+
+use arrow::datatypes::Schema;
+use datafusion::{
+    common::stats::Precision,
+    error::Result,
+    execution::{
+        cache::{cache_unit::DefaultFileStatisticsCache, CacheAccessor},
+        TaskContext,
+    },
+    physical_plan::{execute_stream, ColumnStatistics, ExecutionPlan, Statistics},
+};
+use futures::StreamExt;
+use object_store::ObjectMeta;
+
+/// Collects the exact row and null counts of files by scanning them.
+///
+/// The statistics are cached by the files' object meta, so a file is scanned again only if its
+/// size or last modified time changes.
+#[derive(Clone, Default)]
+pub(crate) struct FileStatisticsCollector {
+    cache: Arc<DefaultFileStatisticsCache>,
+}
+
+impl Debug for FileStatisticsCollector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileStatisticsCollector")
+            .field("len", &self.cache.len())
+            .finish()
+    }
+}
+
+impl FileStatisticsCollector {
+    /// Get the cached statistics of a file, if it hasn't changed since they were collected
+    pub(crate) fn get(&self, object_meta: &ObjectMeta) -> Option<Arc<Statistics>> {
+        self.cache
+            .get_with_extra(&object_meta.location, object_meta)
+    }
+
+    /// Scan a file with a plan that reads all of its columns and cache its statistics
+    pub(crate) async fn collect(
+        &self,
+        object_meta: &ObjectMeta,
+        plan: Arc<dyn ExecutionPlan>,
+        context: Arc<TaskContext>,
+    ) -> Result<Arc<Statistics>> {
+        let schema = plan.schema();
+
+        let mut num_rows = 0;
+        let mut total_byte_size = 0;
+        let mut null_counts = vec![0; schema.fields().len()];
+
+        let mut stream = execute_stream(plan, context)?;
+
+        while let Some(batch) = stream.next().await {
+            let batch = batch?;
+
+            num_rows += batch.num_rows();
+            total_byte_size += batch.get_array_memory_size();
+
+            for (null_count, column) in null_counts.iter_mut().zip(batch.columns()) {
+                *null_count += column.null_count();
+            }
+        }
+
+        let statistics = Arc::new(Statistics {
+            num_rows: Precision::Exact(num_rows),
+            total_byte_size: Precision::Inexact(total_byte_size),
+            column_statistics: null_counts
+                .into_iter()
+                .map(|null_count| ColumnStatistics {
+                    null_count: Precision::Exact(null_count),
+                    ..ColumnStatistics::new_unknown()
+                })
+                .collect(),
+        });
+
+        self.cache
+            .put_with_extra(&object_meta.location, Arc::clone(&statistics), object_meta);
+
+        Ok(statistics)
+    }
+}
+
+/// Sum the statistics of the files of a scan.
+pub(crate) fn merge_file_statistics(schema: &Schema, statistics: &[Arc<Statistics>]) -> Statistics {
+    let mut statistics = statistics.iter();
+
+    let Some(first) = statistics.next() else {
+        return Statistics::new_unknown(schema);
+    };
+
+    statistics.fold(first.as_ref().clone(), |merged, file| Statistics {
+        num_rows: merged.num_rows.add(&file.num_rows),
+        total_byte_size: merged.total_byte_size.add(&file.total_byte_size),
+        column_statistics: merged
+            .column_statistics
+            .iter()
+            .zip(&file.column_statistics)
+            .map(|(merged, file)| ColumnStatistics {
+                null_count: merged.null_count.add(&file.null_count),
+                ..ColumnStatistics::new_unknown()
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::{
+        common::stats::Precision,
+        physical_plan::{ColumnStatistics, Statistics},
+    };
+
+    use super::merge_file_statistics;
+
+    fn file_statistics(num_rows: usize, null_count: usize) -> Arc<Statistics> {
+        Arc::new(Statistics {
+            num_rows: Precision::Exact(num_rows),
+            total_byte_size: Precision::Inexact(num_rows * 10),
+            column_statistics: vec![ColumnStatistics {
+                null_count: Precision::Exact(null_count),
+                ..ColumnStatistics::new_unknown()
+            }],
+        })
+    }
+
+    #[test]
+    fn test_merge_file_statistics() {
+        let schema = Schema::new(vec![Field::new("name", DataType::Utf8, true)]);
+
+        let merged = merge_file_statistics(&schema, &[]);
+        assert_eq!(merged.num_rows, Precision::Absent);
+
+        let merged =
+            merge_file_statistics(&schema, &[file_statistics(3, 1), file_statistics(4, 0)]);
+
+        assert_eq!(merged.num_rows, Precision::Exact(7));
+        assert_eq!(merged.total_byte_size, Precision::Inexact(70));
+        assert_eq!(merged.column_statistics[0].null_count, Precision::Exact(1));
+    }
+}
@@ -28,7 +28,7 @@ use datafusion::{
 };
 use exon_genbank::GenbankConfig;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::GenbankOpener;
 
@@ -150,7 +150,12 @@ impl ExecutionPlan for GenbankScan {
 
         let opener = GenbankOpener::new(config, self.file_compression_type);
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
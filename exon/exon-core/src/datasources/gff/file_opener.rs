@@ -26,6 +26,8 @@ use exon_gff::{BatchReader, GFFConfig};
 use futures::{StreamExt, TryStreamExt};
 use tokio_util::io::StreamReader;
 
+use crate::physical_plan::object_store::file_range_get_options;
+
 /// Implements a datafusion `FileOpener` for GFF files.
 pub struct GFFOpener {
     config: Arc<GFFConfig>,
@@ -48,7 +50,11 @@ impl FileOpener for GFFOpener {
         let file_compression_type = self.file_compression_type;
 
         Ok(Box::pin(async move {
-            let get_result = gff_config.object_store.get(file_meta.location()).await?;
+            // A range is only set for uncompressed files, and it starts on a record boundary.
+            let get_result = gff_config
+                .object_store
+                .get_opts(file_meta.location(), file_range_get_options(&file_meta))
+                .await?;
 
             let stream_reader = get_result.into_stream().map_err(DataFusionError::from);
             let stream_reader = Box::pin(stream_reader);
@@ -27,7 +27,8 @@ use datafusion::{
 use exon_gff::GFFConfig;
 use noodles::core::Region;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::indexed_file_opener::IndexedGffOpener;
 
@@ -140,11 +141,17 @@ impl ExecutionPlan for IndexedGffScanner {
 
         let config = GFFConfig::new(object_store, Arc::clone(&self.base_config.file_schema))
             .with_batch_size(context.session_config().batch_size())
-            .with_projection(self.base_config.file_projection());
+            .with_projection(self.base_config.file_projection())
+            .with_reader_limits(reader_limits(context.session_config()));
 
         let opener = IndexedGffOpener::new(Arc::new(config), Arc::clone(&self.region));
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener).with_region(&self.region),
+            &self.metrics,
+        )?;
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
 }
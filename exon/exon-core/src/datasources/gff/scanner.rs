@@ -28,7 +28,8 @@ use datafusion::{
 };
 use exon_gff::GFFConfig;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::GFFOpener;
 
@@ -142,12 +143,18 @@ impl ExecutionPlan for GFFScan {
 
         let config = GFFConfig::new(object_store, Arc::clone(&self.base_config.file_schema))
             .with_batch_size(context.session_config().batch_size())
-            .with_projection(self.base_config.file_projection());
+            .with_projection(self.base_config.file_projection())
+            .with_reader_limits(reader_limits(context.session_config()));
 
         let opener = GFFOpener::new(Arc::new(config), self.file_compression_type);
 
         // this should have the pc_projector, which would project the scalar fields from the PartitionFile to the RecordBatch
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
@@ -12,43 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{any::Any, sync::Arc};
+use std::sync::Arc;
 
-use arrow::datatypes::{Field, Schema, SchemaRef};
+use arrow::datatypes::Field;
 use async_trait::async_trait;
 use datafusion::{
     catalog::Session,
     datasource::{
-        file_format::file_compression_type::FileCompressionType, physical_plan::FileScanConfig,
-        TableProvider,
+        file_format::file_compression_type::FileCompressionType, listing::ListingTableUrl,
+        physical_plan::FileScanConfig,
     },
-    error::{DataFusionError, Result},
-    logical_expr::{TableProviderFilterPushDown, TableType},
-    physical_plan::{empty::EmptyExec, ExecutionPlan},
-    prelude::Expr,
+    error::Result,
+    physical_plan::ExecutionPlan,
 };
-use exon_common::TableSchema;
+use exon_common::{feature_type_dictionary_data_type, StrandEncoding, TableSchema};
 use exon_gff::new_gff_schema_builder;
-use futures::{StreamExt, TryStreamExt};
 use noodles::core::Region;
 
-use crate::{
-    datasources::{
-        exon_file_type::get_file_extension_with_compression,
-        exon_listing_table_options::{
-            ExonIndexedListingOptions, ExonListingConfig, ExonListingOptions,
-        },
-        hive_partition::filter_matches_partition_cols,
-        indexed_file::indexed_bgzf_file::{
-            augment_partitioned_file_with_byte_range, IndexedBGZFFile,
-        },
-        ExonFileType,
-    },
-    error::Result as ExonResult,
-    physical_plan::{
-        file_scan_config_builder::FileScanConfigBuilder, infer_region,
-        object_store::pruned_partition_list,
-    },
+use crate::datasources::{
+    exon_file_type::get_file_extension_with_compression,
+    exon_listing_table::{ExonFileFormatOptions, ExonListingTable, IdentifierIndex, RegionIndex},
+    exon_listing_table_options::ExonListingOptions,
+    indexed_file::indexed_bgzf_file::IndexedBGZFFile,
+    ExonFileType,
 };
 
 use super::{indexed_scanner::IndexedGffScanner, GFFScan};
@@ -70,6 +56,12 @@ pub struct ListingGFFTableOptions {
 
     /// A region to filter the records
     regions: Vec<Region>,
+
+    /// How the strand column is encoded
+    strand_encoding: StrandEncoding,
+
+    /// Whether the type column is dictionary encoded
+    dictionary_feature_types: bool,
 }
 
 impl Default for ListingGFFTableOptions {
@@ -80,6 +72,8 @@ impl Default for ListingGFFTableOptions {
             table_partition_cols: Vec::new(),
             indexed: false,
             regions: Vec::new(),
+            strand_encoding: StrandEncoding::default(),
+            dictionary_feature_types: false,
         }
     }
 }
@@ -109,22 +103,39 @@ impl ExonListingOptions for ListingGFFTableOptions {
 }
 
 #[async_trait]
-impl ExonIndexedListingOptions for ListingGFFTableOptions {
-    fn indexed(&self) -> bool {
-        self.indexed
+impl ExonFileFormatOptions for ListingGFFTableOptions {
+    async fn infer_table_schema(
+        &self,
+        _state: &dyn Session,
+        _table_path: &ListingTableUrl,
+    ) -> Result<TableSchema> {
+        self.infer_schema().await
+    }
+
+    fn region_index(&self) -> Option<RegionIndex<'_>> {
+        Some(RegionIndex {
+            table_type: "INDEXED_GFF",
+            filter_name: "gff_region_filter",
+            indexed_file: IndexedBGZFFile::Gff,
+            indexed: self.indexed,
+            regions: &self.regions,
+        })
     }
 
-    fn regions(&self) -> &[Region] {
-        &self.regions
+    fn identifier_index(&self) -> Option<IdentifierIndex> {
+        Some(IdentifierIndex {
+            column: "attributes",
+            map_keys: &["ID", "gene_id"],
+            record_field: None,
+        })
     }
 
-    async fn create_physical_plan_with_regions(
+    async fn create_physical_plan_with_region(
         &self,
         conf: FileScanConfig,
-        region: Vec<Region>,
-    ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
-        let scanner =
-            IndexedGffScanner::new(conf.clone(), Arc::new(region.first().unwrap().clone()))?;
+        region: Region,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let scanner = IndexedGffScanner::new(conf, Arc::new(region))?;
 
         Ok(Arc::new(scanner))
     }
@@ -141,6 +152,8 @@ impl ListingGFFTableOptions {
             table_partition_cols: Vec::new(),
             indexed: false,
             regions: Vec::new(),
+            strand_encoding: StrandEncoding::default(),
+            dictionary_feature_types: false,
         }
     }
 
@@ -180,176 +193,36 @@ impl ListingGFFTableOptions {
         }
     }
 
-    /// Infer the base schema for the table from the file schema
-    pub async fn infer_schema(&self) -> datafusion::error::Result<TableSchema> {
-        let schema = new_gff_schema_builder();
-        let schema = schema.add_partition_fields(self.table_partition_cols.clone());
-
-        Ok(schema.build())
-    }
-}
-
-#[derive(Debug, Clone)]
-/// A GFF listing table
-pub struct ListingGFFTable<T> {
-    table_schema: TableSchema,
-
-    config: ExonListingConfig<T>,
-}
-
-impl<T> ListingGFFTable<T> {
-    /// Create a new VCF listing table
-    pub fn new(config: ExonListingConfig<T>, table_schema: TableSchema) -> Self {
+    /// Set how the strand column is encoded
+    pub fn with_strand_encoding(self, strand_encoding: StrandEncoding) -> Self {
         Self {
-            table_schema,
-            config,
+            strand_encoding,
+            ..self
         }
     }
-}
-
-#[async_trait]
-impl<T: ExonIndexedListingOptions + 'static> TableProvider for ListingGFFTable<T> {
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-
-    fn schema(&self) -> SchemaRef {
-        // Listing tables return the file schema with the addition of the partition columns
-        Arc::clone(&self.table_schema.table_schema())
-    }
 
-    fn table_type(&self) -> TableType {
-        TableType::Base
-    }
-
-    fn supports_filters_pushdown(
-        &self,
-        filters: &[&Expr],
-    ) -> Result<Vec<TableProviderFilterPushDown>> {
-        tracing::info!("Pushing down region in gff filter: {:?}", filters);
-        Ok(filters
-            .iter()
-            .map(|f| match f {
-                Expr::ScalarFunction(s) if s.name() == "gff_region_filter" => {
-                    tracing::info!("Pushing down region filter: {:?}", s);
-                    TableProviderFilterPushDown::Exact
-                }
-                _ => filter_matches_partition_cols(f, self.config.options.table_partition_cols()),
-            })
-            .collect())
+    /// Set whether the type column is dictionary encoded
+    pub fn with_dictionary_feature_types(self, dictionary_feature_types: bool) -> Self {
+        Self {
+            dictionary_feature_types,
+            ..self
+        }
     }
 
-    async fn scan(
-        &self,
-        state: &dyn Session,
-        projection: Option<&Vec<usize>>,
-        filters: &[Expr],
-        limit: Option<usize>,
-    ) -> Result<Arc<dyn ExecutionPlan>> {
-        tracing::info!("Scanning GFF table with filters: {:?}", filters);
-        let url = if let Some(url) = self.config.inner.table_paths.first() {
-            url
-        } else {
-            return Ok(Arc::new(EmptyExec::new(Arc::new(Schema::empty()))));
-        };
+    /// Infer the base schema for the table from the file schema
+    pub async fn infer_schema(&self) -> datafusion::error::Result<TableSchema> {
+        let mut schema = new_gff_schema_builder()
+            .with_field_data_type("strand", self.strand_encoding.data_type());
 
-        let object_store = state.runtime_env().object_store(url.object_store())?;
-
-        let regions = filters
-            .iter()
-            .map(|f| {
-                if let Expr::ScalarFunction(s) = f {
-                    let r = infer_region::infer_region_from_udf(s, "gff_region_filter")?;
-                    Ok(r)
-                } else {
-                    Ok(None)
-                }
-            })
-            .collect::<ExonResult<Vec<_>>>()?
-            .into_iter()
-            .flatten()
-            .collect::<Vec<_>>();
-
-        let regions = self.config.options.coalesce_regions(regions);
-
-        if regions.is_empty() && self.config.options.indexed() {
-            return Err(DataFusionError::Plan(
-                "INDEXED_GFF table type requires a region filter. See the 'gff_region_filter' function.".to_string(),
-            ));
+        if self.dictionary_feature_types {
+            schema = schema.with_field_data_type("type", feature_type_dictionary_data_type());
         }
 
-        if self.config.options.indexed() && !regions.is_empty() {
-            let mut file_list = pruned_partition_list(
-                &object_store,
-                url,
-                filters,
-                self.config.options.file_extension(),
-                self.config.options.table_partition_cols(),
-            )
-            .await?;
-
-            let mut file_partitions = Vec::new();
-
-            let region = regions.first().unwrap();
-
-            while let Some(f) = file_list.next().await {
-                let f = f?;
-
-                let file_byte_range = augment_partitioned_file_with_byte_range(
-                    Arc::clone(&object_store),
-                    &f,
-                    region,
-                    &IndexedBGZFFile::Gff,
-                )
-                .await?;
-
-                file_partitions.extend(file_byte_range);
-            }
-
-            let file_scan_config = FileScanConfigBuilder::new(
-                url.object_store(),
-                self.table_schema.file_schema()?,
-                vec![file_partitions],
-            )
-            .projection_option(projection.cloned())
-            .table_partition_cols(self.config.options.table_partition_cols().to_vec())
-            .limit_option(limit)
-            .build();
-
-            let plan = self
-                .config
-                .options
-                .create_physical_plan_with_regions(file_scan_config, vec![region.clone()])
-                .await?;
-            return Ok(plan);
-        }
+        let schema = schema.add_partition_fields(self.table_partition_cols.clone());
 
-        let file_list = pruned_partition_list(
-            &object_store,
-            url,
-            filters,
-            self.config.options.file_extension(),
-            self.config.options.table_partition_cols(),
-        )
-        .await?
-        .try_collect::<Vec<_>>()
-        .await?;
-
-        let file_scan_config = FileScanConfigBuilder::new(
-            url.object_store(),
-            self.table_schema.file_schema()?,
-            vec![file_list],
-        )
-        .projection_option(projection.cloned())
-        .table_partition_cols(self.config.options.table_partition_cols().to_vec())
-        .limit_option(limit)
-        .build();
-
-        let plan = self
-            .config
-            .options
-            .create_physical_plan(file_scan_config)
-            .await?;
-        Ok(plan)
+        Ok(schema.build())
     }
 }
+
+/// A GFF listing table
+pub type ListingGFFTable<T = ListingGFFTableOptions> = ExonListingTable<T>;
@@ -15,6 +15,7 @@
 use std::sync::Arc;
 
 use crate::{
+    config::extract_config_from_state,
     datasources::{exon_listing_table_options::ExonListingConfig, ScanFunction},
     error::ExonError,
     ExonRuntimeEnvExt,
@@ -29,7 +30,6 @@ use datafusion::{
     logical_expr::Expr,
     scalar::ScalarValue,
 };
-use exon_gff::new_gff_schema_builder;
 
 use super::table_provider::{ListingGFFTable, ListingGFFTableOptions};
 
@@ -62,11 +62,16 @@ impl TableFunctionImpl for GFFScanFunction {
                 .await
         })?;
 
-        let schema = new_gff_schema_builder().build();
+        let state = self.ctx.state();
+        let config = extract_config_from_state(&state)?;
 
         let listing_table_options =
             ListingGFFTableOptions::new(listing_scan_function.file_compression_type)
-                .with_indexed(false);
+                .with_indexed(false)
+                .with_strand_encoding(config.strand_encoding()?)
+                .with_dictionary_feature_types(config.dictionary_encode_feature_types);
+
+        let schema = futures::executor::block_on(listing_table_options.infer_schema())?;
 
         let listing_table_config = ExonListingConfig::new_with_options(
             listing_scan_function.listing_table_url,
@@ -121,14 +126,20 @@ impl TableFunctionImpl for GFFIndexedScanFunction {
 
         let region = region_str.parse().map_err(ExonError::from)?;
 
+        let state = self.ctx.state();
+        let config = extract_config_from_state(&state)?;
+
         let listing_table_options = ListingGFFTableOptions::new(FileCompressionType::GZIP)
             .with_indexed(true)
-            .with_region(region);
+            .with_region(region)
+            .with_strand_encoding(config.strand_encoding()?)
+            .with_dictionary_feature_types(config.dictionary_encode_feature_types);
+
+        let schema = futures::executor::block_on(listing_table_options.infer_schema())?;
 
         let listing_table_config =
             ExonListingConfig::new_with_options(listing_table_url, listing_table_options);
 
-        let schema = new_gff_schema_builder().build();
         let listing_table = ListingGFFTable::new(listing_table_config, schema);
 
         Ok(Arc::new(listing_table))
@@ -28,7 +28,8 @@ use datafusion::{
 };
 use exon_gtf::GTFConfig;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::GTFOpener;
 
@@ -142,11 +143,17 @@ impl ExecutionPlan for GTFScan {
 
         let config = GTFConfig::new(object_store, Arc::clone(&self.base_config.file_schema))
             .with_batch_size(context.session_config().batch_size())
-            .with_some_projection(Some(self.base_config.file_projection()));
+            .with_some_projection(Some(self.base_config.file_projection()))
+            .with_reader_limits(reader_limits(context.session_config()));
 
         let opener = GTFOpener::new(Arc::new(config), self.file_compression_type);
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
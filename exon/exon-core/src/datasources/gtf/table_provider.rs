@@ -27,7 +27,7 @@ use datafusion::{
     physical_plan::{empty::EmptyExec, ExecutionPlan},
     prelude::Expr,
 };
-use exon_common::TableSchema;
+use exon_common::{feature_type_dictionary_data_type, StrandEncoding, TableSchema};
 use exon_gtf::new_gtf_schema_builder;
 use futures::TryStreamExt;
 
@@ -55,6 +55,12 @@ pub struct ListingGTFTableOptions {
 
     /// The partition columns
     table_partition_cols: Vec<Field>,
+
+    /// How the strand column is encoded
+    strand_encoding: StrandEncoding,
+
+    /// Whether the type column is dictionary encoded
+    dictionary_feature_types: bool,
 }
 
 #[async_trait]
@@ -96,6 +102,8 @@ impl ListingGTFTableOptions {
             file_extension,
             file_compression_type,
             table_partition_cols: Vec::new(),
+            strand_encoding: StrandEncoding::default(),
+            dictionary_feature_types: false,
         }
     }
 
@@ -107,12 +115,34 @@ impl ListingGTFTableOptions {
         }
     }
 
+    /// Set how the strand column is encoded
+    pub fn with_strand_encoding(self, strand_encoding: StrandEncoding) -> Self {
+        Self {
+            strand_encoding,
+            ..self
+        }
+    }
+
+    /// Set whether the type column is dictionary encoded
+    pub fn with_dictionary_feature_types(self, dictionary_feature_types: bool) -> Self {
+        Self {
+            dictionary_feature_types,
+            ..self
+        }
+    }
+
     /// Infer the schema for the table
     pub fn infer_schema(&self) -> TableSchema {
-        let builder =
-            new_gtf_schema_builder().add_partition_fields(self.table_partition_cols.clone());
+        let mut builder = new_gtf_schema_builder()
+            .with_field_data_type("strand", self.strand_encoding.data_type());
+
+        if self.dictionary_feature_types {
+            builder = builder.with_field_data_type("type", feature_type_dictionary_data_type());
+        }
 
-        builder.build()
+        builder
+            .add_partition_fields(self.table_partition_cols.clone())
+            .build()
     }
 }
 
@@ -15,6 +15,7 @@
 use std::sync::Arc;
 
 use crate::{
+    config::extract_config_from_state,
     datasources::{exon_listing_table_options::ExonListingConfig, ScanFunction},
     ExonRuntimeEnvExt,
 };
@@ -24,7 +25,6 @@ use datafusion::{
     execution::context::SessionContext,
     logical_expr::Expr,
 };
-use exon_gtf::new_gtf_schema_builder;
 
 use super::table_provider::{ListingGTFTable, ListingGTFTableOptions};
 
@@ -57,10 +57,15 @@ impl TableFunctionImpl for GTFScanFunction {
                 .await
         })?;
 
-        let schema = new_gtf_schema_builder().build();
+        let state = self.ctx.state();
+        let config = extract_config_from_state(&state)?;
 
         let listing_table_options =
-            ListingGTFTableOptions::new(listing_scan_function.file_compression_type);
+            ListingGTFTableOptions::new(listing_scan_function.file_compression_type)
+                .with_strand_encoding(config.strand_encoding()?)
+                .with_dictionary_feature_types(config.dictionary_encode_feature_types);
+
+        let schema = listing_table_options.infer_schema();
 
         let listing_table_config = ExonListingConfig::new_with_options(
             listing_scan_function.listing_table_url,
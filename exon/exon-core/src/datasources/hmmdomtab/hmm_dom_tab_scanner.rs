@@ -27,7 +27,7 @@ use datafusion::{
     },
 };
 
-use crate::datasources::ExonFileScanConfig;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::{hmm_dom_tab_config::HMMDomTabConfig, hmm_dom_tab_opener::HMMDomTabOpener};
 
@@ -149,7 +149,12 @@ impl ExecutionPlan for HMMDomTabScan {
 
         let opener = HMMDomTabOpener::new(config, self.file_compression_type);
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
@@ -0,0 +1,509 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{array::Array, datatypes::DataType};
+use datafusion::{
+    common::cast::{as_list_array, as_map_array, as_string_array},
+    error::{DataFusionError, Result},
+    execution::TaskContext,
+    logical_expr::{expr::InList, expr::ScalarFunction, BinaryExpr, Expr, Operator},
+    physical_plan::{execute_stream, ExecutionPlan},
+    scalar::ScalarValue,
+};
+use exon_common::{
+    BloomFilter, IdentifierChunkIndex, IdentifierChunkIndexBuilder, IdentifierFilter,
+    BLOOM_FILTER_EXTENSION, IDENTIFIER_INDEX_EXTENSION,
+};
+use futures::{StreamExt, TryStreamExt};
+use object_store::{path::Path, ObjectMeta, ObjectStore, PutPayload};
+use tokio::io::AsyncBufReadExt;
+use tokio_util::io::StreamReader;
+
+use super::exon_listing_table::IdentifierIndex;
+
+/// The size in bytes of the chunks of identifier indexes, about the most a lookup of a rare
+/// identifier reads per file.
+pub(crate) const IDENTIFIER_INDEX_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// The identifiers a record must have one of, e.g. from `array_has(id, 'rs123')`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct IdentifierPredicate {
+    values: Vec<String>,
+}
+
+impl IdentifierPredicate {
+    /// The predicate of a lookup of literal identifiers, or `None` if the filter can't be used
+    /// to prune files.
+    pub(crate) fn try_from_expr(expr: &Expr, index: &IdentifierIndex) -> Option<Self> {
+        match expr {
+            Expr::BinaryExpr(BinaryExpr {
+                left,
+                op: Operator::Eq,
+                right,
+            }) => {
+                let value = match (is_identifier(left, index), is_identifier(right, index)) {
+                    (true, false) => literal_value(right)?,
+                    (false, true) => literal_value(left)?,
+                    _ => return None,
+                };
+
+                Some(Self {
+                    values: vec![value],
+                })
+            }
+            Expr::BinaryExpr(BinaryExpr {
+                left,
+                op: Operator::Or,
+                right,
+            }) => {
+                let mut predicate = Self::try_from_expr(left, index)?;
+                predicate
+                    .values
+                    .extend(Self::try_from_expr(right, index)?.values);
+
+                Some(predicate)
+            }
+            Expr::InList(InList {
+                expr,
+                list,
+                negated: false,
+            }) if is_identifier(expr, index) => Some(Self {
+                values: list.iter().map(literal_value).collect::<Option<_>>()?,
+            }),
+            Expr::ScalarFunction(ScalarFunction { func, args }) => {
+                match (func.name(), args.as_slice()) {
+                    ("array_has", [array, value]) if is_identifier(array, index) => Some(Self {
+                        values: vec![literal_value(value)?],
+                    }),
+                    ("array_has_any", [array, values]) if is_identifier(array, index) => {
+                        Some(Self {
+                            values: literal_values(values)?,
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether any record of a file could match, which is false only if the file's filter
+    /// definitely contains none of the identifiers.
+    pub(crate) fn may_match(&self, filter: &BloomFilter) -> bool {
+        self.values.iter().any(|v| filter.may_contain(v))
+    }
+
+    /// The prefilter that skips the records without one of the identifiers.
+    pub(crate) fn identifier_filter(&self) -> IdentifierFilter {
+        IdentifierFilter::new(self.values.iter().cloned())
+    }
+}
+
+/// Whether an expression is the identifiers of a record, or an element of them, e.g. `id` or
+/// `attributes['gene_id'][1]`.
+fn is_identifier(expr: &Expr, index: &IdentifierIndex) -> bool {
+    match expr {
+        Expr::Column(c) => c.name == index.column && index.map_keys.is_empty(),
+        Expr::ScalarFunction(ScalarFunction { func, args }) => {
+            match (func.name(), args.as_slice()) {
+                ("get_field", [Expr::Column(c), Expr::Literal(ScalarValue::Utf8(Some(key)))]) => {
+                    c.name == index.column && index.map_keys.contains(&key.as_str())
+                }
+                ("array_element", [array, _]) => is_identifier(array, index),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+fn literal_value(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Literal(
+            ScalarValue::Utf8(Some(v))
+            | ScalarValue::LargeUtf8(Some(v))
+            | ScalarValue::Utf8View(Some(v)),
+        ) => Some(v.clone()),
+        _ => None,
+    }
+}
+
+/// The values of a literal list of strings, e.g. `['rs1', 'rs2']`, before or after it's folded
+/// into a constant.
+fn literal_values(expr: &Expr) -> Option<Vec<String>> {
+    match expr {
+        Expr::ScalarFunction(ScalarFunction { func, args }) if func.name() == "make_array" => {
+            args.iter().map(literal_value).collect()
+        }
+        Expr::Literal(ScalarValue::List(list)) if list.len() == 1 => {
+            let values = list.value(0);
+
+            as_string_array(values.as_ref())
+                .ok()?
+                .iter()
+                .map(|v| v.map(String::from))
+                .collect()
+        }
+        _ => None,
+    }
+}
+
+fn sidecar_location(object_meta: &ObjectMeta, extension: &str) -> Path {
+    Path::from(format!("{}.{}", object_meta.location, extension))
+}
+
+/// Read a sidecar of a file, or `None` if it doesn't exist or is older than the file.
+async fn read_sidecar<T>(
+    object_store: &Arc<dyn ObjectStore>,
+    object_meta: &ObjectMeta,
+    extension: &str,
+    parse: fn(&[u8]) -> Option<T>,
+) -> Result<Option<T>> {
+    let location = sidecar_location(object_meta, extension);
+
+    match object_store.get(&location).await {
+        Ok(get_result) if get_result.meta.last_modified >= object_meta.last_modified => {
+            let bytes = get_result.bytes().await?;
+
+            parse(&bytes)
+                .map(Some)
+                .ok_or_else(|| DataFusionError::Execution(format!("Invalid sidecar {}", location)))
+        }
+        Ok(_) | Err(object_store::Error::NotFound { .. }) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write a sidecar next to a file. Sidecars are only an optimization, so e.g. a read-only store
+/// shouldn't fail the scan.
+async fn write_sidecar(
+    object_store: &Arc<dyn ObjectStore>,
+    object_meta: &ObjectMeta,
+    extension: &str,
+    bytes: Vec<u8>,
+) {
+    let location = sidecar_location(object_meta, extension);
+
+    if let Err(e) = object_store.put(&location, PutPayload::from(bytes)).await {
+        tracing::warn!("Unable to write sidecar {}: {}", location, e);
+    }
+}
+
+/// Read the bloom filter sidecar of a file, or `None` if it doesn't exist or is older than the
+/// file.
+pub(crate) async fn read_bloom_filter(
+    object_store: &Arc<dyn ObjectStore>,
+    object_meta: &ObjectMeta,
+) -> Result<Option<BloomFilter>> {
+    read_sidecar(
+        object_store,
+        object_meta,
+        BLOOM_FILTER_EXTENSION,
+        BloomFilter::try_from_bytes,
+    )
+    .await
+}
+
+/// Build a bloom filter from the identifiers of a file, read with a plan that projects only the
+/// identifier column, and write it next to the file.
+pub(crate) async fn build_bloom_filter(
+    object_store: &Arc<dyn ObjectStore>,
+    object_meta: &ObjectMeta,
+    index: &IdentifierIndex,
+    plan: Arc<dyn ExecutionPlan>,
+    context: Arc<TaskContext>,
+) -> Result<BloomFilter> {
+    let mut hashes = Vec::new();
+
+    let mut stream = execute_stream(plan, context)?;
+
+    while let Some(batch) = stream.next().await {
+        let batch = batch?;
+
+        for column in batch.columns() {
+            push_identifier_hashes(column.as_ref(), index, &mut hashes)?;
+        }
+    }
+
+    let filter = BloomFilter::from_hashes(hashes);
+    write_sidecar(
+        object_store,
+        object_meta,
+        BLOOM_FILTER_EXTENSION,
+        filter.to_bytes(),
+    )
+    .await;
+
+    Ok(filter)
+}
+
+/// Read the identifier index sidecar of a file, or `None` if it doesn't exist or is older than
+/// the file.
+pub(crate) async fn read_identifier_chunk_index(
+    object_store: &Arc<dyn ObjectStore>,
+    object_meta: &ObjectMeta,
+) -> Result<Option<IdentifierChunkIndex>> {
+    read_sidecar(
+        object_store,
+        object_meta,
+        IDENTIFIER_INDEX_EXTENSION,
+        IdentifierChunkIndex::try_from_bytes,
+    )
+    .await
+}
+
+/// Build the identifier index of an uncompressed file from a tab separated field of its record
+/// lines, and write it next to the file. Lines that start with `#` are headers.
+pub(crate) async fn build_identifier_chunk_index(
+    object_store: &Arc<dyn ObjectStore>,
+    object_meta: &ObjectMeta,
+    record_field: usize,
+    chunk_size: u64,
+) -> Result<IdentifierChunkIndex> {
+    let stream = object_store.get(&object_meta.location).await?.into_stream();
+    let mut reader = StreamReader::new(Box::pin(stream.map_err(DataFusionError::from)));
+
+    let mut builder = IdentifierChunkIndexBuilder::new(chunk_size);
+
+    let mut line = Vec::new();
+    let mut offset = 0;
+
+    loop {
+        line.clear();
+
+        let n = reader.read_until(b'\n', &mut line).await?;
+        if n == 0 {
+            break;
+        }
+
+        let start = offset;
+        offset += n as u64;
+
+        if line.starts_with(b"#") {
+            continue;
+        }
+
+        let field = line
+            .split(|b| *b == b'\t')
+            .nth(record_field)
+            .unwrap_or_default();
+
+        let ids = std::str::from_utf8(field)
+            .map_err(|e| {
+                DataFusionError::Execution(format!(
+                    "Invalid identifier in {} at byte {}: {}",
+                    object_meta.location, start, e
+                ))
+            })?
+            .trim_end_matches(['\r', '\n']);
+
+        builder.push(start, offset, ids.split(';'));
+    }
+
+    let index = builder.finish();
+    write_sidecar(
+        object_store,
+        object_meta,
+        IDENTIFIER_INDEX_EXTENSION,
+        index.to_bytes(),
+    )
+    .await;
+
+    Ok(index)
+}
+
+/// Add the hashes of the identifiers in a string, list of strings, or map of lists of strings
+/// array.
+fn push_identifier_hashes(
+    array: &dyn Array,
+    index: &IdentifierIndex,
+    hashes: &mut Vec<u64>,
+) -> Result<()> {
+    match array.data_type() {
+        DataType::Utf8 => {
+            let strings = as_string_array(array)?;
+            hashes.extend(strings.iter().flatten().map(BloomFilter::hash));
+        }
+        DataType::List(_) => {
+            for values in as_list_array(array)?.iter().flatten() {
+                push_identifier_hashes(values.as_ref(), index, hashes)?;
+            }
+        }
+        DataType::Map(_, _) => {
+            let map = as_map_array(array)?;
+
+            let keys = as_string_array(map.keys())?;
+            let offsets = map.value_offsets();
+
+            for i in offsets[0] as usize..offsets[map.len()] as usize {
+                if keys.is_valid(i) && index.map_keys.contains(&keys.value(i)) {
+                    push_identifier_hashes(map.values().slice(i, 1).as_ref(), index, hashes)?;
+                }
+            }
+        }
+        data_type => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Bloom filters are not supported for identifiers of type {}",
+                data_type
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::{
+        functions::core::expr_fn::get_field,
+        functions_nested::expr_fn::{array_element, array_has, array_has_any, make_array},
+        logical_expr::{col, lit},
+    };
+    use exon_common::BloomFilter;
+    use object_store::local::LocalFileSystem;
+
+    use super::*;
+
+    const VCF_INDEX: IdentifierIndex = IdentifierIndex {
+        column: "id",
+        map_keys: &[],
+        record_field: Some(2),
+    };
+
+    const GFF_INDEX: IdentifierIndex = IdentifierIndex {
+        column: "attributes",
+        map_keys: &["ID", "gene_id"],
+        record_field: None,
+    };
+
+    fn predicate(values: &[&str]) -> Option<IdentifierPredicate> {
+        Some(IdentifierPredicate {
+            values: values.iter().map(|v| v.to_string()).collect(),
+        })
+    }
+
+    #[test]
+    fn test_identifier_predicate() {
+        let lookup = array_has(col("id"), lit("rs1"));
+        assert_eq!(
+            IdentifierPredicate::try_from_expr(&lookup, &VCF_INDEX),
+            predicate(&["rs1"])
+        );
+
+        let lookup = array_element(col("id"), lit(1)).in_list(vec![lit("rs1"), lit("rs2")], false);
+        assert_eq!(
+            IdentifierPredicate::try_from_expr(&lookup, &VCF_INDEX),
+            predicate(&["rs1", "rs2"])
+        );
+
+        let lookup = lit("g1")
+            .eq(array_element(
+                get_field(col("attributes"), "gene_id"),
+                lit(1),
+            ))
+            .or(array_has(get_field(col("attributes"), "ID"), lit("g2")));
+        assert_eq!(
+            IdentifierPredicate::try_from_expr(&lookup, &GFF_INDEX),
+            predicate(&["g1", "g2"])
+        );
+
+        let lookup = array_has_any(col("id"), make_array(vec![lit("rs1"), lit("rs2")]));
+        assert_eq!(
+            IdentifierPredicate::try_from_expr(&lookup, &VCF_INDEX),
+            predicate(&["rs1", "rs2"])
+        );
+
+        let other_key = array_has(get_field(col("attributes"), "Name"), lit("g1"));
+        assert_eq!(
+            IdentifierPredicate::try_from_expr(&other_key, &GFF_INDEX),
+            None
+        );
+
+        let partial = array_has(col("id"), lit("rs1")).or(col("pos").eq(lit(1)));
+        assert_eq!(
+            IdentifierPredicate::try_from_expr(&partial, &VCF_INDEX),
+            None
+        );
+
+        let negated = array_element(col("id"), lit(1)).in_list(vec![lit("rs1")], true);
+        assert_eq!(
+            IdentifierPredicate::try_from_expr(&negated, &VCF_INDEX),
+            None
+        );
+    }
+
+    #[test]
+    fn test_may_match() {
+        let filter = BloomFilter::from_hashes(vec![BloomFilter::hash("rs1")]);
+
+        assert_eq!(
+            predicate(&["rs2", "rs1"]).map(|p| p.may_match(&filter)),
+            Some(true)
+        );
+        assert_eq!(
+            predicate(&["rs2"]).map(|p| p.may_match(&filter)),
+            Some(false)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_build_identifier_chunk_index() -> Result<(), Box<dyn std::error::Error>> {
+        let directory = std::env::temp_dir().join("test_build_identifier_chunk_index");
+        std::fs::create_dir_all(&directory)?;
+
+        let header = "##fileformat=VCFv4.3\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n";
+        let records = [
+            "1\t100\trs1\tA\tG\t.\t.\t.\n",
+            "1\t200\trs2;rs3\tA\tG\t.\t.\t.\n",
+            "1\t300\t.\tA\tG\t.\t.\t.\n",
+        ];
+
+        let path = directory.join("test.vcf");
+        std::fs::write(&path, format!("{}{}", header, records.concat()))?;
+        let _ = std::fs::remove_file(directory.join("test.vcf.ids"));
+
+        let object_store: Arc<dyn ObjectStore> = Arc::new(LocalFileSystem::new());
+        let location = Path::from_filesystem_path(&path)?;
+        let object_meta = object_store.head(&location).await?;
+
+        // Each record is a chunk, since it spans more than a byte.
+        let index = build_identifier_chunk_index(&object_store, &object_meta, 2, 1).await?;
+
+        let mut start = header.len() as u64;
+        for (chunk, record) in index.chunks().iter().zip(records) {
+            assert_eq!(
+                (chunk.start, chunk.end),
+                (start, start + record.len() as u64)
+            );
+            start = chunk.end;
+        }
+        assert_eq!(index.chunks().len(), 3);
+
+        assert_eq!(
+            predicate(&["rs3"]).map(|p| p.may_match(&index.chunks()[1].filter)),
+            Some(true)
+        );
+        assert_eq!(
+            predicate(&["rs3"]).map(|p| p.may_match(&index.chunks()[0].filter)),
+            Some(false)
+        );
+
+        let read = read_identifier_chunk_index(&object_store, &object_meta).await?;
+        assert_eq!(read, Some(index));
+
+        Ok(())
+    }
+}
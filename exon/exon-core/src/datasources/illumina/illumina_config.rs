@@ -0,0 +1,63 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::datatypes::SchemaRef;
+use exon_common::DEFAULT_BATCH_SIZE;
+use exon_illumina::IlluminaFileKind;
+use object_store::ObjectStore;
+
+/// Configuration for an Illumina run folder file data source.
+pub struct IlluminaConfig {
+    /// The number of rows to read at a time.
+    pub batch_size: usize,
+    /// The schema of the file. This is static for each kind of file.
+    pub file_schema: SchemaRef,
+    /// The object store to use for reading the files.
+    pub object_store: Arc<dyn ObjectStore>,
+    /// The projection to use for reading the files.
+    pub projection: Option<Vec<usize>>,
+    /// The kind of run folder file to read.
+    pub kind: IlluminaFileKind,
+}
+
+impl IlluminaConfig {
+    /// Create a new Illumina configuration.
+    pub fn new(
+        object_store: Arc<dyn ObjectStore>,
+        file_schema: SchemaRef,
+        kind: IlluminaFileKind,
+    ) -> Self {
+        Self {
+            object_store,
+            file_schema,
+            batch_size: DEFAULT_BATCH_SIZE,
+            projection: None,
+            kind,
+        }
+    }
+
+    /// Set the batch size.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set the projection.
+    pub fn with_some_projection(mut self, projection: Option<Vec<usize>>) -> Self {
+        self.projection = projection;
+        self
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use datafusion::{
+    datasource::physical_plan::{FileMeta, FileOpenFuture, FileOpener},
+    error::Result,
+};
+use futures::StreamExt;
+
+use super::illumina_config::IlluminaConfig;
+
+/// Implements a datafusion `FileOpener` for Illumina run folder files.
+pub struct IlluminaOpener {
+    /// The configuration for the opener.
+    config: Arc<IlluminaConfig>,
+}
+
+impl IlluminaOpener {
+    /// Create a new Illumina file opener.
+    pub fn new(config: Arc<IlluminaConfig>) -> Self {
+        Self { config }
+    }
+}
+
+impl FileOpener for IlluminaOpener {
+    fn open(&self, file_meta: FileMeta) -> Result<FileOpenFuture> {
+        let config = Arc::clone(&self.config);
+
+        Ok(Box::pin(async move {
+            let location = file_meta.location();
+
+            // Run folder files are small and their binary layouts are read as a whole, so the
+            // file is fetched in one request rather than streamed.
+            let data = config.object_store.get(location).await?.bytes().await?;
+
+            let run_folder = exon_illumina::run_folder(location.as_ref());
+            let mut batch = config.kind.read(&data, run_folder)?;
+
+            if let Some(projection) = &config.projection {
+                batch = batch.project(projection)?;
+            }
+
+            let batch_size = config.batch_size.max(1);
+            let batches = (0..batch.num_rows())
+                .step_by(batch_size)
+                .map(|offset| {
+                    let length = batch_size.min(batch.num_rows() - offset);
+                    Ok(batch.slice(offset, length))
+                })
+                .collect::<Vec<_>>();
+
+            Ok(futures::stream::iter(batches).boxed())
+        }))
+    }
+}
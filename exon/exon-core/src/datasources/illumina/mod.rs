@@ -0,0 +1,31 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A datafusion compatible datasource for the files of an Illumina run folder, i.e. the run
+//! metadata in `RunInfo.xml` and `RunParameters.xml` and the InterOp metrics in
+//! `InterOp/TileMetricsOut.bin`, `InterOp/QMetricsOut.bin`, and `InterOp/ErrorMetricsOut.bin`.
+
+mod illumina_config;
+mod illumina_opener;
+mod illumina_scanner;
+
+/// Table provider for Illumina run folder files.
+pub mod table_provider;
+
+pub use self::illumina_config::IlluminaConfig;
+pub use self::illumina_opener::IlluminaOpener;
+pub use self::illumina_scanner::IlluminaScan;
+
+mod udtf;
+pub use self::udtf::IlluminaScanFunction;
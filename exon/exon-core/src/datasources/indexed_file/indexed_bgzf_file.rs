@@ -15,15 +15,22 @@
 use std::sync::Arc;
 
 use datafusion::datasource::listing::PartitionedFile;
+use exon_bam::read_header_with_limits;
+use exon_common::ReaderLimits;
+use itertools::Itertools;
 use noodles::{
-    core::Region,
-    csi::{binning_index::index::reference_sequence::bin::Chunk, BinningIndex},
+    core::{region::Interval, Region},
+    csi::{
+        binning_index::{index::reference_sequence::bin::Chunk, ReferenceSequence as _},
+        BinningIndex,
+    },
 };
 use object_store::{path::Path, ObjectMeta, ObjectStore};
 use tokio_util::io::StreamReader;
 
 use datafusion::error::Result;
 
+#[derive(Debug, Clone, Copy)]
 pub enum IndexedBGZFFile {
     Vcf,
     Bam,
@@ -55,6 +62,25 @@ pub async fn get_byte_range_for_file(
     region: &Region,
     indexed_file: &IndexedBGZFFile,
 ) -> Result<Vec<Chunk>> {
+    let mut chunks = get_byte_ranges_for_regions(
+        object_store,
+        object_meta,
+        std::slice::from_ref(region),
+        indexed_file,
+    )
+    .await?;
+
+    Ok(chunks.pop().unwrap_or_default())
+}
+
+/// Read the index of a file and resolve the reference sequence id of each region, `None` if the
+/// file has no such reference sequence. BAI and tabix indexes share the binning index type.
+async fn read_index_for_regions(
+    object_store: Arc<dyn ObjectStore>,
+    object_meta: &ObjectMeta,
+    regions: &[Region],
+    indexed_file: &IndexedBGZFFile,
+) -> Result<(noodles::tabix::Index, Vec<Option<usize>>)> {
     let path = format!(
         "{}{}",
         object_meta.location,
@@ -65,7 +91,7 @@ pub async fn get_byte_range_for_file(
     let index_bytes = object_store.get(&path).await?.bytes().await?;
     let cursor = std::io::Cursor::new(index_bytes);
 
-    let chunks = match indexed_file {
+    match indexed_file {
         IndexedBGZFFile::Vcf | IndexedBGZFFile::Gff => {
             let index = noodles::tabix::Reader::new(cursor).read_index()?;
 
@@ -73,42 +99,109 @@ pub async fn get_byte_range_for_file(
                 std::io::Error::new(std::io::ErrorKind::InvalidInput, "missing tabix header")
             })?;
 
-            let region_name = region.name();
-            let id = header.reference_sequence_names().get_index_of(region_name);
-
-            match id {
-                Some(id) => {
-                    let chunks = index.query(id, region.interval())?;
+            let ids = regions
+                .iter()
+                .map(|region| {
+                    header
+                        .reference_sequence_names()
+                        .get_index_of(region.name())
+                })
+                .collect();
 
-                    Ok(chunks)
-                }
-                None => Ok(vec![]),
-            }
+            Ok((index, ids))
         }
         IndexedBGZFFile::Bam => {
             let stream = object_store.get(&object_meta.location).await?.into_stream();
             let reader = StreamReader::new(stream);
             let mut bam_reader = noodles::bam::AsyncReader::new(reader);
 
-            let header = bam_reader.read_header().await?;
+            // Only the reference sequence names are needed to plan, the scan checks the header
+            // against the session's limits.
+            let header =
+                read_header_with_limits(bam_reader.get_mut(), &ReaderLimits::default()).await?;
 
             let mut index_reader = noodles::bam::bai::Reader::new(cursor);
             let index = index_reader.read_index()?;
 
-            let id = header.reference_sequences().get_index_of(region.name());
+            let ids = regions
+                .iter()
+                .map(|region| header.reference_sequences().get_index_of(region.name()))
+                .collect();
+
+            Ok((index, ids))
+        }
+    }
+}
 
-            match id {
-                Some(id) => {
-                    let chunks = index.query(id, region.interval())?;
+/// For a given file, get the list of byte ranges for each of the given regions. The index (and
+/// for BAM, the header) is read once for all the regions.
+#[tracing::instrument(
+    name = "exon.resolve_index",
+    skip(object_store, object_meta, regions),
+    fields(path = %object_meta.location, regions = regions.len(), chunks = tracing::field::Empty)
+)]
+pub async fn get_byte_ranges_for_regions(
+    object_store: Arc<dyn ObjectStore>,
+    object_meta: &ObjectMeta,
+    regions: &[Region],
+    indexed_file: &IndexedBGZFFile,
+) -> Result<Vec<Vec<Chunk>>> {
+    let (index, ids) =
+        read_index_for_regions(object_store, object_meta, regions, indexed_file).await?;
+
+    let chunks = regions
+        .iter()
+        .zip(ids)
+        .map(|(region, id)| match id {
+            Some(id) => index.query(id, region.interval()),
+            None => Ok(vec![]),
+        })
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    tracing::Span::current().record("chunks", chunks.iter().map(Vec::len).sum::<usize>());
+
+    Ok(chunks)
+}
 
-                    Ok(chunks)
-                }
-                None => Ok(vec![]),
+/// For a given file, count the records on the reference sequences of the given regions from the
+/// metadata pseudo-bins of the index, without reading the file's data blocks.
+///
+/// The count is only the count of the regions if they each cover a whole reference sequence.
+/// Returns `None` if the index has no metadata for a reference sequence that has records.
+#[tracing::instrument(
+    name = "exon.count_from_index",
+    skip(object_store, object_meta, regions),
+    fields(path = %object_meta.location, regions = regions.len(), records = tracing::field::Empty)
+)]
+pub(crate) async fn count_records_from_index(
+    object_store: Arc<dyn ObjectStore>,
+    object_meta: &ObjectMeta,
+    regions: &[Region],
+    indexed_file: &IndexedBGZFFile,
+) -> Result<Option<u64>> {
+    let (index, ids) =
+        read_index_for_regions(object_store, object_meta, regions, indexed_file).await?;
+
+    let mut records = 0;
+
+    for id in ids.into_iter().flatten().unique() {
+        let reference_sequence = match index.reference_sequences().get(id) {
+            Some(reference_sequence) if !reference_sequence.bins().is_empty() => reference_sequence,
+            // A reference sequence without bins has no records.
+            _ => continue,
+        };
+
+        match reference_sequence.metadata() {
+            Some(metadata) => {
+                records += metadata.mapped_record_count() + metadata.unmapped_record_count()
             }
+            None => return Ok(None),
         }
-    };
+    }
+
+    tracing::Span::current().record("records", records);
 
-    chunks
+    Ok(Some(records))
 }
 
 pub(crate) struct BGZFIndexedOffsets {
@@ -125,6 +218,70 @@ impl From<Chunk> for BGZFIndexedOffsets {
     }
 }
 
+/// The byte range of one chunk of a region-sharded scan, along with the region the chunk was
+/// resolved for.
+pub(crate) struct BGZFRegionOffsets {
+    pub offsets: BGZFIndexedOffsets,
+    pub region: Arc<Region>,
+
+    /// The interval of the previous region on the same reference sequence, if any. Records that
+    /// intersect it are read by that region's chunks.
+    pub preceding_interval: Option<Interval>,
+}
+
+/// Split a partitioned file into one partitioned file per index chunk of the given regions, with
+/// [`BGZFRegionOffsets`] as the extension.
+///
+/// The regions must be sorted and must not overlap, see [`super::region::merge_regions`]. The
+/// files are returned grouped by reference sequence, one group per distinct region name in the
+/// order they appear in `regions`, so the scan can plan one partition per reference sequence.
+pub(crate) async fn shard_partitioned_file_by_region(
+    object_store: Arc<dyn ObjectStore>,
+    partitioned_file: &PartitionedFile,
+    regions: &[Region],
+    indexed_file: &IndexedBGZFFile,
+) -> Result<Vec<Vec<PartitionedFile>>> {
+    let chunks = get_byte_ranges_for_regions(
+        object_store,
+        &partitioned_file.object_meta,
+        regions,
+        indexed_file,
+    )
+    .await?;
+
+    let mut groups: Vec<Vec<PartitionedFile>> = Vec::new();
+    let mut previous: Option<&Region> = None;
+
+    for (region, region_chunks) in regions.iter().zip(chunks) {
+        let preceding_interval = match previous {
+            Some(p) if p.name() == region.name() => Some(p.interval()),
+            _ => None,
+        };
+        previous = Some(region);
+
+        let region = Arc::new(region.clone());
+
+        let shards = region_chunks.into_iter().map(|chunk| {
+            let offsets = BGZFRegionOffsets {
+                offsets: BGZFIndexedOffsets::from(chunk),
+                region: Arc::clone(&region),
+                preceding_interval,
+            };
+
+            let mut new_partition_file = partitioned_file.clone();
+            new_partition_file.extensions = Some(Arc::new(offsets));
+            new_partition_file
+        });
+
+        match groups.last_mut() {
+            Some(group) if preceding_interval.is_some() => group.extend(shards),
+            _ => groups.push(shards.collect()),
+        }
+    }
+
+    Ok(groups)
+}
+
 /// Augment a partitioned file with the byte ranges that need to be read for a given region
 pub(crate) async fn augment_partitioned_file_with_byte_range(
     object_store: Arc<dyn ObjectStore>,
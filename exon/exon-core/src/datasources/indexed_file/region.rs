@@ -1,4 +1,4 @@
-use noodles::core::Region;
+use noodles::core::{region::Interval, Position, Region};
 
 /// A region object store extension.
 pub(crate) struct RegionObjectStoreExtension {
@@ -18,3 +18,87 @@ impl RegionObjectStoreExtension {
         self.region.to_string()
     }
 }
+
+fn interval(start: Option<Position>, end: Option<Position>) -> Interval {
+    match (start, end) {
+        (Some(start), Some(end)) => (start..=end).into(),
+        (Some(start), None) => (start..).into(),
+        (None, Some(end)) => (..=end).into(),
+        (None, None) => (..).into(),
+    }
+}
+
+/// Sort the regions by reference sequence and start, and merge the regions that overlap or abut,
+/// so each position is covered by at most one region.
+pub(crate) fn merge_regions(regions: &[Region]) -> Vec<Region> {
+    let mut sorted = regions.to_vec();
+    sorted.sort_by(|a, b| {
+        a.name()
+            .cmp(b.name())
+            .then_with(|| a.interval().start().cmp(&b.interval().start()))
+    });
+
+    let mut merged: Vec<Region> = Vec::with_capacity(sorted.len());
+
+    for region in sorted {
+        if let Some(last) = merged.last_mut() {
+            let last_interval = last.interval();
+            let next_interval = region.interval();
+
+            let touches = last.name() == region.name()
+                && match (last_interval.end(), next_interval.start()) {
+                    (None, _) | (_, None) => true,
+                    (Some(end), Some(start)) => usize::from(start) <= usize::from(end) + 1,
+                };
+
+            if touches {
+                let end = match (last_interval.end(), next_interval.end()) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    _ => None,
+                };
+
+                *last = Region::new(last.name(), interval(last_interval.start(), end));
+                continue;
+            }
+        }
+
+        merged.push(region);
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use noodles::core::Region;
+
+    use super::merge_regions;
+
+    fn regions(regions: &[&str]) -> Vec<Region> {
+        regions.iter().map(|r| r.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_merge_regions() {
+        let merged = merge_regions(&regions(&[
+            "chr2:10-20",
+            "chr1:100-200",
+            "chr1:150-300",
+            "chr1:1-50",
+            "chr2:21-30",
+            "chr2:40-50",
+        ]));
+
+        assert_eq!(
+            merged,
+            regions(&["chr1:1-50", "chr1:100-300", "chr2:10-30", "chr2:40-50"])
+        );
+    }
+
+    #[test]
+    fn test_merge_regions_unbounded() {
+        let merged = merge_regions(&regions(&["chr1:100-200", "chr1", "chr2:5-10"]));
+
+        assert_eq!(merged, regions(&["chr1", "chr2:5-10"]));
+    }
+}
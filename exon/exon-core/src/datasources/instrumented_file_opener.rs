@@ -0,0 +1,212 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use arrow::{error::ArrowError, record_batch::RecordBatch};
+use datafusion::{
+    datasource::physical_plan::{FileMeta, FileOpenFuture, FileOpener},
+    error::{DataFusionError, Result},
+};
+use exon_common::ReaderLimitError;
+use futures::{stream::BoxStream, Stream, StreamExt};
+use noodles::core::Region;
+use tracing::{field::Empty, Instrument, Span};
+
+use crate::error::{ExonError, SourceLocation};
+
+/// A file opener that runs each file scan inside an `exon.scan_file` tracing span and adds the
+/// file path and the number of records read to errors, so clients can tell which file and record
+/// failed.
+///
+/// The span carries the file path, the byte range and region when the scan has one, and the
+/// number of batches and rows read so far.
+#[derive(Debug)]
+pub(crate) struct InstrumentedFileOpener<O> {
+    inner: O,
+    region: Option<String>,
+}
+
+impl<O> InstrumentedFileOpener<O> {
+    /// Wrap a file opener.
+    pub(crate) fn new(inner: O) -> Self {
+        Self {
+            inner,
+            region: None,
+        }
+    }
+
+    /// Record the region the scan is restricted to on the span.
+    pub(crate) fn with_region(mut self, region: &Region) -> Self {
+        self.region = Some(region.to_string());
+        self
+    }
+}
+
+fn locate(error: ExonError, path: &str, record_number: Option<u64>) -> ExonError {
+    let mut location = SourceLocation::new(path);
+
+    if let Some(record_number) = record_number {
+        location = location.with_record_number(record_number);
+    }
+
+    if let Some(position) = ReaderLimitError::find(&error).and_then(|e| e.position) {
+        location = location.with_byte_offset(position);
+    }
+
+    error.with_location(location)
+}
+
+impl<O> FileOpener for InstrumentedFileOpener<O>
+where
+    O: FileOpener,
+{
+    fn open(&self, file_meta: FileMeta) -> Result<FileOpenFuture> {
+        let path = file_meta.location().to_string();
+
+        let span = tracing::info_span!(
+            "exon.scan_file",
+            path = %path,
+            range_start = Empty,
+            range_end = Empty,
+            region = Empty,
+            batches = 0u64,
+            rows = 0u64,
+        );
+
+        if let Some(range) = &file_meta.range {
+            span.record("range_start", range.start);
+            span.record("range_end", range.end);
+        }
+
+        if let Some(region) = &self.region {
+            span.record("region", region.as_str());
+        }
+
+        let future = span
+            .in_scope(|| self.inner.open(file_meta))
+            .map_err(|e| DataFusionError::from(locate(e.into(), &path, None)))?;
+
+        Ok(Box::pin(
+            async move {
+                let stream = future
+                    .await
+                    .map_err(|e| DataFusionError::from(locate(e.into(), &path, None)))?;
+
+                let stream = InstrumentedStream {
+                    inner: stream,
+                    span: Span::current(),
+                    path,
+                    batches: 0,
+                    rows: 0,
+                };
+
+                Ok(stream.boxed())
+            }
+            .instrument(span),
+        ))
+    }
+}
+
+/// Polls the file's batch stream inside the scan span, counting batches and rows.
+struct InstrumentedStream {
+    inner: BoxStream<'static, std::result::Result<RecordBatch, ArrowError>>,
+    span: Span,
+    path: String,
+    batches: u64,
+    rows: u64,
+}
+
+impl Stream for InstrumentedStream {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let _entered = this.span.enter();
+
+        match this.inner.poll_next_unpin(cx) {
+            Poll::Ready(Some(Ok(batch))) => {
+                this.batches += 1;
+                this.rows += batch.num_rows() as u64;
+
+                this.span.record("batches", this.batches);
+                this.span.record("rows", this.rows);
+
+                Poll::Ready(Some(Ok(batch)))
+            }
+            Poll::Ready(Some(Err(e))) => {
+                let error = locate(e.into(), &this.path, Some(this.rows));
+                tracing::debug!(error = %error, "file scan failed");
+
+                Poll::Ready(Some(Err(ArrowError::ExternalError(Box::new(error)))))
+            }
+            Poll::Ready(None) => {
+                tracing::debug!(
+                    batches = this.batches,
+                    rows = this.rows,
+                    "finished file scan"
+                );
+
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::{ErrorAction, ExonErrorCode},
+        ExonError, ExonSession,
+    };
+
+    #[tokio::test]
+    async fn test_reader_errors_are_located() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let path = exon_test::test_path("fastq", "test.fastq");
+        let sql = format!(
+            "CREATE EXTERNAL TABLE fastq_table STORED AS FASTQ LOCATION '{}'",
+            path.display()
+        );
+        ctx.sql(&sql).await?.collect().await?;
+
+        ctx.sql("SET exon.max_sequence_length = 4")
+            .await?
+            .collect()
+            .await?;
+
+        let err = ctx
+            .sql("SELECT * FROM fastq_table")
+            .await?
+            .collect()
+            .await
+            .unwrap_err();
+        let err = ExonError::from(err);
+
+        assert_eq!(err.code(), ExonErrorCode::ReaderLimitExceeded);
+        assert_eq!(err.code().as_str(), "EXON-3002");
+        assert_eq!(err.action(), ErrorAction::Skip);
+
+        let location = err.location().unwrap();
+        assert!(location.path.ends_with("fastq/test.fastq"));
+        assert_eq!(location.record_number, Some(0));
+
+        Ok(())
+    }
+}
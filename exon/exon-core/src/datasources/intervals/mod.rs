@@ -0,0 +1,24 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bedtools-like set operations over interval tables.
+//!
+//! The first three columns of the input tables are read as the reference name, start, and end of
+//! half-open intervals, e.g. a BED table.
+
+mod table_provider;
+mod udtf;
+
+pub use table_provider::IntervalSetTable;
+pub use udtf::{ComplementIntervalsFunction, MergeIntervalsFunction, SubtractIntervalsFunction};
@@ -0,0 +1,193 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::datatypes::{DataType, SchemaRef};
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    common::Column,
+    datasource::{TableProvider, TableType},
+    error::{DataFusionError, Result},
+    logical_expr::{lit, Expr, LogicalPlan, LogicalPlanBuilder},
+    physical_plan::{expressions::col, projection::ProjectionExec, ExecutionPlan},
+};
+
+use crate::physical_plan::interval_set_exec::{
+    interval_set_schema, IntervalSetExec, IntervalSetOperation,
+};
+
+/// A table that applies an interval set operation to the output of one or two logical plans.
+#[derive(Debug)]
+pub struct IntervalSetTable {
+    operation: IntervalSetOperation,
+    inputs: Vec<LogicalPlan>,
+    schema: SchemaRef,
+}
+
+impl IntervalSetTable {
+    fn try_new(operation: IntervalSetOperation, inputs: Vec<LogicalPlan>) -> Result<Self> {
+        for input in inputs.iter() {
+            if input.schema().fields().len() < 3 {
+                return Err(DataFusionError::Plan(format!(
+                    "Interval {} requires tables with reference, start, and end columns",
+                    operation
+                )));
+            }
+        }
+
+        let schema = interval_set_schema(inputs[0].schema().as_arrow());
+
+        Ok(Self {
+            operation,
+            inputs,
+            schema,
+        })
+    }
+
+    /// Merge the overlapping and book-ended intervals of `input`.
+    pub fn merge(input: LogicalPlan) -> Result<Self> {
+        Self::try_new(IntervalSetOperation::Merge, vec![input])
+    }
+
+    /// Remove the parts of the intervals of `left` that overlap the intervals of `right`.
+    pub fn subtract(left: LogicalPlan, right: LogicalPlan) -> Result<Self> {
+        Self::try_new(IntervalSetOperation::Subtract, vec![left, right])
+    }
+
+    /// The intervals of a genome not covered by the intervals of `input`.
+    ///
+    /// The genome is a table of reference names and lengths, the complement is the genome's
+    /// references as whole intervals with `input` subtracted.
+    pub fn complement(input: LogicalPlan, genome: LogicalPlan) -> Result<Self> {
+        let genome_schema = genome.schema();
+
+        if genome_schema.fields().len() < 2 {
+            return Err(DataFusionError::Plan(
+                "Interval complement requires a genome table with name and length columns"
+                    .to_string(),
+            ));
+        }
+
+        let names = interval_set_schema(input.schema().as_arrow());
+        let column = |i: usize| Expr::Column(Column::from(genome_schema.qualified_field(i)));
+
+        let genome = LogicalPlanBuilder::from(genome.clone())
+            .project(vec![
+                column(0).alias(names.field(0).name()),
+                lit(0_i64).alias(names.field(1).name()),
+                column(1)
+                    .cast_to(&DataType::Int64, genome_schema.as_ref())?
+                    .alias(names.field(2).name()),
+            ])?
+            .build()?;
+
+        Self::subtract(genome, input)
+    }
+}
+
+#[async_trait]
+impl TableProvider for IntervalSetTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let mut inputs = Vec::with_capacity(self.inputs.len());
+        for input in self.inputs.iter() {
+            inputs.push(state.create_physical_plan(input).await?);
+        }
+
+        let exec = Arc::new(IntervalSetExec::try_new(self.operation, inputs)?);
+
+        let Some(projection) = projection else {
+            return Ok(exec);
+        };
+
+        let exprs = projection
+            .iter()
+            .map(|i| {
+                let name = self.schema.field(*i).name();
+                Ok((col(name, &self.schema)?, name.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(ProjectionExec::try_new(exprs, exec)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{array::AsArray, datatypes::Int64Type};
+
+    use crate::ExonSession;
+
+    #[tokio::test]
+    async fn test_complement_intervals() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let intervals = ctx
+            .session
+            .sql("SELECT * FROM (VALUES ('chr2', 0, 5), ('chr1', 10, 20)) AS t(chrom, start, \"end\")")
+            .await?;
+        let genome = ctx
+            .session
+            .sql("SELECT * FROM (VALUES ('chr1', 100), ('chr2', 5), ('chr3', 50)) AS g(name, length)")
+            .await?;
+
+        let batches = ctx
+            .complement_intervals(intervals, genome)?
+            .collect()
+            .await?;
+
+        let mut rows = Vec::new();
+        for batch in batches {
+            assert_eq!(batch.schema().field(0).name(), "chrom");
+
+            let chroms = batch.column(0).as_string::<i32>();
+            let starts = batch.column(1).as_primitive::<Int64Type>();
+            let ends = batch.column(2).as_primitive::<Int64Type>();
+
+            for i in 0..batch.num_rows() {
+                rows.push((chroms.value(i).to_string(), starts.value(i), ends.value(i)));
+            }
+        }
+
+        assert_eq!(
+            rows,
+            vec![
+                ("chr1".to_string(), 0, 10),
+                ("chr1".to_string(), 20, 100),
+                ("chr3".to_string(), 0, 50),
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt::Debug, sync::Arc};
+
+use datafusion::{
+    datasource::{function::TableFunctionImpl, TableProvider},
+    error::{DataFusionError, Result},
+    logical_expr::{Expr, LogicalPlan},
+};
+
+use super::IntervalSetTable;
+
+/// Get the logical plans of the subquery arguments of an interval set function.
+fn subquery_arguments(exprs: &[Expr], n: usize) -> Result<Vec<LogicalPlan>> {
+    if exprs.len() != n {
+        return Err(DataFusionError::Plan(format!(
+            "this function requires {} subquery arguments, got {}",
+            n,
+            exprs.len()
+        )));
+    }
+
+    exprs
+        .iter()
+        .map(|expr| match expr {
+            Expr::ScalarSubquery(subquery) => Ok(subquery.subquery.as_ref().clone()),
+            _ => Err(DataFusionError::Plan(format!(
+                "this function requires subquery arguments, e.g. (SELECT * FROM t), got {}",
+                expr
+            ))),
+        })
+        .collect()
+}
+
+/// A table function that merges the overlapping intervals of a subquery.
+#[derive(Default)]
+pub struct MergeIntervalsFunction {}
+
+impl Debug for MergeIntervalsFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MergeIntervalsFunction").finish()
+    }
+}
+
+impl TableFunctionImpl for MergeIntervalsFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let [input]: [LogicalPlan; 1] = subquery_arguments(exprs, 1)?
+            .try_into()
+            .map_err(|_| DataFusionError::Internal("expected one subquery".to_string()))?;
+
+        Ok(Arc::new(IntervalSetTable::merge(input)?))
+    }
+}
+
+/// A table function that subtracts the intervals of the second subquery from the first.
+#[derive(Default)]
+pub struct SubtractIntervalsFunction {}
+
+impl Debug for SubtractIntervalsFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubtractIntervalsFunction").finish()
+    }
+}
+
+impl TableFunctionImpl for SubtractIntervalsFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let [left, right]: [LogicalPlan; 2] = subquery_arguments(exprs, 2)?
+            .try_into()
+            .map_err(|_| DataFusionError::Internal("expected two subqueries".to_string()))?;
+
+        Ok(Arc::new(IntervalSetTable::subtract(left, right)?))
+    }
+}
+
+/// A table function that returns the intervals of a genome, given as the second subquery of
+/// names and lengths, not covered by the intervals of the first subquery.
+#[derive(Default)]
+pub struct ComplementIntervalsFunction {}
+
+impl Debug for ComplementIntervalsFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComplementIntervalsFunction").finish()
+    }
+}
+
+impl TableFunctionImpl for ComplementIntervalsFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let [input, genome]: [LogicalPlan; 2] = subquery_arguments(exprs, 2)?
+            .try_into()
+            .map_err(|_| DataFusionError::Internal("expected two subqueries".to_string()))?;
+
+        Ok(Arc::new(IntervalSetTable::complement(input, genome)?))
+    }
+}
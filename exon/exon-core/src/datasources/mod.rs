@@ -25,6 +25,15 @@ pub mod bcf;
 /// BED module.
 pub mod bed;
 
+/// Kraken2 and centrifuge classification module.
+pub mod classification;
+
+/// featureCounts, HTSeq-count, and counts matrix module.
+pub mod counts;
+
+/// Salmon and kallisto transcript quantification module.
+pub mod quantification;
+
 /// FASTA module.
 pub mod fasta;
 
@@ -41,16 +50,40 @@ pub mod gff;
 /// HMMDOMTAB module.
 pub mod hmmdomtab;
 
+/// Illumina run folder module.
+pub mod illumina;
+
+pub mod intervals;
+
+/// POD5 module.
+pub mod pod5;
+
 /// MzML module.
 #[cfg(feature = "mzml")]
 pub mod mzml;
 
+/// MTX module.
+pub mod mtx;
+
+/// Remote reference file cache module.
+pub mod object_cache;
+
 // SAM module.
 pub mod sam;
 
+/// Sequencing summary module.
+pub mod sequencing_summary;
+
+/// SRA module.
+#[cfg(feature = "sra")]
+pub mod sra;
+
 /// VCF module.
 pub mod vcf;
 
+/// VCF Zarr module.
+pub mod vcf_zarr;
+
 /// CRAM module.
 pub mod cram;
 
@@ -67,9 +100,25 @@ pub mod fcs;
 /// Exon listing table options.
 pub mod exon_listing_table_options;
 
+/// A listing table shared by the file formats.
+pub mod exon_listing_table;
+
 /// SDF module.
 pub mod sdf;
 
+/// Tables with a user-supplied schema.
+pub mod explicit_schema;
+
+/// Delta Lake and Parquet tables answering region filter UDFs with filters on their columns.
+pub mod region_filter_table;
+
+/// Tables that read their files through their own object store, e.g. one verifying checksums or
+/// reading through the cache.
+pub mod table_object_store;
+
+/// Per-record provenance columns for listing tables.
+pub mod provenance;
+
 /// File types.
 mod exon_file_type;
 
@@ -86,8 +135,16 @@ pub use self::exon_listing_table_factory::ExonListingTableFactory;
 mod exon_file_scan_config;
 pub use self::exon_file_scan_config::ExonFileScanConfig;
 
+mod instrumented_file_opener;
+pub(crate) use self::instrumented_file_opener::InstrumentedFileOpener;
+
 pub(crate) mod indexed_file;
 
+/// Exact statistics of small files.
+pub(crate) mod file_statistics;
+
+pub(crate) mod identifier_index;
+
 mod scan_function;
 
 pub(crate) use self::scan_function::ScanFunction;
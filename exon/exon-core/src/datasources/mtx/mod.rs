@@ -0,0 +1,32 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A datafusion compatible datasource for 10x Genomics feature-barcode matrices, i.e. a
+//! MatrixMarket file with barcodes.tsv and features.tsv (or genes.tsv) files next to it.
+
+mod mtx_batch_reader;
+mod mtx_config;
+mod mtx_opener;
+mod mtx_scanner;
+mod mtx_schema_builder;
+
+/// Table provider for feature-barcode matrices.
+pub mod table_provider;
+
+pub use self::mtx_config::MTXConfig;
+pub use self::mtx_opener::MTXOpener;
+pub use self::mtx_scanner::MTXScan;
+
+mod udtf;
+pub use self::udtf::MTXScanFunction;
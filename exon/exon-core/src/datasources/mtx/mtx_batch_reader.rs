@@ -0,0 +1,287 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, Int64Builder, StringBuilder},
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use super::mtx_config::MTXConfig;
+
+/// The header of the coordinate format that CellRanger writes its matrices in.
+const MATRIX_MARKET_HEADER: &str = "%%MatrixMarket matrix coordinate integer general";
+
+/// A feature in the rows of the matrix, from a line of features.tsv or genes.tsv.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feature {
+    pub id: String,
+    pub name: Option<String>,
+    pub feature_type: Option<String>,
+}
+
+impl Feature {
+    /// Parse a line of features.tsv, or of genes.tsv which has no feature type.
+    pub fn parse(line: &str) -> Self {
+        let mut fields = line.split('\t').map(|field| field.to_string());
+
+        Self {
+            id: fields.next().unwrap_or_default(),
+            name: fields.next(),
+            feature_type: fields.next(),
+        }
+    }
+}
+
+fn invalid_matrix(message: String) -> ArrowError {
+    ArrowError::ParseError(format!("Invalid MatrixMarket file: {message}"))
+}
+
+/// Look up the label of a one-based row or column index.
+fn label<'a, T>(labels: &'a [T], index: &str, kind: &str) -> Result<&'a T, ArrowError> {
+    index
+        .parse::<usize>()
+        .ok()
+        .and_then(|index| index.checked_sub(1))
+        .and_then(|index| labels.get(index))
+        .ok_or_else(|| invalid_matrix(format!("{kind} index {index} is out of range")))
+}
+
+/// Reads the entries of a MatrixMarket file into record batches, labeling each entry with the
+/// barcode of its column and the feature of its row.
+pub struct MTXBatchReader<R> {
+    /// The underlying reader of the matrix.
+    reader: R,
+
+    /// The configuration for this reader.
+    config: Arc<MTXConfig>,
+
+    /// The barcodes of the columns.
+    barcodes: Vec<String>,
+
+    /// The features of the rows.
+    features: Vec<Feature>,
+}
+
+impl<R> MTXBatchReader<R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    /// Create a batch reader, reading the header of the matrix and checking its size against the
+    /// barcodes and features.
+    pub async fn try_new(
+        mut reader: R,
+        config: Arc<MTXConfig>,
+        barcodes: Vec<String>,
+        features: Vec<Feature>,
+    ) -> Result<Self, ArrowError> {
+        let mut line = String::new();
+        reader.read_line(&mut line).await?;
+
+        if !line.trim_end().eq_ignore_ascii_case(MATRIX_MARKET_HEADER) {
+            return Err(invalid_matrix(format!(
+                "expected the header {MATRIX_MARKET_HEADER}, found {}",
+                line.trim_end()
+            )));
+        }
+
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).await? == 0 {
+                return Err(invalid_matrix("missing the size line".to_string()));
+            }
+
+            if !line.starts_with('%') {
+                break;
+            }
+        }
+
+        let size = line
+            .split_whitespace()
+            .map(|value| value.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid_matrix(format!("invalid size line {}", line.trim_end())))?;
+
+        match size.as_slice() {
+            [rows, columns, _] if *rows == features.len() && *columns == barcodes.len() => {}
+            _ => {
+                return Err(invalid_matrix(format!(
+                    "size {} doesn't match {} features and {} barcodes",
+                    line.trim_end(),
+                    features.len(),
+                    barcodes.len()
+                )))
+            }
+        }
+
+        Ok(Self {
+            reader,
+            config,
+            barcodes,
+            features,
+        })
+    }
+
+    pub async fn read_batch(&mut self) -> Result<Option<RecordBatch>, ArrowError> {
+        let mut barcodes = StringBuilder::new();
+        let mut feature_ids = StringBuilder::new();
+        let mut feature_names = StringBuilder::new();
+        let mut feature_types = StringBuilder::new();
+        let mut counts = Int64Builder::new();
+
+        let mut line = String::new();
+        let mut rows = 0;
+
+        while rows < self.config.batch_size {
+            line.clear();
+            if self.reader.read_line(&mut line).await? == 0 {
+                break;
+            }
+
+            let entry = line.split_whitespace().collect::<Vec<_>>();
+            let [row, column, count] = entry.as_slice() else {
+                if entry.is_empty() {
+                    continue;
+                }
+
+                return Err(invalid_matrix(format!("invalid entry {}", line.trim_end())));
+            };
+
+            let feature = label(&self.features, row, "row")?;
+            let barcode = label(&self.barcodes, column, "column")?;
+            let count = count
+                .parse::<i64>()
+                .map_err(|_| invalid_matrix(format!("invalid count {count}")))?;
+
+            barcodes.append_value(barcode);
+            feature_ids.append_value(&feature.id);
+            feature_names.append_option(feature.name.as_deref());
+            feature_types.append_option(feature.feature_type.as_deref());
+            counts.append_value(count);
+
+            rows += 1;
+        }
+
+        if rows == 0 {
+            return Ok(None);
+        }
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(barcodes.finish()),
+            Arc::new(feature_ids.finish()),
+            Arc::new(feature_names.finish()),
+            Arc::new(feature_types.finish()),
+            Arc::new(counts.finish()),
+        ];
+
+        let batch = RecordBatch::try_new(Arc::clone(&self.config.file_schema), columns)?;
+
+        match &self.config.projection {
+            Some(projection) => Ok(Some(batch.project(projection)?)),
+            None => Ok(Some(batch)),
+        }
+    }
+
+    pub fn into_stream(self) -> impl futures::Stream<Item = Result<RecordBatch, ArrowError>> {
+        futures::stream::unfold(self, |mut reader| async move {
+            match reader.read_batch().await {
+                Ok(Some(batch)) => Some((Ok(batch), reader)),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), reader)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{AsArray, Int64Array};
+    use object_store::local::LocalFileSystem;
+
+    use crate::datasources::mtx::mtx_schema_builder::MTXSchemaBuilder;
+
+    use super::*;
+
+    const MATRIX: &str =
+        "%%MatrixMarket matrix coordinate integer general\n%comment\n2 2 3\n1 1 5\n2 1 2\n2 2 7\n";
+
+    fn config() -> Arc<MTXConfig> {
+        let file_schema = MTXSchemaBuilder::default().build().file_schema().unwrap();
+
+        Arc::new(MTXConfig::new(
+            Arc::new(LocalFileSystem::new()),
+            file_schema,
+        ))
+    }
+
+    #[test]
+    fn test_parse_feature() {
+        assert_eq!(
+            Feature::parse("ENSG00000243485\tMIR1302-2HG\tGene Expression"),
+            Feature {
+                id: "ENSG00000243485".to_string(),
+                name: Some("MIR1302-2HG".to_string()),
+                feature_type: Some("Gene Expression".to_string()),
+            }
+        );
+
+        assert_eq!(
+            Feature::parse("ENSG00000243485\tMIR1302-2HG").feature_type,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_matrix() -> Result<(), ArrowError> {
+        let barcodes = vec!["AAAC-1".to_string(), "AAAG-1".to_string()];
+        let features = vec![Feature::parse("g1\tA"), Feature::parse("g2\tB")];
+
+        let mut reader =
+            MTXBatchReader::try_new(MATRIX.as_bytes(), config(), barcodes, features).await?;
+
+        let batch = reader.read_batch().await?.unwrap();
+        assert_eq!(batch.num_rows(), 3);
+
+        let barcodes = batch.column(0).as_string::<i32>();
+        assert_eq!(barcodes.value(1), "AAAC-1");
+        assert_eq!(barcodes.value(2), "AAAG-1");
+
+        let feature_ids = batch.column(1).as_string::<i32>();
+        assert_eq!(feature_ids.value(1), "g2");
+
+        assert_eq!(batch.column(3).null_count(), 3);
+        assert_eq!(
+            batch.column(4).as_any().downcast_ref::<Int64Array>(),
+            Some(&Int64Array::from(vec![5, 2, 7]))
+        );
+
+        assert!(reader.read_batch().await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_size_mismatch() {
+        let barcodes = vec!["AAAC-1".to_string()];
+        let features = vec![Feature::parse("g1\tA"), Feature::parse("g2\tB")];
+
+        let result = MTXBatchReader::try_new(MATRIX.as_bytes(), config(), barcodes, features).await;
+        assert!(result.is_err());
+    }
+}
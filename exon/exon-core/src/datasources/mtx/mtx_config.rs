@@ -0,0 +1,55 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::datatypes::SchemaRef;
+use exon_common::DEFAULT_BATCH_SIZE;
+use object_store::ObjectStore;
+
+/// Configuration for a feature-barcode matrix data source.
+pub struct MTXConfig {
+    /// The number of rows to read at a time.
+    pub batch_size: usize,
+    /// The schema of the MTX file. This is static.
+    pub file_schema: SchemaRef,
+    /// The object store to use for reading MTX files.
+    pub object_store: Arc<dyn ObjectStore>,
+    /// The projection to use for reading MTX files.
+    pub projection: Option<Vec<usize>>,
+}
+
+impl MTXConfig {
+    /// Create a new MTX configuration.
+    pub fn new(object_store: Arc<dyn ObjectStore>, file_schema: SchemaRef) -> Self {
+        Self {
+            object_store,
+            file_schema,
+            batch_size: DEFAULT_BATCH_SIZE,
+            projection: None,
+        }
+    }
+
+    /// Set the batch size.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set the projection.
+    pub fn with_some_projection(mut self, projection: Option<Vec<usize>>) -> Self {
+        self.projection = projection;
+        self
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use datafusion::{
+    datasource::{
+        file_format::file_compression_type::FileCompressionType,
+        physical_plan::{FileMeta, FileOpenFuture, FileOpener},
+    },
+    error::{DataFusionError, Result},
+};
+use futures::{StreamExt, TryStreamExt};
+use object_store::{path::Path, ObjectStore};
+use tokio::io::AsyncBufReadExt;
+use tokio_util::io::StreamReader;
+
+use super::{
+    mtx_batch_reader::{Feature, MTXBatchReader},
+    mtx_config::MTXConfig,
+};
+
+/// The location of a file in the same directory as the matrix.
+fn sidecar_location(matrix_location: &Path, file_name: &str) -> Result<Path> {
+    let location = match matrix_location.as_ref().rsplit_once('/') {
+        Some((directory, _)) => format!("{directory}/{file_name}"),
+        None => file_name.to_string(),
+    };
+
+    Path::parse(location).map_err(|e| DataFusionError::External(Box::new(e)))
+}
+
+/// Read the lines of a sidecar file, or `None` if it doesn't exist.
+async fn read_sidecar_lines(
+    object_store: &Arc<dyn ObjectStore>,
+    location: &Path,
+    file_compression_type: FileCompressionType,
+) -> Result<Option<Vec<String>>> {
+    let get_result = match object_store.get(location).await {
+        Ok(get_result) => get_result,
+        Err(object_store::Error::NotFound { .. }) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let stream_reader = Box::pin(get_result.into_stream().map_err(DataFusionError::from));
+    let stream_reader = file_compression_type.convert_stream(stream_reader)?;
+
+    let mut lines = StreamReader::new(stream_reader).lines();
+
+    let mut sidecar_lines = Vec::new();
+    while let Some(line) = lines.next_line().await? {
+        if !line.is_empty() {
+            sidecar_lines.push(line);
+        }
+    }
+
+    Ok(Some(sidecar_lines))
+}
+
+/// Implements a datafusion `FileOpener` for MatrixMarket files, joining the labels of the
+/// barcodes.tsv and features.tsv (or genes.tsv) files next to each matrix.
+pub struct MTXOpener {
+    /// The configuration for the opener.
+    config: Arc<MTXConfig>,
+    /// The file compression type, shared by the matrix and its sidecar files.
+    file_compression_type: FileCompressionType,
+}
+
+impl MTXOpener {
+    /// Create a new MTX file opener.
+    pub fn new(config: Arc<MTXConfig>, file_compression_type: FileCompressionType) -> Self {
+        Self {
+            config,
+            file_compression_type,
+        }
+    }
+}
+
+impl FileOpener for MTXOpener {
+    fn open(&self, file_meta: FileMeta) -> Result<FileOpenFuture> {
+        let config = Arc::clone(&self.config);
+        let file_compression_type = self.file_compression_type;
+
+        Ok(Box::pin(async move {
+            let object_store = &config.object_store;
+            let location = file_meta.location();
+            let extension = file_compression_type.get_ext();
+
+            let barcodes_location =
+                sidecar_location(location, &format!("barcodes.tsv{extension}"))?;
+            let barcodes =
+                read_sidecar_lines(object_store, &barcodes_location, file_compression_type)
+                    .await?
+                    .ok_or_else(|| {
+                        DataFusionError::Execution(format!(
+                            "Missing barcodes file {barcodes_location} for matrix {location}"
+                        ))
+                    })?;
+
+            let mut features = None;
+            for file_name in ["features.tsv", "genes.tsv"] {
+                let features_location =
+                    sidecar_location(location, &format!("{file_name}{extension}"))?;
+
+                features =
+                    read_sidecar_lines(object_store, &features_location, file_compression_type)
+                        .await?;
+
+                if features.is_some() {
+                    break;
+                }
+            }
+
+            let features = features
+                .ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "Missing features or genes file for matrix {location}"
+                    ))
+                })?
+                .iter()
+                .map(|line| Feature::parse(line))
+                .collect();
+
+            let get_result = object_store.get(location).await?;
+
+            let stream_reader = Box::pin(get_result.into_stream().map_err(DataFusionError::from));
+            let stream_reader = file_compression_type.convert_stream(stream_reader)?;
+            let stream_reader = StreamReader::new(stream_reader);
+
+            let batch_reader =
+                MTXBatchReader::try_new(stream_reader, Arc::clone(&config), barcodes, features)
+                    .await?;
+
+            Ok(batch_reader.into_stream().boxed())
+        }))
+    }
+}
@@ -0,0 +1,60 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::datatypes::{DataType, Field, Schema};
+use exon_common::TableSchema;
+
+/// Builds the schema of a feature-barcode matrix, with one row per non-zero count.
+pub struct MTXSchemaBuilder {
+    file_fields: Vec<Field>,
+    partition_fields: Vec<Field>,
+}
+
+impl MTXSchemaBuilder {
+    pub fn add_partition_fields(&mut self, partition_fields: Vec<Field>) {
+        self.partition_fields.extend(partition_fields);
+    }
+
+    pub fn build(self) -> TableSchema {
+        let mut fields = self.file_fields.clone();
+        fields.extend(self.partition_fields);
+
+        let schema = Schema::new(fields);
+
+        let projection: Vec<usize> = (0..self.file_fields.len()).collect();
+
+        TableSchema::new(Arc::new(schema), projection)
+    }
+}
+
+fn file_fields() -> Vec<Field> {
+    vec![
+        Field::new("barcode", DataType::Utf8, false),
+        Field::new("feature_id", DataType::Utf8, false),
+        Field::new("feature_name", DataType::Utf8, true),
+        Field::new("feature_type", DataType::Utf8, true),
+        Field::new("count", DataType::Int64, false),
+    ]
+}
+
+impl Default for MTXSchemaBuilder {
+    fn default() -> Self {
+        Self {
+            file_fields: file_fields(),
+            partition_fields: vec![],
+        }
+    }
+}
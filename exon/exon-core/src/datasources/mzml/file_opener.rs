@@ -12,19 +12,144 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
+use bytes::Bytes;
 use datafusion::{
     datasource::{
         file_format::file_compression_type::FileCompressionType,
         physical_plan::{FileMeta, FileOpenFuture, FileOpener},
     },
-    error::DataFusionError,
+    error::{DataFusionError, Result},
 };
-use exon_mzml::{BatchReader, MzMLConfig};
-use futures::{StreamExt, TryStreamExt};
+use exon_mzml::{
+    ibd_location,
+    mzml_reader::index::{
+        parse_index_list_offset, parse_spectrum_ranges, INDEX_LIST_OFFSET_TAIL_SIZE,
+    },
+    BatchReader, ImzMLBatchReader, MzMLConfig, SpectrumFilter, SpectrumSummary,
+};
+use futures::{Stream, StreamExt, TryStreamExt};
+use object_store::{path::Path, ObjectMeta, ObjectStore};
 use tokio_util::io::StreamReader;
 
+/// The number of bytes to read from the start of a spectrum to check it against the filter.
+const SPECTRUM_PREFIX_SIZE: usize = 8 * 1024;
+
+const SPECTRUM_START_TAG: &[u8] = b"<spectrum";
+const SPECTRUM_END_TAG: &[u8] = b"</spectrum>";
+
+fn usize_range(range: Range<u64>) -> Range<usize> {
+    range.start as usize..range.end as usize
+}
+
+/// Get the byte range of each spectrum from the index at the end of an indexedmzML file, or
+/// `None` if the file has no index or the index doesn't point at the spectra.
+async fn indexed_spectrum_ranges(
+    object_store: &Arc<dyn ObjectStore>,
+    object_meta: &ObjectMeta,
+) -> Result<Option<Vec<Range<usize>>>> {
+    let location = &object_meta.location;
+    let size = object_meta.size;
+
+    let tail_start = size.saturating_sub(INDEX_LIST_OFFSET_TAIL_SIZE as usize);
+    let tail = object_store.get_range(location, tail_start..size).await?;
+
+    let index_list_offset = match parse_index_list_offset(&tail) {
+        Some(offset) if (offset as usize) < size => offset,
+        _ => return Ok(None),
+    };
+
+    let index_list = object_store
+        .get_range(location, index_list_offset as usize..size)
+        .await?;
+
+    let Some(ranges) = parse_spectrum_ranges(&index_list, index_list_offset) else {
+        return Ok(None);
+    };
+    let ranges = ranges.into_iter().map(usize_range).collect::<Vec<_>>();
+
+    if let Some(first) = ranges.first() {
+        let end = first.end.min(first.start + SPECTRUM_START_TAG.len());
+        let start = object_store.get_range(location, first.start..end).await?;
+
+        if !start.starts_with(SPECTRUM_START_TAG) {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(ranges))
+}
+
+/// Read the spectrum in the range if it matches the filter, reading only its start if it doesn't.
+async fn read_matching_spectrum(
+    object_store: &Arc<dyn ObjectStore>,
+    location: &Path,
+    range: Range<usize>,
+    spectrum_filter: &SpectrumFilter,
+) -> Result<Option<Bytes>> {
+    let invalid_offset = || {
+        DataFusionError::Execution(format!(
+            "Invalid mzML index offset {} in {}",
+            range.start, location
+        ))
+    };
+
+    let prefix_end = range.end.min(range.start + SPECTRUM_PREFIX_SIZE);
+    let mut spectrum = object_store
+        .get_range(location, range.start..prefix_end)
+        .await?;
+
+    if !spectrum.starts_with(SPECTRUM_START_TAG) {
+        return Err(invalid_offset());
+    }
+
+    let summary = match SpectrumSummary::from_prefix(&spectrum) {
+        Some(summary) => summary,
+        None if prefix_end < range.end => {
+            spectrum = object_store.get_range(location, range.clone()).await?;
+            SpectrumSummary::from_prefix(&spectrum).ok_or_else(invalid_offset)?
+        }
+        None => return Err(invalid_offset()),
+    };
+
+    if !spectrum_filter.matches(&summary) {
+        return Ok(None);
+    }
+
+    if spectrum.len() < range.len() {
+        spectrum = object_store.get_range(location, range.clone()).await?;
+    }
+
+    // The range runs up to the next indexed element, so drop the closing tags of the spectrum list.
+    let end = spectrum
+        .windows(SPECTRUM_END_TAG.len())
+        .rposition(|w| w == SPECTRUM_END_TAG)
+        .ok_or_else(invalid_offset)?
+        + SPECTRUM_END_TAG.len();
+
+    Ok(Some(spectrum.slice(..end)))
+}
+
+/// Stream the spectra in the ranges that match the filter.
+fn matching_spectra(
+    object_store: Arc<dyn ObjectStore>,
+    location: Path,
+    ranges: Vec<Range<usize>>,
+    spectrum_filter: SpectrumFilter,
+) -> impl Stream<Item = Result<Bytes>> {
+    futures::stream::iter(ranges)
+        .then(move |range| {
+            let object_store = Arc::clone(&object_store);
+            let location = location.clone();
+
+            async move {
+                read_matching_spectrum(&object_store, &location, range, &spectrum_filter).await
+            }
+        })
+        .try_filter_map(|spectrum| futures::future::ready(Ok(spectrum)))
+}
+
 /// Implements a datafusion `FileOpener` for MzML files.
 pub struct MzMLOpener {
     /// The base configuration for the file scan.
@@ -32,6 +157,9 @@ pub struct MzMLOpener {
 
     /// The compression type of the file.
     file_compression_type: FileCompressionType,
+
+    /// Whether the file is an imzML file with an .ibd file of spectra.
+    imzml: bool,
 }
 
 impl MzMLOpener {
@@ -40,16 +168,70 @@ impl MzMLOpener {
         Self {
             config,
             file_compression_type,
+            imzml: false,
         }
     }
+
+    /// Set whether the file is an imzML file.
+    pub fn with_imzml(self, imzml: bool) -> Self {
+        Self { imzml, ..self }
+    }
 }
 
 impl FileOpener for MzMLOpener {
-    fn open(&self, file_meta: FileMeta) -> datafusion::error::Result<FileOpenFuture> {
+    fn open(&self, file_meta: FileMeta) -> Result<FileOpenFuture> {
         let mzml_config = Arc::clone(&self.config);
         let file_compression_type = self.file_compression_type;
+        let imzml = self.imzml;
 
         Ok(Box::pin(async move {
+            if imzml {
+                let location = file_meta.location();
+
+                let ibd_location = ibd_location(location).ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "imzML file {} must have an .imzML extension to find its .ibd file",
+                        location
+                    ))
+                })?;
+
+                let get_result = mzml_config.object_store.get(location).await?;
+                let stream_reader =
+                    Box::pin(get_result.into_stream().map_err(DataFusionError::from));
+                let stream_reader =
+                    StreamReader::new(file_compression_type.convert_stream(stream_reader)?);
+
+                let imzml_batch_reader =
+                    ImzMLBatchReader::new(stream_reader, mzml_config, ibd_location).into_stream();
+
+                return Ok(imzml_batch_reader.boxed());
+            }
+
+            if let Some(spectrum_filter) = mzml_config.spectrum_filter {
+                let object_store = Arc::clone(&mzml_config.object_store);
+
+                let ranges = if file_compression_type.is_compressed() {
+                    None
+                } else {
+                    indexed_spectrum_ranges(&object_store, &file_meta.object_meta).await?
+                };
+
+                if let Some(ranges) = ranges {
+                    let spectra = matching_spectra(
+                        object_store,
+                        file_meta.location().clone(),
+                        ranges,
+                        spectrum_filter,
+                    );
+
+                    let stream_reader = StreamReader::new(Box::pin(spectra));
+                    let mzml_batch_reader =
+                        BatchReader::new(stream_reader, mzml_config).into_stream();
+
+                    return Ok(mzml_batch_reader.boxed());
+                }
+            }
+
             let get_result = mzml_config.object_store.get(file_meta.location()).await?;
 
             let stream_reader = Box::pin(get_result.into_stream().map_err(DataFusionError::from));
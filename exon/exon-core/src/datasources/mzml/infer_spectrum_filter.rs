@@ -0,0 +1,126 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use arrow::datatypes::DataType;
+use datafusion::{
+    logical_expr::{Between, BinaryExpr, Expr, Operator},
+    scalar::ScalarValue,
+};
+use exon_mzml::SpectrumFilter;
+
+fn float_literal(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Literal(scalar) => match scalar.cast_to(&DataType::Float64).ok()? {
+            ScalarValue::Float64(Some(value)) if !value.is_nan() => Some(value),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn bounded(column: &Expr, min: f64, max: f64) -> Option<SpectrumFilter> {
+    match column {
+        Expr::Column(c) if c.name == "rt" => {
+            Some(SpectrumFilter::default().with_rt_bounds(min, max))
+        }
+        Expr::Column(c) if c.name == "precursor_mz" => {
+            Some(SpectrumFilter::default().with_precursor_mz_bounds(min, max))
+        }
+        _ => None,
+    }
+}
+
+/// Infer a filter on the retention time and precursor m/z of the spectra from a comparison or
+/// `BETWEEN` predicate on the `rt` or `precursor_mz` columns, or a conjunction of them.
+///
+/// Strict comparisons become inclusive bounds, so the filter may keep extra spectra.
+pub(crate) fn infer_spectrum_filter(expr: &Expr) -> Option<SpectrumFilter> {
+    match expr {
+        Expr::Between(Between {
+            expr,
+            negated: false,
+            low,
+            high,
+        }) => bounded(expr, float_literal(low)?, float_literal(high)?),
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        }) => match (infer_spectrum_filter(left), infer_spectrum_filter(right)) {
+            (Some(left), Some(right)) => Some(left.and(right)),
+            (filter, None) | (None, filter) => filter,
+        },
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            let (column, op, value) = match (float_literal(left), float_literal(right)) {
+                (None, Some(value)) => (left, *op, value),
+                (Some(value), None) => (right, op.swap()?, value),
+                _ => return None,
+            };
+
+            match op {
+                Operator::Eq => bounded(column, value, value),
+                Operator::Lt | Operator::LtEq => bounded(column, f64::NEG_INFINITY, value),
+                Operator::Gt | Operator::GtEq => bounded(column, value, f64::INFINITY),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::logical_expr::{col, lit};
+    use exon_mzml::SpectrumFilter;
+
+    use super::infer_spectrum_filter;
+
+    #[test]
+    fn test_infer_spectrum_filter() {
+        let cases = [
+            (
+                col("rt").between(lit(60.0), lit(90.0)),
+                SpectrumFilter::default().with_rt_bounds(60.0, 90.0),
+            ),
+            (
+                lit(500).lt(col("precursor_mz")),
+                SpectrumFilter::default().with_precursor_mz_bounds(500.0, f64::INFINITY),
+            ),
+            (
+                col("rt")
+                    .gt_eq(lit(60.0))
+                    .and(col("rt").lt(lit(90.0)))
+                    .and(col("precursor_mz").eq(lit(445.3))),
+                SpectrumFilter::default()
+                    .with_rt_bounds(60.0, 90.0)
+                    .with_precursor_mz_bounds(445.3, 445.3),
+            ),
+            (
+                col("rt").lt_eq(lit(90.0)).and(col("id").eq(lit("scan=1"))),
+                SpectrumFilter::default().with_rt_bounds(f64::NEG_INFINITY, 90.0),
+            ),
+        ];
+
+        for (expr, expected) in cases {
+            assert_eq!(infer_spectrum_filter(&expr), Some(expected), "{}", expr);
+        }
+
+        assert!(infer_spectrum_filter(&col("rt").not_between(lit(60.0), lit(90.0))).is_none());
+        assert!(infer_spectrum_filter(&col("rt").not_eq(lit(60.0))).is_none());
+        assert!(
+            infer_spectrum_filter(&col("rt").gt(lit(60.0)).or(col("rt").lt(lit(1.0)))).is_none()
+        );
+        assert!(infer_spectrum_filter(&col("precusor_charge").eq(lit(2))).is_none());
+    }
+}
@@ -15,6 +15,7 @@
 //! Data source for mzML files.
 
 mod file_opener;
+mod infer_spectrum_filter;
 mod scanner;
 
 /// Table provider for mzML files.
@@ -27,9 +27,9 @@ use datafusion::{
         SendableRecordBatchStream,
     },
 };
-use exon_mzml::MzMLConfig;
+use exon_mzml::{MzMLConfig, SpectrumFilter};
 
-use crate::datasources::ExonFileScanConfig;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::MzMLOpener;
 
@@ -53,6 +53,12 @@ pub struct MzMLScan {
 
     /// The statistics for the scan.
     statistics: Statistics,
+
+    /// A filter on the spectra to read from indexed files.
+    spectrum_filter: Option<SpectrumFilter>,
+
+    /// Whether the files are imzML files with an .ibd file of spectra.
+    imzml: bool,
 }
 
 impl MzMLScan {
@@ -67,6 +73,21 @@ impl MzMLScan {
             metrics: ExecutionPlanMetricsSet::new(),
             properties,
             statistics,
+            spectrum_filter: None,
+            imzml: false,
+        }
+    }
+
+    /// Set whether the files are imzML files.
+    pub fn with_imzml(self, imzml: bool) -> Self {
+        Self { imzml, ..self }
+    }
+
+    /// Set the spectrum filter.
+    pub fn with_spectrum_filter(self, spectrum_filter: Option<SpectrumFilter>) -> Self {
+        Self {
+            spectrum_filter,
+            ..self
         }
     }
 }
@@ -149,11 +170,18 @@ impl ExecutionPlan for MzMLScan {
 
         let config = MzMLConfig::new(object_store, Arc::clone(&self.base_config.file_schema))
             .with_batch_size(batch_size)
-            .with_some_projection(Some(self.base_config.file_projection()));
-
-        let opener = MzMLOpener::new(Arc::new(config), self.file_compression_type);
-
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+            .with_some_projection(Some(self.base_config.file_projection()))
+            .with_spectrum_filter(self.spectrum_filter);
+
+        let opener =
+            MzMLOpener::new(Arc::new(config), self.file_compression_type).with_imzml(self.imzml);
+
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
@@ -28,7 +28,7 @@ use datafusion::{
     prelude::Expr,
 };
 use exon_common::TableSchema;
-use exon_mzml::MzMLSchemaBuilder;
+use exon_mzml::{MzMLSchemaBuilder, SpectrumFilter};
 use futures::TryStreamExt;
 
 use crate::{
@@ -43,7 +43,18 @@ use crate::{
     },
 };
 
-use super::MzMLScan;
+use super::{infer_spectrum_filter::infer_spectrum_filter, MzMLScan};
+
+#[async_trait]
+/// Options for listing a mzML table that can skip spectra using the index of indexedmzML files
+pub trait ExonSpectrumFilterListingOptions: ExonListingOptions {
+    /// Create a physical plan for the table that only reads the spectra matching the filter
+    async fn create_physical_plan_with_spectrum_filter(
+        &self,
+        conf: FileScanConfig,
+        spectrum_filter: SpectrumFilter,
+    ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>>;
+}
 
 #[derive(Debug, Clone)]
 /// Listing options for a MzML table
@@ -56,6 +67,9 @@ pub struct ListingMzMLTableOptions {
 
     /// The table partition columns
     table_partition_cols: Vec<Field>,
+
+    /// Whether the files are imzML files with an .ibd file of spectra
+    imzml: bool,
 }
 
 impl Default for ListingMzMLTableOptions {
@@ -83,7 +97,26 @@ impl ExonListingOptions for ListingMzMLTableOptions {
         &self,
         conf: FileScanConfig,
     ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
-        let scan = MzMLScan::new(conf.clone(), self.file_compression_type);
+        let scan = MzMLScan::new(conf.clone(), self.file_compression_type).with_imzml(self.imzml);
+
+        Ok(Arc::new(scan))
+    }
+}
+
+#[async_trait]
+impl ExonSpectrumFilterListingOptions for ListingMzMLTableOptions {
+    async fn create_physical_plan_with_spectrum_filter(
+        &self,
+        conf: FileScanConfig,
+        spectrum_filter: SpectrumFilter,
+    ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
+        // The spectra of imzML files are read with the referenceable param groups in the header
+        if self.imzml {
+            return self.create_physical_plan(conf).await;
+        }
+
+        let scan = MzMLScan::new(conf, self.file_compression_type)
+            .with_spectrum_filter(Some(spectrum_filter));
 
         Ok(Arc::new(scan))
     }
@@ -98,6 +131,22 @@ impl ListingMzMLTableOptions {
             file_extension,
             file_compression_type,
             table_partition_cols: Vec::new(),
+            imzml: false,
+        }
+    }
+
+    /// Set whether the files are imzML files, which also sets the file extension
+    pub fn with_imzml(self, imzml: bool) -> Self {
+        let file_type = if imzml {
+            ExonFileType::IMZML
+        } else {
+            ExonFileType::MZML
+        };
+
+        Self {
+            file_extension: file_type.get_file_extension(self.file_compression_type),
+            imzml,
+            ..self
         }
     }
 
@@ -122,7 +171,11 @@ impl ListingMzMLTableOptions {
 
     /// Infer the schema for the table (i.e. the file and partition columns)
     pub async fn infer_schema(&self) -> datafusion::error::Result<TableSchema> {
-        let mut schema_builder = MzMLSchemaBuilder::default();
+        let mut schema_builder = if self.imzml {
+            MzMLSchemaBuilder::default().with_imzml_fields()
+        } else {
+            MzMLSchemaBuilder::default()
+        };
         schema_builder.add_partition_fields(self.table_partition_cols.clone());
 
         let table_schema = schema_builder.build();
@@ -150,7 +203,7 @@ impl<T> ListingMzMLTable<T> {
 }
 
 #[async_trait]
-impl<T: ExonListingOptions + 'static> TableProvider for ListingMzMLTable<T> {
+impl<T: ExonSpectrumFilterListingOptions + 'static> TableProvider for ListingMzMLTable<T> {
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -169,7 +222,16 @@ impl<T: ExonListingOptions + 'static> TableProvider for ListingMzMLTable<T> {
     ) -> Result<Vec<TableProviderFilterPushDown>> {
         Ok(filters
             .iter()
-            .map(|f| filter_matches_partition_cols(f, self.config.options.table_partition_cols()))
+            .map(|f| {
+                match filter_matches_partition_cols(f, self.config.options.table_partition_cols()) {
+                    TableProviderFilterPushDown::Unsupported
+                        if infer_spectrum_filter(f).is_some() =>
+                    {
+                        TableProviderFilterPushDown::Inexact
+                    }
+                    pushdown => pushdown,
+                }
+            })
             .collect())
     }
 
@@ -207,11 +269,25 @@ impl<T: ExonListingOptions + 'static> TableProvider for ListingMzMLTable<T> {
                 .limit_option(limit)
                 .build();
 
-        let plan = self
-            .config
-            .options
-            .create_physical_plan(file_scan_config)
-            .await?;
+        let spectrum_filter = filters
+            .iter()
+            .filter_map(infer_spectrum_filter)
+            .reduce(SpectrumFilter::and);
+
+        let plan = match spectrum_filter {
+            Some(spectrum_filter) => {
+                self.config
+                    .options
+                    .create_physical_plan_with_spectrum_filter(file_scan_config, spectrum_filter)
+                    .await?
+            }
+            None => {
+                self.config
+                    .options
+                    .create_physical_plan(file_scan_config)
+                    .await?
+            }
+        };
 
         Ok(plan)
     }
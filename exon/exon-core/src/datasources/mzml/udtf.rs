@@ -30,6 +30,9 @@ use exon_mzml::MzMLSchemaBuilder;
 /// A table function that returns a table provider for a MzML file.
 pub struct MzMLScanFunction {
     ctx: SessionContext,
+
+    /// Whether the files are imzML files with an .ibd file of spectra.
+    imzml: bool,
 }
 
 impl std::fmt::Debug for MzMLScanFunction {
@@ -41,7 +44,12 @@ impl std::fmt::Debug for MzMLScanFunction {
 impl MzMLScanFunction {
     /// Create a new `MzMLScanFunction`.
     pub fn new(ctx: SessionContext) -> Self {
-        Self { ctx }
+        Self { ctx, imzml: false }
+    }
+
+    /// Set whether the function reads imzML files.
+    pub fn with_imzml(self, imzml: bool) -> Self {
+        Self { imzml, ..self }
     }
 }
 
@@ -55,10 +63,15 @@ impl TableFunctionImpl for MzMLScanFunction {
                 .await
         })?;
 
-        let schema = MzMLSchemaBuilder::default().build();
+        let schema = if self.imzml {
+            MzMLSchemaBuilder::default().with_imzml_fields().build()
+        } else {
+            MzMLSchemaBuilder::default().build()
+        };
 
         let listing_table_options =
-            ListingMzMLTableOptions::new(listing_scan_function.file_compression_type);
+            ListingMzMLTableOptions::new(listing_scan_function.file_compression_type)
+                .with_imzml(self.imzml);
 
         let listing_table_config = ExonListingConfig::new_with_options(
             listing_scan_function.listing_table_url,
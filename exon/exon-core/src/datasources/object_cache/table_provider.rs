@@ -0,0 +1,196 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::{
+    array::{BooleanBuilder, Int64Builder, StringBuilder},
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::TableProvider,
+    error::{DataFusionError, Result},
+    logical_expr::TableType,
+    physical_plan::{memory::MemoryExec, ExecutionPlan},
+    prelude::Expr,
+};
+use object_store::path::Path;
+use url::Url;
+
+use crate::{config::extract_config_from_state, CachingObjectStore, ExonRuntimeEnvExt};
+
+/// A table of the remote files fetched into the cache, fetched when scanned.
+#[derive(Debug, Clone)]
+pub struct CacheFetchTable {
+    /// The URLs of the files to fetch.
+    urls: Vec<Url>,
+
+    /// The schema of the table.
+    schema: SchemaRef,
+}
+
+impl CacheFetchTable {
+    /// Create a new table that fetches the files at the URLs into the cache.
+    pub fn new(urls: Vec<Url>) -> Self {
+        let schema = Schema::new(vec![
+            Field::new("url", DataType::Utf8, false),
+            Field::new("path", DataType::Utf8, false),
+            Field::new("size", DataType::Int64, false),
+            Field::new("hit", DataType::Boolean, false),
+        ]);
+
+        Self {
+            urls,
+            schema: Arc::new(schema),
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for CacheFetchTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let exon_config = extract_config_from_state(state)?;
+        let cache = exon_config.object_cache().ok_or_else(|| {
+            DataFusionError::Configuration(
+                "exon_cache_fetch requires exon.cache_directory to be set".to_string(),
+            )
+        })?;
+
+        let runtime_env = state.runtime_env();
+
+        let mut urls = StringBuilder::new();
+        let mut paths = StringBuilder::new();
+        let mut sizes = Int64Builder::new();
+        let mut hits = BooleanBuilder::new();
+
+        for url in self.urls.iter() {
+            // The registered store is shared by the tables on the host, so it's only registered
+            // if it's missing, and wrapped for the fetch.
+            if runtime_env.object_store_registry.get_store(url).is_err() {
+                runtime_env
+                    .exon_register_object_store_url_with_retry_policy(
+                        url,
+                        exon_config.retry_policy(),
+                    )
+                    .await?;
+            }
+
+            let object_store = runtime_env.object_store_registry.get_store(url)?;
+            let object_store = CachingObjectStore::new(object_store, cache.clone(), url);
+
+            let location = Path::from_url_path(url.path())?;
+            let cached = object_store.fetch(&location).await?.ok_or_else(|| {
+                DataFusionError::Execution(format!(
+                    "{url} is larger than the cache size, exon.cache_max_size"
+                ))
+            })?;
+
+            urls.append_value(url.as_str());
+            paths.append_value(cached.path.to_string_lossy());
+            sizes.append_value(cached.meta.size as i64);
+            hits.append_value(cached.hit);
+        }
+
+        let batch = RecordBatch::try_new(
+            Arc::clone(&self.schema),
+            vec![
+                Arc::new(urls.finish()),
+                Arc::new(paths.finish()),
+                Arc::new(sizes.finish()),
+                Arc::new(hits.finish()),
+            ],
+        )?;
+
+        let exec = MemoryExec::try_new(
+            &[vec![batch]],
+            Arc::clone(&self.schema),
+            projection.cloned(),
+        )?;
+
+        Ok(Arc::new(exec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::AsArray;
+
+    use crate::ExonSession;
+
+    #[tokio::test]
+    async fn test_cache_fetch_requires_cache_directory() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let result = ctx
+            .session
+            .sql("SELECT * FROM exon_cache_fetch('https://example.com/reference.fa')")
+            .await?
+            .collect()
+            .await;
+
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cache_fetch_local_file() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let directory = std::env::temp_dir().join("test_cache_fetch_local_file");
+        let _ = std::fs::remove_dir_all(&directory);
+
+        ctx.session
+            .sql(&format!(
+                "SET exon.cache_directory = '{}'",
+                directory.display()
+            ))
+            .await?;
+
+        let fasta = exon_test::test_path("fasta", "test.fasta");
+        let sql = format!(
+            "SELECT hit, size FROM exon_cache_fetch('file://{}')",
+            fasta.display()
+        );
+
+        let batches = ctx.session.sql(&sql).await?.collect().await?;
+        assert!(!batches[0].column(0).as_boolean().value(0));
+
+        let batches = ctx.session.sql(&sql).await?.collect().await?;
+        assert!(batches[0].column(0).as_boolean().value(0));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use datafusion::{
+    common::ScalarValue,
+    datasource::{function::TableFunctionImpl, TableProvider},
+    error::{DataFusionError, Result},
+    logical_expr::Expr,
+};
+use url::Url;
+
+use super::CacheFetchTable;
+
+/// A table function that fetches remote files into the cache, e.g.
+/// `exon_cache_fetch('https://example.com/reference.fa', 'https://example.com/reference.fa.fai')`.
+#[derive(Debug, Default)]
+pub struct CacheFetchFunction {}
+
+impl TableFunctionImpl for CacheFetchFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        if exprs.is_empty() {
+            return Err(DataFusionError::Plan(
+                "exon_cache_fetch requires at least one URL".to_string(),
+            ));
+        }
+
+        let urls = exprs
+            .iter()
+            .map(|expr| match expr {
+                Expr::Literal(ScalarValue::Utf8(Some(url))) => {
+                    Url::parse(url).map_err(|e| DataFusionError::Plan(format!("{url}: {e}")))
+                }
+                _ => Err(DataFusionError::Plan(
+                    "exon_cache_fetch requires its arguments to be URL strings".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(CacheFetchTable::new(urls)))
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use arrow::error::ArrowError;
+
+/// The signature at the start and end of every POD5 file.
+pub const POD5_SIGNATURE: &[u8] = b"\x8BPOD\r\n\x1A\n";
+
+/// The size of the tail that holds the footer length, a section marker, and the signature.
+pub const FOOTER_TAIL_SIZE: usize = 8 + 16 + POD5_SIGNATURE.len();
+
+/// The kind of table embedded in a POD5 file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentType {
+    ReadsTable,
+    SignalTable,
+    ReadIdIndex,
+    OtherIndex,
+    RunInfoTable,
+}
+
+impl TryFrom<i16> for ContentType {
+    type Error = ArrowError;
+
+    fn try_from(value: i16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::ReadsTable),
+            1 => Ok(Self::SignalTable),
+            2 => Ok(Self::ReadIdIndex),
+            3 => Ok(Self::OtherIndex),
+            4 => Ok(Self::RunInfoTable),
+            _ => Err(invalid_footer(format!("unknown content type {value}"))),
+        }
+    }
+}
+
+/// An Arrow IPC file embedded in a POD5 file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedFile {
+    /// The offset of the embedded file from the start of the POD5 file.
+    pub offset: usize,
+
+    /// The length of the embedded file in bytes.
+    pub length: usize,
+
+    /// The table held by the embedded file.
+    pub content_type: ContentType,
+}
+
+fn invalid_footer(message: String) -> ArrowError {
+    ArrowError::ParseError(format!("Invalid POD5 footer: {message}"))
+}
+
+/// Parse the length of the footer from the tail of a POD5 file.
+pub fn parse_footer_length(tail: &[u8]) -> Result<usize, ArrowError> {
+    if tail.len() != FOOTER_TAIL_SIZE || !tail.ends_with(POD5_SIGNATURE) {
+        return Err(ArrowError::ParseError(
+            "Missing POD5 signature at the end of the file".to_string(),
+        ));
+    }
+
+    let length = i64::from_le_bytes(tail[..8].try_into().unwrap());
+
+    usize::try_from(length).map_err(|_| invalid_footer(format!("negative length {length}")))
+}
+
+/// A table in the footer's flatbuffer.
+struct Table<'a> {
+    buf: &'a [u8],
+    position: usize,
+    vtable: usize,
+    vtable_size: usize,
+}
+
+impl<'a> Table<'a> {
+    fn read<const N: usize>(buf: &'a [u8], position: usize) -> Result<[u8; N], ArrowError> {
+        buf.get(position..position + N)
+            .map(|b| b.try_into().unwrap())
+            .ok_or_else(|| invalid_footer(format!("offset {position} is out of bounds")))
+    }
+
+    fn read_u32(buf: &'a [u8], position: usize) -> Result<usize, ArrowError> {
+        Ok(u32::from_le_bytes(Self::read(buf, position)?) as usize)
+    }
+
+    fn new(buf: &'a [u8], position: usize) -> Result<Self, ArrowError> {
+        let vtable_offset = i32::from_le_bytes(Self::read(buf, position)?) as i64;
+        let vtable = usize::try_from(position as i64 - vtable_offset).map_err(|_| {
+            invalid_footer(format!("vtable offset {vtable_offset} is out of bounds"))
+        })?;
+        let vtable_size = u16::from_le_bytes(Self::read(buf, vtable)?) as usize;
+
+        Ok(Self {
+            buf,
+            position,
+            vtable,
+            vtable_size,
+        })
+    }
+
+    /// The position of a field, or None if the field is absent.
+    fn field(&self, index: usize) -> Result<Option<usize>, ArrowError> {
+        let entry = 4 + 2 * index;
+        if entry + 2 > self.vtable_size {
+            return Ok(None);
+        }
+
+        match u16::from_le_bytes(Self::read(self.buf, self.vtable + entry)?) {
+            0 => Ok(None),
+            offset => Ok(Some(self.position + offset as usize)),
+        }
+    }
+
+    fn i64_field(&self, index: usize) -> Result<i64, ArrowError> {
+        match self.field(index)? {
+            Some(position) => Ok(i64::from_le_bytes(Self::read(self.buf, position)?)),
+            None => Ok(0),
+        }
+    }
+
+    fn i16_field(&self, index: usize) -> Result<i16, ArrowError> {
+        match self.field(index)? {
+            Some(position) => Ok(i16::from_le_bytes(Self::read(self.buf, position)?)),
+            None => Ok(0),
+        }
+    }
+
+    /// The tables of a vector field.
+    fn table_vector_field(&self, index: usize) -> Result<Vec<Table<'a>>, ArrowError> {
+        let Some(position) = self.field(index)? else {
+            return Ok(Vec::new());
+        };
+
+        let vector = position + Self::read_u32(self.buf, position)?;
+        let len = Self::read_u32(self.buf, vector)?;
+
+        (0..len)
+            .map(|i| {
+                let element = vector + 4 + 4 * i;
+                Table::new(self.buf, element + Self::read_u32(self.buf, element)?)
+            })
+            .collect()
+    }
+}
+
+fn usize_field(table: &Table, index: usize, name: &str) -> Result<usize, ArrowError> {
+    let value = table.i64_field(index)?;
+
+    usize::try_from(value).map_err(|_| invalid_footer(format!("negative {name} {value}")))
+}
+
+/// Parse the embedded files from the footer flatbuffer of a POD5 file.
+pub fn parse_footer(footer: &[u8]) -> Result<Vec<EmbeddedFile>, ArrowError> {
+    let root = Table::new(footer, Table::read_u32(footer, 0)?)?;
+
+    root.table_vector_field(3)?
+        .iter()
+        .map(|table| {
+            Ok(EmbeddedFile {
+                offset: usize_field(table, 0, "offset")?,
+                length: usize_field(table, 1, "length")?,
+                content_type: ContentType::try_from(table.i16_field(3)?)?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_footer() -> Result<(), ArrowError> {
+        let file = std::fs::read(exon_test::test_path("pod5", "test.pod5"))?;
+
+        let tail = &file[file.len() - FOOTER_TAIL_SIZE..];
+        let footer_length = parse_footer_length(tail)?;
+
+        let footer_end = file.len() - FOOTER_TAIL_SIZE;
+        let embedded_files = parse_footer(&file[footer_end - footer_length..footer_end])?;
+
+        let content_types = embedded_files
+            .iter()
+            .map(|f| f.content_type)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            content_types,
+            vec![
+                ContentType::ReadsTable,
+                ContentType::SignalTable,
+                ContentType::RunInfoTable
+            ]
+        );
+
+        for embedded_file in embedded_files {
+            let start = embedded_file.offset;
+            let end = start + embedded_file.length;
+
+            assert!(file[start..end].starts_with(b"ARROW1"));
+            assert!(file[start..end].ends_with(b"ARROW1"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_footer_length_requires_signature() {
+        let tail = [0u8; FOOTER_TAIL_SIZE];
+
+        assert!(parse_footer_length(&tail).is_err());
+    }
+}
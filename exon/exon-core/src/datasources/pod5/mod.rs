@@ -0,0 +1,33 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A datafusion compatible datasource for POD5 nanopore signal files.
+
+mod footer;
+mod pod5_batch_reader;
+mod pod5_config;
+mod pod5_opener;
+mod pod5_scanner;
+mod pod5_schema_builder;
+mod vbz;
+
+/// Table provider for POD5 files.
+pub mod table_provider;
+
+pub use self::pod5_config::Pod5Config;
+pub use self::pod5_opener::Pod5Opener;
+pub use self::pod5_scanner::Pod5Scan;
+
+mod udtf;
+pub use self::udtf::Pod5ScanFunction;
@@ -0,0 +1,580 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    io::Cursor,
+    ops::Range,
+    sync::Arc,
+};
+
+use arrow::{
+    array::{
+        Array, ArrayRef, AsArray, BinaryArray, FixedSizeBinaryArray, Int16Builder, Int64Array,
+        LargeBinaryArray, ListBuilder, StringArray, UInt64Array,
+    },
+    buffer::Buffer,
+    compute::{cast, max, min},
+    datatypes::{DataType, Int16Type, Int64Type, SchemaRef, UInt64Type},
+    error::ArrowError,
+    ipc::{
+        convert::fb_to_schema,
+        reader::{read_footer_length, FileDecoder, FileReader},
+        root_as_footer, Block,
+    },
+    record_batch::{RecordBatch, RecordBatchOptions},
+};
+use bytes::Bytes;
+use object_store::{path::Path, ObjectMeta, ObjectStore};
+
+use super::{
+    footer::{parse_footer, parse_footer_length, ContentType, EmbeddedFile, FOOTER_TAIL_SIZE},
+    pod5_config::Pod5Config,
+    vbz::decode_vbz,
+};
+
+/// The size of the Arrow IPC file trailer, the footer length and the magic bytes.
+const IPC_TRAILER_SIZE: usize = 10;
+
+async fn get_range(
+    object_store: &Arc<dyn ObjectStore>,
+    location: &Path,
+    range: Range<usize>,
+) -> Result<Bytes, ArrowError> {
+    object_store
+        .get_range(location, range)
+        .await
+        .map_err(|e| ArrowError::ExternalError(Box::new(e)))
+}
+
+fn column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a ArrayRef, ArrowError> {
+    batch
+        .column_by_name(name)
+        .ok_or_else(|| ArrowError::SchemaError(format!("POD5 table is missing column {name}")))
+}
+
+fn read_embedded_table(bytes: Bytes) -> Result<Vec<RecordBatch>, ArrowError> {
+    FileReader::try_new(Cursor::new(bytes), None)?.collect()
+}
+
+/// Format the 16 bytes of a read id as a hyphenated UUID.
+fn format_read_id(read_id: &[u8]) -> String {
+    let hex = read_id
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>();
+
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Map each acquisition id in the run info table to its sampling rate.
+fn sampling_rates(run_info: &[RecordBatch]) -> Result<HashMap<String, i64>, ArrowError> {
+    let mut sampling_rates = HashMap::new();
+
+    for batch in run_info {
+        let acquisition_ids = cast(column(batch, "acquisition_id")?, &DataType::Utf8)?;
+        let sample_rates = cast(column(batch, "sample_rate")?, &DataType::Int64)?;
+
+        let acquisition_ids = acquisition_ids.as_string::<i32>();
+        let sample_rates = sample_rates.as_primitive::<Int64Type>();
+
+        for (acquisition_id, sample_rate) in acquisition_ids.iter().zip(sample_rates.iter()) {
+            if let (Some(acquisition_id), Some(sample_rate)) = (acquisition_id, sample_rate) {
+                sampling_rates.insert(acquisition_id.to_string(), sample_rate);
+            }
+        }
+    }
+
+    Ok(sampling_rates)
+}
+
+/// The signal and sample counts of one record batch of the signal table.
+struct SignalChunk {
+    signal: ArrayRef,
+    samples: UInt64Array,
+}
+
+impl SignalChunk {
+    fn try_new(batch: &RecordBatch) -> Result<Self, ArrowError> {
+        let samples = cast(column(batch, "samples")?, &DataType::UInt64)?;
+
+        Ok(Self {
+            signal: Arc::clone(column(batch, "signal")?),
+            samples: samples.as_primitive::<UInt64Type>().clone(),
+        })
+    }
+
+    fn append_signal(&self, row: usize, builder: &mut Int16Builder) -> Result<(), ArrowError> {
+        let count = self.samples.value(row) as usize;
+
+        match self.signal.data_type() {
+            DataType::LargeBinary => {
+                let signal = self.signal.as_any().downcast_ref::<LargeBinaryArray>();
+                builder.append_slice(&decode_vbz(signal.unwrap().value(row), count)?);
+            }
+            DataType::Binary => {
+                let signal = self.signal.as_any().downcast_ref::<BinaryArray>();
+                builder.append_slice(&decode_vbz(signal.unwrap().value(row), count)?);
+            }
+            DataType::LargeList(_) => {
+                let signal = self.signal.as_list::<i64>().value(row);
+                builder.append_slice(signal.as_primitive::<Int16Type>().values());
+            }
+            DataType::List(_) => {
+                let signal = self.signal.as_list::<i32>().value(row);
+                builder.append_slice(signal.as_primitive::<Int16Type>().values());
+            }
+            data_type => {
+                return Err(ArrowError::SchemaError(format!(
+                    "Unsupported POD5 signal type {data_type}"
+                )))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The signal table of a POD5 file, whose record batches are fetched as reads reference them.
+struct SignalTable {
+    object_store: Arc<dyn ObjectStore>,
+    location: Path,
+
+    /// The offset of the signal table in the POD5 file.
+    offset: usize,
+
+    decoder: FileDecoder,
+    blocks: Vec<Block>,
+
+    /// The first row of each block read so far, followed by the end of the last one.
+    block_row_starts: Vec<usize>,
+
+    /// The chunks of the blocks that the current reads reference.
+    chunks: BTreeMap<usize, SignalChunk>,
+}
+
+impl SignalTable {
+    async fn try_new(
+        object_store: Arc<dyn ObjectStore>,
+        location: Path,
+        size: usize,
+        embedded_file: &EmbeddedFile,
+    ) -> Result<Self, ArrowError> {
+        let offset = embedded_file.offset;
+        let end = offset
+            .checked_add(embedded_file.length)
+            .filter(|end| *end <= size)
+            .ok_or_else(|| {
+                ArrowError::ParseError(format!(
+                    "POD5 signal table at {offset} with length {} is past the end of {location}",
+                    embedded_file.length
+                ))
+            })?;
+
+        let footer_end = end
+            .checked_sub(IPC_TRAILER_SIZE)
+            .filter(|footer_end| *footer_end >= offset)
+            .ok_or_else(|| {
+                ArrowError::ParseError("POD5 signal table is too small to be an IPC file".into())
+            })?;
+
+        let trailer = get_range(&object_store, &location, footer_end..end).await?;
+        let footer_length = read_footer_length(trailer[..].try_into().unwrap())?;
+
+        let footer_start = footer_end
+            .checked_sub(footer_length)
+            .filter(|footer_start| *footer_start >= offset)
+            .ok_or_else(|| {
+                ArrowError::ParseError(format!(
+                    "Invalid POD5 signal table footer length {footer_length}"
+                ))
+            })?;
+        let footer_bytes = get_range(&object_store, &location, footer_start..footer_end).await?;
+
+        let footer = root_as_footer(&footer_bytes)
+            .map_err(|e| ArrowError::ParseError(format!("Invalid POD5 signal table: {e}")))?;
+
+        let schema = footer
+            .schema()
+            .ok_or_else(|| ArrowError::ParseError("POD5 signal table has no schema".into()))?;
+
+        let blocks = footer
+            .recordBatches()
+            .map(|blocks| blocks.iter().copied().collect())
+            .unwrap_or_default();
+
+        let mut signal_table = Self {
+            object_store,
+            location,
+            offset,
+            decoder: FileDecoder::new(Arc::new(fb_to_schema(schema)), footer.version()),
+            blocks,
+            block_row_starts: vec![0],
+            chunks: BTreeMap::new(),
+        };
+
+        for block in footer.dictionaries().iter().flatten() {
+            let buffer = signal_table.read_block_buffer(block).await?;
+            signal_table.decoder.read_dictionary(block, &buffer)?;
+        }
+
+        Ok(signal_table)
+    }
+
+    async fn read_block_buffer(&self, block: &Block) -> Result<Buffer, ArrowError> {
+        let start = self.offset + block.offset() as usize;
+        let end = start + block.metaDataLength() as usize + block.bodyLength() as usize;
+
+        let bytes = get_range(&self.object_store, &self.location, start..end).await?;
+
+        Ok(Buffer::from_bytes(bytes.into()))
+    }
+
+    async fn read_chunk(&self, index: usize) -> Result<SignalChunk, ArrowError> {
+        let block = &self.blocks[index];
+        let buffer = self.read_block_buffer(block).await?;
+
+        let batch = self
+            .decoder
+            .read_record_batch(block, &buffer)?
+            .ok_or_else(|| ArrowError::ParseError("Empty POD5 signal table block".into()))?;
+
+        SignalChunk::try_new(&batch)
+    }
+
+    /// The index of the block holding a row, which must already be discovered.
+    fn block_index(&self, row: usize) -> usize {
+        self.block_row_starts.partition_point(|start| *start <= row) - 1
+    }
+
+    /// Load the blocks holding the rows in `rows`, dropping blocks before them.
+    async fn load_rows(&mut self, rows: Range<usize>) -> Result<(), ArrowError> {
+        // Blocks are only discovered in order, since their row counts are in their metadata.
+        while rows.end > *self.block_row_starts.last().unwrap()
+            && self.block_row_starts.len() <= self.blocks.len()
+        {
+            let index = self.block_row_starts.len() - 1;
+            let chunk = self.read_chunk(index).await?;
+
+            let end = self.block_row_starts[index] + chunk.samples.len();
+            self.block_row_starts.push(end);
+            self.chunks.insert(index, chunk);
+        }
+
+        let row_count = *self.block_row_starts.last().unwrap();
+        if rows.end > row_count {
+            return Err(ArrowError::ParseError(format!(
+                "POD5 signal row {} is out of range for {row_count} rows",
+                rows.end - 1
+            )));
+        }
+
+        let first = self.block_index(rows.start);
+        let last = self.block_index(rows.end - 1);
+
+        self.chunks.retain(|index, _| *index >= first);
+
+        for index in first..=last {
+            if !self.chunks.contains_key(&index) {
+                let chunk = self.read_chunk(index).await?;
+                self.chunks.insert(index, chunk);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn append_signal(&self, row: usize, builder: &mut Int16Builder) -> Result<(), ArrowError> {
+        let index = self.block_index(row);
+        let chunk = &self.chunks[&index];
+
+        chunk.append_signal(row - self.block_row_starts[index], builder)
+    }
+}
+
+/// Reads the reads of a POD5 file into record batches, with each read's signal decoded.
+pub struct Pod5BatchReader {
+    /// The configuration for this reader.
+    config: Arc<Pod5Config>,
+
+    /// The schema of the batches this reader returns.
+    projected_schema: SchemaRef,
+
+    /// The record batches of the reads table that are left to read.
+    reads: VecDeque<RecordBatch>,
+
+    /// The sampling rate of each acquisition in the run info table.
+    sampling_rates: HashMap<String, i64>,
+
+    /// The signal table, if the signal is projected.
+    signal_table: Option<SignalTable>,
+}
+
+impl Pod5BatchReader {
+    pub async fn try_new(
+        config: Arc<Pod5Config>,
+        object_meta: &ObjectMeta,
+    ) -> Result<Self, ArrowError> {
+        let object_store = &config.object_store;
+        let location = &object_meta.location;
+        let size = object_meta.size;
+
+        let footer_end = size.checked_sub(FOOTER_TAIL_SIZE).ok_or_else(|| {
+            ArrowError::ParseError(format!("{location} is too small to be a POD5 file"))
+        })?;
+
+        let tail = get_range(object_store, location, footer_end..size).await?;
+        let footer_length = parse_footer_length(&tail)?;
+
+        let footer_start = footer_end.checked_sub(footer_length).ok_or_else(|| {
+            ArrowError::ParseError(format!("Invalid POD5 footer length {footer_length}"))
+        })?;
+        let footer = get_range(object_store, location, footer_start..footer_end).await?;
+
+        let embedded_files = parse_footer(&footer)?;
+        let embedded_file = |content_type: ContentType| {
+            embedded_files
+                .iter()
+                .find(|f| f.content_type == content_type)
+                .filter(|f| f.offset + f.length <= footer_start)
+                .ok_or_else(|| {
+                    ArrowError::ParseError(format!("POD5 file is missing its {content_type:?}"))
+                })
+        };
+
+        let fetch = |embedded_file: &EmbeddedFile| {
+            let range = embedded_file.offset..embedded_file.offset + embedded_file.length;
+            get_range(object_store, location, range)
+        };
+
+        let reads = read_embedded_table(fetch(embedded_file(ContentType::ReadsTable)?).await?)?;
+        let run_info =
+            read_embedded_table(fetch(embedded_file(ContentType::RunInfoTable)?).await?)?;
+
+        let projected_field_names = config.projected_field_names();
+
+        let signal_table = if projected_field_names.iter().any(|name| name == "signal") {
+            let signal_table = SignalTable::try_new(
+                Arc::clone(object_store),
+                location.clone(),
+                size,
+                embedded_file(ContentType::SignalTable)?,
+            )
+            .await?;
+
+            Some(signal_table)
+        } else {
+            None
+        };
+
+        let projected_schema = match &config.projection {
+            Some(projection) => Arc::new(config.file_schema.project(projection)?),
+            None => Arc::clone(&config.file_schema),
+        };
+
+        Ok(Self {
+            projected_schema,
+            reads: reads.into(),
+            sampling_rates: sampling_rates(&run_info)?,
+            signal_table,
+            config,
+        })
+    }
+
+    /// The next slice of at most `batch_size` reads.
+    fn next_reads(&mut self) -> Option<RecordBatch> {
+        let batch = self.reads.pop_front()?;
+
+        if batch.num_rows() > self.config.batch_size {
+            let remainder = batch.slice(
+                self.config.batch_size,
+                batch.num_rows() - self.config.batch_size,
+            );
+            self.reads.push_front(remainder);
+
+            return Some(batch.slice(0, self.config.batch_size));
+        }
+
+        Some(batch)
+    }
+
+    async fn read_signal(&mut self, reads: &RecordBatch) -> Result<ArrayRef, ArrowError> {
+        let signal_table = self.signal_table.as_mut().unwrap();
+
+        let signal_rows = column(reads, "signal")?.as_list::<i32>();
+        let offsets = signal_rows.value_offsets();
+
+        let rows = cast(signal_rows.values(), &DataType::UInt64)?;
+        let rows = rows.as_primitive::<UInt64Type>();
+
+        let referenced_rows = rows.slice(
+            offsets[0] as usize,
+            (offsets[reads.num_rows()] - offsets[0]) as usize,
+        );
+        if let (Some(min), Some(max)) = (min(&referenced_rows), max(&referenced_rows)) {
+            signal_table
+                .load_rows(min as usize..max as usize + 1)
+                .await?;
+        }
+
+        let mut builder = ListBuilder::new(Int16Builder::new());
+        for i in 0..reads.num_rows() {
+            for row in offsets[i] as usize..offsets[i + 1] as usize {
+                signal_table.append_signal(rows.value(row) as usize, builder.values())?;
+            }
+
+            builder.append(true);
+        }
+
+        Ok(Arc::new(builder.finish()))
+    }
+
+    async fn read_column(
+        &mut self,
+        reads: &RecordBatch,
+        name: &str,
+    ) -> Result<ArrayRef, ArrowError> {
+        match name {
+            "read_id" => {
+                let read_ids = column(reads, "read_id")?;
+                let read_ids = read_ids
+                    .as_any()
+                    .downcast_ref::<FixedSizeBinaryArray>()
+                    .ok_or_else(|| ArrowError::SchemaError("Invalid POD5 read_id".into()))?;
+
+                let read_ids = read_ids
+                    .iter()
+                    .map(|read_id| read_id.map(format_read_id))
+                    .collect::<StringArray>();
+
+                Ok(Arc::new(read_ids))
+            }
+            "sampling_rate" => {
+                let run_info = cast(column(reads, "run_info")?, &DataType::Utf8)?;
+
+                let sampling_rates = run_info
+                    .as_string::<i32>()
+                    .iter()
+                    .map(|acquisition_id| {
+                        acquisition_id.and_then(|a| self.sampling_rates.get(a).copied())
+                    })
+                    .collect::<Int64Array>();
+
+                Ok(Arc::new(sampling_rates))
+            }
+            "signal" => self.read_signal(reads).await,
+            "start_sample" => cast(column(reads, "start")?, &DataType::Int64),
+            "calibration_offset" | "calibration_scale" => {
+                cast(column(reads, name)?, &DataType::Float32)
+            }
+            _ => cast(column(reads, name)?, &DataType::Int64),
+        }
+    }
+
+    pub async fn read_batch(&mut self) -> Result<Option<RecordBatch>, ArrowError> {
+        let Some(reads) = self.next_reads() else {
+            return Ok(None);
+        };
+
+        let schema = Arc::clone(&self.projected_schema);
+
+        let mut columns = Vec::with_capacity(schema.fields().len());
+        for field in schema.fields() {
+            columns.push(self.read_column(&reads, field.name()).await?);
+        }
+
+        let options = RecordBatchOptions::new().with_row_count(Some(reads.num_rows()));
+        let batch = RecordBatch::try_new_with_options(schema, columns, &options)?;
+
+        Ok(Some(batch))
+    }
+
+    pub fn into_stream(self) -> impl futures::Stream<Item = Result<RecordBatch, ArrowError>> {
+        futures::stream::unfold(self, |mut reader| async move {
+            match reader.read_batch().await {
+                Ok(Some(batch)) => Some((Ok(batch), reader)),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), reader)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    async fn signal_table(
+        embedded_file: impl Fn(&EmbeddedFile) -> EmbeddedFile,
+    ) -> Result<Result<SignalTable, ArrowError>, Box<dyn std::error::Error>> {
+        let file = std::fs::read(exon_test::test_path("pod5", "test.pod5"))?;
+        let size = file.len();
+
+        let footer_end = size - FOOTER_TAIL_SIZE;
+        let footer_length = parse_footer_length(&file[footer_end..])?;
+        let embedded_files = parse_footer(&file[footer_end - footer_length..footer_end])?;
+
+        let signal_file = embedded_files
+            .iter()
+            .find(|f| f.content_type == ContentType::SignalTable)
+            .ok_or("Missing signal table")?;
+
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let location = Path::from("test.pod5");
+        object_store.put(&location, file.into()).await?;
+
+        Ok(SignalTable::try_new(object_store, location, size, &embedded_file(signal_file)).await)
+    }
+
+    #[tokio::test]
+    async fn test_signal_table_bounds() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(signal_table(Clone::clone).await?.is_ok());
+
+        // The table runs past the end of the file.
+        let past_end = signal_table(|f| EmbeddedFile {
+            length: usize::MAX - f.offset,
+            ..f.clone()
+        })
+        .await?;
+        assert!(matches!(past_end, Err(ArrowError::ParseError(_))));
+
+        // The table is smaller than the IPC trailer.
+        let too_small = signal_table(|f| EmbeddedFile {
+            length: IPC_TRAILER_SIZE - 1,
+            ..f.clone()
+        })
+        .await?;
+        assert!(matches!(too_small, Err(ArrowError::ParseError(_))));
+
+        // The footer length in the trailer runs past the start of the table.
+        let footer_past_start = signal_table(|f| EmbeddedFile {
+            offset: f.offset + f.length - IPC_TRAILER_SIZE,
+            length: IPC_TRAILER_SIZE,
+            ..f.clone()
+        })
+        .await?;
+        assert!(matches!(footer_past_start, Err(ArrowError::ParseError(_))));
+
+        Ok(())
+    }
+}
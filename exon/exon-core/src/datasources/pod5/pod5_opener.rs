@@ -0,0 +1,63 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use datafusion::{
+    datasource::{
+        file_format::file_compression_type::FileCompressionType,
+        physical_plan::{FileMeta, FileOpenFuture, FileOpener},
+    },
+    error::DataFusionError,
+};
+use futures::StreamExt;
+
+use super::{pod5_batch_reader::Pod5BatchReader, pod5_config::Pod5Config};
+
+/// Implements a datafusion `FileOpener` for POD5 files.
+pub struct Pod5Opener {
+    /// The configuration for the opener.
+    config: Arc<Pod5Config>,
+    /// The file compression type.
+    file_compression_type: FileCompressionType,
+}
+
+impl Pod5Opener {
+    /// Create a new POD5 file opener.
+    pub fn new(config: Arc<Pod5Config>, file_compression_type: FileCompressionType) -> Self {
+        Self {
+            config,
+            file_compression_type,
+        }
+    }
+}
+
+impl FileOpener for Pod5Opener {
+    fn open(&self, file_meta: FileMeta) -> datafusion::error::Result<FileOpenFuture> {
+        // The embedded tables are read with ranged requests, so the file can't be compressed.
+        if self.file_compression_type.is_compressed() {
+            return Err(DataFusionError::NotImplemented(
+                "Compressed POD5 files are not supported".to_string(),
+            ));
+        }
+
+        let config = Arc::clone(&self.config);
+
+        Ok(Box::pin(async move {
+            let batch_reader = Pod5BatchReader::try_new(config, &file_meta.object_meta).await?;
+
+            Ok(batch_reader.into_stream().boxed())
+        }))
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::datatypes::{DataType, Field, Schema};
+use exon_common::TableSchema;
+
+/// Builds the schema of a POD5 table, with one row per read.
+pub struct Pod5SchemaBuilder {
+    file_fields: Vec<Field>,
+    partition_fields: Vec<Field>,
+}
+
+impl Pod5SchemaBuilder {
+    pub fn add_partition_fields(&mut self, partition_fields: Vec<Field>) {
+        self.partition_fields.extend(partition_fields);
+    }
+
+    pub fn build(self) -> TableSchema {
+        let mut fields = self.file_fields.clone();
+        fields.extend(self.partition_fields);
+
+        let schema = Schema::new(fields);
+
+        let projection: Vec<usize> = (0..self.file_fields.len()).collect();
+
+        TableSchema::new(Arc::new(schema.clone()), projection.clone())
+    }
+}
+
+fn file_fields() -> Vec<Field> {
+    vec![
+        Field::new("read_id", DataType::Utf8, false),
+        Field::new("read_number", DataType::Int64, true),
+        Field::new("channel", DataType::Int64, true),
+        Field::new("well", DataType::Int64, true),
+        Field::new("start_sample", DataType::Int64, true),
+        Field::new("num_samples", DataType::Int64, true),
+        Field::new("sampling_rate", DataType::Int64, true),
+        Field::new("calibration_offset", DataType::Float32, true),
+        Field::new("calibration_scale", DataType::Float32, true),
+        Field::new(
+            "signal",
+            DataType::List(Arc::new(Field::new("item", DataType::Int16, true))),
+            true,
+        ),
+    ]
+}
+
+impl Default for Pod5SchemaBuilder {
+    fn default() -> Self {
+        Self {
+            file_fields: file_fields(),
+            partition_fields: vec![],
+        }
+    }
+}
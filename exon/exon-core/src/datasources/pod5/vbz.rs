@@ -0,0 +1,84 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use arrow::error::ArrowError;
+
+fn invalid_signal(message: &str) -> ArrowError {
+    ArrowError::ParseError(format!("Invalid VBZ signal: {message}"))
+}
+
+/// Decode a VBZ compressed signal of `count` samples.
+///
+/// VBZ is zstd over 16 bit stream-vbyte, where each sample is stored as the zigzag encoded
+/// difference from the previous sample.
+pub fn decode_vbz(compressed: &[u8], count: usize) -> Result<Vec<i16>, ArrowError> {
+    let encoded = zstd::decode_all(compressed)?;
+
+    let (keys, data) = encoded
+        .split_at_checked(count.div_ceil(8))
+        .ok_or_else(|| invalid_signal("missing keys"))?;
+
+    let mut signal = Vec::with_capacity(count);
+    let mut position = 0;
+    let mut previous = 0i16;
+
+    for i in 0..count {
+        let value = if keys[i / 8] & (1 << (i % 8)) == 0 {
+            let byte = data
+                .get(position)
+                .ok_or_else(|| invalid_signal("truncated"))?;
+            position += 1;
+            *byte as u16
+        } else {
+            let bytes = data
+                .get(position..position + 2)
+                .ok_or_else(|| invalid_signal("truncated"))?;
+            position += 2;
+            u16::from_le_bytes([bytes[0], bytes[1]])
+        };
+
+        let delta = (value >> 1) as i16 ^ -((value & 1) as i16);
+        previous = previous.wrapping_add(delta);
+
+        signal.push(previous);
+    }
+
+    Ok(signal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_vbz() -> Result<(), ArrowError> {
+        // Deltas of 100, 2, and -4 fit in one byte, -1300 needs two.
+        let encoded = [0b1000, 200, 4, 7, 0x27, 0x0a];
+        let compressed = zstd::encode_all(&encoded[..], 0)?;
+
+        let signal = decode_vbz(&compressed, 4)?;
+        assert_eq!(signal, vec![100, 102, 98, -1202]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_vbz_truncated() -> Result<(), ArrowError> {
+        let compressed = zstd::encode_all(&[0u8, 200][..], 0)?;
+
+        assert!(decode_vbz(&compressed, 2).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::datatypes::{DataType, Field, SchemaRef, TimeUnit};
+use datafusion::{
+    datasource::listing::PartitionedFile,
+    error::Result,
+    execution::object_store::ObjectStoreUrl,
+    physical_plan::{expressions::col, projection::ProjectionExec, ExecutionPlan},
+    scalar::ScalarValue,
+};
+
+use crate::physical_plan::record_index_exec::RecordIndexExec;
+
+/// The URL of the file a record was read from.
+pub const FILE_PATH_COLUMN: &str = "_file_path";
+
+/// The last modified time of the file a record was read from.
+pub const FILE_LAST_MODIFIED_COLUMN: &str = "_file_last_modified";
+
+/// The size of the file a record was read from when it was listed, where an incremental scan of
+/// the file can resume with the `start_after_offset` option.
+pub const FILE_SIZE_COLUMN: &str = "_file_size";
+
+/// The 0-based index of a record in the file, or in the byte range of the file that was scanned.
+pub const RECORD_INDEX_COLUMN: &str = "_record_index";
+
+/// The provenance fields that are constant for a file.
+pub(crate) fn file_provenance_fields() -> Vec<Field> {
+    vec![
+        Field::new(FILE_PATH_COLUMN, DataType::Utf8, false),
+        Field::new(
+            FILE_LAST_MODIFIED_COLUMN,
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            false,
+        ),
+        Field::new(FILE_SIZE_COLUMN, DataType::UInt64, false),
+    ]
+}
+
+/// The provenance fields that are appended to a table's schema.
+///
+/// The file path and last modified time are attached to each file's records by the file stream
+/// as partition values, so they're exact for every batch, and the record index is counted per
+/// file on top of the scan.
+pub fn provenance_fields() -> Vec<Field> {
+    let mut fields = file_provenance_fields();
+    fields.push(Field::new(RECORD_INDEX_COLUMN, DataType::UInt64, false));
+
+    fields
+}
+
+/// Add the file provenance values to a file's partition values.
+pub(crate) fn add_file_provenance_values(
+    object_store_url: &ObjectStoreUrl,
+    file: &mut PartitionedFile,
+) {
+    let path = format!("{}{}", object_store_url.as_str(), file.object_meta.location);
+    let last_modified = file.object_meta.last_modified.timestamp_millis();
+
+    file.partition_values.push(ScalarValue::Utf8(Some(path)));
+    file.partition_values
+        .push(ScalarValue::TimestampMillisecond(
+            Some(last_modified),
+            Some("UTC".into()),
+        ));
+    file.partition_values
+        .push(ScalarValue::UInt64(Some(file.object_meta.size as u64)));
+}
+
+/// Splits the projection of a table with provenance columns into the projection of the scan,
+/// which has the file provenance columns as its last partition columns, and the columns that
+/// are added on top of it.
+#[derive(Debug)]
+pub(crate) struct ProvenanceProjection {
+    /// The number of fields in the table without the provenance columns.
+    n_table_fields: usize,
+
+    /// The projection of the table, including the provenance columns.
+    projection: Vec<usize>,
+}
+
+impl ProvenanceProjection {
+    pub(crate) fn new(n_table_fields: usize, projection: Option<&Vec<usize>>) -> Self {
+        let projection = match projection {
+            Some(projection) => projection.clone(),
+            None => (0..n_table_fields + provenance_fields().len()).collect(),
+        };
+
+        Self {
+            n_table_fields,
+            projection,
+        }
+    }
+
+    fn record_index(&self) -> usize {
+        self.n_table_fields + file_provenance_fields().len()
+    }
+
+    /// The projection of the scan, which needs the file path to count the record index.
+    pub(crate) fn scan_projection(&self) -> Vec<usize> {
+        let mut projection = self
+            .projection
+            .iter()
+            .copied()
+            .filter(|i| *i != self.record_index())
+            .collect::<Vec<_>>();
+
+        if self.projection.contains(&self.record_index()) {
+            projection.push(self.n_table_fields);
+        }
+
+        projection.sort_unstable();
+        projection.dedup();
+
+        projection
+    }
+
+    /// Add the record index to the scan and project it to the table's projection.
+    pub(crate) fn project(
+        &self,
+        scan: Arc<dyn ExecutionPlan>,
+        table_schema: &SchemaRef,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let input: Arc<dyn ExecutionPlan> = if self.projection.contains(&self.record_index()) {
+            Arc::new(RecordIndexExec::try_new(scan)?)
+        } else {
+            scan
+        };
+
+        let input_schema = input.schema();
+
+        let exprs = self
+            .projection
+            .iter()
+            .map(|i| {
+                let name = table_schema.field(*i).name();
+                Ok((col(name, &input_schema)?, name.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(ProjectionExec::try_new(exprs, input)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProvenanceProjection;
+
+    #[test]
+    fn test_scan_projection() {
+        // The table has 4 fields, then _file_path, _file_last_modified, _file_size, and
+        // _record_index.
+        let projection = ProvenanceProjection::new(4, Some(&vec![7, 1]));
+        assert_eq!(projection.scan_projection(), vec![1, 4]);
+
+        let projection = ProvenanceProjection::new(4, Some(&vec![5, 0]));
+        assert_eq!(projection.scan_projection(), vec![0, 5]);
+
+        let projection = ProvenanceProjection::new(4, None);
+        assert_eq!(projection.scan_projection(), vec![0, 1, 2, 3, 4, 5, 6]);
+    }
+}
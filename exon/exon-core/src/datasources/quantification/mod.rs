@@ -0,0 +1,37 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A datafusion compatible datasource for the transcript quantification of salmon and kallisto,
+//! i.e. salmon `quant.sf` and kallisto `abundance.tsv` files.
+//!
+//! Both tools write the estimates of each sample to its own output directory, e.g.
+//! `salmon/S1/quant.sf`, so the name of that directory is read as the `sample` column. Both kinds
+//! of file share the columns of a table, so salmon and kallisto estimates can be compared.
+
+mod quantification_batch_reader;
+mod quantification_config;
+mod quantification_file_kind;
+mod quantification_opener;
+mod quantification_scanner;
+
+/// Table provider for salmon and kallisto files.
+pub mod table_provider;
+
+pub use self::quantification_config::QuantificationConfig;
+pub use self::quantification_file_kind::QuantificationFileKind;
+pub use self::quantification_opener::QuantificationOpener;
+pub use self::quantification_scanner::QuantificationScan;
+
+mod udtf;
+pub use self::udtf::QuantificationScanFunction;
@@ -0,0 +1,275 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Float64Builder, Int64Builder, StringBuilder},
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use super::quantification_config::QuantificationConfig;
+
+fn invalid_line(config: &QuantificationConfig, message: String) -> ArrowError {
+    ArrowError::ParseError(format!("Invalid {} line: {message}", config.kind))
+}
+
+/// Reads a salmon `quant.sf` or kallisto `abundance.tsv` file into record batches, adding the
+/// sample of the file to each row.
+pub struct QuantificationBatchReader<R> {
+    /// The underlying reader.
+    reader: R,
+
+    /// The configuration for this reader.
+    config: Arc<QuantificationConfig>,
+
+    /// The sample of the file.
+    sample: String,
+
+    /// Whether the header has been read.
+    read_header: bool,
+}
+
+impl<R> QuantificationBatchReader<R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    /// Create a new batch reader for the file of a sample.
+    pub fn new(reader: R, config: Arc<QuantificationConfig>, sample: String) -> Self {
+        Self {
+            reader,
+            config,
+            sample,
+            read_header: false,
+        }
+    }
+
+    /// Read the header, checking it has the columns of the kind of file.
+    async fn read_header(&mut self) -> Result<(), ArrowError> {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).await?;
+
+        let header = line.trim_end_matches(['\n', '\r']);
+        if !header.is_empty()
+            && !header
+                .split('\t')
+                .eq(self.config.kind.header().iter().copied())
+        {
+            return Err(ArrowError::ParseError(format!(
+                "Invalid {} header: expected {}, found {header}",
+                self.config.kind,
+                self.config.kind.header().join(", ")
+            )));
+        }
+
+        self.read_header = true;
+
+        Ok(())
+    }
+
+    pub async fn read_batch(&mut self) -> Result<Option<RecordBatch>, ArrowError> {
+        if !self.read_header {
+            self.read_header().await?;
+        }
+
+        let [name_index, length_index, effective_length_index, tpm_index, num_reads_index] =
+            self.config.kind.column_indices();
+
+        let mut transcript_ids = StringBuilder::new();
+        let mut lengths = Int64Builder::new();
+        let mut effective_lengths = Float64Builder::new();
+        let mut tpms = Float64Builder::new();
+        let mut num_reads = Float64Builder::new();
+
+        let mut line = String::new();
+        let mut rows = 0;
+
+        while rows < self.config.batch_size {
+            line.clear();
+            if self.reader.read_line(&mut line).await? == 0 {
+                break;
+            }
+
+            let trimmed = line.trim_end_matches(['\n', '\r']);
+            if trimmed.trim().is_empty() {
+                continue;
+            }
+
+            let fields = trimmed.split('\t').collect::<Vec<_>>();
+            if fields.len() != self.config.kind.header().len() {
+                return Err(invalid_line(
+                    &self.config,
+                    format!(
+                        "expected {} columns, found {}",
+                        self.config.kind.header().len(),
+                        fields.len()
+                    ),
+                ));
+            }
+
+            let parse_f64 = |index: usize| {
+                fields[index].parse::<f64>().map_err(|_| {
+                    invalid_line(
+                        &self.config,
+                        format!("invalid number {} of {}", fields[index], fields[name_index]),
+                    )
+                })
+            };
+
+            let length = fields[length_index].parse::<i64>().map_err(|_| {
+                invalid_line(
+                    &self.config,
+                    format!(
+                        "invalid length {} of {}",
+                        fields[length_index], fields[name_index]
+                    ),
+                )
+            })?;
+
+            transcript_ids.append_value(fields[name_index]);
+            lengths.append_value(length);
+            effective_lengths.append_value(parse_f64(effective_length_index)?);
+            tpms.append_value(parse_f64(tpm_index)?);
+            num_reads.append_value(parse_f64(num_reads_index)?);
+
+            rows += 1;
+        }
+
+        if rows == 0 {
+            return Ok(None);
+        }
+
+        let mut samples = StringBuilder::new();
+        for _ in 0..rows {
+            samples.append_value(&self.sample);
+        }
+
+        let batch = RecordBatch::try_new(
+            Arc::clone(&self.config.file_schema),
+            vec![
+                Arc::new(transcript_ids.finish()),
+                Arc::new(lengths.finish()),
+                Arc::new(effective_lengths.finish()),
+                Arc::new(tpms.finish()),
+                Arc::new(num_reads.finish()),
+                Arc::new(samples.finish()),
+            ],
+        )?;
+
+        match &self.config.projection {
+            Some(projection) => Ok(Some(batch.project(projection)?)),
+            None => Ok(Some(batch)),
+        }
+    }
+
+    pub fn into_stream(self) -> impl futures::Stream<Item = Result<RecordBatch, ArrowError>> {
+        futures::stream::unfold(self, |mut reader| async move {
+            match reader.read_batch().await {
+                Ok(Some(batch)) => Some((Ok(batch), reader)),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), reader)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{AsArray, Float64Array},
+        datatypes::{Int64Type, Schema},
+    };
+    use object_store::local::LocalFileSystem;
+
+    use crate::datasources::quantification::QuantificationFileKind;
+
+    use super::*;
+
+    fn config(kind: QuantificationFileKind) -> Arc<QuantificationConfig> {
+        let file_schema = Arc::new(Schema::new(kind.fields()));
+
+        Arc::new(QuantificationConfig::new(
+            Arc::new(LocalFileSystem::new()),
+            file_schema,
+            kind,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_read_salmon_quant() -> Result<(), ArrowError> {
+        let quant = "Name\tLength\tEffectiveLength\tTPM\tNumReads\n\
+            t1\t1657\t1408.000\t25000.000000\t5.000\n\
+            t2\t632\t383.000\t0.000000\t0.000\n";
+
+        let mut reader = QuantificationBatchReader::new(
+            quant.as_bytes(),
+            config(QuantificationFileKind::SalmonQuant),
+            "S1".to_string(),
+        );
+
+        let batch = reader.read_batch().await?.unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.column(1).as_primitive::<Int64Type>().value(0), 1657);
+        assert_eq!(
+            batch.column(3).as_any().downcast_ref::<Float64Array>(),
+            Some(&Float64Array::from(vec![25000.0, 0.0]))
+        );
+        assert_eq!(batch.column(5).as_string::<i32>().value(1), "S1");
+
+        assert!(reader.read_batch().await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_kallisto_abundance() -> Result<(), ArrowError> {
+        let abundance = "target_id\tlength\teff_length\test_counts\ttpm\n\
+            t1\t1657\t1458.12\t6\t30000\n";
+
+        let mut reader = QuantificationBatchReader::new(
+            abundance.as_bytes(),
+            config(QuantificationFileKind::KallistoAbundance),
+            "S1".to_string(),
+        );
+
+        let batch = reader.read_batch().await?.unwrap();
+        assert_eq!(
+            batch.column(3).as_any().downcast_ref::<Float64Array>(),
+            Some(&Float64Array::from(vec![30000.0]))
+        );
+        assert_eq!(
+            batch.column(4).as_any().downcast_ref::<Float64Array>(),
+            Some(&Float64Array::from(vec![6.0]))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_invalid_header() {
+        let abundance = "target_id\tlength\teff_length\test_counts\ttpm\n";
+
+        let mut reader = QuantificationBatchReader::new(
+            abundance.as_bytes(),
+            config(QuantificationFileKind::SalmonQuant),
+            "S1".to_string(),
+        );
+
+        assert!(reader.read_batch().await.is_err());
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt::Display, str::FromStr};
+
+use arrow::{
+    datatypes::{DataType, Field},
+    error::ArrowError,
+};
+
+/// The columns of a salmon `quant.sf` file.
+const SALMON_QUANT_HEADER: [&str; 5] = ["Name", "Length", "EffectiveLength", "TPM", "NumReads"];
+
+/// The columns of a kallisto `abundance.tsv` file.
+const KALLISTO_ABUNDANCE_HEADER: [&str; 5] =
+    ["target_id", "length", "eff_length", "est_counts", "tpm"];
+
+/// A kind of transcript quantification file, written by salmon or kallisto.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuantificationFileKind {
+    /// The `quant.sf` file salmon writes to the output directory of a sample.
+    SalmonQuant,
+
+    /// The `abundance.tsv` file kallisto writes to the output directory of a sample. The
+    /// `abundance.h5` file holds the same estimates, but can't be read, so convert it with
+    /// `kallisto h5dump` first.
+    KallistoAbundance,
+}
+
+impl QuantificationFileKind {
+    /// The name of the files of this kind, which is used as their extension.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::SalmonQuant => "quant.sf",
+            Self::KallistoAbundance => "abundance.tsv",
+        }
+    }
+
+    /// The columns of the header of a file of this kind.
+    pub fn header(&self) -> &'static [&'static str] {
+        match self {
+            Self::SalmonQuant => &SALMON_QUANT_HEADER,
+            Self::KallistoAbundance => &KALLISTO_ABUNDANCE_HEADER,
+        }
+    }
+
+    /// The index in a line of this kind of each of the transcript id, length, effective length,
+    /// TPM, and number of reads.
+    pub(crate) fn column_indices(&self) -> [usize; 5] {
+        match self {
+            Self::SalmonQuant => [0, 1, 2, 3, 4],
+            Self::KallistoAbundance => [0, 1, 2, 4, 3],
+        }
+    }
+
+    /// The fields of a quantification table, which are the same for each kind so salmon and
+    /// kallisto estimates can be compared.
+    pub fn fields(&self) -> Vec<Field> {
+        vec![
+            Field::new("transcript_id", DataType::Utf8, false),
+            Field::new("length", DataType::Int64, false),
+            Field::new("effective_length", DataType::Float64, false),
+            Field::new("tpm", DataType::Float64, false),
+            Field::new("num_reads", DataType::Float64, false),
+            Field::new("sample", DataType::Utf8, false),
+        ]
+    }
+}
+
+/// The sample of a quantification file, which salmon and kallisto take from the name of the
+/// output directory of each sample, e.g. `S1` for `salmon/S1/quant.sf`.
+pub(crate) fn sample_from_path(path: &str) -> &str {
+    let mut parts = path.rsplit('/');
+    let file_name = parts.next().unwrap_or(path);
+
+    parts
+        .next()
+        .filter(|directory| !directory.is_empty())
+        .unwrap_or(file_name)
+}
+
+impl Display for QuantificationFileKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SalmonQuant => write!(f, "salmon_quant"),
+            Self::KallistoAbundance => write!(f, "kallisto_abundance"),
+        }
+    }
+}
+
+impl FromStr for QuantificationFileKind {
+    type Err = ArrowError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "salmon_quant" => Ok(Self::SalmonQuant),
+            "kallisto_abundance" => Ok(Self::KallistoAbundance),
+            _ => Err(ArrowError::InvalidArgumentError(format!(
+                "Unknown quantification file kind {s}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{sample_from_path, QuantificationFileKind};
+
+    #[test]
+    fn test_from_str() {
+        assert!(matches!(
+            QuantificationFileKind::from_str("SALMON_QUANT"),
+            Ok(QuantificationFileKind::SalmonQuant)
+        ));
+        assert!(matches!(
+            QuantificationFileKind::from_str("kallisto_abundance"),
+            Ok(QuantificationFileKind::KallistoAbundance)
+        ));
+        assert!(QuantificationFileKind::from_str("rsem").is_err());
+    }
+
+    #[test]
+    fn test_sample_from_path() {
+        assert_eq!(sample_from_path("data/salmon/S1/quant.sf"), "S1");
+        assert_eq!(sample_from_path("S2/abundance.tsv"), "S2");
+        assert_eq!(sample_from_path("quant.sf"), "quant.sf");
+    }
+}
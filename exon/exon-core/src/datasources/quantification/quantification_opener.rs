@@ -0,0 +1,72 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use datafusion::{
+    datasource::{
+        file_format::file_compression_type::FileCompressionType,
+        physical_plan::{FileMeta, FileOpenFuture, FileOpener},
+    },
+    error::DataFusionError,
+};
+use futures::{StreamExt, TryStreamExt};
+use tokio_util::io::StreamReader;
+
+use super::{
+    quantification_batch_reader::QuantificationBatchReader,
+    quantification_config::QuantificationConfig, quantification_file_kind::sample_from_path,
+};
+
+/// Implements a datafusion `FileOpener` for salmon and kallisto files.
+pub struct QuantificationOpener {
+    /// The configuration for the opener.
+    config: Arc<QuantificationConfig>,
+    /// The file compression type.
+    file_compression_type: FileCompressionType,
+}
+
+impl QuantificationOpener {
+    /// Create a new quantification file opener.
+    pub fn new(
+        config: Arc<QuantificationConfig>,
+        file_compression_type: FileCompressionType,
+    ) -> Self {
+        Self {
+            config,
+            file_compression_type,
+        }
+    }
+}
+
+impl FileOpener for QuantificationOpener {
+    fn open(&self, file_meta: FileMeta) -> datafusion::error::Result<FileOpenFuture> {
+        let config = Arc::clone(&self.config);
+        let file_compression_type = self.file_compression_type;
+
+        Ok(Box::pin(async move {
+            let get_result = config.object_store.get(file_meta.location()).await?;
+
+            let stream_reader = Box::pin(get_result.into_stream().map_err(DataFusionError::from));
+            let new_reader = file_compression_type.convert_stream(stream_reader)?;
+
+            let stream_reader = StreamReader::new(new_reader);
+
+            let sample = sample_from_path(file_meta.location().as_ref()).to_string();
+            let batch_reader = QuantificationBatchReader::new(stream_reader, config, sample);
+
+            Ok(batch_reader.into_stream().boxed())
+        }))
+    }
+}
@@ -0,0 +1,428 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::{any::Any, str::FromStr, sync::Arc};
+
+use arrow::datatypes::{Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::{
+    catalog::{Session, TableProviderFactory},
+    common::{Column, DFSchema},
+    datasource::TableProvider,
+    error::{DataFusionError, Result},
+    logical_expr::{
+        utils::conjunction, BinaryExpr, Cast, CreateExternalTable, Operator,
+        TableProviderFilterPushDown, TableType,
+    },
+    physical_plan::{
+        expressions::col, filter::FilterExec, projection::ProjectionExec, ExecutionPlan,
+    },
+    prelude::Expr,
+    scalar::ScalarValue,
+};
+use noodles::core::Region;
+
+/// The region filter UDFs, with the columns they filter when they're only given a region.
+const REGION_FILTERS: [(&str, &[&str]); 3] = [
+    ("vcf_region_filter", &["chrom", "pos"]),
+    ("gff_region_filter", &["seqname", "start"]),
+    ("bam_region_filter", &["reference", "start", "end"]),
+];
+
+/// A literal of a column's type, or `None` if the schema has no such column.
+fn column_literal(schema: &Schema, column: &Column, value: ScalarValue) -> Result<Option<Expr>> {
+    let Ok(field) = schema.field_with_name(&column.name) else {
+        return Ok(None);
+    };
+
+    Ok(Some(Expr::Literal(value.cast_to(field.data_type())?)))
+}
+
+/// The predicate on the columns of a schema equivalent to a region filter UDF call, a
+/// disjunction of them, or either compared to true, e.g. `chrom = '1' AND pos >= 10 AND pos <= 20`
+/// for `vcf_region_filter('1:10-20', chrom, pos)`. A region with a single position column keeps
+/// the rows whose position is in the interval, and one with start and end columns keeps the rows
+/// that overlap it.
+///
+/// Returns `None` if the filter isn't a region filter, its region isn't a literal, or the schema
+/// doesn't have its columns.
+pub(crate) fn region_filter_predicate(filter: &Expr, schema: &Schema) -> Result<Option<Expr>> {
+    match filter {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::Eq,
+            right,
+        }) if **right == Expr::Literal(ScalarValue::Boolean(Some(true))) => {
+            region_filter_predicate(left, schema)
+        }
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::Or,
+            right,
+        }) => match (
+            region_filter_predicate(left, schema)?,
+            region_filter_predicate(right, schema)?,
+        ) {
+            (Some(left), Some(right)) => Ok(Some(left.or(right))),
+            _ => Ok(None),
+        },
+        Expr::ScalarFunction(function) => {
+            let Some((_, default_columns)) = REGION_FILTERS
+                .iter()
+                .find(|(name, _)| *name == function.name())
+            else {
+                return Ok(None);
+            };
+
+            let Some(Expr::Literal(ScalarValue::Utf8(Some(region)))) = function.args.first() else {
+                return Ok(None);
+            };
+
+            let region = Region::from_str(region)
+                .map_err(|e| DataFusionError::Plan(format!("Invalid region {}: {}", region, e)))?;
+
+            let columns = if function.args.len() == 1 {
+                default_columns
+                    .iter()
+                    .map(|name| Some(Column::from_name(*name)))
+                    .collect::<Vec<_>>()
+            } else {
+                function.args[1..]
+                    .iter()
+                    .map(|arg| {
+                        // The UDF's signature may cast the column, e.g. from a Utf8View.
+                        let arg = match arg {
+                            Expr::Cast(Cast { expr, .. }) => expr.as_ref(),
+                            _ => arg,
+                        };
+
+                        // Unqualified, since the predicate is planned against the table alone.
+                        match arg {
+                            Expr::Column(column) => Some(Column::from_name(&column.name)),
+                            _ => None,
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            let Some(columns) = columns.into_iter().collect::<Option<Vec<_>>>() else {
+                return Ok(None);
+            };
+
+            region_predicate(&region, &columns, schema)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The predicate on name and position, or name, start, and end columns for a region.
+fn region_predicate(region: &Region, columns: &[Column], schema: &Schema) -> Result<Option<Expr>> {
+    let name = std::str::from_utf8(region.name())
+        .map_err(|e| DataFusionError::Plan(format!("Invalid region name: {}", e)))?;
+
+    let interval = region.interval();
+    let start = interval
+        .start()
+        .map(|start| ScalarValue::Int64(Some(usize::from(start) as i64)));
+    let end = interval
+        .end()
+        .map(|end| ScalarValue::Int64(Some(usize::from(end) as i64)));
+
+    let Some(name_column) = columns.first() else {
+        return Ok(None);
+    };
+    let Some(name) = column_literal(schema, name_column, ScalarValue::from(name))? else {
+        return Ok(None);
+    };
+
+    let mut predicates = vec![Expr::Column(name_column.clone()).eq(name)];
+
+    // The columns that must be at least the start of the region and at most its end: the
+    // position of a row in the interval, or the end and start of a row that overlaps it.
+    let (after_start, before_end) = match &columns[1..] {
+        [] => (None, None),
+        [position] => (Some(position), Some(position)),
+        [start, end] => (Some(end), Some(start)),
+        _ => return Ok(None),
+    };
+
+    for (column, bound, op) in [
+        (after_start, start, Operator::GtEq),
+        (before_end, end, Operator::LtEq),
+    ] {
+        let (Some(column), Some(bound)) = (column, bound) else {
+            continue;
+        };
+
+        let Some(bound) = column_literal(schema, column, bound)? else {
+            return Ok(None);
+        };
+
+        predicates.push(Expr::BinaryExpr(BinaryExpr::new(
+            Box::new(Expr::Column(column.clone())),
+            op,
+            Box::new(bound),
+        )));
+    }
+
+    Ok(conjunction(predicates))
+}
+
+/// A Delta Lake or Parquet table with rows on a genome, e.g. variants, that answers region filter
+/// UDFs like `vcf_region_filter` with filters on its columns.
+///
+/// The filters are passed to the inner table, so it can skip files and row groups whose min/max
+/// statistics of the chromosome and position columns don't overlap the region, and then applied
+/// to the rows it returns.
+#[derive(Debug)]
+pub struct RegionFilterTable {
+    /// The table with the genomic columns
+    inner: Arc<dyn TableProvider>,
+}
+
+impl RegionFilterTable {
+    /// Create a table answering region filters over the inner table.
+    pub fn new(inner: Arc<dyn TableProvider>) -> Self {
+        Self { inner }
+    }
+
+    fn region_filter_predicate(&self, filter: &Expr) -> Result<Option<Expr>> {
+        region_filter_predicate(filter, &self.inner.schema())
+    }
+}
+
+#[async_trait]
+impl TableProvider for RegionFilterTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+
+    fn table_type(&self) -> TableType {
+        self.inner.table_type()
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        let is_region_filter = filters
+            .iter()
+            .map(|f| Ok(self.region_filter_predicate(f)?.is_some()))
+            .collect::<Result<Vec<_>>>()?;
+
+        let inner_filters = filters
+            .iter()
+            .zip(&is_region_filter)
+            .filter(|(_, is_region_filter)| !**is_region_filter)
+            .map(|(f, _)| *f)
+            .collect::<Vec<_>>();
+        let mut inner_pushdown = self
+            .inner
+            .supports_filters_pushdown(&inner_filters)?
+            .into_iter();
+
+        is_region_filter
+            .into_iter()
+            .map(|is_region_filter| {
+                if is_region_filter {
+                    Ok(TableProviderFilterPushDown::Exact)
+                } else {
+                    inner_pushdown.next().ok_or_else(|| {
+                        DataFusionError::Internal(
+                            "Missing filter pushdown of the inner table".to_string(),
+                        )
+                    })
+                }
+            })
+            .collect()
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let mut predicates = Vec::new();
+        let mut inner_filters = Vec::new();
+
+        for filter in filters {
+            match self.region_filter_predicate(filter)? {
+                Some(predicate) => {
+                    inner_filters.push(predicate.clone());
+                    predicates.push(predicate);
+                }
+                None => inner_filters.push(filter.clone()),
+            }
+        }
+
+        let Some(predicate) = conjunction(predicates) else {
+            return self.inner.scan(state, projection, filters, limit).await;
+        };
+
+        // Scan the columns of the predicate too, and all rows, since some are filtered out here.
+        let schema = self.inner.schema();
+        let projection = match projection {
+            Some(projection) => projection.clone(),
+            None => (0..schema.fields().len()).collect(),
+        };
+
+        let mut inner_projection = projection.clone();
+        for column in predicate.column_refs() {
+            let i = schema.index_of(&column.name)?;
+
+            if !inner_projection.contains(&i) {
+                inner_projection.push(i);
+            }
+        }
+
+        let input = self
+            .inner
+            .scan(state, Some(&inner_projection), &inner_filters, None)
+            .await?;
+        let input_schema = input.schema();
+
+        let df_schema = DFSchema::try_from(input_schema.as_ref().clone())?;
+        let predicate = state.create_physical_expr(predicate, &df_schema)?;
+        let filter = Arc::new(FilterExec::try_new(predicate, input)?);
+
+        let exprs = projection
+            .iter()
+            .map(|i| {
+                let name = schema.field(*i).name();
+
+                Ok((col(name, &input_schema)?, name.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(ProjectionExec::try_new(exprs, filter)?))
+    }
+}
+
+/// Creates the tables of the Delta Lake or Parquet table factory as [`RegionFilterTable`]s.
+#[derive(Debug)]
+pub struct RegionFilterTableFactory {
+    inner: Arc<dyn TableProviderFactory>,
+}
+
+impl RegionFilterTableFactory {
+    /// Create a factory wrapping the tables of the inner factory.
+    pub fn new(inner: Arc<dyn TableProviderFactory>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl TableProviderFactory for RegionFilterTableFactory {
+    async fn create(
+        &self,
+        state: &dyn Session,
+        cmd: &CreateExternalTable,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let inner = self.inner.create(state, cmd).await?;
+
+        Ok(Arc::new(RegionFilterTable::new(inner)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::logical_expr::{col, expr::ScalarFunction, lit, Expr, ScalarUDF};
+
+    use super::region_filter_predicate;
+    use crate::udfs::{
+        sam::bam_region_filter::BAMRegionFilterUDF, vcf::vcf_region_filter::VCFRegionFilterUDF,
+    };
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("chrom", DataType::Utf8, false),
+            Field::new("pos", DataType::Int64, false),
+            Field::new("reference", DataType::Utf8, false),
+            Field::new("start", DataType::Int32, false),
+            Field::new("end", DataType::Int32, false),
+        ])
+    }
+
+    fn call(udf: impl Into<ScalarUDF>, args: Vec<Expr>) -> Expr {
+        Expr::ScalarFunction(ScalarFunction::new_udf(Arc::new(udf.into()), args))
+    }
+
+    #[test]
+    fn test_region_filter_predicate() -> Result<(), Box<dyn std::error::Error>> {
+        let schema = schema();
+
+        let filter = call(
+            VCFRegionFilterUDF::default(),
+            vec![lit("1:10-20"), col("chrom"), col("pos")],
+        );
+        assert_eq!(
+            region_filter_predicate(&filter.eq(lit(true)), &schema)?,
+            Some(
+                col("chrom")
+                    .eq(lit("1"))
+                    .and(col("pos").gt_eq(lit(10i64)))
+                    .and(col("pos").lt_eq(lit(20i64)))
+            )
+        );
+
+        let filter = call(VCFRegionFilterUDF::default(), vec![lit("1")])
+            .or(call(VCFRegionFilterUDF::default(), vec![lit("2:5")]));
+        assert_eq!(
+            region_filter_predicate(&filter, &schema)?,
+            Some(
+                col("chrom")
+                    .eq(lit("1"))
+                    .or(col("chrom").eq(lit("2")).and(col("pos").gt_eq(lit(5i64))))
+            )
+        );
+
+        let filter = call(
+            BAMRegionFilterUDF::default(),
+            vec![
+                lit("chr1:100-200"),
+                col("reference"),
+                col("start"),
+                col("end"),
+            ],
+        );
+        assert_eq!(
+            region_filter_predicate(&filter, &schema)?,
+            Some(
+                col("reference")
+                    .eq(lit("chr1"))
+                    .and(col("end").gt_eq(lit(100i32)))
+                    .and(col("start").lt_eq(lit(200i32)))
+            )
+        );
+
+        let filter = call(
+            VCFRegionFilterUDF::default(),
+            vec![lit("1"), col("chromosome")],
+        );
+        assert_eq!(region_filter_predicate(&filter, &schema)?, None);
+
+        let filter = col("chrom").eq(lit("1"));
+        assert_eq!(region_filter_predicate(&filter, &schema)?, None);
+
+        Ok(())
+    }
+}
@@ -54,6 +54,9 @@ pub struct ListingSAMTableOptions {
 
     /// Whether to infer the schema from the tags
     tag_as_struct: bool,
+
+    /// Whether to use the compact flag, mapping quality, and quality score types
+    compact_types: bool,
 }
 
 #[async_trait]
@@ -87,9 +90,14 @@ impl ListingSAMTableOptions {
         state: &dyn Session,
         table_path: &ListingTableUrl,
     ) -> datafusion::error::Result<TableSchema> {
+        let mut schema_builder = SAMSchemaBuilder::default();
+
+        if self.compact_types {
+            schema_builder = schema_builder.with_compact_types();
+        }
+
         if !self.tag_as_struct {
-            let builder = SAMSchemaBuilder::default()
-                .with_partition_fields(self.table_partition_cols.clone()); // TODO: get rid of clone
+            let builder = schema_builder.with_partition_fields(self.table_partition_cols.clone()); // TODO: get rid of clone
             let table_schema = builder.build();
 
             return Ok(table_schema);
@@ -106,8 +114,6 @@ impl ListingSAMTableOptions {
         )
         .await;
 
-        let mut schema_builder = SAMSchemaBuilder::default();
-
         while let Some(f) = files.next().await {
             let f = f?;
 
@@ -149,6 +155,14 @@ impl ListingSAMTableOptions {
             ..self
         }
     }
+
+    /// Use UInt16 flags, UInt8 mapping qualities, and lists of UInt8 quality scores
+    pub fn with_compact_types(self, compact_types: bool) -> Self {
+        Self {
+            compact_types,
+            ..self
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -169,6 +183,42 @@ impl<T> ListingSAMTable<T> {
     }
 }
 
+impl<T: ExonListingOptions> ListingSAMTable<T> {
+    /// Read the header of the first file in the table, so it can be passed through when the
+    /// table is written as SAM or BAM.
+    pub(crate) async fn read_header(
+        &self,
+        state: &dyn Session,
+    ) -> Result<Option<noodles::sam::Header>> {
+        let Some(table_path) = self.config.first_table_path() else {
+            return Ok(None);
+        };
+
+        let store = state.runtime_env().object_store(table_path)?;
+
+        let mut files = exon_common::object_store_files_from_table_path(
+            &store,
+            table_path.as_ref(),
+            table_path.prefix(),
+            self.config.options.file_extension(),
+            None,
+        )
+        .await;
+
+        let Some(f) = files.next().await else {
+            return Ok(None);
+        };
+
+        let get_result = store.get(&f?.location).await?;
+
+        let stream_reader = Box::pin(get_result.into_stream().map_err(DataFusionError::from));
+        let stream_reader = StreamReader::new(stream_reader);
+        let mut reader = noodles::sam::AsyncReader::new(stream_reader);
+
+        Ok(Some(reader.read_header().await?))
+    }
+}
+
 #[async_trait]
 impl<T: ExonListingOptions + 'static> TableProvider for ListingSAMTable<T> {
     fn as_any(&self) -> &dyn Any {
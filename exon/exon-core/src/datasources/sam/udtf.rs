@@ -52,8 +52,9 @@ impl TableFunctionImpl for SAMScanFunction {
         let state = self.ctx.state();
         let config = extract_config_from_state(&state)?;
 
-        let listing_table_options =
-            ListingSAMTableOptions::default().with_tag_as_struct(config.sam_parse_tags);
+        let listing_table_options = ListingSAMTableOptions::default()
+            .with_tag_as_struct(config.sam_parse_tags)
+            .with_compact_types(config.alignment_compact_types);
 
         let schema = futures::executor::block_on(async {
             let schema = listing_table_options
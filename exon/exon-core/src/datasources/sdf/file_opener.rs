@@ -22,6 +22,7 @@ use datafusion::{
 };
 use exon_sdf::SDFConfig;
 use futures::{StreamExt, TryStreamExt};
+use object_store::{GetOptions, GetRange};
 use tokio_util::io::StreamReader;
 
 #[derive(Debug)]
@@ -52,7 +53,19 @@ impl FileOpener for SDFOpener {
         let file_compression_type = self.file_compression_type;
 
         Ok(Box::pin(async move {
-            let get_result = config.object_store.get(file_meta.location()).await?;
+            // Ranges come from the property index and start and end on record boundaries.
+            let get_options = GetOptions {
+                range: file_meta
+                    .range
+                    .as_ref()
+                    .map(|range| GetRange::Bounded(range.start as usize..range.end as usize)),
+                ..Default::default()
+            };
+
+            let get_result = config
+                .object_store
+                .get_opts(file_meta.location(), get_options)
+                .await?;
 
             let stream = Box::pin(get_result.into_stream().map_err(DataFusionError::from));
             let stream = file_compression_type.convert_stream(stream)?;
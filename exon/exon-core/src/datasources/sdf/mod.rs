@@ -13,6 +13,7 @@
 // limitations under the License.
 
 mod file_opener;
+mod property_index;
 mod scanner;
 mod table_provider;
 
@@ -0,0 +1,259 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::datatypes::DataType;
+use datafusion::{
+    datasource::listing::{FileRange, PartitionedFile},
+    error::{DataFusionError, Result},
+    logical_expr::{expr::ScalarFunction, Between, BinaryExpr, Cast, Expr, Operator, TryCast},
+    scalar::ScalarValue,
+};
+use exon_sdf::{PropertyIndex, PropertyIndexChunk, PROPERTY_INDEX_EXTENSION};
+use futures::TryStreamExt;
+use object_store::{path::Path, ObjectStore, PutPayload};
+use tokio_util::io::StreamReader;
+
+/// An inclusive range that a numeric property must fall in, e.g. from
+/// `CAST(data['Molecular_Weight'] AS DOUBLE) > 300`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PropertyPredicate {
+    property: String,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl PropertyPredicate {
+    /// The predicate of a comparison between a numeric cast of a data property and a literal,
+    /// or `None` if the filter can't be used to prune chunks.
+    pub(crate) fn try_from_expr(expr: &Expr) -> Option<Self> {
+        match expr {
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+                let (property, op, value) = match (property_name(left), property_name(right)) {
+                    (Some(property), None) => (property, *op, literal_value(right)?),
+                    (None, Some(property)) => (property, op.swap()?, literal_value(left)?),
+                    _ => return None,
+                };
+
+                let (min, max) = match op {
+                    Operator::Eq => (Some(value), Some(value)),
+                    Operator::Gt | Operator::GtEq => (Some(value), None),
+                    Operator::Lt | Operator::LtEq => (None, Some(value)),
+                    _ => return None,
+                };
+
+                Some(Self { property, min, max })
+            }
+            Expr::Between(Between {
+                expr,
+                negated: false,
+                low,
+                high,
+            }) => Some(Self {
+                property: property_name(expr)?,
+                min: Some(literal_value(low)?),
+                max: Some(literal_value(high)?),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether any record of the chunk could match, which is true unless all of the property's
+    /// values are numeric and their range doesn't overlap the predicate's.
+    fn may_match(&self, chunk: &PropertyIndexChunk) -> bool {
+        let Some((min, max)) = chunk.range(&self.property) else {
+            return true;
+        };
+
+        self.min.map_or(true, |v| max >= v) && self.max.map_or(true, |v| min <= v)
+    }
+}
+
+/// The name of the data property in a numeric cast of `data['name']`.
+fn property_name(expr: &Expr) -> Option<String> {
+    let (Expr::Cast(Cast { expr, data_type }) | Expr::TryCast(TryCast { expr, data_type })) = expr
+    else {
+        return None;
+    };
+
+    if !data_type.is_numeric() {
+        return None;
+    }
+
+    let Expr::ScalarFunction(ScalarFunction { func, args }) = expr.as_ref() else {
+        return None;
+    };
+
+    match (func.name(), args.as_slice()) {
+        ("get_field", [Expr::Column(c), Expr::Literal(ScalarValue::Utf8(Some(name)))])
+            if c.name == "data" =>
+        {
+            Some(name.clone())
+        }
+        _ => None,
+    }
+}
+
+fn literal_value(expr: &Expr) -> Option<f64> {
+    let Expr::Literal(value) = expr else {
+        return None;
+    };
+
+    match value.cast_to(&DataType::Float64).ok()? {
+        ScalarValue::Float64(Some(v)) if v.is_finite() => Some(v),
+        _ => None,
+    }
+}
+
+/// Read the property index sidecar of an SDF file, or build and write it if it doesn't exist or
+/// is older than the file.
+pub(crate) async fn get_or_build_property_index(
+    object_store: &Arc<dyn ObjectStore>,
+    file: &PartitionedFile,
+    records_per_chunk: usize,
+) -> Result<PropertyIndex> {
+    let location = &file.object_meta.location;
+    let index_location = Path::from(format!("{}.{}", location, PROPERTY_INDEX_EXTENSION));
+
+    match object_store.get(&index_location).await {
+        Ok(get_result) if get_result.meta.last_modified >= file.object_meta.last_modified => {
+            let bytes = get_result.bytes().await?;
+
+            return PropertyIndex::try_from_bytes(&bytes).map_err(|e| {
+                DataFusionError::Execution(format!(
+                    "Unable to read SDF property index {}: {}",
+                    index_location, e
+                ))
+            });
+        }
+        Ok(_) | Err(object_store::Error::NotFound { .. }) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let get_result = object_store.get(location).await?;
+    let stream = get_result.into_stream().map_err(DataFusionError::from);
+    let reader = StreamReader::new(Box::pin(stream));
+
+    let index = PropertyIndex::build(reader, records_per_chunk)
+        .await
+        .map_err(|e| {
+            DataFusionError::Execution(format!(
+                "Unable to build SDF property index for {}: {}",
+                location, e
+            ))
+        })?;
+
+    // The index is only an optimization, so e.g. a read-only store shouldn't fail the scan.
+    if let Err(e) = object_store
+        .put(&index_location, PutPayload::from(index.to_bytes()))
+        .await
+    {
+        tracing::warn!(
+            "Unable to write SDF property index {}: {}",
+            index_location,
+            e
+        );
+    }
+
+    Ok(index)
+}
+
+/// Split a file into the byte ranges of the chunks that may match all the predicates, merging
+/// adjacent chunks.
+pub(crate) fn prune_file(
+    file: &PartitionedFile,
+    index: &PropertyIndex,
+    predicates: &[PropertyPredicate],
+) -> Vec<PartitionedFile> {
+    let mut ranges: Vec<FileRange> = Vec::new();
+
+    for chunk in index.chunks() {
+        if !predicates.iter().all(|p| p.may_match(chunk)) {
+            continue;
+        }
+
+        let (start, end) = (chunk.start() as i64, chunk.end() as i64);
+
+        match ranges.last_mut() {
+            Some(range) if range.end == start => range.end = end,
+            _ => ranges.push(FileRange { start, end }),
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|range| {
+            let mut file = file.clone();
+            file.range = Some(range);
+            file
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::{
+        functions::core::expr_fn::get_field,
+        logical_expr::{cast, col, lit},
+    };
+
+    use super::*;
+
+    fn weight() -> Expr {
+        cast(
+            get_field(col("data"), "Molecular_Weight"),
+            DataType::Float64,
+        )
+    }
+
+    #[test]
+    fn test_property_predicate() {
+        let predicate = PropertyPredicate::try_from_expr(&weight().gt(lit(300.0)));
+        assert_eq!(
+            predicate,
+            Some(PropertyPredicate {
+                property: "Molecular_Weight".to_string(),
+                min: Some(300.0),
+                max: None,
+            })
+        );
+
+        let predicate = PropertyPredicate::try_from_expr(&lit(300).gt(weight()));
+        assert_eq!(
+            predicate,
+            Some(PropertyPredicate {
+                property: "Molecular_Weight".to_string(),
+                min: None,
+                max: Some(300.0),
+            })
+        );
+
+        let predicate = PropertyPredicate::try_from_expr(&weight().between(lit(100.0), lit(200.0)));
+        assert_eq!(
+            predicate,
+            Some(PropertyPredicate {
+                property: "Molecular_Weight".to_string(),
+                min: Some(100.0),
+                max: Some(200.0),
+            })
+        );
+
+        let uncast = get_field(col("data"), "Molecular_Weight").eq(lit("300"));
+        assert_eq!(PropertyPredicate::try_from_expr(&uncast), None);
+
+        let not_equal = weight().not_eq(lit(300.0));
+        assert_eq!(PropertyPredicate::try_from_expr(&not_equal), None);
+    }
+}
@@ -29,7 +29,8 @@ use datafusion::{
 };
 use exon_sdf::SDFConfig;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::SDFOpener;
 
@@ -148,10 +149,16 @@ impl ExecutionPlan for SDFScan {
             Arc::clone(&self.base_config.file_schema),
         )
         .with_projection(self.base_config.file_projection())
-        .with_limit_opt(self.base_config.limit);
+        .with_limit_opt(self.base_config.limit)
+        .with_reader_limits(reader_limits(context.session_config()));
 
         let opener = SDFOpener::new(Arc::new(config), self.file_compression_type);
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
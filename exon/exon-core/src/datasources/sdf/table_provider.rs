@@ -28,6 +28,7 @@ use datafusion::{
     physical_plan::{empty::EmptyExec, ExecutionPlan},
 };
 use exon_common::TableSchema;
+use exon_sdf::DEFAULT_RECORDS_PER_CHUNK;
 use futures::TryStreamExt;
 use object_store::{ObjectMeta, ObjectStore};
 use tokio_util::io::StreamReader;
@@ -42,7 +43,10 @@ use crate::{
     },
 };
 
-use super::scanner::SDFScan;
+use super::{
+    property_index::{get_or_build_property_index, prune_file, PropertyPredicate},
+    scanner::SDFScan,
+};
 
 #[derive(Debug)]
 /// Options specific to the SDF File format.
@@ -55,6 +59,9 @@ pub struct ListingSDFTableOptions {
 
     /// A list of partitioned columns
     table_partition_cols: Vec<Field>,
+
+    /// Whether to add the atoms and bonds of the connection table as list columns.
+    parse_structure: bool,
 }
 
 impl Default for ListingSDFTableOptions {
@@ -63,6 +70,7 @@ impl Default for ListingSDFTableOptions {
             file_extension: "sdf".to_string(),
             file_compression_type: FileCompressionType::UNCOMPRESSED,
             table_partition_cols: Vec::new(),
+            parse_structure: false,
         }
     }
 }
@@ -98,6 +106,12 @@ impl ListingSDFTableOptions {
         self
     }
 
+    /// Update whether to add `atoms` and `bonds` list columns with the connection table
+    pub fn with_parse_structure(mut self, parse_structure: bool) -> Self {
+        self.parse_structure = parse_structure;
+        self
+    }
+
     /// Update the file compression type
     pub fn with_file_compression_type(
         mut self,
@@ -178,6 +192,10 @@ impl ListingSDFTableOptions {
         let mut schema_builder = exon_sdf::SDFSchemaBuilder::default();
         schema_builder.update_data_field(record.data());
 
+        if self.parse_structure {
+            schema_builder.add_structure_fields();
+        }
+
         Ok(schema_builder.build())
     }
 }
@@ -188,6 +206,9 @@ pub struct ListingSDFTable<T> {
     table_schema: TableSchema,
 
     config: ExonListingConfig<T>,
+
+    /// Whether to prune chunks of uncompressed files with a property index sidecar.
+    property_index: bool,
 }
 
 impl<T> ListingSDFTable<T> {
@@ -196,8 +217,22 @@ impl<T> ListingSDFTable<T> {
         Self {
             table_schema,
             config,
+            property_index: false,
         }
     }
+
+    /// Update whether to prune chunks of uncompressed files with min/max property values from a
+    /// sidecar index, which is written next to each file on its first filtered scan.
+    pub fn with_property_index(mut self, property_index: bool) -> Self {
+        self.property_index = property_index;
+        self
+    }
+}
+
+impl<T: ExonListingOptions> ListingSDFTable<T> {
+    fn uses_property_index(&self) -> bool {
+        self.property_index && !self.config.options.file_compression_type().is_compressed()
+    }
 }
 
 #[async_trait]
@@ -220,7 +255,13 @@ impl<T: ExonListingOptions + 'static> TableProvider for ListingSDFTable<T> {
     ) -> datafusion::common::Result<Vec<TableProviderFilterPushDown>> {
         Ok(filters
             .iter()
-            .map(|f| filter_matches_partition_cols(f, self.config.options.table_partition_cols()))
+            .map(|f| {
+                if self.uses_property_index() && PropertyPredicate::try_from_expr(f).is_some() {
+                    return TableProviderFilterPushDown::Inexact;
+                }
+
+                filter_matches_partition_cols(f, self.config.options.table_partition_cols())
+            })
             .collect())
     }
 
@@ -260,6 +301,27 @@ impl<T: ExonListingOptions + 'static> TableProvider for ListingSDFTable<T> {
         .try_collect::<Vec<_>>()
         .await?;
 
+        let predicates = filters
+            .iter()
+            .filter_map(PropertyPredicate::try_from_expr)
+            .collect::<Vec<_>>();
+
+        let file_list = if self.uses_property_index() && !predicates.is_empty() {
+            let mut pruned_file_list = Vec::new();
+
+            for file in file_list {
+                let index =
+                    get_or_build_property_index(&object_store, &file, DEFAULT_RECORDS_PER_CHUNK)
+                        .await?;
+
+                pruned_file_list.extend(prune_file(&file, &index, &predicates));
+            }
+
+            pruned_file_list
+        } else {
+            file_list
+        };
+
         let file_scan_config = FileScanConfigBuilder::new(
             object_store_url.clone(),
             Arc::clone(&self.table_schema.file_schema()?),
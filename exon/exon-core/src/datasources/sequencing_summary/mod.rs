@@ -0,0 +1,33 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A datafusion compatible datasource for nanopore `sequencing_summary.txt` files.
+
+mod sequencing_summary_batch_reader;
+mod sequencing_summary_config;
+mod sequencing_summary_filter;
+mod sequencing_summary_opener;
+mod sequencing_summary_scanner;
+mod sequencing_summary_schema_builder;
+
+/// Table provider for sequencing summary files.
+pub mod table_provider;
+
+pub use self::sequencing_summary_config::SequencingSummaryConfig;
+pub use self::sequencing_summary_filter::SequencingSummaryFilter;
+pub use self::sequencing_summary_opener::SequencingSummaryOpener;
+pub use self::sequencing_summary_scanner::SequencingSummaryScan;
+
+mod udtf;
+pub use self::udtf::SequencingSummaryScanFunction;
@@ -0,0 +1,142 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{csv::reader::Decoder, error::ArrowError, record_batch::RecordBatch};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use super::{
+    sequencing_summary_config::SequencingSummaryConfig,
+    sequencing_summary_schema_builder::split_fields,
+};
+
+/// Reads the lines of a sequencing summary into record batches, skipping the lines that don't
+/// match the prefilter before they're parsed.
+pub struct SequencingSummaryBatchReader<R> {
+    /// The underlying reader.
+    reader: R,
+
+    /// The configuration for this reader.
+    config: Arc<SequencingSummaryConfig>,
+
+    /// The decoder of the matching lines.
+    decoder: Decoder,
+
+    /// The positions of the read id and channel columns.
+    read_id_index: Option<usize>,
+    channel_index: Option<usize>,
+}
+
+impl<R> SequencingSummaryBatchReader<R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    /// Create a batch reader, reading the header and checking it against the file schema.
+    pub async fn try_new(
+        mut reader: R,
+        config: Arc<SequencingSummaryConfig>,
+    ) -> Result<Self, ArrowError> {
+        let mut header = String::new();
+        reader.read_line(&mut header).await?;
+
+        let columns = split_fields(&header);
+
+        let expected_columns = config
+            .file_schema
+            .fields()
+            .iter()
+            .map(|f| f.name().as_str())
+            .collect::<Vec<_>>();
+
+        if columns != expected_columns {
+            return Err(ArrowError::SchemaError(format!(
+                "Sequencing summary columns {columns:?} don't match the table columns {expected_columns:?}"
+            )));
+        }
+
+        let position = |name: &str| columns.iter().position(|column| *column == name);
+        let read_id_index = position("read_id");
+        let channel_index = position("channel");
+
+        Ok(Self {
+            decoder: config.build_decoder(),
+            reader,
+            config,
+            read_id_index,
+            channel_index,
+        })
+    }
+
+    fn matches(&self, line: &[u8]) -> bool {
+        let Some(filter) = &self.config.filter else {
+            return true;
+        };
+
+        let line = std::str::from_utf8(line).unwrap_or_default();
+        let fields = split_fields(line);
+
+        let field = |index: Option<usize>| index.and_then(|i| fields.get(i).copied());
+
+        filter.matches(field(self.read_id_index), field(self.channel_index))
+    }
+
+    pub async fn read_batch(&mut self) -> Result<Option<RecordBatch>, ArrowError> {
+        let mut lines = Vec::new();
+        let mut line = Vec::new();
+        let mut rows = 0;
+
+        while rows < self.config.batch_size {
+            line.clear();
+            if self.reader.read_until(b'\n', &mut line).await? == 0 {
+                break;
+            }
+
+            if line.trim_ascii().is_empty() || !self.matches(&line) {
+                continue;
+            }
+
+            lines.extend_from_slice(&line);
+            if !line.ends_with(b"\n") {
+                lines.push(b'\n');
+            }
+
+            rows += 1;
+        }
+
+        if rows == 0 {
+            return Ok(None);
+        }
+
+        let mut decoded = 0;
+        while decoded < lines.len() {
+            match self.decoder.decode(&lines[decoded..])? {
+                0 => break,
+                n => decoded += n,
+            }
+        }
+
+        self.decoder.flush()
+    }
+
+    pub fn into_stream(self) -> impl futures::Stream<Item = Result<RecordBatch, ArrowError>> {
+        futures::stream::unfold(self, |mut reader| async move {
+            match reader.read_batch().await {
+                Ok(Some(batch)) => Some((Ok(batch), reader)),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), reader)),
+            }
+        })
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    csv::{reader::Decoder, ReaderBuilder},
+    datatypes::SchemaRef,
+};
+use exon_common::DEFAULT_BATCH_SIZE;
+use object_store::ObjectStore;
+
+use super::sequencing_summary_filter::SequencingSummaryFilter;
+
+/// Configuration for a sequencing summary data source.
+pub struct SequencingSummaryConfig {
+    /// The number of rows to read at a time.
+    pub batch_size: usize,
+    /// The schema of the sequencing summary file, from the header of the first file.
+    pub file_schema: SchemaRef,
+    /// The object store to use for reading sequencing summary files.
+    pub object_store: Arc<dyn ObjectStore>,
+    /// The projection to use for reading sequencing summary files.
+    pub projection: Option<Vec<usize>>,
+    /// The prefilter on the read id and channel of each line.
+    pub filter: Option<SequencingSummaryFilter>,
+}
+
+impl SequencingSummaryConfig {
+    /// Create a new sequencing summary configuration.
+    pub fn new(object_store: Arc<dyn ObjectStore>, file_schema: SchemaRef) -> Self {
+        Self {
+            object_store,
+            file_schema,
+            batch_size: DEFAULT_BATCH_SIZE,
+            projection: None,
+            filter: None,
+        }
+    }
+
+    /// Build a decoder for the lines after the header.
+    pub fn build_decoder(&self) -> Decoder {
+        let mut builder = ReaderBuilder::new(Arc::clone(&self.file_schema))
+            .with_header(false)
+            .with_delimiter(b'\t')
+            .with_batch_size(self.batch_size);
+
+        if let Some(projection) = &self.projection {
+            builder = builder.with_projection(projection.clone());
+        }
+
+        builder.build_decoder()
+    }
+
+    /// Set the batch size.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set the projection.
+    pub fn with_some_projection(mut self, projection: Option<Vec<usize>>) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Set the prefilter.
+    pub fn with_filter(mut self, filter: Option<SequencingSummaryFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
+}
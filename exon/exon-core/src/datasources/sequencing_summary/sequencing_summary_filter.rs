@@ -0,0 +1,273 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use arrow::datatypes::DataType;
+use datafusion::{
+    logical_expr::{expr::InList, Between, BinaryExpr, Expr, Operator},
+    scalar::ScalarValue,
+};
+
+/// A prefilter on the read id and channel of the lines of a sequencing summary.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SequencingSummaryFilter {
+    read_ids: Option<HashSet<String>>,
+    channels: Option<(i64, i64)>,
+}
+
+impl SequencingSummaryFilter {
+    /// Only keep the reads with one of these ids.
+    pub fn with_read_ids(self, read_ids: HashSet<String>) -> Self {
+        let read_ids = match self.read_ids {
+            Some(current) => current.intersection(&read_ids).cloned().collect(),
+            None => read_ids,
+        };
+
+        Self {
+            read_ids: Some(read_ids),
+            ..self
+        }
+    }
+
+    /// Bound the channel, inclusively.
+    pub fn with_channel_bounds(self, min: i64, max: i64) -> Self {
+        let channels = match self.channels {
+            Some((current_min, current_max)) => (current_min.max(min), current_max.min(max)),
+            None => (min, max),
+        };
+
+        Self {
+            channels: Some(channels),
+            ..self
+        }
+    }
+
+    /// Combine two filters, keeping the reads that match both.
+    pub fn and(self, other: Self) -> Self {
+        let filter = match other.read_ids {
+            Some(read_ids) => self.with_read_ids(read_ids),
+            None => self,
+        };
+
+        match other.channels {
+            Some((min, max)) => filter.with_channel_bounds(min, max),
+            None => filter,
+        }
+    }
+
+    /// True if a read with this id and channel field may match.
+    pub fn matches(&self, read_id: Option<&str>, channel: Option<&str>) -> bool {
+        if let Some(read_ids) = &self.read_ids {
+            if !read_id.is_some_and(|read_id| read_ids.contains(read_id)) {
+                return false;
+            }
+        }
+
+        if let Some((min, max)) = self.channels {
+            let channel = channel.and_then(|channel| channel.parse::<i64>().ok());
+
+            if !channel.is_some_and(|channel| min <= channel && channel <= max) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+fn column_name(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Column(c) => Some(c.name.as_str()),
+        _ => None,
+    }
+}
+
+fn string_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Literal(ScalarValue::Utf8(Some(value)))
+        | Expr::Literal(ScalarValue::LargeUtf8(Some(value)))
+        | Expr::Literal(ScalarValue::Utf8View(Some(value))) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn integer_literal(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Literal(scalar) if scalar.data_type().is_integer() => {
+            match scalar.cast_to(&DataType::Int64).ok()? {
+                ScalarValue::Int64(value) => value,
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn literal_filter(column: &str, op: Operator, value: &Expr) -> Option<SequencingSummaryFilter> {
+    let filter = SequencingSummaryFilter::default();
+
+    match (column, op) {
+        ("read_id", Operator::Eq) => {
+            Some(filter.with_read_ids(HashSet::from([string_literal(value)?])))
+        }
+        ("channel", Operator::Eq) => {
+            let channel = integer_literal(value)?;
+            Some(filter.with_channel_bounds(channel, channel))
+        }
+        ("channel", Operator::Lt) => {
+            Some(filter.with_channel_bounds(i64::MIN, integer_literal(value)?.saturating_sub(1)))
+        }
+        ("channel", Operator::LtEq) => {
+            Some(filter.with_channel_bounds(i64::MIN, integer_literal(value)?))
+        }
+        ("channel", Operator::Gt) => {
+            Some(filter.with_channel_bounds(integer_literal(value)?.saturating_add(1), i64::MAX))
+        }
+        ("channel", Operator::GtEq) => {
+            Some(filter.with_channel_bounds(integer_literal(value)?, i64::MAX))
+        }
+        _ => None,
+    }
+}
+
+/// Infer a prefilter from a filter on the read id or channel columns.
+pub(crate) fn infer_sequencing_summary_filter(expr: &Expr) -> Option<SequencingSummaryFilter> {
+    match expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        }) => match (
+            infer_sequencing_summary_filter(left),
+            infer_sequencing_summary_filter(right),
+        ) {
+            (Some(left), Some(right)) => Some(left.and(right)),
+            (filter, None) | (None, filter) => filter,
+        },
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            match (column_name(left), column_name(right)) {
+                (Some(column), None) => literal_filter(column, *op, right),
+                (None, Some(column)) => literal_filter(column, op.swap()?, left),
+                _ => None,
+            }
+        }
+        Expr::Between(Between {
+            expr,
+            negated: false,
+            low,
+            high,
+        }) if column_name(expr) == Some("channel") => Some(
+            SequencingSummaryFilter::default()
+                .with_channel_bounds(integer_literal(low)?, integer_literal(high)?),
+        ),
+        Expr::InList(InList {
+            expr,
+            list,
+            negated: false,
+        }) => match column_name(expr)? {
+            "read_id" => {
+                let read_ids = list.iter().map(string_literal).collect::<Option<_>>()?;
+                Some(SequencingSummaryFilter::default().with_read_ids(read_ids))
+            }
+            "channel" => {
+                let channels = list
+                    .iter()
+                    .map(integer_literal)
+                    .collect::<Option<Vec<_>>>()?;
+                Some(
+                    SequencingSummaryFilter::default()
+                        .with_channel_bounds(*channels.iter().min()?, *channels.iter().max()?),
+                )
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use datafusion::logical_expr::{col, lit};
+
+    use super::{infer_sequencing_summary_filter, SequencingSummaryFilter};
+
+    fn read_ids(read_ids: &[&str]) -> HashSet<String> {
+        read_ids.iter().map(|read_id| read_id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_infer_sequencing_summary_filter() {
+        let cases = [
+            (
+                col("read_id").eq(lit("a")),
+                SequencingSummaryFilter::default().with_read_ids(read_ids(&["a"])),
+            ),
+            (
+                col("read_id").in_list(vec![lit("a"), lit("b")], false),
+                SequencingSummaryFilter::default().with_read_ids(read_ids(&["a", "b"])),
+            ),
+            (
+                lit(100).lt(col("channel")),
+                SequencingSummaryFilter::default().with_channel_bounds(101, i64::MAX),
+            ),
+            (
+                col("channel").between(lit(10), lit(20)),
+                SequencingSummaryFilter::default().with_channel_bounds(10, 20),
+            ),
+            (
+                col("channel")
+                    .in_list(vec![lit(12), lit(3), lit(7)], false)
+                    .and(col("read_id").eq(lit("a")))
+                    .and(col("mux").eq(lit(1))),
+                SequencingSummaryFilter::default()
+                    .with_channel_bounds(3, 12)
+                    .with_read_ids(read_ids(&["a"])),
+            ),
+        ];
+
+        for (expr, expected) in cases {
+            assert_eq!(
+                infer_sequencing_summary_filter(&expr),
+                Some(expected),
+                "{}",
+                expr
+            );
+        }
+
+        assert!(infer_sequencing_summary_filter(&col("read_id").not_eq(lit("a"))).is_none());
+        assert!(infer_sequencing_summary_filter(&col("channel").eq(lit("a"))).is_none());
+        assert!(infer_sequencing_summary_filter(
+            &col("read_id").eq(lit("a")).or(col("channel").eq(lit(1)))
+        )
+        .is_none());
+        assert!(infer_sequencing_summary_filter(&col("mux").eq(lit(1))).is_none());
+    }
+
+    #[test]
+    fn test_matches() {
+        let filter = SequencingSummaryFilter::default()
+            .with_read_ids(read_ids(&["a", "b"]))
+            .and(SequencingSummaryFilter::default().with_read_ids(read_ids(&["b", "c"])))
+            .with_channel_bounds(1, 10);
+
+        assert!(filter.matches(Some("b"), Some("10")));
+        assert!(!filter.matches(Some("a"), Some("10")));
+        assert!(!filter.matches(Some("b"), Some("11")));
+        assert!(!filter.matches(Some("b"), Some("")));
+        assert!(!filter.matches(None, Some("1")));
+    }
+}
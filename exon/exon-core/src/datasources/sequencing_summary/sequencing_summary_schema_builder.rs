@@ -0,0 +1,138 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::datatypes::{DataType, Field, Schema};
+use exon_common::TableSchema;
+
+/// The columns of a sequencing summary that aren't strings. Any other column is read as a string.
+const TYPED_COLUMNS: &[(&str, DataType)] = &[
+    ("batch_id", DataType::Int64),
+    ("channel", DataType::Int64),
+    ("mux", DataType::Int64),
+    ("start_time", DataType::Float64),
+    ("duration", DataType::Float64),
+    ("num_events", DataType::Int64),
+    ("minknow_events", DataType::Int64),
+    ("passes_filtering", DataType::Boolean),
+    ("template_start", DataType::Float64),
+    ("num_events_template", DataType::Int64),
+    ("template_duration", DataType::Float64),
+    ("sequence_length_template", DataType::Int64),
+    ("mean_qscore_template", DataType::Float64),
+    ("strand_score_template", DataType::Float64),
+    ("median_template", DataType::Float64),
+    ("mad_template", DataType::Float64),
+    ("scaling_median_template", DataType::Float64),
+    ("scaling_mad_template", DataType::Float64),
+    ("barcode_score", DataType::Float64),
+    ("alignment_genome_start", DataType::Int64),
+    ("alignment_genome_end", DataType::Int64),
+    ("alignment_strand_start", DataType::Int64),
+    ("alignment_strand_end", DataType::Int64),
+    ("alignment_num_insertions", DataType::Int64),
+    ("alignment_num_deletions", DataType::Int64),
+    ("alignment_num_aligned", DataType::Int64),
+    ("alignment_num_correct", DataType::Int64),
+    ("alignment_identity", DataType::Float64),
+    ("alignment_accuracy", DataType::Float64),
+    ("alignment_coverage", DataType::Float64),
+    ("alignment_length", DataType::Int64),
+];
+
+/// Split a line of a sequencing summary into its tab separated fields.
+pub(crate) fn split_fields(line: &str) -> Vec<&str> {
+    line.trim_end_matches(['\r', '\n']).split('\t').collect()
+}
+
+fn column_type(name: &str) -> DataType {
+    TYPED_COLUMNS
+        .iter()
+        .find(|(column, _)| *column == name)
+        .map(|(_, data_type)| data_type.clone())
+        .unwrap_or(DataType::Utf8)
+}
+
+/// Builds the schema of a sequencing summary from the columns in its header.
+pub struct SequencingSummarySchemaBuilder {
+    file_fields: Vec<Field>,
+    partition_fields: Vec<Field>,
+}
+
+impl SequencingSummarySchemaBuilder {
+    /// Create a schema builder for the columns of a header line.
+    pub fn from_header(header: &str) -> Self {
+        let file_fields = split_fields(header)
+            .into_iter()
+            .map(|name| Field::new(name, column_type(name), true))
+            .collect();
+
+        Self {
+            file_fields,
+            partition_fields: vec![],
+        }
+    }
+
+    pub fn add_partition_fields(&mut self, partition_fields: Vec<Field>) {
+        self.partition_fields.extend(partition_fields);
+    }
+
+    pub fn build(self) -> TableSchema {
+        let mut fields = self.file_fields.clone();
+        fields.extend(self.partition_fields);
+
+        let schema = Schema::new(fields);
+
+        let projection: Vec<usize> = (0..self.file_fields.len()).collect();
+
+        TableSchema::new(Arc::new(schema), projection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::datatypes::DataType;
+
+    use super::SequencingSummarySchemaBuilder;
+
+    #[test]
+    fn test_from_header() -> Result<(), Box<dyn std::error::Error>> {
+        let header =
+            "filename\tread_id\tchannel\tstart_time\tpasses_filtering\tbarcode_arrangement\n";
+
+        let table_schema = SequencingSummarySchemaBuilder::from_header(header).build();
+        let file_schema = table_schema.file_schema()?;
+
+        let data_types = file_schema
+            .fields()
+            .iter()
+            .map(|f| (f.name().as_str(), f.data_type().clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            data_types,
+            vec![
+                ("filename", DataType::Utf8),
+                ("read_id", DataType::Utf8),
+                ("channel", DataType::Int64),
+                ("start_time", DataType::Float64),
+                ("passes_filtering", DataType::Boolean),
+                ("barcode_arrangement", DataType::Utf8),
+            ]
+        );
+
+        Ok(())
+    }
+}
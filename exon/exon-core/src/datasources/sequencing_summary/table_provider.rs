@@ -0,0 +1,334 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::datatypes::{Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::{
+        file_format::file_compression_type::FileCompressionType, listing::ListingTableUrl,
+        physical_plan::FileScanConfig, TableProvider,
+    },
+    error::{DataFusionError, Result},
+    logical_expr::{TableProviderFilterPushDown, TableType},
+    physical_plan::{empty::EmptyExec, ExecutionPlan},
+    prelude::Expr,
+};
+use exon_common::TableSchema;
+use futures::TryStreamExt;
+use tokio::io::AsyncBufReadExt;
+use tokio_util::io::StreamReader;
+
+use crate::{
+    datasources::{
+        exon_listing_table_options::{ExonListingConfig, ExonListingOptions},
+        hive_partition::filter_matches_partition_cols,
+        ExonFileType,
+    },
+    physical_plan::{
+        file_scan_config_builder::FileScanConfigBuilder, object_store::pruned_partition_list,
+    },
+};
+
+use super::{
+    sequencing_summary_filter::{infer_sequencing_summary_filter, SequencingSummaryFilter},
+    sequencing_summary_schema_builder::SequencingSummarySchemaBuilder,
+    SequencingSummaryScan,
+};
+
+#[derive(Debug, Clone)]
+/// Listing options for a sequencing summary table
+pub struct ListingSequencingSummaryTableOptions {
+    /// File extension for the table
+    file_extension: String,
+
+    /// File compression type
+    file_compression_type: FileCompressionType,
+
+    /// Partition columns for the table
+    table_partition_cols: Vec<Field>,
+}
+
+#[async_trait]
+impl ExonListingOptions for ListingSequencingSummaryTableOptions {
+    fn table_partition_cols(&self) -> &[Field] {
+        &self.table_partition_cols
+    }
+
+    fn file_extension(&self) -> &str {
+        &self.file_extension
+    }
+
+    fn file_compression_type(&self) -> FileCompressionType {
+        self.file_compression_type
+    }
+
+    async fn create_physical_plan(
+        &self,
+        conf: FileScanConfig,
+    ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
+        let scan = SequencingSummaryScan::new(conf, self.file_compression_type);
+        Ok(Arc::new(scan))
+    }
+}
+
+impl Default for ListingSequencingSummaryTableOptions {
+    fn default() -> Self {
+        Self::new(FileCompressionType::UNCOMPRESSED)
+    }
+}
+
+impl ListingSequencingSummaryTableOptions {
+    /// Create a new set of options
+    pub fn new(file_compression_type: FileCompressionType) -> Self {
+        let file_extension =
+            ExonFileType::SequencingSummary.get_file_extension(file_compression_type);
+
+        Self {
+            file_extension,
+            file_compression_type,
+            table_partition_cols: Vec::new(),
+        }
+    }
+
+    /// Set the partition columns for the table
+    pub fn with_table_partition_cols(self, table_partition_cols: Vec<Field>) -> Self {
+        Self {
+            table_partition_cols,
+            ..self
+        }
+    }
+
+    /// Set the file extension for the table
+    pub fn with_file_extension(self, file_extension: String) -> Self {
+        Self {
+            file_extension,
+            ..self
+        }
+    }
+
+    /// Infer the schema for the table from the header of the first file
+    pub async fn infer_schema(
+        &self,
+        state: &dyn Session,
+        table_path: &ListingTableUrl,
+    ) -> Result<TableSchema> {
+        let store = state.runtime_env().object_store(table_path)?;
+
+        let objects = exon_common::object_store_files_from_table_path(
+            &store,
+            table_path.as_ref(),
+            table_path.prefix(),
+            self.file_extension.as_str(),
+            None,
+        )
+        .await
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        let object = objects.first().ok_or_else(|| {
+            DataFusionError::Execution("No objects found in the table path".to_string())
+        })?;
+
+        let get_result = store.get(&object.location).await?;
+
+        let stream_reader = Box::pin(get_result.into_stream().map_err(DataFusionError::from));
+        let stream_reader = self.file_compression_type.convert_stream(stream_reader)?;
+        let mut stream_reader = StreamReader::new(stream_reader);
+
+        let mut header = String::new();
+        stream_reader.read_line(&mut header).await?;
+
+        let mut schema_builder = SequencingSummarySchemaBuilder::from_header(&header);
+        schema_builder.add_partition_fields(self.table_partition_cols.clone());
+
+        Ok(schema_builder.build())
+    }
+
+    async fn create_physical_plan_with_filter(
+        &self,
+        conf: FileScanConfig,
+        filter: SequencingSummaryFilter,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let scan = SequencingSummaryScan::new(conf, self.file_compression_type).with_filter(filter);
+
+        Ok(Arc::new(scan))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A sequencing summary listing table
+pub struct ListingSequencingSummaryTable {
+    table_schema: TableSchema,
+
+    config: ExonListingConfig<ListingSequencingSummaryTableOptions>,
+}
+
+impl ListingSequencingSummaryTable {
+    /// Create a new sequencing summary listing table
+    pub fn new(
+        config: ExonListingConfig<ListingSequencingSummaryTableOptions>,
+        table_schema: TableSchema,
+    ) -> Self {
+        Self {
+            table_schema,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for ListingSequencingSummaryTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.table_schema.table_schema())
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        Ok(filters
+            .iter()
+            .map(|f| match infer_sequencing_summary_filter(f) {
+                Some(_) => TableProviderFilterPushDown::Inexact,
+                None => {
+                    filter_matches_partition_cols(f, self.config.options.table_partition_cols())
+                }
+            })
+            .collect())
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let url = if let Some(url) = self.config.inner.table_paths.first() {
+            url.object_store()
+        } else {
+            return Ok(Arc::new(EmptyExec::new(Arc::new(Schema::empty()))));
+        };
+
+        let object_store = state.runtime_env().object_store(url.clone())?;
+
+        let file_list = pruned_partition_list(
+            &object_store,
+            &self.config.inner.table_paths[0],
+            filters,
+            self.config.options.file_extension(),
+            self.config.options.table_partition_cols(),
+        )
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        let file_schema = self.table_schema.file_schema()?;
+        let file_scan_config = FileScanConfigBuilder::new(url, file_schema, vec![file_list])
+            .projection_option(projection.cloned())
+            .table_partition_cols(self.config.options.table_partition_cols().to_vec())
+            .limit_option(limit)
+            .build();
+
+        let filter = filters
+            .iter()
+            .filter_map(infer_sequencing_summary_filter)
+            .reduce(SequencingSummaryFilter::and);
+
+        let plan = match filter {
+            Some(filter) => {
+                self.config
+                    .options
+                    .create_physical_plan_with_filter(file_scan_config, filter)
+                    .await?
+            }
+            None => {
+                self.config
+                    .options
+                    .create_physical_plan(file_scan_config)
+                    .await?
+            }
+        };
+
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        datasources::{
+            sequencing_summary::table_provider::ListingSequencingSummaryTableOptions, ExonFileType,
+            ExonListingTableFactory,
+        },
+        ExonSession,
+    };
+
+    use datafusion::{
+        datasource::file_format::file_compression_type::FileCompressionType,
+        logical_expr::{col, lit},
+    };
+    use exon_test::test_listing_table_url;
+
+    #[tokio::test]
+    async fn test_settable_file_extension() -> Result<(), Box<dyn std::error::Error>> {
+        let options = ListingSequencingSummaryTableOptions::default();
+        assert_eq!(options.file_extension, "txt");
+
+        let options_with_tsv = options.with_file_extension("tsv".to_string());
+        assert_eq!(options_with_tsv.file_extension, "tsv");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_listing_with_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        let session_state = ctx.session.state();
+
+        let table_path = test_listing_table_url("sequencing_summary");
+        let table = ExonListingTableFactory::new()
+            .create_from_file_type(
+                &session_state,
+                ExonFileType::SequencingSummary,
+                FileCompressionType::UNCOMPRESSED,
+                table_path.to_string(),
+                Vec::new(),
+                &HashMap::new(),
+            )
+            .await?;
+
+        let df = ctx.session.read_table(table)?;
+        let row_cnt = df.clone().count().await?;
+        assert_eq!(row_cnt, 6);
+
+        let filtered_cnt = df.filter(col("channel").lt_eq(lit(100)))?.count().await?;
+        assert_eq!(filtered_cnt, 3);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, Int64Builder, ListBuilder, StringBuilder},
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    record_batch::RecordBatch,
+};
+use datafusion::error::{DataFusionError, Result};
+
+/// The fields read from the file report, and the order of the table's columns.
+const STRING_FIELDS: [&str; 15] = [
+    "run_accession",
+    "study_accession",
+    "secondary_study_accession",
+    "sample_accession",
+    "secondary_sample_accession",
+    "experiment_accession",
+    "sample_alias",
+    "scientific_name",
+    "instrument_platform",
+    "instrument_model",
+    "library_name",
+    "library_layout",
+    "library_strategy",
+    "library_source",
+    "library_selection",
+];
+
+const INTEGER_FIELDS: [&str; 3] = ["tax_id", "read_count", "base_count"];
+
+/// Fields with a value per FASTQ file of the run, separated by semicolons.
+const FILE_FIELDS: [&str; 3] = ["fastq_ftp", "fastq_md5", "fastq_bytes"];
+
+const DATE_FIELD: &str = "first_public";
+
+/// The comma separated fields to request from the file report.
+pub(crate) fn requested_fields() -> String {
+    STRING_FIELDS
+        .iter()
+        .chain(INTEGER_FIELDS.iter())
+        .chain(FILE_FIELDS.iter())
+        .chain(std::iter::once(&DATE_FIELD))
+        .copied()
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The schema of the runs table.
+pub(crate) fn schema() -> SchemaRef {
+    let mut fields = STRING_FIELDS
+        .iter()
+        .map(|name| Field::new(*name, DataType::Utf8, *name != "run_accession"))
+        .collect::<Vec<_>>();
+
+    fields.extend(
+        INTEGER_FIELDS
+            .iter()
+            .map(|name| Field::new(*name, DataType::Int64, true)),
+    );
+
+    let list = |data_type| DataType::List(Arc::new(Field::new("item", data_type, true)));
+
+    fields.extend([
+        Field::new("fastq_ftp", list(DataType::Utf8), true),
+        Field::new("fastq_md5", list(DataType::Utf8), true),
+        Field::new("fastq_bytes", list(DataType::Int64), true),
+        Field::new(DATE_FIELD, DataType::Date32, true),
+    ]);
+
+    Arc::new(Schema::new(fields))
+}
+
+fn invalid_report(message: impl std::fmt::Display) -> DataFusionError {
+    DataFusionError::Execution(format!("Invalid ENA file report: {message}"))
+}
+
+fn parse_integer(field: &str, value: &str) -> Result<i64> {
+    value
+        .parse()
+        .map_err(|_| invalid_report(format!("{field} {value} is not an integer")))
+}
+
+/// Reads a tab separated file report, with a header line, into a batch of the runs schema.
+///
+/// Columns are matched by name, so the order of the report's columns doesn't matter, and
+/// columns missing from the report are null.
+pub(crate) fn read_filereport(text: &str) -> Result<RecordBatch> {
+    let schema = schema();
+
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+
+    let Some(header) = lines.next() else {
+        return Ok(RecordBatch::new_empty(schema));
+    };
+    let header = header.split('\t').map(str::trim).collect::<Vec<_>>();
+
+    let position = |name: &str| header.iter().position(|column| *column == name);
+
+    if position("run_accession").is_none() {
+        return Err(invalid_report("missing the run_accession column"));
+    }
+
+    let mut strings = STRING_FIELDS
+        .iter()
+        .map(|name| (position(name), StringBuilder::new()))
+        .collect::<Vec<_>>();
+    let mut integers = INTEGER_FIELDS
+        .iter()
+        .map(|name| (*name, position(name), Int64Builder::new()))
+        .collect::<Vec<_>>();
+
+    let mut fastq_ftp = ListBuilder::new(StringBuilder::new());
+    let mut fastq_md5 = ListBuilder::new(StringBuilder::new());
+    let mut fastq_bytes = ListBuilder::new(Int64Builder::new());
+    let mut first_public = Vec::new();
+
+    let file_positions = FILE_FIELDS.map(position);
+    let date_position = position(DATE_FIELD);
+
+    for line in lines {
+        let values = line.split('\t').map(str::trim).collect::<Vec<_>>();
+        let value = |position: Option<usize>| {
+            position
+                .and_then(|i| values.get(i).copied())
+                .filter(|v| !v.is_empty())
+        };
+
+        for (position, builder) in strings.iter_mut() {
+            builder.append_option(value(*position));
+        }
+
+        for (name, position, builder) in integers.iter_mut() {
+            builder.append_option(
+                value(*position)
+                    .map(|v| parse_integer(name, v))
+                    .transpose()?,
+            );
+        }
+
+        for (position, builder) in file_positions[..2]
+            .iter()
+            .zip([&mut fastq_ftp, &mut fastq_md5])
+        {
+            match value(*position) {
+                Some(v) => builder.append_value(v.split(';').map(Some)),
+                None => builder.append_null(),
+            }
+        }
+
+        match value(file_positions[2]) {
+            Some(v) => fastq_bytes.append_value(
+                v.split(';')
+                    .map(|bytes| parse_integer("fastq_bytes", bytes).map(Some))
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            None => fastq_bytes.append_null(),
+        }
+
+        first_public.push(value(date_position));
+    }
+
+    let first_public: ArrayRef = Arc::new(arrow::array::StringArray::from(first_public));
+
+    let mut columns = strings
+        .into_iter()
+        .map(|(_, mut builder)| Arc::new(builder.finish()) as ArrayRef)
+        .collect::<Vec<_>>();
+    columns.extend(
+        integers
+            .into_iter()
+            .map(|(_, _, mut builder)| Arc::new(builder.finish()) as ArrayRef),
+    );
+    columns.extend([
+        Arc::new(fastq_ftp.finish()) as ArrayRef,
+        Arc::new(fastq_md5.finish()),
+        Arc::new(fastq_bytes.finish()),
+        arrow::compute::cast(&first_public, &DataType::Date32)?,
+    ]);
+
+    Ok(RecordBatch::try_new(schema, columns)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{Array, AsArray},
+        datatypes::{Date32Type, Int64Type},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_read_filereport() -> Result<()> {
+        let report = "run_accession\tstudy_accession\ttax_id\tread_count\tfastq_ftp\tfastq_md5\tfastq_bytes\tfirst_public\n\
+SRR000001\tPRJNA000001\t9606\t100\tftp.sra.ebi.ac.uk/SRR000001_1.fastq.gz;ftp.sra.ebi.ac.uk/SRR000001_2.fastq.gz\ta;b\t10;20\t2020-01-02\n\
+SRR000002\tPRJNA000001\t9606\t\t\t\t\t\n";
+
+        let batch = read_filereport(report)?;
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema(), schema());
+
+        let runs = batch
+            .column_by_name("run_accession")
+            .unwrap()
+            .as_string::<i32>();
+        assert_eq!(runs.value(1), "SRR000002");
+
+        // Columns that aren't in the report are null.
+        assert_eq!(
+            batch
+                .column_by_name("instrument_model")
+                .unwrap()
+                .null_count(),
+            2
+        );
+
+        let read_counts = batch.column_by_name("read_count").unwrap();
+        assert_eq!(read_counts.as_primitive::<Int64Type>().value(0), 100);
+        assert!(read_counts.is_null(1));
+
+        let fastq_bytes = batch
+            .column_by_name("fastq_bytes")
+            .unwrap()
+            .as_list::<i32>();
+        assert_eq!(
+            fastq_bytes.value(0).as_primitive::<Int64Type>().values(),
+            &[10, 20]
+        );
+        assert!(fastq_bytes.is_null(1));
+
+        let fastq_ftp = batch.column_by_name("fastq_ftp").unwrap().as_list::<i32>();
+        assert_eq!(
+            fastq_ftp.value(0).as_string::<i32>().value(1),
+            "ftp.sra.ebi.ac.uk/SRR000001_2.fastq.gz"
+        );
+
+        let first_public = batch.column_by_name("first_public").unwrap();
+        assert_eq!(first_public.as_primitive::<Date32Type>().value(0), 18263);
+        assert!(first_public.is_null(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_filereport_errors() -> Result<()> {
+        assert_eq!(read_filereport("")?.num_rows(), 0);
+
+        assert!(read_filereport("study_accession\nPRJNA000001\n").is_err());
+        assert!(read_filereport("run_accession\tread_count\nSRR000001\tmany\n").is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A table function that fetches the run metadata of public sequencing projects from the ENA
+//! portal API, which mirrors the SRA, so it can be joined against local results.
+//!
+//! This makes network requests, so it's only built with the `sra` feature.
+
+mod filereport;
+mod table_provider;
+mod udtf;
+
+pub use table_provider::SRARunsTable;
+pub use udtf::SRARunsFunction;
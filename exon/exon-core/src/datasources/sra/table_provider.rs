@@ -0,0 +1,119 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::{datatypes::SchemaRef, record_batch::RecordBatch};
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::TableProvider,
+    error::{DataFusionError, Result},
+    logical_expr::TableType,
+    physical_plan::{memory::MemoryExec, ExecutionPlan},
+    prelude::Expr,
+};
+
+use super::filereport::{read_filereport, requested_fields, schema};
+
+/// The ENA portal API endpoint for the file report of an accession.
+const ENA_FILEREPORT_URL: &str = "https://www.ebi.ac.uk/ena/portal/api/filereport";
+
+/// A table of the sequencing runs of one or more ENA or SRA accessions, fetched when scanned.
+#[derive(Debug, Clone)]
+pub struct SRARunsTable {
+    /// The project, study, sample, experiment, or run accessions to fetch the runs of.
+    accessions: Vec<String>,
+
+    /// The HTTP client to fetch the file reports with.
+    client: reqwest::Client,
+
+    /// The schema of the table.
+    schema: SchemaRef,
+}
+
+impl SRARunsTable {
+    /// Create a new table of the runs of the accessions.
+    pub fn new(client: reqwest::Client, accessions: Vec<String>) -> Self {
+        Self {
+            accessions,
+            client,
+            schema: schema(),
+        }
+    }
+
+    async fn fetch_runs(&self, accession: &str) -> Result<RecordBatch> {
+        let fields = requested_fields();
+
+        let response = self
+            .client
+            .get(ENA_FILEREPORT_URL)
+            .query(&[
+                ("accession", accession),
+                ("result", "read_run"),
+                ("fields", fields.as_str()),
+                ("format", "tsv"),
+            ])
+            .send()
+            .await
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+        if !status.is_success() {
+            return Err(DataFusionError::Execution(format!(
+                "Failed to fetch the runs of {accession} from ENA: {status} {}",
+                text.trim()
+            )));
+        }
+
+        read_filereport(&text)
+    }
+}
+
+#[async_trait]
+impl TableProvider for SRARunsTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let mut batches = Vec::with_capacity(self.accessions.len());
+        for accession in self.accessions.iter() {
+            batches.push(self.fetch_runs(accession).await?);
+        }
+
+        let exec = MemoryExec::try_new(&[batches], Arc::clone(&self.schema), projection.cloned())?;
+
+        Ok(Arc::new(exec))
+    }
+}
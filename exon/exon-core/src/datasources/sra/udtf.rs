@@ -0,0 +1,85 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use datafusion::{
+    common::ScalarValue,
+    datasource::{function::TableFunctionImpl, TableProvider},
+    error::{DataFusionError, Result},
+    logical_expr::Expr,
+};
+
+use super::SRARunsTable;
+
+/// A table function that fetches the sequencing runs of ENA or SRA accessions, e.g.
+/// `sra_runs('PRJNA257197')`.
+#[derive(Debug, Default)]
+pub struct SRARunsFunction {
+    client: reqwest::Client,
+}
+
+fn accession_argument(expr: &Expr) -> Result<String> {
+    let Expr::Literal(ScalarValue::Utf8(Some(accession))) = expr else {
+        return Err(DataFusionError::Plan(
+            "sra_runs requires its arguments to be accession strings".to_string(),
+        ));
+    };
+
+    // Accessions are letters and digits, so anything else is a mistake rather than a query.
+    if accession.is_empty() || !accession.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(DataFusionError::Plan(format!(
+            "sra_runs got an invalid accession {accession}"
+        )));
+    }
+
+    Ok(accession.to_uppercase())
+}
+
+impl TableFunctionImpl for SRARunsFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        if exprs.is_empty() {
+            return Err(DataFusionError::Plan(
+                "sra_runs requires at least one accession".to_string(),
+            ));
+        }
+
+        let accessions = exprs
+            .iter()
+            .map(accession_argument)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(SRARunsTable::new(self.client.clone(), accessions)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::{logical_expr::lit, prelude::col};
+
+    use super::*;
+
+    #[test]
+    fn test_call_arguments() {
+        let function = SRARunsFunction::default();
+
+        assert!(function
+            .call(&[lit("PRJNA257197"), lit("SRR000001")])
+            .is_ok());
+
+        assert!(function.call(&[]).is_err());
+        assert!(function.call(&[lit("PRJNA257197&result=study")]).is_err());
+        assert!(function.call(&[col("accession")]).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::datatypes::SchemaRef;
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::TableProvider,
+    error::{DataFusionError, Result},
+    execution::{runtime_env::RuntimeEnv, SessionState, SessionStateBuilder},
+    logical_expr::{TableProviderFilterPushDown, TableType},
+    physical_plan::ExecutionPlan,
+    prelude::Expr,
+};
+use object_store::ObjectStore;
+use url::Url;
+
+use crate::{
+    config::ExonConfigExtension,
+    physical_plan::table_object_store_exec::{table_runtime_env, TableObjectStoreExec},
+    CachingObjectStore, ChecksumObjectStore,
+};
+
+/// The object store a table reads its files through, set from the session when the table is
+/// created.
+///
+/// The store wraps the store registered for the table's URL, so the settings of one table don't
+/// change how other tables on the same host are read.
+#[derive(Debug, Clone)]
+pub(crate) struct TableObjectStore {
+    /// The URL of the table.
+    url: Url,
+
+    /// The table's store.
+    object_store: Arc<dyn ObjectStore>,
+}
+
+impl TableObjectStore {
+    /// The table's store, `None` if it reads through the registered store.
+    ///
+    /// Remote files are read through the cache if a cache directory is set, and checksums are
+    /// verified on what's read, cached or not.
+    pub(crate) fn try_new(
+        exon_config: &ExonConfigExtension,
+        runtime_env: &RuntimeEnv,
+        url: &Url,
+    ) -> Result<Option<Self>> {
+        // Local files are already local, so only remote stores read through the cache.
+        let object_cache = exon_config
+            .object_cache()
+            .filter(|_| url.scheme() != "file");
+
+        if object_cache.is_none() && !exon_config.verify_checksums {
+            return Ok(None);
+        }
+
+        let mut object_store = runtime_env.object_store_registry.get_store(url)?;
+
+        if let Some(cache) = object_cache {
+            object_store = Arc::new(CachingObjectStore::new(object_store, cache, url));
+        }
+
+        if exon_config.verify_checksums {
+            object_store = Arc::new(
+                ChecksumObjectStore::new(object_store)
+                    .with_e_tag(exon_config.verify_checksums_with_etag),
+            );
+        }
+
+        Ok(Some(Self {
+            url: url.clone(),
+            object_store,
+        }))
+    }
+
+    /// A copy of the session state whose runtime reads the table's files through its store.
+    pub(crate) fn session_state(&self, state: &dyn Session) -> Result<SessionState> {
+        let session_state = state
+            .as_any()
+            .downcast_ref::<SessionState>()
+            .ok_or_else(|| {
+                DataFusionError::Internal("Expected the session to be a SessionState".to_string())
+            })?;
+
+        let runtime_env = table_runtime_env(
+            state.runtime_env(),
+            &self.url,
+            Arc::clone(&self.object_store),
+        );
+
+        let session_state = SessionStateBuilder::new_from_existing(session_state.clone())
+            .with_runtime_env(Arc::new(runtime_env))
+            .build();
+
+        Ok(session_state)
+    }
+}
+
+/// A table that reads its files through its own object store, e.g. one that verifies checksums or
+/// reads through the cache, instead of the store registered for its URL.
+#[derive(Debug)]
+pub struct TableObjectStoreTable {
+    /// The table that's read
+    inner: Arc<dyn TableProvider>,
+
+    /// The table's store settings
+    object_store: TableObjectStore,
+}
+
+impl TableObjectStoreTable {
+    /// Create a table that reads the inner table's files through the table's store.
+    pub(crate) fn new(inner: Arc<dyn TableProvider>, object_store: TableObjectStore) -> Self {
+        Self {
+            inner,
+            object_store,
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for TableObjectStoreTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+
+    fn table_type(&self) -> TableType {
+        self.inner.table_type()
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        self.inner.supports_filters_pushdown(filters)
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let session_state = self.object_store.session_state(state)?;
+
+        let input = self
+            .inner
+            .scan(&session_state, projection, filters, limit)
+            .await?;
+
+        Ok(Arc::new(TableObjectStoreExec::new(
+            input,
+            self.object_store.url.clone(),
+            Arc::clone(&self.object_store.object_store),
+        )))
+    }
+}
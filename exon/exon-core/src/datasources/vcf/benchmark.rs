@@ -0,0 +1,190 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::{any::Any, fmt::Debug, sync::Arc};
+
+use arrow::datatypes::SchemaRef;
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::{function::TableFunctionImpl, TableProvider, TableType},
+    error::{DataFusionError, Result},
+    execution::context::SessionContext,
+    logical_expr::{Expr, LogicalPlan},
+    physical_plan::{expressions::col, projection::ProjectionExec, ExecutionPlan},
+    scalar::ScalarValue,
+};
+
+use crate::physical_plan::variant_benchmark_exec::{
+    variant_benchmark_schema, VariantBenchmarkExec,
+};
+
+/// A table of the precision and recall of a query callset against a truth set, for each stratum
+/// and class of variant.
+#[derive(Debug)]
+pub struct VariantBenchmarkTable {
+    inputs: Vec<LogicalPlan>,
+    schema: SchemaRef,
+}
+
+impl VariantBenchmarkTable {
+    /// Create a new benchmark of the output of `query` against the output of `truth`, which must
+    /// have the chrom, pos, ref, and alt columns of a VCF table.
+    ///
+    /// The optional `strata` are intervals like a BED table, the first three columns are the
+    /// reference, start, and end of half-open intervals, and the fourth column, if any, names
+    /// the stratum of each interval.
+    pub fn try_new(
+        truth: LogicalPlan,
+        query: LogicalPlan,
+        strata: Option<LogicalPlan>,
+    ) -> Result<Self> {
+        for input in [&truth, &query] {
+            let input_schema = input.schema();
+
+            for name in ["chrom", "pos", "ref", "alt"] {
+                if !input_schema.has_column_with_unqualified_name(name) {
+                    return Err(DataFusionError::Plan(format!(
+                        "Benchmarking variants requires VCF tables with a {} column",
+                        name
+                    )));
+                }
+            }
+        }
+
+        if let Some(strata) = &strata {
+            if strata.schema().fields().len() < 3 {
+                return Err(DataFusionError::Plan(
+                    "Benchmarking variants requires strata with reference, start, and end columns"
+                        .to_string(),
+                ));
+            }
+        }
+
+        let inputs = [truth, query].into_iter().chain(strata).collect();
+
+        Ok(Self {
+            inputs,
+            schema: variant_benchmark_schema(),
+        })
+    }
+}
+
+#[async_trait]
+impl TableProvider for VariantBenchmarkTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let mut inputs = Vec::with_capacity(self.inputs.len());
+        for input in self.inputs.iter() {
+            inputs.push(state.create_physical_plan(input).await?);
+        }
+
+        let exec = Arc::new(VariantBenchmarkExec::try_new(inputs)?);
+
+        let Some(projection) = projection else {
+            return Ok(exec);
+        };
+
+        let exprs = projection
+            .iter()
+            .map(|i| {
+                let name = self.schema.field(*i).name();
+                Ok((col(name, &self.schema)?, name.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(ProjectionExec::try_new(exprs, exec)?))
+    }
+}
+
+/// Get the logical plan of a table name or subquery argument of a table function.
+pub(super) fn table_argument(ctx: &SessionContext, expr: &Expr) -> Result<LogicalPlan> {
+    let table_name = match expr {
+        Expr::ScalarSubquery(subquery) => return Ok(subquery.subquery.as_ref().clone()),
+        Expr::Literal(ScalarValue::Utf8(Some(table_name))) => table_name.clone(),
+        Expr::Column(column) => column.flat_name(),
+        _ => {
+            return Err(DataFusionError::Plan(format!(
+                "this function requires table name or subquery arguments, got {}",
+                expr
+            )))
+        }
+    };
+
+    let df = futures::executor::block_on(ctx.table(table_name.as_str()))?;
+
+    Ok(df.into_unoptimized_plan())
+}
+
+/// A table function that benchmarks a query callset against a truth set, e.g.
+/// `benchmark_variants('truth', 'query', 'strata')`.
+///
+/// The arguments are table names or subqueries, the strata are optional.
+pub struct BenchmarkVariantsFunction {
+    ctx: SessionContext,
+}
+
+impl Debug for BenchmarkVariantsFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BenchmarkVariantsFunction").finish()
+    }
+}
+
+impl BenchmarkVariantsFunction {
+    /// Create a new `BenchmarkVariantsFunction`.
+    pub fn new(ctx: SessionContext) -> Self {
+        Self { ctx }
+    }
+}
+
+impl TableFunctionImpl for BenchmarkVariantsFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let (truth, query, strata) = match exprs {
+            [truth, query] => (truth, query, None),
+            [truth, query, strata] => (truth, query, Some(strata)),
+            _ => {
+                return Err(DataFusionError::Plan(
+                    "this function requires truth and query arguments, and optionally strata"
+                        .to_string(),
+                ))
+            }
+        };
+
+        let strata = strata
+            .map(|strata| table_argument(&self.ctx, strata))
+            .transpose()?;
+
+        Ok(Arc::new(VariantBenchmarkTable::try_new(
+            table_argument(&self.ctx, truth)?,
+            table_argument(&self.ctx, query)?,
+            strata,
+        )?))
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, fmt::Debug, sync::Arc};
+
+use arrow::datatypes::SchemaRef;
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::{function::TableFunctionImpl, TableProvider, TableType},
+    error::{DataFusionError, Result},
+    execution::context::SessionContext,
+    logical_expr::{Expr, LogicalPlan},
+    physical_plan::{expressions::col, projection::ProjectionExec, ExecutionPlan},
+    scalar::ScalarValue,
+};
+
+use crate::physical_plan::resolve_breakends_exec::{
+    breakend_junction_schema, ResolveBreakendsExec,
+};
+
+/// A table of the junctions of the breakend records of a VCF table.
+#[derive(Debug)]
+pub struct BreakendTable {
+    input: LogicalPlan,
+    schema: SchemaRef,
+}
+
+impl BreakendTable {
+    /// Create a new breakend table over the output of `input`, which must have the chrom, pos,
+    /// and alt columns of a VCF table.
+    pub fn try_new(input: LogicalPlan) -> Result<Self> {
+        let input_schema = input.schema();
+
+        for name in ["chrom", "pos", "alt"] {
+            if !input_schema.has_column_with_unqualified_name(name) {
+                return Err(DataFusionError::Plan(format!(
+                    "Resolving breakends requires a VCF table with a {} column",
+                    name
+                )));
+            }
+        }
+
+        Ok(Self {
+            input,
+            schema: breakend_junction_schema(),
+        })
+    }
+}
+
+#[async_trait]
+impl TableProvider for BreakendTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let input = state.create_physical_plan(&self.input).await?;
+        let exec = Arc::new(ResolveBreakendsExec::try_new(input)?);
+
+        let Some(projection) = projection else {
+            return Ok(exec);
+        };
+
+        let exprs = projection
+            .iter()
+            .map(|i| {
+                let name = self.schema.field(*i).name();
+                Ok((col(name, &self.schema)?, name.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(ProjectionExec::try_new(exprs, exec)?))
+    }
+}
+
+/// A table function that pairs the breakend records of a VCF table into junctions.
+///
+/// The argument is either a table name or a subquery, e.g. `(SELECT * FROM vcf_table)`.
+pub struct ResolveBreakendsFunction {
+    ctx: SessionContext,
+}
+
+impl Debug for ResolveBreakendsFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResolveBreakendsFunction").finish()
+    }
+}
+
+impl ResolveBreakendsFunction {
+    /// Create a new `ResolveBreakendsFunction`.
+    pub fn new(ctx: SessionContext) -> Self {
+        Self { ctx }
+    }
+}
+
+impl TableFunctionImpl for ResolveBreakendsFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let table_name = match exprs {
+            [Expr::ScalarSubquery(subquery)] => {
+                let input = subquery.subquery.as_ref().clone();
+                return Ok(Arc::new(BreakendTable::try_new(input)?));
+            }
+            [Expr::Literal(ScalarValue::Utf8(Some(table_name)))] => table_name.clone(),
+            [Expr::Column(column)] => column.flat_name(),
+            _ => {
+                return Err(DataFusionError::Plan(
+                    "this function requires a table name or subquery as its only argument"
+                        .to_string(),
+                ))
+            }
+        };
+
+        let df = futures::executor::block_on(self.ctx.table(table_name.as_str()))?;
+
+        Ok(Arc::new(BreakendTable::try_new(
+            df.into_unoptimized_plan(),
+        )?))
+    }
+}
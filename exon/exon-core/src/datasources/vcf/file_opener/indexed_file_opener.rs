@@ -18,6 +18,7 @@ use datafusion::{
     datasource::physical_plan::{FileMeta, FileOpenFuture, FileOpener},
     error::DataFusionError,
 };
+use exon_common::BoundedReader;
 use exon_vcf::{IndexedAsyncBatchStream, VCFConfig};
 use futures::{StreamExt, TryStreamExt};
 use noodles::{
@@ -32,6 +33,8 @@ use crate::{
     streaming_bgzf::AsyncBGZFReader,
 };
 
+use super::unindex_file_opener::read_header;
+
 /// A file opener for VCF files.
 #[derive(Debug)]
 pub struct IndexedVCFOpener {
@@ -68,12 +71,11 @@ impl FileOpener for IndexedVCFOpener {
 
             let first_bgzf_reader = bgzf::AsyncReader::new(stream_reader);
 
-            let mut vcf_reader = noodles::vcf::AsyncReader::new(first_bgzf_reader);
-
             // We save this header for later to pass to the batch reader for record deserialization.
-            let header = vcf_reader.read_header().await?;
+            let (vcf_reader, header) =
+                read_header(first_bgzf_reader, &config.reader_limits).await?;
 
-            let header_offset = vcf_reader.get_ref().virtual_position();
+            let header_offset = vcf_reader.get_ref().get_ref().virtual_position();
 
             let batch_stream = match file_meta.extensions {
                 Some(ref ext) => {
@@ -105,7 +107,10 @@ impl FileOpener for IndexedVCFOpener {
 
                         let bgzf_reader = async_reader.into_inner();
 
-                        let vcf_reader = noodles::vcf::AsyncReader::new(bgzf_reader);
+                        let vcf_reader = noodles::vcf::AsyncReader::new(BoundedReader::new(
+                            bgzf_reader,
+                            &config.reader_limits,
+                        ));
 
                         IndexedAsyncBatchStream::new(vcf_reader, config, Arc::new(header), region)
                     } else {
@@ -163,7 +168,10 @@ impl FileOpener for IndexedVCFOpener {
 
                         let bgzf_reader = async_reader.into_inner();
 
-                        let vcf_reader = noodles::vcf::AsyncReader::new(bgzf_reader);
+                        let vcf_reader = noodles::vcf::AsyncReader::new(BoundedReader::new(
+                            bgzf_reader,
+                            &config.reader_limits,
+                        ));
 
                         let mut batch_stream = IndexedAsyncBatchStream::new(
                             vcf_reader,
@@ -193,16 +201,18 @@ impl FileOpener for IndexedVCFOpener {
                     let mut async_reader = AsyncBGZFReader::from_reader(stream_reader);
 
                     // If we're at the start of the file, we need to seek to the header offset.
-                    if vcf_reader.get_ref().virtual_position().compressed() == 0
-                        && vcf_reader.get_ref().virtual_position().uncompressed() == 0
-                    {
+                    let position = vcf_reader.get_ref().get_ref().virtual_position();
+                    if position.compressed() == 0 && position.uncompressed() == 0 {
                         tracing::debug!("Seeking to header offset: {:?}", header_offset);
                         async_reader.scan_to_virtual_position(header_offset).await?;
                     }
 
                     let bgzf_reader = async_reader.into_inner();
 
-                    let vcf_reader = noodles::vcf::AsyncReader::new(bgzf_reader);
+                    let vcf_reader = noodles::vcf::AsyncReader::new(BoundedReader::new(
+                        bgzf_reader,
+                        &config.reader_limits,
+                    ));
 
                     IndexedAsyncBatchStream::new(vcf_reader, config, Arc::new(header), region)
                 }
@@ -21,11 +21,15 @@ use datafusion::{
     },
     error::DataFusionError,
 };
+use exon_common::{BoundedReader, ReaderLimit, ReaderLimits};
 use exon_vcf::{AsyncBatchStream, VCFConfig};
 use futures::{StreamExt, TryStreamExt};
 use noodles::bgzf::{self};
+use tokio::io::AsyncBufRead;
 use tokio_util::io::StreamReader;
 
+use crate::physical_plan::object_store::file_range_get_options;
+
 /// A file opener for VCF files.
 #[derive(Debug)]
 pub struct VCFOpener {
@@ -45,6 +49,28 @@ impl VCFOpener {
     }
 }
 
+/// Read the VCF header, failing once it grows past the header size limit.
+pub(super) async fn read_header<R>(
+    reader: R,
+    limits: &ReaderLimits,
+) -> std::io::Result<(
+    noodles::vcf::AsyncReader<BoundedReader<R>>,
+    noodles::vcf::Header,
+)>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut reader = BoundedReader::new(reader, limits);
+    reader.start_budget(ReaderLimit::HeaderSize, limits.max_header_size);
+
+    let mut vcf_reader = noodles::vcf::AsyncReader::new(reader);
+    let header = vcf_reader.read_header().await?;
+
+    vcf_reader.get_mut().end_budget();
+
+    Ok((vcf_reader, header))
+}
+
 impl FileOpener for VCFOpener {
     fn open(&self, file_meta: FileMeta) -> datafusion::error::Result<FileOpenFuture> {
         let config = Arc::clone(&self.config);
@@ -64,9 +90,8 @@ impl FileOpener for VCFOpener {
                 let stream_reader = StreamReader::new(stream_reader);
 
                 let bgzf_reader = bgzf::AsyncReader::new(stream_reader);
-                let mut vcf_reader = noodles::vcf::AsyncReader::new(bgzf_reader);
+                let (vcf_reader, header) = read_header(bgzf_reader, &config.reader_limits).await?;
 
-                let header = vcf_reader.read_header().await?;
                 let batch_stream = AsyncBatchStream::new(vcf_reader, config, Arc::new(header));
 
                 Ok(batch_stream.into_stream().boxed())
@@ -78,8 +103,33 @@ impl FileOpener for VCFOpener {
                 let stream_reader = Box::pin(s.map_err(DataFusionError::from));
                 let stream_reader = StreamReader::new(stream_reader);
 
-                let mut vcf_reader = noodles::vcf::AsyncReader::new(stream_reader);
-                let header = vcf_reader.read_header().await?;
+                let (vcf_reader, header) =
+                    read_header(stream_reader, &config.reader_limits).await?;
+
+                // A range starts on a record boundary after the header, so the records are read
+                // from a second request.
+                if file_meta
+                    .range
+                    .as_ref()
+                    .is_some_and(|range| range.start > 0)
+                {
+                    let s = config
+                        .object_store
+                        .get_opts(file_meta.location(), file_range_get_options(&file_meta))
+                        .await?
+                        .into_stream();
+
+                    let stream_reader = Box::pin(s.map_err(DataFusionError::from));
+                    let stream_reader = StreamReader::new(stream_reader);
+
+                    let vcf_reader = noodles::vcf::AsyncReader::new(BoundedReader::new(
+                        stream_reader,
+                        &config.reader_limits,
+                    ));
+                    let batch_stream = AsyncBatchStream::new(vcf_reader, config, Arc::new(header));
+
+                    return Ok(batch_stream.into_stream().boxed());
+                }
 
                 let batch_stream = AsyncBatchStream::new(vcf_reader, config, Arc::new(header));
 
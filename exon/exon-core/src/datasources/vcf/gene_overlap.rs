@@ -0,0 +1,149 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::{any::Any, fmt::Debug, sync::Arc};
+
+use arrow::datatypes::SchemaRef;
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::{function::TableFunctionImpl, TableProvider, TableType},
+    error::{DataFusionError, Result},
+    execution::context::SessionContext,
+    logical_expr::{Expr, LogicalPlan},
+    physical_plan::{expressions::col, projection::ProjectionExec, ExecutionPlan},
+};
+
+use super::benchmark::table_argument;
+use crate::physical_plan::gene_overlap_exec::{gene_overlap_schema, GeneOverlapExec};
+
+/// A table of the rows of a VCF table annotated with the genes of a GTF table they overlap.
+#[derive(Debug)]
+pub struct GeneOverlapTable {
+    variants: LogicalPlan,
+    genes: LogicalPlan,
+    schema: SchemaRef,
+}
+
+impl GeneOverlapTable {
+    /// Create a new table annotating the output of `variants`, which must have the chrom and pos
+    /// columns of a VCF table, with the genes of `genes`, which must have the seqname, type,
+    /// start, end, and attributes columns of a GTF table.
+    pub fn try_new(variants: LogicalPlan, genes: LogicalPlan) -> Result<Self> {
+        let variant_schema = variants.schema();
+
+        for name in ["chrom", "pos"] {
+            if !variant_schema.has_column_with_unqualified_name(name) {
+                return Err(DataFusionError::Plan(format!(
+                    "Gene overlap requires a VCF table with a {} column",
+                    name
+                )));
+            }
+        }
+
+        for name in ["seqname", "type", "start", "end", "attributes"] {
+            if !genes.schema().has_column_with_unqualified_name(name) {
+                return Err(DataFusionError::Plan(format!(
+                    "Gene overlap requires a GTF table with a {} column",
+                    name
+                )));
+            }
+        }
+
+        let schema = gene_overlap_schema(variant_schema.as_arrow());
+
+        Ok(Self {
+            variants,
+            genes,
+            schema,
+        })
+    }
+}
+
+#[async_trait]
+impl TableProvider for GeneOverlapTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let variants = state.create_physical_plan(&self.variants).await?;
+        let genes = state.create_physical_plan(&self.genes).await?;
+
+        let exec = Arc::new(GeneOverlapExec::try_new(variants, genes)?);
+
+        let Some(projection) = projection else {
+            return Ok(exec);
+        };
+
+        let exprs = projection
+            .iter()
+            .map(|i| {
+                let name = self.schema.field(*i).name();
+                Ok((col(name, &self.schema)?, name.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(ProjectionExec::try_new(exprs, exec)?))
+    }
+}
+
+/// A table function that annotates variants with their overlapping genes, e.g.
+/// `variant_gene_overlap('vcf_table', 'gtf_table')`.
+///
+/// The arguments are table names or subqueries.
+pub struct VariantGeneOverlapFunction {
+    ctx: SessionContext,
+}
+
+impl Debug for VariantGeneOverlapFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VariantGeneOverlapFunction").finish()
+    }
+}
+
+impl VariantGeneOverlapFunction {
+    /// Create a new `VariantGeneOverlapFunction`.
+    pub fn new(ctx: SessionContext) -> Self {
+        Self { ctx }
+    }
+}
+
+impl TableFunctionImpl for VariantGeneOverlapFunction {
+    fn call(&self, exprs: &[Expr]) -> Result<Arc<dyn TableProvider>> {
+        let [variants, genes] = exprs else {
+            return Err(DataFusionError::Plan(
+                "this function requires variants and genes arguments".to_string(),
+            ));
+        };
+
+        Ok(Arc::new(GeneOverlapTable::try_new(
+            table_argument(&self.ctx, variants)?,
+            table_argument(&self.ctx, genes)?,
+        )?))
+    }
+}
@@ -27,7 +27,8 @@ use datafusion::{
 use exon_vcf::VCFConfig;
 use noodles::core::Region;
 
-use crate::datasources::ExonFileScanConfig;
+use crate::config::reader_limits;
+use crate::datasources::{ExonFileScanConfig, InstrumentedFileOpener};
 
 use super::file_opener::indexed_file_opener::IndexedVCFOpener;
 
@@ -51,6 +52,9 @@ pub struct IndexedVCFScanner {
 
     /// The statistics for the scan.
     statistics: Statistics,
+
+    /// The samples to decode the FORMAT values of, or all samples if `None`.
+    samples: Option<Vec<String>>,
 }
 
 impl IndexedVCFScanner {
@@ -65,13 +69,25 @@ impl IndexedVCFScanner {
             region: Arc::clone(&region),
             properties,
             statistics,
+            samples: None,
         })
     }
 
+    /// Only decode the FORMAT values of these samples.
+    pub fn with_samples(mut self, samples: Option<Vec<String>>) -> Self {
+        self.samples = samples;
+        self
+    }
+
     /// Return the base configuration for the scan.
     pub fn base_config(&self) -> &FileScanConfig {
         &self.base_config
     }
+
+    /// Return the region the scan is filtered to.
+    pub fn region(&self) -> &Region {
+        &self.region
+    }
 }
 
 impl DisplayAs for IndexedVCFScanner {
@@ -152,11 +168,18 @@ impl ExecutionPlan for IndexedVCFScanner {
         let file_schema = Arc::clone(&self.base_config.file_schema);
         let config = VCFConfig::new(object_store, file_schema)
             .with_batch_size(batch_size)
-            .with_projection(self.base_config().file_projection());
+            .with_projection(self.base_config().file_projection())
+            .with_reader_limits(reader_limits(context.session_config()))
+            .with_samples(self.samples.clone());
 
         let opener = IndexedVCFOpener::new(Arc::new(config), Arc::clone(&self.region));
 
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener).with_region(&self.region),
+            &self.metrics,
+        )?;
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
 }
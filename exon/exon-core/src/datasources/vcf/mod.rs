@@ -16,14 +16,21 @@
 //!
 //! This module provides functionality for working with VCF files as a data source.
 
+mod benchmark;
+mod breakends;
 mod file_opener;
+mod gene_overlap;
 mod indexed_scanner;
 mod scanner;
 mod schema_builder;
 mod table_provider;
 
+pub use self::benchmark::{BenchmarkVariantsFunction, VariantBenchmarkTable};
+pub use self::breakends::{BreakendTable, ResolveBreakendsFunction};
+pub use self::gene_overlap::{GeneOverlapTable, VariantGeneOverlapFunction};
 pub use self::indexed_scanner::IndexedVCFScanner;
 pub use self::scanner::VCFScan;
+pub(crate) use self::schema_builder::vcf_header_builder_from_schema;
 pub use self::schema_builder::VCFSchemaBuilder;
 pub use self::table_provider::ListingVCFTable;
 pub use self::table_provider::ListingVCFTableOptions;
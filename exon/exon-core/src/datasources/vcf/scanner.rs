@@ -27,9 +27,11 @@ use datafusion::{
         PlanProperties, SendableRecordBatchStream,
     },
 };
+use exon_common::IdentifierFilter;
 use exon_vcf::VCFConfig;
 
-use crate::datasources::{vcf::VCFOpener, ExonFileScanConfig};
+use crate::config::reader_limits;
+use crate::datasources::{vcf::VCFOpener, ExonFileScanConfig, InstrumentedFileOpener};
 
 #[derive(Debug, Clone)]
 /// Implements a datafusion `ExecutionPlan` for VCF files.
@@ -51,6 +53,12 @@ pub struct VCFScan {
 
     /// The statistics for the scan.
     statistics: Statistics,
+
+    /// A prefilter on the IDs of the records to read.
+    identifier_filter: Option<IdentifierFilter>,
+
+    /// The samples to decode the FORMAT values of, or all samples if `None`.
+    samples: Option<Vec<String>>,
 }
 
 impl VCFScan {
@@ -68,9 +76,23 @@ impl VCFScan {
             metrics: ExecutionPlanMetricsSet::new(),
             properties,
             statistics,
+            identifier_filter: None,
+            samples: None,
         })
     }
 
+    /// Only decode the FORMAT values of these samples.
+    pub fn with_samples(mut self, samples: Option<Vec<String>>) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Skip the records that don't match an identifier filter.
+    pub fn with_identifier_filter(mut self, identifier_filter: IdentifierFilter) -> Self {
+        self.identifier_filter = Some(identifier_filter);
+        self
+    }
+
     /// Return the base configuration for the scan.
     pub fn base_config(&self) -> &FileScanConfig {
         &self.base_config
@@ -153,10 +175,18 @@ impl ExecutionPlan for VCFScan {
         let file_schema = Arc::clone(&self.base_config.file_schema);
         let config = VCFConfig::new(object_store, file_schema)
             .with_batch_size(batch_size)
-            .with_projection(self.base_config().file_projection());
+            .with_projection(self.base_config().file_projection())
+            .with_reader_limits(reader_limits(context.session_config()))
+            .with_identifier_filter(self.identifier_filter.clone())
+            .with_samples(self.samples.clone());
 
         let opener = VCFOpener::new(Arc::new(config), self.file_compression_type);
-        let stream = FileStream::new(&self.base_config, partition, opener, &self.metrics)?;
+        let stream = FileStream::new(
+            &self.base_config,
+            partition,
+            InstrumentedFileOpener::new(opener),
+            &self.metrics,
+        )?;
 
         Ok(Box::pin(stream) as SendableRecordBatchStream)
     }
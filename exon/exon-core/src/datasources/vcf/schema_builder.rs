@@ -14,23 +14,28 @@
 
 use std::sync::Arc;
 
-use arrow::datatypes::{Field, Fields, Schema};
-use datafusion::error::Result;
+use arrow::datatypes::{DataType, Field, Fields, Schema};
+use datafusion::error::{DataFusionError, Result};
 use noodles::vcf::{
     header::{
-        record::value::map::{
-            format::Number as FormatNumber,
-            format::Type as FormatType,
-            info::{Number as InfoNumber, Type as InfoType},
+        record::value::{
+            map::{
+                format::Number as FormatNumber,
+                format::Type as FormatType,
+                info::{Number as InfoNumber, Type as InfoType},
+                Format, Info,
+            },
+            Collection, Map,
         },
-        Formats, Infos,
+        Builder, Formats, Infos,
     },
     Header,
 };
 
 // noodles_vcf::header::record::value::map::Typed
 
-use exon_common::TableSchema;
+use exon_common::{infer_genome_build, TableSchema};
+use exon_vcf::structural_variant_fields;
 
 /// A builder for an arrow schema from a VCF header.
 pub struct VCFSchemaBuilder {
@@ -48,6 +53,12 @@ pub struct VCFSchemaBuilder {
 
     /// Whether to parse the FORMAT field.
     parse_formats: bool,
+
+    /// Whether to add the typed structural variant columns.
+    parse_structural_variants: bool,
+
+    /// Whether to dictionary encode the genotypes and filters.
+    dictionary_encode_genotypes: bool,
 }
 
 impl VCFSchemaBuilder {
@@ -69,6 +80,18 @@ impl VCFSchemaBuilder {
         self
     }
 
+    /// Set the parse_structural_variants flag.
+    pub fn with_parse_structural_variants(mut self, parse_structural_variants: bool) -> Self {
+        self.parse_structural_variants = parse_structural_variants;
+        self
+    }
+
+    /// Set the dictionary_encode_genotypes flag.
+    pub fn with_dictionary_encode_genotypes(mut self, dictionary_encode_genotypes: bool) -> Self {
+        self.dictionary_encode_genotypes = dictionary_encode_genotypes;
+        self
+    }
+
     /// Add a partition field to the schema builder.
     pub fn with_partition_field(mut self, field: arrow::datatypes::Field) -> Self {
         self.partition_fields.push(field);
@@ -123,6 +146,8 @@ impl Default for VCFSchemaBuilder {
             partition_fields: Vec::new(),
             parse_info: false,
             parse_formats: false,
+            parse_structural_variants: false,
+            dictionary_encode_genotypes: false,
             header: None,
         }
     }
@@ -138,6 +163,15 @@ impl VCFSchemaBuilder {
 
     /// Builds the schema.
     pub fn build(&mut self) -> Result<TableSchema> {
+        // The structural variant columns follow the VCF columns, before any partition fields
+        if self.parse_structural_variants {
+            self.fields.extend(structural_variant_fields());
+        }
+
+        if self.dictionary_encode_genotypes {
+            self.fields[6] = dictionary_encode_field(&self.fields[6]);
+        }
+
         // If both parse_info and parse_formats are false, then we can just return the default schema
         if !self.parse_info && !self.parse_formats {
             let file_field_partition = self
@@ -151,7 +185,8 @@ impl VCFSchemaBuilder {
             self.fields.extend(self.partition_fields.clone());
 
             let table_schema = Arc::new(Schema::new(self.fields.clone()));
-            let table_schema = TableSchema::new(table_schema, file_field_partition);
+            let table_schema = TableSchema::new(table_schema, file_field_partition)
+                .with_genome_build(self.header.as_ref().and_then(genome_build_from_header));
 
             return Ok(table_schema);
         }
@@ -173,7 +208,12 @@ impl VCFSchemaBuilder {
         }
 
         if self.parse_formats {
-            let format_field = vcf_formats_to_field(header.formats().clone());
+            let mut format_field = vcf_formats_to_field(header.formats().clone());
+
+            if self.dictionary_encode_genotypes {
+                format_field = dictionary_encode_genotype_field(&format_field);
+            }
+
             self.fields[8] = format_field;
         }
 
@@ -188,12 +228,32 @@ impl VCFSchemaBuilder {
         self.fields.extend(self.partition_fields.clone());
 
         let schema = arrow::datatypes::Schema::new(self.fields.clone());
-        let table_schema = TableSchema::new(Arc::new(schema), file_field_projection);
+        let table_schema = TableSchema::new(Arc::new(schema), file_field_projection)
+            .with_genome_build(genome_build_from_header(header));
 
         Ok(table_schema)
     }
 }
 
+/// The genome build of the VCF, from the `##reference` line or the `assembly` of the contigs.
+fn genome_build_from_header(header: &Header) -> Option<&'static str> {
+    let references = match header.other_records().get("reference") {
+        Some(Collection::Unstructured(values)) => values.iter().map(String::as_str).collect(),
+        _ => Vec::new(),
+    };
+
+    let assemblies = header
+        .contigs()
+        .values()
+        .filter_map(|contig| contig.other_fields().get("assembly"))
+        .map(String::as_str);
+
+    references
+        .into_iter()
+        .chain(assemblies)
+        .find_map(infer_genome_build)
+}
+
 fn vcf_info_type_to_arrow_type(ty: InfoType) -> arrow::datatypes::DataType {
     match ty {
         InfoType::Integer => arrow::datatypes::DataType::Int32,
@@ -288,6 +348,344 @@ fn vcf_formats_to_field(formats: Formats) -> arrow::datatypes::Field {
     )
 }
 
+/// Dictionary encode the strings of a field, or the items of a list of strings.
+fn dictionary_encode_field(field: &Field) -> Field {
+    let dictionary_type = DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8));
+
+    match field.data_type() {
+        DataType::Utf8 => field.clone().with_data_type(dictionary_type),
+        DataType::List(item) if item.data_type() == &DataType::Utf8 => {
+            let item = item.as_ref().clone().with_data_type(dictionary_type);
+            field.clone().with_data_type(DataType::List(Arc::new(item)))
+        }
+        _ => field.clone(),
+    }
+}
+
+/// Dictionary encode the GT field of the formats, which has few distinct values in a cohort.
+fn dictionary_encode_genotype_field(formats_field: &Field) -> Field {
+    let DataType::List(item) = formats_field.data_type() else {
+        return formats_field.clone();
+    };
+
+    let DataType::Struct(fields) = item.data_type() else {
+        return formats_field.clone();
+    };
+
+    let fields = fields
+        .iter()
+        .map(|f| match f.name().as_str() {
+            "GT" => dictionary_encode_field(f),
+            _ => f.as_ref().clone(),
+        })
+        .collect::<Fields>();
+
+    let item = item
+        .as_ref()
+        .clone()
+        .with_data_type(DataType::Struct(fields));
+
+    formats_field
+        .clone()
+        .with_data_type(DataType::List(Arc::new(item)))
+}
+
+/// Split a field's type into its scalar type and whether it's a list, the inverse of `wrap_type_in_count`.
+/// Dictionary encoded values have the scalar type of the dictionary's values.
+fn unwrap_list_type(data_type: &DataType) -> (&DataType, bool) {
+    let (data_type, is_list) = match data_type {
+        DataType::List(field) => (field.data_type(), true),
+        data_type => (data_type, false),
+    };
+
+    match data_type {
+        DataType::Dictionary(_, value_type) => (value_type.as_ref(), is_list),
+        data_type => (data_type, is_list),
+    }
+}
+
+fn field_to_vcf_info(field: &Field) -> Result<Map<Info>> {
+    let (data_type, is_list) = unwrap_list_type(field.data_type());
+
+    let ty = match data_type {
+        DataType::Boolean if !is_list => {
+            return Ok(Map::<Info>::new(InfoNumber::Count(0), InfoType::Flag, ""))
+        }
+        DataType::Int32 => InfoType::Integer,
+        DataType::Float32 => InfoType::Float,
+        DataType::Utf8 => InfoType::String,
+        data_type => {
+            return Err(DataFusionError::Plan(format!(
+                "Unsupported type for INFO field {}: {}",
+                field.name(),
+                data_type
+            )))
+        }
+    };
+
+    let number = if is_list {
+        InfoNumber::Unknown
+    } else {
+        InfoNumber::Count(1)
+    };
+
+    Ok(Map::<Info>::new(number, ty, ""))
+}
+
+fn field_to_vcf_format(field: &Field) -> Result<Map<Format>> {
+    let (data_type, is_list) = unwrap_list_type(field.data_type());
+
+    let ty = match data_type {
+        DataType::Int32 => FormatType::Integer,
+        DataType::Float32 => FormatType::Float,
+        DataType::Utf8 => FormatType::String,
+        data_type => {
+            return Err(DataFusionError::Plan(format!(
+                "Unsupported type for FORMAT field {}: {}",
+                field.name(),
+                data_type
+            )))
+        }
+    };
+
+    let number = if is_list {
+        FormatNumber::Unknown
+    } else {
+        FormatNumber::Count(1)
+    };
+
+    Ok(Map::<Format>::new(number, ty, ""))
+}
+
+/// Create a header builder with the INFO and FORMAT records described by the `info` and
+/// `formats` fields of a schema, i.e. the inverse of the schema built with parsed info and formats.
+///
+/// Either field may be missing, but if present it must be parsed rather than a string.
+pub(crate) fn vcf_header_builder_from_schema(schema: &Schema) -> Result<Builder> {
+    let mut builder = Header::builder();
+
+    if let Ok(info_field) = schema.field_with_name("info") {
+        let DataType::Struct(fields) = info_field.data_type() else {
+            return Err(DataFusionError::Plan(
+                "The info column must be parsed, set exon.vcf_parse_info to true".to_string(),
+            ));
+        };
+
+        for field in fields {
+            builder = builder.add_info(field.name().as_str(), field_to_vcf_info(field)?);
+        }
+    }
+
+    if let Ok(formats_field) = schema.field_with_name("formats") {
+        let fields = match formats_field.data_type() {
+            DataType::List(item) => match item.data_type() {
+                DataType::Struct(fields) => fields,
+                _ => {
+                    return Err(DataFusionError::Plan(
+                        "The formats column must be a list of structs".to_string(),
+                    ))
+                }
+            },
+            _ => {
+                return Err(DataFusionError::Plan(
+                    "The formats column must be parsed, set exon.vcf_parse_formats to true"
+                        .to_string(),
+                ))
+            }
+        };
+
+        for field in fields {
+            builder = builder.add_format(field.name().as_str(), field_to_vcf_format(field)?);
+        }
+    }
+
+    Ok(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::datatypes::{DataType, Field};
+    use noodles::vcf::header::record::value::{
+        map::{format, info},
+        Map,
+    };
+
+    use super::{vcf_header_builder_from_schema, VCFSchemaBuilder};
+
+    #[test]
+    fn test_header_from_schema_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let header = noodles::vcf::Header::builder()
+            .add_info(
+                "DP",
+                Map::<info::Info>::new(info::Number::Count(1), info::Type::Integer, "Depth"),
+            )
+            .add_info(
+                "AF",
+                Map::<info::Info>::new(info::Number::AlternateBases, info::Type::Float, "AF"),
+            )
+            .add_info(
+                "DB",
+                Map::<info::Info>::new(info::Number::Count(0), info::Type::Flag, "dbSNP"),
+            )
+            .add_format(
+                "GT",
+                Map::<format::Format>::new(format::Number::Count(1), format::Type::String, "GT"),
+            )
+            .add_format(
+                "AD",
+                Map::<format::Format>::new(format::Number::Unknown, format::Type::Integer, "AD"),
+            )
+            .build();
+
+        let schema = VCFSchemaBuilder::default()
+            .with_header(header)
+            .with_parse_info(true)
+            .with_parse_formats(true)
+            .build()?
+            .file_schema()?;
+
+        let round_trip_header = vcf_header_builder_from_schema(&schema)?.build();
+
+        let round_trip_schema = VCFSchemaBuilder::default()
+            .with_header(round_trip_header)
+            .with_parse_info(true)
+            .with_parse_formats(true)
+            .build()?
+            .file_schema()?;
+
+        assert_eq!(schema, round_trip_schema);
+
+        let unparsed_schema = VCFSchemaBuilder::default().build()?.file_schema()?;
+        assert!(vcf_header_builder_from_schema(&unparsed_schema).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_structural_variant_columns() -> Result<(), Box<dyn std::error::Error>> {
+        let table_schema = VCFSchemaBuilder::default()
+            .with_parse_structural_variants(true)
+            .with_partition_field(Field::new("sample", DataType::Utf8, false))
+            .build()?;
+
+        let schema = table_schema.table_schema();
+        let names = schema
+            .fields()
+            .iter()
+            .skip(9)
+            .map(|f| f.name().as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            vec![
+                "end",
+                "svtype",
+                "svlen",
+                "cipos",
+                "ciend",
+                "mate_chrom",
+                "mate_pos",
+                "sample"
+            ]
+        );
+        assert_eq!(table_schema.file_schema()?.fields().len(), 16);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dictionary_encoded_genotypes() -> Result<(), Box<dyn std::error::Error>> {
+        let header = noodles::vcf::Header::builder()
+            .add_format(
+                "GT",
+                Map::<format::Format>::new(format::Number::Count(1), format::Type::String, "GT"),
+            )
+            .add_format(
+                "DP",
+                Map::<format::Format>::new(format::Number::Count(1), format::Type::Integer, "DP"),
+            )
+            .build();
+
+        let schema = VCFSchemaBuilder::default()
+            .with_header(header.clone())
+            .with_parse_info(true)
+            .with_parse_formats(true)
+            .with_dictionary_encode_genotypes(true)
+            .build()?
+            .file_schema()?;
+
+        let dictionary_type =
+            DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8));
+
+        let DataType::List(filter) = schema.field_with_name("filter")?.data_type() else {
+            return Err("filter should be a list".into());
+        };
+        assert_eq!(filter.data_type(), &dictionary_type);
+
+        let DataType::List(sample) = schema.field_with_name("formats")?.data_type() else {
+            return Err("formats should be a list".into());
+        };
+        let DataType::Struct(fields) = sample.data_type() else {
+            return Err("formats should be a list of structs".into());
+        };
+        assert_eq!(fields[0].data_type(), &dictionary_type);
+        assert_eq!(fields[1].data_type(), &DataType::Int32);
+
+        // The header written for a dictionary encoded schema is the same as for a plain one.
+        let plain_schema = VCFSchemaBuilder::default()
+            .with_header(header)
+            .with_parse_info(true)
+            .with_parse_formats(true)
+            .build()?
+            .file_schema()?;
+
+        let round_trip_schema = VCFSchemaBuilder::default()
+            .with_header(vcf_header_builder_from_schema(&schema)?.build())
+            .with_parse_info(true)
+            .with_parse_formats(true)
+            .build()?
+            .file_schema()?;
+
+        assert_eq!(plain_schema, round_trip_schema);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_genome_build_from_header() -> Result<(), Box<dyn std::error::Error>> {
+        let header: noodles::vcf::Header = concat!(
+            "##fileformat=VCFv4.3\n",
+            "##reference=file:///references/hs37d5.fa\n",
+            "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n",
+        )
+        .parse()?;
+
+        let table_schema = VCFSchemaBuilder::default().with_header(header).build()?;
+        assert_eq!(table_schema.genome_build(), Some("GRCh37"));
+
+        let header: noodles::vcf::Header = concat!(
+            "##fileformat=VCFv4.3\n",
+            "##contig=<ID=chr1,length=248956422,assembly=hg38>\n",
+            "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n",
+        )
+        .parse()?;
+
+        let table_schema = VCFSchemaBuilder::default()
+            .with_header(header)
+            .with_parse_info(true)
+            .build()?;
+        assert_eq!(table_schema.genome_build(), Some("GRCh38"));
+
+        let table_schema = VCFSchemaBuilder::default()
+            .with_header(noodles::vcf::Header::default())
+            .build()?;
+        assert_eq!(table_schema.genome_build(), None);
+
+        Ok(())
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use std::{str::FromStr, sync::Arc};
@@ -12,43 +12,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{any::Any, sync::Arc};
+use std::sync::Arc;
 
-use arrow::datatypes::{Field, SchemaRef};
+use arrow::datatypes::Field;
 use async_trait::async_trait;
 use datafusion::{
     catalog::Session,
     datasource::{
         file_format::file_compression_type::FileCompressionType, listing::ListingTableUrl,
-        physical_plan::FileScanConfig, TableProvider,
+        physical_plan::FileScanConfig,
     },
     error::{DataFusionError, Result},
-    logical_expr::{TableProviderFilterPushDown, TableType},
     physical_plan::ExecutionPlan,
-    prelude::Expr,
 };
-use exon_common::TableSchema;
-use futures::{StreamExt, TryStreamExt};
+use exon_common::{IdentifierFilter, TableSchema};
+use exon_vcf::SampleSelection;
+use futures::TryStreamExt;
 use noodles::{bgzf, core::Region, vcf};
 use object_store::{ObjectMeta, ObjectStore};
 use tokio_util::io::StreamReader;
 
-use crate::{
-    datasources::{
-        exon_listing_table_options::{
-            ExonIndexedListingOptions, ExonListingConfig, ExonListingOptions,
-        },
-        hive_partition::filter_matches_partition_cols,
-        indexed_file::indexed_bgzf_file::{
-            augment_partitioned_file_with_byte_range, IndexedBGZFFile,
-        },
-        ExonFileType,
-    },
-    error::Result as ExonResult,
-    physical_plan::{
-        file_scan_config_builder::FileScanConfigBuilder, infer_region,
-        object_store::pruned_partition_list,
-    },
+use crate::datasources::{
+    exon_listing_table::{ExonFileFormatOptions, ExonListingTable, IdentifierIndex, RegionIndex},
+    exon_listing_table_options::ExonListingOptions,
+    indexed_file::indexed_bgzf_file::IndexedBGZFFile,
+    ExonFileType,
 };
 
 use super::{indexed_scanner::IndexedVCFScanner, VCFScan, VCFSchemaBuilder};
@@ -76,6 +64,15 @@ pub struct ListingVCFTableOptions {
 
     /// Whether to parse the FORMAT field
     parse_formats: bool,
+
+    /// Whether to add the typed structural variant columns
+    parse_structural_variants: bool,
+
+    /// Whether to dictionary encode the genotypes and filters
+    dictionary_encode_genotypes: bool,
+
+    /// The samples to decode the FORMAT values of, or all samples if `None`
+    samples: Option<Vec<String>>,
 }
 
 impl Default for ListingVCFTableOptions {
@@ -88,6 +85,9 @@ impl Default for ListingVCFTableOptions {
             table_partition_cols: Vec::new(),
             parse_info: false,
             parse_formats: false,
+            parse_structural_variants: false,
+            dictionary_encode_genotypes: false,
+            samples: None,
         }
     }
 }
@@ -110,28 +110,60 @@ impl ExonListingOptions for ListingVCFTableOptions {
         &self,
         conf: FileScanConfig,
     ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
-        let scan = VCFScan::new(conf, self.file_compression_type)?;
+        let scan =
+            VCFScan::new(conf, self.file_compression_type)?.with_samples(self.samples.clone());
 
         Ok(Arc::new(scan))
     }
 }
 
 #[async_trait]
-impl ExonIndexedListingOptions for ListingVCFTableOptions {
-    fn indexed(&self) -> bool {
-        self.indexed
+impl ExonFileFormatOptions for ListingVCFTableOptions {
+    async fn infer_table_schema(
+        &self,
+        state: &dyn Session,
+        table_path: &ListingTableUrl,
+    ) -> Result<TableSchema> {
+        self.infer_schema(state, table_path).await
     }
 
-    fn regions(&self) -> &[Region] {
-        &self.regions
+    fn region_index(&self) -> Option<RegionIndex<'_>> {
+        Some(RegionIndex {
+            table_type: "INDEXED_VCF",
+            filter_name: "vcf_region_filter",
+            indexed_file: IndexedBGZFFile::Vcf,
+            indexed: self.indexed,
+            regions: &self.regions,
+        })
     }
 
-    async fn create_physical_plan_with_regions(
+    fn identifier_index(&self) -> Option<IdentifierIndex> {
+        Some(IdentifierIndex {
+            column: "id",
+            map_keys: &[],
+            record_field: Some(2),
+        })
+    }
+
+    async fn create_physical_plan_with_region(
         &self,
         conf: FileScanConfig,
-        region: Vec<Region>,
-    ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
-        let scan = IndexedVCFScanner::new(conf, Arc::new(region[0].clone()))?;
+        region: Region,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let scan =
+            IndexedVCFScanner::new(conf, Arc::new(region))?.with_samples(self.samples.clone());
+
+        Ok(Arc::new(scan))
+    }
+
+    async fn create_physical_plan_with_identifier_filter(
+        &self,
+        conf: FileScanConfig,
+        identifier_filter: IdentifierFilter,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let scan = VCFScan::new(conf, self.file_compression_type)?
+            .with_identifier_filter(identifier_filter)
+            .with_samples(self.samples.clone());
 
         Ok(Arc::new(scan))
     }
@@ -150,6 +182,9 @@ impl ListingVCFTableOptions {
             regions: Vec::new(),
             parse_info: false,
             parse_formats: false,
+            parse_structural_variants: false,
+            dictionary_encode_genotypes: false,
+            samples: None,
         }
     }
 
@@ -191,6 +226,28 @@ impl ListingVCFTableOptions {
         }
     }
 
+    /// Set the parse structural variants field
+    pub fn with_parse_structural_variants(self, parse_structural_variants: bool) -> Self {
+        Self {
+            parse_structural_variants,
+            ..self
+        }
+    }
+
+    /// Set whether to dictionary encode the genotypes and filters
+    pub fn with_dictionary_encode_genotypes(self, dictionary_encode_genotypes: bool) -> Self {
+        Self {
+            dictionary_encode_genotypes,
+            ..self
+        }
+    }
+
+    /// Only decode the FORMAT values of these samples, in this order, e.g. to read two samples of a
+    /// large cohort
+    pub fn with_samples(self, samples: Option<Vec<String>>) -> Self {
+        Self { samples, ..self }
+    }
+
     async fn infer_schema_from_object_meta(
         &self,
         store: &Arc<dyn ObjectStore>,
@@ -210,6 +267,8 @@ impl ListingVCFTableOptions {
         let mut builder = VCFSchemaBuilder::default()
             .with_parse_info(self.parse_info)
             .with_parse_formats(self.parse_formats)
+            .with_parse_structural_variants(self.parse_structural_variants)
+            .with_dictionary_encode_genotypes(self.dictionary_encode_genotypes)
             .with_partition_fields(self.table_partition_cols.clone());
 
         let header = match self.file_compression_type {
@@ -230,6 +289,10 @@ impl ListingVCFTableOptions {
             }
         };
 
+        if let Some(samples) = &self.samples {
+            SampleSelection::try_new(&header, samples)?;
+        }
+
         builder = builder.with_header(header);
 
         let table_schema = builder.build()?;
@@ -264,184 +327,8 @@ impl ListingVCFTableOptions {
     }
 }
 
-#[derive(Debug, Clone)]
 /// A VCF listing table
-pub struct ListingVCFTable<T> {
-    table_schema: TableSchema,
-
-    config: ExonListingConfig<T>,
-}
-
-impl<T> ListingVCFTable<T> {
-    /// Create a new VCF listing table
-    pub fn new(config: ExonListingConfig<T>, table_schema: TableSchema) -> Self {
-        Self {
-            table_schema,
-            config,
-        }
-    }
-}
-
-#[async_trait]
-impl<T: ExonIndexedListingOptions + 'static> TableProvider for ListingVCFTable<T> {
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-
-    fn schema(&self) -> SchemaRef {
-        Arc::clone(&self.table_schema.table_schema())
-    }
-
-    fn table_type(&self) -> TableType {
-        TableType::Base
-    }
-
-    fn supports_filters_pushdown(
-        &self,
-        filters: &[&Expr],
-    ) -> Result<Vec<TableProviderFilterPushDown>> {
-        tracing::trace!(
-            "vcf table provider supports_filters_pushdown: {:?}",
-            filters
-        );
-
-        Ok(filters
-            .iter()
-            .map(|f| {
-                if let Expr::ScalarFunction(s) = f {
-                    if s.name() == "vcf_region_filter" && (s.args.len() == 2 || s.args.len() == 3) {
-                        return TableProviderFilterPushDown::Exact;
-                    }
-                }
-
-                filter_matches_partition_cols(f, self.config.options.table_partition_cols())
-            })
-            .collect())
-    }
-
-    async fn scan(
-        &self,
-        state: &dyn Session,
-        projection: Option<&Vec<usize>>,
-        filters: &[Expr],
-        limit: Option<usize>,
-    ) -> Result<Arc<dyn ExecutionPlan>> {
-        let url = self
-            .config
-            .inner
-            .table_paths
-            .first()
-            .ok_or(DataFusionError::Execution(
-                "No table paths found in the configuration".to_string(),
-            ))?;
-
-        let object_store = state.runtime_env().object_store(url.object_store())?;
-
-        let mut regions = filters
-            .iter()
-            .map(|f| {
-                if let Expr::ScalarFunction(s) = f {
-                    let r = infer_region::infer_region_from_udf(s, "vcf_region_filter")?;
-                    Ok(r)
-                } else {
-                    Ok(None)
-                }
-            })
-            .collect::<ExonResult<Vec<_>>>()?
-            .into_iter()
-            .flatten()
-            .collect::<Vec<_>>();
-
-        // add the regions from the configuration
-        let config_regions = self.config.options.regions().to_vec();
-        regions.extend(config_regions);
-
-        if regions.len() > 1 {
-            return Err(DataFusionError::NotImplemented(
-                "Multiple regions are not supported yet".to_string(),
-            ));
-        }
-
-        if regions.is_empty() && self.config.options.indexed() {
-            return Err(DataFusionError::Plan(
-                "INDEXED_VCF table requires a region filter. See the UDF 'vcf_region_filter'."
-                    .to_string(),
-            ));
-        }
-
-        if regions.is_empty() {
-            let file_list = pruned_partition_list(
-                &object_store,
-                url,
-                filters,
-                self.config.options.file_extension(),
-                self.config.options.table_partition_cols(),
-            )
-            .await?
-            .try_collect::<Vec<_>>()
-            .await?;
-
-            let file_schema = self.table_schema.file_schema()?;
-            let file_scan_config =
-                FileScanConfigBuilder::new(url.object_store(), file_schema, vec![file_list])
-                    .projection_option(projection.cloned())
-                    .limit_option(limit)
-                    .table_partition_cols(self.config.options.table_partition_cols().to_vec())
-                    .build();
-
-            let table = self
-                .config
-                .options
-                .create_physical_plan(file_scan_config)
-                .await?;
-
-            return Ok(table);
-        }
-
-        let mut file_list = pruned_partition_list(
-            &object_store,
-            self.config.inner.table_paths.first().unwrap(),
-            filters,
-            self.config.options.file_extension(),
-            self.config.options.table_partition_cols(),
-        )
-        .await?;
-
-        let mut file_partitions = Vec::new();
-
-        while let Some(f) = file_list.next().await {
-            let f = f?;
-
-            for region in &regions {
-                let file_byte_range = augment_partitioned_file_with_byte_range(
-                    Arc::clone(&object_store),
-                    &f,
-                    region,
-                    &IndexedBGZFFile::Vcf,
-                )
-                .await?;
-
-                file_partitions.extend(file_byte_range);
-            }
-        }
-
-        let file_schema = self.table_schema.file_schema()?;
-        let file_scan_config =
-            FileScanConfigBuilder::new(url.object_store(), file_schema, vec![file_partitions])
-                .projection_option(projection.cloned())
-                .limit_option(limit)
-                .table_partition_cols(self.config.options.table_partition_cols().to_vec())
-                .build();
-
-        let table = self
-            .config
-            .options
-            .create_physical_plan_with_regions(file_scan_config, regions.to_vec())
-            .await?;
-
-        return Ok(table);
-    }
-}
+pub type ListingVCFTable<T = ListingVCFTableOptions> = ExonListingTable<T>;
 
 #[cfg(test)]
 mod tests {
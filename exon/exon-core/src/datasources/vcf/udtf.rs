@@ -61,7 +61,11 @@ impl TableFunctionImpl for VCFScanFunction {
         let listing_table_options =
             ListingVCFTableOptions::new(listing_scan_function.file_compression_type, false)
                 .with_parse_formats(exon_config_extension.vcf_parse_formats)
-                .with_parse_info(exon_config_extension.vcf_parse_info);
+                .with_parse_info(exon_config_extension.vcf_parse_info)
+                .with_parse_structural_variants(exon_config_extension.vcf_parse_structural_variants)
+                .with_dictionary_encode_genotypes(
+                    exon_config_extension.vcf_dictionary_encode_genotypes,
+                );
 
         let schema = futures::executor::block_on(async {
             let schema = listing_table_options
@@ -124,7 +128,11 @@ impl TableFunctionImpl for VCFIndexedScanFunction {
         let listing_table_options = ListingVCFTableOptions::new(FileCompressionType::GZIP, true)
             .with_regions(vec![region])
             .with_parse_info(exon_config_extension.vcf_parse_info)
-            .with_parse_formats(exon_config_extension.vcf_parse_formats);
+            .with_parse_formats(exon_config_extension.vcf_parse_formats)
+            .with_parse_structural_variants(exon_config_extension.vcf_parse_structural_variants)
+            .with_dictionary_encode_genotypes(
+                exon_config_extension.vcf_dictionary_encode_genotypes,
+            );
 
         let schema = futures::executor::block_on(async {
             let schema = listing_table_options
@@ -0,0 +1,287 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Read;
+
+use arrow::error::ArrowError;
+
+const BLOSC_HEADER_SIZE: usize = 16;
+
+const BLOSC_DOSHUFFLE: u8 = 0x1;
+const BLOSC_MEMCPYED: u8 = 0x2;
+const BLOSC_DOBITSHUFFLE: u8 = 0x4;
+const BLOSC_DONT_SPLIT: u8 = 0x10;
+
+/// The codec of the blocks of a blosc frame, from the top bits of its flags.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BloscCodec {
+    BloscLZ,
+    LZ4,
+    Snappy,
+    Zlib,
+    Zstd,
+}
+
+impl TryFrom<u8> for BloscCodec {
+    type Error = ArrowError;
+
+    fn try_from(flags: u8) -> Result<Self, Self::Error> {
+        match flags >> 5 {
+            0 => Ok(Self::BloscLZ),
+            1 => Ok(Self::LZ4),
+            2 => Ok(Self::Snappy),
+            3 => Ok(Self::Zlib),
+            4 => Ok(Self::Zstd),
+            code => Err(invalid_frame(format!("unknown codec {code}"))),
+        }
+    }
+}
+
+fn invalid_frame(message: String) -> ArrowError {
+    ArrowError::ParseError(format!("Invalid blosc frame: {message}"))
+}
+
+fn read_u32(src: &[u8], offset: usize) -> Result<usize, ArrowError> {
+    src.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+        .ok_or_else(|| invalid_frame(format!("truncated at offset {offset}")))
+}
+
+fn decompress_split(
+    codec: BloscCodec,
+    src: &[u8],
+    decompressed_size: usize,
+) -> Result<Vec<u8>, ArrowError> {
+    let decompressed = match codec {
+        BloscCodec::LZ4 => lz4_flex::block::decompress(src, decompressed_size)
+            .map_err(|e| ArrowError::ExternalError(Box::new(e)))?,
+        BloscCodec::Zlib => {
+            let mut decompressed = Vec::with_capacity(decompressed_size);
+            flate2::read::ZlibDecoder::new(src).read_to_end(&mut decompressed)?;
+            decompressed
+        }
+        BloscCodec::Zstd => zstd::bulk::decompress(src, decompressed_size)?,
+        BloscCodec::BloscLZ | BloscCodec::Snappy => {
+            return Err(ArrowError::NotYetImplemented(format!(
+                "Blosc frames compressed with {codec:?} are not supported"
+            )))
+        }
+    };
+
+    if decompressed.len() != decompressed_size {
+        return Err(invalid_frame(format!(
+            "block decompressed to {} bytes, expected {decompressed_size}",
+            decompressed.len()
+        )));
+    }
+
+    Ok(decompressed)
+}
+
+/// Undo the byte shuffle of a block, which groups the n-th bytes of every element together.
+fn unshuffle(block: &[u8], type_size: usize) -> Vec<u8> {
+    let num_elements = block.len() / type_size;
+    let mut unshuffled = vec![0; block.len()];
+
+    for element in 0..num_elements {
+        for byte in 0..type_size {
+            unshuffled[element * type_size + byte] = block[byte * num_elements + element];
+        }
+    }
+
+    let shuffled_length = num_elements * type_size;
+    unshuffled[shuffled_length..].copy_from_slice(&block[shuffled_length..]);
+
+    unshuffled
+}
+
+/// Decompress a blosc frame, as written by the numcodecs `blosc` compressor.
+pub fn decompress_blosc(src: &[u8]) -> Result<Vec<u8>, ArrowError> {
+    if src.len() < BLOSC_HEADER_SIZE {
+        return Err(invalid_frame("missing the header".to_string()));
+    }
+
+    let flags = src[2];
+    let type_size = (src[3] as usize).max(1);
+    let num_bytes = read_u32(src, 4)?;
+    let block_size = read_u32(src, 8)?;
+
+    if flags & BLOSC_MEMCPYED != 0 {
+        return src
+            .get(BLOSC_HEADER_SIZE..BLOSC_HEADER_SIZE + num_bytes)
+            .map(|data| data.to_vec())
+            .ok_or_else(|| invalid_frame("truncated data".to_string()));
+    }
+
+    if num_bytes == 0 {
+        return Ok(Vec::new());
+    }
+
+    if block_size == 0 {
+        return Err(invalid_frame("block size is zero".to_string()));
+    }
+
+    if flags & BLOSC_DOBITSHUFFLE != 0 {
+        return Err(ArrowError::NotYetImplemented(
+            "Blosc frames with bit shuffle are not supported".to_string(),
+        ));
+    }
+
+    let codec = BloscCodec::try_from(flags)?;
+    let num_blocks = num_bytes.div_ceil(block_size);
+
+    let mut decompressed = Vec::with_capacity(num_bytes);
+
+    for block_index in 0..num_blocks {
+        let block_start = read_u32(src, BLOSC_HEADER_SIZE + 4 * block_index)?;
+
+        let leftover_block = block_index == num_blocks - 1 && num_bytes % block_size != 0;
+        let block_length = if leftover_block {
+            num_bytes % block_size
+        } else {
+            block_size
+        };
+
+        let num_splits = if flags & BLOSC_DONT_SPLIT == 0 && !leftover_block {
+            type_size
+        } else {
+            1
+        };
+        let split_length = block_length / num_splits;
+
+        let mut block = Vec::with_capacity(block_length);
+        let mut offset = block_start;
+
+        for _ in 0..num_splits {
+            let compressed_length = read_u32(src, offset)?;
+            offset += 4;
+
+            let split = src
+                .get(offset..offset + compressed_length)
+                .ok_or_else(|| invalid_frame(format!("truncated block {block_index}")))?;
+            offset += compressed_length;
+
+            if compressed_length == split_length {
+                block.extend_from_slice(split);
+            } else {
+                block.extend(decompress_split(codec, split, split_length)?);
+            }
+        }
+
+        if flags & BLOSC_DOSHUFFLE != 0 && type_size > 1 {
+            block = unshuffle(&block, type_size);
+        }
+
+        decompressed.extend(block);
+    }
+
+    Ok(decompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a single block frame, shuffled and split by element byte, like blosc does.
+    fn blosc_frame(
+        data: &[u8],
+        type_size: usize,
+        codec: u8,
+        compress: fn(&[u8]) -> Vec<u8>,
+    ) -> Vec<u8> {
+        let num_elements = data.len() / type_size;
+        let mut shuffled = vec![0; data.len()];
+        for element in 0..num_elements {
+            for byte in 0..type_size {
+                shuffled[byte * num_elements + element] = data[element * type_size + byte];
+            }
+        }
+
+        let mut frame = vec![2, 1, BLOSC_DOSHUFFLE | (codec << 5), type_size as u8];
+        frame.extend((data.len() as u32).to_le_bytes());
+        frame.extend((data.len() as u32).to_le_bytes());
+        frame.extend([0; 4]);
+        frame.extend(((BLOSC_HEADER_SIZE + 4) as u32).to_le_bytes());
+
+        for split in shuffled.chunks(num_elements) {
+            let compressed = compress(split);
+            frame.extend((compressed.len() as u32).to_le_bytes());
+            frame.extend(compressed);
+        }
+
+        let frame_length = frame.len() as u32;
+        frame[12..16].copy_from_slice(&frame_length.to_le_bytes());
+
+        frame
+    }
+
+    fn positions() -> Vec<u8> {
+        (0..256_i32)
+            .flat_map(|i| (10_000 + i * 3).to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn test_decompress_lz4() -> Result<(), ArrowError> {
+        let data = positions();
+        let frame = blosc_frame(&data, 4, 1, lz4_flex::block::compress);
+
+        assert_eq!(decompress_blosc(&frame)?, data);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decompress_zstd() -> Result<(), ArrowError> {
+        let data = positions();
+        let frame = blosc_frame(&data, 4, 4, |split| zstd::bulk::compress(split, 3).unwrap());
+
+        assert_eq!(decompress_blosc(&frame)?, data);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decompress_memcpyed() -> Result<(), ArrowError> {
+        let mut frame = vec![2, 1, BLOSC_MEMCPYED, 1, 3, 0, 0, 0, 3, 0, 0, 0, 19, 0, 0, 0];
+        frame.extend([1, 2, 3]);
+
+        assert_eq!(decompress_blosc(&frame)?, vec![1, 2, 3]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsupported_bitshuffle() {
+        let frame = [
+            2,
+            1,
+            BLOSC_DOBITSHUFFLE,
+            1,
+            4,
+            0,
+            0,
+            0,
+            4,
+            0,
+            0,
+            0,
+            16,
+            0,
+            0,
+            0,
+        ];
+        assert!(decompress_blosc(&frame).is_err());
+    }
+}
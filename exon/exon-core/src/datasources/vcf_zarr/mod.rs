@@ -0,0 +1,39 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A datafusion compatible datasource for VCF Zarr stores, i.e. variants stored as chunked Zarr
+//! v2 arrays in the layout written by vcf2zarr and sgkit.
+//!
+//! Filters on `chrom` and `pos` select the chunks of variants to read, so only the small contig
+//! and position chunks are fetched for the chunks without a matching variant.
+
+mod blosc;
+mod vcf_zarr_config;
+mod vcf_zarr_filter;
+mod vcf_zarr_opener;
+mod vcf_zarr_reader;
+mod vcf_zarr_scanner;
+mod vcf_zarr_schema_builder;
+mod zarr_array;
+mod zarr_codec;
+
+/// Table provider for VCF Zarr stores.
+pub mod table_provider;
+
+pub use self::vcf_zarr_config::VCFZarrConfig;
+pub use self::vcf_zarr_opener::VCFZarrOpener;
+pub use self::vcf_zarr_scanner::VCFZarrScan;
+
+mod udtf;
+pub use self::udtf::VCFZarrScanFunction;
@@ -0,0 +1,285 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::datatypes::{Field, SchemaRef};
+use async_trait::async_trait;
+use datafusion::{
+    catalog::Session,
+    datasource::{
+        listing::{ListingTableUrl, PartitionedFile},
+        physical_plan::FileScanConfig,
+        TableProvider,
+    },
+    error::{DataFusionError, Result},
+    logical_expr::{TableProviderFilterPushDown, TableType},
+    physical_plan::ExecutionPlan,
+    prelude::Expr,
+};
+use exon_common::TableSchema;
+
+use crate::{
+    datasources::{
+        exon_listing_table_options::{ExonListingConfig, ExonListingOptions},
+        ExonFileType,
+    },
+    physical_plan::file_scan_config_builder::FileScanConfigBuilder,
+};
+
+use super::{
+    vcf_zarr_filter::{infer_vcf_zarr_filter, VCFZarrFilter},
+    vcf_zarr_schema_builder::VCFZarrSchemaBuilder,
+    zarr_array::ZarrArrayMetadata,
+    VCFZarrScan,
+};
+
+#[derive(Debug, Clone)]
+/// Listing options for a VCF Zarr table, where the table path is the root of the store
+pub struct ListingVCFZarrTableOptions {
+    /// File extension of the store directory
+    file_extension: String,
+
+    /// Partition columns for the table, which a single store doesn't have
+    table_partition_cols: Vec<Field>,
+}
+
+#[async_trait]
+impl ExonListingOptions for ListingVCFZarrTableOptions {
+    fn table_partition_cols(&self) -> &[Field] {
+        &self.table_partition_cols
+    }
+
+    fn file_extension(&self) -> &str {
+        &self.file_extension
+    }
+
+    async fn create_physical_plan(
+        &self,
+        conf: FileScanConfig,
+    ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
+        let scan = VCFZarrScan::new(conf);
+        Ok(Arc::new(scan))
+    }
+}
+
+impl Default for ListingVCFZarrTableOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ListingVCFZarrTableOptions {
+    /// Create a new set of options
+    pub fn new() -> Self {
+        Self {
+            file_extension: ExonFileType::VCFZarr.get_base_file_extension(),
+            table_partition_cols: Vec::new(),
+        }
+    }
+
+    /// Infer the schema for the table from the arrays of the store
+    pub async fn infer_schema(
+        &self,
+        state: &dyn Session,
+        table_path: &ListingTableUrl,
+    ) -> Result<TableSchema> {
+        let store = state.runtime_env().object_store(table_path)?;
+        let root = table_path.prefix();
+
+        let listing = store.list_with_delimiter(Some(root)).await?;
+
+        let mut arrays = Vec::new();
+        for prefix in listing.common_prefixes {
+            let Some(name) = prefix.filename() else {
+                continue;
+            };
+
+            // Groups and other directories don't have array metadata.
+            let bytes = match store.get(&prefix.child(".zarray")).await {
+                Ok(get_result) => get_result.bytes().await?,
+                Err(object_store::Error::NotFound { .. }) => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            arrays.push((name.to_string(), ZarrArrayMetadata::try_from_slice(&bytes)?));
+        }
+
+        if arrays.is_empty() {
+            return Err(DataFusionError::Execution(format!(
+                "No Zarr arrays found in {table_path}"
+            )));
+        }
+
+        let schema_builder = VCFZarrSchemaBuilder::try_new(&arrays)?;
+
+        Ok(schema_builder.build())
+    }
+
+    async fn create_physical_plan_with_filter(
+        &self,
+        conf: FileScanConfig,
+        filter: VCFZarrFilter,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let scan = VCFZarrScan::new(conf).with_filter(filter);
+
+        Ok(Arc::new(scan))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A VCF Zarr listing table
+pub struct ListingVCFZarrTable {
+    table_schema: TableSchema,
+
+    config: ExonListingConfig<ListingVCFZarrTableOptions>,
+}
+
+impl ListingVCFZarrTable {
+    /// Create a new VCF Zarr listing table
+    pub fn new(
+        config: ExonListingConfig<ListingVCFZarrTableOptions>,
+        table_schema: TableSchema,
+    ) -> Self {
+        Self {
+            table_schema,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for ListingVCFZarrTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.table_schema.table_schema())
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        Ok(filters
+            .iter()
+            .map(|f| match infer_vcf_zarr_filter(f) {
+                Some(_) => TableProviderFilterPushDown::Inexact,
+                None => TableProviderFilterPushDown::Unsupported,
+            })
+            .collect())
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let table_path = self.config.first_table_path().ok_or_else(|| {
+            DataFusionError::Execution("No table paths found in the configuration".to_string())
+        })?;
+
+        // The store is scanned as a single file at its root.
+        let store_file = PartitionedFile::new(table_path.prefix().to_string(), 0);
+
+        let file_schema = self.table_schema.file_schema()?;
+        let file_scan_config = FileScanConfigBuilder::new(
+            table_path.object_store(),
+            file_schema,
+            vec![vec![store_file]],
+        )
+        .projection_option(projection.cloned())
+        .limit_option(limit)
+        .build();
+
+        let filter = filters
+            .iter()
+            .filter_map(infer_vcf_zarr_filter)
+            .reduce(VCFZarrFilter::and);
+
+        let plan = match filter {
+            Some(filter) => {
+                self.config
+                    .options
+                    .create_physical_plan_with_filter(file_scan_config, filter)
+                    .await?
+            }
+            None => {
+                self.config
+                    .options
+                    .create_physical_plan(file_scan_config)
+                    .await?
+            }
+        };
+
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        datasources::{ExonFileType, ExonListingTableFactory},
+        ExonSession,
+    };
+
+    use datafusion::{
+        datasource::file_format::file_compression_type::FileCompressionType,
+        logical_expr::{col, lit},
+    };
+    use exon_test::test_listing_table_url;
+
+    #[tokio::test]
+    async fn test_listing_with_filter() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        let session_state = ctx.session.state();
+
+        let table_path = test_listing_table_url("vcf_zarr/sample.vcz");
+        let table = ExonListingTableFactory::new()
+            .create_from_file_type(
+                &session_state,
+                ExonFileType::VCFZarr,
+                FileCompressionType::UNCOMPRESSED,
+                table_path.to_string(),
+                Vec::new(),
+                &HashMap::new(),
+            )
+            .await?;
+
+        let df = ctx.session.read_table(table)?;
+        let row_cnt = df.clone().count().await?;
+        assert_eq!(row_cnt, 9);
+
+        let filtered_cnt = df
+            .filter(
+                col("chrom")
+                    .eq(lit("20"))
+                    .and(col("pos").gt_eq(lit(1_110_000))),
+            )?
+            .count()
+            .await?;
+        assert_eq!(filtered_cnt, 3);
+
+        Ok(())
+    }
+}
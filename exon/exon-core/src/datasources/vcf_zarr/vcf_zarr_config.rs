@@ -0,0 +1,66 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::datatypes::SchemaRef;
+use exon_common::DEFAULT_BATCH_SIZE;
+use object_store::ObjectStore;
+
+use super::vcf_zarr_filter::VCFZarrFilter;
+
+/// Configuration for a VCF Zarr data source.
+pub struct VCFZarrConfig {
+    /// The number of rows to read at a time.
+    pub batch_size: usize,
+    /// The schema of the VCF Zarr store, from the arrays of the store.
+    pub file_schema: SchemaRef,
+    /// The object store to use for reading VCF Zarr stores.
+    pub object_store: Arc<dyn ObjectStore>,
+    /// The projection to use for reading VCF Zarr stores.
+    pub projection: Option<Vec<usize>>,
+    /// The filter on the chromosome and position used to select the chunks to read.
+    pub filter: Option<VCFZarrFilter>,
+}
+
+impl VCFZarrConfig {
+    /// Create a new VCF Zarr configuration.
+    pub fn new(object_store: Arc<dyn ObjectStore>, file_schema: SchemaRef) -> Self {
+        Self {
+            object_store,
+            file_schema,
+            batch_size: DEFAULT_BATCH_SIZE,
+            projection: None,
+            filter: None,
+        }
+    }
+
+    /// Set the batch size.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set the projection.
+    pub fn with_some_projection(mut self, projection: Option<Vec<usize>>) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Set the chunk selection filter.
+    pub fn with_filter(mut self, filter: Option<VCFZarrFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
+}
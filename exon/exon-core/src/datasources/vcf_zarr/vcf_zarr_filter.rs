@@ -0,0 +1,250 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use arrow::datatypes::DataType;
+use datafusion::{
+    logical_expr::{expr::InList, Between, BinaryExpr, Expr, Operator},
+    scalar::ScalarValue,
+};
+
+/// A filter on the chromosome and position of variants, used to select the chunks of a VCF Zarr
+/// store to read.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VCFZarrFilter {
+    chroms: Option<HashSet<String>>,
+    positions: Option<(i64, i64)>,
+}
+
+impl VCFZarrFilter {
+    /// Only keep the variants on one of these chromosomes.
+    pub fn with_chroms(self, chroms: HashSet<String>) -> Self {
+        let chroms = match self.chroms {
+            Some(current) => current.intersection(&chroms).cloned().collect(),
+            None => chroms,
+        };
+
+        Self {
+            chroms: Some(chroms),
+            ..self
+        }
+    }
+
+    /// Bound the position, inclusively.
+    pub fn with_position_bounds(self, min: i64, max: i64) -> Self {
+        let positions = match self.positions {
+            Some((current_min, current_max)) => (current_min.max(min), current_max.min(max)),
+            None => (min, max),
+        };
+
+        Self {
+            positions: Some(positions),
+            ..self
+        }
+    }
+
+    /// Combine two filters, keeping the variants that match both.
+    pub fn and(self, other: Self) -> Self {
+        let filter = match other.chroms {
+            Some(chroms) => self.with_chroms(chroms),
+            None => self,
+        };
+
+        match other.positions {
+            Some((min, max)) => filter.with_position_bounds(min, max),
+            None => filter,
+        }
+    }
+
+    /// True if variants on this chromosome may match.
+    pub fn matches_chrom(&self, chrom: &str) -> bool {
+        match &self.chroms {
+            Some(chroms) => chroms.contains(chrom),
+            None => true,
+        }
+    }
+
+    /// True if variants at this position may match.
+    pub fn matches_position(&self, position: i64) -> bool {
+        match self.positions {
+            Some((min, max)) => min <= position && position <= max,
+            None => true,
+        }
+    }
+}
+
+fn column_name(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Column(c) => Some(c.name.as_str()),
+        _ => None,
+    }
+}
+
+fn string_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Literal(ScalarValue::Utf8(Some(value)))
+        | Expr::Literal(ScalarValue::LargeUtf8(Some(value)))
+        | Expr::Literal(ScalarValue::Utf8View(Some(value))) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn integer_literal(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Literal(scalar) if scalar.data_type().is_integer() => {
+            match scalar.cast_to(&DataType::Int64).ok()? {
+                ScalarValue::Int64(value) => value,
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn literal_filter(column: &str, op: Operator, value: &Expr) -> Option<VCFZarrFilter> {
+    let filter = VCFZarrFilter::default();
+
+    match (column, op) {
+        ("chrom", Operator::Eq) => {
+            Some(filter.with_chroms(HashSet::from([string_literal(value)?])))
+        }
+        ("pos", Operator::Eq) => {
+            let position = integer_literal(value)?;
+            Some(filter.with_position_bounds(position, position))
+        }
+        ("pos", Operator::Lt) => {
+            Some(filter.with_position_bounds(i64::MIN, integer_literal(value)?.saturating_sub(1)))
+        }
+        ("pos", Operator::LtEq) => {
+            Some(filter.with_position_bounds(i64::MIN, integer_literal(value)?))
+        }
+        ("pos", Operator::Gt) => {
+            Some(filter.with_position_bounds(integer_literal(value)?.saturating_add(1), i64::MAX))
+        }
+        ("pos", Operator::GtEq) => {
+            Some(filter.with_position_bounds(integer_literal(value)?, i64::MAX))
+        }
+        _ => None,
+    }
+}
+
+/// Infer a chromosome and position filter from a predicate, if it constrains either.
+pub(crate) fn infer_vcf_zarr_filter(expr: &Expr) -> Option<VCFZarrFilter> {
+    match expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        }) => match (infer_vcf_zarr_filter(left), infer_vcf_zarr_filter(right)) {
+            (Some(left), Some(right)) => Some(left.and(right)),
+            (filter, None) | (None, filter) => filter,
+        },
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            match (column_name(left), column_name(right)) {
+                (Some(column), None) => literal_filter(column, *op, right),
+                (None, Some(column)) => literal_filter(column, op.swap()?, left),
+                _ => None,
+            }
+        }
+        Expr::Between(Between {
+            expr,
+            negated: false,
+            low,
+            high,
+        }) if column_name(expr) == Some("pos") => Some(
+            VCFZarrFilter::default()
+                .with_position_bounds(integer_literal(low)?, integer_literal(high)?),
+        ),
+        Expr::InList(InList {
+            expr,
+            list,
+            negated: false,
+        }) if column_name(expr) == Some("chrom") => {
+            let chroms = list.iter().map(string_literal).collect::<Option<_>>()?;
+            Some(VCFZarrFilter::default().with_chroms(chroms))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use datafusion::logical_expr::{col, lit};
+
+    use super::{infer_vcf_zarr_filter, VCFZarrFilter};
+
+    fn chroms(chroms: &[&str]) -> HashSet<String> {
+        chroms.iter().map(|chrom| chrom.to_string()).collect()
+    }
+
+    #[test]
+    fn test_infer_vcf_zarr_filter() {
+        let cases = [
+            (
+                col("chrom").eq(lit("1")),
+                VCFZarrFilter::default().with_chroms(chroms(&["1"])),
+            ),
+            (
+                col("chrom").in_list(vec![lit("1"), lit("X")], false),
+                VCFZarrFilter::default().with_chroms(chroms(&["1", "X"])),
+            ),
+            (
+                lit(100).lt(col("pos")),
+                VCFZarrFilter::default().with_position_bounds(101, i64::MAX),
+            ),
+            (
+                col("pos").between(lit(10), lit(20)),
+                VCFZarrFilter::default().with_position_bounds(10, 20),
+            ),
+            (
+                col("chrom")
+                    .eq(lit("1"))
+                    .and(col("pos").gt_eq(lit(10)))
+                    .and(col("pos").lt(lit(20)))
+                    .and(col("variant_quality").gt(lit(30))),
+                VCFZarrFilter::default()
+                    .with_chroms(chroms(&["1"]))
+                    .with_position_bounds(10, 19),
+            ),
+        ];
+
+        for (expr, expected) in cases {
+            assert_eq!(infer_vcf_zarr_filter(&expr), Some(expected), "{}", expr);
+        }
+
+        assert!(infer_vcf_zarr_filter(&col("chrom").not_eq(lit("1"))).is_none());
+        assert!(infer_vcf_zarr_filter(&col("pos").eq(lit("a"))).is_none());
+        assert!(
+            infer_vcf_zarr_filter(&col("chrom").eq(lit("1")).or(col("pos").eq(lit(1)))).is_none()
+        );
+    }
+
+    #[test]
+    fn test_matches() {
+        let filter = VCFZarrFilter::default()
+            .with_chroms(chroms(&["1", "2"]))
+            .and(VCFZarrFilter::default().with_chroms(chroms(&["2", "X"])))
+            .with_position_bounds(100, 200);
+
+        assert!(filter.matches_chrom("2"));
+        assert!(!filter.matches_chrom("1"));
+        assert!(filter.matches_position(200));
+        assert!(!filter.matches_position(99));
+
+        assert!(VCFZarrFilter::default().matches_chrom("1"));
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use datafusion::datasource::physical_plan::{FileMeta, FileOpenFuture, FileOpener};
+use futures::StreamExt;
+
+use super::{vcf_zarr_config::VCFZarrConfig, vcf_zarr_reader::VCFZarrReader};
+
+/// Implements a datafusion `FileOpener` for VCF Zarr stores, where the location of each file
+/// is the root of a store.
+pub struct VCFZarrOpener {
+    /// The configuration for the opener.
+    config: Arc<VCFZarrConfig>,
+}
+
+impl VCFZarrOpener {
+    /// Create a new VCF Zarr store opener.
+    pub fn new(config: Arc<VCFZarrConfig>) -> Self {
+        Self { config }
+    }
+}
+
+impl FileOpener for VCFZarrOpener {
+    fn open(&self, file_meta: FileMeta) -> datafusion::error::Result<FileOpenFuture> {
+        let config = Arc::clone(&self.config);
+
+        Ok(Box::pin(async move {
+            let root = file_meta.location().clone();
+            let reader = VCFZarrReader::try_new(root, config).await?;
+
+            Ok(reader.into_stream().boxed())
+        }))
+    }
+}
@@ -0,0 +1,373 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, BooleanArray, StringArray},
+    compute::{cast, filter_record_batch},
+    datatypes::{DataType, Int64Type, SchemaRef},
+    error::ArrowError,
+    record_batch::{RecordBatch, RecordBatchOptions},
+};
+use object_store::{path::Path, ObjectStore};
+
+use super::{
+    vcf_zarr_config::VCFZarrConfig,
+    vcf_zarr_schema_builder::{array_name, CONTIG_ARRAY, CONTIG_ID_ARRAY, POSITION_ARRAY},
+    zarr_array::{ZarrArrayMetadata, ZarrRows},
+    zarr_codec::decompress_chunk,
+};
+
+fn store_error(e: object_store::Error) -> ArrowError {
+    ArrowError::ExternalError(Box::new(e))
+}
+
+/// Read the metadata of an array of the store.
+async fn read_array_metadata(
+    object_store: &Arc<dyn ObjectStore>,
+    root: &Path,
+    array: &str,
+) -> Result<ZarrArrayMetadata, ArrowError> {
+    let location = root.child(array).child(".zarray");
+    let bytes = object_store
+        .get(&location)
+        .await
+        .map_err(store_error)?
+        .bytes()
+        .await
+        .map_err(store_error)?;
+
+    ZarrArrayMetadata::try_from_slice(&bytes)
+}
+
+/// An array of the store.
+struct ZarrArray {
+    name: String,
+    metadata: ZarrArrayMetadata,
+}
+
+impl ZarrArray {
+    async fn try_new(
+        object_store: &Arc<dyn ObjectStore>,
+        root: &Path,
+        name: &str,
+    ) -> Result<Self, ArrowError> {
+        let metadata = read_array_metadata(object_store, root, name).await?;
+
+        Ok(Self {
+            name: name.to_string(),
+            metadata,
+        })
+    }
+
+    /// Read the rows of one chunk along the first dimension. Missing chunks are filled with
+    /// the fill value of the array.
+    async fn read_rows(
+        &self,
+        object_store: &Arc<dyn ObjectStore>,
+        root: &Path,
+        chunk_index: usize,
+    ) -> Result<ArrayRef, ArrowError> {
+        let chunk_length = self.metadata.chunks[0];
+        let num_rows = chunk_length.min(self.metadata.shape[0] - chunk_index * chunk_length);
+
+        let array_root = root.child(self.name.as_str());
+
+        let chunks = self
+            .metadata
+            .inner_chunk_indices()
+            .into_iter()
+            .map(|inner_chunk_index| {
+                let mut key = vec![chunk_index];
+                key.extend(&inner_chunk_index);
+                let location = array_root.child(self.metadata.chunk_key(&key));
+
+                async move {
+                    match object_store.get(&location).await {
+                        Ok(get_result) => {
+                            let bytes = get_result.bytes().await.map_err(store_error)?;
+                            Ok(Some((inner_chunk_index, bytes)))
+                        }
+                        Err(object_store::Error::NotFound { .. }) => Ok(None),
+                        Err(e) => Err(store_error(e)),
+                    }
+                }
+            });
+
+        let chunks = futures::future::try_join_all(chunks).await?;
+
+        let mut rows = ZarrRows::try_new(&self.metadata, num_rows)?;
+        for (inner_chunk_index, bytes) in chunks.into_iter().flatten() {
+            let chunk = decompress_chunk(self.metadata.compressor.as_ref(), &bytes)?;
+            rows.add_chunk(chunk, &inner_chunk_index)?;
+        }
+
+        rows.finish()
+    }
+}
+
+/// Reads the variants of a VCF Zarr store into record batches, one chunk of variants at a time.
+///
+/// If there's a filter, the contig and position chunks are read first and the other arrays are
+/// only read for the chunks with a matching variant.
+pub struct VCFZarrReader {
+    /// The configuration for this reader.
+    config: Arc<VCFZarrConfig>,
+
+    /// The root of the store.
+    root: Path,
+
+    /// The names of the contigs, indexed by the contig array.
+    contigs: Vec<String>,
+
+    /// The contig index of each variant.
+    contig: ZarrArray,
+
+    /// The position of each variant.
+    position: ZarrArray,
+
+    /// The projected schema.
+    projected_schema: SchemaRef,
+
+    /// The array of each projected column.
+    columns: Vec<ZarrArray>,
+
+    /// The number of variant chunks.
+    num_chunks: usize,
+
+    /// The index of the next variant chunk to read.
+    chunk_index: usize,
+
+    /// The rows of the last chunk that didn't fit in the previous batch.
+    remainder: Option<RecordBatch>,
+}
+
+impl VCFZarrReader {
+    /// Create a reader of the store at this root, reading the metadata of its arrays and the
+    /// names of its contigs.
+    pub async fn try_new(root: Path, config: Arc<VCFZarrConfig>) -> Result<Self, ArrowError> {
+        let object_store = &config.object_store;
+
+        let contig = ZarrArray::try_new(object_store, &root, CONTIG_ARRAY).await?;
+        let position = ZarrArray::try_new(object_store, &root, POSITION_ARRAY).await?;
+        let contig_id = ZarrArray::try_new(object_store, &root, CONTIG_ID_ARRAY).await?;
+
+        let mut contigs = Vec::with_capacity(contig_id.metadata.shape[0]);
+        for chunk_index in 0..contig_id.metadata.num_chunks(0) {
+            let names = contig_id
+                .read_rows(object_store, &root, chunk_index)
+                .await?;
+            let names = names
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| {
+                    ArrowError::SchemaError(format!("{CONTIG_ID_ARRAY} is not an array of strings"))
+                })?;
+
+            contigs.extend(
+                names
+                    .iter()
+                    .map(|name| name.unwrap_or_default().to_string()),
+            );
+        }
+
+        let projected_schema = match &config.projection {
+            Some(projection) => Arc::new(config.file_schema.project(projection)?),
+            None => Arc::clone(&config.file_schema),
+        };
+
+        let mut columns = Vec::with_capacity(projected_schema.fields().len());
+        for field in projected_schema.fields() {
+            let column = ZarrArray::try_new(object_store, &root, array_name(field.name())).await?;
+            columns.push(column);
+        }
+
+        let chunk_length = position.metadata.chunks[0];
+        for array in columns.iter().chain([&contig]) {
+            if array.metadata.shape[0] != position.metadata.shape[0]
+                || array.metadata.chunks[0] != chunk_length
+            {
+                return Err(ArrowError::NotYetImplemented(format!(
+                    "Array {} isn't chunked along the variants like {POSITION_ARRAY}",
+                    array.name
+                )));
+            }
+        }
+
+        let num_chunks = position.metadata.num_chunks(0);
+
+        Ok(Self {
+            config,
+            root,
+            contigs,
+            contig,
+            position,
+            projected_schema,
+            columns,
+            num_chunks,
+            chunk_index: 0,
+            remainder: None,
+        })
+    }
+
+    /// The chromosome of each variant, from the contig indices.
+    fn chroms(&self, contig: &ArrayRef) -> Result<StringArray, ArrowError> {
+        let contig = cast(contig, &DataType::Int64)?;
+
+        contig
+            .as_primitive::<Int64Type>()
+            .iter()
+            .map(|index| {
+                let name = index
+                    .and_then(|index| usize::try_from(index).ok())
+                    .and_then(|index| self.contigs.get(index));
+
+                match name {
+                    Some(name) => Ok(Some(name.as_str())),
+                    None => Err(ArrowError::ParseError(format!(
+                        "Invalid contig index {index:?} in {CONTIG_ARRAY}"
+                    ))),
+                }
+            })
+            .collect()
+    }
+
+    /// Split off the rows past the batch size into the remainder.
+    fn split(&mut self, batch: RecordBatch) -> RecordBatch {
+        let batch_size = self.config.batch_size.max(1);
+
+        if batch.num_rows() > batch_size {
+            self.remainder = Some(batch.slice(batch_size, batch.num_rows() - batch_size));
+            batch.slice(0, batch_size)
+        } else {
+            batch
+        }
+    }
+
+    pub async fn read_batch(&mut self) -> Result<Option<RecordBatch>, ArrowError> {
+        if let Some(remainder) = self.remainder.take() {
+            return Ok(Some(self.split(remainder)));
+        }
+
+        let object_store = Arc::clone(&self.config.object_store);
+
+        while self.chunk_index < self.num_chunks {
+            let chunk_index = self.chunk_index;
+            self.chunk_index += 1;
+
+            let chunk_length = self.position.metadata.chunks[0];
+            let num_rows =
+                chunk_length.min(self.position.metadata.shape[0] - chunk_index * chunk_length);
+
+            let mut chroms = None;
+            let mut positions = None;
+
+            let mask = match &self.config.filter {
+                Some(filter) => {
+                    let contig = self
+                        .contig
+                        .read_rows(&object_store, &self.root, chunk_index)
+                        .await?;
+                    let chunk_chroms = self.chroms(&contig)?;
+
+                    let position = self
+                        .position
+                        .read_rows(&object_store, &self.root, chunk_index)
+                        .await?;
+                    let chunk_positions = cast(&position, &DataType::Int64)?;
+
+                    let mask = chunk_chroms
+                        .iter()
+                        .zip(chunk_positions.as_primitive::<Int64Type>().iter())
+                        .map(|(chrom, position)| {
+                            Some(
+                                chrom.is_some_and(|chrom| filter.matches_chrom(chrom))
+                                    && position
+                                        .is_some_and(|position| filter.matches_position(position)),
+                            )
+                        })
+                        .collect::<BooleanArray>();
+
+                    // Skip the chunk without reading the other arrays.
+                    if mask.true_count() == 0 {
+                        continue;
+                    }
+
+                    chroms = Some(chunk_chroms);
+                    positions = Some(chunk_positions);
+
+                    Some(mask)
+                }
+                None => None,
+            };
+
+            let mut arrays = Vec::with_capacity(self.columns.len());
+            for (field, column) in self.projected_schema.fields().iter().zip(&self.columns) {
+                let array: ArrayRef = match field.name().as_str() {
+                    "chrom" => match chroms.take() {
+                        Some(chroms) => Arc::new(chroms),
+                        None => {
+                            let contig = column
+                                .read_rows(&object_store, &self.root, chunk_index)
+                                .await?;
+                            Arc::new(self.chroms(&contig)?)
+                        }
+                    },
+                    "pos" => match positions.take() {
+                        Some(positions) => positions,
+                        None => {
+                            let position = column
+                                .read_rows(&object_store, &self.root, chunk_index)
+                                .await?;
+                            cast(&position, &DataType::Int64)?
+                        }
+                    },
+                    _ => {
+                        column
+                            .read_rows(&object_store, &self.root, chunk_index)
+                            .await?
+                    }
+                };
+
+                arrays.push(array);
+            }
+
+            let batch = RecordBatch::try_new_with_options(
+                Arc::clone(&self.projected_schema),
+                arrays,
+                &RecordBatchOptions::new().with_row_count(Some(num_rows)),
+            )?;
+
+            let batch = match mask {
+                Some(mask) => filter_record_batch(&batch, &mask)?,
+                None => batch,
+            };
+
+            return Ok(Some(self.split(batch)));
+        }
+
+        Ok(None)
+    }
+
+    pub fn into_stream(self) -> impl futures::Stream<Item = Result<RecordBatch, ArrowError>> {
+        futures::stream::unfold(self, |mut reader| async move {
+            match reader.read_batch().await {
+                Ok(Some(batch)) => Some((Ok(batch), reader)),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), reader)),
+            }
+        })
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    datatypes::{DataType, Field, Schema},
+    error::ArrowError,
+};
+use exon_common::TableSchema;
+
+use super::zarr_array::{column_type, ZarrArrayMetadata};
+
+/// The array of the contig index of each variant, read as the `chrom` column.
+pub(crate) const CONTIG_ARRAY: &str = "variant_contig";
+
+/// The array of the position of each variant, read as the `pos` column.
+pub(crate) const POSITION_ARRAY: &str = "variant_position";
+
+/// The array of the contig names, indexed by the contig array.
+pub(crate) const CONTIG_ID_ARRAY: &str = "contig_id";
+
+/// The name of the array a column of the schema is read from.
+pub(crate) fn array_name(column: &str) -> &str {
+    match column {
+        "chrom" => CONTIG_ARRAY,
+        "pos" => POSITION_ARRAY,
+        column => column,
+    }
+}
+
+/// Builds the schema of a VCF Zarr store, with one row per variant.
+///
+/// The `variant_contig` and `variant_position` arrays are read as the `chrom` and `pos`
+/// columns, and the other `variant_*` and `call_*` arrays as columns of the same name. Arrays
+/// with more than one dimension are read as nested fixed size lists, e.g. `call_genotype` has
+/// a list of ploidy alleles for each sample.
+pub struct VCFZarrSchemaBuilder {
+    file_fields: Vec<Field>,
+    partition_fields: Vec<Field>,
+}
+
+impl VCFZarrSchemaBuilder {
+    /// Create a schema builder from the arrays of a store.
+    pub fn try_new(arrays: &[(String, ZarrArrayMetadata)]) -> Result<Self, ArrowError> {
+        let position = arrays
+            .iter()
+            .find(|(name, _)| name == POSITION_ARRAY)
+            .map(|(_, metadata)| metadata)
+            .ok_or_else(|| {
+                ArrowError::SchemaError(format!(
+                    "VCF Zarr store is missing the {POSITION_ARRAY} array"
+                ))
+            })?;
+
+        for required in [CONTIG_ARRAY, CONTIG_ID_ARRAY] {
+            if !arrays.iter().any(|(name, _)| name == required) {
+                return Err(ArrowError::SchemaError(format!(
+                    "VCF Zarr store is missing the {required} array"
+                )));
+            }
+        }
+
+        let num_variants = position.shape[0];
+
+        let mut file_fields = vec![
+            Field::new("chrom", DataType::Utf8, false),
+            Field::new("pos", DataType::Int64, false),
+        ];
+
+        let mut arrays = arrays
+            .iter()
+            .filter(|(name, metadata)| {
+                (name.starts_with("variant_") || name.starts_with("call_"))
+                    && !name.ends_with("_mask")
+                    && name != CONTIG_ARRAY
+                    && name != POSITION_ARRAY
+                    && metadata.shape[0] == num_variants
+            })
+            .collect::<Vec<_>>();
+        arrays.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        for (name, metadata) in arrays {
+            let data_type = metadata.data_type()?;
+            file_fields.push(Field::new(
+                name,
+                column_type(&data_type, &metadata.shape),
+                true,
+            ));
+        }
+
+        Ok(Self {
+            file_fields,
+            partition_fields: vec![],
+        })
+    }
+
+    pub fn add_partition_fields(&mut self, partition_fields: Vec<Field>) {
+        self.partition_fields.extend(partition_fields);
+    }
+
+    pub fn build(self) -> TableSchema {
+        let mut fields = self.file_fields.clone();
+        fields.extend(self.partition_fields);
+
+        let schema = Schema::new(fields);
+
+        let projection: Vec<usize> = (0..self.file_fields.len()).collect();
+
+        TableSchema::new(Arc::new(schema), projection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(shape: &[usize], dtype: &str) -> ZarrArrayMetadata {
+        let zarray = format!(
+            r#"{{"zarr_format": 2, "shape": {shape:?}, "chunks": {shape:?}, "dtype": "{dtype}",
+                "compressor": null, "fill_value": null, "order": "C", "filters": null}}"#
+        );
+
+        ZarrArrayMetadata::try_from_slice(zarray.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_schema() -> Result<(), ArrowError> {
+        let arrays = vec![
+            ("variant_position".to_string(), metadata(&[4], "<i4")),
+            ("variant_contig".to_string(), metadata(&[4], "<i1")),
+            ("contig_id".to_string(), metadata(&[2], "<U4")),
+            ("sample_id".to_string(), metadata(&[3], "<U8")),
+            ("call_genotype".to_string(), metadata(&[4, 3, 2], "|i1")),
+            (
+                "call_genotype_mask".to_string(),
+                metadata(&[4, 3, 2], "|b1"),
+            ),
+            ("variant_quality".to_string(), metadata(&[4], "<f4")),
+        ];
+
+        let schema = VCFZarrSchemaBuilder::try_new(&arrays)?
+            .build()
+            .table_schema();
+
+        let names = schema
+            .fields()
+            .iter()
+            .map(|f| f.name().as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["chrom", "pos", "call_genotype", "variant_quality"]
+        );
+
+        assert_eq!(
+            schema.field(2).data_type(),
+            &DataType::FixedSizeList(
+                Arc::new(Field::new(
+                    "item",
+                    DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Int8, true)), 2),
+                    true
+                )),
+                3
+            )
+        );
+
+        assert!(VCFZarrSchemaBuilder::try_new(&arrays[1..]).is_err());
+
+        Ok(())
+    }
+}
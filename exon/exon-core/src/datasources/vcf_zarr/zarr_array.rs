@@ -0,0 +1,623 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{
+        ArrayRef, BooleanArray, FixedSizeListArray, Float32Array, Float64Array, Int16Array,
+        Int32Array, Int64Array, Int8Array, StringArray, UInt16Array, UInt32Array, UInt64Array,
+        UInt8Array,
+    },
+    datatypes::{DataType, Field},
+    error::ArrowError,
+};
+use serde::Deserialize;
+
+/// A codec of a Zarr array, i.e. its compressor or one of its filters.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ZarrCodec {
+    /// The numcodecs id of the codec, e.g. `blosc` or `vlen-utf8`.
+    pub id: String,
+}
+
+fn default_dimension_separator() -> String {
+    ".".to_string()
+}
+
+/// The metadata of a Zarr v2 array, from its `.zarray` file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ZarrArrayMetadata {
+    /// The version of the Zarr format.
+    pub zarr_format: u8,
+    /// The length of each dimension of the array.
+    pub shape: Vec<usize>,
+    /// The length of each dimension of a chunk.
+    pub chunks: Vec<usize>,
+    /// The numpy type string of the elements.
+    pub dtype: String,
+    /// The compressor of the chunks, if they're compressed.
+    pub compressor: Option<ZarrCodec>,
+    /// The value of the elements of missing chunks.
+    #[serde(default)]
+    pub fill_value: serde_json::Value,
+    /// The memory layout of the chunks, either `C` or `F`.
+    pub order: String,
+    /// The filters applied to the chunks before they're compressed.
+    #[serde(default)]
+    pub filters: Option<Vec<ZarrCodec>>,
+    /// The separator of the chunk indices in chunk keys.
+    #[serde(default = "default_dimension_separator")]
+    pub dimension_separator: String,
+}
+
+impl ZarrArrayMetadata {
+    /// Parse and validate the contents of a `.zarray` file.
+    pub fn try_from_slice(slice: &[u8]) -> Result<Self, ArrowError> {
+        let metadata: Self = serde_json::from_slice(slice)
+            .map_err(|e| ArrowError::ParseError(format!("Invalid .zarray metadata: {e}")))?;
+
+        if metadata.zarr_format != 2 {
+            return Err(ArrowError::NotYetImplemented(format!(
+                "Zarr format {} is not supported, only version 2 is",
+                metadata.zarr_format
+            )));
+        }
+
+        if metadata.order != "C" {
+            return Err(ArrowError::NotYetImplemented(
+                "Only Zarr arrays in C order are supported".to_string(),
+            ));
+        }
+
+        if metadata.shape.is_empty()
+            || metadata.shape.len() != metadata.chunks.len()
+            || metadata.chunks.contains(&0)
+        {
+            return Err(ArrowError::ParseError(format!(
+                "Invalid Zarr array shape {:?} with chunks {:?}",
+                metadata.shape, metadata.chunks
+            )));
+        }
+
+        Ok(metadata)
+    }
+
+    /// The type of the elements of the array.
+    pub fn data_type(&self) -> Result<ZarrDataType, ArrowError> {
+        ZarrDataType::try_new(&self.dtype, self.filters.as_deref().unwrap_or_default())
+    }
+
+    /// The number of chunks along a dimension.
+    pub fn num_chunks(&self, dimension: usize) -> usize {
+        self.shape[dimension].div_ceil(self.chunks[dimension])
+    }
+
+    /// The key of the chunk with these indices, relative to the array.
+    pub fn chunk_key(&self, chunk_indices: &[usize]) -> String {
+        chunk_indices
+            .iter()
+            .map(|index| index.to_string())
+            .collect::<Vec<_>>()
+            .join(&self.dimension_separator)
+    }
+
+    /// The indices of the chunks along every dimension but the first, which together hold the
+    /// rows of one chunk along the first dimension.
+    pub fn inner_chunk_indices(&self) -> Vec<Vec<usize>> {
+        (1..self.shape.len()).fold(vec![Vec::new()], |indices, dimension| {
+            indices
+                .into_iter()
+                .flat_map(|index| {
+                    (0..self.num_chunks(dimension)).map(move |chunk| {
+                        let mut index = index.clone();
+                        index.push(chunk);
+                        index
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// The number of elements in each row along the first dimension.
+    pub fn row_length(&self) -> usize {
+        self.shape[1..].iter().product()
+    }
+}
+
+/// The kind of the elements of a Zarr array.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ZarrKind {
+    Bool,
+    Int,
+    UInt,
+    Float,
+    /// Fixed length UTF-32 strings.
+    Unicode,
+    /// Fixed length byte strings.
+    Bytes,
+    /// Variable length strings, encoded by the `vlen-utf8` filter.
+    VlenUtf8,
+}
+
+/// The element type of a Zarr array, parsed from its numpy type string.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZarrDataType {
+    /// The kind of the elements.
+    pub kind: ZarrKind,
+    /// The size of each element in bytes, zero for variable length strings.
+    pub item_size: usize,
+    /// True if the elements are stored big endian.
+    pub big_endian: bool,
+}
+
+impl ZarrDataType {
+    /// Parse a numpy type string like `<i4`, given the filters of the array.
+    pub fn try_new(dtype: &str, filters: &[ZarrCodec]) -> Result<Self, ArrowError> {
+        let unsupported =
+            || ArrowError::NotYetImplemented(format!("Unsupported Zarr data type {dtype}"));
+
+        let mut chars = dtype.chars();
+        let big_endian = match chars.next() {
+            Some('<') | Some('|') => false,
+            Some('>') => true,
+            _ => return Err(unsupported()),
+        };
+
+        let kind = chars.next().ok_or_else(unsupported)?;
+        let size = chars.as_str();
+
+        let data_type = match (kind, size) {
+            ('O', "") if filters.iter().any(|f| f.id == "vlen-utf8") => Self {
+                kind: ZarrKind::VlenUtf8,
+                item_size: 0,
+                big_endian,
+            },
+            ('b', "1") => Self {
+                kind: ZarrKind::Bool,
+                item_size: 1,
+                big_endian,
+            },
+            ('i' | 'u', "1" | "2" | "4" | "8") | ('f', "4" | "8") => Self {
+                kind: match kind {
+                    'i' => ZarrKind::Int,
+                    'u' => ZarrKind::UInt,
+                    _ => ZarrKind::Float,
+                },
+                item_size: size.parse().map_err(|_| unsupported())?,
+                big_endian,
+            },
+            ('U', size) => Self {
+                kind: ZarrKind::Unicode,
+                item_size: 4 * size.parse::<usize>().map_err(|_| unsupported())?,
+                big_endian,
+            },
+            ('S', size) => Self {
+                kind: ZarrKind::Bytes,
+                item_size: size.parse().map_err(|_| unsupported())?,
+                big_endian: false,
+            },
+            _ => return Err(unsupported()),
+        };
+
+        if data_type.item_size == 0 && data_type.kind != ZarrKind::VlenUtf8 {
+            return Err(unsupported());
+        }
+
+        if let Some(filter) = filters.iter().find(|f| f.id != "vlen-utf8") {
+            return Err(ArrowError::NotYetImplemented(format!(
+                "Unsupported Zarr filter {}",
+                filter.id
+            )));
+        }
+
+        Ok(data_type)
+    }
+
+    /// The arrow type of a single element.
+    pub fn arrow_type(&self) -> DataType {
+        match (self.kind, self.item_size) {
+            (ZarrKind::Bool, _) => DataType::Boolean,
+            (ZarrKind::Int, 1) => DataType::Int8,
+            (ZarrKind::Int, 2) => DataType::Int16,
+            (ZarrKind::Int, 4) => DataType::Int32,
+            (ZarrKind::Int, _) => DataType::Int64,
+            (ZarrKind::UInt, 1) => DataType::UInt8,
+            (ZarrKind::UInt, 2) => DataType::UInt16,
+            (ZarrKind::UInt, 4) => DataType::UInt32,
+            (ZarrKind::UInt, _) => DataType::UInt64,
+            (ZarrKind::Float, 4) => DataType::Float32,
+            (ZarrKind::Float, _) => DataType::Float64,
+            (ZarrKind::Unicode | ZarrKind::Bytes | ZarrKind::VlenUtf8, _) => DataType::Utf8,
+        }
+    }
+
+    /// The bytes of an element with the fill value of the array, little endian.
+    fn fill_bytes(&self, fill_value: &serde_json::Value) -> Vec<u8> {
+        let mut bytes = vec![0; self.item_size];
+
+        let value = match fill_value {
+            serde_json::Value::Bool(value) => *value as u8 as f64,
+            serde_json::Value::Number(value) => match value.as_i64() {
+                Some(value) if self.kind != ZarrKind::Float => {
+                    let value = value.to_le_bytes();
+                    bytes.copy_from_slice(&value[..self.item_size.min(8)]);
+                    return bytes;
+                }
+                _ => value.as_f64().unwrap_or_default(),
+            },
+            serde_json::Value::String(value) if self.kind == ZarrKind::Float => {
+                match value.as_str() {
+                    "NaN" => f64::NAN,
+                    "Infinity" => f64::INFINITY,
+                    "-Infinity" => f64::NEG_INFINITY,
+                    _ => 0.0,
+                }
+            }
+            _ => return bytes,
+        };
+
+        match (self.kind, self.item_size) {
+            (ZarrKind::Float, 4) => bytes.copy_from_slice(&(value as f32).to_le_bytes()),
+            (ZarrKind::Float, 8) => bytes.copy_from_slice(&value.to_le_bytes()),
+            (ZarrKind::Bool, _) => bytes[0] = (value != 0.0) as u8,
+            _ => {}
+        }
+
+        bytes
+    }
+}
+
+/// The arrow type of a column holding one row of an array per value, nesting a fixed size list
+/// for each dimension after the first.
+pub fn column_type(data_type: &ZarrDataType, shape: &[usize]) -> DataType {
+    shape[1..]
+        .iter()
+        .rev()
+        .fold(data_type.arrow_type(), |inner, length| {
+            DataType::FixedSizeList(Arc::new(Field::new("item", inner, true)), *length as i32)
+        })
+}
+
+/// Decode the bytes of a `vlen-utf8` encoded chunk.
+fn decode_vlen_utf8(bytes: &[u8]) -> Result<Vec<String>, ArrowError> {
+    let invalid = || ArrowError::ParseError("Invalid vlen-utf8 chunk".to_string());
+
+    let read_u32 = |offset: usize| -> Result<usize, ArrowError> {
+        let value = bytes.get(offset..offset + 4).ok_or_else(invalid)?;
+        Ok(u32::from_le_bytes(value.try_into().map_err(|_| invalid())?) as usize)
+    };
+
+    let count = read_u32(0)?;
+    let mut offset = 4;
+
+    let mut values = Vec::with_capacity(count);
+    for _ in 0..count {
+        let length = read_u32(offset)?;
+        offset += 4;
+
+        let value = bytes.get(offset..offset + length).ok_or_else(invalid)?;
+        values.push(String::from_utf8_lossy(value).into_owned());
+        offset += length;
+    }
+
+    Ok(values)
+}
+
+/// Copy the elements of a chunk into the rows of one chunk along the first dimension.
+///
+/// `width` is the number of values per element, e.g. the item size when copying bytes.
+fn scatter<T: Clone>(
+    rows: &mut [T],
+    chunk: &[T],
+    width: usize,
+    metadata: &ZarrArrayMetadata,
+    inner_chunk_index: &[usize],
+) -> Result<(), ArrowError> {
+    let shape = &metadata.shape;
+    let chunks = &metadata.chunks;
+
+    let chunk_length = chunks.iter().product::<usize>();
+    if chunk.len() != chunk_length * width {
+        return Err(ArrowError::ParseError(format!(
+            "Zarr chunk has {} values, expected {}",
+            chunk.len(),
+            chunk_length * width
+        )));
+    }
+
+    let row_length = metadata.row_length();
+    let num_rows = rows.len() / (row_length * width).max(1);
+
+    let mut coordinates = vec![0; chunks.len()];
+    for element in 0..chunk_length {
+        let mut remainder = element;
+        for dimension in (0..chunks.len()).rev() {
+            coordinates[dimension] = remainder % chunks[dimension];
+            remainder /= chunks[dimension];
+        }
+
+        if coordinates[0] >= num_rows {
+            continue;
+        }
+
+        let mut index = coordinates[0];
+        let mut in_bounds = true;
+        for dimension in 1..chunks.len() {
+            let coordinate =
+                inner_chunk_index[dimension - 1] * chunks[dimension] + coordinates[dimension];
+
+            if coordinate >= shape[dimension] {
+                in_bounds = false;
+                break;
+            }
+
+            index = index * shape[dimension] + coordinate;
+        }
+
+        if in_bounds {
+            rows[index * width..(index + 1) * width]
+                .clone_from_slice(&chunk[element * width..(element + 1) * width]);
+        }
+    }
+
+    Ok(())
+}
+
+/// The rows of one chunk along the first dimension of an array, assembled from its chunks.
+pub struct ZarrRows {
+    metadata: ZarrArrayMetadata,
+    data_type: ZarrDataType,
+    num_rows: usize,
+    values: ZarrValues,
+}
+
+enum ZarrValues {
+    Bytes(Vec<u8>),
+    Strings(Vec<String>),
+}
+
+impl ZarrRows {
+    /// Create rows filled with the fill value of the array.
+    pub fn try_new(metadata: &ZarrArrayMetadata, num_rows: usize) -> Result<Self, ArrowError> {
+        let data_type = metadata.data_type()?;
+        let length = num_rows * metadata.row_length();
+
+        let values = match data_type.kind {
+            ZarrKind::VlenUtf8 => {
+                let fill_value = metadata.fill_value.as_str().unwrap_or_default();
+                ZarrValues::Strings(vec![fill_value.to_string(); length])
+            }
+            _ => ZarrValues::Bytes(data_type.fill_bytes(&metadata.fill_value).repeat(length)),
+        };
+
+        Ok(Self {
+            metadata: metadata.clone(),
+            data_type,
+            num_rows,
+            values,
+        })
+    }
+
+    /// Copy a decompressed chunk, with these indices along every dimension but the first, into
+    /// the rows.
+    pub fn add_chunk(
+        &mut self,
+        mut chunk: Vec<u8>,
+        inner_chunk_index: &[usize],
+    ) -> Result<(), ArrowError> {
+        match &mut self.values {
+            ZarrValues::Strings(rows) => {
+                let chunk = decode_vlen_utf8(&chunk)?;
+                scatter(rows, &chunk, 1, &self.metadata, inner_chunk_index)
+            }
+            ZarrValues::Bytes(rows) => {
+                if self.data_type.big_endian {
+                    let word_size = match self.data_type.kind {
+                        ZarrKind::Unicode => 4,
+                        _ => self.data_type.item_size,
+                    };
+
+                    chunk
+                        .chunks_exact_mut(word_size)
+                        .for_each(|word| word.reverse());
+                }
+
+                let width = self.data_type.item_size;
+                scatter(rows, &chunk, width, &self.metadata, inner_chunk_index)
+            }
+        }
+    }
+
+    /// Convert the rows to an arrow array of the column type of the array.
+    pub fn finish(self) -> Result<ArrayRef, ArrowError> {
+        let values: ArrayRef = match self.values {
+            ZarrValues::Strings(values) => Arc::new(StringArray::from(values)),
+            ZarrValues::Bytes(bytes) => bytes_to_array(&self.data_type, &bytes),
+        };
+
+        let mut array = values;
+        let mut data_type = self.data_type.arrow_type();
+
+        for length in self.metadata.shape[1..].iter().rev() {
+            let field = Arc::new(Field::new("item", data_type, true));
+            let list =
+                FixedSizeListArray::try_new(Arc::clone(&field), *length as i32, array, None)?;
+
+            data_type = DataType::FixedSizeList(field, *length as i32);
+            array = Arc::new(list);
+        }
+
+        debug_assert_eq!(array.len(), self.num_rows);
+
+        Ok(array)
+    }
+}
+
+macro_rules! primitive_array {
+    ($array:ty, $native:ty, $bytes:expr) => {
+        Arc::new(
+            $bytes
+                .chunks_exact(std::mem::size_of::<$native>())
+                .map(|value| <$native>::from_le_bytes(value.try_into().unwrap()))
+                .collect::<$array>(),
+        )
+    };
+}
+
+fn fixed_string(bytes: &[u8], data_type: &ZarrDataType) -> String {
+    match data_type.kind {
+        ZarrKind::Unicode => bytes
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .take_while(|c| *c != 0)
+            .map(|c| char::from_u32(c).unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect(),
+        _ => {
+            let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..end]).into_owned()
+        }
+    }
+}
+
+fn bytes_to_array(data_type: &ZarrDataType, bytes: &[u8]) -> ArrayRef {
+    match (data_type.kind, data_type.item_size) {
+        (ZarrKind::Bool, _) => Arc::new(
+            bytes
+                .iter()
+                .map(|b| Some(*b != 0))
+                .collect::<BooleanArray>(),
+        ),
+        (ZarrKind::Int, 1) => primitive_array!(Int8Array, i8, bytes),
+        (ZarrKind::Int, 2) => primitive_array!(Int16Array, i16, bytes),
+        (ZarrKind::Int, 4) => primitive_array!(Int32Array, i32, bytes),
+        (ZarrKind::Int, _) => primitive_array!(Int64Array, i64, bytes),
+        (ZarrKind::UInt, 1) => primitive_array!(UInt8Array, u8, bytes),
+        (ZarrKind::UInt, 2) => primitive_array!(UInt16Array, u16, bytes),
+        (ZarrKind::UInt, 4) => primitive_array!(UInt32Array, u32, bytes),
+        (ZarrKind::UInt, _) => primitive_array!(UInt64Array, u64, bytes),
+        (ZarrKind::Float, 4) => primitive_array!(Float32Array, f32, bytes),
+        (ZarrKind::Float, _) => primitive_array!(Float64Array, f64, bytes),
+        (ZarrKind::Unicode | ZarrKind::Bytes | ZarrKind::VlenUtf8, size) => Arc::new(
+            bytes
+                .chunks_exact(size)
+                .map(|value| Some(fixed_string(value, data_type)))
+                .collect::<StringArray>(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Array, AsArray};
+    use arrow::datatypes::Int16Type;
+
+    use super::*;
+
+    fn metadata(zarray: &str) -> ZarrArrayMetadata {
+        ZarrArrayMetadata::try_from_slice(zarray.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_data_type() -> Result<(), ArrowError> {
+        let vlen = [ZarrCodec {
+            id: "vlen-utf8".to_string(),
+        }];
+
+        assert_eq!(
+            ZarrDataType::try_new("<i4", &[])?.arrow_type(),
+            DataType::Int32
+        );
+        assert_eq!(
+            ZarrDataType::try_new("|b1", &[])?.arrow_type(),
+            DataType::Boolean
+        );
+        assert_eq!(ZarrDataType::try_new("<U3", &[])?.item_size, 12);
+        assert_eq!(ZarrDataType::try_new("|O", &vlen)?.kind, ZarrKind::VlenUtf8);
+
+        assert!(ZarrDataType::try_new("|O", &[]).is_err());
+        assert!(ZarrDataType::try_new("<f2", &[]).is_err());
+        assert!(ZarrDataType::try_new("<M8[s]", &[]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rows_from_inner_chunks() -> Result<(), ArrowError> {
+        // A 3 x 3 big endian array in 2 x 2 chunks, reading the rows of the first row chunk.
+        let metadata = metadata(
+            r#"{"zarr_format": 2, "shape": [3, 3], "chunks": [2, 2], "dtype": ">i2",
+                "compressor": null, "fill_value": -1, "order": "C", "filters": null}"#,
+        );
+
+        assert_eq!(metadata.inner_chunk_indices(), vec![vec![0], vec![1]]);
+        assert_eq!(metadata.chunk_key(&[0, 1]), "0.1");
+
+        let chunk = |values: [i16; 4]| values.iter().flat_map(|v| v.to_be_bytes()).collect();
+
+        let mut rows = ZarrRows::try_new(&metadata, 2)?;
+        rows.add_chunk(chunk([1, 2, 4, 5]), &[0])?;
+        rows.add_chunk(chunk([3, 0, 6, 0]), &[1])?;
+
+        let array = rows.finish()?;
+        let values = array.as_fixed_size_list().values();
+        assert_eq!(
+            values.as_primitive::<Int16Type>().values().to_vec(),
+            vec![1, 2, 3, 4, 5, 6]
+        );
+
+        // A missing chunk keeps the fill value.
+        let mut rows = ZarrRows::try_new(&metadata, 1)?;
+        rows.add_chunk(chunk([7, 8, 0, 0]), &[0])?;
+
+        let array = rows.finish()?;
+        assert_eq!(array.len(), 1);
+        assert_eq!(
+            array
+                .as_fixed_size_list()
+                .values()
+                .as_primitive::<Int16Type>()
+                .values()
+                .to_vec(),
+            vec![7, 8, -1]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_strings() -> Result<(), ArrowError> {
+        let metadata = metadata(
+            r#"{"zarr_format": 2, "shape": [2], "chunks": [2], "dtype": "<U2",
+                "compressor": null, "fill_value": "", "order": "C", "filters": null}"#,
+        );
+
+        let mut rows = ZarrRows::try_new(&metadata, 2)?;
+        rows.add_chunk(
+            [b'2', 0, 0, 0, b'0', 0, 0, 0, b'X', 0, 0, 0, 0, 0, 0, 0].to_vec(),
+            &[],
+        )?;
+
+        let array = rows.finish()?;
+        assert_eq!(array.as_string::<i32>().value(0), "20");
+        assert_eq!(array.as_string::<i32>().value(1), "X");
+
+        assert_eq!(
+            decode_vlen_utf8(&[2, 0, 0, 0, 1, 0, 0, 0, b'A', 0, 0, 0, 0])?,
+            vec!["A".to_string(), String::new()]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Read;
+
+use arrow::error::ArrowError;
+
+use super::{blosc::decompress_blosc, zarr_array::ZarrCodec};
+
+/// Decompress a chunk with the compressor of its array.
+pub fn decompress_chunk(compressor: Option<&ZarrCodec>, src: &[u8]) -> Result<Vec<u8>, ArrowError> {
+    let Some(compressor) = compressor else {
+        return Ok(src.to_vec());
+    };
+
+    let mut decompressed = Vec::new();
+
+    match compressor.id.as_str() {
+        "blosc" => return decompress_blosc(src),
+        "zlib" => {
+            flate2::read::ZlibDecoder::new(src).read_to_end(&mut decompressed)?;
+        }
+        "gzip" => {
+            flate2::read::GzDecoder::new(src).read_to_end(&mut decompressed)?;
+        }
+        "zstd" => {
+            zstd::stream::read::Decoder::new(src)?.read_to_end(&mut decompressed)?;
+        }
+        "lz4" => {
+            // numcodecs prefixes the LZ4 block with its decompressed size.
+            let size = src
+                .get(..4)
+                .map(|size| i32::from_le_bytes([size[0], size[1], size[2], size[3]]))
+                .ok_or_else(|| ArrowError::ParseError("Invalid LZ4 chunk".to_string()))?;
+
+            decompressed = lz4_flex::block::decompress(&src[4..], size.max(0) as usize)
+                .map_err(|e| ArrowError::ExternalError(Box::new(e)))?;
+        }
+        id => {
+            return Err(ArrowError::NotYetImplemented(format!(
+                "Unsupported Zarr compressor {id}"
+            )))
+        }
+    }
+
+    Ok(decompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn codec(id: &str) -> ZarrCodec {
+        ZarrCodec { id: id.to_string() }
+    }
+
+    #[test]
+    fn test_decompress_chunk() -> Result<(), ArrowError> {
+        let data = b"chr1chr1chr2chr2".to_vec();
+
+        assert_eq!(decompress_chunk(None, &data)?, data);
+
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&data)?;
+        let zlib = encoder.finish()?;
+        assert_eq!(decompress_chunk(Some(&codec("zlib")), &zlib)?, data);
+
+        let zstd = zstd::bulk::compress(&data, 3)?;
+        assert_eq!(decompress_chunk(Some(&codec("zstd")), &zstd)?, data);
+
+        let mut lz4 = (data.len() as i32).to_le_bytes().to_vec();
+        lz4.extend(lz4_flex::block::compress(&data));
+        assert_eq!(decompress_chunk(Some(&codec("lz4")), &lz4)?, data);
+
+        assert!(decompress_chunk(Some(&codec("bz2")), &data).is_err());
+
+        Ok(())
+    }
+}
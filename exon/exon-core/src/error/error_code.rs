@@ -0,0 +1,191 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{error::Error, fmt::Display, io};
+
+use arrow::error::ArrowError;
+use datafusion::error::DataFusionError;
+use exon_common::ReaderLimitError;
+
+use super::ExonError;
+
+/// What a client should do about an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorAction {
+    /// The error is likely transient, e.g. a failed network read, so the query can be retried.
+    Retry,
+
+    /// The input is bad, so the file or record can be skipped.
+    Skip,
+
+    /// The query or configuration is wrong and retrying won't help.
+    Report,
+}
+
+/// A stable code for the kind of an [`ExonError`].
+///
+/// The code strings don't change between releases, so clients can match on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ExonErrorCode {
+    /// A bug or an unexpected state.
+    Internal,
+
+    /// A failure executing the query that doesn't fit another code.
+    Execution,
+
+    /// An invalid configuration option or table option.
+    Configuration,
+
+    /// An invalid SQL statement or plan.
+    Sql,
+
+    /// An unsupported feature, function, or file format variant.
+    Unsupported,
+
+    /// A file type Exon doesn't know.
+    InvalidFileType,
+
+    /// A local IO failure.
+    Io,
+
+    /// An object store failure.
+    ObjectStore,
+
+    /// A missing file or object.
+    NotFound,
+
+    /// Running out of memory or another resource.
+    ResourcesExhausted,
+
+    /// A record that can't be parsed.
+    InvalidRecord,
+
+    /// Input that exceeds one of the reader limits.
+    ReaderLimitExceeded,
+}
+
+impl ExonErrorCode {
+    /// The stable code string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExonErrorCode::Internal => "EXON-0000",
+            ExonErrorCode::Execution => "EXON-0001",
+            ExonErrorCode::Configuration => "EXON-1001",
+            ExonErrorCode::Sql => "EXON-1002",
+            ExonErrorCode::Unsupported => "EXON-1003",
+            ExonErrorCode::InvalidFileType => "EXON-1004",
+            ExonErrorCode::Io => "EXON-2001",
+            ExonErrorCode::ObjectStore => "EXON-2002",
+            ExonErrorCode::NotFound => "EXON-2003",
+            ExonErrorCode::ResourcesExhausted => "EXON-2004",
+            ExonErrorCode::InvalidRecord => "EXON-3001",
+            ExonErrorCode::ReaderLimitExceeded => "EXON-3002",
+        }
+    }
+
+    /// The suggested action for errors with this code.
+    pub fn action(&self) -> ErrorAction {
+        match self {
+            ExonErrorCode::Io | ExonErrorCode::ObjectStore | ExonErrorCode::ResourcesExhausted => {
+                ErrorAction::Retry
+            }
+            ExonErrorCode::InvalidRecord | ExonErrorCode::ReaderLimitExceeded => ErrorAction::Skip,
+            _ => ErrorAction::Report,
+        }
+    }
+}
+
+impl Display for ExonErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+pub(crate) fn io_error_code(error: &io::Error) -> ExonErrorCode {
+    match error.kind() {
+        io::ErrorKind::NotFound => ExonErrorCode::NotFound,
+        io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => ExonErrorCode::InvalidRecord,
+        _ => ExonErrorCode::Io,
+    }
+}
+
+pub(crate) fn object_store_error_code(error: &object_store::Error) -> ExonErrorCode {
+    match error {
+        object_store::Error::NotFound { .. } => ExonErrorCode::NotFound,
+        object_store::Error::NotImplemented => ExonErrorCode::Unsupported,
+        _ => ExonErrorCode::ObjectStore,
+    }
+}
+
+/// Classify an error by walking its chain of sources.
+///
+/// Errors the readers box into Arrow errors that don't say more are taken to be bad records.
+pub(crate) fn classify(error: &(dyn Error + 'static)) -> ExonErrorCode {
+    let mut fallback = ExonErrorCode::Execution;
+    let mut current = Some(error);
+
+    while let Some(error) = current {
+        if let Some(e) = error.downcast_ref::<ExonError>() {
+            return e.code();
+        }
+
+        if error.is::<ReaderLimitError>() {
+            return ExonErrorCode::ReaderLimitExceeded;
+        }
+
+        if let Some(e) = error.downcast_ref::<object_store::Error>() {
+            return object_store_error_code(e);
+        }
+
+        if let Some(e) = error.downcast_ref::<io::Error>() {
+            fallback = io_error_code(e);
+            current = e.get_ref().map(|e| e as &(dyn Error + 'static));
+            continue;
+        }
+
+        if let Some(e) = error.downcast_ref::<DataFusionError>() {
+            match e {
+                DataFusionError::Configuration(_) => return ExonErrorCode::Configuration,
+                DataFusionError::Plan(_)
+                | DataFusionError::SQL(..)
+                | DataFusionError::SchemaError(..) => return ExonErrorCode::Sql,
+                DataFusionError::NotImplemented(_) => return ExonErrorCode::Unsupported,
+                DataFusionError::ResourcesExhausted(_) => return ExonErrorCode::ResourcesExhausted,
+                DataFusionError::Internal(_) => return ExonErrorCode::Internal,
+                DataFusionError::Execution(_) => return ExonErrorCode::Execution,
+                _ => {}
+            }
+        }
+
+        if let Some(e) = error.downcast_ref::<ArrowError>() {
+            match e {
+                ArrowError::ExternalError(_) => fallback = ExonErrorCode::InvalidRecord,
+                ArrowError::IoError(..) => fallback = ExonErrorCode::Io,
+                ArrowError::ParseError(_)
+                | ArrowError::CastError(_)
+                | ArrowError::CsvError(_)
+                | ArrowError::JsonError(_)
+                | ArrowError::InvalidArgumentError(_) => return ExonErrorCode::InvalidRecord,
+                ArrowError::NotYetImplemented(_) => return ExonErrorCode::Unsupported,
+                ArrowError::MemoryError(_) => return ExonErrorCode::ResourcesExhausted,
+                _ => return ExonErrorCode::Execution,
+            }
+        }
+
+        current = error.source();
+    }
+
+    fallback
+}
@@ -16,6 +16,7 @@ use std::{error::Error, fmt::Display, num::ParseIntError, str::Utf8Error};
 
 use arrow::error::ArrowError;
 use datafusion::{error::DataFusionError, sql::sqlparser::parser::ParserError};
+use exon_common::ReaderLimitError;
 use exon_fasta::ExonFASTAError;
 use exon_gff::ExonGFFError;
 use exon_sdf::ExonSDFError;
@@ -23,6 +24,9 @@ use noodles::bgzf::virtual_position::TryFromU64U16TupleError;
 
 use self::invalid_chrom::InvalidRegionNameError;
 
+pub use self::error_code::{ErrorAction, ExonErrorCode};
+pub use self::source_location::SourceLocation;
+
 /// Error for an invalid region.
 pub mod invalid_region;
 
@@ -32,6 +36,12 @@ pub mod invalid_interval;
 /// Error for an invalid chromosome.
 pub mod invalid_chrom;
 
+/// Stable error codes.
+mod error_code;
+
+/// Locations of errors in the input.
+mod source_location;
+
 /// Possible errors for Exon.
 #[derive(Debug)]
 pub enum ExonError {
@@ -70,6 +80,69 @@ pub enum ExonError {
 
     /// Unsupported function
     UnsupportedFunction(String),
+
+    /// The input exceeded one of the reader limits
+    ReaderLimitExceeded(ReaderLimitError),
+
+    /// An error reading a file, with where in the file it happened
+    Located(Box<ExonError>, SourceLocation),
+}
+
+impl ExonError {
+    /// The stable code of the error.
+    pub fn code(&self) -> ExonErrorCode {
+        match self {
+            ExonError::DataFusionError(error) => error_code::classify(error),
+            ExonError::ArrowError(error) => error_code::classify(error),
+            ExonError::ExecutionError(_) => ExonErrorCode::Execution,
+            ExonError::ObjectStoreError(error) => error_code::object_store_error_code(error),
+            ExonError::IOError(error) => error_code::classify(error),
+            ExonError::InvalidFileType(_) => ExonErrorCode::InvalidFileType,
+            ExonError::Configuration(_) => ExonErrorCode::Configuration,
+            ExonError::ExonGFFError(_)
+            | ExonError::ExonFASTAError(_)
+            | ExonError::ExonSDFError(_) => match self.source().map(error_code::classify) {
+                Some(ExonErrorCode::Execution) | None => ExonErrorCode::InvalidRecord,
+                Some(code) => code,
+            },
+            ExonError::ParserError(_) => ExonErrorCode::Sql,
+            ExonError::UnsupportedFunction(_) => ExonErrorCode::Unsupported,
+            ExonError::ReaderLimitExceeded(_) => ExonErrorCode::ReaderLimitExceeded,
+            ExonError::Located(error, _) => error.code(),
+        }
+    }
+
+    /// The suggested action for the error.
+    pub fn action(&self) -> ErrorAction {
+        self.code().action()
+    }
+
+    /// Where in the input the error happened, if known.
+    pub fn location(&self) -> Option<&SourceLocation> {
+        if let ExonError::Located(_, location) = self {
+            return Some(location);
+        }
+
+        let mut current = self.source();
+        while let Some(error) = current {
+            if let Some(e) = error.downcast_ref::<ExonError>() {
+                return e.location();
+            }
+
+            current = error.source();
+        }
+
+        None
+    }
+
+    /// Attach a location to the error, keeping any location it already has.
+    pub fn with_location(self, location: SourceLocation) -> Self {
+        if self.location().is_some() {
+            return self;
+        }
+
+        ExonError::Located(Box::new(self), location)
+    }
 }
 
 impl From<ParseIntError> for ExonError {
@@ -80,19 +153,56 @@ impl From<ParseIntError> for ExonError {
 
 impl From<DataFusionError> for ExonError {
     fn from(error: DataFusionError) -> Self {
-        ExonError::DataFusionError(error)
+        let error = match error {
+            DataFusionError::External(e) => match e.downcast::<ExonError>() {
+                Ok(e) => return *e,
+                Err(e) => DataFusionError::External(e),
+            },
+            DataFusionError::ArrowError(ArrowError::ExternalError(e), backtrace) => {
+                match e.downcast::<ExonError>() {
+                    Ok(e) => return *e,
+                    Err(e) => DataFusionError::ArrowError(ArrowError::ExternalError(e), backtrace),
+                }
+            }
+            error => error,
+        };
+
+        match ReaderLimitError::find(&error) {
+            Some(e) => ExonError::ReaderLimitExceeded(e.clone()),
+            None => ExonError::DataFusionError(error),
+        }
     }
 }
 
 impl From<ArrowError> for ExonError {
     fn from(error: ArrowError) -> Self {
-        ExonError::ArrowError(error)
+        let error = match error {
+            ArrowError::ExternalError(e) => match e.downcast::<ExonError>() {
+                Ok(e) => return *e,
+                Err(e) => ArrowError::ExternalError(e),
+            },
+            error => error,
+        };
+
+        match ReaderLimitError::find(&error) {
+            Some(e) => ExonError::ReaderLimitExceeded(e.clone()),
+            None => ExonError::ArrowError(error),
+        }
     }
 }
 
 impl From<std::io::Error> for ExonError {
     fn from(error: std::io::Error) -> Self {
-        ExonError::IOError(error)
+        match ReaderLimitError::find(&error) {
+            Some(e) => ExonError::ReaderLimitExceeded(e.clone()),
+            None => ExonError::IOError(error),
+        }
+    }
+}
+
+impl From<ReaderLimitError> for ExonError {
+    fn from(error: ReaderLimitError) -> Self {
+        ExonError::ReaderLimitExceeded(error)
     }
 }
 
@@ -177,11 +287,28 @@ impl Display for ExonError {
             ExonError::UnsupportedFunction(error) => write!(f, "UnsupportedFunction: {}", error),
             ExonError::ExonFASTAError(error) => write!(f, "ExonFASTAError: {}", error),
             ExonError::ExonSDFError(error) => write!(f, "ExonSDFError: {}", error),
+            ExonError::ReaderLimitExceeded(error) => write!(f, "ReaderLimitExceeded: {}", error),
+            ExonError::Located(error, location) => write!(f, "{} in {}", error, location),
         }
     }
 }
 
-impl Error for ExonError {}
+impl Error for ExonError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExonError::DataFusionError(error) => Some(error),
+            ExonError::ArrowError(error) => Some(error),
+            ExonError::ObjectStoreError(error) => Some(error),
+            ExonError::IOError(error) => Some(error),
+            ExonError::ExonGFFError(error) => Some(error),
+            ExonError::ExonFASTAError(error) => Some(error),
+            ExonError::ExonSDFError(error) => Some(error),
+            ExonError::ReaderLimitExceeded(error) => Some(error),
+            ExonError::Located(error, _) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 impl From<ExonError> for DataFusionError {
     fn from(error: ExonError) -> Self {
@@ -190,7 +317,7 @@ impl From<ExonError> for DataFusionError {
             ExonError::ArrowError(error) => DataFusionError::ArrowError(error, None),
             ExonError::ExecutionError(error) => DataFusionError::Execution(error),
             ExonError::Configuration(error) => DataFusionError::Configuration(error),
-            _ => DataFusionError::Execution(format!("ExonError: {}", error)),
+            error => DataFusionError::External(Box::new(error)),
         }
     }
 }
@@ -0,0 +1,67 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Display;
+
+/// Where in the input an error happened.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The path of the file being read.
+    pub path: String,
+
+    /// The number of records returned from the file before the error.
+    pub record_number: Option<u64>,
+
+    /// The offset in bytes into the decompressed data of the scanned range, if the reader knows
+    /// it.
+    pub byte_offset: Option<u64>,
+}
+
+impl SourceLocation {
+    /// Create a new source location for a file.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the record number.
+    pub fn with_record_number(mut self, record_number: u64) -> Self {
+        self.record_number = Some(record_number);
+        self
+    }
+
+    /// Set the byte offset.
+    pub fn with_byte_offset(mut self, byte_offset: u64) -> Self {
+        self.byte_offset = Some(byte_offset);
+        self
+    }
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path)?;
+
+        if let Some(record_number) = self.record_number {
+            write!(f, " after record {}", record_number)?;
+        }
+
+        if let Some(byte_offset) = self.byte_offset {
+            write!(f, " at byte {}", byte_offset)?;
+        }
+
+        Ok(())
+    }
+}
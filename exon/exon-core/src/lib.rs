@@ -29,7 +29,7 @@
 //! [`datasources`]: datasources
 
 mod session_context;
-pub use session_context::ExonSession;
+pub use session_context::{ExonSession, IGVSessionExport, DEFAULT_IGV_SESSION_TEMPLATE};
 
 #[allow(clippy::cmp_owned)]
 mod config;
@@ -47,20 +47,30 @@ pub mod physical_optimizer;
 /// Physical plan augmentations for Exon.
 pub mod physical_plan;
 
+/// Structured events for planning decisions.
+pub mod planner_events;
+
 /// Utilities for moving data across the FFI boundary.
 #[cfg(feature = "ffi")]
 pub mod ffi;
 
+/// Export tracing spans to an OpenTelemetry collector.
+#[cfg(feature = "otlp")]
+pub mod telemetry;
+
 /// Runtime environment for Exon.
 mod runtime_env;
 
-pub use runtime_env::ExonRuntimeEnvExt;
+pub use runtime_env::{
+    find_checksum_error, CachedObject, CachingObjectStore, ChecksumError, ChecksumKind,
+    ChecksumObjectStore, ExonRuntimeEnvExt, ObjectCache, RetryObjectStore, RetryPolicy,
+};
 
 /// Error types for Exon.
 mod error;
 
-pub use error::ExonError;
 pub use error::Result;
+pub use error::{ErrorAction, ExonError, ExonErrorCode, SourceLocation};
 
 /// Utilities for working with stream bgzf files.
 pub mod streaming_bgzf;
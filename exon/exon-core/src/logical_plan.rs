@@ -12,12 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod audit_scan_node;
 mod exon_data_sink_node;
+mod genome_build_rule;
 
 use std::sync::Arc;
 
+pub(crate) use audit_scan_node::AuditScanNode;
 use datafusion::logical_expr::{Extension, LogicalPlan, UserDefinedLogicalNodeCore};
 pub(crate) use exon_data_sink_node::ExonDataSinkLogicalPlanNode;
+pub(crate) use genome_build_rule::GenomeBuildRule;
 
 pub trait DfExtensionNode: Sized + UserDefinedLogicalNodeCore {
     fn into_extension(self) -> Extension {
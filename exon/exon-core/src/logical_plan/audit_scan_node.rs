@@ -0,0 +1,85 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use datafusion::{
+    common::DFSchemaRef,
+    error::{DataFusionError, Result},
+    logical_expr::{Expr, LogicalPlan, UserDefinedLogicalNodeCore},
+};
+
+use super::DfExtensionNode;
+
+/// A node above a table scan that records the scan in the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
+pub(crate) struct AuditScanNode {
+    pub input: LogicalPlan,
+    pub table: String,
+    pub filters: Vec<String>,
+}
+
+impl AuditScanNode {
+    /// Create a new node over a table scan.
+    pub(crate) fn try_new(input: LogicalPlan) -> Result<Self> {
+        let LogicalPlan::TableScan(scan) = &input else {
+            return Err(DataFusionError::Internal(
+                "AuditScanNode expects a table scan".to_string(),
+            ));
+        };
+
+        let table = scan.table_name.to_string();
+        let filters = scan.filters.iter().map(|f| f.to_string()).collect();
+
+        Ok(Self {
+            input,
+            table,
+            filters,
+        })
+    }
+}
+
+impl DfExtensionNode for AuditScanNode {}
+
+impl UserDefinedLogicalNodeCore for AuditScanNode {
+    fn name(&self) -> &str {
+        "AuditScanNode"
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.input]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        self.input.schema()
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "AuditScanNode: table={}", self.table)
+    }
+
+    fn with_exprs_and_inputs(&self, _exprs: Vec<Expr>, inputs: Vec<LogicalPlan>) -> Result<Self> {
+        let input = inputs.into_iter().next().ok_or_else(|| {
+            DataFusionError::Internal("AuditScanNode expects one input".to_string())
+        })?;
+
+        Ok(Self {
+            input,
+            table: self.table.clone(),
+            filters: self.filters.clone(),
+        })
+    }
+}
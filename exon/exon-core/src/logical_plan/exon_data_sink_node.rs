@@ -83,6 +83,37 @@ impl ExonDataSinkLogicalPlanNode {
 
         inferred_type.transpose()
     }
+
+    /// Returns the string value of the option with the given key, if it was supplied.
+    pub(crate) fn string_option(&self, key: &str) -> crate::Result<Option<String>> {
+        self.options
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| match v {
+                Value::SingleQuotedString(s) | Value::DoubleQuotedString(s) => Ok(s.clone()),
+                Value::Number(n, _) => Ok(n.clone()),
+                Value::Boolean(b) => Ok(b.to_string()),
+                _ => Err(ExonError::ExecutionError(format!(
+                    "Invalid value for option {}",
+                    key
+                ))),
+            })
+            .transpose()
+    }
+
+    /// Returns the boolean value of the option with the given key, if it was supplied.
+    pub(crate) fn bool_option(&self, key: &str) -> crate::Result<Option<bool>> {
+        self.string_option(key)?
+            .map(|v| match v.to_lowercase().as_str() {
+                "true" => Ok(true),
+                "false" => Ok(false),
+                _ => Err(ExonError::ExecutionError(format!(
+                    "Invalid boolean value for option {}: {}",
+                    key, v
+                ))),
+            })
+            .transpose()
+    }
 }
 
 impl From<ExonCopyToStatement> for ExonDataSinkLogicalPlanNode {
@@ -0,0 +1,505 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::BTreeSet, sync::Arc};
+
+use datafusion::{
+    common::{
+        tree_node::{Transformed, TreeNode, TreeNodeRecursion},
+        Column,
+    },
+    config::ConfigOptions,
+    error::{DataFusionError, Result},
+    logical_expr::{cast, utils::conjunction, Expr, Filter, LogicalPlan, Projection, ScalarUDF},
+    optimizer::AnalyzerRule,
+};
+use exon_common::GENOME_BUILD_METADATA_KEY;
+
+use crate::{
+    config::ExonConfigExtension,
+    udfs::liftover::{
+        LiftoverChain, LiftoverOutput, LiftoverRegistry, LiftoverUdf, LiftoverUnmapped,
+    },
+};
+
+/// The sequence name columns of the tables Exon reads and their position columns, which are
+/// lifted across builds.
+const COORDINATE_COLUMNS: &[(&str, &[&str])] = &[
+    ("chrom", &["pos"]),
+    ("reference_sequence_name", &["start", "end"]),
+    ("seqname", &["start", "end"]),
+    ("reference", &["start", "end"]),
+];
+
+/// The build a liftover projection lifts its input to, if the plan is one.
+fn liftover_build(plan: &LogicalPlan) -> Option<String> {
+    let LogicalPlan::Projection(projection) = plan else {
+        return None;
+    };
+
+    let mut genome_build = None;
+
+    for expr in projection.expr.iter() {
+        expr.apply(|expr| {
+            if let Expr::ScalarFunction(function) = expr {
+                if let Some(udf) = function.func.inner().as_any().downcast_ref::<LiftoverUdf>() {
+                    genome_build = Some(udf.to_build().to_string());
+                    return Ok(TreeNodeRecursion::Stop);
+                }
+            }
+
+            Ok(TreeNodeRecursion::Continue)
+        })
+        .ok()?;
+
+        if genome_build.is_some() {
+            break;
+        }
+    }
+
+    genome_build
+}
+
+/// The genome builds of the tables scanned by the plan, where tables that don't declare one are
+/// on the default build, if any, and lifted tables are on the build they're lifted to.
+fn genome_builds(plan: &LogicalPlan, default_build: Option<&str>) -> Result<BTreeSet<String>> {
+    let mut genome_builds = BTreeSet::new();
+
+    plan.apply(|node| {
+        if let Some(genome_build) = liftover_build(node) {
+            genome_builds.insert(genome_build);
+            return Ok(TreeNodeRecursion::Jump);
+        }
+
+        if let LogicalPlan::TableScan(scan) = node {
+            let schema = scan.source.schema();
+
+            let genome_build = schema
+                .metadata()
+                .get(GENOME_BUILD_METADATA_KEY)
+                .map(String::as_str)
+                .or(default_build);
+
+            if let Some(genome_build) = genome_build {
+                genome_builds.insert(genome_build.to_string());
+            }
+        }
+
+        Ok(TreeNodeRecursion::Continue)
+    })?;
+
+    Ok(genome_builds)
+}
+
+/// Lift the coordinate columns of a plan with a chain, in a projection that keeps its other
+/// columns, or `None` if it has no coordinate columns.
+///
+/// The sequence name of an interval is lifted from its start, and its end is unmapped if it lifts
+/// to another sequence.
+fn lift_plan(
+    plan: LogicalPlan,
+    chain: Arc<LiftoverChain>,
+    from_build: &str,
+    to_build: &str,
+    unmapped: LiftoverUnmapped,
+) -> Result<Option<LogicalPlan>> {
+    let udf = |output| {
+        ScalarUDF::from(LiftoverUdf::new(
+            Arc::clone(&chain),
+            from_build,
+            to_build,
+            output,
+            unmapped,
+        ))
+    };
+    let lift_sequence = udf(LiftoverOutput::Sequence);
+    let lift_position = udf(LiftoverOutput::Position);
+
+    let schema = Arc::clone(plan.schema());
+    let mut lifted = Vec::new();
+
+    for (qualifier, field) in schema.iter() {
+        let Some((_, positions)) = COORDINATE_COLUMNS
+            .iter()
+            .find(|(sequence, _)| field.name() == sequence)
+        else {
+            continue;
+        };
+
+        let sequence = Column::new(qualifier.cloned(), field.name());
+        let positions = positions
+            .iter()
+            .map(|position| Column::new(qualifier.cloned(), *position))
+            .filter(|position| schema.has_column(position))
+            .collect::<Vec<_>>();
+
+        let Some(anchor) = positions.first() else {
+            continue;
+        };
+
+        let args = vec![Expr::Column(sequence.clone()), Expr::Column(anchor.clone())];
+        lifted.push((sequence.clone(), lift_sequence.call(args)));
+
+        for position in positions.iter() {
+            let mut args = vec![
+                Expr::Column(sequence.clone()),
+                Expr::Column(position.clone()),
+            ];
+            if position != anchor {
+                args.push(Expr::Column(anchor.clone()));
+            }
+
+            lifted.push((position.clone(), lift_position.call(args)));
+        }
+    }
+
+    if lifted.is_empty() {
+        return Ok(None);
+    }
+
+    let exprs = schema
+        .iter()
+        .map(|(qualifier, field)| {
+            let column = Column::new(qualifier.cloned(), field.name());
+
+            match lifted.iter().find(|(lifted, _)| *lifted == column) {
+                Some((_, expr)) => cast(expr.clone(), field.data_type().clone())
+                    .alias_qualified(qualifier.cloned(), field.name()),
+                None => Expr::Column(column),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let plan = LogicalPlan::Projection(Projection::try_new(exprs, Arc::new(plan))?);
+
+    if unmapped != LiftoverUnmapped::Drop {
+        return Ok(Some(plan));
+    }
+
+    let predicate = conjunction(
+        lifted
+            .into_iter()
+            .map(|(column, _)| Expr::Column(column).is_not_null()),
+    );
+
+    match predicate {
+        Some(predicate) => Ok(Some(LogicalPlan::Filter(Filter::try_new(
+            predicate,
+            Arc::new(plan),
+        )?))),
+        None => Ok(Some(plan)),
+    }
+}
+
+/// The build of the tables in a set of builds, if they're on a single build.
+fn single_build(genome_builds: &BTreeSet<String>) -> Option<&str> {
+    match genome_builds.len() {
+        1 => genome_builds.first().map(String::as_str),
+        _ => None,
+    }
+}
+
+/// Checks and lifts over joins of tables declared on different genome builds.
+///
+/// When `exon.genome_build_liftover` is set and a chain between the builds of the two sides of a
+/// join is registered, the coordinate columns of one side are lifted to the build of the other,
+/// and `exon.liftover_unmapped` sets what happens to the positions that aren't in the chain.
+/// Otherwise, the join fails when `exon.genome_build_check` is set, as the coordinates of the
+/// sides can't be compared.
+///
+/// The tables that don't declare a build are taken to be on `exon.genome_build`.
+#[derive(Debug, Default)]
+pub struct GenomeBuildRule {
+    liftovers: Arc<LiftoverRegistry>,
+}
+
+impl GenomeBuildRule {
+    /// Create a rule that lifts over joins with the chains in a registry.
+    pub fn new(liftovers: Arc<LiftoverRegistry>) -> Self {
+        Self { liftovers }
+    }
+
+    /// Lift one side of a join to the build of the other, if each side is on a single build and a
+    /// chain between them is registered.
+    fn lift_join(
+        &self,
+        join: &LogicalPlan,
+        left: &BTreeSet<String>,
+        right: &BTreeSet<String>,
+        unmapped: LiftoverUnmapped,
+    ) -> Result<Option<LogicalPlan>> {
+        let (Some(left_build), Some(right_build)) = (single_build(left), single_build(right))
+        else {
+            return Ok(None);
+        };
+
+        if left_build == right_build {
+            return Ok(None);
+        }
+
+        let mut inputs = join.inputs().into_iter().cloned().collect::<Vec<_>>();
+
+        let (index, chain, from_build, to_build) = match self.liftovers.get(left_build, right_build)
+        {
+            Some(chain) => (0, chain, left_build, right_build),
+            None => match self.liftovers.get(right_build, left_build) {
+                Some(chain) => (1, chain, right_build, left_build),
+                None => return Ok(None),
+            },
+        };
+
+        let input = inputs[index].clone();
+        let Some(lifted) = lift_plan(input, chain, from_build, to_build, unmapped)? else {
+            return Ok(None);
+        };
+        inputs[index] = lifted;
+
+        Ok(Some(join.with_new_exprs(join.expressions(), inputs)?))
+    }
+}
+
+impl AnalyzerRule for GenomeBuildRule {
+    fn analyze(&self, plan: LogicalPlan, config: &ConfigOptions) -> Result<LogicalPlan> {
+        let Some(exon_config) = config.extensions.get::<ExonConfigExtension>() else {
+            return Ok(plan);
+        };
+
+        if !exon_config.genome_build_check && !exon_config.genome_build_liftover {
+            return Ok(plan);
+        }
+
+        let default_build = exon_config.genome_build();
+        let unmapped = exon_config.liftover_unmapped()?;
+
+        // The schemas of the plans above a lifted join are recomputed, as lifted columns are
+        // nullable.
+        let mut lifted_any = false;
+
+        plan.transform_up(|node| {
+            let node = match lifted_any {
+                true => node.recompute_schema()?,
+                false => node,
+            };
+
+            let LogicalPlan::Join(join) = &node else {
+                return Ok(Transformed::new_transformed(node, lifted_any));
+            };
+
+            let left = genome_builds(&join.left, default_build.as_deref())?;
+            let right = genome_builds(&join.right, default_build.as_deref())?;
+
+            if exon_config.genome_build_liftover {
+                if let Some(lifted) = self.lift_join(&node, &left, &right, unmapped)? {
+                    lifted_any = true;
+                    return Ok(Transformed::yes(lifted));
+                }
+            }
+
+            if exon_config.genome_build_check {
+                for left_build in left.iter() {
+                    if let Some(right_build) = right.iter().find(|b| *b != left_build) {
+                        return Err(DataFusionError::Plan(format!(
+                            "Cannot join tables on genome build {} with tables on genome build {}",
+                            left_build, right_build
+                        )));
+                    }
+                }
+            }
+
+            Ok(Transformed::new_transformed(node, lifted_any))
+        })
+        .map(|transformed| transformed.data)
+    }
+
+    fn name(&self) -> &str {
+        "exon_genome_build_check"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{array::AsArray, datatypes::Int64Type};
+    use exon_test::test_path;
+
+    use crate::ExonSession;
+
+    async fn register_vcf_tables(ctx: &ExonSession) -> Result<(), Box<dyn std::error::Error>> {
+        // index.vcf is on hs37d5, i.e. GRCh37, and the broad VCF is on GRCh38.
+        let tables = [
+            ("grch37", test_path("vcf", "index.vcf")),
+            ("grch38", test_path("vcf-broad", "00-common_all.head.vcf")),
+        ];
+
+        for (name, path) in tables {
+            let sql = format!(
+                "CREATE EXTERNAL TABLE {} STORED AS VCF LOCATION '{}'",
+                name,
+                path.to_str().ok_or("Invalid path")?
+            );
+            ctx.session.sql(&sql).await?;
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_join_across_genome_builds() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        register_vcf_tables(&ctx).await?;
+
+        let sql =
+            "SELECT COUNT(*) FROM grch37 a JOIN grch38 b ON a.chrom = b.chrom AND a.pos = b.pos";
+
+        // Without the check the join is planned as usual.
+        ctx.session.sql(sql).await?.create_physical_plan().await?;
+
+        ctx.session
+            .sql("SET exon.genome_build_check = true")
+            .await?;
+
+        let err = ctx
+            .session
+            .sql(sql)
+            .await?
+            .create_physical_plan()
+            .await
+            .err()
+            .ok_or("expected a genome build error")?;
+        assert!(err.to_string().contains("genome build GRCh37"));
+
+        // A self join is on a single build.
+        let sql = "SELECT COUNT(*) FROM grch38 a JOIN grch38 b ON a.chrom = b.chrom";
+        ctx.session.sql(sql).await?.create_physical_plan().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_join_with_declared_genome_build() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        register_vcf_tables(&ctx).await?;
+
+        ctx.session
+            .sql("SET exon.genome_build_check = true")
+            .await?;
+
+        // The session's build applies to tables without one, e.g. this BED file.
+        ctx.session.sql("SET exon.genome_build = 'hg19'").await?;
+
+        let path = test_path("bed", "test.bed");
+        let sql = format!(
+            "CREATE EXTERNAL TABLE regions STORED AS BED LOCATION '{}'",
+            path.to_str().ok_or("Invalid path")?
+        );
+        ctx.session.sql(&sql).await?;
+
+        let sql =
+            "SELECT COUNT(*) FROM grch37 a JOIN regions r ON a.chrom = r.reference_sequence_name";
+        ctx.session.sql(sql).await?.create_physical_plan().await?;
+
+        let sql =
+            "SELECT COUNT(*) FROM grch38 a JOIN regions r ON a.chrom = r.reference_sequence_name";
+        assert!(ctx
+            .session
+            .sql(sql)
+            .await?
+            .create_physical_plan()
+            .await
+            .is_err());
+
+        // A build declared on the table takes precedence over the session's.
+        let sql = format!(
+            "CREATE EXTERNAL TABLE regions38 STORED AS BED OPTIONS (genome_build 'GRCh38') LOCATION '{}'",
+            path.to_str().ok_or("Invalid path")?
+        );
+        ctx.session.sql(&sql).await?;
+
+        let sql =
+            "SELECT COUNT(*) FROM grch38 a JOIN regions38 r ON a.chrom = r.reference_sequence_name";
+        ctx.session.sql(sql).await?.create_physical_plan().await?;
+
+        Ok(())
+    }
+
+    async fn count(ctx: &ExonSession, sql: &str) -> Result<i64, Box<dyn std::error::Error>> {
+        let batches = ctx.session.sql(sql).await?.collect().await?;
+        let count = batches[0].column(0).as_primitive::<Int64Type>().value(0);
+
+        Ok(count)
+    }
+
+    #[tokio::test]
+    async fn test_join_with_liftover() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        register_vcf_tables(&ctx).await?;
+
+        // The chain lifts 1:9999919-9999928 on GRCh37 to 1:10177-10186 on GRCh38.
+        let path = test_path("chain", "GRCh37ToGRCh38.over.chain");
+        ctx.register_liftover("hg19", "hg38", path.to_str().ok_or("Invalid path")?)
+            .await?;
+
+        ctx.session
+            .sql("SET exon.genome_build_check = true")
+            .await?;
+        ctx.session
+            .sql("SET exon.genome_build_liftover = true")
+            .await?;
+
+        let sql = "SELECT a.chrom, a.pos FROM grch37 a JOIN grch38 b ON a.chrom = b.chrom AND a.pos = b.pos";
+        let batches = ctx.session.sql(sql).await?.collect().await?;
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 1);
+        assert_eq!(
+            batches[0].column(1).as_primitive::<Int64Type>().value(0),
+            10177
+        );
+
+        // Either side may be lifted, and the unmapped positions of the left side are dropped.
+        let sql = "SELECT COUNT(*) FROM grch38 b RIGHT JOIN grch37 a ON a.chrom = b.chrom AND a.pos = b.pos";
+        assert_eq!(count(&ctx, sql).await?, 10);
+
+        ctx.session
+            .sql("SET exon.liftover_unmapped = 'null'")
+            .await?;
+        assert_eq!(count(&ctx, sql).await?, 621);
+
+        ctx.session
+            .sql("SET exon.liftover_unmapped = 'error'")
+            .await?;
+        assert!(ctx.session.sql(sql).await?.collect().await.is_err());
+
+        // Without a chain to lift with, the join still fails the check.
+        ctx.session
+            .sql("SET exon.genome_build = 'CanFam3.1'")
+            .await?;
+
+        let path = test_path("bed", "test.bed");
+        let sql = format!(
+            "CREATE EXTERNAL TABLE regions STORED AS BED LOCATION '{}'",
+            path.to_str().ok_or("Invalid path")?
+        );
+        ctx.session.sql(&sql).await?;
+
+        let sql =
+            "SELECT COUNT(*) FROM grch37 a JOIN regions r ON a.chrom = r.reference_sequence_name";
+        assert!(ctx
+            .session
+            .sql(sql)
+            .await?
+            .create_physical_plan()
+            .await
+            .is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use datafusion::{
+    common::tree_node::{Transformed, TreeNode},
+    config::ConfigOptions,
+    error::Result,
+    physical_optimizer::PhysicalOptimizerRule,
+    physical_plan::ExecutionPlan,
+};
+use noodles::core::{Position, Region};
+
+use crate::{
+    config::ExonConfigExtension,
+    datasources::{
+        bam::IndexedBAMScan, indexed_file::indexed_bgzf_file::IndexedBGZFFile,
+        vcf::IndexedVCFScanner,
+    },
+    physical_plan::index_metadata_exec::IndexMetadataExec,
+    planner_events::PLANNER_EVENT_TARGET,
+};
+
+/// Whether the region covers its whole reference sequence, i.e. has no bounds.
+fn covers_reference_sequence(region: &Region) -> bool {
+    let interval = region.interval();
+
+    interval.start().unwrap_or(Position::MIN) == Position::MIN && interval.end().is_none()
+}
+
+fn rewrite(plan: Arc<dyn ExecutionPlan>) -> Result<Transformed<Arc<dyn ExecutionPlan>>> {
+    // Only scans that output no columns, e.g. under a `COUNT(*)`, can be answered by a count.
+    if !plan.schema().fields().is_empty() {
+        return Ok(Transformed::no(plan));
+    }
+
+    let any = plan.as_any();
+
+    // GFF indexes are left out: tabix counts lines the GFF reader skips.
+    let (base_config, regions, indexed_file) =
+        if let Some(scan) = any.downcast_ref::<IndexedBAMScan>() {
+            (
+                scan.base_config(),
+                scan.regions().to_vec(),
+                IndexedBGZFFile::Bam,
+            )
+        } else if let Some(scan) = any.downcast_ref::<IndexedVCFScanner>() {
+            (
+                scan.base_config(),
+                vec![scan.region().clone()],
+                IndexedBGZFFile::Vcf,
+            )
+        } else {
+            return Ok(Transformed::no(plan));
+        };
+
+    if base_config.limit.is_some() || !regions.iter().all(covers_reference_sequence) {
+        return Ok(Transformed::no(plan));
+    }
+
+    tracing::debug!(
+        target: PLANNER_EVENT_TARGET,
+        event = "index_count",
+        index = ?indexed_file,
+        regions = ?regions,
+    );
+
+    let exec = IndexMetadataExec::new(Arc::clone(&plan), base_config, regions, indexed_file);
+
+    Ok(Transformed::yes(Arc::new(exec)))
+}
+
+/// Replaces indexed BAM and VCF scans with no output columns over whole reference sequences with
+/// an [`IndexMetadataExec`], so counting queries are answered from the indexes.
+#[derive(Debug, Default)]
+pub struct IndexCountRule {}
+
+impl PhysicalOptimizerRule for IndexCountRule {
+    fn optimize(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        config: &ConfigOptions,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let enabled = config
+            .extensions
+            .get::<ExonConfigExtension>()
+            .map_or(true, |config| config.index_count_pushdown);
+
+        if !enabled {
+            return Ok(plan);
+        }
+
+        Ok(plan.transform_up(rewrite)?.data)
+    }
+
+    fn name(&self) -> &str {
+        "exon_index_count"
+    }
+
+    fn schema_check(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Int64Array, StringArray};
+    use datafusion::physical_plan::{collect, ExecutionPlan};
+    use exon_test::test_path;
+
+    use crate::{physical_plan::index_metadata_exec::IndexMetadataExec, ExonSession};
+
+    fn has_index_metadata_exec(plan: &Arc<dyn ExecutionPlan>) -> bool {
+        plan.as_any().is::<IndexMetadataExec>()
+            || plan.children().into_iter().any(has_index_metadata_exec)
+    }
+
+    #[tokio::test]
+    async fn test_count_from_bam_index() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        let table_path = test_path("bam", "test.bam");
+        let table_path = table_path.to_str().ok_or("Invalid path")?;
+
+        let sql = format!(
+            "CREATE EXTERNAL TABLE bam STORED AS INDEXED_BAM LOCATION '{}'",
+            table_path
+        );
+        ctx.session.sql(&sql).await?;
+
+        let sql = "SELECT COUNT(*) FROM bam WHERE bam_region_filter('chr1', reference) = true";
+        let plan = ctx.session.sql(sql).await?.create_physical_plan().await?;
+        assert!(has_index_metadata_exec(&plan));
+
+        let batches = collect(plan, ctx.session.task_ctx()).await?;
+        let count = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .ok_or("expected an int64 count")?
+            .value(0);
+        assert_eq!(count, 61);
+
+        let batches = ctx
+            .session
+            .sql(&format!("EXPLAIN {sql}"))
+            .await?
+            .collect()
+            .await?;
+        let physical_plan = batches
+            .iter()
+            .find_map(|batch| {
+                let plan_type = batch.column(0).as_any().downcast_ref::<StringArray>()?;
+                let plan = batch.column(1).as_any().downcast_ref::<StringArray>()?;
+
+                (0..batch.num_rows())
+                    .find(|&i| plan_type.value(i) == "physical_plan")
+                    .map(|i| plan.value(i).to_string())
+            })
+            .ok_or("expected a physical plan")?;
+        assert!(physical_plan.contains("IndexMetadataExec: files=1, regions=1"));
+
+        // A bounded region is counted from the records.
+        let sql = "SELECT COUNT(*) FROM bam WHERE bam_region_filter('chr1:1-12209145', reference, start, end) = true";
+        let plan = ctx.session.sql(sql).await?.create_physical_plan().await?;
+        assert!(!has_index_metadata_exec(&plan));
+
+        Ok(())
+    }
+}
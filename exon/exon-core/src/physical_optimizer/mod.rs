@@ -11,3 +11,6 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+
+/// A rule that answers counting queries over indexed region scans from the index metadata.
+pub mod index_count_rule;
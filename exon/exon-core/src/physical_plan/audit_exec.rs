@@ -0,0 +1,352 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt,
+    fs::{File, OpenOptions},
+    io::Write,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use arrow::datatypes::SchemaRef;
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::{
+        object_store::ObjectStoreRegistry, runtime_env::RuntimeEnv, FunctionRegistry,
+        SendableRecordBatchStream, TaskContext,
+    },
+    physical_plan::{
+        stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionPlan,
+        PlanProperties,
+    },
+};
+use exon_io::{AuditObjectStore, ObjectReads};
+use futures::StreamExt;
+use object_store::ObjectStore;
+use serde_json::json;
+use url::Url;
+
+/// An append-only JSON lines file of audit log entries.
+///
+/// The file can be queried like any other newline delimited JSON file, e.g. with
+/// `CREATE EXTERNAL TABLE audit STORED AS JSON LOCATION 'audit.jsonl'`.
+#[derive(Debug)]
+pub struct AuditLog {
+    file: Mutex<File>,
+}
+
+impl AuditLog {
+    /// Open the audit log at a local path, creating it if it doesn't exist.
+    pub fn try_new(path: &str) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Append an entry to the log.
+    pub fn write(&self, entry: &serde_json::Value) -> Result<()> {
+        let mut line = entry.to_string();
+        line.push('\n');
+
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| DataFusionError::Execution("Audit log lock poisoned".to_string()))?;
+        file.write_all(line.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+/// An object store registry that records the reads of every store it returns.
+#[derive(Debug)]
+struct AuditObjectStoreRegistry {
+    inner: Arc<dyn ObjectStoreRegistry>,
+    reads: Arc<ObjectReads>,
+}
+
+impl ObjectStoreRegistry for AuditObjectStoreRegistry {
+    fn register_store(
+        &self,
+        url: &Url,
+        store: Arc<dyn ObjectStore>,
+    ) -> Option<Arc<dyn ObjectStore>> {
+        self.inner.register_store(url, store)
+    }
+
+    fn get_store(&self, url: &Url) -> Result<Arc<dyn ObjectStore>> {
+        let store = self.inner.get_store(url)?;
+
+        Ok(Arc::new(AuditObjectStore::new(
+            store,
+            Arc::clone(&self.reads),
+        )))
+    }
+}
+
+/// Writes the audit log entry of a partition when its stream is dropped, so partitions that are
+/// stopped early, e.g. by a limit, are still recorded.
+struct AuditEntry {
+    log: Arc<AuditLog>,
+    entry: serde_json::Value,
+    reads: Arc<ObjectReads>,
+}
+
+impl Drop for AuditEntry {
+    fn drop(&mut self) {
+        let reads = self
+            .reads
+            .take()
+            .into_iter()
+            .map(|read| {
+                json!({
+                    "store": read.store,
+                    "location": read.location.to_string(),
+                    "range": [read.range.start, read.range.end],
+                })
+            })
+            .collect::<Vec<_>>();
+
+        self.entry["reads"] = serde_json::Value::Array(reads);
+
+        if let Err(e) = self.log.write(&self.entry) {
+            tracing::warn!("Failed to write audit log entry: {}", e);
+        }
+    }
+}
+
+/// An execution plan that records the table, filters, and object store reads of a scan
+/// partition in the audit log.
+#[derive(Debug)]
+pub struct AuditExec {
+    input: Arc<dyn ExecutionPlan>,
+    table: String,
+    filters: Vec<String>,
+    user: String,
+    log: Arc<AuditLog>,
+}
+
+impl AuditExec {
+    /// Create a new exec that audits the scan of `table`.
+    pub fn new(
+        input: Arc<dyn ExecutionPlan>,
+        table: String,
+        filters: Vec<String>,
+        user: String,
+        log: Arc<AuditLog>,
+    ) -> Self {
+        Self {
+            input,
+            table,
+            filters,
+            user,
+            log,
+        }
+    }
+
+    /// The task context of the scan, with an object store registry that records reads.
+    ///
+    /// Scans don't call functions, so only the scalar functions are carried over.
+    fn audited_context(
+        &self,
+        context: &TaskContext,
+        reads: Arc<ObjectReads>,
+    ) -> Result<Arc<TaskContext>> {
+        let runtime = context.runtime_env();
+
+        let audited_runtime = RuntimeEnv {
+            memory_pool: Arc::clone(&runtime.memory_pool),
+            disk_manager: Arc::clone(&runtime.disk_manager),
+            cache_manager: Arc::clone(&runtime.cache_manager),
+            object_store_registry: Arc::new(AuditObjectStoreRegistry {
+                inner: Arc::clone(&runtime.object_store_registry),
+                reads,
+            }),
+        };
+
+        let scalar_functions = context
+            .udfs()
+            .into_iter()
+            .map(|name| Ok((name.clone(), context.udf(&name)?)))
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        Ok(Arc::new(TaskContext::new(
+            context.task_id(),
+            context.session_id(),
+            context.session_config().clone(),
+            scalar_functions,
+            HashMap::new(),
+            HashMap::new(),
+            Arc::new(audited_runtime),
+        )))
+    }
+}
+
+impl DisplayAs for AuditExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AuditExec: table={}", self.table)
+    }
+}
+
+impl ExecutionPlan for AuditExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "AuditExec"
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        self.input.properties()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![true]
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.as_slice() {
+            [input] => Ok(Arc::new(Self::new(
+                Arc::clone(input),
+                self.table.clone(),
+                self.filters.clone(),
+                self.user.clone(),
+                Arc::clone(&self.log),
+            ))),
+            _ => Err(DataFusionError::Internal(
+                "AuditExec expects one child".to_string(),
+            )),
+        }
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let reads = Arc::new(ObjectReads::default());
+        let audited_context = self.audited_context(&context, Arc::clone(&reads))?;
+
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        let entry = AuditEntry {
+            log: Arc::clone(&self.log),
+            entry: json!({
+                "timestamp_ms": timestamp_ms,
+                "session_id": context.session_id(),
+                "user": self.user,
+                "table": self.table,
+                "partition": partition,
+                "filters": self.filters,
+            }),
+            reads,
+        };
+
+        let input = self.input.execute(partition, audited_context)?;
+
+        let stream = input.map(move |batch| {
+            let _entry = &entry;
+            batch
+        });
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.input.schema(),
+            stream,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ExonSession;
+
+    #[tokio::test]
+    async fn test_audit_log_records_scans() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let fastq_path = exon_test::test_path("fastq", "test.fastq");
+        let log_path = std::env::temp_dir().join("test_audit_log.jsonl");
+        if log_path.exists() {
+            std::fs::remove_file(&log_path)?;
+        }
+
+        let sql = format!(
+            "CREATE EXTERNAL TABLE fastq_table STORED AS FASTQ LOCATION '{}'",
+            fastq_path.to_str().ok_or("invalid path")?
+        );
+        ctx.sql(&sql).await?.collect().await?;
+
+        let sql = format!(
+            "SET exon.audit_log_path = '{}'",
+            log_path.to_str().ok_or("invalid path")?
+        );
+        ctx.session.sql(&sql).await?;
+        ctx.session.sql("SET exon.audit_user = 'alice'").await?;
+
+        let batches = ctx
+            .sql("SELECT name FROM fastq_table WHERE name = 'SEQ_ID'")
+            .await?
+            .collect()
+            .await?;
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 1);
+
+        let entries = std::fs::read_to_string(&log_path)?
+            .lines()
+            .map(serde_json::from_str::<serde_json::Value>)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        assert!(!entries.is_empty());
+
+        let entry = &entries[0];
+        assert_eq!(entry["table"], "fastq_table");
+        assert_eq!(entry["user"], "alice");
+
+        let reads = entries
+            .iter()
+            .flat_map(|e| e["reads"].as_array().cloned().unwrap_or_default())
+            .collect::<Vec<_>>();
+
+        assert!(reads.iter().any(|read| read["location"]
+            .as_str()
+            .is_some_and(|l| l.ends_with("fastq/test.fastq"))));
+
+        std::fs::remove_file(log_path)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, fmt, sync::Arc};
+
+use arrow::datatypes::SchemaRef;
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_expr::EquivalenceProperties,
+    physical_plan::{
+        stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionPlan,
+        PlanProperties,
+    },
+};
+use exon_common::{ColumnTransformDirection, ColumnTransforms};
+use futures::StreamExt;
+
+/// An execution plan that applies the session's column transformers to the batches of its input.
+///
+/// Scans are wrapped to decrypt or tokenize stored values and the inputs of sinks are wrapped to
+/// encrypt or tokenize values before they're written. The transformed columns lose any ordering
+/// of the input.
+#[derive(Debug)]
+pub struct ColumnTransformExec {
+    input: Arc<dyn ExecutionPlan>,
+    transforms: ColumnTransforms,
+    direction: ColumnTransformDirection,
+    properties: PlanProperties,
+}
+
+impl ColumnTransformExec {
+    /// Create a new exec that transforms the batches of the input.
+    pub fn new(
+        input: Arc<dyn ExecutionPlan>,
+        transforms: ColumnTransforms,
+        direction: ColumnTransformDirection,
+    ) -> Self {
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(input.schema()),
+            input.properties().output_partitioning().clone(),
+            input.properties().execution_mode(),
+        );
+
+        Self {
+            input,
+            transforms,
+            direction,
+            properties,
+        }
+    }
+}
+
+impl DisplayAs for ColumnTransformExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ColumnTransformExec: direction={:?}", self.direction)
+    }
+}
+
+impl ExecutionPlan for ColumnTransformExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "ColumnTransformExec"
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.as_slice() {
+            [input] => Ok(Arc::new(Self::new(
+                Arc::clone(input),
+                self.transforms.clone(),
+                self.direction,
+            ))),
+            _ => Err(DataFusionError::Internal(
+                "ColumnTransformExec expects one child".to_string(),
+            )),
+        }
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let input = self.input.execute(partition, context)?;
+
+        let transforms = self.transforms.clone();
+        let direction = self.direction;
+
+        let stream = input.map(move |batch| Ok(transforms.transform_batch(batch?, direction)?));
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.input.schema(),
+            stream,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{RecordBatch, StringArray},
+        datatypes::{DataType, Field, Schema},
+    };
+    use datafusion::physical_plan::{collect, memory::MemoryExec};
+    use exon_common::{
+        ColumnTransformDirection, ColumnTransformerRegistry, ColumnTransforms,
+        StaticColumnTransformKey,
+    };
+
+    use super::ColumnTransformExec;
+    use crate::ExonSession;
+
+    #[tokio::test]
+    async fn test_column_transform_exec_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let schema = Arc::new(Schema::new(vec![Field::new("name", DataType::Utf8, true)]));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![Arc::new(StringArray::from(vec![
+                Some("a"),
+                None,
+                Some("b"),
+            ]))],
+        )?;
+
+        let registry = ColumnTransformerRegistry::default();
+        registry.set_key_provider(Arc::new(StaticColumnTransformKey::new(vec![0xab; 32])));
+
+        let transforms =
+            ColumnTransforms::try_new("name=aes_gcm", &registry)?.ok_or("missing transforms")?;
+
+        let input = MemoryExec::try_new(&[vec![batch.clone()]], Arc::clone(&schema), None)?;
+        let encrypt = ColumnTransformExec::new(
+            Arc::new(input),
+            transforms.clone(),
+            ColumnTransformDirection::Sink,
+        );
+        let decrypt = ColumnTransformExec::new(
+            Arc::new(encrypt),
+            transforms,
+            ColumnTransformDirection::Scan,
+        );
+
+        let batches = collect(Arc::new(decrypt), ctx.session.task_ctx()).await?;
+
+        assert_eq!(batches, vec![batch]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, fmt, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, Int32Builder, RecordBatch, StringBuilder},
+    compute::cast,
+    datatypes::{DataType, Field, Schema, SchemaRef},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_expr::EquivalenceProperties,
+    physical_plan::{
+        stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionPlan,
+        PlanProperties,
+    },
+};
+use futures::StreamExt;
+
+/// The barcodes of the samples that reads are assigned to, and where to find the barcode in a
+/// read.
+#[derive(Debug, Clone)]
+pub struct SampleBarcodes {
+    samples: Vec<String>,
+    barcodes: Vec<Vec<u8>>,
+    start: usize,
+    length: usize,
+    max_mismatches: usize,
+}
+
+impl SampleBarcodes {
+    /// Create the sample barcodes from `(sample, barcode)` pairs.
+    ///
+    /// The barcode region starts at the 0-based `start` of the sequence and is `length` bases
+    /// long, by default the length of the barcodes, which then must all have the same length.
+    pub fn try_new(
+        barcodes: Vec<(String, String)>,
+        start: usize,
+        length: Option<usize>,
+        max_mismatches: usize,
+    ) -> Result<Self> {
+        if barcodes.is_empty() {
+            return Err(DataFusionError::Plan(
+                "Demultiplexing requires at least one sample barcode".to_string(),
+            ));
+        }
+
+        let length = length.unwrap_or(barcodes[0].1.len());
+
+        if let Some((sample, barcode)) = barcodes.iter().find(|(_, b)| b.len() != length) {
+            return Err(DataFusionError::Plan(format!(
+                "The barcode {} of sample {} isn't {} bases long",
+                barcode, sample, length
+            )));
+        }
+
+        let (samples, barcodes) = barcodes
+            .into_iter()
+            .map(|(sample, barcode)| (sample, barcode.to_ascii_uppercase().into_bytes()))
+            .unzip();
+
+        Ok(Self {
+            samples,
+            barcodes,
+            start,
+            length,
+            max_mismatches,
+        })
+    }
+
+    /// Assign a sequence to the sample whose barcode has the fewest mismatches with its barcode
+    /// region, returning the sample's index and the number of mismatches.
+    ///
+    /// A sequence isn't assigned if no barcode is within the maximum number of mismatches, if two
+    /// samples tie for the fewest mismatches, or if it's too short to have a barcode region. An N
+    /// in the sequence counts as a mismatch.
+    fn assign(&self, sequence: &[u8]) -> Option<(usize, usize)> {
+        let region = sequence.get(self.start..self.start + self.length)?;
+
+        let mut best: Option<(usize, usize)> = None;
+        let mut tied = false;
+
+        for (i, barcode) in self.barcodes.iter().enumerate() {
+            let mismatches = region
+                .iter()
+                .zip(barcode)
+                .filter(|(base, expected)| base.to_ascii_uppercase() != **expected)
+                .count();
+
+            match best {
+                Some((_, fewest)) if mismatches > fewest => {}
+                Some((_, fewest)) if mismatches == fewest => tied = true,
+                _ => {
+                    best = Some((i, mismatches));
+                    tied = false;
+                }
+            }
+        }
+
+        best.filter(|(_, mismatches)| !tied && *mismatches <= self.max_mismatches)
+    }
+
+    /// Add the sample and mismatch columns to a batch with a sequence column.
+    fn demultiplex(&self, schema: &SchemaRef, batch: RecordBatch) -> Result<RecordBatch> {
+        let sequences = batch.column_by_name("sequence").ok_or_else(|| {
+            DataFusionError::Execution("Demultiplexing requires a sequence column".to_string())
+        })?;
+        let sequences = cast(sequences, &DataType::Utf8)?;
+        let sequences = sequences.as_string::<i32>();
+
+        let mut samples = StringBuilder::with_capacity(batch.num_rows(), 0);
+        let mut mismatches = Int32Builder::with_capacity(batch.num_rows());
+
+        for i in 0..sequences.len() {
+            let assignment = sequences
+                .is_valid(i)
+                .then(|| self.assign(sequences.value(i).as_bytes()))
+                .flatten();
+
+            match assignment {
+                Some((sample, n)) => {
+                    samples.append_value(&self.samples[sample]);
+                    mismatches.append_value(n as i32);
+                }
+                None => {
+                    samples.append_null();
+                    mismatches.append_null();
+                }
+            }
+        }
+
+        let mut columns = batch.columns().to_vec();
+        columns.push(Arc::new(samples.finish()) as ArrayRef);
+        columns.push(Arc::new(mismatches.finish()) as ArrayRef);
+
+        Ok(RecordBatch::try_new(Arc::clone(schema), columns)?)
+    }
+}
+
+/// The schema of the demultiplexed reads, the input schema plus the sample and barcode_mismatches
+/// columns.
+pub fn demultiplex_schema(input_schema: &Schema) -> SchemaRef {
+    let mut fields = input_schema.fields().to_vec();
+    fields.push(Arc::new(Field::new("sample", DataType::Utf8, true)));
+    fields.push(Arc::new(Field::new(
+        "barcode_mismatches",
+        DataType::Int32,
+        true,
+    )));
+
+    Arc::new(Schema::new(fields))
+}
+
+/// An execution plan that assigns the reads of a FASTQ table to samples by their barcodes.
+///
+/// Each batch is demultiplexed as it's read, so the input is streamed and its partitioning is
+/// kept.
+#[derive(Debug)]
+pub struct DemultiplexExec {
+    input: Arc<dyn ExecutionPlan>,
+    barcodes: Arc<SampleBarcodes>,
+    schema: SchemaRef,
+    properties: PlanProperties,
+}
+
+impl DemultiplexExec {
+    /// Create a new exec over an input with a sequence column.
+    pub fn try_new(input: Arc<dyn ExecutionPlan>, barcodes: Arc<SampleBarcodes>) -> Result<Self> {
+        let input_schema = input.schema();
+
+        if input_schema.column_with_name("sequence").is_none() {
+            return Err(DataFusionError::Plan(
+                "Demultiplexing requires a FASTQ table with a sequence column".to_string(),
+            ));
+        }
+
+        let schema = demultiplex_schema(&input_schema);
+
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(Arc::clone(&schema)),
+            input.properties().output_partitioning().clone(),
+            input.properties().execution_mode(),
+        );
+
+        Ok(Self {
+            input,
+            barcodes,
+            schema,
+            properties,
+        })
+    }
+}
+
+impl DisplayAs for DemultiplexExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "DemultiplexExec: samples={}, max_mismatches={}",
+            self.barcodes.samples.len(),
+            self.barcodes.max_mismatches
+        )
+    }
+}
+
+impl ExecutionPlan for DemultiplexExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "DemultiplexExec"
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![true]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let [input]: [Arc<dyn ExecutionPlan>; 1] = children.try_into().map_err(|_| {
+            DataFusionError::Internal("DemultiplexExec expects one child".to_string())
+        })?;
+
+        Ok(Arc::new(Self::try_new(input, Arc::clone(&self.barcodes))?))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let input = self.input.execute(partition, context)?;
+
+        let schema = Arc::clone(&self.schema);
+        let barcodes = Arc::clone(&self.barcodes);
+
+        let stream = input.map(move |batch| barcodes.demultiplex(&schema, batch?));
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            Arc::clone(&self.schema),
+            stream,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SampleBarcodes;
+
+    #[test]
+    fn test_assign() -> Result<(), Box<dyn std::error::Error>> {
+        let barcodes = SampleBarcodes::try_new(
+            vec![
+                ("s1".to_string(), "ACGT".to_string()),
+                ("s2".to_string(), "TTTT".to_string()),
+                ("s3".to_string(), "ACGA".to_string()),
+            ],
+            2,
+            None,
+            1,
+        )?;
+
+        assert_eq!(barcodes.assign(b"NNTTTTGG"), Some((1, 0)));
+        assert_eq!(barcodes.assign(b"nnttatgg"), Some((1, 1)));
+
+        // Ties between s1 and s3, too many mismatches, and too short.
+        assert_eq!(barcodes.assign(b"NNACGCGG"), None);
+        assert_eq!(barcodes.assign(b"NNGGGGGG"), None);
+        assert_eq!(barcodes.assign(b"NNACG"), None);
+
+        assert!(SampleBarcodes::try_new(
+            vec![
+                ("s1".to_string(), "ACGT".to_string()),
+                ("s2".to_string(), "TTT".to_string()),
+            ],
+            0,
+            None,
+            1,
+        )
+        .is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,530 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::{any::Any, collections::HashMap, fmt, sync::Arc};
+
+use arrow::{
+    array::{
+        Array, ArrayRef, AsArray, Int64Builder, MapArray, RecordBatch, StringBuilder, UInt32Builder,
+    },
+    compute::{cast, take},
+    datatypes::{DataType, Field, Int64Type, Schema, SchemaRef},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_expr::EquivalenceProperties,
+    physical_plan::{
+        stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, Distribution,
+        ExecutionMode, ExecutionPlan, Partitioning, PlanProperties,
+    },
+};
+use futures::{TryFutureExt, TryStreamExt};
+
+/// The attributes naming the biotype of a gene, in GTF files from Ensembl and GENCODE.
+const BIOTYPE_ATTRIBUTES: [&str; 3] = ["gene_biotype", "gene_type", "biotype"];
+
+/// The schema of variants annotated with their overlapping genes, the variant columns followed
+/// by the gene_id, gene_biotype, region, and exon_distance of the gene.
+pub fn gene_overlap_schema(variant_schema: &Schema) -> SchemaRef {
+    let mut fields = variant_schema
+        .fields()
+        .iter()
+        .map(|f| f.as_ref().clone())
+        .collect::<Vec<_>>();
+
+    fields.extend([
+        Field::new("gene_id", DataType::Utf8, true),
+        Field::new("gene_biotype", DataType::Utf8, true),
+        Field::new("region", DataType::Utf8, false),
+        Field::new("exon_distance", DataType::Int64, true),
+    ]);
+
+    Arc::new(Schema::new(fields))
+}
+
+/// Get a column of a batch cast to `data_type`.
+fn cast_column(batch: &RecordBatch, name: &str, data_type: &DataType) -> Result<ArrayRef> {
+    let column = batch.column_by_name(name).ok_or_else(|| {
+        DataFusionError::Execution(format!("Gene overlap requires a {} column", name))
+    })?;
+
+    Ok(cast(column, data_type)?)
+}
+
+/// Get the first value of one of the attributes of a feature, whose values are strings or lists
+/// of strings.
+fn attribute(attributes: &MapArray, i: usize, names: &[&str]) -> Option<String> {
+    if attributes.is_null(i) {
+        return None;
+    }
+
+    let entries = attributes.value(i);
+    let keys = entries.column(0).as_string_opt::<i32>()?;
+    let values = entries.column(1);
+
+    names.iter().find_map(|name| {
+        let j = (0..keys.len()).find(|j| keys.is_valid(*j) && keys.value(*j) == *name)?;
+
+        if values.is_null(j) {
+            return None;
+        }
+
+        match values.data_type() {
+            DataType::Utf8 => Some(values.as_string::<i32>().value(j).to_string()),
+            DataType::List(_) => {
+                let list = values.as_list::<i32>().value(j);
+                let list = list.as_string_opt::<i32>()?;
+
+                (!list.is_empty() && list.is_valid(0)).then(|| list.value(0).to_string())
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Sort and merge overlapping and adjacent closed intervals.
+fn merge_intervals(intervals: &mut Vec<(i64, i64)>) {
+    intervals.sort_unstable();
+
+    let mut merged: Vec<(i64, i64)> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals.drain(..) {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    *intervals = merged;
+}
+
+/// The distance in bases from the closed interval `[start, end]` to the nearest of the sorted
+/// and merged `intervals`, zero if it overlaps one, or `None` if there are none.
+fn interval_distance(intervals: &[(i64, i64)], start: i64, end: i64) -> Option<i64> {
+    let i = intervals.partition_point(|(_, interval_end)| *interval_end < start);
+
+    let after = intervals.get(i).map(|(interval_start, _)| {
+        if *interval_start <= end {
+            0
+        } else {
+            interval_start - end
+        }
+    });
+    let before = i.checked_sub(1).map(|i| start - intervals[i].1);
+
+    match (before, after) {
+        (Some(before), Some(after)) => Some(before.min(after)),
+        (before, after) => before.or(after),
+    }
+}
+
+/// A gene, spanning the features with its gene_id.
+#[derive(Debug)]
+struct Gene {
+    start: i64,
+    end: i64,
+    gene_id: String,
+    biotype: Option<String>,
+    /// The gene's sorted and merged exons.
+    exons: Vec<(i64, i64)>,
+}
+
+/// The genes of a reference sequence.
+#[derive(Debug, Default)]
+struct ReferenceGenes {
+    /// The genes sorted by start.
+    genes: Vec<Gene>,
+
+    /// The largest end of the genes up to and including each gene, to stop looking back for
+    /// overlapping genes.
+    max_ends: Vec<i64>,
+
+    /// The sorted and merged exons of all the genes.
+    exons: Vec<(i64, i64)>,
+}
+
+impl ReferenceGenes {
+    /// The genes overlapping the closed interval `[start, end]`, in order of their start.
+    fn overlapping(&self, start: i64, end: i64) -> Vec<&Gene> {
+        let n = self.genes.partition_point(|gene| gene.start <= end);
+
+        let mut genes = (0..n)
+            .rev()
+            .take_while(|i| self.max_ends[*i] >= start)
+            .map(|i| &self.genes[i])
+            .filter(|gene| gene.end >= start)
+            .collect::<Vec<_>>();
+
+        genes.reverse();
+        genes
+    }
+}
+
+/// The genes of an annotation by reference sequence.
+#[derive(Debug, Default)]
+struct GeneIndex {
+    references: HashMap<String, ReferenceGenes>,
+}
+
+/// Collects the features of an annotation into genes.
+#[derive(Debug, Default)]
+struct GeneIndexBuilder {
+    genes: HashMap<(String, String), Gene>,
+}
+
+impl GeneIndexBuilder {
+    /// Add the features of a batch of a GTF table, features without a gene_id are skipped.
+    fn push_batch(&mut self, batch: &RecordBatch) -> Result<()> {
+        let seqnames = cast_column(batch, "seqname", &DataType::Utf8)?;
+        let seqnames = seqnames.as_string::<i32>();
+        let types = cast_column(batch, "type", &DataType::Utf8)?;
+        let types = types.as_string::<i32>();
+        let starts = cast_column(batch, "start", &DataType::Int64)?;
+        let starts = starts.as_primitive::<Int64Type>();
+        let ends = cast_column(batch, "end", &DataType::Int64)?;
+        let ends = ends.as_primitive::<Int64Type>();
+
+        let attributes = batch
+            .column_by_name("attributes")
+            .and_then(|attributes| attributes.as_map_opt())
+            .ok_or_else(|| {
+                DataFusionError::Execution(
+                    "Gene overlap requires a map of attributes column".to_string(),
+                )
+            })?;
+
+        for i in 0..batch.num_rows() {
+            if seqnames.is_null(i) || starts.is_null(i) || ends.is_null(i) {
+                continue;
+            }
+
+            let Some(gene_id) = attribute(attributes, i, &["gene_id"]) else {
+                continue;
+            };
+
+            let (start, end) = (starts.value(i), ends.value(i));
+
+            let gene = self
+                .genes
+                .entry((seqnames.value(i).to_string(), gene_id.clone()))
+                .or_insert_with(|| Gene {
+                    start,
+                    end,
+                    gene_id,
+                    biotype: None,
+                    exons: Vec::new(),
+                });
+
+            gene.start = gene.start.min(start);
+            gene.end = gene.end.max(end);
+
+            if gene.biotype.is_none() {
+                gene.biotype = attribute(attributes, i, &BIOTYPE_ATTRIBUTES);
+            }
+
+            if types.is_valid(i) && types.value(i) == "exon" {
+                gene.exons.push((start, end));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn build(self) -> GeneIndex {
+        let mut references = HashMap::<String, ReferenceGenes>::new();
+
+        for ((reference, _), mut gene) in self.genes {
+            merge_intervals(&mut gene.exons);
+
+            let reference = references.entry(reference).or_default();
+            reference.exons.extend(gene.exons.iter().copied());
+            reference.genes.push(gene);
+        }
+
+        for reference in references.values_mut() {
+            reference.genes.sort_unstable_by(|a, b| {
+                (a.start, a.end, &a.gene_id).cmp(&(b.start, b.end, &b.gene_id))
+            });
+
+            let mut max_end = i64::MIN;
+            reference.max_ends = reference
+                .genes
+                .iter()
+                .map(|gene| {
+                    max_end = max_end.max(gene.end);
+                    max_end
+                })
+                .collect();
+
+            merge_intervals(&mut reference.exons);
+        }
+
+        GeneIndex { references }
+    }
+}
+
+/// Annotate a batch of variants with their overlapping genes.
+///
+/// A variant gets a row for each gene it overlaps, which is exonic if it overlaps one of the
+/// gene's exons and intronic otherwise, or genic if the gene has no exons. A variant outside any
+/// gene gets a single intergenic row with the distance to the nearest exon.
+fn annotate_batch(
+    schema: &SchemaRef,
+    genes: &GeneIndex,
+    batch: &RecordBatch,
+) -> Result<RecordBatch> {
+    let chroms = cast_column(batch, "chrom", &DataType::Utf8)?;
+    let chroms = chroms.as_string::<i32>();
+    let positions = cast_column(batch, "pos", &DataType::Int64)?;
+    let positions = positions.as_primitive::<Int64Type>();
+    let references = match batch.column_by_name("ref") {
+        Some(references) => Some(cast(references, &DataType::Utf8)?),
+        None => None,
+    };
+    let references = references.as_ref().map(|r| r.as_string::<i32>());
+
+    let mut indices = UInt32Builder::with_capacity(batch.num_rows());
+    let mut gene_ids = StringBuilder::new();
+    let mut biotypes = StringBuilder::new();
+    let mut regions = StringBuilder::new();
+    let mut distances = Int64Builder::new();
+
+    for i in 0..batch.num_rows() {
+        let reference_genes = (chroms.is_valid(i) && positions.is_valid(i))
+            .then(|| genes.references.get(chroms.value(i)))
+            .flatten();
+
+        let Some(reference_genes) = reference_genes else {
+            indices.append_value(i as u32);
+            gene_ids.append_null();
+            biotypes.append_null();
+            regions.append_value("intergenic");
+            distances.append_null();
+            continue;
+        };
+
+        let start = positions.value(i);
+        let length = references
+            .filter(|r| r.is_valid(i))
+            .map_or(1, |r| r.value(i).len().max(1) as i64);
+        let end = start + length - 1;
+
+        let overlapping = reference_genes.overlapping(start, end);
+
+        if overlapping.is_empty() {
+            indices.append_value(i as u32);
+            gene_ids.append_null();
+            biotypes.append_null();
+            regions.append_value("intergenic");
+            distances.append_option(interval_distance(&reference_genes.exons, start, end));
+            continue;
+        }
+
+        for gene in overlapping {
+            let distance = interval_distance(&gene.exons, start, end);
+
+            let region = match distance {
+                Some(0) => "exonic",
+                Some(_) => "intronic",
+                None => "genic",
+            };
+
+            indices.append_value(i as u32);
+            gene_ids.append_value(&gene.gene_id);
+            biotypes.append_option(gene.biotype.as_deref());
+            regions.append_value(region);
+            distances.append_option(distance);
+        }
+    }
+
+    let indices = indices.finish();
+
+    let mut columns = batch
+        .columns()
+        .iter()
+        .map(|column| Ok(take(column.as_ref(), &indices, None)?))
+        .collect::<Result<Vec<ArrayRef>>>()?;
+
+    columns.push(Arc::new(gene_ids.finish()));
+    columns.push(Arc::new(biotypes.finish()));
+    columns.push(Arc::new(regions.finish()));
+    columns.push(Arc::new(distances.finish()));
+
+    Ok(RecordBatch::try_new(Arc::clone(schema), columns)?)
+}
+
+/// An execution plan that annotates variants with the genes they overlap, the gene's biotype,
+/// whether the variant is exonic, intronic, or intergenic, and its distance to the nearest exon.
+///
+/// The genes are collected from the features of the second input, a GTF table, by their gene_id,
+/// and held in memory while the variants of the first input are streamed. Positions are 1-based
+/// and a variant spans the bases of its reference allele.
+#[derive(Debug)]
+pub struct GeneOverlapExec {
+    variants: Arc<dyn ExecutionPlan>,
+    genes: Arc<dyn ExecutionPlan>,
+    schema: SchemaRef,
+    properties: PlanProperties,
+}
+
+impl GeneOverlapExec {
+    /// Create a new exec over variants with chrom and pos columns, and optionally a ref column,
+    /// and GTF features with seqname, type, start, end, and attributes columns.
+    pub fn try_new(
+        variants: Arc<dyn ExecutionPlan>,
+        genes: Arc<dyn ExecutionPlan>,
+    ) -> Result<Self> {
+        let variant_schema = variants.schema();
+
+        for name in ["chrom", "pos"] {
+            if variant_schema.column_with_name(name).is_none() {
+                return Err(DataFusionError::Plan(format!(
+                    "Gene overlap requires a VCF table with a {} column",
+                    name
+                )));
+            }
+        }
+
+        let gene_schema = genes.schema();
+
+        for name in ["seqname", "type", "start", "end", "attributes"] {
+            if gene_schema.column_with_name(name).is_none() {
+                return Err(DataFusionError::Plan(format!(
+                    "Gene overlap requires a GTF table with a {} column",
+                    name
+                )));
+            }
+        }
+
+        let schema = gene_overlap_schema(&variant_schema);
+
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(Arc::clone(&schema)),
+            Partitioning::UnknownPartitioning(1),
+            ExecutionMode::Bounded,
+        );
+
+        Ok(Self {
+            variants,
+            genes,
+            schema,
+            properties,
+        })
+    }
+}
+
+impl DisplayAs for GeneOverlapExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "GeneOverlapExec")
+    }
+}
+
+impl ExecutionPlan for GeneOverlapExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "GeneOverlapExec"
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        vec![Distribution::SinglePartition; 2]
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false; 2]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.variants, &self.genes]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let [variants, genes]: [Arc<dyn ExecutionPlan>; 2] = children.try_into().map_err(|_| {
+            DataFusionError::Internal("GeneOverlapExec expects two children".to_string())
+        })?;
+
+        Ok(Arc::new(Self::try_new(variants, genes)?))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "GeneOverlapExec has a single partition, got {}",
+                partition
+            )));
+        }
+
+        let variants = self.variants.execute(0, Arc::clone(&context))?;
+        let genes = self.genes.execute(0, context)?;
+
+        let schema = Arc::clone(&self.schema);
+
+        let gene_index = genes
+            .try_fold(
+                GeneIndexBuilder::default(),
+                |mut builder, batch| async move {
+                    builder.push_batch(&batch)?;
+                    Ok(builder)
+                },
+            )
+            .map_ok(|builder| builder.build());
+
+        // Build the gene index, then annotate the variants as they're read.
+        let stream = futures::stream::once(gene_index.map_ok(move |gene_index| {
+            variants.and_then(move |batch| {
+                futures::future::ready(annotate_batch(&schema, &gene_index, &batch))
+            })
+        }))
+        .try_flatten();
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            Arc::clone(&self.schema),
+            stream,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interval_distance;
+
+    #[test]
+    fn test_interval_distance() {
+        let exons = [(10, 20), (40, 50)];
+
+        assert_eq!(interval_distance(&exons, 15, 15), Some(0));
+        assert_eq!(interval_distance(&exons, 18, 25), Some(0));
+        assert_eq!(interval_distance(&exons, 25, 25), Some(5));
+        assert_eq!(interval_distance(&exons, 35, 36), Some(4));
+        assert_eq!(interval_distance(&exons, 5, 5), Some(5));
+        assert_eq!(interval_distance(&exons, 60, 60), Some(10));
+        assert_eq!(interval_distance(&[], 60, 60), None);
+    }
+}
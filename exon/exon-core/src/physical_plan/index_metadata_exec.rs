@@ -0,0 +1,257 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, fmt, sync::Arc};
+
+use arrow::{
+    datatypes::SchemaRef,
+    record_batch::{RecordBatch, RecordBatchOptions},
+};
+use datafusion::{
+    datasource::physical_plan::FileScanConfig,
+    error::{DataFusionError, Result},
+    execution::{object_store::ObjectStoreUrl, SendableRecordBatchStream, TaskContext},
+    physical_plan::{
+        stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionPlan,
+        PlanProperties,
+    },
+};
+use futures::{lock::Mutex, stream::BoxStream, StreamExt, TryStreamExt};
+use itertools::Itertools;
+use noodles::core::Region;
+use object_store::{path::Path, ObjectMeta, ObjectStore};
+
+use crate::datasources::indexed_file::indexed_bgzf_file::{
+    count_records_from_index, IndexedBGZFFile,
+};
+
+/// An execution plan that replaces an indexed region scan with no output columns, e.g. the scan
+/// under `SELECT COUNT(*) FROM bam WHERE bam_region_filter('chr1', reference)`.
+///
+/// The record count comes from the metadata pseudo-bins of the BAI or tabix indexes, so the data
+/// blocks aren't read. The regions must each cover a whole reference sequence. The first
+/// partition emits batches with no columns that add up to the count, and the others are empty.
+/// If an index has no metadata, each partition runs the original scan instead.
+///
+/// The count is read once per execution of the plan and shared by its partitions. It's kept for
+/// later executions only while the files and their indexes are unchanged.
+#[derive(Debug)]
+pub struct IndexMetadataExec {
+    /// The scan this replaces.
+    input: Arc<dyn ExecutionPlan>,
+
+    /// The object store of the files.
+    object_store_url: ObjectStoreUrl,
+
+    /// The files of the scan.
+    files: Vec<ObjectMeta>,
+
+    /// The regions of the scan.
+    regions: Vec<Region>,
+
+    /// The index format of the files.
+    indexed_file: IndexedBGZFFile,
+
+    /// The record count, once read from the indexes.
+    count: Arc<Mutex<Option<CachedCount>>>,
+}
+
+/// A record count read from the indexes, keyed on the metadata of the files and indexes it was
+/// read from.
+#[derive(Debug)]
+struct CachedCount {
+    /// The metadata of each file followed by its index.
+    metadata: Vec<ObjectMeta>,
+
+    /// The record count, `None` if an index has no metadata.
+    count: Option<u64>,
+}
+
+impl IndexMetadataExec {
+    /// Create an exec that answers `input`, a scan of the files in `base_config` over `regions`,
+    /// from the indexes.
+    pub(crate) fn new(
+        input: Arc<dyn ExecutionPlan>,
+        base_config: &FileScanConfig,
+        regions: Vec<Region>,
+        indexed_file: IndexedBGZFFile,
+    ) -> Self {
+        let files = base_config
+            .file_groups
+            .iter()
+            .flatten()
+            .map(|f| f.object_meta.clone())
+            .unique_by(|m| m.location.clone())
+            .collect();
+
+        Self {
+            input,
+            object_store_url: base_config.object_store_url.clone(),
+            files,
+            regions,
+            indexed_file,
+            count: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+/// The current metadata of each file followed by its index.
+async fn file_metadata(
+    object_store: &Arc<dyn ObjectStore>,
+    files: &[ObjectMeta],
+    indexed_file: &IndexedBGZFFile,
+) -> Result<Vec<ObjectMeta>> {
+    let mut metadata = Vec::with_capacity(files.len() * 2);
+
+    for file in files {
+        let index_location = Path::from(format!(
+            "{}{}",
+            file.location,
+            indexed_file.index_file_extension()
+        ));
+
+        metadata.push(object_store.head(&file.location).await?);
+        metadata.push(object_store.head(&index_location).await?);
+    }
+
+    Ok(metadata)
+}
+
+async fn count_records(
+    object_store: Arc<dyn ObjectStore>,
+    files: &[ObjectMeta],
+    regions: &[Region],
+    indexed_file: &IndexedBGZFFile,
+) -> Result<Option<u64>> {
+    let mut total = 0;
+
+    for file in files {
+        match count_records_from_index(Arc::clone(&object_store), file, regions, indexed_file)
+            .await?
+        {
+            Some(count) => total += count,
+            None => {
+                tracing::debug!(path = %file.location, "Index has no metadata, scanning the file");
+                return Ok(None);
+            }
+        }
+    }
+
+    Ok(Some(total))
+}
+
+/// Batches with no columns whose row counts add up to `count`.
+fn row_count_batches(
+    schema: SchemaRef,
+    count: u64,
+    batch_size: usize,
+) -> BoxStream<'static, Result<RecordBatch>> {
+    let batches = (0..count).step_by(batch_size).map(move |start| {
+        let num_rows = std::cmp::min(batch_size as u64, count - start) as usize;
+        let options = RecordBatchOptions::new().with_row_count(Some(num_rows));
+
+        RecordBatch::try_new_with_options(Arc::clone(&schema), vec![], &options)
+            .map_err(DataFusionError::from)
+    });
+
+    futures::stream::iter(batches).boxed()
+}
+
+impl DisplayAs for IndexMetadataExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "IndexMetadataExec: files={}, regions={}",
+            self.files.len(),
+            self.regions.len()
+        )
+    }
+}
+
+impl ExecutionPlan for IndexMetadataExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "IndexMetadataExec"
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        self.input.properties()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        _children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(self)
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let object_store = context.runtime_env().object_store(&self.object_store_url)?;
+        let batch_size = context.session_config().batch_size();
+
+        let input = Arc::clone(&self.input);
+        let files = self.files.clone();
+        let regions = self.regions.clone();
+        let indexed_file = self.indexed_file;
+        let count = Arc::clone(&self.count);
+        let schema = self.schema();
+
+        let stream = futures::stream::once(async move {
+            let total = {
+                let mut count = count.lock().await;
+                let metadata = file_metadata(&object_store, &files, &indexed_file).await?;
+
+                match &*count {
+                    Some(cached) if cached.metadata == metadata => cached.count,
+                    _ => {
+                        let total =
+                            count_records(object_store, &files, &regions, &indexed_file).await?;
+                        *count = Some(CachedCount {
+                            metadata,
+                            count: total,
+                        });
+                        total
+                    }
+                }
+            };
+
+            match total {
+                Some(total) if partition == 0 => Ok(row_count_batches(schema, total, batch_size)),
+                Some(_) => Ok(futures::stream::empty().boxed()),
+                None => Ok(input.execute(partition, context)?.boxed()),
+            }
+        })
+        .try_flatten();
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.input.schema(),
+            stream,
+        )))
+    }
+}
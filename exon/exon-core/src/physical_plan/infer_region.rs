@@ -15,7 +15,7 @@
 use std::str::FromStr;
 
 use datafusion::{
-    logical_expr::{expr::ScalarFunction, Expr},
+    logical_expr::{expr::ScalarFunction, BinaryExpr, Expr, Operator},
     scalar::ScalarValue,
 };
 use noodles::core::Region;
@@ -40,3 +40,78 @@ pub(crate) fn infer_region_from_udf(
 
     Ok(None)
 }
+
+/// Whether the expression is a region filter UDF call, or a disjunction of them, e.g. `bam_region_filter('chr1', reference) OR bam_region_filter('chr2', reference)`.
+pub(crate) fn is_region_filter(expr: &Expr, name: &str) -> bool {
+    match expr {
+        Expr::ScalarFunction(s) => s.name() == name,
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::Or,
+            right,
+        }) => is_region_filter(left, name) && is_region_filter(right, name),
+        _ => false,
+    }
+}
+
+/// Infer the regions from a region filter UDF call, or a disjunction of them. Returns `None` if
+/// the expression is not made up only of region filters with literal regions.
+pub(crate) fn infer_regions_from_expr(expr: &Expr, name: &str) -> ExonResult<Option<Vec<Region>>> {
+    match expr {
+        Expr::ScalarFunction(s) => Ok(infer_region_from_udf(s, name)?.map(|r| vec![r])),
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::Or,
+            right,
+        }) => {
+            let left = infer_regions_from_expr(left, name)?;
+            let right = infer_regions_from_expr(right, name)?;
+
+            match (left, right) {
+                (Some(mut left), Some(right)) => {
+                    left.extend(right);
+                    Ok(Some(left))
+                }
+                _ => Ok(None),
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::logical_expr::{col, expr::ScalarFunction, lit, Expr, ScalarUDF};
+
+    use super::{infer_regions_from_expr, is_region_filter};
+    use crate::udfs::sam::bam_region_filter::BAMRegionFilterUDF;
+
+    fn region_filter(udf: &std::sync::Arc<ScalarUDF>, region: &str) -> Expr {
+        Expr::ScalarFunction(ScalarFunction::new_udf(
+            std::sync::Arc::clone(udf),
+            vec![lit(region), col("reference"), col("start"), col("end")],
+        ))
+    }
+
+    #[test]
+    fn test_infer_regions_from_disjunction() -> Result<(), Box<dyn std::error::Error>> {
+        let udf = std::sync::Arc::new(ScalarUDF::from(BAMRegionFilterUDF::default()));
+
+        let expr = region_filter(&udf, "chr1:1-100")
+            .or(region_filter(&udf, "chr2"))
+            .or(region_filter(&udf, "chr3:5-10"));
+
+        assert!(is_region_filter(&expr, "bam_region_filter"));
+
+        let regions =
+            infer_regions_from_expr(&expr, "bam_region_filter")?.ok_or("expected regions")?;
+        let names = regions.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["chr1:1-100", "chr2", "chr3:5-10"]);
+
+        let mixed = region_filter(&udf, "chr1").or(col("flag").eq(lit(4)));
+        assert!(!is_region_filter(&mixed, "bam_region_filter"));
+        assert!(infer_regions_from_expr(&mixed, "bam_region_filter")?.is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use datafusion::{
+    logical_expr::{BinaryExpr, Expr, Operator},
+    scalar::ScalarValue,
+};
+use exon_common::SequenceFilter;
+
+fn utf8_literal(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Literal(ScalarValue::Utf8(Some(s)))
+        | Expr::Literal(ScalarValue::LargeUtf8(Some(s)))
+        | Expr::Literal(ScalarValue::Utf8View(Some(s))) => Some(s),
+        _ => None,
+    }
+}
+
+/// Infer a prefilter for a sequence column from a `LIKE`, `ILIKE`, `~`, `~*`, or `regexp_like`
+/// predicate, or `None` if the predicate can't be checked on the raw sequence.
+pub(crate) fn infer_sequence_filter(expr: &Expr, column: &str) -> Option<SequenceFilter> {
+    let is_column = |expr: &Expr| matches!(expr, Expr::Column(c) if c.name == column);
+
+    match expr {
+        Expr::Like(like)
+            if !like.negated && like.escape_char.is_none() && is_column(&like.expr) =>
+        {
+            let pattern = utf8_literal(&like.pattern)?;
+            SequenceFilter::try_new_like(pattern, like.case_insensitive).ok()
+        }
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) if is_column(left) => {
+            let case_insensitive = match op {
+                Operator::RegexMatch => false,
+                Operator::RegexIMatch => true,
+                _ => return None,
+            };
+
+            let pattern = utf8_literal(right)?;
+            SequenceFilter::try_new_regex(pattern, case_insensitive).ok()
+        }
+        Expr::ScalarFunction(scalar_function) if scalar_function.name() == "regexp_like" => {
+            match scalar_function.args.as_slice() {
+                [sequence, pattern] if is_column(sequence) => {
+                    SequenceFilter::try_new_regex(utf8_literal(pattern)?, false).ok()
+                }
+                [sequence, pattern, flags]
+                    if is_column(sequence) && utf8_literal(flags)? == "i" =>
+                {
+                    SequenceFilter::try_new_regex(utf8_literal(pattern)?, true).ok()
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::{
+        functions::regex::expr_fn::regexp_like,
+        logical_expr::{binary_expr, col, lit, Operator},
+    };
+
+    use super::infer_sequence_filter;
+
+    #[test]
+    fn test_infer_sequence_filter() {
+        let sequence = b"GATTTGGGGTTCAAAGCAG";
+
+        let cases = [
+            (col("sequence").like(lit("GATT%")), true),
+            (col("sequence").like(lit("%TTCA_A%")), true),
+            (col("sequence").like(lit("ATT%")), false),
+            (col("sequence").ilike(lit("gatt%")), true),
+            (
+                binary_expr(col("sequence"), Operator::RegexMatch, lit("TTC[AG]")),
+                true,
+            ),
+            (
+                binary_expr(col("sequence"), Operator::RegexIMatch, lit("ttc[ag]")),
+                true,
+            ),
+            (regexp_like(col("sequence"), lit("^GGG"), None), false),
+            (
+                regexp_like(col("sequence"), lit("caaa"), Some(lit("i"))),
+                true,
+            ),
+        ];
+
+        for (expr, expected) in cases {
+            let filter = infer_sequence_filter(&expr, "sequence").unwrap();
+            assert_eq!(filter.is_match(sequence), expected, "{}", expr);
+        }
+
+        assert!(
+            infer_sequence_filter(&col("sequence").not_like(lit("GATT%")), "sequence").is_none()
+        );
+        assert!(infer_sequence_filter(&col("name").like(lit("GATT%")), "sequence").is_none());
+        assert!(infer_sequence_filter(&col("sequence").eq(lit("GATT")), "sequence").is_none());
+    }
+}
@@ -0,0 +1,604 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, collections::VecDeque, fmt, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, Int64Array, RecordBatch, StringArray},
+    compute::{cast, SortOptions},
+    datatypes::{DataType, Field, Int64Type, Schema, SchemaRef},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_expr::{EquivalenceProperties, LexRequirement, PhysicalSortRequirement},
+    physical_plan::{
+        expressions::Column, stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType,
+        Distribution, ExecutionMode, ExecutionPlan, Partitioning, PlanProperties,
+    },
+};
+use futures::StreamExt;
+
+/// The set operation applied by an [`IntervalSetExec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntervalSetOperation {
+    /// Merge the overlapping and book-ended intervals of a single input.
+    Merge,
+
+    /// Remove the parts of the left intervals that overlap any of the right intervals.
+    Subtract,
+}
+
+impl fmt::Display for IntervalSetOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Merge => write!(f, "merge"),
+            Self::Subtract => write!(f, "subtract"),
+        }
+    }
+}
+
+/// A half-open interval on a reference sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Interval {
+    reference: String,
+    start: i64,
+    end: i64,
+}
+
+/// Reads the intervals of a stream whose first three columns are the reference, start, and end.
+struct IntervalCursor {
+    input: SendableRecordBatchStream,
+    columns: Option<(StringArray, Int64Array, Int64Array)>,
+    row: usize,
+    last: Option<(String, i64)>,
+}
+
+impl IntervalCursor {
+    fn new(input: SendableRecordBatchStream) -> Self {
+        Self {
+            input,
+            columns: None,
+            row: 0,
+            last: None,
+        }
+    }
+
+    async fn next(&mut self) -> Result<Option<Interval>> {
+        loop {
+            if let Some((references, starts, ends)) = &self.columns {
+                while self.row < references.len() {
+                    let i = self.row;
+                    self.row += 1;
+
+                    if references.is_null(i) || starts.is_null(i) || ends.is_null(i) {
+                        continue;
+                    }
+
+                    let interval = Interval {
+                        reference: references.value(i).to_string(),
+                        start: starts.value(i),
+                        end: ends.value(i),
+                    };
+
+                    if let Some((reference, start)) = &self.last {
+                        if (reference.as_str(), *start)
+                            > (interval.reference.as_str(), interval.start)
+                        {
+                            return Err(DataFusionError::Execution(
+                                "Interval set operations require input sorted by reference and start"
+                                    .to_string(),
+                            ));
+                        }
+                    }
+
+                    self.last = Some((interval.reference.clone(), interval.start));
+
+                    return Ok(Some(interval));
+                }
+            }
+
+            match self.input.next().await {
+                Some(batch) => {
+                    let batch = batch?;
+
+                    let references = cast(batch.column(0), &DataType::Utf8)?;
+                    let starts = cast(batch.column(1), &DataType::Int64)?;
+                    let ends = cast(batch.column(2), &DataType::Int64)?;
+
+                    self.columns = Some((
+                        references.as_string::<i32>().clone(),
+                        starts.as_primitive::<Int64Type>().clone(),
+                        ends.as_primitive::<Int64Type>().clone(),
+                    ));
+                    self.row = 0;
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Merges the overlapping and book-ended intervals read by a cursor.
+struct MergeCursor {
+    cursor: IntervalCursor,
+    pending: Option<Interval>,
+}
+
+impl MergeCursor {
+    fn new(input: SendableRecordBatchStream) -> Self {
+        Self {
+            cursor: IntervalCursor::new(input),
+            pending: None,
+        }
+    }
+
+    async fn next(&mut self) -> Result<Option<Interval>> {
+        loop {
+            let Some(interval) = self.cursor.next().await? else {
+                return Ok(self.pending.take());
+            };
+
+            match self.pending.as_mut() {
+                Some(pending)
+                    if pending.reference == interval.reference && interval.start <= pending.end =>
+                {
+                    pending.end = pending.end.max(interval.end);
+                }
+                Some(_) => return Ok(self.pending.replace(interval)),
+                None => self.pending = Some(interval),
+            }
+        }
+    }
+}
+
+/// The state of an interval set operation's output stream.
+enum IntervalSetState {
+    Merge(MergeCursor),
+    Subtract {
+        left: IntervalCursor,
+        right: MergeCursor,
+        /// The merged right intervals that may still overlap the upcoming left intervals.
+        window: VecDeque<Interval>,
+        /// The next merged right interval that hasn't been added to the window.
+        next_right: Option<Interval>,
+        right_started: bool,
+    },
+}
+
+impl IntervalSetState {
+    /// Push the next output intervals to `output`, returns false once the inputs are exhausted.
+    async fn advance(&mut self, output: &mut Vec<Interval>) -> Result<bool> {
+        match self {
+            Self::Merge(cursor) => match cursor.next().await? {
+                Some(interval) => {
+                    output.push(interval);
+                    Ok(true)
+                }
+                None => Ok(false),
+            },
+            Self::Subtract {
+                left,
+                right,
+                window,
+                next_right,
+                right_started,
+            } => {
+                let Some(interval) = left.next().await? else {
+                    return Ok(false);
+                };
+
+                if !*right_started {
+                    *next_right = right.next().await?;
+                    *right_started = true;
+                }
+
+                // Drop the right intervals that end before this interval, later left intervals
+                // start at or after it so they can't overlap them either.
+                while let Some(front) = window.front() {
+                    if (front.reference.as_str(), front.end)
+                        <= (interval.reference.as_str(), interval.start)
+                    {
+                        window.pop_front();
+                    } else {
+                        break;
+                    }
+                }
+
+                // Fill the window with the right intervals that start before this one ends.
+                while let Some(candidate) = next_right.take() {
+                    if (candidate.reference.as_str(), candidate.start)
+                        >= (interval.reference.as_str(), interval.end)
+                    {
+                        *next_right = Some(candidate);
+                        break;
+                    }
+
+                    if (candidate.reference.as_str(), candidate.end)
+                        > (interval.reference.as_str(), interval.start)
+                    {
+                        window.push_back(candidate);
+                    }
+
+                    *next_right = right.next().await?;
+                }
+
+                let mut start = interval.start;
+
+                for other in window.iter() {
+                    if other.reference != interval.reference || other.start >= interval.end {
+                        continue;
+                    }
+
+                    if other.start > start {
+                        output.push(Interval {
+                            reference: interval.reference.clone(),
+                            start,
+                            end: other.start,
+                        });
+                    }
+
+                    start = start.max(other.end);
+                }
+
+                if start < interval.end {
+                    output.push(Interval {
+                        reference: interval.reference,
+                        start,
+                        end: interval.end,
+                    });
+                }
+
+                Ok(true)
+            }
+        }
+    }
+}
+
+fn intervals_to_batch(schema: &SchemaRef, intervals: Vec<Interval>) -> Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            intervals.iter().map(|i| i.reference.as_str()),
+        )),
+        Arc::new(Int64Array::from_iter_values(
+            intervals.iter().map(|i| i.start),
+        )),
+        Arc::new(Int64Array::from_iter_values(
+            intervals.iter().map(|i| i.end),
+        )),
+    ];
+
+    Ok(RecordBatch::try_new(Arc::clone(schema), columns)?)
+}
+
+/// An execution plan for bedtools-like set operations over sorted interval inputs.
+///
+/// The first three columns of each input are read as the reference name, start, and end of
+/// half-open intervals, and the inputs are required to be sorted by reference and then start.
+/// Intervals are streamed, so only the right intervals overlapping the current left interval are
+/// held in memory.
+#[derive(Debug)]
+pub struct IntervalSetExec {
+    operation: IntervalSetOperation,
+    inputs: Vec<Arc<dyn ExecutionPlan>>,
+    schema: SchemaRef,
+    properties: PlanProperties,
+}
+
+impl IntervalSetExec {
+    /// Create a new interval set exec, merge takes one input and subtract takes two.
+    pub fn try_new(
+        operation: IntervalSetOperation,
+        inputs: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Self> {
+        let n_inputs = match operation {
+            IntervalSetOperation::Merge => 1,
+            IntervalSetOperation::Subtract => 2,
+        };
+
+        if inputs.len() != n_inputs {
+            return Err(DataFusionError::Plan(format!(
+                "Interval {} requires {} inputs, got {}",
+                operation,
+                n_inputs,
+                inputs.len()
+            )));
+        }
+
+        for input in inputs.iter() {
+            if input.schema().fields().len() < 3 {
+                return Err(DataFusionError::Plan(format!(
+                    "Interval {} requires inputs with reference, start, and end columns",
+                    operation
+                )));
+            }
+        }
+
+        let schema = interval_set_schema(&inputs[0].schema());
+
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(Arc::clone(&schema)),
+            Partitioning::UnknownPartitioning(1),
+            ExecutionMode::Bounded,
+        );
+
+        Ok(Self {
+            operation,
+            inputs,
+            schema,
+            properties,
+        })
+    }
+
+    /// The operation of this exec.
+    pub fn operation(&self) -> IntervalSetOperation {
+        self.operation
+    }
+}
+
+/// The output schema of an interval set operation, named after the first three columns of the
+/// (left) input.
+pub fn interval_set_schema(input_schema: &Schema) -> SchemaRef {
+    let names = input_schema
+        .fields()
+        .iter()
+        .take(3)
+        .map(|f| f.name().as_str())
+        .collect::<Vec<_>>();
+
+    let name = |i: usize, default: &'static str| names.get(i).copied().unwrap_or(default);
+
+    Arc::new(Schema::new(vec![
+        Field::new(name(0, "reference_sequence_name"), DataType::Utf8, false),
+        Field::new(name(1, "start"), DataType::Int64, false),
+        Field::new(name(2, "end"), DataType::Int64, false),
+    ]))
+}
+
+impl DisplayAs for IntervalSetExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "IntervalSetExec: operation={}", self.operation)
+    }
+}
+
+impl ExecutionPlan for IntervalSetExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "IntervalSetExec"
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        vec![Distribution::SinglePartition; self.inputs.len()]
+    }
+
+    fn required_input_ordering(&self) -> Vec<Option<LexRequirement>> {
+        self.inputs
+            .iter()
+            .map(|input| {
+                let schema = input.schema();
+
+                let requirements = (0..2)
+                    .map(|i| {
+                        PhysicalSortRequirement::new(
+                            Arc::new(Column::new(schema.field(i).name(), i)),
+                            Some(SortOptions::default()),
+                        )
+                    })
+                    .collect();
+
+                Some(LexRequirement::new(requirements))
+            })
+            .collect()
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false; self.inputs.len()]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        self.inputs.iter().collect()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(Self::try_new(self.operation, children)?))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "IntervalSetExec has a single partition, got {}",
+                partition
+            )));
+        }
+
+        let batch_size = context.session_config().batch_size();
+
+        let state = match self.operation {
+            IntervalSetOperation::Merge => IntervalSetState::Merge(MergeCursor::new(
+                self.inputs[0].execute(0, Arc::clone(&context))?,
+            )),
+            IntervalSetOperation::Subtract => IntervalSetState::Subtract {
+                left: IntervalCursor::new(self.inputs[0].execute(0, Arc::clone(&context))?),
+                right: MergeCursor::new(self.inputs[1].execute(0, Arc::clone(&context))?),
+                window: VecDeque::new(),
+                next_right: None,
+                right_started: false,
+            },
+        };
+
+        let schema = Arc::clone(&self.schema);
+
+        let stream = futures::stream::unfold(Some(state), move |state| {
+            let schema = Arc::clone(&schema);
+
+            async move {
+                let mut state = state?;
+                let mut output = Vec::new();
+
+                while output.len() < batch_size {
+                    match state.advance(&mut output).await {
+                        Ok(true) => {}
+                        Ok(false) if output.is_empty() => return None,
+                        Ok(false) => return Some((intervals_to_batch(&schema, output), None)),
+                        Err(e) => return Some((Err(e), None)),
+                    }
+                }
+
+                Some((intervals_to_batch(&schema, output), Some(state)))
+            }
+        });
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            Arc::clone(&self.schema),
+            stream,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{AsArray, Int64Array, RecordBatch, StringArray},
+        datatypes::{DataType, Field, Int64Type, Schema},
+    };
+    use datafusion::physical_plan::{collect, memory::MemoryExec, ExecutionPlan};
+
+    use super::{IntervalSetExec, IntervalSetOperation};
+    use crate::ExonSession;
+
+    fn intervals(
+        rows: Vec<(&str, i64, i64)>,
+    ) -> Result<Arc<dyn ExecutionPlan>, Box<dyn std::error::Error>> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("chrom", DataType::Utf8, false),
+            Field::new("start", DataType::Int64, false),
+            Field::new("end", DataType::Int64, false),
+        ]));
+
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.0))),
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.1))),
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.2))),
+            ],
+        )?;
+
+        Ok(Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?))
+    }
+
+    async fn run(
+        exec: IntervalSetExec,
+    ) -> Result<Vec<(String, i64, i64)>, Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+        let batches = collect(Arc::new(exec), ctx.session.task_ctx()).await?;
+
+        let mut rows = Vec::new();
+        for batch in batches {
+            let chroms = batch.column(0).as_string::<i32>();
+            let starts = batch.column(1).as_primitive::<Int64Type>();
+            let ends = batch.column(2).as_primitive::<Int64Type>();
+
+            for i in 0..batch.num_rows() {
+                rows.push((chroms.value(i).to_string(), starts.value(i), ends.value(i)));
+            }
+        }
+
+        Ok(rows)
+    }
+
+    #[tokio::test]
+    async fn test_merge() -> Result<(), Box<dyn std::error::Error>> {
+        let input = intervals(vec![
+            ("chr1", 1, 5),
+            ("chr1", 3, 8),
+            ("chr1", 8, 9),
+            ("chr1", 10, 12),
+            ("chr2", 1, 4),
+        ])?;
+
+        let exec = IntervalSetExec::try_new(IntervalSetOperation::Merge, vec![input])?;
+
+        assert_eq!(
+            run(exec).await?,
+            vec![
+                ("chr1".to_string(), 1, 9),
+                ("chr1".to_string(), 10, 12),
+                ("chr2".to_string(), 1, 4),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subtract() -> Result<(), Box<dyn std::error::Error>> {
+        let left = intervals(vec![
+            ("chr1", 0, 100),
+            ("chr1", 10, 20),
+            ("chr2", 0, 10),
+            ("chr3", 0, 10),
+        ])?;
+        let right = intervals(vec![
+            ("chr1", 15, 30),
+            ("chr1", 25, 40),
+            ("chr1", 90, 120),
+            ("chr2", 0, 10),
+        ])?;
+
+        let exec = IntervalSetExec::try_new(IntervalSetOperation::Subtract, vec![left, right])?;
+
+        assert_eq!(
+            run(exec).await?,
+            vec![
+                ("chr1".to_string(), 0, 15),
+                ("chr1".to_string(), 40, 90),
+                ("chr1".to_string(), 10, 15),
+                ("chr3".to_string(), 0, 10),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unsorted_input() -> Result<(), Box<dyn std::error::Error>> {
+        let input = intervals(vec![("chr1", 10, 20), ("chr1", 1, 5)])?;
+
+        let exec = IntervalSetExec::try_new(IntervalSetOperation::Merge, vec![input])?;
+        assert!(run(exec).await.is_err());
+
+        Ok(())
+    }
+}
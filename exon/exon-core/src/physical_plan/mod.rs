@@ -38,3 +38,42 @@ pub mod planner;
 
 /// A macro for extracting the region from a UDF.
 pub mod infer_region;
+
+/// Inference of sequence prefilters from pattern predicates.
+pub mod infer_sequence_filter;
+
+/// An execution plan that piles up sorted alignments into per-position allele counts.
+pub mod pileup_exec;
+
+/// An execution plan for set operations, e.g. merge and subtract, over sorted intervals.
+pub mod interval_set_exec;
+
+/// An execution plan that pairs the breakend records of a VCF table into junctions.
+pub mod resolve_breakends_exec;
+
+/// An execution plan that benchmarks a query callset against a truth set by stratum.
+pub mod variant_benchmark_exec;
+
+/// An execution plan that annotates variants with the genes they overlap.
+pub mod gene_overlap_exec;
+
+/// An execution plan that assigns the reads of a FASTQ table to samples by their barcodes.
+pub mod demultiplex_exec;
+
+/// An execution plan that marks the duplicates of sorted alignments by their UMIs.
+pub mod umi_dedup_exec;
+
+/// An execution plan that adds the index of each record in its file to a file scan.
+pub mod record_index_exec;
+
+/// An execution plan that encrypts, decrypts, or tokenizes configured columns.
+pub mod column_transform_exec;
+
+/// An execution plan that records the reads of a table scan in the audit log.
+pub mod audit_exec;
+
+/// An execution plan that answers a scan with no output columns from the index metadata.
+pub mod index_metadata_exec;
+
+/// An execution plan that runs a table's scan with the table's own object store.
+pub mod table_object_store_exec;
@@ -24,7 +24,7 @@ use datafusion::{
     execution::object_store::ObjectStoreUrl,
     scalar::ScalarValue,
 };
-use object_store::{path::Path, ObjectStore};
+use object_store::{path::Path, GetOptions, GetRange, ObjectStore};
 
 use datafusion::{
     datasource::listing::{ListingTableUrl, PartitionedFile},
@@ -68,6 +68,17 @@ pub async fn get_byte_region(
     }
 }
 
+/// Get options that read the byte range of a file, or the whole file if it has no range.
+pub(crate) fn file_range_get_options(file_meta: &FileMeta) -> GetOptions {
+    GetOptions {
+        range: file_meta
+            .range
+            .as_ref()
+            .map(|range| GetRange::Bounded(range.start as usize..range.end as usize)),
+        ..Default::default()
+    }
+}
+
 /// List files for a scan
 pub async fn list_files_for_scan(
     store: Arc<dyn ObjectStore>,
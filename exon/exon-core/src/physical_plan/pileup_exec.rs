@@ -0,0 +1,532 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, collections::BTreeMap, fmt, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, Int64Array, RecordBatch, StringArray},
+    compute::SortOptions,
+    datatypes::{DataType, Field, Schema, SchemaRef},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_expr::{EquivalenceProperties, LexRequirement, PhysicalSortRequirement},
+    physical_plan::{
+        expressions::col, stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType,
+        Distribution, ExecutionMode, ExecutionPlan, Partitioning, PlanProperties,
+    },
+};
+use futures::StreamExt;
+use noodles::core::Region;
+
+use crate::sinks::columns_from_batch::{get_array_column, get_flag_column};
+
+/// The alleles counted at each position, in the order of the output columns.
+const ALLELES: [&str; 6] = ["a", "c", "g", "t", "ins", "del"];
+
+const INSERTION: usize = 4;
+const DELETION: usize = 5;
+
+// The SAM flags of records that are left out of the pileup: unmapped, secondary, QC fail, and
+// duplicate, the same as samtools' default.
+const SKIP_FLAGS: i32 = 0x4 | 0x100 | 0x200 | 0x400;
+const REVERSE_FLAG: i32 = 0x10;
+
+/// The schema of the pileup, a row per covered position with forward and reverse strand counts
+/// for each allele.
+pub fn pileup_schema() -> SchemaRef {
+    let mut fields = vec![
+        Field::new("reference", DataType::Utf8, false),
+        Field::new("position", DataType::Int64, false),
+    ];
+
+    for allele in ALLELES {
+        fields.push(Field::new(
+            format!("{}_forward", allele),
+            DataType::Int64,
+            false,
+        ));
+        fields.push(Field::new(
+            format!("{}_reverse", allele),
+            DataType::Int64,
+            false,
+        ));
+    }
+
+    Arc::new(Schema::new(fields))
+}
+
+/// Parse a CIGAR string into (length, operation) pairs, `*` is an empty CIGAR.
+pub(crate) fn parse_cigar(cigar: &str) -> Result<Vec<(usize, u8)>> {
+    let mut ops = Vec::new();
+    let mut len = 0;
+
+    for b in cigar.bytes() {
+        match b {
+            b'0'..=b'9' => len = len * 10 + (b - b'0') as usize,
+            b'*' if cigar.len() == 1 => break,
+            b'M' | b'I' | b'D' | b'N' | b'S' | b'H' | b'P' | b'=' | b'X' => {
+                ops.push((len, b));
+                len = 0;
+            }
+            _ => {
+                return Err(DataFusionError::Execution(format!(
+                    "Invalid CIGAR: {}",
+                    cigar
+                )))
+            }
+        }
+    }
+
+    Ok(ops)
+}
+
+/// The allele counts of a position, indexed by allele and then strand.
+type AlleleCounts = [[i64; 2]; 6];
+
+/// Accumulates the allele counts of a region from coordinate-sorted alignments.
+#[derive(Debug)]
+struct Pileup {
+    region: Region,
+    reference: String,
+
+    /// The counts of the positions that can still be covered by later alignments.
+    pending: BTreeMap<usize, AlleleCounts>,
+
+    /// The counts of the positions before the start of the last alignment, ready to be emitted.
+    finished: Vec<(usize, AlleleCounts)>,
+
+    last_start: usize,
+}
+
+impl Pileup {
+    fn new(region: Region) -> Self {
+        Self {
+            reference: region.name().to_string(),
+            region,
+            pending: BTreeMap::new(),
+            finished: Vec::new(),
+            last_start: 0,
+        }
+    }
+
+    fn in_region(&self, position: usize) -> bool {
+        let interval = self.region.interval();
+
+        interval
+            .start()
+            .map_or(true, |s| position >= usize::from(s))
+            && interval.end().map_or(true, |e| position <= usize::from(e))
+    }
+
+    fn count(&mut self, position: usize, allele: usize, strand: usize) {
+        if self.in_region(position) {
+            self.pending.entry(position).or_insert([[0; 2]; 6])[allele][strand] += 1;
+        }
+    }
+
+    /// Move the positions before `position` from pending to finished.
+    fn finish_before(&mut self, position: usize) {
+        let pending = self.pending.split_off(&position);
+        let finished = std::mem::replace(&mut self.pending, pending);
+
+        self.finished.extend(finished);
+    }
+
+    fn add_alignment(
+        &mut self,
+        start: usize,
+        reverse: bool,
+        cigar: &str,
+        sequence: &[u8],
+    ) -> Result<()> {
+        if start < self.last_start {
+            return Err(DataFusionError::Execution(
+                "Pileup requires alignments sorted by start".to_string(),
+            ));
+        }
+
+        // Nothing before this alignment's start can be covered by a later alignment.
+        self.finish_before(start);
+        self.last_start = start;
+
+        let strand = usize::from(reverse);
+
+        let mut ref_pos = start;
+        let mut read_pos = 0;
+
+        for (len, op) in parse_cigar(cigar)? {
+            match op {
+                b'M' | b'=' | b'X' => {
+                    for i in 0..len {
+                        let allele = match sequence.get(read_pos + i).map(u8::to_ascii_uppercase) {
+                            Some(b'A') => 0,
+                            Some(b'C') => 1,
+                            Some(b'G') => 2,
+                            Some(b'T') => 3,
+                            _ => continue,
+                        };
+
+                        self.count(ref_pos + i, allele, strand);
+                    }
+
+                    ref_pos += len;
+                    read_pos += len;
+                }
+                b'I' => {
+                    // An insertion is counted at the reference base before it, if there is one.
+                    if ref_pos > start {
+                        self.count(ref_pos - 1, INSERTION, strand);
+                    }
+
+                    read_pos += len;
+                }
+                b'D' => {
+                    for i in 0..len {
+                        self.count(ref_pos + i, DELETION, strand);
+                    }
+
+                    ref_pos += len;
+                }
+                b'N' => ref_pos += len,
+                b'S' => read_pos += len,
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add the alignments of a batch with the `reference`, `start`, `flag`, `cigar`, and
+    /// `sequence` columns.
+    fn update(&mut self, batch: &RecordBatch) -> Result<()> {
+        let references = get_array_column::<StringArray>(batch, "reference")?;
+        let starts = get_array_column::<Int64Array>(batch, "start")?;
+        let flags = get_flag_column(batch)?;
+        let cigars = get_array_column::<StringArray>(batch, "cigar")?;
+        let sequences = get_array_column::<StringArray>(batch, "sequence")?;
+
+        for i in 0..batch.num_rows() {
+            if references.is_null(i)
+                || references.value(i) != self.reference
+                || starts.is_null(i)
+                || flags.value(i) & SKIP_FLAGS != 0
+            {
+                continue;
+            }
+
+            let sequence = sequences.value(i);
+            if sequence == "*" {
+                continue;
+            }
+
+            self.add_alignment(
+                starts.value(i) as usize,
+                flags.value(i) & REVERSE_FLAG != 0,
+                cigars.value(i),
+                sequence.as_bytes(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Take the finished positions as a record batch.
+    fn take_batch(&mut self, schema: &SchemaRef) -> Result<RecordBatch> {
+        let finished = std::mem::take(&mut self.finished);
+
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(vec![
+                self.reference.as_str();
+                finished.len()
+            ])),
+            Arc::new(Int64Array::from_iter_values(
+                finished.iter().map(|(position, _)| *position as i64),
+            )),
+        ];
+
+        for allele in 0..ALLELES.len() {
+            for strand in 0..2 {
+                columns.push(Arc::new(Int64Array::from_iter_values(
+                    finished.iter().map(|(_, counts)| counts[allele][strand]),
+                )));
+            }
+        }
+
+        Ok(RecordBatch::try_new(Arc::clone(schema), columns)?)
+    }
+}
+
+/// An execution plan that piles up coordinate-sorted alignments over a region, emitting the
+/// stranded counts of each allele at every covered position.
+///
+/// The input needs `reference`, `start`, `flag`, `cigar`, and `sequence` columns, e.g. a BAM, SAM,
+/// or CRAM scan. Positions are emitted in order as soon as no later alignment can cover them, so
+/// memory is bounded by the alignments' lengths rather than the region's.
+#[derive(Debug)]
+pub struct PileupExec {
+    input: Arc<dyn ExecutionPlan>,
+    region: Region,
+    schema: SchemaRef,
+    properties: PlanProperties,
+}
+
+impl PileupExec {
+    /// Create a new pileup over `region` of the alignments in `input`.
+    pub fn try_new(input: Arc<dyn ExecutionPlan>, region: Region) -> Result<Self> {
+        let input_schema = input.schema();
+
+        for column in ["reference", "start", "flag", "cigar", "sequence"] {
+            input_schema.field_with_name(column).map_err(|_| {
+                DataFusionError::Plan(format!("Pileup input requires a {} column", column))
+            })?;
+        }
+
+        let schema = pileup_schema();
+
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(Arc::clone(&schema)),
+            Partitioning::UnknownPartitioning(1),
+            ExecutionMode::Bounded,
+        );
+
+        Ok(Self {
+            input,
+            region,
+            schema,
+            properties,
+        })
+    }
+
+    /// The region of the pileup.
+    pub fn region(&self) -> &Region {
+        &self.region
+    }
+}
+
+impl DisplayAs for PileupExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PileupExec: region={}", self.region)
+    }
+}
+
+impl ExecutionPlan for PileupExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "PileupExec"
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        vec![Distribution::SinglePartition]
+    }
+
+    fn required_input_ordering(&self) -> Vec<Option<LexRequirement>> {
+        let start = col("start", &self.input.schema()).map(|start| {
+            LexRequirement::new(vec![PhysicalSortRequirement::new(
+                start,
+                Some(SortOptions::default()),
+            )])
+        });
+
+        vec![start.ok()]
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.as_slice() {
+            [input] => Ok(Arc::new(Self::try_new(
+                Arc::clone(input),
+                self.region.clone(),
+            )?)),
+            _ => Err(DataFusionError::Internal(
+                "PileupExec requires exactly one child".to_string(),
+            )),
+        }
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "PileupExec has a single partition, got {}",
+                partition
+            )));
+        }
+
+        let input = self.input.execute(0, Arc::clone(&context))?;
+        let batch_size = context.session_config().batch_size();
+
+        let schema = Arc::clone(&self.schema);
+        let pileup = Pileup::new(self.region.clone());
+
+        let stream = futures::stream::unfold(
+            (input, pileup, false),
+            move |(mut input, mut pileup, done)| {
+                let schema = Arc::clone(&schema);
+
+                async move {
+                    if done {
+                        return None;
+                    }
+
+                    loop {
+                        match input.next().await {
+                            Some(Ok(batch)) => {
+                                if let Err(e) = pileup.update(&batch) {
+                                    return Some((Err(e), (input, pileup, true)));
+                                }
+
+                                if pileup.finished.len() >= batch_size {
+                                    let batch = pileup.take_batch(&schema);
+                                    return Some((batch, (input, pileup, false)));
+                                }
+                            }
+                            Some(Err(e)) => return Some((Err(e), (input, pileup, true))),
+                            None => {
+                                pileup.finish_before(usize::MAX);
+
+                                if pileup.finished.is_empty() {
+                                    return None;
+                                }
+
+                                let batch = pileup.take_batch(&schema);
+                                return Some((batch, (input, pileup, true)));
+                            }
+                        }
+                    }
+                }
+            },
+        );
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            Arc::clone(&self.schema),
+            stream,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{AsArray, Int32Array, Int64Array, RecordBatch, StringArray},
+        datatypes::{DataType, Field, Int64Type, Schema},
+    };
+    use datafusion::physical_plan::{collect, memory::MemoryExec};
+
+    use super::PileupExec;
+    use crate::ExonSession;
+
+    fn alignments(
+        rows: Vec<(i64, i32, &str, &str)>,
+    ) -> Result<RecordBatch, arrow::error::ArrowError> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("reference", DataType::Utf8, true),
+            Field::new("start", DataType::Int64, true),
+            Field::new("flag", DataType::Int32, false),
+            Field::new("cigar", DataType::Utf8, false),
+            Field::new("sequence", DataType::Utf8, false),
+        ]));
+
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec!["chr1"; rows.len()])),
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.0))),
+                Arc::new(Int32Array::from_iter_values(rows.iter().map(|r| r.1))),
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.2))),
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.3))),
+            ],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_pileup_exec() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let batch = alignments(vec![
+            (10, 0, "2M1I2M", "ACGTA"),
+            (11, 16, "1M2D1M", "GT"),
+            (12, 4, "*", "AAAA"),
+        ])?;
+
+        let input = MemoryExec::try_new(&[vec![batch.clone()]], batch.schema(), None)?;
+        let pileup = PileupExec::try_new(Arc::new(input), "chr1:11-13".parse()?)?;
+
+        let batches = collect(Arc::new(pileup), ctx.session.task_ctx()).await?;
+        assert_eq!(batches.len(), 1);
+
+        let batch = &batches[0];
+        let column = |name: &str| -> Vec<i64> {
+            batch
+                .column_by_name(name)
+                .unwrap()
+                .as_primitive::<Int64Type>()
+                .values()
+                .to_vec()
+        };
+
+        assert_eq!(column("position"), vec![11, 12, 13]);
+        assert_eq!(column("c_forward"), vec![1, 0, 0]);
+        assert_eq!(column("ins_forward"), vec![1, 0, 0]);
+        assert_eq!(column("g_reverse"), vec![1, 0, 0]);
+        assert_eq!(column("t_forward"), vec![0, 1, 0]);
+        assert_eq!(column("a_forward"), vec![0, 0, 1]);
+        assert_eq!(column("del_reverse"), vec![0, 1, 1]);
+        assert_eq!(column("t_reverse"), vec![0, 0, 0]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pileup_exec_unsorted() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let batch = alignments(vec![(20, 0, "2M", "AC"), (10, 0, "2M", "AC")])?;
+
+        let input = MemoryExec::try_new(&[vec![batch.clone()]], batch.schema(), None)?;
+        let pileup = PileupExec::try_new(Arc::new(input), "chr1".parse()?)?;
+
+        let result = collect(Arc::new(pileup), ctx.session.task_ctx()).await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+}
@@ -16,14 +16,20 @@ use std::{env, str::FromStr, sync::Arc};
 
 use async_trait::async_trait;
 use datafusion::{
+    common::tree_node::{TreeNode, TreeNodeRecursion},
     datasource::{
         file_format::file_compression_type::FileCompressionType, listing::PartitionedFile,
-        physical_plan::FileSinkConfig, DefaultTableSource,
+        physical_plan::FileSinkConfig, source_as_provider, DefaultTableSource,
     },
-    execution::context::SessionState,
+    execution::{context::SessionState, object_store::ObjectStoreUrl},
     logical_expr::{dml::InsertOp, LogicalPlan, LogicalPlanBuilder, UserDefinedLogicalNode},
-    physical_plan::{insert::DataSinkExec, ExecutionPlan},
+    physical_plan::{
+        coalesce_partitions::CoalescePartitionsExec,
+        insert::{DataSink, DataSinkExec},
+        ExecutionPlan, ExecutionPlanProperties,
+    },
     physical_planner::{ExtensionPlanner, PhysicalPlanner},
+    prelude::col,
     sql::{
         parser::{CopyToSource, Statement},
         sqlparser::ast,
@@ -34,10 +40,22 @@ use exon_fasta::FASTASchemaBuilder;
 use exon_fastq::new_fastq_schema_builder;
 
 use crate::{
-    datasources::ExonFileType,
-    logical_plan::ExonDataSinkLogicalPlanNode,
-    physical_plan::object_store::{parse_url, url_to_object_store_url},
-    sinks::SimpleRecordSink,
+    config::extract_exon_config,
+    datasources::{
+        bam::table_provider::{ListingBAMTable, ListingBAMTableOptions},
+        sam::table_provider::{ListingSAMTable, ListingSAMTableOptions},
+        ExonFileType,
+    },
+    logical_plan::{AuditScanNode, ExonDataSinkLogicalPlanNode},
+    physical_plan::{
+        audit_exec::{AuditExec, AuditLog},
+        object_store::{parse_url, url_to_object_store_url},
+    },
+    sinks::{
+        header_from_reference_sequences, infer_n_fields, is_valid_n_fields, read_header,
+        AlignmentSink, BCFSink, BEDSink, FASTASink, FASTQSink, GFFSink, SimpleRecordSink, VCFSink,
+        DEFAULT_LINE_WIDTH, MAX_QUALITY_SCORE,
+    },
 };
 
 pub struct ExomeExtensionPlanner {}
@@ -61,13 +79,16 @@ impl ExtensionPlanner for ExomeExtensionPlanner {
         planner: &dyn PhysicalPlanner,
         node: &dyn UserDefinedLogicalNode,
         _logical_inputs: &[&LogicalPlan],
-        _physical_inputs: &[Arc<dyn ExecutionPlan>],
+        physical_inputs: &[Arc<dyn ExecutionPlan>],
         session_state: &SessionState,
     ) -> datafusion::error::Result<Option<Arc<dyn ExecutionPlan>>> {
-        let logical_node = node
-            .as_any()
-            .downcast_ref::<ExonDataSinkLogicalPlanNode>()
-            .unwrap();
+        if let Some(audit_node) = node.as_any().downcast_ref::<AuditScanNode>() {
+            return plan_audit_scan(audit_node, physical_inputs, session_state).map(Some);
+        }
+
+        let Some(logical_node) = node.as_any().downcast_ref::<ExonDataSinkLogicalPlanNode>() else {
+            return Ok(None);
+        };
 
         let input_plan = match &logical_node.source {
             CopyToSource::Query(q) => {
@@ -109,49 +130,67 @@ impl ExtensionPlanner for ExomeExtensionPlanner {
             }
         };
 
-        let physical_plan = planner
-            .create_physical_plan(&input_plan, session_state)
-            .await?;
+        let stored_as = logical_node.stored_as.as_ref().ok_or_else(|| {
+            datafusion::error::DataFusionError::Plan(
+                "Stored as option is required for ExonDataSinkLogicalPlanNode".to_string(),
+            )
+        })?;
+        let exon_file_type = ExonFileType::from_str(stored_as)?;
 
-        let url = logical_node.target.clone();
+        // Output is sorted by position when asked for, or when it's indexed as it's written.
+        let sorted = logical_node.bool_option("sort")?.unwrap_or(false)
+            || logical_node.bool_option("tabix")?.unwrap_or(false);
 
-        let url = parse_url(&url)?;
-        let authority = match url.host_str() {
-            Some(host) => format!("{}://{}", url.scheme(), host),
-            None => format!("{}://", url.scheme()),
+        let sort_columns = match exon_file_type {
+            ExonFileType::GFF if sorted => Some(("seqname", "start")),
+            ExonFileType::BED if sorted => Some(("reference_sequence_name", "start")),
+            ExonFileType::VCF if sorted => Some(("chrom", "pos")),
+            _ => None,
         };
 
-        let is_local = authority.starts_with("file://");
-
-        let path = if is_local {
-            let p = std::path::Path::new(logical_node.target.as_str());
-
-            if p.is_absolute() {
-                object_store::path::Path::from_absolute_path(p)?
-            } else {
-                let current_dir = env::current_dir()?;
+        let input_plan = match sort_columns {
+            Some((name_column, start_column)) => LogicalPlanBuilder::from(input_plan)
+                .sort(vec![
+                    col(name_column).sort(true, false),
+                    col(start_column).sort(true, false),
+                ])?
+                .build()?,
+            None => input_plan,
+        };
 
-                let absolute_path = current_dir.join(p);
-                object_store::path::Path::from_absolute_path(absolute_path)?
+        let alignment_header = match exon_file_type {
+            ExonFileType::SAM | ExonFileType::BAM => {
+                Some(alignment_header(logical_node, &input_plan, session_state).await?)
             }
-        } else {
-            let path = &url.as_str()[authority.len()..];
-            object_store::path::Path::parse(path)?
+            _ => None,
         };
 
-        let object_store_url = url_to_object_store_url(&parse_url(&logical_node.target)?)?;
-        let p_file = PartitionedFile::new(path, 0);
+        let physical_plan = planner
+            .create_physical_plan(&input_plan, session_state)
+            .await?;
 
-        let stored_as = logical_node.stored_as.as_ref().ok_or_else(|| {
-            datafusion::error::DataFusionError::Plan(
-                "Stored as option is required for ExonDataSinkLogicalPlanNode".to_string(),
-            )
-        })?;
-        let exon_file_type = ExonFileType::from_str(stored_as)?;
+        // The sinks write a single file, so make sure every input partition ends up in it.
+        let physical_plan: Arc<dyn ExecutionPlan> =
+            if physical_plan.output_partitioning().partition_count() > 1 {
+                Arc::new(CoalescePartitionsExec::new(physical_plan))
+            } else {
+                physical_plan
+            };
+
+        let (object_store_url, path) = resolve_location(&logical_node.target)?;
+        let p_file = PartitionedFile::new(path, 0);
 
-        let schema = match ExonFileType::from_str(stored_as)? {
+        let schema = match exon_file_type {
             ExonFileType::FASTA => FASTASchemaBuilder::default().build().file_schema().unwrap(),
             ExonFileType::FASTQ => new_fastq_schema_builder().build().file_schema().unwrap(),
+            // GFF, BED, BCF, VCF, SAM, and BAM columns are looked up by name and only the positional ones
+            // are required.
+            ExonFileType::GFF
+            | ExonFileType::BED
+            | ExonFileType::BCF
+            | ExonFileType::VCF
+            | ExonFileType::SAM
+            | ExonFileType::BAM => physical_plan.schema(),
             _ => {
                 return Err(datafusion::error::DataFusionError::Plan(
                     "Invalid file type".to_string(),
@@ -169,18 +208,297 @@ impl ExtensionPlanner for ExomeExtensionPlanner {
             keep_partition_by_columns: false,
         };
 
-        let compression_type = logical_node
-            .file_compression_type()?
-            .unwrap_or(FileCompressionType::UNCOMPRESSED);
+        let tabix = logical_node.bool_option("tabix")?.unwrap_or(false);
+        let bgzip_requested = logical_node
+            .string_option("compression")?
+            .is_some_and(|c| c.eq_ignore_ascii_case("bgzip"));
 
-        let sink = Arc::new(SimpleRecordSink::new(
-            file_sink_config,
-            compression_type,
-            exon_file_type,
-        ));
+        let sink: Arc<dyn DataSink> = match exon_file_type {
+            ExonFileType::BED => {
+                let n_fields = match logical_node.string_option("n_fields")? {
+                    Some(n_fields) => {
+                        let n_fields = n_fields.parse::<usize>().map_err(|_| {
+                            datafusion::error::DataFusionError::Plan(format!(
+                                "Invalid n_fields option for BED: {}",
+                                n_fields
+                            ))
+                        })?;
+
+                        if !is_valid_n_fields(n_fields) {
+                            return Err(datafusion::error::DataFusionError::Plan(format!(
+                                "BED output must have 3, 4, 5, 6, or 12 fields, got {}",
+                                n_fields
+                            )));
+                        }
+
+                        n_fields
+                    }
+                    None => infer_n_fields(&schema),
+                };
+
+                Arc::new(
+                    BEDSink::new(file_sink_config, n_fields)
+                        .with_bgzip(bgzip_option(logical_node, "BED")?)
+                        .with_tabix(tabix),
+                )
+            }
+            ExonFileType::BCF => {
+                let sink = BCFSink::new(file_sink_config);
+
+                match sample_names_option(logical_node)? {
+                    Some(sample_names) => Arc::new(sink.with_sample_names(sample_names)),
+                    None => Arc::new(sink),
+                }
+            }
+            ExonFileType::VCF => {
+                let mut sink = VCFSink::new(file_sink_config)
+                    .with_bgzip(bgzip_option(logical_node, "VCF")?)
+                    .with_tabix(tabix);
+
+                if let Some(sample_names) = sample_names_option(logical_node)? {
+                    sink = sink.with_sample_names(sample_names);
+                }
+
+                Arc::new(sink)
+            }
+            // GFF output is only written as BGZF when asked for, gzip uses the usual writer.
+            ExonFileType::GFF if tabix || bgzip_requested => Arc::new(
+                GFFSink::new(file_sink_config, FileCompressionType::UNCOMPRESSED)
+                    .with_bgzip(bgzip_requested)
+                    .with_tabix(tabix),
+            ),
+            ExonFileType::FASTA => {
+                let line_width = match logical_node.string_option("line_width")? {
+                    Some(line_width) => line_width.parse::<usize>().map_err(|_| {
+                        datafusion::error::DataFusionError::Plan(format!(
+                            "Invalid line_width option for FASTA: {}",
+                            line_width
+                        ))
+                    })?,
+                    None => DEFAULT_LINE_WIDTH,
+                };
+
+                let compression_type = if bgzip_requested {
+                    FileCompressionType::UNCOMPRESSED
+                } else {
+                    logical_node
+                        .file_compression_type()?
+                        .unwrap_or(FileCompressionType::UNCOMPRESSED)
+                };
+
+                Arc::new(
+                    FASTASink::new(file_sink_config, compression_type)
+                        .with_line_width(line_width)
+                        .with_bgzip(bgzip_requested)
+                        .with_fai(logical_node.bool_option("fai")?.unwrap_or(false)),
+                )
+            }
+            ExonFileType::FASTQ => {
+                let quality_score = match logical_node.string_option("quality_score")? {
+                    Some(quality_score) => match quality_score.parse::<u8>() {
+                        Ok(q) if q <= MAX_QUALITY_SCORE => Some(q),
+                        _ => {
+                            return Err(datafusion::error::DataFusionError::Plan(format!(
+                                "Invalid quality_score option for FASTQ: {}, expected 0 to {}",
+                                quality_score, MAX_QUALITY_SCORE
+                            )))
+                        }
+                    },
+                    None => None,
+                };
+
+                let compression_type = logical_node
+                    .file_compression_type()?
+                    .unwrap_or(FileCompressionType::UNCOMPRESSED);
+
+                Arc::new(
+                    FASTQSink::new(file_sink_config, compression_type)
+                        .with_quality_score(quality_score),
+                )
+            }
+            ExonFileType::SAM | ExonFileType::BAM => {
+                let compression_type = logical_node
+                    .file_compression_type()?
+                    .unwrap_or(FileCompressionType::UNCOMPRESSED);
+
+                let sink = AlignmentSink::new(file_sink_config, compression_type, exon_file_type);
+
+                match alignment_header {
+                    Some(header) => Arc::new(sink.with_header(header)),
+                    None => Arc::new(sink),
+                }
+            }
+            _ => {
+                let compression_type = logical_node
+                    .file_compression_type()?
+                    .unwrap_or(FileCompressionType::UNCOMPRESSED);
+
+                match exon_file_type {
+                    ExonFileType::GFF => Arc::new(GFFSink::new(file_sink_config, compression_type)),
+                    _ => Arc::new(SimpleRecordSink::new(
+                        file_sink_config,
+                        compression_type,
+                        exon_file_type,
+                    )),
+                }
+            }
+        };
 
         let data_sink = DataSinkExec::new(physical_plan, sink, schema, None);
 
         Ok(Some(Arc::new(data_sink)))
     }
 }
+
+/// The header of SAM and BAM output, read from the `header` option's SAM, BAM, or header file,
+/// synthesized from the `sequence_dictionary` option's `.dict` or `.fai` file, or passed through
+/// from the SAM or BAM table being copied. The records are checked against it as they're written.
+async fn alignment_header(
+    logical_node: &ExonDataSinkLogicalPlanNode,
+    input_plan: &LogicalPlan,
+    session_state: &SessionState,
+) -> datafusion::error::Result<noodles::sam::Header> {
+    let header_option = logical_node.string_option("header")?;
+    let sequence_dictionary_option = logical_node.string_option("sequence_dictionary")?;
+
+    match (header_option, sequence_dictionary_option) {
+        (Some(_), Some(_)) => Err(datafusion::error::DataFusionError::Plan(
+            "Only one of the header and sequence_dictionary options can be set".to_string(),
+        )),
+        (Some(header), None) => {
+            let (object_store_url, path) = resolve_location(&header)?;
+            let object_store = session_state.runtime_env().object_store(object_store_url)?;
+
+            read_header(object_store, &path).await
+        }
+        (None, Some(sequence_dictionary)) => {
+            let (object_store_url, path) = resolve_location(&sequence_dictionary)?;
+            let object_store = session_state.runtime_env().object_store(object_store_url)?;
+
+            let header = read_header(object_store, &path).await?;
+            Ok(header_from_reference_sequences(
+                header.reference_sequences().clone(),
+            ))
+        }
+        (None, None) => {
+            let mut providers = Vec::new();
+            input_plan.apply(|plan| {
+                if let LogicalPlan::TableScan(scan) = plan {
+                    providers.push(source_as_provider(&scan.source)?);
+                }
+
+                Ok(TreeNodeRecursion::Continue)
+            })?;
+
+            for provider in providers {
+                let any = provider.as_any();
+
+                let header = if let Some(table) =
+                    any.downcast_ref::<ListingSAMTable<ListingSAMTableOptions>>()
+                {
+                    table.read_header(session_state).await?
+                } else if let Some(table) =
+                    any.downcast_ref::<ListingBAMTable<ListingBAMTableOptions>>()
+                {
+                    table.read_header(session_state).await?
+                } else {
+                    None
+                };
+
+                if let Some(header) = header {
+                    return Ok(header);
+                }
+            }
+
+            // Without a header only unmapped records can be written.
+            Ok(noodles::sam::Header::default())
+        }
+    }
+}
+
+/// Resolve a COPY target or option path to its object store and the path within it, where
+/// relative local paths are relative to the current directory.
+fn resolve_location(
+    location: &str,
+) -> datafusion::error::Result<(ObjectStoreUrl, object_store::path::Path)> {
+    let url = parse_url(location)?;
+    let authority = match url.host_str() {
+        Some(host) => format!("{}://{}", url.scheme(), host),
+        None => format!("{}://", url.scheme()),
+    };
+
+    let is_local = authority.starts_with("file://");
+
+    let path = if is_local {
+        let p = std::path::Path::new(location);
+
+        if p.is_absolute() {
+            object_store::path::Path::from_absolute_path(p)?
+        } else {
+            let current_dir = env::current_dir()?;
+
+            let absolute_path = current_dir.join(p);
+            object_store::path::Path::from_absolute_path(absolute_path)?
+        }
+    } else {
+        let path = &url.as_str()[authority.len()..];
+        object_store::path::Path::parse(path)?
+    };
+
+    Ok((url_to_object_store_url(&url)?, path))
+}
+
+/// Returns true if the output should be written as BGZF blocks.
+///
+/// BGZF is valid gzip, so gzip output is always written as bgzip.
+fn bgzip_option(
+    logical_node: &ExonDataSinkLogicalPlanNode,
+    format: &str,
+) -> datafusion::error::Result<bool> {
+    match logical_node.string_option("compression")? {
+        Some(c) if c.eq_ignore_ascii_case("gzip") || c.eq_ignore_ascii_case("bgzip") => Ok(true),
+        Some(c) if c.eq_ignore_ascii_case("uncompressed") => Ok(false),
+        Some(c) => Err(datafusion::error::DataFusionError::Plan(format!(
+            "Unsupported compression for {} output: {}",
+            format, c
+        ))),
+        None => Ok(false),
+    }
+}
+
+/// The comma separated `sample_names` option of variant output.
+fn sample_names_option(
+    logical_node: &ExonDataSinkLogicalPlanNode,
+) -> datafusion::error::Result<Option<Vec<String>>> {
+    Ok(logical_node
+        .string_option("sample_names")?
+        .map(|sample_names| {
+            sample_names
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .collect()
+        }))
+}
+
+fn plan_audit_scan(
+    node: &AuditScanNode,
+    physical_inputs: &[Arc<dyn ExecutionPlan>],
+    session_state: &SessionState,
+) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
+    let [input] = physical_inputs else {
+        return Err(datafusion::error::DataFusionError::Internal(
+            "AuditScanNode expects one input".to_string(),
+        ));
+    };
+
+    let exon_config = extract_exon_config(session_state.config())?;
+    let log = AuditLog::try_new(&exon_config.audit_log_path)?;
+
+    Ok(Arc::new(AuditExec::new(
+        Arc::clone(input),
+        node.table.clone(),
+        node.filters.clone(),
+        exon_config.audit_user.clone(),
+        Arc::new(log),
+    )))
+}
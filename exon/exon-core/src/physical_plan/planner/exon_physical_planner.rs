@@ -16,16 +16,28 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use datafusion::{
-    common::DFSchema,
+    common::{
+        tree_node::{Transformed, TreeNode},
+        DFSchema,
+    },
     datasource::file_format::{csv::CsvSink, json::JsonSink, parquet::ParquetSink},
-    error::Result,
+    error::{DataFusionError, Result},
     execution::context::SessionState,
-    logical_expr::{Expr, LogicalPlan},
-    physical_plan::{insert::DataSinkExec, ExecutionPlan, PhysicalExpr},
+    logical_expr::{Expr, LogicalPlan, TableProviderFilterPushDown},
+    physical_plan::{
+        empty::EmptyExec, insert::DataSinkExec, memory::MemoryExec,
+        placeholder_row::PlaceholderRowExec, values::ValuesExec, ExecutionPlan, PhysicalExpr,
+    },
     physical_planner::{DefaultPhysicalPlanner, PhysicalPlanner},
 };
+use exon_common::{ColumnTransformDirection, ColumnTransforms};
 
-use crate::ExonRuntimeEnvExt;
+use crate::{
+    config::extract_exon_config,
+    logical_plan::{AuditScanNode, DfExtensionNode},
+    physical_plan::column_transform_exec::ColumnTransformExec,
+    ExonRuntimeEnvExt,
+};
 
 use super::exon_extension_planner::ExomeExtensionPlanner;
 
@@ -51,11 +63,33 @@ impl PhysicalPlanner for ExonPhysicalPlanner {
         logical_plan: &LogicalPlan,
         session_state: &SessionState,
     ) -> Result<Arc<dyn ExecutionPlan>> {
+        let exon_config = extract_exon_config(session_state.config()).ok();
+
+        let transforms = match exon_config {
+            Some(config) => config.column_transforms(session_state.config())?,
+            None => None,
+        };
+
+        let mut logical_plan = logical_plan.clone();
+
+        if let Some(transforms) = &transforms {
+            logical_plan = remove_transformed_scan_filters(logical_plan, transforms)?;
+        }
+
+        if exon_config.is_some_and(|config| !config.audit_log_path.is_empty()) {
+            logical_plan = add_audit_scans(logical_plan)?;
+        }
+
         let plan = self
             .planner
-            .create_physical_plan(logical_plan, session_state)
+            .create_physical_plan(&logical_plan, session_state)
             .await?;
 
+        let plan = match &transforms {
+            Some(transforms) => add_column_transforms(plan, transforms)?,
+            None => plan,
+        };
+
         let runtime = session_state.runtime_env();
 
         // try to downcast plan as FileSinkExec
@@ -95,3 +129,116 @@ impl PhysicalPlanner for ExonPhysicalPlanner {
             .create_physical_expr(expr, input_dfschema, session_state)
     }
 }
+
+/// Remove the filters pushed into table scans that reference transformed columns.
+///
+/// The stored values of those columns are encrypted or tokenized, so a provider evaluating the
+/// filter would compare against the wrong values. Inexact filters are still evaluated above the
+/// scan after the values are transformed, while exact filters would be lost so they're an error.
+fn remove_transformed_scan_filters(
+    logical_plan: LogicalPlan,
+    transforms: &ColumnTransforms,
+) -> Result<LogicalPlan> {
+    let plan = logical_plan.transform_up(|plan| {
+        let LogicalPlan::TableScan(mut scan) = plan else {
+            return Ok(Transformed::no(plan));
+        };
+
+        let (removed, kept): (Vec<_>, Vec<_>) = scan.filters.into_iter().partition(|filter| {
+            filter
+                .column_refs()
+                .iter()
+                .any(|column| transforms.contains(&column.name))
+        });
+
+        if removed.is_empty() {
+            scan.filters = kept;
+            return Ok(Transformed::no(LogicalPlan::TableScan(scan)));
+        }
+
+        let pushdowns = scan
+            .source
+            .supports_filters_pushdown(&removed.iter().collect::<Vec<_>>())?;
+
+        if let Some((filter, _)) = removed
+            .iter()
+            .zip(pushdowns)
+            .find(|(_, pushdown)| *pushdown == TableProviderFilterPushDown::Exact)
+        {
+            return Err(DataFusionError::Plan(format!(
+                "Filter {} on a transformed column of {} can't be pushed down exactly",
+                filter, scan.table_name
+            )));
+        }
+
+        scan.filters = kept;
+        Ok(Transformed::yes(LogicalPlan::TableScan(scan)))
+    })?;
+
+    Ok(plan.data)
+}
+
+/// Add an audit node above every table scan, which is planned as an [`AuditExec`].
+///
+/// [`AuditExec`]: crate::physical_plan::audit_exec::AuditExec
+fn add_audit_scans(logical_plan: LogicalPlan) -> Result<LogicalPlan> {
+    let plan = logical_plan.transform_up(|plan| match plan {
+        LogicalPlan::TableScan(_) => {
+            let node = AuditScanNode::try_new(plan)?;
+            Ok(Transformed::yes(LogicalPlan::Extension(
+                node.into_extension(),
+            )))
+        }
+        _ => Ok(Transformed::no(plan)),
+    })?;
+
+    Ok(plan.data)
+}
+
+/// Returns true if the plan reads from storage rather than from memory.
+fn is_storage_scan(plan: &Arc<dyn ExecutionPlan>) -> bool {
+    let any = plan.as_any();
+
+    plan.children().is_empty()
+        && !any.is::<MemoryExec>()
+        && !any.is::<ValuesExec>()
+        && !any.is::<PlaceholderRowExec>()
+        && !any.is::<EmptyExec>()
+}
+
+/// Transform the values read by storage scans and the values written by a sink.
+///
+/// Table functions and custom plans are covered as long as they read through a leaf exec.
+fn add_column_transforms(
+    plan: Arc<dyn ExecutionPlan>,
+    transforms: &ColumnTransforms,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    let plan = plan
+        .transform_up(|plan| {
+            if is_storage_scan(&plan) && transforms.applies_to(&plan.schema()) {
+                let exec = ColumnTransformExec::new(
+                    plan,
+                    transforms.clone(),
+                    ColumnTransformDirection::Scan,
+                );
+
+                Ok(Transformed::yes(Arc::new(exec) as Arc<dyn ExecutionPlan>))
+            } else {
+                Ok(Transformed::no(plan))
+            }
+        })?
+        .data;
+
+    if !plan.as_any().is::<DataSinkExec>() {
+        return Ok(plan);
+    }
+
+    let input = match plan.children().as_slice() {
+        [input] if transforms.applies_to(&input.schema()) => Arc::clone(input),
+        _ => return Ok(plan),
+    };
+
+    let exec = ColumnTransformExec::new(input, transforms.clone(), ColumnTransformDirection::Sink);
+
+    plan.with_new_children(vec![Arc::new(exec)])
+}
@@ -0,0 +1,247 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, fmt, sync::Arc};
+
+use arrow::{
+    array::{Array, AsArray, RecordBatch, UInt64Array},
+    datatypes::{Schema, SchemaRef},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_expr::EquivalenceProperties,
+    physical_plan::{
+        stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, ExecutionPlan,
+        PlanProperties,
+    },
+};
+use futures::StreamExt;
+
+use crate::datasources::provenance::{provenance_fields, FILE_PATH_COLUMN, RECORD_INDEX_COLUMN};
+
+/// Counts the records of each file of a partition.
+#[derive(Debug, Default)]
+struct RecordCounter {
+    file_path: Option<String>,
+    next_index: u64,
+}
+
+impl RecordCounter {
+    /// Add the record index column to a batch of a file scan, which never spans files.
+    fn add_record_index(
+        &mut self,
+        schema: &SchemaRef,
+        file_path_index: usize,
+        batch: RecordBatch,
+    ) -> Result<RecordBatch> {
+        let file_paths = batch.column(file_path_index).as_string::<i32>();
+
+        if batch.num_rows() > 0 && file_paths.is_valid(0) {
+            let file_path = file_paths.value(0);
+
+            if self.file_path.as_deref() != Some(file_path) {
+                self.file_path = Some(file_path.to_string());
+                self.next_index = 0;
+            }
+        }
+
+        let start = self.next_index;
+        self.next_index += batch.num_rows() as u64;
+
+        let mut columns = batch.columns().to_vec();
+        columns.push(Arc::new(UInt64Array::from_iter_values(
+            start..self.next_index,
+        )));
+
+        Ok(RecordBatch::try_new(Arc::clone(schema), columns)?)
+    }
+}
+
+/// An execution plan that adds the index of each record in its file to a file scan with a file
+/// path column.
+///
+/// Each partition of a file scan reads its files one after the other, so the index restarts when
+/// the file path changes. A file that's split into byte ranges is counted from the start of each
+/// range.
+#[derive(Debug)]
+pub struct RecordIndexExec {
+    input: Arc<dyn ExecutionPlan>,
+    file_path_index: usize,
+    schema: SchemaRef,
+    properties: PlanProperties,
+}
+
+impl RecordIndexExec {
+    /// Create a new exec over a file scan with a file path column.
+    pub fn try_new(input: Arc<dyn ExecutionPlan>) -> Result<Self> {
+        let input_schema = input.schema();
+
+        let file_path_index = input_schema.index_of(FILE_PATH_COLUMN).map_err(|_| {
+            DataFusionError::Plan(format!(
+                "Counting records requires a {} column",
+                FILE_PATH_COLUMN
+            ))
+        })?;
+
+        let record_index = provenance_fields()
+            .into_iter()
+            .find(|f| f.name() == RECORD_INDEX_COLUMN)
+            .ok_or_else(|| DataFusionError::Internal("Missing record index field".to_string()))?;
+
+        let mut fields = input_schema.fields().to_vec();
+        fields.push(Arc::new(record_index));
+
+        let schema = Arc::new(Schema::new_with_metadata(
+            fields,
+            input_schema.metadata().clone(),
+        ));
+
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(Arc::clone(&schema)),
+            input.properties().output_partitioning().clone(),
+            input.properties().execution_mode(),
+        );
+
+        Ok(Self {
+            input,
+            file_path_index,
+            schema,
+            properties,
+        })
+    }
+}
+
+impl DisplayAs for RecordIndexExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RecordIndexExec")
+    }
+}
+
+impl ExecutionPlan for RecordIndexExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "RecordIndexExec"
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![true]
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.as_slice() {
+            [input] => Ok(Arc::new(Self::try_new(Arc::clone(input))?)),
+            _ => Err(DataFusionError::Internal(
+                "RecordIndexExec expects one child".to_string(),
+            )),
+        }
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let input = self.input.execute(partition, context)?;
+
+        let schema = Arc::clone(&self.schema);
+        let file_path_index = self.file_path_index;
+        let mut counter = RecordCounter::default();
+
+        let stream =
+            input.map(move |batch| counter.add_record_index(&schema, file_path_index, batch?));
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            Arc::clone(&self.schema),
+            stream,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{AsArray, RecordBatch, StringArray},
+        datatypes::{DataType, Field, Schema, UInt64Type},
+    };
+    use datafusion::physical_plan::{collect, memory::MemoryExec};
+
+    use super::RecordIndexExec;
+    use crate::ExonSession;
+
+    #[tokio::test]
+    async fn test_record_index_exec() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "_file_path",
+            DataType::Utf8,
+            false,
+        )]));
+
+        let batches = [vec!["a", "a"], vec!["a"], vec!["b", "b"]]
+            .into_iter()
+            .map(|paths| {
+                RecordBatch::try_new(
+                    Arc::clone(&schema),
+                    vec![Arc::new(StringArray::from(paths))],
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let input = MemoryExec::try_new(&[batches], schema, None)?;
+        let exec = RecordIndexExec::try_new(Arc::new(input))?;
+
+        let batches = collect(Arc::new(exec), ctx.session.task_ctx()).await?;
+
+        let indexes = batches
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column(1)
+                    .as_primitive::<UInt64Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(indexes, vec![0, 1, 2, 0, 1]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,459 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, collections::HashMap, fmt, sync::Arc};
+
+use arrow::{
+    array::{
+        Array, ArrayRef, AsArray, Int64Array, Int64Builder, ListArray, RecordBatch, StringArray,
+        StringBuilder, StructArray,
+    },
+    datatypes::{DataType, Field, Schema, SchemaRef},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_expr::EquivalenceProperties,
+    physical_plan::{
+        stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, Distribution,
+        ExecutionMode, ExecutionPlan, Partitioning, PlanProperties,
+    },
+};
+use exon_vcf::Breakend;
+use futures::TryStreamExt;
+
+use crate::sinks::columns_from_batch::{get_array_column, get_optional_array_column};
+
+/// The schema of the junctions of resolved breakends.
+///
+/// The strands follow the BEDPE convention, `+` if the junction is at the right of the position
+/// and `-` if it's at the left.
+pub fn breakend_junction_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("chrom1", DataType::Utf8, false),
+        Field::new("pos1", DataType::Int64, false),
+        Field::new("strand1", DataType::Utf8, false),
+        Field::new("chrom2", DataType::Utf8, false),
+        Field::new("pos2", DataType::Int64, false),
+        Field::new("strand2", DataType::Utf8, false),
+        Field::new("id1", DataType::Utf8, true),
+        Field::new("id2", DataType::Utf8, true),
+    ]))
+}
+
+/// A breakend record of the input.
+#[derive(Debug)]
+struct BreakendRecord {
+    chrom: String,
+    pos: i64,
+    id: Option<String>,
+    mate_id: Option<String>,
+    breakend: Breakend,
+}
+
+impl BreakendRecord {
+    /// True if `other`'s breakend points back at this record.
+    fn is_mate_of(&self, other: &BreakendRecord) -> bool {
+        other.breakend.mate_chrom == self.chrom
+            && other.breakend.mate_pos == self.pos
+            && self.breakend.mate_chrom == other.chrom
+            && self.breakend.mate_pos == other.pos
+    }
+}
+
+/// Get the first value of a string or list of strings array.
+fn first_string(array: &dyn Array, i: usize) -> Option<String> {
+    if array.is_null(i) {
+        return None;
+    }
+
+    match array.data_type() {
+        DataType::Utf8 => Some(array.as_string::<i32>().value(i).to_string()),
+        DataType::List(_) => {
+            let values = array.as_list::<i32>().value(i);
+            let values = values.as_string_opt::<i32>()?;
+
+            (!values.is_empty() && values.is_valid(0)).then(|| values.value(0).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Get the MATEID of a record from a parsed or unparsed info column.
+fn mate_id(info: &dyn Array, i: usize) -> Option<String> {
+    if info.is_null(i) {
+        return None;
+    }
+
+    match info.data_type() {
+        DataType::Struct(_) => {
+            let info = info.as_any().downcast_ref::<StructArray>()?;
+            first_string(info.column_by_name("MATEID")?.as_ref(), i)
+        }
+        DataType::Utf8 => info
+            .as_string::<i32>()
+            .value(i)
+            .split(';')
+            .find_map(|field| field.strip_prefix("MATEID="))
+            .and_then(|ids| ids.split(',').next())
+            .map(|id| id.to_string()),
+        _ => None,
+    }
+}
+
+/// Read the breakend records of a batch, skipping records without a breakend allele.
+fn breakend_records(batch: &RecordBatch, records: &mut Vec<BreakendRecord>) -> Result<()> {
+    let chroms = get_array_column::<StringArray>(batch, "chrom")?;
+    let positions = get_array_column::<Int64Array>(batch, "pos")?;
+    let alts = get_array_column::<ListArray>(batch, "alt")?;
+    let ids = get_optional_array_column::<ListArray>(batch, "id")?;
+    let infos = batch.column_by_name("info");
+
+    for i in 0..batch.num_rows() {
+        if alts.is_null(i) {
+            continue;
+        }
+
+        let alt = alts.value(i);
+        let alt = alt.as_string_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution("alt should be a list of strings".to_string())
+        })?;
+
+        let Some(breakend) = alt.iter().flatten().find_map(Breakend::parse) else {
+            continue;
+        };
+
+        records.push(BreakendRecord {
+            chrom: chroms.value(i).to_string(),
+            pos: positions.value(i),
+            id: ids.and_then(|ids| first_string(ids, i)),
+            mate_id: infos.and_then(|infos| mate_id(infos.as_ref(), i)),
+            breakend,
+        });
+    }
+
+    Ok(())
+}
+
+/// Pair the breakend records into junctions.
+///
+/// A record is paired with the record named by its MATEID, or failing that with the record at its
+/// mate position that points back at it. Each junction is emitted once, from its first record,
+/// and a record without a mate is emitted on its own with the mate position of its allele.
+fn resolve_breakends(schema: &SchemaRef, records: &[BreakendRecord]) -> Result<RecordBatch> {
+    let mut by_id = HashMap::new();
+    let mut by_position = HashMap::<(&str, i64), Vec<usize>>::new();
+
+    for (i, record) in records.iter().enumerate() {
+        if let Some(id) = &record.id {
+            by_id.entry(id.as_str()).or_insert(i);
+        }
+
+        by_position
+            .entry((record.chrom.as_str(), record.pos))
+            .or_default()
+            .push(i);
+    }
+
+    let mut paired = vec![false; records.len()];
+
+    let mut chroms1 = StringBuilder::new();
+    let mut positions1 = Int64Builder::new();
+    let mut strands1 = StringBuilder::new();
+    let mut chroms2 = StringBuilder::new();
+    let mut positions2 = Int64Builder::new();
+    let mut strands2 = StringBuilder::new();
+    let mut ids1 = StringBuilder::new();
+    let mut ids2 = StringBuilder::new();
+
+    for (i, record) in records.iter().enumerate() {
+        if paired[i] {
+            continue;
+        }
+        paired[i] = true;
+
+        let by_mate_id = record
+            .mate_id
+            .as_deref()
+            .and_then(|mate_id| by_id.get(mate_id))
+            .copied()
+            .filter(|j| !paired[*j]);
+
+        let mate = by_mate_id.or_else(|| {
+            by_position
+                .get(&(
+                    record.breakend.mate_chrom.as_str(),
+                    record.breakend.mate_pos,
+                ))?
+                .iter()
+                .copied()
+                .find(|j| !paired[*j] && records[*j].is_mate_of(record))
+        });
+
+        if let Some(j) = mate {
+            paired[j] = true;
+        }
+
+        chroms1.append_value(&record.chrom);
+        positions1.append_value(record.pos);
+        strands1.append_value(record.breakend.strand());
+        chroms2.append_value(&record.breakend.mate_chrom);
+        positions2.append_value(record.breakend.mate_pos);
+        strands2.append_value(record.breakend.mate_strand());
+        ids1.append_option(record.id.as_deref());
+        ids2.append_option(mate.and_then(|j| records[j].id.as_deref()));
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(chroms1.finish()),
+        Arc::new(positions1.finish()),
+        Arc::new(strands1.finish()),
+        Arc::new(chroms2.finish()),
+        Arc::new(positions2.finish()),
+        Arc::new(strands2.finish()),
+        Arc::new(ids1.finish()),
+        Arc::new(ids2.finish()),
+    ];
+
+    Ok(RecordBatch::try_new(Arc::clone(schema), columns)?)
+}
+
+/// An execution plan that pairs the breakend (BND) records of a VCF table into junctions.
+///
+/// Mates can be anywhere in the input, so the breakend records are buffered until the input is
+/// exhausted.
+#[derive(Debug)]
+pub struct ResolveBreakendsExec {
+    input: Arc<dyn ExecutionPlan>,
+    schema: SchemaRef,
+    properties: PlanProperties,
+}
+
+impl ResolveBreakendsExec {
+    /// Create a new exec over an input with chrom, pos, and alt columns, and optionally id and
+    /// info columns.
+    pub fn try_new(input: Arc<dyn ExecutionPlan>) -> Result<Self> {
+        let input_schema = input.schema();
+
+        for name in ["chrom", "pos", "alt"] {
+            if input_schema.column_with_name(name).is_none() {
+                return Err(DataFusionError::Plan(format!(
+                    "Resolving breakends requires a VCF table with a {} column",
+                    name
+                )));
+            }
+        }
+
+        let schema = breakend_junction_schema();
+
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(Arc::clone(&schema)),
+            Partitioning::UnknownPartitioning(1),
+            ExecutionMode::Bounded,
+        );
+
+        Ok(Self {
+            input,
+            schema,
+            properties,
+        })
+    }
+}
+
+impl DisplayAs for ResolveBreakendsExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ResolveBreakendsExec")
+    }
+}
+
+impl ExecutionPlan for ResolveBreakendsExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "ResolveBreakendsExec"
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        vec![Distribution::SinglePartition]
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let [input]: [Arc<dyn ExecutionPlan>; 1] = children.try_into().map_err(|_| {
+            DataFusionError::Internal("ResolveBreakendsExec expects one child".to_string())
+        })?;
+
+        Ok(Arc::new(Self::try_new(input)?))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "ResolveBreakendsExec has a single partition, got {}",
+                partition
+            )));
+        }
+
+        let input = self.input.execute(0, context)?;
+        let schema = Arc::clone(&self.schema);
+
+        let stream = futures::stream::once(async move {
+            let records = input
+                .try_fold(Vec::new(), |mut records, batch| async move {
+                    breakend_records(&batch, &mut records)?;
+                    Ok(records)
+                })
+                .await?;
+
+            resolve_breakends(&schema, &records)
+        });
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            Arc::clone(&self.schema),
+            stream,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{AsArray, ListBuilder, RecordBatch, StringArray, StringBuilder},
+        datatypes::{DataType, Field, Int64Type, Schema},
+    };
+    use datafusion::physical_plan::{collect, memory::MemoryExec};
+
+    use super::ResolveBreakendsExec;
+    use crate::ExonSession;
+
+    fn string_lists(values: &[&str]) -> arrow::array::ListArray {
+        let mut builder = ListBuilder::new(StringBuilder::new());
+
+        for value in values {
+            builder.values().append_value(value);
+            builder.append(true);
+        }
+
+        builder.finish()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_breakends() -> Result<(), Box<dyn std::error::Error>> {
+        let list_type = DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("chrom", DataType::Utf8, false),
+            Field::new("pos", DataType::Int64, false),
+            Field::new("id", list_type.clone(), true),
+            Field::new("alt", list_type, true),
+            Field::new("info", DataType::Utf8, true),
+        ]));
+
+        // bnd_a/bnd_b are mates by MATEID, bnd_c/bnd_d only by their alleles, and bnd_e's mate
+        // isn't in the input.
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(StringArray::from(vec!["1", "1", "2", "2", "3", "1"])),
+                Arc::new(arrow::array::Int64Array::from(vec![
+                    100, 200, 300, 321681, 500, 900,
+                ])),
+                Arc::new(string_lists(&[
+                    "bnd_a", "snv", "bnd_c", "bnd_b", "bnd_e", "bnd_d",
+                ])),
+                Arc::new(string_lists(&[
+                    "G]2:321681]",
+                    "T",
+                    "A[1:900[",
+                    "G]1:100]",
+                    "]4:10]C",
+                    "[2:300[T",
+                ])),
+                Arc::new(StringArray::from(vec![
+                    "SVTYPE=BND;MATEID=bnd_b",
+                    ".",
+                    "SVTYPE=BND",
+                    "SVTYPE=BND;MATEID=bnd_a",
+                    "SVTYPE=BND",
+                    "SVTYPE=BND",
+                ])),
+            ],
+        )?;
+
+        let input = Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None)?);
+        let exec = Arc::new(ResolveBreakendsExec::try_new(input)?);
+
+        let ctx = ExonSession::new_exon()?;
+        let batches = collect(exec, ctx.session.task_ctx()).await?;
+
+        let mut rows = Vec::new();
+        for batch in batches {
+            let string = |i: usize, row: usize| {
+                let column = batch.column(i).as_string::<i32>();
+                column.is_valid(row).then(|| column.value(row).to_string())
+            };
+            let int = |i: usize, row: usize| batch.column(i).as_primitive::<Int64Type>().value(row);
+
+            for row in 0..batch.num_rows() {
+                rows.push(format!(
+                    "{}:{}{} {}:{}{} {:?} {:?}",
+                    string(0, row).unwrap_or_default(),
+                    int(1, row),
+                    string(2, row).unwrap_or_default(),
+                    string(3, row).unwrap_or_default(),
+                    int(4, row),
+                    string(5, row).unwrap_or_default(),
+                    string(6, row),
+                    string(7, row),
+                ));
+            }
+        }
+
+        assert_eq!(
+            rows,
+            vec![
+                "1:100+ 2:321681+ Some(\"bnd_a\") Some(\"bnd_b\")",
+                "2:300+ 1:900- Some(\"bnd_c\") Some(\"bnd_d\")",
+                "3:500- 4:10+ Some(\"bnd_e\") None",
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, collections::HashMap, fmt, sync::Arc};
+
+use arrow::datatypes::SchemaRef;
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::{
+        object_store::ObjectStoreRegistry, runtime_env::RuntimeEnv, FunctionRegistry,
+        SendableRecordBatchStream, TaskContext,
+    },
+    physical_plan::{DisplayAs, DisplayFormatType, ExecutionPlan, PlanProperties},
+};
+use object_store::ObjectStore;
+use url::Url;
+
+/// The key of the store for a URL in an object store registry, its scheme and authority.
+fn object_store_key(url: &Url) -> String {
+    format!(
+        "{}://{}",
+        url.scheme(),
+        &url[url::Position::BeforeHost..url::Position::AfterPort]
+    )
+}
+
+/// An object store registry that returns a table's own store for the table's URL, and the
+/// registered stores for every other URL.
+#[derive(Debug)]
+struct TableObjectStoreRegistry {
+    inner: Arc<dyn ObjectStoreRegistry>,
+    key: String,
+    store: Arc<dyn ObjectStore>,
+}
+
+impl ObjectStoreRegistry for TableObjectStoreRegistry {
+    fn register_store(
+        &self,
+        url: &Url,
+        store: Arc<dyn ObjectStore>,
+    ) -> Option<Arc<dyn ObjectStore>> {
+        self.inner.register_store(url, store)
+    }
+
+    fn get_store(&self, url: &Url) -> Result<Arc<dyn ObjectStore>> {
+        if object_store_key(url) == self.key {
+            return Ok(Arc::clone(&self.store));
+        }
+
+        self.inner.get_store(url)
+    }
+}
+
+/// A runtime that reads the files at `url` through `store`, without changing the store
+/// registered for the URL in `runtime`.
+pub(crate) fn table_runtime_env(
+    runtime: &RuntimeEnv,
+    url: &Url,
+    store: Arc<dyn ObjectStore>,
+) -> RuntimeEnv {
+    RuntimeEnv {
+        memory_pool: Arc::clone(&runtime.memory_pool),
+        disk_manager: Arc::clone(&runtime.disk_manager),
+        cache_manager: Arc::clone(&runtime.cache_manager),
+        object_store_registry: Arc::new(TableObjectStoreRegistry {
+            inner: Arc::clone(&runtime.object_store_registry),
+            key: object_store_key(url),
+            store,
+        }),
+    }
+}
+
+/// An execution plan that runs a table's scan with the table's own object store, e.g. one that
+/// verifies checksums or reads through the cache, in place of the store registered for the
+/// table's URL.
+#[derive(Debug)]
+pub struct TableObjectStoreExec {
+    input: Arc<dyn ExecutionPlan>,
+    url: Url,
+    store: Arc<dyn ObjectStore>,
+}
+
+impl TableObjectStoreExec {
+    /// Create a new exec that runs `input` reading the files at `url` through `store`.
+    pub fn new(input: Arc<dyn ExecutionPlan>, url: Url, store: Arc<dyn ObjectStore>) -> Self {
+        Self { input, url, store }
+    }
+
+    /// The task context of the scan, with the table's object store.
+    ///
+    /// Scans don't call functions, so only the scalar functions are carried over.
+    fn table_context(&self, context: &TaskContext) -> Result<Arc<TaskContext>> {
+        let runtime = table_runtime_env(&context.runtime_env(), &self.url, Arc::clone(&self.store));
+
+        let scalar_functions = context
+            .udfs()
+            .into_iter()
+            .map(|name| Ok((name.clone(), context.udf(&name)?)))
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        Ok(Arc::new(TaskContext::new(
+            context.task_id(),
+            context.session_id(),
+            context.session_config().clone(),
+            scalar_functions,
+            HashMap::new(),
+            HashMap::new(),
+            Arc::new(runtime),
+        )))
+    }
+}
+
+impl DisplayAs for TableObjectStoreExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TableObjectStoreExec: url={}",
+            object_store_key(&self.url)
+        )
+    }
+}
+
+impl ExecutionPlan for TableObjectStoreExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "TableObjectStoreExec"
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        self.input.properties()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![true]
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.as_slice() {
+            [input] => Ok(Arc::new(Self::new(
+                Arc::clone(input),
+                self.url.clone(),
+                Arc::clone(&self.store),
+            ))),
+            _ => Err(DataFusionError::Internal(
+                "TableObjectStoreExec expects one child".to_string(),
+            )),
+        }
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        let table_context = self.table_context(&context)?;
+
+        self.input.execute(partition, table_context)
+    }
+}
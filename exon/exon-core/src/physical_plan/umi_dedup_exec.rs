@@ -0,0 +1,576 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    sync::Arc,
+};
+
+use arrow::{
+    array::{Array, ArrayRef, BooleanArray, Int64Array, RecordBatch, StringArray},
+    compute::{interleave, SortOptions},
+    datatypes::{DataType, Field, Schema, SchemaRef},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_expr::{EquivalenceProperties, LexRequirement, PhysicalSortRequirement},
+    physical_plan::{
+        expressions::col, stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType,
+        Distribution, ExecutionMode, ExecutionPlan, Partitioning, PlanProperties,
+    },
+};
+use futures::StreamExt;
+
+use crate::sinks::columns_from_batch::{get_array_column, get_flag_column};
+
+const REVERSE_FLAG: i32 = 0x10;
+
+/// The schema of the deduplicated alignments, the input schema plus the umi_cluster and
+/// is_duplicate columns.
+pub fn umi_dedup_schema(input_schema: &Schema) -> SchemaRef {
+    let mut fields = input_schema.fields().to_vec();
+    fields.push(Arc::new(Field::new("umi_cluster", DataType::Utf8, true)));
+    fields.push(Arc::new(Field::new(
+        "is_duplicate",
+        DataType::Boolean,
+        false,
+    )));
+
+    Arc::new(Schema::new(fields))
+}
+
+/// True if two UMIs differ by exactly one substitution.
+fn is_one_mismatch(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).filter(|(x, y)| x != y).count() == 1
+}
+
+/// Collapse UMIs with the directional method of UMI-tools.
+///
+/// `counts` are the UMIs and their read counts, most common first. A UMI is absorbed by a
+/// neighbor one mismatch away if the neighbor has at least twice its count minus one, and
+/// absorption is transitive, so each UMI maps to the most common UMI of its cluster.
+fn directional_clusters<'a>(counts: &[(&'a str, usize)]) -> HashMap<&'a str, &'a str> {
+    let mut representatives = HashMap::new();
+
+    for (i, (umi, _)) in counts.iter().enumerate() {
+        if representatives.contains_key(umi) {
+            continue;
+        }
+
+        representatives.insert(*umi, *umi);
+
+        let mut queue = vec![i];
+        while let Some(j) = queue.pop() {
+            let (parent, parent_count) = counts[j];
+
+            for (k, (child, child_count)) in counts.iter().enumerate() {
+                if !representatives.contains_key(child)
+                    && parent_count + 1 >= 2 * child_count
+                    && is_one_mismatch(parent, child)
+                {
+                    representatives.insert(*child, *umi);
+                    queue.push(k);
+                }
+            }
+        }
+    }
+
+    representatives
+}
+
+/// An input row with its UMI.
+#[derive(Debug)]
+struct UmiRead {
+    batch: usize,
+    row: usize,
+    umi: Option<String>,
+}
+
+/// The reads of a strand whose starts are within the window of the first read's start.
+#[derive(Debug)]
+struct PositionGroup {
+    reference: String,
+    start: i64,
+    reads: Vec<UmiRead>,
+}
+
+/// An input row with its UMI cluster and whether it's a duplicate.
+type Assignment = (usize, usize, Option<String>, bool);
+
+impl PositionGroup {
+    /// Cluster the UMIs of the group, marking every read of a cluster but the first as a
+    /// duplicate.
+    fn assign(self, finished: &mut Vec<Assignment>) {
+        let mut counts = HashMap::<&str, usize>::new();
+        for umi in self.reads.iter().filter_map(|read| read.umi.as_deref()) {
+            *counts.entry(umi).or_default() += 1;
+        }
+
+        let mut counts = counts.into_iter().collect::<Vec<_>>();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        let representatives = directional_clusters(&counts);
+        let mut seen = HashSet::new();
+
+        for read in &self.reads {
+            let cluster = read
+                .umi
+                .as_deref()
+                .and_then(|umi| representatives.get(umi).copied());
+            let is_duplicate = cluster.map_or(false, |cluster| !seen.insert(cluster));
+
+            finished.push((
+                read.batch,
+                read.row,
+                cluster.map(str::to_string),
+                is_duplicate,
+            ));
+        }
+    }
+}
+
+/// Deduplicates coordinate-sorted alignments by their UMIs.
+#[derive(Debug)]
+struct UmiDeduplicator {
+    window: i64,
+
+    /// The input batches that still have rows to emit, the first of which has index
+    /// `first_batch`.
+    batches: VecDeque<RecordBatch>,
+    first_batch: usize,
+
+    /// The open position group of the forward and reverse strand.
+    groups: [Option<PositionGroup>; 2],
+
+    last_position: Option<(String, i64)>,
+
+    finished: Vec<Assignment>,
+}
+
+impl UmiDeduplicator {
+    fn new(window: i64) -> Self {
+        Self {
+            window,
+            batches: VecDeque::new(),
+            first_batch: 0,
+            groups: [None, None],
+            last_position: None,
+            finished: Vec::new(),
+        }
+    }
+
+    /// Close the groups that can't contain reads at or after `start` of `reference`.
+    fn close_before(&mut self, reference: Option<&str>, start: i64) {
+        for group in self.groups.iter_mut() {
+            let closed = group.as_ref().map_or(false, |group| {
+                Some(group.reference.as_str()) != reference || start > group.start + self.window
+            });
+
+            if closed {
+                if let Some(group) = group.take() {
+                    group.assign(&mut self.finished);
+                }
+            }
+        }
+    }
+
+    /// Add the alignments of a batch with the `reference`, `start`, `flag`, and `umi` columns.
+    fn update(&mut self, batch: RecordBatch) -> Result<()> {
+        let batch_index = self.first_batch + self.batches.len();
+
+        {
+            let references = get_array_column::<StringArray>(&batch, "reference")?;
+            let starts = get_array_column::<Int64Array>(&batch, "start")?;
+            let flags = get_flag_column(&batch)?;
+            let umis = get_array_column::<StringArray>(&batch, "umi")?;
+
+            for i in 0..batch.num_rows() {
+                // Unplaced reads aren't deduplicated.
+                if references.is_null(i) || starts.is_null(i) {
+                    self.finished.push((batch_index, i, None, false));
+                    continue;
+                }
+
+                let reference = references.value(i);
+                let start = starts.value(i);
+
+                match &mut self.last_position {
+                    Some((last_reference, last_start)) if last_reference == reference => {
+                        if start < *last_start {
+                            return Err(DataFusionError::Execution(
+                                "UMI deduplication requires alignments sorted by start".to_string(),
+                            ));
+                        }
+
+                        *last_start = start;
+                    }
+                    _ => self.last_position = Some((reference.to_string(), start)),
+                }
+
+                self.close_before(Some(reference), start);
+
+                let strand = usize::from(flags.value(i) & REVERSE_FLAG != 0);
+
+                self.groups[strand]
+                    .get_or_insert_with(|| PositionGroup {
+                        reference: reference.to_string(),
+                        start,
+                        reads: Vec::new(),
+                    })
+                    .reads
+                    .push(UmiRead {
+                        batch: batch_index,
+                        row: i,
+                        umi: umis.is_valid(i).then(|| umis.value(i).to_string()),
+                    });
+            }
+        }
+
+        self.batches.push_back(batch);
+
+        Ok(())
+    }
+
+    /// Close the open groups at the end of the input.
+    fn finish(&mut self) {
+        self.close_before(None, i64::MAX);
+    }
+
+    /// Take the finished rows as a record batch, releasing the input batches that no open group
+    /// refers to.
+    fn take_batch(&mut self, schema: &SchemaRef) -> Result<RecordBatch> {
+        let finished = std::mem::take(&mut self.finished);
+
+        let indices = finished
+            .iter()
+            .map(|(batch, row, _, _)| (batch - self.first_batch, *row))
+            .collect::<Vec<_>>();
+
+        let n_input_columns = schema.fields().len() - 2;
+
+        let mut columns = (0..n_input_columns)
+            .map(|i| {
+                let arrays = self
+                    .batches
+                    .iter()
+                    .map(|batch| batch.column(i).as_ref())
+                    .collect::<Vec<&dyn Array>>();
+
+                Ok(interleave(&arrays, &indices)?)
+            })
+            .collect::<Result<Vec<ArrayRef>>>()?;
+
+        columns.push(Arc::new(StringArray::from_iter(
+            finished.iter().map(|(_, _, cluster, _)| cluster.as_deref()),
+        )));
+        columns.push(Arc::new(BooleanArray::from(
+            finished
+                .iter()
+                .map(|(_, _, _, is_duplicate)| *is_duplicate)
+                .collect::<Vec<_>>(),
+        )));
+
+        let first_open_batch = self
+            .groups
+            .iter()
+            .flatten()
+            .filter_map(|group| group.reads.first())
+            .map(|read| read.batch)
+            .min()
+            .unwrap_or(self.first_batch + self.batches.len());
+
+        while self.first_batch < first_open_batch {
+            self.batches.pop_front();
+            self.first_batch += 1;
+        }
+
+        Ok(RecordBatch::try_new(Arc::clone(schema), columns)?)
+    }
+}
+
+/// An execution plan that marks the PCR duplicates of coordinate-sorted alignments by their UMIs.
+///
+/// The input needs `reference`, `start`, `flag`, and `umi` columns, e.g. a BAM scan with the UMI
+/// extracted by `extract_umi`. Alignments on the same strand whose starts are within `window` of
+/// the first alignment of their group are grouped, and the UMIs of a group are collapsed with the
+/// directional method of UMI-tools, tolerating one mismatch. The first alignment of each UMI
+/// cluster is kept and the rest are marked as duplicates.
+///
+/// Groups are emitted as soon as no later alignment can join them, so the output isn't in input
+/// order.
+#[derive(Debug)]
+pub struct UmiDedupExec {
+    input: Arc<dyn ExecutionPlan>,
+    window: i64,
+    schema: SchemaRef,
+    properties: PlanProperties,
+}
+
+impl UmiDedupExec {
+    /// Create a new exec that groups the alignments of `input` whose starts are within `window`.
+    pub fn try_new(input: Arc<dyn ExecutionPlan>, window: i64) -> Result<Self> {
+        let input_schema = input.schema();
+
+        for column in ["reference", "start", "flag", "umi"] {
+            input_schema.field_with_name(column).map_err(|_| {
+                DataFusionError::Plan(format!(
+                    "UMI deduplication input requires a {} column",
+                    column
+                ))
+            })?;
+        }
+
+        let schema = umi_dedup_schema(&input_schema);
+
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(Arc::clone(&schema)),
+            Partitioning::UnknownPartitioning(1),
+            ExecutionMode::Bounded,
+        );
+
+        Ok(Self {
+            input,
+            window,
+            schema,
+            properties,
+        })
+    }
+}
+
+impl DisplayAs for UmiDedupExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "UmiDedupExec: window={}", self.window)
+    }
+}
+
+impl ExecutionPlan for UmiDedupExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "UmiDedupExec"
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        vec![Distribution::SinglePartition]
+    }
+
+    fn required_input_ordering(&self) -> Vec<Option<LexRequirement>> {
+        let input_schema = self.input.schema();
+
+        let ordering = ["reference", "start"]
+            .iter()
+            .map(|name| {
+                Ok(PhysicalSortRequirement::new(
+                    col(name, &input_schema)?,
+                    Some(SortOptions::default()),
+                ))
+            })
+            .collect::<Result<Vec<_>>>();
+
+        vec![ordering.ok().map(LexRequirement::new)]
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.as_slice() {
+            [input] => Ok(Arc::new(Self::try_new(Arc::clone(input), self.window)?)),
+            _ => Err(DataFusionError::Internal(
+                "UmiDedupExec expects one child".to_string(),
+            )),
+        }
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "UmiDedupExec has a single partition, got {}",
+                partition
+            )));
+        }
+
+        let input = self.input.execute(0, Arc::clone(&context))?;
+        let batch_size = context.session_config().batch_size();
+
+        let schema = Arc::clone(&self.schema);
+        let deduplicator = UmiDeduplicator::new(self.window);
+
+        let stream = futures::stream::unfold(
+            (input, deduplicator, false),
+            move |(mut input, mut deduplicator, done)| {
+                let schema = Arc::clone(&schema);
+
+                async move {
+                    if done {
+                        return None;
+                    }
+
+                    loop {
+                        match input.next().await {
+                            Some(Ok(batch)) => {
+                                if let Err(e) = deduplicator.update(batch) {
+                                    return Some((Err(e), (input, deduplicator, true)));
+                                }
+
+                                if deduplicator.finished.len() >= batch_size {
+                                    let batch = deduplicator.take_batch(&schema);
+                                    return Some((batch, (input, deduplicator, false)));
+                                }
+                            }
+                            Some(Err(e)) => return Some((Err(e), (input, deduplicator, true))),
+                            None => {
+                                deduplicator.finish();
+
+                                if deduplicator.finished.is_empty() {
+                                    return None;
+                                }
+
+                                let batch = deduplicator.take_batch(&schema);
+                                return Some((batch, (input, deduplicator, true)));
+                            }
+                        }
+                    }
+                }
+            },
+        );
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            Arc::clone(&self.schema),
+            stream,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{AsArray, Int32Array, Int64Array, RecordBatch, StringArray},
+        datatypes::{DataType, Field, Schema},
+    };
+    use datafusion::physical_plan::{collect, memory::MemoryExec};
+
+    use super::{directional_clusters, UmiDedupExec};
+    use crate::ExonSession;
+
+    #[test]
+    fn test_directional_clusters() {
+        // AAAA absorbs AAAT, which absorbs AATT, but CCCC is too common to be absorbed by CCCA.
+        let clusters = directional_clusters(&[
+            ("AAAA", 10),
+            ("CCCA", 6),
+            ("AAAT", 4),
+            ("CCCC", 4),
+            ("AATT", 1),
+        ]);
+
+        assert_eq!(clusters["AAAT"], "AAAA");
+        assert_eq!(clusters["AATT"], "AAAA");
+        assert_eq!(clusters["CCCA"], "CCCA");
+        assert_eq!(clusters["CCCC"], "CCCC");
+    }
+
+    #[tokio::test]
+    async fn test_umi_dedup_exec() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("reference", DataType::Utf8, true),
+            Field::new("start", DataType::Int64, true),
+            Field::new("flag", DataType::Int32, false),
+            Field::new("umi", DataType::Utf8, true),
+        ]));
+
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(StringArray::from(vec!["r1", "r2", "r3", "r4", "r5", "r6"])),
+                Arc::new(StringArray::from(vec![
+                    "chr1", "chr1", "chr1", "chr1", "chr1", "chr2",
+                ])),
+                Arc::new(Int64Array::from(vec![10, 10, 11, 11, 30, 10])),
+                Arc::new(Int32Array::from(vec![0, 0, 0, 16, 0, 0])),
+                Arc::new(StringArray::from(vec![
+                    "ACGT", "ACGT", "ACGA", "ACGT", "ACGT", "ACGT",
+                ])),
+            ],
+        )?;
+
+        let input = MemoryExec::try_new(&[vec![batch]], schema, None)?;
+        let exec = UmiDedupExec::try_new(Arc::new(input), 5)?;
+
+        let batches = collect(Arc::new(exec), ctx.session.task_ctx()).await?;
+
+        let mut rows = Vec::new();
+        for batch in batches {
+            let names = batch.column_by_name("name").unwrap().as_string::<i32>();
+            let clusters = batch
+                .column_by_name("umi_cluster")
+                .unwrap()
+                .as_string::<i32>();
+            let duplicates = batch.column_by_name("is_duplicate").unwrap().as_boolean();
+
+            for i in 0..batch.num_rows() {
+                rows.push((
+                    names.value(i).to_string(),
+                    clusters.value(i).to_string(),
+                    duplicates.value(i),
+                ));
+            }
+        }
+        rows.sort();
+
+        let expected = [
+            ("r1", "ACGT", false),
+            ("r2", "ACGT", true),
+            ("r3", "ACGT", true),
+            ("r4", "ACGT", false),
+            ("r5", "ACGT", false),
+            ("r6", "ACGT", false),
+        ]
+        .map(|(name, cluster, is_duplicate)| (name.to_string(), cluster.to_string(), is_duplicate));
+
+        assert_eq!(rows, expected);
+
+        Ok(())
+    }
+}
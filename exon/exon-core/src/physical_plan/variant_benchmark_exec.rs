@@ -0,0 +1,742 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    sync::Arc,
+};
+
+use arrow::{
+    array::{
+        Array, ArrayRef, AsArray, Float64Builder, Int64Builder, ListArray, RecordBatch,
+        StringBuilder,
+    },
+    compute::cast,
+    datatypes::{DataType, Field, Int64Type, Schema, SchemaRef},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_expr::EquivalenceProperties,
+    physical_plan::{
+        stream::RecordBatchStreamAdapter, DisplayAs, DisplayFormatType, Distribution,
+        ExecutionMode, ExecutionPlan, Partitioning, PlanProperties,
+    },
+};
+use futures::TryStreamExt;
+
+use crate::sinks::columns_from_batch::get_array_column;
+
+/// The name of the stratum of the whole genome.
+const WHOLE_GENOME_STRATUM: &str = "*";
+
+/// The name of the stratum of intervals without a name.
+const UNNAMED_STRATUM: &str = "regions";
+
+/// The schema of the per-stratum metrics of a variant benchmark.
+///
+/// The metrics of each stratum are given for all its variants, with a variant class of `ALL`,
+/// and for each class of variant it has.
+pub fn variant_benchmark_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("stratum", DataType::Utf8, false),
+        Field::new("variant_class", DataType::Utf8, false),
+        Field::new("truth_total", DataType::Int64, false),
+        Field::new("query_total", DataType::Int64, false),
+        Field::new("true_positives", DataType::Int64, false),
+        Field::new("false_negatives", DataType::Int64, false),
+        Field::new("false_positives", DataType::Int64, false),
+        Field::new("precision", DataType::Float64, true),
+        Field::new("recall", DataType::Float64, true),
+        Field::new("f1_score", DataType::Float64, true),
+    ]))
+}
+
+/// The class of a normalized variant allele.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum VariantClass {
+    /// A single base substitution.
+    Snp,
+
+    /// A substitution of several bases.
+    Mnp,
+
+    /// An insertion or deletion.
+    Indel,
+
+    /// A replacement of bases by a different number of bases.
+    Complex,
+}
+
+impl VariantClass {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Snp => "SNP",
+            Self::Mnp => "MNP",
+            Self::Indel => "INDEL",
+            Self::Complex => "COMPLEX",
+        }
+    }
+}
+
+/// A biallelic variant whose alleles are trimmed of their shared bases.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct NormalizedVariant {
+    chrom: String,
+    pos: i64,
+    reference: String,
+    alternate: String,
+}
+
+impl NormalizedVariant {
+    /// Normalize an allele of a record, or `None` if it's not a sequence allele, e.g. a symbolic
+    /// or breakend allele, or it's the same as the reference.
+    ///
+    /// The shared suffix and then the shared prefix are trimmed, keeping at least one base of
+    /// each allele. Alleles aren't left-aligned, which requires the reference sequence.
+    fn new(chrom: &str, pos: i64, reference: &str, alternate: &str) -> Option<Self> {
+        let is_sequence =
+            |allele: &str| !allele.is_empty() && allele.bytes().all(|b| b.is_ascii_alphabetic());
+
+        if !is_sequence(reference) || !is_sequence(alternate) {
+            return None;
+        }
+
+        let reference = reference.to_ascii_uppercase();
+        let alternate = alternate.to_ascii_uppercase();
+
+        if reference == alternate {
+            return None;
+        }
+
+        let (mut reference, mut alternate) = (reference.as_bytes(), alternate.as_bytes());
+
+        while reference.len() > 1
+            && alternate.len() > 1
+            && reference[reference.len() - 1] == alternate[alternate.len() - 1]
+        {
+            reference = &reference[..reference.len() - 1];
+            alternate = &alternate[..alternate.len() - 1];
+        }
+
+        let mut pos = pos;
+
+        while reference.len() > 1 && alternate.len() > 1 && reference[0] == alternate[0] {
+            reference = &reference[1..];
+            alternate = &alternate[1..];
+            pos += 1;
+        }
+
+        Some(Self {
+            chrom: chrom.to_string(),
+            pos,
+            reference: String::from_utf8_lossy(reference).into_owned(),
+            alternate: String::from_utf8_lossy(alternate).into_owned(),
+        })
+    }
+
+    fn class(&self) -> VariantClass {
+        match (self.reference.len(), self.alternate.len()) {
+            (1, 1) => VariantClass::Snp,
+            (r, a) if r == a => VariantClass::Mnp,
+            (1, _) | (_, 1) => VariantClass::Indel,
+            _ => VariantClass::Complex,
+        }
+    }
+
+    /// The zero-based, half-open interval of the reference bases of the variant.
+    fn span(&self) -> (i64, i64) {
+        let start = self.pos - 1;
+        (start, start + self.reference.len() as i64)
+    }
+}
+
+/// Read the normalized alleles of a batch with chrom, pos, ref, and alt columns.
+fn normalized_variants(
+    batch: &RecordBatch,
+    variants: &mut HashSet<NormalizedVariant>,
+) -> Result<()> {
+    let column = |name: &str, data_type: &DataType| -> Result<ArrayRef> {
+        let column = batch.column_by_name(name).ok_or_else(|| {
+            DataFusionError::Execution(format!("Benchmarking variants requires a {} column", name))
+        })?;
+
+        Ok(cast(column, data_type)?)
+    };
+
+    let chroms = column("chrom", &DataType::Utf8)?;
+    let chroms = chroms.as_string::<i32>();
+    let positions = column("pos", &DataType::Int64)?;
+    let positions = positions.as_primitive::<Int64Type>();
+    let references = column("ref", &DataType::Utf8)?;
+    let references = references.as_string::<i32>();
+    let alts = get_array_column::<ListArray>(batch, "alt")?;
+
+    for i in 0..batch.num_rows() {
+        if chroms.is_null(i) || positions.is_null(i) || references.is_null(i) || alts.is_null(i) {
+            continue;
+        }
+
+        let alt = alts.value(i);
+        let alt = alt.as_string_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution("alt should be a list of strings".to_string())
+        })?;
+
+        for alternate in alt.iter().flatten() {
+            if let Some(variant) = NormalizedVariant::new(
+                chroms.value(i),
+                positions.value(i),
+                references.value(i),
+                alternate,
+            ) {
+                variants.insert(variant);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The intervals of the named strata, merged and sorted by start for each reference sequence.
+#[derive(Debug, Default)]
+struct Strata {
+    strata: BTreeMap<String, HashMap<String, Vec<(i64, i64)>>>,
+}
+
+impl Strata {
+    /// Add the intervals of a batch whose first three columns are the reference, start, and end
+    /// of half-open intervals, and whose optional fourth column names their stratum.
+    fn push_batch(&mut self, batch: &RecordBatch) -> Result<()> {
+        let references = cast(batch.column(0), &DataType::Utf8)?;
+        let references = references.as_string::<i32>();
+        let starts = cast(batch.column(1), &DataType::Int64)?;
+        let starts = starts.as_primitive::<Int64Type>();
+        let ends = cast(batch.column(2), &DataType::Int64)?;
+        let ends = ends.as_primitive::<Int64Type>();
+
+        let names = match batch.columns().get(3) {
+            Some(names) => Some(cast(names, &DataType::Utf8)?),
+            None => None,
+        };
+        let names = names.as_ref().map(|names| names.as_string::<i32>());
+
+        for i in 0..batch.num_rows() {
+            if references.is_null(i) || starts.is_null(i) || ends.is_null(i) {
+                continue;
+            }
+
+            let name = names
+                .filter(|names| names.is_valid(i))
+                .map_or(UNNAMED_STRATUM, |names| names.value(i));
+
+            self.strata
+                .entry(name.to_string())
+                .or_default()
+                .entry(references.value(i).to_string())
+                .or_default()
+                .push((starts.value(i), ends.value(i)));
+        }
+
+        Ok(())
+    }
+
+    /// Sort and merge the overlapping intervals of each stratum.
+    fn merge(mut self) -> Self {
+        for intervals in self.strata.values_mut().flat_map(|s| s.values_mut()) {
+            intervals.sort_unstable();
+
+            let mut merged: Vec<(i64, i64)> = Vec::with_capacity(intervals.len());
+            for (start, end) in intervals.drain(..) {
+                match merged.last_mut() {
+                    Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                    _ => merged.push((start, end)),
+                }
+            }
+
+            *intervals = merged;
+        }
+
+        self
+    }
+
+    /// The names of the strata, after the whole genome stratum.
+    fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(WHOLE_GENOME_STRATUM).chain(self.strata.keys().map(|k| k.as_str()))
+    }
+
+    /// The indexes of the strata, in the order of [`Strata::names`], that overlap a variant.
+    fn strata_of(&self, variant: &NormalizedVariant) -> Vec<usize> {
+        let (start, end) = variant.span();
+
+        let named = self
+            .strata
+            .values()
+            .enumerate()
+            .filter(|(_, stratum)| {
+                stratum.get(&variant.chrom).is_some_and(|intervals| {
+                    let i = intervals.partition_point(|(_, interval_end)| *interval_end <= start);
+                    intervals
+                        .get(i)
+                        .is_some_and(|(interval_start, _)| *interval_start < end)
+                })
+            })
+            .map(|(i, _)| i + 1);
+
+        std::iter::once(0).chain(named).collect()
+    }
+}
+
+/// The counts of the truth and query variants of a stratum and variant class.
+#[derive(Debug, Default, Clone, Copy)]
+struct BenchmarkCounts {
+    truth_total: i64,
+    query_total: i64,
+    true_positives: i64,
+}
+
+/// Match the query variants against the truth variants and count them by stratum and class.
+///
+/// The class `None` counts the variants of all classes.
+fn benchmark_counts(
+    truth: &HashSet<NormalizedVariant>,
+    query: &HashSet<NormalizedVariant>,
+    strata: &Strata,
+) -> BTreeMap<(usize, Option<VariantClass>), BenchmarkCounts> {
+    let mut counts = BTreeMap::new();
+
+    // Every stratum has a row for all its variants, even without any.
+    for (i, _) in strata.names().enumerate() {
+        counts.insert((i, None), BenchmarkCounts::default());
+    }
+
+    // The keys of the counts of a variant's strata, for all classes and for its own class.
+    let keys = |variant: &NormalizedVariant| {
+        let class = variant.class();
+
+        strata
+            .strata_of(variant)
+            .into_iter()
+            .flat_map(move |stratum| [(stratum, None), (stratum, Some(class))])
+    };
+
+    for variant in truth.iter() {
+        let matched = query.contains(variant);
+
+        for key in keys(variant) {
+            let c = counts.entry(key).or_default();
+
+            c.truth_total += 1;
+            if matched {
+                c.true_positives += 1;
+            }
+        }
+    }
+
+    for variant in query.iter() {
+        for key in keys(variant) {
+            counts.entry(key).or_default().query_total += 1;
+        }
+    }
+
+    counts
+}
+
+/// The ratio of two counts, or null if the denominator is zero.
+fn ratio(numerator: i64, denominator: i64) -> Option<f64> {
+    (denominator > 0).then(|| numerator as f64 / denominator as f64)
+}
+
+fn benchmark_batch(
+    schema: &SchemaRef,
+    truth: &HashSet<NormalizedVariant>,
+    query: &HashSet<NormalizedVariant>,
+    strata: &Strata,
+) -> Result<RecordBatch> {
+    let names = strata.names().collect::<Vec<_>>();
+    let counts = benchmark_counts(truth, query, strata);
+
+    let mut stratum_names = StringBuilder::new();
+    let mut classes = StringBuilder::new();
+    let mut truth_totals = Int64Builder::new();
+    let mut query_totals = Int64Builder::new();
+    let mut true_positives = Int64Builder::new();
+    let mut false_negatives = Int64Builder::new();
+    let mut false_positives = Int64Builder::new();
+    let mut precisions = Float64Builder::new();
+    let mut recalls = Float64Builder::new();
+    let mut f1_scores = Float64Builder::new();
+
+    for ((stratum, class), c) in counts {
+        let false_negative = c.truth_total - c.true_positives;
+        let false_positive = c.query_total - c.true_positives;
+
+        let precision = ratio(c.true_positives, c.query_total);
+        let recall = ratio(c.true_positives, c.truth_total);
+        let f1_score = match (precision, recall) {
+            (Some(p), Some(r)) if p + r > 0.0 => Some(2.0 * p * r / (p + r)),
+            (Some(_), Some(_)) => Some(0.0),
+            _ => None,
+        };
+
+        stratum_names.append_value(names[stratum]);
+        classes.append_value(class.map_or("ALL", |class| class.as_str()));
+        truth_totals.append_value(c.truth_total);
+        query_totals.append_value(c.query_total);
+        true_positives.append_value(c.true_positives);
+        false_negatives.append_value(false_negative);
+        false_positives.append_value(false_positive);
+        precisions.append_option(precision);
+        recalls.append_option(recall);
+        f1_scores.append_option(f1_score);
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(stratum_names.finish()),
+        Arc::new(classes.finish()),
+        Arc::new(truth_totals.finish()),
+        Arc::new(query_totals.finish()),
+        Arc::new(true_positives.finish()),
+        Arc::new(false_negatives.finish()),
+        Arc::new(false_positives.finish()),
+        Arc::new(precisions.finish()),
+        Arc::new(recalls.finish()),
+        Arc::new(f1_scores.finish()),
+    ];
+
+    Ok(RecordBatch::try_new(Arc::clone(schema), columns)?)
+}
+
+/// An execution plan that benchmarks the variants of a query callset against a truth set, with
+/// precision and recall for each stratum and class of variant.
+///
+/// Multiallelic records are split into their alleles, which are matched by their position and
+/// bases after trimming the bases they share with the reference allele. The strata are the whole
+/// genome, named `*`, and the optional third input's intervals grouped by name. A variant is in
+/// a stratum if its reference bases overlap one of the stratum's intervals.
+///
+/// Both callsets are buffered until their inputs are exhausted.
+#[derive(Debug)]
+pub struct VariantBenchmarkExec {
+    inputs: Vec<Arc<dyn ExecutionPlan>>,
+    schema: SchemaRef,
+    properties: PlanProperties,
+}
+
+impl VariantBenchmarkExec {
+    /// Create a new exec over truth and query inputs with chrom, pos, ref, and alt columns, and
+    /// optionally a strata input whose first three columns are the reference, start, and end of
+    /// half-open intervals, and whose fourth column names their stratum.
+    pub fn try_new(inputs: Vec<Arc<dyn ExecutionPlan>>) -> Result<Self> {
+        if !(2..=3).contains(&inputs.len()) {
+            return Err(DataFusionError::Plan(format!(
+                "Benchmarking variants requires truth, query, and optionally strata inputs, got {} inputs",
+                inputs.len()
+            )));
+        }
+
+        for input in inputs.iter().take(2) {
+            let input_schema = input.schema();
+
+            for name in ["chrom", "pos", "ref", "alt"] {
+                if input_schema.column_with_name(name).is_none() {
+                    return Err(DataFusionError::Plan(format!(
+                        "Benchmarking variants requires VCF tables with a {} column",
+                        name
+                    )));
+                }
+            }
+        }
+
+        if let Some(strata) = inputs.get(2) {
+            if strata.schema().fields().len() < 3 {
+                return Err(DataFusionError::Plan(
+                    "Benchmarking variants requires strata with reference, start, and end columns"
+                        .to_string(),
+                ));
+            }
+        }
+
+        let schema = variant_benchmark_schema();
+
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(Arc::clone(&schema)),
+            Partitioning::UnknownPartitioning(1),
+            ExecutionMode::Bounded,
+        );
+
+        Ok(Self {
+            inputs,
+            schema,
+            properties,
+        })
+    }
+}
+
+impl DisplayAs for VariantBenchmarkExec {
+    fn fmt_as(&self, _t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "VariantBenchmarkExec: stratified={}",
+            self.inputs.len() > 2
+        )
+    }
+}
+
+impl ExecutionPlan for VariantBenchmarkExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "VariantBenchmarkExec"
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        vec![Distribution::SinglePartition; self.inputs.len()]
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false; self.inputs.len()]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        self.inputs.iter().collect()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(Self::try_new(children)?))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        if partition != 0 {
+            return Err(DataFusionError::Internal(format!(
+                "VariantBenchmarkExec has a single partition, got {}",
+                partition
+            )));
+        }
+
+        let truth = self.inputs[0].execute(0, Arc::clone(&context))?;
+        let query = self.inputs[1].execute(0, Arc::clone(&context))?;
+        let strata = self
+            .inputs
+            .get(2)
+            .map(|strata| strata.execute(0, context))
+            .transpose()?;
+
+        let schema = Arc::clone(&self.schema);
+
+        let stream = futures::stream::once(async move {
+            let read_variants = |input: SendableRecordBatchStream| {
+                input.try_fold(HashSet::new(), |mut variants, batch| async move {
+                    normalized_variants(&batch, &mut variants)?;
+                    Ok(variants)
+                })
+            };
+
+            let truth = read_variants(truth).await?;
+            let query = read_variants(query).await?;
+
+            let strata = match strata {
+                Some(strata) => strata
+                    .try_fold(Strata::default(), |mut strata, batch| async move {
+                        strata.push_batch(&batch)?;
+                        Ok(strata)
+                    })
+                    .await?
+                    .merge(),
+                None => Strata::default(),
+            };
+
+            benchmark_batch(&schema, &truth, &query, &strata)
+        });
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            Arc::clone(&self.schema),
+            stream,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Array, AsArray, Int64Array, ListBuilder, RecordBatch, StringArray, StringBuilder},
+        datatypes::{DataType, Field, Float64Type, Int64Type, Schema},
+    };
+    use datafusion::physical_plan::{collect, memory::MemoryExec, ExecutionPlan};
+
+    use super::{NormalizedVariant, VariantBenchmarkExec, VariantClass};
+    use crate::ExonSession;
+
+    fn variants(rows: &[(&str, i64, &str, &[&str])]) -> Arc<dyn ExecutionPlan> {
+        let list_type = DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("chrom", DataType::Utf8, false),
+            Field::new("pos", DataType::Int64, false),
+            Field::new("ref", DataType::Utf8, false),
+            Field::new("alt", list_type, true),
+        ]));
+
+        let mut alts = ListBuilder::new(StringBuilder::new());
+        for (_, _, _, alt) in rows {
+            for allele in alt.iter() {
+                alts.values().append_value(allele);
+            }
+            alts.append(true);
+        }
+
+        let batch = RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.0))),
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.1))),
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.2))),
+                Arc::new(alts.finish()),
+            ],
+        )
+        .unwrap();
+
+        Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None).unwrap())
+    }
+
+    #[test]
+    fn test_normalized_variant() {
+        let variant = NormalizedVariant::new("1", 100, "ACGT", "AGGT").unwrap();
+        assert_eq!((variant.pos, variant.reference.as_str()), (101, "C"));
+        assert_eq!(variant.alternate, "G");
+        assert_eq!(variant.class(), VariantClass::Snp);
+
+        let variant = NormalizedVariant::new("1", 100, "ATT", "AT").unwrap();
+        assert_eq!((variant.pos, variant.reference.as_str()), (100, "AT"));
+        assert_eq!(variant.alternate, "A");
+        assert_eq!(variant.class(), VariantClass::Indel);
+        assert_eq!(variant.span(), (99, 101));
+
+        let variant = NormalizedVariant::new("1", 100, "AC", "GT").unwrap();
+        assert_eq!(variant.class(), VariantClass::Mnp);
+
+        let variant = NormalizedVariant::new("1", 100, "AC", "GTT").unwrap();
+        assert_eq!(variant.class(), VariantClass::Complex);
+
+        assert!(NormalizedVariant::new("1", 100, "A", "<DEL>").is_none());
+        assert!(NormalizedVariant::new("1", 100, "A", "*").is_none());
+        assert!(NormalizedVariant::new("1", 100, "A", "G]2:321681]").is_none());
+        assert!(NormalizedVariant::new("1", 100, "A", "a").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_variant_benchmark() -> Result<(), Box<dyn std::error::Error>> {
+        let truth = variants(&[
+            ("1", 100, "A", &["G"]),
+            ("1", 200, "AT", &["A"]),
+            ("1", 300, "C", &["T", "G"]),
+            ("2", 100, "G", &["C"]),
+        ]);
+
+        // The deletion is written with an extra shared base, and one allele of the multiallelic
+        // record is missed.
+        let query = variants(&[
+            ("1", 100, "a", &["g"]),
+            ("1", 200, "ATT", &["AT"]),
+            ("1", 300, "C", &["T"]),
+            ("1", 400, "T", &["TA"]),
+        ]);
+
+        let strata_schema = Arc::new(Schema::new(vec![
+            Field::new("chrom", DataType::Utf8, false),
+            Field::new("start", DataType::Int64, false),
+            Field::new("end", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let strata_batch = RecordBatch::try_new(
+            Arc::clone(&strata_schema),
+            vec![
+                Arc::new(StringArray::from(vec!["1", "1", "2"])),
+                Arc::new(Int64Array::from(vec![150, 250, 0])),
+                Arc::new(Int64Array::from(vec![250, 350, 50])),
+                Arc::new(StringArray::from(vec![Some("easy"), Some("easy"), None])),
+            ],
+        )?;
+        let strata = Arc::new(MemoryExec::try_new(
+            &[vec![strata_batch]],
+            strata_schema,
+            None,
+        )?);
+
+        let exec = Arc::new(VariantBenchmarkExec::try_new(vec![truth, query, strata])?);
+
+        let ctx = ExonSession::new_exon()?;
+        let batches = collect(exec, ctx.session.task_ctx()).await?;
+
+        let mut rows = Vec::new();
+        for batch in batches {
+            let string = |i: usize, row: usize| batch.column(i).as_string::<i32>().value(row);
+            let int = |i: usize, row: usize| batch.column(i).as_primitive::<Int64Type>().value(row);
+            let float = |i: usize, row: usize| {
+                let column = batch.column(i).as_primitive::<Float64Type>();
+                column
+                    .is_valid(row)
+                    .then(|| format!("{:.2}", column.value(row)))
+            };
+
+            for row in 0..batch.num_rows() {
+                rows.push(format!(
+                    "{} {} {} {} {} {} {} {:?} {:?}",
+                    string(0, row),
+                    string(1, row),
+                    int(2, row),
+                    int(3, row),
+                    int(4, row),
+                    int(5, row),
+                    int(6, row),
+                    float(7, row),
+                    float(8, row),
+                ));
+            }
+        }
+
+        assert_eq!(
+            rows,
+            vec![
+                "* ALL 5 4 3 2 1 Some(\"0.75\") Some(\"0.60\")",
+                "* SNP 4 2 2 2 0 Some(\"1.00\") Some(\"0.50\")",
+                "* INDEL 1 2 1 0 1 Some(\"0.50\") Some(\"1.00\")",
+                "easy ALL 3 2 2 1 0 Some(\"1.00\") Some(\"0.67\")",
+                "easy SNP 2 1 1 1 0 Some(\"1.00\") Some(\"0.50\")",
+                "easy INDEL 1 1 1 0 0 Some(\"1.00\") Some(\"1.00\")",
+                "regions ALL 0 0 0 0 0 None None",
+            ]
+        );
+
+        Ok(())
+    }
+}
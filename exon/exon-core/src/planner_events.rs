@@ -0,0 +1,109 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Structured events for the planning decisions of Exon tables.
+//!
+//! The events are `tracing` events at the debug level with the [`PLANNER_EVENT_TARGET`] target
+//! and an `event` field naming the decision:
+//!
+//! - `schema_inferred`: the schema inferred for a new table, with its file type and location.
+//! - `schema_coerced`: the columns cast or dropped to fit a table into a user-supplied schema.
+//! - `filter_pushdown`: whether a filter is pushed down to a table's scan, and how.
+//! - `files_pruned`: the number of files a scan listed, and how many are left after pruning.
+//! - `index_count`: a count answered from index metadata instead of a scan.
+//!
+//! Exon never writes to stdout or stderr itself, so the events are only seen if the embedding
+//! application's subscriber enables the target, e.g. with the `exon::planner=debug` directive of
+//! an `EnvFilter`, which can be swapped at runtime with a `tracing_subscriber::reload` layer. The
+//! CLI reads the directives from `EXON_LOG`.
+
+use arrow::datatypes::Schema;
+
+/// The target of the planner events.
+pub const PLANNER_EVENT_TARGET: &str = "exon::planner";
+
+/// The comma separated names of the fields of a schema.
+pub(crate) fn field_names(schema: &Schema) -> String {
+    schema
+        .fields()
+        .iter()
+        .map(|f| f.name().as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{
+        field::{Field, Visit},
+        Event, Subscriber,
+    };
+    use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+    use super::PLANNER_EVENT_TARGET;
+    use crate::ExonSession;
+
+    /// Records the `event` field of the planner events.
+    #[derive(Clone, Default)]
+    struct PlannerEvents(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for PlannerEvents {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "event" {
+                self.0.lock().unwrap().push(value.to_string());
+            }
+        }
+
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    impl<S: Subscriber> Layer<S> for PlannerEvents {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            if event.metadata().target() == PLANNER_EVENT_TARGET {
+                event.record(&mut self.clone());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_planner_events() -> Result<(), Box<dyn std::error::Error>> {
+        let events = PlannerEvents::default();
+        let _guard = tracing_subscriber::registry()
+            .with(events.clone())
+            .set_default();
+
+        let ctx = ExonSession::new_exon()?;
+        let table_path = exon_test::test_path("bed-partition", "");
+
+        let sql = format!(
+            "CREATE EXTERNAL TABLE bed STORED AS BED PARTITIONED BY (sample) LOCATION '{}'",
+            table_path.to_str().ok_or("Invalid path")?
+        );
+        ctx.session.sql(&sql).await?;
+
+        ctx.session
+            .sql("SELECT COUNT(*) FROM bed WHERE sample = '1'")
+            .await?
+            .collect()
+            .await?;
+
+        let events = events.0.lock().unwrap().clone();
+        for event in ["schema_inferred", "filter_pushdown", "files_pruned"] {
+            assert!(events.iter().any(|e| e == event), "no {} event", event);
+        }
+
+        Ok(())
+    }
+}
@@ -17,9 +17,10 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use datafusion::{error::DataFusionError, execution::runtime_env::RuntimeEnv};
 use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::http::HttpBuilder;
 use object_store::ObjectStore;
 
-use exon_io::build_s3_object_store;
+use exon_io::{build_s3_object_store, RetryObjectStore, RetryPolicy};
 
 /// Extension trait for [`RuntimeEnv`] that provides additional methods for Exon use-cases.
 ///
@@ -35,11 +36,23 @@ pub trait ExonRuntimeEnvExt {
     ) -> Result<Option<Arc<dyn ObjectStore>>, DataFusionError>;
 
     /// Register an object store "intelligently" given the URL.
+    ///
+    /// Remote object stores retry transient GET errors with the default [`RetryPolicy`]. Files
+    /// served over plain HTTP(S) are read with range requests, so they can be scanned, including
+    /// through an index, without downloading them first, but their directories can't be listed.
     async fn exon_register_object_store_url(
         &self,
         url: &url::Url,
     ) -> Result<Option<Arc<dyn ObjectStore>>, DataFusionError>;
 
+    /// Register an object store "intelligently" given the URL, remote object stores retry
+    /// transient GET errors with the given policy.
+    async fn exon_register_object_store_url_with_retry_policy(
+        &self,
+        url: &url::Url,
+        retry_policy: RetryPolicy,
+    ) -> Result<Option<Arc<dyn ObjectStore>>, DataFusionError>;
+
     /// Register an object store with the given URI.
     async fn exon_register_object_store_uri(
         &self,
@@ -67,9 +80,28 @@ impl ExonRuntimeEnvExt for Arc<RuntimeEnv> {
     async fn exon_register_object_store_url(
         &self,
         url: &url::Url,
+    ) -> Result<Option<Arc<dyn ObjectStore>>, DataFusionError> {
+        self.exon_register_object_store_url_with_retry_policy(url, RetryPolicy::default())
+            .await
+    }
+
+    async fn exon_register_object_store_url_with_retry_policy(
+        &self,
+        url: &url::Url,
+        retry_policy: RetryPolicy,
     ) -> Result<Option<Arc<dyn ObjectStore>>, DataFusionError> {
         match url.scheme() {
-            "s3" => self.register_s3_object_store(url).await,
+            "s3" => {
+                let s3 = match build_s3_object_store(url).await {
+                    Ok(object_store) => object_store,
+                    Err(e) => return Err(DataFusionError::Execution(e.to_string())),
+                };
+
+                let s3 = Arc::new(RetryObjectStore::new(s3, retry_policy));
+                let previous = self.register_object_store(url, s3);
+
+                Ok(previous)
+            }
             "gs" => {
                 // Check that the GOOGLE_SERVICE_ACCOUNT env var is set
                 if std::env::var("GOOGLE_SERVICE_ACCOUNT").is_err() {
@@ -84,10 +116,24 @@ impl ExonRuntimeEnvExt for Arc<RuntimeEnv> {
                         .build()?,
                 );
 
+                let gcs = Arc::new(RetryObjectStore::new(gcs, retry_policy));
                 let previous = self.register_object_store(url, gcs);
 
                 Ok(previous)
             }
+            "http" | "https" => {
+                // The object store is registered for the host, and paths are relative to it.
+                let base_url = &url[..url::Position::BeforePath];
+                let http = Arc::new(HttpBuilder::new().with_url(base_url).build()?);
+
+                let http = Arc::new(RetryObjectStore::new(http, retry_policy));
+                let previous = self.register_object_store(url, http);
+
+                Ok(previous)
+            }
+            "ftp" => Err(DataFusionError::Execution(format!(
+                "FTP is not supported, use the HTTPS URL of {url} if the server has one"
+            ))),
             "file" => {
                 use object_store::local::LocalFileSystem;
 
@@ -115,3 +161,34 @@ impl ExonRuntimeEnvExt for Arc<RuntimeEnv> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::execution::runtime_env::RuntimeEnv;
+
+    use super::ExonRuntimeEnvExt;
+
+    #[tokio::test]
+    async fn test_register_http_object_store() -> Result<(), Box<dyn std::error::Error>> {
+        let runtime = Arc::new(RuntimeEnv::default());
+
+        let url = url::Url::parse(
+            "https://ftp.ensembl.org/pub/current_fasta/homo_sapiens/dna/Homo_sapiens.GRCh38.dna.chromosome.MT.fa.gz",
+        )?;
+        runtime.exon_register_object_store_url(&url).await?;
+
+        // The store is registered for the host, so other files on it resolve to the same store.
+        let other_url = url::Url::parse("https://ftp.ensembl.org/pub/README")?;
+        assert!(runtime.object_store(&other_url).is_ok());
+
+        let ftp_url = url::Url::parse("ftp://ftp.ensembl.org/pub/README")?;
+        assert!(runtime
+            .exon_register_object_store_url(&ftp_url)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+}
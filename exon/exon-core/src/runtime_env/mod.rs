@@ -14,4 +14,8 @@
 
 mod exon_runtime_env_ext;
 
+pub use exon_io::{
+    find_checksum_error, CachedObject, CachingObjectStore, ChecksumError, ChecksumKind,
+    ChecksumObjectStore, ObjectCache, RetryObjectStore, RetryPolicy,
+};
 pub use exon_runtime_env_ext::ExonRuntimeEnvExt;
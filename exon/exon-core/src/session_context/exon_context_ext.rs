@@ -12,24 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashMap, sync::Arc, vec};
+use std::{collections::HashMap, str::FromStr, sync::Arc, vec};
 
+use arrow::datatypes::{DataType, SchemaRef};
 use datafusion::{
     catalog::TableProviderFactory,
     datasource::{
-        file_format::file_compression_type::FileCompressionType, listing::ListingTableUrl,
+        file_format::file_compression_type::FileCompressionType, listing::ListingTableUrl, MemTable,
     },
     error::{DataFusionError, Result},
     execution::{
         object_store::ObjectStoreUrl, runtime_env::RuntimeEnv, session_state::SessionStateBuilder,
     },
-    logical_expr::LogicalPlan,
-    prelude::{DataFrame, SessionConfig, SessionContext},
+    functions::core::expr_fn::get_field,
+    logical_expr::{cast, col, Expr, LogicalPlan, ScalarUDF},
+    prelude::{CsvReadOptions, DataFrame, SessionConfig, SessionContext},
 };
 #[cfg(feature = "deltalake")]
 use deltalake::{aws::register_handlers, delta_datafusion::DeltaTableFactory, open_table};
+use exon_common::{ColumnTransformKeyProvider, ColumnTransformer, ColumnTransformerRegistry};
+use exon_illumina::IlluminaFileKind;
 
 use crate::{
+    config::{extract_config_from_state, ExonSettingsFunction},
     datasources::{
         bam::table_provider::{ListingBAMTable, ListingBAMTableOptions},
         bcf::table_provider::{ListingBCFTable, ListingBCFTableOptions},
@@ -40,20 +45,29 @@ use crate::{
         bigwig,
         cram::table_provider::{ListingCRAMTable, ListingCRAMTableConfig, ListingCRAMTableOptions},
         exon_listing_table_options::ExonListingConfig,
+        explicit_schema::ExplicitSchemaTable,
         genbank::table_provider::{ListingGenbankTable, ListingGenbankTableOptions},
         gff::table_provider::{ListingGFFTable, ListingGFFTableOptions},
         gtf::table_provider::{ListingGTFTable, ListingGTFTableOptions},
         hmmdomtab::table_provider::{ListingHMMDomTabTable, ListingHMMDomTabTableOptions},
         mzml::table_provider::{ListingMzMLTable, ListingMzMLTableOptions},
+        region_filter_table::RegionFilterTableFactory,
         sam::table_provider::{ListingSAMTable, ListingSAMTableOptions},
         sdf::ListingSDFTableOptions,
         vcf::ListingVCFTable,
     },
     error::ExonError,
-    logical_plan::{DfExtensionNode, ExonDataSinkLogicalPlanNode, ExonLogicalPlan},
+    logical_plan::{
+        DfExtensionNode, ExonDataSinkLogicalPlanNode, ExonLogicalPlan, GenomeBuildRule,
+    },
     sql::{ExonParser, ExonStatement},
     udfs::{
-        register_bigwig_region_filter_udf, sam::cram_region_filter::register_cram_region_filter_udf,
+        gene_id::{normalize_gene_id_columns, GeneIdMapping, MapGeneId},
+        liftover::{read_liftover_chain, LiftoverRegistry},
+        register_bigwig_region_filter_udf,
+        sam::cram_region_filter::register_cram_region_filter_udf,
+        sketch::SketchRegistry,
+        taxonomy::{read_taxdump, taxonomy_names_table, taxonomy_nodes_table, taxonomy_udfs},
     },
 };
 
@@ -68,27 +82,48 @@ use crate::datasources::fcs::FCSScanFunction;
 #[cfg(feature = "genbank")]
 use crate::datasources::genbank::GenbankScanFunction;
 
+#[cfg(feature = "sra")]
+use crate::datasources::sra::SRARunsFunction;
+
 use crate::{
     datasources::{
-        bam::{BAMIndexedScanFunction, BAMScanFunction},
-        bcf::BCFScanFunction,
+        bam::{BAMIndexedScanFunction, BAMPileupFunction, BAMScanFunction, UmiDedupFunction},
+        bcf::{BCFIndexedScanFunction, BCFScanFunction},
         bed::BEDScanFunction,
+        classification::{ClassificationFileKind, ClassificationScanFunction},
+        counts::CountsScanFunction,
         fasta::{
             table_provider::{ListingFASTATable, ListingFASTATableOptions},
             FastaIndexedScanFunction, FastaScanFunction,
         },
         fastq::{
             table_provider::{ListingFASTQTable, ListingFASTQTableOptions},
-            FastqScanFunction,
+            DemultiplexFunction, FastqScanFunction,
         },
         gff::{GFFIndexedScanFunction, GFFScanFunction},
         gtf::GTFScanFunction,
         hmmdomtab::HMMDomTabScanFunction,
+        illumina::IlluminaScanFunction,
+        intervals::{
+            ComplementIntervalsFunction, IntervalSetTable, MergeIntervalsFunction,
+            SubtractIntervalsFunction,
+        },
+        mtx::MTXScanFunction,
+        object_cache::CacheFetchFunction,
+        pod5::Pod5ScanFunction,
+        quantification::{QuantificationFileKind, QuantificationScanFunction},
         sam::SAMScanFunction,
-        vcf::{ListingVCFTableOptions, VCFIndexedScanFunction, VCFScanFunction},
+        sequencing_summary::SequencingSummaryScanFunction,
+        vcf::{
+            BenchmarkVariantsFunction, BreakendTable, GeneOverlapTable, ListingVCFTableOptions,
+            ResolveBreakendsFunction, VCFIndexedScanFunction, VCFScanFunction,
+            VariantBenchmarkTable, VariantGeneOverlapFunction,
+        },
+        vcf_zarr::VCFZarrScanFunction,
         ExonFileType, ExonListingTableFactory,
     },
     new_exon_config,
+    physical_optimizer::index_count_rule::IndexCountRule,
     physical_plan::planner::ExonQueryPlanner,
     udfs::{
         gff::gff_region_filter::register_gff_region_filter_udf,
@@ -151,17 +186,47 @@ impl ExonSession {
             "VCF",
             #[cfg(feature = "mzml")]
             "MZML",
+            #[cfg(feature = "mzml")]
+            "IMZML",
             #[cfg(feature = "fcs")]
             "FCS",
             "SDF",
+            "POD5",
+            "SEQUENCING_SUMMARY",
+            "MTX",
+            "VCF_ZARR",
+            "ILLUMINA_RUN_INFO",
+            "ILLUMINA_RUN_PARAMETERS",
+            "ILLUMINA_TILE_METRICS",
+            "ILLUMINA_QUALITY_METRICS",
+            "ILLUMINA_ERROR_METRICS",
+            "KRAKEN_REPORT",
+            "KRAKEN_OUTPUT",
+            "CENTRIFUGE_REPORT",
+            "CENTRIFUGE_OUTPUT",
+            "COUNTS",
+            "SALMON_QUANT",
+            "KALLISTO_ABUNDANCE",
         ];
 
+        // The genome build rule lifts joins over with the chains registered in the config.
+        let liftovers = config
+            .get_extension::<LiftoverRegistry>()
+            .unwrap_or_default();
+        let config = config.with_extension(Arc::clone(&liftovers));
+
+        // The sketch UDFs share the registry in the config, so sketches outlive a query.
+        let sketches = config.get_extension::<SketchRegistry>().unwrap_or_default();
+        let config = config.with_extension(sketches);
+
         let mut state_builder = SessionStateBuilder::new()
             .with_default_features()
             .with_config(config)
             .with_runtime_env(runtime)
             .with_function_factory(Some(Arc::new(ExonFunctionFactory::default())))
-            .with_query_planner(Arc::new(ExonQueryPlanner::default()));
+            .with_query_planner(Arc::new(ExonQueryPlanner::default()))
+            .with_analyzer_rule(Arc::new(GenomeBuildRule::new(liftovers)))
+            .with_physical_optimizer_rule(Arc::new(IndexCountRule::default()));
 
         let table_factories =
             state_builder
@@ -178,10 +243,23 @@ impl ExonSession {
             );
         }
 
+        // Answer region filters on Parquet tables with filters their row group statistics prune.
+        if let Some(parquet) = table_factories.remove("PARQUET") {
+            table_factories.insert(
+                "PARQUET".to_string(),
+                Arc::new(RegionFilterTableFactory::new(parquet)),
+            );
+        }
+
         #[cfg(feature = "deltalake")]
         {
             register_handlers(None);
-            table_factories.insert("DELTATABLE".to_string(), Arc::new(DeltaTableFactory {}));
+            table_factories.insert(
+                "DELTATABLE".to_string(),
+                Arc::new(RegionFilterTableFactory::new(Arc::new(
+                    DeltaTableFactory {},
+                ))),
+            );
         }
 
         let state = state_builder.build();
@@ -194,8 +272,13 @@ impl ExonSession {
         crate::udfs::massspec::register_udfs(&ctx);
 
         crate::udfs::sequence::register_udfs(&ctx);
-        crate::udfs::sam::samflags::register_udfs(&ctx);
+        crate::udfs::protein::register_udfs(&ctx);
+        crate::udfs::sam::register_udfs(&ctx);
         crate::udfs::vcf::register_vcf_udfs(&ctx);
+        crate::udfs::intervals::register_udfs(&ctx);
+        crate::udfs::reference::register_udfs(&ctx);
+        crate::udfs::sketch::register_udfs(&ctx);
+        crate::udfs::normalization::register_udfs(&ctx);
 
         // Register BAM region filter UDF
         register_bam_region_filter_udf(&ctx);
@@ -219,6 +302,10 @@ impl ExonSession {
             Arc::new(FastaIndexedScanFunction::new(ctx.clone())),
         );
         ctx.register_udtf("fastq_scan", Arc::new(FastqScanFunction::new(ctx.clone())));
+        ctx.register_udtf(
+            "demultiplex",
+            Arc::new(DemultiplexFunction::new(ctx.clone())),
+        );
         ctx.register_udtf("gff_scan", Arc::new(GFFScanFunction::new(ctx.clone())));
         ctx.register_udtf(
             "gff_indexed_scan",
@@ -230,6 +317,57 @@ impl ExonSession {
             "hmm_dom_tab_scan",
             Arc::new(HMMDomTabScanFunction::default()),
         );
+        ctx.register_udtf("pod5_scan", Arc::new(Pod5ScanFunction::default()));
+        ctx.register_udtf(
+            "sequencing_summary_scan",
+            Arc::new(SequencingSummaryScanFunction::new(ctx.clone())),
+        );
+        ctx.register_udtf("mtx_scan", Arc::new(MTXScanFunction::default()));
+        ctx.register_udtf("exon_cache_fetch", Arc::new(CacheFetchFunction::default()));
+        ctx.register_udtf(
+            "exon_settings",
+            Arc::new(ExonSettingsFunction::new(ctx.clone())),
+        );
+
+        for kind in [
+            IlluminaFileKind::RunInfo,
+            IlluminaFileKind::RunParameters,
+            IlluminaFileKind::TileMetrics,
+            IlluminaFileKind::QualityMetrics,
+            IlluminaFileKind::ErrorMetrics,
+        ] {
+            ctx.register_udtf(
+                &format!("illumina_{kind}_scan"),
+                Arc::new(IlluminaScanFunction::new(kind)),
+            );
+        }
+
+        for kind in [
+            ClassificationFileKind::KrakenReport,
+            ClassificationFileKind::KrakenOutput,
+            ClassificationFileKind::CentrifugeReport,
+            ClassificationFileKind::CentrifugeOutput,
+        ] {
+            ctx.register_udtf(
+                &format!("{kind}_scan"),
+                Arc::new(ClassificationScanFunction::new(kind)),
+            );
+        }
+        ctx.register_udtf("counts_scan", Arc::new(CountsScanFunction::default()));
+
+        for kind in [
+            QuantificationFileKind::SalmonQuant,
+            QuantificationFileKind::KallistoAbundance,
+        ] {
+            ctx.register_udtf(
+                &format!("{kind}_scan"),
+                Arc::new(QuantificationScanFunction::new(kind)),
+            );
+        }
+        ctx.register_udtf(
+            "vcf_zarr_scan",
+            Arc::new(VCFZarrScanFunction::new(ctx.clone())),
+        );
 
         #[cfg(feature = "genbank")]
         ctx.register_udtf(
@@ -237,17 +375,28 @@ impl ExonSession {
             Arc::new(GenbankScanFunction::new(ctx.clone())),
         );
 
+        #[cfg(feature = "sra")]
+        ctx.register_udtf("sra_runs", Arc::new(SRARunsFunction::default()));
+
         #[cfg(feature = "fcs")]
         ctx.register_udtf("fcs_scan", Arc::new(FCSScanFunction::new(ctx.clone())));
 
         #[cfg(feature = "mzml")]
         ctx.register_udtf("mzml_scan", Arc::new(MzMLScanFunction::new(ctx.clone())));
 
+        #[cfg(feature = "mzml")]
+        ctx.register_udtf(
+            "imzml_scan",
+            Arc::new(MzMLScanFunction::new(ctx.clone()).with_imzml(true)),
+        );
+
         ctx.register_udtf("bam_scan", Arc::new(BAMScanFunction::new(ctx.clone())));
         ctx.register_udtf(
             "bam_indexed_scan",
             Arc::new(BAMIndexedScanFunction::new(ctx.clone())),
         );
+        ctx.register_udtf("bam_pileup", Arc::new(BAMPileupFunction::new(ctx.clone())));
+        ctx.register_udtf("umi_dedup", Arc::new(UmiDedupFunction::new(ctx.clone())));
 
         ctx.register_udtf("sam_scan", Arc::new(SAMScanFunction::new(ctx.clone())));
         ctx.register_udtf("vcf_scan", Arc::new(VCFScanFunction::new(ctx.clone())));
@@ -256,6 +405,35 @@ impl ExonSession {
             Arc::new(VCFIndexedScanFunction::new(ctx.clone())),
         );
         ctx.register_udtf("bcf_scan", Arc::new(BCFScanFunction::new(ctx.clone())));
+        ctx.register_udtf(
+            "bcf_indexed_scan",
+            Arc::new(BCFIndexedScanFunction::new(ctx.clone())),
+        );
+        ctx.register_udtf(
+            "resolve_breakends",
+            Arc::new(ResolveBreakendsFunction::new(ctx.clone())),
+        );
+        ctx.register_udtf(
+            "benchmark_variants",
+            Arc::new(BenchmarkVariantsFunction::new(ctx.clone())),
+        );
+        ctx.register_udtf(
+            "variant_gene_overlap",
+            Arc::new(VariantGeneOverlapFunction::new(ctx.clone())),
+        );
+
+        ctx.register_udtf(
+            "merge_intervals",
+            Arc::new(MergeIntervalsFunction::default()),
+        );
+        ctx.register_udtf(
+            "subtract_intervals",
+            Arc::new(SubtractIntervalsFunction::default()),
+        );
+        ctx.register_udtf(
+            "complement_intervals",
+            Arc::new(ComplementIntervalsFunction::default()),
+        );
 
         // Register the local file system by default
         ctx.runtime_env().register_object_store(
@@ -575,14 +753,49 @@ impl ExonSession {
         Ok(table)
     }
 
-    /// Read a Delta Lake table.
+    /// Read an Exon table into a schema, casting the columns of the inferred schema to their
+    /// declared types and dropping the ones that aren't declared.
+    pub async fn read_exon_table_with_schema(
+        &self,
+        table_path: &str,
+        file_type: ExonFileType,
+        file_compression_type: Option<FileCompressionType>,
+        schema: SchemaRef,
+    ) -> crate::Result<DataFrame> {
+        let session_state = self.session.state();
+
+        let file_compression_type =
+            file_compression_type.unwrap_or(FileCompressionType::UNCOMPRESSED);
+
+        let table = ExonListingTableFactory::default()
+            .create_from_file_type(
+                &session_state,
+                file_type,
+                file_compression_type,
+                table_path.to_string(),
+                Vec::new(),
+                &HashMap::new(),
+            )
+            .await?;
+
+        let table = ExplicitSchemaTable::try_new(table, schema)?;
+
+        let table = self.session.read_table(Arc::new(table))?;
+
+        Ok(table)
+    }
+
+    /// Read a Delta Lake table, answering region filters like `vcf_region_filter` with filters
+    /// its file statistics prune.
     #[cfg(feature = "deltalake")]
     pub async fn read_deltalake(&self, table_path: &str) -> Result<DataFrame, ExonError> {
         let table = open_table(table_path).await.map_err(|e| {
             ExonError::ExecutionError(format!("Error opening Delta Lake table: {}", e))
         })?;
 
-        let df = self.session.read_table(Arc::new(table))?;
+        let df = self.session.read_table(Arc::new(
+            crate::datasources::region_filter_table::RegionFilterTable::new(Arc::new(table)),
+        ))?;
 
         Ok(df)
     }
@@ -617,8 +830,11 @@ impl ExonSession {
             .infer_schema(&self.session.state(), &table_path)
             .await?;
 
+        let property_index = extract_config_from_state(&self.session.state())?.sdf_property_index;
+
         let config = ExonListingConfig::new_with_options(table_path, options);
-        let table = crate::datasources::sdf::ListingSDFTable::new(config, table_schema);
+        let table = crate::datasources::sdf::ListingSDFTable::new(config, table_schema)
+            .with_property_index(property_index);
 
         let table = self.session.read_table(Arc::new(table))?;
 
@@ -672,7 +888,7 @@ impl ExonSession {
         let table_schema = options.infer_schema();
 
         let config = ExonListingConfig::new_with_options(table_path, options);
-        let table = ListingFASTQTable::try_new(config, table_schema)?;
+        let table = ListingFASTQTable::new(config, table_schema);
 
         let table = self.session.read_table(Arc::new(table))?;
 
@@ -696,6 +912,253 @@ impl ExonSession {
         Ok(())
     }
 
+    /// Load a gene ID cross-reference file into a table and register `map_gene_id` backed by it.
+    ///
+    /// The file is either a GTF, from which the `gene_id` and `gene_name` attributes are mapped
+    /// as `ensembl` and `symbol`, or a TSV with a header such as the HGNC or Ensembl BioMart
+    /// downloads. Common column names are normalized to `ensembl`, `refseq`, `hgnc`, `entrez`, and
+    /// `symbol`, so IDs can then be mapped with e.g. `map_gene_id(gene_id, 'ensembl->symbol')`.
+    /// Registering another mapping replaces the one used by `map_gene_id`.
+    pub async fn register_gene_id_mapping(
+        &self,
+        table_name: &str,
+        table_path: &str,
+    ) -> crate::Result<()> {
+        let df = match crate::datasources::infer_file_type_and_compression(table_path) {
+            Ok((ExonFileType::GTF, file_compression_type)) => {
+                let gene_id = get_field(col("attributes"), "gene_id");
+                let gene_name = get_field(col("attributes"), "gene_name");
+
+                self.read_gtf(
+                    table_path,
+                    ListingGTFTableOptions::new(file_compression_type),
+                )
+                .await?
+                .filter(gene_id.clone().is_not_null())?
+                .select(vec![gene_id.alias("ensembl"), gene_name.alias("symbol")])?
+                .distinct()?
+            }
+            _ => {
+                let extension = table_path.rsplit('.').next().unwrap_or("");
+                let file_compression_type = FileCompressionType::from_str(extension)
+                    .unwrap_or(FileCompressionType::UNCOMPRESSED);
+
+                let options = CsvReadOptions::new()
+                    .has_header(true)
+                    .delimiter(b'\t')
+                    .file_extension("")
+                    .file_compression_type(file_compression_type);
+
+                let df = self.session.read_csv(table_path, options).await?;
+
+                let names = normalize_gene_id_columns(
+                    df.schema().fields().iter().map(|f| f.name().as_str()),
+                );
+
+                let exprs = df
+                    .schema()
+                    .columns()
+                    .into_iter()
+                    .zip(names)
+                    .map(|(column, name)| cast(Expr::Column(column), DataType::Utf8).alias(name))
+                    .collect::<Vec<_>>();
+
+                df.select(exprs)?
+            }
+        };
+
+        let schema: SchemaRef = Arc::new(df.schema().as_arrow().clone());
+        let batches = df.collect().await?;
+
+        let mapping = GeneIdMapping::try_new(&schema, &batches)?;
+        let table = MemTable::try_new(schema, vec![batches])?;
+
+        self.session.register_table(table_name, Arc::new(table))?;
+        self.session
+            .register_udf(ScalarUDF::from(MapGeneId::new(Arc::new(mapping))));
+
+        Ok(())
+    }
+
+    /// Load an NCBI taxdump, the directory of `nodes.dmp` and `names.dmp` from `taxdump.tar.gz`,
+    /// and register `lineage`, `lca`, and `rank_of` backed by its tree.
+    ///
+    /// The nodes and names are registered as the `taxonomy_nodes` and `taxonomy_names` tables.
+    /// Loading another taxdump replaces them and the taxonomy the UDFs use.
+    pub async fn register_taxonomy(&self, taxdump_path: &str) -> crate::Result<()> {
+        let (taxonomy, names) = read_taxdump(&self.session.state(), taxdump_path).await?;
+
+        let tables = [
+            ("taxonomy_nodes", taxonomy_nodes_table(&taxonomy)?),
+            ("taxonomy_names", taxonomy_names_table(&names)?),
+        ];
+
+        for (table_name, table) in tables {
+            self.session.deregister_table(table_name)?;
+            self.session.register_table(table_name, Arc::new(table))?;
+        }
+
+        for udf in taxonomy_udfs(Arc::new(taxonomy)) {
+            self.session.register_udf(udf);
+        }
+
+        Ok(())
+    }
+
+    /// Register a column transformer that can be referenced in `exon.column_transforms`.
+    ///
+    /// Transformers with the name of a built-in transformer, `aes_gcm` or `hmac_token`, replace it.
+    pub fn register_column_transformer(
+        &self,
+        transformer: Arc<dyn ColumnTransformer>,
+    ) -> crate::Result<()> {
+        let registry = self
+            .session
+            .state()
+            .config()
+            .get_extension::<ColumnTransformerRegistry>()
+            .ok_or(ExonError::Configuration(
+                "ColumnTransformerRegistry not found in the session config".to_string(),
+            ))?;
+
+        registry.register(transformer);
+
+        Ok(())
+    }
+
+    /// Set the provider of the key passed to the column transformers, e.g. a
+    /// [`exon_common::StaticColumnTransformKey`] or one backed by a key management service.
+    ///
+    /// The key isn't a config option, so it isn't listed by `SHOW ALL` or `exon_settings()`.
+    pub fn register_column_transform_key_provider(
+        &self,
+        key_provider: Arc<dyn ColumnTransformKeyProvider>,
+    ) -> crate::Result<()> {
+        let registry = self
+            .session
+            .state()
+            .config()
+            .get_extension::<ColumnTransformerRegistry>()
+            .ok_or(ExonError::Configuration(
+                "ColumnTransformerRegistry not found in the session config".to_string(),
+            ))?;
+
+        registry.set_key_provider(key_provider);
+
+        Ok(())
+    }
+
+    /// Register the chain file that lifts coordinates from one genome build to another, e.g.
+    /// UCSC's `hg19ToHg38.over.chain.gz`.
+    ///
+    /// With `exon.genome_build_liftover` set, joins of tables on the two builds then lift the
+    /// coordinates of the tables on `from_build` to `to_build`. Registering another chain for the
+    /// same builds replaces it.
+    pub async fn register_liftover(
+        &self,
+        from_build: &str,
+        to_build: &str,
+        chain_path: &str,
+    ) -> crate::Result<()> {
+        let registry = self
+            .session
+            .state()
+            .config()
+            .get_extension::<LiftoverRegistry>()
+            .ok_or(ExonError::Configuration(
+                "LiftoverRegistry not found in the session config".to_string(),
+            ))?;
+
+        let chain = read_liftover_chain(&self.session.state(), chain_path).await?;
+        registry.register(from_build, to_build, Arc::new(chain));
+
+        Ok(())
+    }
+
+    /// Merge the overlapping and book-ended intervals of a DataFrame.
+    ///
+    /// The first three columns are read as the reference name, start, and end of half-open
+    /// intervals, like `bedtools merge`.
+    pub fn merge_intervals(&self, df: DataFrame) -> crate::Result<DataFrame> {
+        let table = IntervalSetTable::merge(df.into_unoptimized_plan())?;
+
+        Ok(self.session.read_table(Arc::new(table))?)
+    }
+
+    /// Remove the parts of the intervals of `left` that overlap the intervals of `right`, like
+    /// `bedtools subtract`.
+    pub fn subtract_intervals(
+        &self,
+        left: DataFrame,
+        right: DataFrame,
+    ) -> crate::Result<DataFrame> {
+        let table = IntervalSetTable::subtract(
+            left.into_unoptimized_plan(),
+            right.into_unoptimized_plan(),
+        )?;
+
+        Ok(self.session.read_table(Arc::new(table))?)
+    }
+
+    /// The intervals of a genome not covered by the intervals of a DataFrame, like `bedtools
+    /// complement`. The genome DataFrame's first two columns are the reference names and lengths.
+    pub fn complement_intervals(
+        &self,
+        df: DataFrame,
+        genome: DataFrame,
+    ) -> crate::Result<DataFrame> {
+        let table = IntervalSetTable::complement(
+            df.into_unoptimized_plan(),
+            genome.into_unoptimized_plan(),
+        )?;
+
+        Ok(self.session.read_table(Arc::new(table))?)
+    }
+
+    /// Pair the breakend (BND) records of a VCF DataFrame into one row per junction, with both
+    /// coordinates and their strands.
+    pub fn resolve_breakends(&self, df: DataFrame) -> crate::Result<DataFrame> {
+        let table = BreakendTable::try_new(df.into_unoptimized_plan())?;
+
+        Ok(self.session.read_table(Arc::new(table))?)
+    }
+
+    /// Benchmark the variants of a query DataFrame against a truth set, with precision and recall
+    /// for each stratum and class of variant, like a lighter hap.py.
+    ///
+    /// The optional strata DataFrame's first three columns are the reference, start, and end of
+    /// half-open intervals, e.g. a BED table, and its fourth column names each interval's stratum.
+    pub fn benchmark_variants(
+        &self,
+        truth: DataFrame,
+        query: DataFrame,
+        strata: Option<DataFrame>,
+    ) -> crate::Result<DataFrame> {
+        let table = VariantBenchmarkTable::try_new(
+            truth.into_unoptimized_plan(),
+            query.into_unoptimized_plan(),
+            strata.map(|strata| strata.into_unoptimized_plan()),
+        )?;
+
+        Ok(self.session.read_table(Arc::new(table))?)
+    }
+
+    /// Annotate the variants of a VCF DataFrame with the genes of a GTF DataFrame they overlap,
+    /// their biotype, whether each variant is exonic, intronic, or intergenic, and its distance to
+    /// the nearest exon.
+    pub fn variant_gene_overlap(
+        &self,
+        variants: DataFrame,
+        genes: DataFrame,
+    ) -> crate::Result<DataFrame> {
+        let table = GeneOverlapTable::try_new(
+            variants.into_unoptimized_plan(),
+            genes.into_unoptimized_plan(),
+        )?;
+
+        Ok(self.session.read_table(Arc::new(table))?)
+    }
+
     /// Read an inferred Exon table.
     pub async fn read_inferred_exon_table(&self, table_path: &str) -> Result<DataFrame, ExonError> {
         let session_state = self.session.state();
@@ -722,6 +1185,12 @@ impl ExonSession {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Array, AsArray},
+        datatypes::{DataType, Field, Int32Type, Int64Type, Schema},
+    };
     use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
 
     use crate::{
@@ -730,9 +1199,10 @@ mod tests {
             cram::table_provider::ListingCRAMTableOptions,
             fasta::table_provider::ListingFASTATableOptions,
             fastq::table_provider::ListingFASTQTableOptions, sdf::ListingSDFTableOptions,
+            ExonFileType,
         },
         session_context::exon_context_ext::ExonSession,
-        ExonRuntimeEnvExt,
+        ExonError, ExonRuntimeEnvExt,
     };
 
     #[tokio::test]
@@ -780,6 +1250,51 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_read_exon_table_with_schema() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let bed_path = exon_test::test_path("bed", "test.bed");
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("start", DataType::Int32, false),
+            Field::new("reference_sequence_name", DataType::Utf8, false),
+        ]));
+
+        let df = ctx
+            .read_exon_table_with_schema(
+                bed_path.to_str().ok_or("Invalid path")?,
+                ExonFileType::BED,
+                None,
+                schema.clone(),
+            )
+            .await?;
+
+        let batches = df.collect().await?;
+        assert_eq!(batches[0].num_columns(), 2);
+
+        let starts = batches[0].column(0).as_primitive::<Int32Type>();
+        assert_eq!(starts.value(0), 11873);
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "chrom",
+            DataType::Utf8,
+            false,
+        )]));
+
+        let result = ctx
+            .read_exon_table_with_schema(
+                bed_path.to_str().ok_or("Invalid path")?,
+                ExonFileType::BED,
+                None,
+                schema,
+            )
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_read_fastq_gzip() -> Result<(), Box<dyn std::error::Error>> {
         let ctx = ExonSession::new_exon()?;
@@ -1088,4 +1603,113 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_register_gene_id_mapping() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let tsv_path = exon_test::test_path("gene-id", "hgnc.tsv");
+        ctx.register_gene_id_mapping("hgnc", tsv_path.to_str().unwrap())
+            .await?;
+
+        let batches = ctx
+            .sql(
+                "SELECT map_gene_id(ensembl, 'ensembl->symbol') AS symbol, \
+                    map_gene_id('NM_001317000', 'refseq->entrez') AS entrez, \
+                    map_gene_id('ENSG00000223972.5', 'ensembl->hgnc') AS hgnc \
+                FROM hgnc ORDER BY symbol",
+            )
+            .await?
+            .collect()
+            .await?;
+
+        let symbols = batches[0].column(0).as_string::<i32>();
+        assert_eq!(symbols.value(0), "A1BG");
+        assert_eq!(symbols.value(2), "WASH7P");
+
+        assert_eq!(batches[0].column(1).as_string::<i32>().value(0), "1");
+        assert_eq!(
+            batches[0].column(2).as_string::<i32>().value(0),
+            "HGNC:37102"
+        );
+
+        let gtf_path = exon_test::test_path("gtf", "test.gtf");
+        ctx.register_gene_id_mapping("gtf_genes", gtf_path.to_str().unwrap())
+            .await?;
+
+        assert_eq!(ctx.sql("SELECT * FROM gtf_genes").await?.count().await?, 3);
+
+        let batches = ctx
+            .sql("SELECT map_gene_id('ENSG00000227232', 'ensembl->symbol'), map_gene_id('missing', 'ensembl->symbol')")
+            .await?
+            .collect()
+            .await?;
+
+        let symbols = batches[0].column(0).as_string::<i32>();
+        assert_eq!(symbols.value(0), "WASH7P");
+        assert!(batches[0].column(1).is_null(0));
+
+        // The GTF mapping has no RefSeq column, this may fail while planning or executing.
+        let result = match ctx
+            .sql("SELECT map_gene_id('ENSG00000227232', 'ensembl->refseq')")
+            .await
+        {
+            Ok(df) => df.collect().await.map(|_| ()).map_err(ExonError::from),
+            Err(e) => Err(e),
+        };
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_taxonomy() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let nodes_path = exon_test::test_path("taxdump", "nodes.dmp");
+        let taxdump_path = nodes_path.parent().unwrap().to_str().unwrap();
+        ctx.register_taxonomy(taxdump_path).await?;
+
+        let batches = ctx
+            .sql(
+                "SELECT array_to_string(lineage(562), ';'), lca(562, 28901), lca(562, 1423), \
+                    rank_of(561), rank_of(404), lineage(404) IS NULL",
+            )
+            .await?
+            .collect()
+            .await?;
+
+        let batch = &batches[0];
+        assert_eq!(
+            batch.column(0).as_string::<i32>().value(0),
+            "cellular organisms;Bacteria;Pseudomonadota;Gammaproteobacteria;Enterobacterales;Enterobacteriaceae;Escherichia;Escherichia coli"
+        );
+        assert_eq!(batch.column(1).as_primitive::<Int64Type>().value(0), 543);
+        assert_eq!(batch.column(2).as_primitive::<Int64Type>().value(0), 2);
+        assert_eq!(batch.column(3).as_string::<i32>().value(0), "genus");
+        assert!(batch.column(4).is_null(0));
+        assert!(batch.column(5).as_boolean().value(0));
+
+        let names = ctx
+            .sql("SELECT n.name FROM taxonomy_names n JOIN taxonomy_nodes t ON n.tax_id = t.tax_id WHERE n.name_class = 'synonym' AND t.rank = 'species'")
+            .await?
+            .collect()
+            .await?;
+        assert_eq!(
+            names[0].column(0).as_string::<i32>().value(0),
+            "Bacillus coli"
+        );
+
+        // Loading the taxdump again replaces the tables.
+        ctx.register_taxonomy(taxdump_path).await?;
+        assert_eq!(
+            ctx.sql("SELECT * FROM taxonomy_nodes")
+                .await?
+                .count()
+                .await?,
+            17
+        );
+
+        Ok(())
+    }
 }
@@ -12,14 +12,70 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use datafusion::{
     execution::context::{FunctionFactory, RegisterFunction, SessionState},
     logical_expr::CreateFunction,
 };
 
-use crate::error::ExonError;
+use crate::{
+    error::ExonError,
+    udfs::{
+        lookup::create_lookup_udf, pssm::create_pssm_udf, regex_extract::create_regex_extract_udf,
+    },
+};
+
+/// The languages of `CREATE FUNCTION` statements, each a kind of function Exon can create.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FunctionLanguage {
+    /// PSSM UDFs from motif files.
+    Pssm,
+    /// Lookup UDFs from delimited files of keys and values.
+    Lookup,
+    /// UDFs extracting the named groups of a regular expression.
+    RegexExtract,
+    /// UDFs calling a function exported by a WebAssembly module.
+    #[cfg(feature = "wasm")]
+    Wasm,
+}
 
+impl FunctionLanguage {
+    /// The language of a `LANGUAGE` clause, case insensitive.
+    fn from_name(language: &str) -> Option<Self> {
+        match language.to_ascii_lowercase().as_str() {
+            "pssm" => Some(Self::Pssm),
+            "lookup" => Some(Self::Lookup),
+            "regex_extract" => Some(Self::RegexExtract),
+            #[cfg(feature = "wasm")]
+            "wasm" => Some(Self::Wasm),
+            _ => None,
+        }
+    }
+
+    /// Create the function of a statement in the language.
+    async fn create_function(
+        &self,
+        state: &SessionState,
+        statement: &CreateFunction,
+    ) -> datafusion::error::Result<RegisterFunction> {
+        let udf = match self {
+            Self::Pssm => create_pssm_udf(state, statement).await?,
+            Self::Lookup => create_lookup_udf(state, statement).await?,
+            Self::RegexExtract => create_regex_extract_udf(statement)?,
+            #[cfg(feature = "wasm")]
+            Self::Wasm => crate::udfs::wasm::create_wasm_udf(state, statement).await?,
+        };
+
+        Ok(RegisterFunction::Scalar(Arc::new(udf)))
+    }
+}
+
+/// Creates the functions of `CREATE FUNCTION` statements, e.g. PSSM UDFs from motif files with
+/// `LANGUAGE PSSM`, lookup UDFs from files of keys and values with `LANGUAGE LOOKUP`, and UDFs
+/// extracting named groups of a regular expression with `LANGUAGE REGEX_EXTRACT`. With the `wasm`
+/// feature, `LANGUAGE WASM` creates UDFs calling a function exported by a WebAssembly module.
 #[derive(Default, Debug)]
 pub struct ExonFunctionFactory {}
 
@@ -27,19 +83,18 @@ pub struct ExonFunctionFactory {}
 impl FunctionFactory for ExonFunctionFactory {
     async fn create(
         &self,
-        _state: &SessionState,
+        state: &SessionState,
         statement: CreateFunction,
     ) -> datafusion::error::Result<RegisterFunction> {
-        let CreateFunction {
-            temporary: _,
-            name,
-            args: _,
-            return_type: _,
-            params: _,
-            schema: _,
-            or_replace: _,
-        } = statement;
-
-        Err(ExonError::UnsupportedFunction(name.clone()).into())
+        let language = statement
+            .params
+            .language
+            .as_ref()
+            .and_then(|language| FunctionLanguage::from_name(&language.value));
+
+        match language {
+            Some(language) => language.create_function(state, &statement).await,
+            None => Err(ExonError::UnsupportedFunction(statement.name).into()),
+        }
     }
 }
@@ -0,0 +1,309 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use arrow::datatypes::Schema;
+use noodles::core::Region;
+
+use crate::{ExonError, ExonSession};
+
+/// The default IGV session template.
+///
+/// `{genome}` and `{locus}` are replaced with the attribute values and `{resources}` with one
+/// `<Resource>` element per track.
+pub const DEFAULT_IGV_SESSION_TEMPLATE: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<Session genome="{genome}" locus="{locus}" version="8">
+    <Resources>
+{resources}    </Resources>
+</Session>
+"#;
+
+/// The formats tables are exported as, chosen by their columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrackFormat {
+    VCF,
+    GFF,
+    BED,
+}
+
+impl TrackFormat {
+    fn infer(schema: &Schema) -> Option<Self> {
+        let has = |names: &[&str]| names.iter().all(|n| schema.field_with_name(n).is_ok());
+
+        if has(&["chrom", "pos", "ref"]) {
+            Some(Self::VCF)
+        } else if has(&["seqname", "start", "end"]) {
+            Some(Self::GFF)
+        } else if has(&["reference_sequence_name", "start", "end"]) {
+            Some(Self::BED)
+        } else {
+            None
+        }
+    }
+
+    fn stored_as(&self) -> &'static str {
+        match self {
+            Self::VCF => "VCF",
+            Self::GFF => "GFF",
+            Self::BED => "BED",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::VCF => "vcf.gz",
+            Self::GFF => "gff.gz",
+            Self::BED => "bed.gz",
+        }
+    }
+
+    /// A SQL predicate for the rows overlapping the region.
+    fn region_predicate(&self, region: &Region) -> String {
+        let (name_column, start_column, end_column) = match self {
+            Self::VCF => ("chrom", "pos", "pos"),
+            Self::GFF => ("seqname", "start", "\"end\""),
+            Self::BED => ("reference_sequence_name", "start", "\"end\""),
+        };
+
+        let mut predicate = format!(
+            "{} = '{}'",
+            name_column,
+            region.name().to_string().replace('\'', "''")
+        );
+
+        let interval = region.interval();
+
+        // BED starts are 0-based, the other formats and the region are 1-based.
+        let start_offset = match self {
+            Self::BED => 1,
+            _ => 0,
+        };
+
+        if let Some(end) = interval.end() {
+            let _ = write!(
+                predicate,
+                " AND {} + {} <= {}",
+                start_column,
+                start_offset,
+                usize::from(end)
+            );
+        }
+
+        if let Some(start) = interval.start() {
+            let _ = write!(predicate, " AND {} >= {}", end_column, usize::from(start));
+        }
+
+        predicate
+    }
+}
+
+/// Exports registered tables as track files and writes an IGV session that opens them at a
+/// region.
+///
+/// VCF, GFF, and BED tables, recognized by their columns, are written as bgzipped and tabix
+/// indexed files so IGV can load them without a separate indexing step.
+#[derive(Debug, Clone)]
+pub struct IGVSessionExport {
+    genome: String,
+    template: String,
+}
+
+impl Default for IGVSessionExport {
+    fn default() -> Self {
+        Self {
+            genome: "hg38".to_string(),
+            template: DEFAULT_IGV_SESSION_TEMPLATE.to_string(),
+        }
+    }
+}
+
+impl IGVSessionExport {
+    /// Set the IGV genome ID or path of the session, `hg38` by default.
+    pub fn with_genome(mut self, genome: impl Into<String>) -> Self {
+        self.genome = genome.into();
+        self
+    }
+
+    /// Set the session template, see [`DEFAULT_IGV_SESSION_TEMPLATE`] for the placeholders.
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = template.into();
+        self
+    }
+
+    /// Render the session XML for the tracks, given as name and file name pairs.
+    fn render(&self, locus: &str, tracks: &[(String, String)]) -> String {
+        let mut resources = String::new();
+
+        for (name, file_name) in tracks {
+            let _ = writeln!(
+                resources,
+                r#"        <Resource name="{}" path="{}" index="{}.tbi"/>"#,
+                escape_xml(name),
+                escape_xml(file_name),
+                escape_xml(file_name)
+            );
+        }
+
+        self.template
+            .replace("{genome}", &escape_xml(&self.genome))
+            .replace("{locus}", &escape_xml(locus))
+            .replace("{resources}", &resources)
+    }
+
+    /// Write the tables' rows overlapping the region to `output_dir`, with a `session.xml` that
+    /// opens them in IGV, and return the path of the session.
+    pub async fn export(
+        &self,
+        ctx: &ExonSession,
+        table_names: &[&str],
+        region: &str,
+        output_dir: impl AsRef<Path>,
+    ) -> crate::Result<PathBuf> {
+        let region = region
+            .parse::<Region>()
+            .map_err(|e| ExonError::Configuration(format!("Invalid region {}: {}", region, e)))?;
+
+        let output_dir = output_dir.as_ref();
+        std::fs::create_dir_all(output_dir)?;
+
+        let mut tracks = Vec::with_capacity(table_names.len());
+
+        for table_name in table_names {
+            let table = ctx.session.table(*table_name).await?;
+
+            let format = TrackFormat::infer(table.schema().as_arrow()).ok_or_else(|| {
+                ExonError::Configuration(format!(
+                    "Table {} can't be exported as a VCF, GFF, or BED track",
+                    table_name
+                ))
+            })?;
+
+            let file_name = format!("{}.{}", table_name, format.extension());
+            let path = output_dir.join(&file_name);
+
+            let sql = format!(
+                "COPY (SELECT * FROM {} WHERE {}) TO '{}' STORED AS {} OPTIONS (compression 'bgzip', tabix 'true')",
+                table_name,
+                format.region_predicate(&region),
+                path.display(),
+                format.stored_as()
+            );
+            ctx.sql(&sql).await?.collect().await?;
+
+            tracks.push((table_name.to_string(), file_name));
+        }
+
+        let session_path = output_dir.join("session.xml");
+        std::fs::write(&session_path, self.render(&region.to_string(), &tracks))?;
+
+        Ok(session_path)
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::datatypes::{DataType, Field, Schema};
+    use noodles::core::Region;
+
+    use crate::ExonSession;
+
+    use super::{IGVSessionExport, TrackFormat};
+
+    #[test]
+    fn test_infer_and_region_predicate() -> Result<(), Box<dyn std::error::Error>> {
+        let schema = Schema::new(vec![
+            Field::new("reference_sequence_name", DataType::Utf8, false),
+            Field::new("start", DataType::Int64, false),
+            Field::new("end", DataType::Int64, false),
+        ]);
+        let format = TrackFormat::infer(&schema).unwrap();
+        assert_eq!(format, TrackFormat::BED);
+
+        let region = "chr1:10-20".parse::<Region>()?;
+        assert_eq!(
+            format.region_predicate(&region),
+            "reference_sequence_name = 'chr1' AND start + 1 <= 20 AND \"end\" >= 10"
+        );
+
+        let schema = Schema::new(vec![Field::new("name", DataType::Utf8, false)]);
+        assert!(TrackFormat::infer(&schema).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_escapes_values() {
+        let xml = IGVSessionExport::default()
+            .with_genome("/genomes/a&b.json")
+            .render("chr1", &[("t".to_string(), "t<1>.vcf.gz".to_string())]);
+
+        assert!(xml.contains(r#"genome="/genomes/a&amp;b.json" locus="chr1""#));
+        assert!(xml.contains(r#"path="t&lt;1&gt;.vcf.gz" index="t&lt;1&gt;.vcf.gz.tbi""#));
+
+        let xml = IGVSessionExport::default()
+            .with_template("{locus}|{resources}")
+            .render("chr1:1-2", &[]);
+        assert_eq!(xml, "chr1:1-2|");
+    }
+
+    #[tokio::test]
+    async fn test_export_gff_session() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let gff_path = exon_test::test_path("gff", "test.gff");
+        let sql = format!(
+            "CREATE EXTERNAL TABLE gff_table STORED AS GFF LOCATION '{}'",
+            gff_path.display()
+        );
+        ctx.sql(&sql).await?.collect().await?;
+
+        let output_dir = std::env::temp_dir().join("test_export_igv_session");
+        if output_dir.exists() {
+            std::fs::remove_dir_all(&output_dir)?;
+        }
+
+        let session_path = IGVSessionExport::default()
+            .export(&ctx, &["gff_table"], "sq0", &output_dir)
+            .await?;
+
+        assert!(output_dir.join("gff_table.gff.gz").exists());
+        assert!(output_dir.join("gff_table.gff.gz.tbi").exists());
+
+        let xml = std::fs::read_to_string(session_path)?;
+        assert!(xml.contains(r#"<Session genome="hg38" locus="sq0" version="8">"#));
+        assert!(xml.contains(
+            r#"<Resource name="gff_table" path="gff_table.gff.gz" index="gff_table.gff.gz.tbi"/>"#
+        ));
+
+        let err = IGVSessionExport::default()
+            .export(&ctx, &["gff_table"], "sq0:a-b", &output_dir)
+            .await;
+        assert!(err.is_err());
+
+        Ok(())
+    }
+}
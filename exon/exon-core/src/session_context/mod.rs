@@ -14,5 +14,7 @@
 
 mod exon_context_ext;
 mod function_factory;
+mod igv_session;
 
 pub use exon_context_ext::ExonSession;
+pub use igv_session::{IGVSessionExport, DEFAULT_IGV_SESSION_TEMPLATE};
@@ -12,9 +12,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-mod columns_from_batch;
+mod alignment_header;
+mod alignment_sink;
+mod bcf_serializer;
+mod bcf_sink;
+mod bed_serializer;
+mod bed_sink;
+mod bgzf_index_writer;
+pub(crate) mod columns_from_batch;
 mod fasta_serializer;
+mod fasta_sink;
 mod fastq_serializer;
+mod fastq_sink;
+mod gff_serializer;
+mod gff_sink;
+mod sam_serializer;
 mod simple_record_sink;
+mod vcf_sink;
 
+pub(crate) use alignment_header::{header_from_reference_sequences, read_header};
+pub(crate) use alignment_sink::AlignmentSink;
+pub(crate) use bcf_sink::BCFSink;
+pub(crate) use bed_serializer::{infer_n_fields, is_valid_n_fields};
+pub(crate) use bed_sink::BEDSink;
+pub(crate) use fasta_serializer::DEFAULT_LINE_WIDTH;
+pub(crate) use fasta_sink::FASTASink;
+pub(crate) use fastq_serializer::MAX_QUALITY_SCORE;
+pub(crate) use fastq_sink::FASTQSink;
+pub(crate) use gff_sink::GFFSink;
 pub(crate) use simple_record_sink::SimpleRecordSink;
+pub(crate) use vcf_sink::VCFSink;
@@ -0,0 +1,128 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{num::NonZeroUsize, sync::Arc};
+
+use datafusion::error::{DataFusionError, Result};
+use futures::TryStreamExt;
+use noodles::{
+    fasta::fai,
+    sam::{
+        self,
+        header::{record::value::Map, ReferenceSequences},
+    },
+};
+use object_store::{path::Path, ObjectStore};
+use tokio_util::io::StreamReader;
+
+/// Read a SAM header from a file: the header of a BAM file, the `@SQ` lines of a FASTA index, or
+/// the header lines at the start of anything else, e.g. a SAM file or a `.dict` sequence
+/// dictionary.
+pub(crate) async fn read_header(
+    object_store: Arc<dyn ObjectStore>,
+    path: &Path,
+) -> Result<sam::Header> {
+    let get_result = object_store.get(path).await?;
+
+    if path.as_ref().ends_with(".bam") {
+        let stream_reader = Box::pin(get_result.into_stream().map_err(DataFusionError::from));
+        let mut reader = noodles::bam::AsyncReader::new(StreamReader::new(stream_reader));
+
+        return Ok(reader.read_header().await?);
+    }
+
+    let bytes = get_result.bytes().await?;
+
+    if path.as_ref().ends_with(".fai") {
+        let index = fai::Reader::new(bytes.as_ref()).read_index()?;
+
+        let mut reference_sequences = ReferenceSequences::default();
+        for record in Vec::from(index) {
+            let length = usize::try_from(record.length())
+                .ok()
+                .and_then(NonZeroUsize::new)
+                .ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "Invalid length for reference sequence {} in {}",
+                        String::from_utf8_lossy(record.name()),
+                        path
+                    ))
+                })?;
+
+            reference_sequences.insert(record.name().into(), Map::new(length));
+        }
+
+        return Ok(header_from_reference_sequences(reference_sequences));
+    }
+
+    parse_header(&bytes)
+}
+
+/// Parse the header lines at the start of SAM text, ignoring any records after them.
+pub(crate) fn parse_header(bytes: &[u8]) -> Result<sam::Header> {
+    let mut header_len = 0;
+
+    for line in bytes.split_inclusive(|b| *b == b'\n') {
+        if !line.starts_with(b"@") {
+            break;
+        }
+
+        header_len += line.len();
+    }
+
+    let text = std::str::from_utf8(&bytes[..header_len])
+        .map_err(|e| DataFusionError::Execution(format!("Invalid SAM header: {}", e)))?;
+
+    text.parse()
+        .map_err(|e| DataFusionError::Execution(format!("Invalid SAM header: {}", e)))
+}
+
+/// Synthesize a header with an `@HD` line and the given `@SQ` lines.
+pub(crate) fn header_from_reference_sequences(
+    reference_sequences: ReferenceSequences,
+) -> sam::Header {
+    sam::Header::builder()
+        .set_header(Map::default())
+        .set_reference_sequences(reference_sequences)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_header;
+
+    #[test]
+    fn test_parse_header_ignores_records() -> Result<(), Box<dyn std::error::Error>> {
+        let sam = concat!(
+            "@HD\tVN:1.6\n",
+            "@SQ\tSN:chr1\tLN:100\n",
+            "@SQ\tSN:chr2\tLN:50\n",
+            "r0\t4\t*\t0\t255\t*\t*\t0\t0\tACGT\tNDLS\n",
+        );
+
+        let header = parse_header(sam.as_bytes())?;
+
+        let names = header
+            .reference_sequences()
+            .iter()
+            .map(|(name, map)| (name.to_string(), map.length().get()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![("chr1".to_string(), 100), ("chr2".to_string(), 50)]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, fmt::Debug, sync::Arc};
+
+use arrow::array::RecordBatch;
+use datafusion::{
+    datasource::{
+        file_format::file_compression_type::FileCompressionType, physical_plan::FileSinkConfig,
+    },
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_plan::{insert::DataSink, metrics::MetricsSet, DisplayAs, DisplayFormatType},
+};
+use futures::StreamExt;
+use noodles::sam::{self, alignment::io::Write};
+use tokio::io::AsyncWriteExt;
+
+use crate::datasources::ExonFileType;
+
+use super::{bgzf_index_writer::BGZFIndexWriter, sam_serializer::SAMRecordBuilder};
+
+/// A sink that writes SAM or BAM files with a header, which must contain the references of the
+/// records being written.
+pub struct AlignmentSink {
+    file_compression_type: FileCompressionType,
+    file_sink_config: FileSinkConfig,
+    exon_file_type: ExonFileType,
+    header: sam::Header,
+}
+
+impl AlignmentSink {
+    pub fn new(
+        file_sink_config: FileSinkConfig,
+        file_compression_type: FileCompressionType,
+        exon_file_type: ExonFileType,
+    ) -> Self {
+        Self {
+            file_sink_config,
+            file_compression_type,
+            exon_file_type,
+            header: sam::Header::default(),
+        }
+    }
+
+    /// Set the header of the output.
+    pub fn with_header(mut self, header: sam::Header) -> Self {
+        self.header = header;
+        self
+    }
+
+    fn write_records<W>(&self, writer: &mut W, batch: &RecordBatch) -> Result<()>
+    where
+        W: Write,
+    {
+        let records = SAMRecordBuilder::new(&self.header).build_records(batch)?;
+
+        for record in &records {
+            writer.write_alignment_record(&self.header, record)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Debug for AlignmentSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlignmentSink")
+            .field("exon_file_type", &self.exon_file_type)
+            .finish()
+    }
+}
+
+impl DisplayAs for AlignmentSink {
+    fn fmt_as(
+        &self,
+        _display_type: DisplayFormatType,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "AlignmentSink({})", self.exon_file_type)
+    }
+}
+
+#[async_trait::async_trait]
+impl DataSink for AlignmentSink {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        None
+    }
+
+    async fn write_all(
+        &self,
+        mut data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<u64> {
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.file_sink_config.object_store_url)?;
+
+        let location = self.file_sink_config.file_groups[0].path();
+        let mut buf_writer = object_store::buffered::BufWriter::new(object_store, location.clone());
+
+        let mut total_bytes = 0;
+
+        match self.exon_file_type {
+            ExonFileType::SAM => {
+                let mut buf_writer = self
+                    .file_compression_type
+                    .convert_async_writer(buf_writer)?;
+
+                let mut writer = sam::io::Writer::new(Vec::new());
+                writer.write_header(&self.header)?;
+
+                loop {
+                    let bytes = std::mem::take(writer.get_mut());
+                    buf_writer.write_all(&bytes).await?;
+                    total_bytes += bytes.len() as u64;
+
+                    match data.next().await {
+                        Some(batch) => self.write_records(&mut writer, &batch?)?,
+                        None => break,
+                    }
+                }
+
+                buf_writer.shutdown().await?;
+            }
+            ExonFileType::BAM => {
+                // BAM is always BGZF compressed, the records are encoded uncompressed first.
+                let mut writer = noodles::bam::io::Writer::from(Vec::new());
+                writer.write_header(&self.header)?;
+
+                let mut bgzf_writer = BGZFIndexWriter::new(None);
+
+                loop {
+                    let bytes = std::mem::take(writer.get_mut());
+                    buf_writer.write_all(&bgzf_writer.compress(&bytes)?).await?;
+
+                    match data.next().await {
+                        Some(batch) => self.write_records(&mut writer, &batch?)?,
+                        None => break,
+                    }
+                }
+
+                total_bytes = bgzf_writer.compressed_len();
+
+                let (eof, _) = bgzf_writer.finish()?;
+                buf_writer.write_all(&eof).await?;
+                total_bytes += eof.len() as u64;
+
+                buf_writer.shutdown().await?;
+            }
+            _ => {
+                return Err(DataFusionError::Execution(format!(
+                    "Invalid file type for alignment output: {}",
+                    self.exon_file_type
+                )))
+            }
+        }
+
+        Ok(total_bytes)
+    }
+}
@@ -0,0 +1,448 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use arrow::{
+    array::{
+        Array, ArrayRef, AsArray, Float32Array, Int64Array, ListArray, RecordBatch, StringArray,
+        StructArray,
+    },
+    compute::cast,
+    datatypes::{DataType, Float32Type, Int32Type, Schema},
+};
+use datafusion::error::{DataFusionError, Result};
+use noodles::{
+    core::Position,
+    vcf::{
+        self,
+        header::record::value::{
+            map::{Contig, Filter},
+            Map,
+        },
+        variant::{
+            record::samples::keys::key,
+            record_buf::{
+                info::field::{value::Array as InfoArray, Value as InfoValue},
+                samples::{
+                    sample::{value::Array as SampleArray, Value as SampleValue},
+                    Keys,
+                },
+                AlternateBases, Filters, Ids, Info, Samples,
+            },
+            RecordBuf,
+        },
+    },
+};
+
+use crate::datasources::vcf::vcf_header_builder_from_schema;
+
+use super::columns_from_batch::{get_array_column, get_optional_array_column};
+
+/// The header values that can only be known from the data: the contigs and filters, in order of
+/// first appearance, and the number of samples.
+#[derive(Debug, Default)]
+pub(crate) struct VariantHeaderValues {
+    contigs: Vec<String>,
+    filters: Vec<String>,
+    seen_contigs: HashSet<String>,
+    seen_filters: HashSet<String>,
+    n_samples: usize,
+}
+
+impl VariantHeaderValues {
+    /// Update the header values with the chrom, filter, and formats columns of the batch.
+    pub(crate) fn update(&mut self, batch: &RecordBatch) -> Result<()> {
+        let chroms = get_array_column::<StringArray>(batch, "chrom")?;
+        let filters = get_optional_array_column::<ListArray>(batch, "filter")?;
+        let formats = get_optional_array_column::<ListArray>(batch, "formats")?;
+
+        for i in 0..batch.num_rows() {
+            let chrom = chroms.value(i);
+
+            if self.seen_contigs.insert(chrom.to_string()) {
+                self.contigs.push(chrom.to_string());
+            }
+
+            if let Some(filters) = filters {
+                for filter in string_list(filters, i)? {
+                    if filter != "PASS" && self.seen_filters.insert(filter.clone()) {
+                        self.filters.push(filter);
+                    }
+                }
+            }
+
+            if let Some(formats) = formats {
+                if formats.is_valid(i) {
+                    self.n_samples = self.n_samples.max(formats.value_length(i) as usize);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The number of samples, the widest formats list seen.
+    pub(crate) fn n_samples(&self) -> usize {
+        self.n_samples
+    }
+
+    /// Build the header for the schema's INFO and FORMAT fields and the values seen.
+    ///
+    /// If no sample names are given, the samples are named `sample_1`, `sample_2`, and so on.
+    pub(crate) fn header(
+        &self,
+        schema: &Schema,
+        sample_names: Option<&[String]>,
+    ) -> Result<vcf::Header> {
+        let mut builder = vcf_header_builder_from_schema(schema)?;
+
+        for contig in self.contigs.iter() {
+            builder = builder.add_contig(contig.as_str(), Map::<Contig>::new());
+        }
+
+        for filter in self.filters.iter() {
+            builder = builder.add_filter(filter.as_str(), Map::<Filter>::new(""));
+        }
+
+        match sample_names {
+            Some(sample_names) => {
+                if schema.field_with_name("formats").is_ok() && sample_names.len() != self.n_samples
+                {
+                    return Err(DataFusionError::Execution(format!(
+                        "Expected {} sample names, got {}",
+                        self.n_samples,
+                        sample_names.len()
+                    )));
+                }
+
+                for sample_name in sample_names {
+                    builder = builder.add_sample_name(sample_name.as_str());
+                }
+            }
+            None => {
+                for i in 0..self.n_samples {
+                    builder = builder.add_sample_name(format!("sample_{}", i + 1));
+                }
+            }
+        }
+
+        Ok(builder.build())
+    }
+}
+
+/// The non-null strings in row `i` of a list of strings, which may be dictionary encoded.
+fn string_list(array: &ListArray, i: usize) -> Result<Vec<String>> {
+    if array.is_null(i) {
+        return Ok(Vec::new());
+    }
+
+    let values = cast(&array.value(i), &DataType::Utf8)?;
+
+    Ok(values
+        .as_string::<i32>()
+        .iter()
+        .flatten()
+        .map(|v| v.to_string())
+        .collect())
+}
+
+fn unsupported_type(data_type: &DataType) -> DataFusionError {
+    DataFusionError::Execution(format!("Unsupported type for a VCF value: {}", data_type))
+}
+
+/// Convert row `i` of an INFO column to a value, `None` if the key should be left out.
+fn info_value(array: &ArrayRef, i: usize) -> Result<Option<InfoValue>> {
+    if array.is_null(i) {
+        return Ok(None);
+    }
+
+    let value = match array.data_type() {
+        DataType::Boolean => match array.as_boolean().value(i) {
+            true => InfoValue::Flag,
+            false => return Ok(None),
+        },
+        DataType::Int32 => InfoValue::Integer(array.as_primitive::<Int32Type>().value(i)),
+        DataType::Float32 => InfoValue::Float(array.as_primitive::<Float32Type>().value(i)),
+        DataType::Utf8 => InfoValue::String(array.as_string::<i32>().value(i).to_string()),
+        DataType::List(_) => {
+            let values = array.as_list::<i32>().value(i);
+
+            match values.data_type() {
+                DataType::Int32 => InfoValue::Array(InfoArray::Integer(
+                    values.as_primitive::<Int32Type>().iter().collect(),
+                )),
+                DataType::Float32 => InfoValue::Array(InfoArray::Float(
+                    values.as_primitive::<Float32Type>().iter().collect(),
+                )),
+                DataType::Utf8 => InfoValue::Array(InfoArray::String(
+                    values
+                        .as_string::<i32>()
+                        .iter()
+                        .map(|v| v.map(|v| v.to_string()))
+                        .collect(),
+                )),
+                data_type => return Err(unsupported_type(data_type)),
+            }
+        }
+        data_type => return Err(unsupported_type(data_type)),
+    };
+
+    Ok(Some(value))
+}
+
+/// Convert row `i` of a FORMAT column to a sample value. Genotypes are strings, e.g. `0|1`.
+fn sample_value(array: &ArrayRef, i: usize) -> Result<Option<SampleValue>> {
+    if array.is_null(i) {
+        return Ok(None);
+    }
+
+    let value = match array.data_type() {
+        DataType::Int32 => SampleValue::Integer(array.as_primitive::<Int32Type>().value(i)),
+        DataType::Float32 => SampleValue::Float(array.as_primitive::<Float32Type>().value(i)),
+        DataType::Utf8 => SampleValue::String(array.as_string::<i32>().value(i).to_string()),
+        // Dictionary encoded genotypes are written like plain strings.
+        DataType::Dictionary(_, _) => {
+            let value = cast(&array.slice(i, 1), &DataType::Utf8)?;
+            return sample_value(&value, 0);
+        }
+        DataType::List(_) => {
+            let values = array.as_list::<i32>().value(i);
+
+            match values.data_type() {
+                DataType::Int32 => SampleValue::Array(SampleArray::Integer(
+                    values.as_primitive::<Int32Type>().iter().collect(),
+                )),
+                DataType::Float32 => SampleValue::Array(SampleArray::Float(
+                    values.as_primitive::<Float32Type>().iter().collect(),
+                )),
+                DataType::Utf8 => SampleValue::Array(SampleArray::String(
+                    values
+                        .as_string::<i32>()
+                        .iter()
+                        .map(|v| v.map(|v| v.to_string()))
+                        .collect(),
+                )),
+                data_type => return Err(unsupported_type(data_type)),
+            }
+        }
+        data_type => return Err(unsupported_type(data_type)),
+    };
+
+    Ok(Some(value))
+}
+
+/// Convert row `i` of the formats column to samples, padded with missing values to `n_samples`.
+fn samples(formats: &ListArray, i: usize, n_samples: usize) -> Result<Samples> {
+    if formats.is_null(i) {
+        return Ok(Samples::default());
+    }
+
+    let row = formats.value(i);
+    let row = row.as_any().downcast_ref::<StructArray>().ok_or_else(|| {
+        DataFusionError::Execution("formats should be a list of structs".to_string())
+    })?;
+
+    // Like a VCF line, a record only has the keys with a value for at least one sample, and the
+    // genotype has to be the first key if it's present.
+    let mut columns = row
+        .column_names()
+        .into_iter()
+        .zip(row.columns())
+        .filter(|(_, column)| column.null_count() < column.len())
+        .collect::<Vec<_>>();
+    columns.sort_by_key(|(name, _)| *name != key::GENOTYPE);
+
+    let keys = columns
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect::<Keys>();
+
+    // A genotype can't be missing in BCF, so a missing one is written as `.` instead.
+    let missing_sample = columns
+        .iter()
+        .map(|(name, _)| match *name {
+            key::GENOTYPE => Some(SampleValue::String(".".to_string())),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut values = Vec::with_capacity(n_samples);
+
+    for j in 0..row.len() {
+        let sample = columns
+            .iter()
+            .zip(missing_sample.iter())
+            .map(|((_, column), missing)| {
+                sample_value(column, j).map(|value| value.or_else(|| missing.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        values.push(sample);
+    }
+
+    values.resize(n_samples.max(row.len()), missing_sample);
+
+    Ok(Samples::new(keys, values))
+}
+
+/// Convert a record batch with the VCF schema to variant records.
+///
+/// Only `chrom`, `pos`, and `ref` are required, the other columns are written as missing if they
+/// aren't in the batch.
+pub(crate) fn batch_to_records(batch: &RecordBatch, n_samples: usize) -> Result<Vec<RecordBuf>> {
+    let chroms = get_array_column::<StringArray>(batch, "chrom")?;
+    let positions = get_array_column::<Int64Array>(batch, "pos")?;
+    let ids = get_optional_array_column::<ListArray>(batch, "id")?;
+    let references = get_array_column::<StringArray>(batch, "ref")?;
+    let alts = get_optional_array_column::<ListArray>(batch, "alt")?;
+    let quals = get_optional_array_column::<Float32Array>(batch, "qual")?;
+    let filters = get_optional_array_column::<ListArray>(batch, "filter")?;
+    let infos = get_optional_array_column::<StructArray>(batch, "info")?;
+    let formats = get_optional_array_column::<ListArray>(batch, "formats")?;
+
+    let mut records = Vec::with_capacity(batch.num_rows());
+
+    for i in 0..batch.num_rows() {
+        let position = Position::try_from(positions.value(i) as usize)
+            .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+
+        let mut builder = RecordBuf::builder()
+            .set_reference_sequence_name(chroms.value(i))
+            .set_variant_start(position)
+            .set_reference_bases(references.value(i));
+
+        if let Some(ids) = ids {
+            builder = builder.set_ids(string_list(ids, i)?.into_iter().collect::<Ids>());
+        }
+
+        if let Some(alts) = alts {
+            builder = builder.set_alternate_bases(AlternateBases::from(string_list(alts, i)?));
+        }
+
+        if let Some(quals) = quals {
+            if quals.is_valid(i) {
+                builder = builder.set_quality_score(quals.value(i));
+            }
+        }
+
+        if let Some(filters) = filters {
+            builder =
+                builder.set_filters(string_list(filters, i)?.into_iter().collect::<Filters>());
+        }
+
+        if let Some(infos) = infos {
+            if infos.is_valid(i) {
+                let mut info = Info::default();
+
+                for (name, column) in infos.column_names().into_iter().zip(infos.columns()) {
+                    if let Some(value) = info_value(column, i)? {
+                        info.insert(name.to_string(), Some(value));
+                    }
+                }
+
+                builder = builder.set_info(info);
+            }
+        }
+
+        if let Some(formats) = formats {
+            builder = builder.set_samples(samples(formats, i, n_samples)?);
+        }
+
+        records.push(builder.build());
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{
+            ArrayRef, BooleanArray, Int32Array, Int64Array, ListBuilder, RecordBatch, StringArray,
+            StringBuilder, StructArray,
+        },
+        datatypes::{DataType, Field, Schema},
+    };
+    use noodles::vcf::variant::record_buf::info::field::Value as InfoValue;
+
+    use super::{batch_to_records, VariantHeaderValues};
+
+    #[test]
+    fn test_batch_to_records() -> Result<(), Box<dyn std::error::Error>> {
+        let mut filters = ListBuilder::new(StringBuilder::new());
+        filters.values().append_value("PASS");
+        filters.append(true);
+        filters.values().append_value("q10");
+        filters.append(true);
+
+        let info = StructArray::from(vec![
+            (
+                Arc::new(Field::new("DP", DataType::Int32, true)),
+                Arc::new(Int32Array::from(vec![Some(10), None])) as ArrayRef,
+            ),
+            (
+                Arc::new(Field::new("DB", DataType::Boolean, true)),
+                Arc::new(BooleanArray::from(vec![true, false])) as ArrayRef,
+            ),
+        ]);
+
+        let filters = filters.finish();
+
+        let schema = Schema::new(vec![
+            Field::new("chrom", DataType::Utf8, false),
+            Field::new("pos", DataType::Int64, false),
+            Field::new("ref", DataType::Utf8, false),
+            Field::new("filter", filters.data_type().clone(), true),
+            Field::new("info", info.data_type().clone(), true),
+        ]);
+
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![
+                Arc::new(StringArray::from(vec!["chr1", "chr2"])),
+                Arc::new(Int64Array::from(vec![100, 200])),
+                Arc::new(StringArray::from(vec!["A", "C"])),
+                Arc::new(filters),
+                Arc::new(info),
+            ],
+        )?;
+
+        let mut header_values = VariantHeaderValues::default();
+        header_values.update(&batch)?;
+
+        let header = header_values.header(&schema, None)?;
+        assert_eq!(header.contigs().len(), 2);
+        assert!(header.filters().contains_key("q10"));
+        assert!(header.infos().contains_key("DB"));
+        assert!(header.sample_names().is_empty());
+
+        let records = batch_to_records(&batch, header_values.n_samples())?;
+        assert_eq!(records.len(), 2);
+
+        assert_eq!(records[0].reference_sequence_name(), "chr1");
+        assert_eq!(
+            records[0].info().get("DP"),
+            Some(Some(&InfoValue::Integer(10)))
+        );
+        assert_eq!(records[0].info().get("DB"), Some(Some(&InfoValue::Flag)));
+
+        // Null and false values leave the key out.
+        assert_eq!(records[1].info().keys().count(), 0);
+
+        Ok(())
+    }
+}
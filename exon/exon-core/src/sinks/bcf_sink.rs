@@ -0,0 +1,224 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, fmt::Debug, fs::File, sync::Arc};
+
+use arrow::ipc::reader::FileReader;
+use datafusion::{
+    datasource::physical_plan::FileSinkConfig,
+    error::DataFusionError,
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_plan::{
+        common::IPCWriter, insert::DataSink, metrics::MetricsSet, DisplayAs, DisplayFormatType,
+    },
+};
+use futures::StreamExt;
+use noodles::{bcf, vcf::variant::io::Write};
+use tokio::io::AsyncWriteExt;
+
+use super::{
+    bcf_serializer::{batch_to_records, VariantHeaderValues},
+    bgzf_index_writer::BGZFIndexWriter,
+};
+
+/// A sink that writes BCF files from batches with the VCF schema.
+///
+/// The header's contigs, filters, and samples are derived from the data, so the input is spilled
+/// to a temporary file until it's exhausted, and the records are written from it after the
+/// header. The INFO and FORMAT definitions come from the schema, which means the info and formats
+/// columns must be parsed.
+pub struct BCFSink {
+    file_sink_config: FileSinkConfig,
+    sample_names: Option<Vec<String>>,
+}
+
+impl BCFSink {
+    pub fn new(file_sink_config: FileSinkConfig) -> Self {
+        Self {
+            file_sink_config,
+            sample_names: None,
+        }
+    }
+
+    /// Name the samples in the header, by default they're named `sample_1`, `sample_2`, etc.
+    pub fn with_sample_names(mut self, sample_names: Vec<String>) -> Self {
+        self.sample_names = Some(sample_names);
+        self
+    }
+}
+
+impl Debug for BCFSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BCFSink")
+            .field("sample_names", &self.sample_names)
+            .finish()
+    }
+}
+
+impl DisplayAs for BCFSink {
+    fn fmt_as(
+        &self,
+        _display_type: DisplayFormatType,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "BCFSink")
+    }
+}
+
+#[async_trait::async_trait]
+impl DataSink for BCFSink {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        None
+    }
+
+    async fn write_all(
+        &self,
+        mut data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<u64, DataFusionError> {
+        let schema = data.schema();
+
+        let mut header_values = VariantHeaderValues::default();
+
+        let spill_file = context
+            .runtime_env()
+            .disk_manager
+            .create_tmp_file("BCF sink")?;
+        let mut spill_writer = IPCWriter::new(spill_file.path(), &schema)?;
+
+        while let Some(batch) = data.next().await {
+            let batch = batch?;
+
+            header_values.update(&batch)?;
+            spill_writer.write(&batch)?;
+        }
+
+        spill_writer.finish()?;
+
+        let header = header_values.header(&schema, self.sample_names.as_deref())?;
+        let batches = FileReader::try_new(File::open(spill_file.path())?, None)?;
+
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.file_sink_config.object_store_url)?;
+
+        let location = self.file_sink_config.file_groups[0].path();
+
+        let mut buf_writer = object_store::buffered::BufWriter::new(object_store, location.clone());
+
+        // The records are encoded into the writer's buffer, which is drained into BGZF blocks
+        // after the header and each batch.
+        let mut writer = bcf::io::Writer::from(Vec::new());
+        let mut bgzf_writer = BGZFIndexWriter::new(None);
+
+        writer.write_variant_header(&header)?;
+        buf_writer
+            .write_all(&bgzf_writer.compress(&std::mem::take(writer.get_mut()))?)
+            .await?;
+
+        for batch in batches {
+            for record in batch_to_records(&batch?, header_values.n_samples())? {
+                writer.write_variant_record(&header, &record)?;
+            }
+
+            buf_writer
+                .write_all(&bgzf_writer.compress(&std::mem::take(writer.get_mut()))?)
+                .await?;
+        }
+
+        let compressed_len = bgzf_writer.compressed_len();
+        let (eof, _) = bgzf_writer.finish()?;
+
+        buf_writer.write_all(&eof).await?;
+        buf_writer.shutdown().await?;
+
+        Ok(compressed_len + eof.len() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::datasource::listing::PartitionedFile;
+    use datafusion::datasource::physical_plan::FileSinkConfig;
+    use datafusion::execution::object_store::ObjectStoreUrl;
+    use datafusion::logical_expr::dml::InsertOp;
+    use datafusion::physical_plan::insert::DataSink;
+
+    use crate::sinks::BCFSink;
+    use crate::ExonSession;
+
+    #[tokio::test]
+    async fn test_bcf_sink_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let bcf_path = exon_test::test_path("bcf", "index.bcf");
+
+        let sql = format!(
+            "CREATE EXTERNAL TABLE bcf_table STORED AS BCF LOCATION '{}'",
+            bcf_path.to_str().unwrap()
+        );
+        ctx.sql(&sql).await?.collect().await?;
+
+        let df = ctx.sql("SELECT * FROM bcf_table").await?;
+
+        let stream = df.execute_stream().await?;
+        let output_schema = Arc::clone(&stream.schema());
+
+        let temp_path = std::env::temp_dir().join("test_bcf_sink.bcf");
+        let p_file = PartitionedFile::new(temp_path.to_str().unwrap(), 0);
+
+        let file_sink_config = FileSinkConfig {
+            object_store_url: ObjectStoreUrl::local_filesystem(),
+            file_groups: vec![p_file],
+            table_paths: vec![],
+            output_schema,
+            table_partition_cols: vec![],
+            insert_op: InsertOp::Append,
+            keep_partition_by_columns: false,
+        };
+
+        let sink = BCFSink::new(file_sink_config);
+        sink.write_all(stream, &ctx.session.task_ctx()).await?;
+
+        let sql = format!(
+            "SELECT chrom, pos, ref, alt FROM bcf_scan('{}') EXCEPT SELECT chrom, pos, ref, alt FROM bcf_table",
+            temp_path.to_str().unwrap()
+        );
+        let batches = ctx.sql(&sql).await?.collect().await?;
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 0);
+
+        let sql = format!(
+            "SELECT COUNT(*) AS cnt FROM bcf_scan('{}')",
+            temp_path.to_str().unwrap()
+        );
+        let written = ctx.sql(&sql).await?.collect().await?;
+
+        let expected = ctx
+            .sql("SELECT COUNT(*) AS cnt FROM bcf_table")
+            .await?
+            .collect()
+            .await?;
+        assert_eq!(written, expected);
+
+        std::fs::remove_file(temp_path)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Write;
+
+use arrow::{
+    array::{Array, Int64Array, StringArray},
+    datatypes::Schema,
+};
+use bytes::Bytes;
+use datafusion::{datasource::file_format::write::BatchSerializer, error::DataFusionError};
+
+use super::columns_from_batch::{
+    feature_columns_as_utf8, get_array_column, get_optional_array_column,
+};
+
+/// The BED columns in file order.
+const BED_COLUMNS: [&str; 12] = [
+    "reference_sequence_name",
+    "start",
+    "end",
+    "name",
+    "score",
+    "strand",
+    "thick_start",
+    "thick_end",
+    "color",
+    "block_count",
+    "block_sizes",
+    "block_starts",
+];
+
+/// Returns true if `n_fields` is a number of BED columns that can be written (and read back).
+pub(crate) fn is_valid_n_fields(n_fields: usize) -> bool {
+    (3..=6).contains(&n_fields) || n_fields == 12
+}
+
+/// Infer the number of BED columns to write from the columns present in the schema.
+///
+/// This is the longest prefix of the BED columns in the schema, rounded down to a valid number
+/// of columns.
+pub(crate) fn infer_n_fields(schema: &Schema) -> usize {
+    let n_present = BED_COLUMNS
+        .iter()
+        .take_while(|c| schema.column_with_name(c).is_some())
+        .count();
+
+    (3..=n_present)
+        .rev()
+        .find(|n| is_valid_n_fields(*n))
+        .unwrap_or(3)
+}
+
+/// Serializes record batches with the BED schema into BED lines with `n_fields` columns.
+///
+/// Optional columns that are missing or null are written with their BED defaults.
+#[derive(Debug)]
+pub(crate) struct BEDSerializer {
+    n_fields: usize,
+}
+
+impl BEDSerializer {
+    pub(crate) fn new(n_fields: usize) -> Self {
+        Self { n_fields }
+    }
+}
+
+impl BatchSerializer for BEDSerializer {
+    fn serialize(
+        &self,
+        batch: arrow::array::RecordBatch,
+        _initial: bool,
+    ) -> datafusion::error::Result<bytes::Bytes> {
+        if !is_valid_n_fields(self.n_fields) {
+            return Err(DataFusionError::Execution(format!(
+                "Invalid number of BED fields: {}",
+                self.n_fields
+            )));
+        }
+
+        let batch = feature_columns_as_utf8(&batch)?;
+
+        let reference_sequence_names =
+            get_array_column::<StringArray>(&batch, "reference_sequence_name")?;
+        let starts = get_array_column::<Int64Array>(&batch, "start")?;
+        let ends = get_array_column::<Int64Array>(&batch, "end")?;
+        let names = get_optional_array_column::<StringArray>(&batch, "name")?;
+        let scores = get_optional_array_column::<Int64Array>(&batch, "score")?;
+        let strands = get_optional_array_column::<StringArray>(&batch, "strand")?;
+        let thick_starts = get_optional_array_column::<Int64Array>(&batch, "thick_start")?;
+        let thick_ends = get_optional_array_column::<Int64Array>(&batch, "thick_end")?;
+        let colors = get_optional_array_column::<StringArray>(&batch, "color")?;
+        let block_counts = get_optional_array_column::<Int64Array>(&batch, "block_count")?;
+        let block_sizes = get_optional_array_column::<StringArray>(&batch, "block_sizes")?;
+        let block_starts = get_optional_array_column::<StringArray>(&batch, "block_starts")?;
+
+        let string_or = |array: Option<&StringArray>, i: usize, default: String| match array {
+            Some(array) if array.is_valid(i) => array.value(i).to_string(),
+            _ => default,
+        };
+
+        let int_or = |array: Option<&Int64Array>, i: usize, default: i64| match array {
+            Some(array) if array.is_valid(i) => array.value(i),
+            _ => default,
+        };
+
+        let mut lines = String::new();
+
+        for i in 0..batch.num_rows() {
+            let start = starts.value(i);
+            let end = ends.value(i);
+
+            let _ = write!(
+                lines,
+                "{}\t{}\t{}",
+                reference_sequence_names.value(i),
+                start,
+                end
+            );
+
+            if self.n_fields >= 4 {
+                let _ = write!(lines, "\t{}", string_or(names, i, ".".to_string()));
+            }
+
+            if self.n_fields >= 5 {
+                let _ = write!(lines, "\t{}", int_or(scores, i, 0));
+            }
+
+            if self.n_fields >= 6 {
+                let _ = write!(lines, "\t{}", string_or(strands, i, ".".to_string()));
+            }
+
+            if self.n_fields == 12 {
+                // A record without blocks is written as a single block spanning the feature.
+                let _ = write!(
+                    lines,
+                    "\t{}\t{}\t{}\t{}\t{}\t{}",
+                    int_or(thick_starts, i, start),
+                    int_or(thick_ends, i, end),
+                    string_or(colors, i, "0".to_string()),
+                    int_or(block_counts, i, 1),
+                    string_or(block_sizes, i, (end - start).to_string()),
+                    string_or(block_starts, i, "0".to_string()),
+                );
+            }
+
+            lines.push('\n');
+        }
+
+        Ok(Bytes::from(lines))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    use super::{infer_n_fields, is_valid_n_fields};
+
+    #[test]
+    fn test_infer_n_fields() {
+        let fields = vec![
+            Field::new("reference_sequence_name", DataType::Utf8, false),
+            Field::new("start", DataType::Int64, false),
+            Field::new("end", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("score", DataType::Int64, true),
+            Field::new("strand", DataType::Utf8, true),
+            Field::new("thick_start", DataType::Int64, true),
+        ];
+
+        assert_eq!(infer_n_fields(&Schema::new(fields.clone())), 6);
+        assert_eq!(infer_n_fields(&Schema::new(fields[..4].to_vec())), 4);
+
+        let mut fields = fields[..3].to_vec();
+        fields.push(Field::new("strand", DataType::Utf8, true));
+
+        assert_eq!(infer_n_fields(&Schema::new(fields)), 3);
+
+        assert!(is_valid_n_fields(12));
+        assert!(!is_valid_n_fields(8));
+    }
+}
@@ -0,0 +1,252 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, fmt::Debug, sync::Arc};
+
+use arrow::array::{Int64Array, StringArray};
+use datafusion::{
+    datasource::{file_format::write::BatchSerializer, physical_plan::FileSinkConfig},
+    error::DataFusionError,
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_plan::{insert::DataSink, metrics::MetricsSet, DisplayAs, DisplayFormatType},
+};
+use futures::StreamExt;
+use noodles::{core::Position, csi::binning_index::index::header};
+use object_store::{path::Path, PutPayload};
+use tokio::io::AsyncWriteExt;
+
+use super::{
+    bed_serializer::BEDSerializer,
+    bgzf_index_writer::{BGZFIndexWriter, IndexedLine},
+    columns_from_batch::get_array_column,
+};
+
+/// A sink that writes BED files, optionally bgzip compressed and tabix indexed.
+pub struct BEDSink {
+    file_sink_config: FileSinkConfig,
+    n_fields: usize,
+    bgzip: bool,
+    tabix: bool,
+}
+
+impl BEDSink {
+    pub fn new(file_sink_config: FileSinkConfig, n_fields: usize) -> Self {
+        Self {
+            file_sink_config,
+            n_fields,
+            bgzip: false,
+            tabix: false,
+        }
+    }
+
+    /// Write the output as BGZF blocks.
+    pub fn with_bgzip(mut self, bgzip: bool) -> Self {
+        self.bgzip = bgzip;
+        self
+    }
+
+    /// Write a tabix index next to the output, this requires bgzip output sorted by position.
+    pub fn with_tabix(mut self, tabix: bool) -> Self {
+        self.tabix = tabix;
+        self
+    }
+}
+
+impl Debug for BEDSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BEDSink")
+            .field("n_fields", &self.n_fields)
+            .field("bgzip", &self.bgzip)
+            .field("tabix", &self.tabix)
+            .finish()
+    }
+}
+
+impl DisplayAs for BEDSink {
+    fn fmt_as(
+        &self,
+        _display_type: DisplayFormatType,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "BEDSink: n_fields={}", self.n_fields)
+    }
+}
+
+#[async_trait::async_trait]
+impl DataSink for BEDSink {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        None
+    }
+
+    async fn write_all(
+        &self,
+        mut data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<u64, DataFusionError> {
+        if self.tabix && !self.bgzip {
+            return Err(DataFusionError::Plan(
+                "A tabix index can only be built for bgzip compressed BED output".to_string(),
+            ));
+        }
+
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.file_sink_config.object_store_url)?;
+
+        let partition_file = &self.file_sink_config.file_groups[0];
+        let location = partition_file.path();
+
+        let mut buf_writer =
+            object_store::buffered::BufWriter::new(Arc::clone(&object_store), location.clone());
+
+        let serializer = BEDSerializer::new(self.n_fields);
+
+        let mut bgzf_writer = self
+            .bgzip
+            .then(|| BGZFIndexWriter::new(self.tabix.then(|| header::Builder::bed().build())));
+
+        // The number of bytes written to the object store so far.
+        let mut total_bytes = 0;
+
+        while let Some(batch) = data.next().await {
+            let batch = batch?;
+            let bytes = serializer.serialize(batch.clone(), false)?;
+
+            let Some(bgzf_writer) = bgzf_writer.as_mut() else {
+                buf_writer.write_all(&bytes).await?;
+                total_bytes += bytes.len() as u64;
+                continue;
+            };
+
+            if !bgzf_writer.is_indexed() {
+                let compressed = bgzf_writer.compress(&bytes)?;
+                buf_writer.write_all(&compressed).await?;
+                continue;
+            }
+
+            let reference_sequence_names =
+                get_array_column::<StringArray>(&batch, "reference_sequence_name")?;
+            let starts = get_array_column::<Int64Array>(&batch, "start")?;
+            let ends = get_array_column::<Int64Array>(&batch, "end")?;
+
+            let lines = bytes
+                .split_inclusive(|b| *b == b'\n')
+                .enumerate()
+                .map(|(i, line)| {
+                    // BED starts are 0-based, the index expects 1-based positions.
+                    let start = Position::try_from(starts.value(i) as usize + 1)
+                        .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+                    let end = Position::try_from(ends.value(i) as usize)
+                        .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+
+                    Ok(IndexedLine {
+                        line,
+                        reference_sequence_name: reference_sequence_names.value(i),
+                        start,
+                        end,
+                    })
+                })
+                .collect::<Result<Vec<_>, DataFusionError>>()?;
+
+            let compressed = bgzf_writer.compress_lines(lines)?;
+            buf_writer.write_all(&compressed).await?;
+        }
+
+        let index = match bgzf_writer {
+            Some(bgzf_writer) => {
+                total_bytes = bgzf_writer.compressed_len();
+
+                let (eof, index) = bgzf_writer.finish()?;
+                buf_writer.write_all(&eof).await?;
+                total_bytes += eof.len() as u64;
+
+                index
+            }
+            None => None,
+        };
+
+        buf_writer.shutdown().await?;
+
+        if let Some(index) = index {
+            let index_location = Path::from(format!("{}.tbi", location));
+            object_store
+                .put(&index_location, PutPayload::from(index))
+                .await?;
+        }
+
+        Ok(total_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::datasource::listing::PartitionedFile;
+    use datafusion::datasource::physical_plan::FileSinkConfig;
+    use datafusion::execution::object_store::ObjectStoreUrl;
+    use datafusion::logical_expr::dml::InsertOp;
+    use datafusion::physical_plan::insert::DataSink;
+
+    use crate::sinks::BEDSink;
+    use crate::ExonSession;
+
+    #[tokio::test]
+    async fn test_bed_sink_n_fields() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let bed_path = exon_test::test_path("bed", "test.bed");
+
+        let sql = format!(
+            "CREATE EXTERNAL TABLE bed_table STORED AS BED LOCATION '{}'",
+            bed_path.to_str().unwrap()
+        );
+        ctx.sql(&sql).await?.collect().await?;
+
+        let df = ctx.sql("SELECT * FROM bed_table LIMIT 1").await?;
+
+        let stream = df.execute_stream().await?;
+        let output_schema = Arc::clone(&stream.schema());
+
+        let temp_path = std::env::temp_dir().join("test_bed_sink.bed");
+        let p_file = PartitionedFile::new(temp_path.to_str().unwrap(), 0);
+
+        let file_sink_config = FileSinkConfig {
+            object_store_url: ObjectStoreUrl::local_filesystem(),
+            file_groups: vec![p_file],
+            table_paths: vec![],
+            output_schema,
+            table_partition_cols: vec![],
+            insert_op: InsertOp::Append,
+            keep_partition_by_columns: false,
+        };
+
+        let sink = BEDSink::new(file_sink_config, 4);
+        sink.write_all(stream, &ctx.session.task_ctx()).await?;
+
+        let contents = std::fs::read_to_string(&temp_path)?;
+        assert_eq!(
+            contents,
+            "chr1\t11873\t12227\tNR_046018_exon_0_0_chr1_11874_f\n"
+        );
+
+        std::fs::remove_file(temp_path)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+
+use datafusion::error::{DataFusionError, Result};
+use noodles::{
+    bgzf::{self, VirtualPosition},
+    core::Position,
+    csi::binning_index::index::{header::Header, reference_sequence::bin::Chunk},
+    tabix,
+};
+
+/// A line of text output and the 1-based, inclusive interval it covers.
+pub(crate) struct IndexedLine<'a> {
+    pub line: &'a [u8],
+    pub reference_sequence_name: &'a str,
+    pub start: Position,
+    pub end: Position,
+}
+
+/// Compresses text output into BGZF blocks and optionally builds a tabix index of its lines.
+///
+/// Every call compresses its bytes into their own blocks, so the output can be streamed to the
+/// object store as it's produced. Indexed lines must be sorted by position.
+pub(crate) struct BGZFIndexWriter {
+    compressed_len: u64,
+    indexer: Option<tabix::index::Indexer>,
+}
+
+impl BGZFIndexWriter {
+    /// Create a new writer, which builds a tabix index if given an index header.
+    pub(crate) fn new(index_header: Option<Header>) -> Self {
+        let indexer = index_header.map(|header| {
+            let mut indexer = tabix::index::Indexer::default();
+            indexer.set_header(header);
+            indexer
+        });
+
+        Self {
+            compressed_len: 0,
+            indexer,
+        }
+    }
+
+    /// Returns true if a tabix index is being built.
+    pub(crate) fn is_indexed(&self) -> bool {
+        self.indexer.is_some()
+    }
+
+    /// Compress bytes that aren't indexed, e.g. a header.
+    pub(crate) fn compress(&mut self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut writer = bgzf::Writer::new(Vec::new());
+        writer.write_all(bytes)?;
+
+        self.finish_blocks(writer)
+    }
+
+    /// Compress lines, adding them to the index if one is being built.
+    pub(crate) fn compress_lines<'a>(
+        &mut self,
+        lines: impl IntoIterator<Item = IndexedLine<'a>>,
+    ) -> Result<Vec<u8>> {
+        let mut writer = bgzf::Writer::new(Vec::new());
+
+        let compressed_len = self.compressed_len;
+        let to_virtual_position = |vp: VirtualPosition| {
+            VirtualPosition::try_from((compressed_len + vp.compressed(), vp.uncompressed()))
+                .map_err(|e| DataFusionError::Execution(e.to_string()))
+        };
+
+        for line in lines {
+            let chunk_start = to_virtual_position(writer.virtual_position())?;
+            writer.write_all(line.line)?;
+            let chunk_end = to_virtual_position(writer.virtual_position())?;
+
+            if let Some(indexer) = self.indexer.as_mut() {
+                indexer.add_record(
+                    line.reference_sequence_name,
+                    line.start,
+                    line.end,
+                    Chunk::new(chunk_start, chunk_end),
+                )?;
+            }
+        }
+
+        self.finish_blocks(writer)
+    }
+
+    fn finish_blocks(&mut self, mut writer: bgzf::Writer<Vec<u8>>) -> Result<Vec<u8>> {
+        writer.flush()?;
+        let compressed = writer.into_inner();
+
+        self.compressed_len += compressed.len() as u64;
+
+        Ok(compressed)
+    }
+
+    /// The number of compressed bytes returned so far.
+    pub(crate) fn compressed_len(&self) -> u64 {
+        self.compressed_len
+    }
+
+    /// Finish the output, returning the BGZF EOF block and the serialized tabix index.
+    pub(crate) fn finish(self) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+        let eof = bgzf::Writer::new(Vec::new()).finish()?;
+
+        let index = match self.indexer {
+            Some(indexer) => {
+                let mut index_writer = tabix::Writer::new(Vec::new());
+                index_writer.write_index(&indexer.build())?;
+                Some(index_writer.into_inner().finish()?)
+            }
+            None => None,
+        };
+
+        Ok((eof, index))
+    }
+}
@@ -33,3 +33,84 @@ where
         )))
     }
 }
+
+/// Like `get_array_column`, but returns `None` if the column is not in the batch.
+pub(crate) fn get_optional_array_column<'a, T>(
+    batch: &'a arrow::record_batch::RecordBatch,
+    column_name: &str,
+) -> Result<Option<&'a T>, datafusion::error::DataFusionError>
+where
+    T: arrow::array::Array + 'static,
+{
+    match batch.column_by_name(column_name) {
+        Some(_) => get_array_column(batch, column_name).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Get the `flag` column as Int32, casting the UInt16 flags of alignment tables read with compact
+/// types.
+pub(crate) fn get_flag_column(
+    batch: &arrow::record_batch::RecordBatch,
+) -> Result<arrow::array::Int32Array, datafusion::error::DataFusionError> {
+    use arrow::{array::AsArray, datatypes::DataType, datatypes::Int32Type};
+
+    let Some(column) = batch.column_by_name("flag") else {
+        return Err(datafusion::error::DataFusionError::Execution(
+            "flag column not found".to_string(),
+        ));
+    };
+
+    match column.data_type() {
+        DataType::Int32 => Ok(column.as_primitive::<Int32Type>().clone()),
+        DataType::UInt16 => {
+            let flags = arrow::compute::cast(column, &DataType::Int32)?;
+            Ok(flags.as_primitive::<Int32Type>().clone())
+        }
+        data_type => Err(datafusion::error::DataFusionError::Execution(format!(
+            "flag should be an Int32 or UInt16 array, not {}",
+            data_type
+        ))),
+    }
+}
+
+/// Render the typed `strand` and dictionary encoded `type` columns of feature tables as Utf8, see
+/// `exon_common::StrandEncoding`, so tables read with any encoding can be written.
+pub(crate) fn feature_columns_as_utf8(
+    batch: &arrow::record_batch::RecordBatch,
+) -> Result<arrow::record_batch::RecordBatch, datafusion::error::DataFusionError> {
+    use std::sync::Arc;
+
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    let schema = batch.schema();
+
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut columns = Vec::with_capacity(batch.num_columns());
+
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        let column = match (field.name().as_str(), field.data_type()) {
+            ("strand", DataType::Int8 | DataType::Dictionary(_, _)) => {
+                exon_common::strands_as_utf8(column)?
+            }
+            ("type", DataType::Dictionary(_, _)) => arrow::compute::cast(column, &DataType::Utf8)?,
+            _ => {
+                fields.push(field.as_ref().clone());
+                columns.push(Arc::clone(column));
+                continue;
+            }
+        };
+
+        fields.push(Field::new(
+            field.name(),
+            DataType::Utf8,
+            field.is_nullable(),
+        ));
+        columns.push(column);
+    }
+
+    Ok(arrow::record_batch::RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
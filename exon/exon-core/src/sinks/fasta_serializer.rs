@@ -12,31 +12,71 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use arrow::array::StringArray;
+use arrow::array::{RecordBatch, StringArray};
 use bytes::Bytes;
-use datafusion::datasource::file_format::write::BatchSerializer;
+use datafusion::{datasource::file_format::write::BatchSerializer, error::Result};
 use noodles::fasta::{
+    fai,
     record::{Definition, Sequence},
     Record,
 };
 
-use super::columns_from_batch::get_array_column;
+use super::columns_from_batch::{get_array_column, get_optional_array_column};
 
-#[derive(Debug, Default)]
-pub(crate) struct FASTASerializer {}
+/// The number of bases per line of FASTA output, unless asked for otherwise.
+pub(crate) const DEFAULT_LINE_WIDTH: usize = 80;
 
-impl BatchSerializer for FASTASerializer {
-    fn serialize(
+#[derive(Debug)]
+pub(crate) struct FASTASerializer {
+    line_width: usize,
+}
+
+impl Default for FASTASerializer {
+    fn default() -> Self {
+        Self::new(DEFAULT_LINE_WIDTH)
+    }
+}
+
+impl FASTASerializer {
+    /// Create a serializer that wraps sequences every `line_width` bases, or writes each sequence
+    /// on a single line if it's 0.
+    pub(crate) fn new(line_width: usize) -> Self {
+        Self { line_width }
+    }
+
+    /// Serialize the records and add their `.fai` records to the index, where `offset` is the
+    /// position of the serialized records in the uncompressed output.
+    pub(crate) fn serialize_indexed(
         &self,
-        batch: arrow::array::RecordBatch,
-        _initial: bool,
-    ) -> datafusion::error::Result<bytes::Bytes> {
-        let ids = get_array_column::<StringArray>(&batch, "id")?;
-        let descriptions = get_array_column::<StringArray>(&batch, "description")?;
-        let sequences = get_array_column::<StringArray>(&batch, "sequence")?;
+        batch: &RecordBatch,
+        offset: u64,
+        index: &mut Vec<fai::Record>,
+    ) -> Result<Vec<u8>> {
+        self.write_records(batch, offset, Some(index))
+    }
+
+    fn write_records(
+        &self,
+        batch: &RecordBatch,
+        offset: u64,
+        mut index: Option<&mut Vec<fai::Record>>,
+    ) -> Result<Vec<u8>> {
+        // FASTQ records are identified by their name column.
+        let ids = match get_optional_array_column::<StringArray>(batch, "id")? {
+            Some(ids) => ids,
+            None => get_array_column::<StringArray>(batch, "name")?,
+        };
+        let descriptions = get_array_column::<StringArray>(batch, "description")?;
+        let sequences = get_array_column::<StringArray>(batch, "sequence")?;
+
+        let line_base_count = match self.line_width {
+            0 => usize::MAX,
+            line_width => line_width,
+        };
 
-        let b = Vec::new();
-        let mut fasta_writer = noodles::fasta::writer::Writer::new(b);
+        let mut fasta_writer = noodles::fasta::writer::Builder::default()
+            .set_line_base_count(line_base_count)
+            .build_with_writer(Vec::new());
 
         for i in 0..batch.num_rows() {
             let id = ids.value(i);
@@ -46,11 +86,83 @@ impl BatchSerializer for FASTASerializer {
             let definition = Definition::new(id, Some(Vec::from(description)));
             let sequence = Sequence::from(Vec::from(sequence));
 
+            let record_start = fasta_writer.get_ref().len();
+
             let record = Record::new(definition, sequence);
             fasta_writer.write_record(&record)?;
+
+            if let Some(index) = index.as_mut() {
+                // The sequence starts after the definition line.
+                let definition_len = fasta_writer.get_ref()[record_start..]
+                    .iter()
+                    .position(|b| *b == b'\n')
+                    .unwrap_or_default();
+                let sequence_start = (record_start + definition_len + 1) as u64;
+
+                let length = record.sequence().len() as u64;
+                let line_bases = length.min(line_base_count as u64);
+
+                index.push(fai::Record::new(
+                    id,
+                    length,
+                    offset + sequence_start,
+                    line_bases,
+                    line_bases + 1,
+                ));
+            }
         }
 
-        // todo benchmark this
-        Ok(Bytes::from(fasta_writer.get_ref().to_vec()))
+        Ok(fasta_writer.into_inner())
+    }
+}
+
+impl BatchSerializer for FASTASerializer {
+    fn serialize(&self, batch: RecordBatch, _initial: bool) -> Result<Bytes> {
+        Ok(Bytes::from(self.write_records(&batch, 0, None)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{RecordBatch, StringArray},
+        datatypes::{DataType, Field, Schema},
+    };
+    use noodles::fasta::fai;
+
+    use super::FASTASerializer;
+
+    #[test]
+    fn test_serialize_wrapped_with_index() -> Result<(), Box<dyn std::error::Error>> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("description", DataType::Utf8, true),
+            Field::new("sequence", DataType::Utf8, false),
+        ]));
+
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b"])),
+                Arc::new(StringArray::from(vec![Some("desc"), None])),
+                Arc::new(StringArray::from(vec!["ATCGA", "AT"])),
+            ],
+        )?;
+
+        let mut index = Vec::new();
+        let bytes = FASTASerializer::new(2).serialize_indexed(&batch, 10, &mut index)?;
+
+        assert_eq!(bytes, b">a desc\nAT\nCG\nA\n>b \nAT\n");
+        assert_eq!(
+            index,
+            vec![
+                fai::Record::new("a", 5, 18, 2, 3),
+                fai::Record::new("b", 2, 30, 2, 3),
+            ]
+        );
+
+        Ok(())
     }
 }
@@ -0,0 +1,387 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, fmt::Debug, sync::Arc};
+
+use datafusion::{
+    datasource::{
+        file_format::file_compression_type::FileCompressionType, physical_plan::FileSinkConfig,
+    },
+    error::{DataFusionError, Result},
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_plan::{insert::DataSink, metrics::MetricsSet, DisplayAs, DisplayFormatType},
+};
+use futures::StreamExt;
+use noodles::fasta::fai;
+use object_store::{path::Path, PutPayload};
+use tokio::io::AsyncWriteExt;
+
+use super::{
+    bgzf_index_writer::BGZFIndexWriter,
+    fasta_serializer::{FASTASerializer, DEFAULT_LINE_WIDTH},
+};
+
+/// The uncompressed size of the BGZF blocks, which fits in a single block with room to spare.
+const BGZF_BLOCK_SIZE: usize = 0xff00;
+
+/// Compresses the output into BGZF blocks and records the `.gzi` index of the block offsets.
+struct GZIWriter {
+    bgzf_writer: BGZFIndexWriter,
+    uncompressed_len: u64,
+    index: Vec<(u64, u64)>,
+}
+
+impl GZIWriter {
+    fn new() -> Self {
+        Self {
+            bgzf_writer: BGZFIndexWriter::new(None),
+            uncompressed_len: 0,
+            index: Vec::new(),
+        }
+    }
+
+    /// Compress a block of at most [`BGZF_BLOCK_SIZE`] bytes.
+    fn compress_block(&mut self, block: &[u8]) -> Result<Vec<u8>> {
+        // The first block is implicitly at the start of the file.
+        if self.uncompressed_len > 0 {
+            self.index
+                .push((self.bgzf_writer.compressed_len(), self.uncompressed_len));
+        }
+
+        self.uncompressed_len += block.len() as u64;
+
+        self.bgzf_writer.compress(block)
+    }
+
+    /// Serialize the index, the number of entries followed by the compressed and uncompressed
+    /// offsets of each block as little endian integers.
+    fn index_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(8 + self.index.len() * 16);
+        bytes.extend_from_slice(&(self.index.len() as u64).to_le_bytes());
+
+        for (compressed_offset, uncompressed_offset) in &self.index {
+            bytes.extend_from_slice(&compressed_offset.to_le_bytes());
+            bytes.extend_from_slice(&uncompressed_offset.to_le_bytes());
+        }
+
+        bytes
+    }
+}
+
+/// A sink that writes FASTA files with a configurable line width, optionally bgzip compressed,
+/// and optionally with a `.fai` index, plus a `.gzi` index for bgzip output.
+pub struct FASTASink {
+    file_compression_type: FileCompressionType,
+    file_sink_config: FileSinkConfig,
+    line_width: usize,
+    bgzip: bool,
+    fai: bool,
+}
+
+impl FASTASink {
+    pub fn new(
+        file_sink_config: FileSinkConfig,
+        file_compression_type: FileCompressionType,
+    ) -> Self {
+        Self {
+            file_sink_config,
+            file_compression_type,
+            line_width: DEFAULT_LINE_WIDTH,
+            bgzip: false,
+            fai: false,
+        }
+    }
+
+    /// Wrap the sequences every `line_width` bases, or write each on a single line if it's 0.
+    pub fn with_line_width(mut self, line_width: usize) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    /// Write the output as BGZF blocks instead of using the file compression type.
+    pub fn with_bgzip(mut self, bgzip: bool) -> Self {
+        self.bgzip = bgzip;
+        self
+    }
+
+    /// Write a `.fai` index next to the output, and a `.gzi` index if it's bgzip compressed.
+    pub fn with_fai(mut self, fai: bool) -> Self {
+        self.fai = fai;
+        self
+    }
+
+    async fn write_index(
+        &self,
+        context: &Arc<TaskContext>,
+        suffix: &str,
+        bytes: Vec<u8>,
+    ) -> Result<()> {
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.file_sink_config.object_store_url)?;
+
+        let location = self.file_sink_config.file_groups[0].path();
+        let index_location = Path::from(format!("{}.{}", location, suffix));
+
+        object_store
+            .put(&index_location, PutPayload::from(bytes))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn write_fai(&self, context: &Arc<TaskContext>, records: Vec<fai::Record>) -> Result<()> {
+        let mut fai_writer = fai::Writer::new(Vec::new());
+        fai_writer.write_index(&fai::Index::from(records))?;
+
+        self.write_index(context, "fai", fai_writer.into_inner())
+            .await
+    }
+
+    /// Write the records as BGZF blocks, returning the number of compressed bytes written.
+    async fn write_bgzip(
+        &self,
+        mut data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<u64> {
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.file_sink_config.object_store_url)?;
+
+        let location = self.file_sink_config.file_groups[0].path();
+        let mut buf_writer = object_store::buffered::BufWriter::new(object_store, location.clone());
+
+        let serializer = FASTASerializer::new(self.line_width);
+        let mut gzi_writer = GZIWriter::new();
+        let mut fai_records = Vec::new();
+
+        // Records are buffered into full blocks, so the block size doesn't depend on the batches.
+        let mut pending = Vec::new();
+
+        while let Some(batch) = data.next().await {
+            let batch = batch?;
+
+            let offset = gzi_writer.uncompressed_len + pending.len() as u64;
+            pending.extend(serializer.serialize_indexed(&batch, offset, &mut fai_records)?);
+
+            while pending.len() >= BGZF_BLOCK_SIZE {
+                let rest = pending.split_off(BGZF_BLOCK_SIZE);
+                buf_writer
+                    .write_all(&gzi_writer.compress_block(&pending)?)
+                    .await?;
+                pending = rest;
+            }
+        }
+
+        if !pending.is_empty() {
+            buf_writer
+                .write_all(&gzi_writer.compress_block(&pending)?)
+                .await?;
+        }
+
+        let gzi = gzi_writer.index_bytes();
+        let compressed_len = gzi_writer.bgzf_writer.compressed_len();
+        let (eof, _) = gzi_writer.bgzf_writer.finish()?;
+
+        buf_writer.write_all(&eof).await?;
+        buf_writer.shutdown().await?;
+
+        if self.fai {
+            self.write_fai(context, fai_records).await?;
+            self.write_index(context, "gzi", gzi).await?;
+        }
+
+        Ok(compressed_len + eof.len() as u64)
+    }
+}
+
+impl Debug for FASTASink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FASTASink")
+            .field("line_width", &self.line_width)
+            .field("bgzip", &self.bgzip)
+            .field("fai", &self.fai)
+            .finish()
+    }
+}
+
+impl DisplayAs for FASTASink {
+    fn fmt_as(
+        &self,
+        _display_type: DisplayFormatType,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "FASTASink")
+    }
+}
+
+#[async_trait::async_trait]
+impl DataSink for FASTASink {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        None
+    }
+
+    async fn write_all(
+        &self,
+        mut data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<u64> {
+        if self.bgzip {
+            return self.write_bgzip(data, context).await;
+        }
+
+        if self.fai && self.file_compression_type != FileCompressionType::UNCOMPRESSED {
+            return Err(DataFusionError::Plan(
+                "A FASTA index can only be built for uncompressed or bgzip compressed output"
+                    .to_string(),
+            ));
+        }
+
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.file_sink_config.object_store_url)?;
+
+        let location = self.file_sink_config.file_groups[0].path();
+
+        let buf_writer = object_store::buffered::BufWriter::new(object_store, location.clone());
+        let mut buf_writer = self
+            .file_compression_type
+            .convert_async_writer(buf_writer)?;
+
+        let serializer = FASTASerializer::new(self.line_width);
+        let mut fai_records = Vec::new();
+        let mut total_bytes = 0;
+
+        while let Some(batch) = data.next().await {
+            let batch = batch?;
+            let bytes = serializer.serialize_indexed(&batch, total_bytes, &mut fai_records)?;
+
+            buf_writer.write_all(&bytes).await?;
+            total_bytes += bytes.len() as u64;
+        }
+
+        buf_writer.shutdown().await?;
+
+        if self.fai {
+            self.write_fai(context, fai_records).await?;
+        }
+
+        Ok(total_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
+    use datafusion::datasource::listing::PartitionedFile;
+    use datafusion::datasource::physical_plan::FileSinkConfig;
+    use datafusion::execution::object_store::ObjectStoreUrl;
+    use datafusion::logical_expr::dml::InsertOp;
+    use datafusion::physical_plan::insert::DataSink;
+    use noodles::fasta::fai;
+
+    use crate::sinks::FASTASink;
+    use crate::ExonSession;
+
+    async fn write_fasta(
+        ctx: &ExonSession,
+        path: &std::path::Path,
+        bgzip: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let fasta_path = exon_test::test_path("fasta", "test.fasta");
+
+        let sql = format!(
+            "SELECT * FROM fasta_scan('{}')",
+            fasta_path.to_str().ok_or("Invalid path")?
+        );
+        let stream = ctx.sql(&sql).await?.execute_stream().await?;
+
+        let file_sink_config = FileSinkConfig {
+            object_store_url: ObjectStoreUrl::local_filesystem(),
+            file_groups: vec![PartitionedFile::new(
+                path.to_str().ok_or("Invalid path")?,
+                0,
+            )],
+            table_paths: vec![],
+            output_schema: Arc::clone(&stream.schema()),
+            table_partition_cols: vec![],
+            insert_op: InsertOp::Append,
+            keep_partition_by_columns: false,
+        };
+
+        let sink = FASTASink::new(file_sink_config, FileCompressionType::UNCOMPRESSED)
+            .with_line_width(3)
+            .with_bgzip(bgzip)
+            .with_fai(true);
+        sink.write_all(stream, &ctx.session.task_ctx()).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fasta_sink_with_fai() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let temp_path = std::env::temp_dir().join("test_fasta_sink.fasta");
+        write_fasta(&ctx, &temp_path, false).await?;
+
+        let contents = std::fs::read_to_string(&temp_path)?;
+        assert_eq!(
+            contents,
+            ">a description\nATC\nG\n>b description2\nATC\nG\n"
+        );
+
+        let index = fai::read(temp_path.with_extension("fasta.fai"))?;
+        assert_eq!(
+            Vec::from(index),
+            vec![
+                fai::Record::new("a", 4, 15, 3, 4),
+                fai::Record::new("b", 4, 37, 3, 4),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fasta_sink_with_bgzip() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let temp_path = std::env::temp_dir().join("test_fasta_sink.fasta.gz");
+        write_fasta(&ctx, &temp_path, true).await?;
+
+        let mut reader = noodles::bgzf::Reader::new(std::fs::File::open(&temp_path)?);
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut reader, &mut contents)?;
+        assert_eq!(
+            contents,
+            ">a description\nATC\nG\n>b description2\nATC\nG\n"
+        );
+
+        // The output fits in a single block, so the gzip index has no entries.
+        let gzi = noodles::bgzf::gzi::read(temp_path.with_extension("gz.gzi"))?;
+        assert!(gzi.is_empty());
+
+        let index = fai::read(temp_path.with_extension("gz.fai"))?;
+        assert_eq!(Vec::from(index).len(), 2);
+
+        Ok(())
+    }
+}
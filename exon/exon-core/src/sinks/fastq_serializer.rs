@@ -12,42 +12,143 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use arrow::array::StringArray;
+use arrow::array::{Array, RecordBatch, StringArray};
 use bytes::Bytes;
-use datafusion::datasource::file_format::write::BatchSerializer;
+use datafusion::{
+    datasource::file_format::write::BatchSerializer,
+    error::{DataFusionError, Result},
+};
+use exon_common::PHRED_OFFSET;
 
-use super::columns_from_batch::get_array_column;
+use super::columns_from_batch::{get_array_column, get_optional_array_column};
+
+/// The highest Phred quality score that can be encoded as a printable character.
+pub(crate) const MAX_QUALITY_SCORE: u8 = 93;
 
 #[derive(Debug, Default)]
-pub(crate) struct FASTQSerializer {}
+pub(crate) struct FASTQSerializer {
+    /// The encoded quality score used for records without quality scores, e.g. from FASTA.
+    quality_score: Option<u8>,
+}
 
-impl BatchSerializer for FASTQSerializer {
-    fn serialize(
-        &self,
-        batch: arrow::array::RecordBatch,
-        _initial: bool,
-    ) -> datafusion::error::Result<bytes::Bytes> {
-        let names = get_array_column::<StringArray>(&batch, "name")?;
-        let descriptions = get_array_column::<StringArray>(&batch, "description")?;
-        let sequences = get_array_column::<StringArray>(&batch, "sequence")?;
-        let quality_scores = get_array_column::<StringArray>(&batch, "quality_scores")?;
-
-        let b = Vec::new();
-        let mut fasta_writer = noodles::fastq::io::Writer::new(b);
+impl FASTQSerializer {
+    /// Fill in missing quality scores with the given Phred quality score, which must be at most
+    /// [`MAX_QUALITY_SCORE`].
+    pub(crate) fn with_quality_score(mut self, quality_score: u8) -> Self {
+        self.quality_score = Some(quality_score + PHRED_OFFSET);
+        self
+    }
+
+    pub(crate) fn serialize_batch(&self, batch: &RecordBatch) -> Result<Vec<u8>> {
+        // FASTA records are named by their id column.
+        let names = match get_optional_array_column::<StringArray>(batch, "name")? {
+            Some(names) => names,
+            None => get_array_column::<StringArray>(batch, "id")?,
+        };
+        let descriptions = get_optional_array_column::<StringArray>(batch, "description")?;
+        let sequences = get_array_column::<StringArray>(batch, "sequence")?;
+        let quality_scores = get_optional_array_column::<StringArray>(batch, "quality_scores")?;
+
+        let mut fastq_writer = noodles::fastq::io::Writer::new(Vec::new());
 
         for i in 0..batch.num_rows() {
-            let id = names.value(i);
-            let description = descriptions.value(i);
+            let name = names.value(i);
+            let description = descriptions
+                .filter(|descriptions| descriptions.is_valid(i))
+                .map(|descriptions| descriptions.value(i))
+                .unwrap_or_default();
             let sequence = sequences.value(i);
-            let quality_scores = quality_scores.value(i);
 
-            let definition = noodles::fastq::record::Definition::new(id, description);
+            let quality_scores = match quality_scores.filter(|q| q.is_valid(i)) {
+                Some(quality_scores) => {
+                    let quality_scores = quality_scores.value(i);
+
+                    if quality_scores.len() != sequence.len() {
+                        return Err(DataFusionError::Execution(format!(
+                            "Record {} has {} bases but {} quality scores",
+                            name,
+                            sequence.len(),
+                            quality_scores.len()
+                        )));
+                    }
+
+                    quality_scores.as_bytes().to_vec()
+                }
+                None => match self.quality_score {
+                    Some(quality_score) => vec![quality_score; sequence.len()],
+                    None => {
+                        return Err(DataFusionError::Execution(format!(
+                            "Record {} has no quality scores, set the quality_score option to fill them in",
+                            name
+                        )))
+                    }
+                },
+            };
+
+            let definition = noodles::fastq::record::Definition::new(name, description);
             let record = noodles::fastq::Record::new(definition, sequence, quality_scores);
 
-            fasta_writer.write_record(&record)?;
+            fastq_writer.write_record(&record)?;
         }
 
-        // todo benchmark this
-        Ok(Bytes::from(fasta_writer.get_ref().to_vec()))
+        Ok(fastq_writer.into_inner())
+    }
+}
+
+impl BatchSerializer for FASTQSerializer {
+    fn serialize(&self, batch: RecordBatch, _initial: bool) -> Result<Bytes> {
+        Ok(Bytes::from(self.serialize_batch(&batch)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{RecordBatch, StringArray},
+        datatypes::{DataType, Field, Schema},
+    };
+
+    use super::FASTQSerializer;
+
+    fn fasta_batch() -> Result<RecordBatch, Box<dyn std::error::Error>> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("description", DataType::Utf8, true),
+            Field::new("sequence", DataType::Utf8, false),
+        ]));
+
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b"])),
+                Arc::new(StringArray::from(vec![Some("desc"), None])),
+                Arc::new(StringArray::from(vec!["ACGT", "AC"])),
+            ],
+        )?;
+
+        Ok(batch)
+    }
+
+    #[test]
+    fn test_serialize_with_quality_score() -> Result<(), Box<dyn std::error::Error>> {
+        let serializer = FASTQSerializer::default().with_quality_score(40);
+        let bytes = serializer.serialize_batch(&fasta_batch()?)?;
+
+        assert_eq!(
+            String::from_utf8(bytes)?,
+            "@a desc\nACGT\n+\nIIII\n@b\nAC\n+\nII\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_without_quality_scores() -> Result<(), Box<dyn std::error::Error>> {
+        let serializer = FASTQSerializer::default();
+        assert!(serializer.serialize_batch(&fasta_batch()?).is_err());
+
+        Ok(())
     }
 }
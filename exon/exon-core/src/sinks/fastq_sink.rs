@@ -0,0 +1,173 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, fmt::Debug, sync::Arc};
+
+use datafusion::{
+    datasource::{
+        file_format::file_compression_type::FileCompressionType, physical_plan::FileSinkConfig,
+    },
+    error::Result,
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_plan::{insert::DataSink, metrics::MetricsSet, DisplayAs, DisplayFormatType},
+};
+use futures::StreamExt;
+use tokio::io::AsyncWriteExt;
+
+use super::fastq_serializer::FASTQSerializer;
+
+/// A sink that writes FASTQ files, from FASTQ records or from FASTA records with a synthesized
+/// quality score.
+pub struct FASTQSink {
+    file_compression_type: FileCompressionType,
+    file_sink_config: FileSinkConfig,
+    quality_score: Option<u8>,
+}
+
+impl FASTQSink {
+    pub fn new(
+        file_sink_config: FileSinkConfig,
+        file_compression_type: FileCompressionType,
+    ) -> Self {
+        Self {
+            file_sink_config,
+            file_compression_type,
+            quality_score: None,
+        }
+    }
+
+    /// Use the Phred quality score for records without a `quality_scores` column or value.
+    pub fn with_quality_score(mut self, quality_score: Option<u8>) -> Self {
+        self.quality_score = quality_score;
+        self
+    }
+}
+
+impl Debug for FASTQSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FASTQSink")
+            .field("quality_score", &self.quality_score)
+            .finish()
+    }
+}
+
+impl DisplayAs for FASTQSink {
+    fn fmt_as(
+        &self,
+        _display_type: DisplayFormatType,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "FASTQSink")
+    }
+}
+
+#[async_trait::async_trait]
+impl DataSink for FASTQSink {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        None
+    }
+
+    async fn write_all(
+        &self,
+        mut data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<u64> {
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.file_sink_config.object_store_url)?;
+
+        let location = self.file_sink_config.file_groups[0].path();
+
+        let buf_writer = object_store::buffered::BufWriter::new(object_store, location.clone());
+        let mut buf_writer = self
+            .file_compression_type
+            .convert_async_writer(buf_writer)?;
+
+        let serializer = match self.quality_score {
+            Some(quality_score) => FASTQSerializer::default().with_quality_score(quality_score),
+            None => FASTQSerializer::default(),
+        };
+
+        let mut total_bytes = 0;
+
+        while let Some(batch) = data.next().await {
+            let bytes = serializer.serialize_batch(&batch?)?;
+
+            buf_writer.write_all(&bytes).await?;
+            total_bytes += bytes.len() as u64;
+        }
+
+        buf_writer.shutdown().await?;
+
+        Ok(total_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
+    use datafusion::datasource::listing::PartitionedFile;
+    use datafusion::datasource::physical_plan::FileSinkConfig;
+    use datafusion::execution::object_store::ObjectStoreUrl;
+    use datafusion::logical_expr::dml::InsertOp;
+    use datafusion::physical_plan::insert::DataSink;
+
+    use crate::sinks::FASTQSink;
+    use crate::ExonSession;
+
+    #[tokio::test]
+    async fn test_fastq_sink_from_fasta() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let fasta_path = exon_test::test_path("fasta", "test.fasta");
+        let sql = format!(
+            "SELECT * FROM fasta_scan('{}')",
+            fasta_path.to_str().ok_or("Invalid path")?
+        );
+        let stream = ctx.sql(&sql).await?.execute_stream().await?;
+
+        let temp_path = std::env::temp_dir().join("test_fastq_sink.fastq");
+
+        let file_sink_config = FileSinkConfig {
+            object_store_url: ObjectStoreUrl::local_filesystem(),
+            file_groups: vec![PartitionedFile::new(
+                temp_path.to_str().ok_or("Invalid path")?,
+                0,
+            )],
+            table_paths: vec![],
+            output_schema: Arc::clone(&stream.schema()),
+            table_partition_cols: vec![],
+            insert_op: InsertOp::Append,
+            keep_partition_by_columns: false,
+        };
+
+        let sink = FASTQSink::new(file_sink_config, FileCompressionType::UNCOMPRESSED)
+            .with_quality_score(Some(30));
+        sink.write_all(stream, &ctx.session.task_ctx()).await?;
+
+        let contents = std::fs::read_to_string(&temp_path)?;
+        assert_eq!(
+            contents,
+            "@a description\nATCG\n+\n????\n@b description2\nATCG\n+\n????\n"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, fmt::Write};
+
+use arrow::array::{Array, AsArray, Float32Array, Int64Array, MapArray, StringArray};
+use bytes::Bytes;
+use datafusion::{datasource::file_format::write::BatchSerializer, error::DataFusionError};
+
+use super::columns_from_batch::{
+    feature_columns_as_utf8, get_array_column, get_optional_array_column,
+};
+
+/// Characters that must be escaped anywhere in a GFF3 line.
+const COLUMN_RESERVED: &[u8] = b"\t\n\r%";
+
+/// Characters that additionally must be escaped inside the attributes column.
+const ATTRIBUTE_RESERVED: &[u8] = b"\t\n\r%;=&,";
+
+/// Percent-encode the reserved characters and control characters in `value`.
+fn escape(value: &str, reserved: &[u8]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        if c.is_ascii() && (reserved.contains(&(c as u8)) || c.is_ascii_control()) {
+            // Writing to a String can't fail.
+            let _ = write!(escaped, "%{:02X}", c as u8);
+        } else {
+            escaped.push(c);
+        }
+    }
+
+    escaped
+}
+
+/// Check that the columns every GFF3 feature line needs have no nulls.
+fn check_required_columns(
+    seqnames: &StringArray,
+    starts: &Int64Array,
+    ends: &Int64Array,
+) -> datafusion::error::Result<()> {
+    let columns: [(&str, &dyn Array); 3] =
+        [("seqname", seqnames), ("start", starts), ("end", ends)];
+
+    for (name, column) in columns {
+        if column.null_count() > 0 {
+            return Err(DataFusionError::Execution(format!(
+                "GFF features require a {}, but the column has {} null values",
+                name,
+                column.null_count()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Tracks the extent of each reference sequence seen while writing, in order of first appearance.
+#[derive(Debug, Default)]
+pub(crate) struct SequenceRegions {
+    regions: Vec<(String, i64, i64)>,
+    index: HashMap<String, usize>,
+}
+
+impl SequenceRegions {
+    /// Update the regions with the seqname, start, and end columns of the batch.
+    pub(crate) fn update(
+        &mut self,
+        batch: &arrow::array::RecordBatch,
+    ) -> datafusion::error::Result<()> {
+        let seqnames = get_array_column::<StringArray>(batch, "seqname")?;
+        let starts = get_array_column::<Int64Array>(batch, "start")?;
+        let ends = get_array_column::<Int64Array>(batch, "end")?;
+
+        check_required_columns(seqnames, starts, ends)?;
+
+        for i in 0..batch.num_rows() {
+            let seqname = seqnames.value(i);
+            let start = starts.value(i);
+            let end = ends.value(i);
+
+            match self.index.get(seqname) {
+                Some(idx) => {
+                    let region = &mut self.regions[*idx];
+                    region.1 = region.1.min(start);
+                    region.2 = region.2.max(end);
+                }
+                None => {
+                    self.index.insert(seqname.to_string(), self.regions.len());
+                    self.regions.push((seqname.to_string(), start, end));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render the GFF3 header, including a `##sequence-region` directive per reference sequence.
+    pub(crate) fn header(&self) -> String {
+        let mut header = String::from("##gff-version 3\n");
+
+        for (seqname, start, end) in self.regions.iter() {
+            let _ = writeln!(
+                header,
+                "##sequence-region {} {} {}",
+                escape(seqname, COLUMN_RESERVED),
+                start,
+                end
+            );
+        }
+
+        header
+    }
+}
+
+/// Serializes record batches with the GFF schema into GFF3 feature lines.
+///
+/// Only `seqname`, `start`, and `end` are required, any other missing column is written as `.`.
+#[derive(Debug, Default)]
+pub(crate) struct GFFSerializer {}
+
+impl GFFSerializer {
+    fn write_attributes(
+        line: &mut String,
+        attributes: &MapArray,
+        i: usize,
+    ) -> datafusion::error::Result<()> {
+        if attributes.is_null(i) || attributes.value_length(i) == 0 {
+            line.push('.');
+            return Ok(());
+        }
+
+        let entries = attributes.value(i);
+        let keys = entries.column(0).as_string::<i32>();
+        let values = entries.column(1).as_list_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution("attributes values should be a list array".to_string())
+        })?;
+
+        for j in 0..entries.len() {
+            if j > 0 {
+                line.push(';');
+            }
+
+            line.push_str(&escape(keys.value(j), ATTRIBUTE_RESERVED));
+            line.push('=');
+
+            if values.is_null(j) {
+                continue;
+            }
+
+            let attr_values = values.value(j);
+            let attr_values = attr_values.as_string::<i32>();
+
+            for k in 0..attr_values.len() {
+                if k > 0 {
+                    line.push(',');
+                }
+
+                line.push_str(&escape(attr_values.value(k), ATTRIBUTE_RESERVED));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl BatchSerializer for GFFSerializer {
+    fn serialize(
+        &self,
+        batch: arrow::array::RecordBatch,
+        _initial: bool,
+    ) -> datafusion::error::Result<bytes::Bytes> {
+        let batch = feature_columns_as_utf8(&batch)?;
+
+        let seqnames = get_array_column::<StringArray>(&batch, "seqname")?;
+        let sources = get_optional_array_column::<StringArray>(&batch, "source")?;
+        let types = get_optional_array_column::<StringArray>(&batch, "type")?;
+        let starts = get_array_column::<Int64Array>(&batch, "start")?;
+        let ends = get_array_column::<Int64Array>(&batch, "end")?;
+        let scores = get_optional_array_column::<Float32Array>(&batch, "score")?;
+        let strands = get_optional_array_column::<StringArray>(&batch, "strand")?;
+        let phases = get_optional_array_column::<StringArray>(&batch, "phase")?;
+        let attributes = get_optional_array_column::<MapArray>(&batch, "attributes")?;
+
+        check_required_columns(seqnames, starts, ends)?;
+
+        let string_or_dot = |array: Option<&StringArray>, i: usize| match array {
+            Some(array) if array.is_valid(i) => escape(array.value(i), COLUMN_RESERVED),
+            _ => ".".to_string(),
+        };
+
+        let mut lines = String::new();
+
+        for i in 0..batch.num_rows() {
+            let score = match scores {
+                Some(scores) if scores.is_valid(i) => scores.value(i).to_string(),
+                _ => ".".to_string(),
+            };
+
+            let _ = write!(
+                lines,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t",
+                escape(seqnames.value(i), COLUMN_RESERVED),
+                string_or_dot(sources, i),
+                string_or_dot(types, i),
+                starts.value(i),
+                ends.value(i),
+                score,
+                string_or_dot(strands, i),
+                string_or_dot(phases, i),
+            );
+
+            match attributes {
+                Some(attributes) => Self::write_attributes(&mut lines, attributes, i)?,
+                None => lines.push('.'),
+            }
+
+            lines.push('\n');
+        }
+
+        Ok(Bytes::from(lines))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Int64Array, RecordBatch, StringArray},
+        datatypes::{DataType, Field, Schema},
+    };
+    use datafusion::datasource::file_format::write::BatchSerializer;
+
+    use super::escape;
+    use super::{GFFSerializer, SequenceRegions, ATTRIBUTE_RESERVED, COLUMN_RESERVED};
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("chr1", COLUMN_RESERVED), "chr1");
+        assert_eq!(escape("a;b=c", COLUMN_RESERVED), "a;b=c");
+        assert_eq!(escape("a;b=c,d", ATTRIBUTE_RESERVED), "a%3Bb%3Dc%2Cd");
+        assert_eq!(escape("100%\tdone", COLUMN_RESERVED), "100%25%09done");
+    }
+
+    #[test]
+    fn test_null_required_columns() -> Result<(), Box<dyn std::error::Error>> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("seqname", DataType::Utf8, true),
+            Field::new("start", DataType::Int64, true),
+            Field::new("end", DataType::Int64, true),
+        ]));
+
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec![Some("chr1"), Some("chr1")])),
+                Arc::new(Int64Array::from(vec![Some(1), None])),
+                Arc::new(Int64Array::from(vec![Some(10), Some(20)])),
+            ],
+        )?;
+
+        let err = SequenceRegions::default().update(&batch).unwrap_err();
+        assert!(err.to_string().contains("GFF features require a start"));
+
+        let err = GFFSerializer::default()
+            .serialize(batch, false)
+            .unwrap_err();
+        assert!(err.to_string().contains("GFF features require a start"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,315 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, fmt::Debug, fs::File, sync::Arc};
+
+use arrow::{
+    array::{Int64Array, RecordBatch, StringArray},
+    error::ArrowError,
+    ipc::reader::FileReader,
+};
+use datafusion::{
+    datasource::{
+        file_format::{file_compression_type::FileCompressionType, write::BatchSerializer},
+        physical_plan::FileSinkConfig,
+    },
+    error::DataFusionError,
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_plan::{
+        common::IPCWriter, insert::DataSink, metrics::MetricsSet, DisplayAs, DisplayFormatType,
+    },
+};
+use futures::StreamExt;
+use noodles::{core::Position, csi::binning_index::index::header};
+use object_store::{path::Path, PutPayload};
+use tokio::io::AsyncWriteExt;
+
+use super::{
+    bgzf_index_writer::{BGZFIndexWriter, IndexedLine},
+    columns_from_batch::get_array_column,
+    gff_serializer::{GFFSerializer, SequenceRegions},
+};
+
+/// A sink that writes GFF3 files, optionally bgzip compressed and tabix indexed.
+///
+/// The `##sequence-region` directives are derived from the data, so the input is spilled to a
+/// temporary file while the regions are collected, and the features are written from it after
+/// the header.
+pub struct GFFSink {
+    file_compression_type: FileCompressionType,
+    file_sink_config: FileSinkConfig,
+    bgzip: bool,
+    tabix: bool,
+}
+
+impl GFFSink {
+    pub fn new(
+        file_sink_config: FileSinkConfig,
+        file_compression_type: FileCompressionType,
+    ) -> Self {
+        Self {
+            file_sink_config,
+            file_compression_type,
+            bgzip: false,
+            tabix: false,
+        }
+    }
+
+    /// Write the output as BGZF blocks instead of using the file compression type.
+    pub fn with_bgzip(mut self, bgzip: bool) -> Self {
+        self.bgzip = bgzip;
+        self
+    }
+
+    /// Write a tabix index next to the output, this requires bgzip output sorted by position.
+    pub fn with_tabix(mut self, tabix: bool) -> Self {
+        self.tabix = tabix;
+        self
+    }
+
+    /// Write the header and features as BGZF blocks, indexing the features if asked to.
+    async fn write_bgzip(
+        &self,
+        header: &str,
+        batches: impl Iterator<Item = Result<RecordBatch, ArrowError>>,
+        context: &Arc<TaskContext>,
+    ) -> Result<u64, DataFusionError> {
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.file_sink_config.object_store_url)?;
+
+        let location = self.file_sink_config.file_groups[0].path();
+
+        let mut buf_writer =
+            object_store::buffered::BufWriter::new(Arc::clone(&object_store), location.clone());
+
+        let serializer = GFFSerializer::default();
+        let mut bgzf_writer =
+            BGZFIndexWriter::new(self.tabix.then(|| header::Builder::gff().build()));
+
+        buf_writer
+            .write_all(&bgzf_writer.compress(header.as_bytes())?)
+            .await?;
+
+        for batch in batches {
+            let batch = batch?;
+            let bytes = serializer.serialize(batch.clone(), false)?;
+
+            let compressed = if bgzf_writer.is_indexed() {
+                let seqnames = get_array_column::<StringArray>(&batch, "seqname")?;
+                let starts = get_array_column::<Int64Array>(&batch, "start")?;
+                let ends = get_array_column::<Int64Array>(&batch, "end")?;
+
+                let lines = bytes
+                    .split_inclusive(|b| *b == b'\n')
+                    .enumerate()
+                    .map(|(i, line)| {
+                        let start = Position::try_from(starts.value(i) as usize)
+                            .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+                        let end = Position::try_from(ends.value(i) as usize)
+                            .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+
+                        Ok(IndexedLine {
+                            line,
+                            reference_sequence_name: seqnames.value(i),
+                            start,
+                            end,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, DataFusionError>>()?;
+
+                bgzf_writer.compress_lines(lines)?
+            } else {
+                bgzf_writer.compress(&bytes)?
+            };
+
+            buf_writer.write_all(&compressed).await?;
+        }
+
+        let compressed_len = bgzf_writer.compressed_len();
+        let (eof, index) = bgzf_writer.finish()?;
+
+        buf_writer.write_all(&eof).await?;
+        buf_writer.shutdown().await?;
+
+        if let Some(index) = index {
+            let index_location = Path::from(format!("{}.tbi", location));
+            object_store
+                .put(&index_location, PutPayload::from(index))
+                .await?;
+        }
+
+        Ok(compressed_len + eof.len() as u64)
+    }
+}
+
+impl Debug for GFFSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GFFSink")
+            .field("bgzip", &self.bgzip)
+            .field("tabix", &self.tabix)
+            .finish()
+    }
+}
+
+impl DisplayAs for GFFSink {
+    fn fmt_as(
+        &self,
+        _display_type: DisplayFormatType,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "GFFSink")
+    }
+}
+
+#[async_trait::async_trait]
+impl DataSink for GFFSink {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        None
+    }
+
+    async fn write_all(
+        &self,
+        mut data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<u64, DataFusionError> {
+        if self.tabix && !self.bgzip {
+            return Err(DataFusionError::Plan(
+                "A tabix index can only be built for bgzip compressed GFF output".to_string(),
+            ));
+        }
+
+        let mut sequence_regions = SequenceRegions::default();
+
+        let spill_file = context
+            .runtime_env()
+            .disk_manager
+            .create_tmp_file("GFF sink")?;
+        let mut spill_writer = IPCWriter::new(spill_file.path(), &data.schema())?;
+
+        while let Some(batch) = data.next().await {
+            let batch = batch?;
+
+            sequence_regions.update(&batch)?;
+            spill_writer.write(&batch)?;
+        }
+
+        spill_writer.finish()?;
+
+        let header = sequence_regions.header();
+        let batches = FileReader::try_new(File::open(spill_file.path())?, None)?;
+
+        if self.bgzip {
+            return self.write_bgzip(&header, batches, context).await;
+        }
+
+        let serializer = GFFSerializer::default();
+
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.file_sink_config.object_store_url)?;
+
+        let partition_file = &self.file_sink_config.file_groups[0];
+        let location = partition_file.path();
+
+        let buf_writer = object_store::buffered::BufWriter::new(object_store, location.clone());
+        let mut buf_writer = self
+            .file_compression_type
+            .convert_async_writer(buf_writer)?;
+
+        buf_writer.write_all(header.as_bytes()).await?;
+        let mut bytes_written = header.len() as u64;
+
+        for batch in batches {
+            let features = serializer.serialize(batch?, false)?;
+
+            buf_writer.write_all(&features).await?;
+            bytes_written += features.len() as u64;
+        }
+
+        buf_writer.shutdown().await?;
+
+        Ok(bytes_written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
+    use datafusion::datasource::listing::PartitionedFile;
+    use datafusion::datasource::physical_plan::FileSinkConfig;
+    use datafusion::execution::object_store::ObjectStoreUrl;
+    use datafusion::logical_expr::dml::InsertOp;
+    use datafusion::physical_plan::insert::DataSink;
+
+    use crate::sinks::GFFSink;
+    use crate::ExonSession;
+
+    #[tokio::test]
+    async fn test_gff_sink() -> Result<(), Box<dyn std::error::Error>> {
+        let ctx = ExonSession::new_exon()?;
+
+        let gff_path = exon_test::test_path("gff", "test.gff");
+
+        let sql = format!(
+            "CREATE EXTERNAL TABLE gff_table STORED AS GFF LOCATION '{}'",
+            gff_path.to_str().unwrap()
+        );
+        ctx.sql(&sql).await?.collect().await?;
+
+        let df = ctx.sql("SELECT * FROM gff_table LIMIT 2").await?;
+
+        let stream = df.execute_stream().await?;
+        let output_schema = Arc::clone(&stream.schema());
+
+        let temp_path = std::env::temp_dir().join("test_gff_sink.gff");
+        let p_file = PartitionedFile::new(temp_path.to_str().unwrap(), 0);
+
+        let file_sink_config = FileSinkConfig {
+            object_store_url: ObjectStoreUrl::local_filesystem(),
+            file_groups: vec![p_file],
+            table_paths: vec![],
+            output_schema,
+            table_partition_cols: vec![],
+            insert_op: InsertOp::Append,
+            keep_partition_by_columns: false,
+        };
+
+        let sink = GFFSink::new(file_sink_config, FileCompressionType::UNCOMPRESSED);
+        sink.write_all(stream, &ctx.session.task_ctx()).await?;
+
+        let contents = std::fs::read_to_string(&temp_path)?;
+        let lines = contents.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines,
+            vec![
+                "##gff-version 3",
+                "##sequence-region sq0 8 13",
+                "sq0\tcaat\tgene\t8\t13\t.\t+\t.\tgene_id=caat1;gene_name=gene0",
+                "sq0\tcaat\tgene\t8\t13\t.\t+\t.\tgene_id=caat1;gene_name=gene0",
+            ]
+        );
+
+        std::fs::remove_file(temp_path)?;
+
+        Ok(())
+    }
+}
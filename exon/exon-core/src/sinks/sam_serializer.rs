@@ -0,0 +1,391 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, AsArray, GenericListArray, Int32Array, Int64Array, RecordBatch, StringArray, StructArray,
+};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Int64Type, Schema};
+use datafusion::error::{DataFusionError, Result};
+use noodles::{
+    core::Position,
+    sam::{
+        self,
+        alignment::{
+            record::{data::field::Tag, Flags, MappingQuality},
+            record_buf::{data::field::Value, Cigar, Data, QualityScores, Sequence},
+            RecordBuf,
+        },
+    },
+};
+
+use super::columns_from_batch::{get_array_column, get_optional_array_column};
+
+/// Converts record batches with the SAM schema into alignment records, checking the references
+/// of each record against the output header.
+pub(crate) struct SAMRecordBuilder<'a> {
+    header: &'a sam::Header,
+}
+
+impl<'a> SAMRecordBuilder<'a> {
+    pub(crate) fn new(header: &'a sam::Header) -> Self {
+        Self { header }
+    }
+
+    pub(crate) fn build_records(&self, batch: &RecordBatch) -> Result<Vec<RecordBuf>> {
+        let batch = &cast_compact_columns(batch)?;
+
+        let names = get_optional_array_column::<StringArray>(batch, "name")?;
+        let flags = get_array_column::<Int32Array>(batch, "flag")?;
+        let references = get_optional_array_column::<StringArray>(batch, "reference")?;
+        let starts = get_optional_array_column::<Int64Array>(batch, "start")?;
+        let mapping_qualities = get_optional_array_column::<StringArray>(batch, "mapping_quality")?;
+        let cigars = get_optional_array_column::<StringArray>(batch, "cigar")?;
+        let mate_references = get_optional_array_column::<StringArray>(batch, "mate_reference")?;
+        let sequences = get_optional_array_column::<StringArray>(batch, "sequence")?;
+        let quality_scores =
+            get_optional_array_column::<GenericListArray<i32>>(batch, "quality_score")?;
+        let tags = get_optional_array_column::<GenericListArray<i32>>(batch, "tags")?;
+
+        let mut records = Vec::with_capacity(batch.num_rows());
+
+        for i in 0..batch.num_rows() {
+            let name = value(names, i);
+
+            let mut builder = RecordBuf::builder().set_flags(Flags::from(flags.value(i) as u16));
+
+            if let Some(name) = name {
+                builder = builder.set_name(name);
+            }
+
+            if let Some(reference) = value(references, i) {
+                builder =
+                    builder.set_reference_sequence_id(self.reference_sequence_id(reference, name)?);
+            }
+
+            if let Some(start) = starts.filter(|starts| starts.is_valid(i)) {
+                let start = usize::try_from(start.value(i))
+                    .ok()
+                    .and_then(Position::new)
+                    .ok_or_else(|| {
+                        DataFusionError::Execution(format!(
+                            "Invalid start {} for record {}",
+                            start.value(i),
+                            name.unwrap_or("*")
+                        ))
+                    })?;
+
+                builder = builder.set_alignment_start(start);
+            }
+
+            if let Some(mapping_quality) = value(mapping_qualities, i) {
+                let mapping_quality = mapping_quality.parse::<u8>().map_err(|_| {
+                    DataFusionError::Execution(format!(
+                        "Invalid mapping quality {} for record {}",
+                        mapping_quality,
+                        name.unwrap_or("*")
+                    ))
+                })?;
+
+                if let Some(mapping_quality) = MappingQuality::new(mapping_quality) {
+                    builder = builder.set_mapping_quality(mapping_quality);
+                }
+            }
+
+            if let Some(cigar) = value(cigars, i).filter(|cigar| *cigar != "*") {
+                let cigar = Cigar::try_from(sam::record::Cigar::new(cigar.as_bytes()))?;
+                builder = builder.set_cigar(cigar);
+            }
+
+            if let Some(mate_reference) = value(mate_references, i) {
+                builder = builder.set_mate_reference_sequence_id(
+                    self.reference_sequence_id(mate_reference, name)?,
+                );
+            }
+
+            if let Some(sequence) = value(sequences, i) {
+                builder = builder.set_sequence(Sequence::from(sequence.as_bytes()));
+            }
+
+            if let Some(quality_scores) = quality_scores.filter(|q| q.is_valid(i)) {
+                let scores = quality_scores.value(i);
+
+                let scores = scores
+                    .as_primitive_opt::<Int64Type>()
+                    .and_then(|scores| {
+                        scores
+                            .values()
+                            .iter()
+                            .map(|score| u8::try_from(*score).ok())
+                            .collect::<Option<Vec<_>>>()
+                    })
+                    .ok_or_else(|| {
+                        DataFusionError::Execution(format!(
+                            "Invalid quality scores for record {}",
+                            name.unwrap_or("*")
+                        ))
+                    })?;
+
+                builder = builder.set_quality_scores(QualityScores::from(scores));
+            }
+
+            if let Some(tags) = tags.filter(|tags| tags.is_valid(i)) {
+                builder = builder.set_data(build_data(tags.value(i).as_struct())?);
+            }
+
+            let record = builder.build();
+            self.validate_alignment_end(&record)?;
+
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    fn reference_sequence_id(&self, reference: &str, name: Option<&str>) -> Result<usize> {
+        self.header
+            .reference_sequences()
+            .get_index_of(reference.as_bytes())
+            .ok_or_else(|| {
+                DataFusionError::Execution(format!(
+                    "Reference sequence {} of record {} is not in the output header",
+                    reference,
+                    name.unwrap_or("*")
+                ))
+            })
+    }
+
+    /// Check the alignment doesn't extend past the end of its reference sequence.
+    fn validate_alignment_end(&self, record: &RecordBuf) -> Result<()> {
+        let (Some(id), Some(end)) = (record.reference_sequence_id(), record.alignment_end()) else {
+            return Ok(());
+        };
+
+        let Some((reference, map)) = self.header.reference_sequences().get_index(id) else {
+            return Ok(());
+        };
+
+        if usize::from(end) > map.length().get() {
+            return Err(DataFusionError::Execution(format!(
+                "Record {} ends at {}, past the end of reference sequence {} of length {}",
+                record
+                    .name()
+                    .map(|n| n.to_string())
+                    .unwrap_or("*".to_string()),
+                end,
+                reference,
+                map.length()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Cast the compact flag, mapping quality, and quality score columns, as read with the
+/// `alignment_compact_types` option, to the default types, so tables read either way can be
+/// written.
+fn cast_compact_columns(batch: &RecordBatch) -> Result<RecordBatch> {
+    let schema = batch.schema();
+
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut columns = Vec::with_capacity(batch.num_columns());
+
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        let data_type = match (field.name().as_str(), field.data_type()) {
+            ("flag", DataType::UInt16) => Some(DataType::Int32),
+            ("mapping_quality", DataType::UInt8) => Some(DataType::Utf8),
+            ("quality_score", DataType::List(item)) if item.data_type() == &DataType::UInt8 => {
+                Some(DataType::List(Arc::new(Field::new(
+                    "item",
+                    DataType::Int64,
+                    true,
+                ))))
+            }
+            _ => None,
+        };
+
+        match data_type {
+            Some(data_type) => {
+                columns.push(cast(column, &data_type)?);
+                fields.push(Field::new(field.name(), data_type, field.is_nullable()));
+            }
+            None => {
+                columns.push(Arc::clone(column));
+                fields.push(field.as_ref().clone());
+            }
+        }
+    }
+
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
+
+fn value(array: Option<&StringArray>, i: usize) -> Option<&str> {
+    array
+        .filter(|array| array.is_valid(i))
+        .map(|array| array.value(i))
+}
+
+/// Build the data fields from the tag and value pairs of the tags column. The values are
+/// written as integers or floats if they parse as one, and as strings otherwise.
+fn build_data(tags: &StructArray) -> Result<Data> {
+    let names = tags
+        .column_by_name("tag")
+        .and_then(|c| c.as_string_opt::<i32>())
+        .ok_or_else(|| DataFusionError::Execution("tags must have a tag field".to_string()))?;
+    let values = tags
+        .column_by_name("value")
+        .and_then(|c| c.as_string_opt::<i32>())
+        .ok_or_else(|| DataFusionError::Execution("tags must have a value field".to_string()))?;
+
+    let mut data = Data::default();
+
+    for i in 0..tags.len() {
+        if values.is_null(i) {
+            continue;
+        }
+
+        let tag = match names.value(i).as_bytes() {
+            [a, b] => Tag::new(*a, *b),
+            _ => {
+                return Err(DataFusionError::Execution(format!(
+                    "Invalid tag {}",
+                    names.value(i)
+                )))
+            }
+        };
+
+        let value = values.value(i);
+        let value = if let Ok(n) = value.parse::<i32>() {
+            Value::from(n)
+        } else if let Ok(n) = value.parse::<f32>() {
+            Value::from(n)
+        } else {
+            Value::from(value)
+        };
+
+        data.insert(tag, value);
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{
+            Int32Array, Int64Array, ListBuilder, RecordBatch, StringArray, UInt16Array, UInt8Array,
+            UInt8Builder,
+        },
+        datatypes::{DataType, Field, Schema},
+    };
+
+    use crate::sinks::alignment_header::parse_header;
+
+    use super::SAMRecordBuilder;
+
+    fn batch(reference: &str) -> Result<RecordBatch, Box<dyn std::error::Error>> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("flag", DataType::Int32, false),
+            Field::new("reference", DataType::Utf8, true),
+            Field::new("start", DataType::Int64, true),
+            Field::new("cigar", DataType::Utf8, false),
+            Field::new("sequence", DataType::Utf8, false),
+        ]));
+
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec!["r0"])),
+                Arc::new(Int32Array::from(vec![0])),
+                Arc::new(StringArray::from(vec![reference])),
+                Arc::new(Int64Array::from(vec![8])),
+                Arc::new(StringArray::from(vec!["4M"])),
+                Arc::new(StringArray::from(vec!["ACGT"])),
+            ],
+        )?;
+
+        Ok(batch)
+    }
+
+    #[test]
+    fn test_build_records_checks_header() -> Result<(), Box<dyn std::error::Error>> {
+        let header = parse_header(b"@SQ\tSN:chr1\tLN:12\n@SQ\tSN:chr2\tLN:10\n")?;
+        let builder = SAMRecordBuilder::new(&header);
+
+        let records = builder.build_records(&batch("chr1")?)?;
+        assert_eq!(records[0].reference_sequence_id(), Some(0));
+        assert_eq!(records[0].alignment_end().map(usize::from), Some(11));
+
+        // The reference isn't in the header.
+        assert!(builder.build_records(&batch("chr3")?).is_err());
+
+        // The alignment ends past the end of the reference.
+        assert!(builder.build_records(&batch("chr2")?).is_err());
+
+        Ok(())
+    }
+    #[test]
+    fn test_build_records_from_compact_types() -> Result<(), Box<dyn std::error::Error>> {
+        let header = parse_header(b"@SQ\tSN:chr1\tLN:12\n")?;
+
+        let mut quality_scores = ListBuilder::new(UInt8Builder::new());
+        quality_scores.values().append_slice(&[30, 31, 32, 33]);
+        quality_scores.append(true);
+
+        let quality_score_list =
+            DataType::List(Arc::new(Field::new("item", DataType::UInt8, true)));
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("flag", DataType::UInt16, false),
+            Field::new("reference", DataType::Utf8, true),
+            Field::new("start", DataType::Int64, true),
+            Field::new("mapping_quality", DataType::UInt8, true),
+            Field::new("cigar", DataType::Utf8, false),
+            Field::new("sequence", DataType::Utf8, false),
+            Field::new("quality_score", quality_score_list, false),
+        ]));
+
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec!["r0"])),
+                Arc::new(UInt16Array::from(vec![16])),
+                Arc::new(StringArray::from(vec!["chr1"])),
+                Arc::new(Int64Array::from(vec![8])),
+                Arc::new(UInt8Array::from(vec![60])),
+                Arc::new(StringArray::from(vec!["4M"])),
+                Arc::new(StringArray::from(vec!["ACGT"])),
+                Arc::new(quality_scores.finish()),
+            ],
+        )?;
+
+        let records = SAMRecordBuilder::new(&header).build_records(&batch)?;
+
+        assert!(records[0].flags().is_reverse_complemented());
+        assert_eq!(records[0].mapping_quality().map(|q| q.get()), Some(60));
+        assert_eq!(records[0].quality_scores().as_ref(), &[30, 31, 32, 33]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, fmt::Debug, fs::File, sync::Arc};
+
+use arrow::ipc::reader::FileReader;
+use datafusion::{
+    datasource::physical_plan::FileSinkConfig,
+    error::DataFusionError,
+    execution::{SendableRecordBatchStream, TaskContext},
+    physical_plan::{
+        common::IPCWriter, insert::DataSink, metrics::MetricsSet, DisplayAs, DisplayFormatType,
+    },
+};
+use futures::StreamExt;
+use noodles::{
+    core::Position,
+    csi::binning_index::index::header,
+    vcf::{
+        self,
+        variant::{io::Write, Record},
+    },
+};
+use object_store::{path::Path, PutPayload};
+use tokio::io::AsyncWriteExt;
+
+use super::{
+    bcf_serializer::{batch_to_records, VariantHeaderValues},
+    bgzf_index_writer::{BGZFIndexWriter, IndexedLine},
+};
+
+/// A sink that writes VCF files from batches with the VCF schema, optionally bgzip compressed
+/// and tabix indexed.
+///
+/// The header is derived like the [`BCFSink`](super::BCFSink) header, so the input is spilled to
+/// a temporary file until it's exhausted, and the records are written from it after the header.
+pub struct VCFSink {
+    file_sink_config: FileSinkConfig,
+    sample_names: Option<Vec<String>>,
+    bgzip: bool,
+    tabix: bool,
+}
+
+impl VCFSink {
+    pub fn new(file_sink_config: FileSinkConfig) -> Self {
+        Self {
+            file_sink_config,
+            sample_names: None,
+            bgzip: false,
+            tabix: false,
+        }
+    }
+
+    /// Name the samples in the header, by default they're named `sample_1`, `sample_2`, etc.
+    pub fn with_sample_names(mut self, sample_names: Vec<String>) -> Self {
+        self.sample_names = Some(sample_names);
+        self
+    }
+
+    /// Write the output as BGZF blocks.
+    pub fn with_bgzip(mut self, bgzip: bool) -> Self {
+        self.bgzip = bgzip;
+        self
+    }
+
+    /// Write a tabix index next to the output, this requires bgzip output sorted by position.
+    pub fn with_tabix(mut self, tabix: bool) -> Self {
+        self.tabix = tabix;
+        self
+    }
+}
+
+impl Debug for VCFSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VCFSink")
+            .field("sample_names", &self.sample_names)
+            .field("bgzip", &self.bgzip)
+            .field("tabix", &self.tabix)
+            .finish()
+    }
+}
+
+impl DisplayAs for VCFSink {
+    fn fmt_as(
+        &self,
+        _display_type: DisplayFormatType,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "VCFSink")
+    }
+}
+
+#[async_trait::async_trait]
+impl DataSink for VCFSink {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        None
+    }
+
+    async fn write_all(
+        &self,
+        mut data: SendableRecordBatchStream,
+        context: &Arc<TaskContext>,
+    ) -> Result<u64, DataFusionError> {
+        if self.tabix && !self.bgzip {
+            return Err(DataFusionError::Plan(
+                "A tabix index can only be built for bgzip compressed VCF output".to_string(),
+            ));
+        }
+
+        let schema = data.schema();
+
+        let mut header_values = VariantHeaderValues::default();
+
+        let spill_file = context
+            .runtime_env()
+            .disk_manager
+            .create_tmp_file("VCF sink")?;
+        let mut spill_writer = IPCWriter::new(spill_file.path(), &schema)?;
+
+        while let Some(batch) = data.next().await {
+            let batch = batch?;
+
+            header_values.update(&batch)?;
+            spill_writer.write(&batch)?;
+        }
+
+        spill_writer.finish()?;
+
+        let header = header_values.header(&schema, self.sample_names.as_deref())?;
+        let batches = FileReader::try_new(File::open(spill_file.path())?, None)?;
+
+        let object_store = context
+            .runtime_env()
+            .object_store(&self.file_sink_config.object_store_url)?;
+
+        let location = self.file_sink_config.file_groups[0].path();
+
+        let mut buf_writer =
+            object_store::buffered::BufWriter::new(Arc::clone(&object_store), location.clone());
+
+        let mut bgzf_writer = self
+            .bgzip
+            .then(|| BGZFIndexWriter::new(self.tabix.then(|| header::Builder::vcf().build())));
+
+        // The header and records are written into the writer's buffer, which is drained after the
+        // header and each batch.
+        let mut writer = vcf::io::Writer::new(Vec::new());
+        writer.write_variant_header(&header)?;
+        let header_bytes = std::mem::take(writer.get_mut());
+
+        let mut total_bytes = match bgzf_writer.as_mut() {
+            Some(bgzf_writer) => {
+                buf_writer
+                    .write_all(&bgzf_writer.compress(&header_bytes)?)
+                    .await?;
+                0
+            }
+            None => {
+                buf_writer.write_all(&header_bytes).await?;
+                header_bytes.len() as u64
+            }
+        };
+
+        for batch in batches {
+            let records = batch_to_records(&batch?, header_values.n_samples())?;
+
+            // The end of each record's line, so the index knows where its line starts.
+            let mut line_ends = Vec::with_capacity(records.len());
+
+            for record in records.iter() {
+                writer.write_variant_record(&header, record)?;
+                line_ends.push(writer.get_ref().len());
+            }
+
+            let bytes = std::mem::take(writer.get_mut());
+
+            let Some(bgzf_writer) = bgzf_writer.as_mut() else {
+                buf_writer.write_all(&bytes).await?;
+                total_bytes += bytes.len() as u64;
+                continue;
+            };
+
+            let line_starts = std::iter::once(0).chain(line_ends.iter().copied());
+
+            let indexed_lines = records
+                .iter()
+                .zip(line_starts.zip(line_ends.iter().copied()))
+                .map(|(record, (line_start, line_end))| {
+                    let start = record.variant_start().unwrap_or(Position::MIN);
+                    let end = Record::variant_end(record, &header)?;
+
+                    Ok(IndexedLine {
+                        line: &bytes[line_start..line_end],
+                        reference_sequence_name: record.reference_sequence_name(),
+                        start,
+                        end,
+                    })
+                })
+                .collect::<Result<Vec<_>, DataFusionError>>()?;
+
+            let compressed = bgzf_writer.compress_lines(indexed_lines)?;
+            buf_writer.write_all(&compressed).await?;
+        }
+
+        let index = match bgzf_writer {
+            Some(bgzf_writer) => {
+                total_bytes = bgzf_writer.compressed_len();
+
+                let (eof, index) = bgzf_writer.finish()?;
+                buf_writer.write_all(&eof).await?;
+                total_bytes += eof.len() as u64;
+
+                index
+            }
+            None => None,
+        };
+
+        buf_writer.shutdown().await?;
+
+        if let Some(index) = index {
+            let index_location = Path::from(format!("{}.tbi", location));
+            object_store
+                .put(&index_location, PutPayload::from(index))
+                .await?;
+        }
+
+        Ok(total_bytes)
+    }
+}
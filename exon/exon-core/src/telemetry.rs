@@ -0,0 +1,81 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Export Exon's tracing spans over OTLP.
+//!
+//! Scans record an `exon.scan_file` span per file with its path, byte range, region, and the
+//! number of batches and rows read, and index lookups record an `exon.resolve_index` span. With
+//! the `otlp` feature these spans can be sent to an OpenTelemetry collector to profile slow
+//! queries end to end.
+
+use opentelemetry::{trace::TracerProvider as _, KeyValue};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{runtime, trace::TracerProvider, Resource};
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::error::{ExonError, Result};
+
+/// The default OTLP gRPC endpoint.
+pub const DEFAULT_OTLP_ENDPOINT: &str = "http://localhost:4317";
+
+/// An OTLP span exporter. Pending spans are flushed when it is dropped.
+#[derive(Debug)]
+pub struct OtlpTracing {
+    provider: TracerProvider,
+}
+
+impl OtlpTracing {
+    /// Create an exporter that sends spans to the OTLP gRPC endpoint, tagged with the service name.
+    ///
+    /// This must be called from within a tokio runtime.
+    pub fn try_new(endpoint: &str, service_name: &str) -> Result<Self> {
+        let exporter = SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(|e| {
+                ExonError::Configuration(format!(
+                    "failed to create OTLP exporter for {endpoint}: {e}"
+                ))
+            })?;
+
+        let provider = TracerProvider::builder()
+            .with_batch_exporter(exporter, runtime::Tokio)
+            .with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                service_name.to_string(),
+            )]))
+            .build();
+
+        Ok(Self { provider })
+    }
+
+    /// A tracing layer that exports spans through this exporter.
+    pub fn layer<S>(&self) -> OpenTelemetryLayer<S, opentelemetry_sdk::trace::Tracer>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer().with_tracer(self.provider.tracer("exon"))
+    }
+}
+
+impl Drop for OtlpTracing {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            tracing::warn!("Failed to flush OTLP spans: {}", e);
+        }
+    }
+}
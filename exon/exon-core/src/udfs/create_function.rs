@@ -0,0 +1,55 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Helpers for the UDFs created from `CREATE FUNCTION` statements.
+
+use bytes::Bytes;
+use datafusion::{
+    datasource::listing::ListingTableUrl,
+    error::{DataFusionError, Result},
+    execution::context::SessionState,
+    logical_expr::{CreateFunction, Expr},
+    scalar::ScalarValue,
+};
+
+use crate::ExonRuntimeEnvExt;
+
+/// The string body of a `CREATE FUNCTION ... AS '...'` statement, e.g. a path or a pattern.
+pub(crate) fn function_body(statement: &CreateFunction) -> Option<&str> {
+    match &statement.params.function_body {
+        Some(Expr::Literal(ScalarValue::Utf8(Some(body)))) => Some(body),
+        _ => None,
+    }
+}
+
+/// Read the file at a path, e.g. the body of a `CREATE FUNCTION` statement, from its object store.
+pub(crate) async fn read_function_bytes(state: &SessionState, path: &str) -> Result<Bytes> {
+    let url = ListingTableUrl::parse(path)?;
+    state
+        .runtime_env()
+        .exon_register_object_store_url(url.as_ref())
+        .await?;
+
+    let object_store = state.runtime_env().object_store(url.object_store())?;
+    let bytes = object_store.get(url.prefix()).await?.bytes().await?;
+
+    Ok(bytes)
+}
+
+/// Read the UTF-8 file at a path from its object store.
+pub(crate) async fn read_function_file(state: &SessionState, path: &str) -> Result<String> {
+    let bytes = read_function_bytes(state, path).await?;
+
+    String::from_utf8(bytes.to_vec())
+        .map_err(|e| DataFusionError::Execution(format!("Invalid file {}: {}", path, e)))
+}
@@ -0,0 +1,281 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+
+use arrow::{
+    array::{Array, AsArray, RecordBatch, StringArray},
+    datatypes::{DataType, SchemaRef},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+    scalar::ScalarValue,
+};
+
+/// Normalize the column names of a gene ID cross-reference file, so the common Ensembl, RefSeq,
+/// HGNC, Entrez, and symbol columns can be referred to by those short names.
+///
+/// For example, the HGNC download's "Ensembl gene ID" column is `ensembl` and "Approved symbol"
+/// is `symbol`. Other columns are lowercased with non-alphanumeric characters replaced by `_`,
+/// and repeated names get a numeric suffix.
+pub(crate) fn normalize_gene_id_columns<'a>(names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+
+    for name in names {
+        let lower = name.trim().to_lowercase();
+
+        let mut column = if lower.contains("ensembl") || lower == "gene_id" {
+            "ensembl".to_string()
+        } else if lower.contains("refseq") {
+            "refseq".to_string()
+        } else if lower.contains("symbol") || lower == "gene_name" {
+            "symbol".to_string()
+        } else if lower.contains("hgnc") {
+            "hgnc".to_string()
+        } else if lower.contains("entrez") || lower.contains("ncbi") {
+            "entrez".to_string()
+        } else {
+            lower
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect()
+        };
+
+        let base = column.clone();
+        let mut n = 2;
+        while normalized.contains(&column) {
+            column = format!("{}_{}", base, n);
+            n += 1;
+        }
+
+        normalized.push(column);
+    }
+
+    normalized
+}
+
+/// Strip the version suffix of an ID, e.g. `ENSG00000223972.5` to `ENSG00000223972`.
+fn strip_version(id: &str) -> &str {
+    match id.rsplit_once('.') {
+        Some((base, version)) if version.chars().all(|c| c.is_ascii_digit()) => base,
+        _ => id,
+    }
+}
+
+/// An in-memory gene ID cross-reference table, indexed by every column.
+pub(crate) struct GeneIdMapping {
+    columns: Vec<String>,
+    rows: Vec<Vec<Option<String>>>,
+
+    /// For each column, the first row of each ID. Cells with several comma separated IDs are
+    /// indexed under each of them, and versioned IDs also under their unversioned ID.
+    indexes: Vec<HashMap<String, usize>>,
+}
+
+impl Debug for GeneIdMapping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeneIdMapping")
+            .field("columns", &self.columns)
+            .field("n_rows", &self.rows.len())
+            .finish()
+    }
+}
+
+impl GeneIdMapping {
+    /// Build a mapping from batches of string columns.
+    pub(crate) fn try_new(schema: &SchemaRef, batches: &[RecordBatch]) -> Result<Self> {
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|f| f.name().to_string())
+            .collect::<Vec<_>>();
+
+        let mut rows = Vec::new();
+        let mut indexes = vec![HashMap::new(); columns.len()];
+
+        for batch in batches {
+            let arrays = batch
+                .columns()
+                .iter()
+                .map(|array| {
+                    array.as_string_opt::<i32>().ok_or_else(|| {
+                        DataFusionError::Execution(
+                            "gene ID mapping columns must be strings".to_string(),
+                        )
+                    })
+                })
+                .collect::<Result<Vec<&StringArray>>>()?;
+
+            for i in 0..batch.num_rows() {
+                let row_index = rows.len();
+                let mut row = Vec::with_capacity(arrays.len());
+
+                for (array, index) in arrays.iter().zip(indexes.iter_mut()) {
+                    if array.is_null(i) || array.value(i).trim().is_empty() {
+                        row.push(None);
+                        continue;
+                    }
+
+                    let value = array.value(i);
+
+                    for id in value.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                        index.entry(id.to_string()).or_insert(row_index);
+                        index
+                            .entry(strip_version(id).to_string())
+                            .or_insert(row_index);
+                    }
+
+                    row.push(Some(value.to_string()));
+                }
+
+                rows.push(row);
+            }
+        }
+
+        Ok(Self {
+            columns,
+            rows,
+            indexes,
+        })
+    }
+
+    fn column_index(&self, name: &str) -> Result<usize> {
+        self.columns.iter().position(|c| c == name).ok_or_else(|| {
+            DataFusionError::Execution(format!(
+                "Unknown gene ID column {}, the mapping has columns {}",
+                name,
+                self.columns.join(", ")
+            ))
+        })
+    }
+
+    /// Parse a direction like `ensembl->symbol` into the indexes of its columns.
+    fn direction(&self, direction: &str) -> Result<(usize, usize)> {
+        let (from, to) = direction.split_once("->").ok_or_else(|| {
+            DataFusionError::Execution(format!(
+                "Invalid gene ID mapping {}, expected e.g. 'ensembl->symbol'",
+                direction
+            ))
+        })?;
+
+        Ok((
+            self.column_index(from.trim())?,
+            self.column_index(to.trim())?,
+        ))
+    }
+
+    /// Map an ID from one column to another, trying the unversioned ID if there's no exact match.
+    fn map(&self, from: usize, to: usize, id: &str) -> Option<&str> {
+        let index = &self.indexes[from];
+
+        let row = index.get(id).or_else(|| index.get(strip_version(id)))?;
+
+        self.rows[*row][to].as_deref()
+    }
+}
+
+/// A UDF that maps gene IDs between the columns of a registered gene ID mapping, e.g.
+/// `map_gene_id(gene_id, 'ensembl->symbol')`. IDs that aren't in the mapping map to null.
+#[derive(Debug)]
+pub(crate) struct MapGeneId {
+    signature: Signature,
+    mapping: Arc<GeneIdMapping>,
+}
+
+impl MapGeneId {
+    pub(crate) fn new(mapping: Arc<GeneIdMapping>) -> Self {
+        let signature = Signature::exact(vec![DataType::Utf8, DataType::Utf8], Volatility::Stable);
+
+        Self { signature, mapping }
+    }
+}
+
+impl ScalarUDFImpl for MapGeneId {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "map_gene_id"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Utf8)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let Some(ColumnarValue::Scalar(ScalarValue::Utf8(Some(direction)))) = args.get(1) else {
+            return Err(DataFusionError::Execution(
+                "map_gene_id requires the mapping, e.g. 'ensembl->symbol', as its second argument"
+                    .to_string(),
+            ));
+        };
+
+        let (from, to) = self.mapping.direction(direction)?;
+
+        let ids = ColumnarValue::values_to_arrays(&args[..1])?;
+        let ids = ids[0].as_string::<i32>();
+
+        let mapped = ids
+            .iter()
+            .map(|id| id.and_then(|id| self.mapping.map(from, to, id)))
+            .collect::<StringArray>();
+
+        Ok(ColumnarValue::Array(Arc::new(mapped)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_gene_id_columns, strip_version};
+
+    #[test]
+    fn test_normalize_gene_id_columns() {
+        let names = [
+            "HGNC ID",
+            "Approved symbol",
+            "Ensembl gene ID",
+            "RefSeq IDs",
+            "NCBI Gene ID",
+            "Locus type",
+            "Ensembl ID(supplied by Ensembl)",
+        ];
+
+        assert_eq!(
+            normalize_gene_id_columns(names.into_iter()),
+            vec![
+                "hgnc",
+                "symbol",
+                "ensembl",
+                "refseq",
+                "entrez",
+                "locus_type",
+                "ensembl_2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strip_version() {
+        assert_eq!(strip_version("ENSG00000223972.5"), "ENSG00000223972");
+        assert_eq!(strip_version("ENSG00000223972"), "ENSG00000223972");
+        assert_eq!(strip_version("NM_000014.6"), "NM_000014");
+        assert_eq!(strip_version("A.B"), "A.B");
+    }
+}
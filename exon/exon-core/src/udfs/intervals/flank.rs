@@ -0,0 +1,97 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use arrow::datatypes::DataType;
+use datafusion::{
+    error::Result,
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::{
+    check_interval, check_non_negative, int64_arguments, interval_list_array, interval_list_type,
+    value,
+};
+
+/// Returns the flanks of an interval, `left` bases upstream and `right` bases downstream.
+///
+/// The upstream flank is clipped at the start of the chromosome and empty flanks are left out,
+/// e.g. `flank(0, 10, 5, 5)` is `[{start: 10, end: 15}]`.
+#[derive(Debug)]
+pub(crate) struct Flank {
+    signature: Signature,
+}
+
+impl Default for Flank {
+    fn default() -> Self {
+        let signature = Signature::uniform(4, vec![DataType::Int64], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for Flank {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "flank"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(interval_list_type())
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arrays = int64_arguments(self.name(), args, 4)?;
+        let (starts, ends, lefts, rights) = (&arrays[0], &arrays[1], &arrays[2], &arrays[3]);
+
+        let mut rows = Vec::with_capacity(starts.len());
+
+        for i in 0..starts.len() {
+            let (Some(start), Some(end), Some(left), Some(right)) = (
+                value(starts, i),
+                value(ends, i),
+                value(lefts, i),
+                value(rights, i),
+            ) else {
+                rows.push(None);
+                continue;
+            };
+
+            check_interval(self.name(), start, end)?;
+            check_non_negative(self.name(), "left", left)?;
+            check_non_negative(self.name(), "right", right)?;
+
+            let mut flanks = Vec::with_capacity(2);
+
+            let left_start = start.saturating_sub(left).max(0);
+            if left_start < start {
+                flanks.push((left_start, start));
+            }
+
+            if right > 0 {
+                flanks.push((end, end.saturating_add(right)));
+            }
+
+            rows.push(Some(flanks));
+        }
+
+        Ok(ColumnarValue::Array(interval_list_array(rows)?))
+    }
+}
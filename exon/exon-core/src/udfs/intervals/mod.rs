@@ -0,0 +1,154 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! UDFs for building intervals around features, e.g. promoters and tiling windows.
+//!
+//! Intervals are returned as structs with `start` and `end` fields. `flank`, `slop`, and
+//! `windows` use 0-based, half-open coordinates like BED, while the strand-aware `tss`, `tes`,
+//! and `upstream` use 1-based, closed coordinates like GFF and GTF.
+
+mod flank;
+mod slop;
+mod strand;
+mod windows;
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, Int64Array, ListArray, StructArray},
+    buffer::{NullBuffer, OffsetBuffer},
+    compute::cast,
+    datatypes::{DataType, Field, Fields, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::context::SessionContext,
+    logical_expr::{ColumnarValue, ScalarUDF},
+};
+
+/// Register the interval UDFs.
+pub fn register_udfs(ctx: &SessionContext) {
+    ctx.register_udf(ScalarUDF::from(flank::Flank::default()));
+    ctx.register_udf(ScalarUDF::from(slop::Slop::default()));
+    ctx.register_udf(ScalarUDF::from(windows::Windows::default()));
+
+    ctx.register_udf(ScalarUDF::from(strand::Tss::default()));
+    ctx.register_udf(ScalarUDF::from(strand::Tes::default()));
+    ctx.register_udf(ScalarUDF::from(strand::Upstream::default()));
+    ctx.register_udf(ScalarUDF::from(strand::StrandSymbol::default()));
+}
+
+fn interval_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("start", DataType::Int64, false),
+        Field::new("end", DataType::Int64, false),
+    ])
+}
+
+/// The struct type of a single interval.
+fn interval_type() -> DataType {
+    DataType::Struct(interval_fields())
+}
+
+/// The list type of a list of intervals.
+fn interval_list_type() -> DataType {
+    DataType::List(Arc::new(Field::new("item", interval_type(), true)))
+}
+
+/// Convert the arguments of an interval UDF to Int64 arrays of the same length.
+fn int64_arguments(name: &str, args: &[ColumnarValue], n: usize) -> Result<Vec<Int64Array>> {
+    if args.len() != n {
+        return Err(DataFusionError::Execution(format!(
+            "{} takes {} arguments",
+            name, n
+        )));
+    }
+
+    ColumnarValue::values_to_arrays(args)?
+        .iter()
+        .map(|array| {
+            Ok(cast(array, &DataType::Int64)?
+                .as_primitive::<Int64Type>()
+                .clone())
+        })
+        .collect()
+}
+
+/// The value of row `i`, or `None` if it is null.
+fn value(array: &Int64Array, i: usize) -> Option<i64> {
+    array.is_valid(i).then(|| array.value(i))
+}
+
+/// Check that an interval is well formed, i.e. `0 <= start <= end`.
+fn check_interval(name: &str, start: i64, end: i64) -> Result<()> {
+    if start < 0 || end < start {
+        return Err(DataFusionError::Execution(format!(
+            "{} requires 0 <= start <= end, got start {} and end {}",
+            name, start, end
+        )));
+    }
+
+    Ok(())
+}
+
+/// Check that a length argument isn't negative.
+fn check_non_negative(name: &str, argument: &str, value: i64) -> Result<()> {
+    if value < 0 {
+        return Err(DataFusionError::Execution(format!(
+            "{} requires a non-negative {}, got {}",
+            name, argument, value
+        )));
+    }
+
+    Ok(())
+}
+
+fn struct_array(intervals: &[(i64, i64)], nulls: Option<NullBuffer>) -> Result<StructArray> {
+    let starts = Int64Array::from_iter_values(intervals.iter().map(|(start, _)| *start));
+    let ends = Int64Array::from_iter_values(intervals.iter().map(|(_, end)| *end));
+
+    Ok(StructArray::try_new(
+        interval_fields(),
+        vec![Arc::new(starts), Arc::new(ends)],
+        nulls,
+    )?)
+}
+
+/// Build a struct array with an interval per row, null rows are `None`.
+fn interval_array(rows: Vec<Option<(i64, i64)>>) -> Result<ArrayRef> {
+    let nulls = NullBuffer::from(rows.iter().map(Option::is_some).collect::<Vec<_>>());
+    let intervals = rows
+        .into_iter()
+        .map(|row| row.unwrap_or_default())
+        .collect::<Vec<_>>();
+
+    Ok(Arc::new(struct_array(&intervals, Some(nulls))?))
+}
+
+/// Build a list array with a list of intervals per row, null rows are `None`.
+fn interval_list_array(rows: Vec<Option<Vec<(i64, i64)>>>) -> Result<ArrayRef> {
+    let offsets =
+        OffsetBuffer::from_lengths(rows.iter().map(|row| row.as_ref().map_or(0, Vec::len)));
+    let nulls = NullBuffer::from(rows.iter().map(Option::is_some).collect::<Vec<_>>());
+
+    let intervals = rows.into_iter().flatten().flatten().collect::<Vec<_>>();
+    let values = struct_array(&intervals, None)?;
+
+    Ok(Arc::new(ListArray::try_new(
+        Arc::new(Field::new("item", interval_type(), true)),
+        offsets,
+        Arc::new(values),
+        Some(nulls),
+    )?))
+}
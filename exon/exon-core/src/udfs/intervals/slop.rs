@@ -0,0 +1,91 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use arrow::datatypes::DataType;
+use datafusion::{
+    error::Result,
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::{
+    check_interval, check_non_negative, int64_arguments, interval_array, interval_type, value,
+};
+
+/// Returns an interval extended by `amount` bases on both sides.
+///
+/// The result is clipped to the chromosome, `[0, chrom_length)`, a null `chrom_length` only clips
+/// the start, e.g. `slop(5, 10, 10, 15)` is `{start: 0, end: 15}`.
+#[derive(Debug)]
+pub(crate) struct Slop {
+    signature: Signature,
+}
+
+impl Default for Slop {
+    fn default() -> Self {
+        let signature = Signature::uniform(4, vec![DataType::Int64], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for Slop {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "slop"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(interval_type())
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arrays = int64_arguments(self.name(), args, 4)?;
+        let (starts, ends, amounts, chrom_lengths) =
+            (&arrays[0], &arrays[1], &arrays[2], &arrays[3]);
+
+        let mut rows = Vec::with_capacity(starts.len());
+
+        for i in 0..starts.len() {
+            let (Some(start), Some(end), Some(amount)) =
+                (value(starts, i), value(ends, i), value(amounts, i))
+            else {
+                rows.push(None);
+                continue;
+            };
+
+            check_interval(self.name(), start, end)?;
+            check_non_negative(self.name(), "amount", amount)?;
+
+            let mut slop_end = end.saturating_add(amount);
+
+            if let Some(chrom_length) = value(chrom_lengths, i) {
+                check_non_negative(self.name(), "chrom_length", chrom_length)?;
+                slop_end = slop_end.min(chrom_length);
+            }
+
+            let slop_start = start.saturating_sub(amount).max(0).min(slop_end);
+
+            rows.push(Some((slop_start, slop_end)));
+        }
+
+        Ok(ColumnarValue::Array(interval_array(rows)?))
+    }
+}
@@ -0,0 +1,304 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Strand-aware coordinates of features in 1-based, closed GFF/GTF coordinates.
+//!
+//! A `-` strand feature starts at its end, any other strand (`+`, `.`, or `?`) is treated as the
+//! forward strand. Int8 strand codes, see the `strand_encoding` option, are cast to strings, so a
+//! `-1` strand is the reverse strand too.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, AsArray, Int64Array, StringArray},
+    compute::cast,
+    datatypes::{DataType, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+use exon_common::strands_as_utf8;
+
+use super::{check_non_negative, interval_array, interval_type, value};
+
+/// The start, end, and strand columns of a feature, with an optional trailing length column.
+struct StrandedArguments {
+    starts: Int64Array,
+    ends: Int64Array,
+    strands: StringArray,
+    lengths: Option<Int64Array>,
+}
+
+impl StrandedArguments {
+    fn try_new(name: &str, args: &[ColumnarValue], n: usize) -> Result<Self> {
+        if args.len() != n {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes {} arguments",
+                name, n
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let int64 = |i: usize| -> Result<Int64Array> {
+            Ok(cast(&arrays[i], &DataType::Int64)?
+                .as_primitive::<Int64Type>()
+                .clone())
+        };
+
+        Ok(Self {
+            starts: int64(0)?,
+            ends: int64(1)?,
+            strands: cast(&arrays[2], &DataType::Utf8)?
+                .as_string::<i32>()
+                .clone(),
+            lengths: if n > 3 { Some(int64(3)?) } else { None },
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// The start, end, and whether the feature is on the reverse strand for row `i`.
+    fn feature(&self, name: &str, i: usize) -> Result<Option<(i64, i64, bool)>> {
+        let (Some(start), Some(end)) = (value(&self.starts, i), value(&self.ends, i)) else {
+            return Ok(None);
+        };
+
+        if self.strands.is_null(i) {
+            return Ok(None);
+        }
+
+        if start < 1 || end < start {
+            return Err(DataFusionError::Execution(format!(
+                "{} requires 1 <= start <= end, got start {} and end {}",
+                name, start, end
+            )));
+        }
+
+        let reverse = matches!(self.strands.value(i), "-" | "-1");
+
+        Ok(Some((start, end, reverse)))
+    }
+}
+
+fn stranded_signature(n: usize) -> Signature {
+    let mut types = vec![DataType::Int64, DataType::Int64, DataType::Utf8];
+    if n > 3 {
+        types.push(DataType::Int64);
+    }
+
+    Signature::coercible(types, Volatility::Immutable)
+}
+
+/// Returns the transcription start site of a feature, its start on the forward strand and its
+/// end on the reverse strand.
+#[derive(Debug)]
+pub(crate) struct Tss {
+    signature: Signature,
+}
+
+impl Default for Tss {
+    fn default() -> Self {
+        Self {
+            signature: stranded_signature(3),
+        }
+    }
+}
+
+impl ScalarUDFImpl for Tss {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "tss"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Int64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arguments = StrandedArguments::try_new(self.name(), args, 3)?;
+
+        let tss = (0..arguments.len())
+            .map(|i| {
+                let feature = arguments.feature(self.name(), i)?;
+                Ok(feature.map(|(start, end, reverse)| if reverse { end } else { start }))
+            })
+            .collect::<Result<Int64Array>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(tss)))
+    }
+}
+
+/// Returns the transcription end site of a feature, its end on the forward strand and its start
+/// on the reverse strand.
+#[derive(Debug)]
+pub(crate) struct Tes {
+    signature: Signature,
+}
+
+impl Default for Tes {
+    fn default() -> Self {
+        Self {
+            signature: stranded_signature(3),
+        }
+    }
+}
+
+impl ScalarUDFImpl for Tes {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "tes"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Int64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arguments = StrandedArguments::try_new(self.name(), args, 3)?;
+
+        let tes = (0..arguments.len())
+            .map(|i| {
+                let feature = arguments.feature(self.name(), i)?;
+                Ok(feature.map(|(start, end, reverse)| if reverse { start } else { end }))
+            })
+            .collect::<Result<Int64Array>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(tes)))
+    }
+}
+
+/// Returns the `n` bases upstream of a feature's TSS as a `{start, end}` struct.
+///
+/// The region is clipped at position 1, and is null if nothing is left, e.g. for a `+` strand
+/// feature starting at 1. A promoter is `upstream(start, end, strand, 1000)`.
+#[derive(Debug)]
+pub(crate) struct Upstream {
+    signature: Signature,
+}
+
+impl Default for Upstream {
+    fn default() -> Self {
+        Self {
+            signature: stranded_signature(4),
+        }
+    }
+}
+
+impl ScalarUDFImpl for Upstream {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "upstream"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(interval_type())
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arguments = StrandedArguments::try_new(self.name(), args, 4)?;
+        let lengths = arguments.lengths.as_ref().ok_or_else(|| {
+            DataFusionError::Internal("upstream requires a length argument".to_string())
+        })?;
+
+        let mut rows = Vec::with_capacity(arguments.len());
+
+        for i in 0..arguments.len() {
+            let (Some((start, end, reverse)), Some(n)) =
+                (arguments.feature(self.name(), i)?, value(lengths, i))
+            else {
+                rows.push(None);
+                continue;
+            };
+
+            check_non_negative(self.name(), "n", n)?;
+
+            let upstream = if reverse {
+                (end.saturating_add(1), end.saturating_add(n))
+            } else {
+                (start.saturating_sub(n).max(1), start - 1)
+            };
+
+            rows.push((upstream.0 <= upstream.1).then_some(upstream));
+        }
+
+        Ok(ColumnarValue::Array(interval_array(rows)?))
+    }
+}
+
+/// Returns the `+`, `-`, or `?` symbol of a strand column in any `strand_encoding`, e.g. to
+/// render Int8 strand codes.
+#[derive(Debug)]
+pub(crate) struct StrandSymbol {
+    signature: Signature,
+}
+
+impl Default for StrandSymbol {
+    fn default() -> Self {
+        Self {
+            signature: Signature::any(1, Volatility::Immutable),
+        }
+    }
+}
+
+impl ScalarUDFImpl for StrandSymbol {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "strand_symbol"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Utf8)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+        let strands = arrays.first().ok_or_else(|| {
+            DataFusionError::Execution("strand_symbol takes 1 argument".to_string())
+        })?;
+
+        Ok(ColumnarValue::Array(strands_as_utf8(strands)?))
+    }
+}
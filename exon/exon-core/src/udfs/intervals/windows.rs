@@ -0,0 +1,96 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use arrow::datatypes::DataType;
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::{check_interval, int64_arguments, interval_list_array, interval_list_type, value};
+
+/// Returns the windows of `size` bases tiling an interval, starting every `step` bases.
+///
+/// Windows are clipped to the end of the interval, so the last windows may be shorter, e.g.
+/// `windows(0, 25, 10, 10)` is `[{start: 0, end: 10}, {start: 10, end: 20},
+/// {start: 20, end: 25}]`.
+#[derive(Debug)]
+pub(crate) struct Windows {
+    signature: Signature,
+}
+
+impl Default for Windows {
+    fn default() -> Self {
+        let signature = Signature::uniform(4, vec![DataType::Int64], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for Windows {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "windows"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(interval_list_type())
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arrays = int64_arguments(self.name(), args, 4)?;
+        let (starts, ends, sizes, steps) = (&arrays[0], &arrays[1], &arrays[2], &arrays[3]);
+
+        let mut rows = Vec::with_capacity(starts.len());
+
+        for i in 0..starts.len() {
+            let (Some(start), Some(end), Some(size), Some(step)) = (
+                value(starts, i),
+                value(ends, i),
+                value(sizes, i),
+                value(steps, i),
+            ) else {
+                rows.push(None);
+                continue;
+            };
+
+            check_interval(self.name(), start, end)?;
+
+            if size <= 0 || step <= 0 {
+                return Err(DataFusionError::Execution(format!(
+                    "{} requires a positive size and step, got size {} and step {}",
+                    self.name(),
+                    size,
+                    step
+                )));
+            }
+
+            let windows = (start..end)
+                .step_by(step as usize)
+                .map(|window_start| (window_start, window_start.saturating_add(size).min(end)))
+                .collect();
+
+            rows.push(Some(windows));
+        }
+
+        Ok(ColumnarValue::Array(interval_list_array(rows)?))
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Parses UCSC chain files, which align the sequences of one genome build to another.
+//!
+//! Each chain starts with a header line
+//!
+//! ```text
+//! chain score tName tSize tStrand tStart tEnd qName qSize qStrand qStart qEnd id
+//! ```
+//!
+//! followed by lines of `size dt dq`, an ungapped block of `size` bases and the gaps after it in
+//! the source (`t`) and target (`q`) builds, and a last line of only `size`.
+
+use std::collections::HashMap;
+
+use datafusion::error::{DataFusionError, Result};
+
+/// An ungapped block of a chain, in 0-based coordinates.
+#[derive(Debug, Clone)]
+struct ChainBlock {
+    source_start: u64,
+    size: u64,
+    /// The index of the target sequence name.
+    target: usize,
+    target_start: u64,
+    target_size: u64,
+    /// Whether the block aligns to the reverse strand of the target, where `target_start` counts
+    /// from the end of the target sequence.
+    reverse: bool,
+}
+
+/// The blocks of a chain file by source sequence, to lift positions from one build to another.
+#[derive(Debug, Default)]
+pub struct LiftoverChain {
+    blocks: HashMap<String, Vec<ChainBlock>>,
+    targets: Vec<String>,
+}
+
+fn parse_field<T: std::str::FromStr>(
+    fields: &[&str],
+    index: usize,
+    line_number: usize,
+) -> Result<T> {
+    fields
+        .get(index)
+        .and_then(|field| field.parse().ok())
+        .ok_or_else(|| {
+            DataFusionError::Execution(format!(
+                "Invalid chain file line {}, field {} is missing or invalid",
+                line_number,
+                index + 1
+            ))
+        })
+}
+
+impl LiftoverChain {
+    /// Parse the chains of a chain file.
+    ///
+    /// Where the chains overlap in the source build, the block that starts first is kept.
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut chain = Self::default();
+        let mut target_indexes = HashMap::new();
+
+        // The source sequence, the next source and target offsets, and the target of the chain
+        // being read.
+        let mut current: Option<(String, u64, u64, ChainBlock)> = None;
+
+        for (i, line) in contents.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+
+            if fields[0] == "chain" {
+                if current.is_some() {
+                    return Err(DataFusionError::Execution(format!(
+                        "Invalid chain file line {}, the previous chain has no last block",
+                        line_number
+                    )));
+                }
+
+                let source = parse_field::<String>(&fields, 2, line_number)?;
+                let source_start = parse_field::<u64>(&fields, 5, line_number)?;
+                let target_name = parse_field::<String>(&fields, 7, line_number)?;
+                let target_size = parse_field::<u64>(&fields, 8, line_number)?;
+                let target_strand = parse_field::<String>(&fields, 9, line_number)?;
+                let target_start = parse_field::<u64>(&fields, 10, line_number)?;
+
+                let next_index = chain.targets.len();
+                let target = *target_indexes
+                    .entry(target_name.clone())
+                    .or_insert_with(|| {
+                        chain.targets.push(target_name);
+                        next_index
+                    });
+
+                let block = ChainBlock {
+                    source_start: 0,
+                    size: 0,
+                    target,
+                    target_start: 0,
+                    target_size,
+                    reverse: target_strand == "-",
+                };
+
+                current = Some((source, source_start, target_start, block));
+                continue;
+            }
+
+            let Some((source, source_offset, target_offset, block)) = current.as_mut() else {
+                return Err(DataFusionError::Execution(format!(
+                    "Invalid chain file line {}, expected a chain header",
+                    line_number
+                )));
+            };
+
+            let size = parse_field::<u64>(&fields, 0, line_number)?;
+
+            chain
+                .blocks
+                .entry(source.clone())
+                .or_default()
+                .push(ChainBlock {
+                    source_start: *source_offset,
+                    size,
+                    target_start: *target_offset,
+                    ..block.clone()
+                });
+
+            if fields.len() == 1 {
+                current = None;
+                continue;
+            }
+
+            let source_gap = parse_field::<u64>(&fields, 1, line_number)?;
+            let target_gap = parse_field::<u64>(&fields, 2, line_number)?;
+
+            *source_offset += size + source_gap;
+            *target_offset += size + target_gap;
+        }
+
+        if current.is_some() {
+            return Err(DataFusionError::Execution(
+                "Invalid chain file, the last chain has no last block".to_string(),
+            ));
+        }
+
+        for blocks in chain.blocks.values_mut() {
+            blocks.sort_by_key(|block| block.source_start);
+
+            let mut end = 0;
+            blocks.retain(|block| {
+                let keep = block.source_start >= end;
+                if keep {
+                    end = block.source_start + block.size;
+                }
+                keep
+            });
+        }
+
+        Ok(chain)
+    }
+
+    /// The target sequence and 1-based position of a 1-based position in the source build, or
+    /// `None` if the position isn't in a block of the chain.
+    pub fn lift(&self, sequence: &str, position: i64) -> Option<(&str, i64)> {
+        let offset = u64::try_from(position).ok()?.checked_sub(1)?;
+        let blocks = self.blocks.get(sequence)?;
+
+        let index = blocks.partition_point(|block| block.source_start <= offset);
+        let block = blocks.get(index.checked_sub(1)?)?;
+
+        if offset >= block.source_start + block.size {
+            return None;
+        }
+
+        let mut target_offset = block.target_start + (offset - block.source_start);
+        if block.reverse {
+            target_offset = block.target_size.checked_sub(target_offset + 1)?;
+        }
+
+        let target_position = i64::try_from(target_offset).ok()? + 1;
+
+        Some((self.targets[block.target].as_str(), target_position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LiftoverChain;
+
+    const CHAIN: &str = "\
+chain 1000 chr1 1000 + 100 130 chr1 2000 + 500 535 1
+10 5 10
+15
+
+chain 500 chr2 1000 + 0 10 chr7 100 - 20 30 2
+10
+";
+
+    #[test]
+    fn test_lift() -> Result<(), Box<dyn std::error::Error>> {
+        let chain = LiftoverChain::parse(CHAIN)?;
+
+        // The first block maps 101-110 to 501-510.
+        assert_eq!(chain.lift("chr1", 101), Some(("chr1", 501)));
+        assert_eq!(chain.lift("chr1", 110), Some(("chr1", 510)));
+
+        // 111-115 are a gap in the source, and the second block starts after a 10 base target gap.
+        assert_eq!(chain.lift("chr1", 111), None);
+        assert_eq!(chain.lift("chr1", 116), Some(("chr1", 521)));
+        assert_eq!(chain.lift("chr1", 130), Some(("chr1", 535)));
+        assert_eq!(chain.lift("chr1", 131), None);
+        assert_eq!(chain.lift("chr1", 100), None);
+
+        // The reverse strand block maps 1-10 to 80-71 of the 100 base chr7.
+        assert_eq!(chain.lift("chr2", 1), Some(("chr7", 80)));
+        assert_eq!(chain.lift("chr2", 10), Some(("chr7", 71)));
+
+        assert_eq!(chain.lift("chr3", 1), None);
+        assert_eq!(chain.lift("chr1", 0), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid_chain() {
+        assert!(LiftoverChain::parse("10 5 10\n").is_err());
+        assert!(LiftoverChain::parse("chain 1000 chr1 1000 +\n10\n").is_err());
+        assert!(LiftoverChain::parse("chain 1000 chr1 1000 + 0 10 chr1 1000 + 0 10 1\n").is_err());
+    }
+}
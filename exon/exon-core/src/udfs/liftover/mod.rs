@@ -0,0 +1,371 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! UDFs that lift coordinates from one genome build to another with a chain file.
+//!
+//! Chains are registered for a pair of builds with `ExonSession::register_liftover`. When
+//! `exon.genome_build_liftover` is set, joins of tables on those builds lift the coordinate
+//! columns of one side to the build of the other, and `exon.liftover_unmapped` sets what happens
+//! to the positions that aren't in the chain.
+
+mod chain;
+
+use std::{
+    collections::HashMap,
+    io::Read,
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, Int64Array, StringArray},
+    compute::cast,
+    datatypes::{DataType, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::context::SessionState,
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, TypeSignature, Volatility},
+};
+use exon_common::canonical_genome_build;
+
+pub use self::chain::LiftoverChain;
+
+use super::create_function::read_function_bytes;
+
+/// What a liftover does with the positions that aren't in its chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiftoverUnmapped {
+    /// Drop the rows with unmapped positions.
+    Drop,
+    /// Keep the rows with null coordinates.
+    Null,
+    /// Fail the query.
+    Error,
+}
+
+impl FromStr for LiftoverUnmapped {
+    type Err = DataFusionError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "drop" => Ok(Self::Drop),
+            "null" => Ok(Self::Null),
+            "error" => Ok(Self::Error),
+            _ => Err(DataFusionError::Configuration(format!(
+                "invalid liftover unmapped policy: {s}, expected drop, null, or error"
+            ))),
+        }
+    }
+}
+
+/// The chains registered with a session, keyed by the builds they lift from and to.
+#[derive(Debug, Default)]
+pub struct LiftoverRegistry {
+    chains: RwLock<HashMap<(String, String), Arc<LiftoverChain>>>,
+}
+
+impl LiftoverRegistry {
+    /// Register the chain from one build to another, replacing any existing chain for them.
+    pub fn register(&self, from_build: &str, to_build: &str, chain: Arc<LiftoverChain>) {
+        let mut chains = self.chains.write().expect("registry lock poisoned");
+        chains.insert(
+            (
+                canonical_genome_build(from_build),
+                canonical_genome_build(to_build),
+            ),
+            chain,
+        );
+    }
+
+    /// Get the chain from one build to another.
+    pub fn get(&self, from_build: &str, to_build: &str) -> Option<Arc<LiftoverChain>> {
+        let chains = self.chains.read().expect("registry lock poisoned");
+        chains
+            .get(&(from_build.to_string(), to_build.to_string()))
+            .cloned()
+    }
+}
+
+/// Read a chain file, which may be gzipped, from its object store.
+pub(crate) async fn read_liftover_chain(state: &SessionState, path: &str) -> Result<LiftoverChain> {
+    let bytes = read_function_bytes(state, path).await?;
+
+    let mut contents = String::new();
+    let read = if path.ends_with(".gz") {
+        flate2::read::MultiGzDecoder::new(bytes.as_ref()).read_to_string(&mut contents)
+    } else {
+        bytes.as_ref().read_to_string(&mut contents)
+    };
+
+    read.map_err(|e| DataFusionError::Execution(format!("Invalid chain file {}: {}", path, e)))?;
+
+    LiftoverChain::parse(&contents)
+}
+
+/// The coordinate a liftover UDF returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LiftoverOutput {
+    /// The target sequence name.
+    Sequence,
+    /// The target position.
+    Position,
+}
+
+/// Lifts a sequence name and 1-based position with a chain, returning the target sequence name or
+/// position.
+///
+/// An optional third argument is the position the sequence name is lifted from, e.g. the start of
+/// an interval when lifting its end, and the position is unmapped if that lifts to another target
+/// sequence.
+#[derive(Debug)]
+pub(crate) struct LiftoverUdf {
+    signature: Signature,
+    chain: Arc<LiftoverChain>,
+    from_build: String,
+    to_build: String,
+    output: LiftoverOutput,
+    unmapped: LiftoverUnmapped,
+}
+
+impl LiftoverUdf {
+    pub(crate) fn new(
+        chain: Arc<LiftoverChain>,
+        from_build: &str,
+        to_build: &str,
+        output: LiftoverOutput,
+        unmapped: LiftoverUnmapped,
+    ) -> Self {
+        let signature = Signature::one_of(
+            vec![TypeSignature::Any(2), TypeSignature::Any(3)],
+            Volatility::Immutable,
+        );
+
+        Self {
+            signature,
+            chain,
+            from_build: from_build.to_string(),
+            to_build: to_build.to_string(),
+            output,
+            unmapped,
+        }
+    }
+
+    /// The build the UDF lifts to.
+    pub(crate) fn to_build(&self) -> &str {
+        &self.to_build
+    }
+}
+
+impl ScalarUDFImpl for LiftoverUdf {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        match self.output {
+            LiftoverOutput::Sequence => "liftover_sequence",
+            LiftoverOutput::Position => "liftover_position",
+        }
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        match self.output {
+            LiftoverOutput::Sequence => Ok(DataType::Utf8),
+            LiftoverOutput::Position => Ok(DataType::Int64),
+        }
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 2 && args.len() != 3 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes a sequence name, a position, and an optional anchor position",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = cast(&arrays[0], &DataType::Utf8)?;
+        let sequences = sequences.as_string::<i32>();
+
+        let positions = cast(&arrays[1], &DataType::Int64)?;
+        let positions = positions.as_primitive::<Int64Type>();
+
+        let anchors = arrays
+            .get(2)
+            .map(|anchors| cast(anchors, &DataType::Int64))
+            .transpose()?;
+        let anchors = anchors
+            .as_ref()
+            .map(|anchors| anchors.as_primitive::<Int64Type>());
+
+        let lifted = (0..sequences.len())
+            .map(|i| {
+                if sequences.is_null(i) || positions.is_null(i) {
+                    return Ok(None);
+                }
+
+                let sequence = sequences.value(i);
+                let position = positions.value(i);
+
+                let lifted = self.chain.lift(sequence, position).filter(|(target, _)| {
+                    anchors
+                        .filter(|anchors| anchors.is_valid(i))
+                        .is_none_or(|anchors| {
+                            self.chain
+                                .lift(sequence, anchors.value(i))
+                                .is_some_and(|(anchor_target, _)| anchor_target == *target)
+                        })
+                });
+
+                if lifted.is_none() && self.unmapped == LiftoverUnmapped::Error {
+                    return Err(DataFusionError::Execution(format!(
+                        "Can't lift {}:{} from {} to {}, set exon.liftover_unmapped to drop or null to skip it",
+                        sequence, position, self.from_build, self.to_build
+                    )));
+                }
+
+                Ok(lifted)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let array: ArrayRef = match self.output {
+            LiftoverOutput::Sequence => Arc::new(
+                lifted
+                    .iter()
+                    .map(|lifted| lifted.map(|(target, _)| target))
+                    .collect::<StringArray>(),
+            ),
+            LiftoverOutput::Position => Arc::new(
+                lifted
+                    .iter()
+                    .map(|lifted| lifted.map(|(_, position)| position))
+                    .collect::<Int64Array>(),
+            ),
+        };
+
+        Ok(ColumnarValue::Array(array))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Array, AsArray, Int64Array, StringArray};
+    use datafusion::logical_expr::{ColumnarValue, ScalarUDFImpl};
+
+    use super::{LiftoverChain, LiftoverOutput, LiftoverUdf, LiftoverUnmapped};
+
+    const CHAIN: &str = "\
+chain 1000 chr1 1000 + 0 20 chr1 1000 + 100 120 1
+20
+
+chain 500 chr1 1000 + 20 30 chr2 1000 + 0 10 2
+10
+";
+
+    fn invoke(
+        output: LiftoverOutput,
+        unmapped: LiftoverUnmapped,
+        args: Vec<Arc<dyn Array>>,
+    ) -> datafusion::error::Result<Arc<dyn Array>> {
+        let chain = Arc::new(LiftoverChain::parse(CHAIN)?);
+        let udf = LiftoverUdf::new(chain, "GRCh37", "GRCh38", output, unmapped);
+
+        let args = args
+            .into_iter()
+            .map(ColumnarValue::Array)
+            .collect::<Vec<_>>();
+
+        match udf.invoke(&args)? {
+            ColumnarValue::Array(array) => Ok(array),
+            ColumnarValue::Scalar(scalar) => scalar.to_array(),
+        }
+    }
+
+    #[test]
+    fn test_liftover_udf() -> Result<(), Box<dyn std::error::Error>> {
+        let sequences = Arc::new(StringArray::from(vec![
+            Some("chr1"),
+            Some("chr1"),
+            Some("chr1"),
+            None,
+        ]));
+        let positions = Arc::new(Int64Array::from(vec![Some(5), Some(25), Some(50), Some(5)]));
+
+        let lifted = invoke(
+            LiftoverOutput::Sequence,
+            LiftoverUnmapped::Null,
+            vec![sequences.clone(), positions.clone()],
+        )?;
+        let lifted = lifted.as_string::<i32>();
+        assert_eq!(
+            lifted.iter().collect::<Vec<_>>(),
+            vec![Some("chr1"), Some("chr2"), None, None]
+        );
+
+        let lifted = invoke(
+            LiftoverOutput::Position,
+            LiftoverUnmapped::Null,
+            vec![sequences.clone(), positions.clone()],
+        )?;
+        let lifted = lifted.as_primitive::<arrow::datatypes::Int64Type>();
+        assert_eq!(
+            lifted.iter().collect::<Vec<_>>(),
+            vec![Some(105), Some(5), None, None]
+        );
+
+        // An end on another target sequence than its start is unmapped.
+        let anchors = Arc::new(Int64Array::from(vec![Some(1), Some(1), Some(1), Some(1)]));
+        let lifted = invoke(
+            LiftoverOutput::Position,
+            LiftoverUnmapped::Null,
+            vec![sequences.clone(), positions.clone(), anchors],
+        )?;
+        let lifted = lifted.as_primitive::<arrow::datatypes::Int64Type>();
+        assert_eq!(
+            lifted.iter().collect::<Vec<_>>(),
+            vec![Some(105), None, None, None]
+        );
+
+        let err = invoke(
+            LiftoverOutput::Position,
+            LiftoverUnmapped::Error,
+            vec![sequences, positions],
+        )
+        .err()
+        .ok_or("expected an unmapped position error")?;
+        assert!(err.to_string().contains("Can't lift chr1:50"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_unmapped_policy() {
+        assert_eq!(
+            "Drop".parse::<LiftoverUnmapped>().ok(),
+            Some(LiftoverUnmapped::Drop)
+        );
+        assert_eq!(
+            "null".parse::<LiftoverUnmapped>().ok(),
+            Some(LiftoverUnmapped::Null)
+        );
+        assert!("skip".parse::<LiftoverUnmapped>().is_err());
+    }
+}
@@ -0,0 +1,350 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Lookup UDFs, created from a delimited file of keys and values with e.g.
+//!
+//! ```sql
+//! CREATE FUNCTION gene_name(VARCHAR) RETURNS VARCHAR LANGUAGE LOOKUP AS 's3://bucket/gene_names.tsv';
+//! ```
+//!
+//! The file is read once when the function is created. Keys are the first column and values the
+//! second, cast to the `RETURNS` type, or kept as strings without one. Keys that aren't in the file
+//! and empty values are null.
+//!
+//! Arguments after the key set how the file is read, from their `DEFAULT` values:
+//!
+//! - `key_column` and `value_column` are the one-based columns of the keys and values.
+//! - `has_header` skips the first line of the file, false by default.
+//! - `delimiter` separates the columns, a tab by default.
+
+use std::collections::HashMap;
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, StringArray, UInt32Array},
+    compute::{cast, cast_with_options, take, CastOptions},
+    datatypes::DataType,
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::context::SessionState,
+    logical_expr::{
+        ColumnarValue, CreateFunction, Expr, OperateFunctionArg, ScalarUDF, ScalarUDFImpl,
+        Signature, Volatility,
+    },
+    scalar::ScalarValue,
+};
+
+use super::create_function::{function_body, read_function_file};
+
+/// How a lookup UDF reads its file.
+#[derive(Debug, Clone, PartialEq)]
+struct LookupParameters {
+    key_column: usize,
+    value_column: usize,
+    has_header: bool,
+    delimiter: char,
+}
+
+impl Default for LookupParameters {
+    fn default() -> Self {
+        Self {
+            key_column: 1,
+            value_column: 2,
+            has_header: false,
+            delimiter: '\t',
+        }
+    }
+}
+
+impl LookupParameters {
+    /// The parameters from the defaults of the arguments after the key of a lookup UDF.
+    fn try_from_args(name: &str, args: &[OperateFunctionArg]) -> Result<Self> {
+        let error = |message: String| {
+            DataFusionError::Plan(format!("Lookup function {} {}", name, message))
+        };
+
+        let mut parameters = Self::default();
+
+        for arg in args {
+            let Some(parameter) = &arg.name else {
+                return Err(error("needs names for the arguments after the key".into()));
+            };
+            let parameter = parameter.value.to_ascii_lowercase();
+
+            let Some(Expr::Literal(value)) = &arg.default_expr else {
+                return Err(error(format!("needs a literal DEFAULT for {}", parameter)));
+            };
+
+            let column = || match value.cast_to(&DataType::Int64) {
+                Ok(ScalarValue::Int64(Some(column))) if column > 0 => Ok(column as usize),
+                _ => Err(error(format!(
+                    "needs a positive column number for {}, got {}",
+                    parameter, value
+                ))),
+            };
+
+            match parameter.as_str() {
+                "key_column" => parameters.key_column = column()?,
+                "value_column" => parameters.value_column = column()?,
+                "has_header" => {
+                    let ScalarValue::Boolean(Some(has_header)) = value else {
+                        return Err(error(format!(
+                            "needs a boolean for has_header, got {}",
+                            value
+                        )));
+                    };
+
+                    parameters.has_header = *has_header;
+                }
+                "delimiter" => {
+                    let delimiter = match value {
+                        ScalarValue::Utf8(Some(delimiter)) => {
+                            let mut chars = delimiter.chars();
+                            chars.next().filter(|_| chars.next().is_none())
+                        }
+                        _ => None,
+                    };
+
+                    parameters.delimiter = delimiter.ok_or_else(|| {
+                        error(format!(
+                            "needs a single character delimiter, got {}",
+                            value
+                        ))
+                    })?;
+                }
+                _ => {
+                    return Err(error(format!(
+                        "has an unknown parameter {}, expected key_column, value_column, has_header, or delimiter",
+                        parameter
+                    )))
+                }
+            }
+        }
+
+        Ok(parameters)
+    }
+}
+
+/// The keys of a lookup file and their values.
+#[derive(Debug)]
+struct LookupTable {
+    /// The index of the value of each key.
+    keys: HashMap<String, u32>,
+    values: ArrayRef,
+}
+
+impl LookupTable {
+    /// Parse the keys and values of a lookup file, casting the values to a data type.
+    fn try_new(
+        contents: &str,
+        parameters: &LookupParameters,
+        data_type: &DataType,
+    ) -> Result<Self> {
+        let mut keys = HashMap::new();
+        let mut values = Vec::new();
+
+        let lines = contents
+            .lines()
+            .enumerate()
+            .skip(usize::from(parameters.has_header))
+            .filter(|(_, line)| !line.trim().is_empty());
+
+        for (i, line) in lines {
+            let fields = line.split(parameters.delimiter).collect::<Vec<_>>();
+
+            let (Some(key), Some(value)) = (
+                fields.get(parameters.key_column - 1),
+                fields.get(parameters.value_column - 1),
+            ) else {
+                return Err(DataFusionError::Execution(format!(
+                    "Line {} of the lookup file has {} columns, expected at least {}",
+                    i + 1,
+                    fields.len(),
+                    parameters.key_column.max(parameters.value_column)
+                )));
+            };
+
+            let index = u32::try_from(values.len()).map_err(|_| {
+                DataFusionError::Execution("Lookup file has too many keys".to_string())
+            })?;
+
+            if keys.insert(key.to_string(), index).is_some() {
+                return Err(DataFusionError::Execution(format!(
+                    "Line {} of the lookup file repeats the key {}",
+                    i + 1,
+                    key
+                )));
+            }
+
+            values.push(Some(*value).filter(|value| !value.is_empty()));
+        }
+
+        let values = StringArray::from(values);
+        let options = CastOptions {
+            safe: false,
+            ..Default::default()
+        };
+
+        let values = cast_with_options(&values, data_type, &options).map_err(|e| {
+            DataFusionError::Execution(format!(
+                "Invalid {} values in the lookup file: {}",
+                data_type, e
+            ))
+        })?;
+
+        Ok(Self { keys, values })
+    }
+
+    /// The values of keys, null for keys not in the table.
+    fn lookup(&self, keys: &StringArray) -> Result<ArrayRef> {
+        let indices = keys
+            .iter()
+            .map(|key| key.and_then(|key| self.keys.get(key).copied()))
+            .collect::<UInt32Array>();
+
+        Ok(take(&self.values, &indices, None)?)
+    }
+}
+
+/// Returns the value of a key in a lookup file, a UDF created with `CREATE FUNCTION ... LANGUAGE
+/// LOOKUP`.
+#[derive(Debug)]
+pub(crate) struct LookupUdf {
+    name: String,
+    signature: Signature,
+    table: LookupTable,
+}
+
+impl ScalarUDFImpl for LookupUdf {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(self.table.values.data_type().clone())
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 1 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes one argument",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let keys = cast(&arrays[0], &DataType::Utf8)?;
+        let values = self.table.lookup(keys.as_string::<i32>())?;
+
+        Ok(ColumnarValue::Array(values))
+    }
+}
+
+/// Create a lookup UDF from a `CREATE FUNCTION ... LANGUAGE LOOKUP AS 'path'` statement.
+pub(crate) async fn create_lookup_udf(
+    state: &SessionState,
+    statement: &CreateFunction,
+) -> Result<ScalarUDF> {
+    let name = &statement.name;
+
+    let Some(path) = function_body(statement) else {
+        return Err(DataFusionError::Plan(format!(
+            "Lookup function {} needs the path to a lookup file, e.g. AS 'gene_names.tsv'",
+            name
+        )));
+    };
+
+    let args = statement.args.as_deref().unwrap_or_default();
+    let Some((key, args)) = args.split_first() else {
+        return Err(DataFusionError::Plan(format!(
+            "Lookup function {} takes a key as its first argument",
+            name
+        )));
+    };
+
+    if key.default_expr.is_some() {
+        return Err(DataFusionError::Plan(format!(
+            "Lookup function {} can't have a default key",
+            name
+        )));
+    }
+
+    let parameters = LookupParameters::try_from_args(name, args)?;
+    let data_type = statement.return_type.clone().unwrap_or(DataType::Utf8);
+
+    let contents = read_function_file(state, path).await?;
+    let table = LookupTable::try_new(&contents, &parameters, &data_type)?;
+
+    Ok(ScalarUDF::from(LookupUdf {
+        name: name.clone(),
+        signature: Signature::any(1, Volatility::Immutable),
+        table,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{AsArray, StringArray},
+        datatypes::{DataType, Int64Type},
+    };
+
+    use super::{LookupParameters, LookupTable};
+
+    #[test]
+    fn test_lookup_table() -> Result<(), Box<dyn std::error::Error>> {
+        let parameters = LookupParameters {
+            value_column: 3,
+            has_header: true,
+            delimiter: ',',
+            ..Default::default()
+        };
+
+        let table = LookupTable::try_new(
+            "gene,name,length\nENSG1,TP53,393\n\nENSG2,BRCA1,\n",
+            &parameters,
+            &DataType::Int64,
+        )?;
+
+        let keys = StringArray::from(vec![Some("ENSG2"), Some("ENSG1"), Some("ENSG3"), None]);
+        let values = table.lookup(&keys)?;
+
+        assert_eq!(
+            values
+                .as_primitive::<Int64Type>()
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![None, Some(393), None, None]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookup_table_errors() {
+        let parameters = LookupParameters::default();
+
+        assert!(LookupTable::try_new("a\t1\na\t2\n", &parameters, &DataType::Utf8).is_err());
+        assert!(LookupTable::try_new("a\t1\nb\n", &parameters, &DataType::Utf8).is_err());
+        assert!(LookupTable::try_new("a\tone\n", &parameters, &DataType::Int64).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use arrow::{
+    array::{Array, AsArray},
+    datatypes::{DataType, Float64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::peaks::{
+    float64_list_data_type, peak_list_array, peak_list_data_type, spectrum_peaks, Peak,
+};
+
+/// Sum the intensities of the peaks in each m/z bin, where the bins start at 0.
+fn bin_peaks(peaks: &[Peak], bin_width: f64) -> Vec<Peak> {
+    let mut bins = BTreeMap::new();
+
+    for (mz, intensity) in peaks {
+        let bin = (mz / bin_width).floor() as i64;
+        *bins.entry(bin).or_insert(0.0) += intensity;
+    }
+
+    bins.into_iter()
+        .map(|(bin, intensity)| (bin as f64 * bin_width, intensity))
+        .collect()
+}
+
+/// Bins a spectrum by m/z, e.g. `bin_spectrum(mz.mz, intensity.intensity, 1.0)`.
+///
+/// Returns the non-empty bins as a list of structs with the start m/z of the bin and the summed
+/// intensity, ordered by m/z. Unlike `bin_vectors`, the bins aren't limited to a m/z range.
+#[derive(Debug)]
+pub(crate) struct BinSpectrum {
+    signature: Signature,
+}
+
+impl Default for BinSpectrum {
+    fn default() -> Self {
+        let signature = Signature::exact(
+            vec![
+                float64_list_data_type(),
+                float64_list_data_type(),
+                DataType::Float64,
+            ],
+            Volatility::Immutable,
+        );
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for BinSpectrum {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "bin_spectrum"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(peak_list_data_type())
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let [mz_arrays, intensity_arrays, bin_widths] = arrays.as_slice() else {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes an m/z array, an intensity array, and a bin width",
+                self.name()
+            )));
+        };
+
+        let spectra = spectrum_peaks(self.name(), mz_arrays, intensity_arrays)?;
+        let bin_widths = bin_widths.as_primitive::<Float64Type>();
+
+        let binned = spectra
+            .into_iter()
+            .enumerate()
+            .map(|(i, peaks)| {
+                let Some(peaks) = peaks else {
+                    return Ok(None);
+                };
+
+                if bin_widths.is_null(i) || bin_widths.value(i) <= 0.0 {
+                    return Err(DataFusionError::Execution(format!(
+                        "{} takes a positive bin width",
+                        self.name()
+                    )));
+                }
+
+                Ok(Some(bin_peaks(&peaks, bin_widths.value(i))))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(peak_list_array(binned)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::bin_peaks;
+
+    #[test]
+    fn test_bin_peaks() {
+        let peaks = [(100.1, 1.0), (100.4, 2.0), (101.2, 3.0), (99.9, 4.0)];
+
+        assert_eq!(
+            bin_peaks(&peaks, 1.0),
+            vec![(99.0, 4.0), (100.0, 3.0), (101.0, 3.0)]
+        );
+        assert_eq!(bin_peaks(&peaks, 10.0), vec![(90.0, 4.0), (100.0, 6.0)]);
+        assert!(bin_peaks(&[], 1.0).is_empty());
+    }
+}
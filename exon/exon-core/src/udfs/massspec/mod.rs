@@ -12,8 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod bin_spectrum;
 mod bin_vectors;
 mod contains_peak;
+mod peaks;
+mod top_n_peaks;
 
 use datafusion::{execution::context::SessionContext, logical_expr::ScalarUDF};
 
@@ -30,4 +33,14 @@ pub fn register_udfs(ctx: &SessionContext) {
     let bin_vectors_udf = ScalarUDF::from(bin_vectors_udf_impl);
 
     ctx.register_udf(bin_vectors_udf);
+
+    let bin_spectrum_udf_impl = bin_spectrum::BinSpectrum::default();
+    let bin_spectrum_udf = ScalarUDF::from(bin_spectrum_udf_impl);
+
+    ctx.register_udf(bin_spectrum_udf);
+
+    let top_n_peaks_udf_impl = top_n_peaks::TopNPeaks::default();
+    let top_n_peaks_udf = ScalarUDF::from(top_n_peaks_udf_impl);
+
+    ctx.register_udf(top_n_peaks_udf);
 }
@@ -0,0 +1,113 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, Float64Array, ListArray, StructArray},
+    buffer::{NullBuffer, OffsetBuffer},
+    datatypes::{DataType, Field, Fields, Float64Type},
+};
+use datafusion::error::{DataFusionError, Result};
+
+/// A peak of a spectrum as its m/z and intensity.
+pub(super) type Peak = (f64, f64);
+
+fn peak_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("mz", DataType::Float64, false),
+        Field::new("intensity", DataType::Float64, false),
+    ])
+}
+
+/// The type of a list of peaks, i.e. `List<Struct<mz, intensity>>`.
+pub(super) fn peak_list_data_type() -> DataType {
+    DataType::List(Arc::new(Field::new(
+        "item",
+        DataType::Struct(peak_fields()),
+        true,
+    )))
+}
+
+/// The type of the m/z and intensity arrays of a spectrum.
+pub(super) fn float64_list_data_type() -> DataType {
+    DataType::List(Arc::new(Field::new("item", DataType::Float64, true)))
+}
+
+/// Get the peaks of each spectrum from its m/z and intensity arrays, skipping null values, or
+/// `None` if either array is null.
+pub(super) fn spectrum_peaks(
+    name: &str,
+    mz_arrays: &ArrayRef,
+    intensity_arrays: &ArrayRef,
+) -> Result<Vec<Option<Vec<Peak>>>> {
+    let mz_arrays = mz_arrays.as_list::<i32>();
+    let intensity_arrays = intensity_arrays.as_list::<i32>();
+
+    mz_arrays
+        .iter()
+        .zip(intensity_arrays.iter())
+        .map(|(mz, intensity)| {
+            let (Some(mz), Some(intensity)) = (mz, intensity) else {
+                return Ok(None);
+            };
+
+            if mz.len() != intensity.len() {
+                return Err(DataFusionError::Execution(format!(
+                    "{} takes m/z and intensity arrays of the same length",
+                    name
+                )));
+            }
+
+            let peaks = mz
+                .as_primitive::<Float64Type>()
+                .iter()
+                .zip(intensity.as_primitive::<Float64Type>().iter())
+                .filter_map(|(mz, intensity)| Some((mz?, intensity?)))
+                .collect();
+
+            Ok(Some(peaks))
+        })
+        .collect()
+}
+
+/// Build a list of peaks for each spectrum.
+pub(super) fn peak_list_array(spectra: Vec<Option<Vec<Peak>>>) -> Result<ListArray> {
+    let nulls = NullBuffer::from_iter(spectra.iter().map(Option::is_some));
+
+    let lengths = spectra
+        .iter()
+        .map(|peaks| peaks.as_ref().map_or(0, Vec::len));
+    let offsets = OffsetBuffer::<i32>::from_lengths(lengths);
+
+    let (mz, intensity): (Vec<f64>, Vec<f64>) = spectra.into_iter().flatten().flatten().unzip();
+
+    let peaks = StructArray::try_new(
+        peak_fields(),
+        vec![
+            Arc::new(Float64Array::from(mz)),
+            Arc::new(Float64Array::from(intensity)),
+        ],
+        None,
+    )?;
+
+    let field = Arc::new(Field::new("item", DataType::Struct(peak_fields()), true));
+
+    Ok(ListArray::try_new(
+        field,
+        offsets,
+        Arc::new(peaks),
+        Some(nulls),
+    )?)
+}
@@ -0,0 +1,134 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, AsArray},
+    datatypes::{DataType, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::peaks::{
+    float64_list_data_type, peak_list_array, peak_list_data_type, spectrum_peaks, Peak,
+};
+
+/// The `n` most intense peaks, ordered by decreasing intensity and then by m/z.
+fn top_peaks(mut peaks: Vec<Peak>, n: usize) -> Vec<Peak> {
+    peaks.sort_by(|(mz_a, intensity_a), (mz_b, intensity_b)| {
+        intensity_b
+            .total_cmp(intensity_a)
+            .then(mz_a.total_cmp(mz_b))
+    });
+    peaks.truncate(n);
+
+    peaks
+}
+
+/// Picks the most intense peaks of a spectrum, e.g. `top_n_peaks(mz.mz, intensity.intensity, 10)`.
+///
+/// Returns a list of structs with the m/z and intensity of the peaks, ordered by decreasing
+/// intensity.
+#[derive(Debug)]
+pub(crate) struct TopNPeaks {
+    signature: Signature,
+}
+
+impl Default for TopNPeaks {
+    fn default() -> Self {
+        let signature = Signature::exact(
+            vec![
+                float64_list_data_type(),
+                float64_list_data_type(),
+                DataType::Int64,
+            ],
+            Volatility::Immutable,
+        );
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for TopNPeaks {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "top_n_peaks"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(peak_list_data_type())
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let [mz_arrays, intensity_arrays, ns] = arrays.as_slice() else {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes an m/z array, an intensity array, and a number of peaks",
+                self.name()
+            )));
+        };
+
+        let spectra = spectrum_peaks(self.name(), mz_arrays, intensity_arrays)?;
+        let ns = ns.as_primitive::<Int64Type>();
+
+        let top = spectra
+            .into_iter()
+            .enumerate()
+            .map(|(i, peaks)| {
+                let Some(peaks) = peaks else {
+                    return Ok(None);
+                };
+
+                let n = (!ns.is_null(i))
+                    .then(|| usize::try_from(ns.value(i)).ok())
+                    .flatten()
+                    .ok_or_else(|| {
+                        DataFusionError::Execution(format!(
+                            "{} takes a non-negative number of peaks",
+                            self.name()
+                        ))
+                    })?;
+
+                Ok(Some(top_peaks(peaks, n)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(peak_list_array(top)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::top_peaks;
+
+    #[test]
+    fn test_top_peaks() {
+        let peaks = vec![(100.0, 1.0), (101.0, 5.0), (102.0, 3.0), (99.0, 5.0)];
+
+        assert_eq!(top_peaks(peaks.clone(), 2), vec![(99.0, 5.0), (101.0, 5.0)]);
+        assert_eq!(top_peaks(peaks.clone(), 10).len(), 4);
+        assert!(top_peaks(peaks, 0).is_empty());
+    }
+}
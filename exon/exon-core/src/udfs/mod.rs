@@ -20,6 +20,9 @@ pub mod sequence;
 /// UDFs for Mass Spectrometry.
 pub mod massspec;
 
+/// UDFs for protein sequences.
+pub mod protein;
+
 /// UDFs for VCF files.
 pub mod vcf;
 
@@ -29,5 +32,39 @@ pub mod sam;
 /// UDFs for GFF files.
 pub mod gff;
 
+/// UDFs for genomic intervals.
+pub mod intervals;
+
+/// UDFs for the sequence context of positions in an indexed reference FASTA.
+pub mod reference;
+
+/// UDFs that lift coordinates across genome builds with chain files.
+pub mod liftover;
+
+/// UDFs that screen reads for contamination against minimizer sketches of references.
+pub mod sketch;
+
+/// UDFs that look up taxa in an NCBI taxonomy.
+pub mod taxonomy;
+
+/// UDFs that normalize gene expression counts, e.g. to CPM or TPM.
+pub mod normalization;
+
+/// UDFs for position-specific scoring matrices, created with `CREATE FUNCTION`.
+pub(crate) mod pssm;
+
+/// UDFs for lookup files, created with `CREATE FUNCTION`.
+pub(crate) mod lookup;
+
+/// UDFs for the named groups of regular expressions, created with `CREATE FUNCTION`.
+pub(crate) mod regex_extract;
+
+/// UDFs for the functions exported by WebAssembly modules, created with `CREATE FUNCTION`.
+#[cfg(feature = "wasm")]
+pub(crate) mod wasm;
+
+mod create_function;
+
 mod bigwig_region_filter;
+pub(crate) mod gene_id;
 pub use bigwig_region_filter::register_bigwig_region_filter_udf;
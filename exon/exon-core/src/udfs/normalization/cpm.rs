@@ -0,0 +1,94 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::{
+    array::{AsArray, Float64Array},
+    datatypes::{DataType, Float64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+/// The counts per million of a count in a library of a size, or `None` for an empty library.
+pub(super) fn cpm(count: f64, library_size: f64) -> Option<f64> {
+    (library_size > 0.0).then(|| count / library_size * 1e6)
+}
+
+/// Scales a count to counts per million of its library, e.g. with the library size from
+/// `SUM(count) OVER (PARTITION BY sample)`.
+#[derive(Debug)]
+pub(crate) struct Cpm {
+    signature: Signature,
+}
+
+impl Default for Cpm {
+    fn default() -> Self {
+        let signature = Signature::uniform(2, vec![DataType::Float64], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for Cpm {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "cpm"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 2 {
+            return Err(DataFusionError::Execution(
+                "cpm takes a count and a library size".to_string(),
+            ));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+        let counts = arrays[0].as_primitive::<Float64Type>();
+        let library_sizes = arrays[1].as_primitive::<Float64Type>();
+
+        let cpms = counts
+            .iter()
+            .zip(library_sizes.iter())
+            .map(|(count, library_size)| cpm(count?, library_size?))
+            .collect::<Float64Array>();
+
+        Ok(ColumnarValue::Array(Arc::new(cpms)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::cpm;
+
+    #[test]
+    fn test_cpm() {
+        assert_eq!(cpm(5.0, 2_000_000.0), Some(2.5));
+        assert_eq!(cpm(0.0, 100.0), Some(0.0));
+        assert_eq!(cpm(5.0, 0.0), None);
+    }
+}
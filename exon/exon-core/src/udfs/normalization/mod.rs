@@ -0,0 +1,50 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! UDFs that normalize gene expression counts for the depth of each sample, e.g. the counts of a
+//! `COUNTS` table or the reads of a `SALMON_QUANT` table.
+//!
+//! `cpm` scales a count to counts per million of its library, and `tpm` is a window function that
+//! scales the counts of a partition to transcripts per million with their effective lengths.
+//! `size_factor` is an aggregate for the median-of-ratios size factors of DESeq2, which can also be
+//! used as a window function to divide each count by the size factor of its sample.
+//!
+//! ```sql
+//! SELECT gene_id, sample, cpm(count, SUM(count) OVER (PARTITION BY sample)) AS cpm
+//! FROM counts;
+//! ```
+
+mod cpm;
+mod size_factor;
+mod tpm;
+
+use datafusion::{
+    execution::context::SessionContext,
+    logical_expr::{AggregateUDF, ScalarUDF, WindowUDF},
+};
+
+/// Register the normalization UDFs.
+pub fn register_udfs(ctx: &SessionContext) {
+    let cpm = cpm::Cpm::default();
+    let cpm_udf = ScalarUDF::from(cpm);
+    ctx.register_udf(cpm_udf);
+
+    let tpm = tpm::Tpm::default();
+    let tpm_udwf = WindowUDF::from(tpm);
+    ctx.register_udwf(tpm_udwf);
+
+    let size_factor = size_factor::SizeFactor::default();
+    let size_factor_udaf = AggregateUDF::from(size_factor);
+    ctx.register_udaf(size_factor_udaf);
+}
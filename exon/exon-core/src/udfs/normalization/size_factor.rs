@@ -0,0 +1,213 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::{
+    array::{ArrayRef, AsArray, ListArray},
+    datatypes::{DataType, Field, Float64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{
+        function::{AccumulatorArgs, StateFieldsArgs},
+        utils::format_state_name,
+        Accumulator, AggregateUDFImpl, Signature, Volatility,
+    },
+    scalar::ScalarValue,
+};
+
+/// The median of values, averaging the middle two of an even number, or `None` if there are none.
+fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    values.sort_by(f64::total_cmp);
+
+    let middle = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        Some((values[middle - 1] + values[middle]) / 2.0)
+    } else {
+        Some(values[middle])
+    }
+}
+
+/// An aggregate for the median-of-ratios size factor of a sample, as DESeq2 estimates it, from
+/// the counts of its genes and the log geometric mean of each gene's counts across the samples.
+///
+/// Genes with a zero count in any sample have an infinite log geometric mean and are left out, as
+/// are the genes the sample has no reads of.
+///
+/// ```sql
+/// SELECT sample, size_factor(count, log_geometric_mean)
+/// FROM (
+///     SELECT *, AVG(ln(count)) OVER (PARTITION BY gene_id) AS log_geometric_mean
+///     FROM counts
+/// )
+/// GROUP BY sample;
+/// ```
+#[derive(Debug)]
+pub(crate) struct SizeFactor {
+    signature: Signature,
+}
+
+impl Default for SizeFactor {
+    fn default() -> Self {
+        let signature = Signature::uniform(2, vec![DataType::Float64], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl AggregateUDFImpl for SizeFactor {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "size_factor"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<Field>> {
+        Ok(vec![Field::new(
+            format_state_name(args.name, "log_ratios"),
+            DataType::List(Arc::new(Field::new("item", DataType::Float64, true))),
+            true,
+        )])
+    }
+
+    fn accumulator(&self, _acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::<SizeFactorAccumulator>::default())
+    }
+}
+
+#[derive(Debug, Default)]
+struct SizeFactorAccumulator {
+    /// The log of the ratio of each count to the geometric mean of its gene.
+    log_ratios: Vec<f64>,
+}
+
+impl Accumulator for SizeFactorAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        if values.len() != 2 {
+            return Err(DataFusionError::Execution(
+                "size_factor takes a count and a log geometric mean".to_string(),
+            ));
+        }
+
+        let counts = values[0].as_primitive::<Float64Type>();
+        let log_geometric_means = values[1].as_primitive::<Float64Type>();
+
+        for (count, log_geometric_mean) in counts.iter().zip(log_geometric_means.iter()) {
+            if let (Some(count), Some(log_geometric_mean)) = (count, log_geometric_mean) {
+                if count > 0.0 && log_geometric_mean.is_finite() {
+                    self.log_ratios.push(count.ln() - log_geometric_mean);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        let states = states[0].as_list_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution("size_factor state should be a list".to_string())
+        })?;
+
+        for log_ratios in states.iter().flatten() {
+            self.log_ratios
+                .extend(log_ratios.as_primitive::<Float64Type>().iter().flatten());
+        }
+
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        let log_ratios = ListArray::from_iter_primitive::<Float64Type, _, _>([Some(
+            self.log_ratios.iter().map(|log_ratio| Some(*log_ratio)),
+        )]);
+
+        Ok(vec![ScalarValue::List(Arc::new(log_ratios))])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        Ok(ScalarValue::Float64(
+            median(&mut self.log_ratios).map(f64::exp),
+        ))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.log_ratios.capacity() * std::mem::size_of::<f64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{ArrayRef, Float64Array};
+    use datafusion::{logical_expr::Accumulator, scalar::ScalarValue};
+
+    use super::{median, SizeFactorAccumulator};
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&mut [3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&mut [4.0, 1.0, 2.0, 3.0]), Some(2.5));
+        assert_eq!(median(&mut []), None);
+    }
+
+    #[test]
+    fn test_size_factor() -> datafusion::error::Result<()> {
+        // The counts of a sample with twice the depth of the geometric means, and a gene with a
+        // zero count in another sample.
+        let counts: ArrayRef = Arc::new(Float64Array::from(vec![20.0, 200.0, 8.0, 5.0]));
+        let log_geometric_means: ArrayRef = Arc::new(Float64Array::from(vec![
+            10f64.ln(),
+            100f64.ln(),
+            4f64.ln(),
+            f64::NEG_INFINITY,
+        ]));
+
+        let mut accumulator = SizeFactorAccumulator::default();
+        accumulator.update_batch(&[counts, log_geometric_means])?;
+
+        let mut merged = SizeFactorAccumulator::default();
+        let state = accumulator.state()?;
+        merged.merge_batch(&[state[0].to_array()?])?;
+
+        match merged.evaluate()? {
+            ScalarValue::Float64(Some(size_factor)) => {
+                assert!((size_factor - 2.0).abs() < 1e-9, "{size_factor}")
+            }
+            value => panic!("unexpected size factor {value:?}"),
+        }
+
+        assert_eq!(
+            SizeFactorAccumulator::default().evaluate()?,
+            ScalarValue::Float64(None)
+        );
+
+        Ok(())
+    }
+}
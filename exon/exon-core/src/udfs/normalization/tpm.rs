@@ -0,0 +1,142 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::{
+    array::{ArrayRef, AsArray, Float64Array},
+    datatypes::{DataType, Field, Float64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{
+        function::{PartitionEvaluatorArgs, WindowUDFFieldArgs},
+        PartitionEvaluator, Signature, Volatility, WindowUDFImpl,
+    },
+};
+
+/// The transcripts per million of counts with effective lengths, which scales the rate of each
+/// transcript, i.e. its count over its effective length, so the rates sum to a million.
+///
+/// A transcript without a count or a positive effective length has no rate, and so no TPM.
+pub(super) fn tpm(counts: &[Option<f64>], effective_lengths: &[Option<f64>]) -> Vec<Option<f64>> {
+    let rates = counts
+        .iter()
+        .zip(effective_lengths)
+        .map(
+            |(count, effective_length)| match (count, effective_length) {
+                (Some(count), Some(effective_length)) if *effective_length > 0.0 => {
+                    Some(count / effective_length)
+                }
+                _ => None,
+            },
+        )
+        .collect::<Vec<_>>();
+
+    let total = rates.iter().flatten().sum::<f64>();
+
+    rates
+        .into_iter()
+        .map(|rate| rate.filter(|_| total > 0.0).map(|rate| rate / total * 1e6))
+        .collect()
+}
+
+/// A window function that scales the counts of a partition, e.g. the transcripts of a sample, to
+/// transcripts per million with their effective lengths.
+///
+/// ```sql
+/// SELECT sample, transcript_id, tpm(num_reads, effective_length) OVER (PARTITION BY sample)
+/// FROM salmon;
+/// ```
+#[derive(Debug)]
+pub(crate) struct Tpm {
+    signature: Signature,
+}
+
+impl Default for Tpm {
+    fn default() -> Self {
+        let signature = Signature::uniform(2, vec![DataType::Float64], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl WindowUDFImpl for Tpm {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "tpm"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn partition_evaluator(
+        &self,
+        _partition_evaluator_args: PartitionEvaluatorArgs,
+    ) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(TpmEvaluator))
+    }
+
+    fn field(&self, field_args: WindowUDFFieldArgs) -> Result<Field> {
+        Ok(Field::new(field_args.name(), DataType::Float64, true))
+    }
+}
+
+/// Evaluates the TPM of each row over the whole partition, regardless of the window frame.
+#[derive(Debug)]
+struct TpmEvaluator;
+
+impl PartitionEvaluator for TpmEvaluator {
+    fn evaluate_all(&mut self, values: &[ArrayRef], _num_rows: usize) -> Result<ArrayRef> {
+        if values.len() != 2 {
+            return Err(DataFusionError::Execution(
+                "tpm takes a count and an effective length".to_string(),
+            ));
+        }
+
+        let counts = values[0]
+            .as_primitive::<Float64Type>()
+            .iter()
+            .collect::<Vec<_>>();
+        let effective_lengths = values[1]
+            .as_primitive::<Float64Type>()
+            .iter()
+            .collect::<Vec<_>>();
+
+        let tpms = Float64Array::from(tpm(&counts, &effective_lengths));
+
+        Ok(Arc::new(tpms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::tpm;
+
+    #[test]
+    fn test_tpm() {
+        let tpms = tpm(
+            &[Some(100.0), Some(600.0), Some(5.0), None],
+            &[Some(100.0), Some(200.0), Some(0.0), Some(100.0)],
+        );
+
+        assert_eq!(tpms, vec![Some(250_000.0), Some(750_000.0), None, None]);
+
+        assert_eq!(tpm(&[Some(0.0)], &[Some(100.0)]), vec![None]);
+    }
+}
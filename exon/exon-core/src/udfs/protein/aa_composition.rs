@@ -0,0 +1,116 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, AsArray, Float64Builder, StructArray},
+    datatypes::{DataType, Field, Fields},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::amino_acids::{amino_acid_index, residues, AMINO_ACIDS};
+
+/// The fields of the composition struct, one per standard amino acid.
+fn composition_fields() -> Fields {
+    AMINO_ACIDS
+        .iter()
+        .map(|aa| Field::new((*aa as char).to_string(), DataType::Float64, true))
+        .collect()
+}
+
+/// Returns the fraction of the residues of a protein that are each of the standard amino acids,
+/// as a struct with a field per one-letter code, e.g. `aa_composition(sequence)['K']`.
+///
+/// Stop codons aren't counted as residues, and the fractions of a protein with non-standard
+/// residues don't sum to one.
+#[derive(Debug)]
+pub(crate) struct AaComposition {
+    signature: Signature,
+}
+
+impl Default for AaComposition {
+    fn default() -> Self {
+        let signature = Signature::exact(vec![DataType::Utf8], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for AaComposition {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "aa_composition"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Struct(composition_fields()))
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 1 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes one argument",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+        let sequences = arrays[0].as_string::<i32>();
+
+        let mut builders = (0..AMINO_ACIDS.len())
+            .map(|_| Float64Builder::with_capacity(sequences.len()))
+            .collect::<Vec<_>>();
+
+        for sequence in sequences.iter() {
+            let Some(sequence) = sequence else {
+                builders.iter_mut().for_each(|b| b.append_null());
+                continue;
+            };
+
+            let mut counts = [0usize; 20];
+            let mut n = 0;
+
+            for residue in residues(sequence) {
+                if let Some(i) = amino_acid_index(residue) {
+                    counts[i] += 1;
+                }
+                n += 1;
+            }
+
+            for (builder, count) in builders.iter_mut().zip(counts) {
+                builder.append_option((n > 0).then(|| count as f64 / n as f64));
+            }
+        }
+
+        let columns = builders
+            .iter_mut()
+            .map(|b| Arc::new(b.finish()) as ArrayRef)
+            .collect::<Vec<_>>();
+
+        let compositions =
+            StructArray::try_new(composition_fields(), columns, sequences.nulls().cloned())?;
+
+        Ok(ColumnarValue::Array(Arc::new(compositions)))
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Amino acid property tables.
+
+/// The one-letter codes of the 20 standard amino acids, in alphabetical order.
+pub(super) const AMINO_ACIDS: &[u8; 20] = b"ACDEFGHIKLMNPQRSTVWY";
+
+/// The mass of water in Da, lost for each peptide bond.
+const WATER_MASS: f64 = 18.01528;
+
+/// The average masses (Da) of the free amino acids, including selenocysteine (U) and
+/// pyrrolysine (O), as used by ExPASy ProtParam.
+const AVERAGE_MASSES: [(u8, f64); 22] = [
+    (b'A', 89.0932),
+    (b'C', 121.1582),
+    (b'D', 133.1027),
+    (b'E', 147.1293),
+    (b'F', 165.1891),
+    (b'G', 75.0666),
+    (b'H', 155.1546),
+    (b'I', 131.1729),
+    (b'K', 146.1876),
+    (b'L', 131.1729),
+    (b'M', 149.2113),
+    (b'N', 132.1179),
+    (b'O', 255.3134),
+    (b'P', 115.1305),
+    (b'Q', 146.1445),
+    (b'R', 174.201),
+    (b'S', 105.0926),
+    (b'T', 119.1192),
+    (b'U', 168.0532),
+    (b'V', 117.1463),
+    (b'W', 204.2252),
+    (b'Y', 181.1885),
+];
+
+/// The Kyte-Doolittle (1982) hydropathy of the standard amino acids, in the order of
+/// [`AMINO_ACIDS`].
+const KYTE_DOOLITTLE: [f64; 20] = [
+    1.8, 2.5, -3.5, -3.5, 2.8, -0.4, -3.2, 4.5, -3.9, 3.8, 1.9, -3.5, -1.6, -3.5, -4.5, -0.8, -0.7,
+    4.2, -0.9, -1.3,
+];
+
+/// The pKa values of the Bjellqvist (1993) method used by ExPASy's Compute pI/Mw.
+mod pka {
+    /// The positively charged side chains.
+    pub(super) const POSITIVE: [(u8, f64); 3] = [(b'K', 10.0), (b'R', 12.0), (b'H', 5.98)];
+
+    /// The negatively charged side chains.
+    pub(super) const NEGATIVE: [(u8, f64); 4] =
+        [(b'D', 4.05), (b'E', 4.45), (b'C', 9.0), (b'Y', 10.0)];
+
+    /// The N-terminus, which depends on the first residue.
+    pub(super) const N_TERMINAL: [(u8, f64); 7] = [
+        (b'A', 7.59),
+        (b'M', 7.0),
+        (b'S', 6.93),
+        (b'P', 8.36),
+        (b'T', 6.82),
+        (b'V', 7.44),
+        (b'E', 7.7),
+    ];
+    pub(super) const N_TERMINAL_DEFAULT: f64 = 7.5;
+
+    /// The C-terminus, which depends on the last residue.
+    pub(super) const C_TERMINAL: [(u8, f64); 2] = [(b'D', 4.55), (b'E', 4.75)];
+    pub(super) const C_TERMINAL_DEFAULT: f64 = 3.55;
+}
+
+/// The residues of a protein sequence, upper-cased and without stop codons (`*`).
+pub(super) fn residues(sequence: &str) -> impl Iterator<Item = u8> + '_ {
+    sequence
+        .bytes()
+        .filter(|b| *b != b'*')
+        .map(|b| b.to_ascii_uppercase())
+}
+
+/// The index of a standard amino acid in [`AMINO_ACIDS`].
+pub(super) fn amino_acid_index(residue: u8) -> Option<usize> {
+    AMINO_ACIDS.iter().position(|aa| *aa == residue)
+}
+
+/// The Kyte-Doolittle hydropathy of a standard amino acid.
+pub(super) fn hydropathy(residue: u8) -> Option<f64> {
+    amino_acid_index(residue).map(|i| KYTE_DOOLITTLE[i])
+}
+
+fn lookup(table: &[(u8, f64)], residue: u8) -> Option<f64> {
+    table
+        .iter()
+        .find(|(aa, _)| *aa == residue)
+        .map(|(_, value)| *value)
+}
+
+/// The average molecular weight of a protein in Da, or `None` if it's empty or has a residue
+/// other than the standard amino acids, selenocysteine, and pyrrolysine.
+pub(super) fn molecular_weight(sequence: &str) -> Option<f64> {
+    let mut weight = 0.0;
+    let mut n = 0;
+
+    for residue in residues(sequence) {
+        weight += lookup(&AVERAGE_MASSES, residue)?;
+        n += 1;
+    }
+
+    (n > 0).then(|| weight - (n - 1) as f64 * WATER_MASS)
+}
+
+/// The grand average of hydropathy of a protein, or `None` if it's empty or has a residue other
+/// than the standard amino acids.
+pub(super) fn gravy(sequence: &str) -> Option<f64> {
+    let mut total = 0.0;
+    let mut n = 0;
+
+    for residue in residues(sequence) {
+        total += hydropathy(residue)?;
+        n += 1;
+    }
+
+    (n > 0).then(|| total / n as f64)
+}
+
+/// The net charge at a pH of the positively and negatively charged groups of a protein, given
+/// as their pKa and count.
+fn net_charge(positive: &[(f64, usize)], negative: &[(f64, usize)], ph: f64) -> f64 {
+    let positive = positive
+        .iter()
+        .map(|(pk, n)| *n as f64 / (10f64.powf(ph - pk) + 1.0))
+        .sum::<f64>();
+    let negative = negative
+        .iter()
+        .map(|(pk, n)| *n as f64 / (10f64.powf(pk - ph) + 1.0))
+        .sum::<f64>();
+
+    positive - negative
+}
+
+/// The isoelectric point of a protein, the pH at which its net charge is zero, by the
+/// Bjellqvist method, or `None` if it's empty. Residues other than the charged ones are ignored.
+pub(super) fn isoelectric_point(sequence: &str) -> Option<f64> {
+    let first = residues(sequence).next()?;
+    let last = residues(sequence).last()?;
+
+    let count = |table: &[(u8, f64)]| {
+        table
+            .iter()
+            .map(|(aa, pk)| (*pk, residues(sequence).filter(|r| r == aa).count()))
+            .collect::<Vec<_>>()
+    };
+
+    let mut positive = count(&pka::POSITIVE);
+    let mut negative = count(&pka::NEGATIVE);
+
+    positive.push((
+        lookup(&pka::N_TERMINAL, first).unwrap_or(pka::N_TERMINAL_DEFAULT),
+        1,
+    ));
+    negative.push((
+        lookup(&pka::C_TERMINAL, last).unwrap_or(pka::C_TERMINAL_DEFAULT),
+        1,
+    ));
+
+    // The net charge decreases with the pH, so bisect for its zero.
+    let (mut low, mut high) = (0.0, 14.0);
+
+    while high - low > 1e-4 {
+        let ph = (low + high) / 2.0;
+
+        if net_charge(&positive, &negative, ph) > 0.0 {
+            low = ph;
+        } else {
+            high = ph;
+        }
+    }
+
+    Some((low + high) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gravy, hydropathy, isoelectric_point, molecular_weight};
+
+    #[test]
+    fn test_molecular_weight() {
+        let weight = molecular_weight("ACDEF*").unwrap();
+        assert!((weight - 583.611).abs() < 1e-3, "{}", weight);
+
+        assert_eq!(molecular_weight(""), None);
+        assert_eq!(molecular_weight("ACX"), None);
+    }
+
+    #[test]
+    fn test_isoelectric_point() {
+        let pi = isoelectric_point("INGAR").unwrap();
+        assert!((pi - 9.75).abs() < 1e-2, "{}", pi);
+
+        let pi = isoelectric_point("acdef").unwrap();
+        assert!((pi - 3.67).abs() < 1e-2, "{}", pi);
+
+        assert_eq!(isoelectric_point("*"), None);
+    }
+
+    #[test]
+    fn test_hydropathy() {
+        assert_eq!(hydropathy(b'I'), Some(4.5));
+        assert_eq!(hydropathy(b'R'), Some(-4.5));
+        assert_eq!(hydropathy(b'X'), None);
+
+        let average = gravy("ACDEF").unwrap();
+        assert!((average - 0.02).abs() < 1e-9, "{}", average);
+        assert_eq!(gravy("ACDEFX"), None);
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Kyte-Doolittle hydropathy scans for transmembrane helices and signal peptides.
+//!
+//! These are quick triage heuristics, not replacements for trained predictors like TMHMM or
+//! SignalP. Non-standard residues count as neutral.
+
+use super::amino_acids::{hydropathy, residues};
+
+/// The default window of transmembrane scans, about the length of a helix spanning a membrane.
+pub(super) const TRANSMEMBRANE_WINDOW: i64 = 19;
+
+/// The mean hydropathy of a 19 residue window above which Kyte and Doolittle (1982) found it
+/// likely to be a transmembrane helix.
+pub(super) const TRANSMEMBRANE_THRESHOLD: f64 = 1.6;
+
+/// The number of N-terminal residues, after the initiator methionine, looked at for the positive
+/// charges of a signal peptide's n-region.
+const N_REGION_LENGTH: usize = 5;
+
+/// The window and the N-terminal residues looked at for a signal peptide's hydrophobic h-region.
+const H_REGION_WINDOW: usize = 8;
+const H_REGION_END: usize = 30;
+
+/// The mean hydropathies of the h-region scored as 0 and as 1.
+const H_REGION_MIN: f64 = 1.0;
+const H_REGION_MAX: f64 = 2.5;
+
+/// The positions of the last residue of a signal peptide, i.e. its cleavage site.
+const CLEAVAGE_SITES: std::ops::RangeInclusive<usize> = 15..=35;
+
+/// The small residues allowed at -3 and -1 of a signal peptide's cleavage site, the "A-X-A" rule.
+const SMALL_RESIDUES: &[u8] = b"AGSCT";
+
+/// The mean hydropathy of each window of `window` residues of a protein.
+fn window_hydropathy(residues: &[u8], window: usize) -> Vec<f64> {
+    if window == 0 || residues.len() < window {
+        return Vec::new();
+    }
+
+    let values = residues
+        .iter()
+        .map(|r| hydropathy(*r).unwrap_or_default())
+        .collect::<Vec<_>>();
+
+    let mut sum = values[..window].iter().sum::<f64>();
+    let mut means = vec![sum / window as f64];
+
+    for i in window..values.len() {
+        sum += values[i] - values[i - window];
+        means.push(sum / window as f64);
+    }
+
+    means
+}
+
+/// The largest mean hydropathy of a window of a protein, or `None` if it's shorter than the
+/// window.
+pub(super) fn max_hydropathy(sequence: &str, window: usize) -> Option<f64> {
+    let residues = residues(sequence).collect::<Vec<_>>();
+
+    window_hydropathy(&residues, window)
+        .into_iter()
+        .reduce(f64::max)
+}
+
+/// The number of hydrophobic segments of a protein, runs of overlapping windows whose mean
+/// hydropathy is at least `threshold`, as candidate transmembrane helices.
+pub(super) fn transmembrane_segments(sequence: &str, window: usize, threshold: f64) -> i64 {
+    let residues = residues(sequence).collect::<Vec<_>>();
+
+    let mut segments = 0;
+    let mut in_segment = false;
+
+    for mean in window_hydropathy(&residues, window) {
+        if mean >= threshold && !in_segment {
+            segments += 1;
+        }
+
+        in_segment = mean >= threshold;
+    }
+
+    segments
+}
+
+/// A score from 0 to 1 of how much the N-terminus of a protein looks like a signal peptide, or
+/// `None` if it's too short to have one.
+///
+/// The score is the hydrophobicity of the best h-region window in the first 30 residues, scaled
+/// from a mean hydropathy of 1.0 to 2.5, weighted by a positively charged n-region and a small
+/// residue "A-X-A" cleavage site after the h-region, which each add a quarter.
+pub(super) fn signal_peptide_score(sequence: &str) -> Option<f64> {
+    let residues = residues(sequence).collect::<Vec<_>>();
+
+    let n_terminus = &residues[..residues.len().min(H_REGION_END)];
+    let (h_start, h_mean) = window_hydropathy(n_terminus, H_REGION_WINDOW)
+        .into_iter()
+        .enumerate()
+        .reduce(|best, window| if window.1 > best.1 { window } else { best })?;
+
+    let h_score = ((h_mean - H_REGION_MIN) / (H_REGION_MAX - H_REGION_MIN)).clamp(0.0, 1.0);
+
+    let n_region = residues.iter().skip(1).take(N_REGION_LENGTH);
+    let n_score = if n_region.clone().any(|r| matches!(r, b'K' | b'R')) {
+        1.0
+    } else {
+        0.0
+    };
+
+    let h_end = h_start + H_REGION_WINDOW;
+    let is_small = |i: usize| residues.get(i).is_some_and(|r| SMALL_RESIDUES.contains(r));
+
+    let c_score = if CLEAVAGE_SITES
+        .filter(|site| *site > h_end)
+        .any(|site| is_small(site - 3) && is_small(site - 1))
+    {
+        1.0
+    } else {
+        0.0
+    };
+
+    Some(h_score * (0.5 + 0.25 * n_score + 0.25 * c_score))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{max_hydropathy, signal_peptide_score, transmembrane_segments};
+
+    const INSULIN: &str = "MALWMRLLPLLALLALWGPDPAAAFVNQHLCGSHLVEALYLVCGERGFFYTPKTRREAEDLQVGQVELGGGPGAGSLQPLALEGSLQKRGIVEQCCTSICSLYQLENYCN";
+
+    const GAPDH: &str = "MGKVKVGVNGFGRIGRLVTRAAFNSGKVDIVAINDPFIDLNYMVYMFQYDSTHGKFHGTVKAENGKLVINGNPITIFQERDPSKIKWGDAGAEYVVESTGVFTTMEKAGAHLQGGAKRVIISAPSADAPMFVMGVNHEKYDNSLKIISNASCTTNCLAPLAKVIHDNFGIVEGLMTTVHAITATQKTVDGPSGKLWRDGRGALQNIIPASTGAAKAVGKVIPELNGKLTGMAFRVPTANVSVVDLTCRLEKPAKYDDIKKVVKQASEGPLKGILGYTEDQVVSCDFNSDTHSSTFDAGAGIALNDHFVKLISWYDNEFGYSNRVVDLMAHMASKE";
+
+    const BACTERIORHODOPSIN: &str = "MLELLPTAVEGVSQAQITGRPEWIWLALGTALMGLGTLYFLVKGMGVSDPDAKKFYAITTLVPAIAFTMYLSMLLGYGLTMVPFGGEQNPIYWARYADWLFTTPLLLLDLALLVDADQGTILALVGADGIMIGTGLVGALTKVYSYRFVWWAISTAAMLYILYVLFFGFTSKAESMRPEVASTFKVLRNVTVVLWSAYPVVWLIGSEGAGIVPLNIETLLFMVLDVSAKVGFGLILLRSRAIFGEAEAPEPSAGDGAAATSD";
+
+    #[test]
+    fn test_max_hydropathy() {
+        assert_eq!(max_hydropathy("IIV", 2), Some(4.5));
+        assert_eq!(max_hydropathy("IIV", 4), None);
+        assert_eq!(max_hydropathy("IIV", 0), None);
+    }
+
+    #[test]
+    fn test_transmembrane_segments() {
+        assert_eq!(transmembrane_segments(BACTERIORHODOPSIN, 19, 1.6), 6);
+        assert_eq!(transmembrane_segments(GAPDH, 19, 1.6), 0);
+        assert_eq!(transmembrane_segments("LLLLKKKKLLLL", 4, 1.6), 2);
+    }
+
+    #[test]
+    fn test_signal_peptide_score() {
+        assert_eq!(signal_peptide_score(INSULIN), Some(1.0));
+        assert_eq!(signal_peptide_score(GAPDH), Some(0.0));
+        assert_eq!(signal_peptide_score("MKL"), None);
+    }
+}
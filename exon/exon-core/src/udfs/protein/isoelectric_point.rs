@@ -0,0 +1,57 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use arrow::datatypes::DataType;
+use datafusion::{
+    error::Result,
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::{amino_acids, map_sequences};
+
+/// Returns the isoelectric point of a protein by the Bjellqvist method, like ExPASy's Compute
+/// pI/Mw.
+#[derive(Debug)]
+pub(crate) struct IsoelectricPoint {
+    signature: Signature,
+}
+
+impl Default for IsoelectricPoint {
+    fn default() -> Self {
+        let signature = Signature::exact(vec![DataType::Utf8], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for IsoelectricPoint {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "isoelectric_point"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        map_sequences(self.name(), args, amino_acids::isoelectric_point)
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, AsArray, Float64Array},
+    compute::cast,
+    datatypes::{DataType, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, TypeSignature, Volatility},
+};
+
+use super::{hydropathy, window_size};
+
+/// Returns the largest mean Kyte-Doolittle hydropathy of a window of a protein, 19 residues by
+/// default, e.g. `max_hydropathy(sequence, 9)`.
+///
+/// Proteins shorter than the window are null.
+#[derive(Debug)]
+pub(crate) struct MaxHydropathy {
+    signature: Signature,
+}
+
+impl Default for MaxHydropathy {
+    fn default() -> Self {
+        let signature = Signature::one_of(
+            vec![
+                TypeSignature::Coercible(vec![DataType::Utf8]),
+                TypeSignature::Coercible(vec![DataType::Utf8, DataType::Int64]),
+            ],
+            Volatility::Immutable,
+        );
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for MaxHydropathy {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "max_hydropathy"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.is_empty() || args.len() > 2 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes a sequence and optionally a window",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = cast(&arrays[0], &DataType::Utf8)?;
+        let sequences = sequences.as_string::<i32>();
+
+        let windows = arrays
+            .get(1)
+            .map(|array| cast(array, &DataType::Int64))
+            .transpose()?;
+        let windows = windows.as_ref().map(|a| a.as_primitive::<Int64Type>());
+
+        let values = (0..sequences.len())
+            .map(|i| {
+                let window = match windows {
+                    Some(windows) if windows.is_null(i) => return Ok(None),
+                    Some(windows) => window_size(self.name(), windows.value(i))?,
+                    None => window_size(self.name(), hydropathy::TRANSMEMBRANE_WINDOW)?,
+                };
+
+                if sequences.is_null(i) {
+                    return Ok(None);
+                }
+
+                Ok(hydropathy::max_hydropathy(sequences.value(i), window))
+            })
+            .collect::<Result<Float64Array>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(values)))
+    }
+}
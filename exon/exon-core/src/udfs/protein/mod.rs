@@ -0,0 +1,106 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! UDFs for the physicochemical properties of protein sequences, e.g. to filter the proteins of
+//! a FASTA table.
+
+mod aa_composition;
+mod amino_acids;
+mod gravy;
+mod hydropathy;
+mod isoelectric_point;
+mod max_hydropathy;
+mod protein_mw;
+mod signal_peptide_score;
+mod transmembrane_segments;
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{AsArray, Float64Array},
+    datatypes::DataType,
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::context::SessionContext,
+    logical_expr::{ColumnarValue, ScalarUDF},
+};
+
+/// Apply a function to the protein sequences of the only argument of a UDF.
+fn map_sequences(
+    name: &str,
+    args: &[ColumnarValue],
+    f: impl Fn(&str) -> Option<f64>,
+) -> Result<ColumnarValue> {
+    if args.len() != 1 {
+        return Err(DataFusionError::Execution(format!(
+            "{} takes one argument",
+            name
+        )));
+    }
+
+    let arrays = ColumnarValue::values_to_arrays(args)?;
+
+    let values = arrays[0]
+        .as_string_opt::<i32>()
+        .ok_or_else(|| {
+            DataFusionError::Execution(format!("{} takes a {} argument", name, DataType::Utf8))
+        })?
+        .iter()
+        .map(|sequence| sequence.and_then(&f))
+        .collect::<Float64Array>();
+
+    Ok(ColumnarValue::Array(Arc::new(values)))
+}
+
+/// The window of a hydropathy scan, which must be at least one residue.
+fn window_size(name: &str, window: i64) -> Result<usize> {
+    match usize::try_from(window) {
+        Ok(window) if window > 0 => Ok(window),
+        _ => Err(DataFusionError::Execution(format!(
+            "{} takes a positive window, got {}",
+            name, window
+        ))),
+    }
+}
+
+/// Register the protein UDFs.
+pub fn register_udfs(ctx: &SessionContext) {
+    let protein_mw = protein_mw::ProteinMW::default();
+    let protein_mw_udf = ScalarUDF::from(protein_mw);
+    ctx.register_udf(protein_mw_udf);
+
+    let isoelectric_point = isoelectric_point::IsoelectricPoint::default();
+    let isoelectric_point_udf = ScalarUDF::from(isoelectric_point);
+    ctx.register_udf(isoelectric_point_udf);
+
+    let gravy = gravy::Gravy::default();
+    let gravy_udf = ScalarUDF::from(gravy);
+    ctx.register_udf(gravy_udf);
+
+    let aa_composition = aa_composition::AaComposition::default();
+    let aa_composition_udf = ScalarUDF::from(aa_composition);
+    ctx.register_udf(aa_composition_udf);
+
+    let max_hydropathy = max_hydropathy::MaxHydropathy::default();
+    let max_hydropathy_udf = ScalarUDF::from(max_hydropathy);
+    ctx.register_udf(max_hydropathy_udf);
+
+    let transmembrane_segments = transmembrane_segments::TransmembraneSegments::default();
+    let transmembrane_segments_udf = ScalarUDF::from(transmembrane_segments);
+    ctx.register_udf(transmembrane_segments_udf);
+
+    let signal_peptide_score = signal_peptide_score::SignalPeptideScore::default();
+    let signal_peptide_score_udf = ScalarUDF::from(signal_peptide_score);
+    ctx.register_udf(signal_peptide_score_udf);
+}
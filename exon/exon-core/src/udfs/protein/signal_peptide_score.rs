@@ -0,0 +1,61 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use arrow::datatypes::DataType;
+use datafusion::{
+    error::Result,
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::{hydropathy, map_sequences};
+
+/// Returns a score from 0 to 1 of how much the N-terminus of a protein looks like a signal
+/// peptide, from its hydrophobic h-region, positively charged n-region, and "A-X-A" cleavage
+/// site, or null if it's shorter than the h-region window of 8 residues.
+///
+/// Scores of 0.75 and above suggest a secreted protein, but N-terminal transmembrane helices
+/// score highly as well.
+#[derive(Debug)]
+pub(crate) struct SignalPeptideScore {
+    signature: Signature,
+}
+
+impl Default for SignalPeptideScore {
+    fn default() -> Self {
+        let signature = Signature::exact(vec![DataType::Utf8], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for SignalPeptideScore {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "signal_peptide_score"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        map_sequences(self.name(), args, hydropathy::signal_peptide_score)
+    }
+}
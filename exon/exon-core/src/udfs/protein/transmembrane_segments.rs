@@ -0,0 +1,123 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, AsArray, Int64Array},
+    compute::cast,
+    datatypes::{DataType, Float64Type, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, TypeSignature, Volatility},
+};
+
+use super::{hydropathy, window_size};
+
+/// Returns the number of candidate transmembrane helices of a protein, the runs of windows whose
+/// mean Kyte-Doolittle hydropathy is at least a threshold, e.g.
+/// `transmembrane_segments(sequence, 19, 1.6)`.
+///
+/// The window defaults to 19 residues and the threshold to 1.6, after Kyte and Doolittle (1982).
+#[derive(Debug)]
+pub(crate) struct TransmembraneSegments {
+    signature: Signature,
+}
+
+impl Default for TransmembraneSegments {
+    fn default() -> Self {
+        let signature = Signature::one_of(
+            vec![
+                TypeSignature::Coercible(vec![DataType::Utf8]),
+                TypeSignature::Coercible(vec![DataType::Utf8, DataType::Int64]),
+                TypeSignature::Coercible(vec![DataType::Utf8, DataType::Int64, DataType::Float64]),
+            ],
+            Volatility::Immutable,
+        );
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for TransmembraneSegments {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "transmembrane_segments"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Int64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.is_empty() || args.len() > 3 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes a sequence and optionally a window and a threshold",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = cast(&arrays[0], &DataType::Utf8)?;
+        let sequences = sequences.as_string::<i32>();
+
+        let windows = arrays
+            .get(1)
+            .map(|array| cast(array, &DataType::Int64))
+            .transpose()?;
+        let windows = windows.as_ref().map(|a| a.as_primitive::<Int64Type>());
+
+        let thresholds = arrays
+            .get(2)
+            .map(|array| cast(array, &DataType::Float64))
+            .transpose()?;
+        let thresholds = thresholds.as_ref().map(|a| a.as_primitive::<Float64Type>());
+
+        let segments = (0..sequences.len())
+            .map(|i| {
+                let window = match windows {
+                    Some(windows) if windows.is_null(i) => return Ok(None),
+                    Some(windows) => window_size(self.name(), windows.value(i))?,
+                    None => window_size(self.name(), hydropathy::TRANSMEMBRANE_WINDOW)?,
+                };
+
+                let threshold = match thresholds {
+                    Some(thresholds) if thresholds.is_null(i) => return Ok(None),
+                    Some(thresholds) => thresholds.value(i),
+                    None => hydropathy::TRANSMEMBRANE_THRESHOLD,
+                };
+
+                if sequences.is_null(i) {
+                    return Ok(None);
+                }
+
+                Ok(Some(hydropathy::transmembrane_segments(
+                    sequences.value(i),
+                    window,
+                    threshold,
+                )))
+            })
+            .collect::<Result<Int64Array>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(segments)))
+    }
+}
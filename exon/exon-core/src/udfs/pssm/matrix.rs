@@ -0,0 +1,501 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::str::FromStr;
+
+use datafusion::error::{DataFusionError, Result};
+
+/// The pseudo count added to each count of a matrix, so bases never seen at a position don't
+/// score negative infinity.
+pub(crate) const DEFAULT_PSEUDO_COUNT: f64 = 0.1;
+
+/// The frequencies of A, C, G, and T when nothing else is known about a genome.
+pub(crate) const UNIFORM_BACKGROUND: [f64; 4] = [0.25; 4];
+
+/// The formats of motif files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MotifFormat {
+    /// JASPAR count matrices, with or without `>` headers and bracketed rows.
+    Jaspar,
+    /// TRANSFAC matrices, with a row per position between `P0` and `XX` lines.
+    Transfac,
+    /// UniPROBE frequency matrices, with a `A:` to `T:` row per base.
+    Uniprobe,
+}
+
+impl MotifFormat {
+    /// The format of a motif file from its extension.
+    pub(crate) fn from_path(path: &str) -> Result<Self> {
+        let extension = path.rsplit('.').next().unwrap_or_default();
+
+        extension.parse()
+    }
+}
+
+impl FromStr for MotifFormat {
+    type Err = DataFusionError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "jaspar" | "pfm" => Ok(Self::Jaspar),
+            "transfac" | "dat" => Ok(Self::Transfac),
+            "uniprobe" => Ok(Self::Uniprobe),
+            _ => Err(DataFusionError::Execution(format!(
+                "Invalid motif format {}, expected jaspar (.jaspar, .pfm), transfac (.transfac, .dat), or uniprobe (.uniprobe)",
+                s
+            ))),
+        }
+    }
+}
+
+/// The index of a base in ACGT order, `U` is read as `T`.
+fn base_index(base: u8) -> Option<usize> {
+    match base.to_ascii_uppercase() {
+        b'A' => Some(0),
+        b'C' => Some(1),
+        b'G' => Some(2),
+        b'T' | b'U' => Some(3),
+        _ => None,
+    }
+}
+
+fn parse_number(value: &str) -> Result<f64> {
+    value
+        .parse()
+        .map_err(|_| DataFusionError::Execution(format!("Invalid count {} in motif matrix", value)))
+}
+
+/// The counts, or frequencies, of each base at each position of a motif.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CountMatrix {
+    name: String,
+    /// The counts of A, C, G, and T at each position.
+    columns: Vec<[f64; 4]>,
+}
+
+impl CountMatrix {
+    fn try_new(name: String, columns: Vec<[f64; 4]>) -> Result<Self> {
+        if columns.is_empty() {
+            return Err(DataFusionError::Execution(format!(
+                "Motif {} has no positions",
+                name
+            )));
+        }
+
+        if columns.iter().flatten().any(|count| *count < 0.0) {
+            return Err(DataFusionError::Execution(format!(
+                "Motif {} has a negative count",
+                name
+            )));
+        }
+
+        Ok(Self { name, columns })
+    }
+
+    /// A matrix from a row of counts per base, which must all be the same length.
+    fn try_from_rows(name: String, rows: [Vec<f64>; 4]) -> Result<Self> {
+        if rows.iter().any(|row| row.len() != rows[0].len()) {
+            return Err(DataFusionError::Execution(format!(
+                "Motif {} has rows of different lengths",
+                name
+            )));
+        }
+
+        let columns = (0..rows[0].len())
+            .map(|i| [rows[0][i], rows[1][i], rows[2][i], rows[3][i]])
+            .collect();
+
+        Self::try_new(name, columns)
+    }
+
+    /// The name of the motif.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Parse the matrices of a motif file.
+    pub(crate) fn parse_all(contents: &str, format: MotifFormat) -> Result<Vec<Self>> {
+        let matrices = match format {
+            MotifFormat::Jaspar => parse_jaspar(contents)?,
+            MotifFormat::Transfac => parse_transfac(contents)?,
+            MotifFormat::Uniprobe => parse_uniprobe(contents)?,
+        };
+
+        if matrices.is_empty() {
+            return Err(DataFusionError::Execution(
+                "Motif file has no matrices".to_string(),
+            ));
+        }
+
+        Ok(matrices)
+    }
+}
+
+/// Parse rows of base counts, e.g. `A  [ 0 0 82 ]` or `A: 0.1 0.2`, with or without the base.
+fn parse_rows<'a>(name: &str, lines: impl Iterator<Item = &'a str>) -> Result<[Vec<f64>; 4]> {
+    let mut rows: [Option<Vec<f64>>; 4] = Default::default();
+
+    for (i, line) in lines.enumerate() {
+        let line = line.trim();
+
+        let (base, values) = match line.as_bytes().first().copied().and_then(base_index) {
+            Some(base) => (base, &line[1..]),
+            None => (i, line),
+        };
+
+        let values = values
+            .split(|c: char| c.is_whitespace() || matches!(c, ':' | '[' | ']'))
+            .filter(|value| !value.is_empty())
+            .map(parse_number)
+            .collect::<Result<Vec<_>>>()?;
+
+        match rows.get_mut(base) {
+            Some(row @ None) => *row = Some(values),
+            _ => {
+                return Err(DataFusionError::Execution(format!(
+                    "Motif {} has more than one row per base",
+                    name
+                )))
+            }
+        }
+    }
+
+    let [Some(a), Some(c), Some(g), Some(t)] = rows else {
+        return Err(DataFusionError::Execution(format!(
+            "Motif {} needs a row for each of A, C, G, and T",
+            name
+        )));
+    };
+
+    Ok([a, c, g, t])
+}
+
+fn parse_jaspar(contents: &str) -> Result<Vec<CountMatrix>> {
+    let mut matrices = Vec::new();
+    let mut name = String::from("motif");
+    let mut lines = Vec::new();
+
+    let mut push = |name: &str, lines: &mut Vec<&str>| -> Result<()> {
+        if !lines.is_empty() {
+            let rows = parse_rows(name, lines.drain(..))?;
+            matrices.push(CountMatrix::try_from_rows(name.to_string(), rows)?);
+        }
+
+        Ok(())
+    };
+
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        if let Some(header) = line.strip_prefix('>') {
+            push(&name, &mut lines)?;
+            name = header
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+        } else {
+            lines.push(line);
+
+            if lines.len() == 4 {
+                push(&name, &mut lines)?;
+            }
+        }
+    }
+
+    push(&name, &mut lines)?;
+
+    Ok(matrices)
+}
+
+fn parse_transfac(contents: &str) -> Result<Vec<CountMatrix>> {
+    let mut matrices = Vec::new();
+
+    let mut name = String::from("motif");
+    let mut order: Option<Vec<usize>> = None;
+    let mut columns = Vec::new();
+
+    for line in contents.lines() {
+        let mut fields = line.split_whitespace();
+
+        match fields.next() {
+            Some("ID") => name = fields.next().unwrap_or_default().to_string(),
+            Some("P0") | Some("PO") => {
+                let bases = fields
+                    .map(|base| {
+                        base.bytes().next().and_then(base_index).ok_or_else(|| {
+                            DataFusionError::Execution(format!(
+                                "Invalid base {} in motif {}",
+                                base, name
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                order = Some(bases);
+            }
+            Some(position) if position.bytes().all(|b| b.is_ascii_digit()) => {
+                let Some(bases) = &order else {
+                    continue;
+                };
+
+                let mut column = [0.0; 4];
+                for (base, value) in bases.iter().zip(fields) {
+                    column[*base] = parse_number(value)?;
+                }
+
+                columns.push(column);
+            }
+            Some("XX") | Some("//") if order.is_some() => {
+                order = None;
+                matrices.push(CountMatrix::try_new(
+                    std::mem::take(&mut name),
+                    std::mem::take(&mut columns),
+                )?);
+            }
+            _ => {}
+        }
+    }
+
+    if order.is_some() {
+        matrices.push(CountMatrix::try_new(name, columns)?);
+    }
+
+    Ok(matrices)
+}
+
+fn parse_uniprobe(contents: &str) -> Result<Vec<CountMatrix>> {
+    let mut matrices = Vec::new();
+    let mut name = String::from("motif");
+    let mut lines = Vec::new();
+
+    let is_row = |line: &str| {
+        let line = line.trim_start();
+        line.len() > 1 && base_index(line.as_bytes()[0]).is_some() && line.as_bytes()[1] == b':'
+    };
+
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        if is_row(line) {
+            lines.push(line);
+
+            if lines.len() == 4 {
+                let rows = parse_rows(&name, lines.drain(..))?;
+                matrices.push(CountMatrix::try_from_rows(name.clone(), rows)?);
+            }
+        } else {
+            let mut fields = line.split_whitespace();
+
+            name = match fields.next() {
+                Some("Gene:") => fields.next().unwrap_or_default().to_string(),
+                Some(first) => first.to_string(),
+                None => name,
+            };
+        }
+    }
+
+    if !lines.is_empty() {
+        return Err(DataFusionError::Execution(format!(
+            "Motif {} needs a row for each of A, C, G, and T",
+            name
+        )));
+    }
+
+    Ok(matrices)
+}
+
+/// The bases of a sequence as indexes in ACGT order, `None` for other bases, to score a sequence
+/// against many matrices.
+pub(crate) fn encode_sequence(sequence: &[u8]) -> Vec<Option<usize>> {
+    sequence.iter().map(|b| base_index(*b)).collect()
+}
+
+/// A named motif and its scoring matrices, for the forward strand and for the reverse strand if
+/// both are scanned.
+#[derive(Debug, Clone)]
+pub(crate) struct Motif {
+    name: String,
+    matrices: Vec<ScoringMatrix>,
+}
+
+impl Motif {
+    pub(crate) fn new(
+        counts: &CountMatrix,
+        pseudo_count: f64,
+        background: [f64; 4],
+        both_strands: bool,
+    ) -> Self {
+        let mut matrices = vec![ScoringMatrix::new(counts, pseudo_count, background)];
+        if both_strands {
+            matrices.push(matrices[0].reverse_complement());
+        }
+
+        Self {
+            name: counts.name.clone(),
+            matrices,
+        }
+    }
+
+    /// The name of the motif.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The best score of the motif on the scanned strands of an encoded sequence.
+    pub(crate) fn best_score(&self, bases: &[Option<usize>]) -> Option<f64> {
+        self.matrices
+            .iter()
+            .filter_map(|matrix| matrix.best_score(bases))
+            .reduce(f64::max)
+    }
+}
+
+/// The log-odds scores of each base at each position of a motif against a background.
+#[derive(Debug, Clone)]
+struct ScoringMatrix {
+    weights: Vec<[f64; 4]>,
+}
+
+impl ScoringMatrix {
+    /// The log2 odds of the frequencies of a count matrix, with a pseudo count added to every
+    /// count, against the background frequencies of A, C, G, and T.
+    fn new(counts: &CountMatrix, pseudo_count: f64, background: [f64; 4]) -> Self {
+        let weights = counts
+            .columns
+            .iter()
+            .map(|column| {
+                let total = column.iter().sum::<f64>() + 4.0 * pseudo_count;
+
+                let mut weights = [0.0; 4];
+                for (i, weight) in weights.iter_mut().enumerate() {
+                    *weight = ((column[i] + pseudo_count) / total / background[i]).log2();
+                }
+
+                weights
+            })
+            .collect();
+
+        Self { weights }
+    }
+
+    /// The matrix of the motif on the reverse strand, so scoring a sequence with it scores the
+    /// motif against the reverse complement of the sequence.
+    fn reverse_complement(&self) -> Self {
+        let weights = self
+            .weights
+            .iter()
+            .rev()
+            .map(|w| [w[3], w[2], w[1], w[0]])
+            .collect();
+
+        Self { weights }
+    }
+
+    /// The best score of the windows of an encoded sequence, or `None` if it's shorter than the
+    /// motif or every window has a base other than ACGT.
+    fn best_score(&self, bases: &[Option<usize>]) -> Option<f64> {
+        bases
+            .windows(self.weights.len())
+            .filter_map(|window| {
+                window
+                    .iter()
+                    .zip(&self.weights)
+                    .map(|(base, weights)| base.map(|b| weights[b]))
+                    .sum::<Option<f64>>()
+            })
+            .reduce(f64::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        encode_sequence, CountMatrix, Motif, MotifFormat, ScoringMatrix, UNIFORM_BACKGROUND,
+    };
+
+    const JASPAR: &str = ">MA0001.3\tAGL3
+A  [     0      0     82 ]
+C  [    92     79      1 ]
+G  [     0      0      2 ]
+T  [     3     16     10 ]
+";
+
+    const TRANSFAC: &str = "AC  M00005
+XX
+ID  V$AP4_01
+XX
+P0      A      C      G      T
+01      3      0      0      2      W
+02      1      1      3      0      G
+XX
+//
+";
+
+    const UNIPROBE: &str = "Gene:  Cha4-primary  Motif:  A.CTCCGCC
+A:\t0.5\t0.25
+C:\t0.5\t0.25
+G:\t0\t0.25
+T:\t0\t0.25
+";
+
+    #[test]
+    fn test_parse_formats() -> Result<(), Box<dyn std::error::Error>> {
+        let jaspar = CountMatrix::parse_all(JASPAR, MotifFormat::Jaspar)?;
+        assert_eq!(jaspar.len(), 1);
+        assert_eq!(jaspar[0].name, "MA0001.3");
+        assert_eq!(jaspar[0].columns[0], [0.0, 92.0, 0.0, 3.0]);
+
+        let transfac = CountMatrix::parse_all(TRANSFAC, MotifFormat::Transfac)?;
+        assert_eq!(transfac.len(), 1);
+        assert_eq!(transfac[0].name, "V$AP4_01");
+        assert_eq!(
+            transfac[0].columns,
+            vec![[3.0, 0.0, 0.0, 2.0], [1.0, 1.0, 3.0, 0.0]]
+        );
+
+        let uniprobe = CountMatrix::parse_all(UNIPROBE, MotifFormat::Uniprobe)?;
+        assert_eq!(uniprobe[0].name, "Cha4-primary");
+        assert_eq!(uniprobe[0].columns.len(), 2);
+
+        assert_eq!(
+            MotifFormat::from_path("motifs/MA0001.3.pfm")?,
+            MotifFormat::Jaspar
+        );
+        assert!(MotifFormat::from_path("motif.meme").is_err());
+        assert!(CountMatrix::parse_all(
+            "A [ 1 2 ]\nC [ 1 ]\nG [ 1 ]\nT [ 1 ]",
+            MotifFormat::Jaspar
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_best_score() -> Result<(), Box<dyn std::error::Error>> {
+        let counts = CountMatrix::parse_all(UNIPROBE, MotifFormat::Uniprobe)?;
+        let matrix = ScoringMatrix::new(&counts[0], 0.0, UNIFORM_BACKGROUND);
+        let score = |sequence: &[u8]| matrix.best_score(&encode_sequence(sequence));
+
+        assert_eq!(score(b"GCA"), Some(1.0));
+        assert_eq!(score(b"aa"), Some(1.0));
+        assert_eq!(score(b"GNA"), None);
+        assert_eq!(score(b"A"), None);
+
+        // GG is CC on the reverse strand.
+        assert_eq!(score(b"GG"), Some(f64::NEG_INFINITY));
+
+        let motif = Motif::new(&counts[0], 0.0, UNIFORM_BACKGROUND, true);
+        assert_eq!(motif.name(), "Cha4-primary");
+        assert_eq!(motif.best_score(&encode_sequence(b"GG")), Some(1.0));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,331 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Position-specific scoring matrix (PSSM) UDFs, created from a motif file in JASPAR, TRANSFAC,
+//! or UniPROBE format with e.g.
+//!
+//! ```sql
+//! CREATE FUNCTION agl3(VARCHAR) RETURNS DOUBLE LANGUAGE PSSM AS 'motifs/MA0001.3.jaspar';
+//! ```
+//!
+//! The format comes from the extension of the file, and the function returns the best log2-odds
+//! score of the motif over the windows of a sequence.
+//!
+//! Arguments after the sequence set how the function scores, from their `DEFAULT` values when it's
+//! created, and the function is still called with just the sequence:
+//!
+//! ```sql
+//! CREATE FUNCTION agl3(sequence VARCHAR, pseudo_count DOUBLE DEFAULT 0.5,
+//!     background VARCHAR DEFAULT '0.3,0.2,0.2,0.3', threshold DOUBLE DEFAULT 8.0,
+//!     both_strands BOOLEAN DEFAULT true)
+//! RETURNS DOUBLE LANGUAGE PSSM AS 'motifs/MA0001.3.jaspar';
+//! ```
+//!
+//! - `pseudo_count` is added to every count of the matrix, 0.1 by default.
+//! - `background` is the frequencies of A, C, G, and T, uniform by default.
+//! - `threshold` makes sequences whose best score is below it null.
+//! - `both_strands` also scans the reverse complement of the sequence, false by default.
+//!
+//! A motif file with more than one matrix, e.g. a JASPAR or TRANSFAC collection, makes a library
+//! function that returns the best matching motif of a sequence as a `{motif, score}` struct, or a
+//! list of every motif scoring at least the threshold with `all_matches BOOLEAN DEFAULT true`.
+
+mod matrix;
+mod pssm_library_udf;
+mod pssm_udf;
+
+use arrow::datatypes::DataType;
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::context::SessionState,
+    logical_expr::{CreateFunction, Expr, OperateFunctionArg, ScalarUDF},
+    scalar::ScalarValue,
+};
+
+use crate::udfs::create_function::{function_body, read_function_file};
+
+use self::{
+    matrix::{CountMatrix, Motif, MotifFormat, DEFAULT_PSEUDO_COUNT, UNIFORM_BACKGROUND},
+    pssm_library_udf::PssmLibraryUdf,
+    pssm_udf::PssmUdf,
+};
+
+/// How a PSSM UDF scores sequences.
+#[derive(Debug, Clone, PartialEq)]
+struct PssmParameters {
+    pseudo_count: f64,
+    background: [f64; 4],
+    threshold: Option<f64>,
+    both_strands: bool,
+    all_matches: bool,
+}
+
+impl Default for PssmParameters {
+    fn default() -> Self {
+        Self {
+            pseudo_count: DEFAULT_PSEUDO_COUNT,
+            background: UNIFORM_BACKGROUND,
+            threshold: None,
+            both_strands: false,
+            all_matches: false,
+        }
+    }
+}
+
+impl PssmParameters {
+    /// The parameters from the defaults of the arguments after the sequence of a PSSM UDF.
+    fn try_from_args(name: &str, args: &[OperateFunctionArg]) -> Result<Self> {
+        let error =
+            |message: String| DataFusionError::Plan(format!("PSSM function {} {}", name, message));
+
+        let mut parameters = Self::default();
+
+        for arg in args {
+            let Some(parameter) = &arg.name else {
+                return Err(error(
+                    "needs names for the arguments after the sequence".into(),
+                ));
+            };
+            let parameter = parameter.value.to_ascii_lowercase();
+
+            let Some(Expr::Literal(value)) = &arg.default_expr else {
+                return Err(error(format!("needs a literal DEFAULT for {}", parameter)));
+            };
+
+            let number = || match value.cast_to(&DataType::Float64) {
+                Ok(ScalarValue::Float64(Some(number))) if number.is_finite() => Ok(number),
+                _ => Err(error(format!(
+                    "needs a number for {}, got {}",
+                    parameter, value
+                ))),
+            };
+
+            match parameter.as_str() {
+                "pseudo_count" => {
+                    parameters.pseudo_count = number()?;
+
+                    if parameters.pseudo_count < 0.0 {
+                        return Err(error("needs a non-negative pseudo_count".into()));
+                    }
+                }
+                "background" => {
+                    let ScalarValue::Utf8(Some(background)) = value else {
+                        return Err(error(format!(
+                            "needs the background as a string of four frequencies, got {}",
+                            value
+                        )));
+                    };
+
+                    parameters.background = parse_background(background).ok_or_else(|| {
+                        error(format!(
+                            "needs four positive frequencies of A, C, G, and T as the background, got '{}'",
+                            background
+                        ))
+                    })?;
+                }
+                "threshold" => parameters.threshold = Some(number()?),
+                "both_strands" => {
+                    let ScalarValue::Boolean(Some(both_strands)) = value else {
+                        return Err(error(format!(
+                            "needs a boolean for both_strands, got {}",
+                            value
+                        )));
+                    };
+
+                    parameters.both_strands = *both_strands;
+                }
+                "all_matches" => {
+                    let ScalarValue::Boolean(Some(all_matches)) = value else {
+                        return Err(error(format!(
+                            "needs a boolean for all_matches, got {}",
+                            value
+                        )));
+                    };
+
+                    parameters.all_matches = *all_matches;
+                }
+                _ => {
+                    return Err(error(format!(
+                        "has an unknown parameter {}, expected pseudo_count, background, threshold, both_strands, or all_matches",
+                        parameter
+                    )))
+                }
+            }
+        }
+
+        Ok(parameters)
+    }
+}
+
+/// Parse background frequencies of A, C, G, and T, e.g. `0.3,0.2,0.2,0.3`, scaled to sum to one.
+fn parse_background(background: &str) -> Option<[f64; 4]> {
+    let frequencies = background
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|f| !f.is_empty())
+        .map(|f| f.parse::<f64>().ok().filter(|f| f.is_finite() && *f > 0.0))
+        .collect::<Option<Vec<_>>>()?;
+
+    let frequencies: [f64; 4] = frequencies.try_into().ok()?;
+    let total = frequencies.iter().sum::<f64>();
+
+    Some(frequencies.map(|f| f / total))
+}
+
+/// Read the matrices of the motif file at a path.
+async fn read_motifs(state: &SessionState, path: &str) -> Result<Vec<CountMatrix>> {
+    let format = MotifFormat::from_path(path)?;
+
+    let contents = read_function_file(state, path).await?;
+
+    CountMatrix::parse_all(&contents, format)
+}
+
+/// Create a PSSM UDF from a `CREATE FUNCTION ... LANGUAGE PSSM AS 'path'` statement.
+pub(crate) async fn create_pssm_udf(
+    state: &SessionState,
+    statement: &CreateFunction,
+) -> Result<ScalarUDF> {
+    let name = &statement.name;
+
+    let Some(path) = function_body(statement) else {
+        return Err(DataFusionError::Plan(format!(
+            "PSSM function {} needs the path to a motif file, e.g. AS 'motif.jaspar'",
+            name
+        )));
+    };
+
+    let args = statement.args.as_deref().unwrap_or_default();
+    let Some((sequence, args)) = args
+        .split_first()
+        .filter(|(s, _)| s.data_type == DataType::Utf8)
+    else {
+        return Err(DataFusionError::Plan(format!(
+            "PSSM function {} takes a VARCHAR sequence as its first argument",
+            name
+        )));
+    };
+
+    if sequence.default_expr.is_some() {
+        return Err(DataFusionError::Plan(format!(
+            "PSSM function {} can't have a default sequence",
+            name
+        )));
+    }
+
+    let parameters = PssmParameters::try_from_args(name, args)?;
+
+    let motifs = read_motifs(state, path)
+        .await?
+        .iter()
+        .map(|counts| {
+            Motif::new(
+                counts,
+                parameters.pseudo_count,
+                parameters.background,
+                parameters.both_strands,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let is_library = motifs.len() > 1 || parameters.all_matches;
+
+    let return_type = if is_library {
+        PssmLibraryUdf::data_type(parameters.all_matches)
+    } else {
+        DataType::Float64
+    };
+
+    if let Some(declared) = &statement.return_type {
+        if *declared != return_type {
+            return Err(DataFusionError::Plan(format!(
+                "PSSM function {} returns {}, not {}, leave out RETURNS to use it",
+                name, return_type, declared
+            )));
+        }
+    }
+
+    if is_library {
+        return Ok(ScalarUDF::from(PssmLibraryUdf::new(
+            name.clone(),
+            motifs,
+            parameters.threshold,
+            parameters.all_matches,
+        )));
+    }
+
+    let Some(motif) = motifs.into_iter().next() else {
+        return Err(DataFusionError::Plan(format!(
+            "PSSM function {} needs a motif file with a matrix",
+            name
+        )));
+    };
+
+    Ok(ScalarUDF::from(PssmUdf::new(
+        name.clone(),
+        motif,
+        parameters.threshold,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::datatypes::DataType;
+    use datafusion::{
+        logical_expr::{lit, OperateFunctionArg},
+        sql::sqlparser::ast::Ident,
+    };
+
+    use super::{parse_background, PssmParameters};
+
+    fn arg(name: &str, default_expr: datafusion::logical_expr::Expr) -> OperateFunctionArg {
+        OperateFunctionArg {
+            name: Some(Ident::new(name)),
+            data_type: DataType::Float64,
+            default_expr: Some(default_expr),
+        }
+    }
+
+    #[test]
+    fn test_pssm_parameters() -> Result<(), Box<dyn std::error::Error>> {
+        let parameters = PssmParameters::try_from_args(
+            "motif",
+            &[
+                arg("PSEUDO_COUNT", lit(1)),
+                arg("background", lit("3,2,2,3")),
+                arg("threshold", lit(-2.5)),
+                arg("both_strands", lit(true)),
+            ],
+        )?;
+
+        assert_eq!(
+            parameters,
+            PssmParameters {
+                pseudo_count: 1.0,
+                background: [0.3, 0.2, 0.2, 0.3],
+                threshold: Some(-2.5),
+                both_strands: true,
+                all_matches: false,
+            }
+        );
+
+        assert!(PssmParameters::try_from_args("motif", &[arg("pseudo_count", lit(-1.0))]).is_err());
+        assert!(PssmParameters::try_from_args("motif", &[arg("window", lit(5))]).is_err());
+        assert!(PssmParameters::try_from_args("motif", &[arg("both_strands", lit(1))]).is_err());
+
+        assert_eq!(parse_background("0.25 0.25 0.25 0.25"), Some([0.25; 4]));
+        assert_eq!(parse_background("0.5,0.5,0"), None);
+        assert_eq!(parse_background("0.5,0.5,0,0"), None);
+
+        Ok(())
+    }
+}
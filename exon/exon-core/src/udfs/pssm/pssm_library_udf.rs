@@ -0,0 +1,178 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use arrow::{
+    array::{AsArray, Float64Builder, ListBuilder, StringBuilder, StructBuilder},
+    compute::cast,
+    datatypes::{DataType, Field, Fields},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::matrix::{encode_sequence, Motif};
+
+/// The fields of a motif match, the name of the motif and its best score.
+fn match_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("motif", DataType::Utf8, true),
+        Field::new("score", DataType::Float64, true),
+    ])
+}
+
+/// Returns the motif of a library of position-specific scoring matrices that best matches a DNA
+/// sequence as a `{motif, score}` struct, a UDF created with `CREATE FUNCTION ... LANGUAGE PSSM`
+/// from a motif file with more than one matrix.
+///
+/// Only motifs scoring at least the threshold match, and sequences without a match are null. With
+/// `all_matches`, the function returns a list of every matching motif instead, from the best
+/// score down.
+#[derive(Debug)]
+pub(crate) struct PssmLibraryUdf {
+    name: String,
+    signature: Signature,
+    motifs: Vec<Motif>,
+    threshold: Option<f64>,
+    all_matches: bool,
+}
+
+impl PssmLibraryUdf {
+    pub(crate) fn new(
+        name: String,
+        motifs: Vec<Motif>,
+        threshold: Option<f64>,
+        all_matches: bool,
+    ) -> Self {
+        let signature = Signature::exact(vec![DataType::Utf8], Volatility::Immutable);
+
+        Self {
+            name,
+            signature,
+            motifs,
+            threshold,
+            all_matches,
+        }
+    }
+
+    /// The type the function returns.
+    pub(crate) fn data_type(all_matches: bool) -> DataType {
+        let item = DataType::Struct(match_fields());
+
+        if all_matches {
+            DataType::List(Arc::new(Field::new("item", item, true)))
+        } else {
+            item
+        }
+    }
+
+    /// The motifs matching a sequence and their scores, from the best score down, ties in the
+    /// order of the motif file.
+    fn matches(&self, sequence: &[u8]) -> Vec<(&str, f64)> {
+        let bases = encode_sequence(sequence);
+
+        let mut matches = self
+            .motifs
+            .iter()
+            .filter_map(|motif| Some((motif.name(), motif.best_score(&bases)?)))
+            .filter(|(_, score)| self.threshold.is_none_or(|threshold| *score >= threshold))
+            .collect::<Vec<_>>();
+
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        matches
+    }
+}
+
+fn append_match(
+    builder: &mut StructBuilder,
+    motif: Option<&str>,
+    score: Option<f64>,
+) -> Result<()> {
+    builder
+        .field_builder::<StringBuilder>(0)
+        .ok_or_else(|| DataFusionError::Internal("Invalid motif field builder".to_string()))?
+        .append_option(motif);
+
+    builder
+        .field_builder::<Float64Builder>(1)
+        .ok_or_else(|| DataFusionError::Internal("Invalid score field builder".to_string()))?
+        .append_option(score);
+
+    builder.append(motif.is_some());
+
+    Ok(())
+}
+
+impl ScalarUDFImpl for PssmLibraryUdf {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(Self::data_type(self.all_matches))
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 1 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes one argument",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = cast(&arrays[0], &DataType::Utf8)?;
+        let sequences = sequences.as_string::<i32>();
+
+        if self.all_matches {
+            let mut builder = ListBuilder::new(StructBuilder::from_fields(match_fields(), 0));
+
+            for sequence in sequences.iter() {
+                let Some(sequence) = sequence else {
+                    builder.append_null();
+                    continue;
+                };
+
+                for (motif, score) in self.matches(sequence.as_bytes()) {
+                    append_match(builder.values(), Some(motif), Some(score))?;
+                }
+
+                builder.append(true);
+            }
+
+            return Ok(ColumnarValue::Array(Arc::new(builder.finish())));
+        }
+
+        let mut builder = StructBuilder::from_fields(match_fields(), sequences.len());
+
+        for sequence in sequences.iter() {
+            let best = sequence.and_then(|s| self.matches(s.as_bytes()).first().copied());
+
+            append_match(&mut builder, best.map(|b| b.0), best.map(|b| b.1))?;
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(builder.finish())))
+    }
+}
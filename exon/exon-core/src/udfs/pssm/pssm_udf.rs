@@ -0,0 +1,98 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use arrow::{
+    array::{AsArray, Float64Array},
+    compute::cast,
+    datatypes::DataType,
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::matrix::{encode_sequence, Motif};
+
+/// Returns the best score of a position-specific scoring matrix over the windows of a DNA
+/// sequence, a UDF created with `CREATE FUNCTION ... LANGUAGE PSSM`.
+///
+/// The scoring matrices are built once when the function is created. Sequences shorter than the
+/// motif, without a window of only ACGT bases, or whose best score is below the threshold are
+/// null.
+#[derive(Debug)]
+pub(crate) struct PssmUdf {
+    name: String,
+    signature: Signature,
+    motif: Motif,
+    threshold: Option<f64>,
+}
+
+impl PssmUdf {
+    pub(crate) fn new(name: String, motif: Motif, threshold: Option<f64>) -> Self {
+        let signature = Signature::exact(vec![DataType::Utf8], Volatility::Immutable);
+
+        Self {
+            name,
+            signature,
+            motif,
+            threshold,
+        }
+    }
+
+    /// The best score of the motif on a sequence, if it's at least the threshold.
+    fn score(&self, sequence: &[u8]) -> Option<f64> {
+        self.motif
+            .best_score(&encode_sequence(sequence))
+            .filter(|score| self.threshold.is_none_or(|threshold| *score >= threshold))
+    }
+}
+
+impl ScalarUDFImpl for PssmUdf {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 1 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes one argument",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = cast(&arrays[0], &DataType::Utf8)?;
+        let scores = sequences
+            .as_string::<i32>()
+            .iter()
+            .map(|sequence| sequence.and_then(|s| self.score(s.as_bytes())))
+            .collect::<Float64Array>();
+
+        Ok(ColumnarValue::Array(Arc::new(scores)))
+    }
+}
@@ -0,0 +1,305 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use datafusion::error::{DataFusionError, Result};
+use noodles::fasta::fai;
+
+/// The number of bases in a cached page.
+const DEFAULT_PAGE_SIZE: u64 = 16 * 1024;
+
+/// The number of pages kept in the cache.
+const DEFAULT_CAPACITY: usize = 256;
+
+/// A FASTA file and its index.
+#[derive(Debug)]
+struct IndexedReference {
+    path: PathBuf,
+    records: HashMap<String, fai::Record>,
+}
+
+impl IndexedReference {
+    fn try_new(path: &str) -> Result<Self> {
+        let path = PathBuf::from(path.strip_prefix("file://").unwrap_or(path));
+
+        let mut index_path = path.clone().into_os_string();
+        index_path.push(".fai");
+
+        let index = fai::read(&index_path).map_err(|e| {
+            DataFusionError::Execution(format!(
+                "Unable to read the FASTA index {}: {}",
+                index_path.to_string_lossy(),
+                e
+            ))
+        })?;
+
+        let records = Vec::from(index)
+            .into_iter()
+            .map(|record| (String::from_utf8_lossy(record.name()).to_string(), record))
+            .collect();
+
+        Ok(Self { path, records })
+    }
+
+    /// The byte offset of the 0-based position `pos` of a sequence.
+    fn byte_offset(record: &fai::Record, pos: u64) -> u64 {
+        record.offset()
+            + pos / record.line_bases() * record.line_width()
+            + pos % record.line_bases()
+    }
+
+    /// Read the bases in the 0-based, half-open range `start..end` of a sequence.
+    fn read(&self, record: &fai::Record, start: u64, end: u64) -> Result<Vec<u8>> {
+        if start >= end {
+            return Ok(Vec::new());
+        }
+
+        let first = Self::byte_offset(record, start);
+        let last = Self::byte_offset(record, end - 1);
+
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(first))?;
+
+        let mut buf = vec![0; (last - first + 1) as usize];
+        file.read_exact(&mut buf)?;
+
+        buf.retain(|b| *b != b'\n' && *b != b'\r');
+
+        if buf.len() as u64 != end - start {
+            return Err(DataFusionError::Execution(format!(
+                "The FASTA index of {} doesn't match the file",
+                self.path.display()
+            )));
+        }
+
+        Ok(buf)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PageKey {
+    path: String,
+    name: String,
+    page: u64,
+}
+
+/// A least recently used cache of fixed size pages of the sequences of indexed FASTA files.
+///
+/// Only local files are supported, the paths may be prefixed with `file://`.
+#[derive(Debug)]
+pub(crate) struct ReferenceCache {
+    page_size: u64,
+    capacity: usize,
+    references: HashMap<String, Arc<IndexedReference>>,
+    pages: HashMap<PageKey, (Arc<[u8]>, u64)>,
+    tick: u64,
+}
+
+impl Default for ReferenceCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAGE_SIZE, DEFAULT_CAPACITY)
+    }
+}
+
+impl ReferenceCache {
+    /// Create a cache of `capacity` pages of `page_size` bases.
+    pub(crate) fn new(page_size: u64, capacity: usize) -> Self {
+        Self {
+            page_size: page_size.max(1),
+            capacity: capacity.max(1),
+            references: HashMap::new(),
+            pages: HashMap::new(),
+            tick: 0,
+        }
+    }
+
+    fn reference(&mut self, path: &str) -> Result<Arc<IndexedReference>> {
+        if let Some(reference) = self.references.get(path) {
+            return Ok(Arc::clone(reference));
+        }
+
+        let reference = Arc::new(IndexedReference::try_new(path)?);
+        self.references
+            .insert(path.to_string(), Arc::clone(&reference));
+
+        Ok(reference)
+    }
+
+    /// The length of a sequence, or `None` if it isn't in the index.
+    pub(crate) fn sequence_length(&mut self, path: &str, name: &str) -> Result<Option<u64>> {
+        let reference = self.reference(path)?;
+
+        Ok(reference.records.get(name).map(|record| record.length()))
+    }
+
+    fn page(&mut self, path: &str, name: &str, page: u64) -> Result<Option<Arc<[u8]>>> {
+        self.tick += 1;
+
+        let key = PageKey {
+            path: path.to_string(),
+            name: name.to_string(),
+            page,
+        };
+
+        if let Some((bases, last_used)) = self.pages.get_mut(&key) {
+            *last_used = self.tick;
+            return Ok(Some(Arc::clone(bases)));
+        }
+
+        let reference = self.reference(path)?;
+        let Some(record) = reference.records.get(name) else {
+            return Ok(None);
+        };
+
+        let start = page * self.page_size;
+        let end = (start + self.page_size).min(record.length());
+        let bases: Arc<[u8]> = reference.read(record, start, end)?.into();
+
+        if self.pages.len() >= self.capacity {
+            let least_recently_used = self
+                .pages
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone());
+
+            if let Some(key) = least_recently_used {
+                self.pages.remove(&key);
+            }
+        }
+
+        self.pages.insert(key, (Arc::clone(&bases), self.tick));
+
+        Ok(Some(bases))
+    }
+
+    /// The base at the 0-based position `pos` of a sequence, or `None` if it's out of range.
+    pub(crate) fn base(&mut self, path: &str, name: &str, pos: u64) -> Result<Option<u8>> {
+        let page = self.page(path, name, pos / self.page_size)?;
+
+        Ok(page.and_then(|page| page.get((pos % self.page_size) as usize).copied()))
+    }
+
+    /// The bases in the 0-based, half-open range `start..end` of a sequence, clipped to its length.
+    pub(crate) fn bases(
+        &mut self,
+        path: &str,
+        name: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Option<Vec<u8>>> {
+        let Some(length) = self.sequence_length(path, name)? else {
+            return Ok(None);
+        };
+
+        let end = end.min(length);
+        let mut bases = Vec::with_capacity(end.saturating_sub(start) as usize);
+
+        let mut pos = start;
+        while pos < end {
+            let Some(page) = self.page(path, name, pos / self.page_size)? else {
+                return Ok(None);
+            };
+
+            let offset = (pos % self.page_size) as usize;
+            let n = ((end - pos) as usize).min(page.len() - offset);
+
+            bases.extend_from_slice(&page[offset..offset + n]);
+            pos += n as u64;
+        }
+
+        Ok(Some(bases))
+    }
+
+    /// The length of the run of identical bases that contains the 0-based position `pos`, or
+    /// `None` if the position is out of range.
+    pub(crate) fn homopolymer_length(
+        &mut self,
+        path: &str,
+        name: &str,
+        pos: u64,
+    ) -> Result<Option<u64>> {
+        let Some(base) = self.base(path, name, pos)? else {
+            return Ok(None);
+        };
+
+        let base = base.to_ascii_uppercase();
+        let mut length = 1;
+
+        let mut left = pos;
+        while left > 0 {
+            match self.base(path, name, left - 1)? {
+                Some(b) if b.to_ascii_uppercase() == base => {
+                    length += 1;
+                    left -= 1;
+                }
+                _ => break,
+            }
+        }
+
+        let mut right = pos + 1;
+        while let Some(b) = self.base(path, name, right)? {
+            if b.to_ascii_uppercase() != base {
+                break;
+            }
+
+            length += 1;
+            right += 1;
+        }
+
+        Ok(Some(length))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReferenceCache;
+
+    #[test]
+    fn test_reference_cache() -> Result<(), Box<dyn std::error::Error>> {
+        let path = exon_test::test_path("reference", "ref.fa");
+        let path = path.to_str().ok_or("invalid path")?;
+
+        // Small pages so that runs and ranges cross both page and line boundaries.
+        let mut cache = ReferenceCache::new(4, 2);
+
+        assert_eq!(cache.sequence_length(path, "chr1")?, Some(23));
+        assert_eq!(cache.sequence_length(path, "chrX")?, None);
+
+        assert_eq!(
+            cache.bases(path, "chr1", 0, 23)?,
+            Some(b"ACGTAAAAACCCCCGTTTTTTTG".to_vec())
+        );
+        assert_eq!(cache.bases(path, "chr1", 6, 13)?, Some(b"AAACCCC".to_vec()));
+        assert_eq!(cache.bases(path, "chr1", 19, 30)?, Some(b"TTTG".to_vec()));
+        assert_eq!(cache.bases(path, "chr2", 0, 4)?, Some(b"NNNN".to_vec()));
+
+        assert_eq!(cache.homopolymer_length(path, "chr1", 0)?, Some(1));
+        assert_eq!(cache.homopolymer_length(path, "chr1", 6)?, Some(5));
+        assert_eq!(cache.homopolymer_length(path, "chr1", 11)?, Some(5));
+        assert_eq!(cache.homopolymer_length(path, "chr1", 19)?, Some(7));
+        assert_eq!(cache.homopolymer_length(path, "chr1", 23)?, None);
+        assert_eq!(cache.homopolymer_length(path, "chrX", 0)?, None);
+
+        assert!(cache.pages.len() <= 2);
+
+        Ok(())
+    }
+}
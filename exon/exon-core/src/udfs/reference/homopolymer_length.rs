@@ -0,0 +1,88 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, Mutex};
+
+use arrow::{array::Int64Array, datatypes::DataType};
+use datafusion::{
+    error::Result,
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::{cache::ReferenceCache, lock, ReferenceArguments};
+
+/// Returns the length of the run of identical reference bases that contains a position, e.g.
+/// `homopolymer_length('ref.fa', 'chr1', 100)`.
+///
+/// The comparison is case-insensitive, so soft-masked bases extend a run.
+pub(crate) struct HomopolymerLength {
+    signature: Signature,
+    cache: Arc<Mutex<ReferenceCache>>,
+}
+
+impl std::fmt::Debug for HomopolymerLength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HomopolymerLength").finish()
+    }
+}
+
+impl HomopolymerLength {
+    pub(crate) fn new(cache: Arc<Mutex<ReferenceCache>>) -> Self {
+        let signature = Signature::coercible(
+            vec![DataType::Utf8, DataType::Utf8, DataType::Int64],
+            Volatility::Immutable,
+        );
+
+        Self { signature, cache }
+    }
+}
+
+impl ScalarUDFImpl for HomopolymerLength {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "homopolymer_length"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Int64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arguments = ReferenceArguments::try_new(self.name(), args, 1, 0)?;
+        let mut cache = lock(&self.cache)?;
+
+        let mut lengths = Vec::with_capacity(arguments.len());
+
+        for i in 0..arguments.len() {
+            let length = match arguments.row(i) {
+                Some((path, name, integers)) if integers[0] >= 1 => {
+                    let length = cache.homopolymer_length(path, name, integers[0] as u64 - 1)?;
+                    length.map(|length| length as i64)
+                }
+                _ => None,
+            };
+
+            lengths.push(length);
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(Int64Array::from(lengths))))
+    }
+}
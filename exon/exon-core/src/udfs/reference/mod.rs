@@ -0,0 +1,147 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! UDFs for the sequence context of positions in an indexed reference FASTA, e.g. for variant
+//! filters.
+//!
+//! The reference is read through its `.fai` index and a shared LRU cache of sequence pages, so
+//! only the pages around the queried positions are read. Positions are 1-based like VCF, and
+//! positions or sequences not in the reference are null.
+
+mod cache;
+mod homopolymer_length;
+mod mutational_signature;
+mod sequence_context;
+mod trinucleotide_context;
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, Int64Array, StringArray},
+    compute::cast,
+    datatypes::{DataType, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::context::SessionContext,
+    logical_expr::{AggregateUDF, ColumnarValue, ScalarUDF},
+};
+
+use self::cache::ReferenceCache;
+
+/// Register the reference UDFs, which share a page cache, and the mutational signature aggregate.
+pub fn register_udfs(ctx: &SessionContext) {
+    let cache = Arc::new(Mutex::new(ReferenceCache::default()));
+
+    ctx.register_udf(ScalarUDF::from(homopolymer_length::HomopolymerLength::new(
+        Arc::clone(&cache),
+    )));
+    ctx.register_udf(ScalarUDF::from(sequence_context::SequenceContext::new(
+        Arc::clone(&cache),
+    )));
+    ctx.register_udf(ScalarUDF::from(
+        trinucleotide_context::TrinucleotideContext::new(cache),
+    ));
+
+    ctx.register_udaf(AggregateUDF::from(
+        mutational_signature::MutationalSignature::default(),
+    ));
+}
+
+/// The arguments of a reference UDF, the reference path and sequence name followed by integers
+/// and then strings.
+struct ReferenceArguments {
+    paths: StringArray,
+    names: StringArray,
+    integers: Vec<Int64Array>,
+    strings: Vec<StringArray>,
+}
+
+impl ReferenceArguments {
+    fn try_new(
+        name: &str,
+        args: &[ColumnarValue],
+        n_integers: usize,
+        n_strings: usize,
+    ) -> Result<Self> {
+        let n = 2 + n_integers + n_strings;
+
+        if args.len() != n {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes {} arguments",
+                name, n
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let string = |array: &ArrayRef| -> Result<StringArray> {
+            Ok(cast(array, &DataType::Utf8)?.as_string::<i32>().clone())
+        };
+
+        let integers = arrays[2..2 + n_integers]
+            .iter()
+            .map(|array| {
+                Ok(cast(array, &DataType::Int64)?
+                    .as_primitive::<Int64Type>()
+                    .clone())
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let strings = arrays[2 + n_integers..]
+            .iter()
+            .map(string)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            paths: string(&arrays[0])?,
+            names: string(&arrays[1])?,
+            integers,
+            strings,
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// The path, sequence name, and integers of row `i`, or `None` if any of them is null.
+    fn row(&self, i: usize) -> Option<(&str, &str, Vec<i64>)> {
+        if self.paths.is_null(i) || self.names.is_null(i) {
+            return None;
+        }
+
+        let integers = self
+            .integers
+            .iter()
+            .map(|array| array.is_valid(i).then(|| array.value(i)))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some((self.paths.value(i), self.names.value(i), integers))
+    }
+
+    /// The trailing string arguments of row `i`, or `None` if any of them is null.
+    fn strings(&self, i: usize) -> Option<Vec<&str>> {
+        self.strings
+            .iter()
+            .map(|array| array.is_valid(i).then(|| array.value(i)))
+            .collect()
+    }
+}
+
+fn lock(cache: &Mutex<ReferenceCache>) -> Result<MutexGuard<'_, ReferenceCache>> {
+    cache
+        .lock()
+        .map_err(|_| DataFusionError::Execution("The reference cache is poisoned".to_string()))
+}
@@ -0,0 +1,152 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, ListArray},
+    datatypes::{DataType, Field, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{
+        function::AccumulatorArgs, Accumulator, AggregateUDFImpl, Signature, Volatility,
+    },
+    scalar::ScalarValue,
+};
+
+use super::trinucleotide_context::{signature_class_index, N_SIGNATURE_CLASSES};
+
+fn signature_type() -> DataType {
+    DataType::List(Arc::new(Field::new("item", DataType::Int64, true)))
+}
+
+/// An aggregate that counts trinucleotide context labels into the 96 bins of a mutational
+/// signature, e.g. `mutational_signature(trinucleotide_context(...))`.
+///
+/// The bins are in the canonical order from `A[C>A]A` to `T[T>G]T`, nulls and other labels are
+/// ignored.
+#[derive(Debug)]
+pub(crate) struct MutationalSignature {
+    signature: Signature,
+}
+
+impl Default for MutationalSignature {
+    fn default() -> Self {
+        let signature = Signature::exact(vec![DataType::Utf8], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl AggregateUDFImpl for MutationalSignature {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "mutational_signature"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(signature_type())
+    }
+
+    fn accumulator(&self, _acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(MutationalSignatureAccumulator::default()))
+    }
+}
+
+#[derive(Debug)]
+struct MutationalSignatureAccumulator {
+    counts: [i64; N_SIGNATURE_CLASSES],
+}
+
+impl Default for MutationalSignatureAccumulator {
+    fn default() -> Self {
+        Self {
+            counts: [0; N_SIGNATURE_CLASSES],
+        }
+    }
+}
+
+impl MutationalSignatureAccumulator {
+    fn to_scalar(&self) -> ScalarValue {
+        let values = self.counts.iter().map(|count| Some(*count));
+        let list = ListArray::from_iter_primitive::<Int64Type, _, _>(vec![Some(values)]);
+
+        ScalarValue::List(Arc::new(list))
+    }
+}
+
+impl Accumulator for MutationalSignatureAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let labels = values[0].as_string_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution("mutational_signature takes string labels".to_string())
+        })?;
+
+        for label in labels.iter().flatten() {
+            if let Some(i) = signature_class_index(label) {
+                self.counts[i] += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        let states = states[0].as_list_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution("mutational_signature state should be a list".to_string())
+        })?;
+
+        for i in 0..states.len() {
+            if states.is_null(i) {
+                continue;
+            }
+
+            let counts = states.value(i);
+            let counts = counts.as_primitive::<Int64Type>();
+
+            if counts.len() != N_SIGNATURE_CLASSES {
+                return Err(DataFusionError::Execution(format!(
+                    "mutational_signature state should have {} bins, got {}",
+                    N_SIGNATURE_CLASSES,
+                    counts.len()
+                )));
+            }
+
+            for (count, other) in self.counts.iter_mut().zip(counts.values().iter()) {
+                *count += other;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![self.to_scalar()])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        Ok(self.to_scalar())
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
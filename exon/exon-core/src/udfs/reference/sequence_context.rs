@@ -0,0 +1,115 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, Mutex};
+
+use arrow::{array::StringArray, datatypes::DataType};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::{cache::ReferenceCache, lock, ReferenceArguments};
+
+/// Returns the reference bases from `flank` bases before a position to `flank` bases after it,
+/// clipped to the sequence, e.g. `sequence_context('ref.fa', 'chr1', 100, 5)` is 11 bases.
+pub(crate) struct SequenceContext {
+    signature: Signature,
+    cache: Arc<Mutex<ReferenceCache>>,
+}
+
+impl std::fmt::Debug for SequenceContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SequenceContext").finish()
+    }
+}
+
+impl SequenceContext {
+    pub(crate) fn new(cache: Arc<Mutex<ReferenceCache>>) -> Self {
+        let signature = Signature::coercible(
+            vec![
+                DataType::Utf8,
+                DataType::Utf8,
+                DataType::Int64,
+                DataType::Int64,
+            ],
+            Volatility::Immutable,
+        );
+
+        Self { signature, cache }
+    }
+}
+
+impl ScalarUDFImpl for SequenceContext {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "sequence_context"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Utf8)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arguments = ReferenceArguments::try_new(self.name(), args, 2, 0)?;
+        let mut cache = lock(&self.cache)?;
+
+        let mut contexts = Vec::with_capacity(arguments.len());
+
+        for i in 0..arguments.len() {
+            let Some((path, name, integers)) = arguments.row(i) else {
+                contexts.push(None);
+                continue;
+            };
+
+            let (pos, flank) = (integers[0], integers[1]);
+
+            if flank < 0 {
+                return Err(DataFusionError::Execution(format!(
+                    "{} requires a non-negative flank, got {}",
+                    self.name(),
+                    flank
+                )));
+            }
+
+            let in_range = match cache.sequence_length(path, name)? {
+                Some(length) => pos >= 1 && pos as u64 <= length,
+                None => false,
+            };
+
+            if !in_range {
+                contexts.push(None);
+                continue;
+            }
+
+            let start = (pos - 1).saturating_sub(flank).max(0) as u64;
+            let end = pos.saturating_add(flank) as u64;
+
+            let context = cache
+                .bases(path, name, start, end)?
+                .map(|bases| String::from_utf8_lossy(&bases).to_string());
+
+            contexts.push(context);
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(StringArray::from(contexts))))
+    }
+}
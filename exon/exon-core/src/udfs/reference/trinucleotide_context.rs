@@ -0,0 +1,227 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, Mutex};
+
+use arrow::{array::StringArray, datatypes::DataType};
+use datafusion::{
+    error::Result,
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::{cache::ReferenceCache, lock, ReferenceArguments};
+
+/// The bases of a trinucleotide context in the order of the signature classes.
+const BASES: [u8; 4] = *b"ACGT";
+
+/// The pyrimidine substitutions in the order of the signature classes.
+const SUBSTITUTIONS: [(u8, u8); 6] = [
+    (b'C', b'A'),
+    (b'C', b'G'),
+    (b'C', b'T'),
+    (b'T', b'A'),
+    (b'T', b'C'),
+    (b'T', b'G'),
+];
+
+/// The number of trinucleotide substitution classes.
+pub(super) const N_SIGNATURE_CLASSES: usize = 96;
+
+fn complement(base: u8) -> u8 {
+    match base {
+        b'A' => b'T',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'T' => b'A',
+        base => base,
+    }
+}
+
+/// The pyrimidine-normalized label of a substitution in its trinucleotide context, e.g. `A[C>T]G`.
+///
+/// Substitutions of a purine are reverse complemented, and anything but a substitution between
+/// two different unambiguous bases has no label.
+pub(super) fn trinucleotide_label(
+    context: [u8; 3],
+    reference: u8,
+    alternate: u8,
+) -> Option<String> {
+    let context = context.map(|b| b.to_ascii_uppercase());
+    let (reference, alternate) = (
+        reference.to_ascii_uppercase(),
+        alternate.to_ascii_uppercase(),
+    );
+
+    if !context
+        .iter()
+        .chain([&reference, &alternate])
+        .all(|b| BASES.contains(b))
+        || reference == alternate
+        || context[1] != reference
+    {
+        return None;
+    }
+
+    let (five_prime, reference, alternate, three_prime) = match reference {
+        b'C' | b'T' => (context[0], reference, alternate, context[2]),
+        _ => (
+            complement(context[2]),
+            complement(reference),
+            complement(alternate),
+            complement(context[0]),
+        ),
+    };
+
+    Some(format!(
+        "{}[{}>{}]{}",
+        five_prime as char, reference as char, alternate as char, three_prime as char
+    ))
+}
+
+/// The index of a label in the canonical order of the 96 classes, i.e. by substitution, then 5'
+/// base, then 3' base, from `A[C>A]A` to `T[T>G]T`.
+pub(super) fn signature_class_index(label: &str) -> Option<usize> {
+    let &[five_prime, b'[', reference, b'>', alternate, b']', three_prime] = label.as_bytes()
+    else {
+        return None;
+    };
+
+    let substitution = SUBSTITUTIONS
+        .iter()
+        .position(|s| *s == (reference, alternate))?;
+    let five_prime = BASES.iter().position(|b| *b == five_prime)?;
+    let three_prime = BASES.iter().position(|b| *b == three_prime)?;
+
+    Some(substitution * 16 + five_prime * 4 + three_prime)
+}
+
+/// Returns the pyrimidine-normalized trinucleotide context label of a single nucleotide variant,
+/// e.g. `trinucleotide_context('ref.fa', 'chr1', 100, 'G', 'A')` is `T[C>T]A` if the reference
+/// is `TGA` around position 100.
+///
+/// Variants that aren't substitutions, whose reference base doesn't match the reference, or that
+/// are at the ends of a sequence have no context and are null.
+pub(crate) struct TrinucleotideContext {
+    signature: Signature,
+    cache: Arc<Mutex<ReferenceCache>>,
+}
+
+impl std::fmt::Debug for TrinucleotideContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TrinucleotideContext").finish()
+    }
+}
+
+impl TrinucleotideContext {
+    pub(crate) fn new(cache: Arc<Mutex<ReferenceCache>>) -> Self {
+        let signature = Signature::coercible(
+            vec![
+                DataType::Utf8,
+                DataType::Utf8,
+                DataType::Int64,
+                DataType::Utf8,
+                DataType::Utf8,
+            ],
+            Volatility::Immutable,
+        );
+
+        Self { signature, cache }
+    }
+}
+
+impl ScalarUDFImpl for TrinucleotideContext {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "trinucleotide_context"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Utf8)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arguments = ReferenceArguments::try_new(self.name(), args, 1, 2)?;
+        let mut cache = lock(&self.cache)?;
+
+        let mut labels = Vec::with_capacity(arguments.len());
+
+        for i in 0..arguments.len() {
+            let (Some((path, name, integers)), Some(strings)) =
+                (arguments.row(i), arguments.strings(i))
+            else {
+                labels.push(None);
+                continue;
+            };
+
+            let (pos, reference, alternate) = (integers[0], strings[0], strings[1]);
+
+            let (&[reference], &[alternate]) = (reference.as_bytes(), alternate.as_bytes()) else {
+                labels.push(None);
+                continue;
+            };
+
+            if pos < 2 {
+                labels.push(None);
+                continue;
+            }
+
+            let start = pos as u64 - 2;
+            let context = cache
+                .bases(path, name, start, start + 3)?
+                .and_then(|bases| <[u8; 3]>::try_from(bases).ok());
+
+            labels.push(
+                context.and_then(|context| trinucleotide_label(context, reference, alternate)),
+            );
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(StringArray::from(labels))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{signature_class_index, trinucleotide_label};
+
+    #[test]
+    fn test_trinucleotide_label() {
+        assert_eq!(
+            trinucleotide_label(*b"ACG", b'C', b'T'),
+            Some("A[C>T]G".to_string())
+        );
+        assert_eq!(
+            trinucleotide_label(*b"tga", b'G', b'A'),
+            Some("T[C>T]A".to_string())
+        );
+        assert_eq!(trinucleotide_label(*b"ACG", b'A', b'T'), None);
+        assert_eq!(trinucleotide_label(*b"ACG", b'C', b'C'), None);
+        assert_eq!(trinucleotide_label(*b"ANG", b'N', b'T'), None);
+    }
+
+    #[test]
+    fn test_signature_class_index() {
+        assert_eq!(signature_class_index("A[C>A]A"), Some(0));
+        assert_eq!(signature_class_index("A[C>T]G"), Some(34));
+        assert_eq!(signature_class_index("T[T>G]T"), Some(95));
+        assert_eq!(signature_class_index("A[G>T]A"), None);
+        assert_eq!(signature_class_index("ACG"), None);
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Regex extract UDFs, created from a regular expression with named groups with e.g.
+//!
+//! ```sql
+//! CREATE FUNCTION read_name(VARCHAR) LANGUAGE REGEX_EXTRACT
+//!     AS '^(?P<instrument>[^:]+):(?P<run>\d+):(?P<flowcell>[^:]+)';
+//! ```
+//!
+//! The function returns a struct with a string field for each named group of the expression, in
+//! the order of the groups. Strings the expression doesn't match are null, as are groups that
+//! don't take part in a match.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, AsArray, StringBuilder, StructBuilder},
+    compute::cast,
+    datatypes::{DataType, Field, Fields},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{
+        ColumnarValue, CreateFunction, ScalarUDF, ScalarUDFImpl, Signature, Volatility,
+    },
+};
+use regex::Regex;
+
+use super::create_function::function_body;
+
+/// Returns the named groups of a regular expression in a string as a struct, a UDF created with
+/// `CREATE FUNCTION ... LANGUAGE REGEX_EXTRACT`.
+#[derive(Debug)]
+pub(crate) struct RegexExtractUdf {
+    name: String,
+    signature: Signature,
+    regex: Regex,
+    fields: Fields,
+}
+
+impl RegexExtractUdf {
+    fn try_new(name: String, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern).map_err(|e| {
+            DataFusionError::Plan(format!(
+                "Regex extract function {} has an invalid pattern: {}",
+                name, e
+            ))
+        })?;
+
+        let fields = regex
+            .capture_names()
+            .flatten()
+            .map(|group| Field::new(group, DataType::Utf8, true))
+            .collect::<Fields>();
+
+        if fields.is_empty() {
+            return Err(DataFusionError::Plan(format!(
+                "Regex extract function {} needs a pattern with named groups, e.g. (?P<name>...)",
+                name
+            )));
+        }
+
+        Ok(Self {
+            name,
+            signature: Signature::exact(vec![DataType::Utf8], Volatility::Immutable),
+            regex,
+            fields,
+        })
+    }
+
+    /// The type the function returns.
+    fn data_type(&self) -> DataType {
+        DataType::Struct(self.fields.clone())
+    }
+}
+
+impl ScalarUDFImpl for RegexExtractUdf {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(self.data_type())
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 1 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes one argument",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let strings = cast(&arrays[0], &DataType::Utf8)?;
+        let strings = strings.as_string::<i32>();
+
+        let mut builder = StructBuilder::from_fields(self.fields.clone(), strings.len());
+
+        for string in strings.iter() {
+            let captures = string.and_then(|s| self.regex.captures(s));
+
+            for (i, field) in self.fields.iter().enumerate() {
+                let group = captures
+                    .as_ref()
+                    .and_then(|captures| captures.name(field.name()))
+                    .map(|group| group.as_str());
+
+                builder
+                    .field_builder::<StringBuilder>(i)
+                    .ok_or_else(|| {
+                        DataFusionError::Internal(format!("Invalid {} field builder", field.name()))
+                    })?
+                    .append_option(group);
+            }
+
+            builder.append(captures.is_some());
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(builder.finish())))
+    }
+}
+
+/// Create a regex extract UDF from a `CREATE FUNCTION ... LANGUAGE REGEX_EXTRACT AS 'pattern'`
+/// statement.
+pub(crate) fn create_regex_extract_udf(statement: &CreateFunction) -> Result<ScalarUDF> {
+    let name = &statement.name;
+
+    let Some(pattern) = function_body(statement) else {
+        return Err(DataFusionError::Plan(format!(
+            "Regex extract function {} needs a pattern, e.g. AS '(?P<sample>[^_]+)_'",
+            name
+        )));
+    };
+
+    let args = statement.args.as_deref().unwrap_or_default();
+    if args.len() != 1 || args[0].data_type != DataType::Utf8 {
+        return Err(DataFusionError::Plan(format!(
+            "Regex extract function {} takes one VARCHAR argument",
+            name
+        )));
+    }
+
+    let udf = RegexExtractUdf::try_new(name.clone(), pattern)?;
+
+    if let Some(declared) = &statement.return_type {
+        if *declared != udf.data_type() {
+            return Err(DataFusionError::Plan(format!(
+                "Regex extract function {} returns {}, not {}, leave out RETURNS to use it",
+                name,
+                udf.data_type(),
+                declared
+            )));
+        }
+    }
+
+    Ok(ScalarUDF::from(udf))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Array, AsArray, StringArray};
+    use datafusion::logical_expr::{ColumnarValue, ScalarUDFImpl};
+
+    use super::RegexExtractUdf;
+
+    #[test]
+    fn test_regex_extract() -> Result<(), Box<dyn std::error::Error>> {
+        let udf = RegexExtractUdf::try_new(
+            "read_name".to_string(),
+            r"^(?P<instrument>[^:]+):(?P<run>\d+)(:(?P<lane>\d+))?",
+        )?;
+
+        let strings = StringArray::from(vec![Some("M1:42:3"), Some("M1:42"), Some("x"), None]);
+        let ColumnarValue::Array(extracted) =
+            udf.invoke(&[ColumnarValue::Array(Arc::new(strings))])?
+        else {
+            panic!("expected an array");
+        };
+
+        let extracted = extracted.as_struct();
+        assert_eq!(extracted.column_names(), vec!["instrument", "run", "lane"]);
+
+        let lanes = extracted.column(2).as_string::<i32>();
+        assert_eq!(
+            lanes.iter().collect::<Vec<_>>(),
+            vec![Some("3"), None, None, None]
+        );
+
+        assert!(extracted.is_valid(1));
+        assert!(extracted.is_null(2));
+        assert!(extracted.is_null(3));
+
+        assert!(RegexExtractUdf::try_new("bad".to_string(), r"(\d+)").is_err());
+        assert!(RegexExtractUdf::try_new("bad".to_string(), r"(?P<n>").is_err());
+
+        Ok(())
+    }
+}
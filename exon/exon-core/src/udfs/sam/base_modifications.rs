@@ -0,0 +1,428 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{
+        Array, ArrayRef, AsArray, Float64Builder, Int64Builder, ListBuilder, StringBuilder,
+        StructBuilder,
+    },
+    compute::cast,
+    datatypes::{DataType, Field, Fields, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+use noodles::sam::alignment::record::Flags;
+
+use crate::{
+    physical_plan::pileup_exec::parse_cigar, udfs::sequence::reverse_complement::reverse_complement,
+};
+
+/// A base modification call from the MM and ML tags.
+#[derive(Debug, PartialEq)]
+struct ModificationCall<'a> {
+    /// The 1-based position on the sequence as stored, i.e. on the forward strand of the
+    /// reference for reverse complemented alignments.
+    read_position: usize,
+    /// The unmodified base on the originally sequenced strand.
+    base: char,
+    strand: char,
+    /// The modification code, e.g. `m` for 5mC, or a ChEBI number.
+    code: &'a str,
+    probability: Option<f64>,
+}
+
+/// Decode the MM tag and, if present, the ML tag of a sequence into modification calls.
+///
+/// MM entries are e.g. `C+m?,5,12;`, the base, strand, modification codes and optional `.` or
+/// `?`, then the number of that base to skip on the originally sequenced strand before each
+/// call. ML has one value per call and code, in order, and a value of N is a probability
+/// between N/256 and (N+1)/256, reported as its midpoint.
+fn decode_base_modifications<'a>(
+    sequence: &str,
+    reverse: bool,
+    mm: &'a str,
+    ml: Option<&[i64]>,
+) -> std::result::Result<Vec<ModificationCall<'a>>, String> {
+    // MM counts bases on the sequence as it was read, so undo the reverse complement.
+    let original = if reverse {
+        reverse_complement(sequence)
+    } else {
+        sequence.to_string()
+    }
+    .to_ascii_uppercase()
+    .into_bytes();
+
+    let mut ml = ml.map(|ml| ml.iter());
+    let mut calls = Vec::new();
+
+    for entry in mm.split(';').filter(|entry| !entry.is_empty()) {
+        let invalid = || format!("invalid MM entry {}", entry);
+
+        let mut parts = entry.split(',');
+        let header = parts.next().unwrap_or_default().as_bytes();
+
+        let (base, strand, codes) = match header {
+            [base @ (b'A' | b'C' | b'G' | b'T' | b'U' | b'N'), strand @ (b'+' | b'-'), codes @ ..] => {
+                (*base, *strand, codes)
+            }
+            _ => return Err(invalid()),
+        };
+
+        // The codes are single letters, or a single ChEBI number.
+        let codes = std::str::from_utf8(codes)
+            .map_err(|_| invalid())?
+            .trim_end_matches(['.', '?']);
+
+        let codes = if !codes.is_empty() && codes.bytes().all(|b| b.is_ascii_digit()) {
+            vec![codes]
+        } else if !codes.is_empty() && codes.bytes().all(|b| b.is_ascii_lowercase()) {
+            (0..codes.len()).map(|i| &codes[i..i + 1]).collect()
+        } else {
+            return Err(invalid());
+        };
+
+        // RNA modifications are called against U, which is stored as T.
+        let target = if base == b'U' { b'T' } else { base };
+
+        let mut positions = original
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| target == b'N' || **b == target)
+            .map(|(i, _)| i);
+
+        for delta in parts {
+            let delta = delta.parse::<usize>().map_err(|_| invalid())?;
+
+            let i = positions
+                .nth(delta)
+                .ok_or_else(|| format!("MM entry {} is beyond the end of the sequence", entry))?;
+
+            let read_position = if reverse { original.len() - i } else { i + 1 };
+
+            for code in &codes {
+                let probability = match ml.as_mut() {
+                    Some(ml) => match ml.next() {
+                        Some(value @ 0..=255) => Some((*value as f64 + 0.5) / 256.0),
+                        Some(value) => return Err(format!("invalid ML value {}", value)),
+                        None => return Err("ML has fewer values than MM has calls".to_string()),
+                    },
+                    None => None,
+                };
+
+                calls.push(ModificationCall {
+                    read_position,
+                    base: char::from(base),
+                    strand: char::from(strand),
+                    code,
+                    probability,
+                });
+            }
+        }
+    }
+
+    if ml.is_some_and(|mut ml| ml.next().is_some()) {
+        return Err("ML has more values than MM has calls".to_string());
+    }
+
+    Ok(calls)
+}
+
+/// The 1-based reference position of each base of the stored sequence, if it's aligned.
+fn reference_positions(start: i64, cigar: &str) -> Result<Vec<Option<i64>>> {
+    let mut positions = Vec::new();
+    let mut reference_position = start;
+
+    for (len, op) in parse_cigar(cigar)? {
+        match op {
+            b'M' | b'=' | b'X' => {
+                positions.extend((reference_position..).take(len).map(Some));
+                reference_position += len as i64;
+            }
+            b'I' | b'S' => positions.resize(positions.len() + len, None),
+            b'D' | b'N' => reference_position += len as i64,
+            _ => {}
+        }
+    }
+
+    Ok(positions)
+}
+
+/// The ML values of each row, from a list of integers or a comma separated string.
+fn ml_values(array: &ArrayRef) -> Result<Vec<Option<Vec<i64>>>> {
+    match array.data_type() {
+        DataType::List(_) => {
+            let item = Field::new("item", DataType::Int64, true);
+            let lists = cast(array, &DataType::List(Arc::new(item)))?;
+
+            Ok(lists
+                .as_list::<i32>()
+                .iter()
+                .map(|values| {
+                    values.map(|values| {
+                        values
+                            .as_primitive::<Int64Type>()
+                            .iter()
+                            .flatten()
+                            .collect()
+                    })
+                })
+                .collect())
+        }
+        _ => {
+            let strings = cast(array, &DataType::Utf8)?;
+
+            strings
+                .as_string::<i32>()
+                .iter()
+                .map(|values| {
+                    values
+                        .map(|values| {
+                            values
+                                .split(',')
+                                .filter(|value| !value.is_empty())
+                                .map(|value| {
+                                    value.trim().parse::<i64>().map_err(|_| {
+                                        DataFusionError::Execution(format!(
+                                            "invalid ML value {}",
+                                            value
+                                        ))
+                                    })
+                                })
+                                .collect::<Result<Vec<_>>>()
+                        })
+                        .transpose()
+                })
+                .collect()
+        }
+    }
+}
+
+fn modification_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("read_position", DataType::Int64, false),
+        Field::new("reference_position", DataType::Int64, true),
+        Field::new("base", DataType::Utf8, false),
+        Field::new("strand", DataType::Utf8, false),
+        Field::new("code", DataType::Utf8, false),
+        Field::new("probability", DataType::Float64, true),
+    ])
+}
+
+/// Decodes the MM and ML base modification tags of an alignment into modification calls, e.g.
+/// `unnest(parse_base_modifications(sequence, flag, start, cigar, tags['MM'], tags['ML']))` for
+/// a row per call.
+///
+/// Each call is a struct of the 1-based `read_position` on the stored sequence, the aligned
+/// `reference_position` if any, the unmodified `base` and `strand` from MM, the modification
+/// `code`, e.g. `m` for 5mC and `h` for 5hmC, and the ML `probability`. ML can be a list of
+/// integers or a comma separated string, as with `bam_parse_tags` off, and a null ML gives null
+/// probabilities. A null MM or an empty sequence, e.g. of a secondary alignment, returns null.
+#[derive(Debug)]
+pub(crate) struct ParseBaseModifications {
+    signature: Signature,
+}
+
+impl Default for ParseBaseModifications {
+    fn default() -> Self {
+        let signature = Signature::any(6, Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for ParseBaseModifications {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "parse_base_modifications"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        let item = Field::new("item", DataType::Struct(modification_fields()), true);
+
+        Ok(DataType::List(Arc::new(item)))
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 6 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes a sequence, flag, start, cigar, MM tag, and ML tag",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = cast(&arrays[0], &DataType::Utf8)?;
+        let sequences = sequences.as_string::<i32>();
+
+        let flags = cast(&arrays[1], &DataType::Int64)?;
+        let flags = flags.as_primitive::<Int64Type>();
+
+        let starts = cast(&arrays[2], &DataType::Int64)?;
+        let starts = starts.as_primitive::<Int64Type>();
+
+        let cigars = cast(&arrays[3], &DataType::Utf8)?;
+        let cigars = cigars.as_string::<i32>();
+
+        let mms = cast(&arrays[4], &DataType::Utf8)?;
+        let mms = mms.as_string::<i32>();
+
+        let mls = ml_values(&arrays[5])?;
+
+        let mut builder = ListBuilder::new(StructBuilder::from_fields(modification_fields(), 0));
+
+        for i in 0..sequences.len() {
+            let sequence = sequences.is_valid(i).then(|| sequences.value(i));
+
+            let (Some(sequence), true) = (sequence, mms.is_valid(i)) else {
+                builder.append_null();
+                continue;
+            };
+
+            if sequence.is_empty() || sequence == "*" {
+                builder.append_null();
+                continue;
+            }
+
+            let flags = Flags::from_bits_truncate(
+                flags.is_valid(i).then(|| flags.value(i)).unwrap_or(0) as u16,
+            );
+
+            let calls = decode_base_modifications(
+                sequence,
+                flags.is_reverse_complemented(),
+                mms.value(i),
+                mls[i].as_deref(),
+            )
+            .map_err(|e| DataFusionError::Execution(format!("{} failed: {}", self.name(), e)))?;
+
+            let positions = if !flags.is_unmapped() && starts.is_valid(i) && cigars.is_valid(i) {
+                reference_positions(starts.value(i), cigars.value(i))?
+            } else {
+                vec![]
+            };
+
+            let values = builder.values();
+
+            for call in calls {
+                let reference_position = positions.get(call.read_position - 1).copied().flatten();
+
+                field::<Int64Builder>(values, 0)?.append_value(call.read_position as i64);
+                field::<Int64Builder>(values, 1)?.append_option(reference_position);
+                field::<StringBuilder>(values, 2)?.append_value(call.base.to_string());
+                field::<StringBuilder>(values, 3)?.append_value(call.strand.to_string());
+                field::<StringBuilder>(values, 4)?.append_value(call.code);
+                field::<Float64Builder>(values, 5)?.append_option(call.probability);
+
+                values.append(true);
+            }
+
+            builder.append(true);
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(builder.finish())))
+    }
+}
+
+fn field<T: arrow::array::ArrayBuilder>(builder: &mut StructBuilder, i: usize) -> Result<&mut T> {
+    builder.field_builder::<T>(i).ok_or_else(|| {
+        DataFusionError::Internal(format!("Invalid base modification field builder {}", i))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_base_modifications, reference_positions, ModificationCall};
+
+    fn call(read_position: usize, code: &str, probability: Option<f64>) -> ModificationCall<'_> {
+        ModificationCall {
+            read_position,
+            base: 'C',
+            strand: '+',
+            code,
+            probability,
+        }
+    }
+
+    #[test]
+    fn test_decode_base_modifications() {
+        // The Cs are at 2, 4 and 7, and the second skips one C.
+        let calls =
+            decode_base_modifications("ACGCGTCG", false, "C+m,0,1;", Some(&[200, 10])).unwrap();
+        assert_eq!(
+            calls,
+            vec![
+                call(2, "m", Some(200.5 / 256.0)),
+                call(7, "m", Some(10.5 / 256.0))
+            ]
+        );
+
+        // The same read stored reverse complemented.
+        let calls =
+            decode_base_modifications("CGACGCGT", true, "C+m,0,1;", Some(&[200, 10])).unwrap();
+        assert_eq!(
+            calls,
+            vec![
+                call(7, "m", Some(200.5 / 256.0)),
+                call(2, "m", Some(10.5 / 256.0))
+            ]
+        );
+
+        // Multiple codes have a value each per call, and ML is optional.
+        let calls =
+            decode_base_modifications("ACGCGTCG", false, "C+mh?,1;", Some(&[1, 2])).unwrap();
+        assert_eq!(
+            calls,
+            vec![
+                call(4, "m", Some(1.5 / 256.0)),
+                call(4, "h", Some(2.5 / 256.0))
+            ]
+        );
+
+        let calls = decode_base_modifications("ACGCGTCG", false, "C+76792,2;", None).unwrap();
+        assert_eq!(calls, vec![call(7, "76792", None)]);
+
+        assert!(decode_base_modifications("ACGCGTCG", false, "", None)
+            .unwrap()
+            .is_empty());
+
+        assert!(decode_base_modifications("ACGCGTCG", false, "C+m,3;", None).is_err());
+        assert!(decode_base_modifications("ACGCGTCG", false, "C*m,0;", None).is_err());
+        assert!(decode_base_modifications("ACGCGTCG", false, "C+m,0;", Some(&[])).is_err());
+        assert!(decode_base_modifications("ACGCGTCG", false, "C+m,0;", Some(&[1, 2])).is_err());
+        assert!(decode_base_modifications("ACGCGTCG", false, "C+m,0;", Some(&[256])).is_err());
+    }
+
+    #[test]
+    fn test_reference_positions() {
+        let positions = reference_positions(100, "2S2M1D1I1M").unwrap();
+        assert_eq!(
+            positions,
+            vec![None, None, Some(100), Some(101), None, Some(103)]
+        );
+
+        assert!(reference_positions(100, "*").unwrap().is_empty());
+    }
+}
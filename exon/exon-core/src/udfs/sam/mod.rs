@@ -13,5 +13,20 @@
 // limitations under the License.
 
 pub(crate) mod bam_region_filter;
+pub(crate) mod base_modifications;
 pub(crate) mod cram_region_filter;
+pub(crate) mod parse_sa;
 pub(crate) mod samflags;
+
+use datafusion::{execution::context::SessionContext, logical_expr::ScalarUDF};
+
+/// Registers the SAM flag and tag UDFs.
+pub fn register_udfs(ctx: &SessionContext) {
+    samflags::register_udfs(ctx);
+
+    let parse_sa = parse_sa::ParseSA::default();
+    ctx.register_udf(ScalarUDF::from(parse_sa));
+
+    let parse_base_modifications = base_modifications::ParseBaseModifications::default();
+    ctx.register_udf(ScalarUDF::from(parse_base_modifications));
+}
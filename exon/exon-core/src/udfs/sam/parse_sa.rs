@@ -0,0 +1,209 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{AsArray, Int32Builder, Int64Builder, ListBuilder, StringBuilder, StructBuilder},
+    compute::cast,
+    datatypes::{DataType, Field, Fields},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, TypeSignature, Volatility},
+};
+
+/// An alignment from the SA tag, i.e. another part of a chimeric read.
+#[derive(Debug, PartialEq)]
+struct SupplementaryAlignment<'a> {
+    chrom: &'a str,
+    /// The 1-based start of the alignment.
+    pos: i64,
+    strand: &'a str,
+    cigar: &'a str,
+    mapq: i32,
+    nm: i32,
+}
+
+/// Parse an SA tag, `rname,pos,strand,CIGAR,mapQ,NM;` repeated for each alignment.
+fn parse_sa(value: &str) -> std::result::Result<Vec<SupplementaryAlignment<'_>>, String> {
+    value
+        .split(';')
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let invalid = || format!("invalid SA entry {}", entry);
+
+            let [chrom, pos, strand, cigar, mapq, nm] = entry
+                .split(',')
+                .collect::<Vec<_>>()
+                .try_into()
+                .map_err(|_| invalid())?;
+
+            if strand != "+" && strand != "-" {
+                return Err(invalid());
+            }
+
+            Ok(SupplementaryAlignment {
+                chrom,
+                pos: pos.parse().map_err(|_| invalid())?,
+                strand,
+                cigar,
+                mapq: mapq.parse().map_err(|_| invalid())?,
+                nm: nm.parse().map_err(|_| invalid())?,
+            })
+        })
+        .collect()
+}
+
+fn sa_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("chrom", DataType::Utf8, false),
+        Field::new("pos", DataType::Int64, false),
+        Field::new("strand", DataType::Utf8, false),
+        Field::new("cigar", DataType::Utf8, false),
+        Field::new("mapq", DataType::Int32, false),
+        Field::new("nm", DataType::Int32, false),
+    ])
+}
+
+/// Parses the SA tag of a chimeric read into its other alignments, e.g.
+/// `unnest(parse_sa(tags['SA']))` for a row per supplementary alignment.
+///
+/// Each alignment is a struct of its reference `chrom`, 1-based `pos`, `strand`, `cigar`, `mapq`
+/// and edit distance `nm`. A null tag returns null and a malformed tag is an error.
+#[derive(Debug)]
+pub(crate) struct ParseSA {
+    signature: Signature,
+}
+
+impl Default for ParseSA {
+    fn default() -> Self {
+        let signature = Signature::new(
+            TypeSignature::Coercible(vec![DataType::Utf8]),
+            Volatility::Immutable,
+        );
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for ParseSA {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "parse_sa"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        let item = Field::new("item", DataType::Struct(sa_fields()), true);
+
+        Ok(DataType::List(Arc::new(item)))
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 1 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes an SA tag",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let tags = cast(&arrays[0], &DataType::Utf8)?;
+        let tags = tags.as_string::<i32>();
+
+        let mut builder = ListBuilder::new(StructBuilder::from_fields(sa_fields(), 0));
+
+        for tag in tags.iter() {
+            let Some(tag) = tag else {
+                builder.append_null();
+                continue;
+            };
+
+            let alignments = parse_sa(tag).map_err(|e| {
+                DataFusionError::Execution(format!("{} failed: {}", self.name(), e))
+            })?;
+
+            let values = builder.values();
+
+            for alignment in alignments {
+                field::<StringBuilder>(values, 0)?.append_value(alignment.chrom);
+                field::<Int64Builder>(values, 1)?.append_value(alignment.pos);
+                field::<StringBuilder>(values, 2)?.append_value(alignment.strand);
+                field::<StringBuilder>(values, 3)?.append_value(alignment.cigar);
+                field::<Int32Builder>(values, 4)?.append_value(alignment.mapq);
+                field::<Int32Builder>(values, 5)?.append_value(alignment.nm);
+
+                values.append(true);
+            }
+
+            builder.append(true);
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(builder.finish())))
+    }
+}
+
+fn field<T: arrow::array::ArrayBuilder>(builder: &mut StructBuilder, i: usize) -> Result<&mut T> {
+    builder
+        .field_builder::<T>(i)
+        .ok_or_else(|| DataFusionError::Internal(format!("Invalid SA field builder {}", i)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_sa, SupplementaryAlignment};
+
+    #[test]
+    fn test_parse_sa() {
+        let alignments = parse_sa("chr1,100,+,50M50S,60,0;chr2,2000,-,50S50M,30,2;").unwrap();
+
+        assert_eq!(
+            alignments,
+            vec![
+                SupplementaryAlignment {
+                    chrom: "chr1",
+                    pos: 100,
+                    strand: "+",
+                    cigar: "50M50S",
+                    mapq: 60,
+                    nm: 0,
+                },
+                SupplementaryAlignment {
+                    chrom: "chr2",
+                    pos: 2000,
+                    strand: "-",
+                    cigar: "50S50M",
+                    mapq: 30,
+                    nm: 2,
+                },
+            ]
+        );
+
+        // The trailing semicolon is optional.
+        assert_eq!(parse_sa("chr1,100,+,50M50S,60,0").unwrap().len(), 1);
+        assert!(parse_sa("").unwrap().is_empty());
+
+        assert!(parse_sa("chr1,100,+,50M50S,60").is_err());
+        assert!(parse_sa("chr1,100,*,50M50S,60,0;").is_err());
+        assert!(parse_sa("chr1,x,+,50M50S,60,0;").is_err());
+    }
+}
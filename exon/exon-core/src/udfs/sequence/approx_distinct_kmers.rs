@@ -0,0 +1,247 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+
+use arrow::{
+    array::{ArrayRef, AsArray},
+    datatypes::{DataType, Field},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{
+        function::{AccumulatorArgs, StateFieldsArgs},
+        utils::format_state_name,
+        Accumulator, AggregateUDFImpl, Signature, Volatility,
+    },
+    physical_plan::expressions::Literal,
+    scalar::ScalarValue,
+};
+
+use super::kmers::{hash_kmer, CanonicalKmers, MAX_KMER_SIZE};
+
+/// The number of hash bits that pick a register, for a standard error of about 0.8%.
+const PRECISION: u32 = 14;
+
+const N_REGISTERS: usize = 1 << PRECISION;
+
+/// A HyperLogLog sketch of a set of hashes, which merges with another by the max of each register.
+#[derive(Debug)]
+struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self {
+            registers: vec![0; N_REGISTERS],
+        }
+    }
+}
+
+impl HyperLogLog {
+    fn insert(&mut self, hash: u64) {
+        let register = (hash >> (64 - PRECISION)) as usize;
+        let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() as u8 + 1;
+
+        self.registers[register] = self.registers[register].max(rank);
+    }
+
+    fn merge(&mut self, registers: &[u8]) -> Result<()> {
+        if registers.len() != N_REGISTERS {
+            return Err(DataFusionError::Execution(format!(
+                "approx_distinct_kmers state should have {} registers, got {}",
+                N_REGISTERS,
+                registers.len()
+            )));
+        }
+
+        for (register, other) in self.registers.iter_mut().zip(registers) {
+            *register = (*register).max(*other);
+        }
+
+        Ok(())
+    }
+
+    /// Estimate the number of distinct hashes, with linear counting while many registers are
+    /// empty.
+    fn estimate(&self) -> f64 {
+        let m = N_REGISTERS as f64;
+
+        let sum = self
+            .registers
+            .iter()
+            .map(|register| 2f64.powi(-(*register as i32)))
+            .sum::<f64>();
+        let zeros = self
+            .registers
+            .iter()
+            .filter(|register| **register == 0)
+            .count();
+
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let estimate = alpha * m * m / sum;
+
+        if estimate <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            estimate
+        }
+    }
+}
+
+/// An aggregate that estimates the number of distinct canonical k-mers of a set of sequences with
+/// HyperLogLog, e.g. `SELECT approx_distinct_kmers(sequence, 21) FROM fastq`.
+///
+/// `k` is a literal from 1 to 32. K-mers with bases other than ACGT and null sequences are
+/// ignored, and the sketches of partitions merge, so the estimate scales to any number of reads.
+#[derive(Debug)]
+pub(crate) struct ApproxDistinctKmers {
+    signature: Signature,
+}
+
+impl Default for ApproxDistinctKmers {
+    fn default() -> Self {
+        let signature =
+            Signature::exact(vec![DataType::Utf8, DataType::Int64], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl AggregateUDFImpl for ApproxDistinctKmers {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "approx_distinct_kmers"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Int64)
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<Field>> {
+        Ok(vec![Field::new(
+            format_state_name(args.name, "registers"),
+            DataType::Binary,
+            true,
+        )])
+    }
+
+    fn accumulator(&self, acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        let k = acc_args
+            .exprs
+            .get(1)
+            .and_then(|expr| expr.as_any().downcast_ref::<Literal>())
+            .and_then(|literal| match literal.value() {
+                ScalarValue::Int64(Some(k)) => usize::try_from(*k).ok(),
+                _ => None,
+            })
+            .filter(|k| (1..=MAX_KMER_SIZE).contains(k))
+            .ok_or_else(|| {
+                DataFusionError::Execution(format!(
+                    "approx_distinct_kmers takes a literal k from 1 to {}",
+                    MAX_KMER_SIZE
+                ))
+            })?;
+
+        Ok(Box::new(ApproxDistinctKmersAccumulator {
+            k,
+            sketch: HyperLogLog::default(),
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct ApproxDistinctKmersAccumulator {
+    k: usize,
+    sketch: HyperLogLog,
+}
+
+impl Accumulator for ApproxDistinctKmersAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let sequences = values[0].as_string_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution("approx_distinct_kmers takes string sequences".to_string())
+        })?;
+
+        for sequence in sequences.iter().flatten() {
+            for kmer in CanonicalKmers::new(sequence.as_bytes(), self.k) {
+                self.sketch.insert(hash_kmer(kmer));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        let states = states[0].as_binary_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution("approx_distinct_kmers state should be binary".to_string())
+        })?;
+
+        for registers in states.iter().flatten() {
+            self.sketch.merge(registers)?;
+        }
+
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![ScalarValue::Binary(Some(
+            self.sketch.registers.clone(),
+        ))])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        Ok(ScalarValue::Int64(Some(
+            self.sketch.estimate().round() as i64
+        )))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.sketch.registers.capacity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hyperloglog_estimate() {
+        let mut sketch = HyperLogLog::default();
+        let mut other = HyperLogLog::default();
+
+        for i in 0..100_000u64 {
+            sketch.insert(hash_kmer(i));
+            other.insert(hash_kmer(i + 50_000));
+        }
+
+        let estimate = sketch.estimate();
+        assert!((estimate - 100_000.0).abs() < 3_000.0, "{}", estimate);
+
+        sketch.merge(&other.registers).unwrap();
+
+        let estimate = sketch.estimate();
+        assert!((estimate - 150_000.0).abs() < 4_500.0, "{}", estimate);
+
+        assert!(sketch.merge(&[0; 4]).is_err());
+        assert_eq!(HyperLogLog::default().estimate(), 0.0);
+    }
+}
@@ -0,0 +1,292 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, AsArray, Int64Builder, ListBuilder, StructBuilder},
+    compute::cast,
+    datatypes::{DataType, Field, Fields, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::iupac::{base_mask, matches, pattern_masks};
+
+/// The longest pattern that fits the bit vectors of Myers' algorithm.
+const MAX_PATTERN_LENGTH: usize = 64;
+
+/// An approximate match as a 0-based, inclusive range of the sequence.
+#[derive(Debug, PartialEq)]
+struct Match {
+    start: usize,
+    end: usize,
+    distance: usize,
+}
+
+/// Find the approximate matches of a pattern of at most 64 codes with Myers' bit-vector
+/// algorithm, reporting the best end of each run of ends within `max_distance` edits.
+fn approx_matches(sequence: &[u8], masks: &[u8], max_distance: usize) -> Vec<Match> {
+    let m = masks.len();
+
+    // The pattern positions matched by each base mask of the sequence.
+    let mut peq = [0u64; 16];
+    for (base, eq) in peq.iter_mut().enumerate().skip(1) {
+        for (i, mask) in masks.iter().enumerate() {
+            if base as u8 & !mask == 0 {
+                *eq |= 1 << i;
+            }
+        }
+    }
+
+    let high = 1u64 << (m - 1);
+    let (mut pv, mut mv, mut distance) = (!0u64, 0u64, m);
+
+    let mut results = Vec::new();
+    let mut best: Option<(usize, usize)> = None;
+
+    for (j, base) in sequence.iter().enumerate() {
+        let eq = peq[base_mask(*base) as usize];
+        let xv = eq | mv;
+        let xh = ((eq & pv).wrapping_add(pv) ^ pv) | eq;
+
+        let mut ph = mv | !(xh | pv);
+        let mut mh = pv & xh;
+
+        if ph & high != 0 {
+            distance += 1;
+        } else if mh & high != 0 {
+            distance -= 1;
+        }
+
+        ph <<= 1;
+        mh <<= 1;
+        pv = mh | !(xv | ph);
+        mv = ph & xv;
+
+        if distance <= max_distance {
+            if best.map_or(true, |(_, d)| distance < d) {
+                best = Some((j, distance));
+            }
+        } else if let Some((end, distance)) = best.take() {
+            results.push(approx_match(sequence, masks, end, distance));
+        }
+    }
+
+    if let Some((end, distance)) = best {
+        results.push(approx_match(sequence, masks, end, distance));
+    }
+
+    results
+}
+
+/// Find the start of the shortest alignment of the pattern ending at `end` with `distance`
+/// edits, by aligning the reversed pattern to the sequence backwards from `end`.
+fn approx_match(sequence: &[u8], masks: &[u8], end: usize, distance: usize) -> Match {
+    let m = masks.len();
+    let max_length = (m + distance).min(end + 1);
+
+    let mut previous = (0..=max_length).collect::<Vec<_>>();
+
+    for i in 1..=m {
+        let mask = masks[m - i];
+        let mut current = vec![i; max_length + 1];
+
+        for j in 1..=max_length {
+            let substitution = previous[j - 1] + usize::from(!matches(sequence[end + 1 - j], mask));
+            current[j] = substitution.min(previous[j] + 1).min(current[j - 1] + 1);
+        }
+
+        previous = current;
+    }
+
+    let length = (0..=max_length)
+        .min_by_key(|&j| (previous[j], j))
+        .unwrap_or(0);
+
+    Match {
+        start: end + 1 - length,
+        end,
+        distance,
+    }
+}
+
+fn match_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("start", DataType::Int64, false),
+        Field::new("end", DataType::Int64, false),
+        Field::new("distance", DataType::Int64, false),
+    ])
+}
+
+/// Finds the matches of an IUPAC pattern of at most 64 codes with up to `max_mismatches` edits,
+/// i.e. substitutions, insertions, or deletions, e.g. `approx_match(read, 'ACGTNNACGT', 1)`.
+///
+/// Returns the 1-based, inclusive start and end and the edit distance of the best match of each
+/// run of overlapping matches.
+#[derive(Debug)]
+pub(crate) struct ApproxMatch {
+    signature: Signature,
+}
+
+impl Default for ApproxMatch {
+    fn default() -> Self {
+        let signature = Signature::coercible(
+            vec![DataType::Utf8, DataType::Utf8, DataType::Int64],
+            Volatility::Immutable,
+        );
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for ApproxMatch {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "approx_match"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        let item = Field::new("item", DataType::Struct(match_fields()), true);
+
+        Ok(DataType::List(Arc::new(item)))
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 3 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes a sequence, an IUPAC pattern, and a maximum number of mismatches",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = cast(&arrays[0], &DataType::Utf8)?;
+        let sequences = sequences.as_string::<i32>();
+
+        let patterns = cast(&arrays[1], &DataType::Utf8)?;
+        let patterns = patterns.as_string::<i32>();
+
+        let max_mismatches = cast(&arrays[2], &DataType::Int64)?;
+        let max_mismatches = max_mismatches.as_primitive::<Int64Type>();
+
+        let mut builder = ListBuilder::new(StructBuilder::from_fields(match_fields(), 0));
+
+        for i in 0..sequences.len() {
+            if sequences.is_null(i) || patterns.is_null(i) || max_mismatches.is_null(i) {
+                builder.append_null();
+                continue;
+            }
+
+            let pattern = patterns.value(i);
+
+            let masks = pattern_masks(pattern)
+                .filter(|masks| masks.len() <= MAX_PATTERN_LENGTH)
+                .ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "{} takes an IUPAC pattern of at most {} codes, got {}",
+                        self.name(),
+                        MAX_PATTERN_LENGTH,
+                        pattern
+                    ))
+                })?;
+
+            let max_distance = usize::try_from(max_mismatches.value(i))
+                .ok()
+                .filter(|d| *d < masks.len())
+                .ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "{} takes a maximum number of mismatches from 0 to the pattern length - 1",
+                        self.name()
+                    ))
+                })?;
+
+            let values = builder.values();
+
+            for m in approx_matches(sequences.value(i).as_bytes(), &masks, max_distance) {
+                field::<Int64Builder>(values, 0)?.append_value(m.start as i64 + 1);
+                field::<Int64Builder>(values, 1)?.append_value(m.end as i64 + 1);
+                field::<Int64Builder>(values, 2)?.append_value(m.distance as i64);
+
+                values.append(true);
+            }
+
+            builder.append(true);
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(builder.finish())))
+    }
+}
+
+fn field<T: arrow::array::ArrayBuilder>(builder: &mut StructBuilder, i: usize) -> Result<&mut T> {
+    builder
+        .field_builder::<T>(i)
+        .ok_or_else(|| DataFusionError::Internal(format!("Invalid match field builder {}", i)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{approx_matches, pattern_masks, Match};
+
+    #[test]
+    fn test_approx_matches() {
+        let masks = pattern_masks("ACGTAC").unwrap();
+
+        // An exact match.
+        assert_eq!(
+            approx_matches(b"TTACGTACTT", &masks, 0),
+            vec![Match {
+                start: 2,
+                end: 7,
+                distance: 0
+            }]
+        );
+
+        // A substitution.
+        assert_eq!(
+            approx_matches(b"TTACGAACTT", &masks, 1),
+            vec![Match {
+                start: 2,
+                end: 7,
+                distance: 1
+            }]
+        );
+
+        // A deletion of the T.
+        assert_eq!(
+            approx_matches(b"GGACGACGG", &masks, 1),
+            vec![Match {
+                start: 2,
+                end: 6,
+                distance: 1
+            }]
+        );
+
+        assert!(approx_matches(b"GGGGGGGGGG", &masks, 2).is_empty());
+
+        // Ambiguity codes in the pattern.
+        let masks = pattern_masks("ACNNAC").unwrap();
+        assert_eq!(approx_matches(b"ACGGAC", &masks, 0).len(), 1);
+    }
+}
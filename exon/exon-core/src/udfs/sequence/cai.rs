@@ -0,0 +1,236 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, AsArray, Float64Array},
+    compute::cast,
+    datatypes::{DataType, Float64Type, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, TypeSignature, Volatility},
+};
+
+use super::{
+    codon_usage::N_CODONS,
+    genetic_code::{codon, codon_index, GeneticCode},
+};
+
+/// The count of codons missing from a reference, after Sharp and Li (1987), so a single rare
+/// codon doesn't make the index of a gene zero.
+const MISSING_CODON_COUNT: f64 = 0.5;
+
+/// The relative adaptiveness of each codon in a reference, its count over that of the most used
+/// codon of its amino acid, or `None` for stops, amino acids with a single codon, and amino acids
+/// missing from the reference.
+fn relative_adaptiveness(
+    reference: &[f64; N_CODONS],
+    code: &GeneticCode,
+) -> [Option<f64>; N_CODONS] {
+    let amino_acids = (0..N_CODONS)
+        .map(|i| code.translate_codon(&codon(i)) as usize)
+        .collect::<Vec<_>>();
+
+    let mut max_counts = [0.0_f64; 256];
+    let mut n_codons = [0; 256];
+
+    for (aa, count) in amino_acids.iter().zip(reference) {
+        max_counts[*aa] = max_counts[*aa].max(*count);
+        n_codons[*aa] += 1;
+    }
+
+    let mut weights = [None; N_CODONS];
+
+    for (i, aa) in amino_acids.into_iter().enumerate() {
+        if aa == b'*' as usize || n_codons[aa] < 2 || max_counts[aa] <= 0.0 {
+            continue;
+        }
+
+        weights[i] = Some(reference[i].max(MISSING_CODON_COUNT) / max_counts[aa]);
+    }
+
+    weights
+}
+
+/// The geometric mean of the relative adaptiveness of the codons of a coding sequence, or `None`
+/// if none of its codons are scored.
+fn codon_adaptation_index(sequence: &str, weights: &[Option<f64>; N_CODONS]) -> Option<f64> {
+    let (sum, n) = sequence
+        .as_bytes()
+        .chunks_exact(3)
+        .filter_map(codon_index)
+        .filter_map(|i| weights[i])
+        .fold((0.0, 0), |(sum, n), w| (sum + w.ln(), n + 1));
+
+    (n > 0).then(|| (sum / n as f64).exp())
+}
+
+/// Returns the codon adaptation index (CAI) of a coding sequence against a reference codon usage
+/// table, e.g. `cai(sequence, (SELECT codon_usage_table(sequence) FROM highly_expressed))`.
+///
+/// The reference is a struct of codon counts like those of `codon_usage` and
+/// `codon_usage_table`, and the genetic code that groups synonymous codons defaults to the
+/// standard code (NCBI table 1). Stops and amino acids with a single codon, like ATG and TGG in
+/// the standard code, aren't scored, and sequences without scored codons are null.
+#[derive(Debug)]
+pub(crate) struct Cai {
+    signature: Signature,
+}
+
+impl Default for Cai {
+    fn default() -> Self {
+        let signature = Signature::one_of(
+            vec![TypeSignature::Any(2), TypeSignature::Any(3)],
+            Volatility::Immutable,
+        );
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for Cai {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "cai"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 2 && args.len() != 3 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes a sequence, a reference codon usage table, and optionally a genetic code table",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = cast(&arrays[0], &DataType::Utf8)?;
+        let sequences = sequences.as_string::<i32>();
+
+        let references = arrays[1].as_struct_opt().ok_or_else(|| {
+            DataFusionError::Execution(format!(
+                "{} takes a struct of codon counts as the reference, got {}",
+                self.name(),
+                arrays[1].data_type()
+            ))
+        })?;
+
+        let counts = (0..N_CODONS)
+            .map(|i| {
+                let name = String::from_utf8_lossy(&codon(i)).to_string();
+
+                let column = references.column_by_name(&name).ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "{} reference is missing the codon {}",
+                        self.name(),
+                        name
+                    ))
+                })?;
+
+                Ok(cast(column, &DataType::Float64)?)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let counts = counts
+            .iter()
+            .map(|a| a.as_primitive::<Float64Type>())
+            .collect::<Vec<_>>();
+
+        let table_ids = arrays
+            .get(2)
+            .map(|array| cast(array, &DataType::Int64))
+            .transpose()?;
+        let table_ids = table_ids.as_ref().map(|a| a.as_primitive::<Int64Type>());
+
+        let indexes = (0..sequences.len())
+            .map(|i| {
+                let table_id = match table_ids {
+                    Some(table_ids) if table_ids.is_null(i) => return Ok(None),
+                    Some(table_ids) => table_ids.value(i),
+                    None => 1,
+                };
+
+                if sequences.is_null(i) || references.is_null(i) {
+                    return Ok(None);
+                }
+
+                let code = GeneticCode::try_from_id(table_id).ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "{} doesn't support genetic code table {}",
+                        self.name(),
+                        table_id
+                    ))
+                })?;
+
+                let mut reference = [0.0; N_CODONS];
+                for (count, column) in reference.iter_mut().zip(&counts) {
+                    *count = if column.is_valid(i) {
+                        column.value(i)
+                    } else {
+                        0.0
+                    };
+                }
+
+                let weights = relative_adaptiveness(&reference, &code);
+
+                Ok(codon_adaptation_index(sequences.value(i), &weights))
+            })
+            .collect::<Result<Float64Array>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(indexes)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        codon_adaptation_index, codon_index, relative_adaptiveness, GeneticCode, N_CODONS,
+    };
+
+    #[test]
+    fn test_codon_adaptation_index() {
+        let mut reference = [0.0; N_CODONS];
+        for (codon, count) in [(b"GCC", 40.0), (b"GCT", 10.0), (b"AAA", 5.0), (b"ATG", 9.0)] {
+            reference[codon_index(codon).unwrap()] = count;
+        }
+
+        let weights = relative_adaptiveness(&reference, &GeneticCode::default());
+
+        assert_eq!(weights[codon_index(b"GCC").unwrap()], Some(1.0));
+        assert_eq!(weights[codon_index(b"GCT").unwrap()], Some(0.25));
+        assert_eq!(weights[codon_index(b"GCA").unwrap()], Some(0.0125));
+        assert_eq!(weights[codon_index(b"AAG").unwrap()], Some(0.1));
+        assert_eq!(weights[codon_index(b"ATG").unwrap()], None);
+        assert_eq!(weights[codon_index(b"TAA").unwrap()], None);
+        assert_eq!(weights[codon_index(b"CCC").unwrap()], None);
+
+        assert_eq!(codon_adaptation_index("ATGGCCGCCTAA", &weights), Some(1.0));
+
+        let cai = codon_adaptation_index("ATGGCCGCT", &weights).unwrap();
+        assert!((cai - 0.5).abs() < 1e-12, "{}", cai);
+
+        assert_eq!(codon_adaptation_index("ATGTGGCCC", &weights), None);
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, AsArray, Int64Builder, StructArray},
+    buffer::NullBuffer,
+    compute::cast,
+    datatypes::{DataType, Field, Fields},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::genetic_code::{codon, codon_index};
+
+/// The number of codons.
+pub(super) const N_CODONS: usize = 64;
+
+/// The fields of the codon usage struct, a count per codon in TCAG order from TTT to GGG.
+pub(super) fn codon_usage_fields() -> Fields {
+    (0..N_CODONS)
+        .map(|i| {
+            let name = String::from_utf8_lossy(&codon(i)).to_string();
+            Field::new(name, DataType::Int64, true)
+        })
+        .collect()
+}
+
+/// Counts the codons of a coding sequence in frame from its first base, skipping codons with
+/// ambiguous bases and a trailing partial codon.
+pub(super) fn count_codons(sequence: &str, counts: &mut [i64; N_CODONS]) {
+    for c in sequence.as_bytes().chunks_exact(3) {
+        if let Some(i) = codon_index(c) {
+            counts[i] += 1;
+        }
+    }
+}
+
+/// A codon usage struct array from the counts of each row, null for rows without counts.
+pub(super) fn codon_usage_array(rows: &[Option<[i64; N_CODONS]>]) -> Result<StructArray> {
+    let mut builders = (0..N_CODONS)
+        .map(|_| Int64Builder::with_capacity(rows.len()))
+        .collect::<Vec<_>>();
+
+    for row in rows {
+        match row {
+            Some(counts) => {
+                for (builder, count) in builders.iter_mut().zip(counts) {
+                    builder.append_value(*count);
+                }
+            }
+            None => builders.iter_mut().for_each(|b| b.append_null()),
+        }
+    }
+
+    let columns = builders
+        .iter_mut()
+        .map(|b| Arc::new(b.finish()) as ArrayRef)
+        .collect::<Vec<_>>();
+
+    let nulls = NullBuffer::from_iter(rows.iter().map(Option::is_some));
+
+    Ok(StructArray::try_new(
+        codon_usage_fields(),
+        columns,
+        Some(nulls),
+    )?)
+}
+
+/// Returns the counts of the codons of a coding sequence as a struct with a field per codon,
+/// e.g. `codon_usage(sequence)['GCC']`.
+///
+/// Codons are read in frame from the first base, `U` is read as `T`, and codons with ambiguous
+/// bases and a trailing partial codon aren't counted.
+#[derive(Debug)]
+pub(crate) struct CodonUsage {
+    signature: Signature,
+}
+
+impl Default for CodonUsage {
+    fn default() -> Self {
+        let signature = Signature::exact(vec![DataType::Utf8], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for CodonUsage {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "codon_usage"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Struct(codon_usage_fields()))
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 1 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes one argument",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = cast(&arrays[0], &DataType::Utf8)?;
+        let rows = sequences
+            .as_string::<i32>()
+            .iter()
+            .map(|sequence| {
+                sequence.map(|sequence| {
+                    let mut counts = [0; N_CODONS];
+                    count_codons(sequence, &mut counts);
+                    counts
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(ColumnarValue::Array(Arc::new(codon_usage_array(&rows)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{codon_index, count_codons, N_CODONS};
+
+    #[test]
+    fn test_count_codons() {
+        let mut counts = [0; N_CODONS];
+        count_codons("ATGgcuGCNGCCTA", &mut counts);
+
+        // The ambiguous GCN and the partial TA aren't counted.
+        for codon in [b"ATG", b"GCT", b"GCC"] {
+            assert_eq!(counts[codon_index(codon).unwrap()], 1);
+        }
+        assert_eq!(counts.iter().sum::<i64>(), 3);
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::{any::Any, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray},
+    datatypes::{DataType, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{
+        function::AccumulatorArgs, Accumulator, AggregateUDFImpl, Signature, Volatility,
+    },
+    scalar::ScalarValue,
+};
+
+use super::codon_usage::{codon_usage_array, codon_usage_fields, count_codons, N_CODONS};
+
+/// An aggregate that sums the codon usage of a set of coding sequences into a reference table for
+/// `cai`, e.g. `SELECT codon_usage_table(sequence) FROM highly_expressed`.
+///
+/// The table is a struct like that of `codon_usage`, and null sequences are ignored.
+#[derive(Debug)]
+pub(crate) struct CodonUsageTable {
+    signature: Signature,
+}
+
+impl Default for CodonUsageTable {
+    fn default() -> Self {
+        let signature = Signature::exact(vec![DataType::Utf8], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl AggregateUDFImpl for CodonUsageTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "codon_usage_table"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Struct(codon_usage_fields()))
+    }
+
+    fn accumulator(&self, _acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(CodonUsageTableAccumulator::default()))
+    }
+}
+
+#[derive(Debug)]
+struct CodonUsageTableAccumulator {
+    counts: [i64; N_CODONS],
+}
+
+impl Default for CodonUsageTableAccumulator {
+    fn default() -> Self {
+        Self {
+            counts: [0; N_CODONS],
+        }
+    }
+}
+
+impl Accumulator for CodonUsageTableAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let sequences = values[0].as_string_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution("codon_usage_table takes string sequences".to_string())
+        })?;
+
+        for sequence in sequences.iter().flatten() {
+            count_codons(sequence, &mut self.counts);
+        }
+
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        let states = states[0].as_struct_opt().ok_or_else(|| {
+            DataFusionError::Execution("codon_usage_table state should be a struct".to_string())
+        })?;
+
+        if states.num_columns() != N_CODONS {
+            return Err(DataFusionError::Execution(format!(
+                "codon_usage_table state should have {} codons, got {}",
+                N_CODONS,
+                states.num_columns()
+            )));
+        }
+
+        for (count, column) in self.counts.iter_mut().zip(states.columns()) {
+            let column = column.as_primitive::<Int64Type>();
+            *count += column
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| states.is_valid(*i))
+                .filter_map(|(_, c)| c)
+                .sum::<i64>();
+        }
+
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![self.evaluate()?])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        let table = codon_usage_array(&[Some(self.counts)])?;
+
+        Ok(ScalarValue::Struct(Arc::new(table)))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
@@ -0,0 +1,329 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, AsArray, Float64Array},
+    compute::cast,
+    datatypes::{DataType, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, TypeSignature, Volatility},
+};
+
+use super::genetic_code::{codon, codon_index, GeneticCode};
+
+/// The base at a position of a codon index, 0 to 3 in TCAG order.
+fn base_at(codon: usize, position: usize) -> usize {
+    (codon >> (2 * (2 - position))) & 3
+}
+
+/// The codon index with the base at a position replaced.
+fn with_base(codon: usize, position: usize, base: usize) -> usize {
+    let shift = 2 * (2 - position);
+
+    (codon & !(3 << shift)) | (base << shift)
+}
+
+/// The orders of a set of positions.
+fn permutations(positions: &[usize]) -> Vec<Vec<usize>> {
+    if positions.len() <= 1 {
+        return vec![positions.to_vec()];
+    }
+
+    positions
+        .iter()
+        .enumerate()
+        .flat_map(|(i, first)| {
+            let mut rest = positions.to_vec();
+            rest.remove(i);
+
+            permutations(&rest).into_iter().map(move |mut order| {
+                order.insert(0, *first);
+                order
+            })
+        })
+        .collect()
+}
+
+/// The Nei-Gojobori (1986) counts of the sites and differences of aligned coding sequences under
+/// a genetic code.
+struct Ng86<'a> {
+    code: &'a GeneticCode,
+}
+
+impl Ng86<'_> {
+    fn amino_acid(&self, codon_index: usize) -> u8 {
+        self.code.translate_codon(&codon(codon_index))
+    }
+
+    /// The synonymous sites of a codon, the number of its single base changes that don't change
+    /// its amino acid over three. Changes to a stop codon are nonsynonymous.
+    fn synonymous_sites(&self, codon: usize) -> f64 {
+        let amino_acid = self.amino_acid(codon);
+
+        let synonymous = (0..3)
+            .flat_map(|position| (0..4).map(move |base| with_base(codon, position, base)))
+            .filter(|neighbor| *neighbor != codon && self.amino_acid(*neighbor) == amino_acid)
+            .count();
+
+        synonymous as f64 / 3.0
+    }
+
+    /// The synonymous and nonsynonymous differences between two codons, averaged over the orders
+    /// of their base changes. Orders through a stop codon are left out unless every order passes
+    /// through one.
+    fn differences(&self, a: usize, b: usize) -> (f64, f64) {
+        let positions = (0..3)
+            .filter(|p| base_at(a, *p) != base_at(b, *p))
+            .collect::<Vec<_>>();
+
+        let pathways = permutations(&positions)
+            .into_iter()
+            .map(|order| {
+                let (mut synonymous, mut nonsynonymous, mut through_stop) = (0, 0, false);
+                let mut current = a;
+
+                for position in order {
+                    let next = with_base(current, position, base_at(b, position));
+
+                    if self.amino_acid(next) == self.amino_acid(current) {
+                        synonymous += 1;
+                    } else {
+                        nonsynonymous += 1;
+                    }
+
+                    through_stop |= next != b && self.amino_acid(next) == b'*';
+                    current = next;
+                }
+
+                (synonymous, nonsynonymous, through_stop)
+            })
+            .collect::<Vec<_>>();
+
+        let without_stops = pathways
+            .iter()
+            .filter(|(_, _, through_stop)| !through_stop)
+            .collect::<Vec<_>>();
+
+        let pathways = if without_stops.is_empty() {
+            pathways.iter().collect()
+        } else {
+            without_stops
+        };
+
+        let weight = 1.0 / pathways.len() as f64;
+
+        pathways
+            .into_iter()
+            .fold((0.0, 0.0), |(synonymous, nonsynonymous), (s, n, _)| {
+                (
+                    synonymous + *s as f64 * weight,
+                    nonsynonymous + *n as f64 * weight,
+                )
+            })
+    }
+}
+
+/// The Jukes-Cantor distance of a proportion of differing sites, or `None` if it saturates.
+fn jukes_cantor(p: f64) -> Option<f64> {
+    let x = 1.0 - 4.0 * p / 3.0;
+
+    (x > 0.0).then(|| -0.75 * x.ln())
+}
+
+/// The ratio of the nonsynonymous to synonymous Jukes-Cantor distances of two aligned coding
+/// sequences, or `None` if there are no synonymous differences or either distance saturates.
+///
+/// Codons with gaps, ambiguous bases, or stops in either sequence are skipped.
+fn dnds(a: &[u8], b: &[u8], code: &GeneticCode) -> Option<f64> {
+    let ng86 = Ng86 { code };
+
+    let (mut sites, mut differences) = ((0.0, 0.0), (0.0, 0.0));
+
+    for (a, b) in a.chunks_exact(3).zip(b.chunks_exact(3)) {
+        let (Some(a), Some(b)) = (codon_index(a), codon_index(b)) else {
+            continue;
+        };
+
+        if ng86.amino_acid(a) == b'*' || ng86.amino_acid(b) == b'*' {
+            continue;
+        }
+
+        let synonymous_sites = (ng86.synonymous_sites(a) + ng86.synonymous_sites(b)) / 2.0;
+        sites.0 += synonymous_sites;
+        sites.1 += 3.0 - synonymous_sites;
+
+        let (synonymous, nonsynonymous) = ng86.differences(a, b);
+        differences.0 += synonymous;
+        differences.1 += nonsynonymous;
+    }
+
+    if sites.0 <= 0.0 || sites.1 <= 0.0 {
+        return None;
+    }
+
+    let ds = jukes_cantor(differences.0 / sites.0)?;
+    let dn = jukes_cantor(differences.1 / sites.1)?;
+
+    (ds > 0.0).then(|| dn / ds)
+}
+
+/// Returns the ratio of the nonsynonymous to synonymous substitution rates (dN/dS) of two aligned
+/// coding sequences by the Nei-Gojobori (1986) method with a Jukes-Cantor correction, e.g.
+/// `dnds(human.sequence, mouse.sequence)`.
+///
+/// The sequences must be the same length and in frame, codons with gaps, ambiguous bases, or
+/// stops in either are skipped, and the genetic code defaults to the standard code (NCBI table 1).
+/// Pairs without synonymous differences, or whose differences saturate, are null.
+#[derive(Debug)]
+pub(crate) struct Dnds {
+    signature: Signature,
+}
+
+impl Default for Dnds {
+    fn default() -> Self {
+        let signature = Signature::one_of(
+            vec![
+                TypeSignature::Coercible(vec![DataType::Utf8, DataType::Utf8]),
+                TypeSignature::Coercible(vec![DataType::Utf8, DataType::Utf8, DataType::Int64]),
+            ],
+            Volatility::Immutable,
+        );
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for Dnds {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "dnds"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 2 && args.len() != 3 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes two aligned coding sequences and optionally a genetic code table",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let a = cast(&arrays[0], &DataType::Utf8)?;
+        let a = a.as_string::<i32>();
+
+        let b = cast(&arrays[1], &DataType::Utf8)?;
+        let b = b.as_string::<i32>();
+
+        let table_ids = arrays
+            .get(2)
+            .map(|array| cast(array, &DataType::Int64))
+            .transpose()?;
+        let table_ids = table_ids.as_ref().map(|a| a.as_primitive::<Int64Type>());
+
+        let ratios = (0..a.len())
+            .map(|i| {
+                let table_id = match table_ids {
+                    Some(table_ids) if table_ids.is_null(i) => return Ok(None),
+                    Some(table_ids) => table_ids.value(i),
+                    None => 1,
+                };
+
+                if a.is_null(i) || b.is_null(i) {
+                    return Ok(None);
+                }
+
+                let code = GeneticCode::try_from_id(table_id).ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "{} doesn't support genetic code table {}",
+                        self.name(),
+                        table_id
+                    ))
+                })?;
+
+                let (a, b) = (a.value(i).as_bytes(), b.value(i).as_bytes());
+
+                if a.len() != b.len() {
+                    return Err(DataFusionError::Execution(format!(
+                        "{} takes aligned sequences of the same length, got {} and {}",
+                        self.name(),
+                        a.len(),
+                        b.len()
+                    )));
+                }
+
+                Ok(dnds(a, b, &code))
+            })
+            .collect::<Result<Float64Array>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(ratios)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{codon_index, dnds, GeneticCode, Ng86};
+
+    #[test]
+    fn test_ng86_sites_and_differences() {
+        let code = GeneticCode::default();
+        let ng86 = Ng86 { code: &code };
+
+        let index = |codon: &[u8; 3]| codon_index(codon).unwrap();
+
+        // CTT's third position is fourfold degenerate, TTA has a synonymous change at its first
+        // and third positions.
+        assert_eq!(ng86.synonymous_sites(index(b"CTT")), 1.0);
+        assert!((ng86.synonymous_sites(index(b"TTA")) - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(ng86.synonymous_sites(index(b"ATG")), 0.0);
+        assert_eq!(ng86.synonymous_sites(index(b"TGG")), 0.0);
+
+        assert_eq!(ng86.differences(index(b"GCT"), index(b"GCC")), (1.0, 0.0));
+        assert_eq!(ng86.differences(index(b"GCT"), index(b"ACT")), (0.0, 1.0));
+
+        // CTT to ATG goes through either CTG (Leu) or ATT (Ile), so half a synonymous change.
+        assert_eq!(ng86.differences(index(b"CTT"), index(b"ATG")), (0.5, 1.5));
+    }
+
+    #[test]
+    fn test_dnds() {
+        let code = GeneticCode::default();
+
+        assert_eq!(dnds(b"ATGGCTAAA", b"ATGGCTAAA", &code), None);
+        assert_eq!(dnds(b"ATG---AAA", b"ATGGCTAAA", &code), None);
+
+        // Two synonymous and one nonsynonymous difference over 16 codons.
+        let ratio = dnds(
+            b"ATGGCTAAACTTGGTCCAGAATTTCGTAGCTGGCATACCGTTGATTAT",
+            b"ATGGCCAAACTTGGTCCAGATTTTCGTAGCTGGCACACCGTTGATTAT",
+            &code,
+        )
+        .unwrap();
+        assert!((ratio - 0.104292).abs() < 1e-6, "{}", ratio);
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, AsArray, StringArray},
+    datatypes::{DataType, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, TypeSignature, Volatility},
+};
+
+/// The UMI at the end of a read name, after the last `_` (UMI-tools) or `:` (Illumina), if it's
+/// made of bases, with dual UMIs joined by `+`.
+fn umi_from_name(name: &str) -> Option<&str> {
+    let umi = name.rsplit(['_', ':']).next()?;
+
+    let is_umi = !umi.is_empty()
+        && umi.bytes().all(|b| {
+            matches!(
+                b.to_ascii_uppercase(),
+                b'A' | b'C' | b'G' | b'T' | b'N' | b'+'
+            )
+        });
+
+    is_umi.then_some(umi)
+}
+
+/// The UMI in the first `length` bases of a sequence.
+fn umi_from_sequence(sequence: &str, length: i64) -> Option<&str> {
+    sequence.get(..usize::try_from(length).ok()?)
+}
+
+/// Extracts the UMI of a read, either from the end of its name, e.g.
+/// `extract_umi('READ1_ACGTACGT')` or `extract_umi('M1:1:FC:1:1:1:1:ACGT+TTGA')`, or from the
+/// first bases of its sequence, e.g. `extract_umi(sequence, 8)`.
+///
+/// Returns NULL if the name doesn't end in a UMI or the sequence is too short.
+#[derive(Debug)]
+pub(crate) struct ExtractUmi {
+    signature: Signature,
+}
+
+impl Default for ExtractUmi {
+    fn default() -> Self {
+        let signature = Signature::one_of(
+            vec![
+                TypeSignature::Exact(vec![DataType::Utf8]),
+                TypeSignature::Exact(vec![DataType::Utf8, DataType::Int64]),
+            ],
+            Volatility::Immutable,
+        );
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for ExtractUmi {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "extract_umi"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Utf8)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let umis = match arrays.as_slice() {
+            [names] => names
+                .as_string::<i32>()
+                .iter()
+                .map(|name| name.and_then(umi_from_name))
+                .collect::<StringArray>(),
+            [sequences, lengths] => {
+                let sequences = sequences.as_string::<i32>();
+                let lengths = lengths.as_primitive::<Int64Type>();
+
+                (0..sequences.len())
+                    .map(|i| {
+                        if sequences.is_null(i) || lengths.is_null(i) {
+                            return None;
+                        }
+
+                        umi_from_sequence(sequences.value(i), lengths.value(i))
+                    })
+                    .collect::<StringArray>()
+            }
+            _ => {
+                return Err(DataFusionError::Execution(format!(
+                    "{} takes a read name, or a sequence and a UMI length",
+                    self.name()
+                )))
+            }
+        };
+
+        Ok(ColumnarValue::Array(Arc::new(umis)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{umi_from_name, umi_from_sequence};
+
+    #[test]
+    fn test_umi_from_name() {
+        assert_eq!(umi_from_name("READ1_ACGTACGT"), Some("ACGTACGT"));
+        assert_eq!(
+            umi_from_name("M1:1:FC:1:1101:1000:2000:ACGT+TTGA"),
+            Some("ACGT+TTGA")
+        );
+        assert_eq!(umi_from_name("M1:1:FC:1:1101:1000:2000"), None);
+        assert_eq!(umi_from_name("READ1_"), None);
+
+        assert_eq!(umi_from_sequence("ACGTACGT", 4), Some("ACGT"));
+        assert_eq!(umi_from_sequence("ACG", 4), None);
+        assert_eq!(umi_from_sequence("ACG", -1), None);
+    }
+}
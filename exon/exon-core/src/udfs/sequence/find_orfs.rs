@@ -0,0 +1,285 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{
+        Array, AsArray, Int32Builder, Int64Builder, ListBuilder, StringBuilder, StructBuilder,
+    },
+    compute::cast,
+    datatypes::{DataType, Field, Fields, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, TypeSignature, Volatility},
+};
+
+use super::{genetic_code::GeneticCode, reverse_complement::reverse_complement};
+
+/// An open reading frame, from a start codon through a stop codon.
+#[derive(Debug, PartialEq)]
+struct Orf {
+    /// The 1-based start on the forward strand.
+    start: i64,
+    /// The 1-based, inclusive end on the forward strand, including the stop codon.
+    end: i64,
+    strand: &'static str,
+    /// The frame, 1 to 3, relative to the start of the ORF's strand.
+    frame: i32,
+    protein: String,
+}
+
+/// Find the ORFs of a strand as 0-based, half-open ranges of the strand with their proteins.
+fn strand_orfs(
+    sequence: &[u8],
+    code: &GeneticCode,
+    min_length: usize,
+) -> Vec<(usize, usize, String)> {
+    let mut orfs = Vec::new();
+
+    for frame in 0..3 {
+        let mut start = None;
+
+        for i in (frame..sequence.len().saturating_sub(2)).step_by(3) {
+            let codon = &sequence[i..i + 3];
+
+            match start {
+                None if code.is_start(codon) => start = Some(i),
+                Some(s) if code.is_stop(codon) => {
+                    start = None;
+
+                    // The start codon is translated as methionine whatever its usual amino acid.
+                    let protein = std::iter::once('M')
+                        .chain(
+                            sequence[s + 3..i]
+                                .chunks(3)
+                                .map(|codon| code.translate_codon(codon) as char),
+                        )
+                        .collect::<String>();
+
+                    if protein.len() >= min_length {
+                        orfs.push((s, i + 3, protein));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    orfs
+}
+
+/// Find the ORFs on both strands of a sequence, ordered by start.
+fn find_orfs(sequence: &str, code: &GeneticCode, min_length: usize) -> Vec<Orf> {
+    let length = sequence.len();
+
+    let forward = strand_orfs(sequence.as_bytes(), code, min_length)
+        .into_iter()
+        .map(|(s, e, protein)| Orf {
+            start: s as i64 + 1,
+            end: e as i64,
+            strand: "+",
+            frame: (s % 3) as i32 + 1,
+            protein,
+        });
+
+    let reverse_sequence = reverse_complement(sequence);
+    let reverse = strand_orfs(reverse_sequence.as_bytes(), code, min_length)
+        .into_iter()
+        .map(|(s, e, protein)| Orf {
+            start: (length - e) as i64 + 1,
+            end: (length - s) as i64,
+            strand: "-",
+            frame: (s % 3) as i32 + 1,
+            protein,
+        });
+
+    let mut orfs = forward.chain(reverse).collect::<Vec<_>>();
+    orfs.sort_by(|a, b| (a.start, a.strand).cmp(&(b.start, b.strand)));
+
+    orfs
+}
+
+fn orf_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("start", DataType::Int64, false),
+        Field::new("end", DataType::Int64, false),
+        Field::new("strand", DataType::Utf8, false),
+        Field::new("frame", DataType::Int32, false),
+        Field::new("protein", DataType::Utf8, false),
+    ])
+}
+
+/// Finds the open reading frames on both strands of a DNA sequence, e.g.
+/// `find_orfs(sequence, 100, 11)` for ORFs of at least 100 amino acids with the bacterial code.
+///
+/// ORFs run from the first start codon after a stop codon in a frame through the next stop
+/// codon, so nested starts aren't reported separately and ORFs without a stop codon before the
+/// end of the sequence are ignored. The minimum length is of the protein, which excludes the
+/// stop, and the genetic code defaults to the standard code (NCBI table 1).
+#[derive(Debug)]
+pub(crate) struct FindOrfs {
+    signature: Signature,
+}
+
+impl Default for FindOrfs {
+    fn default() -> Self {
+        let signature = Signature::one_of(
+            vec![
+                TypeSignature::Coercible(vec![DataType::Utf8, DataType::Int64]),
+                TypeSignature::Coercible(vec![DataType::Utf8, DataType::Int64, DataType::Int64]),
+            ],
+            Volatility::Immutable,
+        );
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for FindOrfs {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "find_orfs"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        let item = Field::new("item", DataType::Struct(orf_fields()), true);
+
+        Ok(DataType::List(Arc::new(item)))
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 2 && args.len() != 3 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes a sequence, a minimum length, and optionally a genetic code table",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = cast(&arrays[0], &DataType::Utf8)?;
+        let sequences = sequences.as_string::<i32>();
+
+        let min_lengths = cast(&arrays[1], &DataType::Int64)?;
+        let min_lengths = min_lengths.as_primitive::<Int64Type>();
+
+        let table_ids = arrays
+            .get(2)
+            .map(|array| cast(array, &DataType::Int64))
+            .transpose()?;
+        let table_ids = table_ids.as_ref().map(|a| a.as_primitive::<Int64Type>());
+
+        let mut builder = ListBuilder::new(StructBuilder::from_fields(orf_fields(), 0));
+
+        for i in 0..sequences.len() {
+            let table_id = match table_ids {
+                Some(table_ids) if table_ids.is_null(i) => None,
+                Some(table_ids) => Some(table_ids.value(i)),
+                None => Some(1),
+            };
+
+            let (Some(table_id), true, true) =
+                (table_id, sequences.is_valid(i), min_lengths.is_valid(i))
+            else {
+                builder.append_null();
+                continue;
+            };
+
+            let code = GeneticCode::try_from_id(table_id).ok_or_else(|| {
+                DataFusionError::Execution(format!(
+                    "{} doesn't support genetic code table {}",
+                    self.name(),
+                    table_id
+                ))
+            })?;
+
+            let min_length = usize::try_from(min_lengths.value(i)).map_err(|_| {
+                DataFusionError::Execution(format!(
+                    "{} takes a non-negative minimum length",
+                    self.name()
+                ))
+            })?;
+
+            let orfs = builder.values();
+
+            for orf in find_orfs(sequences.value(i), &code, min_length) {
+                field::<Int64Builder>(orfs, 0)?.append_value(orf.start);
+                field::<Int64Builder>(orfs, 1)?.append_value(orf.end);
+                field::<StringBuilder>(orfs, 2)?.append_value(orf.strand);
+                field::<Int32Builder>(orfs, 3)?.append_value(orf.frame);
+                field::<StringBuilder>(orfs, 4)?.append_value(&orf.protein);
+
+                orfs.append(true);
+            }
+
+            builder.append(true);
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(builder.finish())))
+    }
+}
+
+fn field<T: arrow::array::ArrayBuilder>(builder: &mut StructBuilder, i: usize) -> Result<&mut T> {
+    builder
+        .field_builder::<T>(i)
+        .ok_or_else(|| DataFusionError::Internal(format!("Invalid ORF field builder {}", i)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_orfs, GeneticCode, Orf};
+
+    #[test]
+    fn test_find_orfs() {
+        // ATG AAA TGA on the forward strand, and its reverse complement TCA TTT CAT contains
+        // no start, so there is a single ORF.
+        let orfs = find_orfs("CCATGAAATGACC", &GeneticCode::default(), 1);
+
+        assert_eq!(
+            orfs,
+            vec![Orf {
+                start: 3,
+                end: 11,
+                strand: "+",
+                frame: 3,
+                protein: "MK".to_string(),
+            }]
+        );
+
+        assert!(find_orfs("CCATGAAATGACC", &GeneticCode::default(), 3).is_empty());
+
+        // The reverse complement of TTACTTCAT is ATGAAGTAA.
+        let orfs = find_orfs("GTTACTTCATG", &GeneticCode::default(), 1);
+
+        assert_eq!(
+            orfs,
+            vec![Orf {
+                start: 2,
+                end: 10,
+                strand: "-",
+                frame: 2,
+                protein: "MK".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{AsArray, Int64Array},
+    datatypes::DataType,
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+/// The number of consecutive G and C bases at the 3' end of a sequence.
+fn gc_clamp(sequence: &str) -> i64 {
+    sequence
+        .bytes()
+        .rev()
+        .take_while(|b| matches!(b, b'G' | b'C' | b'g' | b'c'))
+        .count() as i64
+}
+
+/// Returns the length of the GC clamp of a primer, i.e. the number of consecutive G and C bases
+/// at its 3' end, like primer3's `PRIMER_GC_CLAMP`.
+#[derive(Debug)]
+pub(crate) struct GCClamp {
+    signature: Signature,
+}
+
+impl Default for GCClamp {
+    fn default() -> Self {
+        let signature = Signature::exact(vec![DataType::Utf8], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for GCClamp {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "gc_clamp"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Int64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 1 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes one argument",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let clamps = arrays[0]
+            .as_string::<i32>()
+            .iter()
+            .map(|sequence| sequence.map(gc_clamp))
+            .collect::<Int64Array>();
+
+        Ok(ColumnarValue::Array(Arc::new(clamps)))
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! NCBI genetic codes for translating codons to amino acids.
+
+/// The amino acids and start codons of a genetic code, indexed by codon in TCAG order, as in
+/// the NCBI `gc.prt` tables.
+#[derive(Debug)]
+struct GeneticCodeTable {
+    id: i64,
+    amino_acids: &'static [u8; 64],
+    starts: &'static [u8; 64],
+}
+
+const TABLES: &[GeneticCodeTable] = &[
+    GeneticCodeTable {
+        id: 1,
+        amino_acids: b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"---M---------------M---------------M----------------------------",
+    },
+    GeneticCodeTable {
+        id: 2,
+        amino_acids: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+        starts: b"--------------------------------MMMM---------------M------------",
+    },
+    GeneticCodeTable {
+        id: 3,
+        amino_acids: b"FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"----------------------------------MM---------------M------------",
+    },
+    GeneticCodeTable {
+        id: 4,
+        amino_acids: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"--MM---------------M------------MMMM---------------M------------",
+    },
+    GeneticCodeTable {
+        id: 5,
+        amino_acids: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+        starts: b"---M----------------------------MMMM---------------M------------",
+    },
+    GeneticCodeTable {
+        id: 6,
+        amino_acids: b"FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"-----------------------------------M----------------------------",
+    },
+    GeneticCodeTable {
+        id: 9,
+        amino_acids: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: b"-----------------------------------M---------------M------------",
+    },
+    GeneticCodeTable {
+        id: 10,
+        amino_acids: b"FFLLSSSSYY**CCCWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"---M-------------------------------M----------------------------",
+    },
+    GeneticCodeTable {
+        id: 11,
+        amino_acids: b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"---M---------------M------------MMMM---------------M------------",
+    },
+    GeneticCodeTable {
+        id: 12,
+        amino_acids: b"FFLLSSSSYY**CC*WLLLSPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"-------------------M---------------M----------------------------",
+    },
+    GeneticCodeTable {
+        id: 13,
+        amino_acids: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSGGVVVVAAAADDEEGGGG",
+        starts: b"---M------------------------------MM---------------M------------",
+    },
+    GeneticCodeTable {
+        id: 14,
+        amino_acids: b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: b"-----------------------------------M----------------------------",
+    },
+    GeneticCodeTable {
+        id: 16,
+        amino_acids: b"FFLLSSSSYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"-----------------------------------M----------------------------",
+    },
+    GeneticCodeTable {
+        id: 21,
+        amino_acids: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: b"-----------------------------------M---------------M------------",
+    },
+    GeneticCodeTable {
+        id: 22,
+        amino_acids: b"FFLLSS*SYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"-----------------------------------M----------------------------",
+    },
+    GeneticCodeTable {
+        id: 23,
+        amino_acids: b"FF*LSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"--------------------------------M--M---------------M------------",
+    },
+    GeneticCodeTable {
+        id: 24,
+        amino_acids: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        starts: b"---M---------------M---------------M---------------M------------",
+    },
+    GeneticCodeTable {
+        id: 25,
+        amino_acids: b"FFLLSSSSYY**CCGWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"---M-------------------------------M---------------M------------",
+    },
+];
+
+/// A genetic code, e.g. the standard code (table 1) or the bacterial code (table 11).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct GeneticCode {
+    table: &'static GeneticCodeTable,
+}
+
+impl Default for GeneticCode {
+    fn default() -> Self {
+        Self { table: &TABLES[0] }
+    }
+}
+
+/// The index of a codon in TCAG order, or `None` if it has a base other than ACGTU.
+pub(crate) fn codon_index(codon: &[u8]) -> Option<usize> {
+    let [a, b, c] = codon else {
+        return None;
+    };
+
+    let base = |b: &u8| match b.to_ascii_uppercase() {
+        b'T' | b'U' => Some(0),
+        b'C' => Some(1),
+        b'A' => Some(2),
+        b'G' => Some(3),
+        _ => None,
+    };
+
+    Some(base(a)? * 16 + base(b)? * 4 + base(c)?)
+}
+
+/// The codon at an index in TCAG order, the inverse of [`codon_index`].
+pub(crate) fn codon(index: usize) -> [u8; 3] {
+    const BASES: &[u8; 4] = b"TCAG";
+
+    [
+        BASES[index / 16 % 4],
+        BASES[index / 4 % 4],
+        BASES[index % 4],
+    ]
+}
+
+impl GeneticCode {
+    /// The NCBI genetic code with the given translation table id, or `None` if it isn't
+    /// supported.
+    pub(crate) fn try_from_id(id: i64) -> Option<Self> {
+        TABLES
+            .iter()
+            .find(|table| table.id == id)
+            .map(|table| Self { table })
+    }
+
+    /// The amino acid of a codon, `*` for a stop codon and `X` for a codon with ambiguous bases.
+    pub(crate) fn translate_codon(&self, codon: &[u8]) -> u8 {
+        codon_index(codon).map_or(b'X', |i| self.table.amino_acids[i])
+    }
+
+    /// Whether the codon is a start codon of this code.
+    pub(crate) fn is_start(&self, codon: &[u8]) -> bool {
+        codon_index(codon).is_some_and(|i| self.table.starts[i] == b'M')
+    }
+
+    /// Whether the codon is a stop codon of this code.
+    pub(crate) fn is_stop(&self, codon: &[u8]) -> bool {
+        self.translate_codon(codon) == b'*'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{codon, codon_index, GeneticCode};
+
+    #[test]
+    fn test_translate_codon() {
+        let standard = GeneticCode::default();
+
+        assert_eq!(standard.translate_codon(b"ATG"), b'M');
+        assert_eq!(standard.translate_codon(b"aug"), b'M');
+        assert_eq!(standard.translate_codon(b"TGG"), b'W');
+        assert_eq!(standard.translate_codon(b"TGA"), b'*');
+        assert_eq!(standard.translate_codon(b"GGN"), b'X');
+        assert!(standard.is_start(b"CTG"));
+        assert!(!standard.is_start(b"GTG"));
+
+        let mitochondrial = GeneticCode::try_from_id(2).unwrap();
+        assert_eq!(mitochondrial.translate_codon(b"TGA"), b'W');
+        assert!(mitochondrial.is_stop(b"AGA"));
+
+        let bacterial = GeneticCode::try_from_id(11).unwrap();
+        assert!(bacterial.is_start(b"GTG"));
+
+        assert!(GeneticCode::try_from_id(7).is_none());
+    }
+
+    #[test]
+    fn test_codon() {
+        assert_eq!(&codon(0), b"TTT");
+        assert_eq!(&codon(63), b"GGG");
+
+        for i in 0..64 {
+            assert_eq!(codon_index(&codon(i)), Some(i));
+        }
+    }
+}
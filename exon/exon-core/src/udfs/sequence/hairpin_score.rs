@@ -0,0 +1,127 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{AsArray, Int64Array},
+    datatypes::DataType,
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+/// The shortest loop of a hairpin.
+const MIN_LOOP_LENGTH: usize = 3;
+
+/// The score of a Watson-Crick pair, 2 for A-T and 4 for G-C as in the Wallace rule, or `None`
+/// if the bases don't pair.
+fn pair_score(a: u8, b: u8) -> Option<i64> {
+    match (a.to_ascii_uppercase(), b.to_ascii_uppercase()) {
+        (b'A', b'T') | (b'T', b'A') => Some(2),
+        (b'G', b'C') | (b'C', b'G') => Some(4),
+        _ => None,
+    }
+}
+
+/// The score of the strongest hairpin stem, i.e. the run of consecutive base pairs closing a
+/// loop of at least three bases with the highest sum of pair scores.
+fn hairpin_score(sequence: &[u8]) -> i64 {
+    let n = sequence.len();
+    let mut best = 0;
+
+    // Extend each innermost pair (i, j) outwards while the bases keep pairing.
+    for i in 0..n {
+        for j in i + MIN_LOOP_LENGTH + 1..n {
+            let score = (0..=i)
+                .zip(j..n)
+                .map_while(|(a, b)| pair_score(sequence[i - a], sequence[b]))
+                .sum::<i64>();
+
+            best = best.max(score);
+        }
+    }
+
+    best
+}
+
+/// Scores the strongest hairpin a primer could form, the sum over the pairs of its best stem of 2
+/// per A-T and 4 per G-C pair, with loops of at least three bases. Higher scores are more stable
+/// hairpins, and 0 means no stem can form.
+#[derive(Debug)]
+pub(crate) struct HairpinScore {
+    signature: Signature,
+}
+
+impl Default for HairpinScore {
+    fn default() -> Self {
+        let signature = Signature::exact(vec![DataType::Utf8], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for HairpinScore {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "hairpin_score"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Int64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 1 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes one argument",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let scores = arrays[0]
+            .as_string::<i32>()
+            .iter()
+            .map(|sequence| sequence.map(|s| hairpin_score(s.as_bytes())))
+            .collect::<Int64Array>();
+
+        Ok(ColumnarValue::Array(Arc::new(scores)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hairpin_score;
+
+    #[test]
+    fn test_hairpin_score() {
+        // GGGC pairs with GCCC around the AAAA loop.
+        assert_eq!(hairpin_score(b"GGGCAAAAGCCC"), 16);
+        // G-C around the CAAG loop.
+        assert_eq!(hairpin_score(b"GCAAGC"), 4);
+        // The A-T pairs would need a loop shorter than three bases.
+        assert_eq!(hairpin_score(b"AATT"), 0);
+        assert_eq!(hairpin_score(b"AAAAAAAA"), 0);
+    }
+}
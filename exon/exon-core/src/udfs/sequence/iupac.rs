@@ -0,0 +1,116 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! IUPAC nucleotide codes as bit masks of the bases they stand for.
+
+const A: u8 = 1;
+const C: u8 = 2;
+const G: u8 = 4;
+const T: u8 = 8;
+
+/// The bases of an IUPAC nucleotide code, case-insensitive, or 0 if it isn't one.
+pub(crate) fn base_mask(code: u8) -> u8 {
+    match code.to_ascii_uppercase() {
+        b'A' => A,
+        b'C' => C,
+        b'G' => G,
+        b'T' | b'U' => T,
+        b'R' => A | G,
+        b'Y' => C | T,
+        b'S' => C | G,
+        b'W' => A | T,
+        b'K' => G | T,
+        b'M' => A | C,
+        b'B' => C | G | T,
+        b'D' => A | G | T,
+        b'H' => A | C | T,
+        b'V' => A | C | G,
+        b'N' => A | C | G | T,
+        _ => 0,
+    }
+}
+
+/// The masks of an IUPAC pattern, or `None` if it's empty or has a character that isn't an
+/// IUPAC code.
+pub(crate) fn pattern_masks(pattern: &str) -> Option<Vec<u8>> {
+    let masks = pattern.bytes().map(base_mask).collect::<Vec<_>>();
+
+    if masks.is_empty() || masks.contains(&0) {
+        None
+    } else {
+        Some(masks)
+    }
+}
+
+fn complement_mask(mask: u8) -> u8 {
+    ((mask & A) << 3) | ((mask & C) << 1) | ((mask & G) >> 1) | ((mask & T) >> 3)
+}
+
+/// The masks of the reverse complement of a pattern.
+pub(crate) fn reverse_complement_masks(masks: &[u8]) -> Vec<u8> {
+    masks.iter().rev().map(|m| complement_mask(*m)).collect()
+}
+
+/// Whether a sequence base matches a pattern mask, i.e. all the bases it may be are allowed by
+/// the pattern, so an `N` in the sequence only matches an `N` in the pattern.
+pub(crate) fn matches(base: u8, mask: u8) -> bool {
+    let base = base_mask(base);
+
+    base != 0 && base & !mask == 0
+}
+
+/// The 0-based starts of the exact matches of a pattern in a sequence, including overlapping
+/// matches.
+pub(crate) fn find_matches<'a>(
+    sequence: &'a [u8],
+    masks: &'a [u8],
+) -> impl Iterator<Item = usize> + 'a {
+    sequence
+        .windows(masks.len())
+        .enumerate()
+        .filter(move |(_, window)| window.iter().zip(masks).all(|(b, m)| matches(*b, *m)))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_matches, pattern_masks, reverse_complement_masks};
+
+    #[test]
+    fn test_find_matches() {
+        let masks = pattern_masks("GRCGYC").unwrap();
+
+        let starts = find_matches(b"GACGTCnnGGCGCCggcgcc", &masks).collect::<Vec<_>>();
+        assert_eq!(starts, vec![0, 8, 14]);
+
+        assert_eq!(
+            reverse_complement_masks(&pattern_masks("GATC").unwrap()),
+            pattern_masks("GATC").unwrap()
+        );
+        assert_eq!(
+            reverse_complement_masks(&pattern_masks("GGTCTC").unwrap()),
+            pattern_masks("GAGACC").unwrap()
+        );
+        assert_eq!(
+            reverse_complement_masks(&pattern_masks("ACRY").unwrap()),
+            pattern_masks("RYGT").unwrap()
+        );
+
+        assert!(pattern_masks("GAXC").is_none());
+        assert!(pattern_masks("").is_none());
+        assert!(find_matches(b"GANTC", &pattern_masks("GAATC").unwrap())
+            .next()
+            .is_none());
+    }
+}
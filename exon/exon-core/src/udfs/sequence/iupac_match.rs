@@ -0,0 +1,100 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, AsArray, Int64Builder, ListBuilder},
+    datatypes::{DataType, Field},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::iupac::{find_matches, pattern_masks};
+
+/// Returns the 1-based starts of the exact matches of an IUPAC pattern in a sequence, e.g.
+/// `iupac_match(sequence, 'GGNCC')`, including overlapping matches.
+///
+/// Ambiguous bases in the sequence only match pattern codes that include all of their bases, so
+/// an `N` in the sequence only matches an `N` in the pattern.
+#[derive(Debug)]
+pub(crate) struct IupacMatch {
+    signature: Signature,
+}
+
+impl Default for IupacMatch {
+    fn default() -> Self {
+        let signature =
+            Signature::exact(vec![DataType::Utf8, DataType::Utf8], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for IupacMatch {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "iupac_match"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        let item = Field::new("item", DataType::Int64, true);
+
+        Ok(DataType::List(Arc::new(item)))
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 2 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes a sequence and an IUPAC pattern",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = arrays[0].as_string::<i32>();
+        let patterns = arrays[1].as_string::<i32>();
+
+        let mut builder = ListBuilder::new(Int64Builder::new());
+
+        for i in 0..sequences.len() {
+            if sequences.is_null(i) || patterns.is_null(i) {
+                builder.append_null();
+                continue;
+            }
+
+            let masks = pattern_masks(patterns.value(i)).ok_or_else(|| {
+                DataFusionError::Execution(format!("{} is not an IUPAC pattern", patterns.value(i)))
+            })?;
+
+            for start in find_matches(sequences.value(i).as_bytes(), &masks) {
+                builder.values().append_value(start as i64 + 1);
+            }
+
+            builder.append(true);
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(builder.finish())))
+    }
+}
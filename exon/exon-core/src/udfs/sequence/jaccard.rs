@@ -0,0 +1,176 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, cmp::Ordering, sync::Arc};
+
+use arrow::{
+    array::{AsArray, Float64Array},
+    datatypes::DataType,
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::minhash_signature::{max_hash, signature_fields, MinHashSignature};
+
+/// Estimate the Jaccard similarity of the k-mers of two signatures, downsampling the one with the
+/// smaller scaled to the other, or `None` if they have no hashes.
+fn jaccard(a: &MinHashSignature, b: &MinHashSignature) -> Result<Option<f64>> {
+    if a.k != b.k {
+        return Err(DataFusionError::Execution(format!(
+            "jaccard compares signatures with the same k, got {} and {}",
+            a.k, b.k
+        )));
+    }
+
+    let max_hash = max_hash(a.scaled.max(b.scaled));
+
+    let mut a = a.hashes.range(..=max_hash).peekable();
+    let mut b = b.hashes.range(..=max_hash).peekable();
+
+    // Count the intersection and union of the sorted hashes in one pass.
+    let (mut intersection, mut union) = (0, 0);
+    while let (Some(a_hash), Some(b_hash)) = (a.peek(), b.peek()) {
+        match a_hash.cmp(b_hash) {
+            Ordering::Less => {
+                a.next();
+            }
+            Ordering::Greater => {
+                b.next();
+            }
+            Ordering::Equal => {
+                a.next();
+                b.next();
+                intersection += 1;
+            }
+        }
+
+        union += 1;
+    }
+    union += a.count() + b.count();
+
+    if union == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(intersection as f64 / union as f64))
+}
+
+/// Estimates the Jaccard similarity of the k-mers of two MinHash signatures from
+/// `minhash_signature`, e.g. for a matrix of the similarity of samples.
+///
+/// Signatures with different scaled factors are compared at the larger one, and signatures with
+/// different k-mer sizes can't be compared.
+#[derive(Debug)]
+pub(crate) struct Jaccard {
+    signature: Signature,
+}
+
+impl Default for Jaccard {
+    fn default() -> Self {
+        let signature_type = DataType::Struct(signature_fields());
+        let signature = Signature::exact(
+            vec![signature_type.clone(), signature_type],
+            Volatility::Immutable,
+        );
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for Jaccard {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "jaccard"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 2 {
+            return Err(DataFusionError::Execution(
+                "jaccard takes two MinHash signatures".to_string(),
+            ));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let signatures = arrays
+            .iter()
+            .map(|array| {
+                let array = array.as_struct_opt().ok_or_else(|| {
+                    DataFusionError::Execution(
+                        "jaccard takes MinHash signatures from minhash_signature".to_string(),
+                    )
+                })?;
+
+                MinHashSignature::from_array(array)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let similarities = signatures[0]
+            .iter()
+            .zip(&signatures[1])
+            .map(|(a, b)| match (a, b) {
+                (Some(a), Some(b)) => jaccard(a, b),
+                _ => Ok(None),
+            })
+            .collect::<Result<Float64Array>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(similarities)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{jaccard, MinHashSignature};
+
+    fn signature(k: usize, scaled: u64, hashes: &[u64]) -> MinHashSignature {
+        MinHashSignature {
+            k,
+            scaled,
+            hashes: hashes.iter().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn test_jaccard() {
+        let a = signature(31, 1, &[1, 2, 3, 4]);
+        let b = signature(31, 1, &[3, 4, 5, 6, 7, 8]);
+
+        assert_eq!(jaccard(&a, &b).unwrap(), Some(0.25));
+        assert_eq!(jaccard(&a, &a).unwrap(), Some(1.0));
+
+        // Hashes over the max hash of the larger scaled are left out of both.
+        let c = signature(31, 2, &[1, 2, 3]);
+        let d = signature(31, 1, &[2, u64::MAX - 1]);
+        assert_eq!(jaccard(&c, &d).unwrap(), Some(1.0 / 3.0));
+
+        assert_eq!(
+            jaccard(&signature(31, 1, &[]), &signature(31, 1, &[])).unwrap(),
+            None
+        );
+        assert!(jaccard(&a, &signature(21, 1, &[1])).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! K-mers of nucleotide sequences, 2-bit encoded into a `u64`, for sketches of reads.
+
+/// The largest k-mer that fits in a `u64`.
+pub(crate) const MAX_KMER_SIZE: usize = 32;
+
+fn base_code(base: u8) -> Option<u64> {
+    match base {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' | b'U' | b'u' => Some(3),
+        _ => None,
+    }
+}
+
+/// An iterator over the canonical k-mers of a sequence, the smaller of each k-mer and its reverse
+/// complement, so a read and its reverse complement have the same k-mers.
+///
+/// K-mers with bases other than ACGT, e.g. N, are skipped.
+pub(crate) struct CanonicalKmers<'a> {
+    sequence: std::slice::Iter<'a, u8>,
+    k: usize,
+    mask: u64,
+    forward: u64,
+    reverse: u64,
+    length: usize,
+}
+
+impl<'a> CanonicalKmers<'a> {
+    /// Create an iterator over the canonical k-mers of `sequence`, for `k` in 1 to 32.
+    pub(crate) fn new(sequence: &'a [u8], k: usize) -> Self {
+        debug_assert!((1..=MAX_KMER_SIZE).contains(&k));
+
+        let mask = if k == MAX_KMER_SIZE {
+            u64::MAX
+        } else {
+            (1 << (2 * k)) - 1
+        };
+
+        Self {
+            sequence: sequence.iter(),
+            k,
+            mask,
+            forward: 0,
+            reverse: 0,
+            length: 0,
+        }
+    }
+}
+
+impl Iterator for CanonicalKmers<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for &base in self.sequence.by_ref() {
+            let Some(code) = base_code(base) else {
+                self.length = 0;
+                continue;
+            };
+
+            self.forward = ((self.forward << 2) | code) & self.mask;
+            self.reverse = (self.reverse >> 2) | ((3 - code) << (2 * (self.k - 1)));
+            self.length += 1;
+
+            if self.length >= self.k {
+                return Some(self.forward.min(self.reverse));
+            }
+        }
+
+        None
+    }
+}
+
+/// Hash an encoded k-mer with the splitmix64 finalizer, which spreads k-mers that share a prefix.
+pub(crate) fn hash_kmer(kmer: u64) -> u64 {
+    let mut hash = kmer.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_kmers() {
+        let kmers = CanonicalKmers::new(b"ACGTNacg", 3).collect::<Vec<_>>();
+
+        // ACG and CGT are reverse complements, and the N starts the k-mers over.
+        assert_eq!(kmers, vec![0b000110, 0b000110, 0b000110]);
+
+        let forward = CanonicalKmers::new(b"AACCGGTTAC", 5).collect::<Vec<_>>();
+        let mut reverse = CanonicalKmers::new(b"GTAACCGGTT", 5).collect::<Vec<_>>();
+        reverse.reverse();
+
+        assert_eq!(forward, reverse);
+        assert_eq!(CanonicalKmers::new(b"ACGT", 32).count(), 0);
+        assert_eq!(CanonicalKmers::new(&[b'A'; 33], 32).count(), 2);
+    }
+}
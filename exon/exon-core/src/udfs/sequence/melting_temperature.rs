@@ -0,0 +1,249 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, AsArray, Float64Array},
+    compute::cast,
+    datatypes::DataType,
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, TypeSignature, Volatility},
+};
+
+/// The gas constant in cal/(K mol).
+const R: f64 = 1.987;
+
+/// The monovalent cation concentration in mol/L.
+const SODIUM: f64 = 0.05;
+
+/// The total oligo concentration in mol/L.
+const OLIGO: f64 = 250e-9;
+
+/// The SantaLucia (1998) unified nearest-neighbor enthalpies (kcal/mol) and entropies
+/// (cal/(K mol)) of the ten distinct Watson-Crick stacks.
+const NEAREST_NEIGHBORS: [(&[u8; 2], f64, f64); 10] = [
+    (b"AA", -7.9, -22.2),
+    (b"AT", -7.2, -20.4),
+    (b"TA", -7.2, -21.3),
+    (b"CA", -8.5, -22.7),
+    (b"GT", -8.4, -22.4),
+    (b"CT", -7.8, -21.0),
+    (b"GA", -8.2, -22.2),
+    (b"CG", -10.6, -27.2),
+    (b"GC", -9.8, -24.4),
+    (b"GG", -8.0, -19.9),
+];
+
+/// The methods of estimating the melting temperature of an oligo.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Method {
+    /// The Wallace rule, 2 °C per A/T and 4 °C per G/C, for oligos up to ~14 nt.
+    Wallace,
+    /// The GC content formula `64.9 + 41 (GC - 16.4) / N`.
+    Gc,
+    /// The SantaLucia (1998) nearest-neighbor model at 50 mM Na+ and 250 nM oligo.
+    NearestNeighbor,
+}
+
+impl std::str::FromStr for Method {
+    type Err = DataFusionError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "wallace" => Ok(Self::Wallace),
+            "gc" => Ok(Self::Gc),
+            "nn" => Ok(Self::NearestNeighbor),
+            _ => Err(DataFusionError::Execution(format!(
+                "Invalid melting temperature method {}, expected wallace, gc, or nn",
+                s
+            ))),
+        }
+    }
+}
+
+fn complement(base: u8) -> u8 {
+    match base {
+        b'A' => b'T',
+        b'C' => b'G',
+        b'G' => b'C',
+        _ => b'A',
+    }
+}
+
+fn nearest_neighbor(stack: [u8; 2]) -> (f64, f64) {
+    let reverse_complement = [complement(stack[1]), complement(stack[0])];
+
+    NEAREST_NEIGHBORS
+        .iter()
+        .find(|(s, _, _)| **s == stack || **s == reverse_complement)
+        .map(|(_, dh, ds)| (*dh, *ds))
+        .unwrap_or_default()
+}
+
+fn nearest_neighbor_tm(sequence: &[u8]) -> Option<f64> {
+    if sequence.len() < 2 {
+        return None;
+    }
+
+    let (mut dh, mut ds) = sequence
+        .windows(2)
+        .map(|w| nearest_neighbor([w[0], w[1]]))
+        .fold((0.0, 0.0), |(dh, ds), (h, s)| (dh + h, ds + s));
+
+    // The initiation terms of each terminal base pair.
+    for base in [sequence[0], sequence[sequence.len() - 1]] {
+        let (h, s) = match base {
+            b'G' | b'C' => (0.1, -2.8),
+            _ => (2.3, 4.1),
+        };
+
+        dh += h;
+        ds += s;
+    }
+
+    let self_complementary = sequence
+        .iter()
+        .rev()
+        .map(|b| complement(*b))
+        .eq(sequence.iter().copied());
+
+    let concentration = if self_complementary {
+        ds += -1.4;
+        OLIGO
+    } else {
+        OLIGO / 4.0
+    };
+
+    ds += 0.368 * (sequence.len() - 1) as f64 * SODIUM.ln();
+
+    Some(dh * 1000.0 / (ds + R * concentration.ln()) - 273.15)
+}
+
+/// The melting temperature of an oligo in °C, or `None` if it has bases other than ACGT.
+fn melting_temperature(sequence: &str, method: Method) -> Option<f64> {
+    let sequence = sequence.to_ascii_uppercase().into_bytes();
+
+    if sequence.is_empty() || !sequence.iter().all(|b| b"ACGT".contains(b)) {
+        return None;
+    }
+
+    let gc = sequence
+        .iter()
+        .filter(|b| **b == b'G' || **b == b'C')
+        .count() as f64;
+    let n = sequence.len() as f64;
+
+    match method {
+        Method::Wallace => Some(2.0 * (n - gc) + 4.0 * gc),
+        Method::Gc => Some(64.9 + 41.0 * (gc - 16.4) / n),
+        Method::NearestNeighbor => nearest_neighbor_tm(&sequence),
+    }
+}
+
+/// Estimates the melting temperature in °C of a DNA oligo with `wallace`, `gc`, or
+/// nearest-neighbor (`nn`, the default) thermodynamics, e.g.
+/// `melting_temperature(primer, 'wallace')`.
+///
+/// Oligos with bases other than ACGT have no melting temperature and are null.
+#[derive(Debug)]
+pub(crate) struct MeltingTemperature {
+    signature: Signature,
+}
+
+impl Default for MeltingTemperature {
+    fn default() -> Self {
+        let signature = Signature::one_of(
+            vec![
+                TypeSignature::Exact(vec![DataType::Utf8]),
+                TypeSignature::Exact(vec![DataType::Utf8, DataType::Utf8]),
+            ],
+            Volatility::Immutable,
+        );
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for MeltingTemperature {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "melting_temperature"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.is_empty() || args.len() > 2 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes a sequence and optionally a method",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = cast(&arrays[0], &DataType::Utf8)?;
+        let sequences = sequences.as_string::<i32>();
+
+        let methods = arrays
+            .get(1)
+            .map(|array| cast(array, &DataType::Utf8))
+            .transpose()?;
+        let methods = methods.as_ref().map(|a| a.as_string::<i32>());
+
+        let temperatures = (0..sequences.len())
+            .map(|i| {
+                let method = match methods {
+                    Some(methods) if methods.is_null(i) => return Ok(None),
+                    Some(methods) => methods.value(i).parse()?,
+                    None => Method::NearestNeighbor,
+                };
+
+                if sequences.is_null(i) {
+                    return Ok(None);
+                }
+
+                Ok(melting_temperature(sequences.value(i), method))
+            })
+            .collect::<Result<Float64Array>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(temperatures)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{melting_temperature, Method};
+
+    #[test]
+    fn test_melting_temperature() {
+        assert_eq!(melting_temperature("ACGTACGT", Method::Wallace), Some(24.0));
+        assert_eq!(melting_temperature("ACGN", Method::Wallace), None);
+
+        let tm = melting_temperature("AGCGGATAACAATTTCACACAGGA", Method::NearestNeighbor);
+        assert!((tm.unwrap() - 56.715).abs() < 0.01, "{:?}", tm);
+    }
+}
@@ -0,0 +1,386 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, collections::BTreeSet, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, Int64Array, ListArray, StructArray, UInt64Array},
+    buffer::OffsetBuffer,
+    datatypes::{DataType, Field, Fields, Int64Type, UInt64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{
+        function::AccumulatorArgs, Accumulator, AggregateUDFImpl, Signature, Volatility,
+    },
+    physical_plan::expressions::Literal,
+    scalar::ScalarValue,
+};
+
+/// The seed sourmash hashes k-mers with.
+const SEED: u64 = 42;
+
+fn fmix64(mut k: u64) -> u64 {
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    k ^ (k >> 33)
+}
+
+/// The first 64 bits of the 128-bit x64 MurmurHash3 of some bytes.
+fn murmurhash3_x64_128(data: &[u8], seed: u64) -> u64 {
+    const C1: u64 = 0x87c3_7b91_1142_53d5;
+    const C2: u64 = 0x4cf5_ad43_2745_937f;
+
+    let mut h1 = seed;
+    let mut h2 = seed;
+
+    let blocks = data.chunks_exact(16);
+    let tail = blocks.remainder();
+
+    for block in blocks {
+        let (k1, k2) = block.split_at(8);
+        let k1 = u64::from_le_bytes(k1.try_into().unwrap_or_default());
+        let k2 = u64::from_le_bytes(k2.try_into().unwrap_or_default());
+
+        h1 ^= k1.wrapping_mul(C1).rotate_left(31).wrapping_mul(C2);
+        h1 = h1
+            .rotate_left(27)
+            .wrapping_add(h2)
+            .wrapping_mul(5)
+            .wrapping_add(0x52dc_e729);
+
+        h2 ^= k2.wrapping_mul(C2).rotate_left(33).wrapping_mul(C1);
+        h2 = h2
+            .rotate_left(31)
+            .wrapping_add(h1)
+            .wrapping_mul(5)
+            .wrapping_add(0x3849_5ab5);
+    }
+
+    let (mut k1, mut k2) = (0u64, 0u64);
+    for (i, byte) in tail.iter().enumerate() {
+        if i < 8 {
+            k1 |= (*byte as u64) << (8 * i);
+        } else {
+            k2 |= (*byte as u64) << (8 * (i - 8));
+        }
+    }
+
+    if tail.len() > 8 {
+        h2 ^= k2.wrapping_mul(C2).rotate_left(33).wrapping_mul(C1);
+    }
+    if !tail.is_empty() {
+        h1 ^= k1.wrapping_mul(C1).rotate_left(31).wrapping_mul(C2);
+    }
+
+    h1 ^= data.len() as u64;
+    h2 ^= data.len() as u64;
+    h1 = h1.wrapping_add(h2);
+    h2 = h2.wrapping_add(h1);
+    h1 = fmix64(h1);
+    h2 = fmix64(h2);
+
+    h1.wrapping_add(h2)
+}
+
+/// The largest hash kept by a FracMinHash sketch with a scaled factor, as in sourmash.
+pub(crate) fn max_hash(scaled: u64) -> u64 {
+    match scaled {
+        0 | 1 => u64::MAX,
+        _ => (u64::MAX as f64 / scaled as f64) as u64,
+    }
+}
+
+/// Add the hashes of the canonical k-mers of a sequence, the lexicographically smaller of each
+/// k-mer and its reverse complement, that are at most `max_hash`. K-mers with bases other than
+/// ACGT are skipped.
+fn add_kmer_hashes(sequence: &[u8], k: usize, max_hash: u64, hashes: &mut BTreeSet<u64>) {
+    let sequence = sequence.to_ascii_uppercase();
+    let mut reverse_complement = vec![0; k];
+
+    for kmer in sequence.windows(k) {
+        let mut valid = true;
+        for (base, complement) in kmer.iter().rev().zip(reverse_complement.iter_mut()) {
+            *complement = match base {
+                b'A' => b'T',
+                b'C' => b'G',
+                b'G' => b'C',
+                b'T' => b'A',
+                _ => {
+                    valid = false;
+                    break;
+                }
+            };
+        }
+
+        if !valid {
+            continue;
+        }
+
+        let hash = murmurhash3_x64_128(kmer.min(reverse_complement.as_slice()), SEED);
+        if hash <= max_hash {
+            hashes.insert(hash);
+        }
+    }
+}
+
+pub(crate) fn signature_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("k", DataType::Int64, false),
+        Field::new("scaled", DataType::Int64, false),
+        Field::new(
+            "hashes",
+            DataType::List(Arc::new(Field::new("item", DataType::UInt64, true))),
+            false,
+        ),
+    ])
+}
+
+/// A FracMinHash signature, the k-mer hashes of a set of sequences at most `max_hash(scaled)`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MinHashSignature {
+    pub(crate) k: usize,
+    pub(crate) scaled: u64,
+    pub(crate) hashes: BTreeSet<u64>,
+}
+
+impl MinHashSignature {
+    fn new(k: usize, scaled: u64) -> Self {
+        Self {
+            k,
+            scaled,
+            hashes: BTreeSet::new(),
+        }
+    }
+
+    fn add_sequence(&mut self, sequence: &[u8]) {
+        add_kmer_hashes(sequence, self.k, max_hash(self.scaled), &mut self.hashes);
+    }
+
+    /// The signatures of a struct array of signatures, with `None` for nulls.
+    pub(crate) fn from_array(array: &StructArray) -> Result<Vec<Option<Self>>> {
+        if array.num_columns() != 3 {
+            return Err(DataFusionError::Execution(format!(
+                "A MinHash signature is a struct of k, scaled, and hashes, got {} fields",
+                array.num_columns()
+            )));
+        }
+
+        let ks = array.column(0).as_primitive_opt::<Int64Type>();
+        let scaleds = array.column(1).as_primitive_opt::<Int64Type>();
+        let hashes = array.column(2).as_list_opt::<i32>();
+
+        let (Some(ks), Some(scaleds), Some(hashes)) = (ks, scaleds, hashes) else {
+            return Err(DataFusionError::Execution(
+                "A MinHash signature is a struct of k, scaled, and hashes".to_string(),
+            ));
+        };
+
+        (0..array.len())
+            .map(|i| {
+                if array.is_null(i) || hashes.is_null(i) {
+                    return Ok(None);
+                }
+
+                let signature_hashes = hashes.value(i);
+                let signature_hashes = signature_hashes
+                    .as_primitive_opt::<UInt64Type>()
+                    .ok_or_else(|| {
+                        DataFusionError::Execution(
+                            "The hashes of a MinHash signature are unsigned integers".to_string(),
+                        )
+                    })?;
+
+                Ok(Some(Self {
+                    k: ks.value(i) as usize,
+                    scaled: scaleds.value(i) as u64,
+                    hashes: signature_hashes.values().iter().copied().collect(),
+                }))
+            })
+            .collect()
+    }
+
+    /// The signature as a struct scalar.
+    fn to_scalar(&self) -> Result<ScalarValue> {
+        let hashes = UInt64Array::from_iter_values(self.hashes.iter().copied());
+        let hashes = ListArray::try_new(
+            Arc::new(Field::new("item", DataType::UInt64, true)),
+            OffsetBuffer::from_lengths([hashes.len()]),
+            Arc::new(hashes),
+            None,
+        )?;
+
+        let signature = StructArray::try_new(
+            signature_fields(),
+            vec![
+                Arc::new(Int64Array::from(vec![self.k as i64])),
+                Arc::new(Int64Array::from(vec![self.scaled as i64])),
+                Arc::new(hashes),
+            ],
+            None,
+        )?;
+
+        Ok(ScalarValue::Struct(Arc::new(signature)))
+    }
+}
+
+/// An aggregate that sketches a set of sequences, e.g. the reads of a sample, into a FracMinHash
+/// signature like sourmash, e.g. `minhash_signature(sequence, 31, 1000)`.
+///
+/// The signature keeps the MurmurHash3 hashes of canonical k-mers that are at most 2^64 / scaled,
+/// about one in every `scaled` distinct k-mers, as a struct of `k`, `scaled`, and sorted `hashes`
+/// for `jaccard`. `k` and `scaled` are positive literals and null sequences are ignored.
+#[derive(Debug)]
+pub(crate) struct MinHashSignatureUdaf {
+    signature: Signature,
+}
+
+impl Default for MinHashSignatureUdaf {
+    fn default() -> Self {
+        let signature = Signature::exact(
+            vec![DataType::Utf8, DataType::Int64, DataType::Int64],
+            Volatility::Immutable,
+        );
+
+        Self { signature }
+    }
+}
+
+impl AggregateUDFImpl for MinHashSignatureUdaf {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "minhash_signature"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Struct(signature_fields()))
+    }
+
+    fn accumulator(&self, acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        let positive_literal = |i: usize| {
+            acc_args
+                .exprs
+                .get(i)
+                .and_then(|expr| expr.as_any().downcast_ref::<Literal>())
+                .and_then(|literal| match literal.value() {
+                    ScalarValue::Int64(Some(value)) if *value > 0 => Some(*value),
+                    _ => None,
+                })
+        };
+
+        let (Some(k), Some(scaled)) = (positive_literal(1), positive_literal(2)) else {
+            return Err(DataFusionError::Execution(
+                "minhash_signature takes a positive literal k and scaled".to_string(),
+            ));
+        };
+
+        Ok(Box::new(MinHashSignatureAccumulator {
+            signature: MinHashSignature::new(k as usize, scaled as u64),
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct MinHashSignatureAccumulator {
+    signature: MinHashSignature,
+}
+
+impl Accumulator for MinHashSignatureAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let sequences = values[0].as_string_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution("minhash_signature takes string sequences".to_string())
+        })?;
+
+        for sequence in sequences.iter().flatten() {
+            self.signature.add_sequence(sequence.as_bytes());
+        }
+
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        let states = states[0].as_struct_opt().ok_or_else(|| {
+            DataFusionError::Execution("minhash_signature state should be a struct".to_string())
+        })?;
+
+        for signature in MinHashSignature::from_array(states)?.into_iter().flatten() {
+            if signature.k != self.signature.k || signature.scaled != self.signature.scaled {
+                return Err(DataFusionError::Execution(format!(
+                    "minhash_signature state should have k {} and scaled {}, got {} and {}",
+                    self.signature.k, self.signature.scaled, signature.k, signature.scaled
+                )));
+            }
+
+            self.signature.hashes.extend(signature.hashes);
+        }
+
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![self.signature.to_scalar()?])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        self.signature.to_scalar()
+    }
+
+    fn size(&self) -> usize {
+        // A B-tree node holds up to 11 hashes, so this is about their size.
+        std::mem::size_of_val(self) + self.signature.hashes.len() * 2 * std::mem::size_of::<u64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{add_kmer_hashes, max_hash, murmurhash3_x64_128};
+
+    #[test]
+    fn test_murmurhash3() {
+        assert_eq!(murmurhash3_x64_128(b"", 0), 0);
+        assert_eq!(murmurhash3_x64_128(b"hello", 0), 0xcbd8_a7b3_41bd_9b02);
+        assert_eq!(
+            murmurhash3_x64_128(b"The quick brown fox jumps over the lazy dog", 0),
+            0xe34b_bc7b_bc07_1b6c
+        );
+    }
+
+    #[test]
+    fn test_kmer_hashes() {
+        let mut forward = BTreeSet::new();
+        add_kmer_hashes(b"ACGGTCAAGTNNACGTTGCA", 5, u64::MAX, &mut forward);
+
+        let mut reverse = BTreeSet::new();
+        add_kmer_hashes(b"tgcaacgtnnacttgaccgt", 5, u64::MAX, &mut reverse);
+
+        assert_eq!(forward, reverse);
+        assert_eq!(forward.len(), 10);
+
+        let mut scaled = BTreeSet::new();
+        add_kmer_hashes(b"ACGGTCAAGTNNACGTTGCA", 5, max_hash(2), &mut scaled);
+        assert!(scaled.iter().all(|hash| *hash <= u64::MAX / 2));
+        assert!(scaled.is_subset(&forward));
+    }
+}
@@ -13,17 +13,44 @@
 // limitations under the License.
 
 mod alignment_score;
+mod approx_distinct_kmers;
+mod approx_match;
+mod cai;
+mod codon_usage;
+mod codon_usage_table;
+mod dnds;
+mod extract_umi;
+mod find_orfs;
+mod gc_clamp;
 mod gc_content;
+mod genetic_code;
+mod hairpin_score;
 mod integer_encoding;
+mod iupac;
+mod iupac_match;
+mod jaccard;
+pub(crate) mod kmers;
 mod locate_regex;
+mod melting_temperature;
+mod minhash_signature;
+mod pack_sequence;
+mod packed_gc_content;
+mod packed_sequence_length;
+mod pairwise_identity;
 mod quality_score_list_to_string;
 mod quality_score_string_to_list;
+mod restriction_sites;
+mod top_sequences;
 mod trim_polya;
+mod unpack_sequence;
 
 /// Module containing the reverse complement UDF.
 pub mod reverse_complement;
 
-use datafusion::{execution::context::SessionContext, logical_expr::ScalarUDF};
+use datafusion::{
+    execution::context::SessionContext,
+    logical_expr::{AggregateUDF, ScalarUDF},
+};
 
 use gc_content::GCContent;
 use reverse_complement::ReverseComplement;
@@ -66,4 +93,88 @@ pub fn register_udfs(ctx: &SessionContext) {
     let integer_encoding = integer_encoding::IntegerEncoding::default();
     let integer_encoding_udf = ScalarUDF::from(integer_encoding);
     ctx.register_udf(integer_encoding_udf);
+
+    let find_orfs = find_orfs::FindOrfs::default();
+    let find_orfs_udf = ScalarUDF::from(find_orfs);
+    ctx.register_udf(find_orfs_udf);
+
+    let melting_temperature = melting_temperature::MeltingTemperature::default();
+    let melting_temperature_udf = ScalarUDF::from(melting_temperature);
+    ctx.register_udf(melting_temperature_udf);
+
+    let gc_clamp = gc_clamp::GCClamp::default();
+    let gc_clamp_udf = ScalarUDF::from(gc_clamp);
+    ctx.register_udf(gc_clamp_udf);
+
+    let hairpin_score = hairpin_score::HairpinScore::default();
+    let hairpin_score_udf = ScalarUDF::from(hairpin_score);
+    ctx.register_udf(hairpin_score_udf);
+
+    let restriction_sites = restriction_sites::RestrictionSites::default();
+    let restriction_sites_udf = ScalarUDF::from(restriction_sites);
+    ctx.register_udf(restriction_sites_udf);
+
+    let iupac_match = iupac_match::IupacMatch::default();
+    let iupac_match_udf = ScalarUDF::from(iupac_match);
+    ctx.register_udf(iupac_match_udf);
+
+    let approx_match = approx_match::ApproxMatch::default();
+    let approx_match_udf = ScalarUDF::from(approx_match);
+    ctx.register_udf(approx_match_udf);
+
+    let extract_umi = extract_umi::ExtractUmi::default();
+    let extract_umi_udf = ScalarUDF::from(extract_umi);
+    ctx.register_udf(extract_umi_udf);
+
+    let pack_sequence = pack_sequence::PackSequence::default();
+    let pack_sequence_udf = ScalarUDF::from(pack_sequence);
+    ctx.register_udf(pack_sequence_udf);
+
+    let unpack_sequence = unpack_sequence::UnpackSequence::default();
+    let unpack_sequence_udf = ScalarUDF::from(unpack_sequence);
+    ctx.register_udf(unpack_sequence_udf);
+
+    let packed_sequence_length = packed_sequence_length::PackedSequenceLength::default();
+    let packed_sequence_length_udf = ScalarUDF::from(packed_sequence_length);
+    ctx.register_udf(packed_sequence_length_udf);
+
+    let packed_gc_content = packed_gc_content::PackedGCContent::default();
+    let packed_gc_content_udf = ScalarUDF::from(packed_gc_content);
+    ctx.register_udf(packed_gc_content_udf);
+
+    let codon_usage = codon_usage::CodonUsage::default();
+    let codon_usage_udf = ScalarUDF::from(codon_usage);
+    ctx.register_udf(codon_usage_udf);
+
+    let cai = cai::Cai::default();
+    let cai_udf = ScalarUDF::from(cai);
+    ctx.register_udf(cai_udf);
+
+    let codon_usage_table = codon_usage_table::CodonUsageTable::default();
+    let codon_usage_table_udaf = AggregateUDF::from(codon_usage_table);
+    ctx.register_udaf(codon_usage_table_udaf);
+
+    let pairwise_identity = pairwise_identity::PairwiseIdentity::default();
+    let pairwise_identity_udf = ScalarUDF::from(pairwise_identity);
+    ctx.register_udf(pairwise_identity_udf);
+
+    let dnds = dnds::Dnds::default();
+    let dnds_udf = ScalarUDF::from(dnds);
+    ctx.register_udf(dnds_udf);
+
+    let approx_distinct_kmers = approx_distinct_kmers::ApproxDistinctKmers::default();
+    let approx_distinct_kmers_udaf = AggregateUDF::from(approx_distinct_kmers);
+    ctx.register_udaf(approx_distinct_kmers_udaf);
+
+    let top_sequences = top_sequences::TopSequences::default();
+    let top_sequences_udaf = AggregateUDF::from(top_sequences);
+    ctx.register_udaf(top_sequences_udaf);
+
+    let minhash_signature = minhash_signature::MinHashSignatureUdaf::default();
+    let minhash_signature_udaf = AggregateUDF::from(minhash_signature);
+    ctx.register_udaf(minhash_signature_udaf);
+
+    let jaccard = jaccard::Jaccard::default();
+    let jaccard_udf = ScalarUDF::from(jaccard);
+    ctx.register_udf(jaccard_udf);
 }
@@ -0,0 +1,87 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{AsArray, BinaryBuilder},
+    datatypes::DataType,
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+use exon_common::packed_sequence::pack_sequence;
+
+/// Packs a sequence of A, C, G, T, and N with 2 bits per base, e.g.
+/// `pack_sequence('ACGTN')`, the same as scans do with `exon.pack_sequences`.
+#[derive(Debug)]
+pub(crate) struct PackSequence {
+    signature: Signature,
+}
+
+impl Default for PackSequence {
+    fn default() -> Self {
+        let signature = Signature::exact(vec![DataType::Utf8], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for PackSequence {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "pack_sequence"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Binary)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let [sequences] = arrays.as_slice() else {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes one sequence",
+                self.name()
+            )));
+        };
+
+        let sequences = sequences.as_string::<i32>();
+
+        let mut builder = BinaryBuilder::with_capacity(sequences.len(), sequences.values().len());
+        let mut packed = Vec::new();
+
+        for sequence in sequences {
+            match sequence {
+                Some(sequence) => {
+                    pack_sequence(sequence.as_bytes(), &mut packed)
+                        .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+                    builder.append_value(&packed);
+                }
+                None => builder.append_null(),
+            }
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(builder.finish())))
+    }
+}
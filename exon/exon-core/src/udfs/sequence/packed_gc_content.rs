@@ -0,0 +1,87 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{AsArray, Float32Array},
+    datatypes::DataType,
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+use exon_common::packed_sequence::{packed_gc_count, packed_sequence_len, PackedSequenceError};
+
+/// The GC content of a packed sequence, like `gc_content` but counted on the packed bases, e.g.
+/// `packed_gc_content(sequence)`.
+#[derive(Debug)]
+pub(crate) struct PackedGCContent {
+    signature: Signature,
+}
+
+impl Default for PackedGCContent {
+    fn default() -> Self {
+        let signature = Signature::exact(vec![DataType::Binary], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for PackedGCContent {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "packed_gc_content"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float32)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let [packed_sequences] = arrays.as_slice() else {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes one packed sequence",
+                self.name()
+            )));
+        };
+
+        let gc_content = packed_sequences
+            .as_binary::<i32>()
+            .iter()
+            .map(|packed| {
+                packed
+                    .map(|packed| {
+                        let gc_count = packed_gc_count(packed)?;
+                        let total_count = packed_sequence_len(packed)?;
+
+                        Ok::<_, PackedSequenceError>(gc_count as f32 / total_count as f32)
+                    })
+                    .transpose()
+            })
+            .collect::<std::result::Result<Float32Array, PackedSequenceError>>()
+            .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+
+        Ok(ColumnarValue::Array(Arc::new(gc_content)))
+    }
+}
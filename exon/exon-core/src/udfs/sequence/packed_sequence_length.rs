@@ -0,0 +1,82 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{AsArray, Int64Array},
+    datatypes::DataType,
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+use exon_common::packed_sequence::packed_sequence_len;
+
+/// The number of bases of a packed sequence, read from its header without unpacking it, e.g.
+/// `packed_sequence_length(sequence)`.
+#[derive(Debug)]
+pub(crate) struct PackedSequenceLength {
+    signature: Signature,
+}
+
+impl Default for PackedSequenceLength {
+    fn default() -> Self {
+        let signature = Signature::exact(vec![DataType::Binary], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for PackedSequenceLength {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "packed_sequence_length"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Int64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let [packed_sequences] = arrays.as_slice() else {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes one packed sequence",
+                self.name()
+            )));
+        };
+
+        let lengths = packed_sequences
+            .as_binary::<i32>()
+            .iter()
+            .map(|packed| {
+                packed
+                    .map(|packed| packed_sequence_len(packed).map(|len| len as i64))
+                    .transpose()
+            })
+            .collect::<std::result::Result<Int64Array, _>>()
+            .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+
+        Ok(ColumnarValue::Array(Arc::new(lengths)))
+    }
+}
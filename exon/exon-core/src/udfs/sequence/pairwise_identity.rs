@@ -0,0 +1,134 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, AsArray, Float64Array},
+    compute::cast,
+    datatypes::DataType,
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+/// Whether a column of an alignment is a gap.
+fn is_gap(base: u8) -> bool {
+    matches!(base, b'-' | b'.')
+}
+
+/// The fraction of the columns of two aligned sequences without a gap in either that are
+/// identical, ignoring case, or `None` if every column has a gap.
+fn pairwise_identity(a: &[u8], b: &[u8]) -> Option<f64> {
+    let (identical, columns) = a
+        .iter()
+        .zip(b)
+        .filter(|(a, b)| !is_gap(**a) && !is_gap(**b))
+        .fold((0, 0), |(identical, columns), (a, b)| {
+            (
+                identical + usize::from(a.eq_ignore_ascii_case(b)),
+                columns + 1,
+            )
+        });
+
+    (columns > 0).then(|| identical as f64 / columns as f64)
+}
+
+/// Returns the identity of two aligned sequences, e.g. rows of a multiple sequence alignment, as
+/// the fraction of the columns without a gap (`-` or `.`) in either that are identical.
+///
+/// The sequences must be the same length, and pairs where every column has a gap are null.
+#[derive(Debug)]
+pub(crate) struct PairwiseIdentity {
+    signature: Signature,
+}
+
+impl Default for PairwiseIdentity {
+    fn default() -> Self {
+        let signature =
+            Signature::exact(vec![DataType::Utf8, DataType::Utf8], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for PairwiseIdentity {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "pairwise_identity"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 2 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes two aligned sequences",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let a = cast(&arrays[0], &DataType::Utf8)?;
+        let a = a.as_string::<i32>();
+
+        let b = cast(&arrays[1], &DataType::Utf8)?;
+        let b = b.as_string::<i32>();
+
+        let identities = (0..a.len())
+            .map(|i| {
+                if a.is_null(i) || b.is_null(i) {
+                    return Ok(None);
+                }
+
+                let (a, b) = (a.value(i).as_bytes(), b.value(i).as_bytes());
+
+                if a.len() != b.len() {
+                    return Err(DataFusionError::Execution(format!(
+                        "{} takes aligned sequences of the same length, got {} and {}",
+                        self.name(),
+                        a.len(),
+                        b.len()
+                    )));
+                }
+
+                Ok(pairwise_identity(a, b))
+            })
+            .collect::<Result<Float64Array>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(identities)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pairwise_identity;
+
+    #[test]
+    fn test_pairwise_identity() {
+        assert_eq!(pairwise_identity(b"ACGT", b"acgA"), Some(0.75));
+        assert_eq!(pairwise_identity(b"AC-GT", b"ACT-T"), Some(1.0));
+        assert_eq!(pairwise_identity(b"--", b"AC"), None);
+    }
+}
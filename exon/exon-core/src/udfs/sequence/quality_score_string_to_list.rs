@@ -25,6 +25,7 @@ use datafusion::{
     logical_expr::{ColumnarValue, ScalarUDFImpl, Volatility},
     scalar::ScalarValue,
 };
+use exon_common::append_phred33_quality_scores;
 
 #[derive(Debug)]
 pub(crate) struct QualityScoreStringToList {
@@ -78,15 +79,7 @@ impl ScalarUDFImpl for QualityScoreStringToList {
                 );
 
                 for i in 0..capacity {
-                    let sequence = strings.value(i);
-
-                    let mut scores = Vec::with_capacity(sequence.len());
-                    for c in sequence.chars() {
-                        scores.push(c as i32 - 33);
-                    }
-
-                    builder.values().append_slice(&scores);
-                    builder.append(true);
+                    append_phred33_quality_scores(&mut builder, strings.value(i).as_bytes());
                 }
 
                 let list = builder.finish();
@@ -94,16 +87,10 @@ impl ScalarUDFImpl for QualityScoreStringToList {
             }
             ColumnarValue::Scalar(scalar) => match scalar {
                 ScalarValue::Utf8(Some(sequence)) => {
-                    let mut scores = Vec::with_capacity(sequence.len());
-                    for c in sequence.chars() {
-                        scores.push(c as i32 - 33);
-                    }
-
                     let values_builder = Int32Builder::new();
                     let mut builder = GenericListBuilder::<i32, Int32Builder>::new(values_builder);
 
-                    builder.values().append_slice(&scores);
-                    builder.append(true);
+                    append_phred33_quality_scores(&mut builder, sequence.as_bytes());
 
                     let values = builder.finish();
                     Ok(ColumnarValue::Array(Arc::new(values)))
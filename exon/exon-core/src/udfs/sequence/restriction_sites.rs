@@ -0,0 +1,220 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, AsArray, Int64Builder, ListBuilder, StringBuilder, StructBuilder},
+    datatypes::{DataType, Field, Fields},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+
+use super::iupac::{find_matches, pattern_masks, reverse_complement_masks};
+
+/// The recognition sites of common restriction enzymes.
+const ENZYMES: &[(&str, &str)] = &[
+    ("AatII", "GACGTC"),
+    ("AccI", "GTMKAC"),
+    ("AflII", "CTTAAG"),
+    ("AgeI", "ACCGGT"),
+    ("AluI", "AGCT"),
+    ("ApaI", "GGGCCC"),
+    ("AscI", "GGCGCGCC"),
+    ("AvaI", "CYCGRG"),
+    ("AvrII", "CCTAGG"),
+    ("BamHI", "GGATCC"),
+    ("BbsI", "GAAGAC"),
+    ("BglII", "AGATCT"),
+    ("BsaI", "GGTCTC"),
+    ("BsiWI", "CGTACG"),
+    ("BsmBI", "CGTCTC"),
+    ("BsrGI", "TGTACA"),
+    ("ClaI", "ATCGAT"),
+    ("DpnI", "GATC"),
+    ("DraI", "TTTAAA"),
+    ("EagI", "CGGCCG"),
+    ("EcoRI", "GAATTC"),
+    ("EcoRV", "GATATC"),
+    ("FseI", "GGCCGGCC"),
+    ("HaeIII", "GGCC"),
+    ("HincII", "GTYRAC"),
+    ("HindIII", "AAGCTT"),
+    ("HpaI", "GTTAAC"),
+    ("KpnI", "GGTACC"),
+    ("MboI", "GATC"),
+    ("MfeI", "CAATTG"),
+    ("MluI", "ACGCGT"),
+    ("MspI", "CCGG"),
+    ("NarI", "GGCGCC"),
+    ("NcoI", "CCATGG"),
+    ("NdeI", "CATATG"),
+    ("NheI", "GCTAGC"),
+    ("NotI", "GCGGCCGC"),
+    ("NsiI", "ATGCAT"),
+    ("PacI", "TTAATTAA"),
+    ("PmeI", "GTTTAAAC"),
+    ("PstI", "CTGCAG"),
+    ("PvuI", "CGATCG"),
+    ("PvuII", "CAGCTG"),
+    ("SacI", "GAGCTC"),
+    ("SacII", "CCGCGG"),
+    ("SalI", "GTCGAC"),
+    ("SapI", "GCTCTTC"),
+    ("Sau3AI", "GATC"),
+    ("SbfI", "CCTGCAGG"),
+    ("ScaI", "AGTACT"),
+    ("SfiI", "GGCCNNNNNGGCC"),
+    ("SmaI", "CCCGGG"),
+    ("SpeI", "ACTAGT"),
+    ("SphI", "GCATGC"),
+    ("StuI", "AGGCCT"),
+    ("SwaI", "ATTTAAAT"),
+    ("TaqI", "TCGA"),
+    ("XbaI", "TCTAGA"),
+    ("XhoI", "CTCGAG"),
+    ("XmaI", "CCCGGG"),
+];
+
+/// The recognition site of an enzyme name, case-insensitive, or else the argument as an IUPAC
+/// pattern.
+fn recognition_site(enzyme_or_pattern: &str) -> &str {
+    ENZYMES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(enzyme_or_pattern))
+        .map_or(enzyme_or_pattern, |(_, site)| site)
+}
+
+fn site_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("start", DataType::Int64, false),
+        Field::new("end", DataType::Int64, false),
+        Field::new("strand", DataType::Utf8, false),
+    ])
+}
+
+/// Finds the recognition sites of a restriction enzyme, e.g. `restriction_sites(sequence,
+/// 'EcoRI')`, or of an IUPAC pattern, e.g. `restriction_sites(sequence, 'GGTCTC')`.
+///
+/// Returns the 1-based, inclusive start and end of each site ordered by start. Sites that
+/// aren't palindromic are also searched for on the reverse strand, which is reported as `-`.
+#[derive(Debug)]
+pub(crate) struct RestrictionSites {
+    signature: Signature,
+}
+
+impl Default for RestrictionSites {
+    fn default() -> Self {
+        let signature =
+            Signature::exact(vec![DataType::Utf8, DataType::Utf8], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for RestrictionSites {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "restriction_sites"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        let item = Field::new("item", DataType::Struct(site_fields()), true);
+
+        Ok(DataType::List(Arc::new(item)))
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 2 {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes a sequence and an enzyme or IUPAC pattern",
+                self.name()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = arrays[0].as_string::<i32>();
+        let patterns = arrays[1].as_string::<i32>();
+
+        let mut builder = ListBuilder::new(StructBuilder::from_fields(site_fields(), 0));
+
+        // The pattern is usually a literal, so keep the masks of the last one.
+        let mut last_pattern = None;
+        let (mut forward, mut reverse) = (Vec::new(), Vec::new());
+
+        for i in 0..sequences.len() {
+            if sequences.is_null(i) || patterns.is_null(i) {
+                builder.append_null();
+                continue;
+            }
+
+            let pattern = patterns.value(i);
+
+            if last_pattern != Some(pattern) {
+                forward = pattern_masks(recognition_site(pattern)).ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "{} is neither a known enzyme nor an IUPAC pattern",
+                        pattern
+                    ))
+                })?;
+                reverse = reverse_complement_masks(&forward);
+                last_pattern = Some(pattern);
+            }
+
+            let sequence = sequences.value(i).as_bytes();
+
+            let mut sites = find_matches(sequence, &forward)
+                .map(|start| (start, "+"))
+                .collect::<Vec<_>>();
+
+            if forward != reverse {
+                sites.extend(find_matches(sequence, &reverse).map(|start| (start, "-")));
+                sites.sort();
+            }
+
+            let values = builder.values();
+
+            for (start, strand) in sites {
+                let start = start as i64 + 1;
+                let end = start + forward.len() as i64 - 1;
+
+                field::<Int64Builder>(values, 0)?.append_value(start);
+                field::<Int64Builder>(values, 1)?.append_value(end);
+                field::<StringBuilder>(values, 2)?.append_value(strand);
+
+                values.append(true);
+            }
+
+            builder.append(true);
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(builder.finish())))
+    }
+}
+
+fn field<T: arrow::array::ArrayBuilder>(builder: &mut StructBuilder, i: usize) -> Result<&mut T> {
+    builder
+        .field_builder::<T>(i)
+        .ok_or_else(|| DataFusionError::Internal(format!("Invalid site field builder {}", i)))
+}
@@ -36,7 +36,7 @@ impl Default for ReverseComplement {
     }
 }
 
-fn reverse_complement(sequence: &str) -> String {
+pub(crate) fn reverse_complement(sequence: &str) -> String {
     sequence
         .chars()
         .rev()
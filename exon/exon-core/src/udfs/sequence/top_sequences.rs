@@ -0,0 +1,418 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    any::Any,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, Int64Array, ListArray, StringArray, StructArray},
+    buffer::OffsetBuffer,
+    datatypes::{DataType, Field, Fields, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{
+        function::AccumulatorArgs, Accumulator, AggregateUDFImpl, Signature, Volatility,
+    },
+    physical_plan::expressions::Literal,
+    scalar::ScalarValue,
+};
+
+/// The counters kept for each sequence to return, as the count of a sequence is over by at most
+/// the number of sequences over the number of counters.
+const COUNTERS_PER_SEQUENCE: usize = 10;
+
+/// The fewest counters kept, so a small `n` still finds the frequent sequences.
+const MIN_COUNTERS: usize = 100;
+
+fn top_sequence_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("sequence", DataType::Utf8, true),
+        Field::new("count", DataType::Int64, true),
+        Field::new("error", DataType::Int64, true),
+    ])
+}
+
+fn top_sequences_type() -> DataType {
+    DataType::List(Arc::new(Field::new(
+        "item",
+        DataType::Struct(top_sequence_fields()),
+        true,
+    )))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Counter {
+    count: u64,
+    error: u64,
+}
+
+/// A space-saving summary of the most frequent sequences, with a bounded number of counters.
+///
+/// A new sequence takes the counter of the least frequent one when they're all in use, so the
+/// count of each sequence is over by at most its error.
+#[derive(Debug)]
+struct SpaceSaving {
+    capacity: usize,
+    counters: HashMap<String, Counter>,
+    /// The sequences of each count, to find the least frequent one.
+    buckets: BTreeMap<u64, BTreeSet<String>>,
+}
+
+impl SpaceSaving {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            counters: HashMap::new(),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.counters.len() >= self.capacity
+    }
+
+    fn min_count(&self) -> u64 {
+        self.buckets.keys().next().copied().unwrap_or(0)
+    }
+
+    fn insert(&mut self, sequence: &str) {
+        if let Some(counter) = self.counters.get_mut(sequence) {
+            let bucket = self.buckets.get_mut(&counter.count);
+            let key = bucket.and_then(|bucket| bucket.take(sequence));
+
+            if self
+                .buckets
+                .get(&counter.count)
+                .is_some_and(BTreeSet::is_empty)
+            {
+                self.buckets.remove(&counter.count);
+            }
+
+            counter.count += 1;
+
+            let key = key.unwrap_or_else(|| sequence.to_string());
+            self.buckets.entry(counter.count).or_default().insert(key);
+
+            return;
+        }
+
+        let counter = if self.is_full() {
+            let Some(mut least) = self.buckets.first_entry() else {
+                return;
+            };
+
+            let min_count = *least.key();
+            if let Some(evicted) = least.get_mut().pop_first() {
+                self.counters.remove(&evicted);
+            }
+            if least.get().is_empty() {
+                least.remove();
+            }
+
+            Counter {
+                count: min_count + 1,
+                error: min_count,
+            }
+        } else {
+            Counter { count: 1, error: 0 }
+        };
+
+        self.counters.insert(sequence.to_string(), counter);
+        self.buckets
+            .entry(counter.count)
+            .or_default()
+            .insert(sequence.to_string());
+    }
+
+    /// Merge the counters of another summary with the same capacity.
+    ///
+    /// A sequence missing from a full summary may have been counted up to its least count, which
+    /// is added to its count and error, then the most frequent sequences keep the counters.
+    fn merge(&mut self, other: Vec<(String, Counter)>) {
+        let min_count = if self.is_full() { self.min_count() } else { 0 };
+        let other_min_count = if other.len() >= self.capacity {
+            other
+                .iter()
+                .map(|(_, counter)| counter.count)
+                .min()
+                .unwrap_or(0)
+        } else {
+            0
+        };
+
+        let mut counters = std::mem::take(&mut self.counters);
+        let mut merged = HashMap::with_capacity(counters.len() + other.len());
+        self.buckets.clear();
+
+        for (sequence, other) in other {
+            let counter = match counters.remove(&sequence) {
+                Some(counter) => Counter {
+                    count: counter.count + other.count,
+                    error: counter.error + other.error,
+                },
+                None => Counter {
+                    count: min_count + other.count,
+                    error: min_count + other.error,
+                },
+            };
+
+            merged.insert(sequence, counter);
+        }
+
+        for (sequence, counter) in counters {
+            let counter = Counter {
+                count: counter.count + other_min_count,
+                error: counter.error + other_min_count,
+            };
+
+            merged.insert(sequence, counter);
+        }
+
+        for (sequence, counter) in Self::ranked(merged).into_iter().take(self.capacity) {
+            self.buckets
+                .entry(counter.count)
+                .or_default()
+                .insert(sequence.clone());
+            self.counters.insert(sequence, counter);
+        }
+    }
+
+    /// The sequences by count, most frequent first, then by sequence.
+    fn ranked(counters: impl IntoIterator<Item = (String, Counter)>) -> Vec<(String, Counter)> {
+        let mut counters = counters.into_iter().collect::<Vec<_>>();
+        counters.sort_by(|(a, a_counter), (b, b_counter)| {
+            b_counter.count.cmp(&a_counter.count).then_with(|| a.cmp(b))
+        });
+
+        counters
+    }
+
+    fn to_scalar(&self, n: usize) -> Result<ScalarValue> {
+        let ranked = Self::ranked(
+            self.counters
+                .iter()
+                .map(|(sequence, counter)| (sequence.clone(), *counter)),
+        );
+        let ranked = &ranked[..n.min(ranked.len())];
+
+        let sequences = StringArray::from_iter_values(ranked.iter().map(|(s, _)| s));
+        let counts = Int64Array::from_iter_values(ranked.iter().map(|(_, c)| c.count as i64));
+        let errors = Int64Array::from_iter_values(ranked.iter().map(|(_, c)| c.error as i64));
+
+        let items = StructArray::try_new(
+            top_sequence_fields(),
+            vec![Arc::new(sequences), Arc::new(counts), Arc::new(errors)],
+            None,
+        )?;
+
+        let list = ListArray::try_new(
+            Arc::new(Field::new(
+                "item",
+                DataType::Struct(top_sequence_fields()),
+                true,
+            )),
+            OffsetBuffer::from_lengths([items.len()]),
+            Arc::new(items),
+            None,
+        )?;
+
+        Ok(ScalarValue::List(Arc::new(list)))
+    }
+}
+
+/// An aggregate that finds the `n` most frequent sequences with the space-saving algorithm, e.g.
+/// `SELECT top_sequences(sequence, 10) FROM fastq` to spot adapter dimers or a contaminant.
+///
+/// It returns a list of structs of `sequence`, `count`, and `error`, most frequent first. Counts
+/// are exact until there are more distinct sequences than counters, then each may be over by up to
+/// its `error`. `n` is a positive literal and null sequences are ignored.
+#[derive(Debug)]
+pub(crate) struct TopSequences {
+    signature: Signature,
+}
+
+impl Default for TopSequences {
+    fn default() -> Self {
+        let signature =
+            Signature::exact(vec![DataType::Utf8, DataType::Int64], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl AggregateUDFImpl for TopSequences {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "top_sequences"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(top_sequences_type())
+    }
+
+    fn accumulator(&self, acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        let n = acc_args
+            .exprs
+            .get(1)
+            .and_then(|expr| expr.as_any().downcast_ref::<Literal>())
+            .and_then(|literal| match literal.value() {
+                ScalarValue::Int64(Some(n)) => usize::try_from(*n).ok(),
+                _ => None,
+            })
+            .filter(|n| *n > 0)
+            .ok_or_else(|| {
+                DataFusionError::Execution(
+                    "top_sequences takes a positive literal number of sequences".to_string(),
+                )
+            })?;
+
+        let capacity = n.saturating_mul(COUNTERS_PER_SEQUENCE).max(MIN_COUNTERS);
+
+        Ok(Box::new(TopSequencesAccumulator {
+            n,
+            summary: SpaceSaving::new(capacity),
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct TopSequencesAccumulator {
+    n: usize,
+    summary: SpaceSaving,
+}
+
+impl Accumulator for TopSequencesAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let sequences = values[0].as_string_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution("top_sequences takes string sequences".to_string())
+        })?;
+
+        for sequence in sequences.iter().flatten() {
+            self.summary.insert(sequence);
+        }
+
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        let states = states[0].as_list_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution("top_sequences state should be a list".to_string())
+        })?;
+
+        for i in 0..states.len() {
+            if states.is_null(i) {
+                continue;
+            }
+
+            let items = states.value(i);
+            let items = items.as_struct_opt().ok_or_else(|| {
+                DataFusionError::Execution(
+                    "top_sequences state should be a list of structs".to_string(),
+                )
+            })?;
+
+            let sequences = items.column(0).as_string::<i32>();
+            let counts = items.column(1).as_primitive::<Int64Type>();
+            let errors = items.column(2).as_primitive::<Int64Type>();
+
+            let counters = sequences
+                .iter()
+                .zip(counts.values().iter().zip(errors.values()))
+                .filter_map(|(sequence, (count, error))| {
+                    let counter = Counter {
+                        count: *count as u64,
+                        error: *error as u64,
+                    };
+
+                    sequence.map(|sequence| (sequence.to_string(), counter))
+                })
+                .collect();
+
+            self.summary.merge(counters);
+        }
+
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![self.summary.to_scalar(self.summary.capacity)?])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        self.summary.to_scalar(self.n)
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self
+                .summary
+                .counters
+                .keys()
+                .map(|sequence| 2 * sequence.capacity() + std::mem::size_of::<Counter>())
+                .sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(summary: &SpaceSaving) -> Vec<(String, u64, u64)> {
+        SpaceSaving::ranked(summary.counters.clone())
+            .into_iter()
+            .map(|(sequence, counter)| (sequence, counter.count, counter.error))
+            .collect()
+    }
+
+    #[test]
+    fn test_space_saving() {
+        let mut summary = SpaceSaving::new(2);
+
+        for sequence in ["AAA", "AAA", "CCC", "GGG", "AAA"] {
+            summary.insert(sequence);
+        }
+
+        // GGG took the counter of CCC, the least frequent sequence.
+        assert_eq!(
+            counts(&summary),
+            vec![("AAA".to_string(), 3, 0), ("GGG".to_string(), 2, 1)]
+        );
+
+        let mut other = SpaceSaving::new(2);
+        other.insert("TTT");
+
+        // TTT may have been counted up to the least count of the full summary, so it's over GGG.
+        summary.merge(other.counters.clone().into_iter().collect());
+        assert_eq!(
+            counts(&summary),
+            vec![("AAA".to_string(), 3, 0), ("TTT".to_string(), 3, 2)]
+        );
+
+        let mut empty = SpaceSaving::new(2);
+        empty.merge(summary.counters.into_iter().collect());
+        assert_eq!(empty.min_count(), 3);
+        assert_eq!(empty.counters.len(), 2);
+    }
+}
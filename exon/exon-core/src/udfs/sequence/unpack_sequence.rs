@@ -0,0 +1,91 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{AsArray, StringBuilder},
+    datatypes::DataType,
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, Volatility},
+};
+use exon_common::packed_sequence::unpack_sequence;
+
+/// Unpacks a sequence that was packed with 2 bits per base, e.g. `unpack_sequence(sequence)` on
+/// a table scanned with `exon.pack_sequences`.
+#[derive(Debug)]
+pub(crate) struct UnpackSequence {
+    signature: Signature,
+}
+
+impl Default for UnpackSequence {
+    fn default() -> Self {
+        let signature = Signature::exact(vec![DataType::Binary], Volatility::Immutable);
+
+        Self { signature }
+    }
+}
+
+impl ScalarUDFImpl for UnpackSequence {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "unpack_sequence"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Utf8)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let [packed_sequences] = arrays.as_slice() else {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes one packed sequence",
+                self.name()
+            )));
+        };
+
+        let packed_sequences = packed_sequences.as_binary::<i32>();
+
+        let mut builder = StringBuilder::with_capacity(
+            packed_sequences.len(),
+            packed_sequences.values().len() * 4,
+        );
+
+        for packed in packed_sequences {
+            match packed {
+                Some(packed) => {
+                    let sequence = unpack_sequence(packed)
+                        .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+
+                    // The unpacked bases are always ASCII.
+                    builder.append_value(String::from_utf8_lossy(&sequence));
+                }
+                None => builder.append_null(),
+            }
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(builder.finish())))
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, collections::BTreeMap, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, ListArray, StringArray, StructArray, UInt64Array},
+    buffer::OffsetBuffer,
+    compute::cast,
+    datatypes::{DataType, Field, Fields, UInt64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{
+        function::{AccumulatorArgs, StateFieldsArgs},
+        utils::format_state_name,
+        Accumulator, AggregateUDFImpl, Signature, TypeSignature, Volatility,
+    },
+    physical_plan::{expressions::Literal, PhysicalExpr},
+    scalar::ScalarValue,
+};
+
+use crate::udfs::sequence::kmers::MAX_KMER_SIZE;
+
+use super::{
+    reference_sketch::{minimizers, ReferenceSketch},
+    SketchRegistry,
+};
+
+/// The default k-mer size, long enough for k-mers to be specific to a genome.
+const DEFAULT_K: usize = 21;
+
+/// The default window of consecutive k-mers, which keeps about a sixth of them as minimizers.
+const DEFAULT_W: usize = 11;
+
+fn sketch_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("reference", DataType::Utf8, true),
+        Field::new(
+            "minimizers",
+            DataType::List(Arc::new(Field::new("item", DataType::UInt64, true))),
+            true,
+        ),
+    ])
+}
+
+fn sketch_type() -> DataType {
+    DataType::List(Arc::new(Field::new(
+        "item",
+        DataType::Struct(sketch_fields()),
+        true,
+    )))
+}
+
+/// The value of a literal argument, if it is one.
+fn literal_argument(exprs: &[Arc<dyn PhysicalExpr>], i: usize) -> Option<&ScalarValue> {
+    exprs
+        .get(i)
+        .and_then(|expr| expr.as_any().downcast_ref::<Literal>())
+        .map(Literal::value)
+}
+
+/// An aggregate that builds a minimizer sketch of reference sequences and registers it with the
+/// session under a name for `classify_read`, e.g.
+/// `SELECT build_sketch('contaminants', id, sequence) FROM contaminant_genomes`.
+///
+/// The sequences of rows with the same reference are sketched together, e.g. the contigs of a
+/// genome. The optional fourth and fifth arguments are the k-mer size, up to 32, and the number of
+/// consecutive k-mers in each window, which default to 21 and 11. It returns the number of
+/// references in the sketch.
+#[derive(Debug)]
+pub(crate) struct BuildSketch {
+    signature: Signature,
+    registry: Arc<SketchRegistry>,
+}
+
+impl BuildSketch {
+    pub(crate) fn new(registry: Arc<SketchRegistry>) -> Self {
+        let signature = Signature::one_of(
+            vec![TypeSignature::Any(3), TypeSignature::Any(5)],
+            Volatility::Volatile,
+        );
+
+        Self {
+            signature,
+            registry,
+        }
+    }
+}
+
+impl AggregateUDFImpl for BuildSketch {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "build_sketch"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Int64)
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<Field>> {
+        Ok(vec![Field::new(
+            format_state_name(args.name, "sketch"),
+            sketch_type(),
+            true,
+        )])
+    }
+
+    fn accumulator(&self, acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        let name = match literal_argument(acc_args.exprs, 0) {
+            Some(ScalarValue::Utf8(Some(name))) => name.clone(),
+            _ => {
+                return Err(DataFusionError::Execution(
+                    "build_sketch takes the name of the sketch as a literal".to_string(),
+                ))
+            }
+        };
+
+        let (k, w) = if acc_args.exprs.len() == 5 {
+            let k = match literal_argument(acc_args.exprs, 3) {
+                Some(ScalarValue::Int64(Some(k))) => usize::try_from(*k).ok(),
+                _ => None,
+            };
+            let w = match literal_argument(acc_args.exprs, 4) {
+                Some(ScalarValue::Int64(Some(w))) => usize::try_from(*w).ok(),
+                _ => None,
+            };
+
+            match (k, w) {
+                (Some(k), Some(w)) if (1..=MAX_KMER_SIZE).contains(&k) && w > 0 => (k, w),
+                _ => {
+                    return Err(DataFusionError::Execution(format!(
+                        "build_sketch takes a literal k from 1 to {} and a positive literal window",
+                        MAX_KMER_SIZE
+                    )))
+                }
+            }
+        } else {
+            (DEFAULT_K, DEFAULT_W)
+        };
+
+        Ok(Box::new(BuildSketchAccumulator {
+            name,
+            k,
+            w,
+            registry: Arc::clone(&self.registry),
+            references: BTreeMap::new(),
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct BuildSketchAccumulator {
+    name: String,
+    k: usize,
+    w: usize,
+    registry: Arc<SketchRegistry>,
+    /// The minimizers of each reference, which may repeat until the sketch is built.
+    references: BTreeMap<String, Vec<u64>>,
+}
+
+impl BuildSketchAccumulator {
+    fn to_scalar(&self) -> Result<ScalarValue> {
+        let names = StringArray::from_iter_values(self.references.keys());
+
+        let lengths = self.references.values().map(Vec::len);
+        let values = UInt64Array::from_iter_values(self.references.values().flatten().copied());
+        let minimizers = ListArray::try_new(
+            Arc::new(Field::new("item", DataType::UInt64, true)),
+            OffsetBuffer::from_lengths(lengths),
+            Arc::new(values),
+            None,
+        )?;
+
+        let sketch = StructArray::try_new(
+            sketch_fields(),
+            vec![Arc::new(names), Arc::new(minimizers)],
+            None,
+        )?;
+
+        let list = ListArray::try_new(
+            Arc::new(Field::new("item", DataType::Struct(sketch_fields()), true)),
+            OffsetBuffer::from_lengths([sketch.len()]),
+            Arc::new(sketch),
+            None,
+        )?;
+
+        Ok(ScalarValue::List(Arc::new(list)))
+    }
+}
+
+impl Accumulator for BuildSketchAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let references = cast(&values[1], &DataType::Utf8)?;
+        let sequences = cast(&values[2], &DataType::Utf8)?;
+
+        for (reference, sequence) in references
+            .as_string::<i32>()
+            .iter()
+            .zip(sequences.as_string::<i32>().iter())
+        {
+            if let (Some(reference), Some(sequence)) = (reference, sequence) {
+                let reference_minimizers = minimizers(sequence.as_bytes(), self.k, self.w);
+
+                self.references
+                    .entry(reference.to_string())
+                    .or_default()
+                    .extend(reference_minimizers);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        let states = states[0].as_list_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution("build_sketch state should be a list".to_string())
+        })?;
+
+        for i in 0..states.len() {
+            if states.is_null(i) {
+                continue;
+            }
+
+            let sketch = states.value(i);
+            let sketch = sketch.as_struct_opt().ok_or_else(|| {
+                DataFusionError::Execution(
+                    "build_sketch state should be a list of structs".to_string(),
+                )
+            })?;
+
+            let names = sketch.column(0).as_string::<i32>();
+            let minimizers = sketch.column(1).as_list::<i32>();
+
+            for (j, name) in names.iter().enumerate() {
+                let Some(name) = name else {
+                    continue;
+                };
+
+                let reference_minimizers = minimizers.value(j);
+
+                self.references
+                    .entry(name.to_string())
+                    .or_default()
+                    .extend(reference_minimizers.as_primitive::<UInt64Type>().values());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        for reference_minimizers in self.references.values_mut() {
+            reference_minimizers.sort_unstable();
+            reference_minimizers.dedup();
+        }
+
+        Ok(vec![self.to_scalar()?])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        let sketch = ReferenceSketch::new(self.k, self.w, self.references.clone());
+        let n_references = sketch.references().len();
+
+        self.registry.register(&self.name, Arc::new(sketch));
+
+        Ok(ScalarValue::Int64(Some(n_references as i64)))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self
+                .references
+                .iter()
+                .map(|(name, minimizers)| {
+                    name.capacity() + minimizers.capacity() * std::mem::size_of::<u64>()
+                })
+                .sum::<usize>()
+    }
+}
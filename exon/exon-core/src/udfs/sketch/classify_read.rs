@@ -0,0 +1,139 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{any::Any, sync::Arc};
+
+use arrow::{
+    array::{Array, AsArray, StringArray},
+    compute::cast,
+    datatypes::{DataType, Float64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    logical_expr::{ColumnarValue, ScalarUDFImpl, Signature, TypeSignature, Volatility},
+};
+
+use super::{ReferenceSketch, SketchRegistry};
+
+/// Classifies a read by the reference of a sketch from `build_sketch` it shares the most
+/// minimizers with, e.g. `classify_read(sequence, 'contaminants')`, or null if it shares none.
+///
+/// The optional third argument is the fraction of the read's minimizers the reference must have,
+/// from 0 to 1, below which the read is null too.
+#[derive(Debug)]
+pub(crate) struct ClassifyRead {
+    signature: Signature,
+    registry: Arc<SketchRegistry>,
+}
+
+impl ClassifyRead {
+    pub(crate) fn new(registry: Arc<SketchRegistry>) -> Self {
+        // The sketch of a name can change when it's built again, so the UDF isn't immutable.
+        let signature = Signature::one_of(
+            vec![TypeSignature::Any(2), TypeSignature::Any(3)],
+            Volatility::Stable,
+        );
+
+        Self {
+            signature,
+            registry,
+        }
+    }
+
+    fn sketch(&self, name: &str) -> Result<Arc<ReferenceSketch>> {
+        self.registry.get(name).ok_or_else(|| {
+            DataFusionError::Execution(format!(
+                "No sketch named {}, build one with build_sketch",
+                name
+            ))
+        })
+    }
+}
+
+impl ScalarUDFImpl for ClassifyRead {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "classify_read"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Utf8)
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != 2 && args.len() != 3 {
+            return Err(DataFusionError::Execution(
+                "classify_read takes a sequence, the name of a sketch, and an optional minimum fraction"
+                    .to_string(),
+            ));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let sequences = cast(&arrays[0], &DataType::Utf8)?;
+        let sequences = sequences.as_string::<i32>();
+
+        let names = cast(&arrays[1], &DataType::Utf8)?;
+        let names = names.as_string::<i32>();
+
+        let min_fractions = arrays
+            .get(2)
+            .map(|min_fractions| cast(min_fractions, &DataType::Float64))
+            .transpose()?;
+        let min_fractions = min_fractions
+            .as_ref()
+            .map(|min_fractions| min_fractions.as_primitive::<Float64Type>());
+
+        // The sketch of the last name, as the name is usually the same for every read.
+        let mut last_sketch: Option<(&str, Arc<ReferenceSketch>)> = None;
+
+        let references = (0..sequences.len())
+            .map(|i| {
+                if sequences.is_null(i) || names.is_null(i) {
+                    return Ok(None);
+                }
+
+                let name = names.value(i);
+                let sketch = match &last_sketch {
+                    Some((last_name, sketch)) if *last_name == name => Arc::clone(sketch),
+                    _ => {
+                        let sketch = self.sketch(name)?;
+                        last_sketch = Some((name, Arc::clone(&sketch)));
+                        sketch
+                    }
+                };
+
+                let min_fraction = min_fractions
+                    .filter(|min_fractions| min_fractions.is_valid(i))
+                    .map_or(0.0, |min_fractions| min_fractions.value(i));
+
+                let reference = sketch
+                    .classify(sequences.value(i).as_bytes())
+                    .filter(|(_, fraction)| *fraction >= min_fraction)
+                    .map(|(reference, _)| reference.to_string());
+
+                Ok(reference)
+            })
+            .collect::<Result<StringArray>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(references)))
+    }
+}
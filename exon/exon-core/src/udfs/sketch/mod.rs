@@ -0,0 +1,80 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! UDFs that screen reads for contamination against minimizer sketches of reference sequences.
+//!
+//! `build_sketch` is an aggregate that sketches the sequences of a reference table, e.g. a FASTA
+//! table of likely contaminants, and registers the sketch with the session under a name.
+//! `classify_read` then returns the reference a read shares the most minimizers with.
+//!
+//! ```sql
+//! SELECT build_sketch('contaminants', id, sequence) FROM contaminant_genomes;
+//!
+//! SELECT classify_read(sequence, 'contaminants') AS contaminant, COUNT(*)
+//! FROM reads
+//! GROUP BY contaminant;
+//! ```
+
+mod build_sketch;
+mod classify_read;
+mod reference_sketch;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use datafusion::{
+    execution::context::SessionContext,
+    logical_expr::{AggregateUDF, ScalarUDF},
+};
+
+pub use self::reference_sketch::ReferenceSketch;
+
+/// The sketches built in a session, by name.
+#[derive(Debug, Default)]
+pub struct SketchRegistry {
+    sketches: RwLock<HashMap<String, Arc<ReferenceSketch>>>,
+}
+
+impl SketchRegistry {
+    /// Register a sketch, replacing any existing sketch with the same name.
+    pub(crate) fn register(&self, name: &str, sketch: Arc<ReferenceSketch>) {
+        let mut sketches = self.sketches.write().expect("registry lock poisoned");
+        sketches.insert(name.to_string(), sketch);
+    }
+
+    /// Get the sketch with a name.
+    pub fn get(&self, name: &str) -> Option<Arc<ReferenceSketch>> {
+        let sketches = self.sketches.read().expect("registry lock poisoned");
+        sketches.get(name).cloned()
+    }
+}
+
+/// Register the sketch UDFs, which share the sketch registry of the session's config.
+pub fn register_udfs(ctx: &SessionContext) {
+    let registry = ctx
+        .state()
+        .config()
+        .get_extension::<SketchRegistry>()
+        .unwrap_or_default();
+
+    let build_sketch = build_sketch::BuildSketch::new(Arc::clone(&registry));
+    let build_sketch_udaf = AggregateUDF::from(build_sketch);
+    ctx.register_udaf(build_sketch_udaf);
+
+    let classify_read = classify_read::ClassifyRead::new(registry);
+    let classify_read_udf = ScalarUDF::from(classify_read);
+    ctx.register_udf(classify_read_udf);
+}
@@ -0,0 +1,184 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use crate::udfs::sequence::kmers::{hash_kmer, CanonicalKmers};
+
+/// The minimizers of a sequence, the smallest hash of the canonical k-mers in each window of `w`
+/// consecutive k-mers, sorted and deduplicated.
+///
+/// A sequence with fewer than `w` k-mers has the smallest hash of all of them.
+pub(crate) fn minimizers(sequence: &[u8], k: usize, w: usize) -> Vec<u64> {
+    let mut minimizers = Vec::new();
+
+    // The candidate minimizers of the window by position, with increasing hashes.
+    let mut window: VecDeque<(usize, u64)> = VecDeque::with_capacity(w);
+    let mut n_kmers = 0;
+
+    for (i, kmer) in CanonicalKmers::new(sequence, k).enumerate() {
+        let hash = hash_kmer(kmer);
+
+        while window.back().is_some_and(|(_, back)| *back >= hash) {
+            window.pop_back();
+        }
+        window.push_back((i, hash));
+
+        if window.front().is_some_and(|(front, _)| front + w <= i) {
+            window.pop_front();
+        }
+
+        if i + 1 >= w {
+            if let Some((_, minimizer)) = window.front() {
+                minimizers.push(*minimizer);
+            }
+        }
+
+        n_kmers = i + 1;
+    }
+
+    if n_kmers > 0 && n_kmers < w {
+        if let Some((_, minimizer)) = window.front() {
+            minimizers.push(*minimizer);
+        }
+    }
+
+    minimizers.sort_unstable();
+    minimizers.dedup();
+
+    minimizers
+}
+
+/// The minimizers of a set of reference sequences, e.g. the genomes of likely contaminants, to
+/// classify reads by the reference they share the most minimizers with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceSketch {
+    k: usize,
+    w: usize,
+    references: Vec<String>,
+    /// The minimizers and the index of a reference they're in, sorted.
+    minimizers: Vec<(u64, u32)>,
+}
+
+impl ReferenceSketch {
+    /// Create a sketch from the minimizers of each reference, computed with `k` and `w`.
+    pub(crate) fn new(k: usize, w: usize, references: BTreeMap<String, Vec<u64>>) -> Self {
+        let mut names = Vec::with_capacity(references.len());
+        let mut minimizers = Vec::new();
+
+        for (i, (name, reference_minimizers)) in references.into_iter().enumerate() {
+            names.push(name);
+            minimizers.extend(reference_minimizers.into_iter().map(|m| (m, i as u32)));
+        }
+
+        minimizers.sort_unstable();
+        minimizers.dedup();
+
+        Self {
+            k,
+            w,
+            references: names,
+            minimizers,
+        }
+    }
+
+    /// The k-mer size of the sketch.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The number of consecutive k-mers in each window of the sketch.
+    pub fn w(&self) -> usize {
+        self.w
+    }
+
+    /// The names of the references, sorted.
+    pub fn references(&self) -> &[String] {
+        &self.references
+    }
+
+    /// The reference a sequence shares the most minimizers with, and the fraction of its
+    /// minimizers that reference has, or `None` if it shares none with any reference.
+    ///
+    /// A tie goes to the reference that sorts first.
+    pub fn classify(&self, sequence: &[u8]) -> Option<(&str, f64)> {
+        let read_minimizers = minimizers(sequence, self.k, self.w);
+
+        let mut hits: HashMap<u32, usize> = HashMap::new();
+        for minimizer in &read_minimizers {
+            let start = self.minimizers.partition_point(|(m, _)| m < minimizer);
+
+            for (_, reference) in self.minimizers[start..]
+                .iter()
+                .take_while(|(m, _)| m == minimizer)
+            {
+                *hits.entry(*reference).or_default() += 1;
+            }
+        }
+
+        let (reference, count) = hits
+            .into_iter()
+            .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))?;
+
+        Some((
+            self.references[reference as usize].as_str(),
+            count as f64 / read_minimizers.len() as f64,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{minimizers, ReferenceSketch};
+
+    /// A stretch of a bacteriophage genome.
+    const GENOME: &[u8] = b"GAGTTTTATCGCTTCCATGACGCAGAAGTTAACACTTTCGGATATTTCTGATGAGTCGAAAAATTATCTTGATAAAGCAGGAATTACTACTGCTTGTTTACGAATTAAATCGAAGTGGACTGCTGGCGGAAAATGAGAAAATTCGACCTATCCTTGCGCAGCTCGAGAAGCTCTTACTTTGCGACCTTTCGCCATCAACTAACGATTCTGTCAAAAACTGACGCGTTGGATGAGGAGAAGTGGCTTAATATGCTTGGCACGTTCGTCAAGGACTGGTTTAGATATGAGTCACATTTTGTTCATGGTAGAGATTCTCTTGTTGACATTTTAAAAGAGCGTGGATTACTATCTGAGTCCGATGCTGTTCAACCACTAATAGGTAAGAAATCATGAGTCAAGTTACTGAACAATCCGTACGTTTCCAGACCGCTTTGGCCTCTATTAAGCTCATTCAGGCTTCTGCCGTTTTGGATTTAACCGAAGATGATTTCGATTTTCTGACGAGTAACAAAGTTTGGATTGCTACTGACCGCTCTCGTGCTCGTCGCTGCGTTGAGGCTTGCGTTTATGGTACGCTGGACTTTGTGGGATACCCTCGCTTTCCTGCTCCTGTTGAGTTTATTGCTGCCGTCATTGCTTATTATGTTCATCCCGTCAACATTCAAACGGCCTGTCTCATCATGGAAGGCGCTGAATTTACGGAAAACATTATTAATGGCGTCGAGCGTCCGGTTAAAGCCGCTGAATTGTTCGCGTTTACCTTGCGTGTACGCGCAGGAAACACTGACGTTCTTACTGACGCAGAAGAAAACGTGCGTCAAAAATTACGTGCGGAAGGAGTGATGTAATGTCTAAAGGTAAAAAACGTTCTGGCGCTCGCCCTGGTCGTCCGCAGCCGTTGCGAGGTACTAAAGGCAAGCGTAAAGGCGCTCGTCTTTGGTATGTAGGTGGTCAACAATTTTAATTGCAGGGGCTTCGGCCCCTTACTTGAGGATAAATTATGTCTAATATTCAAACTGGCGCCGAGCGTATGCCGCATGACCTTTCCCATCTTGGCTTCCTTGCTGGTCAGATTGGTCGTCTTATTACCATTTCAACTACTCCGGTTATCGCTGGCGACTCCTTCGAGATGGACGCCGTTGGCGCTCTCCGTCTTTCTCCATTGCGTCGTGGCCTTGCTATTGACTCTACTGTAGACATTTTTACTTTTTATGTCCCTCATCGTCACGTTTATGGTGAACAGTGGATTAAGTTCATGAAGGATGGTGTTAATGCCACTCCTCTCCCGACTGTTAACACTACTGGTTATATTGACCATGCCGCTTTTCTTGGCACGATTAACCCTGATACCAATAAAATCCCTAAGCATTTGTTTCAGGGTTATTTGAATATCTATAACAACTATTTTAAAGCGCCGTGGATGCCTGACCGTACCGAGGCTAACCCTAATGAGCTTAATCAAGATGATGCTCGTTATGGTTTCCGTTGCTGCCATCTCAAAAACATTTGGACTGCTCCGCTTCCTCCTGAGACTGAGCTTTCTCGCCAAATGACGACTTCTACCACATCTATTGACATTATGGGTCTGCAAGCTGCTTATGCTAATTTGCATACTGACCAAGAACGTGATTACTTCATGCAGCGTTACCATGATGTTATTTCTTCATTTGGAGGTAAAACCTCATATGACGCTGACAACCGTCCTTTACTTGTCATGCGCTCTAATCTCTGGGCATCTGGCTATGATGTTGATGGAACTGACCAAACGTCGTTAGGCCAGTTTTCTGGTCGTGTTCAACAGACCTATAAACATTCTGTGCCGCGTTTCTTTGTTCCTGAGCATGGCACTATGTTTACTCTTGCGCTTGTTCGTTTTCCGCCTACTGCGACTAAAGAGATTCAGTACCTTAACGCTAAAGGTGCTTTGACTTATACCGATATTGCTGGCGACCCTGTTTTGTATGGCAACTTGCCGCCGCGTGAAATTTCTATGAAGGATGTTTTCCGTTCTGGTGATTCGTCTAAGAAGTTTAAGATTGCTGAGGGTCAGTGGTATCGTTATGCGCCTTCGTATGTTTCTCCTGCTTATCACCTTCTTGAAGGCTTCCCATTCATTCAGGAACCGCCTTCTGGTGATTTGCAAGAACGCGCGAGCGTGACATCGTGCGCGAAGAAATTTCTACTCAGTGAACTCGTGCGAGGCCAAATCCATGTAAAA";
+
+    #[test]
+    fn test_minimizers() {
+        assert!(minimizers(b"ACGT", 5, 3).is_empty());
+        assert_eq!(minimizers(b"ACGTACG", 5, 10).len(), 1);
+
+        // A sequence and its reverse complement have the same minimizers.
+        let forward = minimizers(b"GAGTTTTATCGCTTCCATGACGCAGAAGTTAACACTTTCGG", 7, 5);
+        let reverse = minimizers(b"CCGAAAGTGTTAACTTCTGCGTCATGGAAGCGATAAAACTC", 7, 5);
+        assert_eq!(forward, reverse);
+
+        // There's at least one minimizer in each window of 5 k-mers.
+        assert!(forward.len() >= (41 - 7 + 1) / 5);
+    }
+
+    #[test]
+    fn test_classify() {
+        let (genome_a, genome_b) = GENOME.split_at(GENOME.len() / 2);
+
+        let references = BTreeMap::from([
+            ("genome_a".to_string(), minimizers(genome_a, 15, 5)),
+            ("genome_b".to_string(), minimizers(genome_b, 15, 5)),
+        ]);
+        let sketch = ReferenceSketch::new(15, 5, references);
+
+        assert_eq!(sketch.references(), ["genome_a", "genome_b"]);
+
+        let (reference, fraction) = sketch.classify(&genome_b[100..250]).unwrap();
+        assert_eq!(reference, "genome_b");
+        assert_eq!(fraction, 1.0);
+
+        assert_eq!(sketch.classify(&genome_a[200..300]).unwrap().0, "genome_a");
+        assert_eq!(sketch.classify(b"ACGTACGTACGTACGTACGTACGTACGTACGT"), None);
+        assert_eq!(sketch.classify(b"ACGT"), None);
+    }
+}
@@ -0,0 +1,346 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! UDFs that look up taxa in an NCBI taxonomy, e.g. to summarize classified reads by clade.
+//!
+//! A taxdump, the directory of `nodes.dmp` and `names.dmp` from NCBI's `taxdump.tar.gz`, is
+//! loaded with `ExonSession::register_taxonomy`, which registers the `taxonomy_nodes` and
+//! `taxonomy_names` tables and these UDFs backed by its tree:
+//!
+//! - `lineage(taxid)`, the scientific names from below the root down to the taxon.
+//! - `lca(taxid_a, taxid_b)`, the lowest common ancestor of two taxa.
+//! - `rank_of(taxid)`, the rank of the taxon, e.g. `genus`.
+//!
+//! Each returns NULL for tax ids that aren't in the taxonomy.
+
+mod tree;
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, AsArray, Int64Array, ListBuilder, RecordBatch, StringArray, StringBuilder},
+    compute::cast,
+    datatypes::{DataType, Field, Int64Type, Schema},
+};
+use datafusion::{
+    datasource::MemTable,
+    error::{DataFusionError, Result},
+    execution::context::SessionState,
+    logical_expr::{ColumnarValue, ScalarUDF, ScalarUDFImpl, Signature, Volatility},
+};
+
+pub use self::tree::{TaxonName, Taxonomy};
+
+use super::create_function::read_function_file;
+
+/// Read the `nodes.dmp` and `names.dmp` files of a taxdump directory.
+pub(crate) async fn read_taxdump(
+    state: &SessionState,
+    path: &str,
+) -> Result<(Taxonomy, Vec<TaxonName>)> {
+    let path = path.trim_end_matches('/');
+
+    let names = read_function_file(state, &format!("{}/names.dmp", path)).await?;
+    let names = TaxonName::parse_names(&names)?;
+
+    let nodes = read_function_file(state, &format!("{}/nodes.dmp", path)).await?;
+    let taxonomy = Taxonomy::parse(&nodes, &names)?;
+
+    Ok((taxonomy, names))
+}
+
+/// The `taxonomy_nodes` table of the tax id, parent tax id, rank, and scientific name of each
+/// taxon.
+pub(crate) fn taxonomy_nodes_table(taxonomy: &Taxonomy) -> Result<MemTable> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("tax_id", DataType::Int64, false),
+        Field::new("parent_tax_id", DataType::Int64, false),
+        Field::new("rank", DataType::Utf8, false),
+        Field::new("name", DataType::Utf8, true),
+    ]));
+
+    let tax_ids = taxonomy.tax_ids();
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from(tax_ids.clone())),
+        Arc::new(
+            tax_ids
+                .iter()
+                .map(|tax_id| taxonomy.parent(*tax_id))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            tax_ids
+                .iter()
+                .map(|tax_id| taxonomy.rank(*tax_id))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            tax_ids
+                .iter()
+                .map(|tax_id| taxonomy.name(*tax_id))
+                .collect::<StringArray>(),
+        ),
+    ];
+
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+    MemTable::try_new(schema, vec![vec![batch]])
+}
+
+/// The `taxonomy_names` table of every name of each taxon, with its class, e.g. `synonym`.
+pub(crate) fn taxonomy_names_table(names: &[TaxonName]) -> Result<MemTable> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("tax_id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("unique_name", DataType::Utf8, true),
+        Field::new("name_class", DataType::Utf8, false),
+    ]));
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(names.iter().map(|name| name.tax_id).collect::<Int64Array>()),
+        Arc::new(
+            names
+                .iter()
+                .map(|name| Some(name.name.as_str()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            names
+                .iter()
+                .map(|name| name.unique_name.as_deref())
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            names
+                .iter()
+                .map(|name| Some(name.name_class.as_str()))
+                .collect::<StringArray>(),
+        ),
+    ];
+
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+    MemTable::try_new(schema, vec![vec![batch]])
+}
+
+/// The lookup a taxonomy UDF does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TaxonomyFunction {
+    /// The lineage of a taxon.
+    Lineage,
+    /// The lowest common ancestor of two taxa.
+    Lca,
+    /// The rank of a taxon.
+    RankOf,
+}
+
+/// Looks up tax ids in a taxonomy.
+#[derive(Debug)]
+pub(crate) struct TaxonomyUdf {
+    signature: Signature,
+    taxonomy: Arc<Taxonomy>,
+    function: TaxonomyFunction,
+}
+
+impl TaxonomyUdf {
+    pub(crate) fn new(taxonomy: Arc<Taxonomy>, function: TaxonomyFunction) -> Self {
+        let arity = match function {
+            TaxonomyFunction::Lca => 2,
+            TaxonomyFunction::Lineage | TaxonomyFunction::RankOf => 1,
+        };
+
+        Self {
+            signature: Signature::any(arity, Volatility::Immutable),
+            taxonomy,
+            function,
+        }
+    }
+}
+
+impl ScalarUDFImpl for TaxonomyUdf {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        match self.function {
+            TaxonomyFunction::Lineage => "lineage",
+            TaxonomyFunction::Lca => "lca",
+            TaxonomyFunction::RankOf => "rank_of",
+        }
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        match self.function {
+            TaxonomyFunction::Lineage => Ok(DataType::List(Arc::new(Field::new(
+                "item",
+                DataType::Utf8,
+                true,
+            )))),
+            TaxonomyFunction::Lca => Ok(DataType::Int64),
+            TaxonomyFunction::RankOf => Ok(DataType::Utf8),
+        }
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+
+        let tax_ids = arrays
+            .iter()
+            .map(|array| cast(array, &DataType::Int64))
+            .collect::<Result<Vec<_>, _>>()?;
+        let tax_ids = tax_ids
+            .iter()
+            .map(|tax_ids| tax_ids.as_primitive::<Int64Type>())
+            .collect::<Vec<_>>();
+
+        let Some(first) = tax_ids.first() else {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes a tax id",
+                self.name()
+            )));
+        };
+
+        let array: ArrayRef = match self.function {
+            TaxonomyFunction::Lineage => {
+                let mut builder = ListBuilder::new(StringBuilder::new());
+
+                for tax_id in first.iter() {
+                    match tax_id.and_then(|tax_id| self.taxonomy.lineage(tax_id)) {
+                        Some(lineage) => builder.append_value(lineage),
+                        None => builder.append_null(),
+                    }
+                }
+
+                Arc::new(builder.finish())
+            }
+            TaxonomyFunction::Lca => {
+                let [a, b] = tax_ids.as_slice() else {
+                    return Err(DataFusionError::Execution(
+                        "lca takes two tax ids".to_string(),
+                    ));
+                };
+
+                Arc::new(
+                    a.iter()
+                        .zip(b.iter())
+                        .map(|(a, b)| self.taxonomy.lca(a?, b?))
+                        .collect::<Int64Array>(),
+                )
+            }
+            TaxonomyFunction::RankOf => Arc::new(
+                first
+                    .iter()
+                    .map(|tax_id| self.taxonomy.rank(tax_id?))
+                    .collect::<StringArray>(),
+            ),
+        };
+
+        Ok(ColumnarValue::Array(array))
+    }
+}
+
+/// The `lineage`, `lca`, and `rank_of` UDFs backed by a taxonomy.
+pub(crate) fn taxonomy_udfs(taxonomy: Arc<Taxonomy>) -> Vec<ScalarUDF> {
+    [
+        TaxonomyFunction::Lineage,
+        TaxonomyFunction::Lca,
+        TaxonomyFunction::RankOf,
+    ]
+    .into_iter()
+    .map(|function| ScalarUDF::from(TaxonomyUdf::new(Arc::clone(&taxonomy), function)))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Array, AsArray, Int64Array},
+        datatypes::Int64Type,
+    };
+    use datafusion::logical_expr::{ColumnarValue, ScalarUDFImpl};
+
+    use super::{TaxonName, Taxonomy, TaxonomyFunction, TaxonomyUdf};
+
+    const NODES: &str = "1\t|\t1\t|\tno rank\t|\n\
+        2\t|\t1\t|\tsuperkingdom\t|\n\
+        561\t|\t2\t|\tgenus\t|\n\
+        562\t|\t561\t|\tspecies\t|\n\
+        1423\t|\t2\t|\tspecies\t|\n";
+
+    const NAMES: &str = "2\t|\tBacteria\t|\t\t|\tscientific name\t|\n\
+        561\t|\tEscherichia\t|\t\t|\tscientific name\t|\n\
+        562\t|\tEscherichia coli\t|\t\t|\tscientific name\t|\n\
+        1423\t|\tBacillus subtilis\t|\t\t|\tscientific name\t|\n";
+
+    fn invoke(
+        function: TaxonomyFunction,
+        args: Vec<Arc<dyn Array>>,
+    ) -> Result<Arc<dyn Array>, Box<dyn std::error::Error>> {
+        let names = TaxonName::parse_names(NAMES)?;
+        let taxonomy = Arc::new(Taxonomy::parse(NODES, &names)?);
+        let udf = TaxonomyUdf::new(taxonomy, function);
+
+        let args = args
+            .into_iter()
+            .map(ColumnarValue::Array)
+            .collect::<Vec<_>>();
+
+        match udf.invoke(&args)? {
+            ColumnarValue::Array(array) => Ok(array),
+            ColumnarValue::Scalar(scalar) => Ok(scalar.to_array()?),
+        }
+    }
+
+    #[test]
+    fn test_taxonomy_udfs() -> Result<(), Box<dyn std::error::Error>> {
+        let tax_ids = Arc::new(Int64Array::from(vec![Some(562), None, Some(404)]));
+
+        let lineages = invoke(TaxonomyFunction::Lineage, vec![tax_ids.clone()])?;
+        let lineages = lineages.as_list::<i32>();
+        let lineage = lineages.value(0);
+        let lineage = lineage.as_string::<i32>();
+        assert_eq!(
+            lineage.iter().collect::<Vec<_>>(),
+            vec![
+                Some("Bacteria"),
+                Some("Escherichia"),
+                Some("Escherichia coli")
+            ]
+        );
+        assert!(lineages.is_null(1));
+        assert!(lineages.is_null(2));
+
+        let ranks = invoke(TaxonomyFunction::RankOf, vec![tax_ids.clone()])?;
+        assert_eq!(
+            ranks.as_string::<i32>().iter().collect::<Vec<_>>(),
+            vec![Some("species"), None, None]
+        );
+
+        let others = Arc::new(Int64Array::from(vec![Some(1423), Some(2), Some(2)]));
+        let lcas = invoke(TaxonomyFunction::Lca, vec![tax_ids, others])?;
+        assert_eq!(
+            lcas.as_primitive::<Int64Type>(),
+            &Int64Array::from(vec![Some(2), None, None])
+        );
+
+        Ok(())
+    }
+}
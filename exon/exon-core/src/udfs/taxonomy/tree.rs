@@ -0,0 +1,265 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+
+use datafusion::error::{DataFusionError, Result};
+
+/// The fields of a line of a taxdump file, which are separated by `\t|\t` and end with `\t|`.
+fn dmp_fields(line: &str) -> Vec<&str> {
+    let line = line.trim_end_matches(['\n', '\r']);
+
+    line.strip_suffix("\t|")
+        .unwrap_or(line)
+        .split("\t|\t")
+        .collect()
+}
+
+fn parse_tax_id(value: &str, file: &str, line: &str) -> Result<i64> {
+    value.trim().parse().map_err(|_| {
+        DataFusionError::Execution(format!("Invalid tax id in {} line: {}", file, line))
+    })
+}
+
+/// A name of a taxon from `names.dmp`, e.g. its scientific name or a synonym.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaxonName {
+    /// The tax id of the taxon.
+    pub tax_id: i64,
+    /// The name.
+    pub name: String,
+    /// The name made unique among taxa, if the name isn't.
+    pub unique_name: Option<String>,
+    /// The class of the name, e.g. `scientific name` or `synonym`.
+    pub name_class: String,
+}
+
+impl TaxonName {
+    /// Parse the names of `names.dmp`.
+    pub fn parse_names(names: &str) -> Result<Vec<Self>> {
+        names
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let fields = dmp_fields(line);
+
+                let [tax_id, name, unique_name, name_class, ..] = fields.as_slice() else {
+                    return Err(DataFusionError::Execution(format!(
+                        "Invalid names.dmp line, expected 4 fields: {}",
+                        line
+                    )));
+                };
+
+                Ok(Self {
+                    tax_id: parse_tax_id(tax_id, "names.dmp", line)?,
+                    name: name.to_string(),
+                    unique_name: Some(unique_name.to_string()).filter(|name| !name.is_empty()),
+                    name_class: name_class.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TaxonomyNode {
+    parent: i64,
+    rank: String,
+    name: Option<String>,
+}
+
+/// The tree of an NCBI taxonomy from the `nodes.dmp` and `names.dmp` files of a taxdump.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Taxonomy {
+    nodes: HashMap<i64, TaxonomyNode>,
+}
+
+impl Taxonomy {
+    /// Build the tree from the contents of `nodes.dmp` and the names of `names.dmp`, which name
+    /// each taxon by its scientific name.
+    pub fn parse(nodes: &str, names: &[TaxonName]) -> Result<Self> {
+        let mut tree = nodes
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let fields = dmp_fields(line);
+
+                let [tax_id, parent, rank, ..] = fields.as_slice() else {
+                    return Err(DataFusionError::Execution(format!(
+                        "Invalid nodes.dmp line, expected 3 fields: {}",
+                        line
+                    )));
+                };
+
+                let node = TaxonomyNode {
+                    parent: parse_tax_id(parent, "nodes.dmp", line)?,
+                    rank: rank.to_string(),
+                    name: None,
+                };
+
+                Ok((parse_tax_id(tax_id, "nodes.dmp", line)?, node))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        for name in names {
+            if name.name_class != "scientific name" {
+                continue;
+            }
+
+            if let Some(node) = tree.get_mut(&name.tax_id) {
+                node.name = Some(name.name.clone());
+            }
+        }
+
+        Ok(Self { nodes: tree })
+    }
+
+    /// The tax ids of the taxonomy, sorted.
+    pub fn tax_ids(&self) -> Vec<i64> {
+        let mut tax_ids = self.nodes.keys().copied().collect::<Vec<_>>();
+        tax_ids.sort_unstable();
+
+        tax_ids
+    }
+
+    /// The tax id of the parent of a taxon, which is its own for the root.
+    pub fn parent(&self, tax_id: i64) -> Option<i64> {
+        self.nodes.get(&tax_id).map(|node| node.parent)
+    }
+
+    /// The rank of a taxon, e.g. `species`.
+    pub fn rank(&self, tax_id: i64) -> Option<&str> {
+        self.nodes.get(&tax_id).map(|node| node.rank.as_str())
+    }
+
+    /// The scientific name of a taxon.
+    pub fn name(&self, tax_id: i64) -> Option<&str> {
+        self.nodes.get(&tax_id)?.name.as_deref()
+    }
+
+    /// The tax ids from a taxon up to the root, or `None` if the taxon isn't in the tree.
+    ///
+    /// A parent that isn't in the tree ends the path, as does a cycle.
+    fn ancestors(&self, tax_id: i64) -> Option<Vec<i64>> {
+        let mut node = self.nodes.get(&tax_id)?;
+        let mut ancestors = vec![tax_id];
+        let mut seen = HashSet::from([tax_id]);
+
+        while seen.insert(node.parent) {
+            let Some(parent) = self.nodes.get(&node.parent) else {
+                break;
+            };
+
+            ancestors.push(node.parent);
+            node = parent;
+        }
+
+        Some(ancestors)
+    }
+
+    /// The names of the taxa from below the root down to a taxon, e.g.
+    /// `cellular organisms, Bacteria, ..., Escherichia coli`.
+    pub fn lineage(&self, tax_id: i64) -> Option<Vec<Option<&str>>> {
+        let mut ancestors = self.ancestors(tax_id)?;
+
+        // The root is its own parent, and isn't part of a lineage.
+        if let Some(root) = ancestors.last() {
+            if self.parent(*root) == Some(*root) {
+                ancestors.pop();
+            }
+        }
+
+        Some(
+            ancestors
+                .into_iter()
+                .rev()
+                .map(|tax_id| self.name(tax_id))
+                .collect(),
+        )
+    }
+
+    /// The lowest common ancestor of two taxa, or `None` if either isn't in the tree or they have
+    /// none.
+    pub fn lca(&self, a: i64, b: i64) -> Option<i64> {
+        let a_ancestors = self.ancestors(a)?.into_iter().collect::<HashSet<_>>();
+
+        self.ancestors(b)?
+            .into_iter()
+            .find(|tax_id| a_ancestors.contains(tax_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TaxonName, Taxonomy};
+
+    const NODES: &str = "1\t|\t1\t|\tno rank\t|\t\t|\n\
+        2\t|\t1\t|\tsuperkingdom\t|\t\t|\n\
+        543\t|\t2\t|\tfamily\t|\t\t|\n\
+        561\t|\t543\t|\tgenus\t|\t\t|\n\
+        562\t|\t561\t|\tspecies\t|\t\t|\n\
+        590\t|\t543\t|\tgenus\t|\t\t|\n\
+        1423\t|\t2\t|\tspecies\t|\t\t|\n\
+        9999\t|\t404\t|\tspecies\t|\t\t|\n";
+
+    const NAMES: &str = "1\t|\troot\t|\t\t|\tscientific name\t|\n\
+        2\t|\tBacteria\t|\tBacteria <bacteria>\t|\tscientific name\t|\n\
+        543\t|\tEnterobacteriaceae\t|\t\t|\tscientific name\t|\n\
+        561\t|\tEscherichia\t|\t\t|\tscientific name\t|\n\
+        562\t|\tEscherichia coli\t|\t\t|\tscientific name\t|\n\
+        562\t|\tBacillus coli\t|\t\t|\tsynonym\t|\n\
+        590\t|\tSalmonella\t|\t\t|\tscientific name\t|\n";
+
+    #[test]
+    fn test_taxonomy() -> Result<(), Box<dyn std::error::Error>> {
+        let names = TaxonName::parse_names(NAMES)?;
+        assert_eq!(names.len(), 7);
+        assert_eq!(names[1].unique_name.as_deref(), Some("Bacteria <bacteria>"));
+        assert_eq!(names[0].unique_name, None);
+
+        let taxonomy = Taxonomy::parse(NODES, &names)?;
+
+        assert_eq!(taxonomy.name(562), Some("Escherichia coli"));
+        assert_eq!(taxonomy.rank(561), Some("genus"));
+        assert_eq!(taxonomy.rank(404), None);
+
+        assert_eq!(
+            taxonomy.lineage(562),
+            Some(vec![
+                Some("Bacteria"),
+                Some("Enterobacteriaceae"),
+                Some("Escherichia"),
+                Some("Escherichia coli")
+            ])
+        );
+        assert_eq!(taxonomy.lineage(1), Some(vec![]));
+        assert_eq!(taxonomy.lineage(1423), Some(vec![Some("Bacteria"), None]));
+        assert_eq!(taxonomy.lineage(9999), Some(vec![None]));
+
+        assert_eq!(taxonomy.lca(562, 590), Some(543));
+        assert_eq!(taxonomy.lca(562, 1423), Some(2));
+        assert_eq!(taxonomy.lca(562, 561), Some(561));
+        assert_eq!(taxonomy.lca(562, 9999), None);
+        assert_eq!(taxonomy.lca(562, 404), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_taxonomy() {
+        assert!(Taxonomy::parse("1\t|\troot\t|\tno rank\t|\n", &[]).is_err());
+        assert!(Taxonomy::parse("1\t|\t1\n", &[]).is_err());
+        assert!(TaxonName::parse_names("x\t|\troot\t|\t\t|\tscientific name\t|\n").is_err());
+    }
+}
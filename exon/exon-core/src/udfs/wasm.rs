@@ -0,0 +1,589 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! WASM UDFs, created from a function exported by a WebAssembly module with e.g.
+//!
+//! ```sql
+//! CREATE FUNCTION gc_count(VARCHAR) RETURNS BIGINT LANGUAGE WASM AS 's3://bucket/functions.wasm';
+//! ```
+//!
+//! The module exports a function with the name of the UDF. `INT`, `BIGINT`, `FLOAT`, and `DOUBLE`
+//! arguments and results are passed as `i32`, `i64`, `f32`, and `f64`, and `BOOLEAN` as an `i32`
+//! that's true if it isn't zero.
+//!
+//! `VARCHAR` arguments are copied into the exported `memory` of the module, at a pointer from its
+//! exported `alloc(len: i32) -> i32`, and passed as two `i32`s, the pointer and the length. A
+//! `VARCHAR` result is an `i64` with the pointer in its high 32 bits and the length in its low 32
+//! bits.
+//!
+//! Null arguments give null without calling the function. Each batch runs in a new instance of the
+//! module, which can't import anything, so nothing carries over between batches.
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray},
+    datatypes::{DataType, Float32Type, Float64Type, Int32Type, Int64Type},
+};
+use datafusion::{
+    error::{DataFusionError, Result},
+    execution::context::SessionState,
+    logical_expr::{
+        ColumnarValue, CreateFunction, ScalarUDF, ScalarUDFImpl, Signature, Volatility,
+    },
+    scalar::ScalarValue,
+};
+use wasmi::{
+    core::{ValType, F32, F64},
+    Engine, Func, Linker, Memory, Module, Store, TypedFunc, Val,
+};
+
+use super::create_function::{function_body, read_function_bytes};
+
+/// The types of the arguments and results of WASM UDFs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WasmType {
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    Boolean,
+    Utf8,
+}
+
+impl WasmType {
+    fn from_data_type(data_type: &DataType) -> Option<Self> {
+        match data_type {
+            DataType::Int32 => Some(Self::Int32),
+            DataType::Int64 => Some(Self::Int64),
+            DataType::Float32 => Some(Self::Float32),
+            DataType::Float64 => Some(Self::Float64),
+            DataType::Boolean => Some(Self::Boolean),
+            DataType::Utf8 => Some(Self::Utf8),
+            _ => None,
+        }
+    }
+
+    fn data_type(&self) -> DataType {
+        match self {
+            Self::Int32 => DataType::Int32,
+            Self::Int64 => DataType::Int64,
+            Self::Float32 => DataType::Float32,
+            Self::Float64 => DataType::Float64,
+            Self::Boolean => DataType::Boolean,
+            Self::Utf8 => DataType::Utf8,
+        }
+    }
+
+    /// The WASM parameters of an argument of the type.
+    fn params(&self) -> &'static [ValType] {
+        match self {
+            Self::Int32 | Self::Boolean => &[ValType::I32],
+            Self::Int64 => &[ValType::I64],
+            Self::Float32 => &[ValType::F32],
+            Self::Float64 => &[ValType::F64],
+            Self::Utf8 => &[ValType::I32, ValType::I32],
+        }
+    }
+
+    /// The WASM result of a result of the type.
+    fn result(&self) -> ValType {
+        match self {
+            Self::Int32 | Self::Boolean => ValType::I32,
+            Self::Int64 | Self::Utf8 => ValType::I64,
+            Self::Float32 => ValType::F32,
+            Self::Float64 => ValType::F64,
+        }
+    }
+}
+
+/// An instance of a module, with the exports a WASM UDF calls.
+struct WasmInstance {
+    store: Store<()>,
+    function: Func,
+    memory: Option<Memory>,
+    alloc: Option<TypedFunc<i32, i32>>,
+}
+
+impl WasmInstance {
+    fn try_new(engine: &Engine, module: &Module, name: &str) -> Result<Self> {
+        let mut store = Store::new(engine, ());
+
+        let instance = Linker::<()>::new(engine)
+            .instantiate(&mut store, module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|e| {
+                DataFusionError::Execution(format!(
+                    "Could not instantiate the module of WASM function {}: {}",
+                    name, e
+                ))
+            })?;
+
+        let function = instance.get_func(&store, name).ok_or_else(|| {
+            DataFusionError::Execution(format!("WASM module doesn't export a function {}", name))
+        })?;
+
+        let memory = instance.get_memory(&store, "memory");
+        let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc").ok();
+
+        Ok(Self {
+            store,
+            function,
+            memory,
+            alloc,
+        })
+    }
+
+    /// Check the exported function takes and returns the WASM types of a UDF.
+    fn check_types(&self, name: &str, args: &[WasmType], result: WasmType) -> Result<()> {
+        let ty = self.function.ty(&self.store);
+
+        let params = args
+            .iter()
+            .flat_map(|arg| arg.params().iter().copied())
+            .collect::<Vec<_>>();
+
+        if ty.params() != params || ty.results() != [result.result()] {
+            return Err(DataFusionError::Plan(format!(
+                "WASM function {} has parameters {:?} and results {:?}, expected {:?} and [{:?}]",
+                name,
+                ty.params(),
+                ty.results(),
+                params,
+                result.result()
+            )));
+        }
+
+        let uses_strings = result == WasmType::Utf8 || args.contains(&WasmType::Utf8);
+        if uses_strings && self.memory.is_none() {
+            return Err(DataFusionError::Plan(format!(
+                "WASM function {} needs the module to export its memory for VARCHAR values",
+                name
+            )));
+        }
+
+        if args.contains(&WasmType::Utf8) && self.alloc.is_none() {
+            return Err(DataFusionError::Plan(format!(
+                "WASM function {} needs the module to export alloc(i32) -> i32 for VARCHAR arguments",
+                name
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn memory(&self) -> Result<Memory> {
+        self.memory
+            .ok_or_else(|| DataFusionError::Execution("WASM module has no memory".to_string()))
+    }
+
+    /// Copy a string into the memory of the instance, returning its pointer and length.
+    fn write_string(&mut self, string: &str) -> Result<[Val; 2]> {
+        let memory = self.memory()?;
+        let alloc = self
+            .alloc
+            .ok_or_else(|| DataFusionError::Execution("WASM module has no alloc".to_string()))?;
+
+        let len = i32::try_from(string.len()).map_err(|_| {
+            DataFusionError::Execution("String is too long for a WASM function".to_string())
+        })?;
+        let ptr = alloc.call(&mut self.store, len).map_err(wasm_error)?;
+
+        memory
+            .write(&mut self.store, ptr as u32 as usize, string.as_bytes())
+            .map_err(wasm_error)?;
+
+        Ok([Val::I32(ptr), Val::I32(len)])
+    }
+
+    /// Read a string from the memory of the instance, given its packed pointer and length.
+    fn read_string(&self, packed: i64) -> Result<String> {
+        let packed = packed as u64;
+        let (ptr, len) = ((packed >> 32) as usize, (packed & 0xFFFF_FFFF) as usize);
+
+        let mut buffer = vec![0; len];
+        self.memory()?
+            .read(&self.store, ptr, &mut buffer)
+            .map_err(wasm_error)?;
+
+        String::from_utf8(buffer).map_err(|e| {
+            DataFusionError::Execution(format!("WASM function returned an invalid string: {}", e))
+        })
+    }
+
+    /// Call the function with the values of a row.
+    fn call(&mut self, arrays: &[ArrayRef], args: &[WasmType], row: usize) -> Result<Val> {
+        let mut params = Vec::with_capacity(args.len());
+
+        for (array, arg) in arrays.iter().zip(args) {
+            match arg {
+                WasmType::Int32 => {
+                    params.push(Val::I32(array.as_primitive::<Int32Type>().value(row)))
+                }
+                WasmType::Int64 => {
+                    params.push(Val::I64(array.as_primitive::<Int64Type>().value(row)))
+                }
+                WasmType::Float32 => params.push(Val::F32(F32::from_float(
+                    array.as_primitive::<Float32Type>().value(row),
+                ))),
+                WasmType::Float64 => params.push(Val::F64(F64::from_float(
+                    array.as_primitive::<Float64Type>().value(row),
+                ))),
+                WasmType::Boolean => {
+                    params.push(Val::I32(i32::from(array.as_boolean().value(row))))
+                }
+                WasmType::Utf8 => {
+                    params.extend(self.write_string(array.as_string::<i32>().value(row))?)
+                }
+            }
+        }
+
+        let mut results = [Val::I32(0)];
+        self.function
+            .call(&mut self.store, &params, &mut results)
+            .map_err(wasm_error)?;
+
+        let [result] = results;
+        Ok(result)
+    }
+}
+
+fn wasm_error(error: impl std::fmt::Display) -> DataFusionError {
+    DataFusionError::Execution(format!("WASM function failed: {}", error))
+}
+
+/// Calls a function exported by a WebAssembly module, a UDF created with `CREATE FUNCTION ...
+/// LANGUAGE WASM`.
+#[derive(Debug)]
+pub(crate) struct WasmUdf {
+    name: String,
+    signature: Signature,
+    engine: Engine,
+    module: Module,
+    args: Vec<WasmType>,
+    result: WasmType,
+}
+
+impl WasmUdf {
+    fn try_new(
+        name: String,
+        wasm: &[u8],
+        arg_types: &[DataType],
+        return_type: &DataType,
+    ) -> Result<Self> {
+        let unsupported = |data_type: &DataType| {
+            DataFusionError::Plan(format!(
+                "WASM function {} can't take or return {}, expected INT, BIGINT, FLOAT, DOUBLE, BOOLEAN, or VARCHAR",
+                name, data_type
+            ))
+        };
+
+        if arg_types.is_empty() {
+            return Err(DataFusionError::Plan(format!(
+                "WASM function {} needs at least one argument",
+                name
+            )));
+        }
+
+        let args = arg_types
+            .iter()
+            .map(|data_type| {
+                WasmType::from_data_type(data_type).ok_or_else(|| unsupported(data_type))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let result =
+            WasmType::from_data_type(return_type).ok_or_else(|| unsupported(return_type))?;
+
+        let engine = Engine::default();
+        let module = Module::new(&engine, wasm).map_err(|e| {
+            DataFusionError::Plan(format!(
+                "WASM function {} has an invalid module: {}",
+                name, e
+            ))
+        })?;
+
+        WasmInstance::try_new(&engine, &module, &name)?.check_types(&name, &args, result)?;
+
+        Ok(Self {
+            name,
+            signature: Signature::exact(arg_types.to_vec(), Volatility::Immutable),
+            engine,
+            module,
+            args,
+            result,
+        })
+    }
+
+    /// The value of a result of the function.
+    fn scalar(&self, instance: &WasmInstance, result: Val) -> Result<ScalarValue> {
+        let invalid = || {
+            DataFusionError::Execution(format!("WASM function {} returned {:?}", self.name, result))
+        };
+
+        let value = match self.result {
+            WasmType::Int32 => ScalarValue::Int32(Some(result.i32().ok_or_else(invalid)?)),
+            WasmType::Int64 => ScalarValue::Int64(Some(result.i64().ok_or_else(invalid)?)),
+            WasmType::Float32 => {
+                ScalarValue::Float32(Some(result.f32().ok_or_else(invalid)?.to_float()))
+            }
+            WasmType::Float64 => {
+                ScalarValue::Float64(Some(result.f64().ok_or_else(invalid)?.to_float()))
+            }
+            WasmType::Boolean => ScalarValue::Boolean(Some(result.i32().ok_or_else(invalid)? != 0)),
+            WasmType::Utf8 => ScalarValue::Utf8(Some(
+                instance.read_string(result.i64().ok_or_else(invalid)?)?,
+            )),
+        };
+
+        Ok(value)
+    }
+}
+
+impl ScalarUDFImpl for WasmUdf {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(self.result.data_type())
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        if args.len() != self.args.len() {
+            return Err(DataFusionError::Execution(format!(
+                "{} takes {} arguments",
+                self.name(),
+                self.args.len()
+            )));
+        }
+
+        let arrays = ColumnarValue::values_to_arrays(args)?;
+        let rows = arrays.first().map(|array| array.len()).unwrap_or_default();
+
+        let mut instance = WasmInstance::try_new(&self.engine, &self.module, &self.name)?;
+        let null = ScalarValue::try_from(&self.result.data_type())?;
+
+        let values = (0..rows)
+            .map(|row| {
+                if arrays.iter().any(|array| array.is_null(row)) {
+                    return Ok(null.clone());
+                }
+
+                let result = instance.call(&arrays, &self.args, row)?;
+                self.scalar(&instance, result)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let array = ScalarValue::iter_to_array(values)?;
+
+        Ok(ColumnarValue::Array(array))
+    }
+}
+
+/// Create a WASM UDF from a `CREATE FUNCTION ... LANGUAGE WASM AS 'path'` statement.
+pub(crate) async fn create_wasm_udf(
+    state: &SessionState,
+    statement: &CreateFunction,
+) -> Result<ScalarUDF> {
+    let name = &statement.name;
+
+    let Some(path) = function_body(statement) else {
+        return Err(DataFusionError::Plan(format!(
+            "WASM function {} needs the path to a WASM module, e.g. AS 'functions.wasm'",
+            name
+        )));
+    };
+
+    let Some(return_type) = &statement.return_type else {
+        return Err(DataFusionError::Plan(format!(
+            "WASM function {} needs a RETURNS type",
+            name
+        )));
+    };
+
+    let arg_types = statement
+        .args
+        .iter()
+        .flatten()
+        .map(|arg| arg.data_type.clone())
+        .collect::<Vec<_>>();
+
+    let wasm = read_function_bytes(state, path).await?;
+    let udf = WasmUdf::try_new(name.clone(), &wasm, &arg_types, return_type)?;
+
+    Ok(ScalarUDF::from(udf))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{ArrayRef, AsArray, Float64Array, Int32Array, Int64Array, StringArray},
+        datatypes::{DataType, Float64Type, Int64Type},
+    };
+    use datafusion::logical_expr::{ColumnarValue, ScalarUDFImpl};
+
+    use super::WasmUdf;
+
+    fn wasm() -> Vec<u8> {
+        let path = format!(
+            "{}/test-data/models/wasm/functions.wasm",
+            env!("CARGO_MANIFEST_DIR")
+        );
+
+        std::fs::read(path).unwrap()
+    }
+
+    fn invoke(udf: &WasmUdf, arrays: Vec<ArrayRef>) -> ArrayRef {
+        let args = arrays
+            .into_iter()
+            .map(ColumnarValue::Array)
+            .collect::<Vec<_>>();
+
+        let ColumnarValue::Array(array) = udf.invoke(&args).unwrap() else {
+            panic!("expected an array");
+        };
+
+        array
+    }
+
+    #[test]
+    fn test_wasm_numbers() -> Result<(), Box<dyn std::error::Error>> {
+        let udf = WasmUdf::try_new(
+            "add_one".to_string(),
+            &wasm(),
+            &[DataType::Int64],
+            &DataType::Int64,
+        )?;
+
+        let array = invoke(&udf, vec![Arc::new(Int64Array::from(vec![Some(1), None]))]);
+        assert_eq!(
+            array.as_primitive::<Int64Type>().iter().collect::<Vec<_>>(),
+            vec![Some(2), None]
+        );
+
+        let udf = WasmUdf::try_new(
+            "weighted_sum".to_string(),
+            &wasm(),
+            &[DataType::Float64, DataType::Int32],
+            &DataType::Float64,
+        )?;
+
+        let array = invoke(
+            &udf,
+            vec![
+                Arc::new(Float64Array::from(vec![1.5, 2.0])),
+                Arc::new(Int32Array::from(vec![3, -2])),
+            ],
+        );
+        assert_eq!(
+            array.as_primitive::<Float64Type>().values().to_vec(),
+            vec![3.0, 1.0]
+        );
+
+        let udf = WasmUdf::try_new(
+            "is_even".to_string(),
+            &wasm(),
+            &[DataType::Int64],
+            &DataType::Boolean,
+        )?;
+
+        let array = invoke(&udf, vec![Arc::new(Int64Array::from(vec![4, 7]))]);
+        assert_eq!(
+            array.as_boolean().iter().collect::<Vec<_>>(),
+            vec![Some(true), Some(false)]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wasm_strings() -> Result<(), Box<dyn std::error::Error>> {
+        let sequences: ArrayRef = Arc::new(StringArray::from(vec![Some("ACGGT"), Some(""), None]));
+
+        let udf = WasmUdf::try_new(
+            "gc_count".to_string(),
+            &wasm(),
+            &[DataType::Utf8],
+            &DataType::Int64,
+        )?;
+
+        let array = invoke(&udf, vec![sequences.clone()]);
+        assert_eq!(
+            array.as_primitive::<Int64Type>().iter().collect::<Vec<_>>(),
+            vec![Some(3), Some(0), None]
+        );
+
+        let udf = WasmUdf::try_new(
+            "first_base".to_string(),
+            &wasm(),
+            &[DataType::Utf8],
+            &DataType::Utf8,
+        )?;
+
+        let array = invoke(&udf, vec![sequences]);
+        assert_eq!(
+            array.as_string::<i32>().iter().collect::<Vec<_>>(),
+            vec![Some("A"), Some(""), None]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wasm_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let wasm = wasm();
+
+        let missing = WasmUdf::try_new(
+            "missing".to_string(),
+            &wasm,
+            &[DataType::Int64],
+            &DataType::Int64,
+        );
+        assert!(missing.is_err());
+
+        let mismatched = WasmUdf::try_new(
+            "add_one".to_string(),
+            &wasm,
+            &[DataType::Int32],
+            &DataType::Int64,
+        );
+        assert!(mismatched.is_err());
+
+        let unsupported = WasmUdf::try_new(
+            "add_one".to_string(),
+            &wasm,
+            &[DataType::Date32],
+            &DataType::Int64,
+        );
+        assert!(unsupported.is_err());
+
+        let udf = WasmUdf::try_new(
+            "boom".to_string(),
+            &wasm,
+            &[DataType::Int64],
+            &DataType::Int64,
+        )?;
+        let args = [ColumnarValue::Array(Arc::new(Int64Array::from(vec![1])))];
+        assert!(udf.invoke(&args).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+;; Functions for the WASM UDF tests, compiled to functions.wasm with `wat2wasm functions.wat`.
+(module
+  (memory (export "memory") 1)
+
+  ;; A bump allocator for the strings passed to the functions.
+  (global $heap (mut i32) (i32.const 1024))
+
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+    (local.get $ptr))
+
+  (func (export "add_one") (param $x i64) (result i64)
+    (i64.add (local.get $x) (i64.const 1)))
+
+  (func (export "weighted_sum") (param $x f64) (param $y i32) (result f64)
+    (f64.add (local.get $x) (f64.mul (f64.convert_i32_s (local.get $y)) (f64.const 0.5))))
+
+  ;; The number of G and C bases of a string.
+  (func (export "gc_count") (param $ptr i32) (param $len i32) (result i64)
+    (local $end i32)
+    (local $count i64)
+    (local $base i32)
+    (local.set $end (i32.add (local.get $ptr) (local.get $len)))
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $ptr) (local.get $end)))
+        (local.set $base (i32.or (i32.load8_u (local.get $ptr)) (i32.const 32)))
+        (if (i32.or (i32.eq (local.get $base) (i32.const 103)) (i32.eq (local.get $base) (i32.const 99)))
+          (then (local.set $count (i64.add (local.get $count) (i64.const 1)))))
+        (local.set $ptr (i32.add (local.get $ptr) (i32.const 1)))
+        (br $next)))
+    (local.get $count))
+
+  ;; The first base of a string, as a string packed into its pointer and length.
+  (func (export "first_base") (param $ptr i32) (param $len i32) (result i64)
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+      (i64.extend_i32_u (select (i32.const 1) (i32.const 0) (local.get $len)))))
+
+  (func (export "is_even") (param $x i64) (result i32)
+    (i64.eqz (i64.rem_s (local.get $x) (i64.const 2))))
+
+  (func (export "boom") (param $x i64) (result i64)
+    unreachable))
@@ -19,6 +19,7 @@ use clap::Parser;
 use datafusion::{error::DataFusionError, scalar::ScalarValue};
 
 use exon::ExonSession;
+use exon_common::StaticColumnTransformKey;
 use sqllogictest::{ColumnType, DBOutput, DefaultColumnType, TestErrorKind};
 use tracing::Level;
 use tracing_subscriber::FmtSubscriber;
@@ -149,6 +150,12 @@ async fn run_tests(test_options: &Options) -> Result<(), DataFusionError> {
 
     let exon_context = Arc::new(ExonSession::new_exon()?);
 
+    // The column transform key can't be set from SQL, so the tests share a fixed one.
+    let column_transform_key = StaticColumnTransformKey::try_from_hex(
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+    )?;
+    exon_context.register_column_transform_key_provider(Arc::new(column_transform_key))?;
+
     for test_file in test_files {
         let test_file = test_file?;
 
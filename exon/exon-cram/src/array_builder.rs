@@ -15,11 +15,13 @@
 use std::sync::Arc;
 
 use arrow::{
-    array::{ArrayRef, GenericListBuilder, GenericStringBuilder, Int32Builder, Int64Builder},
+    array::{ArrayRef, GenericStringBuilder, Int64Builder},
     error::ArrowError,
 };
 use exon_common::ExonArrayBuilder;
-use exon_sam::TagsBuilder;
+use exon_sam::{
+    has_compact_types, FlagsBuilder, MappingQualitiesBuilder, QualityScoresBuilder, TagsBuilder,
+};
 use noodles::{
     cram::Record as CramRecord,
     sam::alignment::record::{cigar::op::Kind, Cigar},
@@ -35,15 +37,15 @@ pub(crate) struct CRAMArrayBuilder {
 
     // arrays
     names: GenericStringBuilder<i32>,
-    flags: Int32Builder,
+    flags: FlagsBuilder,
     references: GenericStringBuilder<i32>,
     starts: Int64Builder,
     ends: Int64Builder,
-    mapping_qualities: GenericStringBuilder<i32>,
+    mapping_qualities: MappingQualitiesBuilder,
     cigar: GenericStringBuilder<i32>,
     mate_references: GenericStringBuilder<i32>,
     sequences: GenericStringBuilder<i32>,
-    quality_scores: GenericListBuilder<i32, Int64Builder>,
+    quality_scores: QualityScoresBuilder,
 }
 
 impl CRAMArrayBuilder {
@@ -55,6 +57,8 @@ impl CRAMArrayBuilder {
                 TagsBuilder::try_from(field.data_type()).unwrap()
             });
 
+        let compact = has_compact_types(&config.file_schema);
+
         Self {
             rows: 0,
             tags: tags_builder,
@@ -63,15 +67,15 @@ impl CRAMArrayBuilder {
 
             // arrays
             names: GenericStringBuilder::<i32>::with_capacity(capacity, capacity * 8),
-            flags: Int32Builder::new(),
+            flags: FlagsBuilder::new(compact),
             references: GenericStringBuilder::<i32>::with_capacity(capacity, capacity * 8),
             starts: Int64Builder::new(),
             ends: Int64Builder::new(),
-            mapping_qualities: GenericStringBuilder::<i32>::with_capacity(capacity, capacity * 8),
+            mapping_qualities: MappingQualitiesBuilder::new(compact),
             cigar: GenericStringBuilder::<i32>::with_capacity(capacity, capacity * 8),
             mate_references: GenericStringBuilder::<i32>::with_capacity(capacity, capacity * 8),
             sequences: GenericStringBuilder::<i32>::with_capacity(capacity, capacity * 8),
-            quality_scores: GenericListBuilder::<i32, Int64Builder>::new(Int64Builder::new()),
+            quality_scores: QualityScoresBuilder::new(compact),
         }
     }
 
@@ -86,8 +90,7 @@ impl CRAMArrayBuilder {
                     }
                 }
                 1 => {
-                    let flag_bits = record.flags().bits();
-                    self.flags.append_value(flag_bits as i32);
+                    self.flags.append_value(record.flags().bits());
                 }
                 2 => match record.reference_sequence(self.header.reference_sequences()) {
                     Some(Ok((id, _))) => {
@@ -107,7 +110,7 @@ impl CRAMArrayBuilder {
                 }
                 5 => {
                     self.mapping_qualities
-                        .append_option(record.mapping_quality().map(|p| p.get().to_string()));
+                        .append_option(record.mapping_quality().map(|p| p.get()));
                 }
                 6 => {
                     let mut cigar_to_print = Vec::new();
@@ -150,17 +153,7 @@ impl CRAMArrayBuilder {
                 }
                 9 => {
                     let quality_scores = record.quality_scores().as_ref();
-                    let slice_i8: &[i8] = unsafe {
-                        std::slice::from_raw_parts(
-                            quality_scores.as_ptr() as *const i8,
-                            quality_scores.len(),
-                        )
-                    };
-
-                    let slice_i64 = slice_i8.iter().map(|v| *v as i64).collect::<Vec<_>>();
-
-                    self.quality_scores.values().append_slice(&slice_i64);
-                    self.quality_scores.append(true);
+                    self.quality_scores.append(quality_scores);
                 }
                 10 => {
                     // This is _very_ similar to BAM, may not need body any more
@@ -189,15 +182,15 @@ impl ExonArrayBuilder for CRAMArrayBuilder {
         for col_idx in self.projection.iter() {
             match col_idx {
                 0 => arrays.push(Arc::new(self.names.finish())),
-                1 => arrays.push(Arc::new(self.flags.finish())),
+                1 => arrays.push(self.flags.finish()),
                 2 => arrays.push(Arc::new(self.references.finish())),
                 3 => arrays.push(Arc::new(self.starts.finish())),
                 4 => arrays.push(Arc::new(self.ends.finish())),
-                5 => arrays.push(Arc::new(self.mapping_qualities.finish())),
+                5 => arrays.push(self.mapping_qualities.finish()),
                 6 => arrays.push(Arc::new(self.cigar.finish())),
                 7 => arrays.push(Arc::new(self.mate_references.finish())),
                 8 => arrays.push(Arc::new(self.sequences.finish())),
-                9 => arrays.push(Arc::new(self.quality_scores.finish())),
+                9 => arrays.push(self.quality_scores.finish()),
                 10 => arrays.push(Arc::new(self.tags.finish())),
                 _ => panic!("Invalid column index {} for CRAM Array Builder", col_idx),
             }
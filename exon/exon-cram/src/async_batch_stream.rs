@@ -24,7 +24,11 @@ use noodles::cram::AsyncReader;
 use object_store::ObjectStore;
 use tokio::io::AsyncBufRead;
 
-use crate::{array_builder::CRAMArrayBuilder, CRAMConfig, ObjectStoreFastaRepositoryAdapter};
+use crate::{
+    array_builder::CRAMArrayBuilder,
+    limited_reader::{check_record, read_data_container_with_limits},
+    CRAMConfig, ObjectStoreFastaRepositoryAdapter,
+};
 
 pub struct AsyncBatchStream<R>
 where
@@ -40,6 +44,9 @@ where
 
     /// The reference repository
     reference_sequence_repository: noodles::fasta::Repository,
+
+    /// The buffer containers are read into.
+    buf: Vec<u8>,
 }
 
 impl<R> AsyncBatchStream<R>
@@ -70,6 +77,7 @@ where
             header,
             config,
             reference_sequence_repository,
+            buf: Vec::new(),
         })
     }
 
@@ -77,7 +85,13 @@ where
         let mut array_builder =
             CRAMArrayBuilder::new(self.header.clone(), DEFAULT_BATCH_SIZE, &self.config);
 
-        if let Some(container) = self.reader.read_data_container().await? {
+        if let Some(container) = read_data_container_with_limits(
+            self.reader.get_mut(),
+            &mut self.buf,
+            &self.config.reader_limits,
+        )
+        .await?
+        {
             let records = container
                 .slices()
                 .iter()
@@ -101,6 +115,7 @@ where
 
             // iterate through the records and append them to the array builder
             for record in records {
+                check_record(&record, &self.config.reader_limits)?;
                 array_builder.append(record)?;
             }
         } else {
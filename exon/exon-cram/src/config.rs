@@ -15,7 +15,7 @@
 use std::sync::Arc;
 
 use arrow::datatypes::SchemaRef;
-use exon_common::DEFAULT_BATCH_SIZE;
+use exon_common::{ReaderLimits, DEFAULT_BATCH_SIZE};
 use object_store::ObjectStore;
 
 /// Configuration for a CRAM datasource.
@@ -31,6 +31,8 @@ pub struct CRAMConfig {
     pub projection: Option<Vec<usize>>,
     /// The FASTA reference to use.
     pub fasta_reference: Option<String>,
+    /// The limits on header size, container size, sequence length, and tag count.
+    pub reader_limits: ReaderLimits,
 }
 
 impl CRAMConfig {
@@ -46,6 +48,7 @@ impl CRAMConfig {
             file_schema,
             projection: None,
             fasta_reference,
+            reader_limits: ReaderLimits::default(),
         }
     }
 
@@ -68,6 +71,12 @@ impl CRAMConfig {
         self
     }
 
+    /// Set the reader limits.
+    pub fn with_reader_limits(mut self, reader_limits: ReaderLimits) -> Self {
+        self.reader_limits = reader_limits;
+        self
+    }
+
     /// Get the projected schema.
     pub fn projected_schema(&self) -> SchemaRef {
         match &self.projection {
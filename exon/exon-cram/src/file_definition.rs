@@ -0,0 +1,87 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+
+use noodles::cram::{file_definition::Version, r#async::io::Reader, FileDefinition};
+use tokio::io::AsyncRead;
+
+/// The CRAM format versions that can be read, as (major, minor).
+///
+/// 3.1 adds the rANS Nx16, adaptive arithmetic, fqzcomp and name tokenizer block codecs.
+pub const SUPPORTED_VERSIONS: [(u8, u8); 2] = [(3, 0), (3, 1)];
+
+/// Read the CRAM file definition, returning an error if the format version is not supported.
+pub async fn read_file_definition<R>(reader: &mut Reader<R>) -> io::Result<FileDefinition>
+where
+    R: AsyncRead + Unpin,
+{
+    let file_definition = reader.read_file_definition().await?;
+    check_version(file_definition.version())?;
+
+    Ok(file_definition)
+}
+
+fn check_version(version: Version) -> io::Result<()> {
+    if SUPPORTED_VERSIONS.contains(&(version.major(), version.minor())) {
+        return Ok(());
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "unsupported CRAM version {}.{}, supported versions are 3.0 and 3.1",
+            version.major(),
+            version.minor()
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use noodles::cram::{file_definition::Version, r#async::io::Reader};
+
+    use super::{check_version, read_file_definition};
+
+    #[test]
+    fn test_check_version() {
+        assert!(check_version(Version::new(3, 0)).is_ok());
+        assert!(check_version(Version::new(3, 1)).is_ok());
+
+        let err = check_version(Version::new(2, 1)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unsupported CRAM version 2.1, supported versions are 3.0 and 3.1"
+        );
+
+        assert!(check_version(Version::new(4, 0)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_file_definition_rejects_cram_2() {
+        // The magic number, version 2.1, and a 20-byte file ID.
+        let mut src = b"CRAM".to_vec();
+        src.extend([2, 1]);
+        src.extend([0; 20]);
+
+        let mut reader = Reader::new(&src[..]);
+
+        let err = read_file_definition(&mut reader).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "unsupported CRAM version 2.1, supported versions are 3.0 and 3.1"
+        );
+    }
+}
@@ -27,7 +27,11 @@ use noodles::cram::{
 };
 use tokio::io::{AsyncBufRead, AsyncSeek};
 
-use crate::{array_builder::CRAMArrayBuilder, CRAMConfig, ObjectStoreFastaRepositoryAdapter};
+use crate::{
+    array_builder::CRAMArrayBuilder,
+    limited_reader::{check_record, read_data_container_with_limits},
+    CRAMConfig, ObjectStoreFastaRepositoryAdapter,
+};
 
 pub struct IndexedAsyncBatchStream<R>
 where
@@ -48,6 +52,13 @@ where
     /// The CRAM index record.
     // index_records: Vec<Record>,
     ranges: BasicCOITree<crai::Record, u32>,
+
+    /// Whether the container the reader is positioned at has been read. The index records all
+    /// come from that one container, so the stream ends after it.
+    container_read: bool,
+
+    /// The buffer containers are read into.
+    buf: Vec<u8>,
 }
 
 impl<R> IndexedAsyncBatchStream<R>
@@ -91,6 +102,8 @@ where
             config,
             reference_sequence_repository,
             ranges: trees,
+            container_read: false,
+            buf: Vec::new(),
         })
     }
 
@@ -98,12 +111,24 @@ where
         let mut array_builder =
             CRAMArrayBuilder::new(self.header.clone(), DEFAULT_BATCH_SIZE, &self.config);
 
-        let container = if let Some(container) = self.reader.read_data_container().await? {
+        if self.container_read {
+            return Ok(None);
+        }
+
+        let container = if let Some(container) = read_data_container_with_limits(
+            self.reader.get_mut(),
+            &mut self.buf,
+            &self.config.reader_limits,
+        )
+        .await?
+        {
             container
         } else {
             return Ok(None);
         };
 
+        self.container_read = true;
+
         let records = container
             .slices()
             .iter()
@@ -128,10 +153,17 @@ where
                 let start = record.alignment_start().unwrap().get();
                 let end = start + record.alignment_end().unwrap().get();
 
-                self.ranges.query_count(start as i32, end as i32) > 0
+                let mut overlaps = false;
+                self.ranges.query(start as i32, end as i32, |node| {
+                    overlaps |=
+                        node.metadata.reference_sequence_id() == record.reference_sequence_id();
+                });
+
+                overlaps
             });
 
         for record in records {
+            check_record(&record, &self.config.reader_limits)?;
             array_builder.append(record)?;
         }
 
@@ -15,12 +15,22 @@
 mod array_builder;
 mod async_batch_stream;
 mod config;
+mod file_definition;
 mod indexed_async_batch_stream;
+mod limited_reader;
 mod object_store_fasta_repository_adapter;
 
 /// CRAM configuration struct.
 pub use config::CRAMConfig;
 
+/// Read a CRAM file definition and check its version.
+pub use file_definition::{read_file_definition, SUPPORTED_VERSIONS};
+
+/// Read CRAM containers, checking them against the reader limits.
+pub use limited_reader::{
+    check_record, read_data_container_with_limits, read_file_header_with_limits,
+};
+
 /// CRAM Batch Stream.
 pub use async_batch_stream::AsyncBatchStream;
 
@@ -0,0 +1,213 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+
+use exon_common::{read_exact_onto, ReaderLimit, ReaderLimits};
+use noodles::{cram, sam};
+use tokio::io::AsyncRead;
+
+/// The number of bytes after the first byte of an ITF8 integer.
+fn itf8_extra_len(b: u8) -> usize {
+    (b.leading_ones() as usize).min(4)
+}
+
+/// The number of bytes after the first byte of an LTF8 integer.
+fn ltf8_extra_len(b: u8) -> usize {
+    b.leading_ones() as usize
+}
+
+fn decode_itf8(src: &[u8]) -> i32 {
+    let b = |i: usize| i32::from(src[i]);
+
+    match src.len() {
+        1 => b(0),
+        2 => ((b(0) & 0x3f) << 8) | b(1),
+        3 => ((b(0) & 0x1f) << 16) | (b(1) << 8) | b(2),
+        4 => ((b(0) & 0x0f) << 24) | (b(1) << 16) | (b(2) << 8) | b(3),
+        _ => ((b(0) & 0x0f) << 28) | (b(1) << 20) | (b(2) << 12) | (b(3) << 4) | (b(4) & 0x0f),
+    }
+}
+
+/// Read an ITF8 integer onto the end of `buf`, returning its value.
+async fn read_itf8_onto<R>(reader: &mut R, buf: &mut Vec<u8>) -> io::Result<i32>
+where
+    R: AsyncRead + Unpin,
+{
+    let start = buf.len();
+    read_exact_onto(reader, buf, 1).await?;
+    read_exact_onto(reader, buf, itf8_extra_len(buf[start])).await?;
+
+    Ok(decode_itf8(&buf[start..]))
+}
+
+/// Read an LTF8 integer onto the end of `buf`.
+async fn read_ltf8_onto<R>(reader: &mut R, buf: &mut Vec<u8>) -> io::Result<()>
+where
+    R: AsyncRead + Unpin,
+{
+    let start = buf.len();
+    read_exact_onto(reader, buf, 1).await?;
+    read_exact_onto(reader, buf, ltf8_extra_len(buf[start])).await
+}
+
+fn try_into_usize(n: i32) -> io::Result<usize> {
+    usize::try_from(n).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Read a container, its header and blocks, into `buf`.
+///
+/// The container length is checked against `limit` before the blocks are buffered.
+async fn read_container<R>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    limit: ReaderLimit,
+    limits: &ReaderLimits,
+) -> io::Result<()>
+where
+    R: AsyncRead + Unpin,
+{
+    buf.clear();
+
+    read_exact_onto(reader, buf, 4).await?;
+    let length = try_into_usize(i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]))?;
+    limits.check(limit, length)?;
+
+    // The reference sequence ID, alignment start, alignment span, and record count.
+    for _ in 0..4 {
+        read_itf8_onto(reader, buf).await?;
+    }
+
+    // The record counter and base count.
+    for _ in 0..2 {
+        read_ltf8_onto(reader, buf).await?;
+    }
+
+    // The block count.
+    read_itf8_onto(reader, buf).await?;
+
+    // Each landmark is the offset of a slice in the container, so there can't be more than bytes.
+    let landmark_count = try_into_usize(read_itf8_onto(reader, buf).await?)?;
+
+    if landmark_count > length {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid container landmark count: {landmark_count}"),
+        ));
+    }
+
+    for _ in 0..landmark_count {
+        read_itf8_onto(reader, buf).await?;
+    }
+
+    // The header CRC32, then the blocks.
+    read_exact_onto(reader, buf, 4 + length).await
+}
+
+/// Read the CRAM file header from the stream, positioned after the file definition.
+///
+/// The header container length is checked against the header size limit before it's buffered.
+pub async fn read_file_header_with_limits<R>(
+    reader: &mut R,
+    limits: &ReaderLimits,
+) -> io::Result<sam::Header>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buf = Vec::new();
+    read_container(reader, &mut buf, ReaderLimit::HeaderSize, limits).await?;
+
+    cram::io::Reader::new(&buf[..]).read_file_header()
+}
+
+/// Read a data container from the stream, using `buf` to hold its bytes, returning `None` at the
+/// EOF container.
+///
+/// A container is the smallest unit a CRAM file is read in, so its length is checked against the
+/// record size limit before it's buffered.
+pub async fn read_data_container_with_limits<R>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    limits: &ReaderLimits,
+) -> io::Result<Option<cram::DataContainer>>
+where
+    R: AsyncRead + Unpin,
+{
+    read_container(reader, buf, ReaderLimit::RecordSize, limits).await?;
+
+    cram::io::Reader::new(&buf[..]).read_data_container()
+}
+
+/// Check the sequence length and tag count of a record read from a data container.
+pub fn check_record(record: &cram::Record, limits: &ReaderLimits) -> io::Result<()> {
+    limits.check(ReaderLimit::SequenceLength, record.sequence().len())?;
+    limits.check(ReaderLimit::AttributeCount, record.data().len())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use exon_common::{ReaderLimitError, ReaderLimits};
+
+    use super::read_data_container_with_limits;
+
+    /// The CRAM 3.x EOF container.
+    const EOF_CONTAINER: [u8; 38] = [
+        0x0f, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0x0f, 0xe0, 0x45, 0x4f, 0x46, 0x00, 0x00,
+        0x00, 0x00, 0x01, 0x00, 0x05, 0xbd, 0xd9, 0x4f, 0x00, 0x01, 0x00, 0x06, 0x06, 0x01, 0x00,
+        0x01, 0x00, 0x01, 0x00, 0xee, 0x63, 0x01, 0x4b,
+    ];
+
+    #[tokio::test]
+    async fn test_read_eof_container() -> Result<(), Box<dyn std::error::Error>> {
+        let mut buf = Vec::new();
+        let limits = ReaderLimits::default();
+
+        let container =
+            read_data_container_with_limits(&mut &EOF_CONTAINER[..], &mut buf, &limits).await?;
+        assert!(container.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_container_size_limit() {
+        let mut buf = Vec::new();
+
+        let limits = ReaderLimits {
+            max_record_size: 8,
+            ..Default::default()
+        };
+
+        let Err(err) =
+            read_data_container_with_limits(&mut &EOF_CONTAINER[..], &mut buf, &limits).await
+        else {
+            panic!("expected the container to exceed the limit");
+        };
+        assert!(ReaderLimitError::find(&err).is_some());
+
+        // The length is checked before the blocks are read.
+        let mut src = i32::MAX.to_le_bytes().to_vec();
+        src.extend(&EOF_CONTAINER[4..]);
+
+        let limits = ReaderLimits::default();
+
+        let Err(err) = read_data_container_with_limits(&mut &src[..], &mut buf, &limits).await
+        else {
+            panic!("expected the container to exceed the limit");
+        };
+        assert!(ReaderLimitError::find(&err).is_some());
+    }
+}
@@ -15,11 +15,14 @@
 use std::{str::FromStr, sync::Arc};
 
 use arrow::{
-    array::{ArrayRef, GenericListBuilder, GenericStringBuilder, Int8Builder},
+    array::{ArrayRef, BinaryBuilder, GenericListBuilder, GenericStringBuilder, Int8Builder},
     datatypes::SchemaRef,
     error::ArrowError,
 };
-use exon_common::ExonArrayBuilder;
+use exon_common::{
+    packed_sequence::{pack_sequence, PackedSequenceError},
+    ExonArrayBuilder,
+};
 use noodles::fasta::record::Definition;
 
 use crate::{ExonFASTAError, SequenceDataType};
@@ -40,6 +43,7 @@ pub enum SequenceBuilder {
     LargeUtf8(GenericStringBuilder<i64>),
     IntegerEncodeDNA(GenericListBuilder<i32, Int8Builder>),
     IntegerEncodeProtein(GenericListBuilder<i32, Int8Builder>),
+    PackedDNA(BinaryBuilder, Vec<u8>),
 }
 
 impl SequenceBuilder {
@@ -49,6 +53,7 @@ impl SequenceBuilder {
             Self::LargeUtf8(ref mut builder) => Arc::new(builder.finish()),
             Self::IntegerEncodeProtein(ref mut builder) => Arc::new(builder.finish()),
             Self::IntegerEncodeDNA(ref mut builder) => Arc::new(builder.finish()),
+            Self::PackedDNA(ref mut builder, _) => Arc::new(builder.finish()),
         }
     }
 }
@@ -80,6 +85,10 @@ impl FASTAArrayBuilder {
                     capacity,
                 ),
             ),
+            SequenceDataType::PackedDNA => SequenceBuilder::PackedDNA(
+                BinaryBuilder::with_capacity(capacity, capacity),
+                Vec::new(),
+            ),
         };
 
         let projection = match projection {
@@ -203,6 +212,16 @@ impl FASTAArrayBuilder {
 
                     builder.append(true);
                 }
+                SequenceBuilder::PackedDNA(ref mut builder, ref mut packed) => {
+                    pack_sequence(sequence, packed).map_err(|e| match e {
+                        PackedSequenceError::InvalidBase(nt) => {
+                            ExonFASTAError::InvalidNucleotide(nt)
+                        }
+                        e => ExonFASTAError::ArrayBuilderError(e.to_string()),
+                    })?;
+
+                    builder.append_value(packed);
+                }
             }
         }
 
@@ -15,10 +15,10 @@
 use std::sync::Arc;
 
 use arrow::record_batch::RecordBatch;
-use exon_common::ExonArrayBuilder;
+use exon_common::{BoundedReader, ExonArrayBuilder, ReaderLimit};
 use futures::Stream;
 
-use tokio::io::AsyncBufRead;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
 
 use crate::{error::ExonFASTAResult, ExonFASTAError};
 
@@ -27,7 +27,7 @@ use super::{array_builder::FASTAArrayBuilder, config::FASTAConfig};
 /// A FASTA batch reader.
 pub struct BatchReader<R> {
     /// The underlying FASTA reader.
-    reader: noodles::fasta::AsyncReader<R>,
+    reader: noodles::fasta::AsyncReader<BoundedReader<R>>,
 
     /// The FASTA configuration.
     config: Arc<FASTAConfig>,
@@ -48,7 +48,10 @@ where
         let buffer_size = config.fasta_sequence_buffer_capacity;
 
         Self {
-            reader: noodles::fasta::AsyncReader::new(inner),
+            reader: noodles::fasta::AsyncReader::new(BoundedReader::new(
+                inner,
+                &config.reader_limits,
+            )),
             config,
             buf: String::with_capacity(50),
             sequence_buffer: Vec::with_capacity(buffer_size),
@@ -62,13 +65,51 @@ where
         }
 
         self.sequence_buffer.clear();
-        if self.reader.read_sequence(&mut self.sequence_buffer).await? == 0 {
+        if self.read_sequence().await? == 0 {
             return Err(ExonFASTAError::ParseError("invalid sequence".to_string()));
         }
 
         Ok(Some(()))
     }
 
+    /// Read the sequence lines up to the next definition, checking the length as it grows so a
+    /// corrupt file can't grow the buffer past the limit.
+    async fn read_sequence(&mut self) -> ExonFASTAResult<usize> {
+        let reader = self.reader.get_mut();
+        let mut bytes_read = 0;
+
+        loop {
+            let src = reader.fill_buf().await?;
+
+            if src.first().map(|b| *b == b'>').unwrap_or(true) {
+                break;
+            }
+
+            let len = match src.iter().position(|b| *b == b'\n') {
+                Some(i) => {
+                    let line = src[..i].strip_suffix(b"\r").unwrap_or(&src[..i]);
+                    self.sequence_buffer.extend_from_slice(line);
+
+                    i + 1
+                }
+                None => {
+                    self.sequence_buffer.extend_from_slice(src);
+                    src.len()
+                }
+            };
+
+            reader.consume(len);
+            bytes_read += len;
+
+            self.config
+                .reader_limits
+                .check(ReaderLimit::SequenceLength, self.sequence_buffer.len())
+                .map_err(std::io::Error::from)?;
+        }
+
+        Ok(bytes_read)
+    }
+
     async fn read_batch(&mut self) -> ExonFASTAResult<Option<RecordBatch>> {
         let mut array_builder = FASTAArrayBuilder::create(
             self.config.file_schema.clone(),
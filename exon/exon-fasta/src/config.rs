@@ -15,7 +15,7 @@
 use std::{str::FromStr, sync::Arc};
 
 use arrow::datatypes::{DataType, Field, SchemaRef};
-use exon_common::TableSchema;
+use exon_common::{ReaderLimits, TableSchema};
 use noodles::core::Region;
 use object_store::ObjectStore;
 
@@ -27,6 +27,7 @@ pub enum SequenceDataType {
     LargeUtf8,
     IntegerEncodeProtein,
     IntegerEncodeDNA,
+    PackedDNA,
 }
 
 impl FromStr for SequenceDataType {
@@ -38,6 +39,7 @@ impl FromStr for SequenceDataType {
             "large_utf8" => Ok(Self::LargeUtf8),
             "integer_encode_protein" => Ok(Self::IntegerEncodeProtein),
             "integer_encode_dna" => Ok(Self::IntegerEncodeDNA),
+            "packed_dna" => Ok(Self::PackedDNA),
             _ => Err(ExonFASTAError::InvalidSequenceDataType(s.to_string())),
         }
     }
@@ -69,6 +71,9 @@ pub struct FASTAConfig {
 
     /// An optional region file to read from.
     pub region_file: Option<String>,
+
+    /// The limits on definition and sequence length.
+    pub reader_limits: ReaderLimits,
 }
 
 impl FASTAConfig {
@@ -83,6 +88,7 @@ impl FASTAConfig {
             sequence_data_type: SequenceDataType::Utf8,
             region: None,
             region_file: None,
+            reader_limits: ReaderLimits::default(),
         }
     }
 
@@ -98,6 +104,12 @@ impl FASTAConfig {
         self
     }
 
+    /// Create a new FASTA configuration with the given reader limits.
+    pub fn with_reader_limits(mut self, reader_limits: ReaderLimits) -> Self {
+        self.reader_limits = reader_limits;
+        self
+    }
+
     /// Create a new FASTA configuration with a given batch size.
     pub fn with_batch_size(mut self, batch_size: usize) -> Self {
         self.batch_size = batch_size;
@@ -210,6 +222,10 @@ impl FASTASchemaBuilder {
                 let field = Field::new("sequence", data_type, true);
                 fields[2] = field;
             }
+            SequenceDataType::PackedDNA => {
+                let field = Field::new("sequence", DataType::Binary, true);
+                fields[2] = field;
+            }
         }
 
         let file_field_projection = self
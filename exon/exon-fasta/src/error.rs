@@ -56,7 +56,15 @@ impl Display for ExonFASTAError {
     }
 }
 
-impl Error for ExonFASTAError {}
+impl Error for ExonFASTAError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExonFASTAError::ArrowError(error) => Some(error),
+            ExonFASTAError::IOError(error) => Some(error),
+            _ => None,
+        }
+    }
+}
 
 impl From<std::io::Error> for ExonFASTAError {
     fn from(error: std::io::Error) -> Self {
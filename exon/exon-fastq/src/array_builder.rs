@@ -14,8 +14,8 @@
 
 use std::sync::Arc;
 
-use arrow::array::{ArrayRef, GenericStringBuilder};
-use exon_common::{ExonArrayBuilder, DEFAULT_BATCH_SIZE};
+use arrow::array::{ArrayRef, BinaryBuilder, GenericStringBuilder};
+use exon_common::{packed_sequence::pack_sequence, ExonArrayBuilder, DEFAULT_BATCH_SIZE};
 use noodles::fastq::Record;
 
 use crate::error::{ExonFastqError, ExonFastqResult};
@@ -28,6 +28,10 @@ pub struct FASTQArrayBuilder {
     descriptions: GenericStringBuilder<i32>,
     /// A builder for the sequences of the records.
     sequences: GenericStringBuilder<i32>,
+    /// A builder for the packed sequences of the records, if the sequences are packed.
+    packed_sequences: Option<BinaryBuilder>,
+    /// A buffer for packing a sequence.
+    packed_sequence: Vec<u8>,
     /// A builder for the quality scores of the records.
     quality_scores: GenericStringBuilder<i32>,
     /// The projection of the fields.
@@ -37,7 +41,7 @@ pub struct FASTQArrayBuilder {
 }
 
 impl FASTQArrayBuilder {
-    pub fn with_capacity(capacity: usize, projection: Vec<usize>) -> Self {
+    pub fn with_capacity(capacity: usize, projection: Vec<usize>, pack_sequences: bool) -> Self {
         Self {
             names: GenericStringBuilder::<i32>::with_capacity(
                 capacity,
@@ -51,6 +55,9 @@ impl FASTQArrayBuilder {
                 capacity,
                 capacity * DEFAULT_BATCH_SIZE,
             ),
+            packed_sequences: pack_sequences
+                .then(|| BinaryBuilder::with_capacity(capacity, capacity)),
+            packed_sequence: Vec::new(),
             quality_scores: GenericStringBuilder::<i32>::with_capacity(
                 capacity,
                 capacity * DEFAULT_BATCH_SIZE,
@@ -84,8 +91,15 @@ impl FASTQArrayBuilder {
                 }
                 2 => {
                     let record_sequence = record.sequence();
-                    let sequence = std::str::from_utf8(record_sequence)?;
-                    self.sequences.append_value(sequence);
+
+                    if let Some(packed_sequences) = &mut self.packed_sequences {
+                        pack_sequence(record_sequence, &mut self.packed_sequence)
+                            .map_err(|e| ExonFastqError::Parse(e.to_string()))?;
+                        packed_sequences.append_value(&self.packed_sequence);
+                    } else {
+                        let sequence = std::str::from_utf8(record_sequence)?;
+                        self.sequences.append_value(sequence);
+                    }
                 }
                 3 => {
                     let record_quality = record.quality_scores();
@@ -109,7 +123,10 @@ impl FASTQArrayBuilder {
             match col_idx {
                 0 => arrays.push(Arc::new(self.names.finish())),
                 1 => arrays.push(Arc::new(self.descriptions.finish())),
-                2 => arrays.push(Arc::new(self.sequences.finish())),
+                2 => match &mut self.packed_sequences {
+                    Some(packed_sequences) => arrays.push(Arc::new(packed_sequences.finish())),
+                    None => arrays.push(Arc::new(self.sequences.finish())),
+                },
                 3 => arrays.push(Arc::new(self.quality_scores.finish())),
                 c => {
                     return Err(ExonFastqError::InvalidColumnIndex(*c));
@@ -14,7 +14,7 @@
 
 use std::sync::Arc;
 
-use exon_common::ExonArrayBuilder;
+use exon_common::{BoundedReader, ExonArrayBuilder, ReaderLimit, SampleMethod};
 
 use arrow::record_batch::RecordBatch;
 use noodles::fastq;
@@ -27,9 +27,11 @@ use super::{array_builder::FASTQArrayBuilder, FASTQConfig};
 
 pub struct BatchReader<R> {
     /// The underlying FASTQ reader.
-    reader: noodles::fastq::AsyncReader<R>,
+    reader: noodles::fastq::AsyncReader<BoundedReader<R>>,
     /// The FASTQ configuration.
     config: Arc<FASTQConfig>,
+    /// The records of a reservoir sample left to return, once the file has been read.
+    sampled: Option<std::vec::IntoIter<fastq::Record>>,
 }
 
 impl<R> BatchReader<R>
@@ -38,8 +40,12 @@ where
 {
     pub fn new(inner: R, config: Arc<FASTQConfig>) -> Self {
         Self {
-            reader: noodles::fastq::AsyncReader::new(inner),
+            reader: noodles::fastq::AsyncReader::new(BoundedReader::new(
+                inner,
+                &config.reader_limits,
+            )),
             config,
+            sampled: None,
         }
     }
 
@@ -56,18 +62,86 @@ where
     async fn read_record(&mut self, record: &mut fastq::Record) -> ExonFastqResult<Option<()>> {
         match self.reader.read_record(record).await? {
             0 => Ok(None),
-            _ => Ok(Some(())),
+            _ => {
+                self.config
+                    .reader_limits
+                    .check(ReaderLimit::SequenceLength, record.sequence().len())
+                    .map_err(std::io::Error::from)?;
+
+                Ok(Some(()))
+            }
         }
     }
 
+    /// Whether a record matches the sequence filter and is in a fractional sample.
+    fn is_selected(&self, record: &fastq::Record) -> bool {
+        let matches = match &self.config.sequence_filter {
+            Some(sequence_filter) => sequence_filter.is_match(record.sequence()),
+            None => true,
+        };
+
+        matches
+            && self
+                .config
+                .sampling
+                .is_none_or(|sampling| sampling.keep(record.name()))
+    }
+
+    /// Read the rest of the file into a reservoir sample of its records.
+    async fn read_reservoir(&mut self, size: usize) -> ExonFastqResult<Vec<fastq::Record>> {
+        let Some(sampling) = self.config.sampling else {
+            return Ok(Vec::new());
+        };
+
+        // Files sampled with the same seed keep the records at the same indexes, so the mates
+        // of paired reads are sampled together.
+        let mut reservoir = sampling.reservoir(size, ());
+        let mut record = fastq::Record::default();
+
+        while self.read_record(&mut record).await?.is_some() {
+            if self.is_selected(&record) {
+                reservoir.offer(|| record.clone());
+            }
+        }
+
+        Ok(reservoir.into_items())
+    }
+
     async fn read_batch(&mut self, batch_size: usize) -> ExonFastqResult<Option<RecordBatch>> {
-        let mut array = FASTQArrayBuilder::with_capacity(batch_size, self.config.projection());
-        let mut record = fastq::Record::default(); // Allocate once
+        let mut array = FASTQArrayBuilder::with_capacity(
+            batch_size,
+            self.config.projection(),
+            self.config.pack_sequences,
+        );
+
+        let reservoir_size = match self.config.sampling.map(|sampling| sampling.method()) {
+            Some(SampleMethod::Reservoir(size)) => Some(size),
+            _ => None,
+        };
+
+        if let Some(size) = reservoir_size {
+            if self.sampled.is_none() {
+                let records = self.read_reservoir(size).await?;
+                self.sampled = Some(records.into_iter());
+            }
+
+            if let Some(sampled) = self.sampled.as_mut() {
+                for record in sampled.take(batch_size) {
+                    array.append(&record)?;
+                }
+            }
+        } else {
+            let mut record = fastq::Record::default(); // Allocate once
 
-        for _ in 0..batch_size {
-            match self.read_record(&mut record).await? {
-                Some(_) => array.append(&record)?,
-                None => break,
+            while array.len() < batch_size {
+                match self.read_record(&mut record).await? {
+                    Some(_) => {
+                        if self.is_selected(&record) {
+                            array.append(&record)?;
+                        }
+                    }
+                    None => break,
+                }
             }
         }
 
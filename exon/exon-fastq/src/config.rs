@@ -15,7 +15,7 @@
 use std::sync::Arc;
 
 use arrow::datatypes::{DataType, Field, SchemaRef};
-use exon_common::TableSchemaBuilder;
+use exon_common::{ReaderLimits, RecordSampling, SequenceFilter, TableSchemaBuilder};
 use object_store::ObjectStore;
 
 /// Configuration for a FASTQ datasource.
@@ -31,6 +31,18 @@ pub struct FASTQConfig {
 
     /// Any projections to apply to the data.
     pub projection: Option<Vec<usize>>,
+
+    /// A filter that records must match to be read.
+    pub sequence_filter: Option<SequenceFilter>,
+
+    /// Whether to pack the sequences with 2 bits per base.
+    pub pack_sequences: bool,
+
+    /// The limits on line and sequence length.
+    pub reader_limits: ReaderLimits,
+
+    /// A sample of the records to read, instead of all of them.
+    pub sampling: Option<RecordSampling>,
 }
 
 impl FASTQConfig {
@@ -41,6 +53,10 @@ impl FASTQConfig {
             object_store,
             file_schema: new_fastq_schema_builder().build().file_schema().unwrap(),
             projection: None,
+            sequence_filter: None,
+            pack_sequences: false,
+            reader_limits: ReaderLimits::default(),
+            sampling: None,
         }
     }
 
@@ -50,6 +66,37 @@ impl FASTQConfig {
         self
     }
 
+    /// Set the reader limits.
+    pub fn with_reader_limits(mut self, reader_limits: ReaderLimits) -> Self {
+        self.reader_limits = reader_limits;
+        self
+    }
+
+    /// Set the sequence filter.
+    pub fn with_sequence_filter(mut self, sequence_filter: Option<SequenceFilter>) -> Self {
+        self.sequence_filter = sequence_filter;
+        self
+    }
+
+    /// Set the sample of the records to read.
+    pub fn with_sampling(mut self, sampling: Option<RecordSampling>) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Set whether to pack the sequences with 2 bits per base, which also sets the schema.
+    pub fn with_pack_sequences(mut self, pack_sequences: bool) -> Self {
+        let builder = if pack_sequences {
+            new_packed_fastq_schema_builder()
+        } else {
+            new_fastq_schema_builder()
+        };
+
+        self.file_schema = builder.build().file_schema().unwrap();
+        self.pack_sequences = pack_sequences;
+        self
+    }
+
     /// Get the projection, returning the identity projection if none is set.
     pub fn projection(&self) -> Vec<usize> {
         self.projection
@@ -76,13 +123,22 @@ impl FASTQConfig {
     }
 }
 
-pub fn new_fastq_schema_builder() -> TableSchemaBuilder {
+fn fastq_schema_builder(sequence_data_type: DataType) -> TableSchemaBuilder {
     let fields = vec![
         Field::new("name", DataType::Utf8, false),
         Field::new("description", DataType::Utf8, true),
-        Field::new("sequence", DataType::Utf8, false),
+        Field::new("sequence", sequence_data_type, false),
         Field::new("quality_scores", DataType::Utf8, false),
     ];
 
     TableSchemaBuilder::new_with_field_fields(fields)
 }
+
+pub fn new_fastq_schema_builder() -> TableSchemaBuilder {
+    fastq_schema_builder(DataType::Utf8)
+}
+
+/// Create a schema builder where the sequences are packed with 2 bits per base.
+pub fn new_packed_fastq_schema_builder() -> TableSchemaBuilder {
+    fastq_schema_builder(DataType::Binary)
+}
@@ -22,7 +22,15 @@ pub enum ExonFastqError {
     InvalidColumnIndex(usize),
 }
 
-impl Error for ExonFastqError {}
+impl Error for ExonFastqError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExonFastqError::Arrow(error) => Some(error),
+            ExonFastqError::IO(error) => Some(error),
+            _ => None,
+        }
+    }
+}
 
 pub type ExonFastqResult<T> = Result<T, ExonFastqError>;
 
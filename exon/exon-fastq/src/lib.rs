@@ -20,4 +20,5 @@ mod error;
 
 pub use batch_reader::BatchReader;
 pub use config::new_fastq_schema_builder;
+pub use config::new_packed_fastq_schema_builder;
 pub use config::FASTQConfig;
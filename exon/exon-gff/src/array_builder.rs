@@ -22,20 +22,17 @@ use arrow::{
     datatypes::SchemaRef,
     error::ArrowError,
 };
-use exon_common::ExonArrayBuilder;
-use noodles::gff::{
-    record::{Phase, Strand},
-    Record,
-};
+use exon_common::{ExonArrayBuilder, FeatureTypeBuilder, Strand, StrandBuilder};
+use noodles::gff::{record::Phase, Record};
 
 pub struct GFFArrayBuilder {
     seqnames: GenericStringBuilder<i32>,
     sources: GenericStringBuilder<i32>,
-    feature_types: GenericStringBuilder<i32>,
+    feature_types: FeatureTypeBuilder,
     starts: Int64Builder,
     ends: Int64Builder,
     scores: Float32Builder,
-    strands: GenericStringBuilder<i32>,
+    strands: StrandBuilder,
     phases: GenericStringBuilder<i32>,
     attributes:
         MapBuilder<GenericStringBuilder<i32>, GenericListBuilder<i32, GenericStringBuilder<i32>>>,
@@ -51,14 +48,17 @@ impl GFFArrayBuilder {
             None => (0..schema.fields().len()).collect(),
         };
 
+        let feature_types = FeatureTypeBuilder::new(schema.field(2).data_type());
+        let strands = StrandBuilder::new(schema.field(6).data_type());
+
         Self {
             seqnames: GenericStringBuilder::<i32>::new(),
             sources: GenericStringBuilder::<i32>::new(),
-            feature_types: GenericStringBuilder::<i32>::new(),
+            feature_types,
             starts: Int64Builder::new(),
             ends: Int64Builder::new(),
             scores: Float32Builder::new(),
-            strands: GenericStringBuilder::<i32>::new(),
+            strands,
             phases: GenericStringBuilder::<i32>::new(),
             attributes: MapBuilder::new(
                 None,
@@ -87,7 +87,7 @@ impl GFFArrayBuilder {
             match col_idx {
                 0 => self.seqnames.append_value(record.reference_sequence_name()),
                 1 => self.sources.append_value(record.source()),
-                2 => self.feature_types.append_value(record.ty()),
+                2 => self.feature_types.append_value(record.ty())?,
                 3 => {
                     let start_pos = record.start()?;
                     self.starts.append_value(start_pos.get() as i64)
@@ -108,22 +108,14 @@ impl GFFArrayBuilder {
                     }
                 }
                 6 => {
-                    let strand = record.strand()?;
-
-                    match strand {
-                        Strand::Forward => {
-                            self.strands.append_value("+");
-                        }
-                        Strand::Reverse => {
-                            self.strands.append_value("-");
-                        }
-                        Strand::Unknown => {
-                            self.strands.append_null();
-                        }
-                        Strand::None => {
-                            self.strands.append_null();
-                        }
-                    }
+                    let strand = match record.strand()? {
+                        noodles::gff::record::Strand::Forward => Some(Strand::Forward),
+                        noodles::gff::record::Strand::Reverse => Some(Strand::Reverse),
+                        noodles::gff::record::Strand::Unknown => Some(Strand::Unknown),
+                        noodles::gff::record::Strand::None => None,
+                    };
+
+                    self.strands.append_option(strand)?;
                 }
                 7 => {
                     let phase = record.phase();
@@ -182,11 +174,11 @@ impl GFFArrayBuilder {
             match col_idx {
                 0 => arrays.push(Arc::new(self.seqnames.finish())),
                 1 => arrays.push(Arc::new(self.sources.finish())),
-                2 => arrays.push(Arc::new(self.feature_types.finish())),
+                2 => arrays.push(self.feature_types.finish()),
                 3 => arrays.push(Arc::new(self.starts.finish())),
                 4 => arrays.push(Arc::new(self.ends.finish())),
                 5 => arrays.push(Arc::new(self.scores.finish())),
-                6 => arrays.push(Arc::new(self.strands.finish())),
+                6 => arrays.push(self.strands.finish()),
                 7 => arrays.push(Arc::new(self.phases.finish())),
                 8 => arrays.push(Arc::new(self.attributes.finish())),
                 _ => panic!("Invalid col_idx for GFF ({})", col_idx),
@@ -16,7 +16,7 @@ use std::sync::Arc;
 
 use arrow::record_batch::RecordBatch;
 
-use exon_common::ExonArrayBuilder;
+use exon_common::{BoundedReader, ExonArrayBuilder, ReaderLimit};
 use futures::Stream;
 use tokio::io::AsyncBufRead;
 
@@ -27,7 +27,7 @@ use super::{array_builder::GFFArrayBuilder, GFFConfig};
 /// Reads a GFF file into arrow record batches.
 pub struct BatchReader<R> {
     /// The reader to read from.
-    reader: noodles::gff::AsyncReader<R>,
+    reader: noodles::gff::AsyncReader<BoundedReader<R>>,
 
     /// The configuration for this reader.
     config: Arc<GFFConfig>,
@@ -42,7 +42,10 @@ where
 {
     pub fn new(reader: R, config: Arc<GFFConfig>) -> Self {
         Self {
-            reader: noodles::gff::AsyncReader::new(reader),
+            reader: noodles::gff::AsyncReader::new(BoundedReader::new(
+                reader,
+                &config.reader_limits,
+            )),
             config,
             region: None,
         }
@@ -111,6 +114,14 @@ where
                             continue;
                         }
 
+                        self.config
+                            .reader_limits
+                            .check(
+                                ReaderLimit::AttributeCount,
+                                record.attributes().iter().count(),
+                            )
+                            .map_err(std::io::Error::from)?;
+
                         gff_array_builder.append(&record)?;
                     }
                     Some(Err(e)) => return Err(e.into()),
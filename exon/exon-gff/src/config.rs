@@ -15,7 +15,7 @@
 use std::sync::Arc;
 
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
-use exon_common::TableSchemaBuilder;
+use exon_common::{ReaderLimits, TableSchemaBuilder};
 use object_store::ObjectStore;
 
 /// Configuration for a GFF data source.
@@ -32,6 +32,9 @@ pub struct GFFConfig {
 
     /// Any projections to apply to the resulting batches.
     pub projection: Option<Vec<usize>>,
+
+    /// The limits on line length and attribute count.
+    pub reader_limits: ReaderLimits,
 }
 
 impl GFFConfig {
@@ -42,6 +45,7 @@ impl GFFConfig {
             object_store,
             batch_size: 8096,
             projection: None,
+            reader_limits: ReaderLimits::default(),
         }
     }
 
@@ -51,6 +55,12 @@ impl GFFConfig {
         self
     }
 
+    /// Set the reader limits.
+    pub fn with_reader_limits(mut self, reader_limits: ReaderLimits) -> Self {
+        self.reader_limits = reader_limits;
+        self
+    }
+
     /// Set the projection.
     pub fn with_projection(mut self, projection: Vec<usize>) -> Self {
         let file_projection = projection
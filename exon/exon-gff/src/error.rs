@@ -35,7 +35,15 @@ impl Display for ExonGFFError {
     }
 }
 
-impl Error for ExonGFFError {}
+impl Error for ExonGFFError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExonGFFError::ExternalError(e) => Some(e.as_ref()),
+            ExonGFFError::IoError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
 impl From<ArrowError> for ExonGFFError {
     fn from(e: ArrowError) -> Self {
@@ -16,18 +16,20 @@ use std::sync::Arc;
 
 use arrow::{
     array::{ArrayRef, Float32Builder, GenericStringBuilder, Int64Builder, MapBuilder},
+    datatypes::{DataType, Schema},
     error::ArrowError,
 };
-use noodles::gtf::Record;
+use exon_common::{FeatureTypeBuilder, Strand, StrandBuilder};
+use noodles::gtf::{record::Strand as GTFStrand, Record};
 
 pub struct GTFArrayBuilder {
     seqnames: GenericStringBuilder<i32>,
     sources: GenericStringBuilder<i32>,
-    feature_types: GenericStringBuilder<i32>,
+    feature_types: FeatureTypeBuilder,
     starts: Int64Builder,
     ends: Int64Builder,
     scores: Float32Builder,
-    strands: GenericStringBuilder<i32>,
+    strands: StrandBuilder,
     frame: GenericStringBuilder<i32>,
     attributes: MapBuilder<GenericStringBuilder<i32>, GenericStringBuilder<i32>>,
 
@@ -42,14 +44,29 @@ impl Default for GTFArrayBuilder {
 
 impl GTFArrayBuilder {
     pub fn new() -> Self {
+        Self::with_types(&DataType::Utf8, &DataType::Utf8)
+    }
+
+    /// Creates a builder for the feature type and strand data types of the schema.
+    pub fn create(schema: &Schema) -> Self {
+        let data_type = |name| {
+            schema
+                .field_with_name(name)
+                .map_or(DataType::Utf8, |field| field.data_type().clone())
+        };
+
+        Self::with_types(&data_type("type"), &data_type("strand"))
+    }
+
+    fn with_types(feature_type: &DataType, strand: &DataType) -> Self {
         Self {
             seqnames: GenericStringBuilder::<i32>::new(),
             sources: GenericStringBuilder::<i32>::new(),
-            feature_types: GenericStringBuilder::<i32>::new(),
+            feature_types: FeatureTypeBuilder::new(feature_type),
             starts: Int64Builder::new(),
             ends: Int64Builder::new(),
             scores: Float32Builder::new(),
-            strands: GenericStringBuilder::<i32>::new(),
+            strands: StrandBuilder::new(strand),
             frame: GenericStringBuilder::<i32>::new(),
             attributes: MapBuilder::new(
                 None,
@@ -71,11 +88,15 @@ impl GTFArrayBuilder {
     pub fn append(&mut self, record: &Record) -> Result<(), ArrowError> {
         self.seqnames.append_value(record.reference_sequence_name());
         self.sources.append_value(record.source());
-        self.feature_types.append_value(record.ty());
+        self.feature_types.append_value(record.ty())?;
         self.starts.append_value(record.start().get() as i64);
         self.ends.append_value(record.end().get() as i64);
         self.scores.append_option(record.score());
-        self.strands.append_option(record.strand());
+        self.strands
+            .append_option(record.strand().map(|strand| match strand {
+                GTFStrand::Forward => Strand::Forward,
+                GTFStrand::Reverse => Strand::Reverse,
+            }))?;
         self.frame
             .append_option(record.frame().map(|frame| frame.to_string()));
 
@@ -105,11 +126,11 @@ impl GTFArrayBuilder {
         vec![
             Arc::new(seqnames),
             Arc::new(sources),
-            Arc::new(feature_types),
+            feature_types,
             Arc::new(starts),
             Arc::new(ends),
             Arc::new(scores),
-            Arc::new(strands),
+            strands,
             Arc::new(frames),
             Arc::new(attributes),
         ]
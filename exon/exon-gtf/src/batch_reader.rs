@@ -16,6 +16,7 @@ use std::{str::FromStr, sync::Arc};
 
 use arrow::{error::ArrowError, error::Result as ArrowResult, record_batch::RecordBatch};
 
+use exon_common::{BoundedReader, ReaderLimit};
 use futures::Stream;
 use tokio::io::{AsyncBufRead, AsyncBufReadExt};
 
@@ -24,7 +25,7 @@ use super::{array_builder::GTFArrayBuilder, GTFConfig};
 /// Reads a GTF file into arrow record batches.
 pub struct BatchReader<R> {
     /// The reader to read from.
-    reader: R,
+    reader: BoundedReader<R>,
 
     /// The configuration for this reader.
     config: Arc<GTFConfig>,
@@ -35,7 +36,10 @@ where
     R: AsyncBufRead + Unpin + Send,
 {
     pub fn new(reader: R, config: Arc<GTFConfig>) -> Self {
-        Self { reader, config }
+        Self {
+            reader: BoundedReader::new(reader, &config.reader_limits),
+            config,
+        }
     }
 
     pub fn into_stream(self) -> impl Stream<Item = Result<RecordBatch, ArrowError>> {
@@ -79,7 +83,7 @@ where
     }
 
     async fn read_batch(&mut self) -> ArrowResult<Option<RecordBatch>> {
-        let mut gtf_array_builder = GTFArrayBuilder::new();
+        let mut gtf_array_builder = GTFArrayBuilder::create(&self.config.file_schema);
 
         for _ in 0..self.config.batch_size {
             match self.read_line().await? {
@@ -87,6 +91,14 @@ where
                 Some(line) => match line {
                     noodles::gtf::Line::Comment(_) => {}
                     noodles::gtf::Line::Record(record) => {
+                        self.config
+                            .reader_limits
+                            .check(
+                                ReaderLimit::AttributeCount,
+                                record.attributes().iter().count(),
+                            )
+                            .map_err(std::io::Error::from)?;
+
                         gtf_array_builder.append(&record)?;
                     }
                 },
@@ -15,7 +15,7 @@
 use std::sync::Arc;
 
 use arrow::datatypes::{DataType, Field, SchemaRef};
-use exon_common::{TableSchemaBuilder, DEFAULT_BATCH_SIZE};
+use exon_common::{ReaderLimits, TableSchemaBuilder, DEFAULT_BATCH_SIZE};
 use object_store::ObjectStore;
 
 pub fn new_gtf_schema_builder() -> TableSchemaBuilder {
@@ -62,6 +62,9 @@ pub struct GTFConfig {
 
     /// Any projections to apply to the resulting batches.
     pub projection: Option<Vec<usize>>,
+
+    /// The limits on line length and attribute count.
+    pub reader_limits: ReaderLimits,
 }
 
 impl GTFConfig {
@@ -72,6 +75,7 @@ impl GTFConfig {
             object_store,
             batch_size: DEFAULT_BATCH_SIZE,
             projection: None,
+            reader_limits: ReaderLimits::default(),
         }
     }
 
@@ -81,6 +85,12 @@ impl GTFConfig {
         self
     }
 
+    /// Set the reader limits.
+    pub fn with_reader_limits(mut self, reader_limits: ReaderLimits) -> Self {
+        self.reader_limits = reader_limits;
+        self
+    }
+
     /// Set the batch size.
     pub fn with_batch_size(mut self, batch_size: usize) -> Self {
         self.batch_size = batch_size;
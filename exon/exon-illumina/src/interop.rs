@@ -0,0 +1,472 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! InterOp metric files, a version byte and record size byte, a version specific header, and
+//! fixed size little-endian records.
+
+use std::{borrow::Cow, sync::Arc};
+
+use arrow::{
+    array::{ArrayRef, Float64Builder, Int32Builder, Int64Builder, StringBuilder},
+    datatypes::{DataType, Field},
+    error::ArrowError,
+};
+
+fn invalid_interop(file_name: &str, message: impl std::fmt::Display) -> ArrowError {
+    ArrowError::ParseError(format!("Invalid InterOp {file_name}: {message}"))
+}
+
+/// Reads little-endian values from the bytes of an InterOp file.
+struct Cursor<'a> {
+    file_name: &'static str,
+    data: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn new(file_name: &'static str, data: &'a [u8]) -> Self {
+        Self { file_name, data }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ArrowError> {
+        if self.data.len() < n {
+            return Err(invalid_interop(self.file_name, "unexpected end of file"));
+        }
+
+        let (taken, rest) = self.data.split_at(n);
+        self.data = rest;
+
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, ArrowError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ArrowError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, ArrowError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn f32(&mut self) -> Result<f32, ArrowError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    /// Read the version and record size, checking the record size for the version.
+    fn header(&mut self, record_sizes: &[(u8, usize)]) -> Result<(u8, usize), ArrowError> {
+        let version = self.u8()?;
+        let record_size = usize::from(self.u8()?);
+
+        match record_sizes.iter().find(|(v, _)| *v == version) {
+            Some((_, 0)) => Ok((version, record_size)),
+            Some((_, expected)) if *expected == record_size => Ok((version, record_size)),
+            Some((_, expected)) => Err(invalid_interop(
+                self.file_name,
+                format!("version {version} has records of {expected} bytes, not {record_size}"),
+            )),
+            None => Err(invalid_interop(
+                self.file_name,
+                format!("unsupported version {version}"),
+            )),
+        }
+    }
+
+    /// Split the rest of the file into records.
+    fn records(self, record_size: usize) -> Result<impl Iterator<Item = Cursor<'a>>, ArrowError> {
+        if record_size == 0 {
+            return Err(invalid_interop(self.file_name, "empty records"));
+        }
+
+        let records = self.data.chunks_exact(record_size);
+        if !records.remainder().is_empty() {
+            return Err(invalid_interop(self.file_name, "truncated record"));
+        }
+
+        let file_name = self.file_name;
+
+        Ok(records.map(move |record| Cursor::new(file_name, record)))
+    }
+}
+
+fn location_fields() -> Vec<Field> {
+    vec![
+        Field::new("lane", DataType::Int32, false),
+        Field::new("tile", DataType::Int32, false),
+    ]
+}
+
+fn to_i32(value: u32, file_name: &str) -> Result<i32, ArrowError> {
+    i32::try_from(value).map_err(|_| invalid_interop(file_name, format!("{value} is too large")))
+}
+
+pub(crate) fn tile_metric_fields() -> Vec<Field> {
+    let mut fields = location_fields();
+    fields.extend([
+        Field::new("metric", DataType::Utf8, false),
+        Field::new("read", DataType::Int32, true),
+        Field::new("value", DataType::Float64, false),
+    ]);
+
+    fields
+}
+
+/// The name and read of a version 2 tile metric code.
+fn tile_metric(code: u16) -> (Cow<'static, str>, Option<i32>) {
+    match code {
+        100 => ("cluster_density".into(), None),
+        101 => ("cluster_density_pf".into(), None),
+        102 => ("cluster_count".into(), None),
+        103 => ("cluster_count_pf".into(), None),
+        200..=299 if code & 1 == 0 => ("phasing".into(), Some(i32::from(code - 200) / 2 + 1)),
+        200..=299 => ("prephasing".into(), Some(i32::from(code - 201) / 2 + 1)),
+        300..=399 => ("percent_aligned".into(), Some(i32::from(code - 300) + 1)),
+        400 => ("control_lane".into(), None),
+        _ => (format!("code_{code}").into(), None),
+    }
+}
+
+/// Reads TileMetricsOut.bin, versions 2 and 3, into a row per metric of a tile.
+///
+/// Version 2 has a code per metric, see [`tile_metric`]. Version 3 has cluster counts and the
+/// percent aligned per read, but no densities, phasing, or prephasing.
+pub(crate) fn read_tile_metrics(data: &[u8]) -> Result<Vec<ArrayRef>, ArrowError> {
+    const FILE_NAME: &str = "TileMetricsOut.bin";
+
+    let mut cursor = Cursor::new(FILE_NAME, data);
+    let (version, record_size) = cursor.header(&[(2, 10), (3, 15)])?;
+
+    if version == 3 {
+        // The tile area, which isn't used.
+        cursor.f32()?;
+    }
+
+    let mut lanes = Int32Builder::new();
+    let mut tiles = Int32Builder::new();
+    let mut metrics = StringBuilder::new();
+    let mut reads = Int32Builder::new();
+    let mut values = Float64Builder::new();
+
+    let mut append = |lane: u16, tile: u32, metric: &str, read: Option<i32>, value: f32| {
+        lanes.append_value(i32::from(lane));
+        tiles.append_value(to_i32(tile, FILE_NAME)?);
+        metrics.append_value(metric);
+        reads.append_option(read);
+        values.append_value(f64::from(value));
+
+        Ok::<_, ArrowError>(())
+    };
+
+    for mut record in cursor.records(record_size)? {
+        if version == 2 {
+            let lane = record.u16()?;
+            let tile = record.u16()?;
+            let (metric, read) = tile_metric(record.u16()?);
+
+            append(lane, u32::from(tile), &metric, read, record.f32()?)?;
+            continue;
+        }
+
+        let lane = record.u16()?;
+        let tile = record.u32()?;
+
+        match record.u8()? {
+            b't' => {
+                append(lane, tile, "cluster_count", None, record.f32()?)?;
+                append(lane, tile, "cluster_count_pf", None, record.f32()?)?;
+            }
+            b'r' => {
+                let read = to_i32(record.u32()?, FILE_NAME)?;
+                append(lane, tile, "percent_aligned", Some(read), record.f32()?)?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(vec![
+        Arc::new(lanes.finish()),
+        Arc::new(tiles.finish()),
+        Arc::new(metrics.finish()),
+        Arc::new(reads.finish()),
+        Arc::new(values.finish()),
+    ])
+}
+
+pub(crate) fn quality_metric_fields() -> Vec<Field> {
+    let mut fields = location_fields();
+    fields.extend([
+        Field::new("cycle", DataType::Int32, false),
+        Field::new("quality", DataType::Int32, false),
+        Field::new("count", DataType::Int64, false),
+    ]);
+
+    fields
+}
+
+/// Reads QMetricsOut.bin, versions 4 to 7, into a row per quality score with clusters.
+///
+/// Each record is a histogram of the quality scores of a tile and cycle. Versions 6 and 7 can
+/// bin the scores, in which case a bin is reported as the score it was remapped to.
+pub(crate) fn read_quality_metrics(data: &[u8]) -> Result<Vec<ArrayRef>, ArrowError> {
+    const FILE_NAME: &str = "QMetricsOut.bin";
+
+    let mut cursor = Cursor::new(FILE_NAME, data);
+    let (version, record_size) = cursor.header(&[(4, 206), (5, 206), (6, 0), (7, 0)])?;
+
+    let mut qualities = (1..=50).collect::<Vec<i32>>();
+
+    if version >= 5 && cursor.u8()? == 1 {
+        let bins = usize::from(cursor.u8()?);
+
+        // The lower and upper bounds of the bins, then the scores they're remapped to.
+        cursor.take(bins * 2)?;
+        let remapped = cursor.take(bins)?;
+
+        // Version 5 histograms still have a count per score, only later versions per bin.
+        if version >= 6 {
+            qualities = remapped.iter().map(|q| i32::from(*q)).collect();
+        }
+    }
+
+    let location_size = if version == 7 { 8 } else { 6 };
+    if record_size != location_size + 4 * qualities.len() {
+        return Err(invalid_interop(
+            FILE_NAME,
+            format!("records of {record_size} bytes don't match the quality bins"),
+        ));
+    }
+
+    let mut lanes = Int32Builder::new();
+    let mut tiles = Int32Builder::new();
+    let mut cycles = Int32Builder::new();
+    let mut quality_scores = Int32Builder::new();
+    let mut counts = Int64Builder::new();
+
+    for mut record in cursor.records(record_size)? {
+        let lane = i32::from(record.u16()?);
+        let tile = if version == 7 {
+            to_i32(record.u32()?, FILE_NAME)?
+        } else {
+            i32::from(record.u16()?)
+        };
+        let cycle = i32::from(record.u16()?);
+
+        for quality in &qualities {
+            let count = record.u32()?;
+
+            if count > 0 {
+                lanes.append_value(lane);
+                tiles.append_value(tile);
+                cycles.append_value(cycle);
+                quality_scores.append_value(*quality);
+                counts.append_value(i64::from(count));
+            }
+        }
+    }
+
+    Ok(vec![
+        Arc::new(lanes.finish()),
+        Arc::new(tiles.finish()),
+        Arc::new(cycles.finish()),
+        Arc::new(quality_scores.finish()),
+        Arc::new(counts.finish()),
+    ])
+}
+
+pub(crate) fn error_metric_fields() -> Vec<Field> {
+    let mut fields = location_fields();
+    fields.extend([
+        Field::new("cycle", DataType::Int32, false),
+        Field::new("error_rate", DataType::Float64, false),
+    ]);
+
+    fields
+}
+
+/// Reads ErrorMetricsOut.bin, versions 3 and 4, into the error rate of each tile and cycle.
+pub(crate) fn read_error_metrics(data: &[u8]) -> Result<Vec<ArrayRef>, ArrowError> {
+    const FILE_NAME: &str = "ErrorMetricsOut.bin";
+
+    let mut cursor = Cursor::new(FILE_NAME, data);
+    let (version, record_size) = cursor.header(&[(3, 30), (4, 12)])?;
+
+    let mut lanes = Int32Builder::new();
+    let mut tiles = Int32Builder::new();
+    let mut cycles = Int32Builder::new();
+    let mut error_rates = Float64Builder::new();
+
+    for mut record in cursor.records(record_size)? {
+        lanes.append_value(i32::from(record.u16()?));
+
+        if version == 4 {
+            tiles.append_value(to_i32(record.u32()?, FILE_NAME)?);
+        } else {
+            tiles.append_value(i32::from(record.u16()?));
+        }
+
+        cycles.append_value(i32::from(record.u16()?));
+        error_rates.append_value(f64::from(record.f32()?));
+    }
+
+    Ok(vec![
+        Arc::new(lanes.finish()),
+        Arc::new(tiles.finish()),
+        Arc::new(cycles.finish()),
+        Arc::new(error_rates.finish()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{Array, AsArray},
+        datatypes::{Float64Type, Int32Type, Int64Type},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_read_tile_metrics_v2() -> Result<(), ArrowError> {
+        let mut data = vec![2, 10];
+        for (code, value) in [(100u16, 1500.0f32), (202, 0.1), (203, 0.2)] {
+            data.extend(1u16.to_le_bytes());
+            data.extend(1101u16.to_le_bytes());
+            data.extend(code.to_le_bytes());
+            data.extend(value.to_le_bytes());
+        }
+
+        let columns = read_tile_metrics(&data)?;
+
+        let metrics = columns[2].as_string::<i32>();
+        assert_eq!(metrics.value(0), "cluster_density");
+        assert_eq!(metrics.value(1), "phasing");
+        assert_eq!(metrics.value(2), "prephasing");
+
+        let reads = columns[3].as_primitive::<Int32Type>();
+        assert!(reads.is_null(0));
+        assert_eq!(reads.value(1), 2);
+        assert_eq!(reads.value(2), 2);
+
+        assert_eq!(columns[4].as_primitive::<Float64Type>().value(0), 1500.0);
+
+        // A truncated record is an error.
+        assert!(read_tile_metrics(&data[..data.len() - 1]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_tile_metrics_v3() -> Result<(), ArrowError> {
+        let mut data = vec![3, 15];
+        data.extend(0.5f32.to_le_bytes());
+
+        data.extend(1u16.to_le_bytes());
+        data.extend(11101u32.to_le_bytes());
+        data.push(b't');
+        data.extend(1000.0f32.to_le_bytes());
+        data.extend(900.0f32.to_le_bytes());
+
+        data.extend(1u16.to_le_bytes());
+        data.extend(11101u32.to_le_bytes());
+        data.push(b'r');
+        data.extend(1u32.to_le_bytes());
+        data.extend(2.5f32.to_le_bytes());
+
+        let columns = read_tile_metrics(&data)?;
+
+        assert_eq!(columns[1].as_primitive::<Int32Type>().value(0), 11101);
+
+        let metrics = columns[2].as_string::<i32>();
+        assert_eq!(
+            metrics.iter().flatten().collect::<Vec<_>>(),
+            ["cluster_count", "cluster_count_pf", "percent_aligned"]
+        );
+
+        let values = columns[4].as_primitive::<Float64Type>();
+        assert_eq!(values.values().to_vec(), [1000.0, 900.0, 2.5]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_quality_metrics() -> Result<(), ArrowError> {
+        // Version 6 binned into 3 bins remapped to 12, 23, and 37.
+        let mut data = vec![6, 18, 1, 3];
+        data.extend([1, 20, 30, 19, 29, 40, 12, 23, 37]);
+
+        data.extend(1u16.to_le_bytes());
+        data.extend(1101u16.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        for count in [5u32, 0, 95] {
+            data.extend(count.to_le_bytes());
+        }
+
+        let columns = read_quality_metrics(&data)?;
+
+        assert_eq!(
+            columns[3].as_primitive::<Int32Type>().values().to_vec(),
+            [12, 37]
+        );
+        assert_eq!(
+            columns[4].as_primitive::<Int64Type>().values().to_vec(),
+            [5, 95]
+        );
+
+        // Version 4 has a count per score.
+        let mut data = vec![4, 206];
+        data.extend(1u16.to_le_bytes());
+        data.extend(1101u16.to_le_bytes());
+        data.extend(2u16.to_le_bytes());
+        for quality in 1..=50u32 {
+            data.extend(u32::from(quality == 30).to_le_bytes());
+        }
+
+        let columns = read_quality_metrics(&data)?;
+        assert_eq!(
+            columns[2].as_primitive::<Int32Type>().values().to_vec(),
+            [2]
+        );
+        assert_eq!(
+            columns[3].as_primitive::<Int32Type>().values().to_vec(),
+            [30]
+        );
+
+        assert!(read_quality_metrics(&[9, 206]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_error_metrics() -> Result<(), ArrowError> {
+        let mut data = vec![3, 30];
+        data.extend(1u16.to_le_bytes());
+        data.extend(1101u16.to_le_bytes());
+        data.extend(25u16.to_le_bytes());
+        data.extend(0.25f32.to_le_bytes());
+        data.extend([0; 20]);
+
+        let columns = read_error_metrics(&data)?;
+
+        assert_eq!(columns[2].as_primitive::<Int32Type>().value(0), 25);
+        assert_eq!(columns[3].as_primitive::<Float64Type>().value(0), 0.25);
+
+        // The record size must match the version.
+        assert!(read_error_metrics(&[3, 12]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Readers for the run metadata and InterOp metrics that Illumina instruments write to a run
+//! folder, i.e. `RunInfo.xml`, `RunParameters.xml`, and the `InterOp/*.bin` files.
+//!
+//! Each file is read whole into a record batch with the name of its run folder, so runs can be
+//! joined with each other and with tables derived from their FASTQs.
+
+mod interop;
+mod run_info;
+mod run_parameters;
+
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use arrow::{
+    array::{ArrayRef, StringBuilder},
+    datatypes::{Field, Schema, SchemaRef},
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+
+/// A kind of Illumina run folder file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IlluminaFileKind {
+    /// `RunInfo.xml`, one row per read of the run.
+    RunInfo,
+
+    /// `RunParameters.xml`, one row per parameter, named by its element path.
+    RunParameters,
+
+    /// `InterOp/TileMetricsOut.bin`, one row per metric of a tile, e.g. cluster density.
+    TileMetrics,
+
+    /// `InterOp/QMetricsOut.bin`, one row per quality score of a tile and cycle with clusters.
+    QualityMetrics,
+
+    /// `InterOp/ErrorMetricsOut.bin`, the PhiX error rate of a tile and cycle.
+    ErrorMetrics,
+}
+
+impl IlluminaFileKind {
+    /// The lowercase name the files of this kind end with.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::RunInfo => "runinfo.xml",
+            Self::RunParameters => "runparameters.xml",
+            Self::TileMetrics => "tilemetricsout.bin",
+            Self::QualityMetrics => "qmetricsout.bin",
+            Self::ErrorMetrics => "errormetricsout.bin",
+        }
+    }
+
+    /// The fields of a file of this kind, starting with the `run_folder`.
+    pub fn fields(&self) -> Vec<Field> {
+        let mut fields = vec![run_folder_field()];
+
+        fields.extend(match self {
+            Self::RunInfo => run_info::fields(),
+            Self::RunParameters => run_parameters::fields(),
+            Self::TileMetrics => interop::tile_metric_fields(),
+            Self::QualityMetrics => interop::quality_metric_fields(),
+            Self::ErrorMetrics => interop::error_metric_fields(),
+        });
+
+        fields
+    }
+
+    /// The schema of a file of this kind.
+    pub fn schema(&self) -> SchemaRef {
+        Arc::new(Schema::new(self.fields()))
+    }
+
+    /// Reads a whole file of this kind into a record batch.
+    pub fn read(&self, data: &[u8], run_folder: Option<&str>) -> Result<RecordBatch, ArrowError> {
+        let columns = match self {
+            Self::RunInfo => run_info::read(data)?,
+            Self::RunParameters => run_parameters::read(data)?,
+            Self::TileMetrics => interop::read_tile_metrics(data)?,
+            Self::QualityMetrics => interop::read_quality_metrics(data)?,
+            Self::ErrorMetrics => interop::read_error_metrics(data)?,
+        };
+
+        let rows = columns.first().map_or(0, |column| column.len());
+
+        let mut run_folders = StringBuilder::new();
+        for _ in 0..rows {
+            run_folders.append_option(run_folder);
+        }
+
+        let mut arrays: Vec<ArrayRef> = vec![Arc::new(run_folders.finish())];
+        arrays.extend(columns);
+
+        RecordBatch::try_new(self.schema(), arrays)
+    }
+}
+
+impl FromStr for IlluminaFileKind {
+    type Err = ArrowError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "run_info" => Ok(Self::RunInfo),
+            "run_parameters" => Ok(Self::RunParameters),
+            "tile_metrics" => Ok(Self::TileMetrics),
+            "quality_metrics" => Ok(Self::QualityMetrics),
+            "error_metrics" => Ok(Self::ErrorMetrics),
+            _ => Err(ArrowError::InvalidArgumentError(format!(
+                "Unknown Illumina file kind {s}"
+            ))),
+        }
+    }
+}
+
+impl Display for IlluminaFileKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RunInfo => write!(f, "run_info"),
+            Self::RunParameters => write!(f, "run_parameters"),
+            Self::TileMetrics => write!(f, "tile_metrics"),
+            Self::QualityMetrics => write!(f, "quality_metrics"),
+            Self::ErrorMetrics => write!(f, "error_metrics"),
+        }
+    }
+}
+
+fn run_folder_field() -> Field {
+    Field::new("run_folder", arrow::datatypes::DataType::Utf8, true)
+}
+
+/// The name of the run folder of a file path, skipping the `InterOp` directory of metrics.
+pub fn run_folder(path: &str) -> Option<&str> {
+    let mut directories = path.split('/').rev().skip(1).filter(|d| !d.is_empty());
+
+    match directories.next()? {
+        d if d.eq_ignore_ascii_case("interop") => directories.next(),
+        d => Some(d),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_folder;
+
+    #[test]
+    fn test_run_folder() {
+        let run = "runs/200101_M00123_0001_000000000-ABCDE";
+
+        assert_eq!(
+            run_folder(&format!("{run}/RunInfo.xml")),
+            Some("200101_M00123_0001_000000000-ABCDE")
+        );
+        assert_eq!(
+            run_folder(&format!("{run}/InterOp/TileMetricsOut.bin")),
+            Some("200101_M00123_0001_000000000-ABCDE")
+        );
+        assert_eq!(run_folder("RunInfo.xml"), None);
+    }
+}
@@ -0,0 +1,293 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, BooleanBuilder, Int32Builder, StringBuilder},
+    datatypes::{DataType, Field},
+    error::ArrowError,
+};
+use quick_xml::events::{BytesStart, Event};
+
+pub(crate) fn fields() -> Vec<Field> {
+    vec![
+        Field::new("run_id", DataType::Utf8, true),
+        Field::new("run_number", DataType::Int32, true),
+        Field::new("flowcell", DataType::Utf8, true),
+        Field::new("instrument", DataType::Utf8, true),
+        Field::new("date", DataType::Utf8, true),
+        Field::new("read_number", DataType::Int32, false),
+        Field::new("num_cycles", DataType::Int32, false),
+        Field::new("is_indexed", DataType::Boolean, false),
+        Field::new("lane_count", DataType::Int32, true),
+        Field::new("surface_count", DataType::Int32, true),
+        Field::new("swath_count", DataType::Int32, true),
+        Field::new("tile_count", DataType::Int32, true),
+    ]
+}
+
+pub(crate) fn invalid_xml(file_name: &str, e: impl std::fmt::Display) -> ArrowError {
+    ArrowError::ParseError(format!("Invalid {file_name}: {e}"))
+}
+
+/// Get the value of an attribute of an element.
+pub(crate) fn attribute(element: &BytesStart, name: &[u8]) -> Result<Option<String>, ArrowError> {
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|e| invalid_xml("XML attribute", e))?;
+
+        if attribute.key.as_ref() == name {
+            let value = attribute
+                .unescape_value()
+                .map_err(|e| invalid_xml("XML attribute", e))?;
+
+            return Ok(Some(value.into_owned()));
+        }
+    }
+
+    Ok(None)
+}
+
+fn integer_attribute(element: &BytesStart, name: &[u8]) -> Result<Option<i32>, ArrowError> {
+    attribute(element, name)?
+        .map(|value| {
+            value.trim().parse().map_err(|_| {
+                invalid_xml(
+                    "RunInfo.xml",
+                    format!(
+                        "{} is not an integer: {value}",
+                        String::from_utf8_lossy(name)
+                    ),
+                )
+            })
+        })
+        .transpose()
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Read {
+    number: i32,
+    num_cycles: i32,
+    is_indexed: bool,
+}
+
+#[derive(Debug, Default)]
+struct RunInfo {
+    run_id: Option<String>,
+    run_number: Option<i32>,
+    flowcell: Option<String>,
+    instrument: Option<String>,
+    date: Option<String>,
+    reads: Vec<Read>,
+    lane_count: Option<i32>,
+    surface_count: Option<i32>,
+    swath_count: Option<i32>,
+    tile_count: Option<i32>,
+}
+
+impl RunInfo {
+    fn update(&mut self, element: &BytesStart) -> Result<(), ArrowError> {
+        match element.name().as_ref() {
+            b"Run" => {
+                self.run_id = attribute(element, b"Id")?;
+                self.run_number = integer_attribute(element, b"Number")?;
+            }
+            b"Read" => {
+                // Older RunInfo files give the first and last cycles instead of the count.
+                let num_cycles = match integer_attribute(element, b"NumCycles")? {
+                    Some(num_cycles) => Some(num_cycles),
+                    None => integer_attribute(element, b"LastCycle")?
+                        .zip(integer_attribute(element, b"FirstCycle")?)
+                        .map(|(last, first)| last - first + 1),
+                };
+
+                let (Some(number), Some(num_cycles)) =
+                    (integer_attribute(element, b"Number")?, num_cycles)
+                else {
+                    return Err(invalid_xml(
+                        "RunInfo.xml",
+                        "Read is missing Number or NumCycles",
+                    ));
+                };
+
+                let is_indexed = attribute(element, b"IsIndexedRead")?
+                    .is_some_and(|value| value.eq_ignore_ascii_case("Y"));
+
+                self.reads.push(Read {
+                    number,
+                    num_cycles,
+                    is_indexed,
+                });
+            }
+            b"FlowcellLayout" => {
+                self.lane_count = integer_attribute(element, b"LaneCount")?;
+                self.surface_count = integer_attribute(element, b"SurfaceCount")?;
+                self.swath_count = integer_attribute(element, b"SwathCount")?;
+                self.tile_count = integer_attribute(element, b"TileCount")?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn update_text(&mut self, element: &[u8], text: String) {
+        match element {
+            b"Flowcell" => self.flowcell = Some(text),
+            b"Instrument" => self.instrument = Some(text),
+            b"Date" => self.date = Some(text),
+            _ => {}
+        }
+    }
+}
+
+fn parse(data: &[u8]) -> Result<RunInfo, ArrowError> {
+    let mut reader = quick_xml::Reader::from_reader(data);
+    reader.config_mut().trim_text(true);
+
+    let mut run_info = RunInfo::default();
+    let mut elements: Vec<Vec<u8>> = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader
+            .read_event_into(&mut buf)
+            .map_err(|e| invalid_xml("RunInfo.xml", e))?
+        {
+            Event::Start(element) => {
+                run_info.update(&element)?;
+                elements.push(element.name().as_ref().to_vec());
+            }
+            Event::Empty(element) => run_info.update(&element)?,
+            Event::End(_) => {
+                elements.pop();
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|e| invalid_xml("RunInfo.xml", e))?;
+
+                if let Some(element) = elements.last() {
+                    run_info.update_text(element, text.into_owned());
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+
+        buf.clear();
+    }
+
+    if run_info.run_id.is_none() {
+        return Err(invalid_xml("RunInfo.xml", "missing the Run element"));
+    }
+
+    Ok(run_info)
+}
+
+/// Reads the reads of a RunInfo.xml, each with the run and flowcell layout.
+pub(crate) fn read(data: &[u8]) -> Result<Vec<ArrayRef>, ArrowError> {
+    let run_info = parse(data)?;
+
+    let mut run_ids = StringBuilder::new();
+    let mut run_numbers = Int32Builder::new();
+    let mut flowcells = StringBuilder::new();
+    let mut instruments = StringBuilder::new();
+    let mut dates = StringBuilder::new();
+    let mut read_numbers = Int32Builder::new();
+    let mut num_cycles = Int32Builder::new();
+    let mut is_indexed = BooleanBuilder::new();
+    let mut lane_counts = Int32Builder::new();
+    let mut surface_counts = Int32Builder::new();
+    let mut swath_counts = Int32Builder::new();
+    let mut tile_counts = Int32Builder::new();
+
+    for read in &run_info.reads {
+        run_ids.append_option(run_info.run_id.as_deref());
+        run_numbers.append_option(run_info.run_number);
+        flowcells.append_option(run_info.flowcell.as_deref());
+        instruments.append_option(run_info.instrument.as_deref());
+        dates.append_option(run_info.date.as_deref());
+        read_numbers.append_value(read.number);
+        num_cycles.append_value(read.num_cycles);
+        is_indexed.append_value(read.is_indexed);
+        lane_counts.append_option(run_info.lane_count);
+        surface_counts.append_option(run_info.surface_count);
+        swath_counts.append_option(run_info.swath_count);
+        tile_counts.append_option(run_info.tile_count);
+    }
+
+    Ok(vec![
+        Arc::new(run_ids.finish()),
+        Arc::new(run_numbers.finish()),
+        Arc::new(flowcells.finish()),
+        Arc::new(instruments.finish()),
+        Arc::new(dates.finish()),
+        Arc::new(read_numbers.finish()),
+        Arc::new(num_cycles.finish()),
+        Arc::new(is_indexed.finish()),
+        Arc::new(lane_counts.finish()),
+        Arc::new(surface_counts.finish()),
+        Arc::new(swath_counts.finish()),
+        Arc::new(tile_counts.finish()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Read};
+
+    #[test]
+    fn test_parse() -> Result<(), Box<dyn std::error::Error>> {
+        let data = br#"<?xml version="1.0"?>
+<RunInfo Version="2">
+  <Run Id="200101_M00123_0001_000000000-ABCDE" Number="1">
+    <Flowcell>000000000-ABCDE</Flowcell>
+    <Instrument>M00123</Instrument>
+    <Date>200101</Date>
+    <Reads>
+      <Read Number="1" NumCycles="151" IsIndexedRead="N" />
+      <Read Number="2" FirstCycle="152" LastCycle="159" IsIndexedRead="Y" />
+    </Reads>
+    <FlowcellLayout LaneCount="1" SurfaceCount="2" SwathCount="1" TileCount="14" />
+  </Run>
+</RunInfo>"#;
+
+        let run_info = parse(data)?;
+
+        assert_eq!(
+            run_info.run_id.as_deref(),
+            Some("200101_M00123_0001_000000000-ABCDE")
+        );
+        assert_eq!(run_info.flowcell.as_deref(), Some("000000000-ABCDE"));
+        assert_eq!(run_info.tile_count, Some(14));
+        assert_eq!(
+            run_info.reads,
+            vec![
+                Read {
+                    number: 1,
+                    num_cycles: 151,
+                    is_indexed: false,
+                },
+                Read {
+                    number: 2,
+                    num_cycles: 8,
+                    is_indexed: true,
+                },
+            ]
+        );
+
+        assert!(parse(b"<RunInfo></RunInfo>").is_err());
+
+        Ok(())
+    }
+}
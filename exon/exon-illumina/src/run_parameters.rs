@@ -0,0 +1,129 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, StringBuilder},
+    datatypes::{DataType, Field},
+    error::ArrowError,
+};
+use quick_xml::events::Event;
+
+use crate::run_info::invalid_xml;
+
+pub(crate) fn fields() -> Vec<Field> {
+    vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("value", DataType::Utf8, false),
+    ]
+}
+
+/// Parse the text of the elements of a RunParameters.xml, named by their path below the root.
+///
+/// The parameters differ between instruments and software versions, so they're flattened
+/// rather than mapped to columns, e.g. `Setup/ApplicationName`. Repeated elements give a
+/// parameter per element.
+fn parse(data: &[u8]) -> Result<Vec<(String, String)>, ArrowError> {
+    let mut reader = quick_xml::Reader::from_reader(data);
+    reader.config_mut().trim_text(true);
+
+    let mut parameters = Vec::new();
+    let mut elements: Vec<String> = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader
+            .read_event_into(&mut buf)
+            .map_err(|e| invalid_xml("RunParameters.xml", e))?
+        {
+            Event::Start(element) => {
+                elements.push(String::from_utf8_lossy(element.name().as_ref()).into_owned());
+            }
+            Event::End(_) => {
+                elements.pop();
+            }
+            Event::Text(text) if elements.len() > 1 => {
+                let text = text
+                    .unescape()
+                    .map_err(|e| invalid_xml("RunParameters.xml", e))?;
+
+                parameters.push((elements[1..].join("/"), text.into_owned()));
+            }
+            Event::CData(text) if elements.len() > 1 => {
+                let text = String::from_utf8_lossy(&text.into_inner()).into_owned();
+
+                parameters.push((elements[1..].join("/"), text));
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+
+        buf.clear();
+    }
+
+    Ok(parameters)
+}
+
+/// Reads the parameters of a RunParameters.xml.
+pub(crate) fn read(data: &[u8]) -> Result<Vec<ArrayRef>, ArrowError> {
+    let mut names = StringBuilder::new();
+    let mut values = StringBuilder::new();
+
+    for (name, value) in parse(data)? {
+        names.append_value(name);
+        values.append_value(value);
+    }
+
+    Ok(vec![Arc::new(names.finish()), Arc::new(values.finish())])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn test_parse() -> Result<(), Box<dyn std::error::Error>> {
+        let data = br#"<?xml version="1.0"?>
+<RunParameters>
+  <RunID>200101_M00123_0001_000000000-ABCDE</RunID>
+  <Setup>
+    <ApplicationName>MiSeq Control Software</ApplicationName>
+    <Read1>151</Read1>
+  </Setup>
+  <Reagents><Lot>A</Lot><Lot>B</Lot></Reagents>
+  <Empty />
+</RunParameters>"#;
+
+        let parameters = parse(data)?;
+
+        let expected = [
+            ("RunID", "200101_M00123_0001_000000000-ABCDE"),
+            ("Setup/ApplicationName", "MiSeq Control Software"),
+            ("Setup/Read1", "151"),
+            ("Reagents/Lot", "A"),
+            ("Reagents/Lot", "B"),
+        ];
+
+        assert_eq!(
+            parameters,
+            expected
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+}
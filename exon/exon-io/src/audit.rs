@@ -0,0 +1,179 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An object store layer that records the byte ranges read through it.
+
+use std::{
+    fmt::Display,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use object_store::{
+    path::Path, GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+    PutMultipartOpts, PutOptions, PutPayload, PutResult, Result as ObjectStoreResult,
+};
+
+/// A read of a byte range of an object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectRead {
+    /// The store the object was read from, e.g. `AmazonS3(bucket)`
+    pub store: String,
+
+    /// The location of the object
+    pub location: Path,
+
+    /// The byte range that was read
+    pub range: Range<usize>,
+}
+
+/// The reads recorded by one or more [`AuditObjectStore`]s.
+#[derive(Debug, Default)]
+pub struct ObjectReads {
+    reads: Mutex<Vec<ObjectRead>>,
+}
+
+impl ObjectReads {
+    fn push(&self, read: ObjectRead) {
+        self.reads.lock().expect("reads lock poisoned").push(read);
+    }
+
+    /// Take the reads recorded so far.
+    pub fn take(&self) -> Vec<ObjectRead> {
+        std::mem::take(&mut *self.reads.lock().expect("reads lock poisoned"))
+    }
+}
+
+/// An object store that records the byte range of every GET made through it.
+///
+/// HEAD requests and listings aren't recorded. Reads are recorded when the request is made, so a
+/// range that's only partially consumed is still recorded in full.
+#[derive(Debug)]
+pub struct AuditObjectStore {
+    inner: Arc<dyn ObjectStore>,
+    reads: Arc<ObjectReads>,
+}
+
+impl AuditObjectStore {
+    /// Create a new auditing object store around `inner` that records reads to `reads`.
+    pub fn new(inner: Arc<dyn ObjectStore>, reads: Arc<ObjectReads>) -> Self {
+        Self { inner, reads }
+    }
+}
+
+impl Display for AuditObjectStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AuditObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for AuditObjectStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> ObjectStoreResult<PutResult> {
+        self.inner.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOpts,
+    ) -> ObjectStoreResult<Box<dyn MultipartUpload>> {
+        self.inner.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> ObjectStoreResult<GetResult> {
+        let head = options.head;
+        let result = self.inner.get_opts(location, options).await?;
+
+        if !head {
+            self.reads.push(ObjectRead {
+                store: self.inner.to_string(),
+                location: location.clone(),
+                range: result.range.clone(),
+            });
+        }
+
+        Ok(result)
+    }
+
+    async fn delete(&self, location: &Path) -> ObjectStoreResult<()> {
+        self.inner.delete(location).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> ObjectStoreResult<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use object_store::{memory::InMemory, path::Path, ObjectStore};
+
+    use super::{AuditObjectStore, ObjectRead, ObjectReads};
+
+    #[tokio::test]
+    async fn test_records_reads() -> Result<(), Box<dyn std::error::Error>> {
+        let inner = Arc::new(InMemory::new());
+        let location = Path::from("a.txt");
+        inner.put(&location, "hello world".into()).await?;
+
+        let reads = Arc::new(ObjectReads::default());
+        let store = AuditObjectStore::new(inner, Arc::clone(&reads));
+        let store_name = "InMemory".to_string();
+
+        store.head(&location).await?;
+        store.get_range(&location, 6..11).await?;
+        store.get(&location).await?.bytes().await?;
+
+        assert_eq!(
+            reads.take(),
+            vec![
+                ObjectRead {
+                    store: store_name.clone(),
+                    location: location.clone(),
+                    range: 6..11,
+                },
+                ObjectRead {
+                    store: store_name,
+                    location,
+                    range: 0..11,
+                },
+            ]
+        );
+        assert!(reads.take().is_empty());
+
+        Ok(())
+    }
+}
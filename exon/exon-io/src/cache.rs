@@ -0,0 +1,631 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An object store layer that keeps local copies of remote reference files, e.g. FASTA files
+//! and their indexes, in a size limited cache directory.
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use futures::{stream::BoxStream, StreamExt};
+use md5::{Digest, Md5};
+use object_store::{
+    local::LocalFileSystem, path::Path, GetOptions, GetResult, ListResult, MultipartUpload,
+    ObjectMeta, ObjectStore, PutMultipartOpts, PutOptions, PutPayload, PutResult,
+    Result as ObjectStoreResult,
+};
+use tokio::io::AsyncWriteExt;
+
+use crate::{ChecksumError, ChecksumKind};
+
+/// The extensions of the files that are cached by default, i.e. reference sequences, their
+/// indexes and dictionaries, and liftover chains.
+pub const DEFAULT_CACHED_EXTENSIONS: [&str; 12] = [
+    "fa", "fasta", "fna", "fa.gz", "fasta.gz", "fna.gz", "fai", "gzi", "dict", "2bit", "chain",
+    "chain.gz",
+];
+
+/// The extension of the file holding the metadata of a cache entry.
+const META_EXTENSION: &str = "meta";
+
+/// The extension of the sidecar file holding the MD5 of an object.
+const MD5_SIDECAR_EXTENSION: &str = "md5";
+
+fn cache_error(source: impl std::error::Error + Send + Sync + 'static) -> object_store::Error {
+    object_store::Error::Generic {
+        store: "CachingObjectStore",
+        source: Box::new(source),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// The metadata of a cached object, stored next to it as `key=value` lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EntryMeta {
+    size: usize,
+    e_tag: Option<String>,
+    md5: String,
+
+    /// When the entry was last used, in milliseconds since the epoch.
+    used: u64,
+}
+
+impl EntryMeta {
+    fn parse(text: &str) -> Option<Self> {
+        let values = text
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .collect::<HashMap<_, _>>();
+
+        Some(Self {
+            size: values.get("size")?.parse().ok()?,
+            e_tag: values.get("e_tag").map(|e_tag| e_tag.to_string()),
+            md5: values.get("md5")?.to_string(),
+            used: values.get("used")?.parse().ok()?,
+        })
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!("size={}\nmd5={}\nused={}\n", self.size, self.md5, self.used);
+
+        if let Some(e_tag) = &self.e_tag {
+            text.push_str(&format!("e_tag={e_tag}\n"));
+        }
+
+        text
+    }
+
+    /// Whether the entry is a copy of the remote object, going by its size and ETag.
+    fn matches(&self, remote: &ObjectMeta) -> bool {
+        self.size == remote.size && self.e_tag == remote.e_tag
+    }
+}
+
+/// A local copy of a remote object.
+#[derive(Debug, Clone)]
+pub struct CachedObject {
+    /// The path of the local copy
+    pub path: PathBuf,
+
+    /// The metadata of the remote object
+    pub meta: ObjectMeta,
+
+    /// Whether the object was already in the cache
+    pub hit: bool,
+}
+
+/// A directory of local copies of remote objects, limited to a total size in bytes.
+///
+/// Entries are named by the MD5 of their key, the URL of the object, and are evicted least
+/// recently used first. An entry is only used while the size and ETag of the remote object match
+/// the entry, and is checked against the object's `.md5` sidecar, if any, when it's downloaded.
+#[derive(Debug, Clone)]
+pub struct ObjectCache {
+    directory: PathBuf,
+    max_size: usize,
+    extensions: Vec<String>,
+}
+
+impl ObjectCache {
+    /// Create a new cache in `directory` that holds up to `max_size` bytes.
+    pub fn new(directory: impl Into<PathBuf>, max_size: usize) -> Self {
+        // Local copies are read through a `LocalFileSystem`, which needs absolute paths.
+        let directory = directory.into();
+        let directory = std::path::absolute(&directory).unwrap_or(directory);
+
+        Self {
+            directory,
+            max_size,
+            extensions: DEFAULT_CACHED_EXTENSIONS
+                .iter()
+                .map(|e| e.to_string())
+                .collect(),
+        }
+    }
+
+    /// Set the extensions of the objects that are cached.
+    pub fn with_extensions(self, extensions: Vec<String>) -> Self {
+        Self { extensions, ..self }
+    }
+
+    /// The directory of the cache.
+    pub fn directory(&self) -> &std::path::Path {
+        &self.directory
+    }
+
+    /// Whether objects at `location` are cached, going by their extension.
+    pub fn caches(&self, location: &Path) -> bool {
+        let location = location.as_ref().to_lowercase();
+
+        self.extensions
+            .iter()
+            .any(|extension| location.ends_with(&format!(".{}", extension.to_lowercase())))
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.directory
+            .join(format!("{:x}", Md5::digest(key.as_bytes())))
+    }
+
+    async fn read_meta(entry: &std::path::Path) -> Option<EntryMeta> {
+        let text = tokio::fs::read_to_string(entry.with_extension(META_EXTENSION))
+            .await
+            .ok()?;
+
+        EntryMeta::parse(&text)
+    }
+
+    async fn write_meta(entry: &std::path::Path, meta: &EntryMeta) -> ObjectStoreResult<()> {
+        tokio::fs::write(entry.with_extension(META_EXTENSION), meta.to_text())
+            .await
+            .map_err(cache_error)
+    }
+
+    /// Returns a local copy of the object at `location` of `store`, downloading it if the cache
+    /// doesn't have an up to date copy, or `None` if the object is larger than the cache.
+    ///
+    /// `key` identifies the object across stores, e.g. its URL.
+    pub async fn fetch(
+        &self,
+        store: &dyn ObjectStore,
+        key: &str,
+        location: &Path,
+    ) -> ObjectStoreResult<Option<CachedObject>> {
+        let remote = store.head(location).await?;
+
+        if remote.size > self.max_size {
+            return Ok(None);
+        }
+
+        let entry = self.entry_path(key);
+
+        if let Some(mut meta) = Self::read_meta(&entry).await {
+            let local_size = tokio::fs::metadata(&entry).await.map(|m| m.len()).ok();
+
+            if meta.matches(&remote) && local_size == Some(meta.size as u64) {
+                meta.used = now();
+                Self::write_meta(&entry, &meta).await?;
+
+                return Ok(Some(CachedObject {
+                    path: entry,
+                    meta: remote,
+                    hit: true,
+                }));
+            }
+        }
+
+        tokio::fs::create_dir_all(&self.directory)
+            .await
+            .map_err(cache_error)?;
+
+        let md5 = self.download(store, location, &entry).await?;
+
+        if let Some(expected) = Self::expected_md5(store, location).await? {
+            if expected != md5 {
+                let _ = tokio::fs::remove_file(&entry).await;
+
+                return Err(ChecksumError {
+                    location: location.clone(),
+                    kind: ChecksumKind::Md5,
+                    expected,
+                    actual: md5,
+                }
+                .into());
+            }
+        }
+
+        let meta = EntryMeta {
+            size: remote.size,
+            e_tag: remote.e_tag.clone(),
+            md5,
+            used: now(),
+        };
+        Self::write_meta(&entry, &meta).await?;
+
+        self.evict(&entry).await?;
+
+        Ok(Some(CachedObject {
+            path: entry,
+            meta: remote,
+            hit: false,
+        }))
+    }
+
+    /// Download the object to the entry, returning its MD5.
+    ///
+    /// The object is written to a temporary file first, so a failed download never leaves a
+    /// partial entry.
+    async fn download(
+        &self,
+        store: &dyn ObjectStore,
+        location: &Path,
+        entry: &std::path::Path,
+    ) -> ObjectStoreResult<String> {
+        let partial = entry.with_extension(format!("partial-{}", std::process::id()));
+
+        let result = async {
+            let mut file = tokio::fs::File::create(&partial)
+                .await
+                .map_err(cache_error)?;
+            let mut md5 = Md5::new();
+
+            let mut stream = store.get(location).await?.into_stream();
+            while let Some(bytes) = stream.next().await {
+                let bytes = bytes?;
+
+                md5.update(&bytes);
+                file.write_all(&bytes).await.map_err(cache_error)?;
+            }
+
+            file.flush().await.map_err(cache_error)?;
+
+            Ok::<_, object_store::Error>(format!("{:x}", md5.finalize()))
+        }
+        .await;
+
+        match result {
+            Ok(md5) => {
+                tokio::fs::rename(&partial, entry)
+                    .await
+                    .map_err(cache_error)?;
+
+                Ok(md5)
+            }
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&partial).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// The MD5 from the object's `.md5` sidecar, if it has one.
+    async fn expected_md5(
+        store: &dyn ObjectStore,
+        location: &Path,
+    ) -> ObjectStoreResult<Option<String>> {
+        let sidecar = Path::from(format!("{}.{}", location, MD5_SIDECAR_EXTENSION));
+
+        match store.get(&sidecar).await {
+            Ok(result) => {
+                let bytes = result.bytes().await?;
+
+                // md5sum output is the digest followed by the file name.
+                Ok(String::from_utf8_lossy(&bytes)
+                    .split_whitespace()
+                    .next()
+                    .map(|md5| md5.to_ascii_lowercase()))
+            }
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Remove the least recently used entries, other than `keep`, until the cache fits its size.
+    async fn evict(&self, keep: &std::path::Path) -> ObjectStoreResult<()> {
+        let mut entries = Vec::new();
+
+        let mut read_dir = tokio::fs::read_dir(&self.directory)
+            .await
+            .map_err(cache_error)?;
+        while let Some(dir_entry) = read_dir.next_entry().await.map_err(cache_error)? {
+            let path = dir_entry.path();
+
+            if path.extension().and_then(|e| e.to_str()) != Some(META_EXTENSION) {
+                continue;
+            }
+
+            let entry = path.with_extension("");
+            if let Some(meta) = Self::read_meta(&entry).await {
+                entries.push((entry, meta));
+            }
+        }
+
+        let mut total = entries.iter().map(|(_, meta)| meta.size).sum::<usize>();
+        entries.sort_by_key(|(_, meta)| meta.used);
+
+        for (entry, meta) in entries {
+            if total <= self.max_size {
+                break;
+            }
+
+            if entry == keep {
+                continue;
+            }
+
+            tracing::debug!("Evicting {} from the object cache", entry.display());
+
+            let _ = tokio::fs::remove_file(entry.with_extension(META_EXTENSION)).await;
+            let _ = tokio::fs::remove_file(&entry).await;
+
+            total -= meta.size;
+        }
+
+        Ok(())
+    }
+}
+
+/// An [`ObjectStore`] that reads cached objects from a local [`ObjectCache`].
+///
+/// GETs of objects with a cached extension download the whole object into the cache the first
+/// time, then read it, including ranged reads, from the local copy. Other requests, and GETs
+/// with preconditions, go to the inner store.
+#[derive(Debug)]
+pub struct CachingObjectStore {
+    inner: Arc<dyn ObjectStore>,
+    cache: ObjectCache,
+
+    /// The URL of the inner store, which prefixes the keys of its objects in the cache
+    url: String,
+
+    /// The local copies of the objects read through this store
+    cached: Mutex<HashMap<Path, Option<CachedObject>>>,
+
+    local: LocalFileSystem,
+}
+
+impl CachingObjectStore {
+    /// Create a new caching object store around `inner`, the store for `url`.
+    pub fn new(inner: Arc<dyn ObjectStore>, cache: ObjectCache, url: &url::Url) -> Self {
+        Self {
+            inner,
+            cache,
+            url: url[..url::Position::BeforePath].to_string(),
+            cached: Mutex::new(HashMap::new()),
+            local: LocalFileSystem::new(),
+        }
+    }
+
+    /// Returns the local copy of the object at `location`, downloading it if needed, or `None`
+    /// if the object isn't cached.
+    pub async fn fetch(&self, location: &Path) -> ObjectStoreResult<Option<CachedObject>> {
+        let cached = self
+            .cached
+            .lock()
+            .expect("cache lock poisoned")
+            .get(location)
+            .cloned();
+
+        if let Some(cached) = cached {
+            match &cached {
+                Some(object) if !object.path.exists() => {}
+                _ => return Ok(cached),
+            }
+        }
+
+        let key = format!("{}/{}", self.url, location);
+        let cached = self
+            .cache
+            .fetch(self.inner.as_ref(), &key, location)
+            .await?;
+
+        self.cached
+            .lock()
+            .expect("cache lock poisoned")
+            .insert(location.clone(), cached.clone());
+
+        Ok(cached)
+    }
+
+    fn forget(&self, location: &Path) {
+        self.cached
+            .lock()
+            .expect("cache lock poisoned")
+            .remove(location);
+    }
+}
+
+impl Display for CachingObjectStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CachingObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for CachingObjectStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> ObjectStoreResult<PutResult> {
+        self.forget(location);
+        self.inner.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOpts,
+    ) -> ObjectStoreResult<Box<dyn MultipartUpload>> {
+        self.forget(location);
+        self.inner.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> ObjectStoreResult<GetResult> {
+        let has_preconditions = options.if_match.is_some()
+            || options.if_none_match.is_some()
+            || options.if_modified_since.is_some()
+            || options.if_unmodified_since.is_some()
+            || options.version.is_some();
+
+        if options.head || has_preconditions || !self.cache.caches(location) {
+            return self.inner.get_opts(location, options).await;
+        }
+
+        let Some(cached) = self.fetch(location).await? else {
+            return self.inner.get_opts(location, options).await;
+        };
+
+        let local_path = Path::from_absolute_path(&cached.path)?;
+        let local_options = GetOptions {
+            range: options.range,
+            ..Default::default()
+        };
+
+        let mut result = self.local.get_opts(&local_path, local_options).await?;
+        result.meta = cached.meta;
+
+        Ok(result)
+    }
+
+    async fn delete(&self, location: &Path) -> ObjectStoreResult<()> {
+        self.forget(location);
+        self.inner.delete(location).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> ObjectStoreResult<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.forget(to);
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.forget(to);
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use object_store::{memory::InMemory, path::Path, GetOptions, GetRange, ObjectStore};
+
+    use super::{CachingObjectStore, ObjectCache};
+
+    fn cache_directory(name: &str) -> std::path::PathBuf {
+        let directory = std::env::temp_dir().join("exon-object-cache").join(name);
+        let _ = std::fs::remove_dir_all(&directory);
+
+        directory
+    }
+
+    #[tokio::test]
+    async fn test_caching_object_store() -> object_store::Result<()> {
+        let inner = Arc::new(InMemory::new());
+        let location = Path::from("reference.fa");
+        inner.put(&location, ">chr1\nACGT\n".into()).await?;
+
+        let directory = cache_directory("test_caching_object_store");
+        let url = url::Url::parse("https://example.com/reference.fa").unwrap();
+        let store =
+            CachingObjectStore::new(inner.clone(), ObjectCache::new(&directory, 1024), &url);
+
+        let options = GetOptions {
+            range: Some(GetRange::Bounded(6..10)),
+            ..Default::default()
+        };
+        let bytes = store.get_opts(&location, options).await?.bytes().await?;
+        assert_eq!(bytes.as_ref(), b"ACGT");
+
+        let cached = store.fetch(&location).await?.unwrap();
+        assert!(cached.path.starts_with(&directory));
+        assert_eq!(cached.meta.location, location);
+
+        // A new store over the same cache reads the existing copy.
+        let store =
+            CachingObjectStore::new(inner.clone(), ObjectCache::new(&directory, 1024), &url);
+        assert!(store.fetch(&location).await?.unwrap().hit);
+
+        // A changed object is downloaded again.
+        inner.put(&location, ">chr1\nTTTT\n".into()).await?;
+        let store =
+            CachingObjectStore::new(inner.clone(), ObjectCache::new(&directory, 1024), &url);
+        assert!(!store.fetch(&location).await?.unwrap().hit);
+        assert_eq!(
+            store.get(&location).await?.bytes().await?.as_ref(),
+            b">chr1\nTTTT\n"
+        );
+
+        // Files without a cached extension go to the inner store.
+        let other = Path::from("reads.bam");
+        inner.put(&other, "BAM".into()).await?;
+        assert!(store.get(&other).await?.bytes().await?.as_ref() == b"BAM");
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_object_cache_eviction() -> object_store::Result<()> {
+        let inner = InMemory::new();
+        for name in ["a.fa", "b.fa", "c.fa"] {
+            inner.put(&Path::from(name), vec![b'A'; 10].into()).await?;
+        }
+
+        let directory = cache_directory("test_object_cache_eviction");
+        let cache = ObjectCache::new(&directory, 25);
+
+        for name in ["a.fa", "b.fa", "c.fa"] {
+            cache.fetch(&inner, name, &Path::from(name)).await?;
+        }
+
+        // Objects larger than the cache aren't cached.
+        inner
+            .put(&Path::from("d.fa"), vec![b'A'; 30].into())
+            .await?;
+        assert!(cache
+            .fetch(&inner, "d.fa", &Path::from("d.fa"))
+            .await?
+            .is_none());
+
+        // Two of the three objects fit, with a meta file each.
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 4);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_object_cache_checks_md5_sidecar() -> object_store::Result<()> {
+        let inner = InMemory::new();
+        let location = Path::from("reference.fa");
+        inner.put(&location, "ACGT".into()).await?;
+        inner
+            .put(
+                &Path::from("reference.fa.md5"),
+                "00000000000000000000000000000000  reference.fa\n".into(),
+            )
+            .await?;
+
+        let directory = cache_directory("test_object_cache_checks_md5_sidecar");
+        let cache = ObjectCache::new(&directory, 1024);
+
+        assert!(cache
+            .fetch(&inner, "reference.fa", &location)
+            .await
+            .is_err());
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0);
+
+        Ok(())
+    }
+}
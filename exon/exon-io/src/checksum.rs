@@ -0,0 +1,580 @@
+// Copyright 2023 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An object store layer that verifies checksums of the data read through it.
+
+use std::{error::Error, fmt::Display, io::Read, sync::Arc};
+
+use async_trait::async_trait;
+use bytes::{Buf, Bytes, BytesMut};
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
+use md5::{Digest, Md5};
+use object_store::{
+    path::Path, GetOptions, GetResult, GetResultPayload, ListResult, MultipartUpload, ObjectMeta,
+    ObjectStore, PutMultipartOpts, PutOptions, PutPayload, PutResult, Result as ObjectStoreResult,
+};
+
+/// The extension of the sidecar file holding the MD5 of an object.
+const MD5_SIDECAR_EXTENSION: &str = "md5";
+
+/// The length of the BGZF header up to and including the `BSIZE` field.
+const BGZF_HEADER_LEN: usize = 18;
+
+/// The length of the gzip trailer, the CRC32 followed by the uncompressed size.
+const GZIP_TRAILER_LEN: usize = 8;
+
+/// The checksum that failed verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChecksumKind {
+    /// The MD5 of the whole object, from a `.md5` sidecar or the object's ETag
+    Md5,
+
+    /// The CRC32 of the BGZF block starting at the given compressed offset of the read
+    BgzfBlockCrc32 {
+        /// The offset of the block from the start of the read
+        block_offset: usize,
+    },
+}
+
+impl Display for ChecksumKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Md5 => write!(f, "MD5"),
+            Self::BgzfBlockCrc32 { block_offset } => {
+                write!(f, "CRC32 of the BGZF block at offset {}", block_offset)
+            }
+        }
+    }
+}
+
+/// A checksum mismatch found while reading an object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumError {
+    /// The location of the object
+    pub location: Path,
+
+    /// The checksum that didn't match
+    pub kind: ChecksumKind,
+
+    /// The expected checksum
+    pub expected: String,
+
+    /// The checksum of the data that was read
+    pub actual: String,
+}
+
+impl Display for ChecksumError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Checksum mismatch for {}: {} expected {}, found {}",
+            self.location, self.kind, self.expected, self.actual
+        )
+    }
+}
+
+impl Error for ChecksumError {}
+
+/// Find a [`ChecksumError`] in `error` or its chain of sources.
+pub fn find_checksum_error<'a>(error: &'a (dyn Error + 'static)) -> Option<&'a ChecksumError> {
+    let mut current = Some(error);
+
+    while let Some(error) = current {
+        if let Some(checksum_error) = error.downcast_ref::<ChecksumError>() {
+            return Some(checksum_error);
+        }
+
+        // IO errors skip the wrapped error in `source`.
+        current = match error
+            .downcast_ref::<std::io::Error>()
+            .and_then(|e| e.get_ref())
+        {
+            Some(inner) => Some(inner as &(dyn Error + 'static)),
+            None => error.source(),
+        };
+    }
+
+    None
+}
+
+impl From<ChecksumError> for object_store::Error {
+    fn from(error: ChecksumError) -> Self {
+        object_store::Error::Generic {
+            store: "ChecksumObjectStore",
+            source: Box::new(error),
+        }
+    }
+}
+
+/// Returns the MD5 in the ETag, if the ETag is a plain MD5 digest.
+fn md5_from_e_tag(e_tag: &str) -> Option<String> {
+    let e_tag = e_tag.trim_matches('"');
+
+    if e_tag.len() == 32 && e_tag.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(e_tag.to_ascii_lowercase())
+    } else {
+        None
+    }
+}
+
+/// Verifies the CRC32 of each BGZF block as the bytes of the read come in.
+///
+/// Verification is disabled if the read doesn't start with a BGZF block, e.g. the object isn't
+/// BGZF compressed. A trailing partial block, from a ranged read that ends mid block, is ignored.
+#[derive(Debug, Default)]
+struct BgzfVerifier {
+    /// The bytes of the blocks that are not complete yet
+    buf: BytesMut,
+
+    /// The offset of the start of `buf` from the start of the read
+    offset: usize,
+
+    /// True once the read is known to be BGZF, false once it's known not to be
+    enabled: Option<bool>,
+}
+
+impl BgzfVerifier {
+    /// Returns the block size from a BGZF header, or `None` if the header isn't a BGZF header.
+    fn block_size(header: &[u8]) -> Option<usize> {
+        const MAGIC: [u8; 4] = [0x1f, 0x8b, 0x08, 0x04];
+
+        if header[..4] != MAGIC || header[12..16] != [b'B', b'C', 0x02, 0x00] {
+            return None;
+        }
+
+        Some(u16::from_le_bytes([header[16], header[17]]) as usize + 1)
+    }
+
+    fn verify_block(&self, block: &[u8], location: &Path) -> Result<(), ChecksumError> {
+        let data_end = block.len() - GZIP_TRAILER_LEN;
+        let expected = u32::from_le_bytes([
+            block[data_end],
+            block[data_end + 1],
+            block[data_end + 2],
+            block[data_end + 3],
+        ]);
+
+        let mut data = Vec::new();
+        let actual = match flate2::read::DeflateDecoder::new(&block[BGZF_HEADER_LEN..data_end])
+            .read_to_end(&mut data)
+        {
+            Ok(_) => format!("{:08x}", crc32fast::hash(&data)),
+            Err(e) => format!("undecodable block ({})", e),
+        };
+
+        let expected = format!("{:08x}", expected);
+
+        if actual == expected {
+            return Ok(());
+        }
+
+        Err(ChecksumError {
+            location: location.clone(),
+            kind: ChecksumKind::BgzfBlockCrc32 {
+                block_offset: self.offset,
+            },
+            expected,
+            actual,
+        })
+    }
+
+    fn update(&mut self, bytes: &[u8], location: &Path) -> Result<(), ChecksumError> {
+        if self.enabled == Some(false) {
+            return Ok(());
+        }
+
+        self.buf.extend_from_slice(bytes);
+
+        while self.buf.len() >= BGZF_HEADER_LEN {
+            let block_size = match Self::block_size(&self.buf[..BGZF_HEADER_LEN]) {
+                Some(block_size) if block_size >= BGZF_HEADER_LEN + GZIP_TRAILER_LEN => block_size,
+                _ if self.enabled.is_none() => {
+                    self.enabled = Some(false);
+                    self.buf.clear();
+
+                    return Ok(());
+                }
+                _ => {
+                    return Err(ChecksumError {
+                        location: location.clone(),
+                        kind: ChecksumKind::BgzfBlockCrc32 {
+                            block_offset: self.offset,
+                        },
+                        expected: "a BGZF block header".to_string(),
+                        actual: "invalid header".to_string(),
+                    })
+                }
+            };
+
+            self.enabled = Some(true);
+
+            if self.buf.len() < block_size {
+                break;
+            }
+
+            self.verify_block(&self.buf[..block_size], location)?;
+
+            self.buf.advance(block_size);
+            self.offset += block_size;
+        }
+
+        Ok(())
+    }
+}
+
+/// The state of a GET stream whose checksums are verified as it's consumed.
+struct VerifiedStream {
+    store: Arc<dyn ObjectStore>,
+    location: Path,
+    stream: BoxStream<'static, ObjectStoreResult<Bytes>>,
+
+    /// The MD5 of the bytes read so far, `None` if the read doesn't cover the whole object
+    md5: Option<Md5>,
+
+    /// The MD5 from the ETag of the object, if it's trusted
+    e_tag_md5: Option<String>,
+
+    bgzf: BgzfVerifier,
+}
+
+impl VerifiedStream {
+    /// The expected MD5 from the `.md5` sidecar, falling back to the ETag.
+    ///
+    /// This takes `&mut self` as the boxed stream isn't `Sync`, so a shared reference held across
+    /// the await would make the stream's future not `Send`.
+    async fn expected_md5(&mut self) -> ObjectStoreResult<Option<String>> {
+        let sidecar = Path::from(format!("{}.{}", self.location, MD5_SIDECAR_EXTENSION));
+
+        match self.store.get(&sidecar).await {
+            Ok(result) => {
+                let bytes = result.bytes().await?;
+
+                // md5sum output is the digest followed by the file name.
+                let md5 = String::from_utf8_lossy(&bytes)
+                    .split_whitespace()
+                    .next()
+                    .map(|md5| md5.to_ascii_lowercase());
+
+                Ok(md5)
+            }
+            Err(object_store::Error::NotFound { .. }) => Ok(self.e_tag_md5.clone()),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn finish(&mut self) -> ObjectStoreResult<()> {
+        let md5 = match self.md5.take() {
+            Some(md5) => format!("{:x}", md5.finalize()),
+            None => return Ok(()),
+        };
+
+        match self.expected_md5().await? {
+            Some(expected) if expected != md5 => Err(ChecksumError {
+                location: self.location.clone(),
+                kind: ChecksumKind::Md5,
+                expected,
+                actual: md5,
+            }
+            .into()),
+            Some(_) => Ok(()),
+            None => {
+                tracing::debug!("No MD5 found for {}, skipping verification", self.location);
+                Ok(())
+            }
+        }
+    }
+
+    fn into_stream(self) -> BoxStream<'static, ObjectStoreResult<Bytes>> {
+        futures::stream::unfold(Some(self), |state| async move {
+            let mut state = state?;
+
+            match state.stream.next().await {
+                Some(Ok(bytes)) => {
+                    if let Some(md5) = state.md5.as_mut() {
+                        md5.update(&bytes);
+                    }
+
+                    match state.bgzf.update(&bytes, &state.location) {
+                        Ok(()) => Some((Ok(bytes), Some(state))),
+                        Err(e) => Some((Err(e.into()), None)),
+                    }
+                }
+                Some(Err(e)) => Some((Err(e), None)),
+                None => match state.finish().await {
+                    Ok(()) => None,
+                    Err(e) => Some((Err(e), None)),
+                },
+            }
+        })
+        .boxed()
+    }
+}
+
+/// An [`ObjectStore`] that verifies checksums of the data read through it.
+///
+/// Reads of a whole object are checked against the MD5 in a `.md5` sidecar next to the object
+/// or, if enabled, the ETag of the object. Reads of BGZF data are checked block by block against
+/// the CRC32 in each block, this includes ranged reads that start at a block boundary. A mismatch
+/// is returned as a [`ChecksumError`] at the end of the stream, or at the offending block.
+#[derive(Debug)]
+pub struct ChecksumObjectStore {
+    inner: Arc<dyn ObjectStore>,
+
+    /// Whether to trust an ETag that looks like an MD5 digest when there is no sidecar
+    use_e_tag: bool,
+}
+
+impl ChecksumObjectStore {
+    /// Create a new verifying object store around `inner`.
+    pub fn new(inner: Arc<dyn ObjectStore>) -> Self {
+        Self {
+            inner,
+            use_e_tag: false,
+        }
+    }
+
+    /// Use the ETag as the MD5 of objects without a sidecar.
+    ///
+    /// Only enable this for stores where the ETag is the MD5 of the content, e.g. S3 objects
+    /// uploaded in a single part without KMS encryption.
+    pub fn with_e_tag(self, use_e_tag: bool) -> Self {
+        Self { use_e_tag, ..self }
+    }
+}
+
+impl Display for ChecksumObjectStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ChecksumObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for ChecksumObjectStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> ObjectStoreResult<PutResult> {
+        self.inner.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOpts,
+    ) -> ObjectStoreResult<Box<dyn MultipartUpload>> {
+        self.inner.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> ObjectStoreResult<GetResult> {
+        let head = options.head;
+        let result = self.inner.get_opts(location, options).await?;
+
+        if head {
+            return Ok(result);
+        }
+
+        let whole_object = result.range.start == 0 && result.range.end == result.meta.size;
+
+        let e_tag_md5 = match &result.meta.e_tag {
+            Some(e_tag) if self.use_e_tag => md5_from_e_tag(e_tag),
+            _ => None,
+        };
+
+        let GetResult {
+            payload,
+            meta,
+            range,
+            attributes,
+        } = result;
+
+        let verified = |stream| VerifiedStream {
+            store: Arc::clone(&self.inner),
+            location: location.clone(),
+            stream,
+            md5: whole_object.then(Md5::new),
+            e_tag_md5,
+            bgzf: BgzfVerifier::default(),
+        };
+
+        let payload = match payload {
+            GetResultPayload::Stream(stream) => {
+                GetResultPayload::Stream(verified(stream).into_stream())
+            }
+            GetResultPayload::File(file, path) => {
+                // Some readers need the path of a local file, so it's verified up front.
+                let copy =
+                    std::fs::File::open(&path).map_err(|e| object_store::Error::Generic {
+                        store: "ChecksumObjectStore",
+                        source: Box::new(e),
+                    })?;
+
+                let copy = GetResult {
+                    payload: GetResultPayload::File(copy, path.clone()),
+                    meta: meta.clone(),
+                    range: range.clone(),
+                    attributes: attributes.clone(),
+                };
+
+                verified(copy.into_stream())
+                    .into_stream()
+                    .try_for_each(|_| futures::future::ready(Ok(())))
+                    .await?;
+
+                GetResultPayload::File(file, path)
+            }
+        };
+
+        Ok(GetResult {
+            payload,
+            meta,
+            range,
+            attributes,
+        })
+    }
+
+    async fn delete(&self, location: &Path) -> ObjectStoreResult<()> {
+        self.inner.delete(location).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> ObjectStoreResult<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, sync::Arc};
+
+    use bytes::Bytes;
+    use md5::{Digest, Md5};
+    use object_store::{memory::InMemory, path::Path, ObjectStore, PutPayload};
+
+    use super::{find_checksum_error, ChecksumError, ChecksumKind, ChecksumObjectStore};
+
+    /// Compress `data` into a single BGZF block.
+    fn bgzf_block(data: &[u8]) -> Vec<u8> {
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        let cdata = encoder.finish().unwrap();
+
+        let block_size = (18 + cdata.len() + 8 - 1) as u16;
+
+        let mut block = vec![0x1f, 0x8b, 0x08, 0x04, 0, 0, 0, 0, 0, 0xff, 0x06, 0x00];
+        block.extend_from_slice(&[b'B', b'C', 0x02, 0x00]);
+        block.extend_from_slice(&block_size.to_le_bytes());
+        block.extend_from_slice(&cdata);
+        block.extend_from_slice(&crc32fast::hash(data).to_le_bytes());
+        block.extend_from_slice(&(data.len() as u32).to_le_bytes());
+
+        block
+    }
+
+    async fn read(store: &ChecksumObjectStore, location: &str) -> object_store::Result<Bytes> {
+        store.get(&Path::from(location)).await?.bytes().await
+    }
+
+    fn checksum_error(error: &object_store::Error) -> Option<&ChecksumError> {
+        find_checksum_error(error)
+    }
+
+    #[tokio::test]
+    async fn test_md5_sidecar() -> Result<(), Box<dyn std::error::Error>> {
+        let inner = InMemory::new();
+        let md5 = format!("{:x}", Md5::digest(b"ACGT"));
+
+        inner
+            .put(&Path::from("good.fa"), PutPayload::from_static(b"ACGT"))
+            .await?;
+        inner
+            .put(
+                &Path::from("good.fa.md5"),
+                PutPayload::from(format!("{}  good.fa\n", md5)),
+            )
+            .await?;
+
+        inner
+            .put(&Path::from("bad.fa"), PutPayload::from_static(b"ACGA"))
+            .await?;
+        inner
+            .put(&Path::from("bad.fa.md5"), PutPayload::from(md5.clone()))
+            .await?;
+
+        inner
+            .put(&Path::from("none.fa"), PutPayload::from_static(b"ACGA"))
+            .await?;
+
+        let store = ChecksumObjectStore::new(Arc::new(inner));
+
+        assert_eq!(read(&store, "good.fa").await?, Bytes::from_static(b"ACGT"));
+        assert_eq!(read(&store, "none.fa").await?, Bytes::from_static(b"ACGA"));
+
+        let error = read(&store, "bad.fa").await.unwrap_err();
+        let error = checksum_error(&error).ok_or("expected a checksum error")?;
+        assert_eq!(error.kind, ChecksumKind::Md5);
+        assert_eq!(error.expected, md5);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bgzf_block_crc() -> Result<(), Box<dyn std::error::Error>> {
+        let first = bgzf_block(b"chr1\t1\t2\n");
+        let second = bgzf_block(b"chr1\t3\t4\n");
+
+        let mut data = first.clone();
+        data.extend_from_slice(&second);
+
+        let mut corrupt = data.clone();
+        let crc_offset = data.len() - 8;
+        corrupt[crc_offset] ^= 0xff;
+
+        let inner = InMemory::new();
+        inner
+            .put(&Path::from("good.bed.gz"), PutPayload::from(data))
+            .await?;
+        inner
+            .put(&Path::from("bad.bed.gz"), PutPayload::from(corrupt))
+            .await?;
+
+        let store = ChecksumObjectStore::new(Arc::new(inner));
+
+        read(&store, "good.bed.gz").await?;
+
+        let error = read(&store, "bad.bed.gz").await.unwrap_err();
+        let error = checksum_error(&error).ok_or("expected a checksum error")?;
+        assert_eq!(
+            error.kind,
+            ChecksumKind::BgzfBlockCrc32 {
+                block_offset: first.len()
+            }
+        );
+
+        Ok(())
+    }
+}
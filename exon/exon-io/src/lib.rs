@@ -12,6 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod audit;
+mod cache;
+mod checksum;
 mod io;
+mod retry;
 
+pub use audit::{AuditObjectStore, ObjectRead, ObjectReads};
+pub use cache::{CachedObject, CachingObjectStore, ObjectCache, DEFAULT_CACHED_EXTENSIONS};
+pub use checksum::{find_checksum_error, ChecksumError, ChecksumKind, ChecksumObjectStore};
 pub use io::build_s3_object_store;
+pub use retry::{RetryObjectStore, RetryPolicy};
@@ -0,0 +1,489 @@
+// Copyright 2023 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A retry layer for object stores that resumes interrupted GET streams.
+
+use std::{fmt::Display, ops::Range, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{stream::BoxStream, StreamExt};
+use object_store::{
+    path::Path, GetOptions, GetRange, GetResult, GetResultPayload, ListResult, MultipartUpload,
+    ObjectMeta, ObjectStore, PutMultipartOpts, PutOptions, PutPayload, PutResult,
+    Result as ObjectStoreResult,
+};
+
+/// The policy for retrying failed GET requests and interrupted GET streams.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The number of consecutive retries before the error is returned.
+    max_retries: usize,
+
+    /// The backoff before the first retry.
+    initial_backoff: Duration,
+
+    /// The upper bound on the backoff between retries.
+    max_backoff: Duration,
+
+    /// The factor the backoff grows by after each retry.
+    backoff_base: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            backoff_base: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Set the number of consecutive retries, zero disables retrying.
+    pub fn with_max_retries(self, max_retries: usize) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Set the backoff before the first retry.
+    pub fn with_initial_backoff(self, initial_backoff: Duration) -> Self {
+        Self {
+            initial_backoff,
+            ..self
+        }
+    }
+
+    /// Set the upper bound on the backoff between retries.
+    pub fn with_max_backoff(self, max_backoff: Duration) -> Self {
+        Self {
+            max_backoff,
+            ..self
+        }
+    }
+
+    /// Set the factor the backoff grows by after each retry.
+    pub fn with_backoff_base(self, backoff_base: f64) -> Self {
+        Self {
+            backoff_base,
+            ..self
+        }
+    }
+
+    /// The number of consecutive retries before the error is returned.
+    pub fn max_retries(&self) -> usize {
+        self.max_retries
+    }
+
+    /// The backoff before the given retry, starting at zero.
+    pub fn backoff(&self, retry: usize) -> Duration {
+        let factor = self.backoff_base.powi(retry.min(i32::MAX as usize) as i32);
+        let backoff = self.initial_backoff.as_secs_f64() * factor;
+
+        Duration::from_secs_f64(backoff.min(self.max_backoff.as_secs_f64()))
+    }
+}
+
+/// Returns true if the error may be transient.
+///
+/// Stores report HTTP server errors and broken connections as generic errors, the other variants
+/// (not found, failed preconditions, permissions, ...) will fail the same way on a retry.
+fn is_retryable(error: &object_store::Error) -> bool {
+    matches!(error, object_store::Error::Generic { .. })
+}
+
+/// Issue a GET request, retrying transient errors up to the policy's limit.
+///
+/// `retries` is the number of consecutive retries so far, it's shared with the stream so a
+/// request that is retried after a stream failure counts against the same limit.
+async fn get_with_retry(
+    store: &dyn ObjectStore,
+    location: &Path,
+    options: &GetOptions,
+    policy: &RetryPolicy,
+    retries: &mut usize,
+) -> ObjectStoreResult<GetResult> {
+    loop {
+        match store.get_opts(location, options.clone()).await {
+            Err(e) if *retries < policy.max_retries && is_retryable(&e) => {
+                tracing::warn!(
+                    "Retrying GET of {} after error ({}/{}): {}",
+                    location,
+                    *retries + 1,
+                    policy.max_retries,
+                    e
+                );
+
+                tokio::time::sleep(policy.backoff(*retries)).await;
+                *retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// The state of a GET stream that resumes from the last consumed byte after an error.
+struct ResumableStream {
+    store: Arc<dyn ObjectStore>,
+    location: Path,
+    options: GetOptions,
+    policy: RetryPolicy,
+    stream: BoxStream<'static, ObjectStoreResult<Bytes>>,
+
+    /// The remaining byte range of the object to read.
+    remaining: Range<usize>,
+
+    /// The number of consecutive retries, reset once bytes are read.
+    retries: usize,
+}
+
+impl ResumableStream {
+    fn into_stream(self) -> BoxStream<'static, ObjectStoreResult<Bytes>> {
+        futures::stream::unfold(Some(self), |state| async move {
+            let mut state = state?;
+
+            loop {
+                let error = match state.stream.next().await {
+                    Some(Ok(bytes)) => {
+                        if !bytes.is_empty() {
+                            state.remaining.start += bytes.len();
+                            state.retries = 0;
+                        }
+
+                        return Some((Ok(bytes), Some(state)));
+                    }
+                    Some(Err(e)) => e,
+                    None => return None,
+                };
+
+                if state.remaining.is_empty()
+                    || state.retries >= state.policy.max_retries
+                    || !is_retryable(&error)
+                {
+                    return Some((Err(error), None));
+                }
+
+                tracing::warn!(
+                    "Resuming GET of {} at byte {} after error ({}/{}): {}",
+                    state.location,
+                    state.remaining.start,
+                    state.retries + 1,
+                    state.policy.max_retries,
+                    error
+                );
+
+                tokio::time::sleep(state.policy.backoff(state.retries)).await;
+                state.retries += 1;
+
+                // Only read the bytes that haven't been consumed, from the same object version.
+                let options = GetOptions {
+                    range: Some(GetRange::Bounded(state.remaining.clone())),
+                    ..state.options.clone()
+                };
+
+                let result = get_with_retry(
+                    state.store.as_ref(),
+                    &state.location,
+                    &options,
+                    &state.policy,
+                    &mut state.retries,
+                )
+                .await;
+
+                match result {
+                    Ok(result) => state.stream = result.into_stream(),
+                    Err(e) => return Some((Err(e), None)),
+                }
+            }
+        })
+        .boxed()
+    }
+}
+
+/// An [`ObjectStore`] that retries transient GET errors with exponential backoff.
+///
+/// Streams returned by `get_opts` resume with a range request from the last consumed byte
+/// rather than failing, so long scans survive errors that happen mid-stream. Resumed requests
+/// are conditional on the ETag of the original response, if the object changed in between
+/// the precondition error is returned.
+#[derive(Debug)]
+pub struct RetryObjectStore {
+    inner: Arc<dyn ObjectStore>,
+    policy: RetryPolicy,
+}
+
+impl RetryObjectStore {
+    /// Create a new retrying object store around `inner`.
+    pub fn new(inner: Arc<dyn ObjectStore>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    /// The retry policy of the store.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+}
+
+impl Display for RetryObjectStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RetryObjectStore({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for RetryObjectStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> ObjectStoreResult<PutResult> {
+        self.inner.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOpts,
+    ) -> ObjectStoreResult<Box<dyn MultipartUpload>> {
+        self.inner.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> ObjectStoreResult<GetResult> {
+        let mut retries = 0;
+        let result = get_with_retry(
+            self.inner.as_ref(),
+            location,
+            &options,
+            &self.policy,
+            &mut retries,
+        )
+        .await?;
+
+        if options.head {
+            return Ok(result);
+        }
+
+        let stream = match result.payload {
+            GetResultPayload::Stream(stream) => stream,
+            payload @ GetResultPayload::File(_, _) => {
+                return Ok(GetResult { payload, ..result });
+            }
+        };
+
+        let options = GetOptions {
+            if_match: result.meta.e_tag.clone().or(options.if_match),
+            ..options
+        };
+
+        let resumable = ResumableStream {
+            store: Arc::clone(&self.inner),
+            location: location.clone(),
+            options,
+            policy: self.policy.clone(),
+            stream,
+            remaining: result.range.clone(),
+            retries,
+        };
+
+        Ok(GetResult {
+            payload: GetResultPayload::Stream(resumable.into_stream()),
+            ..result
+        })
+    }
+
+    async fn delete(&self, location: &Path) -> ObjectStoreResult<()> {
+        self.inner.delete(location).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, ObjectStoreResult<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> ObjectStoreResult<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> ObjectStoreResult<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use futures::{stream::BoxStream, StreamExt};
+    use object_store::{
+        memory::InMemory, path::Path, GetOptions, GetResult, GetResultPayload, ListResult,
+        MultipartUpload, ObjectMeta, ObjectStore, PutMultipartOpts, PutOptions, PutPayload,
+        PutResult, Result,
+    };
+
+    use super::{RetryObjectStore, RetryPolicy};
+
+    /// A store whose first `failures` GET streams fail after returning half of the bytes, so
+    /// a stream of one byte fails without making progress.
+    #[derive(Debug)]
+    struct FlakyStore {
+        inner: InMemory,
+        failures: AtomicUsize,
+    }
+
+    impl std::fmt::Display for FlakyStore {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "FlakyStore")
+        }
+    }
+
+    #[async_trait]
+    impl ObjectStore for FlakyStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            payload: PutPayload,
+            opts: PutOptions,
+        ) -> Result<PutResult> {
+            self.inner.put_opts(location, payload, opts).await
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: PutMultipartOpts,
+        ) -> Result<Box<dyn MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+            let result = self.inner.get_opts(location, options).await?;
+
+            let failures = self.failures.load(Ordering::SeqCst);
+            if failures == 0 {
+                return Ok(result);
+            }
+            self.failures.store(failures - 1, Ordering::SeqCst);
+
+            let range = result.range.clone();
+            let meta = result.meta.clone();
+            let attributes = result.attributes.clone();
+
+            let bytes = result.bytes().await?;
+            let half = bytes.slice(..bytes.len() / 2);
+
+            let stream = futures::stream::iter(vec![
+                Ok(half),
+                Err(object_store::Error::Generic {
+                    store: "FlakyStore",
+                    source: "connection reset".into(),
+                }),
+            ]);
+
+            Ok(GetResult {
+                payload: GetResultPayload::Stream(stream.boxed()),
+                meta,
+                range,
+                attributes,
+            })
+        }
+
+        async fn delete(&self, location: &Path) -> Result<()> {
+            self.inner.delete(location).await
+        }
+
+        fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, Result<ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+
+        async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+            self.inner.copy(from, to).await
+        }
+
+        async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+            self.inner.copy_if_not_exists(from, to).await
+        }
+    }
+
+    async fn flaky_store(failures: usize, policy: RetryPolicy) -> Result<RetryObjectStore> {
+        let inner = InMemory::new();
+        inner
+            .put(&Path::from("data"), PutPayload::from_static(b"0123456789"))
+            .await?;
+
+        let flaky = FlakyStore {
+            inner,
+            failures: AtomicUsize::new(failures),
+        };
+
+        Ok(RetryObjectStore::new(Arc::new(flaky), policy))
+    }
+
+    #[tokio::test]
+    async fn test_resumes_interrupted_stream() -> Result<()> {
+        let policy = RetryPolicy::default().with_initial_backoff(Duration::from_millis(1));
+        let store = flaky_store(3, policy).await?;
+
+        let bytes = store.get(&Path::from("data")).await?.bytes().await?;
+        assert_eq!(bytes, Bytes::from_static(b"0123456789"));
+
+        let bytes = store.get_range(&Path::from("data"), 2..8).await?;
+        assert_eq!(bytes, Bytes::from_static(b"234567"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() -> Result<()> {
+        let policy = RetryPolicy::default()
+            .with_initial_backoff(Duration::from_millis(1))
+            .with_max_retries(1);
+        let store = flaky_store(100, policy).await?;
+
+        let result = store.get(&Path::from("data")).await?.bytes().await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default()
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(500));
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(10), Duration::from_millis(500));
+    }
+}
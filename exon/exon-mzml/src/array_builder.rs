@@ -23,14 +23,94 @@ use arrow::{
 };
 
 use crate::mzml_reader::{
-    BinaryDataArray, BinaryDataType, CompressionType, DataType as MzDataType, Spectrum,
+    BinaryDataType, CVParam, CompressionType, DataType as MzDataType, Spectrum,
     FLOAT_32_DATA_TYPE_MS_NUMBER, FLOAT_64_DATA_TYPE_MS_NUMBER, INTENSITY_ARRAY, MZ_ARRAY,
     NO_COMPRESSION_MS_NUMBER, WAVE_LENGTH_ARRAY, ZLIB_COMPRESSION_MS_NUMBER,
 };
 
 // https://github.com/wfondrie/depthcharge/blob/d46adf12deba06fb5d1019eb6e7a2ff621bfb388/depthcharge/data/parsers.py#L253
 
-use super::mzml_reader::binary_conversion::decode_binary_array;
+use super::{
+    mzml_reader::binary_conversion::decode_binary_array,
+    spectrum_filter::{retention_time_in_seconds, SCAN_START_TIME, SELECTED_ION_MZ},
+};
+
+/// The type of a binary data array and its values, if the spectrum has any.
+pub(crate) type DecodedDataArray = (Option<BinaryDataType>, Option<Vec<f64>>);
+
+/// Get the array type, compression, and data type of a binary data array from its cvParams.
+pub(crate) fn data_array_types(
+    cv_params: &[CVParam],
+) -> std::io::Result<(
+    Option<BinaryDataType>,
+    Option<CompressionType>,
+    Option<MzDataType>,
+)> {
+    let mut binary_array_type = None;
+    let mut compression_type = None;
+    let mut data_type = None;
+
+    for cv_param in cv_params {
+        match cv_param.accession.as_str() {
+            MZ_ARRAY | INTENSITY_ARRAY | WAVE_LENGTH_ARRAY => {
+                binary_array_type = Some(
+                    BinaryDataType::try_from(cv_param.accession.as_str()).map_err(|e| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            format!("Invalid binary array type: {e}"),
+                        )
+                    })?,
+                );
+            }
+            ZLIB_COMPRESSION_MS_NUMBER | NO_COMPRESSION_MS_NUMBER => {
+                compression_type = Some(CompressionType::try_from(cv_param).map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Invalid compression type: {e}"),
+                    )
+                })?);
+            }
+            FLOAT_32_DATA_TYPE_MS_NUMBER | FLOAT_64_DATA_TYPE_MS_NUMBER => {
+                data_type = Some(MzDataType::try_from(cv_param).map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Invalid data type: {e}"),
+                    )
+                })?);
+            }
+            _ => {}
+        }
+    }
+
+    Ok((binary_array_type, compression_type, data_type))
+}
+
+/// Decode the base64 binary data arrays of the spectrum.
+fn decode_data_arrays(record: &Spectrum) -> std::io::Result<Vec<DecodedDataArray>> {
+    record
+        .binary_data_array_list
+        .binary_data_array
+        .iter()
+        .map(|mz| {
+            let (binary_array_type, compression_type, data_type) = data_array_types(&mz.cv_param)?;
+
+            let values = match (&mz.binary.content, compression_type, data_type) {
+                (Some(_), Some(compression), Some(data_type)) => {
+                    Some(decode_binary_array(&mz.binary, &compression, &data_type)?)
+                }
+                (Some(_), _, _) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "No compression or data type found",
+                    ))
+                }
+                (None, _, _) => None,
+            };
+
+            Ok((binary_array_type, values))
+        })
+        .collect()
+}
 
 pub struct MzMLArrayBuilder {
     id: GenericStringBuilder<i32>,
@@ -46,6 +126,8 @@ pub struct MzMLArrayBuilder {
 
     precursor_mz: Float64Builder,
     precursor_charge: Int64Builder,
+
+    rt: Float64Builder,
 }
 
 impl MzMLArrayBuilder {
@@ -109,6 +191,7 @@ impl MzMLArrayBuilder {
             cv_params: cv_params_builder,
             precursor_mz,
             precursor_charge: Int64Builder::new(),
+            rt: Float64Builder::new(),
         }
     }
 
@@ -116,181 +199,45 @@ impl MzMLArrayBuilder {
         self.id.len()
     }
 
-    fn append_data_arrays_none_content(
+    /// Append a data array of the given type, or a null list if the spectrum has no values for it.
+    fn append_data_array(
         &mut self,
         binary_array_type: Option<BinaryDataType>,
+        values: Option<Vec<f64>>,
     ) -> std::io::Result<()> {
-        match binary_array_type {
-            Some(BinaryDataType::Mz) => {
-                let mz_builder = self
-                    .mz
-                    .field_builder::<ListBuilder<Float64Builder>>(0)
-                    .unwrap();
-
-                mz_builder.append_null();
-                self.mz.append(true);
-            }
-            Some(BinaryDataType::Intensity) => {
-                let intensity_builder = self
-                    .intensity
-                    .field_builder::<ListBuilder<Float64Builder>>(0)
-                    .unwrap();
-
-                intensity_builder.append_null();
-                self.intensity.append(true);
-            }
-            Some(BinaryDataType::Wavelength) => {
-                let wavelength_builder = self
-                    .wavelength
-                    .field_builder::<ListBuilder<Float64Builder>>(0)
-                    .unwrap();
-
-                wavelength_builder.append_null();
-                self.wavelength.append(true);
-            }
+        let builder = match binary_array_type {
+            Some(BinaryDataType::Mz) => &mut self.mz,
+            Some(BinaryDataType::Intensity) => &mut self.intensity,
+            Some(BinaryDataType::Wavelength) => &mut self.wavelength,
             None => {
                 return Err(std::io::Error::new(
                     std::io::ErrorKind::InvalidData,
                     "No binary array type found",
                 ))
             }
-        }
-
-        Ok(())
-    }
-
-    /// Handles the append case where there is a binary data array.
-    fn append_data_arrays_some_content(
-        &mut self,
-        compression_type: Option<CompressionType>,
-        data_type: Option<MzDataType>,
-        binary_array_type: Option<BinaryDataType>,
-        mz: &BinaryDataArray,
-    ) -> std::io::Result<()> {
-        let data_array = match (compression_type, data_type) {
-            (Some(compression), Some(data_type)) => {
-                decode_binary_array(&mz.binary, &compression, &data_type)?
-            }
-            _ => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "No compression or data type found",
-                ))
-            }
         };
 
-        match binary_array_type {
-            Some(BinaryDataType::Mz) => {
-                let mz_builder = self
-                    .mz
-                    .field_builder::<ListBuilder<Float64Builder>>(0)
-                    .unwrap();
+        let list_builder = builder
+            .field_builder::<ListBuilder<Float64Builder>>(0)
+            .unwrap();
 
-                let mz_values = mz_builder.values();
-                for value in data_array {
-                    mz_values.append_value(value);
-                }
-
-                mz_builder.append(true);
-
-                self.mz.append(true);
-            }
-            Some(BinaryDataType::Intensity) => {
-                let intensity_builder = self
-                    .intensity
-                    .field_builder::<ListBuilder<Float64Builder>>(0)
-                    .unwrap();
-
-                let intensity_values = intensity_builder.values();
-                for value in data_array {
-                    intensity_values.append_value(value);
-                }
-
-                intensity_builder.append(true);
-
-                self.intensity.append(true);
-            }
-            Some(BinaryDataType::Wavelength) => {
-                let wavelength_builder = self
-                    .wavelength
-                    .field_builder::<ListBuilder<Float64Builder>>(0)
-                    .unwrap();
-
-                let wavelength_values = wavelength_builder.values();
-                for value in data_array {
-                    wavelength_values.append_value(value);
-                }
-
-                wavelength_builder.append(true);
-
-                self.wavelength.append(true);
-            }
-            None => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "No binary array type found",
-                ))
+        match values {
+            Some(values) => {
+                list_builder.values().append_slice(&values);
+                list_builder.append(true);
             }
+            None => list_builder.append_null(),
         }
 
+        builder.append(true);
+
         Ok(())
     }
 
     /// Append the data arrays to the arrow array batch builder.
-    fn append_data_arrays(&mut self, record: &Spectrum) -> std::io::Result<()> {
-        for mz in &record.binary_data_array_list.binary_data_array {
-            let mut binary_array_type = None;
-            let mut compression_type = None;
-            let mut data_type = None;
-
-            let cv_params = &mz.cv_param;
-
-            for cv_param in cv_params {
-                match cv_param.accession.as_str() {
-                    MZ_ARRAY | INTENSITY_ARRAY | WAVE_LENGTH_ARRAY => {
-                        binary_array_type = Some(
-                            BinaryDataType::try_from(cv_param.accession.as_str()).map_err(|e| {
-                                std::io::Error::new(
-                                    std::io::ErrorKind::InvalidData,
-                                    format!("Invalid binary array type: {e}"),
-                                )
-                            })?,
-                        );
-                    }
-                    ZLIB_COMPRESSION_MS_NUMBER | NO_COMPRESSION_MS_NUMBER => {
-                        compression_type =
-                            Some(CompressionType::try_from(cv_params).map_err(|e| {
-                                std::io::Error::new(
-                                    std::io::ErrorKind::InvalidData,
-                                    format!("Invalid compression type: {e}"),
-                                )
-                            })?);
-                    }
-                    FLOAT_32_DATA_TYPE_MS_NUMBER | FLOAT_64_DATA_TYPE_MS_NUMBER => {
-                        data_type = Some(MzDataType::try_from(cv_params).map_err(|e| {
-                            std::io::Error::new(
-                                std::io::ErrorKind::InvalidData,
-                                format!("Invalid data type: {e}"),
-                            )
-                        })?);
-                    }
-                    _ => {}
-                }
-            }
-
-            match &mz.binary.content {
-                Some(_) => {
-                    self.append_data_arrays_some_content(
-                        compression_type,
-                        data_type,
-                        binary_array_type,
-                        mz,
-                    )?;
-                }
-                None => {
-                    self.append_data_arrays_none_content(binary_array_type)?;
-                }
-            };
+    fn append_data_arrays(&mut self, data_arrays: Vec<DecodedDataArray>) -> std::io::Result<()> {
+        for (binary_array_type, values) in data_arrays {
+            self.append_data_array(binary_array_type, values)?;
         }
 
         // We may not see a certain array type in the data array list, so if not, append null to main equilength arrays.
@@ -328,6 +275,18 @@ impl MzMLArrayBuilder {
     }
 
     pub fn append(&mut self, record: &Spectrum) -> std::io::Result<()> {
+        let data_arrays = decode_data_arrays(record)?;
+
+        self.append_with_data_arrays(record, data_arrays)
+    }
+
+    /// Append the spectrum with data arrays that were decoded elsewhere, e.g. from an external
+    /// binary file.
+    pub fn append_with_data_arrays(
+        &mut self,
+        record: &Spectrum,
+        data_arrays: Vec<DecodedDataArray>,
+    ) -> std::io::Result<()> {
         self.id.append_value(&record.id);
 
         for cv_param in &record.cv_param {
@@ -359,7 +318,7 @@ impl MzMLArrayBuilder {
         }
         self.cv_params.append(true);
 
-        self.append_data_arrays(record)?;
+        self.append_data_arrays(data_arrays)?;
 
         match &record.precursor_list {
             Some(precursor_list) => {
@@ -367,7 +326,7 @@ impl MzMLArrayBuilder {
                 let selected_ion = &precursor.selected_ion_list.selected_ion[0];
 
                 let selected_ion_mz = selected_ion.cv_param.iter().find_map(|f| {
-                    if f.accession == "MS:1000744" {
+                    if f.accession == SELECTED_ION_MZ {
                         if let Some(value) = &f.value {
                             let string_value = value.to_string();
                             let float_value = string_value.parse::<f64>().unwrap();
@@ -412,6 +371,24 @@ impl MzMLArrayBuilder {
             }
         }
 
+        let rt = record
+            .scan_list
+            .as_ref()
+            .and_then(|scan_list| scan_list.scan.first())
+            .and_then(|scan| {
+                scan.cv_param
+                    .iter()
+                    .find(|cv_param| cv_param.accession == SCAN_START_TIME)
+            })
+            .and_then(|cv_param| {
+                retention_time_in_seconds(
+                    cv_param.value.as_deref()?,
+                    cv_param.unit_accession.as_deref(),
+                )
+            });
+
+        self.rt.append_option(rt);
+
         Ok(())
     }
 
@@ -425,6 +402,7 @@ impl MzMLArrayBuilder {
 
         let precursor_mz = self.precursor_mz.finish();
         let precursor_charge = self.precursor_charge.finish();
+        let rt = self.rt.finish();
 
         vec![
             Arc::new(id),
@@ -434,6 +412,7 @@ impl MzMLArrayBuilder {
             Arc::new(cv_params),
             Arc::new(precursor_mz),
             Arc::new(precursor_charge),
+            Arc::new(rt),
         ]
     }
 }
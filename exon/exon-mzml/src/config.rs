@@ -19,12 +19,23 @@ use object_store::ObjectStore;
 
 use exon_common::{TableSchema, DEFAULT_BATCH_SIZE};
 
+use crate::SpectrumFilter;
+
 pub struct MzMLSchemaBuilder {
     file_fields: Vec<Field>,
     partition_fields: Vec<Field>,
 }
 
 impl MzMLSchemaBuilder {
+    /// Add the x and y pixel coordinates of imzML spectra to the file fields.
+    pub fn with_imzml_fields(mut self) -> Self {
+        self.file_fields.extend([
+            Field::new("x", DataType::Int64, true),
+            Field::new("y", DataType::Int64, true),
+        ]);
+        self
+    }
+
     pub fn add_partition_fields(&mut self, partition_fields: Vec<Field>) {
         self.partition_fields.extend(partition_fields);
     }
@@ -63,6 +74,9 @@ pub struct MzMLConfig {
 
     /// Any projections to apply to the resulting batches.
     pub projection: Option<Vec<usize>>,
+
+    /// A filter on the spectra to read from indexed files.
+    pub spectrum_filter: Option<SpectrumFilter>,
 }
 
 impl MzMLConfig {
@@ -73,6 +87,7 @@ impl MzMLConfig {
             batch_size: DEFAULT_BATCH_SIZE,
             file_schema,
             projection: None,
+            spectrum_filter: None,
         }
     }
 
@@ -87,6 +102,12 @@ impl MzMLConfig {
         self.projection = projection;
         self
     }
+
+    /// Set the spectrum filter.
+    pub fn with_spectrum_filter(mut self, spectrum_filter: Option<SpectrumFilter>) -> Self {
+        self.spectrum_filter = spectrum_filter;
+        self
+    }
 }
 
 fn file_fields() -> Vec<Field> {
@@ -143,5 +164,6 @@ fn file_fields() -> Vec<Field> {
         cv_params_field,
         Field::new("precursor_mz", DataType::Float64, true),
         Field::new("precusor_charge", DataType::Int64, true),
+        Field::new("rt", DataType::Float64, true),
     ]
 }
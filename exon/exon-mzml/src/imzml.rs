@@ -0,0 +1,301 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading imzML files, i.e. mzML metadata with the binary data arrays in an external .ibd file.
+
+use std::{collections::HashMap, ops::Range, sync::Arc};
+
+use arrow::{
+    array::{ArrayRef, Int64Builder},
+    error::ArrowError,
+    record_batch::RecordBatch,
+};
+use object_store::path::Path;
+use tokio::io::AsyncBufRead;
+
+use crate::{
+    array_builder::{data_array_types, DecodedDataArray, MzMLArrayBuilder},
+    config::MzMLConfig,
+    mzml_reader::{
+        binary_conversion::decode_binary_bytes, parser::MzMLReader, CVParam,
+        ReferenceableParamGroupRef, Spectrum,
+    },
+};
+
+const POSITION_X: &str = "IMS:1000050";
+const POSITION_Y: &str = "IMS:1000051";
+const EXTERNAL_OFFSET: &str = "IMS:1000102";
+const EXTERNAL_ENCODED_LENGTH: &str = "IMS:1000104";
+
+/// Get the location of the .ibd file with the binary data of an imzML file, i.e. the same path
+/// with an .ibd extension.
+pub fn ibd_location(imzml_location: &Path) -> Option<Path> {
+    let location = imzml_location.as_ref();
+    let extension_start = location.to_ascii_lowercase().rfind(".imzml")?;
+
+    Path::parse(format!("{}.ibd", &location[..extension_start])).ok()
+}
+
+fn invalid_data(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+fn cv_param_value<'a>(cv_params: &'a [CVParam], accession: &str) -> Option<&'a str> {
+    cv_params
+        .iter()
+        .find(|cv_param| cv_param.accession == accession)
+        .and_then(|cv_param| cv_param.value.as_deref())
+}
+
+/// Add the cvParams of the referenced groups to the element's cvParams.
+fn resolve_param_groups(
+    references: &[ReferenceableParamGroupRef],
+    cv_params: &mut Vec<CVParam>,
+    groups: &HashMap<String, Vec<CVParam>>,
+) -> std::io::Result<()> {
+    for reference in references {
+        let group = groups.get(&reference.reference).ok_or_else(|| {
+            invalid_data(format!(
+                "Unknown referenceable param group {}",
+                reference.reference
+            ))
+        })?;
+
+        cv_params.extend(group.iter().cloned());
+    }
+
+    Ok(())
+}
+
+/// Get the byte range of a binary data array in the .ibd file from its cvParams.
+fn external_range(cv_params: &[CVParam]) -> std::io::Result<Range<usize>> {
+    let value = |accession: &str, name: &str| {
+        cv_param_value(cv_params, accession)
+            .and_then(|value| value.parse::<usize>().ok())
+            .ok_or_else(|| invalid_data(format!("Missing or invalid imzML {name}")))
+    };
+
+    let offset = value(EXTERNAL_OFFSET, "external offset")?;
+    let encoded_length = value(EXTERNAL_ENCODED_LENGTH, "external encoded length")?;
+
+    Ok(offset..offset + encoded_length)
+}
+
+/// The pixel coordinates of the spectrum from its first scan.
+fn position(spectrum: &Spectrum, accession: &str) -> Option<i64> {
+    let scan = spectrum.scan_list.as_ref()?.scan.first()?;
+
+    cv_param_value(&scan.cv_param, accession)?.parse().ok()
+}
+
+/// A builder for the mzML columns plus the pixel coordinates of imzML spectra.
+struct ImzMLArrayBuilder {
+    spectra: MzMLArrayBuilder,
+
+    x: Int64Builder,
+    y: Int64Builder,
+}
+
+impl ImzMLArrayBuilder {
+    fn new() -> Self {
+        Self {
+            spectra: MzMLArrayBuilder::new(),
+            x: Int64Builder::new(),
+            y: Int64Builder::new(),
+        }
+    }
+
+    fn append(
+        &mut self,
+        spectrum: &Spectrum,
+        data_arrays: Vec<DecodedDataArray>,
+    ) -> std::io::Result<()> {
+        self.spectra
+            .append_with_data_arrays(spectrum, data_arrays)?;
+
+        self.x.append_option(position(spectrum, POSITION_X));
+        self.y.append_option(position(spectrum, POSITION_Y));
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        let mut arrays = self.spectra.finish();
+
+        arrays.push(Arc::new(self.x.finish()));
+        arrays.push(Arc::new(self.y.finish()));
+
+        arrays
+    }
+}
+
+/// A reader for imzML files that reads in batches, fetching the binary data arrays of each batch
+/// from the .ibd file.
+pub struct ImzMLBatchReader<R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    /// The underlying reader of the imzML metadata.
+    reader: MzMLReader<R>,
+
+    /// The configuration for this reader.
+    config: Arc<MzMLConfig>,
+
+    /// The location of the .ibd file in the config's object store.
+    ibd_location: Path,
+}
+
+impl<R> ImzMLBatchReader<R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    pub fn new(reader: R, config: Arc<MzMLConfig>, ibd_location: Path) -> Self {
+        let reader = MzMLReader::from_reader(reader);
+
+        Self {
+            reader,
+            config,
+            ibd_location,
+        }
+    }
+
+    pub fn into_stream(self) -> impl futures::Stream<Item = Result<RecordBatch, ArrowError>> {
+        futures::stream::unfold(self, |mut reader| async move {
+            match reader.read_batch().await {
+                Ok(Some(batch)) => Some((Ok(batch), reader)),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), reader)),
+            }
+        })
+    }
+
+    /// Read the next spectrum with the cvParams of its referenced groups resolved.
+    async fn read_spectrum(&mut self) -> std::io::Result<Option<Spectrum>> {
+        let Some(mut spectrum) = self.reader.read_spectrum().await? else {
+            return Ok(None);
+        };
+
+        let groups = self.reader.referenceable_param_groups();
+
+        resolve_param_groups(
+            &spectrum.referenceable_param_group_ref,
+            &mut spectrum.cv_param,
+            groups,
+        )?;
+
+        if let Some(scan_list) = spectrum.scan_list.as_mut() {
+            for scan in scan_list.scan.iter_mut() {
+                resolve_param_groups(
+                    &scan.referenceable_param_group_ref,
+                    &mut scan.cv_param,
+                    groups,
+                )?;
+            }
+        }
+
+        for data_array in spectrum.binary_data_array_list.binary_data_array.iter_mut() {
+            resolve_param_groups(
+                &data_array.referenceable_param_group_ref,
+                &mut data_array.cv_param,
+                groups,
+            )?;
+        }
+
+        Ok(Some(spectrum))
+    }
+
+    pub async fn read_batch(&mut self) -> Result<Option<RecordBatch>, ArrowError> {
+        let mut spectra = Vec::new();
+
+        for _ in 0..self.config.batch_size {
+            match self.read_spectrum().await? {
+                Some(spectrum) => spectra.push(spectrum),
+                None => break,
+            }
+        }
+
+        if spectra.is_empty() {
+            return Ok(None);
+        }
+
+        let ranges = spectra
+            .iter()
+            .flat_map(|spectrum| &spectrum.binary_data_array_list.binary_data_array)
+            .map(|data_array| external_range(&data_array.cv_param))
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        let mut data = self
+            .config
+            .object_store
+            .get_ranges(&self.ibd_location, &ranges)
+            .await
+            .map_err(|e| ArrowError::ExternalError(Box::new(e)))?
+            .into_iter();
+
+        let mut array_builder = ImzMLArrayBuilder::new();
+
+        for spectrum in &spectra {
+            let data_arrays = spectrum
+                .binary_data_array_list
+                .binary_data_array
+                .iter()
+                .zip(data.by_ref())
+                .map(|(data_array, bytes)| {
+                    let (binary_array_type, compression_type, data_type) =
+                        data_array_types(&data_array.cv_param)?;
+
+                    let (Some(compression_type), Some(data_type)) = (compression_type, data_type)
+                    else {
+                        return Err(invalid_data(format!(
+                            "No compression or data type found for spectrum {}",
+                            spectrum.id
+                        )));
+                    };
+
+                    let values =
+                        decode_binary_bytes(bytes.to_vec(), &compression_type, &data_type)?;
+
+                    Ok((binary_array_type, Some(values)))
+                })
+                .collect::<std::io::Result<Vec<_>>>()?;
+
+            array_builder.append(spectrum, data_arrays)?;
+        }
+
+        let batch = RecordBatch::try_new(self.config.file_schema.clone(), array_builder.finish())?;
+
+        match &self.config.projection {
+            Some(projection) => Ok(Some(batch.project(projection)?)),
+            None => Ok(Some(batch)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::path::Path;
+
+    use super::ibd_location;
+
+    #[test]
+    fn test_ibd_location() {
+        let location = Path::from("data/sample.imzML");
+        assert_eq!(ibd_location(&location), Some(Path::from("data/sample.ibd")));
+
+        let location = Path::from("data/sample.imzml.gz");
+        assert_eq!(ibd_location(&location), Some(Path::from("data/sample.ibd")));
+
+        assert!(ibd_location(&Path::from("data/sample.mzML")).is_none());
+    }
+}
@@ -17,7 +17,11 @@ pub mod mzml_reader;
 mod array_builder;
 mod batch_reader;
 mod config;
+mod imzml;
+mod spectrum_filter;
 
 pub use batch_reader::BatchReader;
 pub use config::MzMLConfig;
 pub use config::MzMLSchemaBuilder;
+pub use imzml::{ibd_location, ImzMLBatchReader};
+pub use spectrum_filter::{SpectrumFilter, SpectrumSummary};
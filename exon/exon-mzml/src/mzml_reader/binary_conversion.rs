@@ -42,6 +42,15 @@ pub fn decode_binary_array(
         .decode(decoded)
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
 
+    decode_binary_bytes(decoded, ct, dt)
+}
+
+/// Convert the raw bytes of a binary array, e.g. from an imzML .ibd file, into floats.
+pub fn decode_binary_bytes(
+    decoded: Vec<u8>,
+    ct: &CompressionType,
+    dt: &DataType,
+) -> std::io::Result<Vec<f64>> {
     match (ct, dt) {
         (CompressionType::NoCompression, DataType::Float32Bit) => {
             Ok(binary_string_to_array_f32(decoded))
@@ -0,0 +1,140 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading the spectrum offsets of indexedmzML files.
+
+use std::ops::Range;
+
+use quick_xml::events::Event;
+
+/// The number of bytes at the end of an indexedmzML file to search for the `<indexListOffset>`.
+pub const INDEX_LIST_OFFSET_TAIL_SIZE: u64 = 1024;
+
+/// Find the offset of the `<indexList>` in the tail of an indexedmzML file.
+pub fn parse_index_list_offset(tail: &[u8]) -> Option<u64> {
+    let start_tag = b"<indexListOffset>";
+
+    let start = tail
+        .windows(start_tag.len())
+        .rposition(|w| w == start_tag)?
+        + start_tag.len();
+    let end = start + tail[start..].iter().position(|b| *b == b'<')?;
+
+    std::str::from_utf8(&tail[start..end])
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Get the byte range of each spectrum from the `<indexList>` starting at `index_list_offset`.
+///
+/// A spectrum ends where the next indexed element or the index list starts, so the range may
+/// include closing tags after the `</spectrum>`. Returns `None` if the index list can't be read
+/// or an offset is past the index list.
+pub fn parse_spectrum_ranges(index_list: &[u8], index_list_offset: u64) -> Option<Vec<Range<u64>>> {
+    let mut reader = quick_xml::Reader::from_reader(index_list);
+    let mut buf = Vec::new();
+
+    let mut index_name = None;
+    let mut in_offset = false;
+
+    let mut spectrum_offsets = Vec::new();
+    let mut offsets = vec![index_list_offset];
+
+    loop {
+        match reader.read_event_into(&mut buf).ok()? {
+            Event::Start(e) if e.local_name().as_ref() == b"index" => {
+                index_name = e
+                    .try_get_attribute("name")
+                    .ok()
+                    .flatten()
+                    .map(|a| a.value.into_owned());
+            }
+            Event::End(e) if e.local_name().as_ref() == b"index" => {
+                index_name = None;
+            }
+            Event::Start(e) if e.local_name().as_ref() == b"offset" => {
+                in_offset = true;
+            }
+            Event::End(e) if e.local_name().as_ref() == b"offset" => {
+                in_offset = false;
+            }
+            Event::Text(text) if in_offset => {
+                let offset = text.unescape().ok()?.trim().parse::<u64>().ok()?;
+
+                if offset >= index_list_offset {
+                    return None;
+                }
+
+                if index_name.as_deref() == Some(b"spectrum".as_slice()) {
+                    spectrum_offsets.push(offset);
+                }
+
+                offsets.push(offset);
+            }
+            Event::End(e) if e.local_name().as_ref() == b"indexList" => break,
+            Event::Eof => return None,
+            _ => {}
+        }
+
+        buf.clear();
+    }
+
+    offsets.sort_unstable();
+
+    let ranges = spectrum_offsets
+        .into_iter()
+        .map(|start| {
+            let next = offsets.partition_point(|offset| *offset <= start);
+            start..offsets[next]
+        })
+        .collect();
+
+    Some(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spectrum_ranges() {
+        let tail = br#"  </mzML>
+  <indexList count="2">
+    <index name="spectrum">
+      <offset idRef="scan=1">100</offset>
+      <offset idRef="scan=2">250</offset>
+    </index>
+    <index name="chromatogram">
+      <offset idRef="TIC">400</offset>
+    </index>
+  </indexList>
+  <indexListOffset>500</indexListOffset>
+  <fileChecksum>f95166c3d093f46cf87cc14f3925304bc8b813c3</fileChecksum>
+</indexedmzML>"#;
+
+        let index_list_offset = parse_index_list_offset(tail).unwrap();
+        assert_eq!(index_list_offset, 500);
+
+        let index_list_start = tail.windows(10).position(|w| w == b"<indexList").unwrap();
+        let ranges = parse_spectrum_ranges(&tail[index_list_start..], index_list_offset).unwrap();
+
+        assert_eq!(ranges, vec![100..250, 250..400]);
+
+        // Offsets past the index list mean the index is stale.
+        assert!(parse_spectrum_ranges(&tail[index_list_start..], 300).is_none());
+        assert!(parse_index_list_offset(b"</mzML>").is_none());
+    }
+}
@@ -14,6 +14,8 @@
 
 pub mod binary_conversion;
 
+pub mod index;
+
 pub mod parser;
 mod types;
 
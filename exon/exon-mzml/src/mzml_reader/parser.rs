@@ -12,16 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use quick_xml::events::Event;
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::{self, DeError};
 use tokio::io::AsyncBufRead;
 
+use std::collections::HashMap;
 use std::io::Cursor;
 
-use super::types::Spectrum;
+use super::types::{CVParam, ReferenceableParamGroup, Spectrum};
 
 pub struct MzMLReader<R: AsyncBufRead> {
     reader: quick_xml::Reader<R>,
+
+    /// The cvParams of the referenceable param groups seen so far, by group id.
+    referenceable_param_groups: HashMap<String, Vec<CVParam>>,
 }
 
 impl<R> MzMLReader<R>
@@ -30,7 +34,10 @@ where
 {
     // new creates a new MzMLReader from an quick_xml::Reader
     pub fn new(reader: quick_xml::Reader<R>) -> Self {
-        Self { reader }
+        Self {
+            reader,
+            referenceable_param_groups: HashMap::new(),
+        }
     }
 
     pub fn from_reader(buf_reader: R) -> Self {
@@ -40,11 +47,79 @@ where
         Self::new(xml_reader)
     }
 
+    /// The cvParams of the referenceable param groups, which precede the spectra in the file.
+    pub(crate) fn referenceable_param_groups(&self) -> &HashMap<String, Vec<CVParam>> {
+        &self.referenceable_param_groups
+    }
+
+    /// Read the element that starts with the given tag and its children into a new buffer.
+    async fn read_element(&mut self, start: BytesStart<'_>) -> std::io::Result<Vec<u8>> {
+        let end = start.name().as_ref().to_vec();
+
+        let mut buf = Vec::new();
+
+        let mut inner_buf = Vec::new();
+        let mut writer = quick_xml::Writer::new(Cursor::new(&mut inner_buf));
+
+        writer.write_event(Event::Start(start)).unwrap();
+
+        loop {
+            match self.reader.read_event_into_async(&mut buf).await {
+                Ok(Event::Start(e)) => {
+                    writer.write_event(Event::Start(e)).unwrap();
+                }
+                Ok(Event::Empty(e)) => {
+                    writer.write_event(Event::Empty(e)).unwrap();
+                }
+                Ok(Event::Text(e)) => {
+                    writer.write_event(Event::Text(e)).unwrap();
+                }
+                Ok(Event::End(e)) => {
+                    if e.name().as_ref() == end.as_slice() {
+                        writer.write_event(Event::End(e)).unwrap();
+                        break;
+                    } else {
+                        writer.write_event(Event::End(e)).unwrap();
+                    }
+                }
+                Ok(Event::Eof) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        "Unexpected Eof Event",
+                    ))
+                }
+                Err(_) => {
+                    panic!("Error at position: {}", self.reader.buffer_position())
+                }
+                Ok(e) => panic!("event: {e:?}"),
+            }
+
+            buf.clear();
+        }
+
+        Ok(inner_buf)
+    }
+
     pub async fn read_spectrum(&mut self) -> std::io::Result<Option<Spectrum>> {
         let mut outer_buf = Vec::new();
 
         loop {
             match self.reader.read_event_into_async(&mut outer_buf).await {
+                // Keep the referenceable param groups so spectra can resolve their references
+                Ok(Event::Start(e))
+                    if e.name() == quick_xml::name::QName(b"referenceableParamGroup") =>
+                {
+                    let inner_buf = self.read_element(e.into_owned()).await?;
+
+                    let group: ReferenceableParamGroup =
+                        quick_xml::de::from_reader(Cursor::new(inner_buf))
+                            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+                    self.referenceable_param_groups
+                        .insert(group.id, group.cv_param);
+
+                    outer_buf.clear();
+                }
                 // Continue if the event is not the start of a spectrum tag
                 Ok(Event::Start(e)) if e.name() != quick_xml::name::QName(b"spectrum") => {
                     continue;
@@ -52,46 +127,9 @@ where
                 // The start of the spectrum tag has been found, this section extracts spectrum tag and its children
                 // into a new buffer, then deserializes the spectrum tag into a Spectrum struct
                 Ok(Event::Start(e)) => {
-                    let end = b"spectrum";
-
-                    let mut inner_buf = Vec::new();
-                    let mut writer = quick_xml::Writer::new(Cursor::new(&mut inner_buf));
-
-                    writer.write_event(Event::Start(e)).unwrap();
-
-                    loop {
-                        match self.reader.read_event_into_async(&mut outer_buf).await {
-                            Ok(Event::Start(e)) => {
-                                writer.write_event(Event::Start(e)).unwrap();
-                            }
-                            Ok(Event::Empty(e)) => {
-                                writer.write_event(Event::Empty(e)).unwrap();
-                            }
-                            Ok(Event::Text(e)) => {
-                                writer.write_event(Event::Text(e)).unwrap();
-                            }
-                            Ok(Event::End(e)) => {
-                                if e.name() == quick_xml::name::QName(end) {
-                                    writer.write_event(Event::End(e)).unwrap();
-                                    break;
-                                } else {
-                                    writer.write_event(Event::End(e)).unwrap();
-                                }
-                            }
-                            Ok(Event::Eof) => {
-                                return Err(std::io::Error::new(
-                                    std::io::ErrorKind::Other,
-                                    "Unexpected Eof Event",
-                                ))
-                            }
-                            Err(_) => {
-                                panic!("Error at position: {}", self.reader.buffer_position())
-                            }
-                            Ok(e) => panic!("event: {e:?}"),
-                        }
-                    }
+                    let inner_buf = self.read_element(e.into_owned()).await?;
 
-                    let c = Cursor::new(inner_buf.clone());
+                    let c = Cursor::new(inner_buf);
 
                     let spectrum: Result<Spectrum, DeError> = quick_xml::de::from_reader(c);
 
@@ -100,7 +138,7 @@ where
                 Ok(Event::Eof) => {
                     return Ok(None);
                 }
-                Err(e) => println!("{e:?}"),
+                Err(e) => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
                 _ => {
                     outer_buf.clear();
                 }
@@ -24,7 +24,7 @@ use std::io::Cursor;
 
 use super::binary_conversion;
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct CVParam {
     #[serde(rename = "@cvRef")]
     pub cv_ref: String,
@@ -80,6 +80,24 @@ impl CVParam {
     }
 }
 
+/// A reference to a group of cvParams defined in the referenceableParamGroupList.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct ReferenceableParamGroupRef {
+    #[serde(rename = "@ref")]
+    pub reference: String,
+}
+
+/// A group of cvParams that elements can include by reference.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceableParamGroup {
+    #[serde(rename = "@id")]
+    pub id: String,
+
+    #[serde(default)]
+    pub cv_param: CVVector,
+}
+
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct UserParam {
@@ -105,6 +123,9 @@ pub struct ScanWindowList {
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Scan {
+    #[serde(default)]
+    pub referenceable_param_group_ref: Vec<ReferenceableParamGroupRef>,
+    #[serde(default)]
     pub cv_param: CVVector,
     pub scan_window_list: Option<ScanWindowList>,
 }
@@ -112,6 +133,7 @@ pub struct Scan {
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ScanList {
+    #[serde(default)]
     pub cv_param: CVVector,
     pub scan: Vec<Scan>,
 }
@@ -158,6 +180,9 @@ impl Binary {
 pub struct BinaryDataArray {
     #[serde(rename = "@encodedLength")]
     pub encoded_length: String,
+    #[serde(default)]
+    pub referenceable_param_group_ref: Vec<ReferenceableParamGroupRef>,
+    #[serde(default)]
     pub cv_param: CVVector,
     pub binary: Binary,
 }
@@ -383,6 +408,10 @@ pub struct PrecursorList {
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Spectrum {
+    #[serde(default)]
+    pub referenceable_param_group_ref: Vec<ReferenceableParamGroupRef>,
+
+    #[serde(default)]
     pub cv_param: CVVector,
 
     #[serde(rename = "@index")]
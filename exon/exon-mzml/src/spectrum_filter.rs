@@ -0,0 +1,219 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use quick_xml::events::{BytesStart, Event};
+
+/// The accession of the scan start time cvParam.
+pub(crate) const SCAN_START_TIME: &str = "MS:1000016";
+
+/// The accession of the selected ion m/z cvParam.
+pub(crate) const SELECTED_ION_MZ: &str = "MS:1000744";
+
+const UNIT_MINUTE: &str = "UO:0000031";
+const UNIT_HOUR: &str = "UO:0000032";
+
+/// Convert a scan start time to seconds given its unit accession, which defaults to seconds.
+pub(crate) fn retention_time_in_seconds(value: &str, unit_accession: Option<&str>) -> Option<f64> {
+    let value = value.parse::<f64>().ok()?;
+
+    match unit_accession {
+        Some(UNIT_MINUTE) => Some(value * 60.0),
+        Some(UNIT_HOUR) => Some(value * 3600.0),
+        _ => Some(value),
+    }
+}
+
+/// The values of a spectrum that a [`SpectrumFilter`] checks.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpectrumSummary {
+    /// The retention time of the first scan in seconds.
+    pub rt: Option<f64>,
+
+    /// The m/z of the first selected ion of the first precursor.
+    pub precursor_mz: Option<f64>,
+}
+
+impl SpectrumSummary {
+    /// Read the summary from the start of a `<spectrum>` element, i.e. everything before its
+    /// `<binaryDataArrayList>`, or `None` if the bytes end before the binary data arrays.
+    pub fn from_prefix(prefix: &[u8]) -> Option<Self> {
+        let mut reader = quick_xml::Reader::from_reader(prefix);
+        let mut buf = Vec::new();
+
+        let mut summary = SpectrumSummary::default();
+
+        loop {
+            match reader.read_event_into(&mut buf).ok()? {
+                Event::Start(e) if e.local_name().as_ref() == b"binaryDataArrayList" => {
+                    return Some(summary);
+                }
+                Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"cvParam" => {
+                    summary.update(&e);
+                }
+                Event::Eof => return None,
+                _ => {}
+            }
+
+            buf.clear();
+        }
+    }
+
+    fn update(&mut self, cv_param: &BytesStart) {
+        let attribute = |name: &str| {
+            cv_param
+                .try_get_attribute(name)
+                .ok()
+                .flatten()
+                .and_then(|a| a.unescape_value().ok())
+        };
+
+        let Some(accession) = attribute("accession") else {
+            return;
+        };
+
+        match accession.as_ref() {
+            SCAN_START_TIME if self.rt.is_none() => {
+                let unit_accession = attribute("unitAccession");
+
+                self.rt = attribute("value")
+                    .and_then(|value| retention_time_in_seconds(&value, unit_accession.as_deref()));
+            }
+            SELECTED_ION_MZ if self.precursor_mz.is_none() => {
+                self.precursor_mz = attribute("value").and_then(|value| value.parse().ok());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Inclusive bounds on the retention time and precursor m/z of the spectra to read.
+///
+/// Spectra without a retention time or precursor m/z don't match a filter that bounds it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpectrumFilter {
+    rt: Option<(f64, f64)>,
+    precursor_mz: Option<(f64, f64)>,
+}
+
+fn intersect(bounds: Option<(f64, f64)>, min: f64, max: f64) -> Option<(f64, f64)> {
+    match bounds {
+        Some((current_min, current_max)) => Some((current_min.max(min), current_max.min(max))),
+        None => Some((min, max)),
+    }
+}
+
+fn within(bounds: Option<(f64, f64)>, value: Option<f64>) -> bool {
+    match (bounds, value) {
+        (None, _) => true,
+        (Some((min, max)), Some(value)) => min <= value && value <= max,
+        (Some(_), None) => false,
+    }
+}
+
+impl SpectrumFilter {
+    /// Bound the retention time, in seconds.
+    pub fn with_rt_bounds(self, min: f64, max: f64) -> Self {
+        Self {
+            rt: intersect(self.rt, min, max),
+            ..self
+        }
+    }
+
+    /// Bound the precursor m/z.
+    pub fn with_precursor_mz_bounds(self, min: f64, max: f64) -> Self {
+        Self {
+            precursor_mz: intersect(self.precursor_mz, min, max),
+            ..self
+        }
+    }
+
+    /// Combine two filters so a spectrum must match both.
+    pub fn and(self, other: Self) -> Self {
+        let mut filter = self;
+
+        if let Some((min, max)) = other.rt {
+            filter = filter.with_rt_bounds(min, max);
+        }
+
+        if let Some((min, max)) = other.precursor_mz {
+            filter = filter.with_precursor_mz_bounds(min, max);
+        }
+
+        filter
+    }
+
+    /// Check if the spectrum with the summary matches the filter.
+    pub fn matches(&self, summary: &SpectrumSummary) -> bool {
+        within(self.rt, summary.rt) && within(self.precursor_mz, summary.precursor_mz)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_from_prefix() {
+        let prefix = br#"<spectrum index="1" id="scan=2" defaultArrayLength="3">
+          <scanList count="1">
+            <scan>
+              <cvParam cvRef="MS" accession="MS:1000016" name="scan start time" value="1.5" unitCvRef="UO" unitAccession="UO:0000031" unitName="minute"/>
+            </scan>
+          </scanList>
+          <precursorList count="1">
+            <precursor>
+              <selectedIonList count="1">
+                <selectedIon>
+                  <cvParam cvRef="MS" accession="MS:1000744" name="selected ion m/z" value="445.3"/>
+                </selectedIon>
+              </selectedIonList>
+            </precursor>
+          </precursorList>
+          <binaryDataArrayList count="2">
+            <binaryDataArray encodedLength="48">"#;
+
+        let summary = SpectrumSummary::from_prefix(prefix).unwrap();
+
+        assert_eq!(
+            summary,
+            SpectrumSummary {
+                rt: Some(90.0),
+                precursor_mz: Some(445.3),
+            }
+        );
+
+        // The prefix has to reach the binary data arrays.
+        assert!(SpectrumSummary::from_prefix(&prefix[..200]).is_none());
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let summary = SpectrumSummary {
+            rt: Some(90.0),
+            precursor_mz: None,
+        };
+
+        assert!(SpectrumFilter::default().matches(&summary));
+
+        let filter = SpectrumFilter::default()
+            .with_rt_bounds(60.0, f64::INFINITY)
+            .and(SpectrumFilter::default().with_rt_bounds(f64::NEG_INFINITY, 90.0));
+        assert!(filter.matches(&summary));
+
+        assert!(!filter.with_rt_bounds(91.0, 100.0).matches(&summary));
+        assert!(!SpectrumFilter::default()
+            .with_precursor_mz_bounds(400.0, 500.0)
+            .matches(&summary));
+    }
+}
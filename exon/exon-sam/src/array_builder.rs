@@ -15,7 +15,7 @@
 use std::sync::Arc;
 
 use arrow::{
-    array::{ArrayRef, GenericListBuilder, GenericStringBuilder, Int32Builder, Int64Builder},
+    array::{ArrayRef, GenericStringBuilder, Int64Builder},
     error::ArrowError,
     error::Result,
 };
@@ -26,20 +26,23 @@ use noodles::sam::alignment::{
 };
 use noodles::sam::Header;
 
-use crate::{SAMConfig, TagsBuilder};
+use crate::{
+    has_compact_types, FlagsBuilder, MappingQualitiesBuilder, QualityScoresBuilder, SAMConfig,
+    TagsBuilder,
+};
 
 /// Builds an vector of arrays from a SAM file.
 pub struct SAMArrayBuilder {
     names: GenericStringBuilder<i32>,
-    flags: Int32Builder,
+    flags: FlagsBuilder,
     references: GenericStringBuilder<i32>,
     starts: Int64Builder,
     ends: Int64Builder,
-    mapping_qualities: GenericStringBuilder<i32>,
+    mapping_qualities: MappingQualitiesBuilder,
     cigar: GenericStringBuilder<i32>,
     mate_references: GenericStringBuilder<i32>,
     sequences: GenericStringBuilder<i32>,
-    quality_scores: GenericListBuilder<i32, Int64Builder>,
+    quality_scores: QualityScoresBuilder,
 
     tags: TagsBuilder,
 
@@ -62,19 +65,19 @@ impl SAMArrayBuilder {
 
         let projection = sam_config.projection();
 
-        let quality_scores = GenericListBuilder::<i32, Int64Builder>::new(Int64Builder::new());
+        let compact = has_compact_types(&sam_config.file_schema);
 
         Self {
             names: GenericStringBuilder::<i32>::new(),
-            flags: Int32Builder::new(),
+            flags: FlagsBuilder::new(compact),
             references: GenericStringBuilder::<i32>::new(),
             starts: Int64Builder::new(),
             ends: Int64Builder::new(),
-            mapping_qualities: GenericStringBuilder::<i32>::new(),
+            mapping_qualities: MappingQualitiesBuilder::new(compact),
             cigar: GenericStringBuilder::<i32>::new(),
             mate_references: GenericStringBuilder::<i32>::new(),
             sequences: GenericStringBuilder::<i32>::new(),
-            quality_scores,
+            quality_scores: QualityScoresBuilder::new(compact),
 
             tags: tags_builder,
 
@@ -109,8 +112,7 @@ impl SAMArrayBuilder {
                     }
                 }
                 1 => {
-                    let flag_bits = record.flags().bits();
-                    self.flags.append_value(flag_bits as i32);
+                    self.flags.append_value(record.flags().bits());
                 }
                 2 => {
                     let reference_name = match record.reference_sequence(&self.header) {
@@ -130,7 +132,7 @@ impl SAMArrayBuilder {
                 }
                 5 => {
                     self.mapping_qualities
-                        .append_option(record.mapping_quality().map(|v| v.get().to_string()));
+                        .append_option(record.mapping_quality().map(|v| v.get()));
                 }
                 6 => {
                     let mut cigar_to_print = Vec::new();
@@ -171,17 +173,7 @@ impl SAMArrayBuilder {
                 }
                 9 => {
                     let quality_scores = record.quality_scores().as_ref();
-                    let slice_i8: &[i8] = unsafe {
-                        std::slice::from_raw_parts(
-                            quality_scores.as_ptr() as *const i8,
-                            quality_scores.len(),
-                        )
-                    };
-
-                    let slice_i64 = slice_i8.iter().map(|v| *v as i64).collect::<Vec<_>>();
-
-                    self.quality_scores.values().append_slice(&slice_i64);
-                    self.quality_scores.append(true);
+                    self.quality_scores.append(quality_scores);
                 }
                 10 => {
                     // This is _very_ similar to BAM, may not need body any more
@@ -209,15 +201,15 @@ impl SAMArrayBuilder {
         for col_idx in self.projection.iter() {
             match col_idx {
                 0 => arrays.push(Arc::new(self.names.finish())),
-                1 => arrays.push(Arc::new(self.flags.finish())),
+                1 => arrays.push(self.flags.finish()),
                 2 => arrays.push(Arc::new(self.references.finish())),
                 3 => arrays.push(Arc::new(self.starts.finish())),
                 4 => arrays.push(Arc::new(self.ends.finish())),
-                5 => arrays.push(Arc::new(self.mapping_qualities.finish())),
+                5 => arrays.push(self.mapping_qualities.finish()),
                 6 => arrays.push(Arc::new(self.cigar.finish())),
                 7 => arrays.push(Arc::new(self.mate_references.finish())),
                 8 => arrays.push(Arc::new(self.sequences.finish())),
-                9 => arrays.push(Arc::new(self.quality_scores.finish())),
+                9 => arrays.push(self.quality_scores.finish()),
                 10 => arrays.push(Arc::new(self.tags.finish())),
                 _ => panic!("Invalid column index {} for SAM", col_idx),
             }
@@ -16,7 +16,7 @@ use std::sync::Arc;
 
 use arrow::{error::ArrowError, record_batch::RecordBatch};
 
-use exon_common::ExonArrayBuilder;
+use exon_common::{BoundedReader, ExonArrayBuilder, ReaderLimit};
 use futures::Stream;
 use noodles::sam::alignment::RecordBuf;
 use tokio::io::{AsyncBufRead, AsyncRead};
@@ -29,7 +29,7 @@ where
     R: AsyncRead,
 {
     /// The underlying SAM reader.
-    reader: noodles::sam::AsyncReader<R>,
+    reader: noodles::sam::AsyncReader<BoundedReader<R>>,
 
     /// The configuration for this reader.
     config: Arc<SAMConfig>,
@@ -43,9 +43,15 @@ where
     R: AsyncBufRead + Unpin + Send + AsyncRead,
 {
     pub async fn new(inner: R, config: Arc<SAMConfig>) -> std::io::Result<Self> {
+        let limits = config.reader_limits;
+
+        let mut inner = BoundedReader::new(inner, &limits);
+        inner.start_budget(ReaderLimit::HeaderSize, limits.max_header_size);
+
         let mut reader = noodles::sam::AsyncReader::new(inner);
 
         let header = reader.read_header().await?;
+        reader.get_mut().end_budget();
 
         Ok(Self {
             reader,
@@ -73,7 +79,13 @@ where
             .await?
         {
             0 => Ok(None),
-            _ => Ok(Some(record)),
+            _ => {
+                let limits = &self.config.reader_limits;
+                limits.check(ReaderLimit::SequenceLength, record.sequence().len())?;
+                limits.check(ReaderLimit::AttributeCount, record.data().len())?;
+
+                Ok(Some(record))
+            }
         }
     }
 
@@ -0,0 +1,215 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use arrow::{
+    array::{
+        ArrayRef, GenericListBuilder, GenericStringBuilder, Int32Builder, Int64Builder,
+        UInt16Builder, UInt8Builder,
+    },
+    datatypes::{DataType, Schema},
+};
+use exon_common::{append_quality_scores, append_raw_quality_scores};
+
+/// Whether the schema has the compact flag, mapping quality, and quality score columns, see
+/// [`crate::SAMSchemaBuilder::with_compact_types`].
+pub fn has_compact_types(schema: &Schema) -> bool {
+    schema
+        .field_with_name("flag")
+        .is_ok_and(|field| field.data_type() == &DataType::UInt16)
+}
+
+/// Builds the flag column, either as Int32 or, with compact types, UInt16.
+pub enum FlagsBuilder {
+    /// The default Int32 flags.
+    Int32(Int32Builder),
+
+    /// The compact UInt16 flags.
+    UInt16(UInt16Builder),
+}
+
+impl FlagsBuilder {
+    /// Creates a new flags builder.
+    pub fn new(compact: bool) -> Self {
+        if compact {
+            Self::UInt16(UInt16Builder::new())
+        } else {
+            Self::Int32(Int32Builder::new())
+        }
+    }
+
+    /// Appends the flag bits of a record.
+    pub fn append_value(&mut self, flags: u16) {
+        match self {
+            Self::Int32(builder) => builder.append_value(i32::from(flags)),
+            Self::UInt16(builder) => builder.append_value(flags),
+        }
+    }
+
+    /// Finishes the builder and returns the array.
+    pub fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::Int32(builder) => Arc::new(builder.finish()),
+            Self::UInt16(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// Builds the mapping quality column, either as Utf8 or, with compact types, UInt8.
+pub enum MappingQualitiesBuilder {
+    /// The default mapping qualities as strings.
+    Utf8(GenericStringBuilder<i32>),
+
+    /// The compact UInt8 mapping qualities.
+    UInt8(UInt8Builder),
+}
+
+impl MappingQualitiesBuilder {
+    /// Creates a new mapping qualities builder.
+    pub fn new(compact: bool) -> Self {
+        if compact {
+            Self::UInt8(UInt8Builder::new())
+        } else {
+            Self::Utf8(GenericStringBuilder::new())
+        }
+    }
+
+    /// Appends the mapping quality of a record, which is null if it's missing.
+    pub fn append_option(&mut self, mapping_quality: Option<u8>) {
+        match self {
+            Self::Utf8(builder) => builder.append_option(mapping_quality.map(|v| v.to_string())),
+            Self::UInt8(builder) => builder.append_option(mapping_quality),
+        }
+    }
+
+    /// Finishes the builder and returns the array.
+    pub fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::Utf8(builder) => Arc::new(builder.finish()),
+            Self::UInt8(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// Builds the quality score column, either as a list of Int64 or, with compact types, UInt8.
+pub enum QualityScoresBuilder {
+    /// The default Int64 lists, where a missing 0xFF score is -1.
+    Int64(GenericListBuilder<i32, Int64Builder>),
+
+    /// The compact UInt8 lists of the raw scores.
+    UInt8(GenericListBuilder<i32, UInt8Builder>),
+}
+
+impl QualityScoresBuilder {
+    /// Creates a new quality scores builder.
+    pub fn new(compact: bool) -> Self {
+        if compact {
+            Self::UInt8(GenericListBuilder::new(UInt8Builder::new()))
+        } else {
+            Self::Int64(GenericListBuilder::new(Int64Builder::new()))
+        }
+    }
+
+    /// Appends the raw quality scores of a record as one list.
+    pub fn append(&mut self, scores: &[u8]) {
+        match self {
+            Self::Int64(builder) => append_quality_scores(builder, scores),
+            Self::UInt8(builder) => append_raw_quality_scores(builder, scores),
+        }
+    }
+
+    /// Finishes the builder and returns the array.
+    pub fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::Int64(builder) => Arc::new(builder.finish()),
+            Self::UInt8(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{Array, AsArray},
+        datatypes::{Int32Type, Int64Type, UInt16Type, UInt8Type},
+    };
+
+    use crate::SAMSchemaBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_has_compact_types() -> Result<(), Box<dyn std::error::Error>> {
+        let schema = SAMSchemaBuilder::default().build();
+        assert!(!has_compact_types(&schema.file_schema()?));
+
+        let schema = SAMSchemaBuilder::default().with_compact_types().build();
+        assert!(has_compact_types(&schema.file_schema()?));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_builders() {
+        let mut flags = FlagsBuilder::new(true);
+        flags.append_value(0xf00);
+        assert_eq!(flags.finish().as_primitive::<UInt16Type>().value(0), 0xf00);
+
+        let mut mapping_qualities = MappingQualitiesBuilder::new(true);
+        mapping_qualities.append_option(Some(60));
+        mapping_qualities.append_option(None);
+
+        let mapping_qualities = mapping_qualities.finish();
+        let mapping_qualities = mapping_qualities.as_primitive::<UInt8Type>();
+        assert_eq!(mapping_qualities.value(0), 60);
+        assert!(mapping_qualities.is_null(1));
+
+        let mut quality_scores = QualityScoresBuilder::new(true);
+        quality_scores.append(&[30, 40]);
+
+        let quality_scores = quality_scores.finish();
+        assert_eq!(
+            quality_scores
+                .as_list::<i32>()
+                .value(0)
+                .as_primitive::<UInt8Type>()
+                .values(),
+            &[30, 40]
+        );
+    }
+
+    #[test]
+    fn test_default_builders() {
+        let mut flags = FlagsBuilder::new(false);
+        flags.append_value(0xf00);
+        assert_eq!(flags.finish().as_primitive::<Int32Type>().value(0), 0xf00);
+
+        let mut mapping_qualities = MappingQualitiesBuilder::new(false);
+        mapping_qualities.append_option(Some(60));
+        assert_eq!(mapping_qualities.finish().as_string::<i32>().value(0), "60");
+
+        let mut quality_scores = QualityScoresBuilder::new(false);
+        quality_scores.append(&[30, 0xff]);
+
+        let quality_scores = quality_scores.finish();
+        assert_eq!(
+            quality_scores
+                .as_list::<i32>()
+                .value(0)
+                .as_primitive::<Int64Type>()
+                .values(),
+            &[30, -1]
+        );
+    }
+}
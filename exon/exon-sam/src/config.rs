@@ -16,7 +16,7 @@ use std::sync::Arc;
 
 use arrow::datatypes::SchemaRef;
 use arrow::error::Result;
-use exon_common::DEFAULT_BATCH_SIZE;
+use exon_common::{ReaderLimits, DEFAULT_BATCH_SIZE};
 use object_store::ObjectStore;
 
 /// Configuration for a SAM datasource.
@@ -32,6 +32,9 @@ pub struct SAMConfig {
 
     /// Any projections to apply to the resulting batches.
     pub projection: Option<Vec<usize>>,
+
+    /// The limits on header size, line length, sequence length, and tag count.
+    pub reader_limits: ReaderLimits,
 }
 
 impl SAMConfig {
@@ -42,9 +45,16 @@ impl SAMConfig {
             file_schema,
             object_store,
             projection: None,
+            reader_limits: ReaderLimits::default(),
         }
     }
 
+    /// Set the reader limits.
+    pub fn with_reader_limits(mut self, reader_limits: ReaderLimits) -> Self {
+        self.reader_limits = reader_limits;
+        self
+    }
+
     /// Set the batch size.
     pub fn with_batch_size(mut self, batch_size: usize) -> Self {
         self.batch_size = batch_size;
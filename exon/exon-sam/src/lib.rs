@@ -14,12 +14,18 @@
 
 mod array_builder;
 mod batch_reader;
+mod compact_builder;
 mod config;
+mod pacbio_builder;
 mod schema_builder;
 mod tag_builder;
 
 pub use array_builder::SAMArrayBuilder;
 pub use batch_reader::BatchReader;
+pub use compact_builder::{
+    has_compact_types, FlagsBuilder, MappingQualitiesBuilder, QualityScoresBuilder,
+};
 pub use config::SAMConfig;
-pub use schema_builder::SAMSchemaBuilder;
+pub use pacbio_builder::{pacbio_fields, PacBioBuilder, PACBIO_COLUMN_OFFSET};
+pub use schema_builder::{unify_tag_data_types, SAMSchemaBuilder};
 pub use tag_builder::TagsBuilder;
@@ -0,0 +1,211 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, Float32Builder, Int32Builder, ListBuilder, UInt8Builder},
+    datatypes::{DataType, Field},
+    error::{ArrowError, Result},
+};
+use noodles::sam::alignment::{
+    record::data::field::Tag,
+    record_buf::{data::field::Value, Data},
+};
+
+use crate::tag_builder::{integer_array_values, integer_value, invalid_tag_value};
+
+/// The index of the first PacBio column, after the tags.
+pub const PACBIO_COLUMN_OFFSET: usize = 11;
+
+const ZMW: Tag = Tag::new(b'z', b'm');
+const NUM_PASSES: Tag = Tag::new(b'n', b'p');
+const READ_QUALITY: Tag = Tag::new(b'r', b'q');
+const IPD: Tag = Tag::new(b'i', b'p');
+const PULSE_WIDTH: Tag = Tag::new(b'p', b'w');
+
+/// The PacBio columns, the `zm` ZMW hole number, `np` number of passes, `rq` predicted read
+/// accuracy, and the `ip` inter-pulse durations and `pw` pulse widths in frames.
+pub fn pacbio_fields() -> Vec<Field> {
+    let frames = DataType::List(Arc::new(Field::new("item", DataType::UInt8, true)));
+
+    vec![
+        Field::new("zmw", DataType::Int32, true),
+        Field::new("num_passes", DataType::Int32, true),
+        Field::new("read_quality", DataType::Float32, true),
+        Field::new("ipd", frames.clone(), true),
+        Field::new("pulse_width", frames, true),
+    ]
+}
+
+/// Builds the PacBio columns from the tags of the records, which are null if a tag is missing.
+pub struct PacBioBuilder {
+    zmws: Int32Builder,
+    num_passes: Int32Builder,
+    read_qualities: Float32Builder,
+    ipds: ListBuilder<UInt8Builder>,
+    pulse_widths: ListBuilder<UInt8Builder>,
+}
+
+impl Default for PacBioBuilder {
+    fn default() -> Self {
+        Self {
+            zmws: Int32Builder::new(),
+            num_passes: Int32Builder::new(),
+            read_qualities: Float32Builder::new(),
+            ipds: ListBuilder::new(UInt8Builder::new()),
+            pulse_widths: ListBuilder::new(UInt8Builder::new()),
+        }
+    }
+}
+
+impl PacBioBuilder {
+    /// Appends the value of a PacBio column, indexed as in [`pacbio_fields`], from the tags.
+    pub fn append(&mut self, column: usize, data: &Data) -> Result<()> {
+        match column {
+            0 => append_integer(&mut self.zmws, data, ZMW, column),
+            1 => append_integer(&mut self.num_passes, data, NUM_PASSES, column),
+            2 => match data.get(&READ_QUALITY) {
+                Some(Value::Float(v)) => {
+                    self.read_qualities.append_value(*v);
+                    Ok(())
+                }
+                Some(value) => Err(invalid_tag_value(value, &pacbio_fields()[column])),
+                None => {
+                    self.read_qualities.append_null();
+                    Ok(())
+                }
+            },
+            3 => append_frames(&mut self.ipds, data, IPD, column),
+            4 => append_frames(&mut self.pulse_widths, data, PULSE_WIDTH, column),
+            _ => Err(ArrowError::InvalidArgumentError(format!(
+                "Invalid PacBio column index {}",
+                column
+            ))),
+        }
+    }
+
+    /// Finishes a PacBio column, indexed as in [`pacbio_fields`].
+    pub fn finish(&mut self, column: usize) -> ArrayRef {
+        match column {
+            0 => Arc::new(self.zmws.finish()),
+            1 => Arc::new(self.num_passes.finish()),
+            2 => Arc::new(self.read_qualities.finish()),
+            3 => Arc::new(self.ipds.finish()),
+            4 => Arc::new(self.pulse_widths.finish()),
+            _ => panic!("Invalid PacBio column index {}", column),
+        }
+    }
+}
+
+fn append_integer(builder: &mut Int32Builder, data: &Data, tag: Tag, column: usize) -> Result<()> {
+    match data.get(&tag) {
+        Some(value) => {
+            let value = integer_value(value)
+                .and_then(|v| i32::try_from(v).ok())
+                .ok_or_else(|| invalid_tag_value(value, &pacbio_fields()[column]))?;
+
+            builder.append_value(value);
+        }
+        None => builder.append_null(),
+    }
+
+    Ok(())
+}
+
+/// Appends kinetics frames, which are 8-bit codec values unless the BAM was written with
+/// lossless kinetics, in which case they must still fit.
+fn append_frames(
+    builder: &mut ListBuilder<UInt8Builder>,
+    data: &Data,
+    tag: Tag,
+    column: usize,
+) -> Result<()> {
+    match data.get(&tag) {
+        Some(value) => {
+            let frames = integer_array_values(value)
+                .and_then(|values| {
+                    values
+                        .into_iter()
+                        .map(|v| u8::try_from(v).ok())
+                        .collect::<Option<Vec<_>>>()
+                })
+                .ok_or_else(|| invalid_tag_value(value, &pacbio_fields()[column]))?;
+
+            builder.values().append_slice(&frames);
+            builder.append(true);
+        }
+        None => builder.append_null(),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{Array, AsArray},
+        datatypes::{Float32Type, Int32Type, UInt8Type},
+    };
+    use noodles::sam::alignment::record_buf::data::field::value::Array as ValueArray;
+
+    use super::*;
+
+    #[test]
+    fn test_pacbio_builder() -> Result<()> {
+        let mut builder = PacBioBuilder::default();
+
+        let mut data = Data::default();
+        data.insert(ZMW, Value::UInt32(4391137));
+        data.insert(NUM_PASSES, Value::UInt8(12));
+        data.insert(READ_QUALITY, Value::Float(0.999));
+        data.insert(IPD, Value::Array(ValueArray::UInt8(vec![9, 21])));
+        data.insert(PULSE_WIDTH, Value::Array(ValueArray::UInt16(vec![4, 255])));
+
+        for column in 0..5 {
+            builder.append(column, &data)?;
+            builder.append(column, &Data::default())?;
+        }
+
+        let zmws = builder.finish(0);
+        let zmws = zmws.as_primitive::<Int32Type>();
+        assert_eq!(zmws.value(0), 4391137);
+        assert!(zmws.is_null(1));
+
+        assert_eq!(builder.finish(1).as_primitive::<Int32Type>().value(0), 12);
+        assert_eq!(
+            builder.finish(2).as_primitive::<Float32Type>().value(0),
+            0.999
+        );
+
+        let ipds = builder.finish(3);
+        let ipds = ipds.as_list::<i32>();
+        assert_eq!(ipds.value(0).as_primitive::<UInt8Type>().values(), &[9, 21]);
+        assert!(ipds.is_null(1));
+
+        let pulse_widths = builder.finish(4);
+        let pulse_widths = pulse_widths.as_list::<i32>();
+        assert_eq!(
+            pulse_widths.value(0).as_primitive::<UInt8Type>().values(),
+            &[4, 255]
+        );
+
+        // Lossless kinetics that don't fit in 8 bits are an error.
+        let mut data = Data::default();
+        data.insert(IPD, Value::Array(ValueArray::UInt16(vec![256])));
+        assert!(builder.append(3, &data).is_err());
+
+        Ok(())
+    }
+}
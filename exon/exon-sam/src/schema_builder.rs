@@ -12,7 +12,6 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
 use std::sync::Arc;
 
 use arrow::datatypes::{DataType, Field, Fields, Schema};
@@ -21,6 +20,8 @@ use exon_common::TableSchema;
 use noodles::sam::alignment::record_buf::data::field::{value::Array, Value};
 use noodles::sam::alignment::record_buf::Data;
 
+use crate::pacbio_builder::pacbio_fields;
+
 macro_rules! arrow_error {
     ($tag:expr, $field_type:expr, $expected_type:expr) => {
         Err(arrow::error::ArrowError::InvalidArgumentError(
@@ -33,11 +34,98 @@ macro_rules! arrow_error {
     };
 }
 
+/// The arrow data type for a tag value, B arrays become lists of their item type.
+fn tag_value_data_type(value: &Value) -> DataType {
+    let list = |item| DataType::List(Arc::new(Field::new("item", item, true)));
+
+    match value {
+        Value::Character(_) | Value::String(_) | Value::Hex(_) => DataType::Utf8,
+        Value::Int8(_) => DataType::Int8,
+        Value::UInt8(_) => DataType::UInt8,
+        Value::Int16(_) => DataType::Int16,
+        Value::UInt16(_) => DataType::UInt16,
+        Value::Int32(_) => DataType::Int32,
+        Value::UInt32(_) => DataType::UInt32,
+        Value::Float(_) => DataType::Float32,
+        Value::Array(Array::Int8(_)) => list(DataType::Int8),
+        Value::Array(Array::UInt8(_)) => list(DataType::UInt8),
+        Value::Array(Array::Int16(_)) => list(DataType::Int16),
+        Value::Array(Array::UInt16(_)) => list(DataType::UInt16),
+        Value::Array(Array::Int32(_)) => list(DataType::Int32),
+        Value::Array(Array::UInt32(_)) => list(DataType::UInt32),
+        Value::Array(Array::Float(_)) => list(DataType::Float32),
+    }
+}
+
+/// The signedness and bit width of an integer data type.
+fn integer_width(data_type: &DataType) -> Option<(bool, u8)> {
+    match data_type {
+        DataType::Int8 => Some((true, 8)),
+        DataType::UInt8 => Some((false, 8)),
+        DataType::Int16 => Some((true, 16)),
+        DataType::UInt16 => Some((false, 16)),
+        DataType::Int32 => Some((true, 32)),
+        DataType::UInt32 => Some((false, 32)),
+        DataType::Int64 => Some((true, 64)),
+        _ => None,
+    }
+}
+
+fn integer_data_type(signed: bool, bits: u8) -> Option<DataType> {
+    match (signed, bits) {
+        (true, 8) => Some(DataType::Int8),
+        (false, 8) => Some(DataType::UInt8),
+        (true, 16) => Some(DataType::Int16),
+        (false, 16) => Some(DataType::UInt16),
+        (true, 32) => Some(DataType::Int32),
+        (false, 32) => Some(DataType::UInt32),
+        (true, 64) => Some(DataType::Int64),
+        _ => None,
+    }
+}
+
+/// Unify the data types of a tag seen with different types, e.g. in different records or files.
+///
+/// SAM writers pick the smallest integer type that fits each value, so the same tag can be `C`
+/// in one record and `s` in the next. Integers widen to a type that holds both, e.g. UInt8 and
+/// Int8 become Int16, and integer lists widen their items the same way. Returns `None` if the
+/// types can't be unified, e.g. a string and an integer.
+pub fn unify_tag_data_types(a: &DataType, b: &DataType) -> Option<DataType> {
+    if a == b {
+        return Some(a.clone());
+    }
+
+    match (a, b) {
+        (DataType::List(a), DataType::List(b)) => {
+            let item = unify_tag_data_types(a.data_type(), b.data_type())?;
+            Some(DataType::List(Arc::new(Field::new("item", item, true))))
+        }
+        _ => {
+            let (a_signed, a_bits) = integer_width(a)?;
+            let (b_signed, b_bits) = integer_width(b)?;
+
+            if a_signed == b_signed {
+                return integer_data_type(a_signed, a_bits.max(b_bits));
+            }
+
+            // A signed type holds an unsigned one of half its width.
+            let (signed_bits, unsigned_bits) = if a_signed {
+                (a_bits, b_bits)
+            } else {
+                (b_bits, a_bits)
+            };
+
+            integer_data_type(true, signed_bits.max(unsigned_bits * 2).min(64))
+        }
+    }
+}
+
 /// Builds a schema for the BAM file.
 pub struct SAMSchemaBuilder {
     file_fields: Vec<Field>,
     partition_fields: Vec<Field>,
     tags_data_type: Option<DataType>,
+    pacbio_fields: bool,
 }
 
 impl SAMSchemaBuilder {
@@ -47,6 +135,7 @@ impl SAMSchemaBuilder {
             file_fields,
             partition_fields,
             tags_data_type: None,
+            pacbio_fields: false,
         }
     }
 
@@ -66,262 +155,86 @@ impl SAMSchemaBuilder {
         }
     }
 
+    /// Adds the PacBio columns after the tags, see [`pacbio_fields`].
+    pub fn with_pacbio_fields(self) -> Self {
+        Self {
+            pacbio_fields: true,
+            ..self
+        }
+    }
+
+    /// Packs the sequences with 2 bits per base in a binary field.
+    pub fn with_packed_sequences(self) -> Self {
+        let file_fields = self
+            .file_fields
+            .into_iter()
+            .map(|field| match field.name().as_str() {
+                "sequence" => Field::new("sequence", DataType::Binary, field.is_nullable()),
+                _ => field,
+            })
+            .collect();
+
+        Self {
+            file_fields,
+            ..self
+        }
+    }
+
+    /// Uses compact types for the flag, UInt16, mapping quality, UInt8, and quality score, a list
+    /// of UInt8, fields instead of Int32, Utf8, and a list of Int64.
+    pub fn with_compact_types(self) -> Self {
+        let quality_score_list =
+            DataType::List(Arc::new(Field::new("item", DataType::UInt8, true)));
+
+        let file_fields = self
+            .file_fields
+            .into_iter()
+            .map(|field| match field.name().as_str() {
+                "flag" => Field::new("flag", DataType::UInt16, field.is_nullable()),
+                "mapping_quality" => {
+                    Field::new("mapping_quality", DataType::UInt8, field.is_nullable())
+                }
+                "quality_score" => Field::new(
+                    "quality_score",
+                    quality_score_list.clone(),
+                    field.is_nullable(),
+                ),
+                _ => field,
+            })
+            .collect();
+
+        Self {
+            file_fields,
+            ..self
+        }
+    }
+
     /// Sets the data type for the tags field from the data.
+    ///
+    /// Tags already inferred, e.g. from another file, are kept and a tag seen with different
+    /// integer types is widened to a type that holds both, see [`unify_tag_data_types`].
     pub fn with_tags_data_type_from_data(self, data: &Data) -> Result<Self> {
-        let mut fields = HashMap::new();
+        let mut fields = match &self.tags_data_type {
+            Some(DataType::Struct(fields)) => fields
+                .iter()
+                .map(|f| f.as_ref().clone())
+                .collect::<Vec<_>>(),
+            _ => vec![],
+        };
 
         for (tag, value) in data.iter() {
             let tag_name = std::str::from_utf8(tag.as_ref())?;
+            let data_type = tag_value_data_type(value);
 
-            match value {
-                Value::Character(_) | Value::String(_) | Value::Hex(_) => {
-                    let field = fields.entry(tag).or_insert_with(|| {
-                        Field::new(tag_name, arrow::datatypes::DataType::Utf8, true)
-                    });
-                    if field.data_type() != &arrow::datatypes::DataType::Utf8 {
-                        return arrow_error!(
-                            tag_name,
-                            field.data_type(),
-                            arrow::datatypes::DataType::Utf8
-                        );
-                    }
-                }
-                Value::Int8(_) => {
-                    let field = fields.entry(tag).or_insert_with(|| {
-                        Field::new(tag_name, arrow::datatypes::DataType::Int8, true)
-                    });
-                    if field.data_type() != &arrow::datatypes::DataType::Int8 {
-                        return arrow_error!(
-                            tag_name,
-                            field.data_type(),
-                            arrow::datatypes::DataType::Int8
-                        );
-                    }
-                }
-                Value::Int16(_) => {
-                    let field = fields.entry(tag).or_insert_with(|| {
-                        Field::new(tag_name, arrow::datatypes::DataType::Int16, true)
-                    });
-                    if field.data_type() != &arrow::datatypes::DataType::Int16 {
-                        return arrow_error!(
-                            tag_name,
-                            field.data_type(),
-                            arrow::datatypes::DataType::Int16
-                        );
-                    }
-                }
-                Value::Int32(_) => {
-                    let field = fields.entry(tag).or_insert_with(|| {
-                        Field::new(tag_name, arrow::datatypes::DataType::Int32, true)
-                    });
-                    if field.data_type() != &arrow::datatypes::DataType::Int32 {
-                        return arrow_error!(
-                            tag_name,
-                            field.data_type(),
-                            arrow::datatypes::DataType::Int32
-                        );
-                    }
-                }
-                Value::UInt8(_) => {
-                    let field = fields.entry(tag).or_insert_with(|| {
-                        Field::new(tag_name, arrow::datatypes::DataType::UInt8, true)
-                    });
-                    if field.data_type() != &arrow::datatypes::DataType::UInt8 {
-                        return arrow_error!(
-                            tag_name,
-                            field.data_type(),
-                            arrow::datatypes::DataType::UInt8
-                        );
-                    }
-                }
-                Value::UInt16(_) => {
-                    let field = fields.entry(tag).or_insert_with(|| {
-                        Field::new(tag_name, arrow::datatypes::DataType::UInt16, true)
-                    });
-                    if field.data_type() != &arrow::datatypes::DataType::UInt16 {
-                        return arrow_error!(
-                            tag_name,
-                            field.data_type(),
-                            arrow::datatypes::DataType::UInt16
-                        );
-                    }
-                }
-                Value::UInt32(_) => {
-                    let field = fields.entry(tag).or_insert_with(|| {
-                        Field::new(tag_name, arrow::datatypes::DataType::UInt32, true)
-                    });
-                    if field.data_type() != &arrow::datatypes::DataType::UInt32 {
-                        return arrow_error!(
-                            tag_name,
-                            field.data_type(),
-                            arrow::datatypes::DataType::UInt16
-                        );
-                    }
-                }
-                Value::Float(_) => {
-                    let field = fields.entry(tag).or_insert_with(|| {
-                        Field::new(tag_name, arrow::datatypes::DataType::Float32, true)
-                    });
+            match fields.iter_mut().find(|f| f.name() == tag_name) {
+                Some(field) => {
+                    let Some(unified) = unify_tag_data_types(field.data_type(), &data_type) else {
+                        return arrow_error!(tag_name, field.data_type(), data_type);
+                    };
 
-                    if field.data_type() != &arrow::datatypes::DataType::Float32 {
-                        return arrow_error!(
-                            tag_name,
-                            field.data_type(),
-                            arrow::datatypes::DataType::Float32
-                        );
-                    }
-                }
-                Value::Array(array) => {
-                    match array {
-                        Array::Int32(_) => {
-                            let field = fields.entry(tag).or_insert_with(|| {
-                                Field::new(
-                                    tag_name,
-                                    arrow::datatypes::DataType::List(Arc::new(Field::new(
-                                        "item",
-                                        arrow::datatypes::DataType::Int32,
-                                        true,
-                                    ))),
-                                    false,
-                                )
-                            });
-
-                            let expected_type = arrow::datatypes::DataType::List(Arc::new(
-                                Field::new("item", arrow::datatypes::DataType::Int32, true),
-                            ));
-
-                            if field.data_type() != &expected_type {
-                                return arrow_error!(tag_name, field.data_type(), expected_type);
-                            }
-                        }
-                        Array::Int16(_) => {
-                            let field = fields.entry(tag).or_insert_with(|| {
-                                Field::new(
-                                    tag_name,
-                                    arrow::datatypes::DataType::List(Arc::new(Field::new(
-                                        "item",
-                                        arrow::datatypes::DataType::Int16,
-                                        true,
-                                    ))),
-                                    true,
-                                )
-                            });
-
-                            let expected_type = arrow::datatypes::DataType::List(Arc::new(
-                                Field::new("item", arrow::datatypes::DataType::Int16, true),
-                            ));
-
-                            if field.data_type() != &expected_type {
-                                return arrow_error!(tag_name, field.data_type(), expected_type);
-                            }
-                        }
-                        Array::Int8(_) => {
-                            let field = fields.entry(tag).or_insert_with(|| {
-                                Field::new(
-                                    tag_name,
-                                    arrow::datatypes::DataType::List(Arc::new(Field::new(
-                                        "item",
-                                        arrow::datatypes::DataType::Int8,
-                                        true,
-                                    ))),
-                                    true,
-                                )
-                            });
-
-                            let expected_type = arrow::datatypes::DataType::List(Arc::new(
-                                Field::new("item", arrow::datatypes::DataType::Int8, true),
-                            ));
-
-                            if field.data_type() != &expected_type {
-                                return arrow_error!(tag_name, field.data_type(), expected_type);
-                            }
-                        }
-                        Array::UInt8(_) => {
-                            let field = fields.entry(tag).or_insert_with(|| {
-                                Field::new(
-                                    tag_name,
-                                    arrow::datatypes::DataType::List(Arc::new(Field::new(
-                                        "item",
-                                        arrow::datatypes::DataType::UInt8,
-                                        true,
-                                    ))),
-                                    true,
-                                )
-                            });
-
-                            let expected_type = arrow::datatypes::DataType::List(Arc::new(
-                                Field::new("item", arrow::datatypes::DataType::UInt8, true),
-                            ));
-
-                            if field.data_type() != &expected_type {
-                                return arrow_error!(tag_name, field.data_type(), expected_type);
-                            }
-                        }
-                        Array::UInt16(_) => {
-                            let field = fields.entry(tag).or_insert_with(|| {
-                                Field::new(
-                                    tag_name,
-                                    arrow::datatypes::DataType::List(Arc::new(Field::new(
-                                        "item",
-                                        arrow::datatypes::DataType::UInt16,
-                                        true,
-                                    ))),
-                                    true,
-                                )
-                            });
-
-                            let expected_type = arrow::datatypes::DataType::List(Arc::new(
-                                Field::new("item", arrow::datatypes::DataType::UInt16, true),
-                            ));
-
-                            if field.data_type() != &expected_type {
-                                return arrow_error!(tag_name, field.data_type(), expected_type);
-                            }
-                        }
-                        Array::UInt32(_) => {
-                            let field = fields.entry(tag).or_insert_with(|| {
-                                Field::new(
-                                    tag_name,
-                                    arrow::datatypes::DataType::List(Arc::new(Field::new(
-                                        "item",
-                                        arrow::datatypes::DataType::UInt32,
-                                        true,
-                                    ))),
-                                    true,
-                                )
-                            });
-
-                            let expected_type = arrow::datatypes::DataType::List(Arc::new(
-                                Field::new("item", arrow::datatypes::DataType::UInt32, true),
-                            ));
-
-                            if field.data_type() != &expected_type {
-                                return arrow_error!(tag_name, field.data_type(), expected_type);
-                            }
-                        }
-                        Array::Float(_) => {
-                            let field = fields.entry(tag).or_insert_with(|| {
-                                Field::new(
-                                    tag_name,
-                                    arrow::datatypes::DataType::List(Arc::new(Field::new(
-                                        "item",
-                                        arrow::datatypes::DataType::Float32,
-                                        true,
-                                    ))),
-                                    true,
-                                )
-                            });
-
-                            let expected_type = arrow::datatypes::DataType::List(Arc::new(
-                                Field::new("item", arrow::datatypes::DataType::Float32, true),
-                            ));
-
-                            if field.data_type() != &expected_type {
-                                return arrow_error!(tag_name, field.data_type(), expected_type);
-                            }
-                        }
-                    }
+                    *field = Field::new(tag_name, unified, true);
                 }
+                None => fields.push(Field::new(tag_name, data_type, true)),
             }
         }
 
@@ -332,19 +245,7 @@ impl SAMSchemaBuilder {
             ));
         }
 
-        let data_type = DataType::Struct(Fields::from(
-            fields
-                .into_iter()
-                .map(|(tag, field)| {
-                    let data_type = field.data_type().clone();
-
-                    // TODO: remove unwrap
-                    let tag_name = std::str::from_utf8(tag.as_ref()).unwrap();
-
-                    Field::new(tag_name, data_type, true)
-                })
-                .collect::<Vec<_>>(),
-        ));
+        let data_type = DataType::Struct(Fields::from(fields));
 
         Ok(self.with_tags_data_type(data_type))
     }
@@ -358,6 +259,10 @@ impl SAMSchemaBuilder {
             fields.push(tags_field);
         }
 
+        if self.pacbio_fields {
+            fields.extend(pacbio_fields());
+        }
+
         let file_projection = (0..fields.len()).collect::<Vec<_>>();
 
         fields.extend_from_slice(&self.partition_fields);
@@ -405,6 +310,8 @@ impl Default for SAMSchemaBuilder {
 mod tests {
     use noodles::sam::alignment::record::data::field::Tag;
 
+    use crate::PACBIO_COLUMN_OFFSET;
+
     use super::*;
 
     #[test]
@@ -416,6 +323,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_build_with_pacbio_fields() -> Result<()> {
+        let schema = SAMSchemaBuilder::default().with_pacbio_fields().build();
+
+        assert_eq!(schema.fields().len(), 16);
+        assert_eq!(schema.fields()[PACBIO_COLUMN_OFFSET].name(), "zmw");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_with_compact_types() -> Result<()> {
+        let schema = SAMSchemaBuilder::default().with_compact_types().build();
+        let fields = schema.fields();
+
+        assert_eq!(fields.len(), 11);
+        assert_eq!(fields[1].data_type(), &DataType::UInt16);
+        assert_eq!(fields[5].data_type(), &DataType::UInt8);
+        assert_eq!(
+            fields[9].data_type(),
+            &DataType::List(Arc::new(Field::new("item", DataType::UInt8, true)))
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_build_from_empty_data_errors() -> Result<()> {
         let data = Data::default();
@@ -426,6 +359,91 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_unify_tag_data_types() {
+        let list = |item| DataType::List(Arc::new(Field::new("item", item, true)));
+
+        let cases = [
+            (DataType::UInt8, DataType::UInt16, Some(DataType::UInt16)),
+            (DataType::Int8, DataType::Int32, Some(DataType::Int32)),
+            (DataType::UInt8, DataType::Int8, Some(DataType::Int16)),
+            (DataType::Int32, DataType::UInt16, Some(DataType::Int32)),
+            (DataType::UInt32, DataType::Int8, Some(DataType::Int64)),
+            (DataType::Utf8, DataType::Utf8, Some(DataType::Utf8)),
+            (DataType::Utf8, DataType::Int8, None),
+            (DataType::Float32, DataType::Int32, None),
+            (
+                list(DataType::UInt8),
+                list(DataType::Int16),
+                Some(list(DataType::Int16)),
+            ),
+            (list(DataType::Float32), list(DataType::Int16), None),
+            (list(DataType::Int8), DataType::Int8, None),
+        ];
+
+        for (a, b, expected) in cases {
+            assert_eq!(unify_tag_data_types(&a, &b), expected, "{a} and {b}");
+            assert_eq!(unify_tag_data_types(&b, &a), expected, "{b} and {a}");
+        }
+    }
+
+    #[test]
+    fn test_tags_are_unified_across_data() -> Result<()> {
+        let mut first = Data::default();
+        first.insert(Tag::ALIGNMENT_HIT_COUNT, Value::UInt8(1));
+        first.insert(Tag::CELL_BARCODE_ID, Value::from("AA"));
+        first.insert(
+            Tag::ORIGINAL_UMI_QUALITY_SCORES,
+            Value::Array(Array::UInt8(vec![1, 2])),
+        );
+
+        let mut second = Data::default();
+        second.insert(Tag::ALIGNMENT_HIT_COUNT, Value::Int16(-300));
+        second.insert(
+            Tag::ORIGINAL_UMI_QUALITY_SCORES,
+            Value::Array(Array::Int8(vec![-1])),
+        );
+        second.insert(Tag::EDIT_DISTANCE, Value::UInt8(2));
+
+        let schema = SAMSchemaBuilder::default()
+            .with_tags_data_type_from_data(&first)?
+            .with_tags_data_type_from_data(&second)?;
+
+        let Some(DataType::Struct(fields)) = schema.tags_data_type else {
+            return Err(ArrowError::InvalidArgumentError(
+                "tags_data_type is not a struct".into(),
+            ));
+        };
+
+        let fields = fields
+            .iter()
+            .map(|f| (f.name().as_str(), f.data_type().clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            fields,
+            vec![
+                ("NH", DataType::Int16),
+                ("CB", DataType::Utf8),
+                (
+                    "BZ",
+                    DataType::List(Arc::new(Field::new("item", DataType::Int16, true)))
+                ),
+                ("NM", DataType::UInt8),
+            ]
+        );
+
+        let mut conflicting = Data::default();
+        conflicting.insert(Tag::CELL_BARCODE_ID, Value::Int8(1));
+
+        let schema = SAMSchemaBuilder::default()
+            .with_tags_data_type_from_data(&first)?
+            .with_tags_data_type_from_data(&conflicting);
+        assert!(schema.is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_parsing_data() -> Result<()> {
         let mut data = Data::default();
@@ -17,7 +17,7 @@ use std::{str::FromStr, sync::Arc};
 use arrow::{
     array::{
         make_builder, ArrayBuilder, ArrayRef, Float32Builder, GenericListBuilder,
-        GenericStringBuilder, Int16Builder, Int32Builder, Int8Builder, StructBuilder,
+        GenericStringBuilder, Int16Builder, Int32Builder, Int64Builder, Int8Builder, StructBuilder,
         UInt16Builder, UInt32Builder, UInt8Builder,
     },
     datatypes::{DataType, Field, Fields},
@@ -134,6 +134,11 @@ impl TryFrom<&Fields> for TagsStructBuilder {
                             GenericListBuilder::<i32, UInt32Builder>::new(UInt32Builder::new());
                         builders.push(Box::new(builder));
                     }
+                    DataType::Int64 => {
+                        let builder =
+                            GenericListBuilder::<i32, Int64Builder>::new(Int64Builder::new());
+                        builders.push(Box::new(builder));
+                    }
                     _ => {
                         return Err(ArrowError::InvalidArgumentError(format!(
                             "Invalid data type {:?} for tag list",
@@ -157,6 +162,90 @@ impl TryFrom<&Fields> for TagsStructBuilder {
     }
 }
 
+/// Append a null to the struct's field builder of the given type.
+macro_rules! append_null {
+    ($builder:expr, $i:expr, $tag_name:expr, $builder_type:ty) => {
+        $builder
+            .field_builder::<$builder_type>($i)
+            .ok_or_else(|| missing_builder($tag_name))?
+            .append_null()
+    };
+}
+
+/// Append an integer tag value, converting it to the field's integer type.
+macro_rules! append_integer {
+    ($builder:expr, $i:expr, $field:expr, $value:expr, $builder_type:ty, $native:ty) => {{
+        let value = integer_value($value)
+            .and_then(|v| <$native>::try_from(v).ok())
+            .ok_or_else(|| invalid_tag_value($value, $field))?;
+
+        $builder
+            .field_builder::<$builder_type>($i)
+            .ok_or_else(|| missing_builder($field.name()))?
+            .append_value(value);
+    }};
+}
+
+/// Append an integer array tag value, converting its items to the field's item type.
+macro_rules! append_integer_list {
+    ($builder:expr, $i:expr, $field:expr, $value:expr, $builder_type:ty, $native:ty) => {{
+        let values = integer_array_values($value)
+            .and_then(|values| {
+                values
+                    .into_iter()
+                    .map(|v| <$native>::try_from(v).ok())
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| invalid_tag_value($value, $field))?;
+
+        let builder = $builder
+            .field_builder::<GenericListBuilder<i32, $builder_type>>($i)
+            .ok_or_else(|| missing_builder($field.name()))?;
+
+        builder.values().append_slice(&values);
+        builder.append(true);
+    }};
+}
+
+/// Get the value of an integer tag, whatever its width.
+pub(crate) fn integer_value(value: &Value) -> Option<i64> {
+    match value {
+        Value::Int8(v) => Some(i64::from(*v)),
+        Value::UInt8(v) => Some(i64::from(*v)),
+        Value::Int16(v) => Some(i64::from(*v)),
+        Value::UInt16(v) => Some(i64::from(*v)),
+        Value::Int32(v) => Some(i64::from(*v)),
+        Value::UInt32(v) => Some(i64::from(*v)),
+        _ => None,
+    }
+}
+
+/// Get the values of an integer array tag, whatever its item width.
+pub(crate) fn integer_array_values(value: &Value) -> Option<Vec<i64>> {
+    match value {
+        Value::Array(Array::Int8(arr)) => Some(arr.iter().map(|v| i64::from(*v)).collect()),
+        Value::Array(Array::UInt8(arr)) => Some(arr.iter().map(|v| i64::from(*v)).collect()),
+        Value::Array(Array::Int16(arr)) => Some(arr.iter().map(|v| i64::from(*v)).collect()),
+        Value::Array(Array::UInt16(arr)) => Some(arr.iter().map(|v| i64::from(*v)).collect()),
+        Value::Array(Array::Int32(arr)) => Some(arr.iter().map(|v| i64::from(*v)).collect()),
+        Value::Array(Array::UInt32(arr)) => Some(arr.iter().map(|v| i64::from(*v)).collect()),
+        _ => None,
+    }
+}
+
+pub(crate) fn invalid_tag_value(value: &Value, field: &Field) -> ArrowError {
+    ArrowError::InvalidArgumentError(format!(
+        "Invalid tag value {:?} for tag {} a {}",
+        value,
+        field.name(),
+        field.data_type()
+    ))
+}
+
+fn missing_builder(tag_name: &str) -> ArrowError {
+    ArrowError::InvalidArgumentError(format!("Cannot extract builder for tag {}", tag_name))
+}
+
 impl TagsStructBuilder {
     pub fn finish(&mut self) -> arrow::array::StructArray {
         self.builder.finish()
@@ -178,53 +267,73 @@ impl TagsStructBuilder {
             match tag_option {
                 None => match f.data_type() {
                     DataType::Utf8 => {
-                        self.builder
-                            .field_builder::<GenericStringBuilder<i32>>(i)
-                            .unwrap()
-                            .append_null();
-                    }
-                    DataType::UInt8 => {
-                        self.builder
-                            .field_builder::<UInt8Builder>(i)
-                            .unwrap()
-                            .append_null();
-                    }
-                    DataType::Int8 => {
-                        self.builder
-                            .field_builder::<Int8Builder>(i)
-                            .unwrap()
-                            .append_null();
-                    }
-                    DataType::Int16 => {
-                        self.builder
-                            .field_builder::<Int16Builder>(i)
-                            .unwrap()
-                            .append_null();
-                    }
-                    DataType::UInt16 => {
-                        self.builder
-                            .field_builder::<UInt16Builder>(i)
-                            .unwrap()
-                            .append_null();
-                    }
-                    DataType::UInt32 => {
-                        self.builder
-                            .field_builder::<UInt32Builder>(i)
-                            .unwrap()
-                            .append_null();
-                    }
-                    DataType::Int32 => {
-                        self.builder
-                            .field_builder::<Int32Builder>(i)
-                            .unwrap()
-                            .append_null();
-                    }
-                    DataType::Float32 => {
-                        self.builder
-                            .field_builder::<Float32Builder>(i)
-                            .unwrap()
-                            .append_null();
+                        append_null!(self.builder, i, tag_name, GenericStringBuilder<i32>)
                     }
+                    DataType::UInt8 => append_null!(self.builder, i, tag_name, UInt8Builder),
+                    DataType::Int8 => append_null!(self.builder, i, tag_name, Int8Builder),
+                    DataType::Int16 => append_null!(self.builder, i, tag_name, Int16Builder),
+                    DataType::UInt16 => append_null!(self.builder, i, tag_name, UInt16Builder),
+                    DataType::UInt32 => append_null!(self.builder, i, tag_name, UInt32Builder),
+                    DataType::Int32 => append_null!(self.builder, i, tag_name, Int32Builder),
+                    DataType::Int64 => append_null!(self.builder, i, tag_name, Int64Builder),
+                    DataType::Float32 => append_null!(self.builder, i, tag_name, Float32Builder),
+                    DataType::List(item) => match item.data_type() {
+                        DataType::UInt8 => append_null!(
+                            self.builder,
+                            i,
+                            tag_name,
+                            GenericListBuilder<i32, UInt8Builder>
+                        ),
+                        DataType::Int8 => append_null!(
+                            self.builder,
+                            i,
+                            tag_name,
+                            GenericListBuilder<i32, Int8Builder>
+                        ),
+                        DataType::UInt16 => append_null!(
+                            self.builder,
+                            i,
+                            tag_name,
+                            GenericListBuilder<i32, UInt16Builder>
+                        ),
+                        DataType::Int16 => append_null!(
+                            self.builder,
+                            i,
+                            tag_name,
+                            GenericListBuilder<i32, Int16Builder>
+                        ),
+                        DataType::UInt32 => append_null!(
+                            self.builder,
+                            i,
+                            tag_name,
+                            GenericListBuilder<i32, UInt32Builder>
+                        ),
+                        DataType::Int32 => append_null!(
+                            self.builder,
+                            i,
+                            tag_name,
+                            GenericListBuilder<i32, Int32Builder>
+                        ),
+                        DataType::Int64 => append_null!(
+                            self.builder,
+                            i,
+                            tag_name,
+                            GenericListBuilder<i32, Int64Builder>
+                        ),
+                        DataType::Float32 => append_null!(
+                            self.builder,
+                            i,
+                            tag_name,
+                            GenericListBuilder<i32, Float32Builder>
+                        ),
+                        _ => {
+                            return Err(ArrowError::InvalidArgumentError(format!(
+                                "Invalid null data type {:?} for tag {}",
+                                f.data_type(),
+                                tag_name
+                            )))
+                        }
+                    },
                     _ => {
                         return Err(ArrowError::InvalidArgumentError(format!(
                             "Invalid null data type {:?} for tag {}",
@@ -234,243 +343,87 @@ impl TagsStructBuilder {
                     }
                 },
                 Some(tag_value) => match f.data_type() {
-                    DataType::Utf8 => match tag_value {
-                        Value::Character(c) => {
-                            let tag_value_str = c.to_string();
-                            self.builder
-                                .field_builder::<GenericStringBuilder<i32>>(i)
-                                .unwrap()
-                                .append_value(tag_value_str);
-                        }
-                        Value::String(s) => {
-                            let tag_value_str = std::str::from_utf8(s.as_ref())?.to_string();
-                            self.builder
-                                .field_builder::<GenericStringBuilder<i32>>(i)
-                                .unwrap()
-                                .append_value(tag_value_str);
-                        }
-                        Value::Hex(s) => {
-                            let tag_value_str = std::str::from_utf8(s.as_ref())?.to_string();
-                            self.builder
-                                .field_builder::<GenericStringBuilder<i32>>(i)
-                                .unwrap()
-                                .append_value(tag_value_str);
-                        }
-                        _ => {
-                            return Err(ArrowError::InvalidArgumentError(format!(
-                                "Invalid tag value {:?} for tag {} a {}",
-                                tag_value,
-                                tag_name,
-                                f.data_type()
-                            )))
-                        }
-                    },
+                    DataType::Utf8 => {
+                        let tag_value_str = match tag_value {
+                            Value::Character(c) => char::from(*c).to_string(),
+                            Value::String(s) => std::str::from_utf8(s.as_ref())?.to_string(),
+                            Value::Hex(s) => std::str::from_utf8(s.as_ref())?.to_string(),
+                            _ => return Err(invalid_tag_value(tag_value, f)),
+                        };
+
+                        self.builder
+                            .field_builder::<GenericStringBuilder<i32>>(i)
+                            .ok_or_else(|| missing_builder(tag_name))?
+                            .append_value(tag_value_str);
+                    }
                     DataType::Int8 => {
-                        if let Value::Int8(int) = tag_value {
-                            let i8_value = *int;
-                            self.builder
-                                .field_builder::<Int8Builder>(i)
-                                .unwrap()
-                                .append_value(i8_value);
-                        } else {
-                            return Err(ArrowError::InvalidArgumentError(format!(
-                                "Invalid tag value {:?} for tag {} a {}",
-                                tag_value,
-                                tag_name,
-                                f.data_type()
-                            )));
-                        }
+                        append_integer!(self.builder, i, f, tag_value, Int8Builder, i8)
                     }
                     DataType::UInt8 => {
-                        if let Value::UInt8(int) = tag_value {
-                            let u8_value = *int;
-                            self.builder
-                                .field_builder::<UInt8Builder>(i)
-                                .unwrap()
-                                .append_value(u8_value);
-                        } else {
-                            return Err(ArrowError::InvalidArgumentError(format!(
-                                "Invalid tag value {:?} for tag {} a {}",
-                                tag_value,
-                                tag_name,
-                                f.data_type()
-                            )));
-                        }
+                        append_integer!(self.builder, i, f, tag_value, UInt8Builder, u8)
                     }
                     DataType::Int16 => {
-                        if let Value::Int16(int) = tag_value {
-                            let i16_value = *int;
-                            self.builder
-                                .field_builder::<Int16Builder>(i)
-                                .unwrap()
-                                .append_value(i16_value);
-                        } else {
-                            return Err(ArrowError::InvalidArgumentError(format!(
-                                "Invalid tag value {:?} for tag {} a {}",
-                                tag_value,
-                                tag_name,
-                                f.data_type()
-                            )));
-                        }
+                        append_integer!(self.builder, i, f, tag_value, Int16Builder, i16)
+                    }
+                    DataType::UInt16 => {
+                        append_integer!(self.builder, i, f, tag_value, UInt16Builder, u16)
                     }
                     DataType::Int32 => {
-                        if let Value::Int32(int) = tag_value {
-                            let i32_value = *int;
-                            self.builder
-                                .field_builder::<Int32Builder>(i)
-                                .unwrap()
-                                .append_value(i32_value);
-                        } else {
-                            return Err(ArrowError::InvalidArgumentError(format!(
-                                "Invalid tag value {:?} for tag {} a {}",
-                                tag_value,
-                                tag_name,
-                                f.data_type()
-                            )));
-                        }
+                        append_integer!(self.builder, i, f, tag_value, Int32Builder, i32)
                     }
-                    DataType::List(field) => match (field.data_type(), tag_value) {
-                        (DataType::UInt8, Value::Array(Array::UInt8(arr))) => {
-                            let builder = self
-                                .builder
-                                .field_builder::<GenericListBuilder<i32, UInt8Builder>>(i)
-                                .unwrap();
-
-                            let builder_values = builder.values();
-
-                            for v in arr.iter() {
-                                builder_values.append_value(*v);
-                            }
+                    DataType::UInt32 => {
+                        append_integer!(self.builder, i, f, tag_value, UInt32Builder, u32)
+                    }
+                    DataType::Int64 => {
+                        append_integer!(self.builder, i, f, tag_value, Int64Builder, i64)
+                    }
+                    DataType::Float32 => {
+                        let Value::Float(value) = tag_value else {
+                            return Err(invalid_tag_value(tag_value, f));
+                        };
 
-                            builder.append(true);
+                        self.builder
+                            .field_builder::<Float32Builder>(i)
+                            .ok_or_else(|| missing_builder(tag_name))?
+                            .append_value(*value);
+                    }
+                    DataType::List(item) => match item.data_type() {
+                        DataType::Int8 => {
+                            append_integer_list!(self.builder, i, f, tag_value, Int8Builder, i8)
                         }
-                        (DataType::Int8, Value::Array(Array::Int8(arr))) => {
-                            let builder = self
-                                .builder
-                                .field_builder::<GenericListBuilder<i32, Int8Builder>>(i)
-                                .ok_or(ArrowError::InvalidArgumentError(format!(
-                                    "Cannot extract builder for {} of field {}",
-                                    tag_name, i
-                                )))?;
-
-                            let builder_values = builder.values();
-
-                            for v in arr.iter() {
-                                builder_values.append_value(*v);
-                            }
-
-                            builder.append(true);
+                        DataType::UInt8 => {
+                            append_integer_list!(self.builder, i, f, tag_value, UInt8Builder, u8)
                         }
-                        (DataType::UInt16, Value::Array(Array::UInt16(arr))) => {
-                            let builder = self
-                                .builder
-                                .field_builder::<GenericListBuilder<i32, UInt16Builder>>(i)
-                                .unwrap();
-
-                            let builder_values = builder.values();
-
-                            for v in arr.iter() {
-                                builder_values.append_value(*v);
-                            }
-
-                            builder.append(true);
+                        DataType::Int16 => {
+                            append_integer_list!(self.builder, i, f, tag_value, Int16Builder, i16)
                         }
-                        (DataType::Int16, Value::Array(Array::Int16(arr))) => {
-                            let builder = self
-                                .builder
-                                .field_builder::<GenericListBuilder<i32, Int16Builder>>(i)
-                                .unwrap();
-
-                            let builder_values = builder.values();
-
-                            for v in arr.iter() {
-                                builder_values.append_value(*v);
-                            }
-
-                            builder.append(true);
+                        DataType::UInt16 => {
+                            append_integer_list!(self.builder, i, f, tag_value, UInt16Builder, u16)
                         }
-                        (DataType::Float32, Value::Array(Array::Float(arr))) => {
-                            let builder = self
-                                .builder
-                                .field_builder::<GenericListBuilder<i32, Float32Builder>>(i)
-                                .ok_or(ArrowError::InvalidArgumentError(format!(
-                                    "Cannot extract builder for {:?} for tag {} a {}",
-                                    tag_value,
-                                    tag_name,
-                                    f.data_type()
-                                )))?;
-
-                            let builder_values = builder.values();
-
-                            for v in arr.iter() {
-                                builder_values.append_value(*v);
-                            }
-
-                            builder.append(true);
+                        DataType::Int32 => {
+                            append_integer_list!(self.builder, i, f, tag_value, Int32Builder, i32)
                         }
-                        (DataType::UInt32, Value::Array(Array::UInt32(arr))) => {
-                            let builder = self
-                                .builder
-                                .field_builder::<GenericListBuilder<i32, UInt32Builder>>(i)
-                                .ok_or(ArrowError::InvalidArgumentError(format!(
-                                    "Cannot extract builder for {:?} for tag {} a {}",
-                                    tag_value,
-                                    tag_name,
-                                    f.data_type()
-                                )))?;
-
-                            let builder_values = builder.values();
-
-                            for v in arr.iter() {
-                                builder_values.append_value(*v);
-                            }
-
-                            builder.append(true);
+                        DataType::UInt32 => {
+                            append_integer_list!(self.builder, i, f, tag_value, UInt32Builder, u32)
                         }
-                        (DataType::Int32, Value::Array(Array::Int32(arr))) => {
+                        DataType::Int64 => {
+                            append_integer_list!(self.builder, i, f, tag_value, Int64Builder, i64)
+                        }
+                        DataType::Float32 => {
+                            let Value::Array(Array::Float(arr)) = tag_value else {
+                                return Err(invalid_tag_value(tag_value, f));
+                            };
+
                             let builder = self
                                 .builder
-                                .field_builder::<GenericListBuilder<i32, Int32Builder>>(i)
-                                .ok_or(ArrowError::InvalidArgumentError(format!(
-                                    "Cannot extract builder for {:?} for tag {} a {}",
-                                    tag_value,
-                                    tag_name,
-                                    f.data_type()
-                                )))?;
-
-                            let builder_values = builder.values();
-
-                            for v in arr.iter() {
-                                builder_values.append_value(*v);
-                            }
+                                .field_builder::<GenericListBuilder<i32, Float32Builder>>(i)
+                                .ok_or_else(|| missing_builder(tag_name))?;
 
+                            builder.values().append_slice(arr);
                             builder.append(true);
                         }
-                        (_, _) => {
-                            return Err(ArrowError::InvalidArgumentError(format!(
-                                "Invalid tag value {:?} for tag {} a {}",
-                                tag_value,
-                                tag_name,
-                                f.data_type()
-                            )))
-                        }
+                        _ => return Err(invalid_tag_value(tag_value, f)),
                     },
-                    DataType::Float32 => {
-                        if let Value::Float(f) = tag_value {
-                            let f = *f;
-                            self.builder
-                                .field_builder::<Float32Builder>(i)
-                                .unwrap()
-                                .append_value(f);
-                        } else {
-                            return Err(ArrowError::InvalidArgumentError(format!(
-                                "Invalid tag value {:?} for tag {} a {}",
-                                tag_value,
-                                tag_name,
-                                f.data_type()
-                            )));
-                        }
-                    }
                     _ => {
                         return Err(ArrowError::InvalidArgumentError(format!(
                             "Invalid data type {:?} for tag {}",
@@ -745,3 +698,73 @@ impl TagsMapBuilder {
         self.builder.finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Array as _, AsArray},
+        datatypes::{DataType, Field, Fields, Int16Type, UInt32Type},
+        error::ArrowError,
+    };
+    use noodles::sam::alignment::{
+        record::data::field::Tag,
+        record_buf::{
+            data::field::{value::Array, Value},
+            Data,
+        },
+    };
+
+    use super::TagsStructBuilder;
+
+    #[test]
+    fn test_struct_builder_widens_and_nulls() -> Result<(), ArrowError> {
+        let fields = Fields::from(vec![
+            Field::new("NH", DataType::Int16, true),
+            Field::new(
+                "BZ",
+                DataType::List(Arc::new(Field::new("item", DataType::Int16, true))),
+                true,
+            ),
+            Field::new("XU", DataType::UInt32, true),
+        ]);
+
+        let mut builder = TagsStructBuilder::try_from(&fields)?;
+
+        let mut data = Data::default();
+        data.insert(Tag::ALIGNMENT_HIT_COUNT, Value::UInt8(200));
+        data.insert(
+            Tag::ORIGINAL_UMI_QUALITY_SCORES,
+            Value::Array(Array::Int8(vec![-1, 2])),
+        );
+        data.insert(Tag::new(b'X', b'U'), Value::UInt32(u32::MAX));
+        builder.append(&data)?;
+
+        builder.append(&Data::default())?;
+
+        let tags = builder.finish();
+
+        let nh = tags.column(0).as_primitive::<Int16Type>();
+        assert_eq!(nh.value(0), 200);
+        assert!(nh.is_null(1));
+
+        let bz = tags.column(1).as_list::<i32>();
+        assert_eq!(
+            bz.value(0).as_primitive::<Int16Type>().values().to_vec(),
+            vec![-1, 2]
+        );
+        assert!(bz.is_null(1));
+
+        let xu = tags.column(2).as_primitive::<UInt32Type>();
+        assert_eq!(xu.value(0), u32::MAX);
+        assert!(xu.is_null(1));
+
+        // Values that don't fit the column's type are an error rather than truncated.
+        let mut data = Data::default();
+        data.insert(Tag::ALIGNMENT_HIT_COUNT, Value::UInt32(u32::MAX));
+        assert!(builder.append(&data).is_err());
+
+        Ok(())
+    }
+}
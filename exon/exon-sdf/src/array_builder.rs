@@ -15,12 +15,19 @@
 use std::{collections::HashMap, sync::Arc};
 
 use arrow::{
-    array::{ArrayRef, GenericStringBuilder, StringBuilder, StructBuilder},
+    array::{
+        ArrayRef, Float64Builder, GenericStringBuilder, Int8Builder, ListBuilder, StringBuilder,
+        StructBuilder, UInt32Builder, UInt8Builder,
+    },
     datatypes::{DataType, Field, Fields},
 };
 use exon_common::ExonArrayBuilder;
 
-use crate::{record::Data, ExonSDFError, Record, SDFConfig};
+use crate::{
+    record::Data,
+    schema_builder::{atom_fields, bond_fields},
+    ExonSDFError, Record, SDFConfig,
+};
 
 struct DataArrayBuilder {
     inner: StructBuilder,
@@ -83,11 +90,70 @@ impl DataArrayBuilder {
 }
 
 // Structured Data File (SDF) Array Builder
+/// Builds the `atoms` and `bonds` list columns from the connection table of records.
+struct StructureArrayBuilder {
+    atoms: ListBuilder<StructBuilder>,
+    bonds: ListBuilder<StructBuilder>,
+}
+
+impl StructureArrayBuilder {
+    fn new() -> Self {
+        Self {
+            atoms: ListBuilder::new(StructBuilder::from_fields(atom_fields(), 0)),
+            bonds: ListBuilder::new(StructBuilder::from_fields(bond_fields(), 0)),
+        }
+    }
+
+    fn append_atoms(&mut self, record: &Record) -> crate::Result<()> {
+        let atoms = self.atoms.values();
+
+        for atom in record.atoms() {
+            struct_field::<StringBuilder>(atoms, 0)?.append_option(atom.element());
+            struct_field::<Float64Builder>(atoms, 1)?.append_value(atom.x());
+            struct_field::<Float64Builder>(atoms, 2)?.append_value(atom.y());
+            struct_field::<Float64Builder>(atoms, 3)?.append_value(atom.z());
+            struct_field::<Int8Builder>(atoms, 4)?.append_option(atom.charge());
+
+            atoms.append(true);
+        }
+
+        self.atoms.append(true);
+
+        Ok(())
+    }
+
+    fn append_bonds(&mut self, record: &Record) -> crate::Result<()> {
+        let bonds = self.bonds.values();
+
+        for bond in record.bonds() {
+            struct_field::<UInt32Builder>(bonds, 0)?.append_value(bond.atom1() as u32);
+            struct_field::<UInt32Builder>(bonds, 1)?.append_value(bond.atom2() as u32);
+            struct_field::<UInt8Builder>(bonds, 2)?.append_value(bond.bond_type());
+
+            bonds.append(true);
+        }
+
+        self.bonds.append(true);
+
+        Ok(())
+    }
+}
+
+fn struct_field<T: arrow::array::ArrayBuilder>(
+    builder: &mut StructBuilder,
+    i: usize,
+) -> crate::Result<&mut T> {
+    builder
+        .field_builder::<T>(i)
+        .ok_or_else(|| ExonSDFError::Internal(format!("Invalid structure field builder {}", i)))
+}
+
 pub(crate) struct SDFArrayBuilder {
     header: StringBuilder,
     atom_count: arrow::array::UInt32Builder,
     bond_count: arrow::array::UInt32Builder,
     data: DataArrayBuilder,
+    structure: StructureArrayBuilder,
     projection: Vec<usize>,
     n_rows: usize,
 }
@@ -107,6 +173,7 @@ impl SDFArrayBuilder {
         Ok(SDFArrayBuilder {
             n_rows: 0,
             data,
+            structure: StructureArrayBuilder::new(),
             header,
             atom_count,
             bond_count,
@@ -131,6 +198,12 @@ impl SDFArrayBuilder {
                 3 => {
                     self.data.append_value(record.data())?;
                 }
+                4 => {
+                    self.structure.append_atoms(&record)?;
+                }
+                5 => {
+                    self.structure.append_bonds(&record)?;
+                }
                 _ => {
                     return Err(ExonSDFError::InvalidColumnIndex(*col_idx));
                 }
@@ -166,6 +239,12 @@ impl ExonArrayBuilder for SDFArrayBuilder {
             3 => {
                 arrow_arrays.push(self.data.finish());
             }
+            4 => {
+                arrow_arrays.push(Arc::new(self.structure.atoms.finish()));
+            }
+            5 => {
+                arrow_arrays.push(Arc::new(self.structure.bonds.finish()));
+            }
             _ => {}
         });
 
@@ -15,13 +15,13 @@
 use std::sync::Arc;
 
 use arrow::{array::RecordBatch, error::ArrowError};
-use exon_common::ExonArrayBuilder;
+use exon_common::{BoundedReader, ExonArrayBuilder};
 use tokio::io::AsyncBufRead;
 
 use crate::config::SDFConfig;
 
 pub struct BatchReader<R> {
-    reader: crate::io::Reader<R>,
+    reader: crate::io::Reader<BoundedReader<R>>,
     n_records: usize,
     config: Arc<crate::config::SDFConfig>,
 }
@@ -32,7 +32,8 @@ where
 {
     pub fn new(inner: R, config: Arc<SDFConfig>) -> Self {
         BatchReader {
-            reader: crate::io::Reader::new(inner),
+            reader: crate::io::Reader::new(BoundedReader::new(inner, &config.reader_limits))
+                .with_reader_limits(config.reader_limits),
             config,
             n_records: 0,
         }
@@ -15,6 +15,7 @@
 use std::sync::Arc;
 
 use arrow::datatypes::SchemaRef;
+use exon_common::ReaderLimits;
 use object_store::ObjectStore;
 
 /// Configuration for a SDF data source.
@@ -34,6 +35,9 @@ pub struct SDFConfig {
 
     /// The limit of rows to read.
     pub limit: Option<usize>,
+
+    /// The limits on record size and data field count.
+    pub reader_limits: ReaderLimits,
 }
 
 impl SDFConfig {
@@ -48,6 +52,7 @@ impl SDFConfig {
             file_schema,
             projection: None,
             limit: None,
+            reader_limits: ReaderLimits::default(),
         }
     }
 
@@ -64,6 +69,12 @@ impl SDFConfig {
         self
     }
 
+    /// Set the reader limits.
+    pub fn with_reader_limits(mut self, reader_limits: ReaderLimits) -> Self {
+        self.reader_limits = reader_limits;
+        self
+    }
+
     /// Get the projection.
     pub fn projection(&self) -> Vec<usize> {
         self.projection
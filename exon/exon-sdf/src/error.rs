@@ -58,13 +58,21 @@ impl Display for ExonSDFError {
     }
 }
 
-impl Error for ExonSDFError {}
+impl Error for ExonSDFError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExonSDFError::IoError(err) => Some(err),
+            ExonSDFError::ArrowError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 pub type Result<T> = std::result::Result<T, ExonSDFError>;
 
 impl From<std::io::Error> for ExonSDFError {
     fn from(err: std::io::Error) -> Self {
-        ExonSDFError::Internal(err.to_string())
+        ExonSDFError::IoError(err)
     }
 }
 
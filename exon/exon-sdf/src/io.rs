@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use exon_common::{ReaderLimit, ReaderLimits};
 use tokio::io::{AsyncBufRead, AsyncBufReadExt};
 
 use crate::{record::parse_to_record, Record};
@@ -19,6 +20,7 @@ use crate::{record::parse_to_record, Record};
 /// A reader for reading records from an SD file.
 pub struct Reader<R> {
     inner: R,
+    limits: ReaderLimits,
 }
 
 impl<R> Reader<R>
@@ -26,7 +28,16 @@ where
     R: AsyncBufRead + Unpin,
 {
     pub fn new(inner: R) -> Self {
-        Reader { inner }
+        Reader {
+            inner,
+            limits: ReaderLimits::default(),
+        }
+    }
+
+    /// Set the limits on record size and data field count.
+    pub fn with_reader_limits(mut self, limits: ReaderLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
     /// Read a single record's bytes from the underlying reader.
@@ -40,6 +51,8 @@ where
                 return Ok(bytes_read);
             }
 
+            self.limits.check(ReaderLimit::RecordSize, buf.len())?;
+
             if buf.ends_with(b"$$$$\n") || buf.ends_with(b"$$$$\r\n") {
                 return Ok(bytes_read);
             }
@@ -58,6 +71,11 @@ where
         let s = std::str::from_utf8(&buf)?;
 
         let record = parse_to_record(s)?;
+
+        self.limits
+            .check(ReaderLimit::AttributeCount, record.data().len())
+            .map_err(std::io::Error::from)?;
+
         Ok(Some(record))
     }
 }
@@ -113,4 +131,45 @@ $$$$
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_read_record_over_limits() -> crate::Result<()> {
+        let molfile_content = r#"
+    Methane
+    Example
+
+2  1  0  0  0  0            999 V2000
+    0.0000    0.0000    0.0000 C   0  0  0  0  0  0
+    0.0000    1.0000    0.0000 H   0  0  0  0  0  0
+1  2  1  0  0  0
+M  END
+> <MELTING.POINT>
+-182.5
+
+> <BOILING.POINT>
+-161.5
+
+$$$$
+"#
+        .trim();
+
+        let limits = ReaderLimits {
+            max_attribute_count: 1,
+            ..Default::default()
+        };
+        let mut reader =
+            Reader::new(std::io::Cursor::new(molfile_content)).with_reader_limits(limits);
+        assert!(reader.read_record().await.is_err());
+
+        let limits = ReaderLimits {
+            max_record_size: 32,
+            ..Default::default()
+        };
+        let mut reader =
+            Reader::new(std::io::Cursor::new(molfile_content)).with_reader_limits(limits);
+        let err = reader.read_record().await.unwrap_err();
+        assert!(exon_common::ReaderLimitError::find(&err).is_some());
+
+        Ok(())
+    }
 }
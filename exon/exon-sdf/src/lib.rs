@@ -25,9 +25,13 @@ pub(crate) use record::Record;
 mod array_builder;
 mod batch_reader;
 mod config;
+mod property_index;
 mod schema_builder;
 
 pub use batch_reader::BatchReader;
 pub use config::SDFConfig;
+pub use property_index::{
+    PropertyIndex, PropertyIndexChunk, DEFAULT_RECORDS_PER_CHUNK, PROPERTY_INDEX_EXTENSION,
+};
 pub use record::parse_to_record;
-pub use schema_builder::SDFSchemaBuilder;
+pub use schema_builder::{structure_fields, SDFSchemaBuilder};
@@ -0,0 +1,283 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use tokio::io::AsyncBufRead;
+
+use crate::{io::Reader, record::parse_to_record, ExonSDFError};
+
+/// The extension of the property index sidecar, which is appended to the SDF file name.
+pub const PROPERTY_INDEX_EXTENSION: &str = "sdfidx";
+
+/// The default number of records summarized by each chunk of the property index.
+pub const DEFAULT_RECORDS_PER_CHUNK: usize = 512;
+
+const PROPERTY_INDEX_HEADER: &str = "#exon-sdf-property-index\tv1";
+
+/// The min/max values of the numeric properties of a chunk of consecutive records.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyIndexChunk {
+    start: u64,
+    end: u64,
+    ranges: BTreeMap<String, (f64, f64)>,
+}
+
+impl PropertyIndexChunk {
+    /// The byte offset of the first record of the chunk.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// The byte offset just past the last record of the chunk.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// The min/max values of a property, or `None` if some of its values aren't numeric.
+    pub fn range(&self, property: &str) -> Option<(f64, f64)> {
+        self.ranges.get(property).copied()
+    }
+}
+
+/// A sidecar index of property min/max values per chunk of an uncompressed SDF file, which lets
+/// scans skip chunks that can't match a filter on a property.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PropertyIndex {
+    chunks: Vec<PropertyIndexChunk>,
+}
+
+impl PropertyIndex {
+    /// The chunks of the index in file order.
+    pub fn chunks(&self) -> &[PropertyIndexChunk] {
+        &self.chunks
+    }
+
+    /// Build the index by reading every record of an SDF file.
+    pub async fn build<R>(inner: R, records_per_chunk: usize) -> crate::Result<Self>
+    where
+        R: AsyncBufRead + Unpin,
+    {
+        let mut reader = Reader::new(inner);
+        let mut chunks = Vec::new();
+
+        let mut builder = ChunkBuilder::new(0);
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+            let n = reader.read_record_bytes(&mut buf).await?;
+
+            if n == 0 || buf.iter().all(|b| b.is_ascii_whitespace()) {
+                break;
+            }
+
+            let record = parse_to_record(std::str::from_utf8(&buf)?)?;
+            builder.push(record.data(), n as u64);
+
+            if builder.n_records == records_per_chunk {
+                let end = builder.end;
+                chunks.push(builder.finish());
+                builder = ChunkBuilder::new(end);
+            }
+        }
+
+        if builder.n_records > 0 {
+            chunks.push(builder.finish());
+        }
+
+        Ok(Self { chunks })
+    }
+
+    /// Serialize the index as tab-separated lines of chunk offsets and property ranges.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut s = String::from(PROPERTY_INDEX_HEADER);
+        s.push('\n');
+
+        for chunk in &self.chunks {
+            s.push_str(&format!("{}\t{}\n", chunk.start, chunk.end));
+
+            for (property, (min, max)) in &chunk.ranges {
+                s.push_str(&format!("\t\t{}\t{}\t{}\n", property, min, max));
+            }
+        }
+
+        s.into_bytes()
+    }
+
+    /// Parse an index serialized with [`PropertyIndex::to_bytes`].
+    pub fn try_from_bytes(bytes: &[u8]) -> crate::Result<Self> {
+        let s = std::str::from_utf8(bytes)?;
+        let mut lines = s.lines();
+
+        if lines.next() != Some(PROPERTY_INDEX_HEADER) {
+            return Err(ExonSDFError::ParseError(
+                "Invalid SDF property index header".to_string(),
+            ));
+        }
+
+        let invalid = |line: &str| {
+            ExonSDFError::ParseError(format!("Invalid SDF property index line: {}", line))
+        };
+
+        let mut chunks: Vec<PropertyIndexChunk> = Vec::new();
+
+        for line in lines {
+            let fields = line.split('\t').collect::<Vec<_>>();
+
+            match fields.as_slice() {
+                [start, end] => chunks.push(PropertyIndexChunk {
+                    start: start.parse().map_err(|_| invalid(line))?,
+                    end: end.parse().map_err(|_| invalid(line))?,
+                    ranges: BTreeMap::new(),
+                }),
+                ["", "", property, min, max] => {
+                    let chunk = chunks.last_mut().ok_or_else(|| invalid(line))?;
+
+                    let min = min.parse().map_err(|_| invalid(line))?;
+                    let max = max.parse().map_err(|_| invalid(line))?;
+
+                    chunk.ranges.insert(property.to_string(), (min, max));
+                }
+                _ => return Err(invalid(line)),
+            }
+        }
+
+        Ok(Self { chunks })
+    }
+}
+
+struct ChunkBuilder {
+    start: u64,
+    end: u64,
+    n_records: usize,
+    ranges: BTreeMap<String, (f64, f64)>,
+    non_numeric: BTreeSet<String>,
+}
+
+impl ChunkBuilder {
+    fn new(start: u64) -> Self {
+        Self {
+            start,
+            end: start,
+            n_records: 0,
+            ranges: BTreeMap::new(),
+            non_numeric: BTreeSet::new(),
+        }
+    }
+
+    fn push(&mut self, data: &crate::record::Data, n_bytes: u64) {
+        for datum in data {
+            if self.non_numeric.contains(datum.header()) {
+                continue;
+            }
+
+            match datum.data().trim().parse::<f64>() {
+                Ok(value) if value.is_finite() => {
+                    let range = self
+                        .ranges
+                        .entry(datum.header().to_string())
+                        .or_insert((value, value));
+
+                    range.0 = range.0.min(value);
+                    range.1 = range.1.max(value);
+                }
+                _ => {
+                    self.ranges.remove(datum.header());
+                    self.non_numeric.insert(datum.header().to_string());
+                }
+            }
+        }
+
+        self.end += n_bytes;
+        self.n_records += 1;
+    }
+
+    fn finish(self) -> PropertyIndexChunk {
+        PropertyIndexChunk {
+            start: self.start,
+            end: self.end,
+            ranges: self.ranges,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PropertyIndex;
+
+    const CONTENT: &str = "Methane
+  Example
+
+  1  0  0  0  0  0            999 V2000
+    0.0000    0.0000    0.0000 C   0  0  0  0  0  0
+M  END
+> <WEIGHT>
+16.04
+
+> <NAME>
+methane
+
+$$$$
+Ethane
+  Example
+
+  2  1  0  0  0  0            999 V2000
+    0.0000    0.0000    0.0000 C   0  0  0  0  0  0
+    1.0000    0.0000    0.0000 C   0  0  0  0  0  0
+  1  2  1  0  0  0
+M  END
+> <WEIGHT>
+30.07
+
+> <NAME>
+ethane
+
+$$$$
+Water
+  Example
+
+  1  0  0  0  0  0            999 V2000
+    0.0000    0.0000    0.0000 O   0  0  0  0  0  0
+M  END
+> <WEIGHT>
+unknown
+
+$$$$
+";
+
+    #[tokio::test]
+    async fn test_build_property_index() -> crate::Result<()> {
+        let index = PropertyIndex::build(std::io::Cursor::new(CONTENT), 2).await?;
+
+        let chunks = index.chunks();
+        assert_eq!(chunks.len(), 2);
+
+        assert_eq!(chunks[0].start(), 0);
+        assert_eq!(chunks[0].range("WEIGHT"), Some((16.04, 30.07)));
+        assert_eq!(chunks[0].range("NAME"), None);
+
+        assert_eq!(chunks[1].start(), chunks[0].end());
+        assert_eq!(chunks[1].end(), CONTENT.len() as u64);
+        assert_eq!(chunks[1].range("WEIGHT"), None);
+
+        let second = &CONTENT[chunks[1].start() as usize..];
+        assert!(second.starts_with("Water"));
+
+        let roundtrip = PropertyIndex::try_from_bytes(&index.to_bytes())?;
+        assert_eq!(roundtrip, index);
+
+        Ok(())
+    }
+}
@@ -71,6 +71,50 @@ impl Record {
         &self.data
     }
 
+    /// Apply the formal charges of `M  CHG` property lines, which supersede the charges of the
+    /// atom block when present.
+    fn apply_charge_properties(&mut self, properties: &[&str]) -> crate::Result<()> {
+        let mut charge_lines = properties
+            .iter()
+            .filter_map(|line| line.strip_prefix("M  CHG"))
+            .peekable();
+
+        if charge_lines.peek().is_none() {
+            return Ok(());
+        }
+
+        for atom in self.atoms.iter_mut() {
+            atom.set_charge(0);
+        }
+
+        for line in charge_lines {
+            let invalid =
+                || ExonSDFError::ParseError(format!("Failed to parse charge line: {}", line));
+
+            let values = line
+                .split_whitespace()
+                .skip(1)
+                .map(|v| v.parse::<i64>().map_err(|_| invalid()))
+                .collect::<crate::Result<Vec<_>>>()?;
+
+            for pair in values.chunks(2) {
+                let &[atom, charge] = pair else {
+                    return Err(invalid());
+                };
+
+                let atom = usize::try_from(atom)
+                    .ok()
+                    .and_then(|atom| atom.checked_sub(1))
+                    .and_then(|i| self.atoms.get_mut(i))
+                    .ok_or_else(invalid)?;
+
+                atom.set_charge(i8::try_from(charge).map_err(|_| invalid())?);
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn data_mut(&mut self) -> &mut Data {
         &mut self.data
     }
@@ -183,12 +227,59 @@ pub fn parse_to_record(content: &str) -> crate::Result<Record> {
         }
     }
 
-    Ok(Record {
+    let mut record = Record {
         header,
         atom_count,
         bond_count,
         atoms,
         bonds,
         data,
-    })
+    };
+
+    record.apply_charge_properties(&properties)?;
+
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_to_record;
+
+    #[test]
+    fn test_charges() -> crate::Result<()> {
+        let content = "Acetate
+  Example
+
+  3  1  0  0  0  0            999 V2000
+    0.0000    0.0000    0.0000 C   0  0
+    1.0000    0.0000    0.0000 O   0  5
+    2.0000    0.0000    0.0000 Na  0  3
+  1  2  1  0
+M  END
+$$$$
+";
+
+        let record = parse_to_record(content)?;
+        let charges = record
+            .atoms()
+            .iter()
+            .map(|a| a.charge())
+            .collect::<Vec<_>>();
+        assert_eq!(charges, vec![Some(0), Some(-1), Some(1)]);
+
+        let content = content.replace("M  END", "M  CHG  1   2  -2\nM  END");
+
+        let record = parse_to_record(&content)?;
+        let charges = record
+            .atoms()
+            .iter()
+            .map(|a| a.charge())
+            .collect::<Vec<_>>();
+        assert_eq!(charges, vec![Some(0), Some(-2), Some(0)]);
+
+        let bond = &record.bonds()[0];
+        assert_eq!((bond.atom1(), bond.atom2(), bond.bond_type()), (1, 2, 1));
+
+        Ok(())
+    }
 }
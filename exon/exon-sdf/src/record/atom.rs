@@ -19,6 +19,7 @@ pub struct Atom {
     z: f64,
     element: Option<String>,
     mass_difference: Option<i8>,
+    /// The formal charge, converted from the atom block's charge code.
     charge: Option<i8>,
     stereochemistry: Option<i8>,
     hydrogen_count: Option<i8>,
@@ -77,6 +78,32 @@ impl Atom {
         self
     }
 
+    /// The element symbol.
+    pub fn element(&self) -> Option<&str> {
+        self.element.as_deref()
+    }
+
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+
+    pub fn z(&self) -> f64 {
+        self.z
+    }
+
+    /// The formal charge, e.g. -1.
+    pub fn charge(&self) -> Option<i8> {
+        self.charge
+    }
+
+    pub(super) fn set_charge(&mut self, charge: i8) {
+        self.charge = Some(charge);
+    }
+
     pub(super) fn parse(line: &str) -> crate::Result<Self> {
         let parts: Vec<&str> = line.split_whitespace().collect();
 
@@ -92,7 +119,10 @@ impl Atom {
 
         let element = parts.get(3).map(|s| s.to_string());
         let mass_difference = parts.get(4).and_then(|s| s.parse::<i8>().ok());
-        let charge = parts.get(5).and_then(|s| s.parse::<i8>().ok());
+        let charge = parts
+            .get(5)
+            .and_then(|s| s.parse::<i8>().ok())
+            .map(formal_charge);
         let stereochemistry = parts.get(6).and_then(|s| s.parse::<i8>().ok());
         let hydrogen_count = parts.get(7).and_then(|s| s.parse::<i8>().ok());
         let stereo_care = parts.get(8).and_then(|s| s.parse::<i8>().ok());
@@ -110,3 +140,12 @@ impl Atom {
         Ok(atom)
     }
 }
+
+/// Convert an atom block charge code to a formal charge, e.g. 3 is +1 and 5 is -1. The doublet
+/// radical code 4 has no charge.
+fn formal_charge(code: i8) -> i8 {
+    match code {
+        1..=3 | 5..=7 => 4 - code,
+        _ => 0,
+    }
+}
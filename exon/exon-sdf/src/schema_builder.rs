@@ -14,7 +14,7 @@
 
 use std::sync::Arc;
 
-use arrow::datatypes::{Field, Schema};
+use arrow::datatypes::{DataType, Field, Fields, Schema};
 use exon_common::TableSchema;
 
 use crate::record::Data;
@@ -83,6 +83,11 @@ impl SDFSchemaBuilder {
         self.file_fields[3] = Field::new("data", struct_type, false);
     }
 
+    /// Adds the `atoms` and `bonds` columns of the connection table after the data field.
+    pub fn add_structure_fields(&mut self) {
+        self.file_fields.extend(structure_fields());
+    }
+
     /// Builds the schema.
     pub fn build(self) -> TableSchema {
         let mut fields = self.file_fields.clone();
@@ -95,3 +100,35 @@ impl SDFSchemaBuilder {
         TableSchema::new(Arc::new(schema), projection)
     }
 }
+
+/// The fields of an atom, its element, coordinates, and formal charge.
+pub(crate) fn atom_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("element", DataType::Utf8, true),
+        Field::new("x", DataType::Float64, false),
+        Field::new("y", DataType::Float64, false),
+        Field::new("z", DataType::Float64, false),
+        Field::new("charge", DataType::Int8, true),
+    ])
+}
+
+/// The fields of a bond, the 1-based indices of its atoms and its bond order.
+pub(crate) fn bond_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("a1", DataType::UInt32, false),
+        Field::new("a2", DataType::UInt32, false),
+        Field::new("order", DataType::UInt8, false),
+    ])
+}
+
+/// The `atoms` and `bonds` list columns of the connection table.
+pub fn structure_fields() -> Vec<Field> {
+    let list = |fields: Fields| {
+        DataType::List(Arc::new(Field::new("item", DataType::Struct(fields), true)))
+    };
+
+    vec![
+        Field::new("atoms", list(atom_fields()), false),
+        Field::new("bonds", list(bond_fields()), false),
+    ]
+}
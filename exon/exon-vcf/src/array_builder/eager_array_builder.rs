@@ -21,13 +21,19 @@ use arrow::{
 };
 use exon_common::ExonArrayBuilder;
 use noodles::vcf::{
-    variant::record::{AlternateBases, Filters, Ids},
+    variant::record::{AlternateBases, Ids},
     Header,
 };
 
 use noodles::vcf::variant::Record as VCFRecord;
 
-use super::{GenotypeBuilder, InfosBuilder};
+use super::{
+    structural_variant_builder::{
+        StructuralVariant, StructuralVariantBuilder, STRUCTURAL_VARIANT_COLUMN_OFFSET,
+    },
+    FiltersBuilder, GenotypeBuilder, InfosBuilder,
+};
+use crate::SampleSelection;
 
 /// A builder for creating a `ArrayRef` from a `VCF` file.
 pub struct VCFArrayBuilder {
@@ -37,10 +43,11 @@ pub struct VCFArrayBuilder {
     references: GenericStringBuilder<i32>,
     alternates: GenericListBuilder<i32, GenericStringBuilder<i32>>,
     qualities: Float32Builder,
-    filters: GenericListBuilder<i32, GenericStringBuilder<i32>>,
+    filters: FiltersBuilder,
 
     infos: InfosBuilder,
     formats: GenotypeBuilder,
+    structural_variants: StructuralVariantBuilder,
 
     header: Arc<Header>,
 
@@ -77,24 +84,32 @@ impl VCFArrayBuilder {
                 GenericStringBuilder::<i32>::new(),
             ),
             qualities: Float32Builder::new(),
-            filters: GenericListBuilder::<i32, GenericStringBuilder<i32>>::new(
-                GenericStringBuilder::<i32>::new(),
-            ),
+            filters: FiltersBuilder::try_new(schema.field_with_name("filter")?, capacity)?,
 
             infos: InfosBuilder::try_new(info_field, header.clone(), capacity)?,
 
             formats: GenotypeBuilder::try_new(format_field, capacity)?,
+            structural_variants: StructuralVariantBuilder::with_capacity(capacity),
             header,
 
             projection,
         })
     }
 
+    /// Only append the selected samples of each record to the formats column.
+    pub fn with_sample_selection(mut self, sample_selection: Option<SampleSelection>) -> Self {
+        self.formats = self.formats.with_sample_selection(sample_selection);
+        self
+    }
+
     /// Appends a record to the builder.
     pub fn append<T>(&mut self, record: T) -> Result<(), ArrowError>
     where
         T: VCFRecord,
     {
+        // The structural variant columns share their parsing, so it's done once per record.
+        let mut structural_variant = None;
+
         for col_idx in self.projection.iter() {
             match col_idx {
                 0 => {
@@ -137,14 +152,7 @@ impl VCFArrayBuilder {
                     self.qualities.append_option(quality_score);
                 }
                 6 => {
-                    let filters = record.filters();
-
-                    for filter in filters.iter(&self.header) {
-                        let filter = filter?;
-                        self.filters.values().append_value(filter);
-                    }
-
-                    self.filters.append(true);
+                    self.filters.append_value(record.filters(), &self.header)?;
                 }
                 7 => {
                     let info = record.info();
@@ -154,6 +162,19 @@ impl VCFArrayBuilder {
                     let samples = record.samples()?;
                     self.formats.append_value(samples, &self.header)?;
                 }
+                col_idx if *col_idx >= STRUCTURAL_VARIANT_COLUMN_OFFSET => {
+                    if structural_variant.is_none() {
+                        structural_variant =
+                            Some(StructuralVariant::try_new(&record, &self.header)?);
+                    }
+
+                    if let Some(structural_variant) = &structural_variant {
+                        self.structural_variants.append(
+                            col_idx - STRUCTURAL_VARIANT_COLUMN_OFFSET,
+                            structural_variant,
+                        )?;
+                    }
+                }
                 _ => Err(ArrowError::InvalidArgumentError(
                     "Invalid column index".to_string(),
                 ))?,
@@ -178,9 +199,16 @@ impl ExonArrayBuilder for VCFArrayBuilder {
                 3 => arrays.push(Arc::new(self.references.finish())),
                 4 => arrays.push(Arc::new(self.alternates.finish())),
                 5 => arrays.push(Arc::new(self.qualities.finish())),
-                6 => arrays.push(Arc::new(self.filters.finish())),
+                6 => arrays.push(self.filters.finish()),
                 7 => arrays.push(Arc::new(self.infos.finish())),
                 8 => arrays.push(Arc::new(self.formats.finish())),
+                col_idx if *col_idx >= STRUCTURAL_VARIANT_COLUMN_OFFSET => {
+                    let array = self
+                        .structural_variants
+                        .finish(col_idx - STRUCTURAL_VARIANT_COLUMN_OFFSET)
+                        .expect("invalid structural variant column");
+                    arrays.push(array);
+                }
                 _ => panic!("Not implemented"),
             }
         }
@@ -0,0 +1,144 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{ArrayRef, GenericListBuilder, GenericStringBuilder, StringDictionaryBuilder},
+    datatypes::{DataType, Field, Int32Type},
+    error::ArrowError,
+};
+use noodles::vcf::{variant::record::Filters, Header};
+
+/// Builder for the filters of a record batch, either a list of strings or a list of dictionary
+/// encoded strings, so a filter that's repeated across records is stored once per batch.
+pub enum FiltersBuilder {
+    String(GenericListBuilder<i32, GenericStringBuilder<i32>>),
+    Dictionary(GenericListBuilder<i32, StringDictionaryBuilder<Int32Type>>),
+}
+
+impl FiltersBuilder {
+    pub fn try_new(field: &Field, capacity: usize) -> Result<Self, ArrowError> {
+        let item_type = match field.data_type() {
+            DataType::List(item) => item.data_type(),
+            dt => {
+                return Err(ArrowError::SchemaError(format!(
+                    "Unexpected type for VCF filters: {:?}",
+                    dt
+                )))
+            }
+        };
+
+        match item_type {
+            DataType::Utf8 => Ok(Self::String(GenericListBuilder::with_capacity(
+                GenericStringBuilder::new(),
+                capacity,
+            ))),
+            DataType::Dictionary(key, value)
+                if **key == DataType::Int32 && **value == DataType::Utf8 =>
+            {
+                Ok(Self::Dictionary(GenericListBuilder::with_capacity(
+                    StringDictionaryBuilder::new(),
+                    capacity,
+                )))
+            }
+            dt => Err(ArrowError::SchemaError(format!(
+                "Unexpected type for VCF filters: {:?}",
+                dt
+            ))),
+        }
+    }
+
+    /// Appends the filters of a record.
+    pub fn append_value<'a>(
+        &mut self,
+        filters: Box<dyn Filters + 'a>,
+        header: &Header,
+    ) -> Result<(), ArrowError> {
+        for filter in filters.iter(header) {
+            let filter = filter?;
+
+            match self {
+                Self::String(builder) => builder.values().append_value(filter),
+                Self::Dictionary(builder) => {
+                    builder.values().append_value(filter);
+                }
+            }
+        }
+
+        match self {
+            Self::String(builder) => builder.append(true),
+            Self::Dictionary(builder) => builder.append(true),
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::String(builder) => Arc::new(builder.finish()),
+            Self::Dictionary(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Array, AsArray},
+        datatypes::{DataType, Field, Int32Type},
+    };
+    use noodles::vcf;
+
+    use super::FiltersBuilder;
+
+    #[test]
+    fn test_dictionary_filters() -> Result<(), Box<dyn std::error::Error>> {
+        let src = b"##fileformat=VCFv4.3
+##FILTER=<ID=q10,Description=\"Quality below 10\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+1\t1\t.\tA\tC\t.\tPASS\t.
+1\t2\t.\tA\tC\t.\tq10\t.
+1\t3\t.\tA\tC\t.\tPASS\t.
+";
+
+        let mut reader = vcf::io::Reader::new(&src[..]);
+        let header = reader.read_header()?;
+
+        let item = DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8));
+        let field = Field::new(
+            "filter",
+            DataType::List(Arc::new(Field::new("item", item, true))),
+            true,
+        );
+
+        let mut builder = FiltersBuilder::try_new(&field, 3)?;
+
+        for result in reader.records() {
+            let record = result?;
+            builder.append_value(Box::new(record.filters()), &header)?;
+        }
+
+        let array = builder.finish();
+        assert_eq!(array.data_type(), field.data_type());
+
+        let values = array.as_list::<i32>().values().as_dictionary::<Int32Type>();
+        assert_eq!(values.len(), 3);
+        assert_eq!(values.values().len(), 2);
+
+        Ok(())
+    }
+}
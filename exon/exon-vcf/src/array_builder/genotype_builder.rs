@@ -15,16 +15,16 @@
 use arrow::{
     array::{
         make_builder, ArrayBuilder, Float32Builder, GenericListArray, GenericListBuilder,
-        GenericStringBuilder, Int32Builder, StructBuilder,
+        GenericStringBuilder, Int32Builder, StringDictionaryBuilder, StructBuilder,
     },
-    datatypes::{DataType, Field, Fields},
+    datatypes::{DataType, Field, Fields, Int32Type},
     error::ArrowError,
 };
 use noodles::vcf::{
     variant::record::{
         samples::{
             series::{
-                value::{genotype::Phasing, Array},
+                value::{genotype::Phasing, Array, Genotype},
                 Value,
             },
             Sample,
@@ -34,10 +34,13 @@ use noodles::vcf::{
     Header,
 };
 
+use crate::SampleSelection;
+
 /// Builder for the genotypes of a record batch.
 pub struct GenotypeBuilder {
     inner: GenericListBuilder<i32, StructBuilder>,
     fields: Fields,
+    selection: Option<SampleSelection>,
 }
 
 impl GenotypeBuilder {
@@ -107,9 +110,16 @@ impl GenotypeBuilder {
         Ok(Self {
             inner,
             fields: fields.clone(),
+            selection: None,
         })
     }
 
+    /// Only append the selected samples of each record.
+    pub fn with_sample_selection(mut self, selection: Option<SampleSelection>) -> Self {
+        self.selection = selection;
+        self
+    }
+
     pub fn finish(&mut self) -> GenericListArray<i32> {
         self.inner.finish()
     }
@@ -118,209 +128,497 @@ impl GenotypeBuilder {
         self.inner.append(false);
     }
 
-    /// Appends a record to the builder.
+    /// Appends a record to the builder, with a struct per sample in the header's sample order, or
+    /// per selected sample in the selection's order.
     ///
-    /// It is important that the passed genotypes was parsed using the same header as the one used
-    /// to create this builder. If not, some types may not match and the append will fail.
+    /// Values are converted to the types of the builder's fields, so a record parsed with a
+    /// different header than the one used to build the schema is still appended as long as its
+    /// values can be converted, e.g. an Integer to a Float32 field or a single value to a list
+    /// field. Missing values, `.`, and fields missing from a sample are null, and missing values
+    /// inside a list are null items.
     pub fn append_value<'a>(
         &mut self,
         samples: Box<dyn VCFSamples + 'a>,
         header: &Header,
     ) -> Result<(), ArrowError> {
-        for sample in samples.iter() {
-            for (i, field) in self.fields.clone().iter().enumerate() {
-                let field_name = field.name().to_string();
-                let field_type = field.data_type();
-
-                let value = sample.get(header, &field_name).transpose()?;
-
-                match value {
-                    None | Some(None) => match field_type {
-                        DataType::Int32 => {
-                            self.inner
-                                .values()
-                                .field_builder::<Int32Builder>(i)
-                                .expect("expected an int32 builder")
-                                .append_null();
-                        }
-                        DataType::Utf8 => {
-                            self.inner
-                                .values()
-                                .field_builder::<GenericStringBuilder<i32>>(i)
-                                .expect("expected a string builder")
-                                .append_null();
-                        }
-                        DataType::List(inner_type) => match inner_type.data_type() {
-                            DataType::Int32 => {
-                                let vs = self
-                                    .inner
-                                    .values()
-                                    .field_builder::<GenericListBuilder<i32, Int32Builder>>(i)
-                                    .expect("expected a list builder");
-
-                                vs.append_null();
-                            }
-                            DataType::Float32 => {
-                                let vs = self
-                                    .inner
-                                    .values()
-                                    .field_builder::<GenericListBuilder<i32, Float32Builder>>(i)
-                                    .expect("expected a list builder");
-
-                                vs.append_null();
-                            }
-                            DataType::Utf8 => {
-                                let vs = self
-                                    .inner
-                                    .values()
-                                    .field_builder::<GenericListBuilder<i32, GenericStringBuilder<i32>>>(
-                                        i,
-                                    )
-                                    .expect("expected a list builder");
-
-                                vs.append_null();
-                            }
-                            _ => unimplemented!(),
-                        },
-                        DataType::Float32 => {
-                            self.inner
-                                .values()
-                                .field_builder::<Float32Builder>(i)
-                                .expect("expected a float32 builder")
-                                .append_null();
-                        }
-                        _ => unimplemented!(),
-                    },
-                    Some(Some(value)) => match value {
-                        Value::Integer(int_val) => self
-                            .inner
-                            .values()
-                            .field_builder::<Int32Builder>(i)
-                            .expect("expected an int32 builder")
-                            .append_value(int_val),
-                        Value::String(string_val) => self
-                            .inner
-                            .values()
-                            .field_builder::<GenericStringBuilder<i32>>(i)
-                            .expect("expected a string builder")
-                            .append_value(string_val),
-                        Value::Character(char_val) => self
-                            .inner
-                            .values()
-                            .field_builder::<GenericStringBuilder<i32>>(i)
-                            .expect("expected a string builder")
-                            .append_value(&char_val.to_string()),
-                        Value::Float(float_val) => self
-                            .inner
-                            .values()
-                            .field_builder::<Float32Builder>(i)
-                            .expect("expected a float32 builder")
-                            .append_value(float_val),
-                        Value::Genotype(gt) => {
-                            let mut gt_strs = Vec::new();
-                            let mut previous_phasing = "/";
-
-                            for gtt in gt.iter() {
-                                let (allele, phasing) = gtt?;
-
-                                let allele_str =
-                                    allele.map_or(String::from("."), |v| v.to_string());
-
-                                // Determine the phasing symbol for the current allele based on the previous allele
-                                let phasing_str = match phasing {
-                                    Phasing::Unphased => "/",
-                                    Phasing::Phased => "|",
-                                };
-
-                                // For the first allele, don't prepend phasing symbol; otherwise, use the previous phasing
-                                if gt_strs.is_empty() {
-                                    gt_strs.push(allele_str);
-                                } else {
-                                    gt_strs.push(format!("{}{}", previous_phasing, allele_str));
-                                }
-
-                                // Update previous_phasing to the current allele's phasing for the next iteration
-                                previous_phasing = phasing_str;
-                            }
-
-                            let builder = self
-                                .inner
-                                .values()
-                                .field_builder::<GenericStringBuilder<i32>>(i)
-                                .expect("expected a string builder");
-
-                            let gt_str = gt_strs.join("");
-                            builder.append_value(&gt_str);
-                        }
-                        Value::Array(array) => match array {
-                            Array::Float(float_array) => {
-                                let builder = self
-                                    .inner
-                                    .values()
-                                    .field_builder::<GenericListBuilder<i32, Float32Builder>>(i)
-                                    .expect("expected a list builder");
-
-                                let builder_values = builder.values();
-                                for v in float_array.iter() {
-                                    let v = v?;
-                                    builder_values.append_option(v);
-                                }
-                                builder.append(true);
-                            }
-                            Array::Integer(int_array) => {
-                                let builder = self
-                                    .inner
-                                    .values()
-                                    .field_builder::<GenericListBuilder<i32, Int32Builder>>(i)
-                                    .expect("expected a list builder");
-
-                                let builder_values = builder.values();
-                                for v in int_array.iter() {
-                                    let v = v?;
-                                    builder_values.append_option(v);
-                                }
-                                builder.append(true);
-                            }
-                            Array::Character(char_array) => {
-                                let builder = self
-                                    .inner
-                                    .values()
-                                    .field_builder::<GenericListBuilder<i32, GenericStringBuilder<i32>>>(
-                                        i,
-                                    )
-                                    .expect("expected a list builder");
-
-                                let builder_values = builder.values();
-                                for v in char_array.iter() {
-                                    let v = v?;
-                                    builder_values.append_option(v.as_ref().map(|c| c.to_string()));
-                                }
-                                builder.append(true);
-                            }
-                            Array::String(string_array) => {
-                                let builder = self
-                                    .inner
-                                    .values()
-                                    .field_builder::<GenericListBuilder<i32, GenericStringBuilder<i32>>>(
-                                        i,
-                                    )
-                                    .expect("expected a list builder");
-
-                                let builder_values = builder.values();
-                                for v in string_array.iter() {
-                                    let v = v?;
-                                    builder_values.append_option(v);
-                                }
-                                builder.append(true);
-                            }
-                        },
-                    },
+        let selected = self
+            .selection
+            .as_ref()
+            .map(|selection| selection.select(samples.iter()));
+
+        match selected {
+            Some(selected) => {
+                for sample in selected {
+                    self.append_sample(sample.as_deref(), header)?;
+                }
+            }
+            None => {
+                for sample in samples.iter() {
+                    self.append_sample(Some(sample.as_ref()), header)?;
                 }
             }
-            self.inner.values().append(true);
         }
+
         self.inner.append(true);
 
         Ok(())
     }
+
+    /// Appends the struct of a sample, with null fields if the record doesn't have the sample.
+    fn append_sample(
+        &mut self,
+        sample: Option<&dyn Sample>,
+        header: &Header,
+    ) -> Result<(), ArrowError> {
+        for (i, field) in self.fields.clone().iter().enumerate() {
+            let value = match sample {
+                Some(sample) => sample.get(header, field.name()).transpose()?.flatten(),
+                None => None,
+            };
+
+            let values = match value {
+                Some(value) => Some(FieldValues::try_new(value, field)?),
+                None => None,
+            };
+
+            self.append_field(i, field, values)?;
+        }
+
+        self.inner.values().append(true);
+
+        Ok(())
+    }
+
+    fn append_field(
+        &mut self,
+        i: usize,
+        field: &Field,
+        values: Option<FieldValues>,
+    ) -> Result<(), ArrowError> {
+        let builder = self.inner.values();
+
+        let missing_builder = || {
+            ArrowError::InvalidArgumentError(format!(
+                "unexpected builder for format field {}",
+                field.name()
+            ))
+        };
+
+        match (field.data_type(), values) {
+            (DataType::List(item), values) => match values {
+                Some(FieldValues::Int32(values)) => {
+                    let list = builder
+                        .field_builder::<GenericListBuilder<i32, Int32Builder>>(i)
+                        .ok_or_else(missing_builder)?;
+
+                    list.values().extend(values);
+                    list.append(true);
+                }
+                Some(FieldValues::Float32(values)) => {
+                    let list = builder
+                        .field_builder::<GenericListBuilder<i32, Float32Builder>>(i)
+                        .ok_or_else(missing_builder)?;
+
+                    list.values().extend(values);
+                    list.append(true);
+                }
+                Some(FieldValues::Utf8(values)) => {
+                    let list = builder
+                        .field_builder::<GenericListBuilder<i32, GenericStringBuilder<i32>>>(i)
+                        .ok_or_else(missing_builder)?;
+
+                    list.values().extend(values);
+                    list.append(true);
+                }
+                None => match item.data_type() {
+                    DataType::Int32 => builder
+                        .field_builder::<GenericListBuilder<i32, Int32Builder>>(i)
+                        .ok_or_else(missing_builder)?
+                        .append_null(),
+                    DataType::Float32 => builder
+                        .field_builder::<GenericListBuilder<i32, Float32Builder>>(i)
+                        .ok_or_else(missing_builder)?
+                        .append_null(),
+                    _ => builder
+                        .field_builder::<GenericListBuilder<i32, GenericStringBuilder<i32>>>(i)
+                        .ok_or_else(missing_builder)?
+                        .append_null(),
+                },
+            },
+            (DataType::Int32, values) => {
+                let value = values.map(|v| v.into_single(field)).transpose()?;
+
+                builder
+                    .field_builder::<Int32Builder>(i)
+                    .ok_or_else(missing_builder)?
+                    .append_option(value.and_then(|v| v.as_int32()));
+            }
+            (DataType::Float32, values) => {
+                let value = values.map(|v| v.into_single(field)).transpose()?;
+
+                builder
+                    .field_builder::<Float32Builder>(i)
+                    .ok_or_else(missing_builder)?
+                    .append_option(value.and_then(|v| v.as_float32()));
+            }
+            (DataType::Utf8, values) => {
+                let value = values.map(|v| v.into_single(field)).transpose()?;
+
+                builder
+                    .field_builder::<GenericStringBuilder<i32>>(i)
+                    .ok_or_else(missing_builder)?
+                    .append_option(value.and_then(|v| v.into_utf8()));
+            }
+            (DataType::Dictionary(_, _), values) => {
+                let value = values.map(|v| v.into_single(field)).transpose()?;
+
+                builder
+                    .field_builder::<StringDictionaryBuilder<Int32Type>>(i)
+                    .ok_or_else(missing_builder)?
+                    .append_option(value.and_then(|v| v.into_utf8()));
+            }
+            (dt, _) => {
+                return Err(ArrowError::InvalidArgumentError(format!(
+                    "unsupported type {} for format field {}",
+                    dt,
+                    field.name()
+                )))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Format a genotype as a string, e.g. `0|1` or `./.`.
+pub(crate) fn genotype_string(genotype: &dyn Genotype) -> Result<String, ArrowError> {
+    let mut gt = String::new();
+
+    for (i, result) in genotype.iter().enumerate() {
+        let (allele, phasing) = result?;
+
+        // The phasing of an allele is written before it, the first allele has no separator.
+        if i > 0 {
+            gt.push(match phasing {
+                Phasing::Unphased => '/',
+                Phasing::Phased => '|',
+            });
+        }
+
+        match allele {
+            Some(allele) => gt.push_str(&allele.to_string()),
+            None => gt.push('.'),
+        }
+    }
+
+    Ok(gt)
+}
+
+/// A single value of a sample's field, converted to the field's Arrow type.
+enum FieldValue {
+    Int32(Option<i32>),
+    Float32(Option<f32>),
+    Utf8(Option<String>),
+}
+
+impl FieldValue {
+    fn as_int32(&self) -> Option<i32> {
+        match self {
+            Self::Int32(v) => *v,
+            _ => None,
+        }
+    }
+
+    fn as_float32(&self) -> Option<f32> {
+        match self {
+            Self::Float32(v) => *v,
+            _ => None,
+        }
+    }
+
+    fn into_utf8(self) -> Option<String> {
+        match self {
+            Self::Utf8(v) => v,
+            _ => None,
+        }
+    }
+}
+
+/// The values of a sample's field converted to the (item) type of the field, a scalar value is a
+/// single item.
+enum FieldValues {
+    Int32(Vec<Option<i32>>),
+    Float32(Vec<Option<f32>>),
+    Utf8(Vec<Option<String>>),
+}
+
+impl FieldValues {
+    fn try_new(value: Value<'_>, field: &Field) -> Result<Self, ArrowError> {
+        let item_type = match field.data_type() {
+            DataType::List(item) => item.data_type(),
+            dt => dt,
+        };
+
+        // Dictionary encoded strings, e.g. genotypes, are converted like plain strings.
+        let item_type = match item_type {
+            DataType::Dictionary(_, value_type) => value_type.as_ref(),
+            dt => dt,
+        };
+
+        let mismatch = || {
+            ArrowError::InvalidArgumentError(format!(
+                "format field {} has a value that can't be converted to {}",
+                field.name(),
+                item_type
+            ))
+        };
+
+        let values = match (item_type, value) {
+            (DataType::Int32, Value::Integer(n)) => Self::Int32(vec![Some(n)]),
+            (DataType::Int32, Value::Array(Array::Integer(values))) => {
+                Self::Int32(values.iter().collect::<Result<_, _>>()?)
+            }
+            (DataType::Float32, Value::Float(n)) => Self::Float32(vec![Some(n)]),
+            (DataType::Float32, Value::Integer(n)) => Self::Float32(vec![Some(n as f32)]),
+            (DataType::Float32, Value::Array(Array::Float(values))) => {
+                Self::Float32(values.iter().collect::<Result<_, _>>()?)
+            }
+            (DataType::Float32, Value::Array(Array::Integer(values))) => Self::Float32(
+                values
+                    .iter()
+                    .map(|v| v.map(|v| v.map(|v| v as f32)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            (DataType::Utf8, value) => Self::Utf8(match value {
+                Value::String(s) => vec![Some(s.to_string())],
+                Value::Character(c) => vec![Some(c.to_string())],
+                Value::Integer(n) => vec![Some(n.to_string())],
+                Value::Float(n) => vec![Some(n.to_string())],
+                Value::Genotype(gt) => vec![Some(genotype_string(gt.as_ref())?)],
+                Value::Array(Array::String(values)) => values
+                    .iter()
+                    .map(|v| v.map(|v| v.map(|v| v.to_string())))
+                    .collect::<Result<_, _>>()?,
+                Value::Array(Array::Character(values)) => values
+                    .iter()
+                    .map(|v| v.map(|v| v.map(|v| v.to_string())))
+                    .collect::<Result<_, _>>()?,
+                Value::Array(Array::Integer(values)) => values
+                    .iter()
+                    .map(|v| v.map(|v| v.map(|v| v.to_string())))
+                    .collect::<Result<_, _>>()?,
+                Value::Array(Array::Float(values)) => values
+                    .iter()
+                    .map(|v| v.map(|v| v.map(|v| v.to_string())))
+                    .collect::<Result<_, _>>()?,
+            }),
+            _ => return Err(mismatch()),
+        };
+
+        Ok(values)
+    }
+
+    /// The single value of a scalar field, it's an error for a scalar field to have several
+    /// values.
+    fn into_single(self, field: &Field) -> Result<FieldValue, ArrowError> {
+        fn single<T>(mut values: Vec<Option<T>>, field: &Field) -> Result<Option<T>, ArrowError> {
+            match values.len() {
+                0 => Ok(None),
+                1 => Ok(values.pop().flatten()),
+                n => Err(ArrowError::InvalidArgumentError(format!(
+                    "format field {} expects a single value, got {}",
+                    field.name(),
+                    n
+                ))),
+            }
+        }
+
+        Ok(match self {
+            Self::Int32(values) => FieldValue::Int32(single(values, field)?),
+            Self::Float32(values) => FieldValue::Float32(single(values, field)?),
+            Self::Utf8(values) => FieldValue::Utf8(single(values, field)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Array, AsArray},
+        datatypes::{DataType, Field, Fields, Float32Type, Int32Type},
+    };
+    use noodles::vcf;
+
+    use super::GenotypeBuilder;
+    use crate::SampleSelection;
+
+    #[test]
+    fn test_append_value_with_missing_values() -> Result<(), Box<dyn std::error::Error>> {
+        let data = b"##fileformat=VCFv4.3
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Read depth\">
+##FORMAT=<ID=AD,Number=R,Type=Integer,Description=\"Allelic depths\">
+##FORMAT=<ID=GL,Number=G,Type=Float,Description=\"Genotype likelihoods\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\ts1\ts2\ts3
+1\t1\t.\tA\tC\t.\t.\t.\tGT:DP:AD:GL\t0/1|1:5:3,.:.\t./.:.:.:-1,2.5,0\t0|1
+";
+
+        let mut reader = vcf::io::Reader::new(&data[..]);
+        let header = reader.read_header()?;
+        let record = reader.records().next().ok_or("expected a record")??;
+
+        let list_item = |ty: DataType| DataType::List(Arc::new(Field::new("item", ty, true)));
+        let fields = Fields::from(vec![
+            Field::new("GT", DataType::Utf8, true),
+            Field::new("DP", DataType::Int32, true),
+            Field::new("AD", list_item(DataType::Int32), true),
+            Field::new("GL", list_item(DataType::Float32), true),
+        ]);
+        let field = Field::new(
+            "formats",
+            DataType::List(Arc::new(Field::new("item", DataType::Struct(fields), true))),
+            true,
+        );
+
+        let mut builder = GenotypeBuilder::try_new(&field, 1)?;
+        builder.append_value(Box::new(record.samples()), &header)?;
+
+        let formats = builder.finish();
+        let samples = formats.value(0);
+        let samples = samples.as_struct();
+        assert_eq!(samples.len(), 3);
+
+        let gt = samples
+            .column_by_name("GT")
+            .ok_or("missing GT")?
+            .as_string::<i32>();
+        assert_eq!(gt.value(0), "0/1|1");
+        assert_eq!(gt.value(1), "./.");
+        assert_eq!(gt.value(2), "0|1");
+
+        let dp = samples
+            .column_by_name("DP")
+            .ok_or("missing DP")?
+            .as_primitive::<Int32Type>();
+        assert_eq!(dp.value(0), 5);
+        assert!(dp.is_null(1));
+        assert!(dp.is_null(2));
+
+        let ad = samples
+            .column_by_name("AD")
+            .ok_or("missing AD")?
+            .as_list::<i32>();
+        let ad_0 = ad.value(0);
+        let ad_0 = ad_0.as_primitive::<Int32Type>();
+        assert_eq!(ad_0.len(), 2);
+        assert_eq!(ad_0.value(0), 3);
+        assert!(ad_0.is_null(1));
+        assert!(ad.is_null(1));
+        assert!(ad.is_null(2));
+
+        let gl = samples
+            .column_by_name("GL")
+            .ok_or("missing GL")?
+            .as_list::<i32>();
+        assert!(gl.is_null(0));
+        assert_eq!(
+            gl.value(1).as_primitive::<Float32Type>().values().to_vec(),
+            vec![-1.0, 2.5, 0.0]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_value_with_sample_selection() -> Result<(), Box<dyn std::error::Error>> {
+        let data = b"##fileformat=VCFv4.3
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Read depth\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\ts1\ts2\ts3
+1\t1\t.\tA\tC\t.\t.\t.\tGT:DP\t0/0:1\t0/1:2\t1/1:3
+";
+
+        let mut reader = vcf::io::Reader::new(&data[..]);
+        let header = reader.read_header()?;
+        let record = reader.records().next().ok_or("expected a record")??;
+
+        let fields = Fields::from(vec![
+            Field::new("GT", DataType::Utf8, true),
+            Field::new("DP", DataType::Int32, true),
+        ]);
+        let field = Field::new(
+            "formats",
+            DataType::List(Arc::new(Field::new("item", DataType::Struct(fields), true))),
+            true,
+        );
+
+        let selection = SampleSelection::try_new(&header, &["s3".to_string(), "s1".to_string()])?;
+
+        let mut builder =
+            GenotypeBuilder::try_new(&field, 1)?.with_sample_selection(Some(selection));
+        builder.append_value(Box::new(record.samples()), &header)?;
+
+        let formats = builder.finish();
+        let samples = formats.value(0);
+        let samples = samples.as_struct();
+        assert_eq!(samples.len(), 2);
+
+        let gt = samples
+            .column_by_name("GT")
+            .ok_or("missing GT")?
+            .as_string::<i32>();
+        assert_eq!(gt.value(0), "1/1");
+        assert_eq!(gt.value(1), "0/0");
+
+        let dp = samples
+            .column_by_name("DP")
+            .ok_or("missing DP")?
+            .as_primitive::<Int32Type>();
+        assert_eq!(dp.values().to_vec(), vec![3, 1]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_value_with_dictionary_genotypes() -> Result<(), Box<dyn std::error::Error>> {
+        let data = b"##fileformat=VCFv4.3
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\ts1\ts2\ts3
+1\t1\t.\tA\tC\t.\t.\t.\tGT\t0|1\t0|1\t.
+1\t2\t.\tA\tC\t.\t.\t.\tGT\t0|0\t0|1\t0|1
+";
+
+        let mut reader = vcf::io::Reader::new(&data[..]);
+        let header = reader.read_header()?;
+
+        let gt_type = DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8));
+        let fields = Fields::from(vec![Field::new("GT", gt_type, true)]);
+        let field = Field::new(
+            "formats",
+            DataType::List(Arc::new(Field::new("item", DataType::Struct(fields), true))),
+            true,
+        );
+
+        let mut builder = GenotypeBuilder::try_new(&field, 2)?;
+
+        for result in reader.records() {
+            let record = result?;
+            builder.append_value(Box::new(record.samples()), &header)?;
+        }
+
+        let formats = builder.finish();
+        let samples = formats.values().as_struct();
+
+        let gt = samples
+            .column_by_name("GT")
+            .ok_or("missing GT")?
+            .as_dictionary::<Int32Type>();
+        assert_eq!(gt.len(), 6);
+        assert_eq!(gt.null_count(), 1);
+        assert_eq!(gt.values().len(), 2);
+
+        Ok(())
+    }
 }
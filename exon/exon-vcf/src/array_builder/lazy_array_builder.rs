@@ -23,18 +23,22 @@ use exon_common::ExonArrayBuilder;
 use noodles::vcf::{
     variant::record::{
         info::field::{value::Array as InfosArray, Value as InfosValue},
-        samples::series::{
-            value::{genotype::Phasing, Array},
-            Value as SamplesValue,
-        },
-        Filters, Ids, Info, Samples,
+        samples::series::{value::Array, Value as SamplesValue},
+        Ids, Info, Samples,
     },
     Header,
 };
 
 use noodles::vcf::variant::record::AlternateBases;
 
-use super::{GenotypeBuilder, InfosBuilder};
+use super::{
+    genotype_builder::genotype_string,
+    structural_variant_builder::{
+        StructuralVariant, StructuralVariantBuilder, STRUCTURAL_VARIANT_COLUMN_OFFSET,
+    },
+    FiltersBuilder, GenotypeBuilder, InfosBuilder,
+};
+use crate::SampleSelection;
 
 enum InfosFormat {
     Struct(InfosBuilder),
@@ -54,12 +58,15 @@ pub struct LazyVCFArrayBuilder {
     references: GenericStringBuilder<i32>,
     alternates: GenericListBuilder<i32, GenericStringBuilder<i32>>,
     qualities: Float32Builder,
-    filters: GenericListBuilder<i32, GenericStringBuilder<i32>>,
+    filters: FiltersBuilder,
 
     infos: InfosFormat,
     formats: FormatsFormat,
+    structural_variants: StructuralVariantBuilder,
     projection: Vec<usize>,
 
+    sample_selection: Option<SampleSelection>,
+
     header: Arc<Header>,
 
     rows: usize,
@@ -133,27 +140,43 @@ impl LazyVCFArrayBuilder {
                 capacity,
             ),
             qualities: Float32Builder::with_capacity(capacity),
-            filters: GenericListBuilder::<i32, GenericStringBuilder<i32>>::with_capacity(
-                GenericStringBuilder::<i32>::new(),
-                capacity,
-            ),
+            filters: FiltersBuilder::try_new(schema.field_with_name("filter")?, capacity)?,
 
             infos,
             formats,
+            structural_variants: StructuralVariantBuilder::with_capacity(capacity),
 
             projection,
 
+            sample_selection: None,
+
             header,
 
             rows: 0,
         })
     }
 
+    /// Only append the selected samples of each record to the formats column.
+    pub fn with_sample_selection(mut self, sample_selection: Option<SampleSelection>) -> Self {
+        self.formats = match self.formats {
+            FormatsFormat::List(builder) => {
+                FormatsFormat::List(builder.with_sample_selection(sample_selection.clone()))
+            }
+            formats => formats,
+        };
+
+        self.sample_selection = sample_selection;
+        self
+    }
+
     /// Appends a record to the builder.
     pub fn append<T>(&mut self, record: T) -> Result<(), ArrowError>
     where
         T: noodles::vcf::variant::record::Record,
     {
+        // The structural variant columns share their parsing, so it's done once per record.
+        let mut structural_variant = None;
+
         for col_idx in self.projection.iter() {
             match col_idx {
                 0 => {
@@ -193,10 +216,9 @@ impl LazyVCFArrayBuilder {
                     if alt_bases.is_empty() {
                         self.alternates.append_null();
                     } else {
-                        let mut s = String::new();
                         for alt in alt_bases.iter() {
                             let alt = alt?;
-                            s += alt;
+                            self.alternates.values().append_value(alt);
                         }
 
                         self.alternates.append(true);
@@ -207,12 +229,7 @@ impl LazyVCFArrayBuilder {
                     self.qualities.append_option(qs);
                 }
                 6 => {
-                    for filter in record.filters().iter(&self.header) {
-                        let filter = filter?;
-                        self.filters.values().append_value(filter);
-                    }
-
-                    self.filters.append(true);
+                    self.filters.append_value(record.filters(), &self.header)?;
                 }
                 7 => match self.infos {
                     InfosFormat::String(ref mut builder) => {
@@ -319,56 +336,44 @@ impl LazyVCFArrayBuilder {
 
                         let mut sample_strings = Vec::new();
 
-                        for sample in samples.iter() {
+                        let selected = match &self.sample_selection {
+                            Some(selection) => selection.select(samples.iter()),
+                            None => samples.iter().map(Some).collect(),
+                        };
+
+                        for sample in selected {
+                            // A selected sample that the record doesn't have is written as missing.
+                            let Some(sample) = sample else {
+                                sample_strings.push(String::from("."));
+                                continue;
+                            };
+
                             let mut s = Vec::new();
 
                             for si in sample.iter(&self.header) {
                                 let (_, value_option) = si?;
-                                let value = value_option.unwrap();
+
+                                // A missing value is written back as `.`.
+                                let Some(value) = value_option else {
+                                    s.push(String::from("."));
+                                    continue;
+                                };
 
                                 match value {
                                     SamplesValue::String(v) => s.push(v.to_string()),
                                     SamplesValue::Character(v) => s.push(v.to_string()),
                                     SamplesValue::Float(v) => s.push(v.to_string()),
                                     SamplesValue::Genotype(gt) => {
-                                        let mut gt_strs = Vec::new();
-                                        let mut previous_phasing = "/";
-
-                                        for gtt in gt.iter() {
-                                            let (allele, phasing) = gtt?;
-
-                                            let allele_str =
-                                                allele.map_or(String::from("."), |v| v.to_string());
-
-                                            // Determine the phasing symbol for the current allele based on the previous allele
-                                            let phasing_str = match phasing {
-                                                Phasing::Unphased => "/",
-                                                Phasing::Phased => "|",
-                                            };
-
-                                            // For the first allele, don't prepend phasing symbol; otherwise, use the previous phasing
-                                            if gt_strs.is_empty() {
-                                                gt_strs.push(allele_str);
-                                            } else {
-                                                gt_strs.push(format!(
-                                                    "{}{}",
-                                                    previous_phasing, allele_str
-                                                ));
-                                            }
-
-                                            // Update previous_phasing to the current allele's phasing for the next iteration
-                                            previous_phasing = phasing_str;
-                                        }
-
-                                        s.push(gt_strs.join(""));
+                                        s.push(genotype_string(gt.as_ref())?);
                                     }
                                     SamplesValue::Integer(v) => s.push(v.to_string()),
                                     SamplesValue::Array(arr) => match arr {
                                         Array::Character(ca) => {
                                             let mut si = Vec::new();
                                             for v in ca.iter() {
-                                                if let Some(v) = v? {
-                                                    si.push(v.to_string())
+                                                match v? {
+                                                    Some(v) => si.push(v.to_string()),
+                                                    None => si.push(String::from('.')),
                                                 }
                                             }
 
@@ -434,6 +439,19 @@ impl LazyVCFArrayBuilder {
                         builder.append_value(samples, &self.header)?;
                     }
                 },
+                col_idx if *col_idx >= STRUCTURAL_VARIANT_COLUMN_OFFSET => {
+                    if structural_variant.is_none() {
+                        structural_variant =
+                            Some(StructuralVariant::try_new(&record, &self.header)?);
+                    }
+
+                    if let Some(structural_variant) = &structural_variant {
+                        self.structural_variants.append(
+                            col_idx - STRUCTURAL_VARIANT_COLUMN_OFFSET,
+                            structural_variant,
+                        )?;
+                    }
+                }
                 _ => {
                     return Err(ArrowError::SchemaError(
                         "Unexpected number of columns for VCF file".to_string(),
@@ -459,7 +477,7 @@ impl LazyVCFArrayBuilder {
                 3 => arrays.push(Arc::new(self.references.finish())),
                 4 => arrays.push(Arc::new(self.alternates.finish())),
                 5 => arrays.push(Arc::new(self.qualities.finish())),
-                6 => arrays.push(Arc::new(self.filters.finish())),
+                6 => arrays.push(self.filters.finish()),
                 7 => match self.infos {
                     InfosFormat::String(ref mut builder) => {
                         arrays.push(Arc::new(builder.finish()));
@@ -476,6 +494,13 @@ impl LazyVCFArrayBuilder {
                         arrays.push(Arc::new(builder.finish()));
                     }
                 },
+                col_idx if *col_idx >= STRUCTURAL_VARIANT_COLUMN_OFFSET => {
+                    let array = self
+                        .structural_variants
+                        .finish(col_idx - STRUCTURAL_VARIANT_COLUMN_OFFSET)
+                        .expect("invalid structural variant column");
+                    arrays.push(array);
+                }
                 _ => panic!("Not implemented"),
             }
         }
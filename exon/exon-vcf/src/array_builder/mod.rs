@@ -13,11 +13,17 @@
 // limitations under the License.
 
 mod eager_array_builder;
+mod filters_builder;
 mod genotype_builder;
 mod info_builder;
 mod lazy_array_builder;
+mod structural_variant_builder;
 
 pub use self::eager_array_builder::VCFArrayBuilder;
+pub use self::filters_builder::FiltersBuilder;
 pub use self::genotype_builder::GenotypeBuilder;
 pub use self::info_builder::InfosBuilder;
 pub use self::lazy_array_builder::LazyVCFArrayBuilder;
+pub use self::structural_variant_builder::{
+    structural_variant_fields, STRUCTURAL_VARIANT_COLUMN_OFFSET,
+};
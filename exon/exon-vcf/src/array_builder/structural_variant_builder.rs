@@ -0,0 +1,318 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{io, sync::Arc};
+
+use arrow::{
+    array::{ArrayRef, GenericListBuilder, GenericStringBuilder, Int64Builder},
+    datatypes::{DataType, Field},
+    error::ArrowError,
+};
+use noodles::vcf::{
+    variant::record::{
+        info::field::{value::Array, Value},
+        AlternateBases, Info, ReferenceBases,
+    },
+    variant::Record,
+    Header,
+};
+
+use crate::Breakend;
+
+/// The index of the first structural variant column in the VCF schema.
+pub const STRUCTURAL_VARIANT_COLUMN_OFFSET: usize = 9;
+
+/// The typed structural variant columns, in the order they follow the VCF columns.
+pub fn structural_variant_fields() -> Vec<Field> {
+    let list_type = DataType::List(Arc::new(Field::new("item", DataType::Int64, true)));
+
+    vec![
+        Field::new("end", DataType::Int64, true),
+        Field::new("svtype", DataType::Utf8, true),
+        Field::new("svlen", DataType::Int64, true),
+        Field::new("cipos", list_type.clone(), true),
+        Field::new("ciend", list_type, true),
+        Field::new("mate_chrom", DataType::Utf8, true),
+        Field::new("mate_pos", DataType::Int64, true),
+    ]
+}
+
+/// The structural variant annotations of a single record.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct StructuralVariant {
+    end: Option<i64>,
+    svtype: Option<String>,
+    svlen: Option<i64>,
+    cipos: Option<Vec<Option<i64>>>,
+    ciend: Option<Vec<Option<i64>>>,
+    mate: Option<(String, i64)>,
+}
+
+impl StructuralVariant {
+    /// Parse the structural variant annotations from the INFO and ALT fields of a record.
+    ///
+    /// The end is the INFO `END` if present. Otherwise symbolic alleles other than insertions
+    /// span `|SVLEN|` bases after the padding base, and everything else spans the reference.
+    pub(crate) fn try_new<R>(record: &R, header: &Header) -> io::Result<Self>
+    where
+        R: Record + ?Sized,
+    {
+        let info = record.info();
+
+        let mut alternates = Vec::new();
+        for alt in record.alternate_bases().iter() {
+            alternates.push(alt?.to_string());
+        }
+
+        let mate = alternates
+            .iter()
+            .find_map(|alt| Breakend::parse(alt))
+            .map(|breakend| (breakend.mate_chrom, breakend.mate_pos));
+
+        let svtype = match info_value(&info, header, "SVTYPE")? {
+            Some(Value::String(s)) => Some(s.to_string()),
+            Some(_) => return Err(invalid_value("SVTYPE")),
+            None => alternates
+                .iter()
+                .find_map(|alt| symbolic_type(alt))
+                .or_else(|| mate.as_ref().map(|_| "BND".to_string())),
+        };
+
+        let svlen = info_integers(&info, header, "SVLEN")?
+            .and_then(|values| values.into_iter().flatten().next());
+        let cipos = info_integers(&info, header, "CIPOS")?;
+        let ciend = info_integers(&info, header, "CIEND")?;
+
+        let end = match info_integers(&info, header, "END")? {
+            Some(values) => values.into_iter().flatten().next(),
+            None => match record.variant_start().transpose()? {
+                Some(start) => {
+                    let start = start.get() as i64;
+                    let is_symbolic = alternates.iter().any(|alt| alt.starts_with('<'));
+
+                    match svlen {
+                        Some(svlen) if is_symbolic && svtype.as_deref() != Some("INS") => {
+                            Some(start + svlen.abs())
+                        }
+                        _ => Some(start + record.reference_bases().len() as i64 - 1),
+                    }
+                }
+                None => None,
+            },
+        };
+
+        Ok(Self {
+            end,
+            svtype,
+            svlen,
+            cipos,
+            ciend,
+            mate,
+        })
+    }
+}
+
+fn invalid_value(key: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid INFO {} value", key),
+    )
+}
+
+fn info_value<'a, I>(info: &'a I, header: &'a Header, key: &str) -> io::Result<Option<Value<'a>>>
+where
+    I: Info,
+{
+    info.get(header, key).transpose().map(Option::flatten)
+}
+
+/// Get an integer INFO field as a list, whether the header declares it as a scalar or an array.
+fn info_integers<I>(info: &I, header: &Header, key: &str) -> io::Result<Option<Vec<Option<i64>>>>
+where
+    I: Info,
+{
+    match info_value(info, header, key)? {
+        Some(Value::Integer(n)) => Ok(Some(vec![Some(i64::from(n))])),
+        Some(Value::Array(Array::Integer(values))) => values
+            .iter()
+            .map(|v| v.map(|v| v.map(i64::from)))
+            .collect::<io::Result<Vec<_>>>()
+            .map(Some),
+        Some(_) => Err(invalid_value(key)),
+        None => Ok(None),
+    }
+}
+
+/// The type of a symbolic allele, e.g. `DEL` for `<DEL:ME:ALU>`.
+fn symbolic_type(alt: &str) -> Option<String> {
+    let id = alt.strip_prefix('<')?.strip_suffix('>')?;
+    let ty = id.split(':').next()?;
+
+    (!ty.is_empty() && ty != "*").then(|| ty.to_string())
+}
+
+/// Builder for the typed structural variant columns.
+pub(crate) struct StructuralVariantBuilder {
+    ends: Int64Builder,
+    svtypes: GenericStringBuilder<i32>,
+    svlens: Int64Builder,
+    cipos: GenericListBuilder<i32, Int64Builder>,
+    ciend: GenericListBuilder<i32, Int64Builder>,
+    mate_chroms: GenericStringBuilder<i32>,
+    mate_positions: Int64Builder,
+}
+
+impl StructuralVariantBuilder {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            ends: Int64Builder::with_capacity(capacity),
+            svtypes: GenericStringBuilder::<i32>::with_capacity(capacity, capacity * 3),
+            svlens: Int64Builder::with_capacity(capacity),
+            cipos: GenericListBuilder::with_capacity(Int64Builder::new(), capacity),
+            ciend: GenericListBuilder::with_capacity(Int64Builder::new(), capacity),
+            mate_chroms: GenericStringBuilder::<i32>::new(),
+            mate_positions: Int64Builder::with_capacity(capacity),
+        }
+    }
+
+    /// Append the value of the `column`th structural variant column.
+    pub(crate) fn append(
+        &mut self,
+        column: usize,
+        structural_variant: &StructuralVariant,
+    ) -> Result<(), ArrowError> {
+        let append_list = |builder: &mut GenericListBuilder<i32, Int64Builder>,
+                           values: &Option<Vec<_>>| {
+            match values {
+                Some(values) => {
+                    builder.values().extend(values.iter().copied());
+                    builder.append(true);
+                }
+                None => builder.append_null(),
+            }
+        };
+
+        match column {
+            0 => self.ends.append_option(structural_variant.end),
+            1 => self
+                .svtypes
+                .append_option(structural_variant.svtype.as_deref()),
+            2 => self.svlens.append_option(structural_variant.svlen),
+            3 => append_list(&mut self.cipos, &structural_variant.cipos),
+            4 => append_list(&mut self.ciend, &structural_variant.ciend),
+            5 => self
+                .mate_chroms
+                .append_option(structural_variant.mate.as_ref().map(|(chrom, _)| chrom)),
+            6 => self
+                .mate_positions
+                .append_option(structural_variant.mate.as_ref().map(|(_, pos)| *pos)),
+            _ => {
+                return Err(ArrowError::InvalidArgumentError(format!(
+                    "Invalid structural variant column index: {}",
+                    column
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finish the `column`th structural variant column.
+    pub(crate) fn finish(&mut self, column: usize) -> Result<ArrayRef, ArrowError> {
+        let array: ArrayRef = match column {
+            0 => Arc::new(self.ends.finish()),
+            1 => Arc::new(self.svtypes.finish()),
+            2 => Arc::new(self.svlens.finish()),
+            3 => Arc::new(self.cipos.finish()),
+            4 => Arc::new(self.ciend.finish()),
+            5 => Arc::new(self.mate_chroms.finish()),
+            6 => Arc::new(self.mate_positions.finish()),
+            _ => {
+                return Err(ArrowError::InvalidArgumentError(format!(
+                    "Invalid structural variant column index: {}",
+                    column
+                )))
+            }
+        };
+
+        Ok(array)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use noodles::vcf;
+
+    use super::StructuralVariant;
+
+    #[test]
+    fn test_structural_variant_try_new() -> Result<(), Box<dyn std::error::Error>> {
+        let data = b"##fileformat=VCFv4.3
+##INFO=<ID=END,Number=1,Type=Integer,Description=\"End position\">
+##INFO=<ID=SVTYPE,Number=1,Type=String,Description=\"Type of structural variant\">
+##INFO=<ID=SVLEN,Number=.,Type=Integer,Description=\"Length of structural variant\">
+##INFO=<ID=CIPOS,Number=2,Type=Integer,Description=\"Confidence interval around POS\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+1\t100\t.\tT\t<DEL>\t.\t.\tSVTYPE=DEL;SVLEN=-200;CIPOS=-5,5
+1\t100\t.\tT\t<DUP:TANDEM>\t.\t.\tEND=150
+1\t100\t.\tT\t<INS>\t.\t.\tSVLEN=300
+2\t321681\t.\tG\tG]17:198982]\t.\t.\t.
+1\t100\t.\tACG\tA\t.\t.\t.
+";
+
+        let mut reader = vcf::io::Reader::new(&data[..]);
+        let header = reader.read_header()?;
+
+        let structural_variants = reader
+            .records()
+            .map(|record| Ok(StructuralVariant::try_new(&record?, &header)?))
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+
+        assert_eq!(
+            structural_variants,
+            vec![
+                StructuralVariant {
+                    end: Some(300),
+                    svtype: Some("DEL".to_string()),
+                    svlen: Some(-200),
+                    cipos: Some(vec![Some(-5), Some(5)]),
+                    ..Default::default()
+                },
+                StructuralVariant {
+                    end: Some(150),
+                    svtype: Some("DUP".to_string()),
+                    ..Default::default()
+                },
+                StructuralVariant {
+                    end: Some(100),
+                    svtype: Some("INS".to_string()),
+                    svlen: Some(300),
+                    ..Default::default()
+                },
+                StructuralVariant {
+                    end: Some(321681),
+                    svtype: Some("BND".to_string()),
+                    mate: Some(("17".to_string(), 198982)),
+                    ..Default::default()
+                },
+                StructuralVariant {
+                    end: Some(102),
+                    ..Default::default()
+                },
+            ]
+        );
+
+        Ok(())
+    }
+}
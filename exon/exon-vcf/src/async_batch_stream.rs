@@ -83,14 +83,26 @@ where
             self.config.batch_size,
             self.config.projection.clone(),
             self.header.clone(),
-        )?;
+        )?
+        .with_sample_selection(self.config.sample_selection(&self.header)?);
 
         while array_builder.len() < self.config.batch_size {
             let record = self.read_record().await?;
 
             match record {
                 Some(record) => {
-                    array_builder.append(record)?;
+                    // Only the ID field is read to check the filter, so skipped records are never
+                    // fully parsed.
+                    let matches = match &self.config.identifier_filter {
+                        Some(identifier_filter) => {
+                            identifier_filter.is_match(record.ids().as_ref().split(';'))
+                        }
+                        None => true,
+                    };
+
+                    if matches {
+                        array_builder.append(record)?;
+                    }
                 }
                 None => {
                     break;
@@ -0,0 +1,133 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// A breakend allele, e.g. `G]17:198982]`, which joins this record's position to a mate position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakend {
+    /// The reference sequence of the mate.
+    pub mate_chrom: String,
+
+    /// The 1-based position of the mate.
+    pub mate_pos: i64,
+
+    /// The bases outside the brackets, i.e. the reference base and any inserted sequence.
+    pub bases: String,
+
+    /// True if the joined sequence follows the bases (`t[p[` and `t]p]`), false if it precedes
+    /// them (`]p]t` and `[p[t`).
+    pub joined_after: bool,
+
+    /// True if the joined sequence extends right of the mate position (`[p[`), false if it
+    /// extends left of it (`]p]`).
+    pub mate_extends_right: bool,
+}
+
+impl Breakend {
+    /// Parse a breakend allele, returning `None` for any other allele.
+    pub fn parse(alt: &str) -> Option<Self> {
+        let open = alt.find(['[', ']'])?;
+        let bracket = alt[open..].chars().next()?;
+
+        let rest = &alt[open + 1..];
+        let close = rest.find(bracket)?;
+
+        let (mate_chrom, mate_pos) = rest[..close].rsplit_once(':')?;
+        let mate_pos = mate_pos.parse::<i64>().ok()?;
+
+        if mate_chrom.is_empty() {
+            return None;
+        }
+
+        let (bases, joined_after) = if open > 0 {
+            (&alt[..open], true)
+        } else {
+            (&rest[close + 1..], false)
+        };
+
+        if bases.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            mate_chrom: mate_chrom.to_string(),
+            mate_pos,
+            bases: bases.to_string(),
+            joined_after,
+            mate_extends_right: bracket == '[',
+        })
+    }
+
+    /// The strand of this side of the junction, `+` if the joined sequence follows this position.
+    pub fn strand(&self) -> &'static str {
+        if self.joined_after {
+            "+"
+        } else {
+            "-"
+        }
+    }
+
+    /// The strand of the mate side of the junction, `+` if the joined sequence ends at the mate
+    /// position.
+    pub fn mate_strand(&self) -> &'static str {
+        if self.mate_extends_right {
+            "-"
+        } else {
+            "+"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Breakend;
+
+    #[test]
+    fn test_parse_breakend() {
+        let parse = |alt: &str| {
+            Breakend::parse(alt).map(|b| {
+                let (strand, mate_strand) = (b.strand(), b.mate_strand());
+
+                (b.mate_chrom, b.mate_pos, b.bases, strand, mate_strand)
+            })
+        };
+
+        assert_eq!(
+            parse("G]17:198982]"),
+            Some(("17".to_string(), 198982, "G".to_string(), "+", "+"))
+        );
+        assert_eq!(
+            parse("]13:123456]T"),
+            Some(("13".to_string(), 123456, "T".to_string(), "-", "+"))
+        );
+        assert_eq!(
+            parse("C[2:321682["),
+            Some(("2".to_string(), 321682, "C".to_string(), "+", "-"))
+        );
+        assert_eq!(
+            parse("[HLA-A*01:01:01:01:42[AGT"),
+            Some((
+                "HLA-A*01:01:01:01".to_string(),
+                42,
+                "AGT".to_string(),
+                "-",
+                "-"
+            ))
+        );
+
+        assert_eq!(parse("<DEL>"), None);
+        assert_eq!(parse("A"), None);
+        assert_eq!(parse("G]17:x]"), None);
+        assert_eq!(parse("]17:10]"), None);
+    }
+}
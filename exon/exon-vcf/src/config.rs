@@ -14,10 +14,13 @@
 
 use std::sync::Arc;
 
-use arrow::datatypes::SchemaRef;
-use exon_common::DEFAULT_BATCH_SIZE;
+use arrow::{datatypes::SchemaRef, error::ArrowError};
+use exon_common::{IdentifierFilter, ReaderLimits, DEFAULT_BATCH_SIZE};
+use noodles::vcf::Header;
 use object_store::ObjectStore;
 
+use crate::SampleSelection;
+
 /// Configuration for a VCF datasource.
 #[derive(Debug)]
 pub struct VCFConfig {
@@ -29,6 +32,12 @@ pub struct VCFConfig {
     pub file_schema: Arc<arrow::datatypes::Schema>,
     /// Any projections to apply to the resulting batches.
     pub projection: Option<Vec<usize>>,
+    /// The limits on header size and line length.
+    pub reader_limits: ReaderLimits,
+    /// A filter that the IDs of records must match to be read.
+    pub identifier_filter: Option<IdentifierFilter>,
+    /// The names of the samples to decode the FORMAT values of, or all samples if `None`.
+    pub samples: Option<Vec<String>>,
 }
 
 impl VCFConfig {
@@ -39,6 +48,9 @@ impl VCFConfig {
             object_store,
             file_schema,
             projection: None,
+            reader_limits: ReaderLimits::default(),
+            identifier_filter: None,
+            samples: None,
         }
     }
 
@@ -48,6 +60,32 @@ impl VCFConfig {
         self
     }
 
+    /// Set the reader limits.
+    pub fn with_reader_limits(mut self, reader_limits: ReaderLimits) -> Self {
+        self.reader_limits = reader_limits;
+        self
+    }
+
+    /// Set the identifier filter.
+    pub fn with_identifier_filter(mut self, identifier_filter: Option<IdentifierFilter>) -> Self {
+        self.identifier_filter = identifier_filter;
+        self
+    }
+
+    /// Set the samples to decode the FORMAT values of.
+    pub fn with_samples(mut self, samples: Option<Vec<String>>) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Resolve the selected samples against the header of a file.
+    pub fn sample_selection(&self, header: &Header) -> Result<Option<SampleSelection>, ArrowError> {
+        self.samples
+            .as_ref()
+            .map(|samples| SampleSelection::try_new(header, samples))
+            .transpose()
+    }
+
     /// Set the projection.
     pub fn with_projection(mut self, projection: Vec<usize>) -> Self {
         self.projection = Some(projection);
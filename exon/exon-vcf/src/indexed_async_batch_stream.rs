@@ -18,7 +18,7 @@ use arrow::{
     error::{ArrowError, Result as ArrowResult},
     record_batch::RecordBatch,
 };
-use exon_common::ExonArrayBuilder;
+use exon_common::{BoundedReader, ExonArrayBuilder};
 use futures::Stream;
 use noodles::{core::Region, vcf::Record};
 use tokio::io::AsyncBufRead;
@@ -31,7 +31,7 @@ where
     R: AsyncBufRead + Unpin,
 {
     /// The underlying record stream.
-    reader: noodles::vcf::AsyncReader<noodles::bgzf::AsyncReader<R>>,
+    reader: noodles::vcf::AsyncReader<BoundedReader<noodles::bgzf::AsyncReader<R>>>,
 
     /// The VCF configuration.
     config: Arc<VCFConfig>,
@@ -52,7 +52,7 @@ where
 {
     /// Create a new VCF record batch reader.
     pub fn new(
-        reader: noodles::vcf::AsyncReader<noodles::bgzf::AsyncReader<R>>,
+        reader: noodles::vcf::AsyncReader<BoundedReader<noodles::bgzf::AsyncReader<R>>>,
         config: Arc<VCFConfig>,
         header: Arc<noodles::vcf::Header>,
         region: Arc<Region>,
@@ -72,7 +72,8 @@ where
     }
 
     async fn read_record(&mut self) -> std::io::Result<Option<noodles::vcf::Record>> {
-        if self.reader.get_ref().virtual_position().uncompressed() as usize >= self.max_bytes {
+        let position = self.reader.get_ref().get_ref().virtual_position();
+        if position.uncompressed() as usize >= self.max_bytes {
             return Ok(None);
         }
 
@@ -121,7 +122,8 @@ where
             self.config.batch_size,
             self.config.projection.clone(),
             self.header.clone(),
-        )?;
+        )?
+        .with_sample_selection(self.config.sample_selection(&self.header)?);
 
         let mut record_count = 0;
         while record_count < self.config.batch_size {
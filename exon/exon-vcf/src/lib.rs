@@ -14,10 +14,16 @@
 
 mod array_builder;
 mod async_batch_stream;
+mod breakend;
 mod config;
 mod indexed_async_batch_stream;
+mod sample_selection;
 
-pub use array_builder::VCFArrayBuilder;
+pub use array_builder::{
+    structural_variant_fields, VCFArrayBuilder, STRUCTURAL_VARIANT_COLUMN_OFFSET,
+};
 pub use async_batch_stream::AsyncBatchStream;
+pub use breakend::Breakend;
 pub use config::VCFConfig;
 pub use indexed_async_batch_stream::IndexedAsyncBatchStream;
+pub use sample_selection::SampleSelection;
@@ -0,0 +1,123 @@
+// Copyright 2024 WHERE TRUE Technologies.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use arrow::error::ArrowError;
+use noodles::vcf::Header;
+
+/// A subset of the samples of a VCF, so only their FORMAT values are decoded.
+///
+/// The samples are resolved by name against each file's header, so files with a different sample
+/// order still return the selected samples in the same order.
+#[derive(Debug, Clone)]
+pub struct SampleSelection {
+    /// The header index and selection position of each sample, ordered by header index.
+    positions: Vec<(usize, usize)>,
+}
+
+impl SampleSelection {
+    /// Select the samples with these names, in this order.
+    pub fn try_new(header: &Header, names: &[String]) -> Result<Self, ArrowError> {
+        let mut positions = Vec::with_capacity(names.len());
+
+        for (position, name) in names.iter().enumerate() {
+            let index = header.sample_names().get_index_of(name).ok_or_else(|| {
+                ArrowError::InvalidArgumentError(format!(
+                    "The sample {} is not in the VCF header",
+                    name
+                ))
+            })?;
+
+            positions.push((index, position));
+        }
+
+        positions.sort_unstable();
+
+        if let Some(w) = positions.windows(2).find(|w| w[0].0 == w[1].0) {
+            return Err(ArrowError::InvalidArgumentError(format!(
+                "The sample {} is selected more than once",
+                names[w[0].1]
+            )));
+        }
+
+        Ok(Self { positions })
+    }
+
+    /// The number of selected samples.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Whether no samples are selected.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Pick the selected samples from the samples of a record in header order, or `None` for a
+    /// selected sample the record doesn't have. The samples after the last selected one aren't
+    /// read.
+    pub fn select<T>(&self, samples: impl Iterator<Item = T>) -> Vec<Option<T>> {
+        let mut selected = Vec::with_capacity(self.positions.len());
+        selected.resize_with(self.positions.len(), || None);
+
+        let mut positions = self.positions.iter().peekable();
+
+        for (index, sample) in samples.enumerate() {
+            let Some((next_index, position)) = positions.peek() else {
+                break;
+            };
+
+            if index == *next_index {
+                selected[*position] = Some(sample);
+                positions.next();
+            }
+        }
+
+        selected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use noodles::vcf::{self, header::SampleNames};
+
+    use super::SampleSelection;
+
+    fn header(names: &[&str]) -> vcf::Header {
+        vcf::Header::builder()
+            .set_sample_names(names.iter().map(|n| n.to_string()).collect::<SampleNames>())
+            .build()
+    }
+
+    #[test]
+    fn test_sample_selection() -> Result<(), Box<dyn std::error::Error>> {
+        let header = header(&["NA12878", "NA12891", "NA12892"]);
+
+        let names = ["NA12892".to_string(), "NA12878".to_string()];
+        let selection = SampleSelection::try_new(&header, &names)?;
+
+        assert_eq!(
+            selection.select(["a", "b", "c"].into_iter()),
+            vec![Some("c"), Some("a")]
+        );
+        assert_eq!(selection.select(["a"].into_iter()), vec![None, Some("a")]);
+
+        let missing = SampleSelection::try_new(&header, &["NA00001".to_string()]);
+        assert!(missing.is_err());
+
+        let duplicate =
+            SampleSelection::try_new(&header, &["NA12878".to_string(), "NA12878".to_string()]);
+        assert!(duplicate.is_err());
+
+        Ok(())
+    }
+}